@@ -0,0 +1,589 @@
+//! Estimating the energy of a track from its audio, to group tracks like "chill" or "high energy", and describing its
+//! mood and sound.
+//!
+//! This is a heuristic on the loudness, the rate of beats and onsets, the brightness and the bass of the audio, not a
+//! trained classifier. So it only describes how a track sounds, with a [`Mood`] and the closest of a few
+//! [`SoundProfile`]s, and does not classify genres.
+
+use std::fs::File;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// How much of a track is analyzed at most, from the start.
+pub const MAX_ANALYZED: Duration = Duration::from_secs(120);
+
+/// The length of a single analysis frame, of which the loudness is measured.
+const FRAME_LEN: Duration = Duration::from_millis(50);
+
+/// The amount of frames before a frame that it is compared to, to detect a onset.
+const ONSET_WINDOW: usize = 8;
+
+/// How much louder than the [`ONSET_WINDOW`] a frame needs to be to count as a onset.
+const ONSET_RATIO: f32 = 1.5;
+
+/// Frames below this are considered silence, for which no onsets are detected.
+const NOISE_FLOOR: f32 = 0.01;
+
+/// The cutoff of the low-pass filter measuring the bass, in Hz.
+const BASS_CUTOFF: f32 = 150.0;
+
+/// The energy level of a track, derived from [`AudioFeatures::energy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnergyLevel {
+    Chill,
+    Medium,
+    High,
+}
+
+impl EnergyLevel {
+    /// All levels, from low to high energy.
+    pub const ALL: [Self; 3] = [Self::Chill, Self::Medium, Self::High];
+
+    /// Get the level of a [`AudioFeatures::energy`] value.
+    #[must_use]
+    pub fn from_energy(energy: f32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.range().contains(&energy))
+            .unwrap_or(Self::High)
+    }
+
+    /// The [`AudioFeatures::energy`] values of this level.
+    #[must_use]
+    pub const fn range(self) -> std::ops::Range<f32> {
+        match self {
+            Self::Chill => 0.0..0.35,
+            Self::Medium => 0.35..0.65,
+            Self::High => 0.65..f32::MAX,
+        }
+    }
+
+    /// The name shown and used to refer to this level.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Chill => "chill",
+            Self::Medium => "medium",
+            Self::High => "high energy",
+        }
+    }
+
+    /// Parse a level from its [`name`](Self::name).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name() == name)
+    }
+}
+
+/// The mood suggested for a track, by its energy and brightness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mood {
+    /// Low energy and dark
+    Melancholic,
+    /// Low energy and bright
+    Calm,
+    /// High energy and dark
+    Intense,
+    /// High energy and bright
+    Upbeat,
+}
+
+impl Mood {
+    pub const ALL: [Self; 4] = [Self::Melancholic, Self::Calm, Self::Intense, Self::Upbeat];
+
+    /// The name shown and stored in the database.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Melancholic => "melancholic",
+            Self::Calm => "calm",
+            Self::Intense => "intense",
+            Self::Upbeat => "upbeat",
+        }
+    }
+
+    /// Parse a mood from its [`name`](Self::name).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name() == name)
+    }
+}
+
+/// How a track sounds, each with a rough profile of its audio features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundProfile {
+    /// Quiet with a wide loudness range
+    Dynamic,
+    /// Quiet with few onsets
+    Sparse,
+    /// Moderate energy with little bass
+    Light,
+    /// Loud and bright
+    Bright,
+    /// Loud, compressed and with many onsets
+    Driving,
+    /// Dominated by the bass
+    BassHeavy,
+}
+
+impl SoundProfile {
+    pub const ALL: [Self; 6] = [
+        Self::Dynamic,
+        Self::Sparse,
+        Self::Light,
+        Self::Bright,
+        Self::Driving,
+        Self::BassHeavy,
+    ];
+
+    /// The name shown and stored in the database.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Dynamic => "dynamic",
+            Self::Sparse => "sparse",
+            Self::Light => "light",
+            Self::Bright => "bright",
+            Self::Driving => "driving",
+            Self::BassHeavy => "bass-heavy",
+        }
+    }
+
+    /// Parse a profile from its [`name`](Self::name).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name() == name)
+    }
+
+    /// The typical [`AudioFeatures::energy`], onset rate (normalized like for the energy), [`AudioFeatures::brightness`],
+    /// [`AudioFeatures::bass`] and [`AudioFeatures::dynamics`] of the profile.
+    const fn features(self) -> [f32; 5] {
+        match self {
+            Self::Dynamic => [0.25, 0.2, 0.35, 0.15, 0.8],
+            Self::Sparse => [0.2, 0.05, 0.25, 0.3, 0.3],
+            Self::Light => [0.45, 0.45, 0.45, 0.15, 0.5],
+            Self::Bright => [0.8, 0.7, 0.75, 0.25, 0.3],
+            Self::Driving => [0.8, 0.85, 0.5, 0.45, 0.2],
+            Self::BassHeavy => [0.7, 0.55, 0.35, 0.6, 0.35],
+        }
+    }
+}
+
+/// The features measured from the audio of a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioFeatures {
+    /// The overall loudness in dBFS, `0` being the loudest possible
+    pub loudness: f32,
+    /// The detected onsets, like beats or notes, per second
+    pub onset_rate: f32,
+    /// The combined estimate from `0` (calm) to `1` (energetic)
+    pub energy: f32,
+    /// How much the loudness varies between frames, from `0` (compressed) to `1` (very dynamic)
+    pub dynamics: f32,
+    /// From `0` (dark, like a bass) to `1` (bright, like cymbals), from the rate of zero crossings
+    pub brightness: f32,
+    /// The part of the signal power below [`BASS_CUTOFF`], from `0` to `1`
+    pub bass: f32,
+}
+
+impl AudioFeatures {
+    /// Calculate the features from the loudness (RMS) of consecutive frames of [`FRAME_LEN`].
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_frames(frames: &[f32]) -> Self {
+        if frames.is_empty() {
+            return Self {
+                loudness: f32::NEG_INFINITY,
+                onset_rate: 0.0,
+                energy: 0.0,
+                dynamics: 0.0,
+                brightness: 0.0,
+                bass: 0.0,
+            };
+        }
+
+        let mean_square = frames.iter().map(|v| v * v).sum::<f32>() / frames.len() as f32;
+        let loudness = 10.0 * mean_square.max(1e-12).log10();
+
+        let mut onsets = 0;
+        let mut last_was_onset = false;
+        for (idx, frame) in frames.iter().enumerate().skip(1) {
+            let window = &frames[idx.saturating_sub(ONSET_WINDOW)..idx];
+            let average = window.iter().sum::<f32>() / window.len() as f32;
+            let is_onset = *frame > NOISE_FLOOR && *frame > average * ONSET_RATIO;
+            if is_onset && !last_was_onset {
+                onsets += 1;
+            }
+            last_was_onset = is_onset;
+        }
+        let onset_rate = onsets as f32 / (frames.len() as f32 * FRAME_LEN.as_secs_f32());
+
+        // -30 dBFS and below is quiet, -10 dBFS and above is loud; 4 onsets per second is a fast beat
+        let loudness_norm = ((loudness + 30.0) / 20.0).clamp(0.0, 1.0);
+        let onset_norm = (onset_rate / 4.0).clamp(0.0, 1.0);
+
+        // the standard deviation of the loudness of the non-silent frames in dB, 15 dB being very dynamic
+        let audible: Vec<f32> = frames
+            .iter()
+            .filter(|v| **v > NOISE_FLOOR)
+            .map(|v| 20.0 * v.log10())
+            .collect();
+        let dynamics = if audible.is_empty() {
+            0.0
+        } else {
+            let mean = audible.iter().sum::<f32>() / audible.len() as f32;
+            let variance =
+                audible.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / audible.len() as f32;
+            (variance.sqrt() / 15.0).clamp(0.0, 1.0)
+        };
+
+        Self {
+            loudness,
+            onset_rate,
+            energy: 0.6 * loudness_norm + 0.4 * onset_norm,
+            dynamics,
+            brightness: 0.0,
+            bass: 0.0,
+        }
+    }
+
+    #[must_use]
+    pub fn level(&self) -> EnergyLevel {
+        EnergyLevel::from_energy(self.energy)
+    }
+
+    /// Describe the mood, [`None`] for silence.
+    #[must_use]
+    pub fn mood(&self) -> Option<Mood> {
+        if self.onset_rate <= 0.0 && self.energy <= 0.0 {
+            return None;
+        }
+
+        let mood = match (self.energy >= 0.5, self.brightness >= 0.5) {
+            (false, false) => Mood::Melancholic,
+            (false, true) => Mood::Calm,
+            (true, false) => Mood::Intense,
+            (true, true) => Mood::Upbeat,
+        };
+
+        Some(mood)
+    }
+
+    /// Get the closest [`SoundProfile`], [`None`] for silence.
+    #[must_use]
+    pub fn sound_profile(&self) -> Option<SoundProfile> {
+        if self.onset_rate <= 0.0 && self.energy <= 0.0 {
+            return None;
+        }
+
+        let features = [
+            self.energy,
+            (self.onset_rate / 4.0).clamp(0.0, 1.0),
+            self.brightness,
+            self.bass,
+            self.dynamics,
+        ];
+        let distance = |profile: SoundProfile| -> f32 {
+            profile
+                .features()
+                .iter()
+                .zip(features)
+                .map(|(a, b)| (a - b).powi(2))
+                .sum()
+        };
+
+        SoundProfile::ALL
+            .into_iter()
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+    }
+}
+
+/// Collects the RMS of frames from a stream of mono samples, and the zero crossings and bass of all samples.
+#[derive(Debug, Default)]
+struct FrameCollector {
+    sample_rate: u32,
+    frame_samples: usize,
+    sum_squares: f32,
+    samples: usize,
+    frames: Vec<f32>,
+
+    /// The factor of the one-pole low-pass filter for the bass
+    lowpass_alpha: f32,
+    lowpass: f32,
+    total_samples: u64,
+    total_power: f64,
+    bass_power: f64,
+    crossings: u64,
+    last_positive: bool,
+}
+
+impl FrameCollector {
+    fn new(sample_rate: u32) -> Self {
+        let frame_samples =
+            u64::from(sample_rate) * u64::try_from(FRAME_LEN.as_millis()).unwrap_or(50) / 1000;
+        #[allow(clippy::cast_precision_loss)]
+        let lowpass_alpha =
+            1.0 - (-2.0 * std::f32::consts::PI * BASS_CUTOFF / sample_rate.max(1) as f32).exp();
+
+        Self {
+            sample_rate,
+            frame_samples: usize::try_from(frame_samples).unwrap_or(usize::MAX).max(1),
+            lowpass_alpha,
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.lowpass += self.lowpass_alpha * (sample - self.lowpass);
+        self.total_power += f64::from(sample * sample);
+        self.bass_power += f64::from(self.lowpass * self.lowpass);
+        self.total_samples += 1;
+        let positive = sample >= 0.0;
+        if positive != self.last_positive {
+            self.crossings += 1;
+        }
+        self.last_positive = positive;
+
+        self.sum_squares += sample * sample;
+        self.samples += 1;
+        if self.samples == self.frame_samples {
+            #[allow(clippy::cast_precision_loss)]
+            self.frames
+                .push((self.sum_squares / self.samples as f32).sqrt());
+            self.sum_squares = 0.0;
+            self.samples = 0;
+        }
+    }
+
+    /// Get the [`AudioFeatures::brightness`] and [`AudioFeatures::bass`] of all samples.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn brightness_and_bass(&self) -> (f32, f32) {
+        if self.total_samples == 0 || self.total_power <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        // about 500 crossings per second is a dark mix, 4500 a very bright or noisy one
+        let seconds = self.total_samples as f32 / self.sample_rate.max(1) as f32;
+        let crossing_rate = self.crossings as f32 / seconds;
+        let brightness = ((crossing_rate - 500.0) / 4000.0).clamp(0.0, 1.0);
+        let bass = ((self.bass_power / self.total_power) as f32).clamp(0.0, 1.0);
+
+        (brightness, bass)
+    }
+}
+
+/// Decode up to [`MAX_ANALYZED`] of the track at `path` and measure its [`AudioFeatures`].
+///
+/// This decodes the audio, so it takes considerably longer than reading the tags.
+pub fn analyze_file(path: &Path) -> Result<AudioFeatures> {
+    let max_frames =
+        usize::try_from(MAX_ANALYZED.as_millis() / FRAME_LEN.as_millis()).unwrap_or(usize::MAX);
+    let mut collector: Option<FrameCollector> = None;
+    decode_file(path, |sample_rate, channels, samples| {
+        let collector = collector.get_or_insert_with(|| FrameCollector::new(sample_rate));
+        for frame in samples.chunks(channels) {
+            #[allow(clippy::cast_precision_loss)]
+            collector.push(frame.iter().sum::<f32>() / channels as f32);
+        }
+
+        if collector.frames.len() < max_frames {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    })?;
+
+    let Some(collector) = collector else {
+        return Ok(AudioFeatures::from_frames(&[]));
+    };
+    let (brightness, bass) = collector.brightness_and_bass();
+
+    Ok(AudioFeatures {
+        brightness,
+        bass,
+        ..AudioFeatures::from_frames(&collector.frames)
+    })
+}
+
+/// Decode the first audio track of the file at `path`, calling `on_buffer` with the sample rate, the amount of channels
+/// and the interleaved samples of each decoded packet, until it breaks or the stream ends.
+///
+/// Corrupted packets are skipped, like the player does.
+pub(crate) fn decode_file<F>(path: &Path, mut on_buffer: F) -> Result<()>
+where
+    F: FnMut(u32, usize, &[f32]) -> ControlFlow<()>,
+{
+    let file = File::open(path).context("open file")?;
+    let mss = MediaSourceStream::new(Box::new(file), MediaSourceStreamOptions::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|v| v.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .context("probe format")?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|v| v.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| anyhow!("No audio track"))?;
+    let track_id = track.id;
+    if track.codec_params.sample_rate.is_none() {
+        bail!("Unknown sample rate");
+    }
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .context("create decoder")?;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(v) => v,
+            // the end of the stream
+            Err(SymphoniaError::IoError(_) | SymphoniaError::ResetRequired) => break,
+            Err(err) => return Err(err).context("read packet"),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let audio = match decoder.decode(&packet) {
+            Ok(v) => v,
+            // skip corrupted packets, like the player does
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(err) => return Err(err).context("decode packet"),
+        };
+        let spec = *audio.spec();
+        let channels = spec.channels.count().max(1);
+        let mut buffer = SampleBuffer::<f32>::new(audio.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(audio);
+
+        if on_buffer(spec.rate, channels, buffer.samples()).is_break() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{AudioFeatures, EnergyLevel, FrameCollector, Mood, SoundProfile};
+
+    #[test]
+    fn should_map_energy_levels() {
+        assert_eq!(EnergyLevel::from_energy(0.0), EnergyLevel::Chill);
+        assert_eq!(EnergyLevel::from_energy(0.5), EnergyLevel::Medium);
+        assert_eq!(EnergyLevel::from_energy(0.65), EnergyLevel::High);
+        assert_eq!(EnergyLevel::from_energy(1.0), EnergyLevel::High);
+
+        for level in EnergyLevel::ALL {
+            assert_eq!(EnergyLevel::from_name(level.name()), Some(level));
+        }
+        assert_eq!(EnergyLevel::from_name("loud"), None);
+    }
+
+    #[test]
+    fn should_rate_quiet_audio_chill() {
+        let silence = AudioFeatures::from_frames(&[0.0; 100]);
+        assert!(silence.onset_rate.abs() < f32::EPSILON);
+        assert_eq!(silence.level(), EnergyLevel::Chill);
+
+        // a quiet steady tone, about -36 dBFS
+        let quiet = AudioFeatures::from_frames(&[0.016; 100]);
+        assert!(quiet.onset_rate.abs() < f32::EPSILON);
+        assert_eq!(quiet.level(), EnergyLevel::Chill);
+
+        assert_eq!(AudioFeatures::from_frames(&[]).level(), EnergyLevel::Chill);
+    }
+
+    #[test]
+    fn should_rate_loud_beats_high_energy() {
+        // a loud beat every 250ms (5 frames), 4 per second
+        let frames: Vec<f32> = (0..200)
+            .map(|idx| if idx % 5 == 0 { 0.9 } else { 0.2 })
+            .collect();
+        let features = AudioFeatures::from_frames(&frames);
+
+        assert!((features.onset_rate - 4.0).abs() < 0.1, "{features:?}");
+        assert_eq!(features.level(), EnergyLevel::High);
+    }
+
+    #[test]
+    fn should_measure_brightness_and_bass() {
+        // a 50 Hz sine is dark and all bass
+        let mut low = FrameCollector::new(48000);
+        for idx in 0..48000 {
+            #[allow(clippy::cast_precision_loss)]
+            low.push((idx as f32 * 50.0 * std::f32::consts::TAU / 48000.0).sin());
+        }
+        let (brightness, bass) = low.brightness_and_bass();
+        assert!(brightness < 0.1, "{brightness}");
+        assert!(bass > 0.8, "{bass}");
+
+        // a alternating signal at the nyquist frequency is as bright as it gets, without bass
+        let mut high = FrameCollector::new(48000);
+        for idx in 0..48000 {
+            high.push(if idx % 2 == 0 { 0.5 } else { -0.5 });
+        }
+        let (brightness, bass) = high.brightness_and_bass();
+        assert!(brightness > 0.9, "{brightness}");
+        assert!(bass < 0.1, "{bass}");
+    }
+
+    #[test]
+    fn should_describe_mood_and_sound() {
+        let features = AudioFeatures {
+            loudness: -8.0,
+            onset_rate: 3.5,
+            energy: 0.85,
+            dynamics: 0.2,
+            brightness: 0.5,
+            bass: 0.5,
+        };
+        assert_eq!(features.mood(), Some(Mood::Upbeat));
+        assert_eq!(features.sound_profile(), Some(SoundProfile::Driving));
+
+        let features = AudioFeatures {
+            loudness: -28.0,
+            onset_rate: 0.8,
+            energy: 0.2,
+            dynamics: 0.8,
+            brightness: 0.35,
+            bass: 0.1,
+        };
+        assert_eq!(features.mood(), Some(Mood::Melancholic));
+        assert_eq!(features.sound_profile(), Some(SoundProfile::Dynamic));
+
+        assert_eq!(AudioFeatures::from_frames(&[0.0; 100]).mood(), None);
+        assert_eq!(
+            AudioFeatures::from_frames(&[0.0; 100]).sound_profile(),
+            None
+        );
+
+        for mood in Mood::ALL {
+            assert_eq!(Mood::from_name(mood.name()), Some(mood));
+        }
+        for profile in SoundProfile::ALL {
+            assert_eq!(SoundProfile::from_name(profile.name()), Some(profile));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Verify the integrity of a track by decoding it end-to-end, and for FLAC by comparing the MD5 of the decoded audio.
+//!
+//! Unlike [`audio_analysis`](crate::audio_analysis), corrupted packets are not skipped silently but counted as a
+//! failure.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::path::Path;
+
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+
+/// Stop decoding after this many corrupted packets, the file is clearly damaged by then.
+const MAX_DECODE_ERRORS: usize = 100;
+
+/// The kind of a failed verification, see [`verify_file`].
+///
+/// Stored as [`VerifyFailure::number`] in `tracks.verify_failure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerifyFailure {
+    /// The file could not be opened, probed or has no decodable audio
+    Unreadable,
+    /// Some packets could not be decoded
+    DecodeErrors,
+    /// The decoded audio does not match the checksum stored in the file
+    ChecksumMismatch,
+}
+
+impl VerifyFailure {
+    /// All kinds, in the order of their numbers.
+    pub const ALL: [Self; 3] = [Self::Unreadable, Self::DecodeErrors, Self::ChecksumMismatch];
+
+    /// The number stored in the database for this kind.
+    #[must_use]
+    pub const fn number(self) -> u8 {
+        match self {
+            Self::Unreadable => 1,
+            Self::DecodeErrors => 2,
+            Self::ChecksumMismatch => 3,
+        }
+    }
+
+    /// Get the kind for `number`, see [`Self::number`].
+    #[must_use]
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.number() == number)
+    }
+
+    /// The name shown and used to refer to this kind.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Unreadable => "unreadable",
+            Self::DecodeErrors => "decode errors",
+            Self::ChecksumMismatch => "checksum mismatch",
+        }
+    }
+
+    /// Parse a kind from its [`name`](Self::name).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name() == name)
+    }
+}
+
+/// A failed verification of a file, see [`verify_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    pub kind: VerifyFailure,
+    /// A description of what failed, like "3 corrupted packets, the first at 01:02"
+    pub detail: String,
+}
+
+impl VerifyError {
+    fn new(kind: VerifyFailure, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind.name(), self.detail)
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Format `time` like `01:02`.
+fn format_time(time: Time) -> String {
+    format!("{:02}:{:02}", time.seconds / 60, time.seconds % 60)
+}
+
+/// Decode the whole track at `path`, checking the MD5 of FLAC files.
+///
+/// This decodes all of the audio, so it takes a while for each file.
+pub fn verify_file(path: &Path) -> Result<(), VerifyError> {
+    let unreadable =
+        |err: &dyn Display| VerifyError::new(VerifyFailure::Unreadable, err.to_string());
+
+    let file = File::open(path).map_err(|err| unreadable(&err))?;
+    let mss = MediaSourceStream::new(Box::new(file), MediaSourceStreamOptions::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|v| v.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|err| unreadable(&err))?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|v| v.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| unreadable(&"No audio track"))?;
+    let track_id = track.id;
+    let time_base = track.codec_params.time_base;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions { verify: true })
+        .map_err(|err| unreadable(&err))?;
+
+    let mut errors = 0;
+    let mut first_error = None;
+    while errors < MAX_DECODE_ERRORS {
+        let packet = match format.next_packet() {
+            Ok(v) => v,
+            // the end of the stream
+            Err(SymphoniaError::IoError(_) | SymphoniaError::ResetRequired) => break,
+            Err(SymphoniaError::DecodeError(_)) => {
+                errors += 1;
+                continue;
+            }
+            Err(err) => return Err(unreadable(&err)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(_) => (),
+            Err(SymphoniaError::DecodeError(_)) => {
+                errors += 1;
+                if first_error.is_none() {
+                    first_error = time_base.map(|v| v.calc_time(packet.ts()));
+                }
+            }
+            Err(err) => return Err(unreadable(&err)),
+        }
+    }
+
+    if errors > 0 {
+        let amount = if errors >= MAX_DECODE_ERRORS {
+            format!("at least {errors}")
+        } else {
+            errors.to_string()
+        };
+        let detail = match first_error {
+            Some(time) => format!(
+                "{amount} corrupted packets, the first at {}",
+                format_time(time)
+            ),
+            None => format!("{amount} corrupted packets"),
+        };
+        return Err(VerifyError::new(VerifyFailure::DecodeErrors, detail));
+    }
+
+    if decoder.finalize().verify_ok == Some(false) {
+        return Err(VerifyError::new(
+            VerifyFailure::ChecksumMismatch,
+            "the decoded audio does not match the stored MD5",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{VerifyFailure, verify_file};
+
+    /// Build a mono 16-bit WAV file with `samples` of silence.
+    fn wav(samples: u32) -> Vec<u8> {
+        let data_len = samples * 2;
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16_u32.to_le_bytes());
+        // PCM, 1 channel, 8000 Hz, 16000 bytes/s, 2 bytes per frame, 16 bits
+        out.extend_from_slice(&1_u16.to_le_bytes());
+        out.extend_from_slice(&1_u16.to_le_bytes());
+        out.extend_from_slice(&8000_u32.to_le_bytes());
+        out.extend_from_slice(&16000_u32.to_le_bytes());
+        out.extend_from_slice(&2_u16.to_le_bytes());
+        out.extend_from_slice(&16_u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        out.resize(out.len() + data_len as usize, 0);
+        out
+    }
+
+    #[test]
+    fn should_map_failure_names() {
+        for kind in VerifyFailure::ALL {
+            assert_eq!(VerifyFailure::from_number(kind.number()), Some(kind));
+            assert_eq!(VerifyFailure::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(VerifyFailure::from_number(0), None);
+        assert_eq!(VerifyFailure::from_name("broken"), None);
+    }
+
+    #[test]
+    fn should_verify_files() {
+        let dir = std::env::temp_dir().join(format!("termusic-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let valid = dir.join("valid.wav");
+        fs::write(&valid, wav(8000)).unwrap();
+        assert_eq!(verify_file(&valid), Ok(()));
+
+        let garbage = dir.join("garbage.mp3");
+        fs::write(&garbage, "not audio at all").unwrap();
+        assert_eq!(
+            verify_file(&garbage).unwrap_err().kind,
+            VerifyFailure::Unreadable
+        );
+
+        assert_eq!(
+            verify_file(&dir.join("missing.flac")).unwrap_err().kind,
+            VerifyFailure::Unreadable
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,191 @@
+//! The auto-DJ, choosing tracks to fill the playlist with based on the play history in the database.
+//!
+//! Tracks that have been played often are "familiar" and rarely played tracks are "unexplored", the
+//! [`exploration`](AutoDjSettings::exploration) decides which are preferred. Familiar tracks are preferred the longer
+//! they have not been played, and tracks played within [`replay_after_hours`](AutoDjSettings::replay_after_hours) are not
+//! chosen at all.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use rand::Rng;
+
+use crate::config::v2::server::auto_dj::AutoDjSettings;
+use crate::new_database::track_ops::{PlayStats, TrackRead};
+
+/// The days after which a familiar track is fully "fresh" again and not weighted down anymore.
+const STALE_DAYS: f64 = 30.0;
+
+/// Choose up to `amount` tracks from `candidates`, weighted by their [`PlayStats`].
+///
+/// Candidates with a weight of `0`, like never played tracks without any exploration, are only chosen if there are
+/// not enough others.
+#[must_use]
+pub fn choose<R: Rng + ?Sized>(
+    candidates: Vec<(TrackRead, PlayStats)>,
+    amount: usize,
+    settings: &AutoDjSettings,
+    now: DateTime<Utc>,
+    rng: &mut R,
+) -> Vec<TrackRead> {
+    let max_plays = candidates
+        .iter()
+        .map(|(_, stats)| stats.play_count)
+        .max()
+        .unwrap_or_default();
+    let replay_after = TimeDelta::hours(i64::from(settings.replay_after_hours));
+    let exploration = f64::from(settings.exploration());
+
+    // weighted sampling without replacement, the highest keys are chosen (Efraimidis & Spirakis)
+    let mut keyed: Vec<(f64, TrackRead)> = candidates
+        .into_iter()
+        .filter(|(_, stats)| {
+            stats
+                .last_played_at
+                .is_none_or(|last| now - last >= replay_after)
+        })
+        .map(|(track, stats)| {
+            let weight = weight(&stats, max_plays, exploration, now);
+            let random: f64 = rng.random();
+            let key = if weight > 0.0 {
+                random.powf(1.0 / weight)
+            } else {
+                // after all weighted candidates, in random order
+                -random
+            };
+
+            (key, track)
+        })
+        .collect();
+
+    keyed.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    keyed.truncate(amount);
+
+    keyed.into_iter().map(|(_, track)| track).collect()
+}
+
+/// Get the weight of a track to be chosen, from `0` to `1`.
+#[allow(clippy::cast_precision_loss)]
+fn weight(stats: &PlayStats, max_plays: u64, exploration: f64, now: DateTime<Utc>) -> f64 {
+    // logarithmic, so that a few very often played tracks do not make all others "unfamiliar"
+    let familiarity = if max_plays == 0 {
+        0.0
+    } else {
+        (stats.play_count as f64).ln_1p() / (max_plays as f64).ln_1p()
+    };
+    let staleness = stats.last_played_at.map_or(1.0, |last| {
+        ((now - last).num_hours() as f64 / 24.0 / STALE_DAYS).clamp(0.0, 1.0)
+    });
+
+    (1.0 - exploration) * familiarity * staleness + exploration * (1.0 - familiarity)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    use chrono::{DateTime, TimeDelta, Utc};
+    use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::choose;
+    use crate::config::v2::server::auto_dj::AutoDjSettings;
+    use crate::new_database::track_ops::{PlayStats, TrackRead};
+
+    /// Create a candidate `stem`, played `play_count` times with the last play `days_ago`.
+    fn candidate(
+        stem: &str,
+        play_count: u64,
+        days_ago: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> (TrackRead, PlayStats) {
+        let track = TrackRead {
+            id: 0,
+            file_dir: PathBuf::from("/somewhere"),
+            file_stem: OsString::from(stem),
+            file_ext: OsString::from("ext"),
+            duration: None,
+            last_position: None,
+            album: None,
+            title: Some(stem.to_string()),
+            genre: None,
+            artist_display: None,
+            artists: Vec::new(),
+        };
+        let stats = PlayStats {
+            play_count,
+            last_played_at: days_ago.map(|v| now - TimeDelta::days(v)),
+            label: None,
+        };
+
+        (track, stats)
+    }
+
+    fn titles(tracks: &[TrackRead]) -> Vec<&str> {
+        tracks.iter().filter_map(|v| v.title.as_deref()).collect()
+    }
+
+    #[test]
+    fn should_prefer_familiar_or_unexplored() {
+        let now = Utc::now();
+        let candidates = || {
+            vec![
+                candidate("familiar", 10, Some(60), now),
+                candidate("new", 0, None, now),
+            ]
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let settings = AutoDjSettings {
+            exploration: 0.0,
+            ..Default::default()
+        };
+        let chosen = choose(candidates(), 1, &settings, now, &mut rng);
+        assert_eq!(titles(&chosen), ["familiar"]);
+
+        let settings = AutoDjSettings {
+            exploration: 1.0,
+            ..Default::default()
+        };
+        let chosen = choose(candidates(), 1, &settings, now, &mut rng);
+        assert_eq!(titles(&chosen), ["new"]);
+
+        // weightless candidates fill up
+        let chosen = choose(candidates(), 5, &settings, now, &mut rng);
+        assert_eq!(titles(&chosen), ["new", "familiar"]);
+    }
+
+    #[test]
+    fn should_skip_recently_played() {
+        let now = Utc::now();
+        let candidates = vec![
+            candidate("today", 10, Some(0), now),
+            candidate("last_week", 10, Some(7), now),
+        ];
+        let settings = AutoDjSettings {
+            exploration: 0.0,
+            replay_after_hours: 48,
+            ..Default::default()
+        };
+
+        let chosen = choose(candidates, 2, &settings, now, &mut StdRng::seed_from_u64(1));
+        assert_eq!(titles(&chosen), ["last_week"]);
+    }
+
+    #[test]
+    fn should_limit_exploration() {
+        let settings = AutoDjSettings {
+            exploration: 5.0,
+            ..Default::default()
+        };
+        assert!((settings.exploration() - 1.0).abs() < f32::EPSILON);
+
+        let settings = AutoDjSettings {
+            exploration: f32::NAN,
+            ..Default::default()
+        };
+        assert!((settings.exploration() - 0.3).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,291 @@
+//! Process-wide in-memory caches, each bounded by a byte budget instead of a entry count.
+//!
+//! The budgets are configured via [`CacheSettings`], the current usage is available via [`Caches::usage`],
+//! which is also exposed in the [metrics](crate::metrics).
+
+use std::{
+    hash::Hash,
+    path::PathBuf,
+    sync::{Arc, LazyLock},
+};
+
+use lofty::picture::Picture;
+use lru::LruCache;
+use parking_lot::Mutex;
+
+use crate::{config::v2::server::cache::CacheSettings, songtag::lrc::Caption, track::LyricData};
+
+/// The global cache instances.
+pub static CACHES: LazyLock<Caches> = LazyLock::new(|| Caches::new(&CacheSettings::default()));
+
+/// All caches that are managed.
+#[derive(Debug)]
+pub struct Caches {
+    /// Cover art, by track path
+    pub picture: ByteLru<PathBuf, Arc<Picture>>,
+    /// Embedded lyrics, by track path
+    pub lyric: ByteLru<PathBuf, Arc<LyricData>>,
+}
+
+impl Caches {
+    fn new(settings: &CacheSettings) -> Self {
+        Self {
+            picture: ByteLru::new("picture", budget(settings.picture_budget)),
+            lyric: ByteLru::new("lyric", budget(settings.lyric_budget)),
+        }
+    }
+
+    /// Apply new budgets, evicting entries if a cache is now over its budget.
+    pub fn apply_settings(&self, settings: &CacheSettings) {
+        self.picture.set_budget(budget(settings.picture_budget));
+        self.lyric.set_budget(budget(settings.lyric_budget));
+    }
+
+    /// Get the current usage of all caches.
+    #[must_use]
+    pub fn usage(&self) -> [CacheUsage; 2] {
+        [self.picture.usage(), self.lyric.usage()]
+    }
+}
+
+fn budget(size: bytesize::ByteSize) -> usize {
+    usize::try_from(size.as_u64()).unwrap_or(usize::MAX)
+}
+
+/// A snapshot of a cache's usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheUsage {
+    pub name: &'static str,
+    pub entries: usize,
+    pub bytes: usize,
+    pub budget: usize,
+}
+
+/// The approximate amount of heap bytes a cached value uses.
+pub trait CacheWeight {
+    fn weight(&self) -> usize;
+}
+
+impl CacheWeight for PathBuf {
+    fn weight(&self) -> usize {
+        self.as_os_str().len()
+    }
+}
+
+impl CacheWeight for Arc<Picture> {
+    fn weight(&self) -> usize {
+        size_of::<Picture>() + self.data().len()
+    }
+}
+
+impl CacheWeight for Arc<LyricData> {
+    fn weight(&self) -> usize {
+        let raw: usize = self
+            .raw_lyrics
+            .iter()
+            .map(|v| v.lang.len() + v.description.len() + v.text.len())
+            .sum();
+        // the parsed lyric is taken from the first frame, so its text is about the same size
+        let parsed = self.parsed_lyrics.as_ref().map_or(0, |v| {
+            v.captions.len() * size_of::<Caption>()
+                + self.raw_lyrics.first().map_or(0, |v| v.text.len())
+        });
+
+        size_of::<LyricData>() + raw + parsed
+    }
+}
+
+/// A least-recently-used cache that evicts entries once the weight of all entries is over the budget.
+///
+/// A budget of `0` disables the cache.
+#[derive(Debug)]
+pub struct ByteLru<K: Hash + Eq, V> {
+    name: &'static str,
+    inner: Mutex<ByteLruInner<K, V>>,
+}
+
+#[derive(Debug)]
+struct ByteLruInner<K: Hash + Eq, V> {
+    cache: LruCache<K, V>,
+    bytes: usize,
+    budget: usize,
+}
+
+impl<K: Hash + Eq + CacheWeight, V: Clone + CacheWeight> ByteLru<K, V> {
+    #[must_use]
+    pub fn new(name: &'static str, budget: usize) -> Self {
+        Self {
+            name,
+            inner: Mutex::new(ByteLruInner {
+                cache: LruCache::unbounded(),
+                bytes: 0,
+                budget,
+            }),
+        }
+    }
+
+    /// Get a clone of the value for `key`, marking it as recently used.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.inner.lock().cache.get(key).cloned()
+    }
+
+    /// Get the value for `key`, or insert the value created by `fun`.
+    ///
+    /// `fun` is called without holding the lock, so it may be called concurrently for the same key.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `fun`, nothing is inserted in that case.
+    pub fn try_get_or_insert<E>(&self, key: K, fun: impl FnOnce() -> Result<V, E>) -> Result<V, E> {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+
+        let value = fun()?;
+        self.insert(key, value.clone());
+
+        Ok(value)
+    }
+
+    /// Insert `value` for `key`, evicting the least recently used entries until it fits the budget.
+    ///
+    /// Values that are bigger than the whole budget are not inserted.
+    pub fn insert(&self, key: K, value: V) {
+        let weight = key.weight() + value.weight();
+        let mut inner = self.inner.lock();
+        if weight > inner.budget {
+            return;
+        }
+
+        if let Some((old_key, old_value)) = inner.cache.push(key, value) {
+            inner.bytes -= old_key.weight() + old_value.weight();
+        }
+        inner.bytes += weight;
+        inner.evict();
+    }
+
+    /// Change the budget, evicting entries if the cache is now over it.
+    pub fn set_budget(&self, budget: usize) {
+        let mut inner = self.inner.lock();
+        inner.budget = budget;
+        inner.evict();
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock();
+        inner.cache.clear();
+        inner.bytes = 0;
+    }
+
+    #[must_use]
+    pub fn usage(&self) -> CacheUsage {
+        let inner = self.inner.lock();
+
+        CacheUsage {
+            name: self.name,
+            entries: inner.cache.len(),
+            bytes: inner.bytes,
+            budget: inner.budget,
+        }
+    }
+}
+
+impl<K: Hash + Eq + CacheWeight, V: CacheWeight> ByteLruInner<K, V> {
+    /// Evict the least recently used entries until the budget is kept.
+    fn evict(&mut self) {
+        while self.bytes > self.budget {
+            let Some((key, value)) = self.cache.pop_lru() else {
+                break;
+            };
+            self.bytes -= key.weight() + value.weight();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{ByteLru, CacheUsage, CacheWeight};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Sized(usize);
+
+    impl CacheWeight for Sized {
+        fn weight(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn key(name: &str) -> PathBuf {
+        PathBuf::from(name)
+    }
+
+    #[test]
+    fn should_evict_least_recently_used() {
+        let cache = ByteLru::new("test", 31);
+        cache.insert(key("a"), Sized(10));
+        cache.insert(key("b"), Sized(10));
+        // mark "a" as recently used
+        assert_eq!(cache.get(&key("a")), Some(Sized(10)));
+        cache.insert(key("c"), Sized(10));
+
+        assert_eq!(cache.get(&key("b")), None);
+        assert_eq!(cache.get(&key("a")), Some(Sized(10)));
+        assert_eq!(
+            cache.usage(),
+            CacheUsage {
+                name: "test",
+                entries: 2,
+                bytes: 22,
+                budget: 31
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_insert_over_budget() {
+        let cache = ByteLru::new("test", 10);
+        cache.insert(key("a"), Sized(5));
+        cache.insert(key("b"), Sized(20));
+
+        assert_eq!(cache.get(&key("a")), Some(Sized(5)));
+        assert_eq!(cache.get(&key("b")), None);
+
+        // replacing a value accounts for the old one
+        cache.insert(key("a"), Sized(8));
+        assert_eq!(cache.usage().bytes, 9);
+    }
+
+    #[test]
+    fn should_evict_on_budget_change() {
+        let cache = ByteLru::new("test", 100);
+        cache.insert(key("a"), Sized(10));
+        cache.insert(key("b"), Sized(10));
+
+        cache.set_budget(15);
+        assert_eq!(cache.usage().entries, 1);
+        assert_eq!(cache.get(&key("b")), Some(Sized(10)));
+
+        cache.set_budget(0);
+        assert_eq!(cache.usage().entries, 0);
+        assert_eq!(cache.usage().bytes, 0);
+    }
+
+    #[test]
+    fn should_not_cache_errors() {
+        let cache: ByteLru<PathBuf, Sized> = ByteLru::new("test", 100);
+
+        let res: Result<Sized, ()> = cache.try_get_or_insert(key("a"), || Err(()));
+        assert!(res.is_err());
+        assert_eq!(cache.usage().entries, 0);
+
+        let res: Result<Sized, ()> = cache.try_get_or_insert(key("a"), || Ok(Sized(1)));
+        assert_eq!(res, Ok(Sized(1)));
+        let res: Result<Sized, ()> = cache.try_get_or_insert(key("a"), || Ok(Sized(2)));
+        assert_eq!(res, Ok(Sized(1)));
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for the optional endpoint streaming the playing track to remote clients, like a phone on mobile data.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct AudioStreamSettings {
+    /// Enable streaming the playing track on `http://address:port/stream`
+    pub enable: bool,
+    /// Audio stream endpoint Port
+    pub port: u16,
+    /// Audio stream endpoint interface / address
+    pub address: IpAddr,
+    /// The bitrate in kbit/s lossless tracks are transcoded to Opus with, `0` to send them as-is
+    ///
+    /// A client can request a other bitrate with `?bitrate=KBITS`, lossy tracks are always sent as-is.
+    pub bitrate: u16,
+    /// The bitrate by client name, for clients identifying with `?client=NAME`, like `{ phone = 64 }`
+    pub clients: BTreeMap<String, u16>,
+}
+
+impl Default for AudioStreamSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            port: 50104,
+            address: "::1".parse().unwrap(),
+            bitrate: 128,
+            clients: BTreeMap::new(),
+        }
+    }
+}
+
+impl AudioStreamSettings {
+    /// Get the bitrate for lossless tracks, [`None`] to send them as-is.
+    ///
+    /// The bitrate `requested` by the client is preferred over the one configured for the `client` name.
+    #[must_use]
+    pub fn bitrate_for(&self, client: Option<&str>, requested: Option<u16>) -> Option<u16> {
+        let bitrate = requested
+            .or_else(|| client.and_then(|v| self.clients.get(v).copied()))
+            .unwrap_or(self.bitrate);
+
+        (bitrate > 0).then_some(bitrate)
+    }
+}
+
+impl From<&AudioStreamSettings> for SocketAddr {
+    fn from(value: &AudioStreamSettings) -> Self {
+        Self::new(value.address, value.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AudioStreamSettings;
+
+    #[test]
+    fn should_pick_bitrate() {
+        let mut settings = AudioStreamSettings::default();
+        settings.clients.insert("phone".to_string(), 64);
+        settings.clients.insert("desktop".to_string(), 0);
+
+        assert_eq!(settings.bitrate_for(None, None), Some(128));
+        assert_eq!(settings.bitrate_for(Some("unknown"), None), Some(128));
+        assert_eq!(settings.bitrate_for(Some("phone"), None), Some(64));
+        assert_eq!(settings.bitrate_for(Some("phone"), Some(32)), Some(32));
+        assert_eq!(settings.bitrate_for(Some("desktop"), None), None);
+        assert_eq!(settings.bitrate_for(None, Some(0)), None);
+    }
+}
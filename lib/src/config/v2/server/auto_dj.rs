@@ -0,0 +1,55 @@
+use std::num::NonZeroU32;
+
+use serde::{Deserialize, Serialize};
+
+use crate::new_database::track_ops::TrackLabel;
+
+/// Settings for the auto-DJ, which fills the playlist based on the play history, see [`auto_dj`](crate::auto_dj).
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct AutoDjSettings {
+    /// Add more tracks chosen by the auto-DJ whenever the last track of the playlist starts playing
+    pub enabled: bool,
+    /// Amount of tracks to add at once
+    pub tracks: NonZeroU32,
+    /// How much to prefer rarely played tracks over the most played ones, from `0.0` (only familiar tracks) to `1.0`
+    /// (only rarely played tracks)
+    pub exploration: f32,
+    /// Hours that need to pass after a track was played before it is chosen again
+    pub replay_after_hours: u32,
+    /// The color labels (`1` red to `6` magenta) of tracks to never choose, like a label for disliked tracks
+    pub exclude_labels: Vec<u8>,
+}
+
+impl Default for AutoDjSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tracks: NonZeroU32::new(10).unwrap(),
+            exploration: 0.3,
+            replay_after_hours: 24,
+            exclude_labels: Vec::new(),
+        }
+    }
+}
+
+impl AutoDjSettings {
+    /// Get the [`exploration`](Self::exploration), limited to the valid range.
+    #[must_use]
+    pub fn exploration(&self) -> f32 {
+        if self.exploration.is_nan() {
+            return Self::default().exploration;
+        }
+
+        self.exploration.clamp(0.0, 1.0)
+    }
+
+    /// Get the labels in [`exclude_labels`](Self::exclude_labels), ignoring unknown numbers.
+    #[must_use]
+    pub fn exclude_labels(&self) -> Vec<TrackLabel> {
+        self.exclude_labels
+            .iter()
+            .filter_map(|v| TrackLabel::from_number(*v))
+            .collect()
+    }
+}
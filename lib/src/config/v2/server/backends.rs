@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bytesize::ByteSize;
 use serde::{Deserialize, Serialize};
 
@@ -29,7 +31,12 @@ pub const DECODEDBUF_SIZE_DEFAULT: u64 = 192_000 * size_of::<f32>() as u64;
 #[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
 pub struct RustyBackendSettings {
     /// Enable or disable `soundtouch`; only has a effect if `rusty-soundtouch` is compiled-in
+    /// and `preserve_pitch` is enabled, instead of the built-in time-stretch.
     pub soundtouch: bool,
+    /// Keep the pitch when changing the playback speed, instead of higher voices when faster.
+    ///
+    /// Default `true`
+    pub preserve_pitch: bool,
     /// Set the buffer size for the raw file.
     /// This value will be clamped to the actual file's size.
     /// Note this only applies to local files like music or downloaded podcasts. Does not apply to streamed podcasts or radio.
@@ -48,15 +55,145 @@ pub struct RustyBackendSettings {
     /// Default `48_000`
     /// Recommeded Values: `44_100`, `48_000`, `96_000` `192_000`.
     pub output_sample_rate: u32,
+    /// How long to fade-in after resuming, seeking and starting playback, in milliseconds.
+    /// Gapless track changes are not faded. This is independent of any crossfade between tracks.
+    ///
+    /// `0` disables the fade-in. Default `30`
+    pub fade_in_ms: u32,
+    /// How long to fade-out before pausing, seeking and stopping, in milliseconds.
+    ///
+    /// `0` disables the fade-out. Default `30`
+    pub fade_out_ms: u32,
+    /// How long the current track fades out while the next one fades in, in seconds, at most `15`.
+    /// Applies to track endings and to manual track changes, but consecutive tracks of the same album are not
+    /// crossfaded at their end, to keep gapless albums gapless.
+    ///
+    /// `0` disables the crossfade. Default `0`
+    pub crossfade_secs: u8,
+    /// The volume curve of the crossfade.
+    pub crossfade_curve: CrossfadeCurve,
+    /// Which volume the volume controls change.
+    pub volume_control: VolumeControl,
+    /// The ALSA mixer device to use for hardware volume, see `aplay -L`.
+    ///
+    /// Default `default`, which is usually the sound server if one is running.
+    pub mixer_device: String,
+    /// The ALSA mixer control to use for hardware volume, see `amixer scontrols`.
+    ///
+    /// Default `Master`
+    pub mixer_control: String,
+    /// Mute corrupt frames of local files instead of aborting the track, and flag the file as damaged in the database.
+    ///
+    /// The muted regions are logged and can be listed with `termusic-server db damaged`.
+    ///
+    /// Default `false`
+    pub salvage: bool,
+    /// The name of the output device to play on, as listed in the output device popup of the TUI.
+    /// If it is not available, the default device is used until it appears.
+    ///
+    /// Default empty, which is the default device of the system
+    pub output_device: String,
+    /// What to do when the output device played on disappears, like unplugged headphones or a bluetooth dropout.
+    ///
+    /// Default `pause`
+    pub device_loss: DeviceLossBehavior,
+}
+
+/// The maximal [`RustyBackendSettings::crossfade_secs`].
+pub const CROSSFADE_SECS_MAX: u8 = 15;
+
+impl RustyBackendSettings {
+    /// Get the duration of the crossfade, clamped to [`CROSSFADE_SECS_MAX`].
+    #[must_use]
+    pub fn crossfade(&self) -> Duration {
+        Duration::from_secs(self.crossfade_secs.min(CROSSFADE_SECS_MAX).into())
+    }
+}
+
+/// The volume curve of a crossfade.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrossfadeCurve {
+    /// Change the volume evenly.
+    #[default]
+    Linear,
+    /// Change the volume slowly at the quiet end and quickly at the loud end, which sounds more even.
+    Exponential,
+}
+
+impl CrossfadeCurve {
+    /// Get the gain at `progress` of a fade-in, both between `0.0` and `1.0`. A fade-out is the fade-in backwards.
+    #[must_use]
+    pub fn gain(self, progress: f32) -> f32 {
+        /// How steep the exponential curve is, about -43 dB at the start
+        const STEEPNESS: f32 = 5.0;
+
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => progress,
+            Self::Exponential => (STEEPNESS * progress).exp_m1() / STEEPNESS.exp_m1(),
+        }
+    }
+}
+
+/// Which volume the volume controls change.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeControl {
+    /// Only change the software gain of termusic.
+    #[default]
+    Software,
+    /// Only change the system mixer volume (ALSA), the software gain stays at 100%.
+    Hardware,
+    /// Change both the system mixer volume and the software gain.
+    Both,
+}
+
+/// What to do when the output device played on disappears.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceLossBehavior {
+    /// Continue on the fallback device.
+    Ignore,
+    /// Pause before continuing on the fallback device.
+    #[default]
+    Pause,
+    /// Pause before continuing on the fallback device, and resume once the lost device appears again.
+    PauseAndResume,
+}
+
+impl DeviceLossBehavior {
+    /// Whether to pause once the device is lost.
+    #[must_use]
+    pub fn pauses(self) -> bool {
+        matches!(self, Self::Pause | Self::PauseAndResume)
+    }
+
+    /// Whether to resume once the lost device appears again, if paused because of its loss.
+    #[must_use]
+    pub fn resumes(self) -> bool {
+        self == Self::PauseAndResume
+    }
 }
 
 impl Default for RustyBackendSettings {
     fn default() -> Self {
         Self {
             soundtouch: true,
+            preserve_pitch: true,
             file_buffer_size: ByteSize::b(FILEBUF_SIZE_DEFAULT),
             decoded_buffer_size: ByteSize::b(DECODEDBUF_SIZE_DEFAULT),
             output_sample_rate: 48_000,
+            fade_in_ms: 30,
+            fade_out_ms: 30,
+            crossfade_secs: 0,
+            crossfade_curve: CrossfadeCurve::default(),
+            volume_control: VolumeControl::default(),
+            mixer_device: "default".to_string(),
+            mixer_control: "Master".to_string(),
+            salvage: false,
+            output_device: String::new(),
+            device_loss: DeviceLossBehavior::default(),
         }
     }
 }
@@ -87,3 +224,35 @@ impl Default for MpvBackendSettings {
 pub struct GstBackendSettings {
     // None for now
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{CrossfadeCurve, RustyBackendSettings};
+
+    #[test]
+    fn should_clamp_crossfade() {
+        let mut settings = RustyBackendSettings {
+            crossfade_secs: 5,
+            ..Default::default()
+        };
+        assert_eq!(settings.crossfade(), Duration::from_secs(5));
+
+        settings.crossfade_secs = 60;
+        assert_eq!(settings.crossfade(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn should_follow_crossfade_curves() {
+        for curve in [CrossfadeCurve::Linear, CrossfadeCurve::Exponential] {
+            assert!(curve.gain(0.0).abs() < f32::EPSILON);
+            assert!((curve.gain(1.0) - 1.0).abs() < f32::EPSILON);
+            assert!(curve.gain(0.25) < curve.gain(0.75));
+        }
+
+        assert!((CrossfadeCurve::Linear.gain(0.5) - 0.5).abs() < f32::EPSILON);
+        // quieter for longer than linear
+        assert!(CrossfadeCurve::Exponential.gain(0.5) < 0.1);
+    }
+}
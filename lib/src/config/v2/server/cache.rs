@@ -0,0 +1,27 @@
+use bytesize::ByteSize;
+use serde::{Deserialize, Serialize};
+
+/// Byte budgets for the in-memory caches, see [`CACHES`](crate::cache::CACHES).
+///
+/// A budget of `0` disables that cache.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct CacheSettings {
+    /// Budget for decoded cover art, the biggest cache as covers are often multiple MiB
+    ///
+    /// Default `16 MiB`
+    pub picture_budget: ByteSize,
+    /// Budget for embedded lyrics
+    ///
+    /// Default `1 MiB`
+    pub lyric_budget: ByteSize,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            picture_budget: ByteSize::mib(16),
+            lyric_budget: ByteSize::mib(1),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for the command bus, accepting newline-delimited JSON commands on a Unix socket, like from shell scripts.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct CommandBusSettings {
+    /// Enable listening for commands on [`socket_path`](Self::socket_path), only available on unix
+    pub enable: bool,
+    /// The socket to listen on, it is replaced if it already exists
+    pub socket_path: PathBuf,
+}
+
+impl Default for CommandBusSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            socket_path: std::env::temp_dir().join("termusic-commands.socket"),
+        }
+    }
+}
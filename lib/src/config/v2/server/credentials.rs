@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Where API credentials are stored, see [`CREDENTIALS`](crate::credentials::CREDENTIALS).
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct CredentialsSettings {
+    /// The store to save and look up credentials in
+    pub store: CredentialStore,
+}
+
+/// A store for credentials.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialStore {
+    /// Use the system keyring, falling back to the file if it is unavailable.
+    #[default]
+    Auto,
+    /// Only use the system keyring (Secret Service via `secret-tool` on linux, Keychain on macOS).
+    Keyring,
+    /// Only use the plain-text `credentials.toml` in the config directory, readable only by the current user.
+    File,
+}
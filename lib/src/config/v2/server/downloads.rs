@@ -0,0 +1,26 @@
+use std::num::{NonZeroU8, NonZeroU32};
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for the download queue of youtube videos.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct DownloadSettings {
+    /// Max concurrent downloads
+    // realistically, we dont have any more than 255 running
+    pub concurrent_downloads_max: NonZeroU8,
+    /// How often a failed download is retried before it is shown as failed
+    pub max_download_retries: u8,
+    /// How long to wait before the first retry, doubled for every further retry
+    pub retry_delay_secs: NonZeroU32,
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self {
+            concurrent_downloads_max: NonZeroU8::new(2).unwrap(),
+            max_download_retries: 3,
+            retry_delay_secs: NonZeroU32::new(10).unwrap(),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings to lower the music while a notification sound or a spoken announcement plays.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct DuckingSettings {
+    /// Lower the volume by this many dB while ducked, `0` disables ducking
+    pub attenuation_db: u8,
+    /// Ramp the volume down and back up over this many milliseconds
+    pub ramp_ms: u16,
+    /// Restore the volume after this many seconds even if ducking was not ended, in case the announcement never
+    /// reports being done
+    pub max_secs: u16,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            attenuation_db: 12,
+            ramp_ms: 200,
+            max_secs: 30,
+        }
+    }
+}
+
+impl DuckingSettings {
+    /// Get the factor to multiply the volume with while ducked, none if disabled.
+    #[must_use]
+    pub fn factor(&self) -> Option<f64> {
+        (self.attenuation_db > 0).then(|| 10.0_f64.powf(-f64::from(self.attenuation_db) / 20.0))
+    }
+
+    #[must_use]
+    pub fn ramp(&self) -> Duration {
+        Duration::from_millis(u64::from(self.ramp_ms))
+    }
+
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        Duration::from_secs(u64::from(self.max_secs))
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+/// The amount of bands of the equalizer.
+pub const BANDS: usize = 10;
+
+/// The center frequencies of the bands in Hz, one octave apart.
+pub const BAND_FREQUENCIES: [u32; BANDS] =
+    [31, 62, 125, 250, 500, 1_000, 2_000, 4_000, 8_000, 16_000];
+
+/// The maximal boost or cut of a band in dB.
+pub const GAIN_MAX_DB: f32 = 12.0;
+
+/// Settings for the equalizer of the rusty backend, also changed by the clients.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct EqualizerSettings {
+    /// Whether to apply the equalizer
+    pub enabled: bool,
+    /// The preset to apply
+    pub preset: EqualizerPreset,
+    /// The gain in dB of each band from low to high, for the `custom` preset
+    pub custom: [f32; BANDS],
+}
+
+impl EqualizerSettings {
+    /// Get the gain in dB of each band of the current preset, clamped to [`GAIN_MAX_DB`].
+    #[must_use]
+    pub fn gains(&self) -> [f32; BANDS] {
+        let gains = match self.preset {
+            EqualizerPreset::Custom => self.custom,
+            preset => preset.gains(),
+        };
+
+        gains.map(|v| {
+            if v.is_finite() {
+                v.clamp(-GAIN_MAX_DB, GAIN_MAX_DB)
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Get the gains to apply, [`None`] if disabled.
+    #[must_use]
+    pub fn active_gains(&self) -> Option<[f32; BANDS]> {
+        self.enabled.then(|| self.gains())
+    }
+}
+
+/// The band gains of the equalizer.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EqualizerPreset {
+    /// All bands at 0 dB.
+    #[default]
+    Flat,
+    /// Boosted lows and highs.
+    Rock,
+    /// Slightly boosted lows and highs, cut high-mids.
+    Classical,
+    /// Boosted speech frequencies and cut lows, for podcasts.
+    Voice,
+    /// The gains of [`EqualizerSettings::custom`].
+    Custom,
+}
+
+impl EqualizerPreset {
+    pub const ALL: [Self; 5] = [
+        Self::Flat,
+        Self::Rock,
+        Self::Classical,
+        Self::Voice,
+        Self::Custom,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Flat => "flat",
+            Self::Rock => "rock",
+            Self::Classical => "classical",
+            Self::Voice => "voice",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// Get the preset named `name`, like it is returned by [`name`](Self::name).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name() == name)
+    }
+
+    /// Get the next preset, wrapping around to the first.
+    #[must_use]
+    pub fn next(self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|v| *v == self)
+            .unwrap_or_default();
+
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Get the gain in dB of each band of this preset, all 0 dB for [`Custom`](Self::Custom).
+    #[must_use]
+    pub fn gains(self) -> [f32; BANDS] {
+        match self {
+            Self::Flat | Self::Custom => [0.0; BANDS],
+            Self::Rock => [5.0, 4.0, 3.0, 1.0, -1.0, -1.0, 1.0, 3.0, 4.0, 5.0],
+            Self::Classical => [3.0, 2.0, 1.0, 0.0, 0.0, 0.0, -1.0, -2.0, 0.0, 2.0],
+            Self::Voice => [-6.0, -4.0, -2.0, 0.0, 2.0, 4.0, 4.0, 3.0, 0.0, -2.0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{BANDS, EqualizerPreset, EqualizerSettings};
+
+    #[test]
+    #[expect(clippy::float_cmp)] // the gains are only copied or clamped, which is exact
+    fn should_get_preset_gains() {
+        let mut settings = EqualizerSettings {
+            enabled: false,
+            preset: EqualizerPreset::Rock,
+            custom: [20.0; BANDS],
+        };
+        assert_eq!(settings.active_gains(), None);
+        assert_eq!(settings.gains(), EqualizerPreset::Rock.gains());
+
+        settings.preset = EqualizerPreset::Custom;
+        settings.custom[1] = f32::NAN;
+        settings.custom[2] = -3.0;
+        let mut expected = [12.0; BANDS];
+        expected[1] = 0.0;
+        expected[2] = -3.0;
+        assert_eq!(settings.gains(), expected);
+
+        assert_eq!(EqualizerPreset::Custom.next(), EqualizerPreset::Flat);
+        assert_eq!(
+            EqualizerPreset::from_name("voice"),
+            Some(EqualizerPreset::Voice)
+        );
+        assert_eq!(EqualizerPreset::from_name("unknown"), None);
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for announcing and controlling the server in Home Assistant over MQTT.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct HomeAssistantSettings {
+    /// Enable connecting to the MQTT broker
+    pub enable: bool,
+    /// The host of the MQTT broker, unencrypted; a login is looked up in the credentials as `mqtt:<host>`
+    pub broker_host: String,
+    pub broker_port: u16,
+    /// The prefix Home Assistant listens on for discovery messages, `homeassistant` unless changed in Home Assistant
+    pub discovery_prefix: String,
+    /// The id of this instance, used in all topics and ids; needs to be unique if there are multiple servers
+    pub node_id: String,
+    /// The device name shown in Home Assistant
+    pub name: String,
+}
+
+impl Default for HomeAssistantSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            broker_host: "localhost".to_string(),
+            broker_port: 1883,
+            discovery_prefix: "homeassistant".to_string(),
+            node_id: "termusic".to_string(),
+            name: "termusic".to_string(),
+        }
+    }
+}
+
+impl HomeAssistantSettings {
+    /// The topic all state and command topics are below.
+    #[must_use]
+    pub fn base_topic(&self) -> String {
+        format!("termusic/{}", self.node_id)
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings to pause playback for a break after listening for a while.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct ListeningLimitSettings {
+    /// Pause playback after listening this many minutes since the last break, `0` disables the limit
+    /// and the listening time shown in the clients
+    ///
+    /// Pausing for a while does not count as a break, only the pause by the limit resets the time.
+    pub limit_mins: u16,
+    /// Fade out over this many seconds before pausing
+    pub fade_secs: u8,
+}
+
+impl Default for ListeningLimitSettings {
+    fn default() -> Self {
+        Self {
+            limit_mins: 0,
+            fade_secs: 10,
+        }
+    }
+}
+
+impl ListeningLimitSettings {
+    /// Get the limit, none if disabled.
+    #[must_use]
+    pub fn limit(&self) -> Option<Duration> {
+        (self.limit_mins > 0).then(|| Duration::from_secs(u64::from(self.limit_mins) * 60))
+    }
+
+    #[must_use]
+    pub fn fade(&self) -> Duration {
+        Duration::from_secs(u64::from(self.fade_secs))
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// A named sequence of commands, run at once by the server, like to clear the playlist, add random tracks and shuffle.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct MacroSettings {
+    /// The name to run the macro by, like from a key in the TUI (`keys.macros`)
+    pub name: String,
+    /// The commands to run in order, each written like a command of the command bus, like `{ cmd = "PlaylistShuffle" }`
+    ///
+    /// Commands referring to a playlist index refer to the playlist as left by the commands before.
+    pub commands: Vec<serde_json::Value>,
+}
@@ -1,3 +1,8 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
 use serde::{Deserialize, Serialize};
 
 use crate::config::v2::server::ScanDepth;
@@ -21,6 +26,49 @@ pub struct MetadataSettings {
     ///
     /// After split, the Artist values are trimmed.
     pub artist_separators: Vec<String>,
+    /// How albums are inferred for tracks.
+    ///
+    /// Note that a changed value only applies to newly scanned tracks, a full rescan is necessary to apply it to all.
+    pub album_grouping: AlbumGrouping,
+    /// Per-directory overrides for [`album_grouping`](Self::album_grouping), the deepest matching directory is used.
+    ///
+    /// For example `"~/Music/Unsorted" = "directory"`.
+    pub album_grouping_dirs: HashMap<PathBuf, AlbumGrouping>,
+    /// Estimate the energy of tracks from their audio while scanning, to list them as "chill" or "high energy", and
+    /// describe their mood and sound for smart playlists.
+    ///
+    /// This decodes the first minutes of every scanned track, which makes scanning considerably slower.
+    pub analyze_audio: bool,
+    /// Watch the music directories while the server runs, to add, update and remove tracks when files change on disk.
+    ///
+    /// Large libraries may need a higher `fs.inotify.max_user_watches` on linux.
+    pub watch_library: bool,
+}
+
+/// How albums are inferred for a track.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlbumGrouping {
+    /// Only use the album and album artist tags, tracks missing either have no album
+    #[default]
+    Tag,
+    /// Use the directory name as the album and the directory above it as the album artist (if there is no album artist tag)
+    Directory,
+    /// Use the tags if available, otherwise fallback to the directory like [`AlbumGrouping::Directory`]
+    Hybrid,
+}
+
+impl MetadataSettings {
+    /// Get the [`AlbumGrouping`] to use for the track at `path`.
+    #[must_use]
+    pub fn album_grouping_for(&self, path: &Path) -> AlbumGrouping {
+        self.album_grouping_dirs
+            .iter()
+            .map(|(dir, grouping)| (shellexpand::path::tilde(dir), grouping))
+            .filter(|(dir, _)| path.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.components().count())
+            .map_or(self.album_grouping, |(_, grouping)| *grouping)
+    }
 }
 
 /// The default and most common separators used for artists.
@@ -35,6 +83,46 @@ impl Default for MetadataSettings {
                 .iter()
                 .map(ToString::to_string)
                 .collect(),
+            album_grouping: AlbumGrouping::default(),
+            album_grouping_dirs: HashMap::new(),
+            analyze_audio: false,
+            watch_library: true,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{AlbumGrouping, MetadataSettings};
+
+    #[test]
+    fn should_use_deepest_grouping_override() {
+        let settings = MetadataSettings {
+            album_grouping: AlbumGrouping::Tag,
+            album_grouping_dirs: [
+                (PathBuf::from("/music/unsorted"), AlbumGrouping::Directory),
+                (
+                    PathBuf::from("/music/unsorted/mixed"),
+                    AlbumGrouping::Hybrid,
+                ),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            settings.album_grouping_for(Path::new("/music/sorted/a.mp3")),
+            AlbumGrouping::Tag
+        );
+        assert_eq!(
+            settings.album_grouping_for(Path::new("/music/unsorted/album/a.mp3")),
+            AlbumGrouping::Directory
+        );
+        assert_eq!(
+            settings.album_grouping_for(Path::new("/music/unsorted/mixed/a.mp3")),
+            AlbumGrouping::Hybrid
+        );
+    }
+}
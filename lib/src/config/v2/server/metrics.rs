@@ -0,0 +1,31 @@
+use std::net::{IpAddr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for the optional Prometheus metrics endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct MetricsSettings {
+    /// Enable serving metrics on `http://address:port/metrics`
+    pub enable: bool,
+    /// Metrics endpoint Port
+    pub port: u16,
+    /// Metrics endpoint interface / address
+    pub address: IpAddr,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            port: 50102,
+            address: "::1".parse().unwrap(),
+        }
+    }
+}
+
+impl From<&MetricsSettings> for SocketAddr {
+    fn from(value: &MetricsSettings) -> Self {
+        Self::new(value.address, value.port)
+    }
+}
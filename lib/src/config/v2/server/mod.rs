@@ -8,13 +8,50 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 use crate::track::MediaTypesSimple;
+use crate::utils::{is_termux, termux_audio_dir};
+use audio_stream::AudioStreamSettings;
+use auto_dj::AutoDjSettings;
 use backends::BackendSettings;
+use cache::CacheSettings;
+use command_bus::CommandBusSettings;
+use credentials::CredentialsSettings;
+use downloads::DownloadSettings;
+use ducking::DuckingSettings;
+use equalizer::EqualizerSettings;
+use home_assistant::HomeAssistantSettings;
+use listening_limit::ListeningLimitSettings;
+use macros::MacroSettings;
 use metadata::MetadataSettings;
-
+use metrics::MetricsSettings;
+use now_playing::NowPlayingSettings;
+use recording::RecordingSettings;
+use sponsorblock::SponsorBlockSettings;
+use stream_overlay::StreamOverlaySettings;
+use termux::TermuxSettings;
+use webhooks::WebhookSettings;
+
+pub mod audio_stream;
+pub mod auto_dj;
 pub mod backends;
+pub mod cache;
+pub mod command_bus;
 /// Extra things necessary for a config file, like wrappers for versioning
 pub mod config_extra;
+pub mod credentials;
+pub mod downloads;
+pub mod ducking;
+pub mod equalizer;
+pub mod home_assistant;
+pub mod listening_limit;
+pub mod macros;
 pub mod metadata;
+pub mod metrics;
+pub mod now_playing;
+pub mod recording;
+pub mod sponsorblock;
+pub mod stream_overlay;
+pub mod termux;
+pub mod webhooks;
 
 pub type MusicDirsOwned = Vec<PathBuf>;
 
@@ -27,6 +64,25 @@ pub struct ServerSettings {
     pub podcast: PodcastSettings,
     pub backends: BackendSettings,
     pub metadata: MetadataSettings,
+    pub metrics: MetricsSettings,
+    pub recording: RecordingSettings,
+    pub now_playing: NowPlayingSettings,
+    pub stream_overlay: StreamOverlaySettings,
+    pub audio_stream: AudioStreamSettings,
+    pub cache: CacheSettings,
+    pub termux: TermuxSettings,
+    pub credentials: CredentialsSettings,
+    pub auto_dj: AutoDjSettings,
+    pub command_bus: CommandBusSettings,
+    pub webhooks: WebhookSettings,
+    pub home_assistant: HomeAssistantSettings,
+    pub sponsorblock: SponsorBlockSettings,
+    pub downloads: DownloadSettings,
+    pub listening_limit: ListeningLimitSettings,
+    pub equalizer: EqualizerSettings,
+    pub ducking: DuckingSettings,
+    /// Named sequences of commands, none by default
+    pub macros: Vec<MacroSettings>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -44,7 +100,7 @@ pub struct PodcastSettings {
 
 /// Get the default podcast dir, which uses OS-specific paths, or home/Music/podcast
 fn default_podcast_dir() -> PathBuf {
-    dirs::audio_dir().map_or_else(
+    audio_dir().map_or_else(
         || PathBuf::from(shellexpand::tilde("~/Music").as_ref()),
         |mut v| {
             v.push("podcast");
@@ -169,7 +225,7 @@ impl PositionYesNo {
                 PositionYesNoLower::Yes => match media_type {
                     MediaTypesSimple::Music => Some(DEFAULT_YES_TIME_BEFORE_SAVE_MUSIC),
                     MediaTypesSimple::Podcast => Some(DEFAULT_YES_TIME_BEFORE_SAVE_PODCAST),
-                    MediaTypesSimple::LiveRadio => None,
+                    MediaTypesSimple::LiveRadio | MediaTypesSimple::Video => None,
                 },
                 PositionYesNoLower::No => None,
             },
@@ -208,7 +264,7 @@ impl RememberLastPosition {
             RememberLastPosition::Depends { music, podcast } => match media_type {
                 MediaTypesSimple::Music => music.get_time(media_type),
                 MediaTypesSimple::Podcast => podcast.get_time(media_type),
-                MediaTypesSimple::LiveRadio => None,
+                MediaTypesSimple::LiveRadio | MediaTypesSimple::Video => None,
             },
         }
     }
@@ -224,8 +280,8 @@ impl RememberLastPosition {
             RememberLastPosition::Depends { music, podcast } => match media_type {
                 MediaTypesSimple::Music => music.is_enabled(),
                 MediaTypesSimple::Podcast => podcast.is_enabled(),
-                // liveradio cannot store a position
-                MediaTypesSimple::LiveRadio => false,
+                // liveradio cannot store a position, and videos are not in the database to store one
+                MediaTypesSimple::LiveRadio | MediaTypesSimple::Video => false,
             },
         }
     }
@@ -242,6 +298,7 @@ impl Default for RememberLastPosition {
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+#[allow(clippy::struct_excessive_bools)] // configuration, this is not a state machine
 pub struct PlayerSettings {
     /// Music Directories
     pub music_dirs: MusicDirsOwned,
@@ -254,23 +311,51 @@ pub struct PlayerSettings {
     pub library_scan_depth: ScanDepth,
     /// Set if the position should be remembered for tracks
     pub remember_position: RememberLastPosition,
+    /// Continue after a restart where playback was, even if killed: the current track of the queue is saved every few
+    /// seconds with its position and played from there on the next start, regardless of `remember_position`
+    pub resume_playback: bool,
+    /// Keep a automatic "Last position" bookmark of files at least this many minutes long, like audiobooks and long
+    /// mixes, updated whenever playback leaves them. `0` disables it
+    pub auto_bookmark_minutes: u32,
 
     /// Playlist loop mode
     pub loop_mode: LoopMode,
+    /// Play the whole album in order when a track is started, before returning to the loop mode
+    pub album_mode: bool,
+    /// Normalize the loudness of tracks with their `ReplayGain` or R128 tags, the initial state of the normalization toggle
+    pub normalization: bool,
+    /// Which gain to apply when normalizing
+    pub normalization_mode: NormalizationMode,
+    /// Trim the silence at the start and the end of every track, only supported by the rusty backend
+    pub trim_silence: bool,
+    /// Shorten long silences in podcast episodes, the initial state of the smart speed toggle, only supported by the
+    /// rusty backend
+    pub smart_speed: bool,
+    /// How long silences in podcast episodes may last with `smart_speed`, longer ones are shortened to this, in
+    /// milliseconds
+    pub smart_speed_silence_ms: u32,
     /// Volume, how loud something is
     pub volume: u16,
     /// Speed, both positive (forward) or negative (backwards)
     ///
-    /// speed / 10 = actual speed (float but not floats)
+    /// speed / 10 = actual speed (float but not floats), stepped between `5` and `30` (0.5x to 3.0x).
+    /// Podcasts remember their own speed instead, once changed while one of their episodes plays.
     // the number should never be 0, because that would effectively be paused forever
     pub speed: i32,
     /// Enable gapless decoding & prefetching the next track
     pub gapless: bool,
     /// How much to seek on a seek event
     pub seek_step: SeekStep,
+    /// What to do when a track fails to play
+    pub on_error: PlaybackErrorPolicy,
 
     /// Controls if support via Media-Controls (like mpris on linux) is enabled
     pub use_mediacontrols: bool,
+    /// The name desktop environments show for the Media-Controls, the default if empty
+    pub mediacontrols_identity: String,
+    /// The instance name to tell multiple servers apart, like `work` to register mpris as
+    /// `org.mpris.MediaPlayer2.termusic.work`, none if empty
+    pub mediacontrols_instance: String,
     /// Controls if discord status setting is enabled
     pub set_discord_status: bool,
 
@@ -278,6 +363,8 @@ pub struct PlayerSettings {
     pub random_track_quantity: NonZeroU32,
     /// Minimal amount of tracks a album needs to have before being chosen for "random album add"
     pub random_album_min_quantity: NonZeroU32,
+    /// Minutes of never-played tracks to add on "random unplayed add"
+    pub random_unplayed_minutes: NonZeroU32,
 
     /// The backend to use
     pub backend: Backend,
@@ -285,9 +372,18 @@ pub struct PlayerSettings {
 
 /// Get the default Music dir, which uses OS-specific paths, or home/Music
 fn default_music_dirs() -> MusicDirsOwned {
-    Vec::from([
-        dirs::audio_dir().unwrap_or_else(|| PathBuf::from(shellexpand::tilde("~/Music").as_ref()))
-    ])
+    Vec::from(
+        [audio_dir().unwrap_or_else(|| PathBuf::from(shellexpand::tilde("~/Music").as_ref()))],
+    )
+}
+
+/// Get the OS-specific audio dir, which is the shared storage inside Termux, as the home is not accessible to other apps.
+fn audio_dir() -> Option<PathBuf> {
+    if is_termux() {
+        return termux_audio_dir();
+    }
+
+    dirs::audio_dir()
 }
 
 impl Default for PlayerSettings {
@@ -296,25 +392,82 @@ impl Default for PlayerSettings {
             music_dirs: default_music_dirs(),
             library_scan_depth: ScanDepth::Limited(0),
             remember_position: RememberLastPosition::default(),
+            resume_playback: true,
+            auto_bookmark_minutes: 20,
 
             loop_mode: LoopMode::default(),
+            album_mode: false,
+            normalization: false,
+            normalization_mode: NormalizationMode::default(),
+            trim_silence: false,
+            smart_speed: false,
+            smart_speed_silence_ms: 500,
             // rather use a lower value than a high so that ears dont get blown off
             volume: 30,
             speed: 10,
             gapless: true,
             seek_step: SeekStep::default(),
+            on_error: PlaybackErrorPolicy::default(),
 
             use_mediacontrols: true,
+            mediacontrols_identity: String::new(),
+            mediacontrols_instance: String::new(),
             set_discord_status: true,
 
             random_track_quantity: NonZeroU32::new(20).unwrap(),
             random_album_min_quantity: NonZeroU32::new(5).unwrap(),
+            random_unplayed_minutes: NonZeroU32::new(60).unwrap(),
 
             backend: Backend::default(),
         }
     }
 }
 
+/// The name shown for the Media-Controls, if no other is set.
+pub const MEDIACONTROLS_IDENTITY_DEFAULT: &str = "Termusic in Rust";
+
+/// The D-Bus name of the Media-Controls without a instance name.
+pub const MEDIACONTROLS_DBUS_NAME: &str = "termusic";
+
+impl PlayerSettings {
+    /// Get the name desktop environments show for the Media-Controls.
+    #[must_use]
+    pub fn mediacontrols_display_name(&self) -> &str {
+        let identity = self.mediacontrols_identity.trim();
+        if identity.is_empty() {
+            MEDIACONTROLS_IDENTITY_DEFAULT
+        } else {
+            identity
+        }
+    }
+
+    /// Get the D-Bus name of the Media-Controls, like `termusic.work` for the instance `work`.
+    ///
+    /// Characters not allowed in D-Bus names are replaced with `_`.
+    #[must_use]
+    pub fn mediacontrols_dbus_name(&self) -> String {
+        let instance = self.mediacontrols_instance.trim();
+        if instance.is_empty() {
+            return MEDIACONTROLS_DBUS_NAME.to_string();
+        }
+
+        let mut name = format!("{MEDIACONTROLS_DBUS_NAME}.");
+        // a element of a D-Bus name may not start with a digit
+        if instance.starts_with(|v: char| v.is_ascii_digit()) {
+            name.push('_');
+        }
+        name.extend(instance.chars().map(|v| {
+            if v.is_ascii_alphanumeric() || v == '_' || v == '-' {
+                v
+            } else {
+                '_'
+            }
+        }));
+
+        name
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Backend {
@@ -343,6 +496,28 @@ impl Display for Backend {
     }
 }
 
+/// Which gain of the `ReplayGain` tags to apply when normalizing.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalizationMode {
+    /// Bring every track to the same loudness
+    #[default]
+    Track,
+    /// Keep the loudness differences between the tracks of a album, falling back to the track gain if not tagged
+    Album,
+}
+
+/// What to do when a track fails to play, like a missing file, expired url or decode error.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackErrorPolicy {
+    /// Skip to the next track, until too many errors happened in succession
+    #[default]
+    Skip,
+    /// Stop playback at the failed track
+    Stop,
+}
+
 /// Playlist loop modes
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -493,11 +668,23 @@ mod v1_interop {
     use std::num::TryFromIntError;
 
     use super::{
-        Backend, ComSettings, LoopMode, NonZeroU8, NonZeroU32, PlayerSettings, PodcastSettings,
-        PositionYesNo, PositionYesNoLower, RememberLastPosition, ScanDepth, SeekStep,
-        ServerSettings, backends::BackendSettings,
+        Backend, ComSettings, LoopMode, NonZeroU8, NonZeroU32, NormalizationMode,
+        PlaybackErrorPolicy, PlayerSettings, PodcastSettings, PositionYesNo, PositionYesNoLower,
+        RememberLastPosition, ScanDepth, SeekStep, ServerSettings, backends::BackendSettings,
+    };
+    use crate::config::{
+        v1,
+        v2::server::{
+            audio_stream::AudioStreamSettings, auto_dj::AutoDjSettings, cache::CacheSettings,
+            command_bus::CommandBusSettings, credentials::CredentialsSettings,
+            downloads::DownloadSettings, ducking::DuckingSettings, equalizer::EqualizerSettings,
+            home_assistant::HomeAssistantSettings, listening_limit::ListeningLimitSettings,
+            metadata::MetadataSettings, metrics::MetricsSettings, now_playing::NowPlayingSettings,
+            recording::RecordingSettings, sponsorblock::SponsorBlockSettings,
+            stream_overlay::StreamOverlaySettings, termux::TermuxSettings,
+            webhooks::WebhookSettings,
+        },
     };
-    use crate::config::{v1, v2::server::metadata::MetadataSettings};
 
     impl From<v1::Loop> for LoopMode {
         fn from(value: v1::Loop) -> Self {
@@ -585,13 +772,24 @@ mod v1_interop {
                 // library_scan_depth: ScanDepth::Limited(value.max_depth_cli),
                 library_scan_depth: ScanDepth::Limited(10),
                 remember_position: value.player_remember_last_played_position.into(),
+                resume_playback: true,
+                auto_bookmark_minutes: 20,
                 loop_mode: value.player_loop_mode.into(),
+                album_mode: false,
+                normalization: false,
+                normalization_mode: NormalizationMode::default(),
+                trim_silence: false,
+                smart_speed: false,
+                smart_speed_silence_ms: 500,
                 volume: value.player_volume,
                 speed: value.player_speed,
                 gapless: value.player_gapless,
                 seek_step: value.player_seek_step.into(),
+                on_error: PlaybackErrorPolicy::default(),
 
                 use_mediacontrols: value.player_use_mpris,
+                mediacontrols_identity: String::new(),
+                mediacontrols_instance: String::new(),
                 set_discord_status: value.player_use_discord,
 
                 random_track_quantity: NonZeroU32::try_from(
@@ -610,6 +808,7 @@ mod v1_interop {
                     new_key: "player.random_album_min_quantity",
                     source: err,
                 })?,
+                random_unplayed_minutes: NonZeroU32::new(60).unwrap(),
 
                 backend: Backend::default(),
             };
@@ -620,6 +819,24 @@ mod v1_interop {
                 podcast: podcast_settings,
                 backends: BackendSettings::default(),
                 metadata: MetadataSettings::default(),
+                metrics: MetricsSettings::default(),
+                recording: RecordingSettings::default(),
+                now_playing: NowPlayingSettings::default(),
+                stream_overlay: StreamOverlaySettings::default(),
+                audio_stream: AudioStreamSettings::default(),
+                cache: CacheSettings::default(),
+                termux: TermuxSettings::default(),
+                credentials: CredentialsSettings::default(),
+                auto_dj: AutoDjSettings::default(),
+                command_bus: CommandBusSettings::default(),
+                webhooks: WebhookSettings::default(),
+                home_assistant: HomeAssistantSettings::default(),
+                sponsorblock: SponsorBlockSettings::default(),
+                downloads: DownloadSettings::default(),
+                listening_limit: ListeningLimitSettings::default(),
+                equalizer: EqualizerSettings::default(),
+                ducking: DuckingSettings::default(),
+                macros: Vec::new(),
             })
         }
     }
@@ -681,7 +898,15 @@ mod v1_interop {
                         music: PositionYesNo::Simple(PositionYesNoLower::No),
                         podcast: PositionYesNo::Simple(PositionYesNoLower::Yes),
                     },
+                    resume_playback: true,
+                    auto_bookmark_minutes: 20,
                     loop_mode: LoopMode::Random,
+                    album_mode: false,
+                    normalization: false,
+                    normalization_mode: NormalizationMode::default(),
+                    trim_silence: false,
+                    smart_speed: false,
+                    smart_speed_silence_ms: 500,
                     volume: 70,
                     speed: 10,
                     gapless: true,
@@ -689,13 +914,45 @@ mod v1_interop {
                         short_tracks: NonZeroU32::new(5).unwrap(),
                         long_tracks: NonZeroU32::new(30).unwrap(),
                     },
+                    on_error: PlaybackErrorPolicy::Skip,
                     use_mediacontrols: true,
+                    mediacontrols_identity: String::new(),
+                    mediacontrols_instance: String::new(),
                     set_discord_status: true,
                     random_track_quantity: NonZeroU32::new(20).unwrap(),
                     random_album_min_quantity: NonZeroU32::new(5).unwrap(),
+                    random_unplayed_minutes: NonZeroU32::new(60).unwrap(),
                     backend: Backend::default(),
                 }
             );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{MEDIACONTROLS_IDENTITY_DEFAULT, PlayerSettings};
+
+    #[test]
+    fn should_get_mediacontrols_names() {
+        let mut settings = PlayerSettings::default();
+        assert_eq!(settings.mediacontrols_dbus_name(), "termusic");
+        assert_eq!(
+            settings.mediacontrols_display_name(),
+            MEDIACONTROLS_IDENTITY_DEFAULT
+        );
+
+        settings.mediacontrols_instance = String::from(" work ");
+        settings.mediacontrols_identity = String::from("Termusic (work)");
+        assert_eq!(settings.mediacontrols_dbus_name(), "termusic.work");
+        assert_eq!(settings.mediacontrols_display_name(), "Termusic (work)");
+
+        settings.mediacontrols_instance = String::from("2nd profile.ä");
+        assert_eq!(
+            settings.mediacontrols_dbus_name(),
+            "termusic._2nd_profile__"
+        );
+    }
+}
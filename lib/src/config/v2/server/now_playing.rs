@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for writing a now-playing line for external tools.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct NowPlayingSettings {
+    /// File or FIFO to write the now-playing line to on every state change, disabled if unset
+    ///
+    /// A regular file is replaced with the new line, a FIFO gets one line per change written to it.
+    pub file: Option<PathBuf>,
+    /// The format of the line, with the placeholders `{status}`, `{artist}`, `{title}`, `{album}` and `{duration}`
+    pub format: String,
+}
+
+impl Default for NowPlayingSettings {
+    fn default() -> Self {
+        Self {
+            file: None,
+            format: "[{status}] {artist} - {title}".to_string(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for recording what is playing to files.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct RecordingSettings {
+    /// Directory the recordings are saved to
+    pub dir: PathBuf,
+    /// The format to encode recordings with, requires `ffmpeg` in `PATH`
+    pub format: RecordingFormat,
+}
+
+/// Get the default recording dir, which uses OS-specific paths, or home/Music/recordings
+fn default_recording_dir() -> PathBuf {
+    dirs::audio_dir().map_or_else(
+        || PathBuf::from(shellexpand::tilde("~/Music/recordings").as_ref()),
+        |mut v| {
+            v.push("recordings");
+            v
+        },
+    )
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            dir: default_recording_dir(),
+            format: RecordingFormat::default(),
+        }
+    }
+}
+
+/// Formats recordings can be encoded with.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    #[default]
+    Flac,
+    Ogg,
+}
+
+impl RecordingFormat {
+    /// Get the format for the extension of `path`, like `mixtape.flac`.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        [Self::Flac, Self::Ogg]
+            .into_iter()
+            .find(|v| v.extension().eq_ignore_ascii_case(ext))
+    }
+
+    /// The file extension for this format.
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Flac => "flac",
+            Self::Ogg => "ogg",
+        }
+    }
+
+    /// The `ffmpeg` muxer and audio codec for this format.
+    #[must_use]
+    pub fn ffmpeg_format(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Flac => ("flac", "flac"),
+            Self::Ogg => ("ogg", "libvorbis"),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// A category of `SponsorBlock` segments, see <https://wiki.sponsor.ajay.app/w/Segment_Categories>.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SponsorBlockCategory {
+    /// Paid promotion
+    Sponsor,
+    /// Unpaid promotion, like merchandise or the own channel
+    Selfpromo,
+    /// Reminders to like, subscribe or follow
+    Interaction,
+    Intro,
+    Outro,
+    /// Recaps or previews of other content
+    Preview,
+    /// Non-music parts of music videos
+    MusicOfftopic,
+    /// Tangents and jokes that are not required for the main content
+    Filler,
+}
+
+impl SponsorBlockCategory {
+    pub const ALL: [Self; 8] = [
+        Self::Sponsor,
+        Self::Selfpromo,
+        Self::Interaction,
+        Self::Intro,
+        Self::Outro,
+        Self::Preview,
+        Self::MusicOfftopic,
+        Self::Filler,
+    ];
+
+    /// The name used by the `SponsorBlock` api and yt-dlp.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sponsor => "sponsor",
+            Self::Selfpromo => "selfpromo",
+            Self::Interaction => "interaction",
+            Self::Intro => "intro",
+            Self::Outro => "outro",
+            Self::Preview => "preview",
+            Self::MusicOfftopic => "music_offtopic",
+            Self::Filler => "filler",
+        }
+    }
+
+    /// Parse a category from its [`name`](Self::as_str), case-insensitively.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Settings for skipping `SponsorBlock` segments of youtube videos.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct SponsorBlockSettings {
+    /// The categories to remove from downloads and skip while streaming, empty to disable `SponsorBlock`
+    pub categories: Vec<SponsorBlockCategory>,
+    /// The `SponsorBlock` api server
+    pub api_url: String,
+}
+
+impl Default for SponsorBlockSettings {
+    fn default() -> Self {
+        Self {
+            categories: Vec::new(),
+            api_url: "https://sponsor.ajay.app".to_string(),
+        }
+    }
+}
+
+impl SponsorBlockSettings {
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        !self.categories.is_empty()
+    }
+}
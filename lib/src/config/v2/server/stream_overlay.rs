@@ -0,0 +1,37 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for the optional now-playing page for streaming software, like a OBS browser source.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct StreamOverlaySettings {
+    /// Enable serving the page on `http://address:port/`, the data on `/now-playing.json` and the cover on `/cover`
+    pub enable: bool,
+    /// Overlay endpoint Port
+    pub port: u16,
+    /// Overlay endpoint interface / address
+    pub address: IpAddr,
+    /// HTML file to serve instead of the built-in page, read once on startup
+    ///
+    /// The placeholders `{status}`, `{artist}`, `{title}`, `{album}`, `{position}` and `{duration}` are replaced on every request.
+    pub template: Option<PathBuf>,
+}
+
+impl Default for StreamOverlaySettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            port: 50103,
+            address: "::1".parse().unwrap(),
+            template: None,
+        }
+    }
+}
+
+impl From<&StreamOverlaySettings> for SocketAddr {
+    fn from(value: &StreamOverlaySettings) -> Self {
+        Self::new(value.address, value.port)
+    }
+}
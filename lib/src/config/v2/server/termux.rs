@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::is_termux;
+
+/// Settings for running inside [Termux](https://termux.dev) on Android.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct TermuxSettings {
+    /// Enable the Termux compatibility mode, `auto` enables it if running inside Termux
+    pub mode: TermuxMode,
+    /// Show a media notification with playback controls via `termux-notification` while the compatibility mode is active
+    ///
+    /// Requires the `termux-api` package and the Termux:API app.
+    pub notification: bool,
+    /// The output buffer size in frames for the `rusty` backend while the compatibility mode is active
+    ///
+    /// Larger values avoid crackling with `AAudio` / `OpenSL ES` at the cost of latency, `0` lets the device decide.
+    /// Default `4096`
+    pub output_buffer_frames: u32,
+}
+
+impl Default for TermuxSettings {
+    fn default() -> Self {
+        Self {
+            mode: TermuxMode::default(),
+            notification: true,
+            output_buffer_frames: 4096,
+        }
+    }
+}
+
+impl TermuxSettings {
+    /// Get whether the Termux compatibility mode is active.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        match self.mode {
+            TermuxMode::Auto => is_termux(),
+            TermuxMode::Always => true,
+            TermuxMode::Never => false,
+        }
+    }
+
+    /// Get the output buffer size to use, if any.
+    #[must_use]
+    pub fn output_buffer_frames(&self) -> Option<u32> {
+        Some(self.output_buffer_frames).filter(|v| *v > 0 && self.is_active())
+    }
+}
+
+/// When to enable the Termux compatibility mode.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TermuxMode {
+    /// Enable if running inside Termux.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
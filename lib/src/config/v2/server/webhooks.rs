@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for HTTP webhooks sent on playback changes, like for home automation or logging services.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct WebhookSettings {
+    /// The webhooks to send a JSON `POST` request to, none by default
+    pub hooks: Vec<Webhook>,
+    /// How often to retry a failed request, waiting `1s`, `2s`, `4s`... in between
+    pub retries: u8,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            hooks: Vec::new(),
+            retries: 3,
+        }
+    }
+}
+
+/// A single webhook.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct Webhook {
+    /// The URL to send the request to
+    pub url: String,
+    /// The events to send the request on, all by default
+    pub events: Vec<WebhookEvent>,
+    /// Sign the request body with HMAC-SHA256 with this secret, sent as `X-Termusic-Signature: sha256=HEX`
+    pub secret: Option<String>,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            events: WebhookEvent::ALL.to_vec(),
+            secret: None,
+        }
+    }
+}
+
+/// The playback changes a webhook can be sent on.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A different track started playing, or the title of a radio stream changed
+    TrackChange,
+    /// Playback was resumed
+    Play,
+    Pause,
+    Stop,
+}
+
+impl WebhookEvent {
+    pub const ALL: [Self; 4] = [Self::TrackChange, Self::Play, Self::Pause, Self::Stop];
+}
@@ -80,6 +80,18 @@ pub struct Keys {
     pub move_cover_art_keys: KeysMoveCoverArt,
     #[serde(rename = "config")]
     pub config_keys: KeysConfigEditor,
+    /// Keys to run a macro of the server, none by default
+    ///
+    /// Global (applies everywhere, except popups)
+    pub macros: Vec<KeyMacro>,
+}
+
+/// A key to run a macro of the server, see the `macros` of the server config
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct KeyMacro {
+    /// The name of the macro
+    pub name: String,
+    pub key: KeyBinding,
 }
 
 impl Keys {
@@ -108,6 +120,7 @@ impl Default for Keys {
             podcast_keys: KeysPodcast::default(),
             move_cover_art_keys: KeysMoveCoverArt::default(),
             config_keys: KeysConfigEditor::default(),
+            macros: Vec::new(),
         }
     }
 }
@@ -194,6 +207,20 @@ impl CheckConflict for Keys {
             conflicts.extend(new);
         }
         key_path.pop();
+        // the macros are a list, so their path is built here instead of with "CheckConflict"
+        for (idx, key_macro) in self.macros.iter().enumerate() {
+            let path = format!("{}[{idx}]", key_path.join_with_field("macros"));
+            if let Some(existing_path) = global_keys.get(&key_macro.key) {
+                conflicts.push(KeyConflictError {
+                    key_path_first: existing_path.clone(),
+                    key_path_second: path,
+                    key: key_macro.key.clone(),
+                });
+                continue;
+            }
+
+            global_keys.insert(key_macro.key.clone(), path);
+        }
 
         // -------------
         // now lets do all the ones that do not add any global player keys, but need to be checked against those
@@ -242,6 +269,22 @@ pub struct KeysSelectView {
     pub open_config: KeyBinding,
     /// Key to open the Help-Popup
     pub open_help: KeyBinding,
+    /// Key to open the global search, searching tracks, playlists and podcast episodes
+    pub open_global_search: KeyBinding,
+    /// Key to open the credits of the currently playing track, to jump to one of its artists
+    pub open_credits: KeyBinding,
+    /// Key to open the download queue of the server
+    pub open_downloads: KeyBinding,
+    /// Key to open the internet radio station browser
+    pub open_radio: KeyBinding,
+    /// Key to open the equalizer of the server
+    pub open_equalizer: KeyBinding,
+    /// Key to choose the output device of the server
+    pub open_output_devices: KeyBinding,
+    /// Key to open the listening statistics, like the most played artists and tracks
+    pub open_statistics: KeyBinding,
+    /// Key to show or hide the suggestions for the playing track next to the playlist, or to focus them
+    pub toggle_track_suggestions: KeyBinding,
 }
 
 impl Default for KeysSelectView {
@@ -260,6 +303,42 @@ impl Default for KeysSelectView {
                 tuievents::KeyModifiers::CONTROL,
             )
             .into(),
+            open_global_search: tuievents::KeyEvent::new(
+                tuievents::Key::Char('k'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            open_credits: tuievents::Key::Char('i').into(),
+            open_downloads: tuievents::KeyEvent::new(
+                tuievents::Key::Char('d'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            open_radio: tuievents::KeyEvent::new(
+                tuievents::Key::Char('o'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            open_equalizer: tuievents::KeyEvent::new(
+                tuievents::Key::Char('e'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            open_output_devices: tuievents::KeyEvent::new(
+                tuievents::Key::Char('u'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            open_statistics: tuievents::KeyEvent::new(
+                tuievents::Key::Char('p'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            toggle_track_suggestions: tuievents::KeyEvent::new(
+                tuievents::Key::Char('w'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
         }
     }
 }
@@ -272,7 +351,15 @@ impl CheckConflict for KeysSelectView {
             (&self.view_podcasts, "view_podcasts"),
 
             (&self.open_config, "open_config"),
-            (&self.open_help, "open_help")
+            (&self.open_help, "open_help"),
+            (&self.open_global_search, "open_global_search"),
+            (&self.open_credits, "open_credits"),
+            (&self.open_downloads, "open_downloads"),
+            (&self.open_radio, "open_radio"),
+            (&self.open_equalizer, "open_equalizer"),
+            (&self.open_output_devices, "open_output_devices"),
+            (&self.open_statistics, "open_statistics"),
+            (&self.toggle_track_suggestions, "toggle_track_suggestions")
         }
     }
 
@@ -361,6 +448,42 @@ pub struct KeysPlayer {
     /// Will only apply in specific widgets (like the Playlist, but not in Config)
     // TODO: always enable "gapless" in rusty backend and rename option to "prefetch"
     pub toggle_prefetch: KeyBinding,
+    /// Key to toggle karaoke mode, which reduces vocals and highlights the current line in the full synced lyrics
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub toggle_karaoke: KeyBinding,
+    /// Key to start or stop recording what is playing to a file
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub toggle_recording: KeyBinding,
+    /// Key to toggle album mode, which plays the whole album of a started track in order
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub toggle_album_mode: KeyBinding,
+    /// Key to tap the tempo of the current track, which corrects the stored BPM of the beat indicator after 4 taps
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub tap_tempo: KeyBinding,
+    /// Key to toggle loudness normalization, for comparing tracks with and without their `ReplayGain`
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub toggle_normalization: KeyBinding,
+    /// Key to toggle smart speed, which shortens long silences in podcast episodes
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub toggle_smart_speed: KeyBinding,
+    /// Key to set the start of the A-B loop, then its end to repeat that region, then to clear it
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub ab_loop: KeyBinding,
+    /// Key to open a popup to seek to a entered timestamp, like `1:23`
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub seek_to_position: KeyBinding,
+    /// Key to open a popup to rate the current track with 1 to 5 stars
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub rate_track: KeyBinding,
 
     /// Key to save the current playlist as a "m3u" playlist
     pub save_playlist: KeyBinding,
@@ -380,21 +503,50 @@ impl Default for KeysPlayer {
             volume_down: tuievents::Key::Char('-').into(),
             seek_forward: tuievents::Key::Char('f').into(),
             seek_backward: tuievents::Key::Char('b').into(),
-            speed_up: tuievents::KeyEvent::new(
-                tuievents::Key::Char('f'),
+            speed_up: tuievents::Key::Char(']').into(),
+            speed_down: tuievents::Key::Char('[').into(),
+            toggle_prefetch: tuievents::KeyEvent::new(
+                tuievents::Key::Char('g'),
                 tuievents::KeyModifiers::CONTROL,
             )
             .into(),
-            speed_down: tuievents::KeyEvent::new(
-                tuievents::Key::Char('b'),
+            toggle_karaoke: tuievents::KeyEvent::new(
+                tuievents::Key::Char('y'),
                 tuievents::KeyModifiers::CONTROL,
             )
             .into(),
-            toggle_prefetch: tuievents::KeyEvent::new(
-                tuievents::Key::Char('g'),
+            toggle_recording: tuievents::KeyEvent::new(
+                tuievents::Key::Char('r'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            toggle_album_mode: tuievents::KeyEvent::new(
+                tuievents::Key::Char('a'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            tap_tempo: tuievents::KeyEvent::new(
+                tuievents::Key::Char('t'),
                 tuievents::KeyModifiers::CONTROL,
             )
             .into(),
+            toggle_normalization: tuievents::KeyEvent::new(
+                tuievents::Key::Char('n'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            toggle_smart_speed: tuievents::KeyEvent::new(
+                tuievents::Key::Char('x'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            ab_loop: tuievents::KeyEvent::new(
+                tuievents::Key::Char('l'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            seek_to_position: tuievents::Key::Char(':').into(),
+            rate_track: tuievents::Key::Char('*').into(),
             save_playlist: tuievents::KeyEvent::new(
                 tuievents::Key::Char('s'),
                 tuievents::KeyModifiers::CONTROL,
@@ -417,6 +569,15 @@ impl CheckConflict for KeysPlayer {
             (&self.speed_up, "speed_up"),
             (&self.speed_down, "speed_down"),
             (&self.toggle_prefetch, "toggle_prefetch"),
+            (&self.toggle_karaoke, "toggle_karaoke"),
+            (&self.toggle_recording, "toggle_recording"),
+            (&self.toggle_album_mode, "toggle_album_mode"),
+            (&self.tap_tempo, "tap_tempo"),
+            (&self.toggle_normalization, "toggle_normalization"),
+            (&self.toggle_smart_speed, "toggle_smart_speed"),
+            (&self.ab_loop, "ab_loop"),
+            (&self.seek_to_position, "seek_to_position"),
+            (&self.rate_track, "rate_track"),
 
             (&self.save_playlist, "save_playlist"),
         }
@@ -478,6 +639,14 @@ pub struct KeysLyric {
     ///
     /// Will only apply in specific widgets (like the Playlist, but not in Config)
     pub cycle_frames: KeyBinding,
+    /// Key to search the synced lyric of the current track and seek to the selected line
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub search: KeyBinding,
+    /// Key to toggle showing the translation of the lyric or episode description side by side, see `[translation]`
+    ///
+    /// Will only apply in specific widgets (like the Playlist, but not in Config)
+    pub toggle_translation: KeyBinding,
 }
 
 impl Default for KeysLyric {
@@ -498,6 +667,16 @@ impl Default for KeysLyric {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            search: tuievents::KeyEvent::new(
+                tuievents::Key::Char('I'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            toggle_translation: tuievents::KeyEvent::new(
+                tuievents::Key::Char('O'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
         }
     }
 }
@@ -508,6 +687,8 @@ impl CheckConflict for KeysLyric {
             (&self.adjust_offset_forwards, "adjust_offset_forwards"),
             (&self.adjust_offset_backwards, "adjust_offset_backwards"),
             (&self.cycle_frames, "cycle_frames"),
+            (&self.search, "search"),
+            (&self.toggle_translation, "toggle_translation"),
         }
     }
 
@@ -652,6 +833,18 @@ pub struct KeysLibrary {
     pub yank: KeyBinding,
     /// Key to finish moving a node (requires "yank" to start a move)
     pub paste: KeyBinding,
+    /// Key to start copying a node to another (requires "paste" to finish copy)
+    pub copy: KeyBinding,
+    /// Key to rename the currently selected node
+    pub rename: KeyBinding,
+    /// Key to undo the last delete, if still possible
+    pub undo_delete: KeyBinding,
+    /// Key to sync the currently selected node to the device, see [`DeviceSync`](super::DeviceSync)
+    pub sync_device: KeyBinding,
+    /// Key to fetch lyrics for all tracks in the currently selected node and store them in the lyrics cache, see [`Lyrics`](super::Lyrics)
+    pub fetch_lyrics: KeyBinding,
+    /// Key to replace the playlist with the currently selected node (track, directory or playlist file)
+    pub replace_playlist: KeyBinding,
     /// Key to cycle through the Music-Directories
     pub cycle_root: KeyBinding,
     /// Key to add the currently entered node as a music root
@@ -679,6 +872,28 @@ impl Default for KeysLibrary {
             delete: tuievents::Key::Char('d').into(),
             yank: tuievents::Key::Char('y').into(),
             paste: tuievents::Key::Char('p').into(),
+            copy: tuievents::KeyEvent::new(
+                tuievents::Key::Char('Y'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            rename: tuievents::Key::Char('r').into(),
+            undo_delete: tuievents::Key::Char('u').into(),
+            sync_device: tuievents::KeyEvent::new(
+                tuievents::Key::Char('E'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            fetch_lyrics: tuievents::KeyEvent::new(
+                tuievents::Key::Char('W'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            replace_playlist: tuievents::KeyEvent::new(
+                tuievents::Key::Char('R'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
             cycle_root: tuievents::Key::Char('o').into(),
             add_root: tuievents::Key::Char('a').into(),
             remove_root: tuievents::KeyEvent::new(
@@ -701,6 +916,12 @@ impl CheckConflict for KeysLibrary {
             (&self.delete, "delete"),
             (&self.yank, "yank"),
             (&self.paste, "paste"),
+            (&self.copy, "copy"),
+            (&self.rename, "rename"),
+            (&self.undo_delete, "undo_delete"),
+            (&self.sync_device, "sync_device"),
+            (&self.fetch_lyrics, "fetch_lyrics"),
+            (&self.replace_playlist, "replace_playlist"),
             (&self.cycle_root, "cycle_root"),
             (&self.add_root, "add_root"),
             (&self.remove_root, "remove_root"),
@@ -779,6 +1000,20 @@ pub struct KeysPlaylist {
     /// previously known as `cmus_lqueue`
     // NOTE: currently this can be somewhat broken sometimes, cause unknown
     pub add_random_album: KeyBinding,
+    /// Key to add random never-played tracks to the playlist (a set duration)
+    pub add_random_unplayed: KeyBinding,
+    /// Key to sync the whole playlist to the device, see [`DeviceSync`](super::DeviceSync)
+    pub sync_device: KeyBinding,
+    /// Key to fetch lyrics for all tracks in the playlist and store them in the lyrics cache, see [`Lyrics`](super::Lyrics)
+    pub fetch_lyrics: KeyBinding,
+    /// Key to set the start and end offsets of the currently selected track
+    pub set_offsets: KeyBinding,
+    /// Key to add tracks chosen by the auto-DJ from the play history, see [`auto_dj`](crate::auto_dj)
+    pub add_auto_dj: KeyBinding,
+    /// Key to add a named bookmark at the current position of the playing track
+    pub add_bookmark: KeyBinding,
+    /// Key to list the bookmarks of the currently selected track, to jump to or delete them
+    pub show_bookmarks: KeyBinding,
 }
 
 impl Default for KeysPlaylist {
@@ -810,6 +1045,33 @@ impl Default for KeysPlaylist {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            add_random_unplayed: tuievents::KeyEvent::new(
+                tuievents::Key::Char('U'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            sync_device: tuievents::KeyEvent::new(
+                tuievents::Key::Char('E'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            fetch_lyrics: tuievents::KeyEvent::new(
+                tuievents::Key::Char('W'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            set_offsets: tuievents::Key::Char('o').into(),
+            add_auto_dj: tuievents::KeyEvent::new(
+                tuievents::Key::Char('P'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            add_bookmark: tuievents::Key::Char('z').into(),
+            show_bookmarks: tuievents::KeyEvent::new(
+                tuievents::Key::Char('Z'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
         }
     }
 }
@@ -828,6 +1090,13 @@ impl CheckConflict for KeysPlaylist {
 
             (&self.add_random_songs, "add_random_songs"),
             (&self.add_random_album, "add_random_album"),
+            (&self.add_random_unplayed, "add_random_unplayed"),
+            (&self.sync_device, "sync_device"),
+            (&self.fetch_lyrics, "fetch_lyrics"),
+            (&self.set_offsets, "set_offsets"),
+            (&self.add_auto_dj, "add_auto_dj"),
+            (&self.add_bookmark, "add_bookmark"),
+            (&self.show_bookmarks, "show_bookmarks"),
         }
     }
 
@@ -1172,6 +1441,20 @@ pub struct KeysDatabase {
     pub add_selected: KeyBinding,
     /// Add all tracks in the Database view "Tracks" section
     pub add_all: KeyBinding,
+    /// Replace the playlist with all tracks in the Database view "Tracks" section
+    pub replace_playlist: KeyBinding,
+    /// Mark the currently selected track in the "Inbox" as reviewed
+    pub mark_reviewed: KeyBinding,
+    /// Mark all tracks in the Database view "Tracks" section of the "Inbox" as reviewed
+    pub mark_all_reviewed: KeyBinding,
+    /// Show the currently selected track in the library, to move or delete it
+    pub locate: KeyBinding,
+    /// Verify all tracks in the database by decoding them, the failed ones are shown as "Corrupted"
+    pub verify_library: KeyBinding,
+    /// Create a new smart playlist, defined by a query
+    pub new_smart_playlist: KeyBinding,
+    /// Rename or change the query of the currently selected smart playlist
+    pub edit_smart_playlist: KeyBinding,
 }
 
 impl Default for KeysDatabase {
@@ -1183,6 +1466,25 @@ impl Default for KeysDatabase {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            replace_playlist: tuievents::KeyEvent::new(
+                tuievents::Key::Char('R'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            mark_reviewed: tuievents::Key::Char('v').into(),
+            mark_all_reviewed: tuievents::KeyEvent::new(
+                tuievents::Key::Char('V'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            locate: tuievents::Key::Char('o').into(),
+            verify_library: tuievents::KeyEvent::new(
+                tuievents::Key::Char('I'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            new_smart_playlist: tuievents::Key::Char('a').into(),
+            edit_smart_playlist: tuievents::Key::Char('e').into(),
         }
     }
 }
@@ -1191,6 +1493,13 @@ impl CheckConflict for KeysDatabase {
     fn iter(&self) -> impl Iterator<Item = (&KeyBinding, &'static str)> {
         once_chain! {
             (&self.add_all, "add_all"),
+            (&self.replace_playlist, "replace_playlist"),
+            (&self.mark_reviewed, "mark_reviewed"),
+            (&self.mark_all_reviewed, "mark_all_reviewed"),
+            (&self.locate, "locate"),
+            (&self.verify_library, "verify_library"),
+            (&self.new_smart_playlist, "new_smart_playlist"),
+            (&self.edit_smart_playlist, "edit_smart_playlist"),
         }
     }
 
@@ -1896,6 +2205,8 @@ mod v1_interop {
                     view_podcasts: value.global_layout_podcast.into(),
                     open_config: value.global_config_open.into(),
                     open_help: value.global_help.into(),
+                    // not available in v1
+                    ..Default::default()
                 },
                 navigation_keys: KeysNavigation {
                     up: value.global_up.into(),
@@ -1917,11 +2228,15 @@ mod v1_interop {
                     speed_down: value.global_player_speed_down.into(),
                     toggle_prefetch: value.global_player_toggle_gapless.into(),
                     save_playlist: value.global_save_playlist.into(),
+                    // not available in v1
+                    ..Default::default()
                 },
                 lyric_keys: KeysLyric {
                     adjust_offset_forwards: value.global_lyric_adjust_forward.into(),
                     adjust_offset_backwards: value.global_lyric_adjust_backward.into(),
                     cycle_frames: value.global_lyric_cycle.into(),
+                    // not available in v1
+                    ..Default::default()
                 },
                 library_keys: KeysLibrary {
                     // this is weird, but the previous implementation used "global_right" as the loading key to not conflict
@@ -1936,6 +2251,8 @@ mod v1_interop {
                     search: value.library_search.into(),
                     youtube_search: value.library_search_youtube.into(),
                     open_tag_editor: value.library_tag_editor_open.into(),
+                    // not available in v1
+                    ..Default::default()
                 },
                 playlist_keys: KeysPlaylist {
                     delete: value.playlist_delete.into(),
@@ -1948,11 +2265,15 @@ mod v1_interop {
                     swap_down: value.playlist_swap_down.into(),
                     add_random_songs: value.playlist_add_random_tracks.into(),
                     add_random_album: value.playlist_add_random_album.into(),
+                    // not available in v1
+                    ..Default::default()
                 },
                 database_keys: KeysDatabase {
                     // this is weird, but the previous implementation used "global_right" as the loading key to not conflict
                     add_selected: value.global_right.into(),
                     add_all: value.database_add_all.into(),
+                    // not available in v1
+                    ..Default::default()
                 },
                 podcast_keys: KeysPodcast {
                     search: value.podcast_search_add_feed.into(),
@@ -1977,6 +2298,7 @@ mod v1_interop {
                 config_keys: KeysConfigEditor {
                     save: value.config_save.into(),
                 },
+                macros: Vec::new(),
             }
         }
     }
@@ -2007,6 +2329,42 @@ mod v1_interop {
                     tuievents::KeyModifiers::CONTROL,
                 )
                 .into(),
+                open_global_search: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('k'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                open_credits: tuievents::Key::Char('i').into(),
+                open_downloads: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('d'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                open_radio: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('o'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                open_equalizer: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('e'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                open_output_devices: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('u'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                open_statistics: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('p'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_track_suggestions: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('w'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
             };
             assert_eq!(converted.select_view_keys, expected_select_view_keys);
 
@@ -2060,6 +2418,43 @@ mod v1_interop {
                     tuievents::KeyModifiers::CONTROL,
                 )
                 .into(),
+                toggle_karaoke: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('y'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_recording: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('r'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_album_mode: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('a'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                tap_tempo: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('t'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_normalization: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('n'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_smart_speed: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('x'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                ab_loop: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('l'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                seek_to_position: tuievents::Key::Char(':').into(),
+                rate_track: tuievents::Key::Char('*').into(),
                 save_playlist: tuievents::KeyEvent::new(
                     tuievents::Key::Char('s'),
                     tuievents::KeyModifiers::CONTROL,
@@ -2084,6 +2479,16 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                search: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('I'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                toggle_translation: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('O'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
             };
             assert_eq!(converted.lyric_keys, expected_lyric_keys);
 
@@ -2097,6 +2502,28 @@ mod v1_interop {
                 delete: tuievents::Key::Char('d').into(),
                 yank: tuievents::Key::Char('y').into(),
                 paste: tuievents::Key::Char('p').into(),
+                copy: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('Y'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                rename: tuievents::Key::Char('r').into(),
+                undo_delete: tuievents::Key::Char('u').into(),
+                sync_device: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('E'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                fetch_lyrics: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('W'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                replace_playlist: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('R'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
                 cycle_root: tuievents::Key::Char('o').into(),
                 add_root: tuievents::Key::Char('a').into(),
                 remove_root: tuievents::KeyEvent::new(
@@ -2137,6 +2564,33 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                add_random_unplayed: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('U'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                sync_device: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('E'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                fetch_lyrics: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('W'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                set_offsets: tuievents::Key::Char('o').into(),
+                add_auto_dj: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('P'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                add_bookmark: tuievents::Key::Char('z').into(),
+                show_bookmarks: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('Z'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
             };
             assert_eq!(converted.playlist_keys, expected_playlist_keys);
 
@@ -2147,6 +2601,25 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                replace_playlist: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('R'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                mark_reviewed: tuievents::Key::Char('v').into(),
+                mark_all_reviewed: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('V'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                locate: tuievents::Key::Char('o').into(),
+                verify_library: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('I'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                new_smart_playlist: tuievents::Key::Char('a').into(),
+                edit_smart_playlist: tuievents::Key::Char('e').into(),
             };
             assert_eq!(converted.database_keys, expected_database_keys);
 
@@ -2240,6 +2713,7 @@ mod v1_interop {
                 podcast_keys: expected_podcast_keys,
                 move_cover_art_keys: expected_move_cover_art_keys,
                 config_keys: expected_config_editor_keys,
+                macros: Vec::new(),
             };
 
             assert_eq!(converted, expected_keys);
@@ -2297,6 +2771,43 @@ mod v1_interop {
                     tuievents::KeyModifiers::CONTROL,
                 )
                 .into(),
+                toggle_karaoke: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('y'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_recording: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('r'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_album_mode: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('a'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                tap_tempo: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('t'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_normalization: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('n'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                toggle_smart_speed: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('x'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                ab_loop: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('l'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                seek_to_position: tuievents::Key::Char(':').into(),
+                rate_track: tuievents::Key::Char('*').into(),
                 save_playlist: tuievents::KeyEvent::new(
                     tuievents::Key::Char('s'),
                     tuievents::KeyModifiers::CONTROL,
@@ -2666,5 +3177,31 @@ mod test {
                 keys.check_keys()
             );
         }
+
+        #[test]
+        fn should_err_on_macro_key_conflict() {
+            let mut keys = Keys::default();
+            keys.macros = vec![
+                KeyMacro {
+                    name: "party".into(),
+                    key: tuievents::Key::Function(9).into(),
+                },
+                KeyMacro {
+                    name: "quiet".into(),
+                    key: keys.player_keys.toggle_pause.clone(),
+                },
+            ];
+
+            assert_eq!(
+                Err(KeysCheckError {
+                    errored_keys: vec![KeyConflictError {
+                        key_path_first: "keys.global_player.toggle_pause".into(),
+                        key_path_second: "keys.macros[1]".into(),
+                        key: keys.player_keys.toggle_pause.clone()
+                    }]
+                }),
+                keys.check_keys()
+            );
+        }
     }
 }
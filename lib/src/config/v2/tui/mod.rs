@@ -1,4 +1,9 @@
-use std::{collections::HashSet, path::Path};
+use std::{
+    collections::HashSet,
+    num::{NonZeroU8, NonZeroU32},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -23,6 +28,20 @@ pub struct TuiSettings {
     pub theme: theme::ThemeWrap,
     pub keys: keys::Keys,
     pub ytdlp: Ytdlp,
+    pub youtube: Youtube,
+    pub content_filter: ContentFilter,
+    pub suggestions: SearchSuggestions,
+    pub search_history: SearchHistorySettings,
+    pub trending: Trending,
+    pub device_sync: DeviceSync,
+    pub database: DatabaseView,
+    pub lyrics: Lyrics,
+    pub tag_backups: TagBackups,
+    pub playlist: PlaylistView,
+    pub playlist_sync: PlaylistSync,
+    pub beat_indicator: BeatIndicator,
+    pub translation: Translation,
+    pub track_suggestions: TrackSuggestions,
 }
 
 impl TuiSettings {
@@ -65,11 +84,26 @@ impl TuiSettings {
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+#[allow(clippy::struct_excessive_bools)] // configuration, this is not a state machine
 pub struct BehaviorSettings {
     /// Stop / Exit the Server on TUI quit
     pub quit_server_on_exit: bool,
     /// Ask before exiting the TUI (popup)
     pub confirm_quit: bool,
+    /// Only ask before exiting while a track is playing, requires `confirm_quit`
+    pub confirm_quit_only_playing: bool,
+    /// Ask before clearing the whole playlist
+    pub confirm_clear_playlist: bool,
+    /// Ask before deleting files or directories from the library
+    pub confirm_delete: bool,
+    /// Ask before writing changed tags to the file in the tag editor
+    pub confirm_tag_overwrite: bool,
+    /// Move deleted files to the system trash (with a short undo window) instead of deleting them permanently
+    pub delete_to_trash: bool,
+    /// Show a summary (added / removed tracks, total duration) and ask before replacing the playlist
+    pub confirm_queue_replace: bool,
+    /// Show a line with the most useful keys of the focused pane or popup above the footer
+    pub show_key_hints: bool,
 }
 
 impl Default for BehaviorSettings {
@@ -77,6 +111,13 @@ impl Default for BehaviorSettings {
         Self {
             quit_server_on_exit: true,
             confirm_quit: true,
+            confirm_quit_only_playing: false,
+            confirm_clear_playlist: false,
+            confirm_delete: true,
+            confirm_tag_overwrite: false,
+            delete_to_trash: true,
+            confirm_queue_replace: true,
+            show_key_hints: false,
         }
     }
 }
@@ -114,7 +155,14 @@ pub struct CoverArtProtocolsSet(HashSet<CoverArtProtocol>);
 
 impl Default for CoverArtProtocolsSet {
     fn default() -> Self {
-        Self(HashSet::from(*PROTOCOLS_DEFAULT))
+        let mut set = HashSet::from(*PROTOCOLS_DEFAULT);
+        // the block printer is the only one that works in the default windows terminals,
+        // elsewhere it has to be enabled explicitly as it covers the cells below it until the next full redraw
+        if cfg!(windows) {
+            set.insert(CoverArtProtocol::Blocks);
+        }
+
+        Self(set)
     }
 }
 
@@ -127,7 +175,7 @@ impl CoverArtProtocolsSet {
     }
 }
 
-/// All protocols are enabled by default.
+/// All protocols are enabled by default, see [`CoverArtProtocolsSet::default`] for [`CoverArtProtocol::Blocks`].
 pub const PROTOCOLS_DEFAULT: &[CoverArtProtocol; 4] = &[
     CoverArtProtocol::Kitty,
     CoverArtProtocol::Iterm2,
@@ -148,6 +196,9 @@ pub enum CoverArtProtocol {
     Kitty,
     #[serde(rename = "ueberzug")]
     Ueberzug,
+    /// Unicode half-blocks with truecolor, works in most terminals but in a low resolution
+    #[serde(rename = "blocks")]
+    Blocks,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
@@ -163,16 +214,442 @@ pub enum Alignment {
     BottomLeft,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
 pub struct Ytdlp {
-    /// Extra args for yt-dlp
+    /// Extra args for yt-dlp, for both downloads and searches
     pub extra_args: String,
+    /// How many results a page of a yt-dlp search has
+    ///
+    /// yt-dlp is used for searching if no invidious instance is available.
+    pub search_results: NonZeroU8,
+    /// How long a yt-dlp search may take before the process is killed
+    pub search_timeout_secs: NonZeroU32,
+}
+
+impl Default for Ytdlp {
+    fn default() -> Self {
+        Self {
+            extra_args: String::new(),
+            search_results: NonZeroU8::new(20).unwrap(),
+            search_timeout_secs: NonZeroU32::new(30).unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct Youtube {
+    /// Load the cookies of this browser for yt-dlp, like `firefox` or `brave+gnomekeyring`, to access age-restricted
+    /// videos; empty to not use any
+    pub cookies_from_browser: String,
+    /// Load the cookies from this Netscape formatted file instead, if `cookies_from_browser` is empty
+    pub cookies_file: Option<PathBuf>,
+    /// The audio format of downloads, like `mp3`, `opus` or `best` to keep the original format
+    ///
+    /// Lyrics are only embedded into `mp3` downloads, other formats keep them as `.lrc` file next to the download.
+    pub audio_format: String,
+    /// The quality of converted downloads, from `0` (best) to `10` (worst) or a bitrate like `192K`; empty for the
+    /// default of yt-dlp
+    pub audio_quality: String,
+    /// The maximum download rate in bytes per second, like `500K` or `2M`; empty for no limit
+    pub limit_rate: String,
+    /// The proxy for yt-dlp, like `socks5://127.0.0.1:1080`, to access region-locked videos; empty to not use any
+    pub proxy: String,
+    /// The invidious instances to search with and get the trending from, like `https://yewtu.be`
+    ///
+    /// If empty, the public instances listed by `api.invidious.io` are used. Instances are tried by their measured
+    /// health, the fastest working one first.
+    pub invidious_instances: Vec<String>,
+    /// How often to check the health of the invidious instances, in minutes, `0` to never check
+    pub invidious_probe_mins: u16,
+}
+
+impl Default for Youtube {
+    fn default() -> Self {
+        Self {
+            cookies_from_browser: String::new(),
+            cookies_file: None,
+            audio_format: "mp3".to_string(),
+            audio_quality: String::new(),
+            limit_rate: String::new(),
+            proxy: String::new(),
+            invidious_instances: Vec::new(),
+            invidious_probe_mins: 30,
+        }
+    }
+}
+
+impl Youtube {
+    /// Get the interval of the invidious health probe, none if disabled.
+    #[must_use]
+    pub fn invidious_probe_interval(&self) -> Option<Duration> {
+        (self.invidious_probe_mins > 0)
+            .then(|| Duration::from_secs(u64::from(self.invidious_probe_mins) * 60))
+    }
+
+    /// Get the yt-dlp arguments for both downloads and searches, like the cookies and the proxy.
+    #[must_use]
+    pub fn common_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.cookies_from_browser.is_empty() {
+            args.push("--cookies-from-browser".to_string());
+            args.push(self.cookies_from_browser.clone());
+        } else if let Some(file) = &self.cookies_file {
+            args.push("--cookies".to_string());
+            args.push(file.to_string_lossy().into_owned());
+        }
+        if !self.proxy.is_empty() {
+            args.push("--proxy".to_string());
+            args.push(self.proxy.clone());
+        }
+
+        args
+    }
+
+    /// Get the yt-dlp arguments only for downloads, like the format and the rate limit.
+    #[must_use]
+    pub fn download_args(&self) -> Vec<String> {
+        let format = if self.audio_format.is_empty() {
+            "mp3"
+        } else {
+            &self.audio_format
+        };
+        let mut args = vec!["--audio-format".to_string(), format.to_string()];
+        if !self.audio_quality.is_empty() {
+            args.push("--audio-quality".to_string());
+            args.push(self.audio_quality.clone());
+        }
+        if !self.limit_rate.is_empty() {
+            args.push("--limit-rate".to_string());
+            args.push(self.limit_rate.clone());
+        }
+
+        args
+    }
+}
+
+/// A filter hiding youtube search and trending results, like for a setup used by children.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct ContentFilter {
+    /// Hide results whose title or channel contains any of these words, ignoring case
+    pub blocked_keywords: Vec<String>,
+    /// Hide results uploaded by these channels, ignoring case
+    pub blocked_channels: Vec<String>,
+    /// Hide results marked as age-restricted
+    ///
+    /// Only yt-dlp reports this and only for some results, so this does not replace the blocklists.
+    pub hide_age_restricted: bool,
+    /// Prevent changing the filter from within the tui, so that it can only be changed in the config file
+    pub locked: bool,
+}
+
+impl ContentFilter {
+    /// Check if a result with `title` by `channel` should be hidden.
+    #[must_use]
+    pub fn blocks(&self, title: &str, channel: Option<&str>, age_restricted: bool) -> bool {
+        if self.hide_age_restricted && age_restricted {
+            return true;
+        }
+
+        let title = title.to_lowercase();
+        let channel = channel.map(str::to_lowercase).unwrap_or_default();
+        if self
+            .blocked_channels
+            .iter()
+            .any(|v| !channel.is_empty() && v.trim().to_lowercase() == channel)
+        {
+            return true;
+        }
+
+        self.blocked_keywords
+            .iter()
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+            .any(|v| title.contains(&v) || channel.contains(&v))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct SearchSuggestions {
+    /// Where the suggestions for the youtube search come from
+    pub provider: SuggestionBackend,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct SearchHistorySettings {
+    /// How many past queries are kept for each of the web and library searches, `0` disables the history
+    ///
+    /// Pinned queries are always kept.
+    pub size: u16,
+}
+
+impl Default for SearchHistorySettings {
+    fn default() -> Self {
+        Self { size: 100 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct Trending {
+    /// The default region (ISO 3166 code, like `US`) of the youtube music trending
+    ///
+    /// If unset, the region is detected from the locale. The region last picked in the tui is preferred over this.
+    pub region: Option<String>,
+}
+
+/// The service to get youtube search suggestions from.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionBackend {
+    /// The youtube suggestions of google
+    #[default]
+    Google,
+    DuckDuckGo,
+    /// The invidious instance of the last search, or a public one
+    Invidious,
+    /// Only suggest from the search history and the artists and titles in the library
+    Offline,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct DatabaseView {
+    /// How tracks without a album are shown in the `Album` criteria
+    pub untagged_albums: UntaggedAlbums,
+    /// Sort Chinese names by their pinyin and Japanese kana by their romaji, instead of after all latin names
+    pub transliterate_sort: bool,
+}
+
+/// How tracks without a album are shown when browsing albums.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UntaggedAlbums {
+    /// Do not show them
+    #[default]
+    Hidden,
+    /// Group all of them into one `[unknown]` album
+    Unknown,
+    /// Group them into one album per directory
+    Directory,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct DeviceSync {
+    /// The path the device is mounted at, like `/media/player`
+    pub device_path: Option<PathBuf>,
+    /// The format to transcode lossless files to, `none` to copy them as-is
+    pub transcode: TranscodeFormat,
+    /// The bitrate for transcoded files, in kbit/s
+    pub bitrate: u16,
+}
+
+impl Default for DeviceSync {
+    fn default() -> Self {
+        Self {
+            device_path: None,
+            transcode: TranscodeFormat::default(),
+            bitrate: 192,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct PlaylistSync {
+    /// The directory of the `.m3u` playlists to sync, like `~/Music/playlists`
+    pub local_dir: Option<PathBuf>,
+    /// Where to sync the playlists to
+    pub backend: PlaylistSyncBackend,
+    /// The url of the WebDAV folder or the git repository
+    pub remote: String,
+    /// The branch to sync to, only used for [`PlaylistSyncBackend::Git`]
+    pub branch: String,
+    /// Sync once the tui is started
+    pub on_start: bool,
+}
+
+impl Default for PlaylistSync {
+    fn default() -> Self {
+        Self {
+            local_dir: None,
+            backend: PlaylistSyncBackend::default(),
+            remote: String::new(),
+            branch: String::from("main"),
+            on_start: false,
+        }
+    }
+}
+
+/// The kind of remote playlists are synced with.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistSyncBackend {
+    /// Playlist sync is disabled
+    #[default]
+    None,
+    /// A folder on a WebDAV server, with the credentials stored for the key `webdav:<host>`
+    WebDav,
+    /// A git repository, authenticated by git itself
+    Git,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct Lyrics {
+    /// The directory fetched lyrics are cached in, defaults to `termusic/lyrics` in the cache directory
+    pub cache_dir: Option<PathBuf>,
+    /// Also embed lyrics fetched for a whole playlist / directory into the track files
+    pub embed_fetched: bool,
+}
+
+/// What is backed up before tags get written or files get renamed.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagBackupMode {
+    /// Do not back up anything
+    Off,
+    /// Only the tags (including embedded pictures and lyrics), which is small but not a bit-exact copy
+    #[default]
+    Tags,
+    /// A copy of the whole file
+    File,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct TagBackups {
+    /// What to back up before tags get written or files get renamed, restore with `termusic tags restore`
+    pub mode: TagBackupMode,
+    /// The directory the backups are stored in, defaults to `termusic/tag_backups` in the data directory
+    pub dir: Option<PathBuf>,
+    /// How many backups to keep, the oldest ones get removed
+    pub keep: usize,
+}
+
+impl Default for TagBackups {
+    fn default() -> Self {
+        Self {
+            mode: TagBackupMode::default(),
+            dir: None,
+            keep: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct PlaylistView {
+    /// Show a column with the estimated wall-clock time each queued track starts playing at
+    pub show_start_time: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct BeatIndicator {
+    /// Show a beat indicator in the progress bar title that pulses with the tempo of the track, if it has one
+    pub enable: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct TrackSuggestions {
+    /// Show the panel with more from the album, by the artists and of the genre of the playing track next to the
+    /// playlist on startup, it can always be toggled with `keys.select_view_keys.toggle_track_suggestions`
+    pub show: bool,
+    /// How many tracks to suggest for each of the album, the artists, the genre and Last.fm
+    pub per_section: u8,
+    /// Also suggest the tracks of the library that Last.fm lists as similar
+    ///
+    /// Requires a Last.fm API key stored in the credentials as `lastfm`.
+    pub lastfm: bool,
+}
+
+impl Default for TrackSuggestions {
+    fn default() -> Self {
+        Self {
+            show: false,
+            per_section: 5,
+            lastfm: false,
+        }
+    }
+}
+
+/// Services lyrics and episode descriptions can be translated with.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationProvider {
+    /// Do not translate
+    #[default]
+    None,
+    /// A `LibreTranslate` instance, see <https://libretranslate.com>
+    LibreTranslate,
+    /// The `DeepL` API, which always requires a API key
+    DeepL,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct Translation {
+    /// The service to translate the lyrics and episode descriptions with.
+    ///
+    /// A API key is looked up in the credentials as `translate:<host of the api url>`, like `translate:api-free.deepl.com`.
+    pub provider: TranslationProvider,
+    /// The url of the API, defaults to `https://libretranslate.com` or `https://api-free.deepl.com`
+    pub api_url: Option<String>,
+    /// The language to translate to, like `de`, defaults to the language of the system locale
+    pub target_language: Option<String>,
+}
+
+impl Translation {
+    /// Get whether a provider is configured.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.provider != TranslationProvider::None
+    }
+
+    /// Get the url of the API of the provider, without a trailing `/`.
+    #[must_use]
+    pub fn api_url(&self) -> &str {
+        let default = match self.provider {
+            TranslationProvider::None | TranslationProvider::LibreTranslate => {
+                "https://libretranslate.com"
+            }
+            TranslationProvider::DeepL => "https://api-free.deepl.com",
+        };
+
+        self.api_url
+            .as_deref()
+            .unwrap_or(default)
+            .trim_end_matches('/')
+    }
+}
+
+/// Lossy formats lossless files can be transcoded to on device sync.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscodeFormat {
+    /// Copy lossless files without transcoding
+    #[default]
+    None,
+    Mp3,
+    Opus,
+    Ogg,
 }
 
 #[cfg(feature = "config-v1-compat")]
 mod v1_interop {
-    use super::{Alignment, BehaviorSettings, CoverArt, MaybeComSettings, TuiSettings, Ytdlp};
+    use super::{
+        Alignment, BeatIndicator, BehaviorSettings, ContentFilter, CoverArt, DatabaseView,
+        DeviceSync, Lyrics, MaybeComSettings, PlaylistSync, PlaylistView, SearchHistorySettings,
+        SearchSuggestions, TagBackups, TrackSuggestions, Translation, Trending, TuiSettings,
+        Youtube, Ytdlp,
+    };
     use crate::config::{v1, v2::tui::CoverArtProtocolsSet};
 
     impl From<v1::Alignment> for Alignment {
@@ -209,11 +686,27 @@ mod v1_interop {
                 behavior: BehaviorSettings {
                     quit_server_on_exit: value.kill_daemon_when_quit,
                     confirm_quit: value.enable_exit_confirmation,
+                    // not available in v1
+                    ..Default::default()
                 },
                 coverart: value.album_photo_xywh.into(),
                 theme,
                 keys: value.keys.into(),
                 ytdlp: Ytdlp::default(),
+                youtube: Youtube::default(),
+                content_filter: ContentFilter::default(),
+                suggestions: SearchSuggestions::default(),
+                search_history: SearchHistorySettings::default(),
+                trending: Trending::default(),
+                device_sync: DeviceSync::default(),
+                database: DatabaseView::default(),
+                lyrics: Lyrics::default(),
+                tag_backups: TagBackups::default(),
+                playlist: PlaylistView::default(),
+                playlist_sync: PlaylistSync::default(),
+                beat_indicator: BeatIndicator::default(),
+                translation: Translation::default(),
+                track_suggestions: TrackSuggestions::default(),
             }
         }
     }
@@ -231,7 +724,14 @@ mod v1_interop {
                 converted.behavior,
                 BehaviorSettings {
                     quit_server_on_exit: true,
-                    confirm_quit: true
+                    confirm_quit: true,
+                    confirm_quit_only_playing: false,
+                    confirm_clear_playlist: false,
+                    confirm_delete: true,
+                    confirm_tag_overwrite: false,
+                    delete_to_trash: true,
+                    confirm_queue_replace: true,
+                    show_key_hints: false,
                 }
             );
 
@@ -251,3 +751,25 @@ mod v1_interop {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ContentFilter;
+
+    #[test]
+    fn should_filter_content() {
+        let filter = ContentFilter {
+            blocked_keywords: vec!["Explicit".to_string(), " ".to_string()],
+            blocked_channels: vec!["Some Channel ".to_string()],
+            hide_age_restricted: true,
+            locked: false,
+        };
+
+        assert!(filter.blocks("Song (explicit version)", None, false));
+        assert!(filter.blocks("Song", Some("EXPLICIT Records"), false));
+        assert!(filter.blocks("Song", Some("some channel"), false));
+        assert!(filter.blocks("Song", None, true));
+        assert!(!filter.blocks("Song", Some("Some Channel Two"), false));
+        assert!(!ContentFilter::default().blocks("Explicit", None, true));
+    }
+}
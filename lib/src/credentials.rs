@@ -0,0 +1,377 @@
+//! Storage for API credentials of integrations, preferring the system keyring over a plain-text file.
+//!
+//! Integrations look up their secrets by a key like `podcast:example.com` via [`CREDENTIALS`],
+//! which store is used is configured via [`CredentialsSettings`].
+
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+#[cfg(not(target_os = "macos"))]
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+#[cfg(not(target_os = "macos"))]
+use anyhow::bail;
+use anyhow::{Context as _, Result};
+use parking_lot::RwLock;
+
+use crate::config::v2::server::credentials::{CredentialStore, CredentialsSettings};
+use crate::utils::get_app_config_path;
+
+/// The service name the secrets are stored under in the system keyring.
+pub const SERVICE: &str = "termusic";
+/// The file name of the fallback store in the config directory.
+pub const FILE_NAME: &str = "credentials.toml";
+
+/// The global credentials instance.
+pub static CREDENTIALS: LazyLock<Credentials> = LazyLock::new(Credentials::default);
+
+/// A place secrets can be stored in.
+pub trait SecretStore: Send + Sync {
+    /// The name of the store, for messages.
+    fn name(&self) -> &'static str;
+    /// Get the secret for `key`, if there is one.
+    fn get(&self, key: &str) -> Result<Option<String>>;
+    /// Store `secret` for `key`, replacing any previous secret.
+    fn set(&self, key: &str, secret: &str) -> Result<()>;
+    /// Remove the secret for `key`, returns whether there was one.
+    fn remove(&self, key: &str) -> Result<bool>;
+}
+
+/// Look up credentials in the configured stores.
+#[derive(Debug, Default)]
+pub struct Credentials {
+    store: RwLock<CredentialStore>,
+}
+
+impl Credentials {
+    /// Apply a new store selection.
+    pub fn apply_settings(&self, settings: &CredentialsSettings) {
+        *self.store.write() = settings.store;
+    }
+
+    /// Get the stores to use, in order of preference.
+    fn stores(&self) -> Result<Vec<Box<dyn SecretStore>>> {
+        let file = || -> Result<Box<dyn SecretStore>> { Ok(Box::new(FileStore::new_default()?)) };
+
+        Ok(match *self.store.read() {
+            CredentialStore::Auto => vec![Box::new(Keyring), file()?],
+            CredentialStore::Keyring => vec![Box::new(Keyring)],
+            CredentialStore::File => vec![file()?],
+        })
+    }
+
+    /// Get the secret for `key` from the first store that has it.
+    ///
+    /// Stores that fail are skipped if there is a fallback, like the keyring being unavailable.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    /// - if the last store fails
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let stores = self.stores()?;
+        let last = stores.len() - 1;
+        for (idx, store) in stores.iter().enumerate() {
+            match store.get(key) {
+                Ok(Some(secret)) => return Ok(Some(secret)),
+                Ok(None) => (),
+                Err(err) if idx < last => {
+                    debug!("Looking up \"{key}\" in {} failed: {err:#}", store.name());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Store `secret` for `key` in the first store that works, returns the name of the store.
+    ///
+    /// The secret is removed from the stores after it, so that a secret moved to the keyring does not stay in the file.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    /// - if all stores fail
+    pub fn set(&self, key: &str, secret: &str) -> Result<&'static str> {
+        let stores = self.stores()?;
+        let mut last_err = None;
+        for (idx, store) in stores.iter().enumerate() {
+            match store.set(key, secret) {
+                Ok(()) => {
+                    for fallback in &stores[idx + 1..] {
+                        if let Err(err) = fallback.remove(key) {
+                            warn!(
+                                "Removing \"{key}\" from {} failed: {err:#}",
+                                fallback.name()
+                            );
+                        }
+                    }
+                    return Ok(store.name());
+                }
+                Err(err) => {
+                    warn!("Storing \"{key}\" in {} failed: {err:#}", store.name());
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no credential store available")))
+    }
+
+    /// Remove the secret for `key` from all stores, returns whether any store had it.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    /// - if the last store fails
+    pub fn remove(&self, key: &str) -> Result<bool> {
+        let stores = self.stores()?;
+        let last = stores.len() - 1;
+        let mut removed = false;
+        for (idx, store) in stores.iter().enumerate() {
+            match store.remove(key) {
+                Ok(v) => removed |= v,
+                Err(err) if idx < last => {
+                    debug!("Removing \"{key}\" from {} failed: {err:#}", store.name());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// The system keyring: Secret Service (via `secret-tool` of libsecret) on linux, the Keychain on macOS.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyring;
+
+#[cfg(target_os = "macos")]
+impl SecretStore for Keyring {
+    fn name(&self) -> &'static str {
+        "the keychain"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        use security_framework::passwords::get_generic_password;
+
+        match get_generic_password(SERVICE, key) {
+            Ok(secret) => Ok(Some(
+                String::from_utf8(secret).context("secret is not valid utf8")?,
+            )),
+            Err(err) if err.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<()> {
+        security_framework::passwords::set_generic_password(SERVICE, key, secret.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<bool> {
+        match security_framework::passwords::delete_generic_password(SERVICE, key) {
+            Ok(()) => Ok(true),
+            Err(err) if err.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// `errSecItemNotFound`
+#[cfg(target_os = "macos")]
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+#[cfg(not(target_os = "macos"))]
+impl SecretStore for Keyring {
+    fn name(&self) -> &'static str {
+        "the secret service"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        let output = secret_tool("lookup", &[], key)
+            .stderr(Stdio::piped())
+            .output()
+            .context("run secret-tool")?;
+
+        if output.status.success() {
+            let secret = String::from_utf8(output.stdout).context("secret is not valid utf8")?;
+            return Ok(Some(
+                secret.strip_suffix('\n').unwrap_or(&secret).to_string(),
+            ));
+        }
+
+        // "lookup" exits with 1 without any message if there is no secret
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.trim().is_empty() {
+            return Ok(None);
+        }
+
+        bail!("secret-tool lookup: {}", stderr.trim());
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<()> {
+        let label = format!("--label={SERVICE}: {key}");
+        let mut child = secret_tool("store", &[&label], key)
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("run secret-tool")?;
+        // the secret is read from stdin, to not be visible in the process list
+        child
+            .stdin
+            .take()
+            .context("secret-tool stdin")?
+            .write_all(secret.as_bytes())?;
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "secret-tool store: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<bool> {
+        let existed = self.get(key)?.is_some();
+        let status = secret_tool("clear", &[], key)
+            .status()
+            .context("run secret-tool")?;
+        if !status.success() {
+            bail!("secret-tool clear exited with {status}");
+        }
+
+        Ok(existed)
+    }
+}
+
+/// Create a `secret-tool` command with the `options` and the attributes identifying `key`.
+#[cfg(not(target_os = "macos"))]
+fn secret_tool(action: &str, options: &[&str], key: &str) -> Command {
+    let mut cmd = Command::new("secret-tool");
+    // the options need to come before the attributes
+    cmd.arg(action)
+        .args(options)
+        .args(["service", SERVICE, "key", key])
+        .stdout(Stdio::piped());
+
+    cmd
+}
+
+/// A plain-text TOML file, only readable by the current user.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    #[must_use]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Create a store for [`FILE_NAME`] in the config directory.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    pub fn new_default() -> Result<Self> {
+        Ok(Self::new(get_app_config_path()?.join(FILE_NAME)))
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("parse \"{}\"", self.path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(err) => Err(err).with_context(|| format!("read \"{}\"", self.path.display())),
+        }
+    }
+
+    fn write(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        let content = toml::to_string(secrets)?;
+        write_private(&self.path, &content)
+            .with_context(|| format!("write \"{}\"", self.path.display()))
+    }
+}
+
+impl SecretStore for FileStore {
+    fn name(&self) -> &'static str {
+        "the credentials file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.read()?.remove(key))
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<()> {
+        let mut secrets = self.read()?;
+        secrets.insert(key.to_string(), secret.to_string());
+
+        self.write(&secrets)
+    }
+
+    fn remove(&self, key: &str) -> Result<bool> {
+        let mut secrets = self.read()?;
+        if secrets.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.write(&secrets)?;
+
+        Ok(true)
+    }
+}
+
+/// Write `content` to `path`, creating it only readable and writable by the current user.
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+        options.mode(0o600);
+    }
+
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{FileStore, SecretStore};
+
+    #[test]
+    fn should_roundtrip_file_store() {
+        let path =
+            std::env::temp_dir().join(format!("termusic-credentials-{}.toml", std::process::id()));
+        let store = FileStore::new(path.clone());
+
+        assert_eq!(store.get("podcast:example.com").unwrap(), None);
+        store.set("podcast:example.com", "user:pass").unwrap();
+        store.set("other", "secret").unwrap();
+        assert_eq!(
+            store.get("podcast:example.com").unwrap().as_deref(),
+            Some("user:pass")
+        );
+
+        assert!(store.remove("podcast:example.com").unwrap());
+        assert!(!store.remove("podcast:example.com").unwrap());
+        assert_eq!(store.get("other").unwrap().as_deref(), Some("secret"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,347 @@
+//! Syncing tracks to a mounted (portable) device, optionally transcoding lossless files.
+
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::config::v2::tui::{DeviceSync, TranscodeFormat};
+
+/// File name of the manifest stored in the device root, containing the hashes of the already synced sources.
+pub const MANIFEST_FILE_NAME: &str = ".termusic-sync.json";
+
+/// Tracks already synced to a device.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+struct SyncManifest {
+    /// Map of the path relative to the device root to the md5 hash of the source it was created from
+    files: HashMap<String, String>,
+}
+
+impl SyncManifest {
+    /// Load the manifest from `device`, or a empty one if there is none yet.
+    fn load(device: &Path) -> Result<Self> {
+        let path = device.join(MANIFEST_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                serde_json::from_str(&content).with_context(|| path.display().to_string())
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| path.display().to_string()),
+        }
+    }
+
+    /// Save the manifest to `device`.
+    fn save(&self, device: &Path) -> Result<()> {
+        let path = device.join(MANIFEST_FILE_NAME);
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content).with_context(|| path.display().to_string())
+    }
+}
+
+/// Counts of what happened in a [`sync_to_device`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub copied: usize,
+    pub transcoded: usize,
+    /// Tracks which were already up-to-date on the device
+    pub skipped: usize,
+    /// Tracks that could not be synced, with the error
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl TranscodeFormat {
+    /// The file extension for this format, [`None`] for [`TranscodeFormat::None`].
+    #[must_use]
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Mp3 => Some("mp3"),
+            Self::Opus => Some("opus"),
+            Self::Ogg => Some("ogg"),
+        }
+    }
+
+    /// The `ffmpeg` muxer and audio codec for this format.
+    fn ffmpeg_format(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::None => None,
+            Self::Mp3 => Some(("mp3", "libmp3lame")),
+            Self::Opus => Some(("opus", "libopus")),
+            Self::Ogg => Some(("ogg", "libvorbis")),
+        }
+    }
+}
+
+/// Check if the path has the extension of a lossless format.
+#[must_use]
+pub fn is_lossless(path: &Path) -> bool {
+    let Some(ext) = path.extension().and_then(OsStr::to_str) else {
+        return false;
+    };
+
+    matches!(
+        ext.to_lowercase().as_str(),
+        "flac" | "wav" | "aiff" | "aif" | "aifc" | "ape" | "wv"
+    )
+}
+
+/// Get the path on the device for `source`, relative to the device root.
+///
+/// Tracks inside a music root keep their path relative to that root, all others are put into a `other` directory.
+#[must_use]
+pub fn device_relative_path(source: &Path, music_dirs: &[PathBuf]) -> PathBuf {
+    music_dirs
+        .iter()
+        .find_map(|dir| source.strip_prefix(dir).ok())
+        .filter(|rel| !rel.as_os_str().is_empty())
+        .map_or_else(
+            || Path::new("other").join(source.file_name().unwrap_or_default()),
+            Path::to_path_buf,
+        )
+}
+
+/// Copy all `sources` to the device configured in `settings`.
+///
+/// Lossless files are transcoded if configured, which requires `ffmpeg` in `PATH`.
+/// Files whose source did not change since the last sync (by hash) are skipped.
+///
+/// Only fails if the device is not accessible, errors for single tracks are collected in [`SyncReport::failed`].
+pub fn sync_to_device(
+    sources: &[PathBuf],
+    music_dirs: &[PathBuf],
+    settings: &DeviceSync,
+) -> Result<SyncReport> {
+    let Some(device) = settings.device_path.as_deref() else {
+        bail!("No device path configured (\"device_sync.device_path\")");
+    };
+    if !device.is_dir() {
+        bail!("Device path {} is not a directory", device.display());
+    }
+
+    let mut manifest = SyncManifest::load(device)?;
+    let mut report = SyncReport::default();
+
+    for source in sources {
+        match sync_file(source, device, music_dirs, settings, &mut manifest) {
+            Ok(SyncAction::Copied) => report.copied += 1,
+            Ok(SyncAction::Transcoded) => report.transcoded += 1,
+            Ok(SyncAction::Skipped) => report.skipped += 1,
+            Err(err) => report.failed.push((source.clone(), format!("{err:#}"))),
+        }
+    }
+
+    manifest.save(device)?;
+
+    Ok(report)
+}
+
+/// What happened to a single file in a sync.
+enum SyncAction {
+    Copied,
+    Transcoded,
+    Skipped,
+}
+
+/// Sync a single `source` to `device`, updating `manifest` on success.
+fn sync_file(
+    source: &Path,
+    device: &Path,
+    music_dirs: &[PathBuf],
+    settings: &DeviceSync,
+    manifest: &mut SyncManifest,
+) -> Result<SyncAction> {
+    let mut rel = device_relative_path(source, music_dirs);
+    let transcode_ext = settings
+        .transcode
+        .extension()
+        .filter(|_| is_lossless(source));
+    if let Some(ext) = transcode_ext {
+        rel.set_extension(ext);
+    }
+
+    let dest = device.join(&rel);
+    let key = rel.to_string_lossy().to_string();
+    let hash = hash_file(source)?;
+
+    if dest.exists() && manifest.files.get(&key) == Some(&hash) {
+        return Ok(SyncAction::Skipped);
+    }
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).with_context(|| parent.display().to_string())?;
+    }
+
+    // write to a temporary file first, so that a aborted sync does not leave partial files with the proper name
+    let mut part = dest.clone().into_os_string();
+    part.push(".part");
+    let part = PathBuf::from(part);
+
+    let res = if transcode_ext.is_some() {
+        transcode(source, &part, settings.transcode, settings.bitrate)
+    } else {
+        fs::copy(source, &part)
+            .map(|_| ())
+            .with_context(|| format!("{} -> {}", source.display(), part.display()))
+    };
+    let res = res.and_then(|()| {
+        fs::rename(&part, &dest)
+            .with_context(|| format!("{} -> {}", part.display(), dest.display()))
+    });
+
+    if let Err(err) = res {
+        let _ = fs::remove_file(&part);
+        return Err(err);
+    }
+
+    manifest.files.insert(key, hash);
+
+    if transcode_ext.is_some() {
+        Ok(SyncAction::Transcoded)
+    } else {
+        Ok(SyncAction::Copied)
+    }
+}
+
+/// Transcode `source` to `dest` with `ffmpeg`.
+fn transcode(source: &Path, dest: &Path, format: TranscodeFormat, bitrate: u16) -> Result<()> {
+    let Some((muxer, codec)) = format.ffmpeg_format() else {
+        bail!("No format to transcode to");
+    };
+
+    let output = Command::new("ffmpeg")
+        .args(["-nostdin", "-y", "-loglevel", "error", "-i"])
+        .arg(source)
+        // keep the tags, but drop embedded cover art as not all formats support video streams
+        .args(["-map_metadata", "0", "-vn", "-codec:a", codec, "-b:a"])
+        .arg(format!("{bitrate}k"))
+        .args(["-f", muxer])
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .context("failed to run ffmpeg, is it installed?")?;
+
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Get the md5 hash of the file at `path` as hex.
+fn hash_file(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| path.display().to_string())?;
+    let mut reader = BufReader::new(file);
+    let mut context = md5::Context::new();
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        context.consume(&buf[..read]);
+    }
+
+    Ok(hex::encode(context.finalize().as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        MANIFEST_FILE_NAME, SyncReport, device_relative_path, is_lossless, sync_to_device,
+    };
+    use crate::config::v2::tui::{DeviceSync, TranscodeFormat};
+
+    #[test]
+    fn should_get_device_relative_path() {
+        let dirs = [PathBuf::from("/music"), PathBuf::from("/other/music")];
+
+        assert_eq!(
+            device_relative_path(Path::new("/music/artist/album/a.flac"), &dirs),
+            PathBuf::from("artist/album/a.flac")
+        );
+        assert_eq!(
+            device_relative_path(Path::new("/other/music/b.mp3"), &dirs),
+            PathBuf::from("b.mp3")
+        );
+        assert_eq!(
+            device_relative_path(Path::new("/downloads/c.mp3"), &dirs),
+            PathBuf::from("other/c.mp3")
+        );
+
+        assert!(is_lossless(Path::new("/music/a.FLAC")));
+        assert!(!is_lossless(Path::new("/music/a.mp3")));
+    }
+
+    #[test]
+    fn should_skip_already_synced() {
+        let dir = std::env::temp_dir().join(format!("termusic-device-sync-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let music = dir.join("music");
+        let device = dir.join("device");
+        fs::create_dir_all(music.join("album")).unwrap();
+        fs::create_dir_all(&device).unwrap();
+        let track_a = music.join("album/a.mp3");
+        let track_b = music.join("album/b.flac");
+        fs::write(&track_a, "a").unwrap();
+        fs::write(&track_b, "b").unwrap();
+
+        let settings = DeviceSync {
+            device_path: Some(device.clone()),
+            transcode: TranscodeFormat::None,
+            ..Default::default()
+        };
+        let sources = [track_a.clone(), track_b];
+        let music_dirs = [music];
+
+        let report = sync_to_device(&sources, &music_dirs, &settings).unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                copied: 2,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(device.join("album/b.flac")).unwrap(),
+            "b"
+        );
+        assert!(device.join(MANIFEST_FILE_NAME).exists());
+
+        // only the changed file should be copied again
+        fs::write(&track_a, "changed").unwrap();
+        let report = sync_to_device(&sources, &music_dirs, &settings).unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                copied: 1,
+                skipped: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(device.join("album/a.mp3")).unwrap(),
+            "changed"
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
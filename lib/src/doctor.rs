@@ -0,0 +1,149 @@
+//! Result types shared by the `doctor` subcommands of the binaries.
+
+use std::fmt::Display;
+
+/// The outcome of a single [`Check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// Something that may cause problems, but does not prevent playback.
+    Warning,
+    /// Something that is known to prevent playback or startup.
+    Error,
+}
+
+impl CheckStatus {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warning => "warn",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A single diagnostic result, with a hint on how to resolve it if not ok.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub status: CheckStatus,
+    pub name: &'static str,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    #[must_use]
+    pub fn ok(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Ok,
+            name,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    #[must_use]
+    pub fn warning(
+        name: &'static str,
+        message: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            status: CheckStatus::Warning,
+            name,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    #[must_use]
+    pub fn error(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Error,
+            name,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:^5}] {}: {}",
+            self.status.as_str(),
+            self.name,
+            self.message
+        )?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\n        hint: {hint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A collection of [`Check`]s, printed as they are added.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    checks: Vec<Check>,
+}
+
+impl Report {
+    /// Print `check` to stdout and store it for the summary.
+    pub fn push(&mut self, check: Check) {
+        println!("{check}");
+        self.checks.push(check);
+    }
+
+    /// Count the checks that have the given `status`.
+    #[must_use]
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|v| v.status == status).count()
+    }
+
+    /// Get a single line summarizing all checks.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "{} checks: {} ok, {} warnings, {} errors",
+            self.checks.len(),
+            self.count(CheckStatus::Ok),
+            self.count(CheckStatus::Warning),
+            self.count(CheckStatus::Error)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Check, CheckStatus, Report};
+
+    #[test]
+    fn should_format_checks() {
+        assert_eq!(
+            Check::ok("backend", "rusty is available").to_string(),
+            "[ ok  ] backend: rusty is available"
+        );
+        assert_eq!(
+            Check::error("yt-dlp", "not found", "install yt-dlp").to_string(),
+            "[error] yt-dlp: not found\n        hint: install yt-dlp"
+        );
+    }
+
+    #[test]
+    fn should_summarize() {
+        let mut report = Report::default();
+        report.push(Check::ok("a", ""));
+        report.push(Check::warning("b", "", ""));
+        report.push(Check::warning("c", "", ""));
+
+        assert_eq!(report.count(CheckStatus::Warning), 2);
+        assert_eq!(report.count(CheckStatus::Error), 0);
+        assert_eq!(report.summary(), "3 checks: 1 ok, 2 warnings, 0 errors");
+    }
+}
@@ -0,0 +1,478 @@
+//! The download queue of the server, downloading videos with yt-dlp, see gRPC `EnqueueDownload`.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context as _, Result, bail};
+use id3::TagLike;
+use id3::Version::Id3v24;
+use regex::Regex;
+
+use crate::player::{DownloadItem, download_item};
+
+/// Options of yt-dlp a client may not pass in [`DownloadRequest::args`](crate::player::DownloadRequest::args), as they run other programs, load
+/// other configs or change where the download is saved.
+const DENIED_ARGS: &[&str] = &[
+    "--exec",
+    "--exec-before-download",
+    "--netrc-cmd",
+    "--plugin-dirs",
+    "--use-postprocessor",
+    "--downloader",
+    "--external-downloader",
+    "--downloader-args",
+    "--external-downloader-args",
+    "--postprocessor-args",
+    "--ppa",
+    "--ffmpeg-location",
+    "--config-location",
+    "--config-locations",
+    "--batch-file",
+    "-a",
+    "--load-info-json",
+    "--output",
+    "-o",
+    "--paths",
+    "-P",
+];
+
+/// The state of a download in the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DownloadStatus {
+    /// Waiting for a free slot, or for the delay before the next retry
+    Pending,
+    /// yt-dlp is running
+    Active,
+    Paused,
+    /// All retries failed
+    Failed,
+    Done,
+    Cancelled,
+}
+
+impl DownloadStatus {
+    /// All states, in the order of their numbers.
+    pub const ALL: [Self; 6] = [
+        Self::Pending,
+        Self::Active,
+        Self::Paused,
+        Self::Failed,
+        Self::Done,
+        Self::Cancelled,
+    ];
+
+    /// The number used in gRPC for this state.
+    #[must_use]
+    pub const fn number(self) -> u32 {
+        match self {
+            Self::Pending => 0,
+            Self::Active => 1,
+            Self::Paused => 2,
+            Self::Failed => 3,
+            Self::Done => 4,
+            Self::Cancelled => 5,
+        }
+    }
+
+    /// Get the state for `number`, see [`Self::number`].
+    #[must_use]
+    pub fn from_number(number: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.number() == number)
+    }
+
+    /// The name shown for this state.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Active => "downloading",
+            Self::Paused => "paused",
+            Self::Failed => "failed",
+            Self::Done => "done",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Check if the download left the queue.
+    #[must_use]
+    pub const fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Cancelled)
+    }
+}
+
+/// What to do with a download, see gRPC `ControlDownload`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadAction {
+    /// Stop a pending or active download, keeping what was downloaded so far
+    Pause,
+    /// Queue a paused download again
+    Resume,
+    /// Stop and remove a download from the queue
+    Cancel,
+    /// Queue a failed download again, or start a download waiting for its retry right away
+    Retry,
+}
+
+impl DownloadAction {
+    /// All actions, in the order of their numbers.
+    pub const ALL: [Self; 4] = [Self::Pause, Self::Resume, Self::Cancel, Self::Retry];
+
+    /// The number used in gRPC for this action.
+    #[must_use]
+    pub const fn number(self) -> u32 {
+        match self {
+            Self::Pause => 0,
+            Self::Resume => 1,
+            Self::Cancel => 2,
+            Self::Retry => 3,
+        }
+    }
+
+    /// Get the action for `number`, see [`Self::number`].
+    #[must_use]
+    pub fn from_number(number: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.number() == number)
+    }
+}
+
+/// A download in the queue, see gRPC `DownloadItem`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadInfo {
+    pub id: u64,
+    pub url: String,
+    pub title: String,
+    pub status: DownloadStatus,
+    /// The progress of the current attempt in percent
+    pub progress: u8,
+    /// How often the download was started
+    pub attempts: u32,
+    /// The error of the last failed attempt
+    pub error: Option<String>,
+    /// The downloaded file, once done
+    pub file: Option<PathBuf>,
+}
+
+impl TryFrom<DownloadItem> for DownloadInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DownloadItem) -> Result<Self, Self::Error> {
+        let Some(status) = DownloadStatus::from_number(value.status) else {
+            bail!("Unknown download status {}", value.status);
+        };
+
+        Ok(Self {
+            id: value.id,
+            url: value.url,
+            title: value.title,
+            status,
+            progress: u8::try_from(value.progress.min(100)).context("progress(u32) to u8")?,
+            attempts: value.attempts,
+            error: value.optional_error.map(|v| {
+                let download_item::OptionalError::Error(v) = v;
+                v
+            }),
+            file: value.optional_file.map(|v| {
+                let download_item::OptionalFile::File(v) = v;
+                PathBuf::from(v)
+            }),
+        })
+    }
+}
+
+impl From<DownloadInfo> for DownloadItem {
+    fn from(value: DownloadInfo) -> Self {
+        Self {
+            id: value.id,
+            url: value.url,
+            title: value.title,
+            status: value.status.number(),
+            progress: u32::from(value.progress),
+            attempts: value.attempts,
+            optional_error: value.error.map(download_item::OptionalError::Error),
+            optional_file: value
+                .file
+                .map(|v| download_item::OptionalFile::File(v.to_string_lossy().into_owned())),
+        }
+    }
+}
+
+/// Check that `args` do not contain any of the [`DENIED_ARGS`].
+pub fn check_args(args: &[String]) -> Result<()> {
+    for arg in args {
+        let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+        if DENIED_ARGS.contains(&name) {
+            bail!("The yt-dlp option \"{name}\" is not allowed for downloads");
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the arguments for yt-dlp to download the audio of a video into the current directory, before the
+/// [`DownloadRequest::args`](crate::player::DownloadRequest::args) and the url.
+#[must_use]
+pub fn base_args() -> Vec<String> {
+    [
+        "--no-playlist",
+        "--newline",
+        "--extract-audio",
+        "--add-metadata",
+        "--metadata-from-title",
+        "%(artist) - %(title)s",
+        #[cfg(target_os = "windows")]
+        "--restrict-filenames",
+        "--write-sub",
+        "--all-subs",
+        "--convert-subs",
+        "lrc",
+        "--output",
+        "%(title).90s.%(ext)s",
+    ]
+    .into_iter()
+    .map(ToString::to_string)
+    .collect()
+}
+
+/// Parse the percentage of a `--newline` progress line of yt-dlp, like `[download]  42.3% of 3.20MiB at 1.00MiB/s`.
+#[must_use]
+pub fn parse_progress(line: &str) -> Option<u8> {
+    let rest = line.strip_prefix("[download]")?.trim_start();
+    let (percent, _) = rest.split_once('%')?;
+    let percent: f32 = percent.trim().parse().ok()?;
+
+    // cannot be outside of u8, as it is clamped to 0..=100
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Some(percent.clamp(0.0, 100.0) as u8)
+}
+
+#[expect(dead_code)]
+static RE_FILENAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[ffmpeg\] Destination: (?P<name>.*)\.mp3").unwrap());
+
+static RE_FILENAME_YTDLP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[ExtractAudio\] Destination: (?P<name>.+\.[[:alnum:]]+)").unwrap()
+});
+
+// This just parsing the output from youtubedl to get the audio path
+// This is used because we need to get the song name
+// example ~/path/to/song/song.mp3
+#[must_use]
+pub fn extract_filepath(output: &str, dir: &str) -> Option<String> {
+    // #[cfg(not(feature = "yt-dlp"))]
+    // if let Some(cap) = RE_FILENAME.captures(output) {
+    //     if let Some(c) = cap.name("name") {
+    //         let filename = format!("{}/{}.mp3", dir, c.as_str());
+    //         return Ok(filename);
+    //     }
+    // }
+    // #[cfg(feature = "yt-dlp")]
+    if let Some(cap) = RE_FILENAME_YTDLP.captures(output)
+        && let Some(c) = cap.name("name")
+    {
+        let filename = format!("{dir}/{}", c.as_str());
+        return Some(filename);
+    }
+    None
+}
+
+/// Clean up after yt-dlp downloaded `file_fullname` into `path`: remove the info json and embed its lyrics.
+pub fn finish_download(path: &Path, file_fullname: &str) {
+    remove_downloaded_json(path, file_fullname);
+    embed_downloaded_lrc(path, file_fullname);
+}
+
+fn remove_downloaded_json(path: &Path, file_fullname: &str) {
+    let files = walkdir::WalkDir::new(path).follow_links(true);
+    for f in files
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|f| {
+            let p = Path::new(f.file_name());
+            p.extension().is_some_and(|ext| ext == "json")
+        })
+        .filter(|f| {
+            let path_json = Path::new(f.file_name());
+            let p1: &Path = Path::new(file_fullname);
+            path_json.file_stem().is_some_and(|stem_lrc| {
+                p1.file_stem().is_some_and(|p_base| {
+                    stem_lrc
+                        .to_string_lossy()
+                        .contains(p_base.to_string_lossy().as_ref())
+                })
+            })
+        })
+    {
+        std::fs::remove_file(f.path()).ok();
+    }
+}
+
+fn embed_downloaded_lrc(path: &Path, file_fullname: &str) {
+    // only mp3 files have id3 tags, other formats keep the lrc files next to them
+    if !Path::new(file_fullname)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"))
+    {
+        return;
+    }
+
+    let mut id3_tag = if let Ok(tag) = id3::Tag::read_from_path(file_fullname) {
+        tag
+    } else {
+        let mut tags = id3::Tag::new();
+        let file_path = Path::new(file_fullname);
+        if let Some(p_base) = file_path.file_stem() {
+            tags.set_title(p_base.to_string_lossy());
+        }
+        tags.write_to_path(file_path, Id3v24).ok();
+        tags
+    };
+
+    // here we add all downloaded lrc file
+    let files = walkdir::WalkDir::new(path).follow_links(true);
+
+    for entry in files
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|f| f.file_type().is_file())
+        .filter(|f| {
+            let name = f.file_name();
+            let p = Path::new(&name);
+            p.extension().is_some_and(|ext| ext == "lrc")
+        })
+        .filter(|f| {
+            let path_lrc = Path::new(f.file_name());
+            let p1: &Path = Path::new(file_fullname);
+            path_lrc.file_stem().is_some_and(|stem_lrc| {
+                p1.file_stem().is_some_and(|p_base| {
+                    stem_lrc
+                        .to_string_lossy()
+                        .contains(p_base.to_string_lossy().as_ref())
+                })
+            })
+        })
+    {
+        let path_lrc = Path::new(entry.file_name());
+        let mut lang_ext = "eng".to_string();
+        if let Some(p_short) = path_lrc.file_stem() {
+            let p2 = Path::new(p_short);
+            if let Some(ext2) = p2.extension() {
+                lang_ext = ext2.to_string_lossy().to_string();
+            }
+        }
+        let lyric_string = std::fs::read_to_string(entry.path());
+        id3_tag.add_frame(id3::frame::Lyrics {
+            lang: "eng".to_string(),
+            description: lang_ext,
+            text: lyric_string.unwrap_or_else(|_| String::from("[00:00:01] No lyric")),
+        });
+        std::fs::remove_file(entry.path()).ok();
+    }
+
+    id3_tag.write_to_path(file_fullname, Id3v24).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        DownloadAction, DownloadInfo, DownloadStatus, check_args, extract_filepath, parse_progress,
+    };
+    use crate::player::DownloadItem;
+
+    #[test]
+    fn should_map_numbers() {
+        for status in DownloadStatus::ALL {
+            assert_eq!(DownloadStatus::from_number(status.number()), Some(status));
+        }
+        for action in DownloadAction::ALL {
+            assert_eq!(DownloadAction::from_number(action.number()), Some(action));
+        }
+        assert_eq!(DownloadStatus::from_number(6), None);
+        assert_eq!(DownloadAction::from_number(4), None);
+    }
+
+    #[test]
+    fn should_convert_download_info() {
+        let info = DownloadInfo {
+            id: 3,
+            url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+            title: "Some Song".to_string(),
+            status: DownloadStatus::Pending,
+            progress: 42,
+            attempts: 1,
+            error: Some("HTTP Error 403".to_string()),
+            file: None,
+        };
+
+        let grpc = DownloadItem::from(info.clone());
+        assert_eq!(DownloadInfo::try_from(grpc).unwrap(), info);
+    }
+
+    #[test]
+    fn should_deny_args() {
+        let args = |v: &[&str]| v.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert!(
+            check_args(&args(&[
+                "--audio-format",
+                "opus",
+                "--cookies-from-browser",
+                "firefox"
+            ]))
+            .is_ok()
+        );
+        assert!(check_args(&args(&["--exec", "rm -rf ~"])).is_err());
+        assert!(check_args(&args(&["--exec=rm -rf ~"])).is_err());
+        assert!(check_args(&args(&["-o", "/etc/passwd"])).is_err());
+    }
+
+    #[test]
+    fn should_parse_progress() {
+        assert_eq!(
+            parse_progress("[download]  42.3% of    3.20MiB at    1.00MiB/s ETA 00:02"),
+            Some(42)
+        );
+        assert_eq!(
+            parse_progress("[download] 100% of 3.20MiB in 00:00:03"),
+            Some(100)
+        );
+        assert_eq!(
+            parse_progress("[download] Destination: Some Song.webm"),
+            None
+        );
+        assert_eq!(
+            parse_progress("[ExtractAudio] Destination: Some Song.mp3"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_youtube_output_parsing() {
+        // #[cfg(not(feature = "yt-dlp"))]
+        // assert_eq!(
+        //     extract_filepath(
+        //         r"sdflsdf [ffmpeg] Destination: 观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3 sldflsdfj",
+        //         "/tmp"
+        //     )
+        //     .unwrap(),
+        //     "/tmp/观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3".to_string()
+        // );
+        assert_eq!(
+            extract_filepath(
+                r"sdflsdf [ExtractAudio] Destination: 观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3 sldflsdfj",
+                "/tmp"
+            )
+            .unwrap(),
+            "/tmp/观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3".to_string()
+        );
+        assert_eq!(
+            extract_filepath(
+                "[ExtractAudio] Destination: Some.Title.opus\nDeleting original file",
+                "/tmp"
+            )
+            .unwrap(),
+            "/tmp/Some.Title.opus"
+        );
+    }
+}
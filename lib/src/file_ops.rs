@@ -0,0 +1,286 @@
+//! Filesystem operations for library files, like copying, moving and moving to the trash.
+
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write as _},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+/// Copy `from` to `to`, recursively if `from` is a directory.
+///
+/// Fails if `to` already exists.
+pub fn copy_path(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        bail!("Destination {} already exists", to.display());
+    }
+
+    if from.is_dir() {
+        if to.starts_with(from) {
+            bail!("Cannot copy {} into itself", from.display());
+        }
+
+        fs::create_dir_all(to).with_context(|| to.display().to_string())?;
+
+        for entry in fs::read_dir(from).with_context(|| from.display().to_string())? {
+            let entry = entry?;
+            copy_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to).with_context(|| format!("{} -> {}", from.display(), to.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Move `from` to `to`.
+///
+/// If a plain rename is not possible because `to` is on a different filesystem, fall back to copy and delete.
+///
+/// Fails if `to` already exists.
+pub fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        bail!("Destination {} already exists", to.display());
+    }
+
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::CrossesDevices => {
+            copy_path(from, to)?;
+            remove_path(from)
+        }
+        Err(err) => Err(err).with_context(|| format!("{} -> {}", from.display(), to.display())),
+    }
+}
+
+/// Remove `path` permanently, recursively if it is a directory.
+pub fn remove_path(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .with_context(|| path.display().to_string())
+}
+
+/// Delete `path`, either by moving it to the trash (see [`trash_path`]) or permanently (see [`remove_path`]).
+///
+/// Returns the trashed path if `to_trash` is `true`.
+pub fn delete_path(path: &Path, to_trash: bool) -> Result<Option<TrashedPath>> {
+    if to_trash {
+        return trash_path(path).map(Some);
+    }
+
+    remove_path(path)?;
+
+    Ok(None)
+}
+
+/// A path that got moved into the trash with [`trash_path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedPath {
+    /// The path before it got moved to the trash
+    pub original: PathBuf,
+    /// The path inside the trash's `files` directory
+    pub trashed: PathBuf,
+    /// The `.trashinfo` file describing `trashed`
+    pub info: PathBuf,
+}
+
+impl TrashedPath {
+    /// Move the path back to where it was before it got trashed.
+    ///
+    /// Fails if something already exists at the original path.
+    pub fn restore(&self) -> Result<()> {
+        move_path(&self.trashed, &self.original)?;
+        // the info file is useless without the trashed file, so ignore if it is already gone
+        let _ = fs::remove_file(&self.info);
+
+        Ok(())
+    }
+}
+
+/// Get the home trash directory, see the [FreeDesktop.org Trash specification](https://specifications.freedesktop.org/trash-spec/latest/).
+pub fn home_trash_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().context("failed to find the data directory")?;
+
+    Ok(data_dir.join("Trash"))
+}
+
+/// Move `path` into the home trash, following the [FreeDesktop.org Trash specification](https://specifications.freedesktop.org/trash-spec/latest/).
+///
+/// `path` is expected to be absolute.
+pub fn trash_path(path: &Path) -> Result<TrashedPath> {
+    trash_path_in(path, &home_trash_dir()?)
+}
+
+/// Move `path` into the trash at `trash_dir`.
+fn trash_path_in(path: &Path, trash_dir: &Path) -> Result<TrashedPath> {
+    if !path.is_absolute() {
+        bail!("Path to trash is not absolute: {}", path.display());
+    }
+    // "symlink_metadata" as symlinks are trashed as the link itself, not their target
+    path.symlink_metadata()
+        .with_context(|| path.display().to_string())?;
+    let file_name = path.file_name().context("path to trash has no file name")?;
+
+    let files_dir = trash_dir.join("files");
+    let info_dir = trash_dir.join("info");
+    fs::create_dir_all(&files_dir).with_context(|| files_dir.display().to_string())?;
+    fs::create_dir_all(&info_dir).with_context(|| info_dir.display().to_string())?;
+
+    let info_content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_trash_path(path),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+
+    // the info file is created first (atomically) to claim the name, as per the specification
+    let mut suffix: usize = 1;
+    let (trashed, info, mut info_file) = loop {
+        if suffix > 1000 {
+            bail!("No free name in the trash for {}", path.display());
+        }
+
+        let mut name = file_name.to_os_string();
+        if suffix > 1 {
+            name.push(format!(".{suffix}"));
+        }
+        suffix += 1;
+
+        let trashed = files_dir.join(&name);
+        if trashed.symlink_metadata().is_ok() {
+            continue;
+        }
+
+        name.push(".trashinfo");
+        let info = info_dir.join(name);
+
+        match OpenOptions::new().write(true).create_new(true).open(&info) {
+            Ok(file) => break (trashed, info, file),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => (),
+            Err(err) => return Err(err).context("create trashinfo file"),
+        }
+    };
+
+    let res = info_file
+        .write_all(info_content.as_bytes())
+        .context("write trashinfo file")
+        .and_then(|()| move_path(path, &trashed));
+
+    if let Err(err) = res {
+        let _ = fs::remove_file(&info);
+        return Err(err);
+    }
+
+    Ok(TrashedPath {
+        original: path.to_path_buf(),
+        trashed,
+        info,
+    })
+}
+
+/// Percent-encode the path for a `.trashinfo` file, keeping the separators.
+fn encode_trash_path(path: &Path) -> String {
+    let mut out = String::new();
+    for (idx, comp) in path.to_string_lossy().split('/').enumerate() {
+        if idx > 0 {
+            out.push('/');
+        }
+        let _ = write!(out, "{}", urlencoding::encode(comp));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use pretty_assertions::assert_eq;
+
+    use super::{copy_path, encode_trash_path, move_path, trash_path_in};
+
+    /// Get a new empty directory in the temp dir, unique for the given `name`.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-file-ops-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn should_copy_and_move_recursively() {
+        let dir = test_dir("copy");
+        fs::create_dir_all(dir.join("album/cd1")).unwrap();
+        fs::write(dir.join("album/a.mp3"), "a").unwrap();
+        fs::write(dir.join("album/cd1/b.mp3"), "b").unwrap();
+
+        copy_path(&dir.join("album"), &dir.join("copy")).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("copy/cd1/b.mp3")).unwrap(), "b");
+        assert!(dir.join("album/cd1/b.mp3").exists());
+
+        // destination exists
+        assert!(copy_path(&dir.join("album"), &dir.join("copy")).is_err());
+        assert!(move_path(&dir.join("album"), &dir.join("copy")).is_err());
+        // into itself
+        assert!(copy_path(&dir.join("album"), &dir.join("album/cd1/album")).is_err());
+
+        move_path(&dir.join("album"), &dir.join("moved")).unwrap();
+        assert!(!dir.join("album").exists());
+        assert_eq!(fs::read_to_string(dir.join("moved/a.mp3")).unwrap(), "a");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_trash_and_restore() {
+        let dir = test_dir("trash");
+        let trash = dir.join("Trash");
+        let file = dir.join("some track.mp3");
+        fs::write(&file, "a").unwrap();
+
+        let trashed = trash_path_in(&file, &trash).unwrap();
+        assert!(!file.exists());
+        assert_eq!(trashed.trashed, trash.join("files/some track.mp3"));
+        let info = fs::read_to_string(&trashed.info).unwrap();
+        assert!(info.starts_with("[Trash Info]\nPath="));
+        assert!(info.contains("/some%20track.mp3\n"));
+        assert!(info.contains("\nDeletionDate="));
+
+        // same name again should not overwrite the first one
+        fs::write(&file, "b").unwrap();
+        let trashed_2 = trash_path_in(&file, &trash).unwrap();
+        assert_eq!(trashed_2.trashed, trash.join("files/some track.mp3.2"));
+        assert_eq!(
+            trashed_2.info,
+            trash.join("info/some track.mp3.2.trashinfo")
+        );
+
+        // cannot restore while the original path is taken
+        fs::write(&file, "c").unwrap();
+        assert!(trashed.restore().is_err());
+        fs::remove_file(&file).unwrap();
+
+        trashed.restore().unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "a");
+        assert!(!trashed.info.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_encode_trash_path() {
+        assert_eq!(
+            encode_trash_path(Path::new("/music/a b/ü#.mp3")),
+            "/music/a%20b/%C3%BC%23.mp3"
+        );
+    }
+}
@@ -1,12 +1,20 @@
 use anyhow::{Result, anyhow, bail};
+use futures_util::future::join_all;
+use parking_lot::Mutex;
 use rand::seq::SliceRandom;
 use serde_json::Value;
 // left for debug
 // use std::io::Write;
 use reqwest::{Client, ClientBuilder, StatusCode};
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
 
-const INVIDIOUS_INSTANCE_LIST: [&str; 5] = [
+use crate::ratelimit::LIMITER;
+use crate::ytdlp;
+
+/// Public instances, used if the instance list cannot be fetched.
+pub const INVIDIOUS_INSTANCE_LIST: [&str; 5] = [
     "https://inv.nadeko.net",
     "https://invidious.nerdvpn.de",
     "https://yewtu.be",
@@ -26,13 +34,35 @@ const INVIDIOUS_INSTANCE_LIST: [&str; 5] = [
     // "https://www.google.com",
 ];
 
-const INVIDIOUS_DOMAINS: &str = "https://api.invidious.io/instances.json?sort_by=type,users";
+/// The videos in a page of [`Instance::get_playlist`] and [`Instance::get_channel_uploads`].
+pub const COLLECTION_PAGE_SIZE: u32 = 100;
+
+/// The API listing all public instances, used to find instances that are up.
+pub const INVIDIOUS_DOMAINS: &str = "https://api.invidious.io/instances.json?sort_by=type,users";
+
+/// How long to wait for a instance to answer a health probe.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The last measured health of the probed instances, by domain.
+static HEALTH: LazyLock<Mutex<HashMap<String, InstanceHealth>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The health of a instance, as measured by [`probe_instances`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstanceHealth {
+    /// Whether the API answered with the stats of the instance
+    pub api: bool,
+    /// How long the answer took
+    pub latency: Duration,
+}
 
 #[derive(Clone, Debug)]
 pub struct Instance {
     pub domain: Option<String>,
     client: Client,
     query: Option<String>,
+    /// The region used for [`get_trending_music`](Self::get_trending_music)
+    region: Option<String>,
 }
 
 impl PartialEq for Instance {
@@ -43,11 +73,21 @@ impl PartialEq for Instance {
 
 impl Eq for Instance {}
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct YoutubeVideo {
     pub title: String,
     pub length_seconds: u64,
     pub video_id: String,
+    /// The name of the channel that uploaded the video
+    pub uploader: Option<String>,
+    pub view_count: Option<u64>,
+    /// The day the video was uploaded, like `2024-01-31`
+    pub upload_date: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// Whether the video is marked as age-restricted, only reported by yt-dlp for full entries
+    pub age_restricted: bool,
+    /// The page of the result on sites other than youtube, where `video_id` is not enough to find it
+    pub url: Option<String>,
 }
 
 impl Default for Instance {
@@ -60,32 +100,23 @@ impl Default for Instance {
             domain,
             client,
             query,
+            region: None,
         }
     }
 }
 
 impl Instance {
-    pub async fn new(query: &str) -> Result<(Self, Vec<YoutubeVideo>)> {
+    /// Find a working instance for `query`, returned with the first page of results.
+    ///
+    /// `instances` are the configured instances, if empty the public instances are used.
+    pub async fn new(query: &str, instances: &[String]) -> Result<(Self, Vec<YoutubeVideo>)> {
         let client = ClientBuilder::new()
             .timeout(Duration::from_secs(10))
             .build()?;
 
         let mut domain = String::new();
-        let mut domains = vec![];
-
-        // prefor fetch invidious instance from website, but will provide 7 backups
-        if let Ok(domain_list) = Self::get_invidious_instance_list(&client).await {
-            domains = domain_list;
-        } else {
-            for item in &INVIDIOUS_INSTANCE_LIST {
-                domains.push((*item).to_string());
-            }
-        }
-
-        domains.shuffle(&mut rand::rng());
-
         let mut video_result: Vec<YoutubeVideo> = Vec::new();
-        for v in domains {
+        for v in Self::candidate_domains(&client, instances).await {
             let url = format!("{v}/api/v1/search");
 
             let query_vec = vec![
@@ -94,7 +125,8 @@ impl Instance {
                 ("type", "video"),
                 ("sort_by", "relevance"),
             ];
-            if let Ok(result) = client.get(&url).query(&query_vec).send().await
+            // no retries, as the next instance is the fallback
+            if let Ok(result) = LIMITER.send_once(client.get(&url).query(&query_vec)).await
                 && result.status() == 200
                 && let Ok(text) = result.text().await
                 && let Some(vr) = Self::parse_youtube_options(&text)
@@ -114,11 +146,72 @@ impl Instance {
                 domain,
                 client,
                 query: Some(query.to_string()),
+                region: None,
             },
             video_result,
         ))
     }
 
+    /// Find a working instance for the music trending in `region`, returned with the trending videos.
+    ///
+    /// The region is kept for later calls to [`get_trending_music`](Self::get_trending_music).
+    /// `instances` are the configured instances, if empty the public instances are used.
+    pub async fn new_trending(
+        region: &str,
+        instances: &[String],
+    ) -> Result<(Self, Vec<YoutubeVideo>)> {
+        let client = ClientBuilder::new()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        for domain in Self::candidate_domains(&client, instances).await {
+            let instance = Self {
+                domain: Some(domain),
+                client: client.clone(),
+                query: None,
+                region: Some(region.to_string()),
+            };
+            // no retries, as the next instance is the fallback
+            if let Ok(videos) = instance.fetch_trending_music(false).await {
+                return Ok((instance, videos));
+            }
+        }
+
+        bail!("Something is wrong with your connection or all invidious servers are down.");
+    }
+
+    /// Get the instances to try, the healthiest first, see [`rank_by_health`].
+    async fn candidate_domains(client: &Client, instances: &[String]) -> Vec<String> {
+        let mut domains = Self::available_domains(client, instances).await;
+        // shuffle to spread the load over instances that were not probed yet
+        domains.shuffle(&mut rand::rng());
+
+        rank_by_health(domains)
+    }
+
+    /// Get the configured `instances`, or if there are none the public instances.
+    ///
+    /// Prefers the list of public instances, but falls back to [`INVIDIOUS_INSTANCE_LIST`].
+    async fn available_domains(client: &Client, instances: &[String]) -> Vec<String> {
+        let configured: Vec<String> = instances
+            .iter()
+            .map(|v| v.trim().trim_end_matches('/'))
+            .filter(|v| !v.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        if !configured.is_empty() {
+            return configured;
+        }
+
+        match Self::get_invidious_instance_list(client).await {
+            Ok(domain_list) => domain_list,
+            Err(_) => INVIDIOUS_INSTANCE_LIST
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+
     // GetSearchQuery fetches query result from an Invidious instance.
     pub async fn get_search_query(&self, page: u32) -> Result<Vec<YoutubeVideo>> {
         if self.domain.is_none() {
@@ -135,11 +228,12 @@ impl Instance {
             bail!("No query string found")
         };
 
-        let result = self
-            .client
-            .get(url)
-            .query(&[("q", query), ("page", &page.to_string())])
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .get(url)
+                    .query(&[("q", query), ("page", &page.to_string())]),
+            )
             .await?;
 
         match result.status() {
@@ -151,36 +245,58 @@ impl Instance {
         }
     }
 
-    // GetSuggestions returns video suggestions based on prefix strings. This is the
-    // same result as youtube search autocomplete.
-    pub async fn get_suggestions(&self, prefix: &str) -> Result<Vec<YoutubeVideo>> {
-        let url = format!(
-            "http://suggestqueries.google.com/complete/search?client=firefox&ds=yt&q={prefix}"
-        );
-        let result = self.client.get(url).send().await?;
-        match result.status() {
-            StatusCode::OK => match result.text().await {
-                Ok(text) => Self::parse_youtube_options(&text).ok_or_else(|| anyhow!("None Error")),
-                Err(e) => bail!("Error during search: {}", e),
-            },
-            _ => bail!("Error during search"),
-        }
+    /// Get `page`, starting at 1, of the videos of the youtube playlist at `url`, with yt-dlp.
+    ///
+    /// A page has [`COLLECTION_PAGE_SIZE`] videos, a shorter page is the last one.
+    pub async fn get_playlist(url: &str, page: u32) -> Result<Vec<YoutubeVideo>> {
+        let end = page.max(1).saturating_mul(COLLECTION_PAGE_SIZE);
+        let start = end.saturating_sub(COLLECTION_PAGE_SIZE) + 1;
+
+        ytdlp::flat_playlist(url, start, end).await
     }
 
-    // GetTrendingMusic fetch music trending based on region.
-    // Region (ISO 3166 country code) can be provided in the argument.
-    pub async fn get_trending_music(&self, region: &str) -> Result<Vec<YoutubeVideo>> {
-        if self.domain.is_none() {
+    /// Get `page`, starting at 1, of the uploads of the youtube channel at `url`, newest first, with yt-dlp.
+    ///
+    /// A page has [`COLLECTION_PAGE_SIZE`] videos, a shorter page is the last one.
+    pub async fn get_channel_uploads(url: &str, page: u32) -> Result<Vec<YoutubeVideo>> {
+        let Some(uploads) = ytdlp::channel_uploads_url(url) else {
+            bail!("\"{url}\" is not a youtube channel");
+        };
+
+        Self::get_playlist(&uploads, page).await
+    }
+
+    /// The region used for the music trending, if this instance is for the trending.
+    #[must_use]
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Set the region for later calls to [`get_trending_music`](Self::get_trending_music).
+    pub fn set_region(&mut self, region: String) {
+        self.region = Some(region);
+    }
+
+    /// Fetch the music trending in the region of this instance, or the region detected with [`resolve_region`].
+    pub async fn get_trending_music(&self) -> Result<Vec<YoutubeVideo>> {
+        self.fetch_trending_music(true).await
+    }
+
+    async fn fetch_trending_music(&self, retry: bool) -> Result<Vec<YoutubeVideo>> {
+        let Some(domain) = self.domain.as_ref().filter(|v| !v.is_empty()) else {
             bail!("No server available");
-        }
-        let url = format!(
-            "{}/api/v1/trending?type=music&region={region}",
-            self.domain
-                .as_ref()
-                .ok_or(anyhow!("error in domain names"))?
-        );
+        };
+        let region = self.region.clone().unwrap_or_else(|| resolve_region(None));
+        let request = self
+            .client
+            .get(format!("{domain}/api/v1/trending"))
+            .query(&[("type", "music"), ("region", &region)]);
 
-        let result = self.client.get(url).send().await?;
+        let result = if retry {
+            LIMITER.send(request).await?
+        } else {
+            LIMITER.send_once(request).await?
+        };
 
         match result.status() {
             StatusCode::OK => match result.text().await {
@@ -198,30 +314,56 @@ impl Instance {
             // let mut file = std::fs::File::create("data.txt").expect("create failed");
             // file.write_all(data.as_bytes()).expect("write failed");
             if let Some(array) = value.as_array() {
-                for v in array {
-                    if let Some((title, video_id, length_seconds)) = Self::parse_youtube_item(v) {
-                        vec.push(YoutubeVideo {
-                            title,
-                            length_seconds,
-                            video_id,
-                        });
-                    }
-                }
+                vec.extend(array.iter().filter_map(Self::parse_youtube_item));
                 return Some(vec);
             }
         }
         None
     }
 
-    fn parse_youtube_item(value: &Value) -> Option<(String, String, u64)> {
+    fn parse_youtube_item(value: &Value) -> Option<YoutubeVideo> {
         let title = value.get("title")?.as_str()?.to_owned();
         let video_id = value.get("videoId")?.as_str()?.to_owned();
         let length_seconds = value.get("lengthSeconds")?.as_u64()?;
-        Some((title, video_id, length_seconds))
+
+        let upload_date = value
+            .get("published")
+            .and_then(Value::as_i64)
+            .and_then(|v| chrono::DateTime::from_timestamp(v, 0))
+            .map(|v| v.format("%Y-%m-%d").to_string());
+        // prefer the medium quality, which is enough for a preview
+        let thumbnails = value.get("videoThumbnails").and_then(Value::as_array);
+        let thumbnail = thumbnails.and_then(|list| {
+            list.iter()
+                .find(|v| v.get("quality").and_then(Value::as_str) == Some("medium"))
+                .or_else(|| list.first())
+        });
+
+        Some(YoutubeVideo {
+            title,
+            length_seconds,
+            video_id,
+            uploader: value
+                .get("author")
+                .and_then(Value::as_str)
+                .map(str::to_owned),
+            view_count: value.get("viewCount").and_then(Value::as_u64),
+            upload_date,
+            thumbnail_url: thumbnail
+                .and_then(|v| v.get("url"))
+                .and_then(Value::as_str)
+                .map(str::to_owned),
+            age_restricted: false,
+            url: None,
+        })
     }
 
     async fn get_invidious_instance_list(client: &Client) -> Result<Vec<String>> {
-        let result = client.get(INVIDIOUS_DOMAINS).send().await?.text().await?;
+        let result = LIMITER
+            .send(client.get(INVIDIOUS_DOMAINS))
+            .await?
+            .text()
+            .await?;
         // Left here for debug
         // let mut file = std::fs::File::create("data.txt").expect("create failed");
         // file.write_all(result.as_bytes()).expect("write failed");
@@ -269,3 +411,310 @@ impl Instance {
         }
     }
 }
+
+/// Measure the latency and API availability of `domain`.
+async fn probe_instance(client: &Client, domain: &str) -> InstanceHealth {
+    let start = Instant::now();
+    // no retries, a slow or failing instance is what this is looking for
+    let api = match LIMITER
+        .send_once(client.get(format!("{domain}/api/v1/stats")))
+        .await
+    {
+        Ok(res) if res.status() == StatusCode::OK => {
+            res.text().await.is_ok_and(|text| is_instance_stats(&text))
+        }
+        _ => false,
+    };
+
+    InstanceHealth {
+        api,
+        latency: start.elapsed(),
+    }
+}
+
+/// Check if `data` looks like the `/api/v1/stats` response of a invidious instance.
+fn is_instance_stats(data: &str) -> bool {
+    serde_json::from_str::<Value>(data).is_ok_and(|value| {
+        value
+            .get("software")
+            .and_then(|v| v.get("name"))
+            .and_then(Value::as_str)
+            .is_some()
+    })
+}
+
+/// Probe all `domains` at once and remember their health for [`rank_by_health`].
+///
+/// Returns the measured health of each domain.
+pub async fn probe_instances(domains: &[String]) -> Result<Vec<(String, InstanceHealth)>> {
+    let client = ClientBuilder::new().timeout(PROBE_TIMEOUT).build()?;
+
+    let results = join_all(domains.iter().map(|domain| {
+        let client = &client;
+        async move { (domain.clone(), probe_instance(client, domain).await) }
+    }))
+    .await;
+
+    HEALTH.lock().extend(results.iter().cloned());
+
+    Ok(results)
+}
+
+/// Probe the configured `instances`, or the public instances if there are none, every `interval`.
+///
+/// This never returns, so it should be spawned as its own task.
+pub async fn probe_periodically(instances: Vec<String>, interval: Duration) {
+    loop {
+        let domains = match ClientBuilder::new().timeout(PROBE_TIMEOUT).build() {
+            Ok(client) => Instance::available_domains(&client, &instances).await,
+            Err(_) => Vec::new(),
+        };
+        match probe_instances(&domains).await {
+            Ok(results) => {
+                let healthy = results.iter().filter(|(_, health)| health.api).count();
+                debug!(
+                    "Probed {} invidious instances, {healthy} are healthy",
+                    results.len()
+                );
+            }
+            Err(err) => warn!("Could not probe the invidious instances: {err:#}"),
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Order `domains` by their last measured health, keeping the order of equally healthy ones.
+///
+/// Healthy instances come first by latency, then instances that were not probed and unhealthy ones last.
+#[must_use]
+pub fn rank_by_health(domains: Vec<String>) -> Vec<String> {
+    rank_domains(domains, &HEALTH.lock())
+}
+
+fn rank_domains(mut domains: Vec<String>, health: &HashMap<String, InstanceHealth>) -> Vec<String> {
+    domains.sort_by_key(|domain| match health.get(domain) {
+        Some(health) if health.api => (0, health.latency),
+        None => (1, Duration::ZERO),
+        Some(_) => (2, Duration::ZERO),
+    });
+
+    domains
+}
+
+/// Regions offered for the music trending, as ISO 3166 code and name.
+pub const TRENDING_REGIONS: &[(&str, &str)] = &[
+    ("AR", "Argentina"),
+    ("AU", "Australia"),
+    ("AT", "Austria"),
+    ("BE", "Belgium"),
+    ("BR", "Brazil"),
+    ("CA", "Canada"),
+    ("CL", "Chile"),
+    ("CO", "Colombia"),
+    ("CZ", "Czechia"),
+    ("DK", "Denmark"),
+    ("EG", "Egypt"),
+    ("FI", "Finland"),
+    ("FR", "France"),
+    ("DE", "Germany"),
+    ("GR", "Greece"),
+    ("HK", "Hong Kong"),
+    ("HU", "Hungary"),
+    ("IN", "India"),
+    ("ID", "Indonesia"),
+    ("IE", "Ireland"),
+    ("IL", "Israel"),
+    ("IT", "Italy"),
+    ("JP", "Japan"),
+    ("KR", "South Korea"),
+    ("MY", "Malaysia"),
+    ("MX", "Mexico"),
+    ("NL", "Netherlands"),
+    ("NZ", "New Zealand"),
+    ("NG", "Nigeria"),
+    ("NO", "Norway"),
+    ("PE", "Peru"),
+    ("PH", "Philippines"),
+    ("PL", "Poland"),
+    ("PT", "Portugal"),
+    ("RO", "Romania"),
+    ("SA", "Saudi Arabia"),
+    ("SG", "Singapore"),
+    ("ZA", "South Africa"),
+    ("ES", "Spain"),
+    ("SE", "Sweden"),
+    ("CH", "Switzerland"),
+    ("TW", "Taiwan"),
+    ("TH", "Thailand"),
+    ("TR", "Turkey"),
+    ("UA", "Ukraine"),
+    ("AE", "United Arab Emirates"),
+    ("GB", "United Kingdom"),
+    ("US", "United States"),
+    ("VN", "Vietnam"),
+];
+
+/// The region used if none is configured and none can be detected.
+pub const FALLBACK_REGION: &str = "US";
+
+/// Get `region` as a upper-case ISO 3166 code, if it looks like one.
+#[must_use]
+pub fn normalize_region(region: &str) -> Option<String> {
+    let region = region.trim();
+    (region.len() == 2 && region.chars().all(|ch| ch.is_ascii_alphabetic()))
+        .then(|| region.to_ascii_uppercase())
+}
+
+/// Get the region of a POSIX locale like `en_US.UTF-8` or `de_DE@euro`.
+#[must_use]
+pub fn region_from_locale(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?;
+    let (_lang, region) = locale.split_once(['_', '-'])?;
+
+    normalize_region(region)
+}
+
+/// Detect the region from the locale environment variables, in the order the C library uses them.
+#[must_use]
+pub fn detect_region() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .and_then(|v| region_from_locale(&v))
+}
+
+/// Get the region to use: `preferred` if valid, otherwise the detected one, otherwise [`FALLBACK_REGION`].
+#[must_use]
+pub fn resolve_region(preferred: Option<&str>) -> String {
+    preferred
+        .and_then(normalize_region)
+        .or_else(detect_region)
+        .unwrap_or_else(|| FALLBACK_REGION.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use super::{
+        Instance, InstanceHealth, YoutubeVideo, is_instance_stats, normalize_region, rank_domains,
+        region_from_locale, resolve_region,
+    };
+
+    #[test]
+    fn should_parse_region_from_locale() {
+        assert_eq!(region_from_locale("en_US.UTF-8").as_deref(), Some("US"));
+        assert_eq!(region_from_locale("de_DE@euro").as_deref(), Some("DE"));
+        assert_eq!(region_from_locale("pt-br").as_deref(), Some("BR"));
+        assert_eq!(region_from_locale("C.UTF-8"), None);
+        assert_eq!(region_from_locale("POSIX"), None);
+        // a script, not a region
+        assert_eq!(region_from_locale("sr_Latn"), None);
+    }
+
+    #[test]
+    fn should_parse_youtube_options() {
+        let data = r#"[
+            {"type": "video", "title": "Song", "videoId": "abc", "lengthSeconds": 200, "author": "Artist",
+             "viewCount": 1500000, "published": 1706659200,
+             "videoThumbnails": [{"quality": "maxres", "url": "https://i.ytimg.com/maxres.jpg"},
+                                 {"quality": "medium", "url": "https://i.ytimg.com/medium.jpg"}]},
+            {"type": "channel", "author": "Not a video"},
+            {"type": "video", "title": "Bare", "videoId": "def", "lengthSeconds": 10}
+        ]"#;
+
+        assert_eq!(
+            Instance::parse_youtube_options(data).unwrap(),
+            [
+                YoutubeVideo {
+                    title: "Song".to_string(),
+                    length_seconds: 200,
+                    video_id: "abc".to_string(),
+                    uploader: Some("Artist".to_string()),
+                    view_count: Some(1_500_000),
+                    upload_date: Some("2024-01-31".to_string()),
+                    thumbnail_url: Some("https://i.ytimg.com/medium.jpg".to_string()),
+                    age_restricted: false,
+                    url: None,
+                },
+                YoutubeVideo {
+                    title: "Bare".to_string(),
+                    length_seconds: 10,
+                    video_id: "def".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(Instance::parse_youtube_options("{}"), None);
+    }
+
+    #[test]
+    fn should_prefer_configured_region() {
+        assert_eq!(normalize_region(" jp ").as_deref(), Some("JP"));
+        assert_eq!(normalize_region("USA"), None);
+        assert_eq!(resolve_region(Some("gb")), "GB");
+        // invalid regions are ignored, the result then depends on the environment
+        assert_eq!(resolve_region(Some("Germany")).len(), 2);
+    }
+
+    #[test]
+    fn should_rank_by_health() {
+        let health = HashMap::from([
+            (
+                "https://slow".to_string(),
+                InstanceHealth {
+                    api: true,
+                    latency: Duration::from_millis(900),
+                },
+            ),
+            (
+                "https://fast".to_string(),
+                InstanceHealth {
+                    api: true,
+                    latency: Duration::from_millis(100),
+                },
+            ),
+            (
+                "https://down".to_string(),
+                InstanceHealth {
+                    api: false,
+                    latency: Duration::from_millis(10),
+                },
+            ),
+        ]);
+        let domains = [
+            "https://down",
+            "https://new",
+            "https://slow",
+            "https://fast",
+        ]
+        .map(ToString::to_string)
+        .to_vec();
+
+        assert_eq!(
+            rank_domains(domains, &health),
+            [
+                "https://fast",
+                "https://slow",
+                "https://new",
+                "https://down"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_detect_instance_stats() {
+        assert!(is_instance_stats(
+            r#"{"version": "2.0", "software": {"name": "invidious", "version": "2024.01.01"}}"#
+        ));
+        assert!(!is_instance_stats(
+            r#"{"error": "Administrator has disabled this endpoint."}"#
+        ));
+        assert!(!is_instance_stats("<html></html>"));
+    }
+}
@@ -6,18 +6,42 @@
 // TODO: work to remove the following lints
 #![allow(clippy::missing_errors_doc)]
 
+pub mod audio_analysis;
+pub mod audio_verify;
+pub mod auto_dj;
+pub mod cache;
 pub mod common;
 pub mod config;
+pub mod credentials;
+pub mod device_sync;
+pub mod doctor;
+pub mod downloads;
+pub mod file_ops;
 pub mod invidious;
+pub mod loudness;
+pub mod metrics;
 pub mod new_database;
 pub mod player;
 pub mod playlist;
+pub mod playlist_sync;
 pub mod podcast;
+pub mod radio_browser;
+pub mod ratelimit;
+pub mod search_history;
+pub mod search_provider;
+pub mod smart_playlist;
 pub mod songtag;
+pub mod sponsorblock;
+pub mod stats_import;
+pub mod suggestions;
+pub mod tag_backup;
 pub mod taskpool;
 pub mod track;
+pub mod track_suggestions;
+pub mod translate;
 pub mod utils;
 pub mod xywh;
+pub mod ytdlp;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
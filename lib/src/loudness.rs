@@ -0,0 +1,316 @@
+//! Measuring the integrated loudness of a track per EBU R128 (ITU-R BS.1770), to compute a `ReplayGain` for tracks
+//! that are not tagged with one.
+//!
+//! All channels are weighted equally, so the surround channels of multichannel audio are not boosted like the
+//! standard describes.
+
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use lofty::config::WriteOptions;
+use lofty::file::TaggedFileExt;
+use lofty::tag::{ItemKey, Tag, TagExt};
+
+use crate::audio_analysis;
+
+/// The loudness in LUFS a `ReplayGain` 2.0 gain brings a track to.
+pub const REPLAY_GAIN_REFERENCE: f64 = -18.0;
+
+/// The length of a step of a gating block in seconds.
+const STEP_LEN: f64 = 0.1;
+
+/// The amount of steps a gating block of 400ms is made of, so that consecutive blocks overlap by 75%.
+const BLOCK_STEPS: usize = 4;
+
+/// Blocks at or below this loudness in LUFS are silence and ignored.
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Blocks more than this many LU below the loudness of the non-silent blocks are ignored.
+const RELATIVE_GATE: f64 = -10.0;
+
+/// The loudness measured from the audio of a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    /// The integrated loudness in LUFS
+    pub integrated: f64,
+    /// The highest sample, `1.0` being full scale
+    pub peak: f32,
+}
+
+impl Loudness {
+    /// Get the `ReplayGain` 2.0 gain in dB, to bring the track to [`REPLAY_GAIN_REFERENCE`].
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // a gain is a few dB at most
+    pub fn replay_gain(&self) -> f32 {
+        (REPLAY_GAIN_REFERENCE - self.integrated) as f32
+    }
+}
+
+/// A second order IIR filter, in transposed direct form II.
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+
+        output
+    }
+}
+
+/// Get the two filters of the K-weighting at `sample_rate`: a high shelf for the acoustic effects of the head, then
+/// a high pass for the low frequencies which are barely perceived.
+///
+/// The coefficients are derived from the analog prototypes of the standard, so that any sample rate is supported.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = f64::from(sample_rate);
+
+    let (f0, gain, q) = (
+        1_681.974_450_955_533,
+        3.999_843_853_973_347,
+        0.707_175_236_955_419_6,
+    );
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10_f64.powf(gain / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        ..Default::default()
+    };
+
+    let (f0, q) = (38.135_470_876_024_44, 0.500_327_037_323_877_3);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        ..Default::default()
+    };
+
+    [shelf, high_pass]
+}
+
+/// Measures the [`Loudness`] of interleaved samples.
+#[derive(Debug)]
+pub struct LoudnessAnalyzer {
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    /// The amount of frames in a step of a block
+    step_frames: usize,
+    /// The sum of the squared weighted samples of all channels in the current step
+    step_sum: f64,
+    step_len: usize,
+    /// The mean squares of the last [`BLOCK_STEPS`] steps
+    steps: Vec<f64>,
+    /// The mean square of each gating block
+    blocks: Vec<f64>,
+    peak: f32,
+}
+
+impl LoudnessAnalyzer {
+    #[must_use]
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        // positive and far below usize::MAX
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let step_frames = ((f64::from(sample_rate) * STEP_LEN).round() as usize).max(1);
+
+        Self {
+            channels,
+            filters: vec![k_weighting(sample_rate); channels],
+            step_frames,
+            step_sum: 0.0,
+            step_len: 0,
+            steps: Vec::with_capacity(BLOCK_STEPS),
+            blocks: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    /// Add interleaved `samples` of all channels.
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (sample, filters) in frame.iter().zip(&mut self.filters) {
+                self.peak = self.peak.max(sample.abs());
+                let weighted = filters
+                    .iter_mut()
+                    .fold(f64::from(*sample), |v, filter| filter.process(v));
+                self.step_sum += weighted * weighted;
+            }
+
+            self.step_len += 1;
+            if self.step_len == self.step_frames {
+                self.finish_step();
+            }
+        }
+    }
+
+    fn finish_step(&mut self) {
+        #[allow(clippy::cast_precision_loss)]
+        let mean_square = self.step_sum / self.step_frames as f64;
+        self.step_sum = 0.0;
+        self.step_len = 0;
+
+        if self.steps.len() == BLOCK_STEPS {
+            self.steps.remove(0);
+        }
+        self.steps.push(mean_square);
+        if self.steps.len() == BLOCK_STEPS {
+            #[allow(clippy::cast_precision_loss)]
+            self.blocks
+                .push(self.steps.iter().sum::<f64>() / BLOCK_STEPS as f64);
+        }
+    }
+
+    /// Get the gated loudness of all added samples, [`None`] if they are shorter than a block or silent.
+    #[must_use]
+    pub fn finish(&self) -> Option<Loudness> {
+        let absolute: Vec<f64> = self
+            .blocks
+            .iter()
+            .copied()
+            .filter(|v| block_loudness(*v) > ABSOLUTE_GATE)
+            .collect();
+        if absolute.is_empty() {
+            return None;
+        }
+
+        let relative_gate = block_loudness(mean(&absolute)) + RELATIVE_GATE;
+        let relative: Vec<f64> = absolute
+            .into_iter()
+            .filter(|v| block_loudness(*v) > relative_gate)
+            .collect();
+
+        Some(Loudness {
+            integrated: block_loudness(mean(&relative)),
+            peak: self.peak,
+        })
+    }
+}
+
+/// Get the loudness in LUFS of the mean square of a block.
+fn block_loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(1e-20).log10()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Decode the whole track at `path` and measure its [`Loudness`], [`None`] if it is silent.
+///
+/// This decodes the audio, so it takes considerably longer than reading the tags.
+pub fn analyze_file(path: &Path) -> Result<Option<Loudness>> {
+    let mut analyzer: Option<LoudnessAnalyzer> = None;
+    audio_analysis::decode_file(path, |sample_rate, channels, samples| {
+        analyzer
+            .get_or_insert_with(|| LoudnessAnalyzer::new(sample_rate, channels))
+            .push(samples);
+
+        ControlFlow::Continue(())
+    })?;
+
+    Ok(analyzer.and_then(|v| v.finish()))
+}
+
+/// Write the `ReplayGain` track gain and peak of `loudness` to the primary tag of the file at `path`.
+pub fn write_replay_gain_tags(path: &Path, loudness: &Loudness) -> Result<()> {
+    let mut tagged_file = lofty::read_from_path(path).context("read tags")?;
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .context("create primary tag")?;
+
+    tag.insert_text(
+        ItemKey::ReplayGainTrackGain,
+        format!("{:.2} dB", loudness.replay_gain()),
+    );
+    tag.insert_text(
+        ItemKey::ReplayGainTrackPeak,
+        format!("{:.6}", loudness.peak),
+    );
+
+    tag.save_to_path(path, WriteOptions::new())
+        .context("write tags")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Loudness, LoudnessAnalyzer};
+
+    /// Get `seconds` of a interleaved stereo 997 Hz sine with the peak `amplitude`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn sine(sample_rate: u32, seconds: u32, amplitude: f32) -> Vec<f32> {
+        (0..sample_rate * seconds)
+            .flat_map(|idx| {
+                let t = f64::from(idx) / f64::from(sample_rate);
+                let v = (t * 997.0 * std::f64::consts::TAU).sin() as f32 * amplitude;
+                [v, v]
+            })
+            .collect()
+    }
+
+    fn analyze(sample_rate: u32, samples: &[f32]) -> Option<Loudness> {
+        let mut analyzer = LoudnessAnalyzer::new(sample_rate, 2);
+        // in chunks like decoded packets
+        for chunk in samples.chunks(2048) {
+            analyzer.push(chunk);
+        }
+
+        analyzer.finish()
+    }
+
+    #[test]
+    fn should_measure_sine_like_the_reference() {
+        // EBU Tech 3341 case 1: a stereo sine at -23 dBFS measures -23 LUFS
+        let amplitude = 10_f32.powf(-23.0 / 20.0);
+        for sample_rate in [44_100, 48_000] {
+            let loudness = analyze(sample_rate, &sine(sample_rate, 20, amplitude)).unwrap();
+            assert!(
+                (loudness.integrated - -23.0).abs() < 0.1,
+                "{sample_rate}: {loudness:?}"
+            );
+            assert!((loudness.replay_gain() - 5.0).abs() < 0.1, "{loudness:?}");
+            assert!((loudness.peak - amplitude).abs() < 1e-3, "{loudness:?}");
+        }
+    }
+
+    #[test]
+    fn should_gate_silence() {
+        let amplitude = 10_f32.powf(-20.0 / 20.0);
+        let mut samples = sine(48_000, 10, amplitude);
+        samples.extend(std::iter::repeat_n(0.0, 48_000 * 2 * 10));
+
+        let loudness = analyze(48_000, &samples).unwrap();
+        assert!((loudness.integrated - -20.0).abs() < 0.1, "{loudness:?}");
+
+        assert_eq!(analyze(48_000, &vec![0.0; 48_000 * 2]), None);
+        // shorter than a block
+        assert_eq!(analyze(48_000, &sine(48_000, 1, amplitude)[..1000]), None);
+    }
+}
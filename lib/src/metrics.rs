@@ -0,0 +1,246 @@
+//! Process-wide counters for the server, rendered in the Prometheus text exposition format.
+//!
+//! Recording is always active and cheap (atomics), only the exposition is optional and controlled via [`MetricsSettings`](crate::config::v2::server::MetricsSettings).
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+
+use crate::cache::{CACHES, CacheUsage};
+
+/// The global metrics instance.
+pub static METRICS: Metrics = Metrics::new();
+
+/// Content-Type for the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// All metrics that are collected.
+#[derive(Debug)]
+pub struct Metrics {
+    tracks_played: AtomicU64,
+    decode_errors: AtomicU64,
+    buffer_underruns: AtomicU64,
+    /// Requests per gRPC method name
+    grpc_requests: Mutex<BTreeMap<&'static str, u64>>,
+    scans: AtomicU64,
+    /// Sum of all scan durations, in microseconds
+    scan_duration_sum: AtomicU64,
+    /// Duration of the last finished scan, in microseconds
+    scan_duration_last: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            tracks_played: AtomicU64::new(0),
+            decode_errors: AtomicU64::new(0),
+            buffer_underruns: AtomicU64::new(0),
+            grpc_requests: Mutex::new(BTreeMap::new()),
+            scans: AtomicU64::new(0),
+            scan_duration_sum: AtomicU64::new(0),
+            scan_duration_last: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that a track has started playing.
+    pub fn inc_tracks_played(&self) {
+        self.tracks_played.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a decode / playback error reported by a backend.
+    pub fn inc_decode_errors(&self) {
+        self.decode_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record that the output had to wait on the decoder for more data.
+    pub fn inc_buffer_underruns(&self) {
+        self.buffer_underruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a request for gRPC method `method`.
+    pub fn inc_grpc_request(&self, method: &'static str) {
+        *self.grpc_requests.lock().entry(method).or_default() += 1;
+    }
+
+    /// Record a finished library scan which took `duration`.
+    pub fn observe_scan(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self.scans.fetch_add(1, Ordering::Relaxed);
+        self.scan_duration_sum.fetch_add(micros, Ordering::Relaxed);
+        self.scan_duration_last.store(micros, Ordering::Relaxed);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_metric(
+            &mut out,
+            "termusic_tracks_played_total",
+            "counter",
+            "Amount of tracks that started playing.",
+            self.tracks_played.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "termusic_decode_errors_total",
+            "counter",
+            "Amount of decode or playback errors reported by the backend.",
+            self.decode_errors.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "termusic_buffer_underruns_total",
+            "counter",
+            "Amount of times the output had to wait for the decoder.",
+            self.buffer_underruns.load(Ordering::Relaxed),
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP termusic_grpc_requests_total Amount of gRPC requests handled, by method."
+        );
+        let _ = writeln!(out, "# TYPE termusic_grpc_requests_total counter");
+        for (method, count) in self.grpc_requests.lock().iter() {
+            let _ = writeln!(
+                out,
+                "termusic_grpc_requests_total{{method=\"{method}\"}} {count}"
+            );
+        }
+
+        write_metric(
+            &mut out,
+            "termusic_library_scans_total",
+            "counter",
+            "Amount of finished library scans.",
+            self.scans.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "termusic_library_scan_duration_seconds_sum",
+            "counter",
+            "Total time spent in library scans.",
+            micros_as_secs(self.scan_duration_sum.load(Ordering::Relaxed)),
+        );
+        write_metric(
+            &mut out,
+            "termusic_library_scan_last_duration_seconds",
+            "gauge",
+            "Duration of the last finished library scan.",
+            micros_as_secs(self.scan_duration_last.load(Ordering::Relaxed)),
+        );
+
+        write_cache_usage(&mut out, &CACHES.usage());
+
+        out
+    }
+}
+
+/// Write the entries, bytes and budget of all caches, labeled by cache name.
+fn write_cache_usage(out: &mut String, usage: &[CacheUsage]) {
+    write_cache_gauge(
+        out,
+        "termusic_cache_entries",
+        "Amount of entries in a in-memory cache.",
+        usage.iter().map(|v| (v.name, v.entries)),
+    );
+    write_cache_gauge(
+        out,
+        "termusic_cache_bytes",
+        "Approximate bytes used by a in-memory cache.",
+        usage.iter().map(|v| (v.name, v.bytes)),
+    );
+    write_cache_gauge(
+        out,
+        "termusic_cache_budget_bytes",
+        "Configured byte budget of a in-memory cache.",
+        usage.iter().map(|v| (v.name, v.budget)),
+    );
+}
+
+/// Write a gauge with a value per cache name, including its `HELP` and `TYPE` lines.
+fn write_cache_gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    values: impl Iterator<Item = (&'static str, usize)>,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    for (cache, value) in values {
+        let _ = writeln!(out, "{name}{{cache=\"{cache}\"}} {value}");
+    }
+}
+
+/// Write a single metric without labels, including its `HELP` and `TYPE` lines.
+fn write_metric(out: &mut String, name: &str, ty: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {ty}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+#[allow(clippy::cast_precision_loss)] // precision loss only happens after ~285 years
+fn micros_as_secs(micros: u64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Metrics;
+
+    #[test]
+    fn should_render_empty() {
+        let metrics = Metrics::new();
+        let rendered = metrics.render();
+
+        assert!(rendered.contains("# TYPE termusic_tracks_played_total counter\n"));
+        assert!(rendered.contains("\ntermusic_tracks_played_total 0\n"));
+        assert!(rendered.contains("\ntermusic_library_scan_last_duration_seconds 0\n"));
+        assert!(!rendered.contains("termusic_grpc_requests_total{"));
+        assert!(rendered.contains("# TYPE termusic_cache_bytes gauge\n"));
+        assert!(rendered.contains("\ntermusic_cache_entries{cache=\"picture\"} "));
+        assert!(rendered.contains("\ntermusic_cache_budget_bytes{cache=\"lyric\"} "));
+    }
+
+    #[test]
+    fn should_render_recorded_values() {
+        let metrics = Metrics::new();
+        metrics.inc_tracks_played();
+        metrics.inc_tracks_played();
+        metrics.inc_decode_errors();
+        metrics.inc_buffer_underruns();
+        metrics.inc_grpc_request("toggle_pause");
+        metrics.inc_grpc_request("get_progress");
+        metrics.inc_grpc_request("get_progress");
+        metrics.observe_scan(Duration::from_millis(1500));
+        metrics.observe_scan(Duration::from_millis(500));
+
+        let rendered = metrics.render();
+
+        assert!(rendered.contains("\ntermusic_tracks_played_total 2\n"));
+        assert!(rendered.contains("\ntermusic_decode_errors_total 1\n"));
+        assert!(rendered.contains("\ntermusic_buffer_underruns_total 1\n"));
+        // methods are sorted
+        assert!(rendered.contains(
+            "termusic_grpc_requests_total{method=\"get_progress\"} 2\ntermusic_grpc_requests_total{method=\"toggle_pause\"} 1\n"
+        ));
+        assert!(rendered.contains("\ntermusic_library_scans_total 2\n"));
+        assert!(rendered.contains("\ntermusic_library_scan_duration_seconds_sum 2\n"));
+        assert!(rendered.contains("\ntermusic_library_scan_last_duration_seconds 0.5\n"));
+    }
+}
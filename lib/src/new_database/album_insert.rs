@@ -13,6 +13,8 @@ use super::{Integer, artist_insert::ArtistInsertable};
 pub struct AlbumInsertable<'a> {
     pub title: &'a str,
     pub artist_display: &'a str,
+    /// The title to sort by instead of `title`, like from a `ALBUMSORT` tag
+    pub sort_title: Option<&'a str>,
 
     // mapped metadata
     /// Either a reference to a insertable to look-up or a direct integer to use as reference into `artists`.
@@ -25,6 +27,7 @@ impl AlbumInsertable<'_> {
         let insert_album = InsertAlbum {
             title: self.title,
             artist_display: self.artist_display,
+            sort_title: self.sort_title,
         };
 
         let id = insert_album.upsert(conn).context("albums")?;
@@ -71,17 +74,18 @@ struct InsertAlbum<'a> {
     title: &'a str,
 
     artist_display: &'a str,
+    sort_title: Option<&'a str>,
 }
 
 impl InsertAlbum<'_> {
     /// Insert or update the current data with the `title` and `artist_display` as identifier.
     fn upsert(&self, conn: &Connection) -> Result<Integer> {
-        // using "title=title" as "DO NOTHING" would not be returning the id
+        // a existing sort title is kept, as not all tracks of a album may have it tagged
         let mut stmt = conn.prepare_cached(indoc! {"
-            INSERT INTO albums (title, artist_display)
-            VALUES (:title, :artist_display)
+            INSERT INTO albums (title, artist_display, sort_title)
+            VALUES (:title, :artist_display, :sort_title)
             ON CONFLICT(title, artist_display) DO UPDATE SET
-                title=title
+                sort_title=coalesce(excluded.sort_title, albums.sort_title)
             RETURNING id;
         "})?;
 
@@ -89,6 +93,7 @@ impl InsertAlbum<'_> {
             named_params! {
                 ":title": self.title,
                 ":artist_display": self.artist_display,
+                ":sort_title": self.sort_title,
             },
             |row| row.get(0),
         )?;
@@ -142,6 +147,7 @@ mod tests {
         let data = InsertAlbum {
             title: "AlbumA",
             artist_display: "ArtistA",
+            sort_title: None,
         };
 
         let db = db.conn.lock();
@@ -169,13 +175,17 @@ mod tests {
         let data = InsertAlbum {
             title: "AlbumA",
             artist_display: "ArtistA",
+            sort_title: None,
         };
 
         let db = db.conn.lock();
         let album_id = data.upsert(&db).unwrap();
         assert_eq!(album_id, 1);
 
-        let artist = ArtistInsertable { artist: "ArtistA" };
+        let artist = ArtistInsertable {
+            artist: "ArtistA",
+            sort_name: None,
+        };
 
         let artist_id = artist.try_insert_or_update(&db).unwrap();
         assert_eq!(artist_id, 1);
@@ -201,14 +211,22 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA-nickname feat. ArtistB",
+            sort_title: None,
             artists: vec![
                 Either::Left(
                     ArtistInsertable {
                         artist: "LastName, ArtistA",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistB",
+                        sort_name: None,
                     }
                     .into(),
                 ),
-                Either::Left(ArtistInsertable { artist: "ArtistB" }.into()),
             ],
         };
         let album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
@@ -228,14 +246,22 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA-nickname feat. ArtistB",
+            sort_title: None,
             artists: vec![
                 Either::Left(
                     ArtistInsertable {
                         artist: "ArtistA LastName",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistB",
+                        sort_name: None,
                     }
                     .into(),
                 ),
-                Either::Left(ArtistInsertable { artist: "ArtistB" }.into()),
             ],
         };
         let album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
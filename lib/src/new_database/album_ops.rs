@@ -28,6 +28,8 @@ pub struct AlbumRead {
 
     pub title: String,
     pub artist_display: String,
+    /// The title to sort by instead of `title`, if tagged
+    pub sort_title: Option<String>,
 
     pub artists: Vec<ArtistRead>,
 }
@@ -55,7 +57,7 @@ impl RowOrdering {
 /// If the database schema does not match what is expected.
 pub fn get_all_albums(conn: &Connection, order: RowOrdering) -> Result<Vec<AlbumRead>> {
     let stmt = formatdoc! {"
-        SELECT albums.id as album_id, albums.title, albums.artist_display
+        SELECT albums.id as album_id, albums.title, albums.artist_display, albums.sort_title
         FROM albums
         ORDER BY {};
         ",
@@ -85,7 +87,7 @@ pub fn get_all_albums_like(
     order: RowOrdering,
 ) -> Result<Vec<AlbumRead>> {
     let stmt = formatdoc! {"
-        SELECT albums.id as album_id, albums.title, albums.artist_display
+        SELECT albums.id as album_id, albums.title, albums.artist_display, albums.sort_title
         FROM albums
         WHERE albums.title LIKE :like
         ORDER BY {};
@@ -113,7 +115,7 @@ pub fn get_all_albums_like(
 // maybe this should be in "artist_ops" instead?
 pub fn get_all_artists_for_album(conn: &Connection, album_id: Integer) -> Result<Vec<ArtistRead>> {
     let mut stmt = conn.prepare(indoc! {"
-        SELECT artists.id AS artist_id, artists.artist FROM artists
+        SELECT artists.id AS artist_id, artists.artist, artists.sort_name FROM artists
         INNER JOIN albums_artists ON albums_artists.album=(:album_id)
         WHERE artists.id=albums_artists.artist;
     "})?;
@@ -157,6 +159,7 @@ fn common_row_to_album(conn: &Connection, row: &Row<'_>) -> AlbumRead {
     let id = row.get("album_id").unwrap();
     let title = row.get("title").unwrap();
     let artist_display = row.get("artist_display").unwrap();
+    let sort_title = row.get("sort_title").unwrap();
 
     let artists = match get_all_artists_for_album(conn, id) {
         Ok(v) => v,
@@ -170,6 +173,7 @@ fn common_row_to_album(conn: &Connection, row: &Row<'_>) -> AlbumRead {
         id,
         title,
         artist_display,
+        sort_title,
         artists,
     }
 }
@@ -250,9 +254,22 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA",
+            sort_title: None,
             artists: vec![
-                Either::Left(ArtistInsertable { artist: "ArtistA" }.into()),
-                Either::Left(ArtistInsertable { artist: "ArtistB" }.into()),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistA",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistB",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
             ],
         };
         let album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
@@ -276,14 +293,28 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA",
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
+            sort_title: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
         };
         let _album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
 
         let album = AlbumInsertable {
             title: "AlbumB",
             artist_display: "ArtistB",
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistB" }.into())],
+            sort_title: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistB",
+                    sort_name: None,
+                }
+                .into(),
+            )],
         };
         let _album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
 
@@ -296,18 +327,22 @@ mod tests {
                     id: 1,
                     title: "AlbumA".to_string(),
                     artist_display: "ArtistA".to_string(),
+                    sort_title: None,
                     artists: vec![ArtistRead {
                         id: 1,
-                        name: "ArtistA".to_string()
+                        name: "ArtistA".to_string(),
+                        sort_name: None,
                     }]
                 },
                 AlbumRead {
                     id: 2,
                     title: "AlbumB".to_string(),
                     artist_display: "ArtistB".to_string(),
+                    sort_title: None,
                     artists: vec![ArtistRead {
                         id: 2,
-                        name: "ArtistB".to_string()
+                        name: "ArtistB".to_string(),
+                        sort_name: None,
                     }]
                 },
             ]
@@ -321,14 +356,28 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA",
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
+            sort_title: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
         };
         let _album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
 
         let album = AlbumInsertable {
             title: "AlbumB",
             artist_display: "ArtistB",
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistB" }.into())],
+            sort_title: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistB",
+                    sort_name: None,
+                }
+                .into(),
+            )],
         };
         let _album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
 
@@ -341,9 +390,11 @@ mod tests {
                 id: 1,
                 title: "AlbumA".to_string(),
                 artist_display: "ArtistA".to_string(),
+                sort_title: None,
                 artists: vec![ArtistRead {
                     id: 1,
-                    name: "ArtistA".to_string()
+                    name: "ArtistA".to_string(),
+                    sort_name: None,
                 }]
             },]
         );
@@ -356,7 +407,14 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA",
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
+            sort_title: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
         };
         let _album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
 
@@ -376,9 +434,22 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumA",
             artist_display: "ArtistA feat. ArtistB",
+            sort_title: None,
             artists: vec![
-                Either::Left(ArtistInsertable { artist: "ArtistA" }.into()),
-                Either::Left(ArtistInsertable { artist: "ArtistB" }.into()),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistA",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistB",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
             ],
         };
         let album_id_a = album.try_insert_or_update(&db.get_connection()).unwrap();
@@ -386,9 +457,22 @@ mod tests {
         let album = AlbumInsertable {
             title: "AlbumB",
             artist_display: "ArtistA feat. ArtistB",
+            sort_title: None,
             artists: vec![
-                Either::Left(ArtistInsertable { artist: "ArtistA" }.into()),
-                Either::Left(ArtistInsertable { artist: "ArtistB" }.into()),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistA",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistB",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
             ],
         };
         let _album_id = album.try_insert_or_update(&db.get_connection()).unwrap();
@@ -10,6 +10,8 @@ use super::Integer;
 pub struct ArtistInsertable<'a> {
     /// The name of the artist
     pub artist: &'a str,
+    /// The name to sort by instead of `artist`, like `Beatles, The` from a `ARTISTSORT` tag
+    pub sort_name: Option<&'a str>,
 }
 
 impl ArtistInsertable<'_> {
@@ -17,6 +19,7 @@ impl ArtistInsertable<'_> {
     pub fn try_insert_or_update(&self, conn: &Connection) -> Result<Integer> {
         let insert_artist = InsertArtist {
             artist: self.artist,
+            sort_name: self.sort_name,
         };
 
         let id = insert_artist.upsert(conn).context("artists")?;
@@ -43,17 +46,18 @@ impl<'a> From<&'a ArtistInsertable<'a>> for Cow<'a, ArtistInsertable<'a>> {
 struct InsertArtist<'a> {
     /// Artist name and identifier
     artist: &'a str,
+    sort_name: Option<&'a str>,
 }
 
 impl InsertArtist<'_> {
     /// Insert or update the current data with the file as identifier.
     fn upsert(&self, conn: &Connection) -> Result<Integer> {
-        // using "artist=artist" as "DO NOTHING" would not be returning the id
+        // a existing sort name is kept, as not all tracks of a artist may have it tagged
         let mut stmt = conn.prepare_cached(indoc! {"
-            INSERT INTO artists (artist, added_at)
-            VALUES (:artist, :added_at)
-            ON CONFLICT(artist) DO UPDATE SET 
-                artist=artist
+            INSERT INTO artists (artist, added_at, sort_name)
+            VALUES (:artist, :added_at, :sort_name)
+            ON CONFLICT(artist) DO UPDATE SET
+                sort_name=coalesce(excluded.sort_name, artists.sort_name)
             RETURNING id;
         "})?;
 
@@ -63,6 +67,7 @@ impl InsertArtist<'_> {
             named_params! {
                 ":artist": self.artist,
                 ":added_at": now,
+                ":sort_name": self.sort_name,
             },
             |row| row.get(0),
         )?;
@@ -83,7 +88,10 @@ mod tests {
     fn should_insert_artist_simple() {
         let db = gen_database();
 
-        let data = InsertArtist { artist: "ArtistA" };
+        let data = InsertArtist {
+            artist: "ArtistA",
+            sort_name: None,
+        };
 
         let db = db.conn.lock();
 
@@ -17,6 +17,8 @@ pub struct ArtistRead {
     pub id: Integer,
 
     pub name: String,
+    /// The name to sort by instead of `name`, if tagged
+    pub sort_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,7 +48,7 @@ impl RowOrdering {
 /// If the database schema does not match what is expected.
 pub fn get_all_artists(conn: &Connection, order: RowOrdering) -> Result<Vec<ArtistRead>> {
     let stmt = formatdoc! {"
-        SELECT artists.id AS artist_id, artists.artist
+        SELECT artists.id AS artist_id, artists.artist, artists.sort_name
         FROM artists
         ORDER BY {};
         ",
@@ -76,7 +78,7 @@ pub fn get_all_artists_like(
     order: RowOrdering,
 ) -> Result<Vec<ArtistRead>> {
     let stmt = formatdoc! {"
-        SELECT artists.id AS artist_id, artists.artist
+        SELECT artists.id AS artist_id, artists.artist, artists.sort_name
         FROM artists
         WHERE artists.artist LIKE :like
         ORDER BY {};
@@ -107,7 +109,7 @@ pub fn get_artist(conn: &Connection, artist: &str) -> Result<ArtistRead> {
     }
 
     let mut stmt = conn.prepare(indoc! {"
-        SELECT artists.id AS artist_id, artists.artist
+        SELECT artists.id AS artist_id, artists.artist, artists.sort_name
         FROM artists
         WHERE artists.artist=:artist_name;
     "})?;
@@ -148,10 +150,12 @@ pub fn artist_exists(conn: &Connection, artist: &str) -> Result<bool> {
 pub(super) fn common_row_to_artistread(row: &Row<'_>) -> ArtistRead {
     let id = row.get("artist_id").unwrap();
     let artist_title = row.get("artist").unwrap();
+    let sort_name = row.get("sort_name").unwrap();
 
     ArtistRead {
         id,
         name: artist_title,
+        sort_name,
     }
 }
 
@@ -205,10 +209,16 @@ mod tests {
     fn all_artists() {
         let db = gen_database();
 
-        let artist = ArtistInsertable { artist: "ArtistA" };
+        let artist = ArtistInsertable {
+            artist: "ArtistA",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
-        let artist = ArtistInsertable { artist: "ArtistB" };
+        let artist = ArtistInsertable {
+            artist: "ArtistB",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
         let artists: Vec<String> = get_all_artists(&db.get_connection(), RowOrdering::IdAsc)
@@ -224,10 +234,16 @@ mod tests {
     fn all_artists_like() {
         let db = gen_database();
 
-        let artist = ArtistInsertable { artist: "ArtistA" };
+        let artist = ArtistInsertable {
+            artist: "ArtistA",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
-        let artist = ArtistInsertable { artist: "ArtistB" };
+        let artist = ArtistInsertable {
+            artist: "ArtistB",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
         let artists: Vec<String> =
@@ -244,10 +260,16 @@ mod tests {
     fn single_artist() {
         let db = gen_database();
 
-        let artist = ArtistInsertable { artist: "ArtistA" };
+        let artist = ArtistInsertable {
+            artist: "ArtistA",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
-        let artist = ArtistInsertable { artist: "ArtistB" };
+        let artist = ArtistInsertable {
+            artist: "ArtistB",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
         let artist_a = get_artist(&db.get_connection(), "ArtistA").unwrap();
@@ -265,7 +287,10 @@ mod tests {
     fn exists() {
         let db = gen_database();
 
-        let artist = ArtistInsertable { artist: "ArtistA" };
+        let artist = ArtistInsertable {
+            artist: "ArtistA",
+            sort_name: None,
+        };
         let _artist_id = artist.try_insert_or_update(&db.get_connection()).unwrap();
 
         let res = artist_exists(&db.get_connection(), "ArtistA").unwrap();
@@ -0,0 +1,170 @@
+//! Named positions in files to come back to, like chapters of a audiobook or a spot in a long mix.
+//!
+//! Besides the bookmarks added by the user, every location can have one automatic "Last position" bookmark, see
+//! [`set_auto_bookmark`].
+
+use std::time::Duration;
+
+use anyhow::Result;
+use indoc::indoc;
+use rusqlite::{Connection, Row, named_params};
+
+use super::Integer;
+
+/// The name of the automatic bookmark, see [`set_auto_bookmark`].
+pub const AUTO_BOOKMARK_NAME: &str = "Last position";
+
+/// A named position in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub id: Integer,
+    /// The location of the file, like a path or a podcast episode url, as stored in the playlist
+    pub location: String,
+    pub name: String,
+    pub position: Duration,
+    /// Whether this is the automatic "Last position" bookmark
+    pub auto: bool,
+}
+
+impl Bookmark {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        let position: i64 = row.get("position")?;
+
+        Ok(Self {
+            id: row.get("id")?,
+            location: row.get("location")?,
+            name: row.get("name")?,
+            position: Duration::from_millis(u64::try_from(position).unwrap_or_default()),
+            auto: row.get("auto")?,
+        })
+    }
+}
+
+/// Get the position as stored in the database, in milliseconds.
+fn position_millis(position: Duration) -> i64 {
+    i64::try_from(position.as_millis()).unwrap_or(i64::MAX)
+}
+
+/// Get all bookmarks of `location`, ordered by their position.
+pub fn get_bookmarks(conn: &Connection, location: &str) -> Result<Vec<Bookmark>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT id, location, name, position, auto FROM bookmarks
+        WHERE location=:location
+        ORDER BY position ASC, id ASC;
+    "})?;
+
+    let bookmarks = stmt
+        .query_map(named_params! {":location": location}, Bookmark::from_row)?
+        .collect::<Result<_, _>>()?;
+
+    Ok(bookmarks)
+}
+
+/// Add a bookmark named `name` at `position` of `location`, returns its id.
+pub fn add_bookmark(
+    conn: &Connection,
+    location: &str,
+    name: &str,
+    position: Duration,
+) -> Result<Integer> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        INSERT INTO bookmarks (location, name, position, auto, created_at)
+        VALUES (:location, :name, :position, 0, :now);
+    "})?;
+    stmt.execute(named_params! {
+        ":location": location,
+        ":name": name,
+        ":position": position_millis(position),
+        ":now": chrono::Utc::now().to_rfc3339(),
+    })?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Set the automatic "Last position" bookmark of `location` to `position`, replacing the previous one.
+pub fn set_auto_bookmark(conn: &Connection, location: &str, position: Duration) -> Result<()> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        INSERT INTO bookmarks (location, name, position, auto, created_at)
+        VALUES (:location, :name, :position, 1, :now)
+        ON CONFLICT(location) WHERE auto = 1 DO UPDATE SET position=excluded.position, created_at=excluded.created_at;
+    "})?;
+    stmt.execute(named_params! {
+        ":location": location,
+        ":name": AUTO_BOOKMARK_NAME,
+        ":position": position_millis(position),
+        ":now": chrono::Utc::now().to_rfc3339(),
+    })?;
+
+    Ok(())
+}
+
+/// Remove the bookmark with `id`, returns whether it existed.
+pub fn remove_bookmark(conn: &Connection, id: Integer) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM bookmarks WHERE id=:id;",
+        named_params! {":id": id},
+    )?;
+
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        AUTO_BOOKMARK_NAME, add_bookmark, get_bookmarks, remove_bookmark, set_auto_bookmark,
+    };
+    use crate::new_database::test_utils::gen_database;
+
+    #[test]
+    fn should_add_list_and_remove() {
+        let db = gen_database();
+        let conn = db.get_connection();
+        let location = "/somewhere/audiobook.m4b";
+
+        assert!(get_bookmarks(&conn, location).unwrap().is_empty());
+
+        let chapter_2 =
+            add_bookmark(&conn, location, "Chapter 2", Duration::from_secs(1800)).unwrap();
+        add_bookmark(&conn, location, "Chapter 1", Duration::from_secs(60)).unwrap();
+        add_bookmark(&conn, "/somewhere/else.mp3", "Other", Duration::ZERO).unwrap();
+
+        let names: Vec<(String, Duration)> = get_bookmarks(&conn, location)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.name, v.position))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Chapter 1".to_string(), Duration::from_secs(60)),
+                ("Chapter 2".to_string(), Duration::from_secs(1800)),
+            ]
+        );
+
+        assert!(remove_bookmark(&conn, chapter_2).unwrap());
+        assert!(!remove_bookmark(&conn, chapter_2).unwrap());
+        assert_eq!(get_bookmarks(&conn, location).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_keep_one_auto_bookmark() {
+        let db = gen_database();
+        let conn = db.get_connection();
+        let location = "/somewhere/mix.opus";
+
+        add_bookmark(&conn, location, "Drop", Duration::from_secs(600)).unwrap();
+        set_auto_bookmark(&conn, location, Duration::from_secs(120)).unwrap();
+        set_auto_bookmark(&conn, location, Duration::from_millis(2_400_500)).unwrap();
+
+        let bookmarks = get_bookmarks(&conn, location).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert!(!bookmarks[0].auto);
+        assert!(bookmarks[1].auto);
+        assert_eq!(bookmarks[1].name, AUTO_BOOKMARK_NAME);
+        assert_eq!(bookmarks[1].position, Duration::from_millis(2_400_500));
+    }
+}
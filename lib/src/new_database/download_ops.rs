@@ -0,0 +1,308 @@
+//! Deduplicate downloaded files, like podcast episodes and yt-dlp downloads, by their content hash.
+//!
+//! A download identical to a file already in the library (or downloaded before) is replaced with a hard link to that
+//! file, so that both paths stay valid but the content is only stored once. All sources of a content are recorded in
+//! `download_sources`.
+
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Read},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result};
+use indoc::indoc;
+use ring::digest::{Context as DigestContext, SHA256};
+use rusqlite::{Connection, OptionalExtension, named_params};
+
+/// The size and modification time of a file, to detect if a stored hash is still valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    size: u64,
+    modified: i64,
+}
+
+impl FileStamp {
+    /// Get the stamp of the file at `path`, [`None`] if it does not exist.
+    fn of(path: &Path) -> Result<Option<Self>> {
+        let metadata = match fs::metadata(path) {
+            Ok(v) => v,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| path.display().to_string()),
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|v| v.duration_since(UNIX_EPOCH).ok())
+            .and_then(|v| i64::try_from(v.as_secs()).ok())
+            .unwrap_or_default();
+
+        Ok(Some(Self {
+            size: metadata.len(),
+            modified,
+        }))
+    }
+}
+
+/// Get the hex-encoded SHA-256 of the content of the file at `path`.
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| path.display().to_string())?;
+    let mut context = DigestContext::new(&SHA256);
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buf[..read]);
+    }
+
+    Ok(hex::encode(context.finish()))
+}
+
+/// Get the hash of the file at `path` with the stamp `stamp`, from `file_hashes` if still valid, otherwise hashing and
+/// storing it.
+fn hash_of(conn: &Connection, path: &Path, stamp: FileStamp) -> Result<String> {
+    let path_str = path.to_string_lossy();
+    let size = i64::try_from(stamp.size).unwrap_or(i64::MAX);
+
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT hash FROM file_hashes
+        WHERE path=:path AND size=:size AND modified=:modified;
+    "})?;
+    let stored: Option<String> = stmt
+        .query_row(
+            named_params! {":path": path_str, ":size": size, ":modified": stamp.modified},
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(hash) = stored {
+        return Ok(hash);
+    }
+
+    let hash = hash_file(path)?;
+    let mut stmt = conn.prepare_cached(indoc! {"
+        INSERT OR REPLACE INTO file_hashes (path, size, modified, hash)
+        VALUES (:path, :size, :modified, :hash);
+    "})?;
+    stmt.execute(named_params! {":path": path_str, ":size": size, ":modified": stamp.modified, ":hash": hash})?;
+
+    Ok(hash)
+}
+
+/// Get all files that could be identical to a download: the library tracks and the previous downloads.
+fn candidate_paths(conn: &Connection) -> Result<Vec<PathBuf>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT file_dir, file_stem, file_ext FROM tracks
+    "})?;
+    let mut paths: Vec<PathBuf> = stmt
+        .query_map([], |row| {
+            let file_dir: String = row.get(0)?;
+            let file_stem: String = row.get(1)?;
+            let file_ext: String = row.get(2)?;
+
+            Ok(Path::new(&file_dir).join(format!("{file_stem}.{file_ext}")))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare_cached("SELECT path FROM download_sources")?;
+    for path in stmt.query_map([], |row| row.get::<_, String>(0))? {
+        paths.push(PathBuf::from(path?));
+    }
+
+    Ok(paths)
+}
+
+/// Find a file identical to the file at `path` with the hash `hash`.
+///
+/// Only candidates with the same size are hashed, so that not the whole library is read.
+fn find_identical(
+    conn: &Connection,
+    path: &Path,
+    stamp: FileStamp,
+    hash: &str,
+) -> Result<Option<PathBuf>> {
+    for candidate in candidate_paths(conn)? {
+        if candidate == path {
+            continue;
+        }
+        let Some(candidate_stamp) = FileStamp::of(&candidate)? else {
+            continue;
+        };
+        if candidate_stamp.size != stamp.size {
+            continue;
+        }
+
+        match hash_of(conn, &candidate, candidate_stamp) {
+            Ok(v) if v == hash => return Ok(Some(candidate)),
+            Ok(_) => (),
+            Err(err) => warn!("Hashing {} failed: {err:#}", candidate.display()),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Replace `path` with a hard link to `existing`, keeping `path` if linking is not possible, like across filesystems.
+fn replace_with_link(existing: &Path, path: &Path) -> Result<()> {
+    let part = path.with_extension("dedup.part");
+    fs::hard_link(existing, &part)
+        .with_context(|| format!("hard link {} to {}", existing.display(), part.display()))?;
+    // rename over the download, so that `path` exists at all times
+    if let Err(err) = fs::rename(&part, path) {
+        let _ = fs::remove_file(&part);
+        return Err(err).with_context(|| path.display().to_string());
+    }
+
+    Ok(())
+}
+
+/// Record the download of `source` to `path` and deduplicate it against the library and previous downloads.
+///
+/// Returns the file `path` is now a hard link of, or [`None`] if there was no identical file or linking failed, in which
+/// case the download stays a separate copy.
+pub fn dedup_download(conn: &Connection, source: &str, path: &Path) -> Result<Option<PathBuf>> {
+    let stamp =
+        FileStamp::of(path)?.with_context(|| format!("{} does not exist", path.display()))?;
+    let hash = hash_of(conn, path, stamp)?;
+
+    let linked = match find_identical(conn, path, stamp, &hash)? {
+        Some(existing) => match replace_with_link(&existing, path) {
+            Ok(()) => {
+                info!(
+                    "Download {} is identical to {}, linked it",
+                    path.display(),
+                    existing.display()
+                );
+                Some(existing)
+            }
+            Err(err) => {
+                warn!("Deduplicating {} failed: {err:#}", path.display());
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut stmt = conn.prepare_cached(indoc! {"
+        INSERT OR REPLACE INTO download_sources (source, path, hash, downloaded_at)
+        VALUES (:source, :path, :hash, :now);
+    "})?;
+    stmt.execute(named_params! {
+        ":source": source,
+        ":path": path.to_string_lossy(),
+        ":hash": hash,
+        ":now": chrono::Utc::now().to_rfc3339(),
+    })?;
+
+    Ok(linked)
+}
+
+/// Get all sources the content of the file at `path` was downloaded from.
+pub fn get_download_sources(conn: &Connection, path: &Path) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT sources.source FROM download_sources AS sources
+        INNER JOIN file_hashes ON file_hashes.hash = sources.hash
+        WHERE file_hashes.path=:path
+        ORDER BY sources.downloaded_at;
+    "})?;
+    let sources = stmt
+        .query_map(named_params! {":path": path.to_string_lossy()}, |row| {
+            row.get(0)
+        })?
+        .collect::<Result<_, _>>()?;
+
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{dedup_download, get_download_sources, hash_file};
+    use crate::new_database::test_utils::gen_database;
+
+    #[test]
+    fn should_hash_sha256() {
+        let dir = std::env::temp_dir().join(format!("termusic-hash-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            hash_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_link_identical_downloads() {
+        let dir = std::env::temp_dir().join(format!("termusic-dedup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let db = gen_database();
+        let conn = db.get_connection();
+
+        // a track in the library, and a different one of the same size
+        let library = dir.join("library.mp3");
+        fs::write(&library, "content").unwrap();
+        let other = dir.join("other.mp3");
+        fs::write(&other, "CONTENT").unwrap();
+        for stem in ["library", "other"] {
+            conn.execute(
+                "INSERT INTO tracks (file_dir, file_stem, file_ext) VALUES (?1, ?2, 'mp3')",
+                (dir.to_string_lossy(), stem),
+            )
+            .unwrap();
+        }
+
+        let download = dir.join("episode.mp3");
+        fs::write(&download, "content").unwrap();
+        assert_eq!(
+            dedup_download(&conn, "https://example.com/episode.mp3", &download).unwrap(),
+            Some(library.clone())
+        );
+        // still readable at its own path
+        assert_eq!(fs::read_to_string(&download).unwrap(), "content");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(
+                fs::metadata(&download).unwrap().ino(),
+                fs::metadata(&library).unwrap().ino()
+            );
+        }
+
+        // the same content from another source maps to both
+        let video = dir.join("video.mp3");
+        fs::write(&video, "content").unwrap();
+        assert!(
+            dedup_download(&conn, "https://example.com/watch?v=1", &video)
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            get_download_sources(&conn, &library).unwrap(),
+            [
+                "https://example.com/episode.mp3",
+                "https://example.com/watch?v=1"
+            ]
+        );
+
+        // unique content is kept as-is
+        let unique = dir.join("unique.mp3");
+        fs::write(&unique, "unique!").unwrap();
+        assert_eq!(
+            dedup_download(&conn, "https://example.com/unique.mp3", &unique).unwrap(),
+            None
+        );
+
+        drop(conn);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use indoc::indoc;
+use rusqlite::{Connection, named_params};
+
+use crate::new_database::{
+    album_ops::delete_all_unreferenced_albums, artist_ops::delete_all_unreferenced_artists,
+    track_ops::delete_all_unreferenced_genres,
+};
+
+/// The result of a sqlite `integrity_check` or `quick_check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityStatus {
+    Ok,
+    /// The problems reported by sqlite, limited to the first 100
+    Problems(Vec<String>),
+}
+
+impl IntegrityStatus {
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        *self == Self::Ok
+    }
+}
+
+/// Run `PRAGMA quick_check`, which is a faster, less thorough version of [`integrity_check`].
+pub fn quick_check(conn: &Connection) -> Result<IntegrityStatus> {
+    run_check(conn, "quick_check")
+}
+
+/// Run `PRAGMA integrity_check`.
+pub fn integrity_check(conn: &Connection) -> Result<IntegrityStatus> {
+    run_check(conn, "integrity_check")
+}
+
+/// Run the given check pragma and collect its rows.
+fn run_check(conn: &Connection, pragma: &str) -> Result<IntegrityStatus> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {pragma}(100);"))?;
+
+    let rows: Vec<String> = stmt
+        .query_map([], |r| r.get(0))?
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("PRAGMA {pragma}"))?;
+
+    if rows.len() == 1 && rows[0] == "ok" {
+        return Ok(IntegrityStatus::Ok);
+    }
+
+    Ok(IntegrityStatus::Problems(rows))
+}
+
+/// Counts of what got changed in a [`repair`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RepairReport {
+    /// `tracks_metadata` rows without a track
+    pub metadata: usize,
+    /// `tracks_artists` rows without a track or artist
+    pub tracks_artists: usize,
+    /// `albums_artists` rows without a album or artist
+    pub albums_artists: usize,
+    /// `tracks_genres` rows without a track or genre
+    pub tracks_genres: usize,
+    /// `tracks` whose album did not exist anymore and got unset
+    pub tracks_album_unset: usize,
+    /// albums without any track
+    pub albums: usize,
+    /// artists without any track or album
+    pub artists: usize,
+    /// genres without any track
+    pub genres: usize,
+}
+
+impl RepairReport {
+    /// The total amount of deleted rows, without the [`tracks_album_unset`](Self::tracks_album_unset) that were only
+    /// updated.
+    #[must_use]
+    pub fn pruned(&self) -> usize {
+        self.metadata
+            + self.tracks_artists
+            + self.albums_artists
+            + self.tracks_genres
+            + self.albums
+            + self.artists
+            + self.genres
+    }
+}
+
+/// Rebuild all indexes and prune rows that reference non-existing rows.
+///
+/// Foreign keys may not have been enforced when the rows got changed (like older sqlite builds or external tools),
+/// so `ON DELETE` actions may not have been applied, this does what they would have done.
+///
+/// All changes happen in one transaction.
+pub fn repair(conn: &Connection) -> Result<RepairReport> {
+    let transaction = conn.unchecked_transaction()?;
+
+    transaction.execute_batch("REINDEX;").context("REINDEX")?;
+
+    let mut report = RepairReport {
+        metadata: transaction.execute(
+            indoc! {"
+                DELETE FROM tracks_metadata
+                WHERE track NOT IN (SELECT id FROM tracks);
+            "},
+            named_params! {},
+        )?,
+        tracks_artists: transaction.execute(
+            indoc! {"
+                DELETE FROM tracks_artists
+                WHERE track NOT IN (SELECT id FROM tracks)
+                OR artist NOT IN (SELECT id FROM artists);
+            "},
+            named_params! {},
+        )?,
+        albums_artists: transaction.execute(
+            indoc! {"
+                DELETE FROM albums_artists
+                WHERE album NOT IN (SELECT id FROM albums)
+                OR artist NOT IN (SELECT id FROM artists);
+            "},
+            named_params! {},
+        )?,
+        tracks_genres: transaction.execute(
+            indoc! {"
+                DELETE FROM tracks_genres
+                WHERE track NOT IN (SELECT id FROM tracks)
+                OR genre NOT IN (SELECT id FROM genres);
+            "},
+            named_params! {},
+        )?,
+        tracks_album_unset: transaction.execute(
+            indoc! {"
+                UPDATE tracks SET album = NULL
+                WHERE album IS NOT NULL
+                AND album NOT IN (SELECT id FROM albums);
+            "},
+            named_params! {},
+        )?,
+        ..Default::default()
+    };
+
+    // note that albums have to be deleted first, see "Database::process_cleanup"
+    report.albums = delete_all_unreferenced_albums(&transaction)?;
+    report.artists = delete_all_unreferenced_artists(&transaction)?;
+    report.genres = delete_all_unreferenced_genres(&transaction)?;
+
+    transaction.commit()?;
+
+    Ok(report)
+}
+
+/// Run `VACUUM` to rebuild the database file and reclaim free space.
+pub fn vacuum(conn: &Connection) -> Result<()> {
+    conn.execute_batch("VACUUM;").context("VACUUM")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::named_params;
+
+    use super::{IntegrityStatus, RepairReport, integrity_check, quick_check, repair, vacuum};
+    use crate::new_database::{
+        album_ops::count_all_albums, artist_ops::count_all_artists, test_utils::gen_database,
+    };
+
+    #[test]
+    fn should_pass_checks_on_fresh() {
+        let db = gen_database();
+        let conn = db.get_connection();
+
+        assert_eq!(quick_check(&conn).unwrap(), IntegrityStatus::Ok);
+        assert_eq!(integrity_check(&conn).unwrap(), IntegrityStatus::Ok);
+    }
+
+    #[test]
+    fn should_prune_orphans() {
+        let db = gen_database();
+        let conn = db.get_connection();
+
+        // simulate a database modified without foreign keys being enforced
+        conn.execute_batch(
+            "
+            PRAGMA foreign_keys = OFF;
+            INSERT INTO artists(id, artist) VALUES (1, 'ArtistA'), (2, 'ArtistB'), (3, 'ArtistC');
+            INSERT INTO albums(id, title, artist_display) VALUES (1, 'AlbumA', 'ArtistA'), (2, 'AlbumB', 'ArtistB');
+            INSERT INTO albums_artists(album, artist) VALUES (1, 1), (2, 2), (3, 1);
+            INSERT INTO tracks(id, file_dir, file_stem, file_ext, album) VALUES
+                (1, '/dir', 'a', 'mp3', 1),
+                (2, '/dir', 'b', 'mp3', 5);
+            INSERT INTO tracks_metadata(track, title) VALUES (1, 'a'), (2, 'b'), (3, 'c');
+            INSERT INTO tracks_artists(track, artist) VALUES (1, 1), (2, 9), (3, 3);
+            INSERT INTO genres(id, genre) VALUES (1, 'Rock'), (2, 'Pop');
+            INSERT INTO tracks_genres(track, genre) VALUES (1, 1), (3, 2);
+            PRAGMA foreign_keys = ON;
+            ",
+        )
+        .unwrap();
+
+        let report = repair(&conn).unwrap();
+
+        assert_eq!(
+            report,
+            RepairReport {
+                // track 3
+                metadata: 1,
+                // track 3 and artist 9
+                tracks_artists: 2,
+                // album 3; album 2's mapping is removed via cascade
+                albums_artists: 1,
+                // track 3
+                tracks_genres: 1,
+                // track 2 had album 5
+                tracks_album_unset: 1,
+                // album 2 has no tracks
+                albums: 1,
+                // artists 2 and 3 are unreferenced now
+                artists: 2,
+                // genre 2 is unreferenced now
+                genres: 1,
+            }
+        );
+        assert_eq!(report.pruned(), 9);
+
+        assert_eq!(count_all_albums(&conn).unwrap(), 1);
+        assert_eq!(count_all_artists(&conn).unwrap(), 1);
+        let album: Option<i64> = conn
+            .query_row(
+                "SELECT album FROM tracks WHERE id = 2;",
+                named_params! {},
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(album, None);
+
+        // a second run should not find anything
+        assert_eq!(repair(&conn).unwrap(), RepairReport::default());
+
+        vacuum(&conn).unwrap();
+        assert_eq!(integrity_check(&conn).unwrap(), IntegrityStatus::Ok);
+    }
+}
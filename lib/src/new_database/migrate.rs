@@ -1,8 +1,34 @@
 use anyhow::{Context, Result, bail};
 use rusqlite::{Connection, named_params};
 
+/// All migrations, in order. The migration at index `i` brings the database to version `i + 1`.
+const MIGRATIONS: &[&str] = &[
+    include_str!("./migrations/001.sql"),
+    include_str!("./migrations/002.sql"),
+    include_str!("./migrations/003.sql"),
+    include_str!("./migrations/004.sql"),
+    include_str!("./migrations/005.sql"),
+    include_str!("./migrations/006.sql"),
+    include_str!("./migrations/007.sql"),
+    include_str!("./migrations/008.sql"),
+    include_str!("./migrations/009.sql"),
+    include_str!("./migrations/010.sql"),
+    include_str!("./migrations/011.sql"),
+    include_str!("./migrations/012.sql"),
+    include_str!("./migrations/013.sql"),
+    include_str!("./migrations/014.sql"),
+    include_str!("./migrations/015.sql"),
+    include_str!("./migrations/016.sql"),
+    include_str!("./migrations/017.sql"),
+    include_str!("./migrations/018.sql"),
+    include_str!("./migrations/019.sql"),
+    include_str!("./migrations/020.sql"),
+    include_str!("./migrations/021.sql"),
+];
+
 /// The Current Database schema version this application is meant to run against
-pub(super) const DB_VERSION: u32 = 1;
+#[allow(clippy::cast_possible_truncation)] // there will never be more than u32::MAX migrations
+pub(super) const DB_VERSION: u32 = MIGRATIONS.len() as u32;
 
 /// Helper function to get the `user_version` with a single function call.
 #[inline]
@@ -43,16 +69,31 @@ pub(super) fn migrate(conn: &Connection) -> Result<()> {
 }
 
 /// Apply migrations to be at [`DB_VERSION`].
-#[allow(unused_assignments)] // for future possible migrations
-fn apply_migrations(conn: &Connection, mut user_version: u32) -> Result<()> {
-    if user_version == 0 {
-        // Version 2 is the base version, so there are basically no migrations, only creations
-        conn.execute_batch(include_str!("./migrations/001.sql"))
-            .context("Database version 1 could not be created")?;
-        user_version = set_user_version(conn, 1)?;
-
-        set_db_created_at(conn)?;
-        set_db_created_with(conn)?;
+///
+/// Each migration is applied in its own transaction, together with the version bump,
+/// so that a failed migration does not leave the database half-migrated.
+fn apply_migrations(conn: &Connection, user_version: u32) -> Result<()> {
+    for (version, migration) in (1..).zip(MIGRATIONS).skip(user_version as usize) {
+        let transaction = conn
+            .unchecked_transaction()
+            .context("begin migration transaction")?;
+
+        transaction
+            .execute_batch(migration)
+            .with_context(|| format!("Database version {version} could not be applied"))?;
+        set_user_version(&transaction, version)?;
+
+        // Version 1 is the base version, so there are basically no migrations, only creations
+        if version == 1 {
+            set_db_created_at(&transaction)?;
+            set_db_created_with(&transaction)?;
+        }
+
+        transaction
+            .commit()
+            .with_context(|| format!("commit database version {version}"))?;
+
+        info!("Migrated database to version {version}");
     }
 
     set_last_updated_at(conn)?;
@@ -106,7 +147,9 @@ fn set_db_created_with(conn: &Connection) -> Result<()> {
 mod tests {
     use pretty_assertions::assert_eq;
 
-    use crate::new_database::migrate::{DB_VERSION, get_user_version, migrate};
+    use crate::new_database::migrate::{
+        DB_VERSION, MIGRATIONS, get_user_version, migrate, set_user_version,
+    };
 
     use super::super::test_utils::gen_database_raw;
 
@@ -140,6 +183,13 @@ mod tests {
                 "tracks_artists",
                 "albums",
                 "albums_artists",
+                "genres",
+                "tracks_genres",
+                "file_hashes",
+                "download_sources",
+                "bookmarks",
+                "smart_playlists",
+                "plays",
             ];
 
             #[allow(clippy::stable_sort_primitive)]
@@ -149,4 +199,61 @@ mod tests {
 
         assert_eq!(&all_tracks, &expected);
     }
+
+    #[test]
+    fn should_migrate_from_v1() {
+        let conn = gen_database_raw();
+
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        set_user_version(&conn, 1).unwrap();
+
+        migrate(&conn).unwrap();
+        assert_eq!(DB_VERSION, get_user_version(&conn).unwrap());
+
+        let index_count: u32 = conn
+            .query_row(
+                "SELECT COUNT(name) FROM sqlite_schema WHERE type = 'index' AND name IN ('tracks_album', 'tracks_artists_artist', 'albums_artists_artist');",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(index_count, 3);
+    }
+
+    #[test]
+    fn should_map_existing_genres() {
+        let conn = gen_database_raw();
+
+        for migration in &MIGRATIONS[..4] {
+            conn.execute_batch(migration).unwrap();
+        }
+        set_user_version(&conn, 4).unwrap();
+        conn.execute_batch(
+            "
+            INSERT INTO tracks(id, file_dir, file_stem, file_ext) VALUES
+                (1, '/dir', 'a', 'mp3'), (2, '/dir', 'b', 'mp3'), (3, '/dir', 'c', 'mp3');
+            INSERT INTO tracks_metadata(track, genre) VALUES (1, 'Rock'), (2, 'Rock'), (3, NULL);
+            ",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let counts: (u32, u32) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM genres), (SELECT COUNT(*) FROM tracks_genres);",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(counts, (1, 2));
+    }
+
+    #[test]
+    fn should_error_on_newer_version() {
+        let conn = gen_database_raw();
+        set_user_version(&conn, DB_VERSION + 1).unwrap();
+
+        assert!(migrate(&conn).is_err());
+    }
 }
@@ -0,0 +1,6 @@
+--- SECTION: lookup indexes
+
+-- indexes on the referencing columns, so that lookups by album / artist and the orphan cleanup do not require full table scans
+CREATE INDEX IF NOT EXISTS tracks_album ON tracks(album);
+CREATE INDEX IF NOT EXISTS tracks_artists_artist ON tracks_artists(artist);
+CREATE INDEX IF NOT EXISTS albums_artists_artist ON albums_artists(artist);
@@ -0,0 +1,6 @@
+--- SECTION: play history
+
+-- how often a track has been started, for sampling unplayed tracks
+ALTER TABLE tracks ADD COLUMN play_count INTEGER NOT NULL DEFAULT 0;
+-- the date the track has last been started, NULL if never
+ALTER TABLE tracks ADD COLUMN last_played_at DATE;
@@ -0,0 +1,8 @@
+--- SECTION: classical metadata
+
+-- the composer, can be null if not present or cannot be parsed
+ALTER TABLE tracks_metadata ADD COLUMN composer TEXT;
+-- the classical work the track is a movement of, can be null if not present
+ALTER TABLE tracks_metadata ADD COLUMN work TEXT;
+-- the movement number in the work, can be null if not present or cannot be parsed
+ALTER TABLE tracks_metadata ADD COLUMN movement_number INTEGER;
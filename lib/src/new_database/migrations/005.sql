@@ -0,0 +1,28 @@
+--- SECTION: genres
+
+-- the table for all genres
+-- this is so that tracks with "Rock; Pop" can be found under either
+CREATE TABLE IF NOT EXISTS genres(
+    id INTEGER PRIMARY KEY,
+    -- genre is used as a identifier, if not present, it should not be added to the database
+    genre TEXT NOT NULL UNIQUE
+);
+
+-- relation table for a tracks's genre
+-- entry will get deleted if the genre is dropped or the track is dropped
+CREATE TABLE IF NOT EXISTS tracks_genres(
+    track INTEGER NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    genre INTEGER NOT NULL REFERENCES genres(id) ON DELETE CASCADE,
+    PRIMARY KEY (track, genre)
+);
+
+CREATE INDEX IF NOT EXISTS tracks_genres_genre ON tracks_genres(genre);
+
+-- existing values are mapped as-is, multi-valued genres will be split on the next rescan
+INSERT OR IGNORE INTO genres (genre)
+SELECT DISTINCT tracks_metadata.genre FROM tracks_metadata
+WHERE tracks_metadata.genre IS NOT NULL;
+
+INSERT OR IGNORE INTO tracks_genres (track, genre)
+SELECT tracks_metadata.track, genres.id FROM tracks_metadata
+INNER JOIN genres ON genres.genre = tracks_metadata.genre;
@@ -0,0 +1,6 @@
+--- SECTION: sort names
+
+-- the name the artist is sorted by, like "Beatles, The", can be null if not present
+ALTER TABLE artists ADD COLUMN sort_name TEXT;
+-- the title the album is sorted by, can be null if not present
+ALTER TABLE albums ADD COLUMN sort_title TEXT;
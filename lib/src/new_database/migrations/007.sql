@@ -0,0 +1,5 @@
+--- SECTION: color labels
+
+-- the color label set for manual curation, see "TrackLabel" for the values, NULL if none
+ALTER TABLE tracks ADD COLUMN label INTEGER;
+CREATE INDEX IF NOT EXISTS tracks_label ON tracks(label);
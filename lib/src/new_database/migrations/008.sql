@@ -0,0 +1,7 @@
+--- SECTION: inbox
+
+-- whether the track was reviewed in the inbox since it was added
+ALTER TABLE tracks ADD COLUMN reviewed BOOLEAN NOT NULL DEFAULT 0;
+-- the tracks that exist before the inbox are not new anymore
+UPDATE tracks SET reviewed = 1;
+CREATE INDEX IF NOT EXISTS tracks_reviewed ON tracks(reviewed);
@@ -0,0 +1,5 @@
+--- SECTION: track offsets
+
+-- where to start / stop playing the track in seconds, instead of its beginning / end, NULL if not set
+ALTER TABLE tracks ADD COLUMN start_offset INTEGER;
+ALTER TABLE tracks ADD COLUMN end_offset INTEGER;
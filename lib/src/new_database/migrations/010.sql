@@ -0,0 +1,5 @@
+--- SECTION: track energy
+
+-- the energy estimated from the audio, from 0 (calm) to 1 (energetic), NULL if not analyzed
+ALTER TABLE tracks ADD COLUMN energy REAL;
+CREATE INDEX IF NOT EXISTS tracks_energy ON tracks(energy);
@@ -0,0 +1,22 @@
+--- SECTION: download deduplication
+
+-- the content hash of files, to find identical downloads; "size" and "modified" detect changed files to be hashed again
+CREATE TABLE IF NOT EXISTS file_hashes(
+    path TEXT PRIMARY KEY NOT NULL,
+    size INTEGER NOT NULL,
+    -- the modification time in seconds since the unix epoch
+    modified INTEGER NOT NULL,
+    -- the hex-encoded SHA-256 of the content
+    hash TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS file_hashes_hash ON file_hashes(hash);
+
+-- the sources a file was downloaded from, like a podcast episode url or a yt-dlp url
+-- multiple sources can map to the same content, which is then only stored once
+CREATE TABLE IF NOT EXISTS download_sources(
+    source TEXT PRIMARY KEY NOT NULL,
+    path TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    downloaded_at DATE NOT NULL
+);
+CREATE INDEX IF NOT EXISTS download_sources_hash ON download_sources(hash);
@@ -0,0 +1,4 @@
+--- SECTION: track tempo
+
+-- the tempo in beats per minute, from the tag or tapped in the TUI, can be null if not known
+ALTER TABLE tracks_metadata ADD COLUMN bpm REAL;
@@ -0,0 +1,4 @@
+--- SECTION: damaged tracks
+
+-- the regions a salvaging decode had to mute, like "01:02.500-01:03.000, 02:10.000-02:10.026", null if not known to be damaged
+ALTER TABLE tracks ADD COLUMN damaged TEXT;
@@ -0,0 +1,8 @@
+--- SECTION: library verification
+
+-- the kind of the last failed verification, see "VerifyFailure" for the values, NULL if it passed or was not verified
+ALTER TABLE tracks ADD COLUMN verify_failure INTEGER;
+-- a description of the last failed verification, NULL if it passed or was not verified
+ALTER TABLE tracks ADD COLUMN verify_detail TEXT;
+-- when the track was last verified, NULL if never
+ALTER TABLE tracks ADD COLUMN verified_at TEXT;
@@ -0,0 +1,6 @@
+--- SECTION: track loudness
+
+-- the integrated loudness in LUFS analyzed per EBU R128, for tracks without ReplayGain tags, can be null if not analyzed
+ALTER TABLE tracks_metadata ADD COLUMN loudness REAL;
+-- the highest sample of the analyzed audio, 1.0 being full scale
+ALTER TABLE tracks_metadata ADD COLUMN loudness_peak REAL;
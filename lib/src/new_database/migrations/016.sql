@@ -0,0 +1,5 @@
+--- SECTION: track quality
+
+-- the quality badge from the audio properties, see "QualityBadge" for the values, NULL for ordinary lossy tracks or if not scanned since
+ALTER TABLE tracks_metadata ADD COLUMN quality INTEGER;
+CREATE INDEX IF NOT EXISTS tracks_metadata_quality ON tracks_metadata(quality);
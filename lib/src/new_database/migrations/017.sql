@@ -0,0 +1,16 @@
+--- SECTION: bookmarks
+
+-- named positions in files to come back to, like chapters of a audiobook
+CREATE TABLE IF NOT EXISTS bookmarks(
+    id INTEGER PRIMARY KEY,
+    -- the location of the file, like a path or a podcast episode url
+    location TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- the position in milliseconds
+    position INTEGER NOT NULL,
+    -- whether this is the automatic "Last position" bookmark, of which every location has at most one
+    auto INTEGER NOT NULL DEFAULT 0,
+    created_at DATE NOT NULL
+);
+CREATE INDEX IF NOT EXISTS bookmarks_location ON bookmarks(location);
+CREATE UNIQUE INDEX IF NOT EXISTS bookmarks_auto ON bookmarks(location) WHERE auto = 1;
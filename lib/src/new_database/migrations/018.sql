@@ -0,0 +1,4 @@
+--- SECTION: ratings
+
+-- the rating of the track from 1 to 5 stars, NULL if not rated
+ALTER TABLE tracks ADD COLUMN rating INTEGER;
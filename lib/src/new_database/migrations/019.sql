@@ -0,0 +1,14 @@
+--- SECTION: smart playlists
+
+-- playlists defined by a query, evaluated against the library whenever they are shown
+CREATE TABLE IF NOT EXISTS smart_playlists(
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    -- the query, see "smart_playlist.rs"
+    query TEXT NOT NULL,
+    created_at DATE NOT NULL
+);
+
+-- for queries on when tracks were added and how often they were played
+CREATE INDEX IF NOT EXISTS tracks_added_at ON tracks(added_at);
+CREATE INDEX IF NOT EXISTS tracks_play_count ON tracks(play_count);
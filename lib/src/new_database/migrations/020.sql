@@ -0,0 +1,15 @@
+--- SECTION: play statistics
+
+-- how often the track was skipped before half of it was played
+ALTER TABLE tracks ADD COLUMN skip_count INTEGER NOT NULL DEFAULT 0;
+
+-- every time a track was played, for the listening time per week
+CREATE TABLE IF NOT EXISTS plays(
+    id INTEGER PRIMARY KEY,
+    track INTEGER NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    started_at DATE NOT NULL,
+    -- how long the track was actually listened to, in seconds
+    listened INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS plays_started_at ON plays(started_at);
+CREATE INDEX IF NOT EXISTS plays_track ON plays(track);
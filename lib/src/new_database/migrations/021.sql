@@ -0,0 +1,8 @@
+--- SECTION: mood and sound profile
+
+-- the mood and sound profile described from the audio by a heuristic, NULL if not analyzed or silent
+-- this is no genre classification, so it is kept apart from the genre tags
+ALTER TABLE tracks ADD COLUMN mood TEXT;
+ALTER TABLE tracks ADD COLUMN sound_profile TEXT;
+CREATE INDEX IF NOT EXISTS tracks_mood ON tracks(mood);
+CREATE INDEX IF NOT EXISTS tracks_sound_profile ON tracks(sound_profile);
@@ -1,6 +1,11 @@
 #![allow(clippy::unnecessary_debug_formatting)] // for logging we want all paths's characters to be escaped
 
-use std::{fmt::Debug, path::Path, sync::Arc};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
 use parking_lot::{Mutex, MutexGuard};
@@ -10,11 +15,20 @@ use track_insert::TrackInsertable;
 use walkdir::DirEntry;
 
 use crate::{
-    config::{ServerOverlay, v2::server::ScanDepth},
+    audio_analysis, audio_verify,
+    config::{
+        ServerOverlay,
+        v2::server::{ScanDepth, metadata::MetadataSettings},
+    },
+    metrics::METRICS,
     new_database::{
-        album_ops::delete_all_unreferenced_albums, artist_ops::delete_all_unreferenced_artists,
+        album_ops::delete_all_unreferenced_albums,
+        artist_ops::delete_all_unreferenced_artists,
+        maintenance::{IntegrityStatus, RepairReport},
+        pool::ReadPool,
+        track_ops::delete_all_unreferenced_genres,
     },
-    track::{MetadataOptions, parse_metadata_from_file},
+    track::{MetadataOptions, get_picture_for_music_track, parse_metadata_from_file},
     utils::{filetype_supported, get_app_new_database_path},
 };
 
@@ -27,17 +41,27 @@ mod album_insert;
 pub mod album_ops;
 mod artist_insert;
 pub mod artist_ops;
+pub mod bookmark_ops;
+pub mod download_ops;
+pub mod maintenance;
 mod migrate;
+mod pool;
+pub mod resume_ops;
+pub mod smart_playlist_ops;
 mod track_insert;
 pub mod track_ops;
 
 #[allow(clippy::doc_markdown)]
 /// The SQLite Database interface.
 ///
-/// This *can* be shared between threads via `clone`, **but** only one operation may occur at a time.
+/// This *can* be shared between threads via `clone`, **but** only one write operation may occur at a time.
+///
+/// For file-backed databases, [`read`](Self::read) uses a pool of read-only connections and does not wait on writes.
 #[derive(Clone)]
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    /// Read-only connections, not available for in-memory databases
+    readers: Option<Arc<ReadPool>>,
     /// Limit how many scanners are active at a time
     semaphore: Arc<Semaphore>,
 }
@@ -59,8 +83,19 @@ impl Database {
     /// - if database migration fails
     pub fn new(path: &Path) -> Result<Self> {
         let conn = Connection::open(path).context("open/create database")?;
+        // WAL allows readers to continue while a writer is active, like while scanning the library
+        let journal_mode: String = conn
+            .pragma_update_and_check(None, "journal_mode", "WAL", |r| r.get(0))
+            .context("set journal_mode")?;
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            warn!("Database journal_mode could not be set to WAL, is \"{journal_mode}\"");
+        }
 
-        Self::new_from_connection(conn)
+        let mut db = Self::new_from_connection(conn)?;
+        // open readers only after migrations, as read-only connections cannot create the file or schema
+        db.readers = Some(Arc::new(ReadPool::open(path, pool::READ_CONNECTIONS)?));
+
+        Ok(db)
     }
 
     /// Create a new database at the default app config path.
@@ -81,10 +116,54 @@ impl Database {
     fn new_from_connection(conn: Connection) -> Result<Self> {
         migrate::migrate(&conn).context("Database migration")?;
 
+        match maintenance::quick_check(&conn) {
+            Ok(IntegrityStatus::Ok) => (),
+            Ok(IntegrityStatus::Problems(problems)) => warn!(
+                "Database integrity check found problems, consider running \"termusic-server db repair\": {problems:#?}"
+            ),
+            Err(err) => warn!("Database integrity check could not be run: {err:#?}"),
+        }
+
         let conn = Arc::new(Mutex::new(conn));
         // for now limit to one worker at a time
         let semaphore = Arc::new(Semaphore::new(1));
-        Ok(Self { conn, semaphore })
+        Ok(Self {
+            conn,
+            readers: None,
+            semaphore,
+        })
+    }
+
+    /// Run `fun` with a read-only connection on a blocking thread, without blocking the caller.
+    ///
+    /// Uses the writer connection if no read-only connections are available (like for in-memory databases).
+    pub async fn read<F, T>(&self, fun: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let Some(readers) = &self.readers else {
+            return self.write(fun).await;
+        };
+
+        let conn = readers.take().await?;
+
+        tokio::task::spawn_blocking(move || fun(&conn))
+            .await
+            .context("database read task")?
+    }
+
+    /// Run `fun` with the writer connection on a blocking thread, without blocking the caller.
+    pub async fn write<F, T>(&self, fun: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || fun(&conn.lock()))
+            .await
+            .context("database write task")?
     }
 
     /// Scan the given path recursively, limited to [`ServerOverlay::get_library_scan_depth`].
@@ -98,33 +177,51 @@ impl Database {
         config: &ServerOverlay,
         replace_metadata: bool,
     ) -> Result<()> {
-        let path = path
-            .canonicalize()
-            .with_context(|| path.display().to_string())?;
-
-        let walker = {
-            let mut walker = walkdir::WalkDir::new(&path).follow_links(true);
+        let (path, walker) = scan_walker(path, config.get_metadata_scan_depth())?;
+        let metadata_settings = config.settings.metadata.clone();
 
-            if let ScanDepth::Limited(limit) = config.get_metadata_scan_depth() {
-                walker = walker.max_depth(usize::try_from(limit).unwrap_or(usize::MAX));
-            }
+        self.spawn_worker(move |db| {
+            let start = Instant::now();
+            Self::process_iter(
+                walker,
+                &db,
+                &path,
+                replace_metadata,
+                &metadata_settings,
+                false,
+            );
+            METRICS.observe_scan(start.elapsed());
+        });
 
-            walker
-                .into_iter()
-                .filter_map(Result::ok)
-                // only process files which we support
-                .filter(|v| v.file_type().is_file())
-                .filter(|v| filetype_supported(v.path()))
-        };
+        Ok(())
+    }
 
-        let separators = config.settings.metadata.artist_separators.clone();
+    /// Apply the given changes on disk to the database, in order.
+    ///
+    /// Changed paths are scanned replacing the metadata, removed paths are deleted and renamed paths keep their data.
+    ///
+    /// Waits for a permit before starting another worker.
+    pub fn scan_changes(&self, changes: Vec<PathChange>, config: &ServerOverlay) {
+        let depth = config.get_metadata_scan_depth();
+        let metadata_settings = config.settings.metadata.clone();
 
         self.spawn_worker(move |db| {
-            let separators: Vec<&str> = separators.iter().map(String::as_str).collect();
-            Self::process_iter(walker, &db, &path, replace_metadata, &separators);
+            Self::process_changes(&db, changes, depth, &metadata_settings);
         });
+    }
 
-        Ok(())
+    /// Scan the given path like [`scan_path`](Self::scan_path), but on the current thread and always replacing the metadata.
+    ///
+    /// Also extracts the cover art for every file, to include it in the returned timings.
+    pub fn bench_scan(&self, path: &Path, config: &ServerOverlay) -> Result<ScanTimings> {
+        let (path, walker) = scan_walker(path, config.get_metadata_scan_depth())?;
+        let start = Instant::now();
+
+        let mut timings =
+            Self::process_iter(walker, self, &path, true, &config.settings.metadata, true);
+        timings.total = start.elapsed();
+
+        Ok(timings)
     }
 
     /// Spawn a database worker, for work in the background.
@@ -157,23 +254,33 @@ impl Database {
     /// The actual function to walk the iterator of files for [`Self::scan_path`].
     ///
     /// Expects `path` to be absolute.
+    ///
+    /// If `extract_art` is `true`, the cover art is also extracted and timed, but not stored.
     fn process_iter(
         walker: impl Iterator<Item = DirEntry>,
         db: &Self,
         path: &Path,
         replace_metadata: bool,
-        separators: &[&str],
-    ) {
+        metadata_settings: &MetadataSettings,
+        extract_art: bool,
+    ) -> ScanTimings {
+        let separators: Vec<&str> = metadata_settings
+            .artist_separators
+            .iter()
+            .map(String::as_str)
+            .collect();
+
         // keep the permit for the entirety of this function
         info!("Scanning {path:#?}");
 
-        let mut created_updated: usize = 0;
+        let mut timings = ScanTimings::default();
 
         // assumptions in this function:
         // - "walker" iterator is already filtered to only contain files
         // - "walker" iterator is already filtered to only our supported file types
         for record in walker {
             let path = record.path();
+            timings.files += 1;
 
             // skip existing paths, if no full scan is requested
             if !replace_metadata {
@@ -187,6 +294,7 @@ impl Database {
                 }
             }
 
+            let tag_start = Instant::now();
             let track_metadata = match parse_metadata_from_file(
                 path,
                 MetadataOptions {
@@ -195,45 +303,212 @@ impl Database {
                     album_artists: true,
                     artist: true,
                     artists: true,
-                    artist_separators: separators,
+                    artist_separators: &separators,
                     title: true,
                     duration: true,
                     genre: true,
+                    composer: true,
+                    work: true,
+                    bpm: true,
+                    quality: true,
                     ..Default::default()
                 },
             ) {
                 Ok(v) => v,
                 Err(err) => {
                     warn!("Error scanning path {path:#?}: {err:#?}");
+                    timings.failed += 1;
                     continue;
                 }
             };
+            timings.tag_parse += tag_start.elapsed();
 
-            let db_track = match TrackInsertable::try_from_track(path, &track_metadata) {
-                Ok(v) => v,
-                Err(err) => {
-                    warn!("Error converting to database track {path:#?}: {err:#?}");
-                    continue;
+            if extract_art {
+                let art_start = Instant::now();
+                if let Err(err) = get_picture_for_music_track(path) {
+                    warn!("Error extracting the cover of {path:#?}: {err:#?}");
                 }
-            };
+                timings.art_extract += art_start.elapsed();
+            }
 
-            let _id = match db_track.try_insert_or_update(&db.conn.lock()) {
-                Ok(v) => v,
-                Err(err) => {
-                    warn!("Error inserting or updating {path:#?}: {err:#?}");
+            let grouping = metadata_settings.album_grouping_for(path);
+            let db_track =
+                match TrackInsertable::try_from_track_grouped(path, &track_metadata, grouping) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        warn!("Error converting to database track {path:#?}: {err:#?}");
+                        timings.failed += 1;
+                        continue;
+                    }
+                };
+
+            let insert_start = Instant::now();
+            let res = db_track.try_insert_or_update(&db.conn.lock());
+            timings.db_insert += insert_start.elapsed();
+            if let Err(err) = res {
+                warn!("Error inserting or updating {path:#?}: {err:#?}");
+                timings.failed += 1;
+                continue;
+            }
+
+            if metadata_settings.analyze_audio {
+                let analysis_start = Instant::now();
+                let features = audio_analysis::analyze_file(path)
+                    .inspect_err(|err| warn!("Error analyzing the audio of {path:#?}: {err:#?}"))
+                    .ok();
+                timings.audio_analysis += analysis_start.elapsed();
+                let conn = db.conn.lock();
+                if let Err(err) = track_ops::set_energy(&conn, path, features.map(|v| v.energy)) {
+                    warn!("Error storing the energy of {path:#?}: {err:#?}");
+                }
+                let (mood, profile) =
+                    features.map_or((None, None), |v| (v.mood(), v.sound_profile()));
+                if let Err(err) = track_ops::set_sound_description(&conn, path, mood, profile) {
+                    warn!("Error storing the mood and sound profile of {path:#?}: {err:#?}");
+                }
+            }
+
+            timings.created_updated += 1;
+        }
+
+        info!(
+            "Finished Scanning {path:#?} with {} created or updated",
+            timings.created_updated
+        );
+
+        timings
+    }
+
+    /// The actual function for work from [`scan_changes`](Self::scan_changes).
+    fn process_changes(
+        db: &Self,
+        changes: Vec<PathChange>,
+        depth: ScanDepth,
+        metadata_settings: &MetadataSettings,
+    ) {
+        let mut removed = 0;
+
+        for change in changes {
+            let (path, replace_metadata) = match change {
+                PathChange::Removed(path) => {
+                    match track_ops::delete_tracks_path(&db.conn.lock(), &path) {
+                        Ok(affected) => removed += affected,
+                        Err(err) => warn!("Error removing tracks at {path:#?}: {err:#?}"),
+                    }
                     continue;
                 }
+                PathChange::Renamed { from, to } => {
+                    if let Err(err) = track_ops::rename_tracks_path(&db.conn.lock(), &from, &to) {
+                        warn!("Error renaming tracks from {from:#?} to {to:#?}: {err:#?}");
+                    }
+                    // still scan, as not all files may have been known at the old path
+                    (to, false)
+                }
+                PathChange::Modified(path) => (path, true),
             };
 
-            created_updated += 1;
+            match scan_walker(&path, depth) {
+                Ok((path, walker)) => {
+                    Self::process_iter(
+                        walker,
+                        db,
+                        &path,
+                        replace_metadata,
+                        metadata_settings,
+                        false,
+                    );
+                }
+                Err(err) => warn!("Error scanning changed path {path:#?}: {err:#?}"),
+            }
+        }
+
+        // removed tracks may have been the last of their album or artist
+        if removed > 0 {
+            info!("Removed {removed} tracks");
+            if let Err(err) = Self::process_cleanup(db) {
+                warn!("Error processing database cleanup: {err:#?}");
+            }
+        }
+    }
+
+    /// Spawn a worker to verify all tracks in the database by decoding them, see [`audio_verify::verify_file`].
+    ///
+    /// The result of each track is stored, `on_done` is called with the summary once all tracks are verified.
+    pub fn verify_library<F>(&self, on_done: F)
+    where
+        F: FnOnce(Result<VerifySummary>) + Send + 'static,
+    {
+        self.spawn_worker(move |db| {
+            on_done(Self::process_verify(&db));
+        });
+    }
+
+    /// The actual function for work from [`verify_library`](Self::verify_library).
+    fn process_verify(db: &Self) -> Result<VerifySummary> {
+        let paths: Vec<PathBuf> =
+            track_ops::get_all_tracks(&db.get_connection(), track_ops::RowOrdering::IdAsc)?
+                .iter()
+                .map(track_ops::TrackRead::as_pathbuf)
+                .collect();
+
+        info!("Verifying {} tracks", paths.len());
+
+        let mut summary = VerifySummary::default();
+        for path in paths {
+            // decode without holding the lock, as it takes a while
+            let res = audio_verify::verify_file(&path);
+            if let Err(err) = &res {
+                warn!("Verification of {path:#?} failed: {err}");
+                summary.failed += 1;
+            }
+            summary.verified += 1;
+
+            if let Err(err) =
+                track_ops::set_verified(&db.get_connection(), &path, res.err().as_ref())
+            {
+                warn!("Error storing the verification of {path:#?}: {err:#?}");
+            }
         }
 
-        info!("Finished Scanning {path:#?} with {created_updated} created or updated");
+        info!(
+            "Finished verifying {} tracks with {} failed",
+            summary.verified, summary.failed
+        );
+
+        Ok(summary)
+    }
+
+    /// Run a full integrity check, rebuild the indexes and prune orphaned rows.
+    ///
+    /// This runs directly on the current thread, see [`maintenance::repair`].
+    pub fn repair(&self) -> Result<(IntegrityStatus, RepairReport)> {
+        let conn = self.get_connection();
+
+        let status = maintenance::integrity_check(&conn)?;
+        let report = maintenance::repair(&conn)?;
+
+        exec_optimize(&conn)?;
+
+        Ok((status, report))
+    }
+
+    /// Rebuild the database file to reclaim free space.
+    ///
+    /// This runs directly on the current thread, see [`maintenance::vacuum`].
+    pub fn vacuum(&self) -> Result<()> {
+        maintenance::vacuum(&self.get_connection())
+    }
+
+    /// Run a quick integrity check, without modifying anything.
+    ///
+    /// This runs directly on the current thread, see [`maintenance::quick_check`].
+    pub fn quick_check(&self) -> Result<IntegrityStatus> {
+        maintenance::quick_check(&self.get_connection())
     }
 
     /// Spawn a worker to cleanup the database.
     ///
-    /// This includes removing unreferenced albums and artists.
+    /// This includes removing unreferenced albums, artists and genres.
     // TODO: also add option to check for all tracks to actually exist on disk
     pub fn run_cleanup(&self) {
         self.spawn_worker(move |db| {
@@ -259,6 +534,10 @@ impl Database {
 
         info!("Deleted {affected_artists} Artists");
 
+        let affected_genres = delete_all_unreferenced_genres(&conn)?;
+
+        info!("Deleted {affected_genres} Genres");
+
         // finally run optimize to reclaim freed space
         exec_optimize(&conn)?;
 
@@ -268,6 +547,68 @@ impl Database {
     }
 }
 
+/// A change of a file or directory on disk, see [`Database::scan_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathChange {
+    /// The path was created or its content changed
+    Modified(PathBuf),
+    /// The path was removed
+    Removed(PathBuf),
+    /// The path was renamed or moved from `from` to `to`
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+/// The result of [`Database::verify_library`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifySummary {
+    pub verified: usize,
+    pub failed: usize,
+}
+
+/// The time spent in each step of [`Database::bench_scan`], summed over all files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanTimings {
+    /// All supported files found, including skipped and failed ones
+    pub files: usize,
+    pub created_updated: usize,
+    pub failed: usize,
+    pub tag_parse: Duration,
+    pub db_insert: Duration,
+    /// Only measured if requested, see [`Database::bench_scan`]
+    pub art_extract: Duration,
+    /// Only measured if enabled, see [`MetadataSettings::analyze_audio`]
+    pub audio_analysis: Duration,
+    /// The wall-clock time of the whole scan
+    pub total: Duration,
+}
+
+/// Resolve `path` and create a iterator over all supported files in it, limited to `depth`.
+///
+/// If `path` is a file, only that file is returned.
+fn scan_walker(
+    path: &Path,
+    depth: ScanDepth,
+) -> Result<(PathBuf, impl Iterator<Item = DirEntry> + use<>)> {
+    let path = path
+        .canonicalize()
+        .with_context(|| path.display().to_string())?;
+
+    let mut walker = walkdir::WalkDir::new(&path).follow_links(true);
+
+    if let ScanDepth::Limited(limit) = depth {
+        walker = walker.max_depth(usize::try_from(limit).unwrap_or(usize::MAX));
+    }
+
+    let walker = walker
+        .into_iter()
+        .filter_map(Result::ok)
+        // only process files which we support
+        .filter(|v| v.file_type().is_file())
+        .filter(|v| filetype_supported(v.path()));
+
+    Ok((path, walker))
+}
+
 /// Run SQLite operation `PRAGMA optimize`.
 fn exec_optimize(conn: &Connection) -> Result<()> {
     let mut stmt = conn.prepare("PRAGMA optimize;")?;
@@ -326,3 +667,32 @@ mod test_utils {
         assert_eq!(path, Path::new("C:\\somewhere\\else"));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::config::ServerOverlay;
+    use crate::new_database::test_utils::gen_database;
+
+    #[test]
+    fn should_count_bench_scan_failures() {
+        let dir = std::env::temp_dir().join(format!("termusic-bench-scan-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // not a supported file type, so not counted at all
+        fs::write(dir.join("notes.txt"), "text").unwrap();
+        // a supported file type, which fails to parse
+        fs::write(dir.join("broken.mp3"), "not audio").unwrap();
+
+        let db = gen_database();
+        let timings = db.bench_scan(&dir, &ServerOverlay::default()).unwrap();
+
+        assert_eq!(timings.files, 1);
+        assert_eq!(timings.failed, 1);
+        assert_eq!(timings.created_updated, 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,127 @@
+use std::{ops::Deref, path::Path, sync::Arc};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use rusqlite::{Connection, OpenFlags};
+use tokio::sync::Semaphore;
+
+/// The amount of read-only connections to open for a file-backed database.
+pub(super) const READ_CONNECTIONS: usize = 2;
+
+/// A pool of read-only connections, so that reads can happen in parallel to the writer and each other.
+///
+/// This requires the database to be in `WAL` mode, otherwise the readers would still block on a writer.
+pub(super) struct ReadPool {
+    conns: Mutex<Vec<Connection>>,
+    /// Amount of connections available in `conns`
+    available: Semaphore,
+}
+
+impl ReadPool {
+    /// Open `amount` read-only connections to the database at `path`.
+    pub(super) fn open(path: &Path, amount: usize) -> Result<Self> {
+        let conns = (0..amount)
+            .map(|_| {
+                Connection::open_with_flags(
+                    path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )
+                .context("open read-only database connection")
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            available: Semaphore::new(conns.len()),
+            conns: Mutex::new(conns),
+        })
+    }
+
+    /// Wait until a connection is available and take it out of the pool.
+    ///
+    /// The connection is given back once the returned guard is dropped.
+    pub(super) async fn take(self: &Arc<Self>) -> Result<PooledConnection> {
+        let permit = self.available.acquire().await.context("read pool closed")?;
+        // the permit is re-added once the connection is given back
+        permit.forget();
+
+        // the semaphore guarantees that there is at least one connection
+        let conn = self
+            .conns
+            .lock()
+            .pop()
+            .context("read pool has no connections despite a permit")?;
+
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: self.clone(),
+        })
+    }
+
+    /// Give back a connection previously gotten from [`Self::take`].
+    fn put(&self, conn: Connection) {
+        self.conns.lock().push(conn);
+        self.available.add_permits(1);
+    }
+}
+
+/// A connection taken from a [`ReadPool`], which is given back on drop.
+pub(super) struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<ReadPool>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Self::Target {
+        // only ever "None" in "drop"
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.put(conn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::new_database::{Database, artist_ops::count_all_artists};
+
+    #[test]
+    #[allow(clippy::await_holding_lock)] // intentional, see below
+    fn should_read_while_writer_locked() {
+        let dir = std::env::temp_dir().join(format!("termusic-pool-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("library.db");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let db = Database::new(&path).unwrap();
+            assert!(db.readers.is_some());
+
+            db.write(|conn| {
+                conn.execute("INSERT INTO artists(artist) VALUES ('ArtistA');", [])?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+            // hold the writer while reading, readers should not need to wait on it
+            let count = {
+                let _writer = db.get_connection();
+                db.read(count_all_artists).await.unwrap()
+            };
+
+            assert_eq!(count, 1);
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,106 @@
+//! The point to resume playback from after a restart, stored in the key-value `config` table.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use indoc::indoc;
+use rusqlite::{Connection, OptionalExtension, named_params};
+
+/// The `config` key of the location of the track to resume, see [`ResumePoint::track`].
+const KEY_TRACK: &str = "resume_track";
+/// The `config` key of the position to resume at, in milliseconds.
+const KEY_POSITION: &str = "resume_position";
+
+/// The track that was playing and where, to continue there after a restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumePoint {
+    /// The location of the track, like a path or a podcast episode url, as stored in the playlist
+    pub track: String,
+    pub position: Duration,
+}
+
+/// Get a single value of the `config` table.
+fn get_value(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare_cached("SELECT value FROM config WHERE key=:key;")?;
+
+    Ok(stmt
+        .query_row(named_params! {":key": key}, |row| row.get(0))
+        .optional()?)
+}
+
+/// Get the stored [`ResumePoint`], if there is one.
+pub fn get_resume_point(conn: &Connection) -> Result<Option<ResumePoint>> {
+    let Some(track) = get_value(conn, KEY_TRACK)? else {
+        return Ok(None);
+    };
+    let Some(position) = get_value(conn, KEY_POSITION)? else {
+        return Ok(None);
+    };
+    let position: u64 = position
+        .parse()
+        .with_context(|| format!("Parsing the resume position {position:?}"))?;
+
+    Ok(Some(ResumePoint {
+        track,
+        position: Duration::from_millis(position),
+    }))
+}
+
+/// Store `point` as the [`ResumePoint`], replacing any previous one.
+pub fn set_resume_point(conn: &Connection, point: &ResumePoint) -> Result<()> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        INSERT INTO config(key, value) VALUES (:key, :value)
+        ON CONFLICT(key) DO UPDATE SET value=excluded.value;
+    "})?;
+    stmt.execute(named_params! {":key": KEY_TRACK, ":value": point.track})?;
+    stmt.execute(
+        named_params! {":key": KEY_POSITION, ":value": point.position.as_millis().to_string()},
+    )?;
+
+    Ok(())
+}
+
+/// Remove the stored [`ResumePoint`], like when nothing is playing anymore.
+pub fn clear_resume_point(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM config WHERE key IN (:track, :position);",
+        named_params! {":track": KEY_TRACK, ":position": KEY_POSITION},
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{ResumePoint, clear_resume_point, get_resume_point, set_resume_point};
+    use crate::new_database::test_utils::gen_database;
+
+    #[test]
+    fn should_store_and_clear() {
+        let db = gen_database();
+        let conn = db.get_connection();
+
+        assert_eq!(get_resume_point(&conn).unwrap(), None);
+
+        let point = ResumePoint {
+            track: "/somewhere/track.mp3".to_string(),
+            position: Duration::from_millis(61_500),
+        };
+        set_resume_point(&conn, &point).unwrap();
+        assert_eq!(get_resume_point(&conn).unwrap(), Some(point));
+
+        let point = ResumePoint {
+            track: "https://example.com/episode.mp3".to_string(),
+            position: Duration::from_secs(1200),
+        };
+        set_resume_point(&conn, &point).unwrap();
+        assert_eq!(get_resume_point(&conn).unwrap(), Some(point));
+
+        clear_resume_point(&conn).unwrap();
+        assert_eq!(get_resume_point(&conn).unwrap(), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! Saved smart playlists: named queries, evaluated with [`track_ops::get_tracks_matching`] whenever they are shown, so
+//! they always reflect the current library.
+//!
+//! [`track_ops::get_tracks_matching`]: super::track_ops::get_tracks_matching
+
+use anyhow::{Context as _, Result, bail};
+use indoc::indoc;
+use rusqlite::{Connection, OptionalExtension, Row, named_params};
+
+use super::Integer;
+use crate::smart_playlist::SmartQuery;
+
+/// A saved smart playlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmartPlaylist {
+    pub id: Integer,
+    pub name: String,
+    /// The query, see [`SmartQuery`]
+    pub query: String,
+}
+
+impl SmartPlaylist {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            query: row.get("query")?,
+        })
+    }
+
+    /// Parse the [`query`](Self::query) of this playlist.
+    ///
+    /// # Errors
+    ///
+    /// - if the query is not valid, like when saved by a newer version
+    pub fn parse(&self) -> Result<SmartQuery> {
+        SmartQuery::parse(&self.query).with_context(|| format!("smart playlist \"{}\"", self.name))
+    }
+}
+
+/// Get all smart playlists, ordered by their name.
+pub fn get_all(conn: &Connection) -> Result<Vec<SmartPlaylist>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT id, name, query FROM smart_playlists
+        ORDER BY name ASC;
+    "})?;
+
+    let playlists = stmt
+        .query_map([], SmartPlaylist::from_row)?
+        .collect::<Result<_, _>>()?;
+
+    Ok(playlists)
+}
+
+/// Get the smart playlist named `name`.
+pub fn get_by_name(conn: &Connection, name: &str) -> Result<Option<SmartPlaylist>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT id, name, query FROM smart_playlists
+        WHERE name=:name;
+    "})?;
+
+    let playlist = stmt
+        .query_row(named_params! {":name": name}, SmartPlaylist::from_row)
+        .optional()?;
+
+    Ok(playlist)
+}
+
+/// Save the smart playlist `name` with `query`, replacing the playlist named `previous` if given, like when renaming it.
+///
+/// # Errors
+///
+/// - if the name is empty
+/// - if the query is not valid
+/// - if another playlist is already named `name`
+pub fn save(conn: &Connection, previous: Option<&str>, name: &str, query: &str) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("A smart playlist needs a name");
+    }
+    SmartQuery::parse(query)?;
+    if previous != Some(name) && get_by_name(conn, name)?.is_some() {
+        bail!("A smart playlist named \"{name}\" already exists");
+    }
+
+    let query = query.trim();
+    let updated = match previous {
+        Some(previous) => conn.execute(
+            "UPDATE smart_playlists SET name=:name, query=:query WHERE name=:previous;",
+            named_params! {":name": name, ":query": query, ":previous": previous},
+        )?,
+        None => 0,
+    };
+    // not renaming, or the previous playlist was removed meanwhile
+    if updated == 0 {
+        let mut stmt = conn.prepare_cached(indoc! {"
+            INSERT INTO smart_playlists (name, query, created_at)
+            VALUES (:name, :query, :now)
+            ON CONFLICT(name) DO UPDATE SET query=excluded.query;
+        "})?;
+        stmt.execute(named_params! {
+            ":name": name,
+            ":query": query,
+            ":now": chrono::Utc::now().to_rfc3339(),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Remove the smart playlist named `name`, returns whether it existed.
+pub fn remove(conn: &Connection, name: &str) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM smart_playlists WHERE name=:name;",
+        named_params! {":name": name},
+    )?;
+
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{get_all, get_by_name, remove, save};
+    use crate::new_database::test_utils::gen_database;
+
+    #[test]
+    fn should_save_rename_and_remove() {
+        let db = gen_database();
+        let conn = db.get_connection();
+
+        save(
+            &conn,
+            None,
+            "Forgotten",
+            "genre:ambient AND added>30d AND playcount<2",
+        )
+        .unwrap();
+        save(&conn, None, "Favorites", "rating>=4").unwrap();
+        assert!(save(&conn, None, "Broken", "genre>").is_err());
+        assert!(save(&conn, None, " ", "rating>=4").is_err());
+        // renaming onto another playlist would lose it
+        assert!(save(&conn, Some("Favorites"), "Forgotten", "rating>=4").is_err());
+
+        save(&conn, Some("Favorites"), "Loved", "rating=5").unwrap();
+        let names: Vec<(String, String)> = get_all(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.name, v.query))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (
+                    "Forgotten".to_string(),
+                    "genre:ambient AND added>30d AND playcount<2".to_string()
+                ),
+                ("Loved".to_string(), "rating=5".to_string()),
+            ]
+        );
+
+        assert!(remove(&conn, "Loved").unwrap());
+        assert!(!remove(&conn, "Loved").unwrap());
+        assert!(get_by_name(&conn, "Loved").unwrap().is_none());
+    }
+}
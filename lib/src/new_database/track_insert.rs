@@ -6,11 +6,15 @@ use indoc::indoc;
 use rusqlite::{Connection, named_params};
 
 use crate::{
+    common::const_unknown::UNKNOWN_ARTIST,
+    config::v2::server::metadata::AlbumGrouping,
     new_database::{
-        Integer, album_insert::AlbumInsertable, artist_insert::ArtistInsertable,
-        track_ops::delete_tracks_artists_mapping_for,
+        Integer,
+        album_insert::AlbumInsertable,
+        artist_insert::ArtistInsertable,
+        track_ops::{delete_tracks_artists_mapping_for, delete_tracks_genres_mapping_for},
     },
-    track::TrackMetadata,
+    track::{QualityBadge, TrackMetadata},
 };
 
 #[derive(Debug, Clone)]
@@ -30,14 +34,20 @@ pub struct TrackInsertable<'a> {
     pub(super) title: Option<&'a str>,
     pub(super) genre: Option<&'a str>,
     pub(super) artist_display: Option<&'a str>,
+    pub(super) composer: Option<&'a str>,
+    pub(super) work: Option<&'a str>,
+    pub(super) movement_number: Option<u32>,
+    pub(super) bpm: Option<f32>,
+    pub(super) quality: Option<QualityBadge>,
 
     // mapped metadata
     pub(super) artists: Vec<Either<Cow<'a, ArtistInsertable<'a>>, Integer>>,
+    pub(super) genres: Vec<&'a str>,
 }
 
 // TODO: proper errors?
 impl<'a> TrackInsertable<'a> {
-    /// Try to create a insertable from the given options.
+    /// Try to create a insertable from the given options, with albums inferred by [`AlbumGrouping::Tag`].
     ///
     /// # Errors
     ///
@@ -46,7 +56,21 @@ impl<'a> TrackInsertable<'a> {
     /// - if the given `path` does not have components: parent, stem, ext
     ///
     /// Any other potential errors (like empty artist string) will be silently ignored.
+    #[cfg(test)]
     pub fn try_from_track(path: &'a Path, metadata: &'a TrackMetadata) -> Result<Self> {
+        Self::try_from_track_grouped(path, metadata, AlbumGrouping::Tag)
+    }
+
+    /// Try to create a insertable from the given options, with albums inferred by `grouping`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::try_from_track`].
+    pub fn try_from_track_grouped(
+        path: &'a Path,
+        metadata: &'a TrackMetadata,
+        grouping: AlbumGrouping,
+    ) -> Result<Self> {
         let (file_dir, file_stem, file_ext) = path_to_db_comp(path)?;
 
         let title = metadata
@@ -64,48 +88,48 @@ impl<'a> TrackInsertable<'a> {
             .as_ref()
             .filter(|v| !v.is_empty())
             .map(String::as_str);
-        let album_title = metadata
-            .album
+        let composer = metadata
+            .composer
             .as_ref()
             .filter(|v| !v.is_empty())
             .map(String::as_str);
-        let album_artist_display = metadata
-            .album_artist
+        let work = metadata
+            .work
             .as_ref()
             .filter(|v| !v.is_empty())
             .map(String::as_str);
-        let album_artists = metadata.album_artists.as_ref();
-
-        let album = if let (Some(album_title), Some(album_artist_display)) =
-            (album_title, album_artist_display)
-        {
-            let album_artists = album_artists
-                .map(|v| {
-                    v.iter()
-                        .filter(|v| !v.is_empty())
-                        .map(|v| Either::Left(Cow::Owned(ArtistInsertable { artist: v.as_str() })))
-                        .collect()
-                })
-                .unwrap_or_default();
-            Some(Either::Left(Cow::Owned(AlbumInsertable {
-                title: album_title,
-                artist_display: album_artist_display,
-                artists: album_artists,
-            })))
-        } else {
-            None
-        };
+        let album = album_from_metadata(file_dir, metadata, artist_display, grouping);
 
-        let artists = metadata
+        let artists: Vec<_> = metadata
             .artists
+            .iter()
+            .flatten()
+            .filter(|v| !v.is_empty())
+            .map(String::as_str)
+            .collect();
+        // sort names can only be assigned if there is one for each artist
+        let artists_sort = metadata
+            .artists_sort
             .as_ref()
-            .map(|v| {
-                v.iter()
-                    .filter(|v| !v.is_empty())
-                    .map(|v| Either::Left(Cow::Owned(ArtistInsertable { artist: v.as_str() })))
-                    .collect::<Vec<_>>()
+            .filter(|v| v.len() == artists.len());
+        let artists = artists
+            .into_iter()
+            .enumerate()
+            .map(|(idx, artist)| {
+                let sort_name = artists_sort.map(|v| v[idx].as_str());
+                Either::Left(Cow::Owned(ArtistInsertable { artist, sort_name }))
             })
-            .unwrap_or_default();
+            .collect();
+
+        // without separate values, the display value is the only genre
+        let genres = match &metadata.genres {
+            Some(genres) => genres
+                .iter()
+                .filter(|v| !v.is_empty())
+                .map(String::as_str)
+                .collect(),
+            None => genre.into_iter().collect(),
+        };
 
         Ok(Self {
             file_dir,
@@ -119,8 +143,14 @@ impl<'a> TrackInsertable<'a> {
             title,
             genre,
             artist_display,
+            composer,
+            work,
+            movement_number: metadata.movement_number,
+            bpm: metadata.bpm,
+            quality: metadata.quality,
 
             artists,
+            genres,
         })
     }
 
@@ -158,6 +188,11 @@ impl<'a> TrackInsertable<'a> {
             title: self.title,
             genre: self.genre,
             artist_display: self.artist_display,
+            composer: self.composer,
+            work: self.work,
+            movement_number: self.movement_number,
+            bpm: self.bpm,
+            quality: self.quality,
         };
 
         let _ = insert_metadata.upsert(conn).context("tracks_metadata")?;
@@ -178,6 +213,17 @@ impl<'a> TrackInsertable<'a> {
             insert_mapping.upsert(conn).context("tracks_artist")?;
         }
 
+        // same as for artists, the track may now have different genres
+        let _ = delete_tracks_genres_mapping_for(conn, id)?;
+
+        for genre in &self.genres {
+            let genre = InsertGenre { genre }.upsert(conn).context("genres")?;
+
+            let insert_mapping = InsertTrackGenreMapping { track: id, genre };
+
+            insert_mapping.upsert(conn).context("tracks_genres")?;
+        }
+
         Ok(id)
     }
 }
@@ -279,16 +325,25 @@ struct InsertTrackMetadata<'a> {
     title: Option<&'a str>,
     genre: Option<&'a str>,
     artist_display: Option<&'a str>,
+    composer: Option<&'a str>,
+    work: Option<&'a str>,
+    movement_number: Option<u32>,
+    bpm: Option<f32>,
+    quality: Option<QualityBadge>,
 }
 
 impl InsertTrackMetadata<'_> {
     /// Insert or update the current data with the file as identifier.
     fn upsert(&self, conn: &Connection) -> Result<Integer> {
         let mut stmt = conn.prepare_cached(indoc! {"
-            INSERT INTO tracks_metadata (track, title, genre, artist_display)
-            VALUES (:track, :title, :genre, :artist_display)
+            INSERT INTO tracks_metadata (track, title, genre, artist_display, composer, work, movement_number, bpm, quality)
+            VALUES (:track, :title, :genre, :artist_display, :composer, :work, :movement_number, :bpm, :quality)
             ON CONFLICT(track) DO UPDATE SET 
-                title=excluded.title, genre=excluded.genre, artist_display=excluded.artist_display
+                title=excluded.title, genre=excluded.genre, artist_display=excluded.artist_display,
+                composer=excluded.composer, work=excluded.work, movement_number=excluded.movement_number,
+                -- keep a tapped tempo if the tag has none
+                bpm=COALESCE(excluded.bpm, tracks_metadata.bpm),
+                quality=excluded.quality
             RETURNING track;
         "})?;
 
@@ -298,6 +353,11 @@ impl InsertTrackMetadata<'_> {
                 ":title": self.title,
                 ":genre": self.genre,
                 ":artist_display": self.artist_display,
+                ":composer": self.composer,
+                ":work": self.work,
+                ":movement_number": self.movement_number,
+                ":bpm": self.bpm,
+                ":quality": self.quality.map(QualityBadge::number),
             },
             |row| row.get(0),
         )?;
@@ -331,13 +391,147 @@ impl InsertTrackArtistMapping {
     }
 }
 
+/// Stores references for insertion into `genres` directly
+#[derive(Debug, PartialEq)]
+struct InsertGenre<'a> {
+    genre: &'a str,
+}
+
+impl InsertGenre<'_> {
+    /// Insert the current data or get the id of the existing genre.
+    fn upsert(&self, conn: &Connection) -> Result<Integer> {
+        let mut stmt = conn.prepare_cached(indoc! {"
+            INSERT INTO genres (genre)
+            VALUES (:genre)
+            ON CONFLICT(genre) DO UPDATE SET genre=excluded.genre
+            RETURNING id;
+        "})?;
+
+        let id = stmt.query_row(named_params! {":genre": self.genre}, |row| row.get(0))?;
+
+        Ok(id)
+    }
+}
+
+/// Stores references for insertion into `tracks_genres` directly
+#[derive(Debug, PartialEq)]
+struct InsertTrackGenreMapping {
+    track: Integer,
+    genre: Integer,
+}
+
+impl InsertTrackGenreMapping {
+    /// Insert the current data, not caring about the id that was inserted
+    fn upsert(&self, conn: &Connection) -> Result<()> {
+        let mut stmt = conn.prepare_cached(indoc! {"
+            INSERT INTO tracks_genres (track, genre)
+            VALUES (:track, :genre)
+            ON CONFLICT(track, genre) DO NOTHING;
+        "})?;
+
+        stmt.execute(named_params! {
+            ":track": self.track,
+            ":genre": self.genre,
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Infer the album for a track in `file_dir` with `metadata`, according to `grouping`.
+fn album_from_metadata<'a>(
+    file_dir: &'a Path,
+    metadata: &'a TrackMetadata,
+    artist_display: Option<&'a str>,
+    grouping: AlbumGrouping,
+) -> Option<Either<Cow<'a, AlbumInsertable<'a>>, Integer>> {
+    let tag_title = metadata
+        .album
+        .as_ref()
+        .filter(|v| !v.is_empty())
+        .map(String::as_str);
+    let tag_artist_display = metadata
+        .album_artist
+        .as_ref()
+        .filter(|v| !v.is_empty())
+        .map(String::as_str);
+    let dir_title = file_dir.file_name().and_then(OsStr::to_str);
+    let dir_artist_display = file_dir
+        .parent()
+        .and_then(Path::file_name)
+        .and_then(OsStr::to_str);
+
+    let tag_sort_title = metadata
+        .album_sort
+        .as_ref()
+        .filter(|v| !v.is_empty())
+        .map(String::as_str);
+
+    let (album_title, album_artist_display) = match grouping {
+        AlbumGrouping::Tag => (tag_title?, tag_artist_display?),
+        AlbumGrouping::Directory => (
+            dir_title?,
+            tag_artist_display
+                .or(dir_artist_display)
+                .unwrap_or(UNKNOWN_ARTIST),
+        ),
+        AlbumGrouping::Hybrid => {
+            if let Some(tag_title) = tag_title {
+                // a album tag without a album artist is likely a single artist album
+                (
+                    tag_title,
+                    tag_artist_display
+                        .or(artist_display)
+                        .or(dir_artist_display)
+                        .unwrap_or(UNKNOWN_ARTIST),
+                )
+            } else {
+                (
+                    dir_title?,
+                    tag_artist_display
+                        .or(dir_artist_display)
+                        .unwrap_or(UNKNOWN_ARTIST),
+                )
+            }
+        }
+    };
+
+    let album_artists = metadata
+        .album_artists
+        .as_ref()
+        .filter(|_| tag_artist_display.is_some())
+        .map(|v| {
+            v.iter()
+                .filter(|v| !v.is_empty())
+                .map(|v| {
+                    Either::Left(Cow::Owned(ArtistInsertable {
+                        artist: v.as_str(),
+                        sort_name: None,
+                    }))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Either::Left(Cow::Owned(AlbumInsertable {
+        title: album_title,
+        artist_display: album_artist_display,
+        // the sort title only applies to the tagged album title
+        sort_title: tag_sort_title.filter(|_| tag_title == Some(album_title)),
+        artists: album_artists,
+    })))
+}
+
 #[cfg(test)]
 mod tests {
     use std::{path::Path, time::Duration};
 
     use crate::{
+        config::v2::server::metadata::AlbumGrouping,
         new_database::{
+            album_ops::{RowOrdering, get_all_albums},
             artist_insert::ArtistInsertable,
+            artist_ops::get_artist,
             test_utils::{gen_database, test_path},
             track_insert::{InsertTrackArtistMapping, InsertTrackMetadata, TrackInsertable},
             track_ops::{
@@ -405,6 +599,11 @@ mod tests {
             title: Some("test"),
             genre: Some("rock"),
             artist_display: Some("ArtistA"),
+            composer: None,
+            work: None,
+            movement_number: None,
+            bpm: None,
+            quality: None,
         };
 
         let id = metadata.upsert(&db).unwrap();
@@ -439,7 +638,10 @@ mod tests {
         let track_id = data.upsert(&db).unwrap();
         assert_eq!(track_id, 1);
 
-        let artist = ArtistInsertable { artist: "ArtistA" };
+        let artist = ArtistInsertable {
+            artist: "ArtistA",
+            sort_name: None,
+        };
 
         let artist_id = artist.try_insert_or_update(&db).unwrap();
         assert_eq!(artist_id, 1);
@@ -511,4 +713,100 @@ mod tests {
 
         assert_eq!(all_artists, &["ArtistB", "ArtistC"]);
     }
+
+    #[test]
+    fn should_store_sort_names() {
+        let db = gen_database();
+
+        let metadata = TrackMetadata {
+            artist: Some("The Beatles".to_string()),
+            artists: Some(vec!["The Beatles".to_string()]),
+            artists_sort: Some(vec!["Beatles, The".to_string()]),
+            album: Some("The White Album".to_string()),
+            album_artist: Some("The Beatles".to_string()),
+            album_sort: Some("White Album, The".to_string()),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        // a later track without sort tags should not remove them
+        let metadata = TrackMetadata {
+            artists_sort: None,
+            album_sort: None,
+            ..metadata
+        };
+        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        let artist = get_artist(&db.get_connection(), "The Beatles").unwrap();
+        assert_eq!(artist.sort_name.as_deref(), Some("Beatles, The"));
+
+        let albums = get_all_albums(&db.get_connection(), RowOrdering::IdAsc).unwrap();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(albums[0].sort_title.as_deref(), Some("White Album, The"));
+    }
+
+    /// Get the album title and artist display inferred for `path` and `metadata`.
+    fn inferred_album(
+        path: &Path,
+        metadata: &TrackMetadata,
+        grouping: AlbumGrouping,
+    ) -> Option<(String, String)> {
+        let insertable = TrackInsertable::try_from_track_grouped(path, metadata, grouping).unwrap();
+        let album = insertable.album?.left().unwrap();
+
+        Some((album.title.to_string(), album.artist_display.to_string()))
+    }
+
+    #[test]
+    fn should_infer_album_by_grouping() {
+        let path = &test_path(Path::new("/music/ArtistDir/AlbumDir/file.mp3"));
+        let untagged = TrackMetadata::default();
+        let album_only = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            album: Some("AlbumA".to_string()),
+            ..Default::default()
+        };
+        let tagged = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistB".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(inferred_album(path, &untagged, AlbumGrouping::Tag), None);
+        assert_eq!(inferred_album(path, &album_only, AlbumGrouping::Tag), None);
+        assert_eq!(
+            inferred_album(path, &tagged, AlbumGrouping::Tag),
+            Some(("AlbumA".to_string(), "ArtistB".to_string()))
+        );
+
+        assert_eq!(
+            inferred_album(path, &untagged, AlbumGrouping::Directory),
+            Some(("AlbumDir".to_string(), "ArtistDir".to_string()))
+        );
+        assert_eq!(
+            inferred_album(path, &tagged, AlbumGrouping::Directory),
+            Some(("AlbumDir".to_string(), "ArtistB".to_string()))
+        );
+
+        assert_eq!(
+            inferred_album(path, &untagged, AlbumGrouping::Hybrid),
+            Some(("AlbumDir".to_string(), "ArtistDir".to_string()))
+        );
+        assert_eq!(
+            inferred_album(path, &album_only, AlbumGrouping::Hybrid),
+            Some(("AlbumA".to_string(), "ArtistA".to_string()))
+        );
+        assert_eq!(
+            inferred_album(path, &tagged, AlbumGrouping::Hybrid),
+            Some(("AlbumA".to_string(), "ArtistB".to_string()))
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::{
     ffi::OsString,
-    path::{Path, PathBuf},
+    path::{MAIN_SEPARATOR, Path, PathBuf},
     time::Duration,
 };
 
@@ -9,10 +9,15 @@ use either::Either;
 use indoc::{formatdoc, indoc};
 use rusqlite::{Connection, OptionalExtension, Row, ToSql, named_params};
 
+use crate::audio_analysis::{EnergyLevel, Mood, SoundProfile};
+use crate::audio_verify::{VerifyError, VerifyFailure};
+use crate::loudness::Loudness;
 use crate::new_database::{
     artist_ops::{ArtistRead, common_row_to_artistread},
     track_insert::{path_to_db_comp, validate_path},
 };
+use crate::smart_playlist::SmartQuery;
+use crate::track::{DurationFmtShort, QualityBadge};
 
 use super::Integer;
 
@@ -40,6 +45,14 @@ pub(super) fn count_all_track_artist_mapping(conn: &Connection) -> Result<Intege
     Ok(count)
 }
 
+/// Count all rows currently in the `tracks_genres` database
+#[cfg(test)]
+pub(super) fn count_all_track_genre_mapping(conn: &Connection) -> Result<Integer> {
+    let count = conn.query_row("SELECT COUNT(track) FROM tracks_genres;", [], |v| v.get(0))?;
+
+    Ok(count)
+}
+
 /// The lowest information required for a [`TrackRead`] to identify a Album.
 #[derive(Debug, Clone, PartialEq)]
 pub struct AlbumRead {
@@ -146,7 +159,7 @@ pub fn get_all_tracks(conn: &Connection, order: RowOrdering) -> Result<Vec<Track
 // maybe this should be in "artist_ops" instead?
 pub fn get_all_artists_for_track(conn: &Connection, track_id: Integer) -> Result<Vec<ArtistRead>> {
     let mut stmt = conn.prepare(indoc! {"
-        SELECT artists.id AS artist_id, artists.artist FROM artists
+        SELECT artists.id AS artist_id, artists.artist, artists.sort_name FROM artists
         INNER JOIN tracks_artists ON tracks_artists.track=:track_id
         WHERE artists.id=tracks_artists.artist;
     "})?;
@@ -215,6 +228,370 @@ pub fn set_last_position(conn: &Connection, track: &Path, to: Option<Duration>)
     Ok(())
 }
 
+/// Custom start and end offsets of a track, to for example skip long silence, a hidden track or play a radio edit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrackOffsets {
+    /// Where to start playing, instead of the beginning
+    pub start: Option<Duration>,
+    /// Where to stop playing and go to the next track, instead of the end
+    pub end: Option<Duration>,
+}
+
+impl TrackOffsets {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start.is_none() && self.end.is_none()
+    }
+
+    /// Parse offsets written like [`Display`](std::fmt::Display) does: `START - END`, each as `[[H:]M:]S`.
+    ///
+    /// Either side may be empty to not set it, a single value without `-` is only the start.
+    ///
+    /// # Errors
+    ///
+    /// - if a side is not a valid time
+    /// - if the end is not after the start
+    pub fn parse(value: &str) -> Result<Self> {
+        let (start, end) = value.split_once('-').unwrap_or((value, ""));
+        let offsets = Self {
+            start: parse_offset(start)?,
+            end: parse_offset(end)?,
+        };
+
+        if let (Some(start), Some(end)) = (offsets.start, offsets.end)
+            && end <= start
+        {
+            bail!("The end offset has to be after the start offset");
+        }
+
+        Ok(offsets)
+    }
+
+    /// Get the `#EXTVLCOPT` directives of a EXTM3U playlist for these offsets, to be written before the entry.
+    #[must_use]
+    pub fn extm3u_directives(&self) -> Vec<String> {
+        let start = self
+            .start
+            .map(|v| format!("#EXTVLCOPT:start-time={}", v.as_secs()));
+        let end = self
+            .end
+            .map(|v| format!("#EXTVLCOPT:stop-time={}", v.as_secs()));
+
+        start.into_iter().chain(end).collect()
+    }
+}
+
+impl std::fmt::Display for TrackOffsets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(start) = self.start {
+            write!(f, "{}", DurationFmtShort(start))?;
+        }
+        write!(f, " - ")?;
+        if let Some(end) = self.end {
+            write!(f, "{}", DurationFmtShort(end))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a single offset as `[[H:]M:]S`, empty is [`None`].
+fn parse_offset(value: &str) -> Result<Option<Duration>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let mut secs: u64 = 0;
+    let mut parts = 0;
+    for part in value.split(':') {
+        let Ok(part) = part.trim().parse::<u64>() else {
+            bail!("\"{value}\" is not a time like \"1:30\"");
+        };
+        secs = secs.saturating_mul(60).saturating_add(part);
+        parts += 1;
+    }
+    if parts > 3 {
+        bail!("\"{value}\" has more parts than hours, minutes and seconds");
+    }
+
+    Ok(Some(Duration::from_secs(secs)))
+}
+
+/// Get the [`TrackOffsets`] for the given `track`, which are empty if the track is not in the database.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_offsets(conn: &Connection, track: &Path) -> Result<TrackOffsets> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        SELECT start_offset, end_offset FROM tracks
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let result: Option<(Option<Integer>, Option<Integer>)> = stmt
+        .query_row(
+            named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext},
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let to_duration = |v: Integer| Duration::from_secs(u64::try_from(v.max(0)).unwrap());
+    let offsets = result.map_or_else(TrackOffsets::default, |(start, end)| TrackOffsets {
+        start: start.map(to_duration),
+        end: end.map(to_duration),
+    });
+
+    Ok(offsets)
+}
+
+/// Set the [`TrackOffsets`] for the given `track`, empty offsets to remove them.
+pub fn set_offsets(conn: &Connection, track: &Path, offsets: TrackOffsets) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let start_offset = offsets.start.map(|v| v.as_secs());
+    let end_offset = offsets.end.map(|v| v.as_secs());
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET start_offset=:start_offset, end_offset=:end_offset
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":start_offset": start_offset, ":end_offset": end_offset})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get the tempo in beats per minute of the given `track`, [`None`] if it is not known.
+pub fn get_bpm(conn: &Connection, track: &Path) -> Result<Option<f32>> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        SELECT tracks_metadata.bpm FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let bpm: Option<Option<f32>> = stmt
+        .query_row(
+            named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext},
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(bpm.flatten())
+}
+
+/// Set the tempo in beats per minute of the given `track`, like a tapped one.
+///
+/// The tempo is kept on a rescan, unless the file is then tagged with one.
+pub fn set_bpm(conn: &Connection, track: &Path, bpm: f32) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks_metadata SET bpm=:bpm
+        WHERE tracks_metadata.track=(
+            SELECT tracks.id FROM tracks
+            WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext
+        );
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":bpm": bpm})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get the analyzed [`Loudness`] of the given `track`, [`None`] if it was not analyzed.
+pub fn get_loudness(conn: &Connection, track: &Path) -> Result<Option<Loudness>> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        SELECT tracks_metadata.loudness, tracks_metadata.loudness_peak FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let loudness: Option<(Option<f64>, Option<f32>)> = stmt
+        .query_row(
+            named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext},
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    Ok(loudness.and_then(|(integrated, peak)| {
+        Some(Loudness {
+            integrated: integrated?,
+            peak: peak.unwrap_or(1.0),
+        })
+    }))
+}
+
+/// Set the analyzed [`Loudness`] of the given `track`, to normalize it if it has no `ReplayGain` tags.
+///
+/// The loudness is kept on a rescan.
+pub fn set_loudness(conn: &Connection, track: &Path, loudness: &Loudness) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks_metadata SET loudness=:loudness, loudness_peak=:loudness_peak
+        WHERE tracks_metadata.track=(
+            SELECT tracks.id FROM tracks
+            WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext
+        );
+    "})?;
+
+    let affected = stmt.execute(named_params! {
+        ":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext,
+        ":loudness": loudness.integrated, ":loudness_peak": loudness.peak
+    })?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get the paths of all tracks whose loudness was not analyzed yet, see [`set_loudness`].
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_without_loudness(conn: &Connection) -> Result<Vec<PathBuf>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT tracks.file_dir, tracks.file_stem, tracks.file_ext
+        FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        WHERE tracks_metadata.loudness IS NULL
+        ORDER BY tracks.file_dir, tracks.file_stem;
+    "})?;
+
+    let result: Vec<PathBuf> = stmt
+        .query_map(named_params! {}, |row| {
+            let file_dir: String = row.get("file_dir")?;
+            let file_stem: String = row.get("file_stem")?;
+            let file_ext: String = row.get("file_ext")?;
+
+            Ok(PathBuf::from(file_dir).join(format!("{file_stem}.{file_ext}")))
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// A region of a track that could not be decoded and was muted instead, see [`set_damaged`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamagedRegion {
+    pub start: Duration,
+    pub end: Duration,
+}
+
+impl std::fmt::Display for DamagedRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // a damaged region is usually shorter than a second
+        write!(
+            f,
+            "{}.{:03}-{}.{:03}",
+            DurationFmtShort(self.start),
+            self.start.subsec_millis(),
+            DurationFmtShort(self.end),
+            self.end.subsec_millis()
+        )
+    }
+}
+
+/// Flag the given `track` as damaged in the `regions`, for it to be repaired later, or clear the flag if `regions` is
+/// empty.
+pub fn set_damaged(conn: &Connection, track: &Path, regions: &[DamagedRegion]) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let damaged = (!regions.is_empty()).then(|| {
+        regions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET damaged=:damaged
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":damaged": damaged})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get the paths of all tracks flagged as damaged, and their damaged regions.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_damaged_tracks(conn: &Connection) -> Result<Vec<(PathBuf, String)>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.damaged
+        FROM tracks
+        WHERE tracks.damaged IS NOT NULL
+        ORDER BY tracks.file_dir, tracks.file_stem;
+    "})?;
+
+    let result: Vec<(PathBuf, String)> = stmt
+        .query_map(named_params! {}, |row| {
+            let file_dir: String = row.get("file_dir")?;
+            let file_stem: String = row.get("file_stem")?;
+            let file_ext: String = row.get("file_ext")?;
+            let damaged: String = row.get("damaged")?;
+
+            Ok((
+                PathBuf::from(file_dir).join(format!("{file_stem}.{file_ext}")),
+                damaged,
+            ))
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
 /// Get all tracks associated with the given album.
 ///
 /// # Panics
@@ -255,6 +632,37 @@ pub fn get_tracks_from_album(
     Ok(result)
 }
 
+/// Get all tracks of the album with `album_id`, ordered by their path.
+///
+/// The path is used as the order, as the track number is not stored and album files are commonly prefixed by it.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_from_album_id(conn: &Connection, album_id: Integer) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id=tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.album=:album_id
+        ORDER BY tracks.file_dir ASC, tracks.file_stem ASC, tracks.file_ext ASC;
+    "})?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":album_id": album_id}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
 /// Get all tracks associated with the given artist.
 ///
 /// # Panics
@@ -293,9 +701,9 @@ pub fn get_tracks_from_artist(
     Ok(result)
 }
 
-/// Get all tracks associated with a genre.
+/// Get all tracks associated with a genre, including tracks that have other genres too.
 ///
-/// Note `None` will use `IS NULL` to find all tracks without a genre.
+/// Note `None` will find all tracks without any genre.
 ///
 /// # Panics
 ///
@@ -306,9 +714,21 @@ pub fn get_tracks_from_genre(
     order: RowOrdering,
 ) -> Result<Vec<TrackRead>> {
     let (where_clause, params): (&str, &[(&str, &str)]) = if let Some(genre) = genre {
-        ("tracks_metadata.genre=:genre", &[(":genre", genre)])
+        (
+            indoc! {"
+                tracks.id IN (
+                    SELECT tracks_genres.track FROM tracks_genres
+                    INNER JOIN genres ON tracks_genres.genre = genres.id
+                    WHERE genres.genre=:genre
+                )
+            "},
+            &[(":genre", genre)],
+        )
     } else {
-        ("tracks_metadata.genre IS NULL", &[])
+        (
+            "tracks.id NOT IN (SELECT tracks_genres.track FROM tracks_genres)",
+            &[],
+        )
     };
 
     let stmt = formatdoc! {"
@@ -337,36 +757,26 @@ pub fn get_tracks_from_genre(
     Ok(result)
 }
 
-/// Get all tracks associated with the given directory.
+/// Get all tracks composed by `composer`, grouped by work and ordered by movement.
 ///
 /// # Panics
 ///
 /// If the database schema does not match what is expected.
-pub fn get_tracks_from_directory(
-    conn: &Connection,
-    dir: &Path,
-    order: RowOrdering,
-) -> Result<Vec<TrackRead>> {
-    validate_path(dir)?;
-    let dir = dir.to_string_lossy();
-
-    let stmt = formatdoc! {"
-        SELECT 
+pub fn get_tracks_from_composer(conn: &Connection, composer: &str) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT
             tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
             tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
             albums.id AS album_id, albums.title AS album_title
         FROM tracks
-        LEFT JOIN tracks_metadata ON tracks.id=tracks_metadata.track
+        INNER JOIN tracks_metadata ON tracks.id=tracks_metadata.track
         LEFT JOIN albums ON tracks.album = albums.id
-        WHERE tracks.file_dir=:dir
-        ORDER BY {};
-        ",
-        order.as_sql()
-    };
-    let mut stmt = conn.prepare(&stmt)?;
+        WHERE tracks_metadata.composer=:composer
+        ORDER BY tracks_metadata.work ASC, tracks_metadata.movement_number ASC, tracks.file_dir ASC, tracks.file_stem ASC;
+    "})?;
 
     let result: Vec<TrackRead> = stmt
-        .query_map(named_params! {":dir": dir}, |row| {
+        .query_map(named_params! {":composer": composer}, |row| {
             let trackread = common_row_to_trackread(conn, row);
 
             Ok(trackread)
@@ -376,16 +786,84 @@ pub fn get_tracks_from_directory(
     Ok(result)
 }
 
-/// Get all tracks that match a genre `like`.
+/// Get all movements of `work`, grouped by composer and ordered by movement.
 ///
 /// # Panics
 ///
 /// If the database schema does not match what is expected.
-pub fn get_tracks_from_genre_like(
-    conn: &Connection,
-    genre_like: &str,
-    order: RowOrdering,
-) -> Result<Vec<TrackRead>> {
+pub fn get_tracks_from_work(conn: &Connection, work: &str) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id=tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks_metadata.work=:work
+        ORDER BY tracks_metadata.composer ASC, tracks_metadata.movement_number ASC, tracks.file_dir ASC, tracks.file_stem ASC;
+    "})?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":work": work}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all tracks associated with the given directory.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_from_directory(
+    conn: &Connection,
+    dir: &Path,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    validate_path(dir)?;
+    let dir = dir.to_string_lossy();
+
+    let stmt = formatdoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id=tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.file_dir=:dir
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":dir": dir}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all tracks that have any genre matching `like`.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_from_genre_like(
+    conn: &Connection,
+    genre_like: &str,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
     let stmt = formatdoc! {"
         SELECT
             tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
@@ -394,7 +872,11 @@ pub fn get_tracks_from_genre_like(
         FROM tracks
         INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
         LEFT JOIN albums ON tracks.album = albums.id
-        WHERE tracks_metadata.genre LIKE :genre_like
+        WHERE tracks.id IN (
+            SELECT tracks_genres.track FROM tracks_genres
+            INNER JOIN genres ON tracks_genres.genre = genres.id
+            WHERE genres.genre LIKE :genre_like
+        )
         ORDER BY {};
         ",
         order.as_sql()
@@ -536,16 +1018,23 @@ pub fn track_exists(conn: &Connection, track: &Path) -> Result<bool> {
     Ok(exists)
 }
 
-/// Get all genres that are currently in the database.
-/// Note that `NULL` will be mapped to `[unknown]`
+/// Get all genres that are currently referenced by a track.
+/// Note that tracks without any genre will add a `[unknown]` entry.
 ///
 /// # Panics
 ///
 /// If sqlite somehow does not return what is expected.
 pub fn all_distinct_genres(conn: &Connection) -> Result<Vec<String>> {
     let mut stmt = conn.prepare(indoc! {"
-        SELECT DISTINCT tracks_metadata.genre
-        FROM tracks_metadata
+        SELECT genres.genre
+        FROM genres
+        WHERE genres.id IN (SELECT tracks_genres.genre FROM tracks_genres)
+        UNION ALL
+        SELECT NULL
+        WHERE EXISTS (
+            SELECT 1 FROM tracks_metadata
+            WHERE tracks_metadata.track NOT IN (SELECT tracks_genres.track FROM tracks_genres)
+        )
         ",
     })?;
 
@@ -559,6 +1048,71 @@ pub fn all_distinct_genres(conn: &Connection) -> Result<Vec<String>> {
     Ok(result)
 }
 
+/// Get all composers that are currently in the database, tracks without one are not included.
+///
+/// # Panics
+///
+/// If sqlite somehow does not return what is expected.
+pub fn all_distinct_composers(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT tracks_metadata.composer
+        FROM tracks_metadata
+        WHERE tracks_metadata.composer IS NOT NULL
+        ",
+    })?;
+
+    let result: Vec<String> = stmt
+        .query_map(named_params! {}, |row| row.get(0))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all classical works that are currently in the database, tracks without one are not included.
+///
+/// # Panics
+///
+/// If sqlite somehow does not return what is expected.
+pub fn all_distinct_works(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT tracks_metadata.work
+        FROM tracks_metadata
+        WHERE tracks_metadata.work IS NOT NULL
+        ",
+    })?;
+
+    let result: Vec<String> = stmt
+        .query_map(named_params! {}, |row| row.get(0))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get up to `limit` distinct artist names and track titles that match `like`.
+///
+/// # Panics
+///
+/// If sqlite somehow does not return what is expected.
+pub fn all_distinct_names_like(conn: &Connection, like: &str, limit: u32) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT artists.artist FROM artists
+        WHERE artists.artist LIKE :like
+        UNION
+        SELECT tracks_metadata.title FROM tracks_metadata
+        WHERE tracks_metadata.title LIKE :like
+        LIMIT :limit
+        ",
+    })?;
+
+    let result: Vec<String> = stmt
+        .query_map(named_params! {":like": like, ":limit": limit}, |row| {
+            row.get(0)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
 /// Get all distinct directories.
 ///
 /// # Panics
@@ -581,6 +1135,82 @@ pub fn all_distinct_directories(conn: &Connection) -> Result<Vec<String>> {
     Ok(result)
 }
 
+/// Get all tracks without a album.
+///
+/// If `dir` is [`Some`], only tracks directly in that directory are returned.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_without_album(
+    conn: &Connection,
+    dir: Option<&Path>,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    let dir = dir
+        .map(|dir| {
+            validate_path(dir)?;
+            Ok::<_, anyhow::Error>(dir.to_string_lossy())
+        })
+        .transpose()?;
+    let (where_clause, params): (&str, &[(&str, &dyn ToSql)]) = if let Some(dir) = &dir {
+        (
+            "tracks.album IS NULL AND tracks.file_dir=:dir",
+            &[(":dir", dir)],
+        )
+    } else {
+        ("tracks.album IS NULL", &[])
+    };
+
+    let stmt = formatdoc! {"
+        SELECT
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id=tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE {where_clause}
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(params, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all distinct directories that contain tracks without a album.
+///
+/// # Panics
+///
+/// If sqlite somehow does not return what is expected.
+pub fn all_distinct_directories_without_album(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT tracks.file_dir
+        FROM tracks
+        WHERE tracks.album IS NULL
+        ",
+    })?;
+
+    let result: Vec<String> = stmt
+        .query_map(named_params! {}, |row| {
+            let res = row.get(0).unwrap();
+            Ok(res)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
 /// Remove all tracks-artists mappings for the given path or track id.
 ///
 /// Returns the number of deleted rows. Will return `Ok(0)` if the query did not do anything.
@@ -628,232 +1258,1899 @@ pub fn delete_tracks_artists_mapping_for(
     Ok(affected)
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{
-        ffi::{OsStr, OsString},
-        path::{Path, PathBuf},
-        time::Duration,
-    };
+/// Remove all tracks-genres mappings for the given track id.
+///
+/// Returns the number of deleted rows. Will return `Ok(0)` if the query did not do anything.
+pub fn delete_tracks_genres_mapping_for(conn: &Connection, track: Integer) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        DELETE FROM tracks_genres
+        WHERE tracks_genres.track = :track_id;
+    "})?;
 
-    use either::Either;
-    use pretty_assertions::assert_eq;
+    let affected = stmt
+        .execute(named_params! {":track_id": track})
+        .optional()?
+        .unwrap_or_default();
 
-    use crate::{
-        new_database::{
-            album_insert::AlbumInsertable,
-            artist_insert::ArtistInsertable,
-            test_utils::{gen_database, test_path},
-            track_insert::TrackInsertable,
-            track_ops::{
-                AlbumRead, ArtistRead, RowOrdering, TrackRead, all_distinct_directories,
-                all_distinct_genres, count_all_track_artist_mapping,
-                delete_tracks_artists_mapping_for, get_all_tracks, get_last_position,
-                get_track_from_path, get_tracks_from_album, get_tracks_from_artist,
-                get_tracks_from_directory, get_tracks_from_genre, get_tracks_from_genre_like,
-                set_last_position, track_exists,
-            },
-        },
-        track::TrackMetadata,
-    };
+    Ok(affected)
+}
 
-    use super::get_all_artists_for_track;
+/// Remove all genres that are not referenced by any track.
+///
+/// Returns the number of deleted rows. Will return `Ok(0)` if the query did not do anything.
+pub fn delete_all_unreferenced_genres(conn: &Connection) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        DELETE FROM genres
+        WHERE genres.id NOT IN (SELECT tracks_genres.genre FROM tracks_genres);
+    "})?;
 
-    #[test]
-    fn artists_for_track() {
-        let db = gen_database();
+    let affected = stmt
+        .execute(named_params! {})
+        .optional()?
+        .unwrap_or_default();
 
-        let track = TrackInsertable {
-            file_dir: Path::new("/somewhere"),
-            file_stem: OsStr::new("file"),
-            file_ext: OsStr::new("ext"),
-            duration: Some(Duration::from_secs(10)),
-            last_position: None,
-            album: Some(Either::Left(
-                AlbumInsertable {
-                    title: "AlbumA",
-                    artist_display: "ArtistA",
-                    artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
-                }
-                .into(),
-            )),
-            title: Some("file test"),
-            genre: None,
-            artist_display: Some("ArtistA feat. ArtistB"),
-            artists: vec![
-                Either::Left(ArtistInsertable { artist: "ArtistA" }.into()),
-                Either::Left(ArtistInsertable { artist: "ArtistB" }.into()),
-            ],
-        };
-        let track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
+    Ok(affected)
+}
 
-        let mut all_artists: Vec<String> =
-            get_all_artists_for_track(&db.get_connection(), track_id)
-                .unwrap()
-                .into_iter()
-                .map(|v| v.name)
-                .collect();
-        // just making sure they are consistently ordered
-        all_artists.sort();
+/// Get the `WHERE` bounds to select all `file_dir`s that are `dir` or inside of it.
+///
+/// Returns `(dir, lower, upper)`, to be used like `file_dir=:dir OR (file_dir >= :lower AND file_dir < :upper)`,
+/// which unlike `LIKE` does not need escaping and can use the index.
+fn dir_prefix_bounds(dir: &Path) -> Result<(&str, String, String)> {
+    validate_path(dir)?;
+    // checked in "validate_path"
+    let dir = dir.to_str().unwrap().trim_end_matches(MAIN_SEPARATOR);
 
-        assert_eq!(all_artists, &["ArtistA", "ArtistB"]);
+    let lower = format!("{dir}{MAIN_SEPARATOR}");
+    // the next character after the separator, so that everything starting with "lower" is included
+    let upper = format!("{dir}{}", char::from(MAIN_SEPARATOR as u8 + 1));
+
+    Ok((dir, lower, upper))
+}
+
+/// Change the path of all tracks at `from` to be at `to`, for example after the file or directory got renamed or moved.
+///
+/// If `from` is a file, the track is changed directly, if it is a directory, all tracks inside of it are changed.
+/// All data, like `last_position`, stays associated with the tracks.
+///
+/// If `to` is not a valid track path anymore (like a missing extension), the track is removed instead.
+///
+/// Returns the number of changed tracks.
+///
+/// # Panics
+///
+/// If the paths are not UTF-8, which is checked beforehand.
+pub fn rename_tracks_path(conn: &Connection, from: &Path, to: &Path) -> Result<usize> {
+    validate_path(to)?;
+    let (from_dir, lower, upper) = dir_prefix_bounds(from)?;
+    // checked in "validate_path"
+    let to_dir = to.to_str().unwrap().trim_end_matches(MAIN_SEPARATOR);
+
+    let transaction = conn.unchecked_transaction()?;
+
+    let mut affected = 0;
+
+    // "from" as a file
+    if let Ok((file_dir, file_stem, file_ext)) = path_to_db_comp(from) {
+        let params = named_params! {
+            ":file_dir": file_dir.to_str().unwrap(),
+            ":file_stem": file_stem.to_str().unwrap(),
+            ":file_ext": file_ext.to_str().unwrap(),
+        };
+
+        if let Ok((to_file_dir, to_file_stem, to_file_ext)) = path_to_db_comp(to) {
+            // "OR REPLACE" as there may be a stale entry for the destination
+            let mut stmt = transaction.prepare_cached(indoc! {"
+                UPDATE OR REPLACE tracks SET file_dir=:to_file_dir, file_stem=:to_file_stem, file_ext=:to_file_ext
+                WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+            "})?;
+            affected += stmt.execute(named_params! {
+                ":file_dir": file_dir.to_str().unwrap(),
+                ":file_stem": file_stem.to_str().unwrap(),
+                ":file_ext": file_ext.to_str().unwrap(),
+                ":to_file_dir": to_file_dir.to_str().unwrap(),
+                ":to_file_stem": to_file_stem.to_str().unwrap(),
+                ":to_file_ext": to_file_ext.to_str().unwrap(),
+            })?;
+        } else {
+            let mut stmt = transaction.prepare_cached(indoc! {"
+                DELETE FROM tracks
+                WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+            "})?;
+            affected += stmt.execute(params)?;
+        }
     }
 
-    #[test]
-    fn all_tracks() {
-        let db = gen_database();
+    // "from" as a directory
+    let dirs: Vec<String> = {
+        let mut stmt = transaction.prepare_cached(indoc! {"
+            SELECT DISTINCT tracks.file_dir FROM tracks
+            WHERE tracks.file_dir=:dir OR (tracks.file_dir >= :lower AND tracks.file_dir < :upper);
+        "})?;
 
-        let track = TrackInsertable {
-            file_dir: Path::new("/somewhere"),
+        stmt.query_map(
+            named_params! {":dir": from_dir, ":lower": lower, ":upper": upper},
+            |row| row.get(0),
+        )?
+        .collect::<Result<_, rusqlite::Error>>()?
+    };
+
+    {
+        let mut stmt = transaction.prepare_cached(indoc! {"
+            UPDATE OR REPLACE tracks SET file_dir=:to_file_dir
+            WHERE tracks.file_dir=:file_dir;
+        "})?;
+
+        for dir in dirs {
+            let to_file_dir = format!("{to_dir}{}", &dir[from_dir.len()..]);
+            affected +=
+                stmt.execute(named_params! {":file_dir": dir, ":to_file_dir": to_file_dir})?;
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(affected)
+}
+
+/// Remove all tracks at `path`, for example after the file or directory got deleted.
+///
+/// If `path` is a file, only that track is removed, if it is a directory, all tracks inside of it are removed.
+///
+/// Returns the number of removed tracks.
+///
+/// # Panics
+///
+/// If the paths are not UTF-8, which is checked beforehand.
+pub fn delete_tracks_path(conn: &Connection, path: &Path) -> Result<usize> {
+    let (dir, lower, upper) = dir_prefix_bounds(path)?;
+
+    let mut affected = 0;
+
+    if let Ok((file_dir, file_stem, file_ext)) = path_to_db_comp(path) {
+        let mut stmt = conn.prepare_cached(indoc! {"
+            DELETE FROM tracks
+            WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+        "})?;
+        affected += stmt.execute(named_params! {
+            ":file_dir": file_dir.to_str().unwrap(),
+            ":file_stem": file_stem.to_str().unwrap(),
+            ":file_ext": file_ext.to_str().unwrap(),
+        })?;
+    }
+
+    let mut stmt = conn.prepare_cached(indoc! {"
+        DELETE FROM tracks
+        WHERE tracks.file_dir=:dir OR (tracks.file_dir >= :lower AND tracks.file_dir < :upper);
+    "})?;
+    affected += stmt.execute(named_params! {":dir": dir, ":lower": lower, ":upper": upper})?;
+
+    Ok(affected)
+}
+
+/// Mark the track at `track` as played, increasing its play count.
+pub fn mark_played(conn: &Connection, track: &Path) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET play_count=play_count + 1, last_played_at=:now
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":now": now})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// The highest rating of a track, in stars.
+pub const RATING_MAX: u8 = 5;
+
+/// Merge play statistics imported from another player into the track at `track`: the higher play count and the later
+/// last play are kept, the `rating` is only set if the track is not rated yet.
+///
+/// Returns whether the track was found.
+pub fn merge_play_stats(
+    conn: &Connection,
+    track: &Path,
+    play_count: Option<u64>,
+    last_played_at: Option<chrono::DateTime<chrono::Utc>>,
+    rating: Option<u8>,
+) -> Result<bool> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let play_count = play_count.map(|v| Integer::try_from(v).unwrap_or(Integer::MAX));
+    let last_played_at = last_played_at.map(|v| v.to_rfc3339());
+    let rating = rating.map(|v| v.clamp(1, RATING_MAX));
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET
+            play_count=MAX(play_count, COALESCE(:play_count, 0)),
+            last_played_at=CASE
+                WHEN :last_played_at IS NOT NULL AND (last_played_at IS NULL OR last_played_at < :last_played_at)
+                THEN :last_played_at
+                ELSE last_played_at
+            END,
+            rating=COALESCE(rating, :rating)
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {
+        ":file_dir": file_dir,
+        ":file_stem": file_stem,
+        ":file_ext": file_ext,
+        ":play_count": play_count,
+        ":last_played_at": last_played_at,
+        ":rating": rating,
+    })?;
+
+    Ok(affected > 0)
+}
+
+/// Get the rating of the track at `track` in stars, [`None`] if not rated.
+pub fn get_rating(conn: &Connection, track: &Path) -> Result<Option<u8>> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        SELECT tracks.rating FROM tracks
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let rating: Option<Option<Integer>> = stmt
+        .query_row(
+            named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext},
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(rating.flatten().and_then(|v| u8::try_from(v).ok()))
+}
+
+/// Set the rating of the track at `track` in stars, clamped to `1..=RATING_MAX`, [`None`] to remove it.
+pub fn set_rating(conn: &Connection, track: &Path, rating: Option<u8>) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let rating = rating.map(|v| v.clamp(1, RATING_MAX));
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET rating=:rating
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":rating": rating})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Mark the track at `track` as skipped, increasing its skip count.
+pub fn mark_skipped(conn: &Connection, track: &Path) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET skip_count=skip_count + 1
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(
+        named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext},
+    )?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Record that the track at `track` was played from `started_at` on, for `listened` in total.
+///
+/// Returns whether the track was found, tracks outside of the library are not recorded.
+pub fn record_play(
+    conn: &Connection,
+    track: &Path,
+    started_at: chrono::DateTime<chrono::Utc>,
+    listened: Duration,
+) -> Result<bool> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let listened = Integer::try_from(listened.as_secs()).unwrap_or(Integer::MAX);
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        INSERT INTO plays (track, started_at, listened)
+        SELECT tracks.id, :started_at, :listened FROM tracks
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {
+        ":file_dir": file_dir,
+        ":file_stem": file_stem,
+        ":file_ext": file_ext,
+        ":started_at": started_at.to_rfc3339(),
+        ":listened": listened,
+    })?;
+
+    Ok(affected > 0)
+}
+
+/// Get the paths and album titles of all tracks titled `title` by `artist`, ignoring the case.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn find_tracks_by_tags(
+    conn: &Connection,
+    artist: &str,
+    title: &str,
+) -> Result<Vec<(PathBuf, Option<String>)>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT tracks.file_dir, tracks.file_stem, tracks.file_ext, albums.title AS album_title
+        FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks_metadata.title = :title COLLATE NOCASE
+            AND tracks_metadata.artist_display = :artist COLLATE NOCASE;
+    "})?;
+
+    let result = stmt
+        .query_map(named_params! {":artist": artist, ":title": title}, |row| {
+            let file_dir: String = row.get("file_dir")?;
+            let file_stem: String = row.get("file_stem")?;
+            let file_ext: String = row.get("file_ext")?;
+            let album: Option<String> = row.get("album_title")?;
+
+            Ok((
+                PathBuf::from(file_dir).join(format!("{file_stem}.{file_ext}")),
+                album,
+            ))
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// A color label of a track, for quick manual curation.
+///
+/// Stored as [`TrackLabel::number`] in `tracks.label`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TrackLabel {
+    Red,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Magenta,
+}
+
+impl TrackLabel {
+    /// All labels, in the order of their numbers.
+    pub const ALL: [Self; 6] = [
+        Self::Red,
+        Self::Yellow,
+        Self::Green,
+        Self::Cyan,
+        Self::Blue,
+        Self::Magenta,
+    ];
+
+    /// The number of the label, starting at 1, to set it with the keys `1` to `6`.
+    #[must_use]
+    pub fn number(self) -> u8 {
+        match self {
+            Self::Red => 1,
+            Self::Yellow => 2,
+            Self::Green => 3,
+            Self::Cyan => 4,
+            Self::Blue => 5,
+            Self::Magenta => 6,
+        }
+    }
+
+    /// Get the label for `number`, see [`Self::number`].
+    #[must_use]
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.number() == number)
+    }
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::Yellow => "Yellow",
+            Self::Green => "Green",
+            Self::Cyan => "Cyan",
+            Self::Blue => "Blue",
+            Self::Magenta => "Magenta",
+        }
+    }
+
+    /// Get the label named `name`, case-insensitively.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.name().eq_ignore_ascii_case(name))
+    }
+
+    fn from_db(value: Integer) -> Option<Self> {
+        u8::try_from(value).ok().and_then(Self::from_number)
+    }
+}
+
+/// Set the color label of the track at `track`, [`None`] to remove it.
+pub fn set_label(conn: &Connection, track: &Path, label: Option<TrackLabel>) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let label = label.map(TrackLabel::number);
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET label=:label
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":label": label})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get the paths of all tracks with a color label, and their label.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_all_labels(conn: &Connection) -> Result<Vec<(PathBuf, TrackLabel)>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.label
+        FROM tracks
+        WHERE tracks.label IS NOT NULL;
+    "})?;
+
+    let result: Vec<(PathBuf, TrackLabel)> = stmt
+        .query_map(named_params! {}, |row| {
+            let file_dir: String = row.get("file_dir")?;
+            let file_stem: String = row.get("file_stem")?;
+            let file_ext: String = row.get("file_ext")?;
+            let label: Integer = row.get("label")?;
+
+            Ok((
+                PathBuf::from(file_dir).join(format!("{file_stem}.{file_ext}")),
+                TrackLabel::from_db(label),
+            ))
+        })?
+        .filter_map(|v| match v {
+            Ok((path, Some(label))) => Some(Ok((path, label))),
+            // unknown label numbers are ignored
+            Ok((_, None)) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all labels that are set on at least one track, in the order of their numbers.
+pub fn all_distinct_labels(conn: &Connection) -> Result<Vec<TrackLabel>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT tracks.label FROM tracks
+        WHERE tracks.label IS NOT NULL
+        ORDER BY tracks.label ASC;
+    "})?;
+
+    let result: Vec<Integer> = stmt
+        .query_map(named_params! {}, |row| row.get(0))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result.into_iter().filter_map(TrackLabel::from_db).collect())
+}
+
+/// Get all tracks with the color label `label`.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_with_label(
+    conn: &Connection,
+    label: TrackLabel,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    let stmt = formatdoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.label = :label
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":label": label.number()}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get the paths of all tracks with a [`QualityBadge`], and their badge.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_all_qualities(conn: &Connection) -> Result<Vec<(PathBuf, QualityBadge)>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks_metadata.quality
+        FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        WHERE tracks_metadata.quality IS NOT NULL;
+    "})?;
+
+    let result: Vec<(PathBuf, QualityBadge)> = stmt
+        .query_map(named_params! {}, |row| {
+            let file_dir: String = row.get("file_dir")?;
+            let file_stem: String = row.get("file_stem")?;
+            let file_ext: String = row.get("file_ext")?;
+            let quality: u8 = row.get("quality")?;
+
+            Ok((
+                PathBuf::from(file_dir).join(format!("{file_stem}.{file_ext}")),
+                QualityBadge::from_number(quality),
+            ))
+        })?
+        .filter_map(|v| match v {
+            Ok((path, Some(quality))) => Some(Ok((path, quality))),
+            // unknown badge numbers are ignored
+            Ok((_, None)) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all badges that at least one track has, from the highest to the lowest quality.
+pub fn all_distinct_qualities(conn: &Connection) -> Result<Vec<QualityBadge>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT tracks_metadata.quality FROM tracks_metadata
+        WHERE tracks_metadata.quality IS NOT NULL
+        ORDER BY tracks_metadata.quality ASC;
+    "})?;
+
+    let result: Vec<u8> = stmt
+        .query_map(named_params! {}, |row| row.get(0))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result
+        .into_iter()
+        .filter_map(QualityBadge::from_number)
+        .collect())
+}
+
+/// Get all tracks with the badge `quality`.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_with_quality(
+    conn: &Connection,
+    quality: QualityBadge,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    let stmt = formatdoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks_metadata.quality = :quality
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":quality": quality.number()}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all tracks matching the smart playlist `query`, with ages relative to now.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_matching(
+    conn: &Connection,
+    query: &SmartQuery,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    let (condition, params) = query.to_sql(chrono::Utc::now());
+    let stmt = formatdoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE {condition}
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Set whether the track at `track` was reviewed in the inbox.
+pub fn set_reviewed(conn: &Connection, track: &Path, reviewed: bool) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET reviewed=:reviewed
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":reviewed": reviewed})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get all days (`YYYY-MM-DD`, in UTC) that have unreviewed tracks added, the newest first.
+pub fn all_distinct_unreviewed_days(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT substr(tracks.added_at, 1, 10) AS day FROM tracks
+        WHERE tracks.reviewed = 0
+        ORDER BY day DESC;
+    "})?;
+
+    let result: Vec<String> = stmt
+        .query_map(named_params! {}, |row| row.get(0))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all unreviewed tracks added on `day` (`YYYY-MM-DD`, in UTC), or all of them if [`None`], the oldest first.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_unreviewed_tracks(conn: &Connection, day: Option<&str>) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.reviewed = 0 AND (:day IS NULL OR substr(tracks.added_at, 1, 10) = :day)
+        ORDER BY tracks.added_at ASC, tracks.id ASC;
+    "})?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":day": day}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Set the [`AudioFeatures::energy`](crate::audio_analysis::AudioFeatures::energy) of the track at `track`, [`None`] if it could not be analyzed.
+pub fn set_energy(conn: &Connection, track: &Path, energy: Option<f32>) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET energy=:energy
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext, ":energy": energy})?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Set the [`Mood`] and [`SoundProfile`] described from the audio of the track at `track`.
+pub fn set_sound_description(
+    conn: &Connection,
+    track: &Path,
+    mood: Option<Mood>,
+    profile: Option<SoundProfile>,
+) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET mood=:mood, sound_profile=:profile
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {
+        ":file_dir": file_dir,
+        ":file_stem": file_stem,
+        ":file_ext": file_ext,
+        ":mood": mood.map(Mood::name),
+        ":profile": profile.map(SoundProfile::name),
+    })?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get all energy levels that tracks are currently in, from low to high energy.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn all_distinct_energy_levels(conn: &Connection) -> Result<Vec<EnergyLevel>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT EXISTS(SELECT 1 FROM tracks WHERE tracks.energy >= :min AND tracks.energy < :max);
+    "})?;
+
+    let mut result = Vec::new();
+    for level in EnergyLevel::ALL {
+        let range = level.range();
+        let exists: bool = stmt.query_row(
+            named_params! {":min": range.start, ":max": range.end},
+            |row| row.get(0),
+        )?;
+        if exists {
+            result.push(level);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Get all tracks analyzed to be of energy `level`.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_with_energy(
+    conn: &Connection,
+    level: EnergyLevel,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    let stmt = formatdoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.energy >= :min AND tracks.energy < :max
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let range = level.range();
+    let result: Vec<TrackRead> = stmt
+        .query_map(
+            named_params! {":min": range.start, ":max": range.end},
+            |row| {
+                let trackread = common_row_to_trackread(conn, row);
+
+                Ok(trackread)
+            },
+        )?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Record the verification of the track at `track`, `failure` being [`None`] if it passed.
+pub fn set_verified(conn: &Connection, track: &Path, failure: Option<&VerifyError>) -> Result<()> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let kind = failure.map(|v| v.kind.number());
+    let detail = failure.map(|v| v.detail.as_str());
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        UPDATE tracks SET verify_failure=:kind, verify_detail=:detail, verified_at=:now
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let affected = stmt.execute(named_params! {
+        ":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext,
+        ":kind": kind, ":detail": detail, ":now": chrono::Utc::now().to_rfc3339(),
+    })?;
+
+    // update would otherwise fail silently
+    if affected == 0 {
+        bail!("Track not found");
+    }
+
+    Ok(())
+}
+
+/// Get the last failed verification of the track at `track`, [`None`] if it passed or was never verified.
+pub fn get_verify_failure(conn: &Connection, track: &Path) -> Result<Option<VerifyError>> {
+    let (file_dir, file_stem, file_ext) = path_to_db_comp(track)?;
+    let file_dir = file_dir.to_string_lossy();
+    let file_stem = file_stem.to_string_lossy();
+    let file_ext = file_ext.to_string_lossy();
+
+    let mut stmt = conn.prepare_cached(indoc!{"
+        SELECT tracks.verify_failure, tracks.verify_detail FROM tracks
+        WHERE tracks.file_dir=:file_dir AND tracks.file_stem=:file_stem AND tracks.file_ext=:file_ext;
+    "})?;
+
+    let (kind, detail): (Option<Integer>, Option<String>) = stmt.query_row(
+        named_params! {":file_dir": file_dir, ":file_stem": file_stem, ":file_ext": file_ext},
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let kind = kind
+        .and_then(|v| u8::try_from(v).ok())
+        .and_then(VerifyFailure::from_number);
+
+    Ok(kind.map(|kind| VerifyError {
+        kind,
+        detail: detail.unwrap_or_default(),
+    }))
+}
+
+/// Get all kinds of failed verifications that tracks currently have, in the order of their numbers.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn all_distinct_verify_failures(conn: &Connection) -> Result<Vec<VerifyFailure>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT DISTINCT tracks.verify_failure FROM tracks
+        WHERE tracks.verify_failure IS NOT NULL
+        ORDER BY tracks.verify_failure;
+    "})?;
+
+    let result: Vec<VerifyFailure> = stmt
+        .query_map(named_params! {}, |row| row.get::<_, Integer>(0))?
+        .filter_map(|v| {
+            v.map(|v| u8::try_from(v).ok().and_then(VerifyFailure::from_number))
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all tracks whose last verification failed with `kind`.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_tracks_with_verify_failure(
+    conn: &Connection,
+    kind: VerifyFailure,
+    order: RowOrdering,
+) -> Result<Vec<TrackRead>> {
+    let stmt = formatdoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.verify_failure = :kind
+        ORDER BY {};
+        ",
+        order.as_sql()
+    };
+    let mut stmt = conn.prepare(&stmt)?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":kind": kind.number()}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get `amount` random tracks, or less if there are not enough.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_random_tracks(conn: &Connection, amount: u32) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        ORDER BY RANDOM()
+        LIMIT :amount;
+    "})?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":amount": amount}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get all tracks of a random album that has at least `min_tracks` tracks.
+///
+/// Returns a empty [`Vec`] if there is no such album.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_random_album_tracks(conn: &Connection, min_tracks: u32) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.album = (
+            SELECT albums.id FROM albums
+            WHERE (SELECT COUNT(album_tracks.id) FROM tracks AS album_tracks WHERE album_tracks.album = albums.id) >= :min_tracks
+            ORDER BY RANDOM()
+            LIMIT 1
+        )
+        ORDER BY tracks.id ASC;
+    "})?;
+
+    let result: Vec<TrackRead> = stmt
+        .query_map(named_params! {":min_tracks": min_tracks}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+
+            Ok(trackread)
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// Get random never-played tracks with a known duration, until they add up to at least `duration`.
+///
+/// Returns less if there are not enough unplayed tracks.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_random_unplayed_tracks(conn: &Connection, duration: Duration) -> Result<Vec<TrackRead>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        WHERE tracks.play_count = 0 AND tracks.duration > 0
+        ORDER BY RANDOM();
+    "})?;
+
+    let mut result = Vec::new();
+    let mut total = Duration::ZERO;
+    // rows are only read as needed, so stopping early does not read the whole table
+    let mut rows = stmt.query(named_params! {})?;
+    while total < duration
+        && let Some(row) = rows.next()?
+    {
+        let trackread = common_row_to_trackread(conn, row);
+        total += trackread.duration.unwrap_or_default();
+        result.push(trackread);
+    }
+
+    Ok(result)
+}
+
+/// The play statistics of a track, see [`get_play_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayStats {
+    /// How often the track has been started
+    pub play_count: u64,
+    /// When the track has last been started, [`None`] if never
+    pub last_played_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The color label of the track, if any
+    pub label: Option<TrackLabel>,
+}
+
+/// Get all tracks with their [`PlayStats`], except for tracks labeled with any of `exclude_labels`.
+///
+/// # Panics
+///
+/// If the database schema does not match what is expected.
+pub fn get_play_stats(
+    conn: &Connection,
+    exclude_labels: &[TrackLabel],
+) -> Result<Vec<(TrackRead, PlayStats)>> {
+    let mut stmt = conn.prepare(indoc! {"
+        SELECT 
+            tracks.id AS track_id, tracks.file_dir, tracks.file_stem, tracks.file_ext, tracks.duration, tracks.last_position,
+            tracks_metadata.title AS track_title, tracks_metadata.artist_display, tracks_metadata.genre,
+            albums.id AS album_id, albums.title AS album_title,
+            tracks.play_count, tracks.last_played_at, tracks.label
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        LEFT JOIN albums ON tracks.album = albums.id
+        ORDER BY tracks.id ASC;
+    "})?;
+
+    let mut result: Vec<(TrackRead, PlayStats)> = stmt
+        .query_map(named_params! {}, |row| {
+            let trackread = common_row_to_trackread(conn, row);
+            let play_count = row
+                .get("play_count")
+                .map(|v: Integer| u64::try_from(v.max(0)).unwrap())
+                .unwrap();
+            let last_played_at = row
+                .get("last_played_at")
+                .ok()
+                .and_then(|v: String| chrono::DateTime::parse_from_rfc3339(&v).ok())
+                .map(|v| v.to_utc());
+            let label = row
+                .get("label")
+                .ok()
+                .and_then(|v: u8| TrackLabel::from_number(v));
+
+            Ok((
+                trackread,
+                PlayStats {
+                    play_count,
+                    last_played_at,
+                    label,
+                },
+            ))
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+    result.retain(|(_, stats)| !stats.label.is_some_and(|v| exclude_labels.contains(&v)));
+
+    Ok(result)
+}
+
+/// A artist and how often their tracks were played, see [`get_top_artists`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtistPlays {
+    pub artist: String,
+    pub play_count: u64,
+}
+
+/// Get the `limit` artists whose tracks were played the most, most played first.
+pub fn get_top_artists(conn: &Connection, limit: u32) -> Result<Vec<ArtistPlays>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT tracks_metadata.artist_display AS artist, SUM(tracks.play_count) AS plays
+        FROM tracks
+        INNER JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        WHERE tracks.play_count > 0 AND tracks_metadata.artist_display IS NOT NULL
+        GROUP BY tracks_metadata.artist_display
+        ORDER BY plays DESC, artist ASC
+        LIMIT :limit;
+    "})?;
+
+    let result = stmt
+        .query_map(named_params! {":limit": limit}, |row| {
+            Ok(ArtistPlays {
+                artist: row.get("artist")?,
+                play_count: u64::try_from(row.get::<_, Integer>("plays")?.max(0))
+                    .unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// A track and its play statistics, see [`get_top_tracks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackPlays {
+    /// The title of the track, or its file name if it has none
+    pub title: String,
+    pub artist: Option<String>,
+    pub play_count: u64,
+    pub skip_count: u64,
+    /// The rating in stars, [`None`] if not rated
+    pub rating: Option<u8>,
+}
+
+/// Get the `limit` most played tracks, most played first.
+pub fn get_top_tracks(conn: &Connection, limit: u32) -> Result<Vec<TrackPlays>> {
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT
+            COALESCE(tracks_metadata.title, tracks.file_stem) AS title, tracks_metadata.artist_display,
+            tracks.play_count, tracks.skip_count, tracks.rating
+        FROM tracks
+        LEFT JOIN tracks_metadata ON tracks.id = tracks_metadata.track
+        WHERE tracks.play_count > 0
+        ORDER BY tracks.play_count DESC, tracks.rating DESC, title ASC
+        LIMIT :limit;
+    "})?;
+
+    let result = stmt
+        .query_map(named_params! {":limit": limit}, |row| {
+            Ok(TrackPlays {
+                title: row.get("title")?,
+                artist: row.get("artist_display")?,
+                play_count: u64::try_from(row.get::<_, Integer>("play_count")?.max(0))
+                    .unwrap_or_default(),
+                skip_count: u64::try_from(row.get::<_, Integer>("skip_count")?.max(0))
+                    .unwrap_or_default(),
+                rating: row
+                    .get::<_, Option<Integer>>("rating")?
+                    .and_then(|v| u8::try_from(v).ok()),
+            })
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    Ok(result)
+}
+
+/// The time listened to tracks in a week, see [`get_listening_per_week`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekListening {
+    /// The Monday the week starts on, in UTC
+    pub week_start: chrono::NaiveDate,
+    pub listened: Duration,
+}
+
+/// Get the time listened in each of the last `weeks` weeks up to `now`, oldest first, including weeks without plays.
+pub fn get_listening_per_week(
+    conn: &Connection,
+    weeks: u32,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<WeekListening>> {
+    use chrono::{Datelike, Days};
+
+    let today = now.date_naive();
+    let this_week = today - Days::new(u64::from(today.weekday().num_days_from_monday()));
+    let first_week = this_week - Days::new(7 * u64::from(weeks.saturating_sub(1)));
+    let mut result: Vec<WeekListening> = (0..weeks)
+        .map(|idx| WeekListening {
+            week_start: first_week + Days::new(7 * u64::from(idx)),
+            listened: Duration::ZERO,
+        })
+        .collect();
+    if result.is_empty() {
+        return Ok(result);
+    }
+
+    let since = first_week.and_time(chrono::NaiveTime::MIN).and_utc();
+    let mut stmt = conn.prepare_cached(indoc! {"
+        SELECT started_at, listened FROM plays
+        WHERE started_at >= :since;
+    "})?;
+    let plays = stmt
+        .query_map(named_params! {":since": since.to_rfc3339()}, |row| {
+            Ok((
+                row.get::<_, String>("started_at")?,
+                row.get::<_, Integer>("listened")?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    for (started_at, listened) in plays {
+        let Ok(started_at) = chrono::DateTime::parse_from_rfc3339(&started_at) else {
+            continue;
+        };
+        let days = (started_at.to_utc().date_naive() - first_week).num_days();
+        let Some(week) = usize::try_from(days / 7)
+            .ok()
+            .and_then(|idx| result.get_mut(idx))
+        else {
+            continue;
+        };
+        week.listened += Duration::from_secs(u64::try_from(listened.max(0)).unwrap_or_default());
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{OsStr, OsString},
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    use either::Either;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        audio_analysis::{EnergyLevel, Mood, SoundProfile},
+        audio_verify::{VerifyError, VerifyFailure},
+        loudness::Loudness,
+        new_database::{
+            Database,
+            album_insert::AlbumInsertable,
+            artist_insert::ArtistInsertable,
+            test_utils::{gen_database, test_path},
+            track_insert::TrackInsertable,
+            track_ops::{
+                AlbumRead, ArtistPlays, ArtistRead, DamagedRegion, RowOrdering, TrackLabel,
+                TrackOffsets, TrackPlays, TrackRead, all_distinct_composers,
+                all_distinct_directories, all_distinct_directories_without_album,
+                all_distinct_energy_levels, all_distinct_genres, all_distinct_labels,
+                all_distinct_names_like, all_distinct_qualities, all_distinct_unreviewed_days,
+                all_distinct_verify_failures, all_distinct_works, count_all_track_artist_mapping,
+                count_all_track_genre_mapping, delete_all_unreferenced_genres,
+                delete_tracks_artists_mapping_for, delete_tracks_path, find_tracks_by_tags,
+                get_all_labels, get_all_qualities, get_all_tracks, get_bpm, get_damaged_tracks,
+                get_last_position, get_listening_per_week, get_loudness, get_offsets,
+                get_play_stats, get_random_album_tracks, get_random_tracks,
+                get_random_unplayed_tracks, get_rating, get_top_artists, get_top_tracks,
+                get_track_from_path, get_tracks_from_album, get_tracks_from_album_id,
+                get_tracks_from_artist, get_tracks_from_composer, get_tracks_from_directory,
+                get_tracks_from_genre, get_tracks_from_genre_like, get_tracks_from_work,
+                get_tracks_matching, get_tracks_with_energy, get_tracks_with_label,
+                get_tracks_with_quality, get_tracks_with_verify_failure, get_tracks_without_album,
+                get_tracks_without_loudness, get_unreviewed_tracks, get_verify_failure,
+                mark_played, mark_skipped, merge_play_stats, record_play, rename_tracks_path,
+                set_bpm, set_damaged, set_energy, set_label, set_last_position, set_loudness,
+                set_offsets, set_rating, set_reviewed, set_sound_description, set_verified,
+                track_exists,
+            },
+        },
+        smart_playlist::SmartQuery,
+        track::{QualityBadge, TrackMetadata},
+    };
+
+    use super::get_all_artists_for_track;
+
+    #[test]
+    fn artists_for_track() {
+        let db = gen_database();
+
+        let track = TrackInsertable {
+            file_dir: Path::new("/somewhere"),
+            file_stem: OsStr::new("file"),
+            file_ext: OsStr::new("ext"),
+            duration: Some(Duration::from_secs(10)),
+            last_position: None,
+            album: Some(Either::Left(
+                AlbumInsertable {
+                    title: "AlbumA",
+                    artist_display: "ArtistA",
+                    sort_title: None,
+                    artists: vec![Either::Left(
+                        ArtistInsertable {
+                            artist: "ArtistA",
+                            sort_name: None,
+                        }
+                        .into(),
+                    )],
+                }
+                .into(),
+            )),
+            title: Some("file test"),
+            genre: None,
+            artist_display: Some("ArtistA feat. ArtistB"),
+            composer: None,
+            work: None,
+            movement_number: None,
+            bpm: None,
+            quality: None,
+            artists: vec![
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistA",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+                Either::Left(
+                    ArtistInsertable {
+                        artist: "ArtistB",
+                        sort_name: None,
+                    }
+                    .into(),
+                ),
+            ],
+            genres: Vec::new(),
+        };
+        let track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
+
+        let mut all_artists: Vec<String> =
+            get_all_artists_for_track(&db.get_connection(), track_id)
+                .unwrap()
+                .into_iter()
+                .map(|v| v.name)
+                .collect();
+        // just making sure they are consistently ordered
+        all_artists.sort();
+
+        assert_eq!(all_artists, &["ArtistA", "ArtistB"]);
+    }
+
+    #[test]
+    fn all_tracks() {
+        let db = gen_database();
+
+        let track = TrackInsertable {
+            file_dir: Path::new("/somewhere"),
+            file_stem: OsStr::new("file"),
+            file_ext: OsStr::new("ext"),
+            duration: Some(Duration::from_secs(10)),
+            last_position: None,
+            album: Some(Either::Left(
+                AlbumInsertable {
+                    title: "AlbumA",
+                    artist_display: "ArtistA",
+                    sort_title: None,
+                    artists: vec![Either::Left(
+                        ArtistInsertable {
+                            artist: "ArtistA",
+                            sort_name: None,
+                        }
+                        .into(),
+                    )],
+                }
+                .into(),
+            )),
+            title: Some("file test"),
+            genre: None,
+            artist_display: Some("ArtistA"),
+            composer: None,
+            work: None,
+            movement_number: None,
+            bpm: None,
+            quality: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
+            genres: Vec::new(),
+        };
+        let _track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
+
+        let all_tracks = get_all_tracks(&db.get_connection(), RowOrdering::IdAsc).unwrap();
+
+        assert_eq!(
+            all_tracks,
+            &[TrackRead {
+                id: 1,
+                file_dir: PathBuf::from("/somewhere"),
+                file_stem: OsString::from("file"),
+                file_ext: OsString::from("ext"),
+                duration: Some(Duration::from_secs(10)),
+                last_position: None,
+                album: Some(AlbumRead {
+                    id: 1,
+                    title: "AlbumA".to_string()
+                }),
+                title: Some("file test".to_string()),
+                genre: None,
+                artist_display: Some("ArtistA".to_string()),
+                artists: vec![ArtistRead {
+                    id: 1,
+                    name: "ArtistA".to_string(),
+                    sort_name: None,
+                }]
+            }]
+        );
+    }
+
+    #[test]
+    fn last_position_some() {
+        let db = gen_database();
+
+        let track = TrackInsertable {
+            file_dir: &test_path(Path::new("/somewhere")),
+            file_stem: OsStr::new("file"),
+            file_ext: OsStr::new("ext"),
+            duration: Some(Duration::from_secs(10)),
+            last_position: Some(Duration::from_secs(5)),
+            album: Some(Either::Left(
+                AlbumInsertable {
+                    title: "AlbumA",
+                    artist_display: "ArtistA",
+                    sort_title: None,
+                    artists: vec![Either::Left(
+                        ArtistInsertable {
+                            artist: "ArtistA",
+                            sort_name: None,
+                        }
+                        .into(),
+                    )],
+                }
+                .into(),
+            )),
+            title: Some("file test"),
+            genre: None,
+            artist_display: Some("ArtistA"),
+            composer: None,
+            work: None,
+            movement_number: None,
+            bpm: None,
+            quality: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
+            genres: Vec::new(),
+        };
+        let path = &test_path(Path::new("/somewhere/file.ext"));
+        let _track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
+
+        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+
+        assert_eq!(last_position, Some(Duration::from_secs(5)));
+
+        set_last_position(&db.get_connection(), path, None).unwrap();
+
+        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+
+        assert_eq!(last_position, None);
+    }
+
+    #[test]
+    fn last_position_none() {
+        let db = gen_database();
+
+        let track = TrackInsertable {
+            file_dir: &test_path(Path::new("/somewhere")),
             file_stem: OsStr::new("file"),
             file_ext: OsStr::new("ext"),
             duration: Some(Duration::from_secs(10)),
-            last_position: None,
-            album: Some(Either::Left(
-                AlbumInsertable {
-                    title: "AlbumA",
-                    artist_display: "ArtistA",
-                    artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
-                }
-                .into(),
-            )),
-            title: Some("file test"),
-            genre: None,
-            artist_display: Some("ArtistA"),
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
+            last_position: None,
+            album: Some(Either::Left(
+                AlbumInsertable {
+                    title: "AlbumA",
+                    artist_display: "ArtistA",
+                    sort_title: None,
+                    artists: vec![Either::Left(
+                        ArtistInsertable {
+                            artist: "ArtistA",
+                            sort_name: None,
+                        }
+                        .into(),
+                    )],
+                }
+                .into(),
+            )),
+            title: Some("file test"),
+            genre: None,
+            artist_display: Some("ArtistA"),
+            composer: None,
+            work: None,
+            movement_number: None,
+            bpm: None,
+            quality: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
+            genres: Vec::new(),
+        };
+        let path = &test_path(Path::new("/somewhere/file.ext"));
+        let _track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
+
+        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+
+        assert_eq!(last_position, None);
+
+        set_last_position(&db.get_connection(), path, Some(Duration::from_secs(5))).unwrap();
+
+        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+
+        assert_eq!(last_position, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn last_position_not_found() {
+        let db = gen_database();
+
+        let path = &test_path(Path::new("/somewhere/file.ext"));
+
+        // get
+        let err = get_last_position(&db.get_connection(), path).unwrap_err();
+        let err = err.downcast::<rusqlite::Error>().unwrap();
+
+        assert_eq!(err, rusqlite::Error::QueryReturnedNoRows);
+
+        // set
+        let err = set_last_position(&db.get_connection(), path, None).unwrap_err();
+
+        assert!(err.to_string().contains("Track not found"));
+    }
+
+    #[test]
+    fn tracks_by_album() {
+        let db = gen_database();
+
+        let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA2".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        let metadata = TrackMetadata {
+            album: Some("AlbumB".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileB1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        let res = get_tracks_from_album(
+            &db.get_connection(),
+            "AlbumA",
+            "ArtistA",
+            RowOrdering::IdAsc,
+        )
+        .unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+
+        assert_eq!(&res, &["FileA1", "FileA2"]);
+    }
+
+    #[test]
+    fn tracks_by_artist() {
+        let db = gen_database();
+
+        let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistB".to_string()),
+            album_artists: Some(vec!["ArtistB".to_string()]),
+            artist: Some("ArtistB".to_string()),
+            artists: Some(vec!["ArtistB".to_string()]),
+            title: Some("FileA2".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
         };
-        let _track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
+        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        let all_tracks = get_all_tracks(&db.get_connection(), RowOrdering::IdAsc).unwrap();
+        let metadata = TrackMetadata {
+            album: Some("AlbumB".to_string()),
+            album_artist: Some("ArtistB".to_string()),
+            album_artists: Some(vec!["ArtistB".to_string()]),
+            artist: Some("ArtistB".to_string()),
+            artists: Some(vec!["ArtistB".to_string()]),
+            title: Some("FileB1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        assert_eq!(
-            all_tracks,
-            &[TrackRead {
-                id: 1,
-                file_dir: PathBuf::from("/somewhere"),
-                file_stem: OsString::from("file"),
-                file_ext: OsString::from("ext"),
-                duration: Some(Duration::from_secs(10)),
-                last_position: None,
-                album: Some(AlbumRead {
-                    id: 1,
-                    title: "AlbumA".to_string()
-                }),
-                title: Some("file test".to_string()),
-                genre: None,
-                artist_display: Some("ArtistA".to_string()),
-                artists: vec![ArtistRead {
-                    id: 1,
-                    name: "ArtistA".to_string()
-                }]
-            }]
-        );
+        let res =
+            get_tracks_from_artist(&db.get_connection(), "ArtistB", RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+
+        assert_eq!(&res, &["FileA2", "FileB1"]);
     }
 
     #[test]
-    fn last_position_some() {
+    fn tracks_by_genre() {
         let db = gen_database();
 
-        let track = TrackInsertable {
-            file_dir: &test_path(Path::new("/somewhere")),
-            file_stem: OsStr::new("file"),
-            file_ext: OsStr::new("ext"),
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            last_position: Some(Duration::from_secs(5)),
-            album: Some(Either::Left(
-                AlbumInsertable {
-                    title: "AlbumA",
-                    artist_display: "ArtistA",
-                    artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
-                }
-                .into(),
-            )),
-            title: Some("file test"),
-            genre: None,
-            artist_display: Some("ArtistA"),
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
+            genre: Some("Rock".to_string()),
+            ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/file.ext"));
-        let _track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
-
-        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        assert_eq!(last_position, Some(Duration::from_secs(5)));
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA2".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: Some("Pop".to_string()),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        set_last_position(&db.get_connection(), path, None).unwrap();
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileB1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: None,
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+        let res =
+            get_tracks_from_genre(&db.get_connection(), Some("Rock"), RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
 
-        assert_eq!(last_position, None);
+        assert_eq!(&res, &["FileA1"]);
     }
 
     #[test]
-    fn last_position_none() {
+    fn tracks_by_genre_like() {
         let db = gen_database();
 
-        let track = TrackInsertable {
-            file_dir: &test_path(Path::new("/somewhere")),
-            file_stem: OsStr::new("file"),
-            file_ext: OsStr::new("ext"),
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            last_position: None,
-            album: Some(Either::Left(
-                AlbumInsertable {
-                    title: "AlbumA",
-                    artist_display: "ArtistA",
-                    artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
-                }
-                .into(),
-            )),
-            title: Some("file test"),
-            genre: None,
-            artist_display: Some("ArtistA"),
-            artists: vec![Either::Left(ArtistInsertable { artist: "ArtistA" }.into())],
+            genre: Some("Rock".to_string()),
+            ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/file.ext"));
-        let _track_id = track.try_insert_or_update(&db.get_connection()).unwrap();
-
-        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        assert_eq!(last_position, None);
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA2".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: Some("Pop".to_string()),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        set_last_position(&db.get_connection(), path, Some(Duration::from_secs(5))).unwrap();
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileB1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: None,
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        let last_position = get_last_position(&db.get_connection(), path).unwrap();
+        let res =
+            get_tracks_from_genre_like(&db.get_connection(), "%pop%", RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
 
-        assert_eq!(last_position, Some(Duration::from_secs(5)));
+        assert_eq!(&res, &["FileA2"]);
     }
 
     #[test]
-    fn last_position_not_found() {
+    fn tracks_by_genre_null() {
         let db = gen_database();
 
-        let path = &test_path(Path::new("/somewhere/file.ext"));
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: Some("Rock".to_string()),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        // get
-        let err = get_last_position(&db.get_connection(), path).unwrap_err();
-        let err = err.downcast::<rusqlite::Error>().unwrap();
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileA2".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: Some("Pop".to_string()),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        assert_eq!(err, rusqlite::Error::QueryReturnedNoRows);
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileB1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: None,
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        // set
-        let err = set_last_position(&db.get_connection(), path, None).unwrap_err();
+        let res = get_tracks_from_genre(&db.get_connection(), None, RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
 
-        assert!(err.to_string().contains("Track not found"));
+        assert_eq!(&res, &["FileB1"]);
     }
 
     #[test]
-    fn tracks_by_album() {
+    fn genre_distinct() {
         let db = gen_database();
 
         let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
+            genre: Some("Rock".to_string()),
             ..Default::default()
         };
         let path = &test_path(Path::new("/somewhere/fileA1.ext"));
@@ -863,13 +3160,11 @@ mod tests {
             .unwrap();
 
         let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA2".to_string()),
             duration: Some(Duration::from_secs(10)),
+            genre: Some("Pop".to_string()),
             ..Default::default()
         };
         let path = &test_path(Path::new("/somewhere/fileA2.ext"));
@@ -879,13 +3174,11 @@ mod tests {
             .unwrap();
 
         let metadata = TrackMetadata {
-            album: Some("AlbumB".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileB1".to_string()),
             duration: Some(Duration::from_secs(10)),
+            genre: None,
             ..Default::default()
         };
         let path = &test_path(Path::new("/somewhere/fileB1.ext"));
@@ -894,30 +3187,33 @@ mod tests {
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
-        let res = get_tracks_from_album(
-            &db.get_connection(),
-            "AlbumA",
-            "ArtistA",
-            RowOrdering::IdAsc,
-        )
-        .unwrap();
-        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            artists: Some(vec!["ArtistA".to_string()]),
+            title: Some("FileB2".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            genre: Some("Rock".to_string()),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileB2.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
 
-        assert_eq!(&res, &["FileA1", "FileA2"]);
+        let res = all_distinct_genres(&db.get_connection()).unwrap();
+
+        assert_eq!(&res, &["Rock", "Pop", "[unknown]"]);
     }
 
     #[test]
-    fn tracks_by_artist() {
+    fn tracks_by_multiple_genres() {
         let db = gen_database();
 
         let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
+            genre: Some("Rock; Pop".to_string()),
+            genres: Some(vec!["Rock".to_string(), "Pop".to_string()]),
             ..Default::default()
         };
         let path = &test_path(Path::new("/somewhere/fileA1.ext"));
@@ -927,13 +3223,9 @@ mod tests {
             .unwrap();
 
         let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistB".to_string()),
-            album_artists: Some(vec!["ArtistB".to_string()]),
-            artist: Some("ArtistB".to_string()),
-            artists: Some(vec!["ArtistB".to_string()]),
             title: Some("FileA2".to_string()),
-            duration: Some(Duration::from_secs(10)),
+            genre: Some("Pop".to_string()),
+            genres: Some(vec!["Pop".to_string()]),
             ..Default::default()
         };
         let path = &test_path(Path::new("/somewhere/fileA2.ext"));
@@ -942,508 +3234,1230 @@ mod tests {
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
+        assert_eq!(
+            count_all_track_genre_mapping(&db.get_connection()).unwrap(),
+            3
+        );
+        assert_eq!(
+            all_distinct_genres(&db.get_connection()).unwrap(),
+            &["Rock", "Pop"]
+        );
+
+        let res =
+            get_tracks_from_genre(&db.get_connection(), Some("Pop"), RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        assert_eq!(&res, &["FileA1", "FileA2"]);
+
+        let res =
+            get_tracks_from_genre_like(&db.get_connection(), "%rock%", RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        assert_eq!(&res, &["FileA1"]);
+
+        // re-tagging should replace the old mappings
+        let metadata = TrackMetadata {
+            title: Some("FileA1".to_string()),
+            genre: Some("Pop".to_string()),
+            genres: Some(vec!["Pop".to_string()]),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        assert_eq!(
+            count_all_track_genre_mapping(&db.get_connection()).unwrap(),
+            2
+        );
+        assert_eq!(all_distinct_genres(&db.get_connection()).unwrap(), &["Pop"]);
+        assert_eq!(
+            delete_all_unreferenced_genres(&db.get_connection()).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn exists() {
+        let db = gen_database();
+
         let metadata = TrackMetadata {
-            album: Some("AlbumB".to_string()),
-            album_artist: Some("ArtistB".to_string()),
-            album_artists: Some(vec!["ArtistB".to_string()]),
-            artist: Some("ArtistB".to_string()),
-            artists: Some(vec!["ArtistB".to_string()]),
-            title: Some("FileB1".to_string()),
+            title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
-        let res =
-            get_tracks_from_artist(&db.get_connection(), "ArtistB", RowOrdering::IdAsc).unwrap();
-        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        let res = track_exists(&db.get_connection(), path).unwrap();
 
-        assert_eq!(&res, &["FileA2", "FileB1"]);
+        assert!(res);
+
+        let res = track_exists(
+            &db.get_connection(),
+            &test_path(Path::new("/somewhere/else.ext")),
+        )
+        .unwrap();
+
+        assert!(!res);
     }
 
     #[test]
-    fn tracks_by_genre() {
+    fn single_track() {
+        let db = gen_database();
+
+        let metadata = TrackMetadata {
+            title: Some("FileA1".to_string()),
+            duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let _ = insertable
+            .try_insert_or_update(&db.get_connection())
+            .unwrap();
+
+        let res = get_track_from_path(&db.get_connection(), path).unwrap();
+
+        assert_eq!(res.title, Some("FileA1".to_string()));
+
+        let err = get_track_from_path(
+            &db.get_connection(),
+            &test_path(Path::new("/somewhere/else.ext")),
+        )
+        .unwrap_err();
+        let err = err.downcast::<rusqlite::Error>().unwrap();
+
+        assert_eq!(err, rusqlite::Error::QueryReturnedNoRows);
+    }
+
+    #[test]
+    fn track_read_to_path() {
+        let read = TrackRead {
+            id: 0,
+            file_dir: PathBuf::from("/path/to/somewhere"),
+            file_stem: OsString::from("filename"),
+            file_ext: OsString::from("ext"),
+            duration: None,
+            last_position: None,
+            album: None,
+            title: None,
+            genre: None,
+            artist_display: None,
+            artists: Vec::new(),
+        };
+
+        assert_eq!(
+            read.as_pathbuf(),
+            PathBuf::from("/path/to/somewhere/filename.ext")
+        );
+    }
+
+    #[test]
+    fn distinct_directories() {
         let db = gen_database();
 
         let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: Some("Rock".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let path = &test_path(Path::new("/somewhere/dirA/fileA1.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
         let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA2".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: Some("Pop".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let path = &test_path(Path::new("/somewhere/dirA/fileA2.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
         let metadata = TrackMetadata {
+            album: Some("AlbumB".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileB1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: None,
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let path = &test_path(Path::new("/somewhere/dirB/fileB1.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
-        let res =
-            get_tracks_from_genre(&db.get_connection(), Some("Rock"), RowOrdering::IdAsc).unwrap();
-        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        let res = all_distinct_directories(&db.get_connection()).unwrap();
 
-        assert_eq!(&res, &["FileA1"]);
+        assert_eq!(
+            &res,
+            &[
+                test_path(Path::new("/somewhere/dirA")).to_string_lossy(),
+                test_path(Path::new("/somewhere/dirB")).to_string_lossy()
+            ]
+        );
     }
 
     #[test]
-    fn tracks_by_genre_like() {
+    fn tracks_by_directory() {
         let db = gen_database();
 
         let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: Some("Rock".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let path = &test_path(Path::new("/somewhere/dirA/fileA1.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
         let metadata = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileA2".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: Some("Pop".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
+        let path = &test_path(Path::new("/somewhere/dirA/fileA2.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
         let metadata = TrackMetadata {
+            album: Some("AlbumB".to_string()),
+            album_artist: Some("ArtistA".to_string()),
+            album_artists: Some(vec!["ArtistA".to_string()]),
             artist: Some("ArtistA".to_string()),
             artists: Some(vec!["ArtistA".to_string()]),
             title: Some("FileB1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: None,
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
+        let path = &test_path(Path::new("/somewhere/dirB/fileB1.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
-        let res =
-            get_tracks_from_genre_like(&db.get_connection(), "%pop%", RowOrdering::IdAsc).unwrap();
+        let res = get_tracks_from_directory(
+            &db.get_connection(),
+            &test_path(Path::new("/somewhere/dirA")),
+            RowOrdering::IdAsc,
+        )
+        .unwrap();
         let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
 
-        assert_eq!(&res, &["FileA2"]);
+        assert_eq!(&res, &["FileA1", "FileA2"]);
     }
 
     #[test]
-    fn tracks_by_genre_null() {
+    fn tracks_without_album() {
         let db = gen_database();
 
-        let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
+        let tagged = TrackMetadata {
+            album: Some("AlbumA".to_string()),
+            album_artist: Some("ArtistA".to_string()),
             title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            genre: Some("Rock".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        let untagged_a = TrackMetadata {
+            title: Some("FileA2".to_string()),
+            ..Default::default()
+        };
+        let untagged_b = TrackMetadata {
+            title: Some("FileB1".to_string()),
+            ..Default::default()
+        };
+        for (path, metadata) in [
+            ("/somewhere/dirA/fileA1.ext", &tagged),
+            ("/somewhere/dirA/fileA2.ext", &untagged_a),
+            ("/somewhere/dirB/fileB1.ext", &untagged_b),
+        ] {
+            let path = &test_path(Path::new(path));
+            let insertable = TrackInsertable::try_from_track(path, metadata).unwrap();
+            let _ = insertable
+                .try_insert_or_update(&db.get_connection())
+                .unwrap();
+        }
+
+        let res = get_tracks_without_album(&db.get_connection(), None, RowOrdering::IdAsc).unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        assert_eq!(&res, &["FileA2", "FileB1"]);
+
+        let res = get_tracks_without_album(
+            &db.get_connection(),
+            Some(&test_path(Path::new("/somewhere/dirA"))),
+            RowOrdering::IdAsc,
+        )
+        .unwrap();
+        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        assert_eq!(&res, &["FileA2"]);
+
+        let mut res = all_distinct_directories_without_album(&db.get_connection()).unwrap();
+        res.sort();
+        assert_eq!(
+            &res,
+            &[
+                test_path(Path::new("/somewhere/dirA")).to_string_lossy(),
+                test_path(Path::new("/somewhere/dirB")).to_string_lossy()
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_tracks_artists_mapping() {
+        let db = gen_database();
 
         let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA2".to_string()),
+            artist: Some("ArtistA feat. ArtistB".to_string()),
+            artists: Some(vec!["ArtistA".to_string(), "ArtistB".to_string()]),
+            title: Some("FileA1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: Some("Pop".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
+        let path_a1 = &test_path(Path::new("/somewhere/fileA1.ext"));
+        let insertable = TrackInsertable::try_from_track(path_a1, &metadata).unwrap();
         let _ = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
         let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
+            artist: Some("ArtistA feat. ArtistB".to_string()),
+            artists: Some(vec!["ArtistA".to_string(), "ArtistB".to_string()]),
             title: Some("FileB1".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: None,
             ..Default::default()
         };
         let path = &test_path(Path::new("/somewhere/fileB1.ext"));
         let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
+        let track2_id = insertable
             .try_insert_or_update(&db.get_connection())
             .unwrap();
 
-        let res = get_tracks_from_genre(&db.get_connection(), None, RowOrdering::IdAsc).unwrap();
-        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
+        let mapping_counts = count_all_track_artist_mapping(&db.get_connection()).unwrap();
 
-        assert_eq!(&res, &["FileB1"]);
+        assert_eq!(mapping_counts, 4);
+
+        let affected =
+            delete_tracks_artists_mapping_for(&db.get_connection(), Either::Left(path_a1)).unwrap();
+
+        assert_eq!(affected, 2);
+
+        let mapping_counts = count_all_track_artist_mapping(&db.get_connection()).unwrap();
+
+        assert_eq!(mapping_counts, 2);
+
+        let affected =
+            delete_tracks_artists_mapping_for(&db.get_connection(), Either::Right(track2_id))
+                .unwrap();
+
+        assert_eq!(affected, 2);
+
+        let mapping_counts = count_all_track_artist_mapping(&db.get_connection()).unwrap();
+
+        assert_eq!(mapping_counts, 0);
     }
 
     #[test]
-    fn genre_distinct() {
+    fn rename_tracks() {
         let db = gen_database();
+        let conn = db.get_connection();
 
         let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA1".to_string()),
+            title: Some("File".to_string()),
             duration: Some(Duration::from_secs(10)),
-            genre: Some("Rock".to_string()),
             ..Default::default()
         };
-        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        for path in [
+            "/music/album/a.mp3",
+            "/music/album/cd2/b.mp3",
+            "/music/album2/c.mp3",
+            "/music/other/d.mp3",
+        ] {
+            TrackInsertable::try_from_track(&test_path(Path::new(path)), &metadata)
+                .unwrap()
+                .try_insert_or_update(&conn)
+                .unwrap();
+        }
+        set_last_position(
+            &conn,
+            &test_path(Path::new("/music/other/d.mp3")),
+            Some(Duration::from_secs(5)),
+        )
+        .unwrap();
 
-        let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA2".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            genre: Some("Pop".to_string()),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/fileA2.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        // single file, keeping data
+        let affected = rename_tracks_path(
+            &conn,
+            &test_path(Path::new("/music/other/d.mp3")),
+            &test_path(Path::new("/music/other/renamed.ogg")),
+        )
+        .unwrap();
+        assert_eq!(affected, 1);
+        assert!(!track_exists(&conn, &test_path(Path::new("/music/other/d.mp3"))).unwrap());
+        assert_eq!(
+            get_last_position(&conn, &test_path(Path::new("/music/other/renamed.ogg"))).unwrap(),
+            Some(Duration::from_secs(5))
+        );
 
-        let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileB1".to_string()),
-            duration: Some(Duration::from_secs(10)),
+        // directory with sub-directories, but not directories which only share a prefix
+        let affected = rename_tracks_path(
+            &conn,
+            &test_path(Path::new("/music/album")),
+            &test_path(Path::new("/elsewhere/moved")),
+        )
+        .unwrap();
+        assert_eq!(affected, 2);
+        assert!(track_exists(&conn, &test_path(Path::new("/elsewhere/moved/a.mp3"))).unwrap());
+        assert!(track_exists(&conn, &test_path(Path::new("/elsewhere/moved/cd2/b.mp3"))).unwrap());
+        assert!(track_exists(&conn, &test_path(Path::new("/music/album2/c.mp3"))).unwrap());
+
+        let affected = delete_tracks_path(&conn, &test_path(Path::new("/elsewhere"))).unwrap();
+        assert_eq!(affected, 2);
+        let affected =
+            delete_tracks_path(&conn, &test_path(Path::new("/music/album2/c.mp3"))).unwrap();
+        assert_eq!(affected, 1);
+
+        let res: Vec<PathBuf> = get_all_tracks(&conn, RowOrdering::IdAsc)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.as_pathbuf())
+            .collect();
+        assert_eq!(res, &[test_path(Path::new("/music/other/renamed.ogg"))]);
+    }
+
+    /// Insert a track `stem` in `/somewhere` with the given duration in seconds and album.
+    fn insert_sample_track(db: &Database, stem: &str, duration: u64, album: Option<&str>) {
+        let track = TrackInsertable {
+            file_dir: &test_path(Path::new("/somewhere")),
+            file_stem: OsStr::new(stem),
+            file_ext: OsStr::new("ext"),
+            duration: Some(Duration::from_secs(duration)),
+            last_position: None,
+            album: album.map(|title| {
+                Either::Left(
+                    AlbumInsertable {
+                        title,
+                        artist_display: "ArtistA",
+                        sort_title: None,
+                        artists: vec![Either::Left(
+                            ArtistInsertable {
+                                artist: "ArtistA",
+                                sort_name: None,
+                            }
+                            .into(),
+                        )],
+                    }
+                    .into(),
+                )
+            }),
+            title: Some(stem),
             genre: None,
-            ..Default::default()
+            artist_display: Some("ArtistA"),
+            composer: None,
+            work: None,
+            movement_number: None,
+            bpm: None,
+            quality: None,
+            artists: vec![Either::Left(
+                ArtistInsertable {
+                    artist: "ArtistA",
+                    sort_name: None,
+                }
+                .into(),
+            )],
+            genres: Vec::new(),
         };
-        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        track.try_insert_or_update(&db.get_connection()).unwrap();
+    }
 
-        let metadata = TrackMetadata {
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileB2".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            genre: Some("Rock".to_string()),
-            ..Default::default()
+    /// Insert a track `stem` in `/somewhere` as movement `number` of `work` by `composer`.
+    fn insert_movement(db: &Database, stem: &str, composer: &str, work: &str, number: u32) {
+        let track = TrackInsertable {
+            file_dir: &test_path(Path::new("/somewhere")),
+            file_stem: OsStr::new(stem),
+            file_ext: OsStr::new("ext"),
+            duration: None,
+            last_position: None,
+            album: None,
+            title: Some(stem),
+            genre: None,
+            artist_display: Some("Orchestra"),
+            composer: Some(composer),
+            work: Some(work),
+            movement_number: Some(number),
+            bpm: None,
+            quality: None,
+            artists: Vec::new(),
+            genres: Vec::new(),
         };
-        let path = &test_path(Path::new("/somewhere/fileB2.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        track.try_insert_or_update(&db.get_connection()).unwrap();
+    }
 
-        let res = all_distinct_genres(&db.get_connection()).unwrap();
+    #[test]
+    fn tracks_by_composer_and_work() {
+        let db = gen_database();
+        insert_movement(&db, "c", "Beethoven", "Symphony No. 5", 2);
+        insert_movement(&db, "a", "Beethoven", "Symphony No. 5", 1);
+        insert_movement(&db, "b", "Beethoven", "Egmont", 1);
+        insert_movement(&db, "d", "Mahler", "Symphony No. 5", 1);
+        insert_sample_track(&db, "none", 10, None);
+
+        let mut composers = all_distinct_composers(&db.get_connection()).unwrap();
+        composers.sort();
+        assert_eq!(composers, ["Beethoven", "Mahler"]);
+        let mut works = all_distinct_works(&db.get_connection()).unwrap();
+        works.sort();
+        assert_eq!(works, ["Egmont", "Symphony No. 5"]);
+
+        let tracks = get_tracks_from_composer(&db.get_connection(), "Beethoven").unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["b", "a", "c"]);
+
+        let tracks = get_tracks_from_work(&db.get_connection(), "Symphony No. 5").unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["a", "c", "d"]);
+    }
 
-        assert_eq!(&res, &["Rock", "Pop", "[unknown]"]);
+    #[test]
+    fn names_like() {
+        let db = gen_database();
+        insert_sample_track(&db, "Artistic Song", 10, None);
+        insert_sample_track(&db, "Other", 10, None);
+
+        let mut names = all_distinct_names_like(&db.get_connection(), "%art%", 10).unwrap();
+        names.sort();
+        assert_eq!(names, ["ArtistA", "Artistic Song"]);
+
+        let names = all_distinct_names_like(&db.get_connection(), "%art%", 1).unwrap();
+        assert_eq!(names.len(), 1);
     }
 
     #[test]
-    fn exists() {
+    fn random_tracks() {
         let db = gen_database();
+        for stem in ["a", "b", "c"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
 
-        let metadata = TrackMetadata {
-            title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        assert_eq!(get_random_tracks(&db.get_connection(), 2).unwrap().len(), 2);
+        assert_eq!(get_random_tracks(&db.get_connection(), 5).unwrap().len(), 3);
+    }
 
-        let res = track_exists(&db.get_connection(), path).unwrap();
+    #[test]
+    fn random_album_tracks() {
+        let db = gen_database();
+        insert_sample_track(&db, "a1", 10, Some("AlbumA"));
+        insert_sample_track(&db, "a2", 10, Some("AlbumA"));
+        insert_sample_track(&db, "b1", 10, Some("AlbumB"));
+        insert_sample_track(&db, "none", 10, None);
 
-        assert!(res);
+        let tracks = get_random_album_tracks(&db.get_connection(), 2).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["a1", "a2"]);
 
-        let res = track_exists(
+        assert!(
+            get_random_album_tracks(&db.get_connection(), 3)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn tracks_from_album_id() {
+        let db = gen_database();
+        insert_sample_track(&db, "02 second", 10, Some("AlbumA"));
+        insert_sample_track(&db, "01 first", 10, Some("AlbumA"));
+        insert_sample_track(&db, "00 other", 10, Some("AlbumB"));
+
+        let album = get_track_from_path(
             &db.get_connection(),
-            &test_path(Path::new("/somewhere/else.ext")),
+            &test_path(Path::new("/somewhere/02 second.ext")),
+        )
+        .unwrap()
+        .album
+        .unwrap();
+
+        let tracks = get_tracks_from_album_id(&db.get_connection(), album.id).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["01 first", "02 second"]);
+    }
+
+    #[test]
+    fn random_unplayed_tracks() {
+        let db = gen_database();
+        for stem in ["a", "b", "c", "d"] {
+            insert_sample_track(&db, stem, 60, None);
+        }
+        mark_played(
+            &db.get_connection(),
+            &test_path(Path::new("/somewhere/a.ext")),
         )
         .unwrap();
 
-        assert!(!res);
+        let tracks =
+            get_random_unplayed_tracks(&db.get_connection(), Duration::from_secs(90)).unwrap();
+        assert_eq!(tracks.len(), 2);
+        assert!(tracks.iter().all(|v| v.title.as_deref() != Some("a")));
+
+        let tracks =
+            get_random_unplayed_tracks(&db.get_connection(), Duration::from_secs(3600)).unwrap();
+        assert_eq!(tracks.len(), 3);
+    }
+
+    #[test]
+    fn mark_played_not_found() {
+        let db = gen_database();
+
+        assert!(
+            mark_played(
+                &db.get_connection(),
+                &test_path(Path::new("/somewhere/file.ext"))
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn labels() {
+        let db = gen_database();
+        for stem in ["a", "b", "c"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        let path_a = test_path(Path::new("/somewhere/a.ext"));
+        let path_b = test_path(Path::new("/somewhere/b.ext"));
+
+        set_label(&conn, &path_a, Some(TrackLabel::Green)).unwrap();
+        set_label(&conn, &path_b, Some(TrackLabel::Red)).unwrap();
+        assert_eq!(
+            all_distinct_labels(&conn).unwrap(),
+            [TrackLabel::Red, TrackLabel::Green]
+        );
+
+        let tracks = get_tracks_with_label(&conn, TrackLabel::Green, RowOrdering::IdAsc).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["a"]);
+
+        set_label(&conn, &path_b, None).unwrap();
+        assert_eq!(
+            get_all_labels(&conn).unwrap(),
+            [(path_a.clone(), TrackLabel::Green)]
+        );
+
+        assert!(set_label(&conn, &test_path(Path::new("/somewhere/missing.ext")), None).is_err());
+        assert_eq!(TrackLabel::from_number(3), Some(TrackLabel::Green));
+        assert_eq!(TrackLabel::from_name("cyan"), Some(TrackLabel::Cyan));
+    }
+
+    #[test]
+    fn qualities() {
+        let db = gen_database();
+        for (stem, quality) in [
+            ("a", Some(QualityBadge::Lossless)),
+            ("b", Some(QualityBadge::HiRes)),
+            ("c", None),
+        ] {
+            let path = test_path(&Path::new("/somewhere").join(format!("{stem}.ext")));
+            let metadata = TrackMetadata {
+                title: Some(stem.to_string()),
+                quality,
+                ..Default::default()
+            };
+            TrackInsertable::try_from_track(&path, &metadata)
+                .unwrap()
+                .try_insert_or_update(&db.get_connection())
+                .unwrap();
+        }
+        let conn = db.get_connection();
+
+        assert_eq!(
+            all_distinct_qualities(&conn).unwrap(),
+            [QualityBadge::HiRes, QualityBadge::Lossless]
+        );
+        let tracks =
+            get_tracks_with_quality(&conn, QualityBadge::Lossless, RowOrdering::IdAsc).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["a"]);
+
+        let mut all = get_all_qualities(&conn).unwrap();
+        all.sort();
+        assert_eq!(
+            all,
+            [
+                (
+                    test_path(Path::new("/somewhere/a.ext")),
+                    QualityBadge::Lossless
+                ),
+                (
+                    test_path(Path::new("/somewhere/b.ext")),
+                    QualityBadge::HiRes
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn inbox() {
+        let db = gen_database();
+        for stem in ["a", "b", "c"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        // newly added tracks are not reviewed
+        set_reviewed(&conn, &test_path(Path::new("/somewhere/a.ext")), true).unwrap();
+
+        let days = all_distinct_unreviewed_days(&conn).unwrap();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        assert_eq!(days, [today.as_str()]);
+
+        let tracks = get_unreviewed_tracks(&conn, Some(&today)).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["b", "c"]);
+        assert!(
+            get_unreviewed_tracks(&conn, Some("1970-01-01"))
+                .unwrap()
+                .is_empty()
+        );
+
+        set_reviewed(&conn, &test_path(Path::new("/somewhere/b.ext")), true).unwrap();
+        drop(conn);
+
+        // re-scanning a track does not put it back into the inbox
+        insert_sample_track(&db, "b", 20, None);
+        let conn = db.get_connection();
+        let tracks = get_unreviewed_tracks(&conn, None).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["c"]);
+
+        set_reviewed(&conn, &test_path(Path::new("/somewhere/c.ext")), true).unwrap();
+        assert!(all_distinct_unreviewed_days(&conn).unwrap().is_empty());
+
+        assert!(
+            set_reviewed(&conn, &test_path(Path::new("/somewhere/missing.ext")), true).is_err()
+        );
+    }
+
+    #[test]
+    fn bpm() {
+        let db = gen_database();
+        insert_sample_track(&db, "a", 10, None);
+        let conn = db.get_connection();
+        let path = test_path(Path::new("/somewhere/a.ext"));
+        assert_eq!(get_bpm(&conn, &path).unwrap(), None);
+
+        set_bpm(&conn, &path, 120.5).unwrap();
+        assert_eq!(get_bpm(&conn, &path).unwrap(), Some(120.5));
+
+        // a rescan without a tagged tempo keeps the tapped one
+        drop(conn);
+        insert_sample_track(&db, "a", 10, None);
+        let conn = db.get_connection();
+        assert_eq!(get_bpm(&conn, &path).unwrap(), Some(120.5));
+
+        let missing = test_path(Path::new("/somewhere/missing.ext"));
+        assert_eq!(get_bpm(&conn, &missing).unwrap(), None);
+        assert!(set_bpm(&conn, &missing, 90.0).is_err());
+    }
+
+    #[test]
+    fn loudness() {
+        let db = gen_database();
+        insert_sample_track(&db, "a", 10, None);
+        insert_sample_track(&db, "b", 10, None);
+        let conn = db.get_connection();
+        let path = test_path(Path::new("/somewhere/a.ext"));
+        assert_eq!(get_loudness(&conn, &path).unwrap(), None);
+        assert_eq!(get_tracks_without_loudness(&conn).unwrap().len(), 2);
+
+        let loudness = Loudness {
+            integrated: -9.5,
+            peak: 0.98,
+        };
+        set_loudness(&conn, &path, &loudness).unwrap();
+        assert_eq!(get_loudness(&conn, &path).unwrap(), Some(loudness));
+        assert_eq!(
+            get_tracks_without_loudness(&conn).unwrap(),
+            [test_path(Path::new("/somewhere/b.ext"))]
+        );
+
+        // a rescan keeps the analyzed loudness
+        drop(conn);
+        insert_sample_track(&db, "a", 10, None);
+        let conn = db.get_connection();
+        assert_eq!(get_loudness(&conn, &path).unwrap(), Some(loudness));
+
+        let missing = test_path(Path::new("/somewhere/missing.ext"));
+        assert_eq!(get_loudness(&conn, &missing).unwrap(), None);
+        assert!(set_loudness(&conn, &missing, &loudness).is_err());
+    }
+
+    #[test]
+    fn damaged() {
+        let db = gen_database();
+        for stem in ["a", "b"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        let path = test_path(Path::new("/somewhere/a.ext"));
+        assert_eq!(get_damaged_tracks(&conn).unwrap(), []);
+
+        let regions = [
+            DamagedRegion {
+                start: Duration::from_millis(62_500),
+                end: Duration::from_secs(63),
+            },
+            DamagedRegion {
+                start: Duration::from_secs(130),
+                end: Duration::from_millis(130_026),
+            },
+        ];
+        set_damaged(&conn, &path, &regions).unwrap();
+        assert_eq!(
+            get_damaged_tracks(&conn).unwrap(),
+            [(
+                path.clone(),
+                "01:02.500-01:03.000, 02:10.000-02:10.026".to_string()
+            )]
+        );
+
+        set_damaged(&conn, &path, &[]).unwrap();
+        assert_eq!(get_damaged_tracks(&conn).unwrap(), []);
+
+        let missing = test_path(Path::new("/somewhere/missing.ext"));
+        assert!(set_damaged(&conn, &missing, &regions).is_err());
+    }
+
+    #[test]
+    fn energy() {
+        let db = gen_database();
+        for stem in ["a", "b", "c"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        assert!(all_distinct_energy_levels(&conn).unwrap().is_empty());
+
+        set_energy(&conn, &test_path(Path::new("/somewhere/a.ext")), Some(0.9)).unwrap();
+        set_energy(&conn, &test_path(Path::new("/somewhere/b.ext")), Some(0.1)).unwrap();
+        set_energy(&conn, &test_path(Path::new("/somewhere/c.ext")), None).unwrap();
+
+        assert_eq!(
+            all_distinct_energy_levels(&conn).unwrap(),
+            [EnergyLevel::Chill, EnergyLevel::High]
+        );
+        let tracks = get_tracks_with_energy(&conn, EnergyLevel::High, RowOrdering::IdAsc).unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["a"]);
+        assert!(
+            get_tracks_with_energy(&conn, EnergyLevel::Medium, RowOrdering::IdAsc)
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(set_energy(&conn, &test_path(Path::new("/somewhere/missing.ext")), None).is_err());
     }
 
     #[test]
-    fn single_track() {
+    fn sound_description() {
         let db = gen_database();
+        for stem in ["a", "b"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        let path_a = test_path(Path::new("/somewhere/a.ext"));
+        let path_b = test_path(Path::new("/somewhere/b.ext"));
 
-        let metadata = TrackMetadata {
-            title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
+        let titles = |query: &str| -> Vec<String> {
+            let query = SmartQuery::parse(query).unwrap();
+            get_tracks_matching(&conn, &query, RowOrdering::IdAsc)
+                .unwrap()
+                .into_iter()
+                .filter_map(|v| v.title)
+                .collect()
         };
-        let path = &test_path(Path::new("/somewhere/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
-
-        let res = get_track_from_path(&db.get_connection(), path).unwrap();
-
-        assert_eq!(res.title, Some("FileA1".to_string()));
+        assert!(titles("mood:calm").is_empty());
 
-        let err = get_track_from_path(
-            &db.get_connection(),
-            &test_path(Path::new("/somewhere/else.ext")),
+        set_sound_description(
+            &conn,
+            &path_a,
+            Some(Mood::Calm),
+            Some(SoundProfile::BassHeavy),
         )
-        .unwrap_err();
-        let err = err.downcast::<rusqlite::Error>().unwrap();
+        .unwrap();
+        set_sound_description(
+            &conn,
+            &path_b,
+            Some(Mood::Upbeat),
+            Some(SoundProfile::Bright),
+        )
+        .unwrap();
+        assert_eq!(titles("mood:calm"), vec!["a"]);
+        assert_eq!(titles("sound=bass-heavy"), vec!["a"]);
+        assert_eq!(titles("mood!=calm"), vec!["b"]);
 
-        assert_eq!(err, rusqlite::Error::QueryReturnedNoRows);
+        set_sound_description(&conn, &path_a, None, None).unwrap();
+        assert!(titles("mood:calm").is_empty());
+
+        let missing = test_path(Path::new("/somewhere/missing.ext"));
+        assert!(set_sound_description(&conn, &missing, None, None).is_err());
     }
 
     #[test]
-    fn track_read_to_path() {
-        let read = TrackRead {
-            id: 0,
-            file_dir: PathBuf::from("/path/to/somewhere"),
-            file_stem: OsString::from("filename"),
-            file_ext: OsString::from("ext"),
-            duration: None,
-            last_position: None,
-            album: None,
-            title: None,
-            genre: None,
-            artist_display: None,
-            artists: Vec::new(),
+    fn verify_failures() {
+        let db = gen_database();
+        for stem in ["a", "b", "c"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        let path_a = test_path(Path::new("/somewhere/a.ext"));
+        assert!(all_distinct_verify_failures(&conn).unwrap().is_empty());
+        assert_eq!(get_verify_failure(&conn, &path_a).unwrap(), None);
+
+        let failure = VerifyError {
+            kind: VerifyFailure::DecodeErrors,
+            detail: "3 corrupted packets".to_string(),
         };
+        set_verified(&conn, &path_a, Some(&failure)).unwrap();
+        set_verified(&conn, &test_path(Path::new("/somewhere/b.ext")), None).unwrap();
 
         assert_eq!(
-            read.as_pathbuf(),
-            PathBuf::from("/path/to/somewhere/filename.ext")
+            all_distinct_verify_failures(&conn).unwrap(),
+            [VerifyFailure::DecodeErrors]
+        );
+        assert_eq!(get_verify_failure(&conn, &path_a).unwrap(), Some(failure));
+        let tracks =
+            get_tracks_with_verify_failure(&conn, VerifyFailure::DecodeErrors, RowOrdering::IdAsc)
+                .unwrap();
+        let titles: Vec<_> = tracks.iter().filter_map(|v| v.title.as_deref()).collect();
+        assert_eq!(titles, ["a"]);
+
+        // passing a later verification clears the failure
+        set_verified(&conn, &path_a, None).unwrap();
+        assert!(all_distinct_verify_failures(&conn).unwrap().is_empty());
+
+        assert!(
+            set_verified(&conn, &test_path(Path::new("/somewhere/missing.ext")), None).is_err()
         );
     }
 
     #[test]
-    fn distinct_directories() {
+    fn offsets() {
         let db = gen_database();
+        insert_sample_track(&db, "a", 300, None);
+        let conn = db.get_connection();
+        let path = test_path(Path::new("/somewhere/a.ext"));
 
-        let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/dirA/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        assert!(get_offsets(&conn, &path).unwrap().is_empty());
 
-        let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA2".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
+        let offsets = TrackOffsets {
+            start: Some(Duration::from_secs(5)),
+            end: Some(Duration::from_secs(200)),
         };
-        let path = &test_path(Path::new("/somewhere/dirA/fileA2.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        set_offsets(&conn, &path, offsets).unwrap();
+        assert_eq!(get_offsets(&conn, &path).unwrap(), offsets);
 
-        let metadata = TrackMetadata {
-            album: Some("AlbumB".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileB1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/dirB/fileB1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        set_offsets(&conn, &path, TrackOffsets::default()).unwrap();
+        assert!(get_offsets(&conn, &path).unwrap().is_empty());
 
-        let res = all_distinct_directories(&db.get_connection()).unwrap();
+        // tracks not in the database have no offsets
+        let missing = test_path(Path::new("/somewhere/missing.ext"));
+        assert!(get_offsets(&conn, &missing).unwrap().is_empty());
+        assert!(set_offsets(&conn, &missing, offsets).is_err());
+    }
 
+    #[test]
+    fn offsets_parse() {
+        let offsets = TrackOffsets::parse("0:05 - 1:02:03").unwrap();
         assert_eq!(
-            &res,
-            &[
-                test_path(Path::new("/somewhere/dirA")).to_string_lossy(),
-                test_path(Path::new("/somewhere/dirB")).to_string_lossy()
-            ]
+            offsets,
+            TrackOffsets {
+                start: Some(Duration::from_secs(5)),
+                end: Some(Duration::from_secs(3723)),
+            }
+        );
+        assert_eq!(offsets.to_string(), "00:05 - 1:02:03");
+        assert_eq!(TrackOffsets::parse(&offsets.to_string()).unwrap(), offsets);
+        assert_eq!(
+            offsets.extm3u_directives(),
+            ["#EXTVLCOPT:start-time=5", "#EXTVLCOPT:stop-time=3723"]
+        );
+
+        assert_eq!(
+            TrackOffsets::parse(" - 90").unwrap(),
+            TrackOffsets {
+                start: None,
+                end: Some(Duration::from_secs(90)),
+            }
+        );
+        assert_eq!(
+            TrackOffsets::parse("30").unwrap().start,
+            Some(Duration::from_secs(30))
         );
+        assert!(TrackOffsets::parse(" - ").unwrap().is_empty());
+        assert!(TrackOffsets::parse("abc").is_err());
+        assert!(TrackOffsets::parse("1:2:3:4").is_err());
+        assert!(TrackOffsets::parse("2:00 - 1:00").is_err());
     }
 
     #[test]
-    fn tracks_by_directory() {
+    fn play_stats() {
         let db = gen_database();
-
-        let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/dirA/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
-
-        let metadata = TrackMetadata {
-            album: Some("AlbumA".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileA2".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/dirA/fileA2.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
-
-        let metadata = TrackMetadata {
-            album: Some("AlbumB".to_string()),
-            album_artist: Some("ArtistA".to_string()),
-            album_artists: Some(vec!["ArtistA".to_string()]),
-            artist: Some("ArtistA".to_string()),
-            artists: Some(vec!["ArtistA".to_string()]),
-            title: Some("FileB1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/dirB/fileB1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
-
-        let res = get_tracks_from_directory(
-            &db.get_connection(),
-            &test_path(Path::new("/somewhere/dirA")),
-            RowOrdering::IdAsc,
+        for stem in ["a", "b", "c"] {
+            insert_sample_track(&db, stem, 10, None);
+        }
+        let conn = db.get_connection();
+        mark_played(&conn, &test_path(Path::new("/somewhere/a.ext"))).unwrap();
+        mark_played(&conn, &test_path(Path::new("/somewhere/a.ext"))).unwrap();
+        set_label(
+            &conn,
+            &test_path(Path::new("/somewhere/c.ext")),
+            Some(TrackLabel::Red),
         )
         .unwrap();
-        let res: Vec<String> = res.into_iter().map(|v| v.title.unwrap()).collect();
 
-        assert_eq!(&res, &["FileA1", "FileA2"]);
+        let stats = get_play_stats(&conn, &[]).unwrap();
+        let stats: Vec<_> = stats
+            .iter()
+            .map(|(track, stats)| {
+                (
+                    track.title.as_deref().unwrap(),
+                    stats.play_count,
+                    stats.last_played_at.is_some(),
+                    stats.label,
+                )
+            })
+            .collect();
+        assert_eq!(
+            stats,
+            [
+                ("a", 2, true, None),
+                ("b", 0, false, None),
+                ("c", 0, false, Some(TrackLabel::Red))
+            ]
+        );
+
+        let stats = get_play_stats(&conn, &[TrackLabel::Red]).unwrap();
+        assert_eq!(stats.len(), 2);
     }
 
     #[test]
-    fn delete_tracks_artists_mapping() {
+    fn merge_imported_play_stats() {
         let db = gen_database();
-
-        let metadata = TrackMetadata {
-            artist: Some("ArtistA feat. ArtistB".to_string()),
-            artists: Some(vec!["ArtistA".to_string(), "ArtistB".to_string()]),
-            title: Some("FileA1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
+        insert_sample_track(&db, "a", 10, None);
+        let conn = db.get_connection();
+        let path_a = test_path(Path::new("/somewhere/a.ext"));
+        mark_played(&conn, &path_a).unwrap();
+        let played_at = |year| {
+            chrono::DateTime::parse_from_rfc3339(&format!("{year}-01-01T00:00:00+00:00"))
+                .unwrap()
+                .to_utc()
         };
-        let path_a1 = &test_path(Path::new("/somewhere/fileA1.ext"));
-        let insertable = TrackInsertable::try_from_track(path_a1, &metadata).unwrap();
-        let _ = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
 
-        let metadata = TrackMetadata {
-            artist: Some("ArtistA feat. ArtistB".to_string()),
-            artists: Some(vec!["ArtistA".to_string(), "ArtistB".to_string()]),
-            title: Some("FileB1".to_string()),
-            duration: Some(Duration::from_secs(10)),
-            ..Default::default()
-        };
-        let path = &test_path(Path::new("/somewhere/fileB1.ext"));
-        let insertable = TrackInsertable::try_from_track(path, &metadata).unwrap();
-        let track2_id = insertable
-            .try_insert_or_update(&db.get_connection())
-            .unwrap();
+        assert!(
+            merge_play_stats(&conn, &path_a, Some(12), Some(played_at(2001)), Some(4)).unwrap()
+        );
+        // the lower count, earlier play and second rating do not replace the current ones
+        assert!(merge_play_stats(&conn, &path_a, Some(3), Some(played_at(2000)), Some(2)).unwrap());
+        assert!(
+            !merge_play_stats(
+                &conn,
+                &test_path(Path::new("/somewhere/missing.ext")),
+                Some(1),
+                None,
+                None
+            )
+            .unwrap()
+        );
 
-        let mapping_counts = count_all_track_artist_mapping(&db.get_connection()).unwrap();
+        let stats = get_play_stats(&conn, &[]).unwrap();
+        assert_eq!(stats[0].1.play_count, 12);
+        // marked as played just now, which is later than the imported plays
+        assert!(stats[0].1.last_played_at.unwrap() > played_at(2001));
+        assert_eq!(get_rating(&conn, &path_a).unwrap(), Some(4));
+    }
 
-        assert_eq!(mapping_counts, 4);
+    #[test]
+    fn find_by_tags() {
+        let db = gen_database();
+        insert_sample_track(&db, "a", 10, Some("AlbumA"));
+        insert_sample_track(&db, "b", 10, None);
+        let conn = db.get_connection();
 
-        let affected =
-            delete_tracks_artists_mapping_for(&db.get_connection(), Either::Left(path_a1)).unwrap();
+        assert_eq!(
+            find_tracks_by_tags(&conn, "artista", "A").unwrap(),
+            vec![(
+                test_path(Path::new("/somewhere/a.ext")),
+                Some("AlbumA".to_string())
+            )]
+        );
+        assert!(
+            find_tracks_by_tags(&conn, "ArtistB", "a")
+                .unwrap()
+                .is_empty()
+        );
+    }
 
-        assert_eq!(affected, 2);
+    #[test]
+    fn smart_query_matches() {
+        let db = gen_database();
+        insert_sample_track(&db, "a", 10, Some("AlbumA"));
+        insert_sample_track(&db, "b", 200, None);
+        insert_sample_track(&db, "c", 10, None);
+        let conn = db.get_connection();
+        let path_a = test_path(Path::new("/somewhere/a.ext"));
+        mark_played(&conn, &path_a).unwrap();
+        merge_play_stats(&conn, &path_a, Some(12), None, Some(4)).unwrap();
+        set_label(
+            &conn,
+            &test_path(Path::new("/somewhere/b.ext")),
+            Some(TrackLabel::Red),
+        )
+        .unwrap();
 
-        let mapping_counts = count_all_track_artist_mapping(&db.get_connection()).unwrap();
+        let titles = |query: &str| -> Vec<String> {
+            let query = SmartQuery::parse(query).unwrap();
+            get_tracks_matching(&conn, &query, RowOrdering::IdAsc)
+                .unwrap()
+                .into_iter()
+                .filter_map(|v| v.title)
+                .collect()
+        };
 
-        assert_eq!(mapping_counts, 2);
+        assert_eq!(titles("playcount<2"), vec!["b", "c"]);
+        assert_eq!(titles("rating>=4 album:albuma"), vec!["a"]);
+        // tracks without a rating or album are not excluded by a negated term
+        assert_eq!(titles("-rating:4 -album:AlbumA"), vec!["b", "c"]);
+        assert_eq!(
+            titles("title=b OR (duration<1m AND NOT playcount>0)"),
+            vec!["b", "c"]
+        );
+        assert_eq!(titles("label:red"), vec!["b"]);
+        assert_eq!(titles("added<1d"), vec!["a", "b", "c"]);
+        assert!(titles("added>30d").is_empty());
+        // never played is longer ago than any age
+        assert_eq!(titles("lastplayed>1y"), vec!["b", "c"]);
+        assert_eq!(titles("artista 10%"), Vec::<String>::new());
+    }
 
-        let affected =
-            delete_tracks_artists_mapping_for(&db.get_connection(), Either::Right(track2_id))
-                .unwrap();
+    #[test]
+    fn play_statistics() {
+        let db = gen_database();
+        insert_sample_track(&db, "a", 10, None);
+        insert_sample_track(&db, "b", 200, None);
+        insert_sample_track(&db, "c", 10, None);
+        let conn = db.get_connection();
+        let path_a = test_path(Path::new("/somewhere/a.ext"));
+        let path_b = test_path(Path::new("/somewhere/b.ext"));
+
+        for _ in 0..3 {
+            mark_played(&conn, &path_a).unwrap();
+        }
+        mark_played(&conn, &path_b).unwrap();
+        mark_skipped(&conn, &path_b).unwrap();
+        set_rating(&conn, &path_a, Some(9)).unwrap();
+        assert_eq!(get_rating(&conn, &path_a).unwrap(), Some(5));
+        assert!(set_rating(&conn, Path::new("/somewhere/none.ext"), Some(3)).is_err());
 
-        assert_eq!(affected, 2);
+        assert_eq!(
+            get_top_artists(&conn, 10).unwrap(),
+            vec![ArtistPlays {
+                artist: "ArtistA".to_string(),
+                play_count: 4
+            }]
+        );
+        assert_eq!(
+            get_top_tracks(&conn, 10).unwrap(),
+            vec![
+                TrackPlays {
+                    title: "a".to_string(),
+                    artist: Some("ArtistA".to_string()),
+                    play_count: 3,
+                    skip_count: 0,
+                    rating: Some(5),
+                },
+                TrackPlays {
+                    title: "b".to_string(),
+                    artist: Some("ArtistA".to_string()),
+                    play_count: 1,
+                    skip_count: 1,
+                    rating: None,
+                },
+            ]
+        );
 
-        let mapping_counts = count_all_track_artist_mapping(&db.get_connection()).unwrap();
+        let at = |v: &str| chrono::DateTime::parse_from_rfc3339(v).unwrap().to_utc();
+        assert!(
+            record_play(
+                &conn,
+                &path_a,
+                at("2026-10-07T10:00:00Z"),
+                Duration::from_secs(100)
+            )
+            .unwrap()
+        );
+        assert!(
+            record_play(
+                &conn,
+                &path_b,
+                at("2026-10-15T09:00:00Z"),
+                Duration::from_secs(60)
+            )
+            .unwrap()
+        );
+        assert!(
+            !record_play(
+                &conn,
+                &test_path(Path::new("/somewhere/none.ext")),
+                at("2026-10-15T09:00:00Z"),
+                Duration::from_secs(60)
+            )
+            .unwrap()
+        );
 
-        assert_eq!(mapping_counts, 0);
+        let weeks = get_listening_per_week(&conn, 3, at("2026-10-15T12:00:00Z")).unwrap();
+        let weeks: Vec<(String, u64)> = weeks
+            .into_iter()
+            .map(|v| (v.week_start.to_string(), v.listened.as_secs()))
+            .collect();
+        assert_eq!(
+            weeks,
+            vec![
+                ("2026-09-28".to_string(), 0),
+                ("2026-10-05".to_string(), 100),
+                ("2026-10-12".to_string(), 60),
+            ]
+        );
     }
 }
@@ -1,4 +1,6 @@
 #![allow(clippy::module_name_repetitions)]
+use std::path::PathBuf;
+
 use anyhow::{Context, anyhow, bail};
 
 // using lower mod to restrict clippy
@@ -10,6 +12,8 @@ mod protobuf {
 pub use protobuf::*;
 
 use crate::config::v2::server::LoopMode;
+use crate::config::v2::server::equalizer::{BANDS, EqualizerPreset, EqualizerSettings};
+use crate::downloads::DownloadInfo;
 
 // implement transform function for easy use
 impl From<protobuf::Duration> for std::time::Duration {
@@ -30,6 +34,154 @@ impl From<std::time::Duration> for protobuf::Duration {
 /// The primitive in which time (current position / total duration) will be stored as
 pub type PlayerTimeUnit = std::time::Duration;
 
+/// The version of the gRPC API this build implements.
+///
+/// This should be incremented whenever a method or message is added or changed in a way clients may want to check for.
+///
+/// Version `0` is reserved for servers that do not implement `GetCapabilities`.
+pub const API_VERSION: u32 = 20;
+
+/// Names of optional features a server may report in [`ServerCapabilities::features`].
+pub mod features {
+    /// The server supports `SubscribeServerUpdates`.
+    pub const STREAM_UPDATES: &str = "stream-updates";
+    /// The server supports the playlist manipulation methods like `AddToPlaylist` and `SwapTracks`.
+    pub const PLAYLIST: &str = "playlist";
+    /// The server supports changing the playback speed.
+    pub const SPEED: &str = "speed";
+    /// The server supports toggling gapless playback.
+    pub const GAPLESS: &str = "gapless";
+    /// The server supports `SetVocalReduction`, though the active backend may not.
+    pub const VOCAL_REDUCTION: &str = "vocal-reduction";
+    /// The server supports `SetRecording`, though the active backend may not.
+    pub const RECORDING: &str = "recording";
+    /// The server supports `AddRandomToPlaylist`.
+    pub const RANDOM_SAMPLE: &str = "random-sample";
+    /// The server supports `SetAlbumMode`.
+    pub const ALBUM_MODE: &str = "album-mode";
+    /// The server supports `ReportActivity` and includes the activity in `GetProgress` and the stream updates.
+    pub const ACTIVITY: &str = "activity";
+    /// The server supports [`PlaylistAddRandom::AutoDj`](super::playlist_helpers::PlaylistAddRandom::AutoDj).
+    pub const AUTO_DJ: &str = "auto-dj";
+    /// The server supports `SeekTo`.
+    pub const SEEK_TO: &str = "seek-to";
+    /// The server supports `SetNormalization`, though the active backend may not.
+    pub const NORMALIZATION: &str = "normalization";
+    /// The server supports [`PlaylistTrackSource::VideoUrl`](super::playlist_helpers::PlaylistTrackSource::VideoUrl).
+    pub const VIDEO_STREAM: &str = "video-stream";
+    /// The server supports `TransferSession`.
+    pub const SESSION_TRANSFER: &str = "session-transfer";
+    /// The server supports `EnqueueDownload` and the other download methods.
+    pub const DOWNLOAD_QUEUE: &str = "download-queue";
+    /// The server sends the listening time in the stream updates and may pause playback for a break.
+    pub const LISTENING_LIMIT: &str = "listening-limit";
+    /// The server supports `SetEqualizer`, though the active backend may not.
+    pub const EQUALIZER: &str = "equalizer";
+    /// The server supports `SetSmartSpeed`, though the active backend may not.
+    pub const SMART_SPEED: &str = "smart-speed";
+    /// The server supports `SetDucking`.
+    pub const DUCKING: &str = "ducking";
+    /// The server supports `GetOutputDevices` and `SetOutputDevice`, though the active backend may not.
+    pub const OUTPUT_DEVICE: &str = "output-device";
+    /// The server supports `SetAbLoop` and includes the loop in `GetProgress`.
+    pub const AB_LOOP: &str = "ab-loop";
+    /// The server supports `RunMacro`.
+    pub const MACROS: &str = "macros";
+
+    /// All features supported by this build.
+    pub const ALL: &[&str] = &[
+        STREAM_UPDATES,
+        PLAYLIST,
+        SPEED,
+        GAPLESS,
+        VOCAL_REDUCTION,
+        RECORDING,
+        RANDOM_SAMPLE,
+        ALBUM_MODE,
+        ACTIVITY,
+        AUTO_DJ,
+        SEEK_TO,
+        NORMALIZATION,
+        VIDEO_STREAM,
+        SESSION_TRANSFER,
+        DOWNLOAD_QUEUE,
+        LISTENING_LIMIT,
+        EQUALIZER,
+        SMART_SPEED,
+        DUCKING,
+        OUTPUT_DEVICE,
+        AB_LOOP,
+        MACROS,
+    ];
+}
+
+/// The capabilities a server reports, see gRPC `GetCapabilities`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ServerCapabilities {
+    /// The version string of the server binary, only informational.
+    pub server_version: String,
+    /// The API version the server implements, see [`API_VERSION`].
+    pub api_version: u32,
+    /// All backends compiled into the server.
+    pub backends: Vec<String>,
+    /// The backend currently in use.
+    pub active_backend: String,
+    /// All optional features the server supports, see [`features`].
+    pub features: Vec<String>,
+}
+
+impl ServerCapabilities {
+    /// Capabilities to assume for a server that does not implement `GetCapabilities`.
+    ///
+    /// Those servers still support everything that existed before API versioning.
+    #[must_use]
+    pub fn legacy() -> Self {
+        Self {
+            server_version: String::from("unknown"),
+            api_version: 0,
+            backends: Vec::new(),
+            active_backend: String::new(),
+            features: features::ALL.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Check if the server reported support for the given feature.
+    #[must_use]
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|v| v == feature)
+    }
+
+    /// Check if the server implements at least the given API version.
+    #[must_use]
+    pub fn is_at_least(&self, api_version: u32) -> bool {
+        self.api_version >= api_version
+    }
+}
+
+impl From<protobuf::Capabilities> for ServerCapabilities {
+    fn from(value: protobuf::Capabilities) -> Self {
+        Self {
+            server_version: value.server_version,
+            api_version: value.api_version,
+            backends: value.backends,
+            active_backend: value.active_backend,
+            features: value.features,
+        }
+    }
+}
+
+impl From<ServerCapabilities> for protobuf::Capabilities {
+    fn from(value: ServerCapabilities) -> Self {
+        Self {
+            server_version: value.server_version,
+            api_version: value.api_version,
+            backends: value.backends,
+            active_backend: value.active_backend,
+            features: value.features,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
 pub enum RunningStatus {
     #[default]
@@ -114,6 +266,166 @@ impl From<PlayerProgress> for protobuf::UpdateProgress {
     }
 }
 
+/// A region of the current track that is repeated until cleared, see gRPC `SetAbLoop`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbLoop {
+    pub start: PlayerTimeUnit,
+    pub end: PlayerTimeUnit,
+}
+
+impl AbLoop {
+    /// Create a loop from `start` to `end`, none if `end` is not after `start`.
+    #[must_use]
+    pub fn new(start: PlayerTimeUnit, end: PlayerTimeUnit) -> Option<Self> {
+        (end > start).then_some(Self { start, end })
+    }
+
+    /// Get the loop from the gRPC state, none if it is cleared.
+    #[must_use]
+    pub fn from_grpc(value: protobuf::AbLoopState) -> Option<Self> {
+        Self::new(value.start?.into(), value.end?.into())
+    }
+
+    /// Get the gRPC state of `value`, with both fields unset if cleared.
+    #[must_use]
+    pub fn to_grpc(value: Option<Self>) -> protobuf::AbLoopState {
+        protobuf::AbLoopState {
+            start: value.map(|v| v.start.into()),
+            end: value.map(|v| v.end.into()),
+        }
+    }
+}
+
+/// The background work of a client, like downloads and library scans, see gRPC `ReportActivity`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityInfo {
+    /// The downloads still running
+    pub downloads: u32,
+    /// The downloads that finished since the downloads started, including the failed ones
+    pub downloads_done: u32,
+    /// The downloads that failed since the downloads started
+    pub downloads_failed: u32,
+    /// The error of the last failed download
+    pub last_error: Option<String>,
+    /// The library scans running
+    pub scans: u32,
+}
+
+impl ActivityInfo {
+    /// Check if nothing is running.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.downloads == 0 && self.scans == 0
+    }
+
+    /// Get the progress of all downloads since they started, in percent, [`None`] if none is running.
+    #[must_use]
+    pub fn download_percent(&self) -> Option<u32> {
+        if self.downloads == 0 {
+            return None;
+        }
+
+        let total = u64::from(self.downloads) + u64::from(self.downloads_done);
+        // cannot be above 100, as "downloads_done" is at most "total"
+        #[allow(clippy::cast_possible_truncation)]
+        Some((u64::from(self.downloads_done) * 100 / total) as u32)
+    }
+}
+
+impl std::fmt::Display for ActivityInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(percent) = self.download_percent() {
+            let items = if self.downloads == 1 { "item" } else { "items" };
+            parts.push(format!(
+                "downloading {} {items}, {percent}%",
+                self.downloads
+            ));
+        }
+        if self.downloads_failed > 0 {
+            match &self.last_error {
+                Some(err) => parts.push(format!("{} failed: {err}", self.downloads_failed)),
+                None => parts.push(format!("{} failed", self.downloads_failed)),
+            }
+        }
+        if self.scans > 0 {
+            parts.push("scanning the library".to_string());
+        }
+
+        if parts.is_empty() {
+            write!(f, "idle")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+impl From<protobuf::ActivityState> for ActivityInfo {
+    fn from(value: protobuf::ActivityState) -> Self {
+        Self {
+            downloads: value.downloads,
+            downloads_done: value.downloads_done,
+            downloads_failed: value.downloads_failed,
+            last_error: value.optional_last_error.map(|v| {
+                let protobuf::activity_state::OptionalLastError::LastError(v) = v;
+                v
+            }),
+            scans: value.scans,
+        }
+    }
+}
+
+impl From<ActivityInfo> for protobuf::ActivityState {
+    fn from(value: ActivityInfo) -> Self {
+        Self {
+            downloads: value.downloads,
+            downloads_done: value.downloads_done,
+            downloads_failed: value.downloads_failed,
+            optional_last_error: value
+                .last_error
+                .map(protobuf::activity_state::OptionalLastError::LastError),
+            scans: value.scans,
+        }
+    }
+}
+
+impl From<protobuf::EqualizerState> for EqualizerSettings {
+    /// Unknown presets are [`EqualizerPreset::Flat`], missing custom gains are 0 dB.
+    fn from(value: protobuf::EqualizerState) -> Self {
+        let mut custom = [0.0; BANDS];
+        for (band, gain) in custom.iter_mut().zip(value.custom) {
+            *band = gain;
+        }
+
+        Self {
+            enabled: value.enabled,
+            preset: EqualizerPreset::from_name(&value.preset).unwrap_or_default(),
+            custom,
+        }
+    }
+}
+
+impl From<EqualizerSettings> for protobuf::EqualizerState {
+    fn from(value: EqualizerSettings) -> Self {
+        Self {
+            enabled: value.enabled,
+            preset: value.preset.name().to_string(),
+            custom: value.custom.to_vec(),
+        }
+    }
+}
+
+/// The time listened since the last break, see the `listening_limit` server settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ListeningTimeInfo {
+    /// The time played since the last break
+    pub listened: PlayerTimeUnit,
+    /// The configured limit, `None` if there is none
+    pub limit: Option<PlayerTimeUnit>,
+    /// Indicate if playback was just paused for a break
+    pub break_started: bool,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TrackChangedInfo {
     /// Current track index in the playlist
@@ -136,6 +448,10 @@ pub enum UpdateEvents {
     GaplessChanged { gapless: bool },
     PlaylistChanged(UpdatePlaylistEvents),
     Progress(PlayerProgress),
+    ActivityChanged(ActivityInfo),
+    DownloadChanged(DownloadInfo),
+    ListeningTime(ListeningTimeInfo),
+    LibraryChanged(Vec<PathBuf>),
 }
 
 // might not be fully true, but necessary for Msg
@@ -180,6 +496,29 @@ impl From<UpdateEvents> for protobuf::StreamUpdates {
             }
             UpdateEvents::PlaylistChanged(ev) => StreamTypes::PlaylistChanged(ev.into()),
             UpdateEvents::Progress(ev) => StreamTypes::ProgressChanged(ev.into()),
+            UpdateEvents::ActivityChanged(ev) => {
+                StreamTypes::ActivityChanged(UpdateActivityChanged {
+                    msg: Some(ev.into()),
+                })
+            }
+            UpdateEvents::DownloadChanged(ev) => {
+                StreamTypes::DownloadChanged(UpdateDownloadChanged {
+                    msg: Some(ev.into()),
+                })
+            }
+            UpdateEvents::ListeningTime(ev) => StreamTypes::ListeningTime(UpdateListeningTime {
+                listened: Some(ev.listened.into()),
+                limit: ev.limit.map(Into::into),
+                break_started: ev.break_started,
+            }),
+            UpdateEvents::LibraryChanged(dirs) => {
+                StreamTypes::LibraryChanged(UpdateLibraryChanged {
+                    dirs: dirs
+                        .iter()
+                        .map(|v| v.to_string_lossy().to_string())
+                        .collect(),
+                })
+            }
         };
 
         Self { r#type: Some(val) }
@@ -226,6 +565,23 @@ impl TryFrom<protobuf::StreamUpdates> for UpdateEvents {
                 ev.try_into()
                     .context("In \"StreamUpdates.types.progress_changed\"")?,
             ),
+            StreamTypes::ActivityChanged(ev) => Self::ActivityChanged(
+                unwrap_msg(ev.msg, "StreamUpdates.types.activity_changed.msg")?.into(),
+            ),
+            StreamTypes::DownloadChanged(ev) => Self::DownloadChanged(
+                unwrap_msg(ev.msg, "StreamUpdates.types.download_changed.msg")?
+                    .try_into()
+                    .context("In \"StreamUpdates.types.download_changed\"")?,
+            ),
+            StreamTypes::ListeningTime(ev) => Self::ListeningTime(ListeningTimeInfo {
+                listened: unwrap_msg(ev.listened, "StreamUpdates.types.listening_time.listened")?
+                    .into(),
+                limit: ev.limit.map(Into::into),
+                break_started: ev.break_started,
+            }),
+            StreamTypes::LibraryChanged(ev) => {
+                Self::LibraryChanged(ev.dirs.into_iter().map(PathBuf::from).collect())
+            }
         };
 
         Ok(res)
@@ -271,6 +627,16 @@ pub struct PlaylistSwapInfo {
     pub index_b: u64,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistTrackErrorInfo {
+    /// The Index of the track that failed to play.
+    pub at_index: u64,
+    /// The Id of the track.
+    pub trackid: playlist_helpers::PlaylistTrackSource,
+    /// Why the track failed to play, `None` if the error got cleared.
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct PlaylistShuffledInfo {
     pub tracks: PlaylistTracks,
@@ -285,6 +651,7 @@ pub enum UpdatePlaylistEvents {
     PlaylistLoopMode(PlaylistLoopModeInfo),
     PlaylistSwapTracks(PlaylistSwapInfo),
     PlaylistShuffled(PlaylistShuffledInfo),
+    PlaylistTrackError(PlaylistTrackErrorInfo),
 }
 
 type PPlaylistTypes = protobuf::update_playlist::Type;
@@ -301,6 +668,7 @@ impl From<UpdatePlaylistEvents> for protobuf::UpdatePlaylist {
                         .map(protobuf::playlist_add_track::OptionalTitle::Title),
                     duration: Some(vals.duration.into()),
                     id: Some(vals.trackid.into()),
+                    optional_error: None,
                 })
             }
             UpdatePlaylistEvents::PlaylistRemoveTrack(vals) => {
@@ -324,6 +692,15 @@ impl From<UpdatePlaylistEvents> for protobuf::UpdatePlaylist {
                     shuffled: Some(vals.tracks),
                 })
             }
+            UpdatePlaylistEvents::PlaylistTrackError(vals) => {
+                PPlaylistTypes::TrackError(protobuf::PlaylistTrackError {
+                    at_index: vals.at_index,
+                    id: Some(vals.trackid.into()),
+                    optional_error: vals
+                        .error
+                        .map(protobuf::playlist_track_error::OptionalError::Error),
+                })
+            }
         };
 
         Self { r#type: Some(val) }
@@ -371,6 +748,18 @@ impl TryFrom<protobuf::UpdatePlaylist> for UpdatePlaylistEvents {
                 let shuffled = unwrap_msg(ev.shuffled, "UpdatePlaylist.type.shuffled.shuffled")?;
                 Self::PlaylistShuffled(PlaylistShuffledInfo { tracks: shuffled })
             }
+            PPlaylistTypes::TrackError(ev) => Self::PlaylistTrackError(PlaylistTrackErrorInfo {
+                at_index: ev.at_index,
+                trackid: unwrap_msg(
+                    unwrap_msg(ev.id, "UpdatePlaylist.type.track_error.id")?.source,
+                    "UpdatePlaylist.type.track_error.id.source",
+                )?
+                .try_into()?,
+                error: ev.optional_error.map(|v| {
+                    let protobuf::playlist_track_error::OptionalError::Error(v) = v;
+                    v
+                }),
+            }),
         };
 
         Ok(res)
@@ -397,7 +786,7 @@ pub fn clamp_u16(val: u32) -> u16 {
 pub mod playlist_helpers {
     use anyhow::Context;
 
-    use super::{PlaylistTracksToRemoveClear, protobuf, unwrap_msg};
+    use super::{PlayerTimeUnit, PlaylistTracksToRemoveClear, protobuf, unwrap_msg};
 
     /// A Id / Source for a given Track
     #[derive(Debug, Clone, PartialEq)]
@@ -405,6 +794,8 @@ pub mod playlist_helpers {
         Path(String),
         Url(String),
         PodcastUrl(String),
+        /// A video page, streamed with the audio extracted by yt-dlp
+        VideoUrl(String),
     }
 
     impl From<PlaylistTrackSource> for protobuf::track_id::Source {
@@ -413,6 +804,7 @@ pub mod playlist_helpers {
                 PlaylistTrackSource::Path(v) => Self::Path(v),
                 PlaylistTrackSource::Url(v) => Self::Url(v),
                 PlaylistTrackSource::PodcastUrl(v) => Self::PodcastUrl(v),
+                PlaylistTrackSource::VideoUrl(v) => Self::VideoUrl(v),
             }
         }
     }
@@ -433,6 +825,7 @@ pub mod playlist_helpers {
                 protobuf::track_id::Source::Path(v) => Self::Path(v),
                 protobuf::track_id::Source::Url(v) => Self::Url(v),
                 protobuf::track_id::Source::PodcastUrl(v) => Self::PodcastUrl(v),
+                protobuf::track_id::Source::VideoUrl(v) => Self::VideoUrl(v),
             })
         }
     }
@@ -445,11 +838,41 @@ pub mod playlist_helpers {
         }
     }
 
+    /// The title and duration of a track to add, only used if the server does not know them itself, like for videos.
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    pub struct PlaylistTrackInfo {
+        pub title: Option<String>,
+        pub duration: Option<PlayerTimeUnit>,
+    }
+
+    impl From<PlaylistTrackInfo> for protobuf::TrackInfo {
+        fn from(value: PlaylistTrackInfo) -> Self {
+            Self {
+                optional_title: value.title.map(protobuf::track_info::OptionalTitle::Title),
+                duration: value.duration.map(Into::into),
+            }
+        }
+    }
+
+    impl From<protobuf::TrackInfo> for PlaylistTrackInfo {
+        fn from(value: protobuf::TrackInfo) -> Self {
+            Self {
+                title: value.optional_title.map(|v| {
+                    let protobuf::track_info::OptionalTitle::Title(v) = v;
+                    v
+                }),
+                duration: value.duration.map(Into::into),
+            }
+        }
+    }
+
     /// Data for requesting some tracks to be added in the server
     #[derive(Debug, Clone, PartialEq)]
     pub struct PlaylistAddTrack {
         pub at_index: u64,
         pub tracks: Vec<PlaylistTrackSource>,
+        /// The info of each track in `tracks`, either empty or one per track.
+        pub infos: Vec<PlaylistTrackInfo>,
     }
 
     impl PlaylistAddTrack {
@@ -458,12 +881,24 @@ pub mod playlist_helpers {
             Self {
                 at_index,
                 tracks: vec![track],
+                infos: Vec::new(),
             }
         }
 
         #[must_use]
         pub fn new_vec(at_index: u64, tracks: Vec<PlaylistTrackSource>) -> Self {
-            Self { at_index, tracks }
+            Self {
+                at_index,
+                tracks,
+                infos: Vec::new(),
+            }
+        }
+
+        /// Add the known info of each track, see [`PlaylistTrackInfo`].
+        #[must_use]
+        pub fn with_infos(mut self, infos: Vec<PlaylistTrackInfo>) -> Self {
+            self.infos = infos;
+            self
         }
     }
 
@@ -472,6 +907,7 @@ pub mod playlist_helpers {
             Self {
                 at_index: value.at_index,
                 tracks: value.tracks.into_iter().map(Into::into).collect(),
+                infos: value.infos.into_iter().map(Into::into).collect(),
             }
         }
     }
@@ -489,6 +925,7 @@ pub mod playlist_helpers {
             Ok(Self {
                 at_index: value.at_index,
                 tracks,
+                infos: value.infos.into_iter().map(Into::into).collect(),
             })
         }
     }
@@ -633,4 +1070,177 @@ pub mod playlist_helpers {
             })
         }
     }
+
+    /// Data for requesting random tracks from the library database to be added to the end of the playlist
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum PlaylistAddRandom {
+        /// Add the given amount of random tracks
+        Tracks(u32),
+        /// Add a random album with at least the given amount of tracks
+        Album(u32),
+        /// Add random never-played tracks until they add up to at least the given duration
+        Unplayed(std::time::Duration),
+        /// Add the given amount of tracks chosen by the auto-DJ, see [`auto_dj`](crate::auto_dj)
+        AutoDj(u32),
+    }
+
+    impl From<PlaylistAddRandom> for protobuf::PlaylistAddRandom {
+        fn from(value: PlaylistAddRandom) -> Self {
+            let kind = match value {
+                PlaylistAddRandom::Tracks(v) => protobuf::playlist_add_random::Type::Tracks(v),
+                PlaylistAddRandom::Album(v) => protobuf::playlist_add_random::Type::Album(v),
+                PlaylistAddRandom::Unplayed(v) => {
+                    protobuf::playlist_add_random::Type::Unplayed(v.into())
+                }
+                PlaylistAddRandom::AutoDj(v) => protobuf::playlist_add_random::Type::AutoDj(v),
+            };
+
+            Self { r#type: Some(kind) }
+        }
+    }
+
+    impl TryFrom<protobuf::PlaylistAddRandom> for PlaylistAddRandom {
+        type Error = anyhow::Error;
+
+        fn try_from(value: protobuf::PlaylistAddRandom) -> Result<Self, Self::Error> {
+            Ok(match unwrap_msg(value.r#type, "PlaylistAddRandom.type")? {
+                protobuf::playlist_add_random::Type::Tracks(v) => Self::Tracks(v),
+                protobuf::playlist_add_random::Type::Album(v) => Self::Album(v),
+                protobuf::playlist_add_random::Type::Unplayed(v) => Self::Unplayed(v.into()),
+                protobuf::playlist_add_random::Type::AutoDj(v) => Self::AutoDj(v),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::{
+        API_VERSION, AbLoop, ActivityInfo, ListeningTimeInfo, ServerCapabilities, UpdateEvents,
+        features, protobuf,
+    };
+
+    #[test]
+    fn legacy_capabilities_should_support_pre_versioning_features() {
+        let caps = ServerCapabilities::legacy();
+
+        assert_eq!(caps.api_version, 0);
+        assert!(!caps.is_at_least(API_VERSION));
+        for feature in features::ALL {
+            assert!(caps.supports(feature));
+        }
+        assert!(!caps.supports("unknown-feature"));
+    }
+
+    #[test]
+    fn capabilities_should_roundtrip_grpc() {
+        let caps = ServerCapabilities {
+            server_version: String::from("v0.0.0"),
+            api_version: API_VERSION,
+            backends: vec![String::from("rusty")],
+            active_backend: String::from("rusty"),
+            features: vec![features::PLAYLIST.to_string()],
+        };
+
+        let grpc = protobuf::Capabilities::from(caps.clone());
+        assert_eq!(ServerCapabilities::from(grpc), caps);
+        assert!(caps.supports(features::PLAYLIST));
+        assert!(!caps.supports(features::SPEED));
+    }
+
+    #[test]
+    fn activity_should_summarize() {
+        assert_eq!(ActivityInfo::default().to_string(), "idle");
+
+        let activity = ActivityInfo {
+            downloads: 3,
+            downloads_done: 2,
+            downloads_failed: 1,
+            last_error: Some(String::from("no response")),
+            scans: 1,
+        };
+        assert_eq!(activity.download_percent(), Some(40));
+        assert_eq!(
+            activity.to_string(),
+            "downloading 3 items, 40%, 1 failed: no response, scanning the library"
+        );
+
+        let done = ActivityInfo {
+            downloads_done: 2,
+            ..Default::default()
+        };
+        assert!(done.is_idle());
+        assert_eq!(done.download_percent(), None);
+    }
+
+    #[test]
+    fn activity_should_roundtrip_grpc() {
+        let activity = ActivityInfo {
+            downloads: 1,
+            last_error: Some(String::from("error")),
+            ..Default::default()
+        };
+
+        let grpc = protobuf::StreamUpdates::from(UpdateEvents::ActivityChanged(activity.clone()));
+        assert_eq!(
+            UpdateEvents::try_from(grpc).unwrap(),
+            UpdateEvents::ActivityChanged(activity)
+        );
+    }
+
+    #[test]
+    fn listening_time_should_roundtrip_grpc() {
+        for limit in [None, Some(Duration::from_secs(3600))] {
+            let info = ListeningTimeInfo {
+                listened: Duration::from_secs(120),
+                limit,
+                break_started: true,
+            };
+
+            let grpc = protobuf::StreamUpdates::from(UpdateEvents::ListeningTime(info));
+            assert_eq!(
+                UpdateEvents::try_from(grpc).unwrap(),
+                UpdateEvents::ListeningTime(info)
+            );
+        }
+    }
+
+    #[test]
+    fn library_changed_should_roundtrip_grpc() {
+        let ev = UpdateEvents::LibraryChanged(vec![
+            PathBuf::from("/music/album"),
+            PathBuf::from("/music"),
+        ]);
+
+        let grpc = protobuf::StreamUpdates::from(ev.clone());
+        assert_eq!(UpdateEvents::try_from(grpc).unwrap(), ev);
+    }
+
+    #[test]
+    fn ab_loop_should_roundtrip_grpc() {
+        let ab_loop = AbLoop::new(Duration::from_secs(10), Duration::from_millis(12500)).unwrap();
+
+        assert_eq!(
+            AbLoop::from_grpc(AbLoop::to_grpc(Some(ab_loop))),
+            Some(ab_loop)
+        );
+        assert_eq!(AbLoop::from_grpc(AbLoop::to_grpc(None)), None);
+        // a loop has to end after it starts
+        assert_eq!(
+            AbLoop::new(Duration::from_secs(10), Duration::from_secs(10)),
+            None
+        );
+        assert_eq!(
+            AbLoop::from_grpc(protobuf::AbLoopState {
+                start: Some(Duration::from_secs(10).into()),
+                end: None,
+            }),
+            None
+        );
+    }
 }
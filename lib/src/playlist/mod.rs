@@ -1,10 +1,12 @@
 //! This is a very simple url extractor for different kinds of playlist formats: M3U, PLS, ASX, XSPF
+//! and the termusic [shared playlist](share)
 //!
 //! It is not optimized yet and does create a lot of strings on the way.
 
 mod asx;
 mod m3u;
 mod pls;
+pub mod share;
 mod xspf;
 
 use std::{
@@ -141,6 +143,19 @@ pub fn decode(content: &str) -> Result<Vec<PlaylistValue>> {
         for item in items {
             set.push(item.location);
         }
+    } else if content.starts_with(share::HEADER) {
+        let items = share::decode(content);
+        set.reserve(items.len());
+        for item in items {
+            let value = match item.source {
+                share::ShareSource::Youtube(id) => {
+                    PlaylistValue::Url(Url::parse(&share::ShareSource::youtube_url(&id))?)
+                }
+                share::ShareSource::Url(url) => PlaylistValue::Url(Url::parse(&url)?),
+                share::ShareSource::Path(path) => PlaylistValue::Path(path),
+            };
+            set.push(value);
+        }
     } else if content_small.contains("[playlist]") {
         let items = pls::decode(content);
         set.reserve(items.len());
@@ -213,6 +228,20 @@ Title1=mytitle
         );
     }
 
+    #[test]
+    fn should_parse_share() {
+        let items =
+            decode("# termusic shared playlist\nSong\thttps://youtu.be/abc\nLocal\tArtist/a.mp3\n")
+                .unwrap();
+        assert_eq!(
+            items,
+            [
+                PlaylistValue::Url(Url::parse("https://www.youtube.com/watch?v=abc").unwrap()),
+                PlaylistValue::Path("Artist/a.mp3".into()),
+            ]
+        );
+    }
+
     #[test]
     fn should_parse_m3u() {
         let playlist = "/some/absolute/unix/path.mp3";
@@ -0,0 +1,283 @@
+//! A plain text format to share a playlist between termusic users.
+//!
+//! Each entry is a line of the title and the source, separated by a tab (shown as `<TAB>`):
+//!
+//! ```txt
+//! # termusic shared playlist
+//! Some Song<TAB>https://www.youtube.com/watch?v=dQw4w9WgXcQ
+//! Some Radio<TAB>https://example.com/stream.mp3
+//! Local Song<TAB>Artist/Album/01 - Local Song.mp3
+//! ```
+//!
+//! Local files are stored relative to the music directory they are in, so that they can be found in the music
+//! directories of the importing user, if they have the same layout.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::track::{MediaTypes, Track};
+
+/// The first line of a shared playlist.
+pub const HEADER: &str = "# termusic shared playlist";
+
+/// The ending of the file name of a shared playlist, the extension is `txt` to be easily opened by anyone.
+pub const FILE_SUFFIX: &str = ".share.txt";
+
+/// Where a shared entry can be found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareSource {
+    /// A youtube video, identified by its id
+    Youtube(String),
+    /// A url, like a radio stream or a podcast episode
+    Url(String),
+    /// A local file, relative to a music directory if it is in one
+    Path(PathBuf),
+}
+
+impl ShareSource {
+    /// Parse a source as written by [`encode`], also accepting the common youtube url forms.
+    #[must_use]
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Some(id) = youtube_id(value) {
+            return Self::Youtube(id.to_string());
+        }
+        if value.contains("://") {
+            return Self::Url(value.to_string());
+        }
+
+        Self::Path(PathBuf::from(value))
+    }
+
+    /// Get the url of a [`ShareSource::Youtube`] to download it.
+    #[must_use]
+    pub fn youtube_url(id: &str) -> String {
+        format!("https://www.youtube.com/watch?v={id}")
+    }
+
+    /// Find a [`ShareSource::Path`] in `music_dirs`, or relative to `base`, the directory of the shared playlist.
+    ///
+    /// Returns [`None`] for other sources or if the file does not exist in any of them.
+    #[must_use]
+    pub fn resolve_path(&self, music_dirs: &[PathBuf], base: &Path) -> Option<PathBuf> {
+        let Self::Path(path) = self else {
+            return None;
+        };
+
+        if path.is_absolute() {
+            return path.exists().then(|| path.clone());
+        }
+
+        music_dirs
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(std::iter::once(base))
+            .map(|dir| dir.join(path))
+            .find(|v| v.exists())
+    }
+}
+
+impl std::fmt::Display for ShareSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Youtube(id) => write!(f, "{}", Self::youtube_url(id)),
+            Self::Url(url) => write!(f, "{url}"),
+            // relative paths always use "/", so that the list can be shared between systems
+            Self::Path(path) if path.is_relative() => {
+                let parts: Vec<_> = path
+                    .components()
+                    .map(|v| v.as_os_str().to_string_lossy())
+                    .collect();
+                write!(f, "{}", parts.join("/"))
+            }
+            Self::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A entry of a shared playlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareEntry {
+    /// The title to show, may be empty
+    pub title: String,
+    pub source: ShareSource,
+}
+
+impl ShareEntry {
+    /// Create the entry for `track`, with local files relative to the first of `music_dirs` they are in.
+    #[must_use]
+    pub fn from_track(track: &Track, music_dirs: &[PathBuf]) -> Self {
+        let source = match track.inner() {
+            MediaTypes::Track(data) => {
+                let path = data.path();
+                let relative = music_dirs
+                    .iter()
+                    .find_map(|dir| path.strip_prefix(dir).ok())
+                    .unwrap_or(path);
+                ShareSource::Path(relative.to_path_buf())
+            }
+            MediaTypes::Radio(data) => ShareSource::parse(data.url()),
+            MediaTypes::Podcast(data) => ShareSource::Url(data.url().to_string()),
+            MediaTypes::Video(data) => ShareSource::parse(data.url()),
+        };
+
+        let title = match (track.artist(), track.title()) {
+            (Some(artist), Some(title)) => format!("{artist} - {title}"),
+            (None, Some(title)) => title.to_string(),
+            _ => track
+                .path()
+                .and_then(Path::file_stem)
+                .map(|v| v.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+
+        Self { title, source }
+    }
+}
+
+/// Check if `path` is named like a shared playlist, see [`FILE_SUFFIX`].
+#[must_use]
+pub fn is_share_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|v| v.to_string_lossy().ends_with(FILE_SUFFIX))
+}
+
+/// Write the entries as a shared playlist.
+#[must_use]
+pub fn encode(entries: &[ShareEntry]) -> String {
+    let mut out = String::from(HEADER);
+    out.push('\n');
+    for entry in entries {
+        // tabs and newlines would break the format, and are not meaningful in a title
+        let title = entry.title.replace(['\t', '\n', '\r'], " ");
+        let _ = writeln!(out, "{title}\t{}", entry.source);
+    }
+
+    out
+}
+
+/// Read the entries of a shared playlist, ignoring empty lines and `#` comments like the [`HEADER`].
+///
+/// Lines without a tab are only a source, so that a plain list of urls or paths can also be imported.
+#[must_use]
+pub fn decode(content: &str) -> Vec<ShareEntry> {
+    content
+        .lines()
+        .map(str::trim_end)
+        // a title may start with "#", but is always followed by a tab
+        .filter(|v| !v.trim().is_empty() && (!v.starts_with('#') || v.contains('\t')))
+        .map(|line| {
+            let (title, source) = line.rsplit_once('\t').unwrap_or(("", line));
+            ShareEntry {
+                title: title.trim().to_string(),
+                source: ShareSource::parse(source),
+            }
+        })
+        .collect()
+}
+
+/// Get the video id of a youtube url, like `https://www.youtube.com/watch?v=ID`, `https://youtu.be/ID`
+/// or `https://music.youtube.com/watch?v=ID`.
+#[must_use]
+pub fn youtube_id(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+
+    let id = if let Some(rest) = rest.strip_prefix("youtu.be/") {
+        rest.split(['?', '&', '#']).next()?
+    } else if rest.starts_with("youtube.com/") || rest.starts_with("music.youtube.com/") {
+        let (_, query) = rest.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|v| v.strip_prefix("v="))?
+            .split('#')
+            .next()?
+    } else {
+        return None;
+    };
+
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|v| v.is_ascii_alphanumeric() || v == '-' || v == '_');
+
+    valid.then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{ShareEntry, ShareSource, decode, encode, is_share_file, youtube_id};
+
+    #[test]
+    fn should_get_youtube_id() {
+        assert_eq!(
+            youtube_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=10"),
+            Some("dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            youtube_id("https://youtu.be/dQw4w9WgXcQ?si=x"),
+            Some("dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            youtube_id("https://music.youtube.com/watch?list=a&v=a-b_c"),
+            Some("a-b_c")
+        );
+        assert_eq!(youtube_id("https://example.com/watch?v=abc"), None);
+        assert_eq!(youtube_id("https://www.youtube.com/watch?v="), None);
+    }
+
+    #[test]
+    fn should_roundtrip() {
+        let entries = vec![
+            ShareEntry {
+                title: String::from("Song\twith tab"),
+                source: ShareSource::Youtube(String::from("abc")),
+            },
+            ShareEntry {
+                title: String::from("Radio"),
+                source: ShareSource::Url(String::from("https://example.com/stream.mp3")),
+            },
+            ShareEntry {
+                title: String::new(),
+                source: ShareSource::Path(PathBuf::from("Artist/Album/01.mp3")),
+            },
+        ];
+
+        let text = encode(&entries);
+        assert_eq!(
+            text,
+            "# termusic shared playlist\nSong with tab\thttps://www.youtube.com/watch?v=abc\nRadio\thttps://example.com/stream.mp3\n\tArtist/Album/01.mp3\n"
+        );
+
+        let mut expected = entries;
+        expected[0].title = String::from("Song with tab");
+        assert_eq!(decode(&text), expected);
+    }
+
+    #[test]
+    fn should_decode_plain_lists() {
+        let res = decode("\n# comment\nhttps://youtu.be/abc\n/music/a.mp3\n");
+        assert_eq!(
+            res,
+            [
+                ShareEntry {
+                    title: String::new(),
+                    source: ShareSource::Youtube(String::from("abc")),
+                },
+                ShareEntry {
+                    title: String::new(),
+                    source: ShareSource::Path(PathBuf::from("/music/a.mp3")),
+                },
+            ]
+        );
+
+        assert!(is_share_file(&PathBuf::from("/tmp/party.share.txt")));
+        assert!(!is_share_file(&PathBuf::from("/tmp/notes.txt")));
+    }
+}
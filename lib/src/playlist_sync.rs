@@ -0,0 +1,772 @@
+//! Syncing the playlists of a local directory with a remote WebDAV folder or git repository.
+//!
+//! Each playlist is a `.m3u` file, named after the playlist. The entries of every playlist at the last sync are
+//! stored in [`STATE_FILE_NAME`] in the local directory, so that additions and removals on either side can be
+//! merged instead of one side overwriting the other, see [`merge`].
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::{Client, ClientBuilder, Method, RequestBuilder, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::config::v2::tui::{PlaylistSync, PlaylistSyncBackend};
+use crate::credentials::CREDENTIALS;
+use crate::podcast::{BasicAuth, parse_basic_auth};
+use crate::ratelimit::LIMITER;
+
+/// File name of the state stored in the local directory, containing the entries of each playlist at the last sync.
+pub const STATE_FILE_NAME: &str = ".termusic-playlist-sync.json";
+
+/// The extension of synced playlists.
+const EXTENSION: &str = "m3u";
+
+/// Get the [`CREDENTIALS`] key for a WebDAV server on `host`.
+///
+/// The secret is `user:password` (or just `user`) for HTTP basic authentication.
+#[must_use]
+pub fn credential_key(host: &str) -> String {
+    format!("webdav:{host}")
+}
+
+/// The entries of each playlist at the last sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+struct SyncState {
+    playlists: BTreeMap<String, Vec<String>>,
+}
+
+impl SyncState {
+    /// Load the state from `dir`, or a empty one if there is none yet.
+    fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(STATE_FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                serde_json::from_str(&content).with_context(|| path.display().to_string())
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| path.display().to_string()),
+        }
+    }
+
+    /// Save the state to `dir`.
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(STATE_FILE_NAME);
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content).with_context(|| path.display().to_string())
+    }
+}
+
+/// What happened in a [`sync_playlists`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Playlists written to the remote
+    pub uploaded: usize,
+    /// Playlists written to the local directory
+    pub downloaded: usize,
+    /// Playlists deleted on either side, because they were deleted on the other
+    pub deleted: usize,
+    /// Playlists which were the same on both sides
+    pub unchanged: usize,
+    /// Playlists deleted on one side but changed on the other, which were kept, with the reason
+    pub conflicts: Vec<(String, String)>,
+}
+
+impl std::fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} uploaded, {} downloaded, {} deleted, {} up-to-date",
+            self.uploaded, self.downloaded, self.deleted, self.unchanged
+        )?;
+        if !self.conflicts.is_empty() {
+            write!(f, ", {} conflicts", self.conflicts.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Merge the changes of `local` and `remote` since `base`, the entries at the last sync.
+///
+/// Entries removed on either side are removed, entries added on the remote are inserted after the entry preceding
+/// them on the remote, so the local order is kept with the remote additions in their place.
+#[must_use]
+pub fn merge(base: &[String], local: &[String], remote: &[String]) -> Vec<String> {
+    let base: HashSet<&String> = base.iter().collect();
+    let remote_set: HashSet<&String> = remote.iter().collect();
+
+    // the entries the remote did not remove
+    let mut res: Vec<String> = local
+        .iter()
+        .filter(|v| !base.contains(v) || remote_set.contains(v))
+        .cloned()
+        .collect();
+
+    let mut anchor: Option<usize> = None;
+    for entry in remote {
+        if let Some(pos) = res.iter().position(|v| v == entry) {
+            anchor = Some(pos);
+            continue;
+        }
+        // in the base, but not in the result, so it was removed locally
+        if base.contains(entry) {
+            continue;
+        }
+
+        let pos = anchor.map_or(0, |v| v + 1);
+        res.insert(pos, entry.clone());
+        anchor = Some(pos);
+    }
+
+    res
+}
+
+/// The state of a playlist after a sync, see [`resolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Resolved {
+    /// The entries on both sides, [`None`] if it is deleted on both
+    entries: Option<Vec<String>>,
+    /// Why a deletion was not applied
+    conflict: Option<&'static str>,
+}
+
+/// Resolve the entries of one playlist, [`None`] meaning it does not exist (anymore) on that side.
+fn resolve(
+    base: Option<&[String]>,
+    local: Option<&[String]>,
+    remote: Option<&[String]>,
+) -> Resolved {
+    let (entries, conflict) = match (local, remote) {
+        (Some(local), Some(remote)) => (Some(merge(base.unwrap_or_default(), local, remote)), None),
+        (Some(local), None) => match base {
+            Some(base) if base == local => (None, None),
+            Some(_) => (
+                Some(local.to_vec()),
+                Some("deleted on the remote, but changed locally"),
+            ),
+            None => (Some(local.to_vec()), None),
+        },
+        (None, Some(remote)) => match base {
+            Some(base) if base == remote => (None, None),
+            Some(_) => (
+                Some(remote.to_vec()),
+                Some("deleted locally, but changed on the remote"),
+            ),
+            None => (Some(remote.to_vec()), None),
+        },
+        (None, None) => (None, None),
+    };
+
+    Resolved { entries, conflict }
+}
+
+/// Get the entries of a m3u playlist, all lines that are not empty or `#` comments.
+#[must_use]
+pub fn parse_entries(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && !v.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Write `entries` as a m3u playlist.
+#[must_use]
+pub fn format_entries(entries: &[String]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for entry in entries {
+        let _ = writeln!(out, "{entry}");
+    }
+
+    out
+}
+
+/// A place the playlists are synced with.
+#[allow(async_fn_in_trait)] // all implementations are local, no `Send` bound is needed
+pub trait PlaylistRemote {
+    /// Get the remote ready, like fetching the latest state of a repository.
+    async fn prepare(&mut self) -> Result<()>;
+    /// Get the names of all playlists.
+    async fn list(&self) -> Result<Vec<String>>;
+    /// Get the content of the playlist `name`.
+    async fn read(&self, name: &str) -> Result<String>;
+    /// Create or overwrite the playlist `name`.
+    async fn write(&mut self, name: &str, content: &str) -> Result<()>;
+    /// Delete the playlist `name`.
+    async fn delete(&mut self, name: &str) -> Result<()>;
+    /// Publish all changes, like committing and pushing them to a repository.
+    async fn finish(&mut self) -> Result<()>;
+}
+
+/// The configured remote.
+#[derive(Debug, Clone)]
+pub enum Remote {
+    WebDav(WebDav),
+    Git(Git),
+}
+
+impl Remote {
+    /// Create the remote configured in `settings`.
+    ///
+    /// # Errors
+    ///
+    /// - if no backend or remote is configured
+    /// - if the remote is not a valid url for the backend
+    /// - if the http client cannot be created
+    pub fn new(settings: &PlaylistSync) -> Result<Self> {
+        let remote = settings.remote.trim();
+        if remote.is_empty() {
+            bail!("No remote configured (\"playlist_sync.remote\")");
+        }
+
+        Ok(match settings.backend {
+            PlaylistSyncBackend::None => {
+                bail!("No backend configured (\"playlist_sync.backend\")")
+            }
+            PlaylistSyncBackend::WebDav => Self::WebDav(WebDav::new(remote)?),
+            PlaylistSyncBackend::Git => Self::Git(Git::new(remote, &settings.branch)?),
+        })
+    }
+}
+
+impl PlaylistRemote for Remote {
+    async fn prepare(&mut self) -> Result<()> {
+        match self {
+            Self::WebDav(v) => v.prepare().await,
+            Self::Git(v) => v.prepare().await,
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        match self {
+            Self::WebDav(v) => v.list().await,
+            Self::Git(v) => v.list().await,
+        }
+    }
+
+    async fn read(&self, name: &str) -> Result<String> {
+        match self {
+            Self::WebDav(v) => v.read(name).await,
+            Self::Git(v) => v.read(name).await,
+        }
+    }
+
+    async fn write(&mut self, name: &str, content: &str) -> Result<()> {
+        match self {
+            Self::WebDav(v) => v.write(name, content).await,
+            Self::Git(v) => v.write(name, content).await,
+        }
+    }
+
+    async fn delete(&mut self, name: &str) -> Result<()> {
+        match self {
+            Self::WebDav(v) => v.delete(name).await,
+            Self::Git(v) => v.delete(name).await,
+        }
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        match self {
+            Self::WebDav(v) => v.finish().await,
+            Self::Git(v) => v.finish().await,
+        }
+    }
+}
+
+/// A folder on a WebDAV server, authenticated with the [`CREDENTIALS`] of [`credential_key`] if stored.
+#[derive(Debug, Clone)]
+pub struct WebDav {
+    client: Client,
+    /// The folder, always ending with a `/`
+    base: Url,
+    auth: Option<BasicAuth>,
+}
+
+impl WebDav {
+    /// Create the remote for the folder at `url`.
+    ///
+    /// # Errors
+    ///
+    /// - if `url` is not a valid url
+    /// - if the http client cannot be created
+    pub fn new(url: &str) -> Result<Self> {
+        let url = if url.ends_with('/') {
+            url.to_string()
+        } else {
+            format!("{url}/")
+        };
+        let base = Url::parse(&url).with_context(|| format!("parse webdav url \"{url}\""))?;
+
+        let auth = base.host_str().and_then(|host| {
+            CREDENTIALS
+                .get(&credential_key(host))
+                .inspect_err(|err| {
+                    warn!("Looking up the credentials for \"{host}\" failed: {err:#}");
+                })
+                .ok()
+                .flatten()
+                .map(|secret| parse_basic_auth(&secret))
+        });
+
+        let client = ClientBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self { client, base, auth })
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.auth {
+            Some((user, password)) => request.basic_auth(user, password.as_ref()),
+            None => request,
+        }
+    }
+
+    fn playlist_url(&self, name: &str) -> Result<Url> {
+        let file = urlencoding::encode(&format!("{name}.{EXTENSION}")).into_owned();
+        Ok(self.base.join(&file)?)
+    }
+
+    /// List the folder with a `PROPFIND`, [`None`] if it does not exist.
+    async fn propfind(&self) -> Result<Option<String>> {
+        let request = self
+            .request(Method::from_bytes(b"PROPFIND")?, self.base.clone())
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml")
+            .body(r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/></d:prop></d:propfind>"#);
+        let response = LIMITER.send(request).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.text().await?))
+    }
+}
+
+impl PlaylistRemote for WebDav {
+    async fn prepare(&mut self) -> Result<()> {
+        if self.propfind().await?.is_some() {
+            return Ok(());
+        }
+
+        let request = self.request(Method::from_bytes(b"MKCOL")?, self.base.clone());
+        LIMITER
+            .send(request)
+            .await?
+            .error_for_status()
+            .context("create the webdav folder")?;
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let Some(body) = self.propfind().await? else {
+            return Ok(Vec::new());
+        };
+
+        Ok(parse_propfind_names(&body)?
+            .into_iter()
+            .filter_map(|file| {
+                file.strip_suffix(&format!(".{EXTENSION}"))
+                    .map(ToString::to_string)
+            })
+            .collect())
+    }
+
+    async fn read(&self, name: &str) -> Result<String> {
+        let request = self.request(Method::GET, self.playlist_url(name)?);
+        let response = LIMITER.send(request).await?.error_for_status()?;
+
+        Ok(response.text().await?)
+    }
+
+    async fn write(&mut self, name: &str, content: &str) -> Result<()> {
+        let request = self
+            .request(Method::PUT, self.playlist_url(name)?)
+            .body(content.to_string());
+        LIMITER.send(request).await?.error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn delete(&mut self, name: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, self.playlist_url(name)?);
+        let response = LIMITER.send(request).await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            response.error_for_status()?;
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Get the decoded file names of all `href`s in a `PROPFIND` response, without the listed folder itself.
+fn parse_propfind_names(body: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(body);
+    reader.config_mut().trim_text(true);
+    let mut in_href = false;
+    let mut names = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.local_name().as_ref() == b"href" => in_href = true,
+            Event::End(e) if e.local_name().as_ref() == b"href" => in_href = false,
+            Event::Text(text) if in_href => {
+                let href = text.decode()?;
+                // folders end with a "/", and the only one listed is the requested folder
+                if href.ends_with('/') {
+                    continue;
+                }
+                let Some(file) = href.rsplit('/').next() else {
+                    continue;
+                };
+                names.push(urlencoding::decode(file)?.into_owned());
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(names)
+}
+
+/// A git repository, with a working copy in the cache directory.
+///
+/// The authentication is left to git, like a ssh key or a credential helper.
+#[derive(Debug, Clone)]
+pub struct Git {
+    url: String,
+    branch: String,
+    /// The working copy
+    dir: PathBuf,
+    changed: bool,
+}
+
+impl Git {
+    /// Create the remote for the repository at `url`, syncing to `branch`.
+    ///
+    /// # Errors
+    ///
+    /// - if the cache directory cannot be found
+    pub fn new(url: &str, branch: &str) -> Result<Self> {
+        let cache_dir = dirs::cache_dir().context("failed to find the cache directory")?;
+        // one working copy per repository
+        let dir_name: String = url
+            .chars()
+            .map(|v| if v.is_ascii_alphanumeric() { v } else { '_' })
+            .collect();
+        let branch = branch.trim();
+
+        Ok(Self {
+            url: url.to_string(),
+            branch: if branch.is_empty() { "main" } else { branch }.to_string(),
+            dir: cache_dir
+                .join("termusic")
+                .join("playlist-sync")
+                .join(dir_name),
+            changed: false,
+        })
+    }
+
+    fn playlist_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.{EXTENSION}"))
+    }
+
+    /// Run git with `args` in the working copy.
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()
+            .await
+            .context("run git, is it installed?")?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl PlaylistRemote for Git {
+    async fn prepare(&mut self) -> Result<()> {
+        if !self.dir.join(".git").exists() {
+            std::fs::create_dir_all(&self.dir).with_context(|| self.dir.display().to_string())?;
+            self.git(&["clone", &self.url, "."]).await?;
+        }
+
+        self.git(&["fetch", "origin"]).await?;
+        let upstream = format!("origin/{}", self.branch);
+        // discard anything left over from a failed sync
+        if self
+            .git(&["rev-parse", "--verify", &upstream])
+            .await
+            .is_ok()
+        {
+            self.git(&["checkout", "-f", "-B", &self.branch, &upstream])
+                .await?;
+        } else {
+            // a new (or empty) repository
+            self.git(&["checkout", "-f", "-B", &self.branch]).await?;
+        }
+        self.git(&["clean", "-fd"]).await?;
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let dir = std::fs::read_dir(&self.dir).with_context(|| self.dir.display().to_string())?;
+
+        Ok(dir
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(OsStr::new(EXTENSION)))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect())
+    }
+
+    async fn read(&self, name: &str) -> Result<String> {
+        let path = self.playlist_path(name);
+        std::fs::read_to_string(&path).with_context(|| path.display().to_string())
+    }
+
+    async fn write(&mut self, name: &str, content: &str) -> Result<()> {
+        let path = self.playlist_path(name);
+        std::fs::write(&path, content).with_context(|| path.display().to_string())?;
+        self.changed = true;
+
+        Ok(())
+    }
+
+    async fn delete(&mut self, name: &str) -> Result<()> {
+        let path = self.playlist_path(name);
+        std::fs::remove_file(&path).with_context(|| path.display().to_string())?;
+        self.changed = true;
+
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+
+        self.git(&["add", "-A"]).await?;
+        self.git(&["commit", "-m", "Sync playlists"]).await?;
+        let refspec = format!("HEAD:{}", self.branch);
+        self.git(&["push", "origin", &refspec])
+            .await
+            .context("the remote may have changed during the sync, sync again")?;
+        self.changed = false;
+
+        Ok(())
+    }
+}
+
+/// Get the names of all playlists in `dir`.
+fn local_names(dir: &Path) -> Result<Vec<String>> {
+    let entries = std::fs::read_dir(dir).with_context(|| dir.display().to_string())?;
+
+    Ok(entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension() == Some(OsStr::new(EXTENSION)))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .collect())
+}
+
+/// Sync the playlists in the configured local directory with the configured remote.
+///
+/// # Errors
+///
+/// - if the sync is not fully configured
+/// - if the local directory or the remote cannot be read or written
+pub async fn sync_playlists(settings: &PlaylistSync) -> Result<SyncReport> {
+    let Some(dir) = &settings.local_dir else {
+        bail!("No local directory configured (\"playlist_sync.local_dir\")");
+    };
+    let dir = shellexpand::path::tilde(dir).into_owned();
+    let mut remote = Remote::new(settings)?;
+
+    remote.prepare().await.context("prepare the remote")?;
+    sync_with(&dir, &mut remote).await
+}
+
+/// Sync the playlists in `dir` with `remote`, which is already prepared.
+async fn sync_with(dir: &Path, remote: &mut impl PlaylistRemote) -> Result<SyncReport> {
+    let mut state = SyncState::load(dir)?;
+    let local = local_names(dir)?;
+    let remote_names = remote.list().await.context("list the remote playlists")?;
+
+    let names: BTreeSet<String> = local
+        .iter()
+        .chain(remote_names.iter())
+        .chain(state.playlists.keys())
+        .cloned()
+        .collect();
+
+    let mut report = SyncReport::default();
+    for name in names {
+        let path = dir.join(format!("{name}.{EXTENSION}"));
+        let local_entries = if local.contains(&name) {
+            let content =
+                std::fs::read_to_string(&path).with_context(|| path.display().to_string())?;
+            Some(parse_entries(&content))
+        } else {
+            None
+        };
+        let remote_entries = if remote_names.contains(&name) {
+            Some(parse_entries(&remote.read(&name).await?))
+        } else {
+            None
+        };
+
+        let resolved = resolve(
+            state.playlists.get(&name).map(Vec::as_slice),
+            local_entries.as_deref(),
+            remote_entries.as_deref(),
+        );
+        if let Some(reason) = resolved.conflict {
+            report.conflicts.push((name.clone(), reason.to_string()));
+        }
+
+        let Some(entries) = resolved.entries else {
+            if local_entries.is_some() {
+                std::fs::remove_file(&path).with_context(|| path.display().to_string())?;
+                report.deleted += 1;
+            }
+            if remote_entries.is_some() {
+                remote.delete(&name).await?;
+                report.deleted += 1;
+            }
+            state.playlists.remove(&name);
+            continue;
+        };
+
+        let content = format_entries(&entries);
+        let mut changed = false;
+        if local_entries.as_ref() != Some(&entries) {
+            std::fs::write(&path, &content).with_context(|| path.display().to_string())?;
+            report.downloaded += 1;
+            changed = true;
+        }
+        if remote_entries.as_ref() != Some(&entries) {
+            remote.write(&name, &content).await?;
+            report.uploaded += 1;
+            changed = true;
+        }
+        if !changed {
+            report.unchanged += 1;
+        }
+
+        state.playlists.insert(name, entries);
+    }
+
+    remote.finish().await.context("publish the changes")?;
+    state.save(dir)?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Resolved, format_entries, merge, parse_entries, parse_propfind_names, resolve};
+
+    fn list(v: &[&str]) -> Vec<String> {
+        v.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn should_merge_additions_and_removals() {
+        let base = list(&["a", "b", "c"]);
+        // locally "b" removed and "x" added, remotely "c" removed and "y" added after "a"
+        let local = list(&["a", "c", "x"]);
+        let remote = list(&["a", "y", "b"]);
+
+        assert_eq!(merge(&base, &local, &remote), list(&["a", "y", "x"]));
+
+        // the same additions on both sides are not duplicated
+        let local = list(&["a", "b", "c", "z"]);
+        let remote = list(&["z", "a", "b", "c"]);
+        assert_eq!(merge(&base, &local, &remote), list(&["a", "b", "c", "z"]));
+
+        // without a base everything is added
+        assert_eq!(
+            merge(&[], &list(&["a", "b"]), &list(&["c", "b", "d"])),
+            list(&["c", "a", "b", "d"])
+        );
+    }
+
+    #[test]
+    fn should_resolve_deletions() {
+        let base = list(&["a"]);
+        let changed = list(&["a", "b"]);
+
+        // deleted on one side and unchanged on the other
+        assert_eq!(
+            resolve(Some(&base), Some(&base), None),
+            Resolved {
+                entries: None,
+                conflict: None
+            }
+        );
+        assert_eq!(resolve(Some(&base), None, Some(&base)).entries, None);
+
+        // deleted on one side, but changed on the other
+        let res = resolve(Some(&base), Some(&changed), None);
+        assert_eq!(res.entries, Some(changed.clone()));
+        assert!(res.conflict.is_some());
+        let res = resolve(Some(&base), None, Some(&changed));
+        assert_eq!(res.entries, Some(changed.clone()));
+        assert!(res.conflict.is_some());
+
+        // new on one side
+        assert_eq!(
+            resolve(None, None, Some(&changed)),
+            Resolved {
+                entries: Some(changed),
+                conflict: None
+            }
+        );
+    }
+
+    #[test]
+    fn should_parse_entries_and_propfind() {
+        let entries = list(&["/music/a.mp3", "https://example.com/stream"]);
+        let content = format_entries(&entries);
+        assert_eq!(
+            parse_entries(&format!("{content}\n#EXTINF:1,Title\n")),
+            entries
+        );
+
+        let body = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+    <d:response><d:href>/dav/playlists/</d:href></d:response>
+    <d:response><d:href>/dav/playlists/Road%20Trip.m3u</d:href></d:response>
+    <d:response><d:href>/dav/playlists/notes.txt</d:href></d:response>
+</d:multistatus>"#;
+        assert_eq!(
+            parse_propfind_names(body).unwrap(),
+            list(&["Road Trip.m3u", "notes.txt"])
+        );
+    }
+}
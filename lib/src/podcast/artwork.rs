@@ -0,0 +1,134 @@
+//! On-disk cache for the artwork of podcast feeds and episodes, so that it is only downloaded once.
+
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::ratelimit::LIMITER;
+
+/// Get the default artwork cache directory, `termusic/artwork` in the cache directory.
+pub fn default_cache_dir() -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir().context("failed to find the cache directory")?;
+
+    Ok(cache_dir.join("termusic").join("artwork"))
+}
+
+/// Artwork stored as one file per url.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtworkCache {
+    dir: PathBuf,
+}
+
+impl ArtworkCache {
+    #[must_use]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Create a cache in [`default_cache_dir`].
+    pub fn new_default() -> Result<Self> {
+        Ok(Self::new(default_cache_dir()?))
+    }
+
+    /// Get the path of the cache file for `url`.
+    fn file_path(&self, url: &str) -> PathBuf {
+        self.dir.join(cache_key(url))
+    }
+
+    /// Get the path of the cached artwork of `url`, if it is cached.
+    #[must_use]
+    pub fn cached_path(&self, url: &str) -> Option<PathBuf> {
+        Some(self.file_path(url)).filter(|v| v.is_file())
+    }
+
+    /// Get the cached artwork of `url`, if there is one.
+    #[must_use]
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let path = self.file_path(url);
+        match fs::read(&path) {
+            Ok(v) => Some(v),
+            Err(err) => {
+                if err.kind() != ErrorKind::NotFound {
+                    warn!("Error reading cached artwork {}: {err:#}", path.display());
+                }
+                None
+            }
+        }
+    }
+
+    /// Store `data` as the artwork of `url`, replacing a previously cached one.
+    pub fn insert(&self, url: &str, data: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir).with_context(|| self.dir.display().to_string())?;
+
+        let path = self.file_path(url);
+        // write to a temporary file first, so that readers never see a partial file
+        let part = path.with_extension("part");
+        fs::write(&part, data).with_context(|| part.display().to_string())?;
+        fs::rename(&part, &path).with_context(|| path.display().to_string())
+    }
+
+    /// Get the artwork of `url` from the cache, or download and cache it.
+    ///
+    /// Failing to cache the downloaded artwork is only logged.
+    pub async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        if let Some(data) = self.get(url) {
+            return Ok(data);
+        }
+
+        let response = LIMITER.send(reqwest::Client::new().get(url)).await?;
+        if response.status() != reqwest::StatusCode::OK {
+            bail!("Error non-OK Status code: {}", response.status());
+        }
+        let data = response.bytes().await?.to_vec();
+
+        if let Err(err) = self.insert(url, &data) {
+            warn!("Error caching artwork of \"{url}\": {err:#}");
+        }
+
+        Ok(data)
+    }
+}
+
+/// Get the file name for `url`, a stable hash as urls can be longer than allowed for file names.
+fn cache_key(url: &str) -> String {
+    // FNV-1a, which unlike the std hasher is guaranteed to be the same across versions
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{ArtworkCache, cache_key};
+
+    #[test]
+    fn should_insert_and_get() {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-artwork-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ArtworkCache::new(dir.clone());
+        let url = "https://example.com/feed/cover.jpg?size=600";
+
+        assert_eq!(cache.get(url), None);
+        assert_eq!(cache.cached_path(url), None);
+
+        cache.insert(url, b"image").unwrap();
+        assert_eq!(cache.get(url).as_deref(), Some(b"image".as_slice()));
+        assert_eq!(cache.cached_path(url), Some(dir.join(cache_key(url))));
+        assert_eq!(cache.get("https://example.com/other.jpg"), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_hash_stable() {
+        assert_eq!(cache_key(""), "cbf29ce484222325");
+        assert_eq!(cache_key("a"), "af63dc4c8601ec8c");
+    }
+}
@@ -151,7 +151,7 @@ impl<'a> EpisodeDBInsertable<'a> {
             ":description": self.description,
             ":pubdate": self.pubdate.map(|v| v.timestamp()),
             ":duration": self.duration,
-            ":image_url": self.duration,
+            ":image_url": self.image_url,
             ":epid": id,
         ])
     }
@@ -4,7 +4,7 @@ use rusqlite::{Connection, params};
 use semver::Version;
 
 /// The Current Database schema version this application is meant to run against
-pub(super) const DB_VERSION: u32 = 1;
+pub(super) const DB_VERSION: u32 = 2;
 
 /// Helper function to get the `user_version` with a single function call
 #[inline]
@@ -59,6 +59,12 @@ fn apply_migrations(conn: &Connection, mut user_version: u32) -> Result<()> {
         user_version = set_user_version(conn, 1)?;
     }
 
+    if user_version == 1 {
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .context("PodcastDatabase version 2 could not be applied")?;
+        user_version = set_user_version(conn, 2)?;
+    }
+
     Ok(())
 }
 
@@ -130,7 +136,7 @@ mod tests {
 
         assert_eq!(0, get_user_version(&conn).unwrap());
         migrate(&conn).unwrap();
-        assert_eq!(1, get_user_version(&conn).unwrap());
+        assert_eq!(2, get_user_version(&conn).unwrap());
 
         let all_tracks: Vec<String> = {
             let mut prep = conn.prepare("SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%';").unwrap();
@@ -141,5 +147,9 @@ mod tests {
         };
 
         assert_eq!(&all_tracks, &["podcasts", "episodes", "files", "version"]);
+
+        // version 2
+        conn.prepare("SELECT playback_speed FROM podcasts;")
+            .unwrap();
     }
 }
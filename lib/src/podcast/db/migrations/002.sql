@@ -0,0 +1,2 @@
+-- the playback speed to use for the episodes of a podcast, overwriting the global speed, "NULL" for the global speed
+ALTER TABLE podcasts ADD COLUMN playback_speed INTEGER;
@@ -7,7 +7,7 @@ use chrono::{DateTime, Utc};
 use episode_db::{EpisodeDB, EpisodeDBInsertable};
 use file_db::{FileDB, FileDBInsertable};
 use indoc::indoc;
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, params};
 
 use super::{Episode, EpisodeNoId, Podcast, PodcastNoId, RE_ARTICLES};
 use crate::track::Track;
@@ -391,6 +391,54 @@ impl Database {
         episode.ok_or(anyhow!("No Episode found with url \"{ep_uri}\""))
     }
 
+    /// Get the artwork url of the podcast the episode with url `ep_uri` belongs to, if it has one.
+    pub fn get_podcast_image_by_episode_url(&self, ep_uri: &str) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare_cached(indoc! {"
+            SELECT podcasts.image_url FROM podcasts
+            INNER JOIN episodes ON podcasts.id = episodes.podcast_id
+            WHERE episodes.url = ?;
+        "})?;
+
+        let image_url = stmt
+            .query_row(params![ep_uri], |row| row.get(0))
+            .optional()?
+            .flatten();
+
+        Ok(image_url)
+    }
+
+    /// Get the playback speed of the podcast the episode with url `ep_uri` belongs to, if one was remembered.
+    pub fn get_podcast_speed_by_episode_url(&self, ep_uri: &str) -> Result<Option<i32>> {
+        let mut stmt = self.conn.prepare_cached(indoc! {"
+            SELECT podcasts.playback_speed FROM podcasts
+            INNER JOIN episodes ON podcasts.id = episodes.podcast_id
+            WHERE episodes.url = ?;
+        "})?;
+
+        let speed = stmt
+            .query_row(params![ep_uri], |row| row.get(0))
+            .optional()?
+            .flatten();
+
+        Ok(speed)
+    }
+
+    /// Remember the playback `speed` for the podcast the episode with url `ep_uri` belongs to.
+    ///
+    /// Returns `false` if there is no such episode.
+    pub fn set_podcast_speed_by_episode_url(&self, ep_uri: &str, speed: i32) -> Result<bool> {
+        let mut stmt = self.conn.prepare_cached(indoc! {"
+            UPDATE podcasts SET playback_speed = ?1
+            WHERE id = (SELECT podcast_id FROM episodes WHERE url = ?2);
+        "})?;
+
+        let updated = stmt
+            .execute(params![speed, ep_uri])
+            .context("update podcast speed")?;
+
+        Ok(updated > 0)
+    }
+
     /// Deletes all rows in all tables
     pub fn clear_db(&self) -> Result<()> {
         self.conn.execute("DELETE FROM files;", [])?;
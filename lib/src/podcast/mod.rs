@@ -1,5 +1,6 @@
 // Thanks to the author of shellcaster(https://github.com/jeff-hughes/shellcaster). Most parts of following code are taken from it.
 
+pub mod artwork;
 pub mod db;
 #[allow(clippy::module_name_repetitions)]
 pub mod episode;
@@ -18,13 +19,15 @@ use bytes::Buf;
 use chrono::{DateTime, Utc};
 use opml::{Body, Head, OPML, Outline};
 use regex::Regex;
-use reqwest::ClientBuilder;
+use reqwest::{ClientBuilder, RequestBuilder, Url};
 use rfc822_sanitizer::parse_from_rfc2822_with_fallback;
 use rss::{Channel, Item};
 use sanitize_filename::{Options, sanitize_with_options};
 use tokio::sync::mpsc::unbounded_channel;
 
 use crate::config::v2::server::PodcastSettings;
+use crate::credentials::CREDENTIALS;
+use crate::ratelimit::LIMITER;
 use crate::taskpool::TaskPool;
 use db::Database;
 use episode::{Episode, EpisodeNoId};
@@ -119,8 +122,13 @@ async fn get_feed_data(url: &str, mut max_retries: usize) -> Result<PodcastNoId>
         .connect_timeout(Duration::from_secs(5))
         .build()?;
 
+    let auth = basic_auth_for(url);
+
     let resp: reqwest::Response = loop {
-        let response = agent.get(url).send().await;
+        // the retries are counted here, but still delayed by the backoff of the limiter
+        let response = LIMITER
+            .send_once(with_basic_auth(agent.get(url), auth.as_ref()))
+            .await;
         if let Ok(resp) = response {
             break resp;
         }
@@ -134,6 +142,43 @@ async fn get_feed_data(url: &str, mut max_retries: usize) -> Result<PodcastNoId>
     Ok(parse_feed_data(channel, url))
 }
 
+/// Get the [`CREDENTIALS`] key for feeds and episodes on `host`.
+///
+/// The secret is `user:password` (or just `user`) for HTTP basic authentication.
+#[must_use]
+pub fn credential_key(host: &str) -> String {
+    format!("podcast:{host}")
+}
+
+/// A user and optional password for HTTP basic authentication.
+pub(crate) type BasicAuth = (String, Option<String>);
+
+/// Look up the basic authentication stored for the host of `url`, see [`credential_key`].
+fn basic_auth_for(url: &str) -> Option<BasicAuth> {
+    let host = Url::parse(url).ok()?.host_str()?.to_string();
+    let secret = CREDENTIALS
+        .get(&credential_key(&host))
+        .inspect_err(|err| warn!("Looking up the credentials for \"{host}\" failed: {err:#}"))
+        .ok()??;
+
+    Some(parse_basic_auth(&secret))
+}
+
+/// Split a `user:password` secret.
+pub(crate) fn parse_basic_auth(secret: &str) -> BasicAuth {
+    match secret.split_once(':') {
+        Some((user, password)) => (user.to_string(), Some(password.to_string())),
+        None => (secret.to_string(), None),
+    }
+}
+
+fn with_basic_auth(request: RequestBuilder, auth: Option<&BasicAuth>) -> RequestBuilder {
+    match auth {
+        Some((user, password)) => request.basic_auth(user, password.as_ref()),
+        None => request,
+    }
+}
+
 /// Given a Channel with the RSS feed data, this parses the data about a
 /// podcast and its episodes and returns a Podcast. There are existing
 /// specifications for podcast RSS feeds that a feed should adhere to, but
@@ -495,8 +540,12 @@ async fn download_file(
         .build()
         .expect("reqwest client build failed");
 
+    let auth = basic_auth_for(&ep_data.url);
+
     let response: reqwest::Response = loop {
-        let response = agent.get(&ep_data.url).send().await;
+        let response = LIMITER
+            .send_once(with_basic_auth(agent.get(&ep_data.url), auth.as_ref()))
+            .await;
         if let Ok(resp) = response {
             break resp;
         }
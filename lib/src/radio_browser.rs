@@ -0,0 +1,280 @@
+//! Search internet radio stations in the [radio-browser.info](https://www.radio-browser.info) directory, and keep
+//! bookmarks of stations in [`FILE_NAME`].
+//!
+//! See <https://api.radio-browser.info>.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use reqwest::{ClientBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ratelimit::LIMITER;
+use crate::utils::get_app_config_path;
+
+/// The file name of the station bookmarks in the config directory.
+pub const FILE_NAME: &str = "radio_stations.toml";
+
+/// The api of radio-browser, which resolves to one of its mirrors.
+const API_URL: &str = "https://all.api.radio-browser.info";
+
+/// The maximal amount of stations of a search.
+const SEARCH_LIMIT: u16 = 50;
+
+/// A internet radio station.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct Station {
+    /// The id of the station in the radio-browser directory
+    pub uuid: String,
+    pub name: String,
+    /// The stream url, with playlist files already resolved
+    pub url: String,
+    pub country: String,
+    /// Comma separated, like `jazz,smooth jazz`
+    pub tags: String,
+    pub codec: String,
+    /// In kbit/s, 0 if unknown
+    pub bitrate: u32,
+}
+
+/// Search the stations whose name contains `query`, the most popular first.
+///
+/// Stations which failed the last check of radio-browser are skipped.
+pub async fn search(query: &str) -> Result<Vec<Station>> {
+    let client = ClientBuilder::new()
+        .timeout(Duration::from_secs(10))
+        // radio-browser asks clients to identify themself
+        .user_agent(concat!("termusic/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let res = LIMITER
+        .send(
+            client
+                .get(format!("{API_URL}/json/stations/search"))
+                .query(&[
+                    ("name", query),
+                    ("limit", &SEARCH_LIMIT.to_string()),
+                    ("hidebroken", "true"),
+                    ("order", "clickcount"),
+                    ("reverse", "true"),
+                ]),
+        )
+        .await
+        .context("radio-browser search")?;
+
+    match res.status() {
+        StatusCode::OK => Ok(parse_stations(&res.text().await?)),
+        status => bail!("radio-browser responded with {status}"),
+    }
+}
+
+/// Parse the stations of a `stations/search` response, skipping stations without a stream url.
+fn parse_stations(data: &str) -> Vec<Station> {
+    let Ok(Value::Array(items)) = serde_json::from_str::<Value>(data) else {
+        return Vec::new();
+    };
+
+    items
+        .iter()
+        .filter_map(|v| {
+            let str_field = |key: &str| {
+                v.get(key)
+                    .and_then(Value::as_str)
+                    .map(|v| v.trim().to_string())
+                    .unwrap_or_default()
+            };
+            // "url" may be a playlist file, "url_resolved" is the stream itself
+            let url = Some(str_field("url_resolved"))
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| str_field("url"));
+            if url.is_empty() {
+                return None;
+            }
+
+            Some(Station {
+                uuid: str_field("stationuuid"),
+                name: str_field("name"),
+                url,
+                country: str_field("country"),
+                tags: str_field("tags"),
+                codec: str_field("codec"),
+                bitrate: v
+                    .get("bitrate")
+                    .and_then(Value::as_u64)
+                    .and_then(|v| u32::try_from(v).ok())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// The bookmarked stations, in the order they were bookmarked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct StationBookmarks {
+    stations: Vec<Station>,
+}
+
+impl StationBookmarks {
+    /// Load the bookmarks from `path`, a missing file is no bookmarks.
+    ///
+    /// # Errors
+    ///
+    /// - if the file cannot be read or parsed
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                toml::from_str(&content).with_context(|| format!("parse \"{}\"", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("read \"{}\"", path.display())),
+        }
+    }
+
+    /// Write the bookmarks to `path`.
+    ///
+    /// # Errors
+    ///
+    /// - if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self)?;
+
+        std::fs::write(path, content).with_context(|| format!("write \"{}\"", path.display()))
+    }
+
+    /// Get the path of [`FILE_NAME`] in the config directory.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(get_app_config_path()?.join(FILE_NAME))
+    }
+
+    /// Load the bookmarks from the config directory, broken bookmarks are only logged.
+    #[must_use]
+    pub fn load_default() -> Self {
+        Self::default_path()
+            .and_then(|path| Self::load(&path))
+            .unwrap_or_else(|err| {
+                warn!("Loading the radio station bookmarks failed: {err:#}");
+                Self::default()
+            })
+    }
+
+    /// Load the bookmarks from the config directory, apply `fun` to them and save them again.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    /// - if the file cannot be read, parsed or written
+    pub fn update_default(fun: impl FnOnce(&mut Self)) -> Result<Self> {
+        let path = Self::default_path()?;
+        let mut bookmarks = Self::load(&path)?;
+        fun(&mut bookmarks);
+        bookmarks.save(&path)?;
+
+        Ok(bookmarks)
+    }
+
+    #[must_use]
+    pub fn stations(&self) -> &[Station] {
+        &self.stations
+    }
+
+    /// Check if a station with the stream `url` is bookmarked.
+    #[must_use]
+    pub fn contains(&self, url: &str) -> bool {
+        self.stations.iter().any(|v| v.url == url)
+    }
+
+    /// Get the name of the bookmarked station with the stream `url`.
+    #[must_use]
+    pub fn name_of(&self, url: &str) -> Option<&str> {
+        self.stations
+            .iter()
+            .find(|v| v.url == url)
+            .map(|v| v.name.as_str())
+    }
+
+    /// Bookmark `station` or remove the bookmark if it already is, returns whether it is bookmarked now.
+    pub fn toggle(&mut self, station: &Station) -> bool {
+        if self.contains(&station.url) {
+            self.stations.retain(|v| v.url != station.url);
+            return false;
+        }
+
+        self.stations.push(station.clone());
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Station, StationBookmarks, parse_stations};
+
+    #[test]
+    fn should_parse_stations() {
+        let data = r#"[
+            {"stationuuid": "960e57c5-0601-11e8-ae97-52543be04c81", "name": " Jazz Radio ",
+             "url": "https://example.com/jazz.m3u", "url_resolved": "https://example.com/jazz.mp3",
+             "country": "France", "tags": "jazz,smooth jazz", "codec": "MP3", "bitrate": 128},
+            {"stationuuid": "2", "name": "Only url", "url": "https://example.com/stream", "url_resolved": ""},
+            {"stationuuid": "3", "name": "No url", "url": "", "bitrate": 64}
+        ]"#;
+
+        assert_eq!(
+            parse_stations(data),
+            [
+                Station {
+                    uuid: "960e57c5-0601-11e8-ae97-52543be04c81".to_string(),
+                    name: "Jazz Radio".to_string(),
+                    url: "https://example.com/jazz.mp3".to_string(),
+                    country: "France".to_string(),
+                    tags: "jazz,smooth jazz".to_string(),
+                    codec: "MP3".to_string(),
+                    bitrate: 128,
+                },
+                Station {
+                    uuid: "2".to_string(),
+                    name: "Only url".to_string(),
+                    url: "https://example.com/stream".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+        assert_eq!(parse_stations("{}"), []);
+    }
+
+    #[test]
+    fn should_toggle_and_persist_bookmarks() {
+        let station = Station {
+            name: "Jazz Radio".to_string(),
+            url: "https://example.com/jazz.mp3".to_string(),
+            ..Default::default()
+        };
+        let mut bookmarks = StationBookmarks::default();
+
+        assert!(bookmarks.toggle(&station));
+        assert!(bookmarks.contains(&station.url));
+        assert_eq!(bookmarks.name_of(&station.url), Some("Jazz Radio"));
+
+        let path = std::env::temp_dir().join(format!(
+            "termusic-radio-stations-{}.toml",
+            std::process::id()
+        ));
+        bookmarks.save(&path).unwrap();
+        assert_eq!(StationBookmarks::load(&path).unwrap(), bookmarks);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(!bookmarks.toggle(&station));
+        assert_eq!(bookmarks.stations(), []);
+        assert_eq!(bookmarks.name_of(&station.url), None);
+    }
+}
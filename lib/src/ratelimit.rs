@@ -0,0 +1,246 @@
+//! A shared rate limiter with exponential backoff for all requests to external APIs.
+//!
+//! The state is kept per host, so that a flaky or rate-limiting service is backed off from by every caller,
+//! instead of each caller retrying on its own and hammering the service.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rand::Rng as _;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::time::Instant;
+
+/// The global limiter instance.
+pub static LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(Policy::default()));
+
+/// How requests are spaced and retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// The minimal time between two requests to the same host
+    pub min_interval: Duration,
+    /// The backoff after the first failure, doubled for each consecutive failure
+    pub base_delay: Duration,
+    /// The maximal backoff, to not stall for too long
+    pub max_delay: Duration,
+    /// How often [`RateLimiter::send`] retries a failed request
+    pub max_retries: u32,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_millis(100),
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            max_retries: 2,
+        }
+    }
+}
+
+impl Policy {
+    /// Get the backoff after `failures` consecutive failures, `jitter` (`0.0..=1.0`) picks a point in the upper half.
+    ///
+    /// The jitter avoids all callers retrying at the same time.
+    #[must_use]
+    pub fn backoff(&self, failures: u32, jitter: f64) -> Duration {
+        if failures == 0 {
+            return Duration::ZERO;
+        }
+
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(failures - 1))
+            .min(self.max_delay);
+
+        exp.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// The state of a single host.
+#[derive(Debug)]
+struct HostState {
+    /// The earliest time the next request may be sent
+    next_slot: Instant,
+    /// The consecutive failures
+    failures: u32,
+}
+
+/// A rate limiter, keeping track of requests per host.
+#[derive(Debug)]
+pub struct RateLimiter {
+    policy: Policy,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+/// The outcome of a single request.
+enum Outcome {
+    Success,
+    /// The request failed in a way that may work later, with the delay the server asked for
+    Retry(Option<Duration>),
+    /// The request failed in a way that retrying does not help
+    Fatal,
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Send `request` once the host's rate limit and backoff allows it, retrying connection errors, timeouts,
+    /// `429 Too Many Requests` and server errors with exponential backoff, up to [`Policy::max_retries`].
+    ///
+    /// Requests with a streaming body cannot be retried and are only sent once.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        self.send_with_retries(request, self.policy.max_retries)
+            .await
+    }
+
+    /// Like [`send`](Self::send), but never retries, for callers that have their own fallback, like trying another instance.
+    ///
+    /// The host's backoff still applies and is updated by the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the request.
+    pub async fn send_once(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        self.send_with_retries(request, 0).await
+    }
+
+    async fn send_with_retries(
+        &self,
+        request: RequestBuilder,
+        retries: u32,
+    ) -> reqwest::Result<Response> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let host = request.url().host_str().unwrap_or_default().to_string();
+
+        let mut attempt = 0;
+        let mut current = request;
+        loop {
+            // keep a copy for the next attempt, before the request is consumed
+            let next = (attempt < retries).then(|| current.try_clone()).flatten();
+
+            self.wait_for_slot(&host).await;
+            let res = client.execute(current).await;
+            let outcome = classify(&res);
+            self.record(&host, &outcome);
+
+            let Some(next) = next else {
+                return res;
+            };
+            match outcome {
+                Outcome::Success | Outcome::Fatal => return res,
+                Outcome::Retry(_) => {
+                    debug!("Retrying request to \"{host}\" after {res:?}");
+                    attempt += 1;
+                    current = next;
+                }
+            }
+        }
+    }
+
+    /// Wait until the next request to `host` may be sent, reserving the slot.
+    async fn wait_for_slot(&self, host: &str) {
+        let now = Instant::now();
+        let slot = {
+            let mut hosts = self.hosts.lock();
+            let state = hosts.entry(host.to_string()).or_insert(HostState {
+                next_slot: now,
+                failures: 0,
+            });
+            let slot = state.next_slot.max(now);
+            state.next_slot = slot + self.policy.min_interval;
+
+            slot
+        };
+
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Update the backoff of `host` with `outcome`.
+    fn record(&self, host: &str, outcome: &Outcome) {
+        let mut hosts = self.hosts.lock();
+        let Some(state) = hosts.get_mut(host) else {
+            return;
+        };
+
+        match outcome {
+            Outcome::Success => state.failures = 0,
+            Outcome::Retry(retry_after) => {
+                state.failures = state.failures.saturating_add(1);
+                let backoff = retry_after
+                    .map_or_else(
+                        || self.policy.backoff(state.failures, rand::rng().random()),
+                        |v| v.min(self.policy.max_delay),
+                    )
+                    .max(self.policy.min_interval);
+                state.next_slot = state.next_slot.max(Instant::now() + backoff);
+            }
+            Outcome::Fatal => (),
+        }
+    }
+}
+
+/// Decide if the result of a request is worth retrying.
+fn classify(res: &reqwest::Result<Response>) -> Outcome {
+    match res {
+        Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+            Outcome::Retry(retry_after(response))
+        }
+        Ok(response) if response.status().is_server_error() => Outcome::Retry(None),
+        Ok(_) => Outcome::Success,
+        Err(err) if err.is_connect() || err.is_timeout() => Outcome::Retry(None),
+        Err(_) => Outcome::Fatal,
+    }
+}
+
+/// Get the delay of a `Retry-After` header, only the seconds form is supported.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::Policy;
+
+    #[test]
+    fn should_back_off_exponentially() {
+        let policy = Policy::default();
+
+        assert_eq!(policy.backoff(0, 1.0), Duration::ZERO);
+        assert_eq!(policy.backoff(1, 1.0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3, 1.0), Duration::from_secs(2));
+        // capped to the maximum
+        assert_eq!(policy.backoff(10, 1.0), Duration::from_secs(8));
+        assert_eq!(policy.backoff(u32::MAX, 1.0), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn should_apply_jitter_in_upper_half() {
+        let policy = Policy::default();
+
+        assert_eq!(policy.backoff(2, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2, 0.5), Duration::from_millis(750));
+        // out of range jitter is clamped
+        assert_eq!(policy.backoff(2, 7.0), Duration::from_secs(1));
+    }
+}
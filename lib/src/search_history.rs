@@ -0,0 +1,252 @@
+//! The history of search queries, to recall past searches and keep pinned favorites.
+//!
+//! The web (youtube) and library searches each have their own [`HistoryList`], stored together in [`FILE_NAME`],
+//! along with the last picked region of the youtube music trending.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::get_app_config_path;
+
+/// The file name of the search history in the config directory.
+pub const FILE_NAME: &str = "search_history.toml";
+
+/// Which searches a history is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    /// The youtube search
+    Web,
+    /// The searches in the library, database, playlist and podcasts
+    Library,
+}
+
+/// The histories of all searches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct SearchHistory {
+    web: HistoryList,
+    library: HistoryList,
+    trending_region: Option<String>,
+}
+
+impl SearchHistory {
+    /// Load the history from `path`, a missing file is a empty history.
+    ///
+    /// # Errors
+    ///
+    /// - if the file cannot be read or parsed
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                toml::from_str(&content).with_context(|| format!("parse \"{}\"", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("read \"{}\"", path.display())),
+        }
+    }
+
+    /// Write the history to `path`.
+    ///
+    /// # Errors
+    ///
+    /// - if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self)?;
+
+        std::fs::write(path, content).with_context(|| format!("write \"{}\"", path.display()))
+    }
+
+    /// Get the path of [`FILE_NAME`] in the config directory.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(get_app_config_path()?.join(FILE_NAME))
+    }
+
+    /// Load the history from the config directory, a broken history is only logged, as it is not essential.
+    #[must_use]
+    pub fn load_default() -> Self {
+        Self::default_path()
+            .and_then(|path| Self::load(&path))
+            .unwrap_or_else(|err| {
+                warn!("Loading the search history failed: {err:#}");
+                Self::default()
+            })
+    }
+
+    /// Load the history from the config directory, apply `fun` to it and save it again.
+    ///
+    /// # Errors
+    ///
+    /// - if the config directory cannot be resolved
+    /// - if the file cannot be read, parsed or written
+    pub fn update_default(fun: impl FnOnce(&mut Self)) -> Result<Self> {
+        let path = Self::default_path()?;
+        let mut history = Self::load(&path)?;
+        fun(&mut history);
+        history.save(&path)?;
+
+        Ok(history)
+    }
+
+    #[must_use]
+    pub fn get(&self, kind: HistoryKind) -> &HistoryList {
+        match kind {
+            HistoryKind::Web => &self.web,
+            HistoryKind::Library => &self.library,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: HistoryKind) -> &mut HistoryList {
+        match kind {
+            HistoryKind::Web => &mut self.web,
+            HistoryKind::Library => &mut self.library,
+        }
+    }
+
+    /// The region last picked for the youtube music trending.
+    #[must_use]
+    pub fn trending_region(&self) -> Option<&str> {
+        self.trending_region.as_deref()
+    }
+
+    pub fn set_trending_region(&mut self, region: String) {
+        self.trending_region = Some(region);
+    }
+}
+
+/// The past queries of one kind of search, and the pinned queries which are never dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct HistoryList {
+    /// The newest last
+    entries: Vec<String>,
+    pinned: Vec<String>,
+}
+
+impl HistoryList {
+    /// Add `query` as the newest entry, moving it if it already exists, and keep at most `max` entries.
+    pub fn push(&mut self, query: &str, max: usize) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|v| v != query);
+        self.entries.push(query.to_string());
+
+        let over = self.entries.len().saturating_sub(max);
+        self.entries.drain(..over);
+    }
+
+    /// Get all entries, the newest last.
+    #[must_use]
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Iterate over all entries, the newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &String> + Clone {
+        self.entries.iter().rev()
+    }
+
+    #[must_use]
+    pub fn pinned(&self) -> &[String] {
+        &self.pinned
+    }
+
+    #[must_use]
+    pub fn is_pinned(&self, query: &str) -> bool {
+        self.pinned.iter().any(|v| v == query)
+    }
+
+    /// Pin `query` or unpin it if it already is, returns whether it is pinned now.
+    pub fn toggle_pin(&mut self, query: &str) -> bool {
+        if self.is_pinned(query) {
+            self.pinned.retain(|v| v != query);
+            return false;
+        }
+
+        self.pinned.push(query.to_string());
+
+        true
+    }
+
+    /// Get the pinned queries, then the other entries the newest first.
+    #[must_use]
+    pub fn all(&self) -> Vec<&str> {
+        self.pinned
+            .iter()
+            .chain(self.recent().filter(|v| !self.is_pinned(v)))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{HistoryKind, HistoryList, SearchHistory};
+
+    #[test]
+    fn should_keep_history_bounded() {
+        let mut history = HistoryList::default();
+        history.push("a", 2);
+        history.push("b", 2);
+        history.push("a", 2);
+        history.push("  ", 2);
+        assert_eq!(history.entries(), ["b", "a"]);
+
+        history.push("c", 2);
+        assert_eq!(history.entries(), ["a", "c"]);
+
+        history.push("d", 0);
+        assert!(history.entries().is_empty());
+    }
+
+    #[test]
+    fn should_list_pinned_first() {
+        let mut history = HistoryList::default();
+        history.push("a", 10);
+        history.push("b", 10);
+        history.push("c", 10);
+
+        assert!(history.toggle_pin("a"));
+        assert!(history.toggle_pin("old favorite"));
+        assert_eq!(history.all(), ["a", "old favorite", "c", "b"]);
+
+        assert!(!history.toggle_pin("a"));
+        assert_eq!(history.all(), ["old favorite", "c", "b", "a"]);
+
+        // pinned queries are kept, even if dropped from the entries
+        history.push("d", 1);
+        assert_eq!(history.all(), ["old favorite", "d"]);
+    }
+
+    #[test]
+    fn should_roundtrip_file() {
+        let mut history = SearchHistory::default();
+        history.get_mut(HistoryKind::Web).push("lofi", 5);
+        history.get_mut(HistoryKind::Library).push("beatles", 5);
+        history.get_mut(HistoryKind::Library).toggle_pin("queen");
+        history.set_trending_region("DE".to_string());
+
+        let path = std::env::temp_dir().join(format!(
+            "termusic-search-history-{}.toml",
+            std::process::id()
+        ));
+        history.save(&path).unwrap();
+        assert_eq!(SearchHistory::load(&path).unwrap(), history);
+        let _ = std::fs::remove_file(&path);
+
+        // missing fields are filled in
+        let parsed: SearchHistory = toml::from_str("[web]\nentries = [\"a\"]\n").unwrap();
+        assert_eq!(parsed.get(HistoryKind::Web).entries(), ["a"]);
+        assert!(parsed.get(HistoryKind::Library).entries().is_empty());
+    }
+}
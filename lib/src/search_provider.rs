@@ -0,0 +1,256 @@
+//! The sites the youtube search popup can search, youtube, soundcloud and bandcamp.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde_json::{Value, json};
+
+use crate::invidious::{Instance, YoutubeVideo};
+use crate::playlist::share::ShareSource;
+use crate::ratelimit::LIMITER;
+
+/// The site to search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchSite {
+    /// Youtube, with invidious or yt-dlp
+    #[default]
+    Youtube,
+    /// Soundcloud, with yt-dlp
+    SoundCloud,
+    /// Bandcamp, with its search api
+    Bandcamp,
+}
+
+impl SearchSite {
+    pub const ALL: [Self; 3] = [Self::Youtube, Self::SoundCloud, Self::Bandcamp];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Youtube => "youtube",
+            Self::SoundCloud => "soundcloud",
+            Self::Bandcamp => "bandcamp",
+        }
+    }
+
+    /// Get the next site, wrapping around to the first.
+    #[must_use]
+    pub fn next(self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|v| *v == self)
+            .unwrap_or_default();
+
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Get the prefix of the yt-dlp search url, like `ytsearch` for `ytsearch10:query`, none if yt-dlp cannot search
+    /// the site.
+    #[must_use]
+    pub fn ytdlp_prefix(self) -> Option<&'static str> {
+        match self {
+            Self::Youtube => Some("ytsearch"),
+            Self::SoundCloud => Some("scsearch"),
+            Self::Bandcamp => None,
+        }
+    }
+
+    /// Get the page of `item`, a result of this site, to download or stream it with yt-dlp.
+    #[must_use]
+    pub fn item_url(self, item: &YoutubeVideo) -> String {
+        match (self, &item.url) {
+            (Self::Youtube, _) | (_, None) => ShareSource::youtube_url(&item.video_id),
+            (_, Some(url)) => url.clone(),
+        }
+    }
+}
+
+/// A search of a site, for the youtube search popup.
+#[allow(async_fn_in_trait)] // all implementations are local, no `Send` bound is needed
+pub trait SearchProvider {
+    /// The name of the provider, for the title of the results.
+    fn display_name(&self) -> &str;
+    /// The site the results are from.
+    fn site(&self) -> SearchSite;
+    /// Get `page`, starting at 1, of the results.
+    async fn search(&self, page: u32) -> Result<Vec<YoutubeVideo>>;
+    /// Whether there are more results than the first page.
+    fn paginated(&self) -> bool {
+        true
+    }
+    /// Get the page of `item`, to download or stream it with yt-dlp.
+    fn item_url(&self, item: &YoutubeVideo) -> String {
+        self.site().item_url(item)
+    }
+}
+
+impl SearchProvider for Instance {
+    fn display_name(&self) -> &str {
+        self.domain.as_deref().unwrap_or("invidious")
+    }
+
+    fn site(&self) -> SearchSite {
+        SearchSite::Youtube
+    }
+
+    async fn search(&self, page: u32) -> Result<Vec<YoutubeVideo>> {
+        self.get_search_query(page).await
+    }
+
+    fn paginated(&self) -> bool {
+        // the music trending has no pages
+        self.region().is_none()
+    }
+}
+
+/// The bandcamp search api, which is used by its search field.
+const BANDCAMP_SEARCH: &str = "https://bandcamp.com/api/bcsearch_public_api/1/autocomplete_elastic";
+
+/// A search of the tracks on bandcamp.
+///
+/// The api only returns a single page of results.
+#[derive(Debug, Clone)]
+pub struct Bandcamp {
+    client: Client,
+    query: String,
+}
+
+impl PartialEq for Bandcamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.query == other.query
+    }
+}
+
+impl Eq for Bandcamp {}
+
+impl Bandcamp {
+    /// Create a new search for `query`.
+    ///
+    /// # Errors
+    ///
+    /// - if the http client cannot be created
+    pub fn new(query: String) -> Result<Self> {
+        let client = ClientBuilder::new()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        Ok(Self { client, query })
+    }
+}
+
+impl SearchProvider for Bandcamp {
+    fn display_name(&self) -> &'static str {
+        "bandcamp"
+    }
+
+    fn site(&self) -> SearchSite {
+        SearchSite::Bandcamp
+    }
+
+    async fn search(&self, page: u32) -> Result<Vec<YoutubeVideo>> {
+        if page > 1 {
+            return Ok(Vec::new());
+        }
+
+        let request = self.client.post(BANDCAMP_SEARCH).json(&json!({
+            "search_text": self.query,
+            // only tracks, albums need to be imported
+            "search_filter": "t",
+            "full_page": false,
+            "fan_id": null,
+        }));
+        let res = LIMITER.send(request).await.context("bandcamp search")?;
+
+        match res.status() {
+            StatusCode::OK => Ok(parse_bandcamp(&res.text().await?)),
+            status => bail!("bandcamp responded with {status}"),
+        }
+    }
+
+    fn paginated(&self) -> bool {
+        false
+    }
+}
+
+/// Parse the tracks of a bandcamp search response.
+fn parse_bandcamp(data: &str) -> Vec<YoutubeVideo> {
+    let Ok(value) = serde_json::from_str::<Value>(data) else {
+        return Vec::new();
+    };
+    let Some(results) = value
+        .get("auto")
+        .and_then(|v| v.get("results"))
+        .and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+
+    results
+        .iter()
+        .filter(|v| v.get("type").and_then(Value::as_str) == Some("t"))
+        .filter_map(|v| {
+            let str_field = |key: &str| v.get(key).and_then(Value::as_str).map(str::to_string);
+
+            Some(YoutubeVideo {
+                title: str_field("name")?,
+                video_id: v.get("id")?.as_u64()?.to_string(),
+                uploader: str_field("band_name"),
+                thumbnail_url: str_field("img"),
+                url: Some(str_field("item_url_path")?),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{SearchSite, parse_bandcamp};
+    use crate::invidious::YoutubeVideo;
+
+    #[test]
+    fn should_parse_bandcamp() {
+        let data = r#"{"auto": {"results": [
+            {"type": "t", "id": 123, "name": "Track", "band_name": "Artist",
+             "img": "https://f4.bcbits.com/img/a1_3.jpg", "item_url_path": "https://artist.bandcamp.com/track/track"},
+            {"type": "a", "id": 456, "name": "Album", "item_url_path": "https://artist.bandcamp.com/album/album"},
+            {"type": "t", "id": 789, "name": "No url"}
+        ]}}"#;
+
+        assert_eq!(
+            parse_bandcamp(data),
+            [YoutubeVideo {
+                title: "Track".to_string(),
+                video_id: "123".to_string(),
+                uploader: Some("Artist".to_string()),
+                thumbnail_url: Some("https://f4.bcbits.com/img/a1_3.jpg".to_string()),
+                url: Some("https://artist.bandcamp.com/track/track".to_string()),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(parse_bandcamp("{}"), []);
+    }
+
+    #[test]
+    fn should_get_item_urls() {
+        let item = YoutubeVideo {
+            video_id: "abc".to_string(),
+            url: Some("https://soundcloud.com/artist/track".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            SearchSite::Youtube.item_url(&item),
+            "https://www.youtube.com/watch?v=abc"
+        );
+        assert_eq!(
+            SearchSite::SoundCloud.item_url(&item),
+            "https://soundcloud.com/artist/track"
+        );
+        assert_eq!(SearchSite::Bandcamp.next(), SearchSite::Youtube);
+        assert_eq!(SearchSite::Youtube.next(), SearchSite::SoundCloud);
+    }
+}
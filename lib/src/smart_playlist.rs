@@ -0,0 +1,762 @@
+//! The query language of smart playlists, evaluated against the library database.
+//!
+//! A query is made of terms like `genre:ambient`, `added>30d` or `playcount<2`, combined with `AND` (also implied
+//! between terms), `OR`, `NOT` (or a leading `-`) and parentheses. Values with spaces are quoted, like
+//! `artist:"Boards of Canada"`, and a term without a field matches the artist, title or album.
+//!
+//! Operators are `:` (contains for text, equals otherwise), `=`, `!=`, `<`, `<=`, `>` and `>=`.
+//!
+//! Dates (`added`, `lastplayed`) are compared either to a date like `2024-05-01`, or to their age like `30d`: `added>30d`
+//! is added more than 30 days ago, `lastplayed<2w` played within the last 2 weeks. Tracks never played are older than any
+//! age. The units are `h`, `d`, `w`, `m` (30 days) and `y` (365 days).
+
+use std::fmt::Write as _;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Days, NaiveDate, TimeDelta, Utc};
+use rusqlite::types::Value;
+
+use crate::audio_analysis::EnergyLevel;
+use crate::new_database::track_ops::{RATING_MAX, TrackLabel};
+use crate::track::QualityBadge;
+
+/// All fields a query can filter on, for help texts.
+pub const FIELDS: &[&str] = &[
+    "artist",
+    "title",
+    "album",
+    "genre",
+    "composer",
+    "work",
+    "dir",
+    "ext",
+    "playcount",
+    "rating",
+    "duration",
+    "bpm",
+    "energy",
+    "mood",
+    "sound",
+    "label",
+    "quality",
+    "added",
+    "lastplayed",
+];
+
+/// A comparison of a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// `:`, contains for text, otherwise like [`Op::Eq`]
+    Match,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    /// The operators, longer ones first as they are matched in order.
+    const ALL: [(&str, Op); 7] = [
+        ("!=", Self::Ne),
+        (">=", Self::Ge),
+        ("<=", Self::Le),
+        (":", Self::Match),
+        ("=", Self::Eq),
+        (">", Self::Gt),
+        ("<", Self::Lt),
+    ];
+
+    fn as_sql(self) -> &'static str {
+        match self {
+            Self::Match | Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+
+    /// Get the operator for the swapped sides, like `>` for `<`.
+    fn flipped(self) -> Self {
+        match self {
+            Self::Lt => Self::Gt,
+            Self::Le => Self::Ge,
+            Self::Gt => Self::Lt,
+            Self::Ge => Self::Le,
+            other => other,
+        }
+    }
+}
+
+/// The text columns, matched case-insensitive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextField {
+    Artist,
+    Title,
+    Album,
+    Genre,
+    Composer,
+    Work,
+    Dir,
+    Ext,
+    /// The [`Mood`](crate::audio_analysis::Mood) described from the audio
+    Mood,
+    /// The [`SoundProfile`](crate::audio_analysis::SoundProfile) described from the audio
+    SoundProfile,
+}
+
+impl TextField {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Artist => "tracks_metadata.artist_display",
+            Self::Title => "tracks_metadata.title",
+            Self::Album => "albums.title",
+            Self::Genre => "tracks_metadata.genre",
+            Self::Composer => "tracks_metadata.composer",
+            Self::Work => "tracks_metadata.work",
+            Self::Dir => "tracks.file_dir",
+            Self::Ext => "tracks.file_ext",
+            Self::Mood => "tracks.mood",
+            Self::SoundProfile => "tracks.sound_profile",
+        }
+    }
+}
+
+/// The numeric columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberField {
+    PlayCount,
+    Rating,
+    /// In seconds
+    Duration,
+    Bpm,
+    /// From 0 to 1
+    Energy,
+}
+
+impl NumberField {
+    fn column(self) -> &'static str {
+        match self {
+            Self::PlayCount => "tracks.play_count",
+            Self::Rating => "tracks.rating",
+            Self::Duration => "tracks.duration",
+            Self::Bpm => "tracks_metadata.bpm",
+            Self::Energy => "tracks.energy",
+        }
+    }
+}
+
+/// The date columns, stored as RFC3339.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateField {
+    Added,
+    LastPlayed,
+}
+
+impl DateField {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Added => "tracks.added_at",
+            Self::LastPlayed => "tracks.last_played_at",
+        }
+    }
+}
+
+/// A date of a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateValue {
+    /// How long ago, like `30d`
+    Age(TimeDelta),
+    /// A day
+    Day(NaiveDate),
+}
+
+/// A single term of a query.
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    /// A term without a field, matching artist, title or album
+    Any(String),
+    Text(TextField, Op, String),
+    Number(NumberField, Op, f64),
+    Date(DateField, Op, DateValue),
+    Label(Op, TrackLabel),
+    /// Compared by quality, [`QualityBadge::HiRes`] being the highest
+    Quality(Op, QualityBadge),
+    EnergyLevel(Op, EnergyLevel),
+}
+
+/// A parsed query.
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Condition),
+}
+
+/// A token of a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    /// A word, with the byte length of the part before a quote, in which operators are searched
+    Word(String, usize),
+}
+
+/// Split a query into tokens.
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut word = String::new();
+                let mut unquoted_len = None;
+                while let Some(&ch) = chars.peek() {
+                    if ch == '"' {
+                        chars.next();
+                        unquoted_len.get_or_insert(word.len());
+                        let mut closed = false;
+                        for ch in chars.by_ref() {
+                            if ch == '"' {
+                                closed = true;
+                                break;
+                            }
+                            word.push(ch);
+                        }
+                        if !closed {
+                            bail!("Missing closing quote in \"{query}\"");
+                        }
+                        continue;
+                    }
+                    if ch.is_whitespace() || ch == '(' || ch == ')' {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                let unquoted_len = unquoted_len.unwrap_or(word.len());
+                tokens.push(Token::Word(word, unquoted_len));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parse a age like `30d` or a day like `2024-05-01`.
+fn parse_date(value: &str) -> Result<DateValue> {
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(DateValue::Day(day));
+    }
+
+    let split = value.len() - value.chars().last().map_or(0, char::len_utf8);
+    let (amount, unit) = value.split_at(split);
+    let Ok(amount) = amount.parse::<i64>() else {
+        bail!("\"{value}\" is neither a date like \"2024-05-01\" nor a age like \"30d\"");
+    };
+    let hours = match unit {
+        "h" => Some(amount),
+        "d" => amount.checked_mul(24),
+        "w" => amount.checked_mul(24 * 7),
+        "m" => amount.checked_mul(24 * 30),
+        "y" => amount.checked_mul(24 * 365),
+        _ => bail!("Unknown unit in \"{value}\", expected one of h, d, w, m, y"),
+    };
+    let Some(age) = hours.and_then(TimeDelta::try_hours) else {
+        bail!("\"{value}\" is too long ago");
+    };
+
+    Ok(DateValue::Age(age))
+}
+
+/// Parse a number, durations optionally with the unit `s`, `m` or `h`.
+fn parse_number(field: NumberField, value: &str) -> Result<f64> {
+    let (value, factor) = match (field, value.chars().last()) {
+        (NumberField::Duration, Some('s')) => (&value[..value.len() - 1], 1.0),
+        (NumberField::Duration, Some('m')) => (&value[..value.len() - 1], 60.0),
+        (NumberField::Duration, Some('h')) => (&value[..value.len() - 1], 3600.0),
+        _ => (value, 1.0),
+    };
+    let Ok(number) = value.parse::<f64>() else {
+        bail!("\"{value}\" is not a number");
+    };
+    if field == NumberField::Rating && !(0.0..=f64::from(RATING_MAX)).contains(&number) {
+        bail!("A rating is from 0 to {RATING_MAX} stars");
+    }
+
+    Ok(number * factor)
+}
+
+/// Parse a term like `genre:ambient`, or a word to match artist, title or album.
+fn parse_term(word: &str, unquoted_len: usize) -> Result<Condition> {
+    // the first operator in the word, the longer one if multiple start there like ">=" and ">"
+    let found = Op::ALL
+        .iter()
+        .filter_map(|(text, op)| {
+            word[..unquoted_len]
+                .find(text)
+                .map(|idx| (idx, idx + text.len(), *op))
+        })
+        .min_by_key(|(idx, end, _)| (*idx, std::cmp::Reverse(*end)));
+    let Some((start, end, op)) = found else {
+        return Ok(Condition::Any(word.to_string()));
+    };
+
+    let field = word[..start].to_lowercase();
+    let value = &word[end..];
+    if value.is_empty() {
+        bail!("Missing value in \"{word}\"");
+    }
+
+    let text = |field| {
+        if matches!(op, Op::Lt | Op::Le | Op::Gt | Op::Ge) {
+            bail!("Text can only be compared with \":\", \"=\" or \"!=\", in \"{word}\"");
+        }
+        Ok(Condition::Text(field, op, value.to_string()))
+    };
+    let number = |field| Ok(Condition::Number(field, op, parse_number(field, value)?));
+    let date = |field| Ok(Condition::Date(field, op, parse_date(value)?));
+
+    match field.as_str() {
+        "artist" => text(TextField::Artist),
+        "title" => text(TextField::Title),
+        "album" => text(TextField::Album),
+        "genre" => text(TextField::Genre),
+        "composer" => text(TextField::Composer),
+        "work" => text(TextField::Work),
+        "dir" | "directory" => text(TextField::Dir),
+        "ext" | "format" => text(TextField::Ext),
+        "mood" => text(TextField::Mood),
+        "sound" => text(TextField::SoundProfile),
+        "playcount" | "plays" => number(NumberField::PlayCount),
+        "rating" | "stars" => number(NumberField::Rating),
+        "duration" | "length" => number(NumberField::Duration),
+        "bpm" => number(NumberField::Bpm),
+        "energy" => match EnergyLevel::from_name(value) {
+            Some(level) if matches!(op, Op::Match | Op::Eq | Op::Ne) => {
+                Ok(Condition::EnergyLevel(op, level))
+            }
+            _ => number(NumberField::Energy),
+        },
+        "added" => date(DateField::Added),
+        "lastplayed" | "played" => date(DateField::LastPlayed),
+        "label" => {
+            if !matches!(op, Op::Match | Op::Eq | Op::Ne) {
+                bail!("A label can only be compared with \":\", \"=\" or \"!=\", in \"{word}\"");
+            }
+            let Some(label) = TrackLabel::from_name(value) else {
+                bail!("Unknown label \"{value}\"");
+            };
+            Ok(Condition::Label(op, label))
+        }
+        "quality" => {
+            let Some(badge) = QualityBadge::from_name(value) else {
+                bail!("Unknown quality \"{value}\"");
+            };
+            Ok(Condition::Quality(op, badge))
+        }
+        _ => bail!(
+            "Unknown field \"{field}\", expected one of: {}",
+            FIELDS.join(", ")
+        ),
+    }
+}
+
+/// A recursive descent parser over the tokens of a query.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Check whether the next token is the keyword `keyword`, and consume it if so.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let is_keyword = matches!(
+            self.peek(),
+            Some(Token::Word(word, len)) if *len == word.len() && word.eq_ignore_ascii_case(keyword)
+        );
+        if is_keyword {
+            self.pos += 1;
+        }
+
+        is_keyword
+    }
+
+    /// `or := and ("OR" and)*`
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    /// `and := unary (["AND"] unary)*`
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        loop {
+            if self.keyword("AND") {
+                expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+                continue;
+            }
+            match self.peek() {
+                None | Some(Token::Close) => break,
+                Some(Token::Word(word, len))
+                    if *len == word.len() && word.eq_ignore_ascii_case("OR") =>
+                {
+                    break;
+                }
+                _ => expr = Expr::And(Box::new(expr), Box::new(self.unary()?)),
+            }
+        }
+
+        Ok(expr)
+    }
+
+    /// `unary := ("NOT" | "-") unary | "(" or ")" | term`
+    fn unary(&mut self) -> Result<Expr> {
+        if self.keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        match self.tokens.get(self.pos).cloned() {
+            None => bail!("The query ends where a term is expected"),
+            Some(Token::Close) => bail!("Unexpected \")\""),
+            Some(Token::Open) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    bail!("Missing closing \")\"");
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(Token::Word(word, len)) => {
+                self.pos += 1;
+                if let Some(rest) = word.strip_prefix('-')
+                    && !rest.is_empty()
+                    && len > 0
+                {
+                    return Ok(Expr::Not(Box::new(Expr::Term(parse_term(rest, len - 1)?))));
+                }
+                Ok(Expr::Term(parse_term(&word, len)?))
+            }
+        }
+    }
+}
+
+/// A parsed smart playlist query, see the module documentation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartQuery {
+    expr: Expr,
+}
+
+impl SmartQuery {
+    /// Parse `query`.
+    ///
+    /// # Errors
+    ///
+    /// - if the query is empty
+    /// - if the query is not valid, with a message for the user
+    pub fn parse(query: &str) -> Result<Self> {
+        let tokens = tokenize(query)?;
+        if tokens.is_empty() {
+            bail!("The query is empty");
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            bail!("Unexpected \")\"");
+        }
+
+        Ok(Self { expr })
+    }
+
+    /// Get the SQL condition for a `WHERE` over `tracks` joined with `tracks_metadata` and `albums`, with ages relative to
+    /// `now`.
+    ///
+    /// Returns the condition and its positional parameters.
+    #[must_use]
+    pub fn to_sql(&self, now: DateTime<Utc>) -> (String, Vec<Value>) {
+        let mut sql = String::new();
+        let mut params = Vec::new();
+        expr_to_sql(&self.expr, now, &mut sql, &mut params);
+
+        (sql, params)
+    }
+}
+
+/// Escape `value` for a `LIKE` with `ESCAPE '\'`.
+fn like_pattern(value: &str) -> String {
+    let mut pattern = String::from("%");
+    for ch in value.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(ch);
+    }
+    pattern.push('%');
+
+    pattern
+}
+
+fn expr_to_sql(expr: &Expr, now: DateTime<Utc>, sql: &mut String, params: &mut Vec<Value>) {
+    match expr {
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            let op = if matches!(expr, Expr::And(..)) {
+                " AND "
+            } else {
+                " OR "
+            };
+            sql.push('(');
+            expr_to_sql(left, now, sql, params);
+            sql.push_str(op);
+            expr_to_sql(right, now, sql, params);
+            sql.push(')');
+        }
+        Expr::Not(inner) => {
+            sql.push_str("NOT ");
+            expr_to_sql(inner, now, sql, params);
+        }
+        Expr::Term(condition) => {
+            // NULL columns never match, so that "NOT" matches them
+            sql.push_str("COALESCE((");
+            condition_to_sql(condition, now, sql, params);
+            sql.push_str("), 0)");
+        }
+    }
+}
+
+fn condition_to_sql(
+    condition: &Condition,
+    now: DateTime<Utc>,
+    sql: &mut String,
+    params: &mut Vec<Value>,
+) {
+    match condition {
+        Condition::Any(value) => {
+            let _ = write!(
+                sql,
+                "{0} LIKE ? ESCAPE '\\' OR {1} LIKE ? ESCAPE '\\' OR {2} LIKE ? ESCAPE '\\'",
+                TextField::Artist.column(),
+                TextField::Title.column(),
+                TextField::Album.column()
+            );
+            params.extend(std::iter::repeat_n(Value::Text(like_pattern(value)), 3));
+        }
+        Condition::Text(field, Op::Match, value) => {
+            let _ = write!(sql, "{} LIKE ? ESCAPE '\\'", field.column());
+            params.push(Value::Text(like_pattern(value)));
+        }
+        Condition::Text(field, op, value) => {
+            let _ = write!(sql, "{} {} ? COLLATE NOCASE", field.column(), op.as_sql());
+            params.push(Value::Text(value.clone()));
+        }
+        Condition::Number(field, op, value) => {
+            let _ = write!(sql, "{} {} ?", field.column(), op.as_sql());
+            params.push(Value::Real(*value));
+        }
+        Condition::Date(field, op, DateValue::Age(age)) => {
+            // an older age is a earlier date
+            let at = (now - *age).to_rfc3339();
+            let column = field.column();
+            match op.flipped() {
+                // never played is older than any age
+                Op::Lt | Op::Le if *field == DateField::LastPlayed => {
+                    let _ = write!(
+                        sql,
+                        "{column} IS NULL OR {column} {} ?",
+                        op.flipped().as_sql()
+                    );
+                }
+                // like "lastplayed:2w", within the age
+                Op::Match | Op::Eq => {
+                    let _ = write!(sql, "{column} >= ?");
+                }
+                Op::Ne => {
+                    let _ = write!(sql, "{column} < ?");
+                }
+                flipped => {
+                    let _ = write!(sql, "{column} {} ?", flipped.as_sql());
+                }
+            }
+            params.push(Value::Text(at));
+        }
+        Condition::Date(field, op, DateValue::Day(day)) => {
+            let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+            let end = day
+                .checked_add_days(Days::new(1))
+                .and_then(|v| v.and_hms_opt(0, 0, 0))
+                .map_or(start, |v| v.and_utc());
+            let column = field.column();
+            let (clause, values) = match op {
+                Op::Match | Op::Eq => (format!("{column} >= ? AND {column} < ?"), vec![start, end]),
+                Op::Ne => (format!("{column} < ? OR {column} >= ?"), vec![start, end]),
+                Op::Lt => (format!("{column} < ?"), vec![start]),
+                Op::Le => (format!("{column} < ?"), vec![end]),
+                Op::Gt => (format!("{column} >= ?"), vec![end]),
+                Op::Ge => (format!("{column} >= ?"), vec![start]),
+            };
+            sql.push_str(&clause);
+            params.extend(values.into_iter().map(|v| Value::Text(v.to_rfc3339())));
+        }
+        Condition::Label(op, label) => {
+            let _ = write!(sql, "tracks.label {} ?", op.as_sql());
+            params.push(Value::Integer(label.number().into()));
+        }
+        Condition::Quality(op, badge) => {
+            // the better the quality, the lower the number
+            let _ = write!(sql, "tracks_metadata.quality {} ?", op.flipped().as_sql());
+            params.push(Value::Integer(badge.number().into()));
+        }
+        Condition::EnergyLevel(op, level) => {
+            let range = level.range();
+            let clause = if *op == Op::Ne {
+                "NOT (tracks.energy >= ? AND tracks.energy < ?)"
+            } else {
+                "tracks.energy >= ? AND tracks.energy < ?"
+            };
+            sql.push_str(clause);
+            params.push(Value::Real(range.start.into()));
+            params.push(Value::Real(range.end.into()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Condition, DateField, DateValue, Expr, NumberField, Op, SmartQuery, TextField};
+
+    fn term(condition: Condition) -> Expr {
+        Expr::Term(condition)
+    }
+
+    #[test]
+    fn should_parse_implicit_and_explicit_and() {
+        let explicit = SmartQuery::parse("genre:ambient AND added>30d AND playcount<2").unwrap();
+        let implicit = SmartQuery::parse("genre:ambient added>30d playcount<2").unwrap();
+        assert_eq!(explicit, implicit);
+
+        assert_eq!(
+            explicit.expr,
+            Expr::And(
+                Box::new(Expr::And(
+                    Box::new(term(Condition::Text(
+                        TextField::Genre,
+                        Op::Match,
+                        "ambient".to_string()
+                    ))),
+                    Box::new(term(Condition::Date(
+                        DateField::Added,
+                        Op::Gt,
+                        DateValue::Age(chrono::TimeDelta::hours(30 * 24))
+                    ))),
+                )),
+                Box::new(term(Condition::Number(NumberField::PlayCount, Op::Lt, 2.0))),
+            )
+        );
+    }
+
+    #[test]
+    fn should_parse_or_not_and_groups() {
+        let query =
+            SmartQuery::parse("(rating>=4 OR NOT lastplayed<1y) -artist:\"Some One\"").unwrap();
+        assert_eq!(
+            query.expr,
+            Expr::And(
+                Box::new(Expr::Or(
+                    Box::new(term(Condition::Number(NumberField::Rating, Op::Ge, 4.0))),
+                    Box::new(Expr::Not(Box::new(term(Condition::Date(
+                        DateField::LastPlayed,
+                        Op::Lt,
+                        DateValue::Age(chrono::TimeDelta::hours(365 * 24))
+                    ))))),
+                )),
+                Box::new(Expr::Not(Box::new(term(Condition::Text(
+                    TextField::Artist,
+                    Op::Match,
+                    "Some One".to_string()
+                ))))),
+            )
+        );
+    }
+
+    #[test]
+    fn should_parse_audio_descriptions() {
+        let query = SmartQuery::parse("mood:calm OR sound=sparse").unwrap();
+        assert_eq!(
+            query.expr,
+            Expr::Or(
+                Box::new(term(Condition::Text(
+                    TextField::Mood,
+                    Op::Match,
+                    "calm".to_string()
+                ))),
+                Box::new(term(Condition::Text(
+                    TextField::SoundProfile,
+                    Op::Eq,
+                    "sparse".to_string()
+                ))),
+            )
+        );
+    }
+
+    #[test]
+    fn should_keep_operators_in_quotes() {
+        let query = SmartQuery::parse("title:\"a:b>c\" words").unwrap();
+        assert_eq!(
+            query.expr,
+            Expr::And(
+                Box::new(term(Condition::Text(
+                    TextField::Title,
+                    Op::Match,
+                    "a:b>c".to_string()
+                ))),
+                Box::new(term(Condition::Any("words".to_string()))),
+            )
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_queries() {
+        for query in [
+            "",
+            "unknown:value",
+            "genre>rock",
+            "playcount<many",
+            "added>30x",
+            "(genre:rock",
+            "genre:rock)",
+            "title:\"unclosed",
+            "rating:9",
+            "label:purple",
+            "genre:",
+        ] {
+            assert!(SmartQuery::parse(query).is_err(), "{query}");
+        }
+    }
+}
@@ -3,6 +3,7 @@ mod model;
 use crate::{songtag::service::SongTagServiceError, utils::random_ascii};
 
 use super::{ServiceProvider, SongTag, UrlTypes, service::SongTagService};
+use crate::ratelimit::LIMITER;
 use anyhow::anyhow;
 use bytes::Buf;
 use lofty::picture::Picture;
@@ -58,12 +59,13 @@ impl SongTagService for Api {
             ("keyword", keywords),
         ];
 
-        let result = self
-            .client
-            .post(URL_SEARCH_KUGOU)
-            .header("Referer", "https://m.music.migu.cn")
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .post(URL_SEARCH_KUGOU)
+                    .header("Referer", "https://m.music.migu.cn")
+                    .query(&query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -95,11 +97,8 @@ impl SongTagService for Api {
             ("man", "yes"),
         ];
 
-        let result = self
-            .client
-            .get(URL_LYRIC_SEARCH_KUGOU)
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(self.client.get(URL_LYRIC_SEARCH_KUGOU).query(&query_params))
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -119,11 +118,12 @@ impl SongTagService for Api {
             ("ver", "1"),
         ];
 
-        let result = self
-            .client
-            .get(URL_LYRIC_DOWNLOAD_KUGOU)
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .get(URL_LYRIC_DOWNLOAD_KUGOU)
+                    .query(&query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -162,12 +162,13 @@ impl SongTagService for Api {
 
         let query_params = vec![("hash", &pic_id), ("album_id", &album_id)];
 
-        let result = self
-            .client
-            .get(URL_SONG_DOWNLOAD_KUGOU)
-            .header("Cookie", format!("kg_mid={cookie_mid}"))
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .get(URL_SONG_DOWNLOAD_KUGOU)
+                    .header("Cookie", format!("kg_mid={cookie_mid}"))
+                    .query(&query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -178,10 +179,8 @@ impl SongTagService for Api {
             SongTagServiceError::Other(anyhow!(err).context("Extract picture url from result"))
         })?;
 
-        let result = self
-            .client
-            .get(pic_url)
-            .send()
+        let result = LIMITER
+            .send(self.client.get(pic_url))
             .await
             .map_err(anyhow::Error::from)?;
 
@@ -216,12 +215,13 @@ impl SongTagService for Api {
 
         let query_params = vec![("hash", &song.song_id), ("album_id", album_id)];
 
-        let result = self
-            .client
-            .get(URL_SONG_DOWNLOAD_KUGOU)
-            .header("Cookie", format!("kg_mid={cookie_mid}"))
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .get(URL_SONG_DOWNLOAD_KUGOU)
+                    .header("Cookie", format!("kg_mid={cookie_mid}"))
+                    .query(&query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -51,30 +51,46 @@ impl Lyric {
     /// if `time` cannot be represented as a [`i64`]
     #[must_use]
     pub fn get_text(&self, time: Duration) -> Option<&str> {
+        let index = self.get_caption_index(time)?;
+
+        Some(&self.captions[index].text)
+    }
+
+    /// Get the index of the caption at `time` or next lowest, the same caption as [`get_text`](Self::get_text).
+    ///
+    /// # Panics
+    ///
+    /// if `time` cannot be represented as a [`i64`]
+    #[must_use]
+    pub fn get_caption_index(&self, time: Duration) -> Option<usize> {
         if self.captions.is_empty() {
             return None;
         }
 
-        let mut time = i64::try_from(time.as_millis()).expect("Cannot represent input time as i64");
+        let time = i64::try_from(time.as_millis()).expect("Cannot represent input time as i64");
 
         // use a 2 second offset because of client progress delay
-        let mut adjusted_time = time + 2000;
-        adjusted_time += self.offset;
-        if adjusted_time < 0 {
-            adjusted_time = 0;
-        }
+        let time = (time + 2000 + self.offset).max(0);
 
-        time = adjusted_time;
+        let passed = self
+            .captions
+            .iter()
+            .take_while(|caption| time >= caption.timestamp)
+            .count();
 
-        let mut text = &self.captions.first()?.text;
-        for caption in &self.captions {
-            if time >= caption.timestamp {
-                text = &caption.text;
-            } else {
-                break;
-            }
-        }
-        Some(text)
+        // before the first caption, the first caption is used
+        Some(passed.saturating_sub(1))
+    }
+
+    /// Get the playback position at which the caption at `index` is reached, to seek to it.
+    ///
+    /// This function takes `self.offset` into account.
+    #[must_use]
+    pub fn caption_position(&self, index: usize) -> Option<Duration> {
+        let caption = self.captions.get(index)?;
+        let millis = (caption.timestamp - self.offset).max(0);
+
+        Some(Duration::from_millis(millis.unsigned_abs()))
     }
 
     /// Get a index for the next lowest caption from `time` (in milliseconds)
@@ -125,7 +141,7 @@ impl Lyric {
             }
         }
         // we sort the captions by time_stamp. This is to fix some lyrics downloaded are not sorted
-        self.captions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        self.captions.sort_by_key(|a| a.timestamp);
     }
 
     /// Format current [`Lyric`] as a LRC file
@@ -164,6 +180,12 @@ impl Lyric {
 }
 
 impl Caption {
+    /// Get the text of the caption.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
     /// Try to parse a single [`Caption`]
     fn parse_line(line: &str) -> Option<Self> {
         //[00:12.00]Line 1 lyrics
@@ -271,7 +293,7 @@ impl FromStr for Lyric {
         }
 
         // we sort the captions by Timestamp. This is to fix some lyrics downloaded are not sorted
-        captions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        captions.sort_by_key(|a| a.timestamp);
 
         let mut lyric = Self { offset, captions };
 
@@ -555,4 +577,55 @@ mod tests {
             "text4"
         );
     }
+
+    #[test]
+    fn should_get_caption_index() {
+        let lyrics = Lyric {
+            offset: 0,
+            captions: vec![
+                Caption {
+                    timestamp: 3 * 1000,
+                    text: "text1".into(),
+                },
+                Caption {
+                    timestamp: 5 * 1000,
+                    text: "text2".into(),
+                },
+            ],
+        };
+
+        assert_eq!(lyrics.get_caption_index(Duration::from_secs(0)), Some(0));
+        assert_eq!(lyrics.get_caption_index(Duration::from_secs(2)), Some(0));
+        assert_eq!(lyrics.get_caption_index(Duration::from_secs(3)), Some(1));
+        assert_eq!(lyrics.get_caption_index(Duration::from_secs(60)), Some(1));
+        let empty = Lyric {
+            offset: 0,
+            captions: Vec::new(),
+        };
+        assert_eq!(empty.get_caption_index(Duration::from_secs(0)), None);
+    }
+
+    #[test]
+    fn should_get_caption_position() {
+        let lyrics = Lyric {
+            offset: 500,
+            captions: vec![
+                Caption {
+                    timestamp: 200,
+                    text: "text1".into(),
+                },
+                Caption {
+                    timestamp: 5 * 1000,
+                    text: "text2".into(),
+                },
+            ],
+        };
+
+        assert_eq!(lyrics.caption_position(0), Some(Duration::ZERO));
+        assert_eq!(
+            lyrics.caption_position(1),
+            Some(Duration::from_millis(4500))
+        );
+        assert_eq!(lyrics.caption_position(2), None);
+    }
 }
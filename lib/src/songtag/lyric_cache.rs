@@ -0,0 +1,140 @@
+//! On-disk cache for fetched lyrics, keyed by artist and title, so that lyrics are also available offline.
+
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config::v2::tui::Lyrics;
+
+/// The maximal length of the artist and title each in a cache file name.
+const MAX_KEY_PART_LEN: usize = 100;
+
+/// Get the default lyric cache directory, `termusic/lyrics` in the cache directory.
+pub fn default_cache_dir() -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir().context("failed to find the cache directory")?;
+
+    Ok(cache_dir.join("termusic").join("lyrics"))
+}
+
+/// Lyrics stored as one `.lrc` file per artist and title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LyricCache {
+    dir: PathBuf,
+}
+
+impl LyricCache {
+    #[must_use]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Create a cache in the configured directory, or in [`default_cache_dir`] if not configured.
+    pub fn from_settings(settings: &Lyrics) -> Result<Self> {
+        let dir = match &settings.cache_dir {
+            Some(dir) => shellexpand::path::tilde(dir).into_owned(),
+            None => default_cache_dir()?,
+        };
+
+        Ok(Self::new(dir))
+    }
+
+    /// Get the path of the cache file for `artist` and `title`.
+    fn file_path(&self, artist: &str, title: &str) -> PathBuf {
+        self.dir.join(cache_key(artist, title))
+    }
+
+    /// Get the cached lyric for `artist` and `title`, if there is one.
+    #[must_use]
+    pub fn get(&self, artist: &str, title: &str) -> Option<String> {
+        let path = self.file_path(artist, title);
+        match fs::read_to_string(&path) {
+            Ok(v) => Some(v),
+            Err(err) => {
+                if err.kind() != ErrorKind::NotFound {
+                    warn!("Error reading cached lyric {}: {err:#}", path.display());
+                }
+                None
+            }
+        }
+    }
+
+    /// Check if there is a cached lyric for `artist` and `title`.
+    #[must_use]
+    pub fn contains(&self, artist: &str, title: &str) -> bool {
+        self.file_path(artist, title).is_file()
+    }
+
+    /// Store `lyric` for `artist` and `title`, replacing a previously cached one.
+    pub fn insert(&self, artist: &str, title: &str, lyric: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir).with_context(|| self.dir.display().to_string())?;
+
+        let path = self.file_path(artist, title);
+        // write to a temporary file first, so that readers never see a partial file
+        let part = path.with_extension("lrc.part");
+        fs::write(&part, lyric).with_context(|| part.display().to_string())?;
+        fs::rename(&part, &path).with_context(|| path.display().to_string())
+    }
+}
+
+/// Get the file name for `artist` and `title`, case-insensitive and safe for all filesystems.
+fn cache_key(artist: &str, title: &str) -> String {
+    format!("{} - {}.lrc", sanitize(artist), sanitize(title))
+}
+
+/// Lowercase `value` and replace all characters which are not alphanumeric with `_`.
+fn sanitize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|ch| if ch.is_alphanumeric() { ch } else { '_' })
+        .take(MAX_KEY_PART_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{LyricCache, cache_key};
+
+    #[test]
+    fn should_sanitize_cache_key() {
+        assert_eq!(
+            cache_key("AC/DC", " Back In Black "),
+            "ac_dc - back_in_black.lrc"
+        );
+        assert_eq!(
+            cache_key("Sigur Rós", "Hoppípolla"),
+            "sigur_rós - hoppípolla.lrc"
+        );
+        assert_eq!(cache_key("..", "a:b"), "__ - a_b.lrc");
+    }
+
+    #[test]
+    fn should_insert_and_get() {
+        let dir = std::env::temp_dir().join(format!("termusic-lyric-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = LyricCache::new(dir.clone());
+
+        assert!(!cache.contains("Artist", "Title"));
+        assert_eq!(cache.get("Artist", "Title"), None);
+
+        cache.insert("Artist", "Title", "[00:01.00]line").unwrap();
+        assert!(cache.contains("artist", "title"));
+        assert_eq!(
+            cache.get("ARTIST", "TITLE").as_deref(),
+            Some("[00:01.00]line")
+        );
+
+        cache.insert("Artist", "Title", "[00:02.00]new").unwrap();
+        assert_eq!(
+            cache.get("Artist", "Title").as_deref(),
+            Some("[00:02.00]new")
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,5 +1,6 @@
 mod model;
 
+use crate::ratelimit::LIMITER;
 use anyhow::anyhow;
 use bytes::Buf;
 use lofty::picture::Picture;
@@ -59,12 +60,13 @@ impl SongTagService for Api {
             ("type", "2"),
         ];
 
-        let result = self
-            .client
-            .post(URL_SEARCH_MIGU)
-            .header("Referer", REFERER)
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .post(URL_SEARCH_MIGU)
+                    .header("Referer", REFERER)
+                    .query(&query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -95,12 +97,13 @@ impl SongTagService for Api {
 
         let query_params = &[("copyrightId", &lyric_id)];
 
-        let result = self
-            .client
-            .get(URL_LYRIC_MIGU)
-            .header("Referer", REFERER)
-            .query(&query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .get(URL_LYRIC_MIGU)
+                    .header("Referer", REFERER)
+                    .query(&query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -125,12 +128,13 @@ impl SongTagService for Api {
 
         let query_params = &[("songId", &song.song_id)];
 
-        let result = self
-            .client
-            .get(URL_PIC_MIGU)
-            .header("Referer", REFERER)
-            .query(query_params)
-            .send()
+        let result = LIMITER
+            .send(
+                self.client
+                    .get(URL_PIC_MIGU)
+                    .header("Referer", REFERER)
+                    .query(query_params),
+            )
             .await
             .map_err(anyhow::Error::from)?
             .text()
@@ -142,10 +146,8 @@ impl SongTagService for Api {
         })?;
         let url = format!("https:{pic_url}");
 
-        let result = self
-            .client
-            .get(url)
-            .send()
+        let result = LIMITER
+            .send(self.client.get(url))
             .await
             .map_err(anyhow::Error::from)?;
 
@@ -15,6 +15,7 @@ use crate::utils::get_parent_folder;
 
 mod kugou;
 pub mod lrc;
+pub mod lyric_cache;
 mod migu;
 mod netease_v2;
 mod service;
@@ -71,6 +72,11 @@ pub enum SongtagSearchResult {
 
 // Search function of 3 servers. Run in parallel to get results faster.
 pub async fn search(search_str: &str, tx_done: impl Fn(SongtagSearchResult) + Send + 'static) {
+    tx_done(SongtagSearchResult::Finish(search_all(search_str).await));
+}
+
+/// Search all servers for `search_str` and collect the results of all of them.
+async fn search_all(search_str: &str) -> Vec<SongTag> {
     let mut results: Vec<SongTag> = Vec::new();
 
     let handle_netease = async {
@@ -106,7 +112,32 @@ pub async fn search(search_str: &str, tx_done: impl Fn(SongtagSearchResult) + Se
         Err(err) => error!("Kogou Error: {err:#}"),
     }
 
-    tx_done(SongtagSearchResult::Finish(results));
+    results
+}
+
+/// The maximal amount of search results to try to fetch lyrics from in [`fetch_lyric_by_tags`].
+const LYRIC_FETCH_CANDIDATES: usize = 5;
+
+/// Search all servers for `artist` and `title` and fetch the lyric of the best match.
+///
+/// Results with a matching title are tried first, returns [`None`] if no result had a lyric.
+pub async fn fetch_lyric_by_tags(artist: &str, title: &str) -> Result<Option<String>> {
+    let mut results = search_all(&format!("{artist} {title}")).await;
+    // stable sort, so the order of the servers is kept otherwise
+    results.sort_by_key(|v| !v.title().is_some_and(|v| v.eq_ignore_ascii_case(title)));
+
+    for song_tag in results.iter().take(LYRIC_FETCH_CANDIDATES) {
+        match song_tag.fetch_lyric().await {
+            Ok(Some(lyric)) if !lyric.trim().is_empty() => return Ok(Some(lyric)),
+            Ok(_) => (),
+            Err(err) => warn!(
+                "Fetching Lyric from {} failed: {err:#}",
+                song_tag.service_provider()
+            ),
+        }
+    }
+
+    Ok(None)
 }
 
 pub type TrackDLMsgURL = Arc<str>;
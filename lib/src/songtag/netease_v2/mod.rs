@@ -1,5 +1,6 @@
 use std::{collections::HashMap, time::Duration};
 
+use crate::ratelimit::LIMITER;
 use anyhow::anyhow;
 use base64::{Engine, engine::general_purpose};
 use bytes::Buf as _;
@@ -166,8 +167,11 @@ impl SongTagService for Api {
             ("limit", &limit),
         ];
 
-        let response = Self::common_request(self.client.post(URL_SEARCH_NETEASE), query_params)
-            .send()
+        let response = LIMITER
+            .send(Self::common_request(
+                self.client.post(URL_SEARCH_NETEASE),
+                query_params,
+            ))
             .await
             .map_err(anyhow::Error::from)?;
 
@@ -202,8 +206,11 @@ impl SongTagService for Api {
             ("tv", "-1"),
         ];
 
-        let response = Self::common_request(self.client.post(URL_LYRIC_NETEASE), query_params)
-            .send()
+        let response = LIMITER
+            .send(Self::common_request(
+                self.client.post(URL_LYRIC_NETEASE),
+                query_params,
+            ))
             .await
             .map_err(anyhow::Error::from)?;
 
@@ -234,10 +241,8 @@ impl SongTagService for Api {
         let id_encrypted = Self::encode_pic_id(pic_id);
         let pic_url = format!("{URL_PICTURE_SERVICE}{id_encrypted}/{pic_id}.jpg?param=300y300");
 
-        let result = self
-            .client
-            .get(pic_url)
-            .send()
+        let result = LIMITER
+            .send(self.client.get(pic_url))
             .await
             .map_err(anyhow::Error::from)?;
 
@@ -268,8 +273,11 @@ impl SongTagService for Api {
             ("encodeType", "aac"),
         ];
 
-        let response = Self::common_request(self.client.post(URL_DOWNLOAD_NETEASE), query_params)
-            .send()
+        let response = LIMITER
+            .send(Self::common_request(
+                self.client.post(URL_DOWNLOAD_NETEASE),
+                query_params,
+            ))
             .await
             .map_err(anyhow::Error::from)?;
 
@@ -0,0 +1,190 @@
+//! Get the `SponsorBlock` segments of youtube videos, to skip them while streaming or remove them from downloads.
+//!
+//! See <https://wiki.sponsor.ajay.app/w/API_Docs>.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use reqwest::{ClientBuilder, StatusCode};
+use serde_json::Value;
+
+use crate::config::v2::server::sponsorblock::{SponsorBlockCategory, SponsorBlockSettings};
+use crate::ratelimit::LIMITER;
+
+/// A segment of a video to skip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub start: Duration,
+    pub end: Duration,
+    pub category: SponsorBlockCategory,
+}
+
+impl Segment {
+    /// Check if `position` is within this segment.
+    #[must_use]
+    pub fn contains(&self, position: Duration) -> bool {
+        (self.start..self.end).contains(&position)
+    }
+}
+
+/// Get the arguments for yt-dlp to cut the configured categories out of a download, none if disabled.
+#[must_use]
+pub fn ytdlp_remove_args(settings: &SponsorBlockSettings) -> Option<[String; 4]> {
+    if !settings.is_enabled() {
+        return None;
+    }
+
+    Some([
+        "--sponsorblock-remove".to_string(),
+        join_categories(&settings.categories, ","),
+        "--sponsorblock-api".to_string(),
+        settings.api_url.clone(),
+    ])
+}
+
+/// Join the names of `categories` with `separator`.
+fn join_categories(categories: &[SponsorBlockCategory], separator: &str) -> String {
+    categories
+        .iter()
+        .map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Get the segments of the configured categories for the youtube video `video_id`, ordered by their start.
+///
+/// Returns no segments if `SponsorBlock` is disabled or the video has none.
+pub async fn get_segments(settings: &SponsorBlockSettings, video_id: &str) -> Result<Vec<Segment>> {
+    if !settings.is_enabled() {
+        return Ok(Vec::new());
+    }
+
+    let client = ClientBuilder::new()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let categories = format!("[\"{}\"]", join_categories(&settings.categories, "\",\""));
+    let url = format!(
+        "{}/api/skipSegments",
+        settings.api_url.trim_end_matches('/')
+    );
+
+    let res = LIMITER
+        .send(
+            client
+                .get(url)
+                .query(&[("videoID", video_id), ("categories", &categories)]),
+        )
+        .await
+        .context("request segments")?;
+
+    match res.status() {
+        StatusCode::OK => Ok(parse_segments(&res.text().await?)),
+        // the video has no segments
+        StatusCode::NOT_FOUND => Ok(Vec::new()),
+        status => bail!("SponsorBlock responded with {status}"),
+    }
+}
+
+/// Parse the segments of a `skipSegments` response, skipping segments that are only marked and not meant to be skipped.
+fn parse_segments(data: &str) -> Vec<Segment> {
+    let Ok(Value::Array(items)) = serde_json::from_str::<Value>(data) else {
+        return Vec::new();
+    };
+
+    let mut segments: Vec<Segment> = items
+        .iter()
+        .filter(|v| {
+            v.get("actionType")
+                .and_then(Value::as_str)
+                .is_none_or(|v| v == "skip")
+        })
+        .filter_map(|v| {
+            let times = v.get("segment")?.as_array()?;
+            let start = Duration::try_from_secs_f64(times.first()?.as_f64()?).ok()?;
+            let end = Duration::try_from_secs_f64(times.get(1)?.as_f64()?).ok()?;
+            let category = SponsorBlockCategory::from_name(v.get("category")?.as_str()?)?;
+
+            (start < end).then_some(Segment {
+                start,
+                end,
+                category,
+            })
+        })
+        .collect();
+    segments.sort_by_key(|v| v.start);
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Segment, parse_segments, ytdlp_remove_args};
+    use crate::config::v2::server::sponsorblock::{SponsorBlockCategory, SponsorBlockSettings};
+
+    #[test]
+    fn should_parse_segments() {
+        let data = r#"[
+            {"segment": [120.5, 150.0], "category": "outro", "actionType": "skip"},
+            {"segment": [10, 30.25], "category": "sponsor", "actionType": "skip"},
+            {"segment": [40, 50], "category": "sponsor", "actionType": "poi"},
+            {"segment": [60, 60], "category": "intro"},
+            {"segment": [70, 80], "category": "unknown"}
+        ]"#;
+
+        assert_eq!(
+            parse_segments(data),
+            [
+                Segment {
+                    start: Duration::from_secs(10),
+                    end: Duration::from_millis(30_250),
+                    category: SponsorBlockCategory::Sponsor,
+                },
+                Segment {
+                    start: Duration::from_millis(120_500),
+                    end: Duration::from_secs(150),
+                    category: SponsorBlockCategory::Outro,
+                },
+            ]
+        );
+        assert_eq!(parse_segments("Not Found"), []);
+    }
+
+    #[test]
+    fn should_build_ytdlp_args() {
+        let mut settings = SponsorBlockSettings::default();
+        assert_eq!(ytdlp_remove_args(&settings), None);
+
+        settings.categories = vec![
+            SponsorBlockCategory::Sponsor,
+            SponsorBlockCategory::MusicOfftopic,
+        ];
+        assert_eq!(
+            ytdlp_remove_args(&settings),
+            Some([
+                "--sponsorblock-remove".to_string(),
+                "sponsor,music_offtopic".to_string(),
+                "--sponsorblock-api".to_string(),
+                "https://sponsor.ajay.app".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_parse_category_names() {
+        for category in SponsorBlockCategory::ALL {
+            assert_eq!(
+                SponsorBlockCategory::from_name(category.as_str()),
+                Some(category)
+            );
+        }
+        assert_eq!(
+            SponsorBlockCategory::from_name(" Sponsor "),
+            Some(SponsorBlockCategory::Sponsor)
+        );
+        assert_eq!(SponsorBlockCategory::from_name("ads"), None);
+    }
+}
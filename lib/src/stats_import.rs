@@ -0,0 +1,518 @@
+//! Import ratings, play counts and last plays from other players, to keep years of listening data when migrating.
+//!
+//! Supported are:
+//! - text exports with a header row, separated by tabs, semicolons or commas, like the playlist columns of foobar2000
+//!   (`%path%`, `%artist%`, `%title%`, `%album%`, `%play_count%`, `%rating%`, `%last_played%`, exported with its
+//!   "Text Tools" or "Export as CSV") or the "Export to CSV" of `MediaMonkey`
+//! - `.ratings` sidecars next to the tracks, named like the track with `.ratings` appended or replacing the extension,
+//!   containing `key=value` lines of `rating`, `play_count` and `last_played`, or only the rating
+//!
+//! Every entry is mapped onto a library track by its path, after [`PathMapping`], or otherwise by its artist and title,
+//! see [`import`].
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result, bail};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::Connection;
+use walkdir::WalkDir;
+
+use crate::new_database::track_ops::{self, RATING_MAX};
+
+/// The extension of rating sidecars.
+pub const SIDECAR_EXTENSION: &str = "ratings";
+
+/// The statistics of a single track from a export.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedStats {
+    /// The path as exported, which may be from another system
+    pub path: Option<String>,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+    pub play_count: Option<u64>,
+    /// The rating from 1 to [`RATING_MAX`] stars
+    pub rating: Option<u8>,
+    pub last_played_at: Option<DateTime<Utc>>,
+}
+
+impl ImportedStats {
+    /// Get whether there is anything to import.
+    fn is_empty(&self) -> bool {
+        self.play_count.is_none() && self.rating.is_none() && self.last_played_at.is_none()
+    }
+
+    /// Describe the entry for messages, by its path or tags.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        if let Some(path) = &self.path {
+            return path.clone();
+        }
+
+        format!(
+            "{} - {}",
+            self.artist.as_deref().unwrap_or("?"),
+            self.title.as_deref().unwrap_or("?")
+        )
+    }
+}
+
+/// Replace the `from` prefix of exported paths with `to`, like `D:\Music` with `/home/user/Music`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMapping {
+    pub from: String,
+    pub to: PathBuf,
+}
+
+impl PathMapping {
+    /// Parse `FROM=TO`.
+    ///
+    /// # Errors
+    ///
+    /// - if there is no `=`
+    pub fn parse(value: &str) -> Result<Self> {
+        let Some((from, to)) = value.split_once('=') else {
+            bail!("\"{value}\" is not like \"FROM=TO\"");
+        };
+
+        Ok(Self {
+            from: from.to_string(),
+            to: PathBuf::from(to),
+        })
+    }
+
+    /// Map `path`, if it starts with [`from`](Self::from). Windows separators are converted.
+    fn apply(&self, path: &str) -> Option<PathBuf> {
+        let rest = path.strip_prefix(&self.from)?;
+        let rest = rest.trim_start_matches(['/', '\\']);
+
+        Some(
+            rest.split(['/', '\\'])
+                .filter(|v| !v.is_empty())
+                .fold(self.to.clone(), |acc, v| acc.join(v)),
+        )
+    }
+}
+
+/// Which of the columns of a export is which value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Path,
+    Artist,
+    Title,
+    Album,
+    PlayCount,
+    Rating,
+    LastPlayed,
+}
+
+impl Column {
+    /// Get the column of the header `name`, ignoring case, spaces and the `%` of foobar2000 title formatting.
+    fn from_header(name: &str) -> Option<Self> {
+        let name: String = name
+            .chars()
+            .filter(|v| v.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        let column = match name.as_str() {
+            "path" | "filename" | "songpath" | "location" | "file" => Self::Path,
+            "artist" | "trackartist" => Self::Artist,
+            "title" | "songtitle" => Self::Title,
+            "album" => Self::Album,
+            "playcount" | "played" | "plays" | "playcounter" => Self::PlayCount,
+            "rating" => Self::Rating,
+            "lastplayed" | "lasttimeplayed" => Self::LastPlayed,
+            _ => return None,
+        };
+
+        Some(column)
+    }
+}
+
+/// Split a line of a export at `separator`, with fields optionally quoted by `"` and `""` for a literal quote.
+fn split_fields(line: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ch if ch == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            ch => field.push(ch),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// Get the value of a exported field, [`None`] if empty or unknown like the `?` of foobar2000.
+fn field_value(field: &str) -> Option<&str> {
+    let field = field.trim();
+    if field.is_empty() || field == "?" {
+        return None;
+    }
+
+    Some(field)
+}
+
+/// Parse a rating of 0 to 5 stars, with fractions rounded, or of 0 to 100 like `MediaMonkey`. 0 is not rated.
+fn parse_rating(value: &str) -> Option<u8> {
+    let value: f64 = value.trim().parse().ok()?;
+    if !value.is_finite() || value <= 0.0 {
+        return None;
+    }
+    let stars = if value > f64::from(RATING_MAX) {
+        value / 20.0
+    } else {
+        value
+    };
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Some((stars.round() as u8).clamp(1, RATING_MAX))
+}
+
+/// Parse a timestamp as exported by common players. Timestamps without a timezone are taken as UTC.
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    const FORMATS: &[&str] = &[
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%m/%d/%Y %I:%M:%S %p",
+        "%m/%d/%Y %H:%M:%S",
+        "%m/%d/%Y %H:%M",
+        "%d.%m.%Y %H:%M:%S",
+        "%d.%m.%Y %H:%M",
+    ];
+    const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y"];
+
+    let value = value.trim();
+    if let Ok(v) = DateTime::parse_from_rfc3339(value) {
+        return Some(v.to_utc());
+    }
+    if let Some(v) = FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    {
+        return Some(v.and_utc());
+    }
+
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .and_then(|v| v.and_hms_opt(0, 0, 0))
+        .map(|v| v.and_utc())
+}
+
+/// Parse a text export with a header row, see the module documentation.
+///
+/// # Errors
+///
+/// - if the header has neither a path nor a artist and title column
+pub fn parse_export(content: &str) -> Result<Vec<ImportedStats>> {
+    let mut lines = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .filter(|v| !v.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Ok(Vec::new());
+    };
+
+    let separator = [
+        ('\t', header.matches('\t').count()),
+        (';', header.matches(';').count()),
+    ]
+    .into_iter()
+    .find(|(_, count)| *count > 0)
+    .map_or(',', |(separator, _)| separator);
+    let columns: Vec<Option<Column>> = split_fields(header, separator)
+        .iter()
+        .map(|v| Column::from_header(v))
+        .collect();
+
+    let has = |column| columns.contains(&Some(column));
+    if !(has(Column::Path) || (has(Column::Artist) && has(Column::Title))) {
+        bail!("The header needs a path column, or artist and title columns: \"{header}\"");
+    }
+
+    let mut entries = Vec::new();
+    for line in lines {
+        let mut entry = ImportedStats::default();
+        for (column, field) in columns.iter().zip(split_fields(line, separator)) {
+            let (Some(column), Some(value)) = (column, field_value(&field)) else {
+                continue;
+            };
+            match column {
+                Column::Path => entry.path = Some(value.to_string()),
+                Column::Artist => entry.artist = Some(value.to_string()),
+                Column::Title => entry.title = Some(value.to_string()),
+                Column::Album => entry.album = Some(value.to_string()),
+                Column::PlayCount => entry.play_count = value.parse().ok(),
+                Column::Rating => entry.rating = parse_rating(value),
+                Column::LastPlayed => entry.last_played_at = parse_timestamp(value),
+            }
+        }
+        if !entry.is_empty() {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Parse the content of a `.ratings` sidecar of the track at `track`, see the module documentation.
+#[must_use]
+pub fn parse_sidecar(track: &Path, content: &str) -> ImportedStats {
+    let mut entry = ImportedStats {
+        path: Some(track.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+
+    for line in content.lines().map(str::trim).filter(|v| !v.is_empty()) {
+        let Some((key, value)) = line.split_once(['=', ':']) else {
+            // a sidecar of only the rating
+            entry.rating = entry.rating.or_else(|| parse_rating(line));
+            continue;
+        };
+        let Some(value) = field_value(value) else {
+            continue;
+        };
+        match Column::from_header(key) {
+            Some(Column::PlayCount) => entry.play_count = value.parse().ok(),
+            Some(Column::Rating) => entry.rating = parse_rating(value),
+            Some(Column::LastPlayed) => entry.last_played_at = parse_timestamp(value),
+            _ => (),
+        }
+    }
+
+    entry
+}
+
+/// Get the track a sidecar at `sidecar` belongs to: `song.mp3` for `song.mp3.ratings`, or the only other file named
+/// `song` for `song.ratings`.
+fn sidecar_track(sidecar: &Path) -> Option<PathBuf> {
+    let track = sidecar.with_extension("");
+    if track.extension().is_some() && track.is_file() {
+        return Some(track);
+    }
+
+    let stem = sidecar.file_stem()?;
+    let mut candidates = std::fs::read_dir(sidecar.parent()?)
+        .ok()?
+        .filter_map(|v| v.ok().map(|v| v.path()))
+        .filter(|v| {
+            v.is_file()
+                && v.file_stem() == Some(stem)
+                && v.extension().is_some_and(|v| v != SIDECAR_EXTENSION)
+        });
+    let track = candidates.next()?;
+    // ambiguous, like "song.mp3" and "song.flac"
+    if candidates.next().is_some() {
+        return None;
+    }
+
+    Some(track)
+}
+
+/// Find all `.ratings` sidecars in `dir` and its subdirectories, and parse them.
+///
+/// Returns the parsed sidecars, and the sidecars without a matching track.
+#[must_use]
+pub fn scan_sidecars(dir: &Path) -> (Vec<ImportedStats>, Vec<PathBuf>) {
+    let mut entries = Vec::new();
+    let mut orphans = Vec::new();
+
+    let sidecars = WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|v| {
+            v.file_type().is_file() && v.path().extension().is_some_and(|v| v == SIDECAR_EXTENSION)
+        });
+    for sidecar in sidecars {
+        let sidecar = sidecar.path();
+        let Some(track) = sidecar_track(sidecar) else {
+            orphans.push(sidecar.to_path_buf());
+            continue;
+        };
+        match std::fs::read_to_string(sidecar) {
+            Ok(content) => entries.push(parse_sidecar(&track, &content)),
+            Err(err) => warn!("Error reading {}: {err:#}", sidecar.display()),
+        }
+    }
+
+    (entries, orphans)
+}
+
+/// The outcome of a [`import`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// How many entries were mapped onto a library track
+    pub matched: usize,
+    /// The entries that did not match exactly one library track, described by [`ImportedStats::describe`]
+    pub unmatched: Vec<String>,
+}
+
+/// Find the library track of `entry`: by its path, mapped with `mapping`, otherwise by its artist and title, with the
+/// album to choose between multiple matches.
+fn find_track(
+    conn: &Connection,
+    entry: &ImportedStats,
+    mapping: Option<&PathMapping>,
+) -> Result<Option<PathBuf>> {
+    if let Some(path) = &entry.path {
+        let path = mapping
+            .and_then(|v| v.apply(path))
+            .unwrap_or_else(|| PathBuf::from(path));
+        if track_ops::track_exists(conn, &path).unwrap_or(false) {
+            return Ok(Some(path));
+        }
+    }
+
+    let (Some(artist), Some(title)) = (&entry.artist, &entry.title) else {
+        return Ok(None);
+    };
+    let mut found = track_ops::find_tracks_by_tags(conn, artist, title)?;
+    if found.len() > 1
+        && let Some(album) = &entry.album
+    {
+        found.retain(|(_, v)| v.as_ref().is_some_and(|v| v.eq_ignore_ascii_case(album)));
+    }
+    if found.len() != 1 {
+        return Ok(None);
+    }
+
+    Ok(found.pop().map(|(path, _)| path))
+}
+
+/// Map `entries` onto the library tracks and merge them with [`track_ops::merge_play_stats`], unless `dry_run`.
+///
+/// # Errors
+///
+/// - if the database cannot be read or written
+pub fn import(
+    conn: &Connection,
+    entries: &[ImportedStats],
+    mapping: Option<&PathMapping>,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+
+    for entry in entries {
+        let Some(track) = find_track(conn, entry, mapping)? else {
+            report.unmatched.push(entry.describe());
+            continue;
+        };
+        if !dry_run {
+            track_ops::merge_play_stats(
+                conn,
+                &track,
+                entry.play_count,
+                entry.last_played_at,
+                entry.rating,
+            )
+            .with_context(|| track.display().to_string())?;
+        }
+        report.matched += 1;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        ImportedStats, PathMapping, parse_export, parse_rating, parse_sidecar, parse_timestamp,
+    };
+
+    #[test]
+    fn should_parse_foobar2000_export() {
+        let content = "%path%\t%artist%\t%title%\t%play_count%\t%rating%\t%last_played%\n\
+            D:\\Music\\a.flac\tArtistA\tTitle A\t12\t4\t2019-05-04 21:33:10\n\
+            D:\\Music\\b.flac\tArtistA\tTitle B\t?\t?\t?\n";
+
+        assert_eq!(
+            parse_export(content).unwrap(),
+            vec![ImportedStats {
+                path: Some("D:\\Music\\a.flac".to_string()),
+                artist: Some("ArtistA".to_string()),
+                title: Some("Title A".to_string()),
+                album: None,
+                play_count: Some(12),
+                rating: Some(4),
+                last_played_at: parse_timestamp("2019-05-04T21:33:10Z"),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_mediamonkey_export() {
+        let content = "\"Title\",\"Artist\",\"Album\",\"Rating\",\"Played #\",\"Last Played\"\n\
+            \"Say \"\"Hi\"\"\",\"ArtistA\",\"AlbumA\",\"90\",\"3\",\"5/4/2019 9:33:10 PM\"\n";
+
+        assert_eq!(
+            parse_export(content).unwrap(),
+            vec![ImportedStats {
+                path: None,
+                artist: Some("ArtistA".to_string()),
+                title: Some("Say \"Hi\"".to_string()),
+                album: Some("AlbumA".to_string()),
+                play_count: Some(3),
+                rating: Some(5),
+                last_played_at: parse_timestamp("2019-05-04T21:33:10Z"),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_err_without_identifying_columns() {
+        assert!(parse_export("rating,play_count\n4,2\n").is_err());
+    }
+
+    #[test]
+    fn should_parse_ratings() {
+        assert_eq!(parse_rating("0"), None);
+        assert_eq!(parse_rating("-1"), None);
+        assert_eq!(parse_rating("3"), Some(3));
+        assert_eq!(parse_rating("3.5"), Some(4));
+        assert_eq!(parse_rating("60"), Some(3));
+        assert_eq!(parse_rating("100"), Some(5));
+        assert_eq!(parse_rating("abc"), None);
+    }
+
+    #[test]
+    fn should_parse_sidecars() {
+        let track = Path::new("/music/a.mp3");
+        let entry = parse_sidecar(track, "rating=4\nplay_count: 7\nlast_played=2020-01-02\n");
+        assert_eq!(entry.rating, Some(4));
+        assert_eq!(entry.play_count, Some(7));
+        assert_eq!(
+            entry.last_played_at,
+            parse_timestamp("2020-01-02T00:00:00Z")
+        );
+
+        assert_eq!(parse_sidecar(track, "5\n").rating, Some(5));
+    }
+
+    #[test]
+    fn should_map_paths() {
+        let mapping = PathMapping::parse("D:\\Music=/home/user/Music").unwrap();
+        assert_eq!(
+            mapping.apply("D:\\Music\\Artist\\a.flac"),
+            Some(PathBuf::from("/home/user/Music/Artist/a.flac"))
+        );
+        assert_eq!(mapping.apply("E:\\Other\\a.flac"), None);
+        assert!(PathMapping::parse("no separator").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Suggestions for the youtube search, from online services or offline from the search history and library.
+//!
+//! Which [`SuggestionProvider`] is used is configured via [`SuggestionBackend`], see [`Provider::new`].
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use rand::seq::SliceRandom as _;
+use reqwest::{Client, ClientBuilder, RequestBuilder};
+use serde_json::Value;
+
+use crate::config::v2::tui::SuggestionBackend;
+use crate::invidious::{INVIDIOUS_INSTANCE_LIST, rank_by_health};
+use crate::new_database::{Database, track_ops};
+use crate::ratelimit::LIMITER;
+use crate::search_history::HistoryList;
+
+/// The maximal amount of suggestions returned by any provider.
+pub const MAX_SUGGESTIONS: usize = 10;
+
+/// A source of search suggestions.
+#[allow(async_fn_in_trait)] // all implementations are local, no `Send` bound is needed
+pub trait SuggestionProvider {
+    /// The name of the provider, for messages.
+    fn display_name(&self) -> &'static str;
+    /// Get up to [`MAX_SUGGESTIONS`] suggestions that complete `query`, the best first.
+    async fn suggest(&self, query: &str) -> Result<Vec<String>>;
+}
+
+/// The configured provider.
+#[derive(Debug, Clone)]
+pub enum Provider {
+    Google(Google),
+    DuckDuckGo(DuckDuckGo),
+    Invidious(Invidious),
+    Offline(Offline),
+}
+
+impl Provider {
+    /// Create the provider for `backend`.
+    ///
+    /// `invidious_domain` is the instance of the last search, `offline` is only used for [`SuggestionBackend::Offline`].
+    ///
+    /// # Errors
+    ///
+    /// - if the http client cannot be created
+    pub fn new(
+        backend: SuggestionBackend,
+        invidious_domain: Option<String>,
+        offline: Offline,
+    ) -> Result<Self> {
+        Ok(match backend {
+            SuggestionBackend::Google => Self::Google(Google::new(client()?)),
+            SuggestionBackend::DuckDuckGo => Self::DuckDuckGo(DuckDuckGo::new(client()?)),
+            SuggestionBackend::Invidious => {
+                Self::Invidious(Invidious::new(client()?, invidious_domain))
+            }
+            SuggestionBackend::Offline => Self::Offline(offline),
+        })
+    }
+}
+
+impl SuggestionProvider for Provider {
+    fn display_name(&self) -> &'static str {
+        match self {
+            Self::Google(v) => v.display_name(),
+            Self::DuckDuckGo(v) => v.display_name(),
+            Self::Invidious(v) => v.display_name(),
+            Self::Offline(v) => v.display_name(),
+        }
+    }
+
+    async fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        match self {
+            Self::Google(v) => v.suggest(query).await,
+            Self::DuckDuckGo(v) => v.suggest(query).await,
+            Self::Invidious(v) => v.suggest(query).await,
+            Self::Offline(v) => v.suggest(query).await,
+        }
+    }
+}
+
+fn client() -> Result<Client> {
+    Ok(ClientBuilder::new()
+        .timeout(Duration::from_secs(5))
+        .build()?)
+}
+
+/// Send `request` and get its body, failing on non-success status codes.
+async fn fetch(request: RequestBuilder) -> Result<String> {
+    let response = LIMITER.send(request).await?.error_for_status()?;
+
+    Ok(response.text().await?)
+}
+
+/// The youtube suggestions of google.
+#[derive(Debug, Clone)]
+pub struct Google {
+    client: Client,
+}
+
+impl Google {
+    #[must_use]
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl SuggestionProvider for Google {
+    fn display_name(&self) -> &'static str {
+        "Google"
+    }
+
+    async fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        let text = fetch(
+            self.client
+                .get("https://suggestqueries.google.com/complete/search")
+                .query(&[("client", "firefox"), ("ds", "yt"), ("q", query)]),
+        )
+        .await?;
+
+        parse_opensearch(&text)
+    }
+}
+
+/// The suggestions of duckduckgo.
+#[derive(Debug, Clone)]
+pub struct DuckDuckGo {
+    client: Client,
+}
+
+impl DuckDuckGo {
+    #[must_use]
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl SuggestionProvider for DuckDuckGo {
+    fn display_name(&self) -> &'static str {
+        "DuckDuckGo"
+    }
+
+    async fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        let text = fetch(
+            self.client
+                .get("https://duckduckgo.com/ac/")
+                .query(&[("q", query), ("type", "list")]),
+        )
+        .await?;
+
+        parse_opensearch(&text)
+    }
+}
+
+/// The suggestions of a invidious instance.
+#[derive(Debug, Clone)]
+pub struct Invidious {
+    client: Client,
+    /// The instance to use, if unset the public instances are tried in a random order
+    domain: Option<String>,
+}
+
+impl Invidious {
+    #[must_use]
+    pub fn new(client: Client, domain: Option<String>) -> Self {
+        Self {
+            client,
+            domain: domain.filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+impl SuggestionProvider for Invidious {
+    fn display_name(&self) -> &'static str {
+        "Invidious"
+    }
+
+    async fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        let domains = if let Some(domain) = &self.domain {
+            vec![domain.clone()]
+        } else {
+            let mut domains: Vec<String> = INVIDIOUS_INSTANCE_LIST
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            domains.shuffle(&mut rand::rng());
+            rank_by_health(domains)
+        };
+
+        let mut last_err = None;
+        for domain in domains {
+            let request = self
+                .client
+                .get(format!("{domain}/api/v1/search/suggestions"))
+                .query(&[("q", query)]);
+            // no retries, as the next instance is the fallback
+            let res = async {
+                let response = LIMITER.send_once(request).await?.error_for_status()?;
+                parse_invidious(&response.text().await?)
+            }
+            .await;
+
+            match res {
+                Ok(v) => return Ok(v),
+                Err(err) => {
+                    debug!("Getting suggestions from \"{domain}\" failed: {err:#}");
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no invidious instance available")))
+    }
+}
+
+/// Suggestions from the search history and the artists and titles in the library, without any network access.
+#[derive(Debug, Clone, Default)]
+pub struct Offline {
+    history: HistoryList,
+    db: Option<Database>,
+}
+
+impl Offline {
+    #[must_use]
+    pub fn new(history: HistoryList, db: Option<Database>) -> Self {
+        Self { history, db }
+    }
+}
+
+impl SuggestionProvider for Offline {
+    fn display_name(&self) -> &'static str {
+        "Offline"
+    }
+
+    async fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        let library = match &self.db {
+            Some(db) if !query.trim().is_empty() => {
+                let like = format!("%{}%", query.trim());
+                db.read(move |conn| {
+                    // get more than needed, as the prefix matches are sorted first
+                    track_ops::all_distinct_names_like(conn, &like, 100)
+                })
+                .await?
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(match_offline(query, self.history.recent(), library.iter()))
+    }
+}
+
+/// Get the entries matching `query` case-insensitively, the ones starting with it first,
+/// then the ones containing it, each in the order of `history` before `library`.
+///
+/// Duplicates and entries equal to the query are left out.
+fn match_offline<'a>(
+    query: &str,
+    history: impl Iterator<Item = &'a String> + Clone,
+    library: impl Iterator<Item = &'a String> + Clone,
+) -> Vec<String> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return history.take(MAX_SUGGESTIONS).cloned().collect();
+    }
+
+    let candidates = history.chain(library);
+    let prefix = candidates
+        .clone()
+        .filter(|v| v.to_lowercase().starts_with(&query));
+    let contains = candidates.filter(|v| {
+        let lower = v.to_lowercase();
+        !lower.starts_with(&query) && lower.contains(&query)
+    });
+
+    let mut res: Vec<String> = Vec::new();
+    for entry in prefix.chain(contains) {
+        if res.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+        let lower = entry.to_lowercase();
+        if lower == query || res.iter().any(|v| v.to_lowercase() == lower) {
+            continue;
+        }
+        res.push(entry.clone());
+    }
+
+    res
+}
+
+/// Parse the opensearch suggestions format `["query", ["suggestion", ...]]`.
+fn parse_opensearch(data: &str) -> Result<Vec<String>> {
+    let value: Value = serde_json::from_str(data).context("parse suggestions")?;
+    let Some(list) = value.get(1).and_then(Value::as_array) else {
+        bail!("unexpected suggestions format");
+    };
+
+    Ok(list
+        .iter()
+        .filter_map(Value::as_str)
+        .take(MAX_SUGGESTIONS)
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Parse the invidious suggestions format `{"query": "query", "suggestions": ["suggestion", ...]}`.
+fn parse_invidious(data: &str) -> Result<Vec<String>> {
+    let value: Value = serde_json::from_str(data).context("parse suggestions")?;
+    let Some(list) = value.get("suggestions").and_then(Value::as_array) else {
+        bail!("unexpected suggestions format");
+    };
+
+    // invidious returns the suggestions html-escaped
+    Ok(list
+        .iter()
+        .filter_map(Value::as_str)
+        .take(MAX_SUGGESTIONS)
+        .map(|v| {
+            v.replace("&#39;", "'")
+                .replace("&quot;", "\"")
+                .replace("&amp;", "&")
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{match_offline, parse_invidious, parse_opensearch};
+
+    #[test]
+    fn should_parse_opensearch() {
+        let res =
+            parse_opensearch(r#"["lofi",["lofi hip hop","lofi girl",3],{"extra":true}]"#).unwrap();
+        assert_eq!(res, ["lofi hip hop", "lofi girl"]);
+
+        assert!(parse_opensearch(r#"{"suggestions":[]}"#).is_err());
+    }
+
+    #[test]
+    fn should_parse_invidious() {
+        let res = parse_invidious(
+            r#"{"query":"rock","suggestions":["rock &amp; roll","guns n&#39; roses"]}"#,
+        )
+        .unwrap();
+        assert_eq!(res, ["rock & roll", "guns n' roses"]);
+    }
+
+    #[test]
+    fn should_match_offline_prefix_first() {
+        let history = ["the beatles".to_string(), "Best of Queen".to_string()];
+        let library = [
+            "Queen".to_string(),
+            "Bee Gees".to_string(),
+            "The Beatles".to_string(),
+            "ABBA".to_string(),
+        ];
+
+        let res = match_offline("be", history.iter().rev(), library.iter());
+        // newest history first, then the library; "The Beatles" is a duplicate of the history
+        assert_eq!(res, ["Best of Queen", "Bee Gees", "the beatles"]);
+
+        // the exact query is not suggested
+        let res = match_offline("queen", history.iter().rev(), library.iter());
+        assert_eq!(res, ["Best of Queen"]);
+
+        // a empty query suggests the history
+        let res = match_offline(" ", history.iter().rev(), library.iter());
+        assert_eq!(res, ["Best of Queen", "the beatles"]);
+    }
+}
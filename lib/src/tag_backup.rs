@@ -0,0 +1,592 @@
+//! Rolling backups of library files, made before their tags get written or they get renamed, so that bad edits can be
+//! undone with `termusic tags restore`.
+//!
+//! Every backup is a directory named by the time it was made, holding a `manifest.json` describing what got backed up
+//! and the backed up data itself.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use lofty::config::WriteOptions;
+use lofty::file::TaggedFileExt;
+use lofty::picture::{MimeType, Picture, PictureType};
+use lofty::tag::{ItemKey, ItemValue, Tag, TagExt, TagItem, TagType};
+use serde::{Deserialize, Serialize};
+
+use crate::config::v2::tui::{TagBackupMode, TagBackups};
+use crate::file_ops::move_path;
+
+/// The file name of the [`Manifest`] in a backup directory.
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Get the default backup directory, `termusic/tag_backups` in the data directory.
+pub fn default_backup_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().context("failed to find the data directory")?;
+
+    Ok(data_dir.join("termusic").join("tag_backups"))
+}
+
+/// Description of a single backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// When the backup was made, as RFC3339
+    pub created: String,
+    /// What was about to happen, like "Tag editor"
+    pub description: String,
+    pub entries: Vec<BackupEntry>,
+}
+
+/// A single backed up file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BackupEntry {
+    /// The tags of the file at `path`
+    Tags { path: PathBuf, tags: Vec<SavedTag> },
+    /// A copy of the whole file at `path`, stored as `copy` in the backup directory
+    File { path: PathBuf, copy: String },
+    /// The file or directory at `from` got renamed to `to`
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+impl BackupEntry {
+    /// Get the path the entry currently is expected at.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Tags { path, .. } | Self::File { path, .. } => path,
+            Self::Rename { to, .. } => to,
+        }
+    }
+}
+
+/// A tag of a file, with only the text items and the pictures.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedTag {
+    tag_type: String,
+    items: Vec<SavedItem>,
+    pictures: Vec<SavedPicture>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SavedItem {
+    /// The key as named in the tag type, like `TIT2` for ID3v2
+    key: String,
+    value: String,
+    /// Whether `value` is a [`ItemValue::Locator`] instead of [`ItemValue::Text`]
+    #[serde(default)]
+    locator: bool,
+    lang: [u8; 3],
+    description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SavedPicture {
+    pic_type: u8,
+    mime_type: Option<String>,
+    description: Option<String>,
+    /// The file name of the picture data in the backup directory
+    file: String,
+}
+
+impl SavedTag {
+    /// Convert `tag`, storing its pictures as `<name>-<index>.bin` in `dir`.
+    fn from_tag(tag: &Tag, dir: &Path, name: &str) -> Result<Self> {
+        let tag_type = tag.tag_type();
+        let items = tag
+            .items()
+            .filter_map(|item| {
+                let key = item.key().map_key(tag_type, true)?;
+                let (value, locator) = match item.value() {
+                    ItemValue::Text(v) => (v.clone(), false),
+                    ItemValue::Locator(v) => (v.clone(), true),
+                    ItemValue::Binary(_) => return None,
+                };
+
+                Some(SavedItem {
+                    key: key.to_string(),
+                    value,
+                    locator,
+                    lang: *item.lang(),
+                    description: item.description().to_string(),
+                })
+            })
+            .collect();
+
+        let mut pictures = Vec::new();
+        for (idx, picture) in tag.pictures().iter().enumerate() {
+            let file = format!("{name}-{idx}.bin");
+            let path = dir.join(&file);
+            fs::write(&path, picture.data()).with_context(|| path.display().to_string())?;
+
+            pictures.push(SavedPicture {
+                pic_type: picture.pic_type().as_u8(),
+                mime_type: picture.mime_type().map(|v| v.as_str().to_string()),
+                description: picture.description().map(ToString::to_string),
+                file,
+            });
+        }
+
+        Ok(Self {
+            tag_type: tag_type_name(tag_type).to_string(),
+            items,
+            pictures,
+        })
+    }
+
+    /// Convert back to a [`Tag`], reading the pictures from `dir`.
+    fn to_tag(&self, dir: &Path) -> Result<Tag> {
+        let tag_type = tag_type_from_name(&self.tag_type)?;
+        let mut tag = Tag::new(tag_type);
+
+        for item in &self.items {
+            let value = if item.locator {
+                ItemValue::Locator(item.value.clone())
+            } else {
+                ItemValue::Text(item.value.clone())
+            };
+            let mut tag_item = TagItem::new(ItemKey::from_key(tag_type, &item.key), value);
+            tag_item.set_lang(item.lang);
+            tag_item.set_description(item.description.clone());
+            tag.push(tag_item);
+        }
+
+        for picture in &self.pictures {
+            let path = dir.join(&picture.file);
+            let data = fs::read(&path).with_context(|| path.display().to_string())?;
+            tag.push_picture(Picture::new_unchecked(
+                PictureType::from_u8(picture.pic_type),
+                picture.mime_type.as_deref().map(MimeType::from_str),
+                picture.description.clone(),
+                data,
+            ));
+        }
+
+        Ok(tag)
+    }
+}
+
+/// Get the name a [`TagType`] is stored as in a [`Manifest`].
+fn tag_type_name(tag_type: TagType) -> &'static str {
+    match tag_type {
+        TagType::Ape => "ape",
+        TagType::Id3v1 => "id3v1",
+        TagType::Id3v2 => "id3v2",
+        TagType::Mp4Ilst => "mp4ilst",
+        TagType::VorbisComments => "vorbiscomments",
+        TagType::RiffInfo => "riffinfo",
+        TagType::AiffText => "aifftext",
+        _ => "unknown",
+    }
+}
+
+/// Get the [`TagType`] stored as `name`, see [`tag_type_name`].
+fn tag_type_from_name(name: &str) -> Result<TagType> {
+    Ok(match name {
+        "ape" => TagType::Ape,
+        "id3v1" => TagType::Id3v1,
+        "id3v2" => TagType::Id3v2,
+        "mp4ilst" => TagType::Mp4Ilst,
+        "vorbiscomments" => TagType::VorbisComments,
+        "riffinfo" => TagType::RiffInfo,
+        "aifftext" => TagType::AiffText,
+        _ => bail!("Unknown tag type \"{name}\""),
+    })
+}
+
+/// A backup as listed by [`BackupStore::list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    /// The name of the backup directory, to be given to [`BackupStore::restore`]
+    pub id: String,
+    pub manifest: Manifest,
+}
+
+/// The result of [`BackupStore::restore`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    /// The amount of entries that got restored
+    pub restored: usize,
+    /// The errors of the entries that could not be restored, prefixed by their path
+    pub errors: Vec<String>,
+}
+
+/// The backups in a directory, keeping only the newest ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupStore {
+    dir: PathBuf,
+    mode: TagBackupMode,
+    keep: usize,
+}
+
+impl BackupStore {
+    #[must_use]
+    pub fn new(dir: PathBuf, mode: TagBackupMode, keep: usize) -> Self {
+        Self { dir, mode, keep }
+    }
+
+    /// Create a store in the configured directory, or in [`default_backup_dir`] if not configured.
+    pub fn from_settings(settings: &TagBackups) -> Result<Self> {
+        let dir = match &settings.dir {
+            Some(dir) => shellexpand::path::tilde(dir).into_owned(),
+            None => default_backup_dir()?,
+        };
+
+        Ok(Self::new(dir, settings.mode, settings.keep))
+    }
+
+    /// Get whether anything gets backed up.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.mode != TagBackupMode::Off
+    }
+
+    /// Back up the files at `paths` before their tags get written.
+    ///
+    /// Returns the id of the backup, or [`None`] if backups are disabled.
+    pub fn backup_tags<P: AsRef<Path>>(
+        &self,
+        description: &str,
+        paths: &[P],
+    ) -> Result<Option<String>> {
+        if !self.is_enabled() || paths.is_empty() {
+            return Ok(None);
+        }
+
+        self.create(description, |dir| {
+            paths
+                .iter()
+                .enumerate()
+                .map(|(idx, path)| self.backup_file(dir, idx, path.as_ref()))
+                .collect()
+        })
+        .map(Some)
+    }
+
+    /// Record that the file or directory at `from` is about to be renamed to `to`.
+    ///
+    /// Returns the id of the backup, or [`None`] if backups are disabled.
+    pub fn backup_rename(
+        &self,
+        description: &str,
+        from: &Path,
+        to: &Path,
+    ) -> Result<Option<String>> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+
+        self.create(description, |_| {
+            Ok(vec![BackupEntry::Rename {
+                from: from.to_path_buf(),
+                to: to.to_path_buf(),
+            }])
+        })
+        .map(Some)
+    }
+
+    /// Back up a single file into `dir`, as configured by the mode.
+    fn backup_file(&self, dir: &Path, idx: usize, path: &Path) -> Result<BackupEntry> {
+        if self.mode == TagBackupMode::File {
+            let copy = match path.extension() {
+                Some(ext) => format!("{idx}.{}", ext.to_string_lossy()),
+                None => idx.to_string(),
+            };
+            fs::copy(path, dir.join(&copy)).with_context(|| path.display().to_string())?;
+
+            return Ok(BackupEntry::File {
+                path: path.to_path_buf(),
+                copy,
+            });
+        }
+
+        let tagged_file = lofty::read_from_path(path)
+            .with_context(|| format!("read tags of {}", path.display()))?;
+        let tags = tagged_file
+            .tags()
+            .iter()
+            .enumerate()
+            .map(|(tag_idx, tag)| SavedTag::from_tag(tag, dir, &format!("{idx}-{tag_idx}")))
+            .collect::<Result<_>>()?;
+
+        Ok(BackupEntry::Tags {
+            path: path.to_path_buf(),
+            tags,
+        })
+    }
+
+    /// Create a new backup directory, fill it with `backup` and remove the oldest backups.
+    ///
+    /// The directory is removed again if `backup` fails.
+    fn create(
+        &self,
+        description: &str,
+        backup: impl FnOnce(&Path) -> Result<Vec<BackupEntry>>,
+    ) -> Result<String> {
+        fs::create_dir_all(&self.dir).with_context(|| self.dir.display().to_string())?;
+
+        let now = chrono::Utc::now();
+        let base = now.format("%Y%m%d-%H%M%S%.3f").to_string();
+        let mut counter = 0;
+        let (id, dir) = loop {
+            let id = if counter == 0 {
+                base.clone()
+            } else {
+                format!("{base}-{counter:03}")
+            };
+            let dir = self.dir.join(&id);
+            match fs::create_dir(&dir) {
+                Ok(()) => break (id, dir),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => counter += 1,
+                Err(err) => return Err(err).with_context(|| dir.display().to_string()),
+            }
+        };
+
+        let res = backup(&dir).and_then(|entries| {
+            let manifest = Manifest {
+                created: now.to_rfc3339(),
+                description: description.to_string(),
+                entries,
+            };
+            let path = dir.join(MANIFEST_NAME);
+            fs::write(&path, serde_json::to_vec_pretty(&manifest)?)
+                .with_context(|| path.display().to_string())
+        });
+        if let Err(err) = res {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(err.context("backup"));
+        }
+
+        if let Err(err) = self.prune() {
+            warn!("Error removing old tag backups: {err:#}");
+        }
+
+        Ok(id)
+    }
+
+    /// Get the ids of all backups, oldest first.
+    fn ids(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(v) => v,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err).with_context(|| self.dir.display().to_string()),
+        };
+
+        let mut ids: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter(|v| v.path().join(MANIFEST_NAME).is_file())
+            .filter_map(|v| v.file_name().into_string().ok())
+            .collect();
+        ids.sort_unstable();
+
+        Ok(ids)
+    }
+
+    /// Remove the oldest backups, keeping at least the newest one.
+    fn prune(&self) -> Result<()> {
+        let ids = self.ids()?;
+        let remove = ids.len().saturating_sub(self.keep.max(1));
+        for id in &ids[..remove] {
+            let dir = self.dir.join(id);
+            fs::remove_dir_all(&dir).with_context(|| dir.display().to_string())?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the directory of the backup `id`.
+    fn backup_dir(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) {
+            bail!("Invalid backup id \"{id}\"");
+        }
+
+        Ok(self.dir.join(id))
+    }
+
+    /// Read the [`Manifest`] of the backup `id`.
+    pub fn get(&self, id: &str) -> Result<Manifest> {
+        let path = self.backup_dir(id)?.join(MANIFEST_NAME);
+        let content = match fs::read(&path) {
+            Ok(v) => v,
+            Err(err) if err.kind() == ErrorKind::NotFound => bail!("No backup \"{id}\""),
+            Err(err) => return Err(err).with_context(|| path.display().to_string()),
+        };
+
+        serde_json::from_slice(&content).with_context(|| path.display().to_string())
+    }
+
+    /// Get all backups, newest first.
+    pub fn list(&self) -> Result<Vec<BackupInfo>> {
+        let mut ids = self.ids()?;
+        ids.reverse();
+
+        ids.into_iter()
+            .map(|id| {
+                let manifest = self.get(&id)?;
+                Ok(BackupInfo { id, manifest })
+            })
+            .collect()
+    }
+
+    /// Get the id of the newest backup, if there is one.
+    pub fn latest(&self) -> Result<Option<String>> {
+        Ok(self.ids()?.pop())
+    }
+
+    /// Restore all files of the backup `id`, undoing the entries from last to first.
+    ///
+    /// Entries that fail do not stop the others from being restored, see [`RestoreReport::errors`].
+    pub fn restore(&self, id: &str) -> Result<RestoreReport> {
+        let manifest = self.get(id)?;
+        let dir = self.backup_dir(id)?;
+
+        let mut report = RestoreReport::default();
+        for entry in manifest.entries.iter().rev() {
+            match restore_entry(&dir, entry) {
+                Ok(()) => report.restored += 1,
+                Err(err) => report
+                    .errors
+                    .push(format!("{}: {err:#}", entry.path().display())),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Restore a single `entry` of the backup in `dir`.
+fn restore_entry(dir: &Path, entry: &BackupEntry) -> Result<()> {
+    match entry {
+        BackupEntry::Tags { path, tags } => {
+            let tagged_file = lofty::read_from_path(path).context("read tags")?;
+            // remove all current tags, as the edit might have added a tag of a new type
+            for tag in tagged_file.tags() {
+                tag.tag_type()
+                    .remove_from_path(path)
+                    .context("remove tag")?;
+            }
+            for saved in tags {
+                saved
+                    .to_tag(dir)?
+                    .save_to_path(path, WriteOptions::new())
+                    .context("write tag")?;
+            }
+        }
+        BackupEntry::File { path, copy } => {
+            fs::copy(dir.join(copy), path).context("copy file")?;
+        }
+        BackupEntry::Rename { from, to } => move_path(to, from)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use lofty::picture::{MimeType, Picture, PictureType};
+    use lofty::tag::{Accessor, Tag, TagType};
+    use pretty_assertions::assert_eq;
+
+    use super::{BackupStore, SavedTag};
+    use crate::config::v2::tui::TagBackupMode;
+
+    /// Get a new empty directory for test `name`.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-tag-backup-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn should_backup_and_restore_file_copies() {
+        let dir = test_dir("file");
+        let track = dir.join("track.flac");
+        fs::write(&track, "original").unwrap();
+
+        let store = BackupStore::new(dir.join("backups"), TagBackupMode::File, 5);
+        let id = store.backup_tags("Tag editor", &[&track]).unwrap().unwrap();
+        fs::write(&track, "changed").unwrap();
+
+        let report = store.restore(&id).unwrap();
+        assert_eq!(report.restored, 1);
+        assert!(report.errors.is_empty());
+        assert_eq!(fs::read_to_string(&track).unwrap(), "original");
+
+        assert!(store.restore("../track.flac").is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_undo_renames_and_keep_the_newest() {
+        let dir = test_dir("rename");
+        let store = BackupStore::new(dir.join("backups"), TagBackupMode::Tags, 2);
+
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c"] {
+            let from = dir.join(name);
+            let to = dir.join(format!("{name}-renamed"));
+            fs::write(&from, name).unwrap();
+            ids.push(store.backup_rename("Rename", &from, &to).unwrap().unwrap());
+            fs::rename(&from, &to).unwrap();
+        }
+
+        let listed: Vec<String> = store.list().unwrap().into_iter().map(|v| v.id).collect();
+        assert_eq!(listed, vec![ids[2].clone(), ids[1].clone()]);
+        assert_eq!(store.latest().unwrap(), Some(ids[2].clone()));
+
+        let report = store.restore(&ids[2]).unwrap();
+        assert_eq!(report.restored, 1);
+        assert!(dir.join("c").is_file());
+        assert!(!dir.join("c-renamed").exists());
+
+        // already restored, so the renamed file is gone
+        let report = store.restore(&ids[2]).unwrap();
+        assert_eq!(report.restored, 0);
+        assert_eq!(report.errors.len(), 1);
+
+        let off = BackupStore::new(dir.join("backups"), TagBackupMode::Off, 2);
+        assert_eq!(
+            off.backup_tags("Tag editor", &[dir.join("c")]).unwrap(),
+            None
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_convert_tags() {
+        let dir = test_dir("tags");
+
+        let mut tag = Tag::new(TagType::VorbisComments);
+        tag.set_title("Some Title".to_string());
+        tag.set_artist("Some Artist".to_string());
+        tag.push_picture(Picture::new_unchecked(
+            PictureType::CoverFront,
+            Some(MimeType::Png),
+            None,
+            vec![1, 2, 3],
+        ));
+
+        let saved = SavedTag::from_tag(&tag, &dir, "0-0").unwrap();
+        let restored = saved.to_tag(&dir).unwrap();
+
+        assert_eq!(restored.tag_type(), TagType::VorbisComments);
+        assert_eq!(restored.title().as_deref(), Some("Some Title"));
+        assert_eq!(restored.artist().as_deref(), Some("Some Artist"));
+        assert_eq!(restored.pictures().len(), 1);
+        assert_eq!(restored.pictures()[0].data(), &[1, 2, 3]);
+        assert_eq!(restored.pictures()[0].pic_type(), PictureType::CoverFront);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
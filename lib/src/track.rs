@@ -1,10 +1,8 @@
 use std::{
     borrow::Cow,
-    cell::RefCell,
     fmt::Display,
     fs::File,
     io::BufReader,
-    num::NonZeroUsize,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
@@ -20,9 +18,9 @@ use lofty::{
     probe::Probe,
     tag::{Accessor, ItemKey, ItemValue, Tag as LoftyTag},
 };
-use lru::LruCache;
 
 use crate::{
+    cache::CACHES, config::v2::server::NormalizationMode,
     player::playlist_helpers::PlaylistTrackSource, podcast::episode::Episode, songtag::lrc::Lyric,
     utils::SplitArrayIter,
 };
@@ -33,6 +31,7 @@ pub enum MediaTypesSimple {
     Music,
     Podcast,
     LiveRadio,
+    Video,
 }
 
 #[derive(Debug, Clone)]
@@ -110,12 +109,42 @@ impl RadioTrackData {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoTrackData {
+    /// The video page url, used as the sole identifier for equality
+    url: String,
+}
+
+impl VideoTrackData {
+    /// Get the url of the video page
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Create new [`VideoTrackData`] with only the url.
+    ///
+    /// This should mainly be used for tests only.
+    #[must_use]
+    pub fn new(url: String) -> Self {
+        Self { url }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TrackData {
     /// The Track file path, used as the sole identifier for equality
     path: PathBuf,
 
     album: Option<String>,
+    /// The classical work and the track's movement in it, if tagged
+    work: Option<String>,
+    movement: Option<String>,
+    movement_number: Option<u32>,
+    /// The gain in dB to normalize the loudness, see [`normalization_gain`]
+    normalization_gain: Option<f32>,
+    /// The gain in dB to normalize the loudness of the whole album
+    album_normalization_gain: Option<f32>,
 
     file_type: Option<FileType>,
 }
@@ -138,6 +167,46 @@ impl TrackData {
         self.album.as_deref()
     }
 
+    /// Get a title like `Symphony No. 5: II. Andante con moto`, if the track is tagged as a movement of a work.
+    ///
+    /// The movement name or number is optional, but the work is required.
+    #[must_use]
+    pub fn movement_title(&self) -> Option<String> {
+        let work = self.work.as_deref().filter(|v| !v.is_empty())?;
+        let movement = self.movement.as_deref().filter(|v| !v.is_empty());
+
+        let title = match (self.movement_number, movement) {
+            (Some(number), Some(movement)) => {
+                format!("{work}: {}. {movement}", RomanNumeral(number))
+            }
+            (Some(number), None) => format!("{work}: {}.", RomanNumeral(number)),
+            (None, Some(movement)) => format!("{work}: {movement}"),
+            (None, None) => return None,
+        };
+
+        Some(title)
+    }
+
+    /// The gain in dB to apply to normalize the loudness of the track, if it has `ReplayGain` or R128 tags.
+    #[must_use]
+    pub fn normalization_gain(&self) -> Option<f32> {
+        self.normalization_gain
+    }
+
+    /// The gain in dB to apply in `mode`, the album gain falling back to the track gain if the album is not tagged.
+    #[must_use]
+    pub fn normalization_gain_for(&self, mode: NormalizationMode) -> Option<f32> {
+        match mode {
+            NormalizationMode::Track => self.normalization_gain,
+            NormalizationMode::Album => self.album_normalization_gain.or(self.normalization_gain),
+        }
+    }
+
+    /// Set the track gain, like the one of a analyzed loudness, if the track is not already tagged with one.
+    pub fn set_fallback_normalization_gain(&mut self, gain: f32) {
+        self.normalization_gain.get_or_insert(gain);
+    }
+
     /// The lofty File-Type; may not exist if lofty could not parse the file.
     ///
     /// Note that if lofty cannot parse the file, that **does not** mean that symphonia cannot play it.
@@ -154,16 +223,216 @@ impl TrackData {
         Self {
             path,
             album: None,
+            work: None,
+            movement: None,
+            movement_number: None,
+            normalization_gain: None,
+            album_normalization_gain: None,
             file_type: None,
         }
     }
 }
 
+/// All kinds of sources a [`Track`] can be from.
+///
+/// Code that does not care about the specific kind should use [`MediaTypes::source`] instead of matching.
 #[derive(Debug, Clone, PartialEq)]
 pub enum MediaTypes {
     Track(TrackData),
     Radio(RadioTrackData),
     Podcast(PodcastTrackData),
+    Video(VideoTrackData),
+}
+
+impl MediaTypes {
+    /// Get the uniform interface of the source.
+    #[must_use]
+    pub fn source(&self) -> &dyn TrackSource {
+        match self {
+            MediaTypes::Track(track_data) => track_data,
+            MediaTypes::Radio(radio_track_data) => radio_track_data,
+            MediaTypes::Podcast(podcast_track_data) => podcast_track_data,
+            MediaTypes::Video(video_track_data) => video_track_data,
+        }
+    }
+}
+
+/// Where the audio of a [`TrackSource`] can be acquired from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamLocation<'a> {
+    /// A local file
+    File(&'a Path),
+    /// A remote stream, over http(s)
+    Url(&'a str),
+    /// A video page, the audio stream has to be extracted from with [`extract_audio`](crate::ytdlp::extract_audio)
+    Extract(&'a str),
+}
+
+/// The uniform interface of a source, like a local file, a radio station or a podcast episode.
+///
+/// A new kind of source implements this and adds a variant to [`MediaTypes`];
+/// the playlist, backends and UI only use this interface where they do not need the specific kind.
+///
+/// Title, artist and duration are common to all sources and stored in the [`Track`] itself.
+pub trait TrackSource: std::fmt::Debug + Send + Sync {
+    /// Get a Enum without values to check against types.
+    fn media_type(&self) -> MediaTypesSimple;
+
+    /// The main identifier, a path or url, as stored in playlists and used to find the track again.
+    fn location(&self) -> Cow<'_, str>;
+
+    /// Where to acquire the audio from for playback.
+    ///
+    /// This may differ from the [`location`](Self::location), like a podcast episode that has been downloaded.
+    fn stream(&self) -> StreamLocation<'_>;
+
+    /// Whether this is a live stream, which has no fixed duration and may change its metadata while playing.
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    /// Get the album, if the source has one.
+    fn album(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get a display-able identifier.
+    fn id_str(&self) -> Cow<'_, str> {
+        self.location()
+    }
+
+    /// Create a [`PlaylistTrackSource`] from the identifier for GRPC.
+    fn playlist_source(&self) -> PlaylistTrackSource;
+
+    /// Get a cover / picture, returns `Ok(None)` if the source has none, or it is not implemented for it.
+    ///
+    /// # Errors
+    ///
+    /// If getting the picture failed, see [`Track::get_picture`].
+    fn picture(&self) -> Result<Option<Arc<Picture>>> {
+        Ok(None)
+    }
+}
+
+impl TrackSource for TrackData {
+    fn media_type(&self) -> MediaTypesSimple {
+        MediaTypesSimple::Music
+    }
+
+    fn location(&self) -> Cow<'_, str> {
+        self.path.to_string_lossy()
+    }
+
+    fn stream(&self) -> StreamLocation<'_> {
+        StreamLocation::File(&self.path)
+    }
+
+    fn album(&self) -> Option<&str> {
+        self.album()
+    }
+
+    fn id_str(&self) -> Cow<'_, str> {
+        // A music track will always have a file_name (and not terminate in ".."), but dont panic if not
+        self.path
+            .file_name()
+            .map_or_else(|| self.location(), |v| v.to_string_lossy())
+    }
+
+    fn playlist_source(&self) -> PlaylistTrackSource {
+        PlaylistTrackSource::Path(self.location().into_owned())
+    }
+
+    fn picture(&self) -> Result<Option<Arc<Picture>>> {
+        let path_key = self.path().to_owned();
+
+        // TODO: option to disable getting with folder cover for tag editor?
+        let res = CACHES.picture.try_get_or_insert(path_key, || {
+            let picture = get_picture_for_music_track(self.path()).map_err(Some)?;
+
+            let Some(picture) = picture else {
+                return Err(None);
+            };
+
+            Ok(Arc::new(picture))
+        });
+
+        // this has to be done as ByteLru::try_get_or_insert enforces that the Ok result is the value itself, no mapping can be done.
+        match res {
+            Ok(v) => Ok(Some(v)),
+            Err(None) => Ok(None),
+            Err(Some(err)) => Err(err),
+        }
+    }
+}
+
+impl TrackSource for RadioTrackData {
+    fn media_type(&self) -> MediaTypesSimple {
+        MediaTypesSimple::LiveRadio
+    }
+
+    fn location(&self) -> Cow<'_, str> {
+        self.url.as_str().into()
+    }
+
+    fn stream(&self) -> StreamLocation<'_> {
+        StreamLocation::Url(&self.url)
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    fn playlist_source(&self) -> PlaylistTrackSource {
+        PlaylistTrackSource::Url(self.url.clone())
+    }
+
+    fn picture(&self) -> Result<Option<Arc<Picture>>> {
+        trace!("Unimplemented: radio picture");
+        Ok(None)
+    }
+}
+
+impl TrackSource for PodcastTrackData {
+    fn media_type(&self) -> MediaTypesSimple {
+        MediaTypesSimple::Podcast
+    }
+
+    fn location(&self) -> Cow<'_, str> {
+        self.url.as_str().into()
+    }
+
+    fn stream(&self) -> StreamLocation<'_> {
+        self.localfile
+            .as_deref()
+            .map_or(StreamLocation::Url(&self.url), StreamLocation::File)
+    }
+
+    fn playlist_source(&self) -> PlaylistTrackSource {
+        PlaylistTrackSource::PodcastUrl(self.url.clone())
+    }
+
+    fn picture(&self) -> Result<Option<Arc<Picture>>> {
+        trace!("Unimplemented: podcast picture");
+        Ok(None)
+    }
+}
+
+impl TrackSource for VideoTrackData {
+    fn media_type(&self) -> MediaTypesSimple {
+        MediaTypesSimple::Video
+    }
+
+    fn location(&self) -> Cow<'_, str> {
+        self.url.as_str().into()
+    }
+
+    fn stream(&self) -> StreamLocation<'_> {
+        StreamLocation::Extract(&self.url)
+    }
+
+    fn playlist_source(&self) -> PlaylistTrackSource {
+        PlaylistTrackSource::VideoUrl(self.url.clone())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -172,15 +441,6 @@ pub struct LyricData {
     pub parsed_lyrics: Option<Lyric>,
 }
 
-type PictureCache = LruCache<PathBuf, Arc<Picture>>;
-type LyricCache = LruCache<PathBuf, Arc<LyricData>>;
-
-// NOTE: thread_locals are like "LazyLock"s, they only get initialized on first access.
-std::thread_local! {
-    static PICTURE_CACHE: RefCell<PictureCache> = RefCell::new(PictureCache::new(NonZeroUsize::new(5).unwrap()));
-    static LYRIC_CACHE: RefCell<LyricCache> = RefCell::new(LyricCache::new(NonZeroUsize::new(5).unwrap()));
-}
-
 #[derive(Debug, Clone)]
 pub struct Track {
     inner: MediaTypes,
@@ -188,6 +448,9 @@ pub struct Track {
     duration: Option<Duration>,
     title: Option<String>,
     artist: Option<String>,
+
+    /// Why the last attempt to play this track failed, cleared once it plays
+    error: Option<String>,
 }
 
 impl PartialEq for Track {
@@ -221,6 +484,7 @@ impl Track {
             duration,
             title: Some(ep.title.clone()),
             artist: None,
+            error: None,
         }
     }
 
@@ -235,6 +499,22 @@ impl Track {
             // will be fetched later, maybe consider storing a cache in the database?
             title: None,
             artist: None,
+            error: None,
+        }
+    }
+
+    /// Create a new Track from a video page url, streamed without downloading it
+    #[must_use]
+    pub fn new_video<U: Into<String>>(url: U) -> Self {
+        let video_data = VideoTrackData { url: url.into() };
+
+        Self {
+            inner: MediaTypes::Video(video_data),
+            duration: None,
+            // only known once the stream is extracted for playback
+            title: None,
+            artist: None,
+            error: None,
         }
     }
 
@@ -254,6 +534,8 @@ impl Track {
                 artist: true,
                 title: true,
                 duration: true,
+                work: true,
+                replay_gain: true,
                 ..Default::default()
             },
         ) {
@@ -272,6 +554,15 @@ impl Track {
         let track_data = TrackData {
             path,
             album: metadata.album,
+            work: metadata.work,
+            movement: metadata.movement,
+            movement_number: metadata.movement_number,
+            normalization_gain: metadata
+                .replay_gain
+                .map(|gain| normalization_gain(gain, metadata.replay_peak)),
+            album_normalization_gain: metadata
+                .replay_album_gain
+                .map(|gain| normalization_gain(gain, metadata.replay_album_peak)),
             file_type: metadata.file_type,
         };
 
@@ -280,6 +571,7 @@ impl Track {
             duration: metadata.duration,
             title: metadata.title,
             artist: metadata.artist,
+            error: None,
         })
     }
 
@@ -298,6 +590,27 @@ impl Track {
         self.duration
     }
 
+    /// Get why the last attempt to play this track failed, if it did.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Mark this track as failed to play with the reason, or clear it with `None`.
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    /// Fill in the `title` and `duration` where they are not known, like for a video whose info the client fetched.
+    pub fn fill_info(&mut self, title: Option<String>, duration: Option<Duration>) {
+        if self.title.is_none() {
+            self.title = title;
+        }
+        if self.duration.is_none() {
+            self.duration = duration;
+        }
+    }
+
     /// Format the Track's duration to a short-form.
     ///
     /// see [`DurationFmtShort`] for formatting.
@@ -317,6 +630,7 @@ impl Track {
             MediaTypes::Track(_track_data) => None,
             MediaTypes::Radio(radio_track_data) => Some(radio_track_data.url()),
             MediaTypes::Podcast(podcast_track_data) => Some(podcast_track_data.url()),
+            MediaTypes::Video(video_track_data) => Some(video_track_data.url()),
         }
     }
 
@@ -341,6 +655,15 @@ impl Track {
         }
     }
 
+    #[must_use]
+    pub fn as_track_mut(&mut self) -> Option<&mut TrackData> {
+        if let MediaTypes::Track(track_data) = &mut self.inner {
+            Some(track_data)
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     pub fn as_radio(&self) -> Option<&RadioTrackData> {
         if let MediaTypes::Radio(radio_data) = &self.inner {
@@ -359,37 +682,38 @@ impl Track {
         }
     }
 
+    #[must_use]
+    pub fn as_video(&self) -> Option<&VideoTrackData> {
+        if let MediaTypes::Video(video_data) = &self.inner {
+            Some(video_data)
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     pub fn inner(&self) -> &MediaTypes {
         &self.inner
     }
 
+    /// Get the uniform interface of the track's source, see [`TrackSource`].
+    #[must_use]
+    pub fn source(&self) -> &dyn TrackSource {
+        self.inner.source()
+    }
+
     /// Get a Enum without values to check against types.
     ///
     /// Mainly for not having to change too many functions yet.
     #[must_use]
     pub fn media_type(&self) -> MediaTypesSimple {
-        match &self.inner {
-            MediaTypes::Track(_) => MediaTypesSimple::Music,
-            MediaTypes::Radio(_) => MediaTypesSimple::LiveRadio,
-            MediaTypes::Podcast(_) => MediaTypesSimple::Podcast,
-        }
+        self.source().media_type()
     }
 
     /// Create a [`PlaylistTrackSource`] from the current track identifier for GRPC.
     #[must_use]
     pub fn as_track_source(&self) -> PlaylistTrackSource {
-        match &self.inner {
-            MediaTypes::Track(track_data) => {
-                PlaylistTrackSource::Path(track_data.path.to_string_lossy().to_string())
-            }
-            MediaTypes::Radio(radio_track_data) => {
-                PlaylistTrackSource::Url(radio_track_data.url.clone())
-            }
-            MediaTypes::Podcast(podcast_track_data) => {
-                PlaylistTrackSource::PodcastUrl(podcast_track_data.url.clone())
-            }
-        }
+        self.source().playlist_source()
     }
 
     /// Get a cover / picture for the current track.
@@ -407,57 +731,13 @@ impl Track {
     /// - reading the file fails
     /// - parsing the file as a picture fails
     pub fn get_picture(&self) -> Result<Option<Arc<Picture>>> {
-        match &self.inner {
-            MediaTypes::Track(track_data) => {
-                let path_key = track_data.path().to_owned();
-
-                // TODO: option to disable getting with folder cover for tag editor?
-                let res = PICTURE_CACHE.with_borrow_mut(|cache| {
-                    cache
-                        .try_get_or_insert(path_key, || {
-                            let picture =
-                                get_picture_for_music_track(track_data.path()).map_err(Some)?;
-
-                            let Some(picture) = picture else {
-                                return Err(None);
-                            };
-
-                            Ok(Arc::new(picture))
-                        })
-                        .cloned()
-                });
-
-                // this has to be done as LruCache::try_get_or_insert enforces that the Ok result is the value itself, no mapping can be done.
-                match res {
-                    Ok(v) => return Ok(Some(v)),
-                    Err(None) => return Ok(None),
-                    Err(Some(err)) => return Err(err),
-                }
-            }
-            MediaTypes::Radio(_radio_track_data) => trace!("Unimplemented: radio picture"),
-            MediaTypes::Podcast(_podcast_track_data) => trace!("Unimplemented: podcast picture"),
-        }
-
-        Ok(None)
+        self.source().picture()
     }
 
     /// Get a display-able identifier
-    ///
-    /// # Panics
-    ///
-    /// If somehow a [`MediaTypes::Track`] does not have a `file_name`.
     #[must_use]
     pub fn id_str(&self) -> Cow<'_, str> {
-        match &self.inner {
-            // A music track will always have a file_name (and not terminate in "..")
-            MediaTypes::Track(track_data) => track_data
-                .path()
-                .file_name()
-                .map(|v| v.to_string_lossy())
-                .unwrap(),
-            MediaTypes::Radio(radio_track_data) => radio_track_data.url().into(),
-            MediaTypes::Podcast(podcast_track_data) => podcast_track_data.url().into(),
-        }
+        self.source().id_str()
     }
 
     /// Get the lyrics data for the current Track.
@@ -470,31 +750,27 @@ impl Track {
 
         let path_key = track_data.path().to_owned();
 
-        let res = LYRIC_CACHE.with_borrow_mut(|cache| {
-            cache
-                .try_get_or_insert(path_key, || {
-                    let result = parse_metadata_from_file(
-                        track_data.path(),
-                        MetadataOptions {
-                            lyrics: true,
-                            ..Default::default()
-                        },
-                    )?;
-                    let lyric_frames = result.lyric_frames.unwrap_or_default();
-
-                    let parsed_lyric = lyric_frames
-                        .first()
-                        .and_then(|frame| Lyric::from_str(&frame.text).ok());
-
-                    Ok(Arc::new(LyricData {
-                        raw_lyrics: lyric_frames,
-                        parsed_lyrics: parsed_lyric,
-                    }))
-                })
-                .cloned()
+        let res = CACHES.lyric.try_get_or_insert(path_key, || {
+            let result = parse_metadata_from_file(
+                track_data.path(),
+                MetadataOptions {
+                    lyrics: true,
+                    ..Default::default()
+                },
+            )?;
+            let lyric_frames = result.lyric_frames.unwrap_or_default();
+
+            let parsed_lyric = lyric_frames
+                .first()
+                .and_then(|frame| Lyric::from_str(&frame.text).ok());
+
+            Ok(Arc::new(LyricData {
+                raw_lyrics: lyric_frames,
+                parsed_lyrics: parsed_lyric,
+            }))
         });
 
-        // this has to be done as LruCache::try_get_or_insert enforces that the Ok result is the value itself, no mapping can be done.
+        // this has to be done as ByteLru::try_get_or_insert enforces that the Ok result is the value itself, no mapping can be done.
         match res {
             Ok(v) => Ok(Some(v)),
             Err(None) => Ok(None),
@@ -513,6 +789,7 @@ impl PartialEq<PlaylistTrackSource> for &Track {
             PlaylistTrackSource::PodcastUrl(url) => {
                 self.as_podcast().is_some_and(|v| v.url() == url)
             }
+            PlaylistTrackSource::VideoUrl(url) => self.as_video().is_some_and(|v| v.url() == url),
         }
     }
 }
@@ -524,7 +801,7 @@ impl PartialEq<PlaylistTrackSource> for &Track {
 /// - if reading the file fails
 /// - if parsing the file fails
 /// - also see [`find_folder_picture`]
-fn get_picture_for_music_track(track_path: &Path) -> Result<Option<Picture>> {
+pub(crate) fn get_picture_for_music_track(track_path: &Path) -> Result<Option<Picture>> {
     let result = parse_metadata_from_file(
         track_path,
         MetadataOptions {
@@ -632,6 +909,118 @@ impl DurationFmtShort {
     }
 }
 
+/// Format the given number as a upper-case roman numeral via a `Display` impl, as commonly used for movements.
+///
+/// `0` and numbers above `3999` cannot be represented and are formatted as a arabic number instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomanNumeral(pub u32);
+
+impl Display for RomanNumeral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NUMERALS: &[(u32, &str)] = &[
+            (1000, "M"),
+            (900, "CM"),
+            (500, "D"),
+            (400, "CD"),
+            (100, "C"),
+            (90, "XC"),
+            (50, "L"),
+            (40, "XL"),
+            (10, "X"),
+            (9, "IX"),
+            (5, "V"),
+            (4, "IV"),
+            (1, "I"),
+        ];
+
+        let mut rest = self.0;
+        if rest == 0 || rest > 3999 {
+            return write!(f, "{rest}");
+        }
+
+        for (value, numeral) in NUMERALS {
+            while rest >= *value {
+                f.write_str(numeral)?;
+                rest -= value;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The Opus tag of the track gain, see [`R128_TO_REPLAY_GAIN`].
+const R128_TRACK_GAIN: &str = "R128_TRACK_GAIN";
+/// The Opus tag of the album gain, see [`R128_TO_REPLAY_GAIN`].
+const R128_ALBUM_GAIN: &str = "R128_ALBUM_GAIN";
+
+/// The dB to add to a R128 gain, which is relative to -23 LUFS, to get a `ReplayGain` 2.0 gain, which is relative to
+/// -18 LUFS.
+const R128_TO_REPLAY_GAIN: f32 = 5.0;
+
+/// Read the `ReplayGain` track and album gains in dB and their peaks from `tag`, see [`read_replay_gain`].
+fn read_replay_gains(tag: &LoftyTag, res: &mut TrackMetadata) {
+    (res.replay_gain, res.replay_peak) = read_replay_gain(
+        tag,
+        &ItemKey::ReplayGainTrackGain,
+        &ItemKey::ReplayGainTrackPeak,
+        R128_TRACK_GAIN,
+    );
+    (res.replay_album_gain, res.replay_album_peak) = read_replay_gain(
+        tag,
+        &ItemKey::ReplayGainAlbumGain,
+        &ItemKey::ReplayGainAlbumPeak,
+        R128_ALBUM_GAIN,
+    );
+}
+
+/// Read a `ReplayGain` gain in dB and its peak from `tag`, falling back to the R128 gain of Opus files.
+///
+/// R128 tags have no peak, as Opus decoders are expected to apply the gain.
+fn read_replay_gain(
+    tag: &LoftyTag,
+    gain_key: &ItemKey,
+    peak_key: &ItemKey,
+    r128_key: &str,
+) -> (Option<f32>, Option<f32>) {
+    // the gain is usually tagged with its unit, like "-6.54 dB"
+    let gain = tag.get_string(gain_key).and_then(|v| {
+        let v = v.trim();
+        v.strip_suffix("dB")
+            .or_else(|| v.strip_suffix("db"))
+            .unwrap_or(v)
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite())
+    });
+    let peak = tag
+        .get_string(peak_key)
+        .and_then(|v| v.trim().parse::<f32>().ok())
+        .filter(|v| *v > 0.0 && v.is_finite());
+
+    if gain.is_some() {
+        return (gain, peak);
+    }
+
+    // a R128 gain is a Q7.8 fixed point number in dB, like "-1408" for -5.5 dB
+    let r128 = tag
+        .get_string(&ItemKey::Unknown(r128_key.to_string()))
+        .and_then(|v| v.trim().parse::<i16>().ok())
+        .map(|v| f32::from(v) / 256.0 + R128_TO_REPLAY_GAIN);
+
+    (r128, None)
+}
+
+/// Get the gain in dB to apply from the `ReplayGain` `gain` in dB, lowered so that the `peak` does not clip.
+#[must_use]
+pub fn normalization_gain(gain: f32, peak: Option<f32>) -> f32 {
+    match peak {
+        Some(peak) => gain.min(-20.0 * peak.log10()),
+        None => gain,
+    }
+}
+
 /// See [`TrackMetadata`] for explanation of values.
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 #[allow(clippy::struct_excessive_bools)] // configuration, this is not a state machine
@@ -648,6 +1037,15 @@ pub struct MetadataOptions<'a> {
     pub title: bool,
     pub duration: bool,
     pub genre: bool,
+    pub composer: bool,
+    pub performer: bool,
+    /// Includes the movement name and number.
+    pub work: bool,
+    pub bpm: bool,
+    /// Includes the peaks and the album gain.
+    pub replay_gain: bool,
+    /// The [`QualityBadge`] from the audio properties.
+    pub quality: bool,
     pub cover: bool,
     pub lyrics: bool,
     pub file_times: bool,
@@ -667,6 +1065,12 @@ impl MetadataOptions<'_> {
             title: true,
             duration: true,
             genre: true,
+            composer: true,
+            performer: true,
+            work: true,
+            bpm: true,
+            replay_gain: true,
+            quality: true,
             cover: true,
             lyrics: true,
             file_times: true,
@@ -690,12 +1094,38 @@ pub struct TrackMetadata {
     pub artist: Option<String>,
     /// ID3v2 tag `TXX:ARTISTS` <https://picard-docs.musicbrainz.org/en/appendices/tag_mapping.html>
     pub artists: Option<Vec<String>>,
+    /// ID3v2 tag `TSOP` or equivalent, like `Beatles, The`; one value per artist if there are multiple
+    pub artists_sort: Option<Vec<String>>,
+    /// ID3v2 tag `TSOA` or equivalent
+    pub album_sort: Option<String>,
     /// ID3v2 tag `TIT2` or equivalent
     pub title: Option<String>,
     /// Total duration, this may or may not come from a tag
     pub duration: Option<Duration>,
-    /// ID3v2 tag `TCON` or equivalent
+    /// ID3v2 tag `TCON` or equivalent, all values joined for display
     pub genre: Option<String>,
+    /// ID3v2 tag `TCON` or equivalent, all values with `;` separated values split
+    pub genres: Option<Vec<String>>,
+    /// ID3v2 tag `TCOM` or equivalent
+    pub composer: Option<String>,
+    /// ID3v2 tag `TMCL` or equivalent, the credited performers like instrumentalists or soloists
+    pub performers: Option<Vec<String>>,
+    /// ID3v2 tag `TXX:WORK` or equivalent, the classical work a track is a movement of
+    pub work: Option<String>,
+    /// ID3v2 tag `MVNM` or equivalent
+    pub movement: Option<String>,
+    /// ID3v2 tag `MVIN` or equivalent
+    pub movement_number: Option<u32>,
+    /// ID3v2 tag `TBPM` or equivalent, the tempo in beats per minute
+    pub bpm: Option<f32>,
+    /// ID3v2 tag `TXX:REPLAYGAIN_TRACK_GAIN` or equivalent, the gain in dB to normalize the loudness
+    pub replay_gain: Option<f32>,
+    /// ID3v2 tag `TXX:REPLAYGAIN_TRACK_PEAK` or equivalent, the highest sample, `1.0` being full scale
+    pub replay_peak: Option<f32>,
+    /// ID3v2 tag `TXX:REPLAYGAIN_ALBUM_GAIN` or equivalent, the gain in dB to normalize the loudness of the album
+    pub replay_album_gain: Option<f32>,
+    /// ID3v2 tag `TXX:REPLAYGAIN_ALBUM_PEAK` or equivalent, the highest sample of the album
+    pub replay_album_peak: Option<f32>,
     /// ID3v2 tag `APIC` or equivalent
     pub cover: Option<Picture>,
     /// ID3v2 tags `USLT` or equivalent
@@ -703,6 +1133,8 @@ pub struct TrackMetadata {
     pub file_times: Option<FileTimes>,
 
     pub file_type: Option<FileType>,
+    /// The quality of the audio, from its properties instead of a tag
+    pub quality: Option<QualityBadge>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -711,6 +1143,81 @@ pub struct FileTimes {
     pub created: Option<SystemTime>,
 }
 
+/// A badge for the quality of the audio of a track, shown next to it and usable as a filter.
+///
+/// Ordinary lossy tracks above [`QualityBadge::LOSSY_MAX_KBPS`] have no badge.
+///
+/// Stored as [`QualityBadge::number`] in `tracks_metadata.quality`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum QualityBadge {
+    /// Lossless with at least 24 bit and 96 kHz
+    HiRes,
+    Lossless,
+    /// Lossy with a bitrate of at most [`QualityBadge::LOSSY_MAX_KBPS`]
+    Lossy,
+}
+
+impl QualityBadge {
+    /// All badges, in the order of their numbers.
+    pub const ALL: [Self; 3] = [Self::HiRes, Self::Lossless, Self::Lossy];
+
+    /// The highest bitrate in kbps a lossy track gets the [`QualityBadge::Lossy`] badge at.
+    pub const LOSSY_MAX_KBPS: u32 = 128;
+
+    /// Get the badge for audio with the given properties.
+    ///
+    /// Only lossless formats have a `bit_depth`, lossy formats decode to whatever depth is asked of them.
+    #[must_use]
+    pub fn from_properties(
+        bit_depth: Option<u8>,
+        sample_rate: Option<u32>,
+        bitrate_kbps: Option<u32>,
+    ) -> Option<Self> {
+        match bit_depth {
+            Some(bits) if bits >= 24 && sample_rate.is_some_and(|v| v >= 96_000) => {
+                Some(Self::HiRes)
+            }
+            Some(_) => Some(Self::Lossless),
+            None => bitrate_kbps
+                .filter(|v| *v > 0 && *v <= Self::LOSSY_MAX_KBPS)
+                .map(|_| Self::Lossy),
+        }
+    }
+
+    #[must_use]
+    pub fn number(self) -> u8 {
+        match self {
+            Self::HiRes => 1,
+            Self::Lossless => 2,
+            Self::Lossy => 3,
+        }
+    }
+
+    /// Get the badge for `number`, see [`Self::number`].
+    #[must_use]
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.number() == number)
+    }
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::HiRes => "Hi-Res",
+            Self::Lossless => "Lossless",
+            Self::Lossy => "Lossy",
+        }
+    }
+
+    /// Get the badge named `name`, case-insensitively and with or without the dash.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.replace('-', "");
+        Self::ALL
+            .into_iter()
+            .find(|v| v.name().replace('-', "").eq_ignore_ascii_case(&name))
+    }
+}
+
 /// Try to parse all specified metadata in the given `options`.
 pub fn parse_metadata_from_file(
     path: &Path,
@@ -733,6 +1240,15 @@ pub fn parse_metadata_from_file(
 
     res.file_type = Some(tagged_file.file_type());
 
+    if options.quality {
+        let properties = tagged_file.properties();
+        res.quality = QualityBadge::from_properties(
+            properties.bit_depth(),
+            properties.sample_rate(),
+            properties.audio_bitrate(),
+        );
+    }
+
     if let Some(tag) = tagged_file.primary_tag() {
         handle_tag(tag, options, &mut res);
     } else if let Some(tag) = tagged_file.first_tag() {
@@ -753,7 +1269,17 @@ pub fn parse_metadata_from_file(
     Ok(res)
 }
 
+/// Get all values of `key` in `tag`, trimmed and with empty values removed.
+fn non_empty_strings(tag: &LoftyTag, key: &ItemKey) -> Vec<String> {
+    tag.get_strings(key)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
 /// The inner working to actually copy data from the given [`LoftyTag`] into the `res`ult
+#[allow(clippy::too_many_lines)]
 fn handle_tag(tag: &LoftyTag, options: MetadataOptions<'_>, res: &mut TrackMetadata) {
     if let Some(len_tag) = tag.get_string(&ItemKey::Length) {
         match len_tag.parse::<u64>() {
@@ -765,7 +1291,8 @@ fn handle_tag(tag: &LoftyTag, options: MetadataOptions<'_>, res: &mut TrackMetad
     }
 
     if options.artist {
-        res.artist = tag.artist().map(Cow::into_owned);
+        // multiple "TPE1" frames or null-separated values are separate items
+        res.artist = join_values(tag.get_strings(&ItemKey::TrackArtist));
     }
     if options.artists {
         let mut artists: Vec<String> = tag
@@ -773,18 +1300,26 @@ fn handle_tag(tag: &LoftyTag, options: MetadataOptions<'_>, res: &mut TrackMetad
             .map(ToString::to_string)
             .collect();
 
-        if artists.is_empty()
-            && !options.artist_separators.is_empty()
-            && let Some(artist) = tag.artist()
-        {
-            let artists_iter = split_artists(&artist, options);
-            artists.extend(artists_iter);
+        if artists.is_empty() {
+            for artist in tag.get_strings(&ItemKey::TrackArtist) {
+                if options.artist_separators.is_empty() {
+                    artists.push(artist.trim().to_string());
+                } else {
+                    artists.extend(split_artists(artist, options));
+                }
+            }
+            artists.retain(|v| !v.is_empty());
+            artists.dedup();
         }
 
         res.artists = Some(artists);
+        res.artists_sort = Some(non_empty_strings(tag, &ItemKey::TrackArtistSortOrder));
     }
     if options.album {
         res.album = tag.album().map(Cow::into_owned);
+        res.album_sort = tag
+            .get_string(&ItemKey::AlbumTitleSortOrder)
+            .map(ToString::to_string);
     }
     if options.album_artist {
         res.album_artist = tag
@@ -820,7 +1355,34 @@ fn handle_tag(tag: &LoftyTag, options: MetadataOptions<'_>, res: &mut TrackMetad
         res.title = tag.title().map(Cow::into_owned);
     }
     if options.genre {
-        res.genre = tag.genre().map(Cow::into_owned);
+        let genres = split_genres(tag.get_strings(&ItemKey::Genre));
+        res.genre = join_values(genres.iter().map(String::as_str));
+        res.genres = Some(genres);
+    }
+    if options.composer {
+        res.composer = tag.get_string(&ItemKey::Composer).map(ToString::to_string);
+    }
+    if options.performer {
+        res.performers = Some(non_empty_strings(tag, &ItemKey::Performer));
+    }
+    if options.work {
+        res.work = tag.get_string(&ItemKey::Work).map(ToString::to_string);
+        res.movement = tag.get_string(&ItemKey::Movement).map(ToString::to_string);
+        // ID3v2 "MVIN" is stored as "number/total"
+        res.movement_number = tag
+            .get_string(&ItemKey::MovementNumber)
+            .and_then(|v| v.split('/').next())
+            .and_then(|v| v.trim().parse().ok());
+    }
+    if options.bpm {
+        res.bpm = tag
+            .get_string(&ItemKey::Bpm)
+            .or_else(|| tag.get_string(&ItemKey::IntegerBpm))
+            .and_then(|v| v.trim().parse::<f32>().ok())
+            .filter(|v| *v > 0.0);
+    }
+    if options.replay_gain {
+        read_replay_gains(tag, res);
     }
 
     if options.cover {
@@ -839,6 +1401,30 @@ fn handle_tag(tag: &LoftyTag, options: MetadataOptions<'_>, res: &mut TrackMetad
     }
 }
 
+/// Join all values of a multi-valued tag for display, [`None`] if there are no values.
+fn join_values<'a>(values: impl Iterator<Item = &'a str>) -> Option<String> {
+    let values: Vec<&str> = values.map(str::trim).filter(|v| !v.is_empty()).collect();
+
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(values.join("; "))
+}
+
+/// Split all genre values on `;`, as some taggers write multiple genres into a single value.
+fn split_genres<'a>(values: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut genres: Vec<String> = Vec::new();
+
+    for genre in values.flat_map(|v| v.split(';')).map(str::trim) {
+        if !genre.is_empty() && !genres.iter().any(|v| v == genre) {
+            genres.push(genre.to_string());
+        }
+    }
+
+    genres
+}
+
 /// Create a iterator which separates `artist` with options from `options`
 #[inline]
 fn split_artists<'a>(
@@ -894,4 +1480,394 @@ mod tests {
             );
         }
     }
+
+    mod source {
+        use std::path::{Path, PathBuf};
+
+        use pretty_assertions::assert_eq;
+
+        use crate::track::{
+            MediaTypes, MediaTypesSimple, PodcastTrackData, RadioTrackData, StreamLocation,
+            TrackData, VideoTrackData,
+        };
+
+        #[test]
+        fn should_locate_all_kinds() {
+            let track = MediaTypes::Track(TrackData::new(PathBuf::from("/music/file.mp3")));
+            let source = track.source();
+            assert_eq!(source.media_type(), MediaTypesSimple::Music);
+            assert_eq!(source.location(), "/music/file.mp3");
+            assert_eq!(source.id_str(), "file.mp3");
+            assert_eq!(
+                source.stream(),
+                StreamLocation::File(Path::new("/music/file.mp3"))
+            );
+            assert!(!source.is_live());
+
+            let radio = MediaTypes::Radio(RadioTrackData::new("http://radio/".to_string()));
+            let source = radio.source();
+            assert_eq!(source.media_type(), MediaTypesSimple::LiveRadio);
+            assert_eq!(source.location(), "http://radio/");
+            assert_eq!(source.stream(), StreamLocation::Url("http://radio/"));
+            assert!(source.is_live());
+
+            let url = "https://www.youtube.com/watch?v=abc";
+            let video = MediaTypes::Video(VideoTrackData::new(url.to_string()));
+            let source = video.source();
+            assert_eq!(source.media_type(), MediaTypesSimple::Video);
+            assert_eq!(source.location(), url);
+            assert_eq!(source.stream(), StreamLocation::Extract(url));
+            assert!(!source.is_live());
+        }
+
+        #[test]
+        fn should_stream_downloaded_podcast_from_file() {
+            let mut data = PodcastTrackData::new("http://podcast/ep.mp3".to_string());
+            assert_eq!(
+                MediaTypes::Podcast(data.clone()).source().stream(),
+                StreamLocation::Url("http://podcast/ep.mp3")
+            );
+
+            data.localfile = Some(PathBuf::from("/podcasts/ep.mp3"));
+            let podcast = MediaTypes::Podcast(data);
+            let source = podcast.source();
+            // the identifier stays the url
+            assert_eq!(source.location(), "http://podcast/ep.mp3");
+            assert_eq!(
+                source.stream(),
+                StreamLocation::File(Path::new("/podcasts/ep.mp3"))
+            );
+        }
+    }
+
+    mod roman_numeral {
+        use crate::track::RomanNumeral;
+
+        #[test]
+        fn should_format_numerals() {
+            assert_eq!(RomanNumeral(1).to_string(), "I");
+            assert_eq!(RomanNumeral(4).to_string(), "IV");
+            assert_eq!(RomanNumeral(9).to_string(), "IX");
+            assert_eq!(RomanNumeral(14).to_string(), "XIV");
+            assert_eq!(RomanNumeral(1994).to_string(), "MCMXCIV");
+        }
+
+        #[test]
+        fn should_format_unrepresentable_as_number() {
+            assert_eq!(RomanNumeral(0).to_string(), "0");
+            assert_eq!(RomanNumeral(4000).to_string(), "4000");
+        }
+    }
+
+    mod quality_badge {
+        use crate::track::QualityBadge;
+
+        #[test]
+        fn should_badge_by_properties() {
+            assert_eq!(
+                QualityBadge::from_properties(Some(24), Some(96_000), Some(4608)),
+                Some(QualityBadge::HiRes)
+            );
+            assert_eq!(
+                QualityBadge::from_properties(Some(24), Some(48_000), Some(2304)),
+                Some(QualityBadge::Lossless)
+            );
+            assert_eq!(
+                QualityBadge::from_properties(Some(16), Some(44_100), Some(1411)),
+                Some(QualityBadge::Lossless)
+            );
+            assert_eq!(
+                QualityBadge::from_properties(None, Some(44_100), Some(128)),
+                Some(QualityBadge::Lossy)
+            );
+            assert_eq!(
+                QualityBadge::from_properties(None, Some(44_100), Some(320)),
+                None
+            );
+            assert_eq!(QualityBadge::from_properties(None, None, None), None);
+        }
+
+        #[test]
+        fn should_find_by_name() {
+            assert_eq!(QualityBadge::from_name("hires"), Some(QualityBadge::HiRes));
+            assert_eq!(QualityBadge::from_name("Hi-Res"), Some(QualityBadge::HiRes));
+            assert_eq!(
+                QualityBadge::from_name("LOSSLESS"),
+                Some(QualityBadge::Lossless)
+            );
+            assert_eq!(QualityBadge::from_name("mp3"), None);
+        }
+    }
+
+    mod normalization_gain {
+        use std::path::PathBuf;
+
+        use crate::config::v2::server::NormalizationMode;
+        use crate::track::TrackData;
+
+        #[test]
+        fn should_fall_back_to_track_gain() {
+            let mut track = TrackData::new(PathBuf::from("/somewhere/file.mp3"));
+            assert_eq!(track.normalization_gain_for(NormalizationMode::Album), None);
+
+            // a analyzed gain does not replace a tagged one
+            track.set_fallback_normalization_gain(-3.0);
+            track.set_fallback_normalization_gain(-9.0);
+            assert_eq!(track.normalization_gain(), Some(-3.0));
+            assert_eq!(
+                track.normalization_gain_for(NormalizationMode::Album),
+                Some(-3.0)
+            );
+
+            track.album_normalization_gain = Some(-1.0);
+            assert_eq!(
+                track.normalization_gain_for(NormalizationMode::Track),
+                Some(-3.0)
+            );
+            assert_eq!(
+                track.normalization_gain_for(NormalizationMode::Album),
+                Some(-1.0)
+            );
+        }
+    }
+
+    mod movement_title {
+        use std::path::PathBuf;
+
+        use crate::track::TrackData;
+
+        fn track(work: Option<&str>, movement: Option<&str>, number: Option<u32>) -> TrackData {
+            let mut track = TrackData::new(PathBuf::from("/somewhere/file.mp3"));
+            track.work = work.map(ToString::to_string);
+            track.movement = movement.map(ToString::to_string);
+            track.movement_number = number;
+
+            track
+        }
+
+        #[test]
+        fn should_format_movement_title() {
+            assert_eq!(
+                track(Some("Symphony No. 5"), Some("Andante con moto"), Some(2))
+                    .movement_title()
+                    .as_deref(),
+                Some("Symphony No. 5: II. Andante con moto")
+            );
+            assert_eq!(
+                track(Some("Symphony No. 5"), Some("Andante con moto"), None)
+                    .movement_title()
+                    .as_deref(),
+                Some("Symphony No. 5: Andante con moto")
+            );
+            assert_eq!(
+                track(Some("Symphony No. 5"), None, Some(3))
+                    .movement_title()
+                    .as_deref(),
+                Some("Symphony No. 5: III.")
+            );
+        }
+
+        #[test]
+        fn should_not_format_without_work_or_movement() {
+            assert_eq!(
+                track(None, Some("Andante con moto"), Some(2)).movement_title(),
+                None
+            );
+            assert_eq!(
+                track(Some("Symphony No. 5"), None, None).movement_title(),
+                None
+            );
+        }
+    }
+
+    mod multi_value {
+        use lofty::tag::{ItemKey, ItemValue, Tag, TagItem, TagType};
+        use pretty_assertions::assert_eq;
+
+        use crate::track::{MetadataOptions, TrackMetadata, handle_tag, normalization_gain};
+
+        fn tag_with(values: &[(ItemKey, &str)]) -> Tag {
+            let mut tag = Tag::new(TagType::VorbisComments);
+            for (key, value) in values {
+                assert!(tag.push(TagItem::new(
+                    key.clone(),
+                    ItemValue::Text(value.to_string())
+                )));
+            }
+
+            tag
+        }
+
+        #[test]
+        fn should_read_multiple_artists_and_genres() {
+            let tag = tag_with(&[
+                (ItemKey::TrackArtist, "ArtistA"),
+                (ItemKey::TrackArtist, "ArtistB feat. ArtistC"),
+                (ItemKey::Genre, "Rock; Pop"),
+                (ItemKey::Genre, "Jazz"),
+                (ItemKey::Genre, "Pop"),
+            ]);
+            let options = MetadataOptions {
+                artist_separators: &["feat."],
+                ..MetadataOptions::all()
+            };
+            let mut res = TrackMetadata::default();
+            handle_tag(&tag, options, &mut res);
+
+            assert_eq!(
+                res.artist.as_deref(),
+                Some("ArtistA; ArtistB feat. ArtistC")
+            );
+            assert_eq!(
+                res.artists,
+                Some(vec![
+                    "ArtistA".to_string(),
+                    "ArtistB".to_string(),
+                    "ArtistC".to_string()
+                ])
+            );
+            assert_eq!(res.genre.as_deref(), Some("Rock; Pop; Jazz"));
+            assert_eq!(
+                res.genres,
+                Some(vec![
+                    "Rock".to_string(),
+                    "Pop".to_string(),
+                    "Jazz".to_string()
+                ])
+            );
+        }
+
+        #[test]
+        fn should_prefer_artists_tag() {
+            let tag = tag_with(&[
+                (ItemKey::TrackArtist, "ArtistA & ArtistB"),
+                (ItemKey::TrackArtists, "ArtistA"),
+                (ItemKey::TrackArtists, "ArtistB"),
+            ]);
+            let options = MetadataOptions {
+                artist_separators: &["&"],
+                ..MetadataOptions::all()
+            };
+            let mut res = TrackMetadata::default();
+            handle_tag(&tag, options, &mut res);
+
+            assert_eq!(res.artist.as_deref(), Some("ArtistA & ArtistB"));
+            assert_eq!(
+                res.artists,
+                Some(vec!["ArtistA".to_string(), "ArtistB".to_string()])
+            );
+            assert_eq!(res.genre, None);
+            assert_eq!(res.genres, Some(Vec::new()));
+        }
+
+        #[test]
+        fn should_read_sort_names() {
+            let tag = tag_with(&[
+                (ItemKey::TrackArtist, "The Beatles"),
+                (ItemKey::TrackArtistSortOrder, "Beatles, The"),
+                (ItemKey::AlbumTitle, "The White Album"),
+                (ItemKey::AlbumTitleSortOrder, "White Album, The"),
+            ]);
+            let mut res = TrackMetadata::default();
+            handle_tag(&tag, MetadataOptions::all(), &mut res);
+
+            assert_eq!(res.artists_sort, Some(vec!["Beatles, The".to_string()]));
+            assert_eq!(res.album_sort.as_deref(), Some("White Album, The"));
+        }
+
+        #[test]
+        fn should_read_performers() {
+            let tag = tag_with(&[
+                (ItemKey::TrackArtist, "Orchestra"),
+                (ItemKey::Composer, "Beethoven"),
+                (ItemKey::Performer, "SoloistA"),
+                (ItemKey::Performer, " "),
+                (ItemKey::Performer, "SoloistB"),
+            ]);
+            let mut res = TrackMetadata::default();
+            handle_tag(&tag, MetadataOptions::all(), &mut res);
+
+            assert_eq!(res.composer.as_deref(), Some("Beethoven"));
+            assert_eq!(
+                res.performers,
+                Some(vec!["SoloistA".to_string(), "SoloistB".to_string()])
+            );
+        }
+
+        #[test]
+        fn should_read_bpm() {
+            let mut res = TrackMetadata::default();
+            handle_tag(
+                &tag_with(&[(ItemKey::Bpm, " 127.5 ")]),
+                MetadataOptions::all(),
+                &mut res,
+            );
+            assert_eq!(res.bpm, Some(127.5));
+
+            // unparsable and zero values are not a tempo
+            for value in ["fast", "0"] {
+                let mut res = TrackMetadata::default();
+                handle_tag(
+                    &tag_with(&[(ItemKey::Bpm, value)]),
+                    MetadataOptions::all(),
+                    &mut res,
+                );
+                assert_eq!(res.bpm, None);
+            }
+        }
+
+        #[test]
+        fn should_read_replay_gain() {
+            let mut res = TrackMetadata::default();
+            handle_tag(
+                &tag_with(&[
+                    (ItemKey::ReplayGainTrackGain, "-6.54 dB"),
+                    (ItemKey::ReplayGainTrackPeak, "0.988"),
+                ]),
+                MetadataOptions::all(),
+                &mut res,
+            );
+            assert_eq!(res.replay_gain, Some(-6.54));
+            assert_eq!(res.replay_peak, Some(0.988));
+
+            let mut res = TrackMetadata::default();
+            handle_tag(
+                &tag_with(&[(ItemKey::ReplayGainTrackGain, "+2.1")]),
+                MetadataOptions::all(),
+                &mut res,
+            );
+            assert_eq!(res.replay_gain, Some(2.1));
+            assert_eq!(res.replay_peak, None);
+            assert_eq!(res.replay_album_gain, None);
+        }
+
+        #[test]
+        fn should_read_album_and_r128_gain() {
+            let mut tag = tag_with(&[
+                (ItemKey::ReplayGainAlbumGain, "-4.20 dB"),
+                (ItemKey::ReplayGainAlbumPeak, "0.5"),
+            ]);
+            // opus tags are not mapped by lofty
+            tag.push_unchecked(TagItem::new(
+                ItemKey::Unknown("R128_TRACK_GAIN".to_string()),
+                ItemValue::Text("-1408".to_string()),
+            ));
+            let mut res = TrackMetadata::default();
+            handle_tag(&tag, MetadataOptions::all(), &mut res);
+
+            // -1408 / 256 = -5.5 dB relative to -23 LUFS
+            assert_eq!(res.replay_gain, Some(-0.5));
+            assert_eq!(res.replay_peak, None);
+            assert_eq!(res.replay_album_gain, Some(-4.2));
+            assert_eq!(res.replay_album_peak, Some(0.5));
+        }
+
+        #[test]
+        fn should_limit_normalization_gain_by_peak() {
+            assert!((normalization_gain(-6.0, Some(0.9)) - -6.0).abs() < 1e-6);
+            // a peak of 0.5 can only be raised by about 6 dB before clipping
+            assert!((normalization_gain(10.0, Some(0.5)) - 6.0206).abs() < 1e-3);
+            assert!((normalization_gain(10.0, None) - 10.0).abs() < 1e-6);
+        }
+    }
 }
@@ -0,0 +1,367 @@
+//! Tracks to suggest based on the playing one: more from its album, more by its artists, others of its genre and
+//! similar tracks according to Last.fm, limited to the tracks in the library.
+//!
+//! See <https://www.last.fm/api/show/track.getSimilar>.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use rand::seq::SliceRandom as _;
+use reqwest::ClientBuilder;
+use rusqlite::Connection;
+use serde_json::Value;
+
+use crate::credentials::CREDENTIALS;
+use crate::new_database::Integer;
+use crate::new_database::track_ops::{self, RowOrdering, TrackRead};
+use crate::ratelimit::LIMITER;
+
+/// The [`CREDENTIALS`] key of the Last.fm API key.
+pub const LASTFM_CREDENTIAL_KEY: &str = "lastfm";
+
+/// How many similar tracks to request from Last.fm, as most of them are usually not in the library.
+const LASTFM_LIMIT: u32 = 100;
+
+/// Why a track is suggested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionSection {
+    Album,
+    Artist,
+    Genre,
+    LastFm,
+}
+
+impl SuggestionSection {
+    /// A short name of the section, for display.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Album => "album",
+            Self::Artist => "artist",
+            Self::Genre => "genre",
+            Self::LastFm => "last.fm",
+        }
+    }
+}
+
+/// A track of the library to suggest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackSuggestion {
+    pub section: SuggestionSection,
+    pub path: PathBuf,
+    pub title: String,
+    pub artist: Option<String>,
+}
+
+impl TrackSuggestion {
+    fn from_track(section: SuggestionSection, track: TrackRead) -> Self {
+        let path = track.as_pathbuf();
+        let title = track
+            .title
+            .unwrap_or_else(|| track.file_stem.to_string_lossy().to_string());
+
+        Self {
+            section,
+            path,
+            title,
+            artist: track.artist_display,
+        }
+    }
+}
+
+/// Get up to `per_section` tracks each from the album, the artists and the genre of the track at `current`.
+///
+/// Every track is only suggested once, in the first section it appears in, and the current track not at all.
+///
+/// # Errors
+///
+/// - if `current` is not in the database
+/// - if the database queries fail
+pub fn from_database(
+    conn: &Connection,
+    current: &Path,
+    per_section: usize,
+) -> Result<Vec<TrackSuggestion>> {
+    let track = track_ops::get_track_from_path(conn, current).context("get current track")?;
+    let mut seen = HashSet::from([current.to_path_buf()]);
+    let mut suggestions = Vec::new();
+
+    if let Some(album) = &track.album {
+        let tracks = track_ops::get_tracks_from_album_id(conn, album.id)?;
+        suggestions.extend(take_section(
+            SuggestionSection::Album,
+            after_current(tracks, track.id),
+            &mut seen,
+            per_section,
+        ));
+    }
+
+    let mut by_artists = Vec::new();
+    for artist in &track.artists {
+        by_artists.extend(track_ops::get_tracks_from_artist(
+            conn,
+            &artist.name,
+            RowOrdering::AddedDesc,
+        )?);
+    }
+    suggestions.extend(take_section(
+        SuggestionSection::Artist,
+        by_artists,
+        &mut seen,
+        per_section,
+    ));
+
+    if let Some(genre) = &track.genre {
+        let mut tracks =
+            track_ops::get_tracks_from_genre(conn, Some(genre.as_str()), RowOrdering::IdAsc)?;
+        // a different choice every time, as genres are usually big
+        tracks.shuffle(&mut rand::rng());
+        suggestions.extend(take_section(
+            SuggestionSection::Genre,
+            tracks,
+            &mut seen,
+            per_section,
+        ));
+    }
+
+    Ok(suggestions)
+}
+
+/// Rotate the tracks of a album so that the ones after the track with `current_id` come first.
+fn after_current(mut tracks: Vec<TrackRead>, current_id: Integer) -> Vec<TrackRead> {
+    if let Some(idx) = tracks.iter().position(|v| v.id == current_id) {
+        tracks.rotate_left(idx + 1);
+    }
+
+    tracks
+}
+
+/// Take up to `limit` of `tracks` that are not in `seen` yet, adding them to `seen`.
+fn take_section(
+    section: SuggestionSection,
+    tracks: Vec<TrackRead>,
+    seen: &mut HashSet<PathBuf>,
+    limit: usize,
+) -> Vec<TrackSuggestion> {
+    tracks
+        .into_iter()
+        .filter(|v| seen.insert(v.as_pathbuf()))
+        .take(limit)
+        .map(|v| TrackSuggestion::from_track(section, v))
+        .collect()
+}
+
+/// Get the artists and titles of the tracks Last.fm lists as similar to `title` by `artist`, the most similar first.
+///
+/// # Errors
+///
+/// - if no API key is stored as [`LASTFM_CREDENTIAL_KEY`]
+/// - if Last.fm could not be reached or responded with a error
+pub async fn lastfm_similar(artist: &str, title: &str) -> Result<Vec<(String, String)>> {
+    let Some(api_key) = CREDENTIALS
+        .get(LASTFM_CREDENTIAL_KEY)
+        .context("look up the Last.fm API key")?
+    else {
+        bail!(
+            "Last.fm requires a API key, see \"termusic-server credentials set {LASTFM_CREDENTIAL_KEY}\""
+        );
+    };
+
+    let client = ClientBuilder::new()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let limit = LASTFM_LIMIT.to_string();
+    let request = client.get("https://ws.audioscrobbler.com/2.0/").query(&[
+        ("method", "track.getsimilar"),
+        ("artist", artist),
+        ("track", title),
+        ("autocorrect", "1"),
+        ("limit", &limit),
+        ("api_key", &api_key),
+        ("format", "json"),
+    ]);
+
+    let body: Value = LIMITER
+        .send(request)
+        .await
+        .context("request similar tracks")?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    parse_lastfm_similar(&body)
+}
+
+/// Parse the response of `track.getSimilar`.
+fn parse_lastfm_similar(body: &Value) -> Result<Vec<(String, String)>> {
+    if let Some(message) = body.get("message").and_then(Value::as_str) {
+        bail!("Last.fm: {message}");
+    }
+
+    let tracks = body
+        .pointer("/similartracks/track")
+        .and_then(Value::as_array)
+        .context("no similar tracks in the response")?;
+
+    Ok(tracks
+        .iter()
+        .filter_map(|v| {
+            let title = v.get("name")?.as_str()?;
+            let artist = v.pointer("/artist/name")?.as_str()?;
+            Some((artist.to_string(), title.to_string()))
+        })
+        .collect())
+}
+
+/// Get up to `limit` tracks of the library matching the `similar` artists and titles, in the same order.
+///
+/// Tracks at a path in `exclude`, like the ones already suggested, are skipped.
+///
+/// # Errors
+///
+/// - if the database queries fail
+pub fn lastfm_in_library(
+    conn: &Connection,
+    similar: &[(String, String)],
+    exclude: &[PathBuf],
+    limit: usize,
+) -> Result<Vec<TrackSuggestion>> {
+    let mut seen: HashSet<PathBuf> = exclude.iter().cloned().collect();
+    let mut suggestions = Vec::new();
+
+    for (artist, title) in similar {
+        if suggestions.len() >= limit {
+            break;
+        }
+
+        // the same track may be in the library multiple times, like on a compilation, suggesting it once is enough
+        let Some((path, _album)) = track_ops::find_tracks_by_tags(conn, artist, title)?
+            .into_iter()
+            .find(|(path, _)| !seen.contains(path))
+        else {
+            continue;
+        };
+
+        seen.insert(path.clone());
+        suggestions.push(TrackSuggestion {
+            section: SuggestionSection::LastFm,
+            path,
+            title: title.clone(),
+            artist: Some(artist.clone()),
+        });
+    }
+
+    Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::{
+        SuggestionSection, TrackSuggestion, after_current, parse_lastfm_similar, take_section,
+    };
+    use crate::new_database::Integer;
+    use crate::new_database::track_ops::TrackRead;
+
+    fn track(id: Integer, name: &str) -> TrackRead {
+        TrackRead {
+            id,
+            file_dir: PathBuf::from("/music"),
+            file_stem: OsString::from(name),
+            file_ext: OsString::from("mp3"),
+            duration: None,
+            last_position: None,
+            album: None,
+            title: Some(name.to_uppercase()),
+            genre: None,
+            artist_display: Some(String::from("Artist")),
+            artists: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn should_start_after_current() {
+        let tracks = vec![track(1, "a"), track(2, "b"), track(3, "c"), track(4, "d")];
+
+        let ids: Vec<Integer> = after_current(tracks.clone(), 2)
+            .iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(ids, vec![3, 4, 1, 2]);
+
+        let ids: Vec<Integer> = after_current(tracks, 5).iter().map(|v| v.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn should_take_unseen_tracks() {
+        let mut seen = HashSet::from([PathBuf::from("/music/a.mp3")]);
+
+        let section = take_section(
+            SuggestionSection::Artist,
+            vec![track(1, "a"), track(2, "b"), track(3, "c"), track(4, "d")],
+            &mut seen,
+            2,
+        );
+        assert_eq!(
+            section,
+            vec![
+                TrackSuggestion {
+                    section: SuggestionSection::Artist,
+                    path: PathBuf::from("/music/b.mp3"),
+                    title: String::from("B"),
+                    artist: Some(String::from("Artist")),
+                },
+                TrackSuggestion {
+                    section: SuggestionSection::Artist,
+                    path: PathBuf::from("/music/c.mp3"),
+                    title: String::from("C"),
+                    artist: Some(String::from("Artist")),
+                },
+            ]
+        );
+
+        let section = take_section(
+            SuggestionSection::Genre,
+            vec![track(3, "c"), track(4, "d")],
+            &mut seen,
+            2,
+        );
+        assert_eq!(section.len(), 1);
+        assert_eq!(section[0].path, PathBuf::from("/music/d.mp3"));
+    }
+
+    #[test]
+    fn should_parse_lastfm_similar() {
+        let body = json!({
+            "similartracks": {
+                "track": [
+                    {"name": "Song A", "match": 1.0, "artist": {"name": "Artist A"}},
+                    {"name": "Broken", "match": 0.9},
+                    {"name": "Song B", "match": 0.8, "artist": {"name": "Artist B"}},
+                ]
+            }
+        });
+        assert_eq!(
+            parse_lastfm_similar(&body).unwrap(),
+            vec![
+                (String::from("Artist A"), String::from("Song A")),
+                (String::from("Artist B"), String::from("Song B")),
+            ]
+        );
+
+        let error = json!({"error": 6, "message": "Track not found"});
+        assert_eq!(
+            parse_lastfm_similar(&error).unwrap_err().to_string(),
+            "Last.fm: Track not found"
+        );
+    }
+}
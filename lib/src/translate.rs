@@ -0,0 +1,261 @@
+//! Translate the lines of lyrics and episode descriptions with `LibreTranslate` or `DeepL`.
+//!
+//! See <https://libretranslate.com/docs> and <https://developers.deepl.com/docs/api-reference/translate>.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use reqwest::{ClientBuilder, StatusCode, Url};
+use serde_json::{Value, json};
+
+use crate::config::v2::tui::{Translation, TranslationProvider};
+use crate::credentials::CREDENTIALS;
+use crate::ratelimit::LIMITER;
+
+/// The most texts to translate in a single request, `DeepL` rejects more than 50.
+const MAX_TEXTS_PER_REQUEST: usize = 50;
+
+/// Get the [`CREDENTIALS`] key for the API key of a translation service on `host`.
+#[must_use]
+pub fn credential_key(host: &str) -> String {
+    format!("translate:{host}")
+}
+
+/// Get the language to translate to: the configured one, otherwise the one of the system locale.
+#[must_use]
+pub fn target_language(settings: &Translation) -> Option<String> {
+    if let Some(lang) = settings.target_language.as_deref().map(str::trim)
+        && !lang.is_empty()
+    {
+        return Some(lang.to_string());
+    }
+
+    // the same precedence as for messages in POSIX
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .and_then(|v| locale_language(&v))
+}
+
+/// Get the ISO 639 language code of a locale like `de_DE.UTF-8`, [`None`] for the language independent `C` and
+/// `POSIX` locales.
+fn locale_language(locale: &str) -> Option<String> {
+    let lang = locale
+        .split(['_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    ((2..=3).contains(&lang.len()) && lang.chars().all(|v| v.is_ascii_alphabetic())).then_some(lang)
+}
+
+/// Translate each of `texts` to the [`target_language`], in the same order.
+///
+/// Empty texts are kept empty without a request.
+///
+/// # Errors
+///
+/// - if no provider is configured or no target language could be determined
+/// - if the service could not be reached or responded with a error
+pub async fn translate(settings: &Translation, texts: &[String]) -> Result<Vec<String>> {
+    if !settings.is_enabled() {
+        bail!("No translation provider is configured");
+    }
+    let target = target_language(settings)
+        .ok_or_else(|| anyhow!("No target language is configured or set in the locale"))?;
+
+    let api_url = settings.api_url();
+    let api_key = Url::parse(api_url)
+        .with_context(|| format!("parse translation url \"{api_url}\""))?
+        .host_str()
+        .and_then(|host| {
+            CREDENTIALS
+                .get(&credential_key(host))
+                .inspect_err(|err| {
+                    warn!("Looking up the translation API key for \"{host}\" failed: {err:#}");
+                })
+                .ok()
+                .flatten()
+        });
+    if settings.provider == TranslationProvider::DeepL && api_key.is_none() {
+        bail!("DeepL requires a API key, see \"termusic-server credentials set\"");
+    }
+
+    let client = ClientBuilder::new()
+        .timeout(Duration::from_secs(20))
+        .build()?;
+
+    let mut translated: Vec<String> = texts.iter().map(|_| String::new()).collect();
+    let pending: Vec<usize> = (0..texts.len())
+        .filter(|idx| !texts[*idx].trim().is_empty())
+        .collect();
+
+    for chunk in pending.chunks(MAX_TEXTS_PER_REQUEST) {
+        let chunk_texts: Vec<&str> = chunk.iter().map(|idx| texts[*idx].as_str()).collect();
+        let body = request_body(settings.provider, &chunk_texts, &target, api_key.as_deref());
+
+        let mut request = match settings.provider {
+            TranslationProvider::DeepL => client.post(format!("{api_url}/v2/translate")),
+            TranslationProvider::None | TranslationProvider::LibreTranslate => {
+                client.post(format!("{api_url}/translate"))
+            }
+        };
+        if settings.provider == TranslationProvider::DeepL
+            && let Some(key) = &api_key
+        {
+            request = request.header("Authorization", format!("DeepL-Auth-Key {key}"));
+        }
+
+        let res = LIMITER
+            .send(request.json(&body))
+            .await
+            .context("request translation")?;
+        match res.status() {
+            StatusCode::OK => (),
+            StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED => {
+                bail!("The translation service rejected the API key")
+            }
+            status => bail!("The translation service responded with {status}"),
+        }
+
+        let lines = parse_response(settings.provider, &res.text().await?, chunk.len())?;
+        for (idx, line) in chunk.iter().zip(lines) {
+            translated[*idx] = line;
+        }
+    }
+
+    Ok(translated)
+}
+
+/// Get the JSON body of a request translating `texts` to `target`.
+fn request_body(
+    provider: TranslationProvider,
+    texts: &[&str],
+    target: &str,
+    api_key: Option<&str>,
+) -> Value {
+    match provider {
+        // the key is sent as a header instead
+        TranslationProvider::DeepL => json!({
+            "text": texts,
+            "target_lang": target.to_uppercase(),
+        }),
+        TranslationProvider::None | TranslationProvider::LibreTranslate => {
+            let mut body = json!({
+                "q": texts,
+                "source": "auto",
+                "target": target,
+                "format": "text",
+            });
+            if let Some(key) = api_key {
+                body["api_key"] = Value::from(key);
+            }
+
+            body
+        }
+    }
+}
+
+/// Parse the translated texts of a response, which have to be exactly `expected` many.
+fn parse_response(
+    provider: TranslationProvider,
+    data: &str,
+    expected: usize,
+) -> Result<Vec<String>> {
+    let value: Value = serde_json::from_str(data).context("parse translation response")?;
+
+    let texts: Option<Vec<String>> = match provider {
+        TranslationProvider::DeepL => {
+            value
+                .get("translations")
+                .and_then(Value::as_array)
+                .map(|v| {
+                    v.iter()
+                        .filter_map(|v| v.get("text")?.as_str().map(str::to_string))
+                        .collect()
+                })
+        }
+        TranslationProvider::None | TranslationProvider::LibreTranslate => value
+            .get("translatedText")
+            .and_then(Value::as_array)
+            .map(|v| {
+                v.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            }),
+    };
+
+    match texts {
+        Some(texts) if texts.len() == expected => Ok(texts),
+        Some(texts) => bail!("Expected {expected} translated texts, got {}", texts.len()),
+        None => bail!(
+            "Unexpected translation response: {}",
+            value.get("error").unwrap_or(&value)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::{locale_language, parse_response, request_body};
+    use crate::config::v2::tui::TranslationProvider;
+
+    #[test]
+    fn should_get_locale_language() {
+        assert_eq!(locale_language("de_DE.UTF-8").as_deref(), Some("de"));
+        assert_eq!(locale_language("pt_BR").as_deref(), Some("pt"));
+        assert_eq!(locale_language("en").as_deref(), Some("en"));
+        assert_eq!(locale_language("sr_RS@latin").as_deref(), Some("sr"));
+        assert_eq!(locale_language("C.UTF-8"), None);
+        assert_eq!(locale_language("POSIX"), None);
+    }
+
+    #[test]
+    fn should_build_requests() {
+        assert_eq!(
+            request_body(TranslationProvider::DeepL, &["Hello"], "de", Some("key")),
+            json!({"text": ["Hello"], "target_lang": "DE"})
+        );
+        assert_eq!(
+            request_body(
+                TranslationProvider::LibreTranslate,
+                &["Hello", "World"],
+                "de",
+                Some("key")
+            ),
+            json!({"q": ["Hello", "World"], "source": "auto", "target": "de", "format": "text", "api_key": "key"})
+        );
+    }
+
+    #[test]
+    fn should_parse_responses() {
+        let deepl = r#"{"translations": [{"detected_source_language": "EN", "text": "Hallo"}, {"text": "Welt"}]}"#;
+        assert_eq!(
+            parse_response(TranslationProvider::DeepL, deepl, 2).unwrap(),
+            ["Hallo", "Welt"]
+        );
+
+        let libre =
+            r#"{"translatedText": ["Hallo", "Welt"], "detectedLanguage": [{"language": "en"}]}"#;
+        assert_eq!(
+            parse_response(TranslationProvider::LibreTranslate, libre, 2).unwrap(),
+            ["Hallo", "Welt"]
+        );
+        assert!(parse_response(TranslationProvider::LibreTranslate, libre, 3).is_err());
+
+        let err = parse_response(
+            TranslationProvider::LibreTranslate,
+            r#"{"error": "Invalid API key"}"#,
+            1,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected translation response: \"Invalid API key\""
+        );
+    }
+}
@@ -4,6 +4,7 @@ use std::iter::FusedIterator;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
+use icu_normalizer::DecomposingNormalizer;
 use pinyin::ToPinyin;
 use rand::Rng;
 use unicode_segmentation::UnicodeSegmentation;
@@ -28,6 +29,89 @@ pub fn get_pin_yin(input: &str) -> String {
     b
 }
 
+/// Hepburn romaji for the hiragana `U+3041..=U+3096`, small kana are read like their full-size counterpart.
+const HIRAGANA_ROMAJI: [&str; 86] = [
+    "a", "a", "i", "i", "u", "u", "e", "e", "o", "o", // ぁ - お
+    "ka", "ga", "ki", "gi", "ku", "gu", "ke", "ge", "ko", "go", // か - ご
+    "sa", "za", "shi", "ji", "su", "zu", "se", "ze", "so", "zo", // さ - ぞ
+    "ta", "da", "chi", "ji", "tsu", "tsu", "zu", "te", "de", "to", "do", // た - ど
+    "na", "ni", "nu", "ne", "no", // な - の
+    "ha", "ba", "pa", "hi", "bi", "pi", "fu", "bu", "pu", "he", "be", "pe", "ho", "bo",
+    "po", // は - ぽ
+    "ma", "mi", "mu", "me", "mo", // ま - も
+    "ya", "ya", "yu", "yu", "yo", "yo", // ゃ - よ
+    "ra", "ri", "ru", "re", "ro", // ら - ろ
+    "wa", "wa", "i", "e", "wo", "n", "vu", "ka", "ke", // ゎ - ゖ
+];
+
+/// Get a key to sort `input` by, which ignores case and accents, like `Élan` sorting next to `elan`.
+///
+/// With `transliterate`, Chinese characters are sorted by their pinyin and Japanese kana by their romaji,
+/// instead of after all latin names.
+/// Compare keys with [`alphanumeric_sort::compare_str`] to also sort numbers naturally.
+#[must_use]
+pub fn get_sort_key(input: &str, transliterate: bool) -> String {
+    let transliterated;
+    let input = if transliterate {
+        transliterated = transliterate_cjk(input);
+        transliterated.as_str()
+    } else {
+        input
+    };
+
+    let mut key = String::with_capacity(input.len());
+    for char in DecomposingNormalizer::new_nfkd().normalize(input).chars() {
+        match char {
+            // combining marks, which are split off of accented letters by the decomposition
+            '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}' => (),
+            // letters that do not decompose
+            'ß' => key.push_str("ss"),
+            'æ' | 'Æ' => key.push_str("ae"),
+            'œ' | 'Œ' => key.push_str("oe"),
+            'þ' | 'Þ' => key.push_str("th"),
+            'ø' | 'Ø' => key.push('o'),
+            'đ' | 'Đ' => key.push('d'),
+            'ł' | 'Ł' => key.push('l'),
+            char => key.extend(char.to_lowercase()),
+        }
+    }
+
+    key
+}
+
+/// Replace Chinese characters with their pinyin and kana with their (approximate) romaji.
+fn transliterate_cjk(input: &str) -> String {
+    let mut res = String::with_capacity(input.len());
+    for (char, pinyin) in input.chars().zip(input.to_pinyin()) {
+        if let Some(pinyin) = pinyin {
+            res.push_str(pinyin.plain());
+            continue;
+        }
+
+        // katakana are at a fixed offset to their hiragana
+        let hiragana = match char {
+            '\u{30A1}'..='\u{30F6}' => u32::from(char) - 0x60,
+            // the long vowel mark does not change the order
+            '\u{30FC}' => continue,
+            _ => u32::from(char),
+        };
+
+        match hiragana
+            .checked_sub(0x3041)
+            .and_then(|idx| HIRAGANA_ROMAJI.get(idx as usize))
+        {
+            Some(romaji) => res.push_str(romaji),
+            None => res.push(char),
+        }
+    }
+
+    res
+}
+
 // TODO: decide filetype supported by backend instead of in library
 #[must_use]
 pub fn filetype_supported(path: &Path) -> bool {
@@ -65,6 +149,7 @@ pub fn is_playlist(path: &Path) -> bool {
     };
 
     matches!(ext, "m3u" | "m3u8" | "pls" | "asx" | "xspf")
+        || crate::playlist::share::is_share_file(path)
 }
 
 /// Get the parent path of the given `path`, if there is none use the tempdir
@@ -89,6 +174,33 @@ pub fn get_app_config_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Get whether the current process runs inside [Termux](https://termux.dev) on Android.
+#[must_use]
+pub fn is_termux() -> bool {
+    std::env::var_os("TERMUX_VERSION").is_some()
+        || std::env::var_os("PREFIX").is_some_and(|v| is_termux_prefix(&v))
+}
+
+/// Check if `prefix` is the Termux install prefix, like `/data/data/com.termux/files/usr`.
+fn is_termux_prefix(prefix: &OsStr) -> bool {
+    prefix.to_string_lossy().contains("/com.termux/")
+}
+
+/// Get the shared Music directory of Android inside Termux, which is accessible to other apps like file managers.
+///
+/// Prefers the `~/storage/music` link created by `termux-setup-storage`, which grants the storage permission.
+#[must_use]
+pub fn termux_audio_dir() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+
+    [
+        home.join("storage/music"),
+        home.join("storage/shared/Music"),
+    ]
+    .into_iter()
+    .find(|v| v.is_dir())
+}
+
 /// Get the termusic database path for [`new_database`](crate::new_database).
 pub fn get_app_new_database_path() -> Result<PathBuf> {
     let mut db_path = get_app_config_path().context("failed to get app configuration path")?;
@@ -347,6 +459,30 @@ mod tests {
         assert_eq!(get_pin_yin("Annett Louisan"), "ANNETT LOUISAN".to_string());
     }
 
+    #[test]
+    fn should_detect_termux_prefix() {
+        assert!(is_termux_prefix(OsStr::new(
+            "/data/data/com.termux/files/usr"
+        )));
+        assert!(!is_termux_prefix(OsStr::new("/usr")));
+        assert!(!is_termux_prefix(OsStr::new("/opt/com.termuxfake")));
+    }
+
+    #[test]
+    fn test_sort_key() {
+        assert_eq!(get_sort_key("Élan", false), "elan");
+        assert_eq!(get_sort_key("Beatles, The", false), "beatles, the");
+        assert_eq!(get_sort_key("Straße", false), "strasse");
+        assert_eq!(get_sort_key("Ｓｉｇｕｒ Ｒóｓ", false), "sigur ros");
+        assert_eq!(get_sort_key("陈一发儿", false), "陈一发儿");
+
+        assert_eq!(get_sort_key("陈一发儿", true), "chenyifaer");
+        assert_eq!(get_sort_key("Gala乐队", true), "galaledui");
+        assert_eq!(get_sort_key("ひらがな", true), "hiragana");
+        assert_eq!(get_sort_key("カタカナ", true), "katakana");
+        assert_eq!(get_sort_key("ラーメン", true), "ramen");
+    }
+
     #[test]
     fn test_substr() {
         // 0 length fallback
@@ -0,0 +1,437 @@
+//! Extract the audio stream of a video page with yt-dlp, so that it can be streamed without downloading it first, and
+//! list the videos of youtube playlists and channels.
+
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use parking_lot::Mutex;
+use serde_json::Value;
+
+use crate::invidious::YoutubeVideo;
+use crate::playlist::share::youtube_id;
+
+/// How long listing a page of a playlist may take, before yt-dlp is killed.
+const FLAT_PLAYLIST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a extracted stream is reused, youtube stream urls usually expire after about 6 hours.
+const EXTRACTED_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 4);
+
+/// The streams extracted by [`extract_audio_cached`], by page url.
+static EXTRACTED: LazyLock<Mutex<HashMap<String, (Instant, ExtractedAudio)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The audio stream of a video page, extracted with [`extract_audio`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedAudio {
+    /// The direct url of the audio stream, usually only valid for a few hours
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// Check if `url` is a video page that can be streamed with [`extract_audio`].
+///
+/// These are youtube videos, soundcloud tracks and bandcamp tracks.
+#[must_use]
+pub fn is_video_url(url: &str) -> bool {
+    youtube_id(url).is_some() || is_soundcloud_track(url) || is_bandcamp_track(url)
+}
+
+/// Split `url` into the host and the path, without the query.
+fn host_and_path(url: &str) -> Option<(&str, &str)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest.split(['?', '#']).next()?;
+
+    Some(rest.split_once('/').unwrap_or((rest, "")))
+}
+
+/// Check if `url` is a soundcloud track, like `https://soundcloud.com/artist/track`.
+fn is_soundcloud_track(url: &str) -> bool {
+    let Some((host, path)) = host_and_path(url) else {
+        return false;
+    };
+    if !matches!(
+        host,
+        "soundcloud.com" | "www.soundcloud.com" | "m.soundcloud.com"
+    ) {
+        return false;
+    }
+    let segments: Vec<&str> = path.split('/').filter(|v| !v.is_empty()).collect();
+
+    // "artist/sets/name" is a playlist, "artist/tracks" the list of uploads
+    segments.len() == 2 && !matches!(segments[1], "sets" | "tracks" | "albums" | "likes")
+}
+
+/// Check if `url` is a bandcamp track, like `https://artist.bandcamp.com/track/name`.
+fn is_bandcamp_track(url: &str) -> bool {
+    host_and_path(url).is_some_and(|(host, path)| {
+        host.ends_with(".bandcamp.com")
+            && path
+                .strip_prefix("track/")
+                .is_some_and(|v| !v.trim_end_matches('/').is_empty())
+    })
+}
+
+/// Extract the best audio stream of the video at `page_url` with yt-dlp.
+///
+/// This blocks until yt-dlp is finished, which usually takes a second or two.
+pub fn extract_audio(page_url: &str) -> Result<ExtractedAudio> {
+    let output = Command::new("yt-dlp")
+        .args([
+            "--no-playlist",
+            "--no-warnings",
+            "--socket-timeout",
+            "10",
+            "--format",
+            "bestaudio/best",
+            "--print",
+            "title",
+            "--print",
+            "urls",
+            page_url,
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .context("run yt-dlp")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "yt-dlp exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        );
+    }
+
+    parse_extracted(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Like [`extract_audio`], but reuse the stream of a recent extraction of `page_url`.
+///
+/// Returns the stream and whether it was reused. With `refresh` the stream is always extracted again, like when the reused url was rejected as expired.
+pub fn extract_audio_cached(page_url: &str, refresh: bool) -> Result<(ExtractedAudio, bool)> {
+    let now = Instant::now();
+    if !refresh
+        && let Some((extracted_at, extracted)) = EXTRACTED.lock().get(page_url)
+        && now.duration_since(*extracted_at) < EXTRACTED_MAX_AGE
+    {
+        return Ok((extracted.clone(), true));
+    }
+
+    let extracted = extract_audio(page_url)?;
+
+    let mut cache = EXTRACTED.lock();
+    cache.retain(|_, (extracted_at, _)| now.duration_since(*extracted_at) < EXTRACTED_MAX_AGE);
+    cache.insert(page_url.to_string(), (now, extracted.clone()));
+
+    Ok((extracted, false))
+}
+
+/// Parse the `--print title --print urls` output of yt-dlp.
+fn parse_extracted(output: &str) -> Result<ExtractedAudio> {
+    let mut lines = output.lines().map(str::trim);
+    let title = lines.next().filter(|v| !v.is_empty() && *v != "NA");
+    let Some(url) = lines.find(|v| v.starts_with("http")) else {
+        bail!("yt-dlp did not return a stream url");
+    };
+
+    Ok(ExtractedAudio {
+        url: url.to_string(),
+        title: title.map(ToString::to_string),
+    })
+}
+
+/// A youtube url listing videos instead of being one, see [`youtube_collection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YoutubeCollection {
+    /// A playlist, like `https://www.youtube.com/playlist?list=ID`
+    Playlist,
+    /// The uploads of a channel, like `https://www.youtube.com/@name` or `https://www.youtube.com/channel/ID`
+    Channel,
+}
+
+/// Get the path segments of a youtube channel url, like `["@name"]` or `["channel", "ID"]`.
+fn channel_segments(url: &str) -> Option<Vec<&str>> {
+    let path = youtube_path(url)?;
+    let path = path.split(['?', '#']).next()?;
+    let mut segments = path.split('/').filter(|v| !v.is_empty());
+
+    let first = segments.next()?;
+    if first.len() > 1 && first.starts_with('@') {
+        return Some(vec![first]);
+    }
+    if matches!(first, "channel" | "c" | "user") {
+        return Some(vec![first, segments.next()?]);
+    }
+
+    None
+}
+
+/// Get the part of a youtube url after the domain, like `playlist?list=ID` for `https://www.youtube.com/playlist?list=ID`.
+fn youtube_path(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest
+        .strip_prefix("www.")
+        .or_else(|| rest.strip_prefix("m."))
+        .or_else(|| rest.strip_prefix("music."))
+        .unwrap_or(rest);
+
+    rest.strip_prefix("youtube.com/")
+}
+
+/// Get which kind of youtube collection `url` is, [`None`] if it is not one, like a single video.
+#[must_use]
+pub fn youtube_collection(url: &str) -> Option<YoutubeCollection> {
+    let path = youtube_path(url.trim())?;
+
+    if let Some(query) = path.strip_prefix("playlist?") {
+        return query
+            .split('&')
+            .any(|v| v.len() > "list=".len() && v.starts_with("list="))
+            .then_some(YoutubeCollection::Playlist);
+    }
+
+    channel_segments(url.trim()).map(|_| YoutubeCollection::Channel)
+}
+
+/// Get the url of the uploads of the youtube channel at `url`, newest first.
+#[must_use]
+pub fn channel_uploads_url(url: &str) -> Option<String> {
+    let segments = channel_segments(url.trim())?;
+
+    Some(format!(
+        "https://www.youtube.com/{}/videos",
+        segments.join("/")
+    ))
+}
+
+/// List the videos `start..=end`, starting at 1, of the playlist at `url` with yt-dlp, without extracting them.
+///
+/// yt-dlp is killed if it does not finish within [`FLAT_PLAYLIST_TIMEOUT`].
+pub async fn flat_playlist(url: &str, start: u32, end: u32) -> Result<Vec<YoutubeVideo>> {
+    let child = tokio::process::Command::new("yt-dlp")
+        .args([
+            "--flat-playlist",
+            "--dump-json",
+            "--no-warnings",
+            "--playlist-items",
+            &format!("{start}:{end}"),
+            url,
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // the process is killed once the future is dropped on timeout
+        .kill_on_drop(true)
+        .spawn()
+        .context("run yt-dlp")?;
+
+    let Ok(output) = tokio::time::timeout(FLAT_PLAYLIST_TIMEOUT, child.wait_with_output()).await
+    else {
+        bail!("yt-dlp did not finish within {FLAT_PLAYLIST_TIMEOUT:?}");
+    };
+    let output = output.context("wait for yt-dlp")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "yt-dlp exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        );
+    }
+
+    Ok(parse_flat_playlist(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parse the `--dump-json` lines of a flat yt-dlp playlist or search, skipping entries that are not videos.
+#[must_use]
+pub fn parse_flat_playlist(output: &str) -> Vec<YoutubeVideo> {
+    output
+        .lines()
+        .filter_map(|line| parse_ytdlp_item(&serde_json::from_str(line).ok()?))
+        .collect()
+}
+
+/// Parse a single `--dump-json` entry of yt-dlp, [`None`] if it is not a video.
+fn parse_ytdlp_item(value: &Value) -> Option<YoutubeVideo> {
+    let str_field = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+
+    // flat entries usually only have a timestamp, full entries a "YYYYMMDD" date
+    let upload_date = str_field("upload_date")
+        .filter(|v| v.len() == 8 && v.bytes().all(|v| v.is_ascii_digit()))
+        .map(|v| format!("{}-{}-{}", &v[..4], &v[4..6], &v[6..]))
+        .or_else(|| {
+            let timestamp = value.get("timestamp").and_then(Value::as_i64)?;
+            let date = chrono::DateTime::from_timestamp(timestamp, 0)?;
+            Some(date.format("%Y-%m-%d").to_string())
+        });
+    // the thumbnails are ordered from the worst to the best
+    let thumbnail_url = str_field("thumbnail").or_else(|| {
+        let thumbnails = value.get("thumbnails")?.as_array()?;
+        let url = thumbnails.last()?.get("url")?.as_str()?;
+        Some(url.to_string())
+    });
+
+    Some(YoutubeVideo {
+        title: str_field("title")?,
+        video_id: str_field("id")?,
+        // live streams have no duration
+        length_seconds: value
+            .get("duration")
+            .and_then(Value::as_f64)
+            .and_then(|v| Duration::try_from_secs_f64(v).ok())
+            .map_or(0, |v| v.as_secs()),
+        uploader: str_field("channel").or_else(|| str_field("uploader")),
+        view_count: value.get("view_count").and_then(Value::as_u64),
+        upload_date,
+        thumbnail_url,
+        age_restricted: value
+            .get("age_limit")
+            .and_then(Value::as_u64)
+            .is_some_and(|v| v >= 18),
+        url: str_field("webpage_url"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        ExtractedAudio, YoutubeCollection, channel_uploads_url, is_video_url, parse_extracted,
+        parse_flat_playlist, youtube_collection,
+    };
+    use crate::invidious::YoutubeVideo;
+
+    #[test]
+    fn should_detect_video_urls() {
+        assert!(is_video_url("https://www.youtube.com/watch?v=abc"));
+        assert!(is_video_url("https://youtu.be/abc"));
+        assert!(is_video_url("http://music.youtube.com/watch?v=abc"));
+        assert!(!is_video_url("https://example.com/watch?v=abc"));
+        assert!(!is_video_url("https://www.youtube.com/"));
+        assert!(!is_video_url("/home/user/youtube.com/song.mp3"));
+
+        assert!(is_video_url("https://soundcloud.com/artist/some-set?in=x"));
+        assert!(!is_video_url("https://soundcloud.com/artist"));
+        assert!(!is_video_url("https://soundcloud.com/artist/sets/name"));
+        assert!(is_video_url("https://artist.bandcamp.com/track/name"));
+        assert!(!is_video_url("https://artist.bandcamp.com/album/name"));
+        assert!(!is_video_url("https://bandcamp.com/track/name"));
+    }
+
+    #[test]
+    fn should_parse_extracted() {
+        let output = "Some Title\nhttps://rr1.example.com/videoplayback?id=1\n";
+        assert_eq!(
+            parse_extracted(output).unwrap(),
+            ExtractedAudio {
+                url: "https://rr1.example.com/videoplayback?id=1".to_string(),
+                title: Some("Some Title".to_string()),
+            }
+        );
+
+        let output = "NA\nhttps://rr1.example.com/live\n";
+        assert_eq!(
+            parse_extracted(output).unwrap(),
+            ExtractedAudio {
+                url: "https://rr1.example.com/live".to_string(),
+                title: None,
+            }
+        );
+
+        assert!(parse_extracted("Title\n").is_err());
+    }
+
+    #[test]
+    fn should_detect_collections() {
+        assert_eq!(
+            youtube_collection("https://www.youtube.com/playlist?list=PL123"),
+            Some(YoutubeCollection::Playlist)
+        );
+        assert_eq!(
+            youtube_collection("https://music.youtube.com/playlist?feature=share&list=PL123"),
+            Some(YoutubeCollection::Playlist)
+        );
+        assert_eq!(
+            youtube_collection("https://www.youtube.com/@name"),
+            Some(YoutubeCollection::Channel)
+        );
+        assert_eq!(
+            youtube_collection("https://youtube.com/channel/UC123/videos"),
+            Some(YoutubeCollection::Channel)
+        );
+        assert_eq!(
+            youtube_collection("https://www.youtube.com/playlist?list="),
+            None
+        );
+        assert_eq!(
+            youtube_collection("https://www.youtube.com/watch?v=abc&list=PL123"),
+            None
+        );
+        assert_eq!(youtube_collection("https://www.youtube.com/channel/"), None);
+        assert_eq!(youtube_collection("https://example.com/@name"), None);
+    }
+
+    #[test]
+    fn should_get_channel_uploads_url() {
+        assert_eq!(
+            channel_uploads_url("https://www.youtube.com/@name/featured?si=1").as_deref(),
+            Some("https://www.youtube.com/@name/videos")
+        );
+        assert_eq!(
+            channel_uploads_url("http://m.youtube.com/c/name").as_deref(),
+            Some("https://www.youtube.com/c/name/videos")
+        );
+        assert_eq!(
+            channel_uploads_url("https://www.youtube.com/playlist?list=PL123"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_parse_flat_playlist() {
+        let output = r#"{"id": "abc", "title": "First", "duration": 61.5, "channel": "Artist", "view_count": 1234, "upload_date": "20240131", "age_limit": 18, "thumbnails": [{"url": "https://i.ytimg.com/small.jpg"}, {"url": "https://i.ytimg.com/big.jpg"}]}
+{"id": "def", "title": "Live", "duration": null, "uploader": "Someone", "timestamp": 1700000000, "thumbnail": "https://i.ytimg.com/live.jpg"}
+not json
+{"title": "no id"}
+"#;
+
+        assert_eq!(
+            parse_flat_playlist(output),
+            [
+                YoutubeVideo {
+                    title: "First".to_string(),
+                    length_seconds: 61,
+                    video_id: "abc".to_string(),
+                    uploader: Some("Artist".to_string()),
+                    view_count: Some(1234),
+                    upload_date: Some("2024-01-31".to_string()),
+                    thumbnail_url: Some("https://i.ytimg.com/big.jpg".to_string()),
+                    age_restricted: true,
+                    url: None,
+                },
+                YoutubeVideo {
+                    title: "Live".to_string(),
+                    length_seconds: 0,
+                    video_id: "def".to_string(),
+                    uploader: Some("Someone".to_string()),
+                    view_count: None,
+                    upload_date: Some("2023-11-14".to_string()),
+                    thumbnail_url: Some("https://i.ytimg.com/live.jpg".to_string()),
+                    age_restricted: false,
+                    url: None,
+                },
+            ]
+        );
+    }
+}
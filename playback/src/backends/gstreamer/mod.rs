@@ -14,7 +14,8 @@ use gstreamer::{ClockTime, StateChangeError, StateChangeSuccess};
 use gstreamer::{Element, SeekFlags, SeekType, event::Seek};
 use parking_lot::Mutex;
 use termusiclib::config::ServerOverlay;
-use termusiclib::track::{MediaTypes, Track};
+use termusiclib::track::{StreamLocation, Track};
+use termusiclib::ytdlp;
 use tokio::sync::mpsc;
 
 use crate::{MediaInfo, PlayerCmd, PlayerErrorType, PlayerProgress, PlayerTrait, Speed, Volume};
@@ -154,10 +155,10 @@ impl PlaybinWrap {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum PlayerInternalCmd {
     Eos,
-    Error(PlayerErrorType),
+    Error(PlayerErrorType, String),
     AboutToFinish,
     SkipNext,
     ReloadSpeed,
@@ -394,8 +395,10 @@ impl GStreamerBackend {
                             info!("Recoverable Error, sending Event");
                             eos_watcher.store(true, std::sync::atomic::Ordering::SeqCst);
 
-                            let _ = main_tx
-                                .blocking_send(PlayerInternalCmd::Error(PlayerErrorType::Current));
+                            let _ = main_tx.blocking_send(PlayerInternalCmd::Error(
+                                PlayerErrorType::Current,
+                                err.message().to_string(),
+                            ));
 
                             *lock = Some(current_uri);
                         }
@@ -464,8 +467,8 @@ impl GStreamerBackend {
                         error!("error in sending Eos: {e}");
                     }
                 }
-                PlayerInternalCmd::Error(ty) => {
-                    if let Err(e) = cmd_tx.send(PlayerCmd::Error(ty)) {
+                PlayerInternalCmd::Error(ty, reason) => {
+                    if let Err(e) = cmd_tx.send(PlayerCmd::Error(ty, reason)) {
                         error!("error in sending Error: {e}");
                     }
                 }
@@ -649,9 +652,16 @@ impl Drop for GStreamerBackend {
 
 /// Helper function to consistently set the `uri` on `playbin` from a [`Track`]
 fn set_uri_from_track(playbin: &PlaybinWrap, track: &Track) {
-    match track.inner() {
-        MediaTypes::Track(track_data) => playbin.set_uri(track_data.path().to_uri()),
-        MediaTypes::Radio(radio_track_data) => playbin.set_uri(radio_track_data.url()),
-        MediaTypes::Podcast(podcast_track_data) => playbin.set_uri(podcast_track_data.url()),
+    match track.source().stream() {
+        StreamLocation::File(path) => playbin.set_uri(path.to_uri()),
+        StreamLocation::Url(url) => playbin.set_uri(url),
+        StreamLocation::Extract(page_url) => match ytdlp::extract_audio(page_url) {
+            Ok(extracted) => playbin.set_uri(extracted.url),
+            Err(err) => {
+                error!("Failed to extract the audio stream of \"{page_url}\": {err:#}");
+                // let playbin report the error for the track
+                playbin.set_uri(page_url);
+            }
+        },
     }
 }
@@ -21,6 +21,29 @@ pub enum BackendSelect {
     Rusty,
 }
 
+impl BackendSelect {
+    /// All backends that are compiled-in.
+    pub const AVAILABLE: &[Self] = &[
+        #[cfg(feature = "mpv")]
+        Self::Mpv,
+        #[cfg(feature = "gst")]
+        Self::GStreamer,
+        Self::Rusty,
+    ];
+
+    /// Get the name of the backend, matching the config values.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            #[cfg(feature = "mpv")]
+            Self::Mpv => ConfigBackend::Mpv.as_str(),
+            #[cfg(feature = "gst")]
+            Self::GStreamer => ConfigBackend::Gstreamer.as_str(),
+            Self::Rusty => ConfigBackend::Rusty.as_str(),
+        }
+    }
+}
+
 /// Error for when [`ThemeColor`] parsing fails
 #[derive(Debug, Clone, PartialEq)]
 pub enum BackendSelectConvertError {
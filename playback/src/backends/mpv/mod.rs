@@ -12,7 +12,8 @@ use libmpv::{
 };
 use parking_lot::Mutex;
 use termusiclib::config::ServerOverlay;
-use termusiclib::track::{MediaTypes, Track};
+use termusiclib::config::v2::server::NormalizationMode;
+use termusiclib::track::{StreamLocation, Track};
 
 use crate::{MediaInfo, PlayerCmd, PlayerProgress, PlayerTrait, Speed, Volume};
 
@@ -23,6 +24,8 @@ pub struct MpvBackend {
     volume: u16,
     speed: i32,
     gapless: bool,
+    normalization: bool,
+    normalization_mode: NormalizationMode,
     command_tx: Sender<PlayerInternalCmd>,
     position: Arc<Mutex<Duration>>,
     total_duration: ArcTotalDuration,
@@ -36,6 +39,8 @@ enum PlayerInternalCmd {
     // GetProgress,
     Play(String),
     QueueNext(String),
+    /// The value of the mpv `replaygain` property, see [`replaygain_property`]
+    ReplayGain(&'static str),
     Resume,
     Seek(i64),
     SeekAbsolute(Duration),
@@ -51,6 +56,8 @@ impl MpvBackend {
         let volume = config.settings.player.volume;
         let speed = config.settings.player.speed;
         let gapless = config.settings.player.gapless;
+        let normalization = config.settings.player.normalization;
+        let normalization_mode = config.settings.player.normalization_mode;
         let position = Arc::new(Mutex::new(Duration::default()));
         let total_duration = Arc::new(Mutex::new(None));
         let media_title = Arc::new(Mutex::new(String::new()));
@@ -74,6 +81,10 @@ impl MpvBackend {
         let gapless_setting = if gapless { "yes" } else { "no" };
         mpv.set_property("gapless-audio", gapless_setting)
             .expect("gapless setting failed");
+        let _ = mpv.set_property(
+            "replaygain",
+            replaygain_property(normalization, normalization_mode),
+        );
 
         let icmd_tx = command_tx.clone();
         std::thread::Builder::new()
@@ -95,6 +106,8 @@ impl MpvBackend {
             volume,
             speed,
             gapless,
+            normalization,
+            normalization_mode,
             command_tx,
             position,
             total_duration,
@@ -153,8 +166,10 @@ impl MpvBackend {
                             // -17 = Unknown format
                             if matches!(raw_i32, -13 | -14 | -16 | -17) {
                                 // Note that mpv only errors for the current file and does not pre-evaluate / pre-emit errors for enqueuement
-                                let _ =
-                                    cmd_tx.send(PlayerCmd::Error(crate::PlayerErrorType::Current));
+                                let _ = cmd_tx.send(PlayerCmd::Error(
+                                    crate::PlayerErrorType::Current,
+                                    err.to_string(),
+                                ));
                             }
                         }
 
@@ -263,6 +278,9 @@ impl MpvBackend {
             PlayerInternalCmd::Volume(volume) => {
                 let _ = mpv.set_property("volume", i64::from(volume));
             }
+            PlayerInternalCmd::ReplayGain(mode) => {
+                let _ = mpv.set_property("replaygain", mode);
+            }
             PlayerInternalCmd::Pause => {
                 let _ = mpv.pause();
             }
@@ -324,10 +342,19 @@ fn format_duration(dur: Duration) -> String {
 }
 
 fn track_to_string(track: &Track) -> String {
-    match track.inner() {
-        MediaTypes::Track(track_data) => track_data.path().to_string_lossy().to_string(),
-        MediaTypes::Radio(radio_track_data) => radio_track_data.url().to_string(),
-        MediaTypes::Podcast(podcast_track_data) => podcast_track_data.url().to_string(),
+    match track.source().stream() {
+        StreamLocation::File(path) => path.to_string_lossy().to_string(),
+        // mpv extracts the audio of video pages itself with its ytdl hook
+        StreamLocation::Url(url) | StreamLocation::Extract(url) => url.to_string(),
+    }
+}
+
+/// Get the value of the mpv `replaygain` property, mpv reads the `ReplayGain` tags itself.
+fn replaygain_property(normalization: bool, mode: NormalizationMode) -> &'static str {
+    match (normalization, mode) {
+        (false, _) => "no",
+        (true, NormalizationMode::Track) => "track",
+        (true, NormalizationMode::Album) => "album",
     }
 }
 
@@ -405,6 +432,20 @@ impl PlayerTrait for MpvBackend {
         self.gapless = to;
     }
 
+    fn normalization(&self) -> bool {
+        self.normalization
+    }
+
+    fn set_normalization(&mut self, to: bool) {
+        self.normalization = to;
+        let _ = self
+            .command_tx
+            .send(PlayerInternalCmd::ReplayGain(replaygain_property(
+                to,
+                self.normalization_mode,
+            )));
+    }
+
     fn skip_one(&mut self) {
         let _ = self.command_tx.send(PlayerInternalCmd::Eos);
     }
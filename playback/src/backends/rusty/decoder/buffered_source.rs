@@ -53,7 +53,6 @@ impl BufferedSource {
     }
 
     /// Create a new Buffered-Source with [`FILEBUF_SIZE_DEFAULT`]
-    #[expect(unused)]
     pub fn new_default_size(file: File) -> Self {
         Self::new(
             file,
@@ -1,11 +1,11 @@
-use std::{fmt, io::ErrorKind, num::NonZeroU64, sync::LazyLock, time::Duration};
+use std::{borrow::Cow, fmt, io::ErrorKind, num::NonZeroU64, sync::LazyLock, time::Duration};
 
 use symphonia::{
     core::{
-        audio::{AudioBufferRef, SampleBuffer, SignalSpec},
+        audio::{AudioBuffer, AudioBufferRef, SampleBuffer, Signal, SignalSpec},
         codecs::{self, CODEC_TYPE_NULL, CodecParameters, CodecRegistry},
         errors::Error,
-        formats::{FormatOptions, FormatReader, SeekMode, SeekTo, Track},
+        formats::{FormatOptions, FormatReader, Packet, SeekMode, SeekTo, Track},
         io::MediaSourceStream,
         meta::{MetadataOptions, MetadataRevision, StandardTagKey, Value},
         probe::{Hint, ProbeResult, ProbedMetadata},
@@ -13,6 +13,7 @@ use symphonia::{
     },
     default::{get_probe, register_enabled_codecs},
 };
+use termusiclib::new_database::track_ops::DamagedRegion;
 use tokio::sync::mpsc;
 
 use super::{Source, source::SampleType};
@@ -75,6 +76,210 @@ impl MediaTitleTxWrap {
     }
 }
 
+/// Sender for the regions a salvaging decoder muted, see [`Symphonia::new`].
+pub type DamageTx = mpsc::UnboundedSender<DamagedRegion>;
+
+/// How many errors in a row are salvaged before giving up, as the file is then likely not decodable at all.
+const MAX_SALVAGE_ERRORS: usize = 100;
+
+/// State of the salvage mode, which mutes corrupt packets instead of aborting the track.
+struct Salvage {
+    damage_tx: DamageTx,
+    /// The spec to render the silence with, the one of the last decoded packet
+    spec: Option<SignalSpec>,
+    /// The end of the last packet, to place damage without a timestamp
+    position: Duration,
+    /// The region currently extended by consecutive corrupt packets
+    region: Option<DamagedRegion>,
+    /// The errors since the last decoded packet
+    errors: usize,
+    /// The silence played instead of a corrupt packet
+    silence: AudioBuffer<SampleType>,
+}
+
+impl Salvage {
+    fn new(damage_tx: DamageTx, params: &CodecParameters) -> Self {
+        let spec = params
+            .sample_rate
+            .zip(params.channels)
+            .map(|(rate, channels)| SignalSpec::new(rate, channels));
+
+        Self {
+            damage_tx,
+            spec,
+            position: Duration::ZERO,
+            region: None,
+            errors: 0,
+            silence: AudioBuffer::unused(),
+        }
+    }
+
+    /// Count a error, returning whether to continue decoding.
+    fn count_error(&mut self) -> bool {
+        self.errors += 1;
+
+        self.errors <= MAX_SALVAGE_ERRORS
+    }
+
+    /// Extend the current damaged region, or start a new one.
+    fn mark_damaged(&mut self, start: Duration, end: Duration) {
+        let region = self
+            .region
+            .get_or_insert(DamagedRegion { start, end: start });
+        region.end = region.end.max(end);
+        self.position = self.position.max(end);
+    }
+
+    /// Report the current damaged region, if there is one, as the damage ended.
+    fn finish_region(&mut self) {
+        if let Some(region) = self.region.take() {
+            warn!("Muted damaged region {region} after {} errors", self.errors);
+            let _ = self.damage_tx.send(region);
+        }
+    }
+
+    /// Mark a corrupt `packet` as damaged and get the silence to play instead with its timestamp, so that the rest of the
+    /// track stays in time.
+    fn mute_packet(
+        &mut self,
+        packet: &Packet,
+        time_base: Option<TimeBase>,
+        err: &str,
+    ) -> Option<(AudioBufferRef<'_>, Option<Duration>)> {
+        let (start, end) = match time_base {
+            Some(tb) => (
+                Duration::from(tb.calc_time(packet.ts())),
+                Duration::from(tb.calc_time(packet.ts() + packet.dur())),
+            ),
+            None => (self.position, self.position),
+        };
+        self.mark_damaged(start, end);
+        debug!("Muting corrupt packet at {start:?}: {err}");
+
+        let frames = match (time_base, self.spec) {
+            (Some(_), Some(spec)) => {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let frames =
+                    (end.saturating_sub(start).as_secs_f64() * f64::from(spec.rate)) as usize;
+                frames
+            }
+            _ => usize::try_from(packet.dur()).unwrap_or_default(),
+        };
+
+        self.silence(frames).map(|silence| (silence, Some(start)))
+    }
+
+    /// Render the silence for a corrupt packet of `frames` length, [`None`] if the spec is not known yet.
+    fn silence(&mut self, frames: usize) -> Option<AudioBufferRef<'_>> {
+        let spec = self.spec?;
+        if frames == 0 {
+            return None;
+        }
+
+        if self.silence.capacity() < frames || *self.silence.spec() != spec {
+            self.silence = AudioBuffer::new(frames as u64, spec);
+        }
+        self.silence.clear();
+        self.silence.render_silence(Some(frames));
+
+        Some(AudioBufferRef::F32(Cow::Borrowed(&self.silence)))
+    }
+}
+
+impl Drop for Salvage {
+    fn drop(&mut self) {
+        self.finish_region();
+    }
+}
+
+/// Encoder delay and padding which the demuxer does not trim itself, like the iTunes `iTunSMPB` tag of mp4 files.
+///
+/// The LAME / Xing header of mp3 files and the ogg pre-skip are already trimmed by symphonia with `enable_gapless`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EncoderTrim {
+    /// The frames the encoder inserted before the audio
+    delay: u64,
+    /// The frames of actual audio after the delay, everything after is padding
+    frames: u64,
+}
+
+impl EncoderTrim {
+    /// The name of the iTunes freeform tag, prefixed with its namespace like `com.apple.iTunes:iTunSMPB`.
+    const ITUNSMPB: &'static str = "iTunSMPB";
+
+    /// Find the iTunes gapless info in the tags of `metadata`, with `n_frames` as the length of the track, including
+    /// delay and padding.
+    fn from_metadata(metadata: &MetadataRevision, n_frames: Option<u64>) -> Option<Self> {
+        metadata
+            .tags()
+            .iter()
+            .filter(|v| v.key.ends_with(Self::ITUNSMPB))
+            .find_map(|v| match &v.value {
+                Value::String(value) => Self::parse_itunsmpb(value, n_frames),
+                _ => None,
+            })
+    }
+
+    /// Parse a `iTunSMPB` value like ` 00000000 00000840 000001CA 00000000000A5DF6 ...`: hex fields of which the second
+    /// is the delay, the third the padding and the fourth the amount of actual audio frames.
+    fn parse_itunsmpb(value: &str, n_frames: Option<u64>) -> Option<Self> {
+        let mut fields = value
+            .split_whitespace()
+            .skip(1)
+            .map(|v| u64::from_str_radix(v, 16).ok());
+        let delay = fields.next()??;
+        let padding = fields.next()??;
+        let frames = match fields.next()?? {
+            // some encoders leave the amount empty
+            0 => n_frames?.checked_sub(delay + padding)?,
+            frames => frames,
+        };
+
+        if delay == 0 && padding == 0 {
+            return None;
+        }
+
+        Some(Self { delay, frames })
+    }
+
+    /// Get how many frames to cut from the start and the end of a decoded buffer of `len` frames, which starts at
+    /// frame `start` of the track.
+    #[allow(clippy::cast_possible_truncation)] // both are at most "len", which is a usize
+    fn cut(&self, start: u64, len: usize) -> (usize, usize) {
+        let len_u64 = len as u64;
+        let end = start + len_u64;
+        let cut_start = self.delay.saturating_sub(start).min(len_u64);
+        let cut_end = end
+            .saturating_sub(self.delay + self.frames)
+            .min(len_u64 - cut_start);
+
+        (cut_start as usize, cut_end as usize)
+    }
+
+    /// Get how many frames to cut from the start and the end of a decoded packet of `len` frames at `rate`, which
+    /// starts at `ts`.
+    fn cut_packet(
+        &self,
+        len: usize,
+        rate: u32,
+        ts: u64,
+        time_base: Option<TimeBase>,
+    ) -> (usize, usize) {
+        let rate = u64::from(rate);
+        let start = match time_base {
+            Some(tb) => ts * rate * u64::from(tb.numer) / u64::from(tb.denom),
+            None => ts,
+        };
+
+        self.cut(start, len)
+    }
+
+    /// Get the duration of the actual audio at `rate`.
+    fn duration(&self, rate: u32) -> Duration {
+        Duration::from_nanos(self.frames.saturating_mul(1_000_000_000) / u64::from(rate))
+    }
+}
+
 /// Custom registry for termusic, which might use extensions
 static CODEC_REGISTRY: LazyLock<CodecRegistry> = LazyLock::new(|| {
     let mut registry = CodecRegistry::new();
@@ -89,11 +294,15 @@ pub struct Symphonia {
     current_frame_offset: usize,
     probed: ProbeResult,
     buffer: SampleBuffer<SampleType>,
+    /// The end of the samples in `buffer` to play, the rest is encoder padding, see [`EncoderTrim`].
+    buffer_end: usize,
     spec: SignalSpec,
     duration: Option<Duration>,
     track_id: u32,
     time_base: Option<TimeBase>,
     seek_required_ts: Option<NonZeroU64>,
+    salvage: Option<Salvage>,
+    trim: Option<EncoderTrim>,
 
     media_title_tx: MediaTitleTxWrap,
 }
@@ -102,19 +311,23 @@ impl Symphonia {
     /// Create a new symphonia decoder.
     ///
     /// The returned `Option<MediaTitleRx>` is always `Some` if parameter `media_title` is `true`.
+    ///
+    /// With `salvage`, corrupt packets are muted instead of aborting the track, and the muted regions are sent to it.
     #[inline]
     pub fn new(
         mss: MediaSourceStream,
         gapless: bool,
         media_title: bool,
+        salvage: Option<DamageTx>,
     ) -> Result<(Self, Option<MediaTitleRx>), SymphoniaDecoderError> {
-        Self::init(mss, gapless, media_title)
+        Self::init(mss, gapless, media_title, salvage)
     }
 
     fn init(
         mss: MediaSourceStream,
         gapless: bool,
         media_title: bool,
+        salvage: Option<DamageTx>,
     ) -> Result<(Self, Option<MediaTitleRx>), SymphoniaDecoderError> {
         let mut probed = get_probe().format(
             &Hint::default(),
@@ -148,9 +361,39 @@ impl Symphonia {
             &codecs::DecoderOptions { verify: true },
         )?;
 
-        let duration = Self::get_duration(&track.codec_params);
+        let mut duration = Self::get_duration(&track.codec_params);
         let track_id = track.id;
         let time_base = track.codec_params.time_base;
+        let mut salvage = salvage.map(|tx| Salvage::new(tx, &track.codec_params));
+        // only if the demuxer does not already trim the delay and padding itself
+        let find_trim =
+            gapless && track.codec_params.delay.is_none() && track.codec_params.padding.is_none();
+        let n_frames = track.codec_params.n_frames;
+        let sample_rate = track.codec_params.sample_rate;
+
+        let trim = if find_trim {
+            probed
+                .format
+                .metadata()
+                .current()
+                .and_then(|v| EncoderTrim::from_metadata(v, n_frames))
+                .or_else(|| {
+                    probed
+                        .metadata
+                        .get()
+                        .as_ref()
+                        .and_then(|v| v.current())
+                        .and_then(|v| EncoderTrim::from_metadata(v, n_frames))
+                })
+        } else {
+            None
+        };
+        if let Some(trim) = trim {
+            debug!("Trimming encoder delay and padding: {trim:?}");
+            if let Some(rate) = sample_rate {
+                duration = Some(trim.duration(rate));
+            }
+        }
         let mut media_title_tx = MediaTitleTxWrap::new();
 
         let media_title_rx = if media_title {
@@ -161,7 +404,7 @@ impl Symphonia {
 
         // decode the first part, to get the spec and initial buffer
         let mut buffer = None;
-        let DecodeLoopResult { spec } = decode_loop(
+        let DecodeLoopResult { spec, cut } = decode_loop(
             &mut *probed.format,
             &mut *decoder,
             BufferInputType::New(&mut buffer),
@@ -170,23 +413,29 @@ impl Symphonia {
             &mut media_title_tx,
             &mut probed.metadata,
             &mut None,
+            &mut salvage,
+            trim,
         )?
         .ok_or(SymphoniaDecoderError::UnexpectedEOFInit)?;
 
         // safe to unwrap because "decode_loop" ensures it will be set
         let buffer = buffer.unwrap();
+        let buffer_end = buffer.len() - cut.1;
 
         Ok((
             Self {
                 decoder,
-                current_frame_offset: 0,
+                current_frame_offset: cut.0,
                 probed,
                 buffer,
+                buffer_end,
                 spec,
                 duration,
                 track_id,
                 time_base,
                 seek_required_ts: None,
+                salvage,
+                trim,
 
                 media_title_tx,
             },
@@ -235,7 +484,7 @@ impl Symphonia {
     /// `None` means End-of-File (EOF/EOS).
     pub fn decode_once(&mut self) -> Option<()> {
         if self.exhausted_buffer() {
-            let DecodeLoopResult { spec } = decode_loop(
+            let DecodeLoopResult { spec, cut } = decode_loop(
                 &mut *self.probed.format,
                 &mut *self.decoder,
                 BufferInputType::Existing(&mut self.buffer),
@@ -244,13 +493,16 @@ impl Symphonia {
                 &mut self.media_title_tx,
                 &mut self.probed.metadata,
                 &mut self.seek_required_ts,
+                &mut self.salvage,
+                self.trim,
             )
             .inspect_err(|err| warn!("Error while decoding: {err:#?}"))
             .ok()??;
 
             self.spec = spec;
 
-            self.current_frame_offset = 0;
+            self.current_frame_offset = cut.0;
+            self.buffer_end = self.buffer.len() - cut.1;
         }
 
         Some(())
@@ -258,7 +510,7 @@ impl Symphonia {
 
     /// Get whether the current buffer is used up.
     pub fn exhausted_buffer(&self) -> bool {
-        self.buffer.samples().is_empty() || self.current_frame_offset == self.buffer.len()
+        self.samples().is_empty() || self.current_frame_offset >= self.buffer_end
     }
 
     /// Increase the offset from which to read the buffer from.
@@ -277,26 +529,29 @@ impl Symphonia {
         #[allow(unsafe_code)]
         unsafe {
             // re-interpret the SampleType slice as a u8 slice with the same byte-length.
-            let len = size_of_val(self.buffer.samples());
-            std::slice::from_raw_parts(
-                self.buffer.samples()[self.current_frame_offset..]
-                    .as_ptr()
-                    .cast::<u8>(),
-                len,
-            )
+            let samples = self.get_buffer();
+            std::slice::from_raw_parts(samples.as_ptr().cast::<u8>(), size_of_val(samples))
         }
     }
 
     /// Get the current buffer, but only the part has not been read yet.
     pub fn get_buffer(&self) -> &[SampleType] {
-        &self.buffer.samples()[self.current_frame_offset..]
+        self.samples()
+            .get(self.current_frame_offset..)
+            .unwrap_or_default()
+    }
+
+    /// Get the samples of the current buffer to play, without the encoder padding.
+    #[inline]
+    fn samples(&self) -> &[SampleType] {
+        &self.buffer.samples()[..self.buffer_end]
     }
 }
 
 impl Source for Symphonia {
     #[inline]
     fn current_span_len(&self) -> Option<usize> {
-        Some(self.buffer.samples().len())
+        Some(self.samples().len())
     }
 
     #[inline]
@@ -328,6 +583,7 @@ impl Source for Symphonia {
                 // clear sample buffer after seek
                 self.current_frame_offset = 0;
                 self.buffer.clear();
+                self.buffer_end = 0;
 
                 // Coarse seeking may seek (slightly) beyond the requested ts, so it may not actually need to be set
                 if seeked_to.required_ts > seeked_to.actual_ts {
@@ -341,6 +597,12 @@ impl Source for Symphonia {
                     self.decoder.reset();
                 }
 
+                if let Some(salvage) = &mut self.salvage {
+                    // damage does not continue across a seek
+                    salvage.finish_region();
+                    salvage.position = pos;
+                }
+
                 Ok(())
             }
             Err(_) => Ok(()),
@@ -355,7 +617,7 @@ impl Iterator for Symphonia {
     fn next(&mut self) -> Option<Self::Item> {
         self.decode_once()?;
 
-        let sample = *self.buffer.samples().get(self.current_frame_offset)?;
+        let sample = *self.samples().get(self.current_frame_offset)?;
         self.advance_offset(1);
 
         Some(sample)
@@ -423,6 +685,8 @@ impl From<symphonia::core::errors::Error> for SymphoniaDecoderError {
 #[derive(Debug)]
 struct DecodeLoopResult {
     spec: SignalSpec,
+    /// The samples to cut from the start and the end of the buffer, see [`EncoderTrim`].
+    cut: (usize, usize),
 }
 
 // is there maybe a better option for this?
@@ -442,7 +706,7 @@ impl std::fmt::Debug for BufferInputType<'_> {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 /// Decode until finding a valid packet and get the samples from it
 ///
 /// If [`BufferInputType::New`] is used, it is guaranteed to be [`Some`] if function result is [`Ok`].
@@ -457,10 +721,12 @@ fn decode_loop(
     media_title_tx: &mut MediaTitleTxWrap,
     probed: &mut ProbedMetadata,
     seek_required_ts: &mut Option<NonZeroU64>,
+    salvage: &mut Option<Salvage>,
+    trim: Option<EncoderTrim>,
 ) -> Result<Option<DecodeLoopResult>, symphonia::core::errors::Error> {
-    let (audio_buf, elapsed) = loop {
+    let (audio_buf, elapsed, cut) = loop {
         // Note: this matching looks quite messy, but will be cleaned-up with symphonia 0.6
-        let Some(packet) = format.next_packet().map(Some).or_else(|err| {
+        let packet = format.next_packet().map(Some).or_else(|err| {
             match err {
                 // Before Symphonia 0.6, expected EOF / EOS is badly implemented
                 // the matching to the string is required, as there are other types of actually unexpected EOF's
@@ -472,8 +738,21 @@ fn decode_loop(
                 }
                 _ => Err(err),
             }
-        })?
-        else {
+        });
+        let packet = match (packet, salvage.as_mut()) {
+            (Ok(packet), _) => packet,
+            // the demuxer can usually resync to the next packet after damaged data
+            (Err(Error::DecodeError(err)), Some(salvage)) => {
+                if !salvage.count_error() {
+                    return Err(Error::DecodeError(err));
+                }
+                warn!("Skipping damaged data: {err}");
+                salvage.mark_damaged(salvage.position, salvage.position);
+                continue;
+            }
+            (Err(err), _) => return Err(err),
+        };
+        let Some(packet) = packet else {
             // indicated no more packets to decode, also End-Of-Stream
             match buffer {
                 BufferInputType::New(Some(sample_buffer))
@@ -512,10 +791,42 @@ fn decode_loop(
                 let ts = packet.ts();
                 let elapsed = time_base.map(|tb| Duration::from(tb.calc_time(ts)));
 
-                break (audio_buf, elapsed);
+                // only cut the samples once copied, to not copy the decoded buffer
+                let cut = trim.map_or((0, 0), |trim| {
+                    trim.cut_packet(audio_buf.frames(), audio_buf.spec().rate, ts, time_base)
+                });
+                // the whole packet was delay or padding
+                if cut.0 + cut.1 >= audio_buf.frames() {
+                    continue;
+                }
+
+                if let Some(salvage) = salvage.as_mut() {
+                    salvage.finish_region();
+                    salvage.errors = 0;
+                    salvage.spec = Some(*audio_buf.spec());
+                    if let Some(tb) = time_base {
+                        salvage.position = tb.calc_time(ts + packet.dur()).into();
+                    }
+                }
+
+                break (audio_buf, elapsed, cut);
             }
             Err(Error::DecodeError(err)) => {
-                info!("Non-fatal Decoder Error: {err}");
+                let Some(salvage) = salvage.as_mut() else {
+                    info!("Non-fatal Decoder Error: {err}");
+                    continue;
+                };
+                if !salvage.count_error() {
+                    return Err(Error::DecodeError(err));
+                }
+
+                if let Some((muted, elapsed)) = salvage.mute_packet(&packet, time_base, err) {
+                    break (muted, elapsed, (0, 0));
+                }
+            }
+            Err(Error::ResetRequired) if salvage.is_some() => {
+                // a track change inside the stream, like in a chained ogg file
+                decoder.reset();
             }
             Err(err) => return Err(err),
         }
@@ -535,6 +846,8 @@ fn decode_loop(
     }
 
     let spec = *audio_buf.spec();
+    let channels = spec.channels.count();
+    let cut = (cut.0 * channels, cut.1 * channels);
 
     match buffer {
         BufferInputType::New(buffer) => {
@@ -545,7 +858,7 @@ fn decode_loop(
         }
     }
 
-    Ok(Some(DecodeLoopResult { spec }))
+    Ok(Some(DecodeLoopResult { spec, cut }))
 }
 
 /// Do container metadata / track start metadata
@@ -602,3 +915,61 @@ fn find_title_metadata(metadata: &MetadataRevision) -> Option<&String> {
             }
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::EncoderTrim;
+
+    #[test]
+    fn should_parse_itunsmpb() {
+        let value =
+            " 00000000 00000840 000001CA 00000000000A5DF6 00000000 00000000 00000000 00000000";
+        assert_eq!(
+            EncoderTrim::parse_itunsmpb(value, None),
+            Some(EncoderTrim {
+                delay: 0x840,
+                frames: 0xA_5DF6,
+            })
+        );
+
+        // the amount of frames is computed from the track length if missing
+        let value = " 00000000 00000840 000001CA 0000000000000000";
+        assert_eq!(EncoderTrim::parse_itunsmpb(value, None), None);
+        assert_eq!(
+            EncoderTrim::parse_itunsmpb(value, Some(10_000)),
+            Some(EncoderTrim {
+                delay: 0x840,
+                frames: 10_000 - 0x840 - 0x1CA,
+            })
+        );
+
+        assert_eq!(
+            EncoderTrim::parse_itunsmpb(" 00000000 00000000 00000000 00000100", None),
+            None
+        );
+        assert_eq!(EncoderTrim::parse_itunsmpb("garbage", None), None);
+    }
+
+    #[test]
+    fn should_cut_delay_and_padding() {
+        let trim = EncoderTrim {
+            delay: 1500,
+            frames: 3000,
+        };
+
+        // entirely delay
+        assert_eq!(trim.cut(0, 1024), (1024, 0));
+        // partially delay
+        assert_eq!(trim.cut(1024, 1024), (476, 0));
+        // only audio
+        assert_eq!(trim.cut(2048, 1024), (0, 0));
+        // partially padding, the audio ends at 4500
+        assert_eq!(trim.cut(4096, 1024), (0, 620));
+        // entirely padding
+        assert_eq!(trim.cut(5120, 1024), (0, 1024));
+        // delay and padding in one buffer
+        assert_eq!(trim.cut(0, 8192), (1500, 3692));
+    }
+}
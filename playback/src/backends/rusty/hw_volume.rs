@@ -0,0 +1,137 @@
+//! Control the system mixer volume, instead of only the software gain.
+
+#[cfg(target_os = "linux")]
+use anyhow::Context as _;
+use anyhow::Result;
+
+/// A handle to a system mixer control, like ALSA's `Master`.
+///
+/// Only ALSA is supported, which also controls sound servers through their ALSA plugins.
+#[derive(Debug)]
+pub struct HardwareVolume {
+    #[cfg(target_os = "linux")]
+    mixer: parking_lot::Mutex<alsa::mixer::Mixer>,
+    #[cfg(target_os = "linux")]
+    control: String,
+    #[cfg(not(target_os = "linux"))]
+    never: std::convert::Infallible,
+}
+
+#[cfg(target_os = "linux")]
+impl HardwareVolume {
+    /// Open the mixer `control` (like `Master`) on `device` (like `default`).
+    ///
+    /// # Errors
+    ///
+    /// - if the mixer cannot be opened
+    /// - if the control does not exist or does not have a playback volume
+    pub fn open(device: &str, control: &str) -> Result<Self> {
+        let mixer = alsa::mixer::Mixer::new(device, false)
+            .with_context(|| format!("failed to open mixer {device:#?}"))?;
+        let this = Self {
+            mixer: parking_lot::Mutex::new(mixer),
+            control: control.to_string(),
+        };
+        // check that the control is usable
+        this.with_selem(|_| Ok(()))?;
+
+        Ok(this)
+    }
+
+    /// Run `fun` with the mixer control, after applying changes made by other programs.
+    fn with_selem<T>(&self, fun: impl FnOnce(&alsa::mixer::Selem<'_>) -> Result<T>) -> Result<T> {
+        let mixer = self.mixer.lock();
+        mixer.handle_events()?;
+        let selem = mixer
+            .find_selem(&alsa::mixer::SelemId::new(&self.control, 0))
+            .with_context(|| format!("mixer control {:#?} not found", self.control))?;
+        if !selem.has_playback_volume() {
+            anyhow::bail!("mixer control {:#?} has no playback volume", self.control);
+        }
+
+        fun(&selem)
+    }
+
+    /// Get the current volume in percent.
+    pub fn volume(&self) -> Result<u16> {
+        self.with_selem(|selem| {
+            let (min, max) = selem.get_playback_volume_range();
+            let value = selem.get_playback_volume(alsa::mixer::SelemChannelId::mono())?;
+
+            Ok(to_percent(value, min, max))
+        })
+    }
+
+    /// Set the volume of all channels in percent.
+    pub fn set_volume(&self, volume: u16) -> Result<()> {
+        self.with_selem(|selem| {
+            let (min, max) = selem.get_playback_volume_range();
+            selem.set_playback_volume_all(from_percent(volume, min, max))?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl HardwareVolume {
+    /// Hardware volume is only supported on linux.
+    ///
+    /// # Errors
+    ///
+    /// Always.
+    pub fn open(_device: &str, _control: &str) -> Result<Self> {
+        anyhow::bail!("hardware volume is only supported on linux (ALSA)")
+    }
+
+    pub fn volume(&self) -> Result<u16> {
+        match self.never {}
+    }
+
+    pub fn set_volume(&self, _volume: u16) -> Result<()> {
+        match self.never {}
+    }
+}
+
+/// Convert the mixer `value` in the range `min..=max` to percent.
+#[cfg_attr(not(target_os = "linux"), expect(dead_code))]
+fn to_percent(value: i64, min: i64, max: i64) -> u16 {
+    if max <= min {
+        return 0;
+    }
+    let percent = ((value.clamp(min, max) - min) * 100 + (max - min) / 2) / (max - min);
+
+    u16::try_from(percent).unwrap_or(100)
+}
+
+/// Convert `percent` to a mixer value in the range `min..=max`.
+#[cfg_attr(not(target_os = "linux"), expect(dead_code))]
+fn from_percent(percent: u16, min: i64, max: i64) -> i64 {
+    min + (max - min) * i64::from(percent.min(100)) / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_percent, to_percent};
+
+    #[test]
+    fn should_convert_percent() {
+        assert_eq!(to_percent(0, 0, 65536), 0);
+        assert_eq!(to_percent(65536, 0, 65536), 100);
+        assert_eq!(to_percent(32768, 0, 65536), 50);
+        assert_eq!(to_percent(-20, -10, 10), 0);
+        assert_eq!(to_percent(5, 5, 5), 0);
+
+        assert_eq!(from_percent(0, 0, 87), 0);
+        assert_eq!(from_percent(100, 0, 87), 87);
+        assert_eq!(from_percent(150, 0, 87), 87);
+        assert_eq!(from_percent(50, -10, 10), 0);
+
+        for percent in 0..=100 {
+            assert_eq!(
+                to_percent(from_percent(percent, 0, 65536), 0, 65536),
+                percent
+            );
+        }
+    }
+}
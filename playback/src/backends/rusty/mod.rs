@@ -1,6 +1,6 @@
 use std::fmt::Display;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -13,9 +13,9 @@ use rodio::OutputStreamBuilder;
 use rodio::Source;
 use std::num::{NonZeroU16, NonZeroUsize};
 use stream_download::http::{
-    HttpStream,
+    HttpStream, HttpStreamError,
     reqwest::{
-        Client,
+        Client, StatusCode,
         header::{HeaderMap, HeaderValue},
     },
 };
@@ -28,12 +28,16 @@ use symphonia::core::io::{
     MediaSource, MediaSourceStream, MediaSourceStreamOptions, ReadOnlySource,
 };
 use termusiclib::config::SharedServerSettings;
-use termusiclib::track::{MediaTypes, Track};
+use termusiclib::config::v2::server::NormalizationMode;
+use termusiclib::config::v2::server::backends::{CrossfadeCurve, VolumeControl};
+use termusiclib::config::v2::server::equalizer::BANDS;
+use termusiclib::track::{StreamLocation, Track, TrackData};
+use termusiclib::ytdlp::{self, ExtractedAudio};
 use tokio::runtime::Handle;
 use tokio::select;
 use tokio::sync::oneshot;
 
-use crate::backends::rusty::decoder::SymphoniaDecoderError;
+use crate::backends::rusty::decoder::{DamageTx, SymphoniaDecoderError};
 use crate::{
     MediaInfo, PlayerCmd, PlayerCmdCallbackSender, PlayerCmdSender, PlayerProgress, PlayerTrait,
     Speed, Volume,
@@ -41,11 +45,21 @@ use crate::{
 use decoder::buffered_source::BufferedSource;
 use decoder::read_seek_source::ReadSeekSource;
 use decoder::{MediaTitleRx, MediaTitleType, Symphonia};
+use hw_volume::HardwareVolume;
+use output::Output;
+pub use output::list_output_devices;
+use recorder::{RecordTags, RecordTap};
+pub use render::{RenderOptions, RenderReport, RenderedTrack, render_queue};
 use sink::{Sink, SourceOptions};
+use source::SpecificType;
 use source::async_ring::{AsyncRingSource, AsyncRingSourceProvider, SeekData};
 
 mod decoder;
+mod hw_volume;
 mod icy_metadata;
+mod output;
+mod recorder;
+mod render;
 mod sink;
 // public to bench lower modules
 pub(crate) mod source;
@@ -64,15 +78,43 @@ enum PlayerInternalCmd {
     Skip,
     Speed(i32),
     Stop,
-    TogglePause,
+    Pause,
     Volume(u16),
+    VocalReduction(bool),
+    Normalization(bool),
+    SmartSpeed(bool),
+    Equalizer(Option<[f32; BANDS]>),
+    /// Play on the output device with the name, or the default device if empty.
+    OutputDevice(String),
+    /// Check whether the output device should change, like when the device was lost or a preferred one appeared.
+    CheckOutput,
     Eos,
 }
 
+/// The processing of the played audio that can be toggled while playing.
+#[derive(Debug, Clone, Copy, Default)]
+struct DspToggles {
+    vocal_reduction: bool,
+    normalization: bool,
+    smart_speed: bool,
+}
+
 pub struct RustyBackend {
     volume: Arc<AtomicU16>,
     speed: i32,
     gapless: bool,
+    dsp: DspToggles,
+    /// The gains of the equalizer bands, [`None`] if disabled.
+    equalizer: Option<[f32; BANDS]>,
+    /// The system mixer, if enabled via [`VolumeControl`] and it could be opened.
+    hw_volume: Option<HardwareVolume>,
+    volume_control: VolumeControl,
+    /// The recorder shared with the sink.
+    record: Arc<RecordTap>,
+    /// The name of the output device played on, empty if none could be opened.
+    output_device: Arc<Mutex<String>>,
+    /// The album of the last played or enqueued track, to not crossfade between the tracks of a album.
+    last_album: Option<String>,
     command_tx: Sender<PlayerInternalCmd>,
     position: Arc<Mutex<Duration>>,
     total_duration: ArcTotalDuration,
@@ -89,11 +131,39 @@ impl RustyBackend {
         let (picmd_tx, picmd_rx): (Sender<PlayerInternalCmd>, Receiver<PlayerInternalCmd>) =
             mpsc::channel();
         let picmd_tx_local = picmd_tx.clone();
-        let volume = Arc::new(AtomicU16::from(config_read.settings.player.volume));
-        let volume_local = volume.clone();
         let speed = config_read.settings.player.speed;
         let gapless = config_read.settings.player.gapless;
-        let output_sample_rate = config_read.settings.backends.rusty.output_sample_rate;
+        let dsp = DspToggles {
+            vocal_reduction: false,
+            normalization: config_read.settings.player.normalization,
+            smart_speed: config_read.settings.player.smart_speed,
+        };
+        let equalizer = config_read.settings.equalizer.active_gains();
+        let rusty_settings = &config_read.settings.backends.rusty;
+        let output_sample_rate = rusty_settings.output_sample_rate;
+        let output_buffer_frames = config_read.settings.termux.output_buffer_frames();
+        let preferred_output = rusty_settings.output_device.clone();
+        let pause_on_device_loss = rusty_settings.device_loss.pauses();
+        let volume_control = rusty_settings.volume_control;
+        let hw_volume = if volume_control == VolumeControl::Software {
+            None
+        } else {
+            HardwareVolume::open(&rusty_settings.mixer_device, &rusty_settings.mixer_control)
+                .inspect_err(|err| {
+                    error!("Error opening the hardware volume, using software volume: {err:#}");
+                })
+                .ok()
+        };
+        let software_volume = match (&hw_volume, volume_control) {
+            // only the hardware volume is changed, so the software gain has to stay at 100%
+            (Some(_), VolumeControl::Hardware) => 100,
+            (Some(hw_volume), VolumeControl::Both) => hw_volume
+                .volume()
+                .unwrap_or(config_read.settings.player.volume),
+            _ => config_read.settings.player.volume,
+        };
+        let volume = Arc::new(AtomicU16::from(software_volume));
+        let volume_local = volume.clone();
         drop(config_read);
 
         let position = Arc::new(Mutex::new(Duration::default()));
@@ -104,6 +174,10 @@ impl RustyBackend {
         let media_title = Arc::new(Mutex::new(String::new()));
         let media_title_local = media_title.clone();
         let radio_downloaded = Arc::new(Mutex::new(100_u64));
+        let record = Arc::new(RecordTap::default());
+        let record_local = record.clone();
+        let output_device = Arc::new(Mutex::new(String::new()));
+        let output_device_local = output_device.clone();
         // let radio_downloaded_local = radio_downloaded.clone();
         // this should likely be a parameter, but works for now
         let tokio_handle = Handle::current();
@@ -122,6 +196,13 @@ impl RustyBackend {
                     volume_inside: volume_local,
                     speed_inside: speed,
                     output_sample_rate,
+                    output_buffer_frames,
+                    preferred_output,
+                    output_device: output_device_local,
+                    pause_on_device_loss,
+                    record: record_local,
+                    dsp,
+                    equalizer,
                 }));
             })
             .expect("failed to spawn thread");
@@ -131,6 +212,13 @@ impl RustyBackend {
             volume,
             speed,
             gapless,
+            dsp,
+            equalizer,
+            hw_volume,
+            volume_control,
+            record,
+            output_device,
+            last_album: None,
             command_tx: picmd_tx,
             position,
             media_title,
@@ -140,6 +228,20 @@ impl RustyBackend {
         }
     }
 
+    /// Get whether the track before `track` may crossfade into it, which is not the case for tracks of the same
+    /// album, as those are likely gapless.
+    fn crossfade_into(&mut self, track: &Track) -> bool {
+        let album = track
+            .as_track()
+            .and_then(TrackData::album)
+            .filter(|v| !v.is_empty())
+            .map(ToString::to_string);
+        let same_album = album.is_some() && album == self.last_album;
+        self.last_album = album;
+
+        !same_album
+    }
+
     #[allow(clippy::needless_pass_by_value)]
     fn command(&self, cmd: PlayerInternalCmd) {
         if let Err(e) = self.command_tx.send(cmd) {
@@ -155,7 +257,10 @@ impl PlayerTrait for RustyBackend {
         // "await occurs here (rx.await), with `config_read` maybe used later"
         let query_options = {
             let config_read = self.config.read_recursive();
-            let soundtouch = config_read.settings.backends.rusty.soundtouch;
+            let speed_type = SpecificType::new(
+                config_read.settings.backends.rusty.soundtouch,
+                config_read.settings.backends.rusty.preserve_pitch,
+            );
             let file_buf_size = usize::try_from(
                 config_read
                     .settings
@@ -174,13 +279,34 @@ impl PlayerTrait for RustyBackend {
                     .as_u64(),
             )
             .unwrap_or(usize::MAX);
+            let fade_in =
+                Duration::from_millis(config_read.settings.backends.rusty.fade_in_ms.into());
+            let fade_out =
+                Duration::from_millis(config_read.settings.backends.rusty.fade_out_ms.into());
+            let salvage = config_read.settings.backends.rusty.salvage;
+            let crossfade = config_read.settings.backends.rusty.crossfade();
+            let crossfade_curve = config_read.settings.backends.rusty.crossfade_curve;
+            let normalization_mode = config_read.settings.player.normalization_mode;
+            let trim_silence = config_read.settings.player.trim_silence;
+            let smart_speed_silence =
+                Duration::from_millis(config_read.settings.player.smart_speed_silence_ms.into());
+            drop(config_read);
 
             QueueNextOptions {
                 gapless_decode: self.gapless,
-                soundtouch,
+                speed_type,
                 file_buf_size,
                 ringbuf_size,
                 enqueue: false,
+                fade_in,
+                fade_out,
+                salvage,
+                crossfade,
+                crossfade_curve,
+                crossfade_into: self.crossfade_into(track),
+                normalization_mode,
+                trim_silence,
+                smart_speed_silence,
             }
         };
 
@@ -195,11 +321,26 @@ impl PlayerTrait for RustyBackend {
     }
 
     fn volume(&self) -> Volume {
+        if let Some(hw_volume) = &self.hw_volume {
+            match hw_volume.volume() {
+                Ok(volume) => return volume,
+                Err(err) => debug!("Error getting the hardware volume: {err:#}"),
+            }
+        }
+
         self.volume.load(Ordering::SeqCst)
     }
 
     fn set_volume(&mut self, volume: Volume) -> Volume {
         let volume = volume.min(100);
+        if let Some(hw_volume) = &self.hw_volume {
+            if let Err(err) = hw_volume.set_volume(volume) {
+                error!("Error setting the hardware volume: {err:#}");
+            }
+            if self.volume_control == VolumeControl::Hardware {
+                return volume;
+            }
+        }
         self.volume.store(volume, Ordering::SeqCst);
         self.command(PlayerInternalCmd::Volume(volume));
 
@@ -207,7 +348,7 @@ impl PlayerTrait for RustyBackend {
     }
 
     fn pause(&mut self) {
-        self.command(PlayerInternalCmd::TogglePause);
+        self.command(PlayerInternalCmd::Pause);
     }
 
     fn resume(&mut self) {
@@ -259,13 +400,82 @@ impl PlayerTrait for RustyBackend {
         self.gapless = to;
     }
 
+    fn vocal_reduction(&self) -> bool {
+        self.dsp.vocal_reduction
+    }
+
+    fn set_vocal_reduction(&mut self, to: bool) {
+        self.dsp.vocal_reduction = to;
+        self.command(PlayerInternalCmd::VocalReduction(to));
+    }
+
+    fn normalization(&self) -> bool {
+        self.dsp.normalization
+    }
+
+    fn set_normalization(&mut self, to: bool) {
+        self.dsp.normalization = to;
+        self.command(PlayerInternalCmd::Normalization(to));
+    }
+
+    fn smart_speed(&self) -> bool {
+        self.dsp.smart_speed
+    }
+
+    fn set_smart_speed(&mut self, to: bool) {
+        self.dsp.smart_speed = to;
+        self.command(PlayerInternalCmd::SmartSpeed(to));
+    }
+
+    fn equalizer(&self) -> Option<[f32; BANDS]> {
+        self.equalizer
+    }
+
+    fn set_equalizer(&mut self, gains: Option<[f32; BANDS]>) {
+        self.equalizer = gains;
+        self.command(PlayerInternalCmd::Equalizer(gains));
+    }
+
+    fn recording(&self) -> bool {
+        self.record.is_active()
+    }
+
+    fn output_device(&self) -> Option<String> {
+        Some(self.output_device.lock().clone())
+    }
+
+    fn set_output_device(&mut self, name: String) {
+        self.config
+            .write()
+            .settings
+            .backends
+            .rusty
+            .output_device
+            .clone_from(&name);
+        self.command(PlayerInternalCmd::OutputDevice(name));
+    }
+
+    fn set_recording(&mut self, to: bool) -> Result<()> {
+        if to {
+            let settings = self.config.read_recursive().settings.recording.clone();
+            self.record.start(&settings)?;
+        } else {
+            self.record.stop();
+        }
+
+        Ok(())
+    }
+
     fn skip_one(&mut self) {
         self.command(PlayerInternalCmd::Skip);
     }
 
     fn enqueue_next(&mut self, track: &Track) {
         let config_read = self.config.read_recursive();
-        let soundtouch = config_read.settings.backends.rusty.soundtouch;
+        let speed_type = SpecificType::new(
+            config_read.settings.backends.rusty.soundtouch,
+            config_read.settings.backends.rusty.preserve_pitch,
+        );
         let file_buf_size = usize::try_from(
             config_read
                 .settings
@@ -284,17 +494,37 @@ impl PlayerTrait for RustyBackend {
                 .as_u64(),
         )
         .unwrap_or(usize::MAX);
+        let fade_in = Duration::from_millis(config_read.settings.backends.rusty.fade_in_ms.into());
+        let fade_out =
+            Duration::from_millis(config_read.settings.backends.rusty.fade_out_ms.into());
+        let salvage = config_read.settings.backends.rusty.salvage;
+        let crossfade = config_read.settings.backends.rusty.crossfade();
+        let crossfade_curve = config_read.settings.backends.rusty.crossfade_curve;
+        let normalization_mode = config_read.settings.player.normalization_mode;
+        let trim_silence = config_read.settings.player.trim_silence;
+        let smart_speed_silence =
+            Duration::from_millis(config_read.settings.player.smart_speed_silence_ms.into());
 
         drop(config_read);
+        let crossfade_into = self.crossfade_into(track);
 
         self.command(PlayerInternalCmd::Play(
             Box::new(track.clone()),
             QueueNextOptions {
                 gapless_decode: self.gapless,
-                soundtouch,
+                speed_type,
                 file_buf_size,
                 ringbuf_size,
                 enqueue: true,
+                fade_in,
+                fade_out,
+                salvage,
+                crossfade,
+                crossfade_curve,
+                crossfade_into,
+                normalization_mode,
+                trim_silence,
+                smart_speed_silence,
             },
             PlayerCmdCallbackSender(None),
         ));
@@ -314,15 +544,28 @@ impl PlayerTrait for RustyBackend {
 
 /// Common options across the `append_to_sink*` functions
 #[derive(Debug, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent options, this is not a state machine
 struct CommonAppendOptions {
     /// Enable or disable gapless decoding
     gapless_decode: bool,
-    /// Enable or disable soundtouch speed modifier or with `false` use rodio's speed modifier
-    soundtouch: bool,
+    /// The speed modifier to use, see [`SpecificType::new`]
+    speed_type: SpecificType,
     /// Enable or disable async decoding (decode to happen on a different thread than the playback)
     async_decode: bool,
     /// The size for the ring buffer.
     ringbuf_size: usize,
+    /// The tags to record the source with, see [`SourceOptions::record_tags`].
+    record_tags: Option<RecordTags>,
+    /// The normalization gain of the source, see [`SourceOptions::normalization_gain`].
+    normalization_gain: Option<f32>,
+    /// Where to report the damaged regions to, if corrupt packets should be muted, see [`Symphonia::new`].
+    salvage: Option<DamageTx>,
+    /// Whether the source before may crossfade into this one, see [`SourceOptions::crossfade`].
+    crossfade: bool,
+    /// Whether to drop the silence at the start and the end, see [`SourceOptions::trim_silence`].
+    trim_silence: bool,
+    /// The duration long silences are shortened to with smart speed, see [`SourceOptions::smart_speed_silence`].
+    smart_speed_silence: Option<Duration>,
 }
 
 /// Extra options specific to [`append_to_sink_test`]
@@ -346,6 +589,7 @@ fn append_to_sink_inner<TD: Display, F: FnOnce(&mut Symphonia, Option<MediaTitle
         mss,
         common_options.gapless_decode,
         specific_options.media_title,
+        common_options.salvage.clone(),
     ) {
         Err(err) => {
             error!("Error decoding '{trace}': {err:?}");
@@ -376,14 +620,24 @@ fn append_to_sink_inner<TD: Display, F: FnOnce(&mut Symphonia, Option<MediaTitle
         sink.append(
             cons,
             &SourceOptions {
-                soundtouch: common_options.soundtouch,
+                speed_type: common_options.speed_type,
+                record_tags: common_options.record_tags.clone(),
+                normalization_gain: common_options.normalization_gain,
+                crossfade: common_options.crossfade,
+                trim_silence: common_options.trim_silence,
+                smart_speed_silence: common_options.smart_speed_silence,
             },
         );
     } else {
         sink.append(
             decoder,
             &SourceOptions {
-                soundtouch: common_options.soundtouch,
+                speed_type: common_options.speed_type,
+                record_tags: common_options.record_tags.clone(),
+                normalization_gain: common_options.normalization_gain,
+                crossfade: common_options.crossfade,
+                trim_silence: common_options.trim_silence,
+                smart_speed_silence: common_options.smart_speed_silence,
             },
         );
     }
@@ -588,6 +842,100 @@ fn common_media_title_cb(
     }
 }
 
+/// Open the default output device like the player thread does, to check that audio output is possible.
+///
+/// Returns a description of the device and the opened stream configuration.
+///
+/// # Errors
+///
+/// - if there is no default output device
+/// - if no stream could be opened on it
+pub fn probe_output_device() -> Result<String> {
+    use rodio::DeviceTrait as _;
+    use rodio::cpal::traits::HostTrait as _;
+
+    let default_device = rodio::cpal::default_host()
+        .default_output_device()
+        .context("No default output device found")?;
+    let name = default_device
+        .name()
+        .unwrap_or_else(|_| "<unnamed>".to_string());
+
+    let mut stream = OutputStreamBuilder::from_device(default_device)
+        .and_then(|v| v.open_stream_or_fallback())
+        .with_context(|| format!("Opening an output stream on \"{name}\""))?;
+    stream.log_on_drop(false);
+    let config = stream.config();
+
+    Ok(format!(
+        "\"{name}\" at {} Hz with {} channels",
+        config.sample_rate(),
+        config.channel_count()
+    ))
+}
+
+/// The result of [`bench_decode`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodeBench {
+    /// The wall-clock time spent opening and decoding
+    pub elapsed: Duration,
+    /// Interleaved samples decoded, over all channels
+    pub samples: u64,
+    pub sample_rate: u32,
+    pub channels: usize,
+}
+
+impl DecodeBench {
+    /// Get the duration of the decoded audio.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn audio_duration(&self) -> Duration {
+        if self.sample_rate == 0 || self.channels == 0 {
+            return Duration::ZERO;
+        }
+        let frames = self.samples / self.channels as u64;
+
+        Duration::from_secs_f64(frames as f64 / f64::from(self.sample_rate))
+    }
+
+    /// Get how many times faster than realtime the decode was.
+    #[must_use]
+    pub fn speed(&self) -> f64 {
+        self.audio_duration().as_secs_f64() / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Decode the whole file at `path` as fast as possible, without any output.
+///
+/// # Errors
+///
+/// - if the file cannot be opened
+/// - if no decoder could be created for the file
+pub fn bench_decode(path: &Path) -> Result<DecodeBench> {
+    let start = std::time::Instant::now();
+    let file = File::open(path).context("Failed to open music file")?;
+    let mss = MediaSourceStream::new(
+        Box::new(BufferedSource::new_default_size(file)),
+        MediaSourceStreamOptions::default(),
+    );
+    let (mut decoder, _) = Symphonia::new(mss, false, false, None)?;
+
+    let mut samples: u64 = 0;
+    while decoder.decode_once().is_some() {
+        let len = decoder.get_buffer().len();
+        samples += len as u64;
+        decoder.advance_offset(len);
+    }
+    let (spec, _) = decoder.get_spec();
+
+    Ok(DecodeBench {
+        elapsed: start.elapsed(),
+        samples,
+        sample_rate: spec.rate,
+        channels: spec.channels.count(),
+    })
+}
+
 #[derive(Debug)]
 struct PlayerThreadArgs {
     total_duration: ArcTotalDuration,
@@ -605,6 +953,59 @@ struct PlayerThreadArgs {
     speed_inside: i32,
 
     output_sample_rate: u32,
+    /// A fixed output buffer size, if not decided by the device
+    output_buffer_frames: Option<u32>,
+    /// The name of the output device to play on, empty for the default device
+    preferred_output: String,
+    /// The name of the output device played on, shared with the backend
+    output_device: Arc<Mutex<String>>,
+    /// Whether to pause once the output device is lost, before playing on the fallback device
+    pause_on_device_loss: bool,
+
+    record: Arc<RecordTap>,
+    /// The initially enabled processing
+    dsp: DspToggles,
+    /// The initial gains of the equalizer bands
+    equalizer: Option<[f32; BANDS]>,
+}
+
+/// Spawn the thread that periodically sends [`PlayerInternalCmd::CheckOutput`], to follow devices appearing.
+fn output_check_thread(picmd_tx: Sender<PlayerInternalCmd>) {
+    let res = std::thread::Builder::new()
+        .name("output device check".into())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(output::CHECK_INTERVAL);
+                if picmd_tx.send(PlayerInternalCmd::CheckOutput).is_err() {
+                    break;
+                }
+            }
+        });
+
+    if let Err(err) = res {
+        error!("Error starting the output device check thread: {err:#}");
+    }
+}
+
+/// Switch the output device if necessary, see [`Output::check`].
+///
+/// A lost device pauses the sink before the fallback device is opened, if enabled, to not continue on the speakers
+/// once headphones are unplugged.
+fn check_output(output: &mut Output, sink: &Sink, args: &PlayerThreadArgs) {
+    if let Some(name) = output.lost_device()
+        && args.pause_on_device_loss
+        && !sink.is_paused()
+        && !sink.is_empty()
+    {
+        info!("Pausing, the output device \"{name}\" is gone");
+        sink.pause();
+        let _ = args.pcmd_tx.send(PlayerCmd::OutputLost(name));
+    }
+
+    if output.check(&args.preferred_output) {
+        let name = args.output_device.lock().clone();
+        let _ = args.pcmd_tx.send(PlayerCmd::OutputSwitched(name));
+    }
 }
 
 /// Player thread loop
@@ -624,17 +1025,26 @@ async fn player_thread(mut args: PlayerThreadArgs) {
     // This needs to be reset on many occasions like Seek or Stream Start.
     let mut send_atf = false;
 
-    let stream = {
-        let builder = OutputStreamBuilder::from_default_device().unwrap();
-        builder
-            .with_sample_rate(args.output_sample_rate)
-            .open_stream_or_fallback()
-            .unwrap()
-    };
-    let handle = stream.mixer();
-    let sink = Sink::try_new(handle, args.picmd_tx.clone(), args.pcmd_tx.clone());
+    let mut output = Output::new(
+        &args.preferred_output,
+        args.output_sample_rate,
+        args.output_buffer_frames,
+        args.output_device.clone(),
+        args.picmd_tx.clone(),
+    );
+    output_check_thread(args.picmd_tx.clone());
+    let sink = Sink::try_new(
+        output.mixer(),
+        args.picmd_tx.clone(),
+        args.pcmd_tx.clone(),
+        args.record.clone(),
+    );
     sink.set_speed(args.speed_inside as f32 / 10.0);
     sink.set_volume(f32::from(args.volume_inside.load(Ordering::SeqCst)) / 100.0);
+    sink.set_vocal_reduction(args.dsp.vocal_reduction);
+    sink.set_normalization(args.dsp.normalization);
+    sink.set_smart_speed(args.dsp.smart_speed);
+    sink.set_equalizer(args.equalizer);
     loop {
         let Ok(cmd) = args.picmd_rx.recv() else {
             // only error can be a disconnect (no more senders)
@@ -643,6 +1053,8 @@ async fn player_thread(mut args: PlayerThreadArgs) {
 
         match cmd {
             PlayerInternalCmd::Play(track, options, cb) => {
+                sink.set_fade(options.fade_in, options.fade_out);
+                sink.set_crossfade(options.crossfade, options.crossfade_curve);
                 if let Err(err) = queue_next(
                     &track,
                     &sink,
@@ -658,21 +1070,18 @@ async fn player_thread(mut args: PlayerThreadArgs) {
                 {
                     error!("Failed to enqueue track: {err:#?}");
 
-                    if options.enqueue {
-                        let _ = args
-                            .pcmd_tx
-                            .send(PlayerCmd::Error(crate::PlayerErrorType::Enqueue));
+                    let ty = if options.enqueue {
+                        crate::PlayerErrorType::Enqueue
                     } else {
-                        let _ = args
-                            .pcmd_tx
-                            .send(PlayerCmd::Error(crate::PlayerErrorType::Current));
-                    }
+                        crate::PlayerErrorType::Current
+                    };
+                    let _ = args.pcmd_tx.send(PlayerCmd::Error(ty, format!("{err:#}")));
                 }
                 // maybe this should be called by the source / decoder to be fully correct
                 cb.call();
             }
-            PlayerInternalCmd::TogglePause => {
-                sink.toggle_playback();
+            PlayerInternalCmd::Pause => {
+                sink.pause();
             }
             PlayerInternalCmd::Resume => {
                 sink.play();
@@ -688,6 +1097,25 @@ async fn player_thread(mut args: PlayerThreadArgs) {
                 sink.set_volume(f32::from(volume) / 100.0);
                 args.volume_inside.store(volume, Ordering::SeqCst);
             }
+            PlayerInternalCmd::VocalReduction(to) => {
+                sink.set_vocal_reduction(to);
+            }
+            PlayerInternalCmd::Normalization(to) => {
+                sink.set_normalization(to);
+            }
+            PlayerInternalCmd::SmartSpeed(to) => {
+                sink.set_smart_speed(to);
+            }
+            PlayerInternalCmd::Equalizer(gains) => {
+                sink.set_equalizer(gains);
+            }
+            PlayerInternalCmd::OutputDevice(name) => {
+                args.preferred_output = name;
+                check_output(&mut output, &sink, &args);
+            }
+            PlayerInternalCmd::CheckOutput => {
+                check_output(&mut output, &sink, &args);
+            }
             PlayerInternalCmd::Skip => {
                 // the sink can be empty, if for example nothing could be enqueued, so a "skip_one" would be a no-op and never send EOS, which is required to go to the next track
                 if sink.is_empty() {
@@ -711,7 +1139,10 @@ async fn player_thread(mut args: PlayerThreadArgs) {
                     && let Some(d) = *args.total_duration.lock()
                 {
                     let progress = new_position.as_secs_f64() / d.as_secs_f64();
-                    if progress >= 0.5 && d.saturating_sub(new_position) < Duration::from_secs(2) {
+                    if progress >= 0.5
+                        && d.saturating_sub(new_position)
+                            < Duration::from_secs(2) + sink.crossfade()
+                    {
                         if let Err(e) = args.pcmd_tx.send(PlayerCmd::AboutToFinish) {
                             error!("command AboutToFinish sent failed: {e}");
                         }
@@ -764,17 +1195,35 @@ async fn player_thread(mut args: PlayerThreadArgs) {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // independent options, this is not a state machine
 struct QueueNextOptions {
     /// Enable or disable gapless decoding
     gapless_decode: bool,
-    /// Enable or disable soundtouch speed modifier or with `false` use rodio's speed modifier
-    soundtouch: bool,
+    /// The speed modifier to use, see [`SpecificType::new`]
+    speed_type: SpecificType,
     /// Determines which append function and which duration type to use.
     enqueue: bool,
     /// Determines the size of the [`BufferedSource`].
     file_buf_size: usize,
     /// Determines the size of the [`AsyncRingSource`].
     ringbuf_size: usize,
+    /// How long to fade-in, see [`Sink::set_fade`].
+    fade_in: Duration,
+    /// How long to fade-out, see [`Sink::set_fade`].
+    fade_out: Duration,
+    /// Mute corrupt packets of local files and flag them as damaged, see [`Symphonia::new`].
+    salvage: bool,
+    /// How long to crossfade, see [`Sink::set_crossfade`].
+    crossfade: Duration,
+    crossfade_curve: CrossfadeCurve,
+    /// Whether the track before may crossfade into this one, see [`SourceOptions::crossfade`].
+    crossfade_into: bool,
+    /// Which gain to normalize with, see [`TrackData::normalization_gain_for`].
+    normalization_mode: NormalizationMode,
+    /// Whether to drop the silence at the start and the end of tracks, see [`SourceOptions::trim_silence`].
+    trim_silence: bool,
+    /// The duration long silences of podcast episodes are shortened to, see [`SourceOptions::smart_speed_silence`].
+    smart_speed_silence: Duration,
 }
 
 /// Queue the given track into the [`Sink`], while also setting all of the other variables
@@ -791,18 +1240,28 @@ async fn queue_next(
     pcmd_tx: &PlayerCmdSender,
 ) -> Result<()> {
     // clear out the sources when we dont "enqueue" as we want to directly play it
-    if !options.enqueue && !sink.is_empty() {
+    // a single playing source can instead fade out while the new one is appended behind it
+    let crossfading = || sink.len() == 1 && sink.crossfade_out();
+    if !options.enqueue && !sink.is_empty() && !crossfading() {
         // dont have the source that are cleared-out send a EOS as that the player-trait does not know about those sources
         // and assumes the one that is about to be added has finished.
         sink.stop_no_eos();
         // not waiting here as the sink automatically does that on ".append"
     }
 
-    match track.inner() {
-        MediaTypes::Track(track_data) => {
-            *is_radio = false;
-            let file_path = track_data.path();
-            let file = File::open(file_path).context("Failed to open music file")?;
+    let source = track.source();
+    *is_radio = source.is_live();
+    let normalization_gain = track
+        .as_track()
+        .and_then(|v| v.normalization_gain_for(options.normalization_mode));
+    let smart_speed_silence = track.as_podcast().map(|_| options.smart_speed_silence);
+
+    match source.stream() {
+        StreamLocation::File(file_path) => {
+            let file = File::open(file_path).context("Failed to open file")?;
+            let salvage = options
+                .salvage
+                .then(|| damage_report(file_path.to_path_buf(), pcmd_tx));
 
             if options.enqueue {
                 append_to_sink_queue(
@@ -811,9 +1270,15 @@ async fn queue_next(
                     sink,
                     &CommonAppendOptions {
                         gapless_decode: options.gapless_decode,
-                        soundtouch: options.soundtouch,
+                        speed_type: options.speed_type,
                         ringbuf_size: options.ringbuf_size,
                         async_decode: true,
+                        record_tags: Some(RecordTags::from_track(track)),
+                        normalization_gain,
+                        salvage,
+                        crossfade: options.crossfade_into,
+                        trim_silence: options.trim_silence,
+                        smart_speed_silence,
                     },
                     next_duration_opt,
                     common_media_title_cb(media_title.clone(), pcmd_tx),
@@ -825,9 +1290,15 @@ async fn queue_next(
                     sink,
                     &CommonAppendOptions {
                         gapless_decode: options.gapless_decode,
-                        soundtouch: options.soundtouch,
+                        speed_type: options.speed_type,
                         ringbuf_size: options.ringbuf_size,
                         async_decode: true,
+                        record_tags: Some(RecordTags::from_track(track)),
+                        normalization_gain,
+                        salvage,
+                        crossfade: options.crossfade_into,
+                        trim_silence: options.trim_silence,
+                        smart_speed_silence,
                     },
                     total_duration,
                     common_media_title_cb(media_title.clone(), pcmd_tx),
@@ -836,9 +1307,7 @@ async fn queue_next(
 
             Ok(())
         }
-        MediaTypes::Radio(radio_track_data) => {
-            *is_radio = true;
-            let url = radio_track_data.url();
+        StreamLocation::Url(url) if source.is_live() => {
             let settings = StreamSettings::default();
 
             let mut headers = HeaderMap::new();
@@ -874,7 +1343,7 @@ async fn queue_next(
             let media_title_clone = media_title.clone();
 
             let pcmd_tx = pcmd_tx.clone();
-            let cb = move |title: &str| {
+            let title_cb = move |title: &str| {
                 let new_title = if title.is_empty() {
                     "<no title>".to_string()
                 } else {
@@ -887,9 +1356,21 @@ async fn queue_next(
 
             // set initial title to what the header says
             if let Some(icy_description) = icy_description {
-                cb(&icy_description);
+                title_cb(&icy_description);
             }
 
+            // the station is recorded as the album, and every new stream title starts a new recording
+            let record = sink.record_tap().clone();
+            let station = track.title().map(ToString::to_string);
+            record.set_stream_tags(RecordTags {
+                album: station.clone(),
+                ..Default::default()
+            });
+            let cb = move |title: &str| {
+                record.set_stream_tags(RecordTags::from_icy(title, station.as_deref()));
+                title_cb(title);
+            };
+
             let media_source: Box<dyn MediaSource> = if let Some(meta_interval) = meta_interval {
                 Box::new(ReadOnlySource::new(
                     icy_metadata::FilterOutIcyMetadata::new(reader, cb, meta_interval),
@@ -906,9 +1387,16 @@ async fn queue_next(
                     sink,
                     &CommonAppendOptions {
                         gapless_decode: options.gapless_decode,
-                        soundtouch: options.soundtouch,
+                        speed_type: options.speed_type,
                         ringbuf_size: options.ringbuf_size,
                         async_decode: false,
+                        record_tags: None,
+                        normalization_gain,
+                        salvage: None,
+                        crossfade: options.crossfade_into,
+                        // a live stream has no end, and its silence has to be played to stay live
+                        trim_silence: false,
+                        smart_speed_silence: None,
                     },
                     next_duration_opt,
                 )?;
@@ -919,9 +1407,16 @@ async fn queue_next(
                     sink,
                     &CommonAppendOptions {
                         gapless_decode: options.gapless_decode,
-                        soundtouch: options.soundtouch,
+                        speed_type: options.speed_type,
                         ringbuf_size: options.ringbuf_size,
                         async_decode: false,
+                        record_tags: None,
+                        normalization_gain,
+                        salvage: None,
+                        crossfade: options.crossfade_into,
+                        // a live stream has no end, and its silence has to be played to stay live
+                        trim_silence: false,
+                        smart_speed_silence: None,
                     },
                     total_duration,
                 )?;
@@ -929,87 +1424,156 @@ async fn queue_next(
 
             Ok(())
         }
-        MediaTypes::Podcast(podcast_track_data) => {
-            *is_radio = false;
-            if let Some(file_path) = podcast_track_data.localfile() {
-                let file = File::open(Path::new(&file_path))
-                    .context("Failed to open local podcast file")?;
-                if options.enqueue {
-                    append_to_sink_queue(
-                        Box::new(BufferedSource::new(file, options.file_buf_size)),
-                        file_path.display(),
-                        sink,
-                        &CommonAppendOptions {
-                            gapless_decode: options.gapless_decode,
-                            soundtouch: options.soundtouch,
-                            ringbuf_size: options.ringbuf_size,
-                            async_decode: true,
-                        },
-                        next_duration_opt,
-                        common_media_title_cb(media_title.clone(), pcmd_tx),
-                    )?;
-                } else {
-                    append_to_sink(
-                        Box::new(BufferedSource::new(file, options.file_buf_size)),
-                        file_path.display(),
-                        sink,
-                        &CommonAppendOptions {
-                            gapless_decode: options.gapless_decode,
-                            soundtouch: options.soundtouch,
-                            ringbuf_size: options.ringbuf_size,
-                            async_decode: true,
-                        },
-                        total_duration,
-                        common_media_title_cb(media_title.clone(), pcmd_tx),
-                    )?;
-                }
-                return Ok(());
-            }
-
-            let url = podcast_track_data.url();
-            let settings = StreamSettings::default();
-
+        StreamLocation::Url(url) => {
             let stream = HttpStream::<Client>::create(url.parse()?).await?;
+            queue_remote(
+                stream,
+                url,
+                track,
+                sink,
+                &options,
+                normalization_gain,
+                total_duration,
+                next_duration_opt,
+                common_media_title_cb(media_title.clone(), pcmd_tx),
+            )
+            .await
+        }
+        StreamLocation::Extract(page_url) => {
+            let (stream, extracted) = open_extracted(page_url).await?;
+
+            // the decoder resets the title at the start, but a video stream rarely has a title tag itself
+            let title_cb = common_media_title_cb(media_title.clone(), pcmd_tx);
+            let title = extracted.title;
+            let title_cb = move |cmd| match (cmd, &title) {
+                (MediaTitleType::Reset, Some(title)) => {
+                    title_cb(MediaTitleType::Value(title.clone()));
+                }
+                (cmd, _) => title_cb(cmd),
+            };
 
-            let file_len = stream.content_length();
-
-            let reader = StreamDownload::from_stream(
+            queue_remote(
                 stream,
-                TempStorageProvider::with_prefix(".termusic-stream-cache-"),
-                settings,
+                &extracted.url,
+                track,
+                sink,
+                &options,
+                normalization_gain,
+                total_duration,
+                next_duration_opt,
+                title_cb,
             )
-            .await?;
+            .await
+        }
+    }
+}
 
-            if options.enqueue {
-                append_to_sink_queue(
-                    Box::new(ReadSeekSource::new(reader, file_len)),
-                    url,
-                    sink,
-                    &CommonAppendOptions {
-                        gapless_decode: options.gapless_decode,
-                        soundtouch: options.soundtouch,
-                        ringbuf_size: options.ringbuf_size,
-                        async_decode: false,
-                    },
-                    next_duration_opt,
-                    common_media_title_cb(media_title.clone(), pcmd_tx),
-                )?;
-            } else {
-                append_to_sink(
-                    Box::new(ReadSeekSource::new(reader, file_len)),
-                    url,
-                    sink,
-                    &CommonAppendOptions {
-                        gapless_decode: options.gapless_decode,
-                        soundtouch: options.soundtouch,
-                        ringbuf_size: options.ringbuf_size,
-                        async_decode: false,
-                    },
-                    total_duration,
-                    common_media_title_cb(media_title.clone(), pcmd_tx),
-                )?;
+/// Extract the audio stream of the video page at `page_url` with yt-dlp and open it.
+///
+/// A reused stream url may have expired if it is rejected with 403 or 410, in which case it is extracted again once.
+async fn open_extracted(page_url: &str) -> Result<(HttpStream<Client>, ExtractedAudio)> {
+    let mut refresh = false;
+    loop {
+        let page = page_url.to_string();
+        let (extracted, reused) =
+            tokio::task::spawn_blocking(move || ytdlp::extract_audio_cached(&page, refresh))
+                .await?
+                .context("Failed to extract the audio stream")?;
+
+        match HttpStream::<Client>::create(extracted.url.parse()?).await {
+            Ok(stream) => return Ok((stream, extracted)),
+            Err(HttpStreamError::ResponseFailure(err))
+                if reused
+                    && matches!(
+                        err.response().status(),
+                        StatusCode::FORBIDDEN | StatusCode::GONE
+                    ) =>
+            {
+                info!("Stream url of \"{page_url}\" expired, extracting it again");
+                refresh = true;
             }
-            Ok(())
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Create the channel for a salvaging decoder, which flags the file at `path` as damaged once decoding is done, if
+/// there were any damaged regions.
+fn damage_report(path: PathBuf, pcmd_tx: &PlayerCmdSender) -> DamageTx {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let pcmd_tx = pcmd_tx.clone();
+
+    Handle::current().spawn(async move {
+        let mut regions = Vec::new();
+        // the channel closes once the decoder is dropped
+        while let Some(region) = rx.recv().await {
+            regions.push(region);
         }
+
+        if !regions.is_empty() {
+            let _ = pcmd_tx.send(PlayerCmd::Damaged(path, regions));
+        }
+    });
+
+    tx
+}
+
+/// Queue a remote, non-live stream into the [`Sink`], cached in a temporary file so that it can be seeked.
+#[allow(clippy::too_many_arguments)]
+async fn queue_remote<MT: Fn(MediaTitleType) + Send + 'static>(
+    stream: HttpStream<Client>,
+    url: &str,
+    track: &Track,
+    sink: &Sink,
+    options: &QueueNextOptions,
+    normalization_gain: Option<f32>,
+
+    total_duration: &ArcTotalDuration,
+    next_duration_opt: &mut Option<Duration>,
+    media_title_fn: MT,
+) -> Result<()> {
+    let settings = StreamSettings::default();
+
+    let file_len = stream.content_length();
+
+    let reader = StreamDownload::from_stream(
+        stream,
+        TempStorageProvider::with_prefix(".termusic-stream-cache-"),
+        settings,
+    )
+    .await?;
+
+    let common_options = CommonAppendOptions {
+        gapless_decode: options.gapless_decode,
+        speed_type: options.speed_type,
+        ringbuf_size: options.ringbuf_size,
+        async_decode: false,
+        record_tags: Some(RecordTags::from_track(track)),
+        normalization_gain,
+        salvage: None,
+        crossfade: options.crossfade_into,
+        trim_silence: options.trim_silence,
+        smart_speed_silence: track.as_podcast().map(|_| options.smart_speed_silence),
+    };
+    if options.enqueue {
+        append_to_sink_queue(
+            Box::new(ReadSeekSource::new(reader, file_len)),
+            url,
+            sink,
+            &common_options,
+            next_duration_opt,
+            media_title_fn,
+        )?;
+    } else {
+        append_to_sink(
+            Box::new(ReadSeekSource::new(reader, file_len)),
+            url,
+            sink,
+            &common_options,
+            total_duration,
+            media_title_fn,
+        )?;
     }
+
+    Ok(())
 }
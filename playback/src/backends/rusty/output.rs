@@ -0,0 +1,304 @@
+//! The output stream of the player, which can switch between output devices without interrupting the [`Sink`](super::sink::Sink).
+//!
+//! The sink plays on a mixer of its own, whose samples every new output stream continues to pull, so that a device can
+//! be switched, lost and re-opened while the queue keeps its sources and positions.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use rodio::cpal::traits::HostTrait as _;
+use rodio::mixer::{Mixer, MixerSource};
+use rodio::source::SeekError;
+use rodio::{
+    ChannelCount, DeviceTrait as _, OutputStream, OutputStreamBuilder, SampleRate, Source,
+};
+
+use super::PlayerInternalCmd;
+use super::source::SampleType;
+
+/// How often to check whether the output device should change, like when a headset was connected.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The channels of the player mixer, if no device could be opened at the start.
+const FALLBACK_CHANNELS: ChannelCount = 2;
+
+/// Get the names of all output devices.
+///
+/// # Errors
+///
+/// - if the output devices cannot be enumerated
+pub fn list_output_devices() -> Result<Vec<String>> {
+    let devices = rodio::cpal::default_host()
+        .output_devices()
+        .context("Enumerating the output devices")?;
+
+    Ok(devices.filter_map(|v| v.name().ok()).collect())
+}
+
+/// Find the output device named `name`, or the default device of the system if `name` is empty.
+fn find_device(name: &str) -> Option<rodio::Device> {
+    let host = rodio::cpal::default_host();
+    if name.is_empty() {
+        return host.default_output_device();
+    }
+
+    host.output_devices()
+        .ok()?
+        .find(|v| v.name().is_ok_and(|v| v == name))
+}
+
+/// The samples of the player mixer, shared so that every new output stream continues where the last one stopped.
+struct SharedOutput {
+    source: Arc<Mutex<MixerSource>>,
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+}
+
+impl Iterator for SharedOutput {
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // a mixer without sources ends, but the output has to stay for the next source
+        Some(self.source.lock().next().unwrap_or(0.0))
+    }
+}
+
+impl Source for SharedOutput {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _pos: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+/// The output stream of the player, see the module documentation.
+pub struct Output {
+    /// The mixer the sink plays on, outliving every output stream.
+    mixer: Mixer,
+    source: Arc<Mutex<MixerSource>>,
+    /// The output stream currently played on, [`None`] if no device could be opened.
+    stream: Option<OutputStream>,
+    /// Set by the error callback of `stream` once its device is gone.
+    lost: Arc<AtomicBool>,
+    /// The name of the device `stream` plays on, empty without a stream, shared with the backend.
+    device: Arc<Mutex<String>>,
+    sample_rate: SampleRate,
+    /// A fixed output buffer size, if not decided by the device
+    buffer_frames: Option<u32>,
+    picmd_tx: Sender<PlayerInternalCmd>,
+}
+
+impl Output {
+    /// Open the output on the device named `preferred`, or the default device if empty or not found.
+    ///
+    /// If no device can be opened at all, the output waits for one to appear on the next [`check`](Self::check).
+    pub fn new(
+        preferred: &str,
+        sample_rate: SampleRate,
+        buffer_frames: Option<u32>,
+        device: Arc<Mutex<String>>,
+        picmd_tx: Sender<PlayerInternalCmd>,
+    ) -> Self {
+        let lost = Arc::new(AtomicBool::new(false));
+        let opened = find_device(preferred)
+            .or_else(|| find_device(""))
+            .context("No output device found")
+            .and_then(|v| open_stream(v, sample_rate, buffer_frames, &lost, &picmd_tx));
+        let stream = match opened {
+            Ok((stream, name)) => {
+                info!("Playing on the output device \"{name}\"");
+                *device.lock() = name;
+                Some(stream)
+            }
+            Err(err) => {
+                error!("Error opening the output, waiting for a device: {err:#}");
+                None
+            }
+        };
+
+        // the mixer of the sink matches the first device, later devices convert from it
+        let (channels, mixer_rate) = stream
+            .as_ref()
+            .map_or((FALLBACK_CHANNELS, sample_rate), |v| {
+                (v.config().channel_count(), v.config().sample_rate())
+            });
+        let (mixer, source) = rodio::mixer::mixer(channels, mixer_rate);
+        let source = Arc::new(Mutex::new(source));
+
+        let output = Self {
+            mixer,
+            source,
+            stream,
+            lost,
+            device,
+            sample_rate,
+            buffer_frames,
+            picmd_tx,
+        };
+        if let Some(stream) = &output.stream {
+            stream.mixer().add(output.shared_output());
+        }
+
+        output
+    }
+
+    /// Get the mixer for the sink to play on.
+    pub fn mixer(&self) -> &Mixer {
+        &self.mixer
+    }
+
+    /// Get the name of the device played on, if it is gone and not yet replaced.
+    pub fn lost_device(&self) -> Option<String> {
+        if self.stream.is_none() || !self.lost.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        Some(self.device.lock().clone())
+    }
+
+    /// Switch to the device named `preferred` if it is available and not already played on, or to the default device if
+    /// `preferred` is empty and the default changed.
+    ///
+    /// A lost device is replaced by the default one until `preferred` appears again.
+    ///
+    /// Returns whether a new device is played on.
+    pub fn check(&mut self, preferred: &str) -> bool {
+        let lost = self.stream.is_none() || self.lost.load(Ordering::SeqCst);
+        let current = self.device.lock().clone();
+
+        let device = if preferred.is_empty() {
+            // follow the default device of the system, like a headset that became the default
+            let Some(device) = find_device("") else {
+                return false;
+            };
+            if !lost && device.name().is_ok_and(|v| v == current) {
+                return false;
+            }
+            device
+        } else if lost || current != preferred {
+            match find_device(preferred) {
+                Some(device) => device,
+                None if lost => {
+                    let Some(device) = find_device("") else {
+                        return false;
+                    };
+                    device
+                }
+                // keep playing on the current device until the preferred one appears
+                None => return false,
+            }
+        } else {
+            return false;
+        };
+
+        self.switch_to(device)
+    }
+
+    /// Replace the output stream with one on `device`, returns whether it could be opened.
+    fn switch_to(&mut self, device: rodio::Device) -> bool {
+        // close the old stream first, some devices can only be opened once
+        self.stream = None;
+        self.device.lock().clear();
+
+        self.lost = Arc::new(AtomicBool::new(false));
+        match open_stream(
+            device,
+            self.sample_rate,
+            self.buffer_frames,
+            &self.lost,
+            &self.picmd_tx,
+        ) {
+            Ok((stream, name)) => {
+                info!("Switched the output to \"{name}\"");
+                stream.mixer().add(self.shared_output());
+                *self.device.lock() = name;
+                self.stream = Some(stream);
+                true
+            }
+            Err(err) => {
+                error!("Error switching the output: {err:#}");
+                false
+            }
+        }
+    }
+
+    /// Get a new source of the samples of [`mixer`](Self::mixer), to add to a output stream.
+    fn shared_output(&self) -> SharedOutput {
+        SharedOutput {
+            source: self.source.clone(),
+            channels: self.mixer_channels(),
+            sample_rate: self.mixer_sample_rate(),
+        }
+    }
+
+    fn mixer_channels(&self) -> ChannelCount {
+        self.source.lock().channels()
+    }
+
+    fn mixer_sample_rate(&self) -> SampleRate {
+        self.source.lock().sample_rate()
+    }
+}
+
+/// Open a output stream on `device`, setting `lost` and requesting a [`Output::check`] once the device is gone.
+///
+/// Returns the stream and the name of the device.
+fn open_stream(
+    device: rodio::Device,
+    sample_rate: SampleRate,
+    buffer_frames: Option<u32>,
+    lost: &Arc<AtomicBool>,
+    picmd_tx: &Sender<PlayerInternalCmd>,
+) -> Result<(OutputStream, String)> {
+    let name = device.name().unwrap_or_else(|_| "<unnamed>".to_string());
+    let lost = lost.clone();
+    let picmd_tx = picmd_tx.clone();
+
+    let mut builder = OutputStreamBuilder::from_device(device)?
+        .with_sample_rate(sample_rate)
+        .with_error_callback(move |err| {
+            if matches!(err, rodio::cpal::StreamError::DeviceNotAvailable) {
+                warn!("The output device is gone");
+                lost.store(true, Ordering::SeqCst);
+                let _ = picmd_tx.send(PlayerInternalCmd::CheckOutput);
+            } else {
+                error!("Error in the output stream: {err}");
+            }
+        });
+    if let Some(frames) = buffer_frames {
+        builder = builder.with_buffer_size(rodio::cpal::BufferSize::Fixed(frames));
+    }
+    let mut stream = builder
+        .open_stream_or_fallback()
+        .with_context(|| format!("Opening an output stream on \"{name}\""))?;
+    // the stream is dropped on every switch, which is not worth a message
+    stream.log_on_drop(false);
+
+    Ok((stream, name))
+}
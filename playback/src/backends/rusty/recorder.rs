@@ -0,0 +1,476 @@
+//! Recording what is playing to files, encoded by `ffmpeg`.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local};
+use parking_lot::Mutex;
+use termusiclib::config::v2::server::recording::{RecordingFormat, RecordingSettings};
+use termusiclib::track::Track;
+
+use super::source::SampleType;
+
+/// The amount of sample chunks that can be queued for the writer, before new ones get dropped.
+const QUEUE_CHUNKS: usize = 64;
+
+/// The amount of written sample chunks kept to reuse their allocation, see [`RecordTap::take_buffer`].
+const POOL_CHUNKS: usize = 8;
+
+/// Sample chunks the recorder thread is done with.
+type ChunkPool = Arc<Mutex<Vec<Vec<SampleType>>>>;
+
+/// Tags to write to a recording file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordTags {
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+}
+
+impl RecordTags {
+    /// Get the tags for `track`.
+    #[must_use]
+    pub fn from_track(track: &Track) -> Self {
+        Self {
+            artist: track.artist().map(ToString::to_string),
+            title: track.title().map(ToString::to_string),
+            album: track
+                .as_track()
+                .and_then(|v| v.album())
+                .map(ToString::to_string),
+        }
+    }
+
+    /// Get the tags from a ICY stream title, which is commonly `Artist - Title`, and the `station` as album.
+    #[must_use]
+    pub fn from_icy(stream_title: &str, station: Option<&str>) -> Self {
+        let (artist, title) = match stream_title.split_once(" - ") {
+            Some((artist, title)) => (Some(artist.trim()), title.trim()),
+            None => (None, stream_title.trim()),
+        };
+
+        Self {
+            artist: artist.filter(|v| !v.is_empty()).map(ToString::to_string),
+            title: Some(title)
+                .filter(|v| !v.is_empty())
+                .map(ToString::to_string),
+            album: station.map(ToString::to_string),
+        }
+    }
+}
+
+/// Messages from the playback to the recorder thread.
+#[derive(Debug)]
+enum RecordMsg {
+    /// Interleaved samples in the given format.
+    Samples {
+        channels: u16,
+        sample_rate: u32,
+        samples: Vec<SampleType>,
+    },
+    /// The tags for the following samples, different tags start a new file.
+    Tags(RecordTags),
+}
+
+/// The shared handle between the playback sources and the recorder, which is only connected while recording.
+#[derive(Debug, Default)]
+pub struct RecordTap {
+    active: AtomicBool,
+    tx: Mutex<Option<SyncSender<RecordMsg>>>,
+    /// The last tags of a stream that updates its own tags, like a ICY radio stream.
+    stream_tags: Mutex<Option<RecordTags>>,
+    pool: ChunkPool,
+}
+
+impl RecordTap {
+    /// Get whether recording is currently active.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Start recording to a new file in the configured directory, does nothing if already recording.
+    ///
+    /// # Errors
+    ///
+    /// - if `ffmpeg` cannot be run
+    /// - if the recording directory cannot be created
+    pub fn start(&self, settings: &RecordingSettings) -> Result<()> {
+        let mut tx = self.tx.lock();
+        if tx.is_some() {
+            return Ok(());
+        }
+
+        check_ffmpeg()?;
+
+        let dir = settings.dir.clone();
+        std::fs::create_dir_all(&dir).with_context(|| dir.display().to_string())?;
+        let format = settings.format;
+
+        let (new_tx, rx) = mpsc::sync_channel(QUEUE_CHUNKS);
+        let pool = self.pool.clone();
+        std::thread::Builder::new()
+            .name("playback recorder".into())
+            .spawn(move || record_loop(&rx, &dir, format, &pool))
+            .context("failed to spawn recorder thread")?;
+
+        *tx = Some(new_tx);
+        self.active.store(true, Ordering::Relaxed);
+        info!("Started recording to {}", settings.dir.display());
+
+        Ok(())
+    }
+
+    /// Stop recording, finishing the current file.
+    pub fn stop(&self) {
+        self.active.store(false, Ordering::Relaxed);
+        // dropping the sender makes the recorder thread finish
+        if self.tx.lock().take().is_some() {
+            info!("Stopped recording");
+        }
+    }
+
+    /// Set the tags of a stream that updates its own tags, starting a new file if recording.
+    pub fn set_stream_tags(&self, tags: RecordTags) {
+        *self.stream_tags.lock() = Some(tags.clone());
+        self.send(RecordMsg::Tags(tags));
+    }
+
+    /// Get the last tags set via [`set_stream_tags`](Self::set_stream_tags).
+    pub fn stream_tags(&self) -> Option<RecordTags> {
+        self.stream_tags.lock().clone()
+    }
+
+    /// Set the tags for the following samples.
+    pub fn send_tags(&self, tags: RecordTags) {
+        self.send(RecordMsg::Tags(tags));
+    }
+
+    /// Get a empty buffer for the next chunk of samples, reusing a chunk already written if possible.
+    ///
+    /// This never blocks, as it is called from the audio thread.
+    pub fn take_buffer(&self, capacity: usize) -> Vec<SampleType> {
+        let reused = self.pool.try_lock().and_then(|mut pool| pool.pop());
+        match reused {
+            Some(mut buf) => {
+                buf.reserve(capacity);
+                buf
+            }
+            None => Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Record interleaved `samples`, its buffer should be from [`take_buffer`](Self::take_buffer).
+    pub fn send_samples(&self, channels: u16, sample_rate: u32, samples: Vec<SampleType>) {
+        self.send(RecordMsg::Samples {
+            channels,
+            sample_rate,
+            samples,
+        });
+    }
+
+    /// Send `msg` to the recorder thread, if recording.
+    ///
+    /// This never blocks, as it is called from the audio thread.
+    fn send(&self, msg: RecordMsg) {
+        let Some(tx) = self.tx.try_lock() else {
+            return;
+        };
+        let Some(tx) = tx.as_ref() else {
+            return;
+        };
+
+        match tx.try_send(msg) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => warn!("Recorder is too slow, dropping samples"),
+            Err(TrySendError::Disconnected(_)) => {
+                self.active.store(false, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Check that `ffmpeg` can be run, to fail early instead of for every file.
+pub(super) fn check_ffmpeg() -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .arg("-version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("failed to run ffmpeg, is it installed?")?;
+    if !status.success() {
+        bail!("ffmpeg failed ({status})");
+    }
+
+    Ok(())
+}
+
+/// A file currently being recorded to.
+pub(super) struct RecordFile {
+    child: Child,
+    stdin: ChildStdin,
+    channels: u16,
+    sample_rate: u32,
+    path: PathBuf,
+    /// Reused buffer for the samples as bytes.
+    buf: Vec<u8>,
+}
+
+impl RecordFile {
+    /// Start `ffmpeg` encoding raw samples to a new file in `dir`.
+    fn new(
+        dir: &Path,
+        format: RecordingFormat,
+        tags: &RecordTags,
+        channels: u16,
+        sample_rate: u32,
+    ) -> Result<Self> {
+        let path = dir.join(record_file_name(Local::now(), tags, format));
+
+        Self::create(path, format, tags, channels, sample_rate)
+    }
+
+    /// Start `ffmpeg` encoding raw samples to `path`, overwriting it if it exists.
+    pub(super) fn create(
+        path: PathBuf,
+        format: RecordingFormat,
+        tags: &RecordTags,
+        channels: u16,
+        sample_rate: u32,
+    ) -> Result<Self> {
+        let (muxer, codec) = format.ffmpeg_format();
+
+        let mut cmd = Command::new("ffmpeg");
+        cmd.args(["-y", "-loglevel", "error", "-f", "f32le", "-ar"])
+            .arg(sample_rate.to_string())
+            .arg("-ac")
+            .arg(channels.to_string())
+            .args(["-i", "-"]);
+        for (key, value) in [
+            ("artist", &tags.artist),
+            ("title", &tags.title),
+            ("album", &tags.album),
+        ] {
+            if let Some(value) = value {
+                cmd.arg("-metadata").arg(format!("{key}={value}"));
+            }
+        }
+        let mut child = cmd
+            .args(["-codec:a", codec, "-f", muxer])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("failed to run ffmpeg")?;
+        let stdin = child.stdin.take().context("ffmpeg stdin")?;
+
+        info!("Recording to {}", path.display());
+
+        Ok(Self {
+            child,
+            stdin,
+            channels,
+            sample_rate,
+            path,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Write `samples` to the encoder.
+    pub(super) fn write(&mut self, samples: &[SampleType]) -> Result<()> {
+        self.buf.clear();
+        for sample in samples {
+            self.buf.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        self.stdin
+            .write_all(&self.buf)
+            .with_context(|| self.path.display().to_string())
+    }
+
+    /// Close the input and wait for the encoder to finish the file.
+    fn finish(self) {
+        let path = self.path.clone();
+        match self.close() {
+            Ok(()) => info!("Finished recording {}", path.display()),
+            Err(err) => error!("Error finishing {}: {err:#}", path.display()),
+        }
+    }
+
+    /// Close the input and wait for the encoder to finish the file, returning whether it succeeded.
+    pub(super) fn close(self) -> Result<()> {
+        let Self {
+            mut child, stdin, ..
+        } = self;
+        drop(stdin);
+
+        let status = child.wait().context("failed to wait for ffmpeg")?;
+        if !status.success() {
+            bail!("ffmpeg failed ({status})");
+        }
+
+        Ok(())
+    }
+}
+
+/// The recorder thread, writing all samples until the sender is dropped.
+fn record_loop(rx: &Receiver<RecordMsg>, dir: &Path, format: RecordingFormat, pool: &ChunkPool) {
+    let mut tags = RecordTags::default();
+    let mut current: Option<RecordFile> = None;
+    // do not retry creating a file for every chunk, only after the tags change
+    let mut failed = false;
+
+    while let Ok(msg) = rx.recv() {
+        match msg {
+            RecordMsg::Tags(new_tags) => {
+                if new_tags != tags {
+                    tags = new_tags;
+                    failed = false;
+                    if let Some(file) = current.take() {
+                        file.finish();
+                    }
+                }
+            }
+            RecordMsg::Samples {
+                channels,
+                sample_rate,
+                mut samples,
+            } => {
+                if let Some(file) = current
+                    .take_if(|file| file.channels != channels || file.sample_rate != sample_rate)
+                {
+                    file.finish();
+                }
+                if current.is_none() && !failed {
+                    match RecordFile::new(dir, format, &tags, channels, sample_rate) {
+                        Ok(file) => current = Some(file),
+                        Err(err) => {
+                            error!("Error starting a recording: {err:#}");
+                            failed = true;
+                        }
+                    }
+                }
+
+                if let Some(file) = current.as_mut()
+                    && let Err(err) = file.write(&samples)
+                {
+                    error!("Error writing the recording: {err:#}");
+                    failed = true;
+                    if let Some(file) = current.take() {
+                        file.finish();
+                    }
+                }
+
+                let mut pool = pool.lock();
+                if pool.len() < POOL_CHUNKS {
+                    samples.clear();
+                    pool.push(samples);
+                }
+            }
+        }
+    }
+
+    if let Some(file) = current {
+        file.finish();
+    }
+}
+
+/// Get the file name for a recording started at `time`, like `2024-01-01_12-00-00 Artist - Title.flac`.
+fn record_file_name(time: DateTime<Local>, tags: &RecordTags, format: RecordingFormat) -> String {
+    let label = match (&tags.artist, &tags.title) {
+        (Some(artist), Some(title)) => format!("{artist} - {title}"),
+        (None, Some(title)) => title.clone(),
+        (Some(artist), None) => artist.clone(),
+        (None, None) => "recording".to_string(),
+    };
+    // replace characters that are not allowed in file names on some systems
+    let label: String = label
+        .chars()
+        .map(|ch| {
+            if matches!(ch, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || ch.is_control()
+            {
+                '_'
+            } else {
+                ch
+            }
+        })
+        .collect();
+
+    format!(
+        "{} {label}.{}",
+        time.format("%Y-%m-%d_%H-%M-%S"),
+        format.extension()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+    use pretty_assertions::assert_eq;
+    use termusiclib::config::v2::server::recording::RecordingFormat;
+
+    use super::{RecordTags, RecordTap, record_file_name};
+
+    #[test]
+    fn should_reuse_chunks() {
+        let tap = RecordTap::default();
+        assert!(tap.take_buffer(16).capacity() >= 16);
+
+        let mut written = Vec::with_capacity(64);
+        written.push(1.0);
+        written.clear();
+        let ptr = written.as_ptr();
+        tap.pool.lock().push(written);
+
+        let buf = tap.take_buffer(16);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert!(tap.pool.lock().is_empty());
+    }
+
+    #[test]
+    fn should_parse_icy_tags() {
+        assert_eq!(
+            RecordTags::from_icy("Some Artist - Some Title", Some("Radio")),
+            RecordTags {
+                artist: Some("Some Artist".to_string()),
+                title: Some("Some Title".to_string()),
+                album: Some("Radio".to_string()),
+            }
+        );
+        assert_eq!(
+            RecordTags::from_icy("Only a Title", None),
+            RecordTags {
+                artist: None,
+                title: Some("Only a Title".to_string()),
+                album: None,
+            }
+        );
+        assert_eq!(RecordTags::from_icy("", None), RecordTags::default());
+    }
+
+    #[test]
+    fn should_name_record_files() {
+        let time = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let tags = RecordTags {
+            artist: Some("AC/DC".to_string()),
+            title: Some("Back: In Black?".to_string()),
+            album: None,
+        };
+
+        assert_eq!(
+            record_file_name(time, &tags, RecordingFormat::Flac),
+            "2024-01-02_03-04-05 AC_DC - Back_ In Black_.flac"
+        );
+        assert_eq!(
+            record_file_name(time, &RecordTags::default(), RecordingFormat::Ogg),
+            "2024-01-02_03-04-05 recording.ogg"
+        );
+    }
+}
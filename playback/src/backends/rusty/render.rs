@@ -0,0 +1,407 @@
+//! Rendering a queue of tracks to a single file and a CUE sheet, like a mixtape, with the same sources as the
+//! playback, but as fast as the tracks can be decoded and encoded.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use rodio::source::UniformSourceIterator;
+use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+use termusiclib::config::v2::server::backends::CrossfadeCurve;
+use termusiclib::config::v2::server::equalizer::BANDS;
+use termusiclib::config::v2::server::recording::RecordingFormat;
+use termusiclib::config::v2::server::{NormalizationMode, ServerSettings};
+use termusiclib::track::{StreamLocation, Track, TrackData};
+
+use super::decoder::Symphonia;
+use super::decoder::buffered_source::BufferedSource;
+use super::recorder::{RecordFile, RecordTags, check_ffmpeg};
+use super::source::{SampleType, SourceExt};
+
+/// The channels of a rendered file.
+const CHANNELS: u16 = 2;
+
+/// The amount of samples written to the encoder at once.
+const CHUNK_SAMPLES: usize = 16 * 1024;
+
+/// The frames per second of a CUE sheet timestamp.
+const CUE_FRAMES: u64 = 75;
+
+/// The options of the playback to render with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub format: RecordingFormat,
+    pub sample_rate: u32,
+    /// How long the tracks crossfade, zero for none
+    pub crossfade: Duration,
+    pub crossfade_curve: CrossfadeCurve,
+    /// The gains of the equalizer bands, [`None`] if disabled
+    pub equalizer: Option<[f32; BANDS]>,
+    /// Which gain to normalize with, [`None`] if disabled
+    pub normalization: Option<NormalizationMode>,
+    /// Whether to drop the silence at the start and the end of tracks
+    pub trim_silence: bool,
+}
+
+impl RenderOptions {
+    /// Get the options of the rusty backend in `settings`, rendering to `format`.
+    #[must_use]
+    pub fn from_settings(settings: &ServerSettings, format: RecordingFormat) -> Self {
+        Self {
+            format,
+            sample_rate: settings.backends.rusty.output_sample_rate,
+            crossfade: settings.backends.rusty.crossfade(),
+            crossfade_curve: settings.backends.rusty.crossfade_curve,
+            equalizer: settings.equalizer.active_gains(),
+            normalization: settings
+                .player
+                .normalization
+                .then_some(settings.player.normalization_mode),
+            trim_silence: settings.player.trim_silence,
+        }
+    }
+}
+
+/// A track of a rendered file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedTrack {
+    pub path: PathBuf,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    /// Where the track starts in the rendered file, which is the start of the crossfade into it
+    pub start: Duration,
+}
+
+/// The result of [`render_queue`].
+#[derive(Debug)]
+pub struct RenderReport {
+    pub tracks: Vec<RenderedTrack>,
+    /// The tracks that could not be rendered, like radio streams or files that failed to decode
+    pub skipped: Vec<(String, anyhow::Error)>,
+    /// The duration of the rendered file
+    pub duration: Duration,
+    pub elapsed: Duration,
+    /// The path of the written CUE sheet
+    pub cue_path: PathBuf,
+}
+
+impl RenderReport {
+    /// Get how many times faster than real time the rendering was.
+    #[must_use]
+    pub fn speed(&self) -> f64 {
+        self.duration.as_secs_f64() / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Mixes the tracks into the encoder, holding back the end of the current track to crossfade it with the next one.
+struct Mixer {
+    file: RecordFile,
+    sample_rate: u32,
+    /// The end of the current track, as long as the crossfade
+    tail: VecDeque<SampleType>,
+    /// The length of a crossfade in samples
+    crossfade_samples: usize,
+    curve: CrossfadeCurve,
+    chunk: Vec<SampleType>,
+    /// The amount of samples given to the encoder
+    written: u64,
+}
+
+impl Mixer {
+    /// Get the position of the next sample to write.
+    fn position(&self) -> Duration {
+        let frames = self.written / u64::from(CHANNELS);
+        Duration::from_secs(frames / u64::from(self.sample_rate))
+            + Duration::from_nanos(
+                frames % u64::from(self.sample_rate) * 1_000_000_000 / u64::from(self.sample_rate),
+            )
+    }
+
+    /// Add `sample` to the encoder, writing full chunks.
+    fn push(&mut self, sample: SampleType) -> Result<()> {
+        self.chunk.push(sample);
+        self.written += 1;
+        if self.chunk.len() >= CHUNK_SAMPLES {
+            self.file.write(&self.chunk)?;
+            self.chunk.clear();
+        }
+
+        Ok(())
+    }
+
+    /// Add the samples of `source` after the held back end of the track before it, crossfading if `crossfade`.
+    ///
+    /// Returns where the track starts in the rendered file.
+    fn add_track(
+        &mut self,
+        mut source: impl Iterator<Item = SampleType>,
+        crossfade: bool,
+    ) -> Result<Duration> {
+        if !crossfade {
+            while let Some(sample) = self.tail.pop_front() {
+                self.push(sample)?;
+            }
+        }
+        let start = self.position();
+
+        let fade_len = self.tail.len();
+        for idx in 0..fade_len {
+            #[allow(clippy::cast_precision_loss)]
+            let progress = idx as f32 / fade_len as f32;
+            let previous = self.tail.pop_front().unwrap_or_default();
+            let sample = source.next().unwrap_or_default();
+            let mixed =
+                previous * self.curve.gain(1.0 - progress) + sample * self.curve.gain(progress);
+            self.push(mixed)?;
+        }
+
+        for sample in source {
+            self.tail.push_back(sample);
+            if self.tail.len() > self.crossfade_samples
+                && let Some(sample) = self.tail.pop_front()
+            {
+                self.push(sample)?;
+            }
+        }
+
+        Ok(start)
+    }
+
+    /// Write the rest and wait for the encoder to finish, returning the duration of the file.
+    fn finish(mut self) -> Result<Duration> {
+        while let Some(sample) = self.tail.pop_front() {
+            self.push(sample)?;
+        }
+        if !self.chunk.is_empty() {
+            self.file.write(&self.chunk)?;
+        }
+        let duration = self.position();
+        self.file.close()?;
+
+        Ok(duration)
+    }
+}
+
+/// Open the local file of `track` for rendering with `options`.
+fn open_track(
+    track: &Track,
+    options: &RenderOptions,
+) -> Result<impl Iterator<Item = SampleType> + use<>> {
+    let source = track.source();
+    let StreamLocation::File(path) = source.stream() else {
+        bail!("only local files can be rendered");
+    };
+    let file = File::open(path).context("Failed to open file")?;
+    let mss = MediaSourceStream::new(
+        Box::new(BufferedSource::new_default_size(file)),
+        MediaSourceStreamOptions::default(),
+    );
+    let (decoder, _) = Symphonia::new(mss, true, false, None)?;
+
+    let gain = options.normalization.and_then(|mode| {
+        track
+            .as_track()
+            .and_then(|v| v.normalization_gain_for(mode))
+    });
+    let source = decoder
+        .normalize(gain.unwrap_or_default(), gain.is_some())
+        .equalize(options.equalizer)
+        .skip_silence(options.trim_silence, None);
+
+    Ok(UniformSourceIterator::new(
+        source,
+        CHANNELS,
+        options.sample_rate,
+    ))
+}
+
+/// Render the local files of `tracks` to `output` with `options`, and write a CUE sheet of them next to it.
+///
+/// Consecutive tracks of the same album are not crossfaded, like in the playback.
+///
+/// # Errors
+///
+/// - if `ffmpeg` cannot be run
+/// - if no track could be rendered
+/// - if the file or the CUE sheet cannot be written
+pub fn render_queue(
+    tracks: &[Track],
+    output: &Path,
+    options: &RenderOptions,
+) -> Result<RenderReport> {
+    let started = Instant::now();
+    check_ffmpeg()?;
+
+    let title = output
+        .file_stem()
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_tags = RecordTags {
+        artist: None,
+        title: None,
+        album: Some(title.clone()),
+    };
+    let file = RecordFile::create(
+        output.to_path_buf(),
+        options.format,
+        &file_tags,
+        CHANNELS,
+        options.sample_rate,
+    )?;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let crossfade_samples = (options.crossfade.as_millis() as usize)
+        * options.sample_rate as usize
+        * usize::from(CHANNELS)
+        / 1000;
+    let mut mixer = Mixer {
+        file,
+        sample_rate: options.sample_rate,
+        tail: VecDeque::with_capacity(crossfade_samples + 1),
+        crossfade_samples,
+        curve: options.crossfade_curve,
+        chunk: Vec::with_capacity(CHUNK_SAMPLES),
+        written: 0,
+    };
+
+    let mut rendered = Vec::new();
+    let mut skipped = Vec::new();
+    let mut last_album: Option<String> = None;
+    for track in tracks {
+        let source = match open_track(track, options) {
+            Ok(v) => v,
+            Err(err) => {
+                skipped.push((track.source().location().into_owned(), err));
+                continue;
+            }
+        };
+
+        let album = track
+            .as_track()
+            .and_then(TrackData::album)
+            .filter(|v| !v.is_empty())
+            .map(ToString::to_string);
+        let crossfade = album.is_none() || album != last_album;
+        last_album = album;
+
+        let start = mixer.add_track(source, crossfade)?;
+        info!("Rendered {}", track.source().location());
+        rendered.push(RenderedTrack {
+            path: track.path().map(Path::to_path_buf).unwrap_or_default(),
+            artist: track.artist().map(ToString::to_string),
+            title: track.title().map(ToString::to_string),
+            start,
+        });
+    }
+
+    let duration = mixer.finish()?;
+    if rendered.is_empty() {
+        let _ = std::fs::remove_file(output);
+        bail!("No track of the queue could be rendered");
+    }
+
+    let cue_path = output.with_extension("cue");
+    let file_name = output
+        .file_name()
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or_default();
+    std::fs::write(&cue_path, cue_sheet(&title, &file_name, &rendered))
+        .with_context(|| cue_path.display().to_string())?;
+
+    Ok(RenderReport {
+        tracks: rendered,
+        skipped,
+        duration,
+        elapsed: started.elapsed(),
+        cue_path,
+    })
+}
+
+/// Format `duration` as a CUE sheet timestamp, `minutes:seconds:frames` with 75 frames per second.
+fn cue_time(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let frames = u64::from(duration.subsec_millis()) * CUE_FRAMES / 1000;
+
+    format!("{:02}:{:02}:{frames:02}", secs / 60, secs % 60)
+}
+
+/// Quote `value` for a CUE sheet, which cannot escape double quotes.
+fn cue_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+/// Get the CUE sheet titled `title` of the `tracks` in the file `file_name`.
+fn cue_sheet(title: &str, file_name: &str, tracks: &[RenderedTrack]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "TITLE {}", cue_quote(title));
+    let _ = writeln!(out, "FILE {} WAVE", cue_quote(file_name));
+
+    for (idx, track) in tracks.iter().enumerate() {
+        let _ = writeln!(out, "  TRACK {:02} AUDIO", idx + 1);
+        let title = track.title.clone().unwrap_or_else(|| {
+            track
+                .path
+                .file_stem()
+                .map(|v| v.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let _ = writeln!(out, "    TITLE {}", cue_quote(&title));
+        if let Some(artist) = &track.artist {
+            let _ = writeln!(out, "    PERFORMER {}", cue_quote(artist));
+        }
+        let _ = writeln!(out, "    INDEX 01 {}", cue_time(track.start));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{RenderedTrack, cue_sheet, cue_time};
+
+    #[test]
+    fn should_format_cue_time() {
+        assert_eq!(cue_time(Duration::ZERO), "00:00:00");
+        assert_eq!(cue_time(Duration::from_millis(61_500)), "01:01:37");
+        assert_eq!(cue_time(Duration::from_secs(100 * 60)), "100:00:00");
+    }
+
+    #[test]
+    fn should_write_cue_sheet() {
+        let tracks = [
+            RenderedTrack {
+                path: PathBuf::from("/music/first.flac"),
+                artist: Some("Some \"Artist\"".to_string()),
+                title: Some("First".to_string()),
+                start: Duration::ZERO,
+            },
+            RenderedTrack {
+                path: PathBuf::from("/music/second.mp3"),
+                artist: None,
+                title: None,
+                start: Duration::from_secs(185),
+            },
+        ];
+
+        assert_eq!(
+            cue_sheet("mixtape", "mixtape.flac", &tracks),
+            "TITLE \"mixtape\"\n\
+             FILE \"mixtape.flac\" WAVE\n\
+             \x20 TRACK 01 AUDIO\n\
+             \x20   TITLE \"First\"\n\
+             \x20   PERFORMER \"Some 'Artist'\"\n\
+             \x20   INDEX 01 00:00:00\n\
+             \x20 TRACK 02 AUDIO\n\
+             \x20   TITLE \"second\"\n\
+             \x20   INDEX 01 03:05:00\n"
+        );
+    }
+}
@@ -3,13 +3,17 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use parking_lot::{Mutex, RwLock};
 use rodio::mixer::Mixer;
+use rodio::source::SeekError;
 use rodio::{Source, queue};
+use termusiclib::config::v2::server::backends::CrossfadeCurve;
+use termusiclib::config::v2::server::equalizer::BANDS;
 
 use super::PlayerInternalCmd;
+use super::recorder::{RecordTags, RecordTap};
 use super::source::SourceExt as _;
 use super::source::{SampleType, SpecificType};
 use crate::PlayerCmd;
@@ -30,6 +34,11 @@ pub struct Sink {
 
     picmd_tx: Sender<PlayerInternalCmd>,
     pcmd_tx: crate::PlayerCmdSender,
+
+    /// The recorder every source is copied to.
+    record: Arc<RecordTap>,
+    /// The mixer the queue plays on, which also plays the sources fading out in a crossfade.
+    mixer: Option<Mixer>,
 }
 
 /// The Controls for the Sink, most values store the value to be applied while some others store a remaining amount.
@@ -50,25 +59,370 @@ struct Controls {
     ///
     /// Automatically gets reset to `false` once cleared.
     stopped_no_eos: AtomicBool,
+    /// Stores whether the fade-out for the current stop has finished.
+    ///
+    /// Automatically gets reset to `false` once cleared.
+    stop_faded: AtomicBool,
     /// Stores the speed to be applied.
     speed: Mutex<f32>,
+    /// Stores whether to apply vocal reduction.
+    vocal_reduction: AtomicBool,
+    /// Stores whether to apply the normalization gain of the sources.
+    normalization: AtomicBool,
+    /// Stores whether to shorten long silences of the sources they apply to, see [`SourceOptions::smart_speed_silence`].
+    smart_speed: AtomicBool,
+    /// Stores the gains of the equalizer bands, [`None`] if disabled.
+    equalizer: Mutex<Option<[f32; BANDS]>>,
+    /// Stores how long to fade-in after resuming, seeking and starting while nothing was playing.
+    fade_in: Mutex<Duration>,
+    /// Stores how long to fade-out before pausing, seeking and stopping.
+    fade_out: Mutex<Duration>,
     /// Stores how many sources should be skipped.
     ///
     /// Used for skipping / clearing while accounting for the case that a new source is added before finishing clearing.
     to_clear: Mutex<u32>,
     /// The current position in the currently playing source (may be off by a few milliseconds).
     position: RwLock<Duration>,
+    /// Stores how long to crossfade between sources, zero disables the crossfade.
+    crossfade: Mutex<Duration>,
+    /// Stores the volume curve of the crossfade.
+    crossfade_curve: Mutex<CrossfadeCurve>,
+    /// Stores whether the source queued after the current one may be crossfaded into at the end of the current one.
+    crossfade_next: AtomicBool,
+    /// The fade-in for the next source to start, set when a source fades out for a crossfade.
+    ///
+    /// Automatically gets reset once taken, or ignored once the fade-out would have finished.
+    crossfade_in: Mutex<Option<(Duration, Instant)>>,
+    /// The state of the currently playing source, [`None`] if no source is playing.
+    current: Mutex<Option<Arc<SourceState>>>,
+}
+
+impl Controls {
+    /// Take the fade-in of a crossfade, if a fade-out is still ongoing.
+    fn take_crossfade_in(&self) -> Option<Duration> {
+        self.crossfade_in
+            .lock()
+            .take()
+            .and_then(|(duration, since)| duration.checked_sub(since.elapsed()))
+            .filter(|v| !v.is_zero())
+    }
+
+    /// Unset the current source if it is `state`.
+    fn unset_current(&self, state: &Arc<SourceState>) {
+        let mut current = self.current.lock();
+        if current.as_ref().is_some_and(|v| Arc::ptr_eq(v, state)) {
+            current.take();
+        }
+    }
+}
+
+/// The crossfade state of a single appended source.
+#[derive(Debug, Default)]
+struct SourceState {
+    /// Whether a hand-off to a [`CrossfadeLane`] has been requested, see `handoff`.
+    handoff_requested: AtomicBool,
+    /// The duration of the requested fade-out.
+    handoff: Mutex<Option<Duration>>,
+    /// Whether the source is fading out on its own [`CrossfadeLane`], while the queue went on to the next source.
+    handed_off: AtomicBool,
+    /// Whether to not send a EOS when handed off.
+    no_eos: AtomicBool,
+}
+
+impl SourceState {
+    /// Request to fade this source out over `duration` on its own lane, returns `false` if it already is.
+    fn request_handoff(&self, duration: Duration, no_eos: bool) -> bool {
+        if self.handed_off.load(Ordering::SeqCst)
+            || self.handoff_requested.swap(true, Ordering::SeqCst)
+        {
+            return false;
+        }
+        self.no_eos.store(no_eos, Ordering::SeqCst);
+        *self.handoff.lock() = Some(duration);
+
+        true
+    }
+}
+
+/// A fade of a [`CrossfadeCurve`] over a amount of samples.
+#[derive(Debug, Clone)]
+struct Ramp {
+    curve: CrossfadeCurve,
+    /// Whether the gain rises or falls.
+    fade_in: bool,
+    /// The progress between `0.0` and `1.0`.
+    progress: f32,
+    /// The progress per sample.
+    step: f32,
+}
+
+impl Ramp {
+    fn new(
+        curve: CrossfadeCurve,
+        fade_in: bool,
+        duration: Duration,
+        source: &impl Source<Item = SampleType>,
+    ) -> Self {
+        let mut ramp = Self {
+            curve,
+            fade_in,
+            progress: 0.0,
+            step: 0.0,
+        };
+        ramp.finish_in(duration, source);
+
+        ramp
+    }
+
+    /// Finish the rest of the fade within `duration`, if that is sooner.
+    fn finish_in(&mut self, duration: Duration, source: &impl Source<Item = SampleType>) {
+        #[allow(clippy::cast_precision_loss)]
+        let samples = duration.as_secs_f32()
+            * source.sample_rate() as f32
+            * f32::from(source.channels().max(1));
+        let step = if samples < 1.0 {
+            1.0
+        } else {
+            (1.0 - self.progress) / samples
+        };
+        self.step = self.step.max(step);
+    }
+
+    #[inline]
+    fn is_done(&self) -> bool {
+        self.progress >= 1.0
+    }
+
+    /// Get the gain for the next sample, and only progress if `advance`.
+    #[inline]
+    fn next_gain(&mut self, advance: bool) -> f32 {
+        let gain = if self.fade_in {
+            self.curve.gain(self.progress)
+        } else {
+            self.curve.gain(1.0 - self.progress)
+        };
+        if advance {
+            self.progress = (self.progress + self.step).min(1.0);
+        }
+
+        gain
+    }
+}
+
+/// A source in the queue, which can be handed off to its own [`CrossfadeLane`] to fade out, while the queue goes on
+/// to the next source.
+///
+/// Also fades in when it starts while the source before it fades out.
+struct Crossfadable<S> {
+    inner: Arc<Mutex<S>>,
+    state: Arc<SourceState>,
+    controls: Arc<Controls>,
+    /// The mixer to add the [`CrossfadeLane`] on, no crossfade is possible without it.
+    mixer: Option<Mixer>,
+    /// Whether the first sample has been taken.
+    started: bool,
+    fade_in: Option<Ramp>,
+}
+
+impl<S> Crossfadable<S>
+where
+    S: Source<Item = SampleType> + Send + 'static,
+{
+    /// Hand off the rest of the source to a [`CrossfadeLane`] fading out over `duration`.
+    ///
+    /// Returns `false` if there is no mixer to hand off to.
+    fn handoff(&mut self, duration: Duration) -> bool {
+        let Some(mixer) = &self.mixer else {
+            return false;
+        };
+        self.controls.unset_current(&self.state);
+
+        let curve = *self.controls.crossfade_curve.lock();
+        let fade_out = Ramp::new(curve, false, duration, &*self.inner.lock());
+        self.state.handed_off.store(true, Ordering::SeqCst);
+        mixer.add(CrossfadeLane {
+            inner: self.inner.clone(),
+            fade_out,
+            controls: self.controls.clone(),
+            stopping: false,
+        });
+
+        *self.controls.crossfade_in.lock() = Some((duration, Instant::now()));
+        // the new next source decides again when it is appended
+        self.controls.crossfade_next.store(false, Ordering::SeqCst);
+
+        true
+    }
+}
+
+impl<S> Iterator for Crossfadable<S>
+where
+    S: Source<Item = SampleType> + Send + 'static,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            *self.controls.current.lock() = Some(self.state.clone());
+            if let Some(duration) = self.controls.take_crossfade_in() {
+                let curve = *self.controls.crossfade_curve.lock();
+                self.fade_in = Some(Ramp::new(curve, true, duration, &*self.inner.lock()));
+            }
+        }
+
+        if self.state.handoff_requested.load(Ordering::SeqCst) {
+            let handoff = self.state.handoff.lock().take();
+            if let Some(duration) = handoff
+                && self.handoff(duration)
+            {
+                return None;
+            }
+        }
+
+        let Some(sample) = self.inner.lock().next() else {
+            self.controls.unset_current(&self.state);
+            return None;
+        };
+
+        let Some(fade_in) = &mut self.fade_in else {
+            return Some(sample);
+        };
+        let gain = fade_in.next_gain(!self.controls.pause.load(Ordering::SeqCst));
+        if fade_in.is_done() {
+            self.fade_in = None;
+        }
+
+        Some(sample * gain)
+    }
+}
+
+impl<S> Source for Crossfadable<S>
+where
+    S: Source<Item = SampleType> + Send + 'static,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.lock().current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.lock().channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.lock().sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.lock().total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.lock().try_seek(pos)
+    }
+}
+
+/// The rest of a source fading out on its own on the mixer, while the queue already plays the next source.
+struct CrossfadeLane<S> {
+    inner: Arc<Mutex<S>>,
+    fade_out: Ramp,
+    controls: Arc<Controls>,
+    /// Whether the sink got stopped and the fade-out got shortened to the stop fade-out.
+    stopping: bool,
+}
+
+impl<S> Iterator for CrossfadeLane<S>
+where
+    S: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.stopping && self.controls.stopped.load(Ordering::SeqCst) {
+            self.stopping = true;
+            let fade_out = *self.controls.fade_out.lock();
+            self.fade_out.finish_in(fade_out, &*self.inner.lock());
+        }
+        if self.fade_out.is_done() {
+            return None;
+        }
+
+        let sample = self.inner.lock().next()?;
+        let gain = self
+            .fade_out
+            .next_gain(!self.controls.pause.load(Ordering::SeqCst));
+
+        Some(sample * gain)
+    }
+}
+
+impl<S> Source for CrossfadeLane<S>
+where
+    S: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.lock().current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.lock().channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.lock().sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _pos: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
 }
 
 /// Options to apply to a specific source
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SourceOptions {
-    pub soundtouch: bool,
+    /// The speed modifier to change the speed of this source with
+    pub speed_type: SpecificType,
+    /// The tags to record this source with, `None` for sources which update their own tags, like radio streams.
+    pub record_tags: Option<RecordTags>,
+    /// The gain in dB to normalize the loudness of this source with, see [`Sink::set_normalization`].
+    pub normalization_gain: Option<f32>,
+    /// Whether the source before this one may crossfade into this one when it ends, see [`Sink::set_crossfade`].
+    pub crossfade: bool,
+    /// Whether to drop the silence at the start and the end of this source.
+    pub trim_silence: bool,
+    /// The duration long silences of this source are shortened to while [`Sink::set_smart_speed`] is enabled,
+    /// [`None`] if smart speed does not apply to it, like for music.
+    pub smart_speed_silence: Option<Duration>,
 }
 
 impl Default for SourceOptions {
     fn default() -> Self {
-        Self { soundtouch: true }
+        Self {
+            speed_type: SpecificType::new(true, true),
+            record_tags: None,
+            normalization_gain: None,
+            crossfade: true,
+            trim_silence: false,
+            smart_speed_silence: None,
+        }
     }
 }
 
@@ -80,9 +434,11 @@ impl Sink {
         mixer: &Mixer,
         picmd_tx: Sender<PlayerInternalCmd>,
         pcmd_tx: crate::PlayerCmdSender,
+        record: Arc<RecordTap>,
     ) -> Self {
-        let (sink, queue_rx) = Self::new_idle(picmd_tx, pcmd_tx);
+        let (mut sink, queue_rx) = Self::new_idle(picmd_tx, pcmd_tx, record);
         mixer.add(queue_rx);
+        sink.mixer = Some(mixer.clone());
         sink
     }
 
@@ -91,25 +447,40 @@ impl Sink {
     pub fn new_idle(
         picmd_tx: Sender<PlayerInternalCmd>,
         pcmd_tx: crate::PlayerCmdSender,
+        record: Arc<RecordTap>,
     ) -> (Self, queue::SourcesQueueOutput) {
         let (queue_tx, queue_rx) = queue::queue(true);
 
         let sink = Sink {
             queue_tx,
             sleep_until_end: Mutex::new(None),
+            mixer: None,
             controls: Arc::new(Controls {
                 pause: AtomicBool::new(false),
                 volume: Mutex::new(1.0),
                 stopped: AtomicBool::new(false),
                 stopped_no_eos: AtomicBool::new(false),
+                stop_faded: AtomicBool::new(false),
                 seek: Mutex::new(None),
                 speed: Mutex::new(1.0),
+                vocal_reduction: AtomicBool::new(false),
+                normalization: AtomicBool::new(false),
+                smart_speed: AtomicBool::new(false),
+                equalizer: Mutex::new(None),
+                fade_in: Mutex::new(Duration::ZERO),
+                fade_out: Mutex::new(Duration::ZERO),
                 to_clear: Mutex::new(0),
                 position: RwLock::new(Duration::from_secs(0)),
+                crossfade: Mutex::new(Duration::ZERO),
+                crossfade_curve: Mutex::new(CrossfadeCurve::default()),
+                crossfade_next: AtomicBool::new(false),
+                crossfade_in: Mutex::new(None),
+                current: Mutex::new(None),
             }),
             sound_count: Arc::new(AtomicUsize::new(0)),
             picmd_tx,
             pcmd_tx,
+            record,
         };
 
         (sink, queue_rx)
@@ -117,7 +488,7 @@ impl Sink {
 
     /// Appends a sound to the queue of sounds to play.
     #[inline]
-    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_possible_wrap, clippy::too_many_lines)]
     pub fn append<S>(&self, source: S, options: &SourceOptions)
     where
         S: Source<Item = SampleType> + Send + 'static,
@@ -129,33 +500,118 @@ impl Sink {
             }
             self.controls.stopped.store(false, Ordering::SeqCst);
             self.controls.stopped_no_eos.store(false, Ordering::SeqCst);
+            self.controls.stop_faded.store(false, Ordering::SeqCst);
         }
 
         let controls = self.controls.clone();
+        // only fade-in if nothing is playing, not on gapless track changes or when crossfading in
+        let fade_in = if self.sound_count.load(Ordering::SeqCst) == 0
+            && self.controls.crossfade_in.lock().is_none()
+        {
+            *self.controls.fade_in.lock()
+        } else {
+            Duration::ZERO
+        };
+        if self.sound_count.load(Ordering::SeqCst) > 0 {
+            self.controls
+                .crossfade_next
+                .store(options.crossfade, Ordering::SeqCst);
+        }
 
+        let state = Arc::new(SourceState::default());
+        let progress_state = state.clone();
+        let access_state = state.clone();
+        let access_count = self.sound_count.clone();
         let progress_tx = self.picmd_tx.clone();
+        let smart_speed_silence = options.smart_speed_silence;
+        let smart_speed = smart_speed_silence.filter(|_| self.smart_speed());
         let source = source
+            .record_tee(self.record.clone(), options.record_tags.clone())
+            .vocal_reduction(self.controls.vocal_reduction.load(Ordering::SeqCst))
+            .normalize(
+                options.normalization_gain.unwrap_or_default(),
+                self.controls.normalization.load(Ordering::SeqCst),
+            )
+            .equalize(*self.controls.equalizer.lock())
+            .fade(fade_in)
             .track_position()
-            .custom_speed(1.0, SpecificType::soundtouch(options.soundtouch))
+            .skip_silence(options.trim_silence, smart_speed)
+            .custom_speed(1.0, options.speed_type)
             .amplify(1.0)
             .pausable(false)
             .skippable()
             // as of rodio 0.20.x, "stoppable" is the same as "skippable"
             // .stoppable()
             .periodic_access(Duration::from_millis(500), move |src| {
+                // the position of a source fading out is not the position of the playing track anymore
+                if progress_state.handed_off.load(Ordering::SeqCst) {
+                    return;
+                }
                 let _ = progress_tx.send(PlayerInternalCmd::Progress(
-                    src.inner().inner().inner().inner().get_pos(),
+                    src.inner().inner().inner().inner().inner().get_pos(),
                 ));
             })
             .periodic_access(Duration::from_millis(5), move |src| {
                 let src = src.inner_mut();
-                if controls.stopped.load(Ordering::SeqCst) {
+                let fade_in = *controls.fade_in.lock();
+                let fade_out = *controls.fade_out.lock();
+                if access_state.handed_off.load(Ordering::SeqCst) {
+                    // fading out on its own lane, the queue already controls the next source, so only the settings
+                    // below still apply
+                    src.inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .detach();
+                } else if controls.stopped.load(Ordering::SeqCst) {
+                    // fade-out before actually stopping
+                    let paused = src.inner().is_paused();
+                    let fade = src
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut();
+                    // only the playing source needs to fade-out, not the queued ones skipped after it
+                    if !paused && !fade.is_silent() && !controls.stop_faded.load(Ordering::SeqCst) {
+                        if fade.target() > 0.0 {
+                            fade.fade_to(0.0, fade_out);
+                        }
+                        return;
+                    }
+
+                    controls.stop_faded.store(true, Ordering::SeqCst);
                     src.skip();
                     // reset position to be at 0, otherwise the position could be stale if there is no new source
                     *controls.position.write() = Duration::ZERO;
+
+                    return;
                 } else {
-                    if let Some(seek_time) = controls.seek.lock().take() {
-                        let _ = src.try_seek(seek_time);
+                    {
+                        let mut seek = controls.seek.lock();
+                        if let Some(seek_time) = *seek {
+                            // fade-out before seeking, the fade-in happens with the pause handling below
+                            let fade = src
+                                .inner_mut()
+                                .inner_mut()
+                                .inner_mut()
+                                .inner_mut()
+                                .inner_mut()
+                                .inner_mut();
+                            if fade.is_silent() {
+                                seek.take();
+                                let _ = src.try_seek(seek_time);
+                            } else if fade.target() > 0.0 {
+                                fade.fade_to(0.0, fade_out);
+                            }
+                        }
                     }
                     {
                         let mut to_clear = controls.to_clear.lock();
@@ -168,15 +624,73 @@ impl Sink {
                             return;
                         }
                     }
-                    *controls.position.write() = src.inner().inner().inner().inner().get_pos();
-
-                    let amp = src.inner_mut();
-                    amp.inner_mut().set_factor(*controls.volume.lock());
-                    amp.set_paused(controls.pause.load(Ordering::SeqCst));
+                    let track_pos = src.inner().inner().inner().inner().inner();
+                    let pos = track_pos.get_pos();
+                    *controls.position.write() = pos;
+
+                    // start the crossfade into the next source before this one ends
+                    let crossfade = *controls.crossfade.lock();
+                    if !crossfade.is_zero()
+                        && access_count.load(Ordering::SeqCst) > 1
+                        && controls.crossfade_next.load(Ordering::SeqCst)
+                        && let Some(total) = track_pos.total_duration()
+                    {
+                        let remaining = total
+                            .saturating_sub(pos)
+                            .div_f32(controls.speed.lock().max(0.1));
+                        if remaining <= crossfade {
+                            access_state.request_handoff(remaining, false);
+                        }
+                    }
+                }
 
-                    amp.inner_mut()
+                let amp = src.inner_mut();
+                amp.inner_mut().set_factor(*controls.volume.lock());
+
+                amp.inner_mut()
+                    .inner_mut()
+                    .set_factor(*controls.speed.lock());
+
+                amp.inner_mut().inner_mut().inner_mut().set_shorten_to(
+                    smart_speed_silence.filter(|_| controls.smart_speed.load(Ordering::SeqCst)),
+                );
+
+                let paused = amp.is_paused();
+                let fade = amp
+                    .inner_mut()
+                    .inner_mut()
+                    .inner_mut()
+                    .inner_mut()
+                    .inner_mut();
+                let equalizer = fade.inner_mut();
+                equalizer.set_gains(*controls.equalizer.lock());
+                let normalize = equalizer.inner_mut();
+                normalize.set_enabled(controls.normalization.load(Ordering::SeqCst));
+                normalize
+                    .inner_mut()
+                    .set_enabled(controls.vocal_reduction.load(Ordering::SeqCst));
+
+                if controls.pause.load(Ordering::SeqCst) {
+                    // fade-out before actually pausing
+                    if fade.target() > 0.0 {
+                        fade.fade_to(0.0, fade_out);
+                    }
+                    if fade.is_silent() {
+                        amp.set_paused(true);
+                    }
+                } else {
+                    if paused {
+                        amp.set_paused(false);
+                    }
+                    let fade = amp
+                        .inner_mut()
                         .inner_mut()
-                        .set_factor(*controls.speed.lock());
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut();
+                    if fade.target() < 1.0 && controls.seek.lock().is_none() {
+                        fade.fade_to(1.0, fade_in);
+                    }
                 }
             });
 
@@ -187,12 +701,23 @@ impl Sink {
         let sound_count = self.sound_count.clone();
         let pcmd_tx = self.pcmd_tx.clone();
         let picmd_tx = self.picmd_tx.clone();
+        let source = Crossfadable {
+            inner: Arc::new(Mutex::new(source)),
+            state: state.clone(),
+            controls: self.controls.clone(),
+            mixer: self.mixer.clone(),
+            started: false,
+            fade_in: None,
+        };
+
         let controls = self.controls.clone();
         let source = source.cbdone(move || {
             // the original function of `rodio::source::Done`, but we want to do more than that
             sound_count.fetch_sub(1, Ordering::Relaxed);
 
-            if !controls.stopped_no_eos.load(Ordering::SeqCst) {
+            if !controls.stopped_no_eos.load(Ordering::SeqCst)
+                && !state.no_eos.load(Ordering::SeqCst)
+            {
                 // using ".is_err()" here as the only error that can come from this channel is "Channel Closed"
                 if pcmd_tx.send(PlayerCmd::Eos).is_err() {
                     // not high priority, may log this on graceful exit because stop and player loop exit are not waiting on each-other
@@ -243,6 +768,69 @@ impl Sink {
         *self.controls.speed.lock() = value;
     }
 
+    /// Gets whether vocal reduction is applied.
+    #[inline]
+    pub fn vocal_reduction(&self) -> bool {
+        self.controls.vocal_reduction.load(Ordering::SeqCst)
+    }
+
+    /// Gets whether the normalization gain of the sources is applied.
+    #[inline]
+    pub fn normalization(&self) -> bool {
+        self.controls.normalization.load(Ordering::SeqCst)
+    }
+
+    /// Enable or disable applying the normalization gain, see [`SourceOptions::normalization_gain`].
+    #[inline]
+    pub fn set_normalization(&self, value: bool) {
+        self.controls.normalization.store(value, Ordering::SeqCst);
+    }
+
+    /// Gets whether long silences are shortened, see [`SourceOptions::smart_speed_silence`].
+    #[inline]
+    pub fn smart_speed(&self) -> bool {
+        self.controls.smart_speed.load(Ordering::SeqCst)
+    }
+
+    /// Enable or disable shortening long silences, see [`SourceOptions::smart_speed_silence`].
+    #[inline]
+    pub fn set_smart_speed(&self, value: bool) {
+        self.controls.smart_speed.store(value, Ordering::SeqCst);
+    }
+
+    /// Gets the gains of the equalizer bands, [`None`] if disabled.
+    #[inline]
+    pub fn equalizer(&self) -> Option<[f32; BANDS]> {
+        *self.controls.equalizer.lock()
+    }
+
+    /// Change the gains in dB of the equalizer bands, [`None`] to disable the equalizer.
+    #[inline]
+    pub fn set_equalizer(&self, gains: Option<[f32; BANDS]>) {
+        *self.controls.equalizer.lock() = gains;
+    }
+
+    /// Get the recorder all sources are copied to.
+    #[inline]
+    pub fn record_tap(&self) -> &Arc<RecordTap> {
+        &self.record
+    }
+
+    /// Enable or disable vocal reduction, see [`SourceExt::vocal_reduction`](super::source::SourceExt::vocal_reduction).
+    #[inline]
+    pub fn set_vocal_reduction(&self, value: bool) {
+        self.controls.vocal_reduction.store(value, Ordering::SeqCst);
+    }
+
+    /// Changes how long to fade-in and fade-out, see [`SourceExt::fade`](super::source::SourceExt::fade).
+    ///
+    /// A duration of zero disables the respective fade.
+    #[inline]
+    pub fn set_fade(&self, fade_in: Duration, fade_out: Duration) {
+        *self.controls.fade_in.lock() = fade_in;
+        *self.controls.fade_out.lock() = fade_out;
+    }
+
     /// Resumes playback of a paused sink.
     ///
     /// No effect if not paused.
@@ -278,15 +866,6 @@ impl Sink {
         *self.controls.seek.lock() = Some(seek_to);
     }
 
-    /// Toggles playback of the sink
-    pub fn toggle_playback(&self) {
-        if self.is_paused() {
-            self.play();
-        } else {
-            self.pause();
-        }
-    }
-
     /// Removes all currently loaded `Source`s from the `Sink`, and pauses it.
     ///
     /// See `pause()` for information about pausing a `Sink`.
@@ -303,8 +882,14 @@ impl Sink {
     /// If there are more `Source`s appended to the `Sink` at the time,
     /// it will play the next one. Otherwise, the `Sink` will finish as if
     /// it had finished playing a `Source` all the way through.
+    ///
+    /// If crossfading is enabled, the current `Source` fades out while the next one already plays.
     #[allow(clippy::cast_possible_truncation)]
     pub fn skip_one(&self) {
+        if self.crossfade_current(false) {
+            return;
+        }
+
         let len = self.sound_count.load(Ordering::SeqCst) as u32;
         let mut to_clear = self.controls.to_clear.lock();
         if len > *to_clear {
@@ -312,6 +897,43 @@ impl Sink {
         }
     }
 
+    /// Fade out the current `Source` like [`skip_one`](Self::skip_one), but without sending EOS, to crossfade into
+    /// a `Source` appended afterwards.
+    ///
+    /// Returns `false` if there is nothing to crossfade, like if crossfading is disabled or the sink is paused.
+    pub fn crossfade_out(&self) -> bool {
+        self.crossfade_current(true)
+    }
+
+    /// Request the current `Source` to fade out on its own lane of the mixer.
+    fn crossfade_current(&self, no_eos: bool) -> bool {
+        let crossfade = *self.controls.crossfade.lock();
+        if crossfade.is_zero() || self.is_paused() || self.mixer.is_none() {
+            return false;
+        }
+        let Some(current) = self.controls.current.lock().clone() else {
+            return false;
+        };
+
+        current.request_handoff(crossfade, no_eos)
+    }
+
+    /// Changes how long to crossfade between sources, a duration of zero disables crossfading.
+    ///
+    /// Whether the end of a source crossfades into the next is set with [`SourceOptions::crossfade`] of the next,
+    /// skipping always crossfades.
+    #[inline]
+    pub fn set_crossfade(&self, duration: Duration, curve: CrossfadeCurve) {
+        *self.controls.crossfade.lock() = duration;
+        *self.controls.crossfade_curve.lock() = curve;
+    }
+
+    /// Gets how long to crossfade between sources, zero if disabled.
+    #[inline]
+    pub fn crossfade(&self) -> Duration {
+        *self.controls.crossfade.lock()
+    }
+
     /// Stops the sink by emptying the queue.
     #[inline]
     pub fn stop(&self) {
@@ -361,3 +983,38 @@ impl Drop for Sink {
         self.controls.stopped.store(true, Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+    use termusiclib::config::v2::server::backends::CrossfadeCurve;
+
+    use super::Ramp;
+
+    #[test]
+    fn should_ramp_over_duration() {
+        // 1 second of 2 channels at 4 Hz is 8 samples
+        let source = SamplesBuffer::new(2, 4, vec![0.0; 8]);
+        let mut ramp = Ramp::new(
+            CrossfadeCurve::Linear,
+            false,
+            Duration::from_secs(1),
+            &source,
+        );
+
+        assert!((ramp.next_gain(true) - 1.0).abs() < f32::EPSILON);
+        // not advancing while paused
+        assert!((ramp.next_gain(false) - 0.875).abs() < f32::EPSILON);
+        for _ in 0..6 {
+            ramp.next_gain(true);
+        }
+        assert!(!ramp.is_done());
+
+        // stopping shortens the rest of the fade
+        ramp.finish_in(Duration::ZERO, &source);
+        ramp.next_gain(true);
+        assert!(ramp.is_done());
+    }
+}
@@ -8,6 +8,7 @@ use async_ringbuf::{
 use parking_lot::RwLock;
 use rodio::Source;
 use symphonia::core::audio::SignalSpec;
+use termusiclib::metrics::METRICS;
 use tokio::{
     runtime::Handle,
     sync::{mpsc, oneshot},
@@ -385,6 +386,11 @@ impl AsyncRingSource {
             // When not doing this, cpu load can be 1.0~1.4 on average.
             // When doing the current way, the load is ~0.5~0.6 on average, the same as if running the decoder directly as
             // as source instead of using this ringbuffer.
+
+            // Having to wait while in the middle of a data message means the decoder did not keep up.
+            if self.last_msg.is_some() {
+                METRICS.inc_buffer_underruns();
+            }
             self.handle
                 .block_on(self.inner.wait_occupied(wait_for_bytes));
         }
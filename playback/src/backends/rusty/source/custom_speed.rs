@@ -3,25 +3,35 @@ use std::time::Duration;
 use rodio::Source;
 
 use super::SampleType;
+use super::time_stretch::TimeStretch;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum SpecificType {
+    /// Resample, which changes the pitch along with the speed
+    #[default]
     Rodio,
+    /// Keep the pitch with the built-in [`TimeStretch`](super::time_stretch::TimeStretch)
+    TimeStretch,
     #[cfg(feature = "rusty-soundtouch")]
     Soundtouch,
 }
 
 impl SpecificType {
-    /// Enable or disable soundtouch usage, has no effect if soundtouch has not been compiled-in
+    /// Choose the speed modifier: if `preserve_pitch`, soundtouch if enabled and compiled-in, otherwise the built-in
+    /// time-stretch.
     #[must_use]
     #[allow(unused_variables)] // because of feature gates
-    pub fn soundtouch(value: bool) -> Self {
+    pub fn new(soundtouch: bool, preserve_pitch: bool) -> Self {
+        if !preserve_pitch {
+            return Self::Rodio;
+        }
+
         #[cfg(feature = "rusty-soundtouch")]
-        if value {
+        if soundtouch {
             return Self::Soundtouch;
         }
 
-        Self::Rodio
+        Self::TimeStretch
     }
 }
 
@@ -32,6 +42,9 @@ where
 {
     match specific {
         SpecificType::Rodio => CustomSpeed::Rodio(input.speed(initial_speed)),
+        SpecificType::TimeStretch => {
+            CustomSpeed::TimeStretch(TimeStretch::new(input, initial_speed))
+        }
         #[cfg(feature = "rusty-soundtouch")]
         SpecificType::Soundtouch => {
             trace!("Using soundtouch");
@@ -45,6 +58,7 @@ where
 #[allow(dead_code)]
 pub enum CustomSpeed<I> {
     Rodio(rodio::source::Speed<I>),
+    TimeStretch(TimeStretch<I>),
     #[cfg(feature = "rusty-soundtouch")]
     SoundTouch(super::soundtouch::SoundTouchSource<I>),
 }
@@ -96,6 +110,7 @@ where
     fn as_source(&self) -> &dyn Source<Item = SampleType> {
         match self {
             CustomSpeed::Rodio(speed) => speed,
+            CustomSpeed::TimeStretch(stretch) => stretch,
             #[cfg(feature = "rusty-soundtouch")]
             CustomSpeed::SoundTouch(soundtouch) => soundtouch,
         }
@@ -105,6 +120,7 @@ where
     fn as_source_mut(&mut self) -> &mut dyn Source<Item = SampleType> {
         match self {
             CustomSpeed::Rodio(speed) => speed,
+            CustomSpeed::TimeStretch(stretch) => stretch,
             #[cfg(feature = "rusty-soundtouch")]
             CustomSpeed::SoundTouch(soundtouch) => soundtouch,
         }
@@ -115,6 +131,7 @@ where
     pub fn inner(&self) -> &I {
         match self {
             CustomSpeed::Rodio(speed) => speed.inner(),
+            CustomSpeed::TimeStretch(stretch) => stretch.inner(),
             #[cfg(feature = "rusty-soundtouch")]
             CustomSpeed::SoundTouch(soundtouch) => soundtouch.inner(),
         }
@@ -122,10 +139,10 @@ where
 
     /// Returns a mutable reference to the inner source.
     #[inline]
-    #[expect(dead_code)]
     pub fn inner_mut(&mut self) -> &mut I {
         match self {
             CustomSpeed::Rodio(speed) => speed.inner_mut(),
+            CustomSpeed::TimeStretch(stretch) => stretch.inner_mut(),
             #[cfg(feature = "rusty-soundtouch")]
             CustomSpeed::SoundTouch(soundtouch) => soundtouch.inner_mut(),
         }
@@ -136,6 +153,7 @@ where
     pub fn set_factor(&mut self, factor: f32) {
         match self {
             CustomSpeed::Rodio(speed) => speed.set_factor(factor),
+            CustomSpeed::TimeStretch(stretch) => stretch.set_factor(factor),
             #[cfg(feature = "rusty-soundtouch")]
             CustomSpeed::SoundTouch(soundtouch) => soundtouch.set_factor(f64::from(factor)),
         }
@@ -0,0 +1,264 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+use termusiclib::config::v2::server::equalizer::{BAND_FREQUENCIES, BANDS};
+
+use super::SampleType;
+
+/// The quality factor of the bands, about one octave wide.
+const Q: f32 = 1.41;
+
+/// The highest center frequency of a band relative to the sample rate, higher bands are skipped.
+const NYQUIST_MARGIN: f32 = 0.45;
+
+/// The coefficients of a biquad filter, already divided by `a0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    /// Create a peaking filter boosting or cutting `gain_db` around `freq`, see the "Audio EQ Cookbook" by Robert
+    /// Bristow-Johnson.
+    fn peaking(freq: f32, gain_db: f32, sample_rate: f32) -> Self {
+        let a = 10.0_f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * Q);
+        let a0 = 1.0 + alpha / a;
+
+        Self {
+            b0: (1.0 + alpha * a) / a0,
+            b1: (-2.0 * cos) / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: (-2.0 * cos) / a0,
+            a2: (1.0 - alpha / a) / a0,
+        }
+    }
+
+    /// Filter `sample`, with `state` being the delayed values of the channel, in transposed direct form II.
+    #[inline]
+    fn process(&self, sample: f32, state: &mut [f32; 2]) -> f32 {
+        let out = self.b0 * sample + state[0];
+        state[0] = self.b1 * sample - self.a1 * out + state[1];
+        state[1] = self.b2 * sample - self.a2 * out;
+
+        out
+    }
+}
+
+/// Boost or cut the bands of [`BAND_FREQUENCIES`] with peaking filters.
+///
+/// The whole signal is lowered by the highest boost, so that boosting a band does not clip.
+#[derive(Debug, Clone)]
+pub struct Equalizer<I> {
+    input: I,
+    /// The gains in dB of the bands, [`None`] to pass the signal through unchanged.
+    gains: Option<[f32; BANDS]>,
+    /// The filters of the bands, empty if the signal is passed through unchanged.
+    filters: Vec<Biquad>,
+    /// The delayed values of each filter of each channel, the filters of a channel next to each other.
+    state: Vec<[f32; 2]>,
+    /// The factor applied before the filters.
+    preamp: f32,
+    /// The channels and sample rate the filters were calculated for.
+    format: (u16, u32),
+    /// The channel of the next sample.
+    channel: u16,
+}
+
+impl<I> Equalizer<I>
+where
+    I: Source<Item = SampleType>,
+{
+    /// Wrap the `input` source, initially with `gains`.
+    #[inline]
+    pub fn new(input: I, gains: Option<[f32; BANDS]>) -> Self {
+        Self {
+            input,
+            gains,
+            filters: Vec::new(),
+            state: Vec::new(),
+            preamp: 1.0,
+            format: (0, 0),
+            channel: 0,
+        }
+    }
+
+    /// Change the gains in dB of the bands, [`None`] to pass the signal through unchanged.
+    #[inline]
+    pub fn set_gains(&mut self, gains: Option<[f32; BANDS]>) {
+        if self.gains != gains {
+            self.gains = gains;
+            // recalculate the filters at the start of the next frame
+            self.format = (0, 0);
+        }
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Recalculate the filters if the gains or the format of the input changed.
+    fn update_filters(&mut self) {
+        let format = (
+            self.input.channels().max(1),
+            self.input.sample_rate().max(1),
+        );
+        if self.format == format {
+            return;
+        }
+        self.format = format;
+
+        #[allow(clippy::cast_precision_loss)]
+        let sample_rate = format.1 as f32;
+        self.filters.clear();
+        self.preamp = 1.0;
+        if let Some(gains) = self.gains.filter(|v| v.iter().any(|v| *v != 0.0)) {
+            #[allow(clippy::cast_precision_loss)]
+            self.filters.extend(
+                BAND_FREQUENCIES
+                    .iter()
+                    .zip(gains)
+                    .map(|(freq, gain)| (*freq as f32, gain))
+                    .filter(|(freq, _)| *freq < sample_rate * NYQUIST_MARGIN)
+                    .map(|(freq, gain)| Biquad::peaking(freq, gain, sample_rate)),
+            );
+            let boost = gains.iter().copied().fold(0.0, f32::max);
+            self.preamp = 10.0_f32.powf(-boost / 20.0);
+        }
+
+        // keep the delayed values while only the gains change, to not click
+        let len = usize::from(format.0) * self.filters.len();
+        if self.state.len() != len {
+            self.state = vec![[0.0; 2]; len];
+        }
+    }
+}
+
+impl<I> Iterator for Equalizer<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // the format can only change at the start of a frame
+        if self.channel == 0 {
+            self.update_filters();
+        }
+        let sample = self.input.next()?;
+        let channel = usize::from(self.channel);
+        self.channel = (self.channel + 1) % self.format.0;
+
+        if self.filters.is_empty() {
+            return Some(sample);
+        }
+
+        let len = self.filters.len();
+        let state = &mut self.state[channel * len..(channel + 1) * len];
+        let out = self
+            .filters
+            .iter()
+            .zip(state)
+            .fold(sample * self.preamp, |acc, (filter, state)| {
+                filter.process(acc, state)
+            });
+
+        Some(out)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Equalizer<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use pretty_assertions::assert_eq;
+    use rodio::buffer::SamplesBuffer;
+    use termusiclib::config::v2::server::equalizer::BANDS;
+
+    use super::Equalizer;
+
+    /// Get the peak of the second half of a sine of `freq` with a amplitude of `0.5` through the equalizer.
+    fn peak(freq: f32, gains: [f32; BANDS]) -> f32 {
+        let samples: Vec<f32> = (0..8820)
+            .map(|i| {
+                #[allow(clippy::cast_precision_loss)]
+                let t = i as f32 / 44100.0;
+                0.5 * (2.0 * PI * freq * t).sin()
+            })
+            .collect();
+        let source = Equalizer::new(SamplesBuffer::new(1, 44100, samples), Some(gains));
+
+        source.skip(4410).fold(0.0, |acc, v| acc.max(v.abs()))
+    }
+
+    #[test]
+    fn should_passthrough() {
+        let samples = vec![0.5, -0.5, 0.25, 0.25, 1.0, -1.0];
+
+        let flat = Equalizer::new(
+            SamplesBuffer::new(2, 44100, samples.clone()),
+            Some([0.0; BANDS]),
+        );
+        assert_eq!(flat.collect::<Vec<_>>(), samples);
+
+        let disabled = Equalizer::new(SamplesBuffer::new(2, 44100, samples.clone()), None);
+        assert_eq!(disabled.collect::<Vec<_>>(), samples);
+    }
+
+    #[test]
+    fn should_boost_band() {
+        // boost the 4 kHz band, everything else is lowered by the preamp
+        let mut gains = [0.0; BANDS];
+        gains[7] = 12.0;
+
+        let boosted = peak(4000.0, gains);
+        assert!((boosted - 0.5).abs() < 0.05, "{boosted}");
+        let other = peak(125.0, gains);
+        assert!((other - 0.125).abs() < 0.02, "{other}");
+    }
+}
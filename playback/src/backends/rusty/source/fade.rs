@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+
+use super::SampleType;
+
+/// Linearly fade the volume of a source towards a target, used to avoid pops on pause, resume, seek and stop.
+#[derive(Debug, Clone)]
+pub struct Fade<I> {
+    input: I,
+    /// The current gain, between `0.0` and `1.0`.
+    gain: f32,
+    /// The gain to fade towards, either `0.0` or `1.0`.
+    target: f32,
+    /// The gain change per sample.
+    step: f32,
+}
+
+impl<I> Fade<I>
+where
+    I: Source<Item = SampleType>,
+{
+    /// Wrap the `input` source, fading in over `fade_in` if it is not zero.
+    #[inline]
+    pub fn new(input: I, fade_in: Duration) -> Self {
+        let mut fade = Self {
+            input,
+            gain: 1.0,
+            target: 1.0,
+            step: 0.0,
+        };
+        if !fade_in.is_zero() {
+            fade.gain = 0.0;
+            fade.fade_to(1.0, fade_in);
+        }
+
+        fade
+    }
+
+    /// Start fading from the current gain to `target` over `duration`, where a zero `duration` applies it directly.
+    ///
+    /// The `duration` is the time for a full fade, fading from a partial gain takes proportionally less.
+    pub fn fade_to(&mut self, target: f32, duration: Duration) {
+        self.target = target.clamp(0.0, 1.0);
+
+        #[allow(clippy::cast_precision_loss)]
+        let samples = duration.as_secs_f32()
+            * self.input.sample_rate() as f32
+            * f32::from(self.input.channels().max(1));
+        if samples < 1.0 {
+            self.gain = self.target;
+            self.step = 0.0;
+        } else {
+            self.step = 1.0 / samples;
+        }
+    }
+
+    /// Get the gain currently faded towards.
+    #[inline]
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Get whether the source has been fully faded out.
+    #[inline]
+    pub fn is_silent(&self) -> bool {
+        self.gain <= 0.0 && self.target <= 0.0
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    #[expect(dead_code)]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+}
+
+impl<I> Iterator for Fade<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+
+        if self.gain < self.target {
+            self.gain = (self.gain + self.step).min(self.target);
+        } else if self.gain > self.target {
+            self.gain = (self.gain - self.step).max(self.target);
+        }
+
+        Some(sample * self.gain)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Fade<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::Fade;
+
+    #[test]
+    fn should_fade_in() {
+        // 4 samples per millisecond
+        let source = SamplesBuffer::new(1, 4000, vec![1.0; 8]);
+        let out: Vec<f32> = Fade::new(source, Duration::from_millis(1)).collect();
+
+        assert_eq!(out, [0.25, 0.5, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn should_fade_out() {
+        let source = SamplesBuffer::new(1, 4000, vec![1.0; 6]);
+        let mut fade = Fade::new(source, Duration::ZERO);
+        assert_eq!(fade.next(), Some(1.0));
+
+        fade.fade_to(0.0, Duration::from_millis(1));
+        assert!(!fade.is_silent());
+        let out: Vec<f32> = fade.by_ref().take(4).collect();
+        assert_eq!(out, [0.75, 0.5, 0.25, 0.0]);
+        assert!(fade.is_silent());
+        assert_eq!(fade.next(), Some(0.0));
+    }
+
+    #[test]
+    fn should_apply_zero_duration_directly() {
+        let source = SamplesBuffer::new(2, 44100, vec![1.0; 4]);
+        let mut fade = Fade::new(source, Duration::ZERO);
+
+        fade.fade_to(0.0, Duration::ZERO);
+        assert!(fade.is_silent());
+        assert_eq!(fade.next(), Some(0.0));
+
+        fade.fade_to(1.0, Duration::ZERO);
+        assert_eq!(fade.collect::<Vec<_>>(), [1.0, 1.0, 1.0]);
+    }
+}
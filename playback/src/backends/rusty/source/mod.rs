@@ -9,6 +9,13 @@ pub mod soundtouch;
 pub mod async_ring;
 mod cb_done;
 mod custom_speed;
+mod equalizer;
+mod fade;
+mod normalize;
+mod record_tee;
+mod skip_silence;
+mod time_stretch;
+mod vocal_reduction;
 
 /// Our sample type we choose to use across all places
 pub type SampleType = f32;
@@ -30,6 +37,71 @@ pub trait SourceExt: Source {
         custom_speed::custom_speed(self, initial_speed, specific)
     }
 
+    /// Reduce vocals by cancelling the center channel, initially `enabled` or not.
+    fn vocal_reduction(self, enabled: bool) -> vocal_reduction::VocalReduction<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        vocal_reduction::VocalReduction::new(self, enabled)
+    }
+
+    /// Apply a gain of `gain_db` to normalize the loudness, initially `enabled` or not.
+    fn normalize(self, gain_db: f32, enabled: bool) -> normalize::Normalize<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        normalize::Normalize::new(self, gain_db, enabled)
+    }
+
+    /// Boost or cut the equalizer bands by `gains` in dB, passing the signal through unchanged if `None`.
+    fn equalize(
+        self,
+        gains: Option<[f32; termusiclib::config::v2::server::equalizer::BANDS]>,
+    ) -> equalizer::Equalizer<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        equalizer::Equalizer::new(self, gains)
+    }
+
+    /// Drop the silence at the start and end with `trim`, and shorten silences longer than `shorten_to`.
+    fn skip_silence(
+        self,
+        trim: bool,
+        shorten_to: Option<std::time::Duration>,
+    ) -> skip_silence::SkipSilence<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        skip_silence::SkipSilence::new(self, trim, shorten_to)
+    }
+
+    /// Fade the volume in and out, initially fading in over `fade_in`.
+    fn fade(self, fade_in: std::time::Duration) -> fade::Fade<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        fade::Fade::new(self, fade_in)
+    }
+
+    /// Copy all samples to `tap` while it is recording, tagged with `tags` or the tap's stream tags if `None`.
+    fn record_tee(
+        self,
+        tap: std::sync::Arc<crate::backends::rusty::recorder::RecordTap>,
+        tags: Option<crate::backends::rusty::recorder::RecordTags>,
+    ) -> record_tee::RecordTee<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        record_tee::RecordTee::new(self, tap, tags)
+    }
+
     /// Run a function once at the end of a source.
     fn cbdone<Fn: FnOnce()>(self, fun: Fn) -> cb_done::CbDone<Self, Fn>
     where
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+
+use super::SampleType;
+
+/// How long switching the normalization on or off takes, to avoid a jump in loudness.
+const RAMP: Duration = Duration::from_millis(100);
+
+/// Apply the `ReplayGain` of a source to normalize its loudness, ramping the gain when enabling or disabling it.
+#[derive(Debug, Clone)]
+pub struct Normalize<I> {
+    input: I,
+    /// The factor to apply while enabled.
+    factor: f32,
+    enabled: bool,
+    /// The currently applied factor.
+    current: f32,
+    /// The factor change per sample while ramping.
+    step: f32,
+}
+
+impl<I> Normalize<I>
+where
+    I: Source<Item = SampleType>,
+{
+    /// Wrap the `input` source with a gain of `gain_db`, initially `enabled` or not.
+    #[inline]
+    pub fn new(input: I, gain_db: f32, enabled: bool) -> Self {
+        let factor = 10.0_f32.powf(gain_db / 20.0);
+
+        Self {
+            input,
+            factor,
+            enabled,
+            current: if enabled { factor } else { 1.0 },
+            step: 0.0,
+        }
+    }
+
+    /// Enable or disable the normalization, ramping to the new gain over [`RAMP`].
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled == enabled {
+            return;
+        }
+        self.enabled = enabled;
+
+        #[allow(clippy::cast_precision_loss)]
+        let samples = RAMP.as_secs_f32()
+            * self.input.sample_rate() as f32
+            * f32::from(self.input.channels().max(1));
+        self.step = (self.target() - self.current).abs() / samples.max(1.0);
+    }
+
+    /// Get the factor currently ramped towards.
+    #[inline]
+    fn target(&self) -> f32 {
+        if self.enabled { self.factor } else { 1.0 }
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+}
+
+impl<I> Iterator for Normalize<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+
+        let target = self.target();
+        if self.current < target {
+            self.current = (self.current + self.step).min(target);
+        } else if self.current > target {
+            self.current = (self.current - self.step).max(target);
+        }
+
+        Some(sample * self.current)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Normalize<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rodio::buffer::SamplesBuffer;
+
+    use super::Normalize;
+
+    #[test]
+    fn should_apply_gain() {
+        // -6 dB is about half the amplitude
+        let source = SamplesBuffer::new(1, 44100, vec![1.0; 2]);
+        let out: Vec<f32> = Normalize::new(source, -6.0206, true).collect();
+        assert!(out.iter().all(|v| (v - 0.5).abs() < 1e-4), "{out:?}");
+
+        let source = SamplesBuffer::new(1, 44100, vec![1.0; 2]);
+        let out: Vec<f32> = Normalize::new(source, -6.0206, false).collect();
+        assert_eq!(out, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn should_ramp_on_toggle() {
+        // 4 samples in the 100ms ramp
+        let source = SamplesBuffer::new(1, 40, vec![1.0; 6]);
+        let mut normalize = Normalize::new(source, -6.0206, false);
+        assert_eq!(normalize.next(), Some(1.0));
+
+        normalize.set_enabled(true);
+        let out: Vec<f32> = normalize.collect();
+        let expected = [0.875, 0.75, 0.625, 0.5, 0.5];
+        assert!(
+            out.iter().zip(expected).all(|(v, e)| (v - e).abs() < 1e-4),
+            "{out:?}"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+
+use super::SampleType;
+use crate::backends::rusty::recorder::{RecordTags, RecordTap};
+
+/// The amount of samples to collect before sending them to the recorder.
+const CHUNK_LEN: usize = 4096;
+
+/// Copy all samples passing through to a [`RecordTap`], while it is recording.
+#[derive(Debug)]
+pub struct RecordTee<I> {
+    input: I,
+    tap: Arc<RecordTap>,
+    /// The tags of this source, `None` to use the stream tags of the tap.
+    tags: Option<RecordTags>,
+    /// Whether the tags have been sent for the current recording.
+    sent_tags: bool,
+    buf: Vec<SampleType>,
+    /// The channels and sample rate of the samples in `buf`.
+    format: (u16, u32),
+    /// Whether to not copy any samples anymore, see [`Self::detach`].
+    detached: bool,
+}
+
+impl<I> RecordTee<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    pub fn new(input: I, tap: Arc<RecordTap>, tags: Option<RecordTags>) -> Self {
+        let format = (input.channels(), input.sample_rate());
+        Self {
+            input,
+            tap,
+            tags,
+            sent_tags: false,
+            buf: Vec::new(),
+            format,
+            detached: false,
+        }
+    }
+
+    /// Stop copying the samples of this source, like for the rest of a source fading out in a crossfade, while the
+    /// next source is already copied.
+    pub fn detach(&mut self) {
+        if !self.detached {
+            self.flush();
+            self.detached = true;
+        }
+    }
+
+    /// Send the collected samples to the recorder.
+    fn flush(&mut self) {
+        if self.buf.is_empty() {
+            return;
+        }
+
+        if !self.sent_tags {
+            self.sent_tags = true;
+            if let Some(tags) = self.tags.clone().or_else(|| self.tap.stream_tags()) {
+                self.tap.send_tags(tags);
+            }
+        }
+
+        let samples = std::mem::replace(&mut self.buf, self.tap.take_buffer(CHUNK_LEN));
+        self.tap.send_samples(self.format.0, self.format.1, samples);
+    }
+}
+
+impl<I> Iterator for RecordTee<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.detached {
+            return self.input.next();
+        }
+        if !self.tap.is_active() {
+            // a new recording might be started later, which needs the tags again
+            self.sent_tags = false;
+            self.buf.clear();
+            return self.input.next();
+        }
+
+        let format = (self.input.channels(), self.input.sample_rate());
+        if format != self.format {
+            self.flush();
+            self.format = format;
+        }
+
+        let Some(sample) = self.input.next() else {
+            self.flush();
+            return None;
+        };
+
+        self.buf.push(sample);
+        // only flush whole frames
+        if self.buf.len() >= CHUNK_LEN
+            && self.buf.len().is_multiple_of(usize::from(format.0.max(1)))
+        {
+            self.flush();
+        }
+
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for RecordTee<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
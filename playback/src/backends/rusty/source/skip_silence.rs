@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+
+use super::SampleType;
+
+/// Samples quieter than this are silence, about -54 dBFS.
+const SILENCE_THRESHOLD: SampleType = 0.002;
+
+/// Trim the silence at the start and the end of a source, and shorten long silences in between.
+///
+/// Silent frames are held back until the next audible frame, then the kept part of the silence is played as digital
+/// silence. This means a silence is read ahead as fast as the input can provide it.
+#[derive(Debug, Clone)]
+pub struct SkipSilence<I> {
+    input: I,
+    /// Whether to drop the silence at the start and the end.
+    trim: bool,
+    /// The duration silences are shortened to, [`None`] to keep them as they are.
+    shorten_to: Option<Duration>,
+    /// Whether a audible frame has been read yet, to know which silence is at the start.
+    started: bool,
+    /// The samples of the current frame not yet returned.
+    frame: Vec<SampleType>,
+    /// The position in `frame` of the next sample to return.
+    frame_pos: usize,
+    /// The amount of silent samples still to return before the current frame.
+    silence: u64,
+}
+
+impl<I> SkipSilence<I>
+where
+    I: Source<Item = SampleType>,
+{
+    /// Wrap the `input` source, dropping the silence at its start and end with `trim`, and shortening silences longer
+    /// than `shorten_to`.
+    #[inline]
+    pub fn new(input: I, trim: bool, shorten_to: Option<Duration>) -> Self {
+        Self {
+            input,
+            trim,
+            shorten_to,
+            started: false,
+            frame: Vec::new(),
+            frame_pos: 0,
+            silence: 0,
+        }
+    }
+
+    /// Change the duration silences are shortened to, [`None`] to keep them as they are.
+    #[inline]
+    pub fn set_shorten_to(&mut self, shorten_to: Option<Duration>) {
+        self.shorten_to = shorten_to;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Get whether anything may be changed, otherwise the input is passed through as-is.
+    #[inline]
+    fn is_active(&self) -> bool {
+        self.trim || self.shorten_to.is_some()
+    }
+
+    /// Get the amount of samples of silence to keep of a silence of `samples`.
+    fn kept_samples(&self, samples: u64) -> u64 {
+        let Some(shorten_to) = self.shorten_to else {
+            return samples;
+        };
+        let per_sec = u64::from(self.input.sample_rate()) * u64::from(self.input.channels().max(1));
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let max = (shorten_to.as_secs_f64() * per_sec as f64) as u64;
+
+        samples.min(max)
+    }
+
+    /// Read the next frame into `frame`, returns `false` at the end of the input.
+    fn read_frame(&mut self) -> bool {
+        let channels = usize::from(self.input.channels().max(1));
+        self.frame.clear();
+        self.frame_pos = 0;
+        self.frame.extend(self.input.by_ref().take(channels));
+
+        !self.frame.is_empty()
+    }
+
+    /// Read frames until a audible one, and set the silence to return before it.
+    ///
+    /// Returns `false` at the end of the input.
+    fn next_audible_frame(&mut self) -> bool {
+        let mut silent_samples: u64 = 0;
+        loop {
+            if !self.read_frame() {
+                // the end of the input, so the silence is at the end
+                if !self.trim {
+                    self.silence = self.kept_samples(silent_samples);
+                }
+                return false;
+            }
+
+            if self.frame.iter().any(|v| v.abs() >= SILENCE_THRESHOLD) {
+                break;
+            }
+            silent_samples += self.frame.len() as u64;
+        }
+
+        if self.started || !self.trim {
+            self.silence = self.kept_samples(silent_samples);
+        }
+        self.started = true;
+
+        true
+    }
+}
+
+impl<I> Iterator for SkipSilence<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.silence > 0 {
+            self.silence -= 1;
+            return Some(0.0);
+        }
+        if let Some(sample) = self.frame.get(self.frame_pos) {
+            self.frame_pos += 1;
+            return Some(*sample);
+        }
+        if !self.is_active() {
+            return self.input.next();
+        }
+
+        let more = self.next_audible_frame();
+        if self.silence > 0 {
+            self.silence -= 1;
+            return Some(0.0);
+        }
+        if !more {
+            return None;
+        }
+        self.frame_pos = 1;
+
+        self.frame.first().copied()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // samples may be dropped
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for SkipSilence<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        // the span of the input does not line up anymore once samples are dropped
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.frame.clear();
+        self.frame_pos = 0;
+        self.silence = 0;
+        // only the silence at the very start is trimmed, not the one at where was seeked to
+        self.started = !pos.is_zero();
+
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::SkipSilence;
+
+    /// Get mono samples of `audible` 0.5 and `silent` 0.0 samples after each other.
+    fn samples(parts: &[(usize, usize)]) -> Vec<f32> {
+        parts
+            .iter()
+            .flat_map(|(audible, silent)| {
+                std::iter::repeat_n(0.5, *audible).chain(std::iter::repeat_n(0.0, *silent))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_trim_start_and_end() {
+        let input = [vec![0.0; 5], samples(&[(3, 4), (2, 6)])].concat();
+        let source = SkipSilence::new(SamplesBuffer::new(1, 1000, input), true, None);
+
+        assert_eq!(source.collect::<Vec<_>>(), samples(&[(3, 4), (2, 0)]));
+    }
+
+    #[test]
+    fn should_shorten_long_silences() {
+        let input = samples(&[(3, 20), (2, 1), (1, 4)]);
+        // 2 samples at 1kHz
+        let source = SkipSilence::new(
+            SamplesBuffer::new(1, 1000, input),
+            false,
+            Some(Duration::from_millis(2)),
+        );
+
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            samples(&[(3, 2), (2, 1), (1, 2)])
+        );
+    }
+
+    #[test]
+    fn should_keep_frames_together() {
+        // a frame is only silent if all channels are
+        let input = vec![0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0];
+        let source = SkipSilence::new(SamplesBuffer::new(2, 1000, input), true, None);
+
+        assert_eq!(source.collect::<Vec<_>>(), [0.0, 0.5, 0.0, 0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn should_passthrough() {
+        let input = samples(&[(0, 3), (2, 5)]);
+        let source = SkipSilence::new(SamplesBuffer::new(1, 1000, input.clone()), false, None);
+
+        assert_eq!(source.collect::<Vec<_>>(), input);
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+
+use super::SampleType;
+
+/// The length of a segment in seconds, consecutive segments overlap by half of it.
+const SEGMENT_SECS: f64 = 0.04;
+
+/// How far a segment may be shifted from its ideal position in seconds, to continue the previous segment.
+const SHIFT_SECS: f64 = 0.01;
+
+/// Speeds closer to `1.0` than this are passed through unchanged.
+const PASSTHROUGH: f32 = 0.01;
+
+/// Every how many frames the segments are compared, as comparing every frame is unnecessarily exact.
+const COMPARE_STRIDE: usize = 4;
+
+/// Change the speed of a source while keeping its pitch, by overlapping segments of it (WSOLA).
+///
+/// For every half segment of output, a segment is taken from the input position advanced by the speed, shifted by up
+/// to [`SHIFT_SECS`] to where it best continues the waveform of the previous segment, so that the overlaps do not
+/// cancel out.
+#[derive(Debug)]
+pub struct TimeStretch<I> {
+    input: I,
+    factor: f32,
+    /// Whether the segments are currently overlapped, or the input is passed through
+    active: bool,
+    channels: usize,
+    /// The frames of half a segment, the step of the output
+    hop: usize,
+    /// The frames a segment may be shifted by in both directions
+    shift: usize,
+    /// A periodic Hann window of a whole segment, the halves sum to `1`
+    window: Vec<f32>,
+    /// Interleaved input samples, starting at the frame `buffer_start` since the stretching started
+    buffer: VecDeque<SampleType>,
+    buffer_start: usize,
+    /// The ideal input frame of the next segment
+    position: f64,
+    /// The input frame the previous segment started at, [`None`] for the first segment
+    previous: Option<usize>,
+    /// The windowed second half of the previous segment, added to the first half of the next
+    overlap: Vec<SampleType>,
+    /// Processed samples to output
+    output: VecDeque<SampleType>,
+    /// Whether the input ended while stretching
+    ended: bool,
+}
+
+impl<I> TimeStretch<I>
+where
+    I: Source<Item = SampleType>,
+{
+    /// Wrap the `input` source, initially at speed `factor`.
+    pub fn new(input: I, factor: f32) -> Self {
+        Self {
+            input,
+            factor,
+            active: false,
+            channels: 1,
+            hop: 1,
+            shift: 0,
+            window: Vec::new(),
+            buffer: VecDeque::new(),
+            buffer_start: 0,
+            position: 0.0,
+            previous: None,
+            overlap: Vec::new(),
+            output: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Modifies the speed factor.
+    #[inline]
+    pub fn set_factor(&mut self, factor: f32) {
+        self.factor = factor;
+    }
+
+    /// Start overlapping segments from the current position of the input.
+    fn activate(&mut self) {
+        self.channels = usize::from(self.input.channels().max(1));
+        let rate = f64::from(self.input.sample_rate());
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // small positive values
+        {
+            self.hop = ((rate * SEGMENT_SECS / 2.0) as usize).max(1);
+            self.shift = (rate * SHIFT_SECS) as usize;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let len = (self.hop * 2) as f32;
+        self.window = (0..self.hop * 2)
+            .map(|n| {
+                #[allow(clippy::cast_precision_loss)]
+                let n = n as f32;
+                0.5 - 0.5 * (2.0 * PI * n / len).cos()
+            })
+            .collect();
+        self.buffer.clear();
+        self.buffer_start = 0;
+        self.position = 0.0;
+        self.previous = None;
+        self.overlap = vec![0.0; self.hop * self.channels];
+        self.active = true;
+    }
+
+    /// Read input until the buffer reaches frame `end`, returns the frame the buffer actually ends at.
+    fn fill(&mut self, end: usize) -> usize {
+        let wanted = end.saturating_sub(self.buffer_start) * self.channels;
+        while self.buffer.len() < wanted {
+            let Some(sample) = self.input.next() else {
+                break;
+            };
+            self.buffer.push_back(sample);
+        }
+
+        self.buffer_start + self.buffer.len() / self.channels
+    }
+
+    /// Get the sample of `channel` at input `frame`, silence past the end of the input.
+    fn sample(&self, frame: usize, channel: usize) -> SampleType {
+        let idx = (frame - self.buffer_start) * self.channels + channel;
+        self.buffer.get(idx).copied().unwrap_or(0.0)
+    }
+
+    /// Get the sum of all channels at input `frame`.
+    fn mono(&self, frame: usize) -> SampleType {
+        (0..self.channels).map(|ch| self.sample(frame, ch)).sum()
+    }
+
+    /// Find the start of the segment around `ideal` that best continues at the frame `continuation`.
+    fn best_start(&self, ideal: usize, continuation: usize) -> usize {
+        let low = ideal.saturating_sub(self.shift).max(self.buffer_start);
+        let high = ideal + self.shift;
+
+        let mut best = (ideal.max(low), f32::MIN);
+        for start in low..=high {
+            let (mut correlation, mut energy) = (0.0, 0.0);
+            for n in (0..self.hop).step_by(COMPARE_STRIDE) {
+                let candidate = self.mono(start + n);
+                correlation += candidate * self.mono(continuation + n);
+                energy += candidate * candidate;
+            }
+            let score = correlation / (energy + 1e-9).sqrt();
+            if score > best.1 {
+                best = (start, score);
+            }
+        }
+
+        best.0
+    }
+
+    /// Output the next half segment, returns `false` if the input has ended and everything was output.
+    ///
+    /// If not `stretch`ing anymore, the segment continues exactly where the previous one left off, after which the
+    /// rest of the buffer is output as-is and the input passed through again.
+    fn step(&mut self, stretch: bool) -> bool {
+        let hop = self.hop;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        // positive and not longer than the input
+        let ideal = self.position.round() as usize;
+        let continuation = self.previous.map(|v| v + hop);
+
+        let end = self.fill(ideal.max(continuation.unwrap_or(0)) + self.shift + hop * 2);
+        if ideal >= end {
+            // the input ended, the previous segment still fades out
+            if self.previous.is_some() {
+                self.output.extend(self.overlap.drain(..));
+            }
+            self.active = false;
+            self.ended = true;
+            return !self.output.is_empty();
+        }
+
+        let start = match continuation {
+            None => ideal,
+            Some(continuation) if !stretch => continuation,
+            Some(continuation) => self.best_start(ideal, continuation),
+        };
+
+        for n in 0..hop {
+            for ch in 0..self.channels {
+                let idx = n * self.channels + ch;
+                let sample = self.sample(start + n, ch);
+                // the first segment starts unwindowed, as there is no previous one to fade in from
+                let value = if self.previous.is_some() {
+                    self.overlap[idx] + self.window[n] * sample
+                } else {
+                    sample
+                };
+                self.output.push_back(value);
+                self.overlap[idx] = self.window[hop + n] * self.sample(start + hop + n, ch);
+            }
+        }
+
+        if !stretch {
+            // the overlap was the windowed continuation, so the input continues after this segment
+            let skip = (start + hop - self.buffer_start) * self.channels;
+            self.output.extend(self.buffer.drain(..).skip(skip));
+            self.active = false;
+            return true;
+        }
+
+        self.previous = Some(start);
+        #[allow(clippy::cast_precision_loss)] // a few thousand frames
+        let advance = hop as f64 * f64::from(self.factor);
+        self.position += advance;
+
+        // keep the frames the next segment may start at, and the continuation of this one
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let next_low = (self.position.round() as usize).saturating_sub(self.shift);
+        let keep = next_low.min(start + hop);
+        let drop = keep.saturating_sub(self.buffer_start) * self.channels;
+        self.buffer.drain(..drop.min(self.buffer.len()));
+        self.buffer_start = keep.max(self.buffer_start);
+
+        true
+    }
+}
+
+impl<I> Iterator for TimeStretch<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.output.pop_front() {
+                return Some(sample);
+            }
+
+            if self.ended {
+                return None;
+            }
+
+            let stretch = (self.factor - 1.0).abs() >= PASSTHROUGH;
+            if !self.active {
+                if !stretch {
+                    return self.input.next();
+                }
+                self.activate();
+            }
+
+            if !self.step(stretch) {
+                return None;
+            }
+        }
+    }
+}
+
+impl<I> Source for TimeStretch<I>
+where
+    I: Source<Item = SampleType>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        if self.active || !self.output.is_empty() {
+            // segments do not line up with the spans of the input
+            None
+        } else {
+            self.input.current_span_len()
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // start over from the new position
+        self.active = false;
+        self.ended = false;
+        self.buffer.clear();
+        self.output.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use pretty_assertions::assert_eq;
+    use rodio::buffer::SamplesBuffer;
+
+    use super::TimeStretch;
+
+    const RATE: u32 = 8000;
+
+    /// Get `secs` of a mono sine of `freq`.
+    fn sine(freq: f32, secs: f32) -> Vec<f32> {
+        let len = (RATE as f32 * secs) as usize;
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                0.5 * (2.0 * PI * freq * t).sin()
+            })
+            .collect()
+    }
+
+    /// Get the frequency of the mono `samples` from their zero crossings.
+    fn frequency(samples: &[f32]) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|v| (v[0] < 0.0) != (v[1] < 0.0))
+            .count();
+
+        let secs = samples.len() as f32 / RATE as f32;
+        crossings as f32 / 2.0 / secs
+    }
+
+    #[test]
+    fn should_passthrough_normal_speed() {
+        let samples = sine(440.0, 0.5);
+        let stretched: Vec<f32> =
+            TimeStretch::new(SamplesBuffer::new(1, RATE, samples.clone()), 1.0).collect();
+
+        assert_eq!(stretched, samples);
+    }
+
+    #[test]
+    fn should_change_length_but_keep_pitch() {
+        let samples = sine(440.0, 2.0);
+        for factor in [0.5, 1.5, 2.0] {
+            let stretched: Vec<f32> =
+                TimeStretch::new(SamplesBuffer::new(1, RATE, samples.clone()), factor).collect();
+
+            let expected = samples.len() as f32 / factor;
+            let len = stretched.len() as f32;
+            assert!(
+                (len - expected).abs() < RATE as f32 * 0.05,
+                "{factor}: {len} != {expected}"
+            );
+
+            let freq = frequency(&stretched);
+            assert!((freq - 440.0).abs() < 10.0, "{factor}: {freq}");
+        }
+    }
+
+    #[test]
+    fn should_continue_seamlessly_after_stretching() {
+        let samples = sine(440.0, 1.0);
+        let mut stretch = TimeStretch::new(SamplesBuffer::new(2, RATE, samples.clone()), 1.5);
+        let mut stretched: Vec<f32> = stretch.by_ref().take(2000).collect();
+        stretch.set_factor(1.0);
+        stretched.extend(stretch);
+
+        // every sample after the transition is the unchanged input
+        let tail = &stretched[stretched.len() - 1000..];
+        assert_eq!(tail, &samples[samples.len() - 1000..]);
+    }
+}
@@ -0,0 +1,198 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use rodio::{Source, source::SeekError};
+
+use super::SampleType;
+
+/// Frequency below which the center channel is kept, so that bass and kick drums are not removed with the vocals.
+const BASS_CUTOFF_HZ: f32 = 200.0;
+
+/// Reduce vocals by cancelling the center channel of a stereo source.
+///
+/// Vocals are usually mixed equally into both channels, so subtracting the channels from each other removes them.
+/// Sources with a channel count other than 2 are passed through unchanged.
+#[derive(Debug, Clone)]
+pub struct VocalReduction<I> {
+    input: I,
+    enabled: bool,
+    /// The already processed right sample of the current frame.
+    pending: Option<SampleType>,
+    /// The amount of samples left of the current frame that are passed through unchanged.
+    passthrough: u16,
+    /// The low-passed center channel, see [`BASS_CUTOFF_HZ`].
+    low_mid: SampleType,
+    /// The low-pass coefficient and the sample rate it was calculated for.
+    alpha: (u32, SampleType),
+}
+
+impl<I> VocalReduction<I>
+where
+    I: Source<Item = SampleType>,
+{
+    /// Wrap the `input` source, initially `enabled` or not.
+    #[inline]
+    pub fn new(input: I, enabled: bool) -> Self {
+        Self {
+            input,
+            enabled,
+            pending: None,
+            passthrough: 0,
+            low_mid: 0.0,
+            alpha: (0, 0.0),
+        }
+    }
+
+    /// Enable or disable the vocal reduction.
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    #[expect(dead_code)]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Get the low-pass coefficient for the current sample rate.
+    fn alpha(&mut self) -> SampleType {
+        let sample_rate = self.input.sample_rate();
+        if self.alpha.0 != sample_rate {
+            #[allow(clippy::cast_precision_loss)]
+            let alpha = 1.0 - (-2.0 * PI * BASS_CUTOFF_HZ / sample_rate.max(1) as f32).exp();
+            self.alpha = (sample_rate, alpha);
+        }
+
+        self.alpha.1
+    }
+
+    /// Remove the center of a stereo frame, except for its low frequencies.
+    fn process(&mut self, left: SampleType, right: SampleType) -> (SampleType, SampleType) {
+        let mid = left.midpoint(right);
+        let side = (left - right) / 2.0;
+        let alpha = self.alpha();
+        self.low_mid += alpha * (mid - self.low_mid);
+
+        (self.low_mid + side, self.low_mid - side)
+    }
+}
+
+impl<I> Iterator for VocalReduction<I>
+where
+    I: Source<Item = SampleType>,
+{
+    type Item = SampleType;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.pending.take() {
+            return Some(right);
+        }
+        if self.passthrough > 0 {
+            self.passthrough -= 1;
+            return self.input.next();
+        }
+
+        // at the start of a frame
+        let channels = self.input.channels();
+        if !self.enabled || channels != 2 {
+            self.passthrough = channels.saturating_sub(1);
+            return self.input.next();
+        }
+
+        let left = self.input.next()?;
+        let Some(right) = self.input.next() else {
+            return Some(left);
+        };
+        let (left, right) = self.process(left, right);
+        self.pending = Some(right);
+
+        Some(left)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for VocalReduction<I>
+where
+    I: Source<Item = SampleType>,
+{
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rodio::buffer::SamplesBuffer;
+
+    use super::VocalReduction;
+
+    #[test]
+    fn should_remove_center() {
+        // a center-only signal at the nyquist frequency
+        let samples: Vec<f32> = (0..200)
+            .flat_map(|i| {
+                let v = if i % 2 == 0 { 1.0 } else { -1.0 };
+                [v, v]
+            })
+            .collect();
+        let source = VocalReduction::new(SamplesBuffer::new(2, 44100, samples), true);
+
+        let out: Vec<f32> = source.collect();
+        assert_eq!(out.len(), 400);
+        // the low-pass filter lets through very little of the high frequency
+        assert!(out.iter().all(|v| v.abs() < 0.05), "{out:?}");
+    }
+
+    #[test]
+    fn should_keep_side() {
+        let samples = vec![0.5, -0.5, -0.25, 0.25, 1.0, -1.0];
+        let source = VocalReduction::new(SamplesBuffer::new(2, 44100, samples.clone()), true);
+
+        assert_eq!(source.collect::<Vec<_>>(), samples);
+    }
+
+    #[test]
+    fn should_passthrough() {
+        let samples = vec![0.5, 0.5, 0.25, 0.25, 1.0, 1.0];
+
+        let disabled = VocalReduction::new(SamplesBuffer::new(2, 44100, samples.clone()), false);
+        assert_eq!(disabled.collect::<Vec<_>>(), samples);
+
+        let mono = VocalReduction::new(SamplesBuffer::new(1, 44100, samples.clone()), true);
+        assert_eq!(mono.collect::<Vec<_>>(), samples);
+    }
+}
@@ -0,0 +1,43 @@
+//! The automatic "Last position" bookmark of long files, see `player.auto_bookmark_minutes`.
+
+use std::time::Duration;
+
+use termusiclib::new_database::bookmark_ops;
+
+use crate::{GeneralPlayer, PlayerTrait};
+
+impl GeneralPlayer {
+    /// Store the position of the current track as its automatic bookmark, if it is long enough.
+    ///
+    /// Called whenever playback leaves the track, like [`player_save_last_position`](Self::player_save_last_position).
+    pub fn player_save_auto_bookmark(&mut self) {
+        let minutes = self.config.read().settings.player.auto_bookmark_minutes;
+        if minutes == 0 {
+            return;
+        }
+
+        let playlist = self.playlist.read();
+        let Some(track) = playlist.current_track() else {
+            return;
+        };
+        // live streams have no position to come back to
+        if track.source().is_live()
+            || track
+                .duration()
+                .is_none_or(|v| v < Duration::from_secs(u64::from(minutes) * 60))
+        {
+            return;
+        }
+        let location = track.source().location().into_owned();
+        drop(playlist);
+
+        let Some(position) = self.position().filter(|v| !v.is_zero()) else {
+            return;
+        };
+        if let Err(err) =
+            bookmark_ops::set_auto_bookmark(&self.db.get_connection(), &location, position)
+        {
+            warn!("Error saving the automatic bookmark of \"{location}\": {err:#}");
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -5,28 +7,52 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use parking_lot::RwLock;
 pub use playlist::Playlist;
+use termusiclib::auto_dj;
+use termusiclib::cache::CACHES;
 use termusiclib::config::SharedServerSettings;
+use termusiclib::config::v2::server::ServerSettings;
+use termusiclib::config::v2::server::auto_dj::AutoDjSettings;
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
-use termusiclib::new_database::{Database, track_ops};
+use termusiclib::config::v2::server::equalizer::{BANDS, EqualizerSettings};
+use termusiclib::credentials::CREDENTIALS;
+use termusiclib::metrics::METRICS;
+use termusiclib::new_database::Database;
+use termusiclib::new_database::track_ops::{
+    self, DamagedRegion, PlayStats, TrackOffsets, TrackRead,
+};
 use termusiclib::player::playlist_helpers::{
-    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+    PlaylistAddRandom, PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed,
+    PlaylistSwapTrack, PlaylistTrackSource,
 };
 use termusiclib::player::{
-    PlayerProgress, PlayerTimeUnit, RunningStatus, TrackChangedInfo, UpdateEvents,
+    AbLoop, ListeningTimeInfo, PlayerProgress, PlayerTimeUnit, RunningStatus, TrackChangedInfo,
+    UpdateEvents,
 };
+use termusiclib::playlist::share::youtube_id;
 use termusiclib::podcast::db::Database as DBPod;
-use termusiclib::track::{MediaTypes, Track};
+use termusiclib::sponsorblock::{self, Segment};
+use termusiclib::track::{MediaTypes, Track, normalization_gain};
 use termusiclib::utils::get_app_config_path;
 use tokio::runtime::Handle;
 use tokio::sync::mpsc::error::SendError;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::sync::{broadcast, oneshot};
+use tokio::task::AbortHandle;
 
+pub use backends::rusty::{
+    DecodeBench, RenderOptions, RenderReport, RenderedTrack, bench_decode, list_output_devices,
+    probe_output_device, render_queue,
+};
 pub use backends::{Backend, BackendSelect};
 
+mod bookmarks;
 mod discord;
 mod mpris;
+mod now_playing;
 pub mod playlist;
+mod resume;
+mod termux;
+mod webhooks;
 
 #[macro_use]
 extern crate log;
@@ -44,7 +70,7 @@ pub type PlayerCmdCallback = oneshot::Receiver<()>;
 pub type PlayerCmdReciever = UnboundedReceiver<(PlayerCmd, PlayerCmdCallbackSender)>;
 
 /// Wrapper around the potential oneshot sender to implement convenience functions.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct PlayerCmdCallbackSender(Option<oneshot::Sender<()>>);
 
 impl PlayerCmdCallbackSender {
@@ -113,10 +139,27 @@ pub enum PlayerCmd {
     /// This will basically be treated as a [`Eos`](PlayerCmd::Eos), with some extra handling.
     ///
     /// This should **not** be used if the whole backend is unrecoverable.
-    Error(PlayerErrorType),
+    ///
+    /// The [`String`] is the reason, shown to the user on the failed playlist entry.
+    Error(PlayerErrorType, String),
+    /// The Backend muted damaged regions of the given file instead of aborting it, to be flagged for repair.
+    Damaged(PathBuf, Vec<DamagedRegion>),
+    /// The `SponsorBlock` segments of the video with the given url were fetched.
+    SponsorSegments(String, Vec<Segment>),
+    /// Random tracks were sampled from the library database, to add to the end of the playlist, see
+    /// [`GeneralPlayer::playlist_add_random`].
+    RandomTracks(Vec<TrackRead>),
+    /// The Backend paused because the output device with the given name is gone, like unplugged headphones.
+    OutputLost(String),
+    /// The Backend now plays on the output device with the given name.
+    OutputSwitched(String),
 
     // Internal only
     Tick,
+    /// Update the volume while ducking ramps, sent more often than [`Tick`](PlayerCmd::Tick).
+    DuckTick,
+    /// Check the A-B loop right when its end should be reached, see [`GeneralPlayer::check_ab_loop`].
+    AbLoopCheck,
 
     // Mainly called from outside sources (client, mpris)
     CycleLoop,
@@ -129,17 +172,40 @@ pub enum PlayerCmd {
     ReloadPlaylist,
     SeekBackward,
     SeekForward,
+    /// Seek to a absolute position in the current track.
+    SeekTo(Duration),
+    /// Repeat a region of the current track, or stop repeating it.
+    AbLoop(Option<AbLoop>),
+    /// Run all commands of the macro with the given name, before any other command.
+    RunMacro(String),
     SkipNext,
     SpeedDown,
     SpeedUp,
     ToggleGapless,
     TogglePause,
+    /// Enable or disable vocal reduction for karaoke.
+    VocalReduction(bool),
+    /// Enable or disable loudness normalization.
+    Normalization(bool),
+    /// Enable or disable smart speed, shortening long silences in podcast episodes.
+    SmartSpeed(bool),
+    /// Change the equalizer.
+    Equalizer(EqualizerSettings),
+    /// Play on the output device with the name, or the default device of the system if empty.
+    OutputDevice(String),
+    /// Start or stop recording what is playing.
+    Recording(bool),
+    /// Enable or disable album mode.
+    AlbumMode(bool),
+    /// Start or end lowering the volume for a notification sound or announcement.
+    Duck(bool),
     VolumeDown,
     VolumeUp,
     VolumeSet(Volume),
 
     PlaylistPlaySpecific(PlaylistPlaySpecific),
     PlaylistAddTrack(PlaylistAddTrack),
+    PlaylistAddRandom(PlaylistAddRandom),
     PlaylistRemoveTrack(PlaylistRemoveTrackIndexed),
     PlaylistClear,
     PlaylistSwapTrack(PlaylistSwapTrack),
@@ -158,6 +224,9 @@ pub struct GeneralPlayer {
     pub current_track_updated: bool,
     pub mpris: Option<mpris::Mpris>,
     pub discord: Option<discord::Rpc>,
+    pub now_playing: Option<now_playing::NowPlaying>,
+    pub termux_notification: Option<termux::TermuxNotification>,
+    pub webhooks: Option<webhooks::Webhooks>,
     pub db: Database,
     pub db_podcast: DBPod,
     pub cmd_tx: PlayerCmdSender,
@@ -165,6 +234,42 @@ pub struct GeneralPlayer {
 
     /// Keep track of continues backend errors (like `NotFound`) to not keep trying infinitely.
     pub errors_since_last_progress: usize,
+    /// The offsets of the current track, the end is removed once it was reached.
+    current_offsets: TrackOffsets,
+    /// The `SponsorBlock` segments of the current video that were not skipped yet.
+    current_sponsor_segments: Vec<Segment>,
+    /// The region of the current track that is repeated.
+    ab_loop: Option<AbLoop>,
+    /// The pending [`PlayerCmd::AbLoopCheck`], see [`Self::player_check_ab_loop`].
+    ab_loop_check: Option<AbortHandle>,
+    /// The point to continue from after a restart.
+    resume: resume::Resume,
+    /// The name of the lost output device playback was paused for, to resume once it appears again.
+    paused_for_output: Option<String>,
+}
+
+/// How often the progress is updated, a A-B loop ending sooner is checked precisely with [`PlayerCmd::AbLoopCheck`].
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Get the artwork url of `track` if it is a podcast episode, falling back to the artwork of its feed.
+fn podcast_art_url(db_podcast: &DBPod, track: &Track) -> Option<String> {
+    let MediaTypes::Podcast(podcast_data) = track.inner() else {
+        return None;
+    };
+    if let Some(url) = podcast_data.image_url() {
+        return Some(url.to_string());
+    }
+
+    db_podcast
+        .get_podcast_image_by_episode_url(podcast_data.url())
+        .inspect_err(|err| warn!("Getting the podcast artwork failed: {err:#}"))
+        .ok()
+        .flatten()
+}
+
+/// Get whether the termux notification should be shown with the given settings.
+fn termux_notification_enabled(settings: &ServerSettings) -> bool {
+    settings.termux.notification && settings.termux.is_active()
 }
 
 impl GeneralPlayer {
@@ -190,7 +295,7 @@ impl GeneralPlayer {
         let db = Database::new_default_path()?;
 
         let mpris = if config.read().settings.player.use_mediacontrols {
-            let mut mpris = mpris::Mpris::new(cmd_tx.clone());
+            let mut mpris = mpris::Mpris::new(cmd_tx.clone(), &config.read().settings.player);
 
             // set volume on start, as souvlaki (0.8.2) defaults to 1.0 until set by us
             // also otherwise we only set this once the volume actually changes or mpris is re-started via config reload
@@ -205,6 +310,14 @@ impl GeneralPlayer {
         } else {
             None
         };
+        let now_playing = now_playing::NowPlaying::new(&config_read.settings.now_playing);
+        let webhooks = webhooks::Webhooks::new(&config_read.settings);
+        let resume = resume::Resume::load(&db, config_read.settings.player.resume_playback);
+        let termux_notification = if termux_notification_enabled(&config_read.settings) {
+            Some(termux::TermuxNotification::new())
+        } else {
+            None
+        };
 
         drop(config_read);
 
@@ -214,6 +327,9 @@ impl GeneralPlayer {
             config,
             mpris,
             discord,
+            now_playing,
+            termux_notification,
+            webhooks,
             db,
             db_podcast,
             cmd_tx,
@@ -221,6 +337,12 @@ impl GeneralPlayer {
             current_track_updated: false,
 
             errors_since_last_progress: 0,
+            current_offsets: TrackOffsets::default(),
+            current_sponsor_segments: Vec::new(),
+            ab_loop: None,
+            ab_loop_check: None,
+            resume,
+            paused_for_output: None,
         })
     }
 
@@ -266,12 +388,24 @@ impl GeneralPlayer {
         let parsed = ServerConfigVersionedDefaulted::from_config_path()?.into_settings();
         config.settings = parsed;
 
+        CACHES.apply_settings(&config.settings.cache);
+        CREDENTIALS.apply_settings(&config.settings.credentials);
+
+        // the names can only be set on registration, so register again with the new names
+        if self
+            .mpris
+            .as_ref()
+            .is_some_and(|v| !v.has_names_of(&config.settings.player))
+        {
+            self.mpris.take();
+        }
+
         if config.settings.player.use_mediacontrols && self.mpris.is_none() {
             // start mpris if new config has it enabled, but is not active yet
-            let mut mpris = mpris::Mpris::new(self.cmd_tx.clone());
+            let mut mpris = mpris::Mpris::new(self.cmd_tx.clone(), &config.settings.player);
             // actually set the metadata of the currently playing track, otherwise the controls will work but no title or coverart will be set until next track
             if let Some(track) = self.playlist.read().current_track() {
-                mpris.add_and_play(track);
+                mpris.add_and_play(track, podcast_art_url(&self.db_podcast, track).as_deref());
             }
             // the same for volume
             mpris.update_volume(self.volume());
@@ -296,6 +430,31 @@ impl GeneralPlayer {
             self.discord.take();
         }
 
+        if termux_notification_enabled(&config.settings) != self.termux_notification.is_some() {
+            // start or stop the termux notification, the current track is shown on the next update below or on the next change
+            self.termux_notification =
+                termux_notification_enabled(&config.settings).then(termux::TermuxNotification::new);
+        }
+
+        if self.webhooks.as_ref().map(webhooks::Webhooks::settings)
+            != Some(&config.settings.webhooks)
+        {
+            // (re-)create the webhooks if their settings changed, or stop them if all got removed
+            self.webhooks = webhooks::Webhooks::new(&config.settings);
+        }
+
+        if self
+            .now_playing
+            .as_ref()
+            .map(now_playing::NowPlaying::settings)
+            != Some(&config.settings.now_playing)
+        {
+            // (re-)start the now playing writer if its settings changed, or stop it if it got disabled
+            self.now_playing = now_playing::NowPlaying::new(&config.settings.now_playing);
+            drop(config);
+            self.now_playing_update();
+        }
+
         info!("Config Reloaded");
 
         Ok(())
@@ -331,19 +490,37 @@ impl GeneralPlayer {
 
         if let Some(track) = playlist.current_track().cloned() {
             info!("Starting Track {track:#?}");
+            METRICS.inc_tracks_played();
+            if let MediaTypes::Track(track_data) = track.inner()
+                && let Err(err) =
+                    track_ops::mark_played(&self.db.get_connection(), track_data.path())
+            {
+                warn!("Error marking track as played: {err:#}");
+            }
+            self.current_offsets = self.get_offsets(&track);
+            self.current_sponsor_segments.clear();
+            self.ab_loop = None;
+            if let Some(check) = self.ab_loop_check.take() {
+                check.abort();
+            }
+            self.request_sponsor_segments(&track);
+            self.auto_dj_fill(&playlist);
 
             if playlist.has_next_track() {
                 playlist.set_next_track(None);
                 drop(playlist);
+                self.apply_track_speed(&track);
                 self.current_track_updated = true;
                 info!("gapless next track played");
                 self.add_and_play_mpris_discord();
+                self.player_seek_to_start_offset();
 
                 self.send_track_changed();
 
                 return;
             }
             drop(playlist);
+            self.apply_track_speed(&track);
 
             self.current_track_updated = true;
             let wait = async {
@@ -352,15 +529,91 @@ impl GeneralPlayer {
             Handle::current().block_on(wait);
 
             self.add_and_play_mpris_discord();
-            self.player_restore_last_position();
+            if !self.player_restore_resume_point(&track) && !self.player_restore_last_position() {
+                self.player_seek_to_start_offset();
+            }
 
             self.send_track_changed();
         }
     }
 
+    /// Get the speed to play `track` at: the remembered speed of its podcast, otherwise the configured speed.
+    fn track_speed(&self, track: &Track) -> Speed {
+        let podcast_speed = track.as_podcast().and_then(|podcast_data| {
+            self.db_podcast
+                .get_podcast_speed_by_episode_url(podcast_data.url())
+                .inspect_err(|err| warn!("Getting the podcast speed failed: {err:#}"))
+                .ok()
+                .flatten()
+        });
+
+        podcast_speed
+            .unwrap_or_else(|| self.config.read_recursive().settings.player.speed)
+            .clamp(MIN_SPEED, MAX_SPEED)
+    }
+
+    /// Change the speed to the [`track_speed`](Self::track_speed) of `track`, if different.
+    fn apply_track_speed(&mut self, track: &Track) {
+        let speed = self.track_speed(track);
+        if speed != self.speed() {
+            info!("Changing speed to {speed} for the track");
+            self.set_speed(speed);
+        }
+    }
+
+    /// Remember `speed` for the podcast of the current episode, or as the configured speed for all other tracks.
+    pub fn remember_speed(&mut self, speed: Speed) {
+        let podcast_url = self
+            .playlist
+            .read()
+            .current_track()
+            .and_then(|track| track.as_podcast().map(|v| v.url().to_string()));
+
+        if let Some(url) = podcast_url {
+            match self
+                .db_podcast
+                .set_podcast_speed_by_episode_url(&url, speed)
+            {
+                Ok(true) => return,
+                Ok(false) => (),
+                Err(err) => warn!("Remembering the podcast speed failed: {err:#}"),
+            }
+        }
+
+        self.config.write().settings.player.speed = speed;
+    }
+
     /// Handle [`PlayerCmd::MetadataChanged`] for all things the [`GeneralPlayer`] controls.
     pub fn metadata_changed(&mut self) {
         self.send_track_changed();
+        self.now_playing_update();
+    }
+
+    /// Update the now playing line and the termux notification with the current track and status, if enabled.
+    fn now_playing_update(&mut self) {
+        let media_title = self.media_info().media_title;
+        let playlist = self.playlist.read();
+        if let Some(ref mut now_playing) = self.now_playing {
+            now_playing.update(
+                playlist.current_track(),
+                media_title.as_deref(),
+                playlist.status(),
+            );
+        }
+        if let Some(ref mut termux_notification) = self.termux_notification {
+            termux_notification.update(
+                playlist.current_track(),
+                media_title.as_deref(),
+                playlist.status(),
+            );
+        }
+        if let Some(ref mut webhooks) = self.webhooks {
+            webhooks.update(
+                playlist.current_track(),
+                media_title.as_deref(),
+                playlist.status(),
+            );
+        }
     }
 
     /// Send event [`UpdateEvents::TrackChanged`]. In a function to de-duplicate calls.
@@ -377,13 +630,15 @@ impl GeneralPlayer {
     fn add_and_play_mpris_discord(&mut self) {
         if let Some(track) = self.playlist.read().current_track() {
             if let Some(ref mut mpris) = self.mpris {
-                mpris.add_and_play(track);
+                let art_url = podcast_art_url(&self.db_podcast, track);
+                mpris.add_and_play(track, art_url.as_deref());
             }
 
             if let Some(ref discord) = self.discord {
                 discord.update(track);
             }
         }
+        self.now_playing_update();
     }
     pub fn enqueue_next_from_playlist(&mut self) {
         let mut playlist = self.playlist.write();
@@ -463,6 +718,35 @@ impl GeneralPlayer {
             }
         }
     }
+
+    /// Pause for the lost output device `name`, which the backend already stopped playing on.
+    pub fn output_lost(&mut self, name: String) {
+        self.pause();
+        self.paused_for_output = Some(name);
+    }
+
+    /// Resume playback if it was paused for the loss of the output device `name`, now played on again, and
+    /// `backends.rusty.device_loss` is `pause_and_resume`.
+    pub fn output_switched(&mut self, name: &str) {
+        if self.paused_for_output.as_deref() != Some(name) {
+            return;
+        }
+        self.paused_for_output = None;
+
+        if self
+            .config
+            .read()
+            .settings
+            .backends
+            .rusty
+            .device_loss
+            .resumes()
+        {
+            info!("Resuming, the output device \"{name}\" is back");
+            self.play();
+        }
+    }
+
     /// # Panics
     ///
     /// if the underlying "seek" returns a error (which current never happens)
@@ -497,7 +781,7 @@ impl GeneralPlayer {
                 track_ops::set_last_position(&self.db.get_connection(), track_data.path(), to)
                     .with_context(|| track_data.path().to_string_lossy().to_string())?;
             }
-            MediaTypes::Radio(_) => (),
+            MediaTypes::Radio(_) | MediaTypes::Video(_) => (),
             MediaTypes::Podcast(_podcast_track_data) => {
                 let to = to.unwrap_or_default();
                 self.db_podcast
@@ -509,6 +793,176 @@ impl GeneralPlayer {
         Ok(())
     }
 
+    /// Sample random tracks from the library database in the background and add them to the end of the playlist with
+    /// [`PlayerCmd::RandomTracks`], `cb` is called once they were added or sampling failed.
+    ///
+    /// Requires that the function is called on a thread with a entered tokio runtime
+    pub fn playlist_add_random(&self, kind: PlaylistAddRandom, cb: PlayerCmdCallbackSender) {
+        let db = self.db.clone();
+        let playlist = self.playlist.clone();
+        let settings = self.config.read().settings.auto_dj.clone();
+        let cmd_tx = self.cmd_tx.clone();
+        tokio::spawn(async move {
+            let res = db
+                .read(move |conn| match kind {
+                    PlaylistAddRandom::Tracks(amount) => track_ops::get_random_tracks(conn, amount),
+                    PlaylistAddRandom::Album(min_tracks) => {
+                        track_ops::get_random_album_tracks(conn, min_tracks)
+                    }
+                    PlaylistAddRandom::Unplayed(duration) => {
+                        track_ops::get_random_unplayed_tracks(conn, duration)
+                    }
+                    PlaylistAddRandom::AutoDj(amount) => {
+                        track_ops::get_play_stats(conn, &settings.exclude_labels()).map(
+                            |candidates| {
+                                auto_dj_choose(candidates, &playlist.read(), amount, &settings)
+                            },
+                        )
+                    }
+                })
+                .await;
+
+            match res {
+                Ok(tracks) if tracks.is_empty() => {
+                    warn!("No matching tracks in the library for {kind:?}");
+                }
+                Ok(tracks) => {
+                    if let Ok(rx) = cmd_tx.send_cb(PlayerCmd::RandomTracks(tracks)) {
+                        // wait until the tracks were added
+                        let _ = rx.await;
+                    }
+                }
+                Err(err) => error!("Error adding random tracks: {err:#}"),
+            }
+            cb.call();
+        });
+    }
+
+    /// Add `tracks` from the library database to the end of `playlist`.
+    ///
+    /// # Errors
+    ///
+    /// if adding the tracks fails
+    ///
+    /// # Panics
+    ///
+    /// if the playlist length is above u64
+    pub fn playlist_add_to_end(&self, playlist: &mut Playlist, tracks: &[TrackRead]) -> Result<()> {
+        let sources = tracks
+            .iter()
+            .map(|v| PlaylistTrackSource::Path(v.as_pathbuf().to_string_lossy().to_string()))
+            .collect();
+        let at_index = u64::try_from(playlist.len()).unwrap();
+        playlist.add_tracks(
+            PlaylistAddTrack::new_vec(at_index, sources),
+            &self.db_podcast,
+        )?;
+
+        Ok(())
+    }
+
+    /// Add tracks chosen by the auto-DJ if it is enabled and the last track of `playlist` is playing.
+    ///
+    /// Requires that the function is called on a thread with a entered tokio runtime
+    fn auto_dj_fill(&self, playlist: &Playlist) {
+        let (enabled, amount) = {
+            let config = self.config.read();
+            let auto_dj = &config.settings.auto_dj;
+            (auto_dj.enabled, auto_dj.tracks.get())
+        };
+        if !enabled
+            || playlist.get_current_track_index() + 1 < playlist.len()
+            || !playlist.album_queue().is_empty()
+        {
+            return;
+        }
+
+        self.playlist_add_random(
+            PlaylistAddRandom::AutoDj(amount),
+            PlayerCmdCallbackSender::default(),
+        );
+    }
+
+    /// Get whether album mode is enabled, see [`playlist_queue_album`](Self::playlist_queue_album).
+    pub fn album_mode(&self) -> bool {
+        self.config.read().settings.player.album_mode
+    }
+
+    /// Enable or disable album mode, disabling stops a currently queued album after the current track.
+    pub fn set_album_mode(&mut self, to: bool) {
+        self.config.write().settings.player.album_mode = to;
+        if !to {
+            self.playlist.write().clear_album_queue();
+        }
+    }
+
+    /// Queue the rest of the current track's album to play in order, regardless of the [`LoopMode`](termusiclib::config::v2::server::LoopMode).
+    ///
+    /// Album tracks that are not in the playlist yet are inserted after the current track.
+    /// Does nothing if the current track is not a music file or not part of a album in the library.
+    ///
+    /// # Errors
+    ///
+    /// - if querying the database fails
+    ///
+    /// # Panics
+    ///
+    /// if the playlist length is above u64
+    pub fn playlist_queue_album(&mut self) -> Result<()> {
+        let playlist = self.playlist.read();
+        let current_index = playlist.get_current_track_index();
+        let Some(MediaTypes::Track(track_data)) =
+            playlist.tracks().get(current_index).map(Track::inner)
+        else {
+            return Ok(());
+        };
+        let current_path = track_data.path().to_path_buf();
+        drop(playlist);
+
+        let album_tracks = {
+            let conn = self.db.get_connection();
+            let Ok(track) = track_ops::get_track_from_path(&conn, &current_path) else {
+                debug!("Not queueing album, track is not in the library");
+                return Ok(());
+            };
+            let Some(album) = track.album else {
+                return Ok(());
+            };
+            track_ops::get_tracks_from_album_id(&conn, album.id)?
+        };
+
+        let queue: Vec<String> = album_tracks
+            .iter()
+            .map(TrackRead::as_pathbuf)
+            .skip_while(|v| *v != current_path)
+            .skip(1)
+            .map(|v| v.to_string_lossy().to_string())
+            .collect();
+        info!("Album mode: queueing {} tracks", queue.len());
+
+        let mut playlist = self.playlist.write();
+        let existing: Vec<PlaylistTrackSource> = playlist
+            .tracks()
+            .iter()
+            .map(Track::as_track_source)
+            .collect();
+        let missing: Vec<PlaylistTrackSource> = queue
+            .iter()
+            .map(|v| PlaylistTrackSource::Path(v.clone()))
+            .filter(|v| !existing.contains(v))
+            .collect();
+        if !missing.is_empty() {
+            let at_index = u64::try_from(current_index + 1).unwrap();
+            playlist.add_tracks(
+                PlaylistAddTrack::new_vec(at_index, missing),
+                &self.db_podcast,
+            )?;
+        }
+        playlist.set_album_queue(queue);
+
+        Ok(())
+    }
+
     #[allow(clippy::cast_sign_loss)]
     pub fn player_save_last_position(&mut self) {
         let playlist = self.playlist.read();
@@ -545,11 +999,14 @@ impl GeneralPlayer {
         }
     }
 
-    pub fn player_restore_last_position(&mut self) {
+    /// Seek to the last position of the current track, if enabled.
+    ///
+    /// Returns whether a position was restored.
+    pub fn player_restore_last_position(&mut self) -> bool {
         let playlist = self.playlist.read();
         let Some(track) = playlist.current_track().cloned() else {
             info!("Not restoring Last position as there is no current track");
-            return;
+            return false;
         };
         drop(playlist);
 
@@ -572,7 +1029,7 @@ impl GeneralPlayer {
                         restored = true;
                     }
                 }
-                MediaTypes::Radio(_) => (),
+                MediaTypes::Radio(_) | MediaTypes::Video(_) => (),
                 MediaTypes::Podcast(_podcast_track_data) => {
                     if let Ok(last_pos) = self.db_podcast.get_last_position(&track) {
                         self.seek_to(last_pos);
@@ -591,15 +1048,235 @@ impl GeneralPlayer {
         if restored && let Err(err) = self.set_last_position(&track, None) {
             error!("Resetting last_position failed. Error: {err:#?}");
         }
+
+        restored
+    }
+
+    /// Get the [`TrackOffsets`] of `track`, only local tracks can have them.
+    fn get_offsets(&self, track: &Track) -> TrackOffsets {
+        let MediaTypes::Track(track_data) = track.inner() else {
+            return TrackOffsets::default();
+        };
+
+        track_ops::get_offsets(&self.db.get_connection(), track_data.path()).unwrap_or_else(|err| {
+            warn!("Error getting the track offsets: {err:#}");
+            TrackOffsets::default()
+        })
+    }
+
+    /// Get `track` with the gain of its analyzed loudness, if it is a local track without `ReplayGain` tags.
+    ///
+    /// The loudness is analyzed by `termusic-server db loudness`.
+    fn with_analyzed_gain<'a>(&self, track: &'a Track) -> Cow<'a, Track> {
+        let Some(track_data) = track
+            .as_track()
+            .filter(|v| v.normalization_gain().is_none())
+        else {
+            return Cow::Borrowed(track);
+        };
+
+        let loudness = match track_ops::get_loudness(&self.db.get_connection(), track_data.path()) {
+            Ok(Some(v)) => v,
+            Ok(None) => return Cow::Borrowed(track),
+            Err(err) => {
+                warn!("Error getting the analyzed loudness: {err:#}");
+                return Cow::Borrowed(track);
+            }
+        };
+
+        let mut track = track.clone();
+        if let Some(track_data) = track.as_track_mut() {
+            track_data.set_fallback_normalization_gain(normalization_gain(
+                loudness.replay_gain(),
+                Some(loudness.peak),
+            ));
+        }
+
+        Cow::Owned(track)
+    }
+
+    /// Seek to the start offset of the current track, if it has one.
+    fn player_seek_to_start_offset(&mut self) {
+        if let Some(start) = self.current_offsets.start {
+            info!("Starting at the start offset {start:?}");
+            self.seek_to(start);
+        }
+    }
+
+    /// Skip to the next track once the end offset of the current track is reached.
+    fn player_check_end_offset(&mut self, progress: &PlayerProgress) {
+        let Some(end) = self.current_offsets.end else {
+            return;
+        };
+        if progress.position.is_none_or(|v| v < end) {
+            return;
+        }
+
+        info!("Reached the end offset {end:?}, skipping to the next track");
+        // only skip once, even if the next progress is still for this track
+        self.current_offsets.end = None;
+        self.next();
+    }
+
+    /// Fetch the `SponsorBlock` segments of `track` in the background if it is a video, see [`PlayerCmd::SponsorSegments`].
+    ///
+    /// Requires that the function is called on a thread with a entered tokio runtime
+    fn request_sponsor_segments(&self, track: &Track) {
+        let MediaTypes::Video(video_data) = track.inner() else {
+            return;
+        };
+        let settings = self.config.read().settings.sponsorblock.clone();
+        if !settings.is_enabled() {
+            return;
+        }
+        let url = video_data.url().to_string();
+        let Some(id) = youtube_id(&url).map(ToString::to_string) else {
+            return;
+        };
+
+        let cmd_tx = self.cmd_tx.clone();
+        tokio::spawn(async move {
+            match sponsorblock::get_segments(&settings, &id).await {
+                Ok(segments) if segments.is_empty() => (),
+                Ok(segments) => {
+                    let _ = cmd_tx.send(PlayerCmd::SponsorSegments(url, segments));
+                }
+                Err(err) => warn!("Error getting the SponsorBlock segments of {id}: {err:#}"),
+            }
+        });
+    }
+
+    /// Set the `SponsorBlock` `segments` of the video `url`, if it is still the current track.
+    pub fn set_sponsor_segments(&mut self, url: &str, segments: Vec<Segment>) {
+        let is_current =
+            self.playlist.read().current_track().is_some_and(
+                |track| matches!(track.inner(), MediaTypes::Video(v) if v.url() == url),
+            );
+        if !is_current {
+            return;
+        }
+
+        info!("Got {} SponsorBlock segments for {url}", segments.len());
+        self.current_sponsor_segments = segments;
+    }
+
+    /// Skip to the end of the `SponsorBlock` segment the position is in, if any.
+    fn player_check_sponsor_segments(&mut self, progress: &PlayerProgress) {
+        let Some(position) = progress.position else {
+            return;
+        };
+        let Some(idx) = self
+            .current_sponsor_segments
+            .iter()
+            .position(|v| v.contains(position))
+        else {
+            return;
+        };
+
+        // only skip once, to allow seeking back into the segment
+        let segment = self.current_sponsor_segments.remove(idx);
+        info!(
+            "Skipping the SponsorBlock segment {:?} to {:?}",
+            segment.category, segment.end
+        );
+        self.seek_to(segment.end);
+    }
+
+    /// Get the region of the current track that is repeated, if any.
+    pub fn ab_loop(&self) -> Option<AbLoop> {
+        self.ab_loop
+    }
+
+    /// Repeat a region of the current track, or stop repeating it.
+    ///
+    /// The loop is cleared on the next track change.
+    pub fn set_ab_loop(&mut self, to: Option<AbLoop>) {
+        self.ab_loop = to;
+        if let Some(check) = self.ab_loop_check.take() {
+            check.abort();
+        }
+        if let Some(progress) = self.get_progress() {
+            self.player_check_ab_loop(&progress);
+        }
+    }
+
+    /// Seek back to the start of the A-B loop if its end was reached, see [`PlayerCmd::AbLoopCheck`].
+    pub fn check_ab_loop(&mut self) {
+        let Some(ab_loop) = self.ab_loop else {
+            return;
+        };
+        if self
+            .get_progress()
+            .and_then(|v| v.position)
+            .is_some_and(|v| v >= ab_loop.end)
+        {
+            self.seek_to(ab_loop.start);
+        }
+    }
+
+    /// Seek back to the start of the A-B loop once its end is reached.
+    ///
+    /// If the end is reached before the next progress update, a [`PlayerCmd::AbLoopCheck`] is scheduled for that
+    /// time, as the loop would otherwise overshoot by up to [`PROGRESS_INTERVAL`]. Only the latest check is kept.
+    fn player_check_ab_loop(&mut self, progress: &PlayerProgress) {
+        let Some(ab_loop) = self.ab_loop else {
+            return;
+        };
+        let Some(position) = progress.position else {
+            return;
+        };
+        if position >= ab_loop.end {
+            self.seek_to(ab_loop.start);
+            return;
+        }
+
+        // the position is in track time, which passes faster or slower than real time with the speed
+        let speed = u32::try_from(self.speed()).unwrap_or(10).max(1);
+        let remaining = ab_loop.end.saturating_sub(position) * 10 / speed;
+        if remaining >= PROGRESS_INTERVAL || self.playlist.read().status() != RunningStatus::Running
+        {
+            return;
+        }
+
+        if let Some(check) = self.ab_loop_check.take() {
+            check.abort();
+        }
+        let cmd_tx = self.cmd_tx.clone();
+        let check = tokio::spawn(async move {
+            tokio::time::sleep(remaining).await;
+            let _ = cmd_tx.send(PlayerCmd::AbLoopCheck);
+        });
+        self.ab_loop_check = Some(check.abort_handle());
     }
 
     /// Update all the places that should be updated on a new Progress report.
     pub fn update_progress(&mut self, progress: &PlayerProgress) {
         self.mpris_update_progress(progress);
+        self.player_check_end_offset(progress);
+        self.player_check_sponsor_segments(progress);
+        self.player_check_ab_loop(progress);
 
         self.send_stream_ev_no_err(UpdateEvents::Progress(*progress));
     }
 
+    /// Notify about a volume change that did not happen through termusic, like with the system mixer.
+    pub fn volume_changed_externally(&mut self, volume: Volume) {
+        self.mpris_volume_update();
+        self.send_stream_ev(UpdateEvents::VolumeChanged { volume });
+    }
+
+    /// Set the volume of the backend without notifying anyone, for temporary changes like a fade out.
+    ///
+    /// The caller should restore the original volume afterwards.
+    pub fn set_volume_unreported(&mut self, volume: Volume) {
+        self.get_player_mut().set_volume(volume);
+    }
+
+    /// Notify the clients about the time listened since the last break.
+    pub fn listening_time_update(&self, info: ListeningTimeInfo) {
+        self.send_stream_ev(UpdateEvents::ListeningTime(info));
+    }
+
     /// Send stream events with consistent error handling
     fn send_stream_ev(&self, ev: UpdateEvents) {
         // there is only one error case: no receivers
@@ -619,7 +1296,8 @@ impl GeneralPlayer {
 #[async_trait]
 impl PlayerTrait for GeneralPlayer {
     async fn add_and_play(&mut self, track: &Track) {
-        self.get_player_mut().add_and_play(track).await;
+        let track = self.with_analyzed_gain(track);
+        self.get_player_mut().add_and_play(&track).await;
     }
     fn volume(&self) -> Volume {
         self.get_player().volume()
@@ -640,6 +1318,7 @@ impl PlayerTrait for GeneralPlayer {
     }
     /// This function should not be used directly, use `GeneralPlayer::pause`
     fn pause(&mut self) {
+        self.paused_for_output = None;
         self.playlist.write().set_status(RunningStatus::Paused);
         self.get_player_mut().pause();
         if let Some(ref mut mpris) = self.mpris {
@@ -648,9 +1327,11 @@ impl PlayerTrait for GeneralPlayer {
         if let Some(ref discord) = self.discord {
             discord.pause();
         }
+        self.now_playing_update();
     }
     /// This function should not be used directly, use `GeneralPlayer::play`
     fn resume(&mut self) {
+        self.paused_for_output = None;
         self.playlist.write().set_status(RunningStatus::Running);
         self.get_player_mut().resume();
         if let Some(ref mut mpris) = self.mpris {
@@ -660,6 +1341,7 @@ impl PlayerTrait for GeneralPlayer {
         if let Some(ref discord) = self.discord {
             discord.resume(time_pos);
         }
+        self.now_playing_update();
     }
     fn is_paused(&self) -> bool {
         self.get_player().is_paused()
@@ -692,6 +1374,7 @@ impl PlayerTrait for GeneralPlayer {
     fn stop(&mut self) {
         self.playlist.write().stop();
         self.get_player_mut().stop();
+        self.now_playing_update();
     }
 
     fn get_progress(&self) -> Option<PlayerProgress> {
@@ -707,6 +1390,54 @@ impl PlayerTrait for GeneralPlayer {
         self.send_stream_ev(UpdateEvents::GaplessChanged { gapless: to });
     }
 
+    fn vocal_reduction(&self) -> bool {
+        self.get_player().vocal_reduction()
+    }
+
+    fn set_vocal_reduction(&mut self, to: bool) {
+        self.get_player_mut().set_vocal_reduction(to);
+    }
+
+    fn normalization(&self) -> bool {
+        self.get_player().normalization()
+    }
+
+    fn set_normalization(&mut self, to: bool) {
+        self.get_player_mut().set_normalization(to);
+    }
+
+    fn smart_speed(&self) -> bool {
+        self.get_player().smart_speed()
+    }
+
+    fn set_smart_speed(&mut self, to: bool) {
+        self.get_player_mut().set_smart_speed(to);
+    }
+
+    fn equalizer(&self) -> Option<[f32; BANDS]> {
+        self.get_player().equalizer()
+    }
+
+    fn set_equalizer(&mut self, gains: Option<[f32; BANDS]>) {
+        self.get_player_mut().set_equalizer(gains);
+    }
+
+    fn recording(&self) -> bool {
+        self.get_player().recording()
+    }
+
+    fn set_recording(&mut self, to: bool) -> Result<()> {
+        self.get_player_mut().set_recording(to)
+    }
+
+    fn output_device(&self) -> Option<String> {
+        self.get_player().output_device()
+    }
+
+    fn set_output_device(&mut self, name: String) {
+        self.get_player_mut().set_output_device(name);
+    }
+
     fn skip_one(&mut self) {
         self.get_player_mut().skip_one();
     }
@@ -716,7 +1447,8 @@ impl PlayerTrait for GeneralPlayer {
     }
 
     fn enqueue_next(&mut self, track: &Track) {
-        self.get_player_mut().enqueue_next(track);
+        let track = self.with_analyzed_gain(track);
+        self.get_player_mut().enqueue_next(&track);
     }
 
     fn media_info(&self) -> MediaInfo {
@@ -724,6 +1456,28 @@ impl PlayerTrait for GeneralPlayer {
     }
 }
 
+/// Let the auto-DJ choose `amount` tracks of the library `candidates` that are not in `playlist` yet.
+fn auto_dj_choose(
+    mut candidates: Vec<(TrackRead, PlayStats)>,
+    playlist: &Playlist,
+    amount: u32,
+    settings: &AutoDjSettings,
+) -> Vec<TrackRead> {
+    candidates.retain(|(track, _)| {
+        playlist
+            .find_index_from_file(&track.as_pathbuf().to_string_lossy())
+            .is_none()
+    });
+
+    auto_dj::choose(
+        candidates,
+        usize::try_from(amount).unwrap_or(usize::MAX),
+        settings,
+        chrono::Utc::now(),
+        &mut rand::rng(),
+    )
+}
+
 /// Some information that may be available from the backend
 /// This is different from [`Track`] as this is everything parsed from the decoder's metadata
 /// and [`Track`] stores some different extra stuff
@@ -740,7 +1494,7 @@ pub type Speed = i32;
 // yes this is currently the same as speed, but for consistentcy with VolumeSigned (and maybe other types)
 pub type SpeedSigned = Speed;
 
-pub const MIN_SPEED: Speed = 1;
+pub const MIN_SPEED: Speed = 5;
 pub const MAX_SPEED: Speed = 30;
 
 #[allow(clippy::module_name_repetitions)]
@@ -792,6 +1546,65 @@ pub trait PlayerTrait {
     fn stop(&mut self);
     fn gapless(&self) -> bool;
     fn set_gapless(&mut self, to: bool);
+    /// Get whether vocal reduction is applied.
+    ///
+    /// Backends that do not support vocal reduction always return `false`.
+    fn vocal_reduction(&self) -> bool {
+        false
+    }
+    /// Enable or disable vocal reduction, see [`vocal_reduction`](PlayerTrait::vocal_reduction).
+    fn set_vocal_reduction(&mut self, _to: bool) {}
+    /// Get whether the `ReplayGain` of tracks is applied to normalize their loudness.
+    ///
+    /// Backends that do not support normalization always return `false`.
+    fn normalization(&self) -> bool {
+        false
+    }
+    /// Enable or disable normalization, see [`normalization`](PlayerTrait::normalization).
+    fn set_normalization(&mut self, _to: bool) {}
+    /// Get whether long silences in podcast episodes are shortened.
+    ///
+    /// Backends that do not support smart speed always return `false`.
+    fn smart_speed(&self) -> bool {
+        false
+    }
+    /// Enable or disable smart speed, see [`smart_speed`](PlayerTrait::smart_speed).
+    fn set_smart_speed(&mut self, _to: bool) {}
+    /// Get the gains in dB of the equalizer bands, [`None`] if the equalizer is disabled.
+    ///
+    /// Backends that do not support a equalizer always return [`None`].
+    fn equalizer(&self) -> Option<[f32; BANDS]> {
+        None
+    }
+    /// Change the equalizer, see [`equalizer`](PlayerTrait::equalizer).
+    fn set_equalizer(&mut self, _gains: Option<[f32; BANDS]>) {}
+    /// Get whether what is playing is currently being recorded.
+    ///
+    /// Backends that do not support recording always return `false`.
+    fn recording(&self) -> bool {
+        false
+    }
+    /// Start or stop recording, see [`recording`](PlayerTrait::recording).
+    ///
+    /// # Errors
+    ///
+    /// If the recording could not be started, or the backend does not support recording.
+    fn set_recording(&mut self, to: bool) -> Result<()> {
+        if to {
+            anyhow::bail!("Recording is not supported by this backend");
+        }
+
+        Ok(())
+    }
+    /// Get the name of the output device played on, [`None`] if the backend does not support choosing one.
+    ///
+    /// The name is empty while no device could be opened.
+    fn output_device(&self) -> Option<String> {
+        None
+    }
+    /// Play on the output device named `name`, or the default device of the system if empty, see
+    /// [`output_device`](PlayerTrait::output_device).
+    fn set_output_device(&mut self, _name: String) {}
     fn skip_one(&mut self);
     /// Quickly access the position.
     ///
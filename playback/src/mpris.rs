@@ -1,10 +1,14 @@
 use std::sync::mpsc::{self, Receiver};
 
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
 use base64::Engine;
+use lofty::picture::Picture;
 use souvlaki::{MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, PlatformConfig};
 use termusiclib::{
     common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE},
-    track::Track,
+    config::v2::server::PlayerSettings,
+    podcast::artwork::ArtworkCache,
+    track::{MediaTypes, Track},
 };
 
 use crate::{
@@ -13,11 +17,22 @@ use crate::{
 
 pub struct Mpris {
     controls: MediaControls,
+    /// The D-Bus name the controls were registered with
+    dbus_name: String,
+    /// The name shown for the controls
+    identity: String,
     pub rx: Receiver<MediaControlEvent>,
+    /// The window SMTC is attached to, it needs to live as long as the controls
+    #[cfg(target_os = "windows")]
+    _dummy_window: windows::DummyWindow,
 }
 
 impl Mpris {
-    pub fn new(cmd_tx: crate::PlayerCmdSender) -> Self {
+    /// Register the controls with the names of `settings`.
+    pub fn new(cmd_tx: crate::PlayerCmdSender, settings: &PlayerSettings) -> Self {
+        let dbus_name = settings.mediacontrols_dbus_name();
+        let identity = settings.mediacontrols_display_name().to_string();
+
         // #[cfg(not(target_os = "windows"))]
         // let hwnd = None;
 
@@ -33,15 +48,16 @@ impl Mpris {
         let hwnd = None;
 
         #[cfg(target_os = "windows")]
-        let (hwnd, _dummy_window) = {
+        let (hwnd, dummy_window) = {
             let dummy_window = windows::DummyWindow::new().unwrap();
             let handle = Some(dummy_window.handle.0);
             (handle, dummy_window)
         };
 
+        info!("Registering media controls as {dbus_name:?} named {identity:?}");
         let config = PlatformConfig {
-            dbus_name: "termusic",
-            display_name: "Termusic in Rust",
+            dbus_name: &dbus_name,
+            display_name: &identity,
             hwnd,
         };
 
@@ -58,36 +74,41 @@ impl Mpris {
             })
             .ok();
 
-        Self { controls, rx }
+        Self {
+            controls,
+            dbus_name,
+            identity,
+            rx,
+            #[cfg(target_os = "windows")]
+            _dummy_window: dummy_window,
+        }
     }
 }
 
 impl Mpris {
-    pub fn add_and_play(&mut self, track: &Track) {
+    /// Get whether the controls were registered with the names of `settings`.
+    pub fn has_names_of(&self, settings: &PlayerSettings) -> bool {
+        self.dbus_name == settings.mediacontrols_dbus_name()
+            && self.identity == settings.mediacontrols_display_name()
+    }
+
+    /// Set `track` as playing, `podcast_art_url` being the artwork of the episode or its feed for podcasts.
+    pub fn add_and_play(&mut self, track: &Track, podcast_art_url: Option<&str>) {
         // This is to fix a bug that the first track is not updated
         std::thread::sleep(std::time::Duration::from_millis(100));
         self.controls
             .set_playback(MediaPlayback::Playing { progress: None })
             .ok();
 
-        let cover_art = match track.get_picture() {
-            Ok(v) => v.map(|v| {
-                format!(
-                    "data:{};base64,{}",
-                    v.mime_type().map_or_else(
-                        || {
-                            error!("Unknown mimetype for picture of track {track:#?}");
-                            "application/octet-stream"
-                        },
-                        |v| v.as_str()
-                    ),
-                    base64::engine::general_purpose::STANDARD_NO_PAD.encode(v.data())
-                )
-            }),
-            Err(err) => {
-                error!("Fetching the cover failed: {err:#?}");
-                None
-            }
+        let cover_art = match track.inner() {
+            MediaTypes::Podcast(_) => podcast_art_url.map(podcast_cover_url),
+            _ => match track.get_picture() {
+                Ok(v) => v.and_then(|v| cover_url(track, &v)),
+                Err(err) => {
+                    error!("Fetching the cover failed: {err:#?}");
+                    None
+                }
+            },
         };
 
         let album = track.as_track().and_then(|v| v.album());
@@ -270,6 +291,59 @@ impl GeneralPlayer {
     }
 }
 
+/// Get the podcast artwork at `url` as a `file://` url if it was already downloaded, or otherwise `url` itself.
+fn podcast_cover_url(url: &str) -> String {
+    ArtworkCache::new_default()
+        .ok()
+        .and_then(|v| v.cached_path(url))
+        .map_or_else(|| url.to_string(), |v| format!("file://{}", v.display()))
+}
+
+/// Get the cover as a `data:` url, which is supported by MPRIS (D-Bus).
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[allow(clippy::unnecessary_wraps)] // same signature as the windows / macos version
+fn cover_url(track: &Track, picture: &Picture) -> Option<String> {
+    Some(format!(
+        "data:{};base64,{}",
+        picture.mime_type().map_or_else(
+            || {
+                error!("Unknown mimetype for picture of track {track:#?}");
+                "application/octet-stream"
+            },
+            |v| v.as_str()
+        ),
+        base64::engine::general_purpose::STANDARD_NO_PAD.encode(picture.data())
+    ))
+}
+
+/// Get the cover as a `file://` url, as SMTC and the macOS Now-Playing info center do not support `data:` urls.
+///
+/// The cover is written to a single temporary file, which is overwritten for each track.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn cover_url(track: &Track, picture: &Picture) -> Option<String> {
+    use lofty::picture::MimeType;
+
+    let ext = match picture.mime_type() {
+        Some(MimeType::Png) => "png",
+        Some(MimeType::Jpeg) => "jpg",
+        Some(MimeType::Bmp) => "bmp",
+        Some(MimeType::Gif) => "gif",
+        Some(MimeType::Tiff) => "tiff",
+        _ => {
+            error!("Unsupported mimetype for picture of track {track:#?}");
+            return None;
+        }
+    };
+    let path = std::env::temp_dir().join(format!("termusic-mediacontrols-cover.{ext}"));
+
+    if let Err(err) = std::fs::write(&path, picture.data()) {
+        error!("Writing the cover to {} failed: {err:#?}", path.display());
+        return None;
+    }
+
+    Some(format!("file://{}", path.display()))
+}
+
 // demonstrates how to make a minimal window to allow use of media keys on the command line
 // ref: https://github.com/Sinono3/souvlaki/blob/master/examples/print_events.rs
 #[cfg(target_os = "windows")]
@@ -0,0 +1,191 @@
+//! Write a plain-text now-playing line to a file or FIFO, for external tools like status bars or overlays.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use termusiclib::common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use termusiclib::config::v2::server::now_playing::NowPlayingSettings;
+use termusiclib::player::RunningStatus;
+use termusiclib::track::{DurationFmtShort, MediaTypes, Track};
+
+/// Handle for the now-playing writer thread.
+#[derive(Debug)]
+pub struct NowPlaying {
+    tx: Sender<String>,
+    settings: NowPlayingSettings,
+    /// The last line sent, to not write duplicates.
+    last_line: String,
+}
+
+impl NowPlaying {
+    /// Start the writer thread, returns [`None`] if no file is configured.
+    pub fn new(settings: &NowPlayingSettings) -> Option<Self> {
+        let path = settings.file.clone()?;
+        let path = PathBuf::from(shellexpand::path::tilde(&path).as_ref());
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("now playing writer".into())
+            .spawn(move || thread_fn(&path, &rx))
+            .expect("failed to start now playing writer thread");
+
+        Some(Self {
+            tx,
+            settings: settings.clone(),
+            last_line: String::new(),
+        })
+    }
+
+    /// Get the settings this handle was created with.
+    pub fn settings(&self) -> &NowPlayingSettings {
+        &self.settings
+    }
+
+    /// Update the line with the current `track`, the `media_title` of a radio stream and the playback `status`.
+    pub fn update(
+        &mut self,
+        track: Option<&Track>,
+        media_title: Option<&str>,
+        status: RunningStatus,
+    ) {
+        let line = format_line(&self.settings.format, track, media_title, status);
+        if line == self.last_line {
+            return;
+        }
+
+        self.last_line.clone_from(&line);
+        self.tx.send(line).ok();
+    }
+}
+
+/// Replace all placeholders in `format`, see [`NowPlayingSettings::format`].
+fn format_line(
+    format: &str,
+    track: Option<&Track>,
+    media_title: Option<&str>,
+    status: RunningStatus,
+) -> String {
+    let status = match status {
+        RunningStatus::Running => "playing",
+        RunningStatus::Paused => "paused",
+        RunningStatus::Stopped => "stopped",
+    };
+
+    let (artist, title, album, duration) = match track {
+        None => (String::new(), String::new(), String::new(), String::new()),
+        Some(track) => {
+            let album = match track.inner() {
+                MediaTypes::Track(data) => data.album().unwrap_or_default(),
+                MediaTypes::Podcast(_) | MediaTypes::Radio(_) | MediaTypes::Video(_) => "",
+            };
+            let duration = track
+                .duration()
+                .map(|v| DurationFmtShort(v).to_string())
+                .unwrap_or_default();
+            // radio streams have the station as the track title and the current song as the media title
+            let (artist, title) = match media_title.filter(|v| !v.is_empty()) {
+                Some(media_title) if matches!(track.inner(), MediaTypes::Radio(_)) => {
+                    (track.title().unwrap_or(UNKNOWN_ARTIST), media_title)
+                }
+                // the title of a video is only known from the extracted stream
+                Some(media_title) if matches!(track.inner(), MediaTypes::Video(_)) => {
+                    (UNKNOWN_ARTIST, media_title)
+                }
+                _ => (
+                    track.artist().unwrap_or(UNKNOWN_ARTIST),
+                    track.title().unwrap_or(UNKNOWN_TITLE),
+                ),
+            };
+
+            (
+                artist.to_string(),
+                title.to_string(),
+                album.to_string(),
+                duration,
+            )
+        }
+    };
+
+    format
+        .replace("{status}", status)
+        .replace("{artist}", &artist)
+        .replace("{title}", &title)
+        .replace("{album}", &album)
+        .replace("{duration}", &duration)
+        // a single line is expected by readers
+        .replace(['\n', '\r'], " ")
+}
+
+/// Write the newest line on every change, until the handle is dropped.
+fn thread_fn(path: &Path, rx: &Receiver<String>) {
+    while let Ok(mut line) = rx.recv() {
+        // only the newest line is relevant, for example if the FIFO reader was not there for a while
+        while let Ok(newer) = rx.try_recv() {
+            line = newer;
+        }
+
+        if let Err(err) = write_line(path, &line) {
+            warn!("Error writing now playing to {}: {err:#}", path.display());
+        }
+    }
+}
+
+/// Write `line` to `path`, blocking until there is a reader if it is a FIFO.
+fn write_line(path: &Path, line: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt as _;
+
+        if fs::metadata(path).is_ok_and(|v| v.file_type().is_fifo()) {
+            let mut fifo = OpenOptions::new().write(true).open(path)?;
+            return writeln!(fifo, "{line}");
+        }
+    }
+
+    // write to a temporary file first, so that readers never see a partial file
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    fs::write(&part, format!("{line}\n"))?;
+    fs::rename(&part, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+    use termusiclib::player::RunningStatus;
+
+    use super::{format_line, write_line};
+
+    #[test]
+    fn should_format_without_track() {
+        assert_eq!(
+            format_line(
+                "[{status}] {artist} - {title}",
+                None,
+                None,
+                RunningStatus::Stopped
+            ),
+            "[stopped]  - "
+        );
+        assert_eq!(
+            format_line("{status}\n{title}", None, None, RunningStatus::Paused),
+            "paused "
+        );
+    }
+
+    #[test]
+    fn should_write_file() {
+        let path =
+            std::env::temp_dir().join(format!("termusic-now-playing-{}", std::process::id()));
+
+        write_line(&path, "first").unwrap();
+        write_line(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+
+        let _ = fs::remove_file(&path);
+    }
+}
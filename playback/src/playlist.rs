@@ -1,9 +1,12 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::{Display, Write as _};
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use parking_lot::RwLock;
@@ -15,6 +18,7 @@ use termusiclib::config::v2::server::LoopMode;
 use termusiclib::player::PlaylistLoopModeInfo;
 use termusiclib::player::PlaylistShuffledInfo;
 use termusiclib::player::PlaylistSwapInfo;
+use termusiclib::player::PlaylistTrackErrorInfo;
 use termusiclib::player::PlaylistTracks;
 use termusiclib::player::UpdateEvents;
 use termusiclib::player::UpdatePlaylistEvents;
@@ -27,6 +31,7 @@ use termusiclib::player::{PlaylistAddTrackInfo, PlaylistRemoveTrackInfo};
 use termusiclib::podcast::{db::Database as DBPod, episode::Episode};
 use termusiclib::track::{MediaTypes, Track, TrackData};
 use termusiclib::utils::{filetype_supported, get_app_config_path, get_parent_folder};
+use termusiclib::ytdlp;
 
 use crate::SharedPlaylist;
 use crate::StreamTX;
@@ -49,6 +54,8 @@ pub struct Playlist {
     loop_mode: LoopMode,
     /// Indexes into `tracks` that have been previously been played (for `previous`)
     played_index: Vec<usize>,
+    /// Paths of the album tracks still to play in order, before returning to the [`LoopMode`]
+    album_queue: VecDeque<String>,
     /// Indicator if the playlist should advance the `current_*` and `next_*` values
     need_proceed_to_next: bool,
     stream_tx: StreamTX,
@@ -71,6 +78,7 @@ impl Playlist {
             current_track_index: 0,
             current_track,
             played_index: Vec::new(),
+            album_queue: VecDeque::new(),
             next_track_index: None,
             need_proceed_to_next: false,
             stream_tx,
@@ -172,7 +180,11 @@ impl Playlist {
                     }
                 }
                 if !is_podcast {
-                    let track = Track::new_radio(&line);
+                    let track = if ytdlp::is_video_url(&line) {
+                        Track::new_video(line)
+                    } else {
+                        Track::new_radio(&line)
+                    };
                     playlist_items.push(track);
                 }
                 continue;
@@ -230,14 +242,29 @@ impl Playlist {
                 bail!("Track does not have a id, which is required to load!");
             };
 
-            let track = match PlaylistTrackSource::try_from(id)? {
+            let error = track.optional_error.map(|v| {
+                let player::playlist_add_track::OptionalError::Error(v) = v;
+                v
+            });
+
+            let title = track.optional_title.map(|v| {
+                let player::playlist_add_track::OptionalTitle::Title(v) = v;
+                v
+            });
+            let duration = track.duration.map(Duration::from).filter(|v| !v.is_zero());
+
+            let mut track = match PlaylistTrackSource::try_from(id)? {
                 PlaylistTrackSource::Path(v) => Track::read_track_from_path(v)?,
                 PlaylistTrackSource::Url(v) => Track::new_radio(&v),
                 PlaylistTrackSource::PodcastUrl(v) => {
                     let episode = podcast_db.get_episode_by_url(&v)?;
                     Track::from_podcast_episode(&episode)
                 }
+                PlaylistTrackSource::VideoUrl(v) => Track::new_video(v),
             };
+            track.set_error(error);
+            // the server may know the info of a video that cannot be fetched here
+            track.fill_info(title, duration);
 
             playlist_items.push(track);
         }
@@ -288,12 +315,7 @@ impl Playlist {
         writer.write_all(self.current_track_index.to_string().as_bytes())?;
         writer.write_all(b"\n")?;
         for track in &self.tracks {
-            let id = match track.inner() {
-                MediaTypes::Track(track_data) => track_data.path().to_string_lossy(),
-                MediaTypes::Radio(radio_track_data) => radio_track_data.url().into(),
-                MediaTypes::Podcast(podcast_track_data) => podcast_track_data.url().into(),
-            };
-            writeln!(writer, "{id}")?;
+            writeln!(writer, "{}", track.source().location())?;
         }
 
         writer.flush()?;
@@ -328,9 +350,42 @@ impl Playlist {
         // then "has_next_track" is later used to check if enqueuing has used.
         if let Some(index) = self.next_track_index {
             self.current_track_index = index;
+        } else {
+            self.current_track_index = self.get_next_track_index();
+        }
+        self.advance_album_queue();
+    }
+
+    /// Set the album tracks to play in order after the current track, see [`album_queue`](Self::album_queue).
+    pub fn set_album_queue(&mut self, paths: Vec<String>) {
+        self.album_queue = paths.into();
+        // the already enqueued track may not be the next album track
+        self.set_next_track(None);
+    }
+
+    /// Stop playing the album queue, returning to the [`LoopMode`] after the current track.
+    pub fn clear_album_queue(&mut self) {
+        if !self.album_queue.is_empty() {
+            self.album_queue.clear();
+            self.set_next_track(None);
+        }
+    }
+
+    /// Get the album tracks still to play in order.
+    #[must_use]
+    pub fn album_queue(&self) -> &VecDeque<String> {
+        &self.album_queue
+    }
+
+    /// Remove all queued album tracks up to and including the current track, if it was queued.
+    fn advance_album_queue(&mut self) {
+        let Some(current) = self.tracks.get(self.current_track_index) else {
             return;
+        };
+        let current = Self::track_location(current);
+        if let Some(pos) = self.album_queue.iter().position(|v| *v == current) {
+            self.album_queue.drain(..=pos);
         }
-        self.current_track_index = self.get_next_track_index();
     }
 
     /// Check that the given `info` track source matches the given `track_inner` types.
@@ -369,6 +424,14 @@ impl Playlist {
                     );
                 }
             }
+            (PlaylistTrackSource::VideoUrl(file_url), MediaTypes::Video(id)) => {
+                if file_url != id.url() {
+                    bail!(
+                        "URI mismatch, expected \"{file_url}\" at \"{at_index}\", found \"{}\"",
+                        id.url()
+                    );
+                }
+            }
             (expected, got) => {
                 bail!(
                     "Type mismatch, expected \"{expected:#?}\" at \"{at_index}\" found \"{got:#?}\""
@@ -396,6 +459,7 @@ impl Playlist {
         Self::check_same_source(&info.id, track_at_idx.inner(), new_index)?;
 
         self.played_index.push(self.current_track_index);
+        self.album_queue.clear();
         self.set_next_track(None);
         self.set_current_track_index(new_index);
         self.proceed_false();
@@ -405,7 +469,17 @@ impl Playlist {
     }
 
     /// Get the next track index based on the [`LoopMode`] used.
+    ///
+    /// Queued album tracks that are still in the playlist take precedence.
     fn get_next_track_index(&self) -> usize {
+        if let Some(index) = self
+            .album_queue
+            .iter()
+            .find_map(|v| self.find_index_from_file(v))
+        {
+            return index;
+        }
+
         let mut next_track_index = self.current_track_index;
         match self.loop_mode {
             LoopMode::Single => {}
@@ -523,21 +597,8 @@ impl Playlist {
     // TODO: refactor this function to likely return either a consistent URI format or a enum
     // TODO: refactor to return a reference if possible
     pub fn get_current_track(&mut self) -> Option<String> {
-        let mut result = None;
-        if let Some(track) = self.current_track() {
-            match track.inner() {
-                MediaTypes::Track(track_data) => {
-                    result = Some(track_data.path().to_string_lossy().to_string());
-                }
-                MediaTypes::Radio(radio_track_data) => {
-                    result = Some(radio_track_data.url().to_string());
-                }
-                MediaTypes::Podcast(podcast_track_data) => {
-                    result = Some(podcast_track_data.url().to_string());
-                }
-            }
-        }
-        result
+        self.current_track()
+            .map(|track| track.source().location().into_owned())
     }
 
     /// Get the next track index and return a reference to it.
@@ -628,17 +689,12 @@ impl Playlist {
     fn get_m3u_file(&self, parent_folder: &Path) -> String {
         let mut m3u = String::from("#EXTM3U\n");
         for track in &self.tracks {
-            let file = match track.inner() {
-                MediaTypes::Track(track_data) => {
-                    let path_relative = diff_paths(track_data.path(), parent_folder);
-
-                    path_relative.map_or_else(
-                        || track_data.path().to_string_lossy(),
-                        |v| v.to_string_lossy().to_string().into(),
-                    )
-                }
-                MediaTypes::Radio(radio_track_data) => radio_track_data.url().into(),
-                MediaTypes::Podcast(podcast_track_data) => podcast_track_data.url().into(),
+            let file = match track.as_track() {
+                Some(track_data) => diff_paths(track_data.path(), parent_folder).map_or_else(
+                    || track.source().location(),
+                    |v| v.to_string_lossy().to_string().into(),
+                ),
+                None => track.source().location(),
             };
 
             let _ = writeln!(m3u, "{file}");
@@ -733,6 +789,7 @@ impl Playlist {
             PlaylistTrackSource::Path(path) => Self::track_from_path(path)?,
             PlaylistTrackSource::Url(uri) => Self::track_from_uri(uri),
             PlaylistTrackSource::PodcastUrl(uri) => Self::track_from_podcasturi(uri, db_pod)?,
+            PlaylistTrackSource::VideoUrl(uri) => Track::new_video(uri.as_str()),
         };
 
         Ok(track)
@@ -765,11 +822,14 @@ impl Playlist {
         );
 
         let mut added_tracks = 0;
+        // empty if the client does not know more than the server
+        let mut infos = tracks.infos.into_iter();
 
         if at_index >= self.len() {
             // insert tracks at the end
             for track_location in tracks.tracks {
-                let track = match Self::source_to_track(&track_location, db_pod) {
+                let info = infos.next();
+                let mut track = match Self::source_to_track(&track_location, db_pod) {
                     Ok(v) => v,
                     Err(err) => {
                         warn!("Error adding track: {err}");
@@ -777,6 +837,9 @@ impl Playlist {
                         continue;
                     }
                 };
+                if let Some(info) = info {
+                    track.fill_info(info.title, info.duration);
+                }
 
                 self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistAddTrack(
                     PlaylistAddTrackInfo {
@@ -795,7 +858,8 @@ impl Playlist {
             let mut at_index = at_index;
             // insert tracks at position
             for track_location in tracks.tracks {
-                let track = match Self::source_to_track(&track_location, db_pod) {
+                let info = infos.next();
+                let mut track = match Self::source_to_track(&track_location, db_pod) {
                     Ok(v) => v,
                     Err(err) => {
                         warn!("Error adding track: {err}");
@@ -803,6 +867,9 @@ impl Playlist {
                         continue;
                     }
                 };
+                if let Some(info) = info {
+                    track.fill_info(info.title, info.duration);
+                }
 
                 self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistAddTrack(
                     PlaylistAddTrackInfo {
@@ -814,6 +881,10 @@ impl Playlist {
                 ));
 
                 self.tracks.insert(at_index, track);
+                // keep pointing at the same track
+                if at_index <= self.current_track_index {
+                    self.current_track_index += 1;
+                }
                 self.is_modified = true;
                 at_index += 1;
                 added_tracks += 1;
@@ -982,6 +1053,7 @@ impl Playlist {
     pub fn clear(&mut self) {
         self.tracks.clear();
         self.played_index.clear();
+        self.album_queue.clear();
         self.next_track_index.take();
         self.current_track_index = 0;
         self.need_proceed_to_next = false;
@@ -1032,6 +1104,9 @@ impl Playlist {
                     duration: Some(track.duration().unwrap_or_default().into()),
                     id: Some(track_source.into()),
                     optional_title: None,
+                    optional_error: track
+                        .error()
+                        .map(|v| player::playlist_add_track::OptionalError::Error(v.to_string())),
                 })
             })
             .collect::<Result<_>>()?;
@@ -1044,18 +1119,15 @@ impl Playlist {
     }
 
     /// Find the index in the playlist for `item`, if it exists there.
-    fn find_index_from_file(&self, item: &str) -> Option<usize> {
-        for (index, track) in self.tracks.iter().enumerate() {
-            let file = match track.inner() {
-                MediaTypes::Track(track_data) => track_data.path().to_string_lossy(),
-                MediaTypes::Radio(radio_track_data) => radio_track_data.url().into(),
-                MediaTypes::Podcast(podcast_track_data) => podcast_track_data.url().into(),
-            };
-            if file == item {
-                return Some(index);
-            }
-        }
-        None
+    pub(crate) fn find_index_from_file(&self, item: &str) -> Option<usize> {
+        self.tracks
+            .iter()
+            .position(|track| Self::track_location(track) == item)
+    }
+
+    /// Get the Path/Url of `track`, as used by [`find_index_from_file`](Self::find_index_from_file).
+    fn track_location(track: &Track) -> Cow<'_, str> {
+        track.source().location()
     }
 
     /// Get a random index in the playlist.
@@ -1139,6 +1211,28 @@ impl Playlist {
         self.tracks.get_mut(self.current_track_index)
     }
 
+    /// Mark the track at `index` as failed to play with the reason, or clear it with `None`.
+    ///
+    /// Only sends a event if the error actually changed.
+    pub fn set_track_error(&mut self, index: usize, error: Option<String>) {
+        let (Some(track), Ok(at_index)) = (self.tracks.get_mut(index), u64::try_from(index)) else {
+            return;
+        };
+        if track.error() == error.as_deref() {
+            return;
+        }
+        track.set_error(error.clone());
+        let trackid = track.as_track_source();
+
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistTrackError(
+            PlaylistTrackErrorInfo {
+                at_index,
+                trackid,
+                error,
+            },
+        ));
+    }
+
     pub fn clear_current_track(&mut self) {
         self.current_track = None;
     }
@@ -1162,6 +1256,11 @@ impl Playlist {
         self.next_track_index = track_idx;
     }
 
+    #[must_use]
+    pub fn next_track_index(&self) -> Option<usize> {
+        self.next_track_index
+    }
+
     #[must_use]
     pub fn has_next_track(&self) -> bool {
         self.next_track_index.is_some()
@@ -1310,12 +1409,69 @@ mod tests {
     use std::path::PathBuf;
 
     use termusiclib::{
-        player::playlist_helpers::PlaylistTrackSource,
-        track::{MediaTypes, PodcastTrackData, RadioTrackData, TrackData},
+        config::{ServerOverlay, new_shared_server_settings, v2::server::LoopMode},
+        player::{
+            PlaylistTrackErrorInfo, UpdateEvents, UpdatePlaylistEvents,
+            playlist_add_track::OptionalError,
+            playlist_helpers::{PlaylistAddTrack, PlaylistTrackSource},
+        },
+        podcast::db::Database as DBPod,
+        track::{MediaTypes, PodcastTrackData, RadioTrackData, Track, TrackData},
     };
+    use tokio::sync::broadcast;
 
     use super::Playlist;
 
+    /// Create a playlist with a radio track for each `url`, in the given `loop_mode`.
+    fn playlist_with(urls: &[&str], loop_mode: LoopMode) -> Playlist {
+        let config = new_shared_server_settings(ServerOverlay::default());
+        let (tx, _) = broadcast::channel(10);
+        let mut playlist = Playlist::new(&config, tx);
+        playlist.loop_mode = loop_mode;
+        playlist.tracks = urls.iter().map(|v| Track::new_radio(*v)).collect();
+
+        playlist
+    }
+
+    /// Get the location of the current track index.
+    fn current_location(playlist: &Playlist) -> String {
+        Playlist::track_location(&playlist.tracks[playlist.get_current_track_index()]).into_owned()
+    }
+
+    #[test]
+    fn should_keep_track_error_in_grpc_tracks() {
+        let mut playlist = playlist_with(&["http://a/", "http://b/"], LoopMode::Playlist);
+        let mut rx = playlist.stream_tx.subscribe();
+
+        playlist.set_track_error(1, Some("not found".to_string()));
+        // setting the same error again does not send another event
+        playlist.set_track_error(1, Some("not found".to_string()));
+        // out-of-bounds indexes are ignored
+        playlist.set_track_error(5, Some("not found".to_string()));
+
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            UpdateEvents::PlaylistChanged(UpdatePlaylistEvents::PlaylistTrackError(
+                PlaylistTrackErrorInfo {
+                    at_index: 1,
+                    trackid: PlaylistTrackSource::Url("http://b/".to_string()),
+                    error: Some("not found".to_string()),
+                }
+            ))
+        );
+        assert!(rx.try_recv().is_err());
+
+        let tracks = playlist.as_grpc_playlist_tracks().unwrap();
+        assert_eq!(tracks.tracks[0].optional_error, None);
+        assert_eq!(
+            tracks.tracks[1].optional_error,
+            Some(OptionalError::Error("not found".to_string()))
+        );
+
+        playlist.set_track_error(1, None);
+        assert_eq!(playlist.tracks[1].error(), None);
+    }
+
     #[test]
     fn should_pass_check_info() {
         let path = "/somewhere/file.mp3".to_string();
@@ -1357,4 +1513,67 @@ mod tests {
         )
         .unwrap_err();
     }
+
+    #[test]
+    fn should_play_album_queue_before_loop_mode() {
+        let mut playlist = playlist_with(&["a", "b", "c", "d"], LoopMode::Single);
+        playlist.set_album_queue(vec!["c".to_string(), "d".to_string()]);
+
+        playlist.next();
+        assert_eq!(current_location(&playlist), "c");
+        playlist.next();
+        assert_eq!(current_location(&playlist), "d");
+        assert!(playlist.album_queue().is_empty());
+
+        // back to the loop mode
+        playlist.next();
+        assert_eq!(current_location(&playlist), "d");
+    }
+
+    #[test]
+    fn should_skip_album_tracks_not_in_playlist() {
+        let mut playlist = playlist_with(&["a", "b", "c"], LoopMode::Playlist);
+        playlist.set_album_queue(vec!["removed".to_string(), "c".to_string()]);
+
+        playlist.next();
+        assert_eq!(current_location(&playlist), "c");
+        assert!(playlist.album_queue().is_empty());
+    }
+
+    #[test]
+    fn should_clear_album_queue() {
+        let mut playlist = playlist_with(&["a", "b", "c"], LoopMode::Playlist);
+        playlist.set_album_queue(vec!["c".to_string()]);
+        playlist.clear_album_queue();
+
+        playlist.next();
+        assert_eq!(current_location(&playlist), "b");
+    }
+
+    #[test]
+    fn should_keep_current_track_when_inserting_before() {
+        let dir = std::env::temp_dir().join(format!("termusic-playlist-{}", std::process::id()));
+        let db_pod = DBPod::new(&dir).unwrap();
+        let mut playlist = playlist_with(&["http://a/", "http://b/"], LoopMode::Playlist);
+        playlist.current_track_index = 1;
+
+        let tracks = vec![
+            PlaylistTrackSource::Url("http://c/".to_string()),
+            PlaylistTrackSource::Url("http://d/".to_string()),
+        ];
+        playlist
+            .add_tracks(PlaylistAddTrack::new_vec(0, tracks), &db_pod)
+            .unwrap();
+        assert_eq!(playlist.get_current_track_index(), 3);
+        assert_eq!(current_location(&playlist), "http://b/");
+
+        // inserting after the current track does not move it
+        let track = PlaylistTrackSource::Url("http://e/".to_string());
+        playlist
+            .add_tracks(PlaylistAddTrack::new_single(4, track), &db_pod)
+            .unwrap();
+        assert_eq!(current_location(&playlist), "http://b/");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,127 @@
+//! Continue after a restart where playback was, see `player.resume_playback`.
+
+use std::time::{Duration, Instant};
+
+use termusiclib::new_database::Database;
+use termusiclib::new_database::resume_ops::{self, ResumePoint};
+use termusiclib::track::Track;
+
+use crate::{GeneralPlayer, PlayerTrait};
+
+/// How often the current position is saved while playing, which is how much is lost if the server is killed.
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The state of resuming, see the module documentation.
+#[derive(Debug, Default)]
+pub struct Resume {
+    /// The point loaded at the start, until the first track was started.
+    pending: Option<ResumePoint>,
+    /// The point last stored, to not write the same again.
+    saved: Option<ResumePoint>,
+    /// When the point was last stored.
+    saved_at: Option<Instant>,
+}
+
+impl Resume {
+    /// Load the stored point to resume from, if `enabled`.
+    pub fn load(db: &Database, enabled: bool) -> Self {
+        if !enabled {
+            return Self::default();
+        }
+
+        let pending = resume_ops::get_resume_point(&db.get_connection())
+            .inspect_err(|err| warn!("Error loading the resume point: {err:#}"))
+            .ok()
+            .flatten();
+
+        Self {
+            saved: pending.clone(),
+            pending,
+            saved_at: None,
+        }
+    }
+}
+
+impl GeneralPlayer {
+    /// Store the current track and its position to resume from after a restart, if enabled.
+    ///
+    /// Unless `force`d, this only stores every [`SAVE_INTERVAL`] and in the background. The playlist is saved too if
+    /// modified, so that its current track matches the stored one.
+    ///
+    /// Requires that the function is called on a thread with a entered tokio runtime
+    pub fn player_save_resume_point(&mut self, force: bool) {
+        if !self.config.read().settings.player.resume_playback {
+            return;
+        }
+        if !force
+            && self
+                .resume
+                .saved_at
+                .is_some_and(|v| v.elapsed() < SAVE_INTERVAL)
+        {
+            return;
+        }
+        self.resume.saved_at = Some(Instant::now());
+
+        let point = self
+            .playlist
+            .read()
+            .current_track()
+            .map(|track| ResumePoint {
+                track: track.source().location().into_owned(),
+                // live streams have no position to resume at
+                position: if track.source().is_live() {
+                    Duration::ZERO
+                } else {
+                    self.position().unwrap_or_default()
+                },
+            });
+        // a failed write is tried again with the next change of the point
+        let changed = point != self.resume.saved;
+        self.resume.saved.clone_from(&point);
+
+        let playlist = self.playlist.clone();
+        let db = self.db.clone();
+        let save = move || {
+            if let Err(err) = playlist.write().save_if_modified() {
+                warn!("Error saving the playlist for resuming: {err:#}");
+            }
+            if !changed {
+                return;
+            }
+            let conn = db.get_connection();
+            let res = match &point {
+                Some(point) => resume_ops::set_resume_point(&conn, point),
+                None => resume_ops::clear_resume_point(&conn),
+            };
+            if let Err(err) = res {
+                warn!("Error saving the resume point: {err:#}");
+            }
+        };
+
+        if force {
+            // when quitting, which must not happen before it is stored
+            save();
+        } else {
+            tokio::task::spawn_blocking(save);
+        }
+    }
+
+    /// Seek to the position loaded at the start if `track` is the one it was stored for, only for the first track
+    /// started.
+    ///
+    /// Returns whether a position was restored.
+    pub fn player_restore_resume_point(&mut self, track: &Track) -> bool {
+        let Some(point) = self.resume.pending.take() else {
+            return false;
+        };
+        if point.track != track.source().location() || point.position.is_zero() {
+            return false;
+        }
+
+        info!("Resuming {} at {:?}", point.track, point.position);
+        self.seek_to(point.position);
+
+        true
+    }
+}
@@ -0,0 +1,218 @@
+//! Show a media notification with playback controls inside Termux, via `termux-notification` of the `termux-api` package.
+
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use termusiclib::common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use termusiclib::player::RunningStatus;
+use termusiclib::track::{MediaTypes, Track};
+
+/// The notification id, to replace the notification on every update instead of adding new ones.
+const NOTIFICATION_ID: &str = "termusic";
+
+/// Handle for the notification thread, the notification is removed once this is dropped.
+#[derive(Debug)]
+pub struct TermuxNotification {
+    tx: Sender<Option<Content>>,
+    /// The last content sent, to not call `termux-notification` for duplicates.
+    last: Option<Content>,
+}
+
+/// What is shown in the notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Content {
+    title: String,
+    artist: String,
+    playing: bool,
+}
+
+impl TermuxNotification {
+    /// Start the notification thread.
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("termux notification".into())
+            .spawn(move || thread_fn(&rx))
+            .expect("failed to start termux notification thread");
+
+        Self { tx, last: None }
+    }
+
+    /// Update the notification with the current `track`, the `media_title` of a radio stream and the playback `status`.
+    ///
+    /// The notification is removed while stopped.
+    pub fn update(
+        &mut self,
+        track: Option<&Track>,
+        media_title: Option<&str>,
+        status: RunningStatus,
+    ) {
+        let content = track
+            .filter(|_| status != RunningStatus::Stopped)
+            .map(|track| {
+                // radio streams have the station as the track title and the current song as the media title
+                let (artist, title) = match media_title.filter(|v| !v.is_empty()) {
+                    Some(media_title) if matches!(track.inner(), MediaTypes::Radio(_)) => {
+                        (track.title().unwrap_or(UNKNOWN_ARTIST), media_title)
+                    }
+                    // the title of a video is only known from the extracted stream
+                    Some(media_title) if matches!(track.inner(), MediaTypes::Video(_)) => {
+                        (UNKNOWN_ARTIST, media_title)
+                    }
+                    _ => (
+                        track.artist().unwrap_or(UNKNOWN_ARTIST),
+                        track.title().unwrap_or(UNKNOWN_TITLE),
+                    ),
+                };
+
+                Content {
+                    title: title.to_string(),
+                    artist: artist.to_string(),
+                    playing: status == RunningStatus::Running,
+                }
+            });
+        if content == self.last {
+            return;
+        }
+
+        self.last.clone_from(&content);
+        self.tx.send(content).ok();
+    }
+}
+
+/// Show the newest content on every change, until the handle is dropped.
+fn thread_fn(rx: &Receiver<Option<Content>>) {
+    // the buttons call this binary again, to send the command to the running server
+    let ctl = match std::env::current_exe() {
+        Ok(v) => format!("{} ctl", shell_quote(&v.to_string_lossy())),
+        Err(err) => {
+            warn!(
+                "Could not get the current executable, the notification buttons will not work: {err}"
+            );
+            "termusic-server ctl".to_string()
+        }
+    };
+
+    while let Ok(mut content) = rx.recv() {
+        // only the newest content is relevant, as every call takes a while
+        while let Ok(newer) = rx.try_recv() {
+            content = newer;
+        }
+
+        let res = match content {
+            Some(content) => Command::new("termux-notification")
+                .args(notification_args(&content, &ctl))
+                .status(),
+            None => remove_notification(),
+        };
+
+        match res {
+            Ok(status) if !status.success() => {
+                warn!("termux-notification exited with {status}");
+            }
+            Ok(_) => (),
+            Err(err) => {
+                error!(
+                    "Could not run termux-notification, is the \"termux-api\" package installed? Disabling the notification: {err}"
+                );
+                return;
+            }
+        }
+    }
+
+    let _ = remove_notification();
+}
+
+fn remove_notification() -> std::io::Result<std::process::ExitStatus> {
+    Command::new("termux-notification-remove")
+        .arg(NOTIFICATION_ID)
+        .status()
+}
+
+/// Get the `termux-notification` arguments to show `content`, with the buttons calling `ctl`.
+fn notification_args(content: &Content, ctl: &str) -> Vec<String> {
+    let toggle = format!("{ctl} toggle-pause");
+    let mut args = vec![
+        "--id".to_string(),
+        NOTIFICATION_ID.to_string(),
+        "--type".to_string(),
+        "media".to_string(),
+        "--alert-once".to_string(),
+        "--title".to_string(),
+        content.title.clone(),
+        "--content".to_string(),
+        content.artist.clone(),
+        "--media-play".to_string(),
+        toggle.clone(),
+        "--media-pause".to_string(),
+        toggle,
+        "--media-next".to_string(),
+        format!("{ctl} next"),
+        "--media-previous".to_string(),
+        format!("{ctl} previous"),
+    ];
+    // only allow swiping the notification away while paused
+    if content.playing {
+        args.push("--ongoing".to_string());
+    }
+
+    args
+}
+
+/// Quote `value` for use in a `sh` command line, which the notification actions are run with.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Content, notification_args, shell_quote};
+
+    #[test]
+    fn should_quote_for_shell() {
+        assert_eq!(
+            shell_quote("/data/it's here/termusic-server"),
+            r"'/data/it'\''s here/termusic-server'"
+        );
+    }
+
+    #[test]
+    fn should_build_notification_args() {
+        let mut content = Content {
+            title: "Title".to_string(),
+            artist: "Artist".to_string(),
+            playing: false,
+        };
+
+        let args = notification_args(&content, "'server' ctl");
+        assert_eq!(
+            args,
+            [
+                "--id",
+                "termusic",
+                "--type",
+                "media",
+                "--alert-once",
+                "--title",
+                "Title",
+                "--content",
+                "Artist",
+                "--media-play",
+                "'server' ctl toggle-pause",
+                "--media-pause",
+                "'server' ctl toggle-pause",
+                "--media-next",
+                "'server' ctl next",
+                "--media-previous",
+                "'server' ctl previous"
+            ]
+        );
+
+        content.playing = true;
+        let args = notification_args(&content, "'server' ctl");
+        assert_eq!(args.last().map(String::as_str), Some("--ongoing"));
+    }
+}
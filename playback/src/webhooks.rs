@@ -0,0 +1,355 @@
+//! Send HTTP webhooks with a JSON payload on track changes, pauses and stops, like for home automation or logging
+//! services.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use ring::hmac;
+use serde::Serialize;
+use termusiclib::common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use termusiclib::config::v2::server::ServerSettings;
+use termusiclib::config::v2::server::webhooks::{Webhook, WebhookEvent, WebhookSettings};
+use termusiclib::player::RunningStatus;
+use termusiclib::player::playlist_helpers::PlaylistTrackSource;
+use termusiclib::track::{MediaTypes, Track};
+use tokio::runtime::Handle;
+
+/// The header the [`signature`] is sent in.
+const SIGNATURE_HEADER: &str = "X-Termusic-Signature";
+/// The header the event name is sent in, to route requests without parsing the body.
+const EVENT_HEADER: &str = "X-Termusic-Event";
+
+/// The timeout for a single request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What is currently playing, to detect which [`WebhookEvent`] happened.
+#[derive(Debug, Clone, PartialEq)]
+struct PlaybackState {
+    track: Option<(PlaylistTrackSource, Option<String>)>,
+    status: RunningStatus,
+}
+
+/// Handle for sending the configured webhooks.
+#[derive(Debug)]
+pub struct Webhooks {
+    settings: WebhookSettings,
+    client: reqwest::Client,
+    handle: Handle,
+    /// The cover URL of the stream overlay, if enabled.
+    art_url: Option<String>,
+    last: Option<PlaybackState>,
+}
+
+impl Webhooks {
+    /// Create the handle, returns [`None`] if no webhooks are configured.
+    ///
+    /// Requires that the function is called on a thread with a entered tokio runtime.
+    pub fn new(settings: &ServerSettings) -> Option<Self> {
+        if settings.webhooks.hooks.is_empty() {
+            return None;
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .inspect_err(|err| warn!("Error creating the webhook client: {err}"))
+            .ok()?;
+        let art_url = settings.stream_overlay.enable.then(|| {
+            format!(
+                "http://{}/cover",
+                SocketAddr::from(&settings.stream_overlay)
+            )
+        });
+
+        Some(Self {
+            settings: settings.webhooks.clone(),
+            client,
+            handle: Handle::current(),
+            art_url,
+            last: None,
+        })
+    }
+
+    /// Get the settings this handle was created with.
+    pub fn settings(&self) -> &WebhookSettings {
+        &self.settings
+    }
+
+    /// Send the webhooks for the current `track`, the `media_title` of a radio stream and the playback `status`, if
+    /// any of them changed.
+    pub fn update(
+        &mut self,
+        track: Option<&Track>,
+        media_title: Option<&str>,
+        status: RunningStatus,
+    ) {
+        let state = PlaybackState {
+            track: track.map(|v| {
+                (
+                    v.as_track_source(),
+                    media_title.filter(|v| !v.is_empty()).map(str::to_string),
+                )
+            }),
+            status,
+        };
+        let event = event_for(self.last.as_ref(), &state);
+        self.last = Some(state);
+        let Some(event) = event else {
+            return;
+        };
+
+        let payload = WebhookPayload::new(event, track, media_title, status, self.art_url.clone());
+        let body = match serde_json::to_vec(&payload) {
+            Ok(v) => v,
+            Err(err) => {
+                warn!("Error serializing the webhook payload: {err}");
+                return;
+            }
+        };
+
+        for hook in self
+            .settings
+            .hooks
+            .iter()
+            .filter(|v| !v.url.is_empty() && v.events.contains(&event))
+        {
+            let client = self.client.clone();
+            let hook = hook.clone();
+            let body = body.clone();
+            let retries = self.settings.retries;
+            self.handle.spawn(async move {
+                if let Err(err) = deliver(&client, &hook, event, body, retries).await {
+                    warn!("Error sending webhook to \"{}\": {err:#}", hook.url);
+                }
+            });
+        }
+    }
+}
+
+/// Get the event that happened between `last` and `new`, if any.
+fn event_for(last: Option<&PlaybackState>, new: &PlaybackState) -> Option<WebhookEvent> {
+    if new.track.is_some() && last.is_none_or(|v| v.track != new.track) {
+        return Some(WebhookEvent::TrackChange);
+    }
+
+    // the first state is only reported if something is playing
+    if last?.status == new.status {
+        return None;
+    }
+
+    match new.status {
+        RunningStatus::Running => Some(WebhookEvent::Play),
+        RunningStatus::Paused => Some(WebhookEvent::Pause),
+        RunningStatus::Stopped => Some(WebhookEvent::Stop),
+    }
+}
+
+/// Send `body` to `hook`, retrying up to `retries` times with a increasing delay.
+async fn deliver(
+    client: &reqwest::Client,
+    hook: &Webhook,
+    event: WebhookEvent,
+    body: Vec<u8>,
+    retries: u8,
+) -> Result<()> {
+    let signature = hook
+        .secret
+        .as_ref()
+        .map(|secret| format!("sha256={}", signature(secret, &body)));
+
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event_name(event))
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let res = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => Err(anyhow!("status {}", response.status())),
+            Err(err) => Err(err.into()),
+        };
+
+        if attempt >= retries {
+            return res;
+        }
+        if let Err(err) = res {
+            debug!("Webhook to \"{}\" failed, retrying: {err:#}", hook.url);
+        }
+        tokio::time::sleep(Duration::from_secs(1 << attempt.min(6))).await;
+        attempt += 1;
+    }
+}
+
+/// Get the hex-encoded HMAC-SHA256 of `body` with `secret`.
+fn signature(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hex::encode(hmac::sign(&key, body).as_ref())
+}
+
+/// Get the name of `event`, like in the config.
+fn event_name(event: WebhookEvent) -> &'static str {
+    match event {
+        WebhookEvent::TrackChange => "track_change",
+        WebhookEvent::Play => "play",
+        WebhookEvent::Pause => "pause",
+        WebhookEvent::Stop => "stop",
+    }
+}
+
+/// The JSON body of a webhook request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct WebhookPayload {
+    event: &'static str,
+    status: &'static str,
+    /// The time of the event, in RFC 3339
+    timestamp: String,
+    track: Option<WebhookTrack>,
+}
+
+/// The metadata of the track in a [`WebhookPayload`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct WebhookTrack {
+    /// One of `track`, `radio`, `podcast` or `video`
+    kind: &'static str,
+    /// The path of a track, or the url of a radio stream, podcast episode or video page
+    location: String,
+    artist: String,
+    title: String,
+    album: Option<String>,
+    duration_secs: Option<u64>,
+    art_url: Option<String>,
+}
+
+impl WebhookPayload {
+    fn new(
+        event: WebhookEvent,
+        track: Option<&Track>,
+        media_title: Option<&str>,
+        status: RunningStatus,
+        overlay_art_url: Option<String>,
+    ) -> Self {
+        let status = match status {
+            RunningStatus::Running => "playing",
+            RunningStatus::Paused => "paused",
+            RunningStatus::Stopped => "stopped",
+        };
+
+        Self {
+            event: event_name(event),
+            status,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            track: track.map(|v| WebhookTrack::new(v, media_title, overlay_art_url)),
+        }
+    }
+}
+
+impl WebhookTrack {
+    fn new(track: &Track, media_title: Option<&str>, overlay_art_url: Option<String>) -> Self {
+        let (kind, location, album, art_url) = match track.inner() {
+            MediaTypes::Track(data) => (
+                "track",
+                data.path().to_string_lossy().to_string(),
+                data.album().map(str::to_string),
+                overlay_art_url,
+            ),
+            MediaTypes::Radio(data) => ("radio", data.url().to_string(), None, None),
+            MediaTypes::Video(data) => ("video", data.url().to_string(), None, None),
+            MediaTypes::Podcast(data) => (
+                "podcast",
+                data.url().to_string(),
+                None,
+                data.image_url().map(str::to_string),
+            ),
+        };
+        // radio streams have the station as the track title and the current song as the media title
+        let (artist, title) = match media_title.filter(|v| !v.is_empty()) {
+            Some(media_title) if kind == "radio" => {
+                (track.title().unwrap_or(UNKNOWN_ARTIST), media_title)
+            }
+            // the title of a video is only known from the extracted stream
+            Some(media_title) if kind == "video" => (UNKNOWN_ARTIST, media_title),
+            _ => (
+                track.artist().unwrap_or(UNKNOWN_ARTIST),
+                track.title().unwrap_or(UNKNOWN_TITLE),
+            ),
+        };
+
+        Self {
+            kind,
+            location,
+            artist: artist.to_string(),
+            title: title.to_string(),
+            album,
+            duration_secs: track.duration().map(|v| v.as_secs()),
+            art_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::config::v2::server::webhooks::WebhookEvent;
+    use termusiclib::player::RunningStatus;
+    use termusiclib::player::playlist_helpers::PlaylistTrackSource;
+
+    use super::{PlaybackState, event_for, signature};
+
+    fn state(path: Option<&str>, status: RunningStatus) -> PlaybackState {
+        PlaybackState {
+            track: path.map(|v| (PlaylistTrackSource::Path(v.to_string()), None)),
+            status,
+        }
+    }
+
+    #[test]
+    fn should_detect_events() {
+        let playing_a = state(Some("/a.mp3"), RunningStatus::Running);
+        let paused_a = state(Some("/a.mp3"), RunningStatus::Paused);
+        let playing_b = state(Some("/b.mp3"), RunningStatus::Running);
+        let stopped = state(None, RunningStatus::Stopped);
+
+        assert_eq!(event_for(None, &playing_a), Some(WebhookEvent::TrackChange));
+        assert_eq!(event_for(None, &stopped), None);
+        assert_eq!(event_for(Some(&playing_a), &playing_a), None);
+        assert_eq!(
+            event_for(Some(&playing_a), &paused_a),
+            Some(WebhookEvent::Pause)
+        );
+        assert_eq!(
+            event_for(Some(&paused_a), &playing_a),
+            Some(WebhookEvent::Play)
+        );
+        assert_eq!(
+            event_for(Some(&playing_a), &playing_b),
+            Some(WebhookEvent::TrackChange)
+        );
+        assert_eq!(
+            event_for(Some(&playing_b), &stopped),
+            Some(WebhookEvent::Stop)
+        );
+
+        // a new radio title is a track change
+        let mut radio_title = playing_a.clone();
+        radio_title.track.as_mut().unwrap().1 = Some("Song".to_string());
+        assert_eq!(
+            event_for(Some(&playing_a), &radio_title),
+            Some(WebhookEvent::TrackChange)
+        );
+    }
+
+    #[test]
+    fn should_sign_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
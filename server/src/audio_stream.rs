@@ -0,0 +1,219 @@
+//! Minimal HTTP endpoint to stream the playing track to remote clients, transcoding lossless tracks to Opus.
+
+use std::ffi::OsStr;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use termusiclib::config::SharedServerSettings;
+use termusiclib::device_sync::is_lossless;
+use termusicplayback::SharedPlaylist;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+
+use crate::metrics::{MAX_REQUEST_SIZE, request_path};
+
+/// The data shared between all connections.
+#[derive(Debug)]
+struct StreamState {
+    playlist: SharedPlaylist,
+    /// The current settings, so that the bitrates can be changed with a config reload
+    config: SharedServerSettings,
+}
+
+/// Start the audio stream endpoint, if enabled in the config.
+pub async fn start_audio_stream_endpoint(
+    config: &SharedServerSettings,
+    playlist: SharedPlaylist,
+    cancel_token: CancellationToken,
+) -> Result<()> {
+    let settings = config.read().settings.audio_stream.clone();
+    if !settings.enable {
+        return Ok(());
+    }
+
+    let addr = SocketAddr::from(&settings);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Error binding audio stream address: {addr}"))?;
+    info!(
+        "Audio stream listening on http://{}/stream",
+        listener.local_addr()?
+    );
+
+    let state = Arc::new(StreamState {
+        playlist,
+        config: config.clone(),
+    });
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                res = listener.accept() => {
+                    match res {
+                        Ok((stream, _)) => {
+                            let state = state.clone();
+                            let cancel_token = cancel_token.clone();
+                            tokio::spawn(async move {
+                                select! {
+                                    res = handle_connection(stream, &state) => {
+                                        if let Err(err) = res {
+                                            debug!("Error handling audio stream request: {err:#}");
+                                        }
+                                    }
+                                    () = cancel_token.cancelled() => (),
+                                }
+                            });
+                        }
+                        Err(err) => warn!("Error accepting audio stream connection: {err}"),
+                    }
+                },
+                () = cancel_token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Handle a single HTTP request, only `GET /stream` is served.
+///
+/// The stream is the track playing at the time of the request, from the start. Clients request the next one once it
+/// changed.
+async fn handle_connection(mut stream: TcpStream, state: &StreamState) -> Result<()> {
+    let mut buf = vec![0; MAX_REQUEST_SIZE];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+
+    let path = state
+        .playlist
+        .read()
+        .current_track()
+        .and_then(|v| v.path().map(Path::to_path_buf));
+    let (Some("/stream"), Some(path)) = (request_path(&request), path) else {
+        stream
+            .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .await?;
+        stream.shutdown().await?;
+        return Ok(());
+    };
+
+    let bitrate = {
+        let client = query_param(&request, "client");
+        let requested = query_param(&request, "bitrate").and_then(|v| v.parse().ok());
+        let config = state.config.read();
+        config.settings.audio_stream.bitrate_for(client, requested)
+    };
+
+    match bitrate {
+        Some(bitrate) if is_lossless(&path) => send_transcoded(&mut stream, &path, bitrate).await?,
+        _ => send_file(&mut stream, &path).await?,
+    }
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+/// Send the file at `path` as-is.
+async fn send_file(stream: &mut TcpStream, path: &Path) -> Result<()> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| path.display().to_string())?;
+    let len = file.metadata().await?.len();
+
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {len}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        content_type(path)
+    );
+    stream.write_all(head.as_bytes()).await?;
+    tokio::io::copy(&mut file, stream).await?;
+
+    Ok(())
+}
+
+/// Send the file at `path` transcoded to Opus with `ffmpeg` while it is sent, the body ends with the connection.
+async fn send_transcoded(stream: &mut TcpStream, path: &Path, bitrate: u16) -> Result<()> {
+    let mut child = Command::new("ffmpeg")
+        .args(["-nostdin", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-vn", "-codec:a", "libopus", "-b:a"])
+        .arg(format!("{bitrate}k"))
+        .args(["-f", "ogg", "pipe:1"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        // ffmpeg is killed once the client disconnects
+        .kill_on_drop(true)
+        .spawn()
+        .context("failed to run ffmpeg, is it installed?")?;
+    let mut stdout = child.stdout.take().context("ffmpeg stdout")?;
+
+    stream
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: audio/ogg\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n")
+        .await?;
+    tokio::io::copy(&mut stdout, stream).await?;
+    child.wait().await?;
+
+    Ok(())
+}
+
+/// Get the value of `key` in the query of a request line, without percent-decoding.
+fn query_param<'a>(request: &'a str, key: &str) -> Option<&'a str> {
+    let target = request.lines().next()?.split(' ').nth(1)?;
+    let (_, query) = target.split_once('?')?;
+
+    query
+        .split('&')
+        .find_map(|v| v.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// Get the content type of a file sent as-is.
+fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_lowercase);
+
+    match ext.as_deref() {
+        Some("mp3") => "audio/mpeg",
+        Some("ogg" | "oga" | "opus") => "audio/ogg",
+        Some("m4a" | "mp4" | "aac") => "audio/mp4",
+        Some("flac") => "audio/flac",
+        Some("wav") => "audio/wav",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{content_type, query_param};
+
+    #[test]
+    fn should_get_query_param() {
+        let request = "GET /stream?client=phone&bitrate=64 HTTP/1.1\r\nHost: x\r\n\r\n";
+        assert_eq!(query_param(request, "client"), Some("phone"));
+        assert_eq!(query_param(request, "bitrate"), Some("64"));
+        assert_eq!(query_param(request, "bit"), None);
+
+        assert_eq!(query_param("GET /stream HTTP/1.1\r\n", "client"), None);
+    }
+
+    #[test]
+    fn should_get_content_type() {
+        assert_eq!(content_type(Path::new("/music/a.MP3")), "audio/mpeg");
+        assert_eq!(content_type(Path::new("/music/a.opus")), "audio/ogg");
+        assert_eq!(
+            content_type(Path::new("/music/a")),
+            "application/octet-stream"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! The hidden `--bench` mode, timing decode and scan performance to diagnose slow libraries.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use termusiclib::config::ServerOverlay;
+use termusiclib::new_database::{Database, ScanTimings};
+use termusicplayback::{DecodeBench, bench_decode};
+
+/// Decode every file and scan every directory in `paths`, printing the timings.
+///
+/// Directories are scanned into a temporary database, to not modify the library.
+pub fn run(paths: &[PathBuf], config: &ServerOverlay) -> Result<()> {
+    let mut failed = 0;
+
+    for path in paths {
+        let res = if path.is_dir() {
+            bench_scan(path, config).map(|v| format_scan(path, &v))
+        } else {
+            bench_decode(path).map(|v| format_decode(path, &v))
+        };
+
+        match res {
+            Ok(report) => println!("{report}"),
+            Err(err) => {
+                println!("{}: {err:#}", path.display());
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} paths failed");
+    }
+
+    Ok(())
+}
+
+/// Scan `path` into a new temporary database, which is removed afterwards.
+fn bench_scan(path: &Path, config: &ServerOverlay) -> Result<ScanTimings> {
+    let db_path = std::env::temp_dir().join(format!("termusic-bench-{}.db", std::process::id()));
+
+    let res = Database::new(&db_path)
+        .context("create temporary database")
+        .and_then(|db| db.bench_scan(path, config));
+
+    // the database may have been partially created, also remove the WAL files
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = db_path.clone().into_os_string();
+        file.push(suffix);
+        let _ = std::fs::remove_file(file);
+    }
+
+    res
+}
+
+fn format_decode(path: &Path, bench: &DecodeBench) -> String {
+    format!(
+        "decode {}: {:.2?} of audio in {:.2?} ({:.1}x realtime), {} Hz, {} channels",
+        path.display(),
+        bench.audio_duration(),
+        bench.elapsed,
+        bench.speed(),
+        bench.sample_rate,
+        bench.channels
+    )
+}
+
+fn format_scan(path: &Path, timings: &ScanTimings) -> String {
+    let measured =
+        timings.tag_parse + timings.db_insert + timings.art_extract + timings.audio_analysis;
+    let per_file = |duration: Duration| {
+        let files = u32::try_from(timings.files.max(1)).unwrap_or(u32::MAX);
+        duration / files
+    };
+
+    let mut out = format!(
+        "scan {}: {} files ({} created or updated, {} failed) in {:.2?}",
+        path.display(),
+        timings.files,
+        timings.created_updated,
+        timings.failed,
+        timings.total
+    );
+    for (name, duration) in [
+        ("tag parse", timings.tag_parse),
+        ("db insert", timings.db_insert),
+        ("art extraction", timings.art_extract),
+        ("audio analysis", timings.audio_analysis),
+        // walking the directories and converting the metadata
+        ("other", timings.total.saturating_sub(measured)),
+    ] {
+        let _ = write!(
+            out,
+            "\n  {name:<15} {duration:>10.2?} ({:.2?} per file)",
+            per_file(duration)
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use termusiclib::new_database::ScanTimings;
+
+    use super::format_scan;
+
+    #[test]
+    fn should_format_scan_timings() {
+        let timings = ScanTimings {
+            files: 4,
+            created_updated: 3,
+            failed: 1,
+            tag_parse: Duration::from_millis(400),
+            db_insert: Duration::from_millis(200),
+            art_extract: Duration::from_millis(100),
+            audio_analysis: Duration::from_millis(100),
+            total: Duration::from_secs(1),
+        };
+
+        assert_eq!(
+            format_scan(Path::new("/music"), &timings),
+            "scan /music: 4 files (3 created or updated, 1 failed) in 1.00s\n  tag parse         400.00ms (100.00ms per file)\n  db insert         200.00ms (50.00ms per file)\n  art extraction    100.00ms (25.00ms per file)\n  audio analysis    100.00ms (25.00ms per file)\n  other             200.00ms (50.00ms per file)"
+        );
+    }
+}
@@ -24,6 +24,9 @@ pub struct Args {
     /// Select the backend, default is `rusty`
     #[arg(short, long, env = "TMS_BACKEND")]
     pub backend: Option<Backend>,
+    /// Decode the given files and scan the given directories as fast as possible, printing timings instead of starting the server.
+    #[arg(long, hide = true, value_name = "PATH", num_args = 1..)]
+    pub bench: Vec<PathBuf>,
     #[clap(flatten)]
     pub log_options: LogOptions,
 }
@@ -79,6 +82,118 @@ pub enum Action {
         #[arg(value_name = "FILE")]
         file: PathBuf,
     },
+    /// Check the backend, audio output, config and database, printing hints for any problem.
+    Doctor,
+    /// Library database maintenance.
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// Send a playback command to the running server, for example from media notifications or key bindings.
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+    /// Manage credentials of integrations, stored in the system keyring or the "credentials.toml" fallback.
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsAction,
+    },
+    /// Render the saved queue to a single file and a CUE sheet next to it, like a mixtape.
+    ///
+    /// The tracks are decoded as fast as possible with the crossfade, equalizer and normalization settings of the
+    /// rusty backend, and encoded by `ffmpeg`. Radio streams and other remote tracks are skipped.
+    Render {
+        /// The file to write, its extension "flac" or "ogg" chooses the format.
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+/// Subcommands for the library database
+#[derive(Subcommand, Debug)]
+pub enum DbAction {
+    /// Check the database integrity, rebuild indexes and prune orphaned rows.
+    Repair,
+    /// Rebuild the database file to reclaim free space.
+    Vacuum,
+    /// List the tracks flagged as damaged by the salvage mode of the rusty backend, with their muted regions.
+    Damaged {
+        /// Clear the flag of a repaired track instead.
+        #[arg(long, value_name = "PATH")]
+        clear: Option<PathBuf>,
+    },
+    /// Analyze the loudness of the tracks without `ReplayGain` tags per EBU R128, for normalization to apply to them too.
+    ///
+    /// The loudness is stored in the database, tracks already analyzed are skipped.
+    Loudness {
+        /// Also write the computed gain to the `ReplayGain` tags of the files.
+        #[arg(long)]
+        write_tags: bool,
+    },
+    /// Import ratings, play counts and last played times from other players, merged into the library tracks.
+    ///
+    /// FILE is a text export with a header row, like from foobar2000 or `MediaMonkey`, or a directory to scan for
+    /// ".ratings" sidecars. Play counts and last played times only ever increase, existing ratings are kept.
+    ImportStats {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        /// Replace the prefix of the exported paths, like "D:\Music=/home/user/Music".
+        #[arg(long, value_name = "FROM=TO")]
+        map_root: Option<String>,
+        /// Only report what would be imported.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Subcommands for credentials
+#[derive(Subcommand, Debug)]
+pub enum CredentialsAction {
+    /// Store the secret for a key, read from stdin.
+    ///
+    /// Podcast feeds and episodes use HTTP basic authentication with the key "podcast:<host>" and the secret "user:password",
+    /// playlist sync WebDAV servers the key "webdav:<host>".
+    Set {
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+    /// Remove the secret for a key from all stores.
+    Remove {
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+}
+
+/// Playback commands for the running server
+#[derive(Subcommand, Debug, Clone)]
+pub enum CtlCommand {
+    /// Toggle between playing and paused.
+    TogglePause,
+    /// Skip to the next track.
+    Next,
+    /// Skip to the previous track.
+    Previous,
+    /// Continue the playlist at the current position on another server, pausing this one.
+    ///
+    /// Local files need to be at the same paths on the other server.
+    Transfer {
+        /// The address of the other server, like "http://192.168.1.2:50101" or "unix:///run/user/1000/termusic.socket"
+        #[arg(value_name = "ADDRESS")]
+        target: String,
+    },
+    /// Lower the music volume as configured in "ducking", while running the given command if any.
+    ///
+    /// For example `ctl duck -- espeak "Battery low"` or `ctl duck -- paplay message.oga` from a notification daemon.
+    /// Without a command, the volume stays lowered until `ctl duck --end` or "ducking.max_secs".
+    Duck {
+        /// End ducking instead, ramping the volume back up.
+        #[arg(long, conflicts_with = "command")]
+        end: bool,
+        /// The command to run while ducked, like a text-to-speech program.
+        #[arg(trailing_var_arg = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
 }
 
 const DEFAULT_LOGFILE_FILENAME: &str = "termusic-server.log";
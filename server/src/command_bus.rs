@@ -0,0 +1,433 @@
+//! A command bus on a Unix socket, to control the server from shell scripts without protobuf tooling.
+//!
+//! Each line sent to the socket is one [`BusCommand`] as JSON, like `{"cmd":"PlaylistAddFront","path":"/music/a.mp3"}`,
+//! which is answered with one line of `{"ok":true}` or `{"ok":false,"error":"..."}`.
+//! Like for gRPC, a command being accepted does not mean it succeeded, errors while running it are only logged.
+//!
+//! The socket is only available on unix, the commands are also used for [macros](crate::macros) on all platforms.
+
+use std::time::Duration;
+
+#[cfg(unix)]
+use anyhow::Context as _;
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+#[cfg(unix)]
+use serde::Serialize;
+#[cfg(unix)]
+use termusiclib::config::SharedServerSettings;
+use termusiclib::player::AbLoop;
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddRandom, PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed,
+    PlaylistTrackSource,
+};
+use termusiclib::track::Track;
+use termusiclib::ytdlp;
+use termusicplayback::{PlayerCmd, Volume};
+#[cfg(unix)]
+use termusicplayback::{PlayerCmdSender, SharedPlaylist};
+#[cfg(unix)]
+use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use tokio::select;
+#[cfg(unix)]
+use tokio_util::sync::CancellationToken;
+
+/// A command accepted on the bus, mostly mapping directly to a [`PlayerCmd`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "cmd")]
+pub enum BusCommand {
+    Play,
+    Pause,
+    TogglePause,
+    SkipNext,
+    SkipPrevious,
+    SeekForward,
+    SeekBackward,
+    /// Seek to `millis` in the current track
+    SeekTo {
+        millis: u64,
+    },
+    /// Repeat the current track from `start_ms` to `end_ms`, clears the loop if either is unset
+    AbLoop {
+        start_ms: Option<u64>,
+        end_ms: Option<u64>,
+    },
+    VolumeUp,
+    VolumeDown,
+    VolumeSet {
+        volume: Volume,
+    },
+    SpeedUp,
+    SpeedDown,
+    CycleLoop,
+    ToggleGapless,
+    AlbumMode {
+        enable: bool,
+    },
+    VocalReduction {
+        enable: bool,
+    },
+    Normalization {
+        enable: bool,
+    },
+    SmartSpeed {
+        enable: bool,
+    },
+    Recording {
+        enable: bool,
+    },
+    /// Play on the output device `name`, or the default device of the system if empty
+    OutputDevice {
+        name: String,
+    },
+    /// Start or end lowering the volume for a notification sound or announcement
+    Duck {
+        active: bool,
+    },
+    /// Add `path`, a file or a `http(s)` radio url, at index `at`, or at the end if unset
+    PlaylistAdd {
+        path: String,
+        at: Option<u64>,
+    },
+    /// Add `path`, a file or a `http(s)` radio url, at the start
+    PlaylistAddFront {
+        path: String,
+    },
+    /// Remove the track at `index`
+    PlaylistRemove {
+        index: u64,
+    },
+    /// Play the track at `index`
+    PlaylistPlay {
+        index: u64,
+    },
+    PlaylistClear,
+    PlaylistShuffle,
+    PlaylistRemoveDeletedTracks,
+    /// Add `tracks` random tracks from the library database
+    PlaylistAddRandom {
+        tracks: u32,
+    },
+    /// Add `tracks` tracks chosen by the auto-DJ, see [`auto_dj`](termusiclib::auto_dj)
+    PlaylistAddAutoDj {
+        tracks: u32,
+    },
+    /// Run the macro `name`, see [`macros`](crate::macros)
+    Macro {
+        name: String,
+    },
+    ReloadConfig,
+    ReloadPlaylist,
+    Quit,
+}
+
+impl BusCommand {
+    /// Convert to the [`PlayerCmd`] to send, `tracks` being the current playlist for the commands referring to an index.
+    ///
+    /// # Errors
+    ///
+    /// If the index of a command is not in `tracks`.
+    pub fn into_player_cmd(self, tracks: &[Track]) -> Result<PlayerCmd> {
+        let cmd = match self {
+            Self::Play => PlayerCmd::Play,
+            Self::Pause => PlayerCmd::Pause,
+            Self::TogglePause => PlayerCmd::TogglePause,
+            Self::SkipNext => PlayerCmd::SkipNext,
+            Self::SkipPrevious => PlayerCmd::SkipPrevious,
+            Self::SeekForward => PlayerCmd::SeekForward,
+            Self::SeekBackward => PlayerCmd::SeekBackward,
+            Self::SeekTo { millis } => PlayerCmd::SeekTo(Duration::from_millis(millis)),
+            Self::AbLoop { start_ms, end_ms } => {
+                PlayerCmd::AbLoop(start_ms.zip(end_ms).and_then(|(start, end)| {
+                    AbLoop::new(Duration::from_millis(start), Duration::from_millis(end))
+                }))
+            }
+            Self::VolumeUp => PlayerCmd::VolumeUp,
+            Self::VolumeDown => PlayerCmd::VolumeDown,
+            Self::VolumeSet { volume } => PlayerCmd::VolumeSet(volume),
+            Self::SpeedUp => PlayerCmd::SpeedUp,
+            Self::SpeedDown => PlayerCmd::SpeedDown,
+            Self::CycleLoop => PlayerCmd::CycleLoop,
+            Self::ToggleGapless => PlayerCmd::ToggleGapless,
+            Self::AlbumMode { enable } => PlayerCmd::AlbumMode(enable),
+            Self::VocalReduction { enable } => PlayerCmd::VocalReduction(enable),
+            Self::Normalization { enable } => PlayerCmd::Normalization(enable),
+            Self::SmartSpeed { enable } => PlayerCmd::SmartSpeed(enable),
+            Self::Recording { enable } => PlayerCmd::Recording(enable),
+            Self::OutputDevice { name } => PlayerCmd::OutputDevice(name),
+            Self::Duck { active } => PlayerCmd::Duck(active),
+            Self::PlaylistAdd { path, at } => {
+                let at = at.unwrap_or(u64::try_from(tracks.len())?);
+                PlayerCmd::PlaylistAddTrack(PlaylistAddTrack::new_single(at, source_from_str(path)))
+            }
+            Self::PlaylistAddFront { path } => {
+                PlayerCmd::PlaylistAddTrack(PlaylistAddTrack::new_single(0, source_from_str(path)))
+            }
+            Self::PlaylistRemove { index } => PlayerCmd::PlaylistRemoveTrack(
+                PlaylistRemoveTrackIndexed::new_single(index, track_at(tracks, index)?),
+            ),
+            Self::PlaylistPlay { index } => PlayerCmd::PlaylistPlaySpecific(PlaylistPlaySpecific {
+                track_index: index,
+                id: track_at(tracks, index)?,
+            }),
+            Self::PlaylistClear => PlayerCmd::PlaylistClear,
+            Self::PlaylistShuffle => PlayerCmd::PlaylistShuffle,
+            Self::PlaylistRemoveDeletedTracks => PlayerCmd::PlaylistRemoveDeletedTracks,
+            Self::PlaylistAddRandom { tracks } => {
+                PlayerCmd::PlaylistAddRandom(PlaylistAddRandom::Tracks(tracks))
+            }
+            Self::PlaylistAddAutoDj { tracks } => {
+                PlayerCmd::PlaylistAddRandom(PlaylistAddRandom::AutoDj(tracks))
+            }
+            Self::Macro { name } => PlayerCmd::RunMacro(name),
+            Self::ReloadConfig => PlayerCmd::ReloadConfig,
+            Self::ReloadPlaylist => PlayerCmd::ReloadPlaylist,
+            Self::Quit => PlayerCmd::Quit,
+        };
+
+        Ok(cmd)
+    }
+}
+
+/// Get the source for `path`, a `http(s)` url being a video page or a radio stream like in the tui.
+fn source_from_str(path: String) -> PlaylistTrackSource {
+    if ytdlp::is_video_url(&path) {
+        PlaylistTrackSource::VideoUrl(path)
+    } else if path.starts_with("http") {
+        PlaylistTrackSource::Url(path)
+    } else {
+        PlaylistTrackSource::Path(path)
+    }
+}
+
+/// Get the source of the track at `index`, to identify it for the index-based playlist commands.
+fn track_at(tracks: &[Track], index: u64) -> Result<PlaylistTrackSource> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|v| tracks.get(v))
+        .map(Track::as_track_source)
+        .ok_or_else(|| anyhow!("Index {index} is out of bound {}", tracks.len()))
+}
+
+/// The reply to every line.
+#[cfg(unix)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct BusReply {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[cfg(unix)]
+impl From<Result<()>> for BusReply {
+    fn from(value: Result<()>) -> Self {
+        match value {
+            Ok(()) => Self {
+                ok: true,
+                error: None,
+            },
+            Err(err) => Self {
+                ok: false,
+                error: Some(format!("{err:#}")),
+            },
+        }
+    }
+}
+
+/// Start the command bus, if enabled in the config.
+#[cfg(unix)]
+pub fn start_command_bus(
+    config: &SharedServerSettings,
+    cmd_tx: PlayerCmdSender,
+    playlist: SharedPlaylist,
+    cancel_token: CancellationToken,
+) -> Result<()> {
+    let settings = config.read().settings.command_bus.clone();
+    if !settings.enable {
+        return Ok(());
+    }
+
+    let path = &settings.socket_path;
+    // if the file already exists, tokio will error with "Address already in use"
+    if path.exists() {
+        warn!("Socket Path {} already exists, unlinking!", path.display());
+        let _ = std::fs::remove_file(path);
+    }
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Error binding command bus socket: {}", path.display()))?;
+    info!("Command bus listening on {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                res = listener.accept() => {
+                    match res {
+                        Ok((stream, _)) => {
+                            let cmd_tx = cmd_tx.clone();
+                            let playlist = playlist.clone();
+                            tokio::spawn(async move {
+                                if let Err(err) = handle_connection(stream, &cmd_tx, &playlist).await {
+                                    debug!("Error handling command bus connection: {err:#}");
+                                }
+                            });
+                        }
+                        Err(err) => warn!("Error accepting command bus connection: {err}"),
+                    }
+                },
+                () = cancel_token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Handle all lines of a single connection, until it is closed.
+#[cfg(unix)]
+async fn handle_connection(
+    stream: UnixStream,
+    cmd_tx: &PlayerCmdSender,
+    playlist: &SharedPlaylist,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let res = handle_line(&line, cmd_tx, playlist).await;
+        if let Err(err) = &res {
+            debug!("Error in command bus command {line:?}: {err:#}");
+        }
+
+        let mut reply = serde_json::to_vec(&BusReply::from(res))?;
+        reply.push(b'\n');
+        writer.write_all(&reply).await?;
+    }
+
+    Ok(())
+}
+
+/// Parse and send a single command, waiting for it to be processed.
+#[cfg(unix)]
+async fn handle_line(
+    line: &str,
+    cmd_tx: &PlayerCmdSender,
+    playlist: &SharedPlaylist,
+) -> Result<()> {
+    let command: BusCommand = serde_json::from_str(line).context("parse command")?;
+    let cmd = command.into_player_cmd(playlist.read().tracks())?;
+    let rx = cmd_tx.send_cb(cmd).context("send command")?;
+    // wait until the command was processed, so that the next line sees its effect
+    let _ = rx.await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use termusiclib::player::playlist_helpers::{
+        PlaylistAddRandom, PlaylistAddTrack, PlaylistTrackSource,
+    };
+    use termusicplayback::PlayerCmd;
+
+    use super::BusCommand;
+    #[cfg(unix)]
+    use super::BusReply;
+
+    #[test]
+    fn should_parse_commands() {
+        let parse = |v: &str| serde_json::from_str::<BusCommand>(v).unwrap();
+
+        assert_eq!(parse(r#"{"cmd":"TogglePause"}"#), BusCommand::TogglePause);
+        assert_eq!(
+            parse(r#"{"cmd":"AbLoop","start_ms":1000,"end_ms":5000}"#),
+            BusCommand::AbLoop {
+                start_ms: Some(1000),
+                end_ms: Some(5000)
+            }
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"VolumeSet","volume":50}"#),
+            BusCommand::VolumeSet { volume: 50 }
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"PlaylistAdd","path":"/music/a.mp3"}"#),
+            BusCommand::PlaylistAdd {
+                path: "/music/a.mp3".to_string(),
+                at: None
+            }
+        );
+
+        assert!(serde_json::from_str::<BusCommand>(r#"{"cmd":"Unknown"}"#).is_err());
+        assert!(serde_json::from_str::<BusCommand>(r#"{"cmd":"VolumeSet"}"#).is_err());
+        assert!(serde_json::from_str::<BusCommand>(r#"{"path":"/music/a.mp3"}"#).is_err());
+    }
+
+    #[test]
+    fn should_convert_to_player_cmd() {
+        let cmd = BusCommand::PlaylistAddFront {
+            path: "/music/a.mp3".to_string(),
+        }
+        .into_player_cmd(&[])
+        .unwrap();
+        let PlayerCmd::PlaylistAddTrack(info) = cmd else {
+            panic!("Expected PlaylistAddTrack, got {cmd:?}");
+        };
+        assert_eq!(
+            info,
+            PlaylistAddTrack::new_single(0, PlaylistTrackSource::Path("/music/a.mp3".to_string()))
+        );
+
+        let cmd = BusCommand::PlaylistAdd {
+            path: "https://example.com/stream".to_string(),
+            at: Some(3),
+        }
+        .into_player_cmd(&[])
+        .unwrap();
+        let PlayerCmd::PlaylistAddTrack(info) = cmd else {
+            panic!("Expected PlaylistAddTrack, got {cmd:?}");
+        };
+        assert_eq!(
+            info,
+            PlaylistAddTrack::new_single(
+                3,
+                PlaylistTrackSource::Url("https://example.com/stream".to_string())
+            )
+        );
+
+        let cmd = BusCommand::PlaylistAddAutoDj { tracks: 5 }
+            .into_player_cmd(&[])
+            .unwrap();
+        assert!(matches!(
+            cmd,
+            PlayerCmd::PlaylistAddRandom(PlaylistAddRandom::AutoDj(5))
+        ));
+
+        assert!(
+            BusCommand::PlaylistPlay { index: 0 }
+                .into_player_cmd(&[])
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn should_serialize_replies() {
+        assert_eq!(
+            serde_json::to_string(&BusReply::from(Ok(()))).unwrap(),
+            r#"{"ok":true}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&BusReply::from(Err(anyhow::anyhow!("failed")))).unwrap(),
+            r#"{"ok":false,"error":"failed"}"#
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! The `ctl` subcommand, sending a single playback command to the running server.
+
+use std::net::SocketAddr;
+
+use anyhow::{Context as _, Result, bail};
+use termusiclib::config::ServerOverlay;
+use termusiclib::config::v2::server::{ComProtocol, ComSettings};
+use termusiclib::player::music_player_client::MusicPlayerClient;
+use termusiclib::player::{DuckingState, Empty, SessionTransfer};
+use tonic::transport::Channel;
+
+use crate::cli::CtlCommand;
+
+/// Get the address to connect to the server configured with `com`.
+pub fn server_address(com: &ComSettings) -> String {
+    match com.protocol {
+        ComProtocol::HTTP => format!("http://{}", SocketAddr::from(com)),
+        ComProtocol::UDS => format!("unix://{}", com.socket_path.display()),
+    }
+}
+
+/// Connect to the server configured in `config` and send `command`.
+pub async fn run(command: CtlCommand, config: &ServerOverlay) -> Result<()> {
+    let addr = server_address(&config.settings.com);
+
+    let mut client = MusicPlayerClient::connect(addr.clone())
+        .await
+        .with_context(|| format!("connect to the running server at \"{addr}\""))?;
+
+    match &command {
+        CtlCommand::TogglePause => client.toggle_pause(Empty {}).await.map(|_| ()),
+        CtlCommand::Next => client.skip_next(Empty {}).await.map(|_| ()),
+        CtlCommand::Previous => client.skip_previous(Empty {}).await.map(|_| ()),
+        CtlCommand::Transfer { target } => client
+            .transfer_session(SessionTransfer {
+                target: target.clone(),
+            })
+            .await
+            .map(|_| ()),
+        CtlCommand::Duck { end, command } if command.is_empty() => client
+            .set_ducking(DuckingState { active: !end })
+            .await
+            .map(|_| ()),
+        CtlCommand::Duck { command, .. } => return duck_while(&mut client, command).await,
+    }
+    .with_context(|| format!("send {command:?}"))?;
+
+    Ok(())
+}
+
+/// Duck the music while running `command`, like a text-to-speech program, then end ducking even if it failed.
+async fn duck_while(client: &mut MusicPlayerClient<Channel>, command: &[String]) -> Result<()> {
+    client
+        .set_ducking(DuckingState { active: true })
+        .await
+        .context("start ducking")?;
+
+    let status = tokio::process::Command::new(&command[0])
+        .args(&command[1..])
+        .status()
+        .await;
+
+    client
+        .set_ducking(DuckingState { active: false })
+        .await
+        .context("end ducking")?;
+
+    let status = status.with_context(|| format!("run {:?}", command[0]))?;
+    if !status.success() {
+        bail!("{:?} exited with {status}", command[0]);
+    }
+
+    Ok(())
+}
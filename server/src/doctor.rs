@@ -0,0 +1,178 @@
+//! The `doctor` subcommand, checking everything the server needs for playback.
+
+use anyhow::{Result, bail};
+use termusiclib::config::v2::server::ServerSettings;
+use termusiclib::config::v2::server::config_extra::{FILE_NAME, ServerConfigVersionedDefaulted};
+use termusiclib::doctor::{Check, CheckStatus, Report};
+use termusiclib::new_database::Database;
+use termusiclib::new_database::maintenance::IntegrityStatus;
+use termusiclib::utils::{get_app_config_path, get_app_new_database_path};
+use termusicplayback::{BackendSelect, probe_output_device};
+
+use crate::cli;
+
+/// Run all server-side checks, printing each result as it completes.
+///
+/// Returns a error if any check failed, so that the exit code reflects the result.
+pub fn run(args: &cli::Args) -> Result<()> {
+    let mut report = Report::default();
+
+    let settings = check_config(&mut report);
+    let backend = check_backend(&mut report, args, settings.as_ref());
+    check_audio_output(&mut report, backend);
+    if let Some(settings) = &settings {
+        check_music_dirs(&mut report, settings);
+    }
+    check_database(&mut report);
+
+    println!("{}", report.summary());
+
+    let errors = report.count(CheckStatus::Error);
+    if errors > 0 {
+        bail!("{errors} checks failed");
+    }
+
+    Ok(())
+}
+
+/// Check that the server config can be loaded.
+fn check_config(report: &mut Report) -> Option<ServerSettings> {
+    let path = get_app_config_path().map(|v| v.join(FILE_NAME));
+    let path = path
+        .as_ref()
+        .map_or_else(|_| FILE_NAME.to_string(), |v| v.display().to_string());
+
+    match ServerConfigVersionedDefaulted::from_config_path() {
+        Ok(config) => {
+            report.push(Check::ok("server config", format!("loaded \"{path}\"")));
+            Some(config.into_settings())
+        }
+        Err(err) => {
+            report.push(Check::error(
+                "server config",
+                format!("could not load \"{path}\": {err:#}"),
+                "fix the reported value, or move the file away to have a default written on next start",
+            ));
+            None
+        }
+    }
+}
+
+/// Check that the backend that would be used on start is compiled-in.
+fn check_backend(
+    report: &mut Report,
+    args: &cli::Args,
+    settings: Option<&ServerSettings>,
+) -> Option<BackendSelect> {
+    let available = BackendSelect::AVAILABLE
+        .iter()
+        .map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    report.push(Check::ok("backends", format!("compiled-in: {available}")));
+
+    if let Some(backend) = args.backend {
+        report.push(Check::ok(
+            "backend",
+            format!("\"{backend}\" selected via command line"),
+        ));
+        return Some(backend.into());
+    }
+
+    let configured = settings?.player.backend;
+    match BackendSelect::try_from(configured) {
+        Ok(backend) => {
+            report.push(Check::ok(
+                "backend",
+                format!("\"{}\" selected via config", backend.as_str()),
+            ));
+            Some(backend)
+        }
+        Err(err) => {
+            report.push(Check::error(
+                "backend",
+                err.to_string(),
+                format!(
+                    "set \"player.backend\" to one of {available}, or rebuild with the \"{}\" feature",
+                    configured.as_str()
+                ),
+            ));
+            None
+        }
+    }
+}
+
+/// Check that the default audio output device can be opened.
+fn check_audio_output(report: &mut Report, backend: Option<BackendSelect>) {
+    const HINT: &str = "check that a sound server (like PipeWire or PulseAudio) is running, that a output device is connected and not muted, and that your user may access it (for example being in the \"audio\" group)";
+
+    match probe_output_device() {
+        Ok(device) => report.push(Check::ok("audio output", format!("opened {device}"))),
+        // other backends have their own output handling, which may still work
+        Err(err) if backend.is_some_and(|v| v != BackendSelect::Rusty) => {
+            report.push(Check::warning("audio output", format!("{err:#}"), HINT));
+        }
+        Err(err) => report.push(Check::error("audio output", format!("{err:#}"), HINT)),
+    }
+}
+
+/// Check that the configured music directories exist.
+fn check_music_dirs(report: &mut Report, settings: &ServerSettings) {
+    let music_dirs = &settings.player.music_dirs;
+    if music_dirs.is_empty() {
+        report.push(Check::warning(
+            "music dirs",
+            "none configured",
+            "add a directory to \"player.music_dirs\" to have tracks in the library",
+        ));
+        return;
+    }
+
+    for dir in music_dirs {
+        let expanded = shellexpand::path::tilde(dir);
+        if expanded.is_dir() {
+            report.push(Check::ok(
+                "music dirs",
+                format!("found \"{}\"", dir.display()),
+            ));
+        } else {
+            report.push(Check::warning(
+                "music dirs",
+                format!("\"{}\" is not a directory", dir.display()),
+                "fix or remove the entry in \"player.music_dirs\"",
+            ));
+        }
+    }
+}
+
+/// Check that the library database opens and passes a quick integrity check.
+fn check_database(report: &mut Report) {
+    let path = get_app_new_database_path().map_or_else(
+        |_| "library database".to_string(),
+        |v| v.display().to_string(),
+    );
+
+    let status = Database::new_default_path().and_then(|db| db.quick_check());
+    match status {
+        Ok(IntegrityStatus::Ok) => {
+            report.push(Check::ok(
+                "database",
+                format!("\"{path}\" passed quick_check"),
+            ));
+        }
+        Ok(IntegrityStatus::Problems(problems)) => report.push(Check::error(
+            "database",
+            format!(
+                "\"{path}\" has {} problems, first: {}",
+                problems.len(),
+                problems.first().map_or("", String::as_str)
+            ),
+            "run \"termusic-server db repair\", or delete the file to have it rebuilt on next scan",
+        )),
+        Err(err) => report.push(Check::error(
+            "database",
+            format!("could not open \"{path}\": {err:#}"),
+            "check the file permissions, or delete the file to have it rebuilt on next scan",
+        )),
+    }
+}
@@ -0,0 +1,464 @@
+//! Queue of yt-dlp downloads, run in the background with a concurrency limit and retries with backoff.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use parking_lot::Mutex;
+use termusiclib::config::SharedServerSettings;
+use termusiclib::downloads::{
+    self, DownloadAction, DownloadInfo, DownloadStatus, extract_filepath, finish_download,
+};
+use termusiclib::new_database::{Database, download_ops};
+use termusiclib::player::{DownloadRequest, UpdateEvents, download_request};
+use termusiclib::sponsorblock;
+use termusicplayback::StreamTX;
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, BufReader};
+use tokio::process::Command;
+use tokio::select;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// How often to check for downloads whose retry delay passed.
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to run for a download, does not change between attempts.
+#[derive(Debug)]
+struct Job {
+    url: String,
+    fallback_url: Option<String>,
+    dir: PathBuf,
+    args: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    info: DownloadInfo,
+    job: Arc<Job>,
+    /// Do not start before this, set while waiting to retry
+    retry_at: Option<Instant>,
+    /// The running attempt
+    task: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    next_id: u64,
+    /// All downloads that are not done or cancelled, in the order they were added
+    entries: Vec<Entry>,
+}
+
+impl Inner {
+    fn get_mut(&mut self, id: u64) -> Result<&mut Entry> {
+        self.entries
+            .iter_mut()
+            .find(|v| v.info.id == id)
+            .with_context(|| format!("No download with id {id}"))
+    }
+}
+
+/// The download queue, cheap to clone.
+#[derive(Clone)]
+pub struct DownloadManager {
+    inner: Arc<Mutex<Inner>>,
+    /// Wakes the scheduler to start pending downloads
+    notify: Arc<Notify>,
+    stream_tx: StreamTX,
+    config: SharedServerSettings,
+    /// For deduplicating finished downloads, if the database could be opened
+    db: Option<Database>,
+}
+
+impl std::fmt::Debug for DownloadManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadManager")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DownloadManager {
+    pub fn new(stream_tx: StreamTX, config: SharedServerSettings) -> Self {
+        let db = Database::new_default_path()
+            .inspect_err(|err| {
+                warn!("Opening the database failed, downloads will not be deduplicated: {err:#}");
+            })
+            .ok();
+
+        Self {
+            inner: Arc::default(),
+            notify: Arc::default(),
+            stream_tx,
+            config,
+            db,
+        }
+    }
+
+    /// Spawn the task starting queued downloads, until `cancel_token` is cancelled.
+    pub fn start(&self, cancel_token: CancellationToken) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            loop {
+                manager.schedule();
+                select! {
+                    () = manager.notify.notified() => {},
+                    () = tokio::time::sleep(SCHEDULE_INTERVAL) => {},
+                    () = cancel_token.cancelled() => break,
+                }
+            }
+
+            // dont leave yt-dlp running after the server exited
+            for entry in &mut manager.inner.lock().entries {
+                if let Some(task) = entry.task.take() {
+                    task.abort();
+                }
+            }
+        });
+    }
+
+    /// Add a download to the queue, returning its id.
+    pub fn enqueue(&self, request: DownloadRequest) -> Result<u64> {
+        downloads::check_args(&request.args)?;
+        let dir = PathBuf::from(request.dir);
+        if !dir.is_absolute() || !dir.is_dir() {
+            bail!("\"{}\" is not a existing directory", dir.display());
+        }
+        if request.url.is_empty() {
+            bail!("Missing url");
+        }
+
+        let settings = self.config.read();
+        let mut args = downloads::base_args();
+        if let Some(remove) = sponsorblock::ytdlp_remove_args(&settings.settings.sponsorblock) {
+            args.extend(remove);
+        }
+        drop(settings);
+        args.extend(request.args);
+
+        let fallback_url = request.optional_fallback_url.map(|v| {
+            let download_request::OptionalFallbackUrl::FallbackUrl(v) = v;
+            v
+        });
+        let title = if request.title.is_empty() {
+            request.url.clone()
+        } else {
+            request.title
+        };
+
+        let mut inner = self.inner.lock();
+        inner.next_id += 1;
+        let info = DownloadInfo {
+            id: inner.next_id,
+            url: request.url.clone(),
+            title,
+            status: DownloadStatus::Pending,
+            progress: 0,
+            attempts: 0,
+            error: None,
+            file: None,
+        };
+        let id = info.id;
+        self.send(&info);
+        inner.entries.push(Entry {
+            info,
+            job: Arc::new(Job {
+                url: request.url,
+                fallback_url,
+                dir,
+                args,
+            }),
+            retry_at: None,
+            task: None,
+        });
+        drop(inner);
+
+        self.notify.notify_one();
+
+        Ok(id)
+    }
+
+    /// Get all downloads that are not done or cancelled.
+    pub fn list(&self) -> Vec<DownloadInfo> {
+        self.inner
+            .lock()
+            .entries
+            .iter()
+            .map(|v| v.info.clone())
+            .collect()
+    }
+
+    /// Pause, resume, cancel or retry the download `id`.
+    pub fn control(&self, id: u64, action: DownloadAction) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let entry = inner.get_mut(id)?;
+        let status = entry.info.status;
+
+        match action {
+            DownloadAction::Pause => {
+                if !matches!(status, DownloadStatus::Pending | DownloadStatus::Active) {
+                    bail!("Only pending or active downloads can be paused");
+                }
+                if let Some(task) = entry.task.take() {
+                    task.abort();
+                }
+                entry.info.status = DownloadStatus::Paused;
+                entry.retry_at = None;
+            }
+            DownloadAction::Resume => {
+                if status != DownloadStatus::Paused {
+                    bail!("Only paused downloads can be resumed");
+                }
+                entry.info.status = DownloadStatus::Pending;
+            }
+            DownloadAction::Cancel => {
+                if let Some(task) = entry.task.take() {
+                    task.abort();
+                }
+                entry.info.status = DownloadStatus::Cancelled;
+                self.send(&entry.info);
+                inner.entries.retain(|v| v.info.id != id);
+                drop(inner);
+                self.notify.notify_one();
+
+                return Ok(());
+            }
+            DownloadAction::Retry => {
+                if !matches!(status, DownloadStatus::Failed | DownloadStatus::Pending) {
+                    bail!("Only failed or pending downloads can be retried");
+                }
+                if status == DownloadStatus::Failed {
+                    entry.info.attempts = 0;
+                }
+                entry.info.status = DownloadStatus::Pending;
+                entry.retry_at = None;
+            }
+        }
+
+        self.send(&entry.info);
+        drop(inner);
+        self.notify.notify_one();
+
+        Ok(())
+    }
+
+    /// Start pending downloads, as long as there are free slots.
+    fn schedule(&self) {
+        let max = usize::from(
+            self.config
+                .read()
+                .settings
+                .downloads
+                .concurrent_downloads_max
+                .get(),
+        );
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+        let mut active = inner
+            .entries
+            .iter()
+            .filter(|v| v.info.status == DownloadStatus::Active)
+            .count();
+
+        for entry in &mut inner.entries {
+            if active >= max {
+                break;
+            }
+            if entry.info.status != DownloadStatus::Pending
+                || entry.retry_at.is_some_and(|v| v > now)
+            {
+                continue;
+            }
+
+            active += 1;
+            entry.info.status = DownloadStatus::Active;
+            entry.info.attempts += 1;
+            entry.info.progress = 0;
+            entry.retry_at = None;
+            self.send(&entry.info);
+
+            let manager = self.clone();
+            let id = entry.info.id;
+            let job = entry.job.clone();
+            entry.task = Some(tokio::spawn(async move {
+                let res = manager.run(id, &job).await;
+                manager.finish(id, res);
+            }));
+        }
+    }
+
+    /// Download `job`, trying the fallback url if the url fails, and post-process the file.
+    async fn run(&self, id: u64, job: &Job) -> Result<Option<PathBuf>> {
+        let output = match self.run_ytdlp(id, job, &job.url).await {
+            Ok(v) => v,
+            Err(err) => {
+                let Some(fallback) = &job.fallback_url else {
+                    return Err(err);
+                };
+                self.run_ytdlp(id, job, fallback).await.map_err(|fallback_err| {
+                    anyhow!("Both primary and fallback failed. Primary: {err:#} Fallback: {fallback_err:#}")
+                })?
+            }
+        };
+
+        let Some(file) = extract_filepath(&output, &job.dir.to_string_lossy()) else {
+            return Ok(None);
+        };
+        let dir = job.dir.clone();
+        let url = job.url.clone();
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            finish_download(&dir, &file);
+            // after embedding the lyrics, as that changes the content
+            if let Some(db) = db
+                && let Err(err) =
+                    download_ops::dedup_download(&db.get_connection(), &url, Path::new(&file))
+            {
+                warn!("Deduplicating the download {file} failed: {err:#}");
+            }
+
+            PathBuf::from(file)
+        })
+        .await
+        .map(Some)
+        .context("post-process the download")
+    }
+
+    /// Run yt-dlp for `url`, reporting its progress and returning its output.
+    async fn run_ytdlp(&self, id: u64, job: &Job, url: &str) -> Result<String> {
+        let mut child = Command::new("yt-dlp")
+            .args(&job.args)
+            .arg(url)
+            .current_dir(&job.dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("start yt-dlp")?;
+
+        let mut stderr = child.stderr.take().context("yt-dlp stderr")?;
+        let stderr_task = tokio::spawn(async move {
+            let mut out = String::new();
+            let _ = stderr.read_to_string(&mut out).await;
+            out
+        });
+
+        let mut lines = BufReader::new(child.stdout.take().context("yt-dlp stdout")?).lines();
+        let mut output = String::new();
+        while let Some(line) = lines.next_line().await? {
+            if let Some(progress) = downloads::parse_progress(&line) {
+                self.set_progress(id, progress);
+            } else if let Some(name) = line.strip_prefix("[download] Destination: ") {
+                self.set_title(id, name);
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+
+        let status = child.wait().await.context("wait for yt-dlp")?;
+        let stderr = stderr_task.await.unwrap_or_default();
+        if !status.success() {
+            let err = stderr
+                .lines()
+                .rfind(|v| v.starts_with("ERROR:"))
+                .unwrap_or(stderr.trim());
+            bail!("yt-dlp exited with {status}: {err}");
+        }
+
+        Ok(output)
+    }
+
+    /// Update the progress of the download `id`, sending a event only if the whole percentage changed.
+    fn set_progress(&self, id: u64, progress: u8) {
+        let mut inner = self.inner.lock();
+        if let Ok(entry) = inner.get_mut(id)
+            && entry.info.progress != progress
+        {
+            entry.info.progress = progress;
+            self.send(&entry.info);
+        }
+    }
+
+    /// Use the file name of the download `id` as title, if it was not given in the request.
+    fn set_title(&self, id: u64, file_name: &str) {
+        let mut inner = self.inner.lock();
+        if let Ok(entry) = inner.get_mut(id)
+            && entry.info.title == entry.info.url
+            && let Some(stem) = Path::new(file_name).file_stem()
+        {
+            entry.info.title = stem.to_string_lossy().into_owned();
+            self.send(&entry.info);
+        }
+    }
+
+    /// Handle the end of a attempt of the download `id`.
+    fn finish(&self, id: u64, res: Result<Option<PathBuf>>) {
+        let mut inner = self.inner.lock();
+        let Ok(entry) = inner.get_mut(id) else {
+            return;
+        };
+        entry.task = None;
+
+        match res {
+            Ok(file) => {
+                info!("Downloaded \"{}\"", entry.info.url);
+                entry.info.status = DownloadStatus::Done;
+                entry.info.progress = 100;
+                entry.info.error = None;
+                entry.info.file = file;
+                self.send(&entry.info);
+                inner.entries.retain(|v| v.info.id != id);
+            }
+            Err(err) => {
+                warn!("Downloading \"{}\" failed: {err:#}", entry.info.url);
+                let settings = &self.config.read().settings.downloads;
+                entry.info.error = Some(format!("{err:#}"));
+                if entry.info.attempts <= u32::from(settings.max_download_retries) {
+                    entry.info.status = DownloadStatus::Pending;
+                    entry.retry_at = Some(
+                        Instant::now()
+                            + retry_delay(settings.retry_delay_secs.get(), entry.info.attempts),
+                    );
+                } else {
+                    entry.info.status = DownloadStatus::Failed;
+                }
+                self.send(&entry.info);
+            }
+        }
+
+        drop(inner);
+        self.notify.notify_one();
+    }
+
+    fn send(&self, info: &DownloadInfo) {
+        // there may be no clients listening
+        let _ = self
+            .stream_tx
+            .send(UpdateEvents::DownloadChanged(info.clone()));
+    }
+}
+
+/// The delay before retrying after the `attempts`th failed attempt, doubling `delay_secs` for every further attempt.
+fn retry_delay(delay_secs: u32, attempts: u32) -> Duration {
+    let factor = 2_u64.saturating_pow(attempts.saturating_sub(1));
+    Duration::from_secs(u64::from(delay_secs).saturating_mul(factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::retry_delay;
+
+    #[test]
+    fn should_back_off() {
+        assert_eq!(retry_delay(10, 1), Duration::from_secs(10));
+        assert_eq!(retry_delay(10, 2), Duration::from_secs(20));
+        assert_eq!(retry_delay(10, 4), Duration::from_secs(80));
+        assert_eq!(retry_delay(10, 200), Duration::from_secs(u64::MAX));
+    }
+}
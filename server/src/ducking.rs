@@ -0,0 +1,176 @@
+//! Lower the volume while a notification sound or a spoken announcement plays, and ramp it back up afterwards.
+
+use std::time::Instant;
+
+use termusiclib::config::v2::server::ducking::DuckingSettings;
+use termusicplayback::Volume;
+
+#[derive(Debug, Clone, Copy)]
+struct Duck {
+    /// Whether ramping down or staying down, otherwise ramping back up
+    active: bool,
+    /// When `active` last changed, to ramp from
+    changed: Instant,
+    /// The volume factor when `active` last changed, to ramp from
+    from: f64,
+    /// When ducking was last requested, to end it after [`DuckingSettings::max`]
+    requested: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct Ducker {
+    duck: Option<Duck>,
+}
+
+impl Ducker {
+    /// Check if the volume is currently lowered or still ramping back up.
+    pub fn is_ducked(&self) -> bool {
+        self.duck.is_some()
+    }
+
+    /// Start or end ducking, a start while already ducked extends it.
+    pub fn set_active(&mut self, active: bool, now: Instant, settings: &DuckingSettings) {
+        match &mut self.duck {
+            Some(duck) if duck.active == active && active => duck.requested = now,
+            Some(duck) if duck.active == active => (),
+            Some(duck) => {
+                *duck = Duck {
+                    active,
+                    changed: now,
+                    from: Self::factor(*duck, now, settings),
+                    requested: if active { now } else { duck.requested },
+                };
+            }
+            None if active && settings.factor().is_some() => {
+                self.duck = Some(Duck {
+                    active,
+                    changed: now,
+                    from: 1.0,
+                    requested: now,
+                });
+            }
+            None => (),
+        }
+    }
+
+    /// End ducking at once, returns whether the volume was lowered and needs to be restored.
+    pub fn cancel(&mut self) -> bool {
+        self.duck.take().is_some()
+    }
+
+    /// Get the volume to apply for `volume` as set by the user, none if not ducked.
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        volume: Volume,
+        settings: &DuckingSettings,
+    ) -> Option<Volume> {
+        let duck = self.duck?;
+        if duck.active && now.saturating_duration_since(duck.requested) >= settings.max() {
+            // the end was never requested, like when the announcement crashed
+            self.set_active(false, now, settings);
+        }
+        let duck = self.duck?;
+
+        let ramped = now.saturating_duration_since(duck.changed) >= settings.ramp();
+        if !duck.active && ramped {
+            self.duck = None;
+            return Some(volume);
+        }
+
+        let factor = Self::factor(duck, now, settings);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Some((f64::from(volume) * factor).round() as Volume)
+    }
+
+    /// Get the volume factor of `duck` at `now`, ramping from its last change.
+    fn factor(duck: Duck, now: Instant, settings: &DuckingSettings) -> f64 {
+        let target = if duck.active {
+            settings.factor().unwrap_or(1.0)
+        } else {
+            1.0
+        };
+        let ramp = settings.ramp();
+        let progress = if ramp.is_zero() {
+            1.0
+        } else {
+            (now.saturating_duration_since(duck.changed).as_secs_f64() / ramp.as_secs_f64())
+                .min(1.0)
+        };
+
+        duck.from + (target - duck.from) * progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use termusiclib::config::v2::server::ducking::DuckingSettings;
+
+    use super::Ducker;
+
+    #[test]
+    fn should_ramp_down_and_up() {
+        let settings = DuckingSettings {
+            attenuation_db: 6,
+            ramp_ms: 200,
+            max_secs: 30,
+        };
+        let mut ducker = Ducker::default();
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        assert_eq!(ducker.tick(at(0), 80, &settings), None);
+        ducker.set_active(true, at(0), &settings);
+        assert!(ducker.is_ducked());
+        assert_eq!(ducker.tick(at(0), 80, &settings), Some(80));
+        assert_eq!(ducker.tick(at(100), 80, &settings), Some(60));
+        assert_eq!(ducker.tick(at(200), 80, &settings), Some(40));
+        assert_eq!(ducker.tick(at(900), 80, &settings), Some(40));
+
+        ducker.set_active(false, at(1000), &settings);
+        assert_eq!(ducker.tick(at(1000), 80, &settings), Some(40));
+        assert_eq!(ducker.tick(at(1100), 80, &settings), Some(60));
+        assert_eq!(ducker.tick(at(1200), 80, &settings), Some(80));
+        assert!(!ducker.is_ducked());
+    }
+
+    #[test]
+    fn should_end_after_max() {
+        let settings = DuckingSettings {
+            attenuation_db: 6,
+            ramp_ms: 0,
+            max_secs: 10,
+        };
+        let mut ducker = Ducker::default();
+        let start = Instant::now();
+
+        ducker.set_active(true, start, &settings);
+        assert_eq!(ducker.tick(start, 80, &settings), Some(40));
+        // a new request extends it
+        ducker.set_active(true, start + Duration::from_secs(5), &settings);
+        assert_eq!(
+            ducker.tick(start + Duration::from_secs(11), 80, &settings),
+            Some(40)
+        );
+        assert_eq!(
+            ducker.tick(start + Duration::from_secs(15), 80, &settings),
+            Some(80)
+        );
+        assert!(!ducker.is_ducked());
+    }
+
+    #[test]
+    fn should_not_duck_if_disabled() {
+        let settings = DuckingSettings {
+            attenuation_db: 0,
+            ..DuckingSettings::default()
+        };
+        let mut ducker = Ducker::default();
+
+        ducker.set_active(true, Instant::now(), &settings);
+        assert!(!ducker.is_ducked());
+        assert_eq!(ducker.tick(Instant::now(), 80, &settings), None);
+    }
+}
@@ -0,0 +1,402 @@
+//! Announce the server in Home Assistant with MQTT discovery, with the playback state as sensors and buttons and a
+//! volume slider to control it.
+//!
+//! The MQTT integration of Home Assistant has no `media_player` platform, so the server shows up as a device with these
+//! entities instead, which works without custom components.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::{Value, json};
+use termusiclib::config::SharedServerSettings;
+use termusiclib::config::v2::server::home_assistant::HomeAssistantSettings;
+use termusiclib::credentials::CREDENTIALS;
+use termusicplayback::{PlayerCmd, PlayerCmdSender, SharedPlaylist, Volume};
+use tokio::io::AsyncWriteExt as _;
+use tokio::net::TcpStream;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+
+use crate::PlayerStats;
+use crate::mqtt::{self, ConnectOptions, LastWill, Packet};
+use crate::stream_overlay::NowPlayingInfo;
+
+/// The keep alive sent to the broker, in seconds.
+const KEEP_ALIVE: u16 = 60;
+
+/// How often the state is checked for changes to publish.
+const STATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait before reconnecting after the connection was lost.
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Get the [`CREDENTIALS`] key for the broker on `host`.
+///
+/// The secret is `user:password` (or just `user`).
+fn credential_key(host: &str) -> String {
+    format!("mqtt:{host}")
+}
+
+/// The topics of one instance, all below [`HomeAssistantSettings::base_topic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Topics {
+    availability: String,
+    state: String,
+    command: String,
+    volume_set: String,
+}
+
+impl Topics {
+    fn new(settings: &HomeAssistantSettings) -> Self {
+        let base = settings.base_topic();
+        Self {
+            availability: format!("{base}/availability"),
+            state: format!("{base}/state"),
+            command: format!("{base}/command"),
+            volume_set: format!("{base}/volume/set"),
+        }
+    }
+}
+
+/// The state published on [`Topics::state`], which all sensors read their value from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct PlayerState {
+    /// `playing`, `paused` or `idle`
+    state: &'static str,
+    title: String,
+    artist: String,
+    album: String,
+    duration_secs: u64,
+    volume: Volume,
+}
+
+impl PlayerState {
+    fn new(playlist: &SharedPlaylist, playerstats: &Mutex<PlayerStats>) -> Self {
+        let playlist = playlist.read();
+        let stats = playerstats.lock();
+        let info = NowPlayingInfo::new(playlist.current_track(), playlist.status(), &stats);
+
+        Self {
+            state: if info.status == "stopped" {
+                "idle"
+            } else {
+                info.status
+            },
+            title: info.title,
+            artist: info.artist,
+            album: info.album,
+            duration_secs: info.duration_secs,
+            volume: stats.volume,
+        }
+    }
+}
+
+/// Get the discovery messages of all entities, as the topic and the config.
+fn discovery_messages(settings: &HomeAssistantSettings, topics: &Topics) -> Vec<(String, Value)> {
+    let node_id = &settings.node_id;
+    let device = json!({
+        "identifiers": [node_id],
+        "name": settings.name,
+        "manufacturer": "termusic",
+        "model": "termusic-server",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+
+    let sensor = |key: &str, name: &str, icon: &str| {
+        json!({
+            "name": name,
+            "icon": icon,
+            "state_topic": topics.state,
+            "value_template": format!("{{{{ value_json.{key} }}}}"),
+        })
+    };
+    let button = |name: &str, icon: &str, payload: &str| {
+        json!({
+            "name": name,
+            "icon": icon,
+            "command_topic": topics.command,
+            "payload_press": payload,
+        })
+    };
+
+    let entities = [
+        ("sensor", "state", sensor("state", "State", "mdi:music")),
+        (
+            "sensor",
+            "title",
+            sensor("title", "Title", "mdi:music-note"),
+        ),
+        (
+            "sensor",
+            "artist",
+            sensor("artist", "Artist", "mdi:account-music"),
+        ),
+        ("sensor", "album", sensor("album", "Album", "mdi:album")),
+        (
+            "button",
+            "play_pause",
+            button("Play/Pause", "mdi:play-pause", "play_pause"),
+        ),
+        ("button", "next", button("Next", "mdi:skip-next", "next")),
+        (
+            "button",
+            "previous",
+            button("Previous", "mdi:skip-previous", "previous"),
+        ),
+        (
+            "number",
+            "volume",
+            json!({
+                "name": "Volume",
+                "icon": "mdi:volume-high",
+                "command_topic": topics.volume_set,
+                "state_topic": topics.state,
+                "value_template": "{{ value_json.volume }}",
+                "min": 0,
+                "max": 100,
+                "step": 1,
+                "mode": "slider",
+            }),
+        ),
+    ];
+
+    entities
+        .into_iter()
+        .map(|(component, object_id, mut config)| {
+            let unique_id = format!("{node_id}_{object_id}");
+            config["unique_id"] = json!(unique_id);
+            config["object_id"] = json!(unique_id);
+            config["availability_topic"] = json!(topics.availability);
+            config["device"] = device.clone();
+            let topic = format!(
+                "{}/{component}/{node_id}/{object_id}/config",
+                settings.discovery_prefix
+            );
+
+            (topic, config)
+        })
+        .collect()
+}
+
+/// Get the command for a message received on `topic`, `None` if it is not a known command.
+fn command_for(topics: &Topics, topic: &str, payload: &[u8]) -> Option<PlayerCmd> {
+    let payload = std::str::from_utf8(payload).ok()?.trim();
+
+    if topic == topics.volume_set {
+        // Home Assistant sends numbers as floats, like `42.0`
+        let volume = payload.parse::<f64>().ok()?.round().clamp(0.0, 100.0);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        return Some(PlayerCmd::VolumeSet(volume as Volume));
+    }
+    if topic != topics.command {
+        return None;
+    }
+
+    match payload {
+        "play" => Some(PlayerCmd::Play),
+        "pause" => Some(PlayerCmd::Pause),
+        "play_pause" => Some(PlayerCmd::TogglePause),
+        "next" => Some(PlayerCmd::SkipNext),
+        "previous" => Some(PlayerCmd::SkipPrevious),
+        _ => None,
+    }
+}
+
+/// Start the Home Assistant integration, if enabled in the config.
+///
+/// Connecting is retried in the background, so a unavailable broker does not stop the server from starting.
+pub fn start_home_assistant(
+    config: &SharedServerSettings,
+    cmd_tx: PlayerCmdSender,
+    playlist: SharedPlaylist,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    cancel_token: CancellationToken,
+) {
+    let settings = config.read().settings.home_assistant.clone();
+    if !settings.enable {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                res = run_connection(&settings, &cmd_tx, &playlist, &playerstats, &cancel_token) => {
+                    match res {
+                        // only returns successfully if cancelled
+                        Ok(()) => break,
+                        Err(err) => warn!("Home Assistant MQTT connection to \"{}:{}\" failed: {err:#}", settings.broker_host, settings.broker_port),
+                    }
+                },
+                () = cancel_token.cancelled() => break,
+            }
+
+            select! {
+                () = tokio::time::sleep(RECONNECT_DELAY) => (),
+                () = cancel_token.cancelled() => break,
+            }
+        }
+    });
+}
+
+/// Connect to the broker, announce all entities and keep their state up to date until cancelled or disconnected.
+async fn run_connection(
+    settings: &HomeAssistantSettings,
+    cmd_tx: &PlayerCmdSender,
+    playlist: &SharedPlaylist,
+    playerstats: &Mutex<PlayerStats>,
+    cancel_token: &CancellationToken,
+) -> Result<()> {
+    let topics = Topics::new(settings);
+    let login = CREDENTIALS
+        .get(&credential_key(&settings.broker_host))
+        .context("look up the login")?;
+    let (username, password) = match login.as_deref().map(|v| v.split_once(':')) {
+        Some(Some((user, password))) => (Some(user), Some(password)),
+        Some(None) => (login.as_deref(), None),
+        None => (None, None),
+    };
+
+    let stream = TcpStream::connect((settings.broker_host.as_str(), settings.broker_port))
+        .await
+        .context("connect")?;
+    let (mut reader, mut writer) = stream.into_split();
+
+    writer
+        .write_all(&mqtt::connect(&ConnectOptions {
+            client_id: &format!("termusic-{}", settings.node_id),
+            username,
+            password,
+            keep_alive: KEEP_ALIVE,
+            last_will: Some(LastWill {
+                topic: &topics.availability,
+                payload: b"offline",
+            }),
+        }))
+        .await?;
+    match mqtt::read_packet(&mut reader).await? {
+        Packet::ConnAck { return_code: 0 } => (),
+        Packet::ConnAck { return_code } => bail!("Connection refused with code {return_code}"),
+        packet => bail!("Expected CONNACK, got {packet:?}"),
+    }
+    info!(
+        "Connected to MQTT broker \"{}:{}\" for Home Assistant",
+        settings.broker_host, settings.broker_port
+    );
+
+    for (topic, config) in discovery_messages(settings, &topics) {
+        publish(&mut writer, &topic, &serde_json::to_vec(&config)?).await?;
+    }
+    publish(&mut writer, &topics.availability, b"online").await?;
+    writer
+        .write_all(&mqtt::subscribe(1, &[&topics.command, &topics.volume_set]))
+        .await?;
+
+    // reading a packet is not cancel-safe, so it is done in its own task instead of in the select below
+    let reader_topics = topics.clone();
+    let reader_cmd_tx = cmd_tx.clone();
+    let mut reader_task = tokio::spawn(async move {
+        loop {
+            if let Packet::Publish { topic, payload } = mqtt::read_packet(&mut reader).await? {
+                match command_for(&reader_topics, &topic, &payload) {
+                    Some(cmd) => reader_cmd_tx.send(cmd).context("send command")?,
+                    None => debug!("Unknown Home Assistant command on \"{topic}\""),
+                }
+            }
+        }
+    });
+
+    let mut state_interval = tokio::time::interval(STATE_INTERVAL);
+    let mut ping_interval = tokio::time::interval(Duration::from_secs(u64::from(KEEP_ALIVE / 2)));
+    let mut last_state = None;
+    let res = loop {
+        select! {
+            _ = state_interval.tick() => {
+                let state = PlayerState::new(playlist, playerstats);
+                if last_state.as_ref() != Some(&state) {
+                    publish(&mut writer, &topics.state, &serde_json::to_vec(&state)?).await?;
+                    last_state = Some(state);
+                }
+            },
+            _ = ping_interval.tick() => {
+                writer.write_all(&mqtt::PINGREQ).await?;
+            },
+            res = &mut reader_task => {
+                break res.context("reader task").and_then(|v| v);
+            },
+            () = cancel_token.cancelled() => {
+                publish(&mut writer, &topics.availability, b"offline").await?;
+                writer.write_all(&mqtt::DISCONNECT).await?;
+                break Ok(());
+            }
+        }
+    };
+    reader_task.abort();
+
+    res
+}
+
+/// Publish a retained message, so that Home Assistant has it after restarting.
+async fn publish(writer: &mut OwnedWriteHalf, topic: &str, payload: &[u8]) -> Result<()> {
+    writer
+        .write_all(&mqtt::publish(topic, payload, true))
+        .await
+        .with_context(|| format!("publish to \"{topic}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use termusiclib::config::v2::server::home_assistant::HomeAssistantSettings;
+    use termusicplayback::PlayerCmd;
+
+    use super::{Topics, command_for, discovery_messages};
+
+    #[test]
+    fn should_map_commands() {
+        let topics = Topics::new(&HomeAssistantSettings::default());
+        assert_eq!(topics.command, "termusic/termusic/command");
+
+        assert!(matches!(
+            command_for(&topics, &topics.command, b"play_pause"),
+            Some(PlayerCmd::TogglePause)
+        ));
+        assert!(matches!(
+            command_for(&topics, &topics.volume_set, b"42.0"),
+            Some(PlayerCmd::VolumeSet(42))
+        ));
+        assert!(matches!(
+            command_for(&topics, &topics.volume_set, b"150"),
+            Some(PlayerCmd::VolumeSet(100))
+        ));
+        assert!(command_for(&topics, &topics.command, b"unknown").is_none());
+        assert!(command_for(&topics, &topics.state, b"play").is_none());
+    }
+
+    #[test]
+    fn should_announce_entities() {
+        let settings = HomeAssistantSettings::default();
+        let messages = discovery_messages(&settings, &Topics::new(&settings));
+
+        let (topic, config) = messages
+            .iter()
+            .find(|(topic, _)| topic.ends_with("/volume/config"))
+            .unwrap();
+        assert_eq!(topic, "homeassistant/number/termusic/volume/config");
+        assert_eq!(config["unique_id"], "termusic_volume");
+        assert_eq!(config["command_topic"], "termusic/termusic/volume/set");
+        assert_eq!(
+            config["availability_topic"],
+            "termusic/termusic/availability"
+        );
+        assert_eq!(config["device"]["identifiers"][0], "termusic");
+
+        let (_, config) = messages
+            .iter()
+            .find(|(topic, _)| topic.ends_with("/title/config"))
+            .unwrap();
+        assert_eq!(config["value_template"], "{{ value_json.title }}");
+    }
+}
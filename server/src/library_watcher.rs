@@ -0,0 +1,357 @@
+//! Watch the music directories and apply changes on disk to the database, see [`MetadataSettings::watch_library`](termusiclib::config::v2::server::metadata::MetadataSettings::watch_library).
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use termusiclib::config::SharedServerSettings;
+use termusiclib::new_database::{Database, PathChange};
+use termusiclib::player::UpdateEvents;
+use termusiclib::utils::filetype_supported;
+use termusicplayback::StreamTX;
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// How long no events need to arrive before the changes are applied, as copying a album causes many events.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+/// How often to check if the changes settled.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Start watching the music directories, if enabled, until `cancel_token` is cancelled.
+pub fn start_library_watcher(
+    config: &SharedServerSettings,
+    stream_tx: StreamTX,
+    cancel_token: CancellationToken,
+) {
+    let dirs: Vec<PathBuf> = {
+        let config_read = config.read();
+        if !config_read.settings.metadata.watch_library {
+            return;
+        }
+
+        config_read
+            .settings
+            .player
+            .music_dirs
+            .iter()
+            .map(|v| shellexpand::path::tilde(v).into_owned())
+            .collect()
+    };
+
+    let db = match Database::new_default_path() {
+        Ok(v) => v,
+        Err(err) => {
+            warn!("Opening the database failed, not watching the music directories: {err:#}");
+            return;
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let watcher = match watch_dirs(&dirs, tx) {
+        Ok(v) => v,
+        Err(err) => {
+            warn!("Watching the music directories failed: {err:#}");
+            return;
+        }
+    };
+
+    let config = config.clone();
+    tokio::spawn(async move {
+        // keep the watcher for as long as the task runs, dropping it stops watching
+        let _watcher = watcher;
+        let mut pending = PendingChanges::default();
+
+        loop {
+            select! {
+                Some(res) = rx.recv() => match res {
+                    Ok(event) => {
+                        for change in classify_event(event) {
+                            pending.push(change, Instant::now());
+                        }
+                    }
+                    Err(err) => warn!("Error watching the music directories: {err:#}"),
+                },
+                () = tokio::time::sleep(CHECK_INTERVAL) => (),
+                () = cancel_token.cancelled() => break,
+            }
+
+            if let Some(changes) = pending.take_settled(Instant::now()) {
+                apply_changes(&db, &config, &stream_tx, changes);
+            }
+        }
+    });
+}
+
+/// Create a watcher for all `dirs`, sending its events to `tx`.
+///
+/// Directories that cannot be watched (like not existing ones) are skipped with a warning.
+fn watch_dirs(
+    dirs: &[PathBuf],
+    tx: mpsc::UnboundedSender<notify::Result<Event>>,
+) -> Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    })
+    .context("create watcher")?;
+
+    for dir in dirs {
+        // the database stores the resolved paths, and events are reported below the watched path
+        let dir = match dir.canonicalize() {
+            Ok(v) => v,
+            Err(err) => {
+                warn!("Error watching {}: {err:#}", dir.display());
+                continue;
+            }
+        };
+
+        match watcher.watch(&dir, RecursiveMode::Recursive) {
+            Ok(()) => info!("Watching {} for changes", dir.display()),
+            Err(err) => warn!("Error watching {}: {err:#}", dir.display()),
+        }
+    }
+
+    Ok(watcher)
+}
+
+/// Apply `changes` to the database and tell clients which directories changed.
+fn apply_changes(
+    db: &Database,
+    config: &SharedServerSettings,
+    stream_tx: &StreamTX,
+    changes: Vec<PathChange>,
+) {
+    debug!(
+        "Applying {} changes in the music directories",
+        changes.len()
+    );
+
+    let dirs = changed_dirs(&changes);
+    db.scan_changes(changes, &config.read());
+
+    // the library tree is read from disk, so it does not need to wait for the database
+    let _ = stream_tx.send(UpdateEvents::LibraryChanged(dirs));
+}
+
+/// Get the changes to apply for a watcher event.
+///
+/// Ignores changes to unsupported files, like the temporary files of a tag editor.
+fn classify_event(event: Event) -> Vec<PathChange> {
+    let mut paths = event.paths.into_iter();
+
+    match event.kind {
+        EventKind::Create(_)
+        | EventKind::Modify(
+            ModifyKind::Data(_) | ModifyKind::Any | ModifyKind::Name(RenameMode::To),
+        ) => paths
+            .filter(|v| is_relevant(v))
+            .map(PathChange::Modified)
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            match (paths.next(), paths.next()) {
+                (Some(from), Some(to)) => vec![PathChange::Renamed { from, to }],
+                _ => Vec::new(),
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+            paths.map(PathChange::Removed).collect()
+        }
+        // some platforms cannot tell which side of the rename a path is
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .filter_map(|v| {
+                if !v.exists() {
+                    Some(PathChange::Removed(v))
+                } else if is_relevant(&v) {
+                    Some(PathChange::Modified(v))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        // access and permission changes do not change the library
+        _ => Vec::new(),
+    }
+}
+
+/// Check if `path` is a directory or a file that can be added to the library.
+fn is_relevant(path: &Path) -> bool {
+    filetype_supported(path) || path.is_dir()
+}
+
+/// Get the parent directories of all changed paths, which are the directories whose content changed.
+fn changed_dirs(changes: &[PathChange]) -> Vec<PathBuf> {
+    let mut dirs = BTreeSet::new();
+
+    for change in changes {
+        let paths = match change {
+            PathChange::Modified(path) | PathChange::Removed(path) => [Some(path), None],
+            PathChange::Renamed { from, to } => [Some(from), Some(to)],
+        };
+
+        dirs.extend(
+            paths
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.parent())
+                .map(Path::to_path_buf),
+        );
+    }
+
+    dirs.into_iter().collect()
+}
+
+/// Changes collected from the watcher, until no new events arrived for [`SETTLE_TIME`].
+#[derive(Debug, Default)]
+struct PendingChanges {
+    /// In the order they happened, each only once
+    changes: Vec<PathChange>,
+    last_event: Option<Instant>,
+}
+
+impl PendingChanges {
+    /// Add a change that happened at `now`.
+    ///
+    /// A already pending equal change is moved to the end, as only the order of the last changes matters.
+    fn push(&mut self, change: PathChange, now: Instant) {
+        // a full rename replaces the separate events some platforms send for each side
+        if let PathChange::Renamed { from, to } = &change {
+            self.changes.retain(|v| match v {
+                PathChange::Removed(path) => path != from,
+                PathChange::Modified(path) => path != to,
+                PathChange::Renamed { .. } => true,
+            });
+        }
+
+        self.changes.retain(|v| *v != change);
+        self.changes.push(change);
+        self.last_event = Some(now);
+    }
+
+    /// Take all changes, if no new change happened for [`SETTLE_TIME`] before `now`.
+    fn take_settled(&mut self, now: Instant) -> Option<Vec<PathChange>> {
+        let last_event = self.last_event?;
+        if now.duration_since(last_event) < SETTLE_TIME {
+            return None;
+        }
+
+        self.last_event = None;
+        Some(std::mem::take(&mut self.changes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    use notify::event::{CreateKind, DataChange, MetadataKind, ModifyKind, RemoveKind, RenameMode};
+    use notify::{Event, EventKind};
+    use termusiclib::new_database::PathChange;
+
+    use super::{PendingChanges, SETTLE_TIME, changed_dirs, classify_event};
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        paths.iter().fold(Event::new(kind), |ev, path| {
+            ev.add_path(PathBuf::from(path))
+        })
+    }
+
+    #[test]
+    fn should_classify_events() {
+        assert_eq!(
+            classify_event(event(
+                EventKind::Create(CreateKind::File),
+                &["/music/a.mp3", "/music/a.mp3.part"]
+            )),
+            vec![PathChange::Modified(PathBuf::from("/music/a.mp3"))]
+        );
+        assert_eq!(
+            classify_event(event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &["/music/a.flac"]
+            )),
+            vec![PathChange::Modified(PathBuf::from("/music/a.flac"))]
+        );
+        assert_eq!(
+            classify_event(event(EventKind::Remove(RemoveKind::Any), &["/music/album"])),
+            vec![PathChange::Removed(PathBuf::from("/music/album"))]
+        );
+        assert_eq!(
+            classify_event(event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/music/a.mp3", "/music/b.mp3"]
+            )),
+            vec![PathChange::Renamed {
+                from: PathBuf::from("/music/a.mp3"),
+                to: PathBuf::from("/music/b.mp3")
+            }]
+        );
+        assert_eq!(
+            classify_event(event(
+                EventKind::Modify(ModifyKind::Metadata(MetadataKind::Permissions)),
+                &["/music/a.mp3"]
+            )),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn should_wait_for_changes_to_settle() {
+        let start = Instant::now();
+        let mut pending = PendingChanges::default();
+        assert_eq!(pending.take_settled(start), None);
+
+        let a = PathChange::Modified(PathBuf::from("/music/a.mp3"));
+        let b = PathChange::Removed(PathBuf::from("/music/b.mp3"));
+        pending.push(a.clone(), start);
+        pending.push(b.clone(), start + Duration::from_secs(1));
+        pending.push(a.clone(), start + Duration::from_secs(1));
+
+        assert_eq!(pending.take_settled(start + SETTLE_TIME), None);
+        assert_eq!(
+            pending.take_settled(start + Duration::from_secs(1) + SETTLE_TIME),
+            Some(vec![b, a])
+        );
+        assert_eq!(pending.take_settled(start + SETTLE_TIME * 2), None);
+    }
+
+    #[test]
+    fn should_merge_rename_sides() {
+        let now = Instant::now();
+        let mut pending = PendingChanges::default();
+
+        let renamed = PathChange::Renamed {
+            from: PathBuf::from("/music/a"),
+            to: PathBuf::from("/music/b"),
+        };
+        pending.push(PathChange::Removed(PathBuf::from("/music/a")), now);
+        pending.push(PathChange::Modified(PathBuf::from("/music/b")), now);
+        pending.push(renamed.clone(), now);
+
+        assert_eq!(pending.take_settled(now + SETTLE_TIME), Some(vec![renamed]));
+    }
+
+    #[test]
+    fn should_list_changed_dirs() {
+        let changes = [
+            PathChange::Modified(PathBuf::from("/music/album/a.mp3")),
+            PathChange::Removed(PathBuf::from("/music/album/b.mp3")),
+            PathChange::Renamed {
+                from: PathBuf::from("/music/old"),
+                to: PathBuf::from("/other/new"),
+            },
+        ];
+
+        assert_eq!(
+            changed_dirs(&changes),
+            vec![
+                PathBuf::from("/music"),
+                PathBuf::from("/music/album"),
+                PathBuf::from("/other")
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Track the time listened since the last break, and fade out and pause once the configured limit is reached.
+
+use std::time::{Duration, Instant};
+
+use termusiclib::config::v2::server::listening_limit::ListeningLimitSettings;
+use termusiclib::player::ListeningTimeInfo;
+use termusicplayback::Volume;
+
+/// Ignore ticks further apart than this, like after the system was suspended.
+const MAX_TICK_GAP: Duration = Duration::from_secs(5);
+
+/// What the player loop should do after a [`ListeningTimer::tick`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerAction {
+    None,
+    /// The listened minutes changed
+    Report(ListeningTimeInfo),
+    /// Set the volume temporarily while fading out
+    Fade(Volume),
+    /// The fade was interrupted, restore the volume
+    Restore(Volume),
+    /// Pause playback and restore the volume, the break started
+    Break {
+        volume: Volume,
+        info: ListeningTimeInfo,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    started: Instant,
+    /// The volume before the fade, to restore after the pause
+    volume: Volume,
+}
+
+#[derive(Debug, Default)]
+pub struct ListeningTimer {
+    /// The time played since the last break
+    listened: Duration,
+    last_tick: Option<Instant>,
+    /// The listened minutes last reported to the clients
+    reported_mins: u64,
+    fade: Option<Fade>,
+}
+
+impl ListeningTimer {
+    /// Check if the volume is currently lowered by a fade out.
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Get the current state to report to the clients.
+    pub fn info(&self, settings: &ListeningLimitSettings) -> ListeningTimeInfo {
+        ListeningTimeInfo {
+            listened: self.listened,
+            limit: settings.limit(),
+            break_started: false,
+        }
+    }
+
+    /// Count the time since the last tick if `playing` and decide what to do, `volume` is the current volume.
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        playing: bool,
+        volume: Volume,
+        settings: &ListeningLimitSettings,
+    ) -> TimerAction {
+        let elapsed = self
+            .last_tick
+            .map(|v| now.saturating_duration_since(v))
+            .filter(|v| *v <= MAX_TICK_GAP)
+            .unwrap_or_default();
+        self.last_tick = Some(now);
+
+        if !playing {
+            // paused by the user while fading, the break is not forced then
+            return match self.fade.take() {
+                Some(fade) => TimerAction::Restore(fade.volume),
+                None => TimerAction::None,
+            };
+        }
+        self.listened += elapsed;
+
+        // the listening time is only reported with a limit, so that the clients only show it if it matters
+        let Some(limit) = settings.limit() else {
+            return match self.fade.take() {
+                Some(fade) => TimerAction::Restore(fade.volume),
+                None => TimerAction::None,
+            };
+        };
+
+        let fade_len = settings.fade();
+        if self.fade.is_none() && self.listened + fade_len >= limit {
+            self.fade = Some(Fade {
+                started: now,
+                volume,
+            });
+        }
+        let Some(fade) = self.fade else {
+            return self.report(settings);
+        };
+
+        let faded = now.saturating_duration_since(fade.started);
+        if faded >= fade_len {
+            let info = ListeningTimeInfo {
+                break_started: true,
+                ..self.info(settings)
+            };
+            *self = Self {
+                last_tick: self.last_tick,
+                ..Self::default()
+            };
+            return TimerAction::Break {
+                volume: fade.volume,
+                info,
+            };
+        }
+
+        let remaining = 1.0 - faded.as_secs_f64() / fade_len.as_secs_f64();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        TimerAction::Fade((f64::from(fade.volume) * remaining) as Volume)
+    }
+
+    /// Report the listened time if the minutes changed.
+    fn report(&mut self, settings: &ListeningLimitSettings) -> TimerAction {
+        let mins = self.listened.as_secs() / 60;
+        if mins == self.reported_mins {
+            return TimerAction::None;
+        }
+        self.reported_mins = mins;
+
+        TimerAction::Report(self.info(settings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use termusiclib::config::v2::server::listening_limit::ListeningLimitSettings;
+    use termusiclib::player::ListeningTimeInfo;
+
+    use super::{ListeningTimer, TimerAction};
+
+    #[test]
+    fn should_fade_and_break_at_the_limit() {
+        let settings = ListeningLimitSettings {
+            limit_mins: 1,
+            fade_secs: 10,
+        };
+        let mut timer = ListeningTimer::default();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(timer.tick(at(0), true, 80, &settings), TimerAction::None);
+        for secs in 1..50 {
+            assert_eq!(timer.tick(at(secs), true, 80, &settings), TimerAction::None);
+        }
+        // pausing does not count
+        assert_eq!(timer.tick(at(55), false, 80, &settings), TimerAction::None);
+
+        assert_eq!(
+            timer.tick(at(56), true, 80, &settings),
+            TimerAction::Fade(80)
+        );
+        assert!(timer.is_fading());
+        assert_eq!(
+            timer.tick(at(61), true, 80, &settings),
+            TimerAction::Fade(40)
+        );
+        assert_eq!(
+            timer.tick(at(66), true, 40, &settings),
+            TimerAction::Break {
+                volume: 80,
+                info: ListeningTimeInfo {
+                    listened: Duration::from_secs(60),
+                    limit: Some(Duration::from_secs(60)),
+                    break_started: true,
+                }
+            }
+        );
+        assert!(!timer.is_fading());
+        assert_eq!(timer.info(&settings).listened, Duration::ZERO);
+    }
+
+    #[test]
+    fn should_restore_volume_if_paused_while_fading() {
+        let settings = ListeningLimitSettings {
+            limit_mins: 1,
+            fade_secs: 60,
+        };
+        let mut timer = ListeningTimer::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            timer.tick(start, true, 50, &settings),
+            TimerAction::Fade(50)
+        );
+        assert_eq!(
+            timer.tick(start + Duration::from_secs(1), false, 49, &settings),
+            TimerAction::Restore(50)
+        );
+        assert!(!timer.is_fading());
+    }
+
+    #[test]
+    fn should_report_minutes() {
+        let settings = ListeningLimitSettings {
+            limit_mins: 60,
+            fade_secs: 10,
+        };
+        let mut timer = ListeningTimer::default();
+        let start = Instant::now();
+
+        for secs in 0..60 {
+            assert_eq!(
+                timer.tick(start + Duration::from_secs(secs), true, 50, &settings),
+                TimerAction::None
+            );
+        }
+        assert_eq!(
+            timer.tick(start + Duration::from_secs(60), true, 50, &settings),
+            TimerAction::Report(ListeningTimeInfo {
+                listened: Duration::from_secs(60),
+                limit: Some(Duration::from_secs(3600)),
+                break_started: false,
+            })
+        );
+        // a long gap, like a suspend, is not counted
+        assert_eq!(
+            timer.tick(start + Duration::from_secs(600), true, 50, &settings),
+            TimerAction::None
+        );
+
+        // nothing is reported without a limit
+        assert_eq!(
+            timer.tick(
+                start + Duration::from_secs(721),
+                true,
+                50,
+                &ListeningLimitSettings::default()
+            ),
+            TimerAction::None
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! macOS specific setup, see [`run_with_main_loop`].
+
+use std::thread;
+
+use anyhow::{Result, anyhow};
+use core_foundation::runloop::CFRunLoop;
+use dispatch::Queue;
+
+/// Run `fun` on a new thread while the main thread runs the main run loop, returning the result of `fun`.
+///
+/// The Now-Playing info center and remote command center (used for the media keys, Control Center, Touch Bar and headset controls)
+/// dispatch their events to the main queue, which is only processed while the main thread runs its run loop.
+pub fn run_with_main_loop<F>(fun: F) -> Result<()>
+where
+    F: FnOnce() -> Result<()> + Send + 'static,
+{
+    let handle = thread::Builder::new()
+        .name("main worker".to_string())
+        .spawn(move || {
+            let res = fun();
+            // stop the run loop from within itself, as "CFRunLoop" may not be sent to other threads
+            Queue::main().exec_async(|| CFRunLoop::get_current().stop());
+            res
+        })?;
+
+    // the main run loop is never empty, as it always contains the main dispatch queue
+    CFRunLoop::run_current();
+
+    handle
+        .join()
+        .map_err(|_| anyhow!("main worker thread panicked"))?
+}
@@ -0,0 +1,142 @@
+//! User-defined macros, a named sequence of [`BusCommand`]s run at once, see [`MacroSettings`].
+//!
+//! All commands of a macro are run before any other received command, so that nothing can interleave with them.
+
+use std::collections::VecDeque;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use termusiclib::config::v2::server::ServerSettings;
+use termusiclib::config::v2::server::macros::MacroSettings;
+use termusicplayback::{PlayerCmd, PlayerCmdCallbackSender, PlayerCmdReciever, SharedPlaylist};
+
+use crate::command_bus::BusCommand;
+
+/// Get the commands of the macro `name`.
+///
+/// # Errors
+///
+/// - if there is no macro `name`
+/// - if any of its commands is invalid, or runs another macro
+pub fn macro_commands(settings: &ServerSettings, name: &str) -> Result<Vec<BusCommand>> {
+    let macro_settings = settings
+        .macros
+        .iter()
+        .find(|v| v.name == name)
+        .ok_or_else(|| anyhow!("No macro named {name:?}"))?;
+
+    parse_commands(macro_settings).with_context(|| format!("Macro {name:?}"))
+}
+
+/// Parse all commands of `macro_settings`.
+fn parse_commands(macro_settings: &MacroSettings) -> Result<Vec<BusCommand>> {
+    macro_settings
+        .commands
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            let command: BusCommand = serde_json::from_value(value.clone())
+                .with_context(|| format!("Command {}", idx + 1))?;
+            if matches!(command, BusCommand::Macro { .. }) {
+                // could otherwise run itself forever
+                bail!("Command {}: macros cannot run other macros", idx + 1);
+            }
+
+            Ok(command)
+        })
+        .collect()
+}
+
+/// The commands of the running macro that are still to run.
+#[derive(Debug, Default)]
+pub struct MacroQueue {
+    commands: VecDeque<BusCommand>,
+    /// The callback of [`PlayerCmd::RunMacro`], called once the last command ran.
+    done: PlayerCmdCallbackSender,
+}
+
+impl MacroQueue {
+    /// Queue `commands` to run next, calling `done` after the last one.
+    pub fn start(&mut self, commands: Vec<BusCommand>, done: PlayerCmdCallbackSender) {
+        if commands.is_empty() {
+            done.call();
+            return;
+        }
+        self.commands = commands.into();
+        self.done = done;
+    }
+
+    /// Get the next command to run: the next command of the running macro, otherwise the next received command.
+    ///
+    /// The rest of the macro is dropped if a command does not fit the playlist anymore, like a index out of bounds.
+    pub fn next_cmd(
+        &mut self,
+        cmd_rx: &mut PlayerCmdReciever,
+        playlist: &SharedPlaylist,
+    ) -> Option<(PlayerCmd, PlayerCmdCallbackSender)> {
+        while let Some(command) = self.commands.pop_front() {
+            let res = command.into_player_cmd(playlist.read().tracks());
+            match res {
+                Ok(cmd) if self.commands.is_empty() => {
+                    return Some((cmd, std::mem::take(&mut self.done)));
+                }
+                Ok(cmd) => return Some((cmd, PlayerCmdCallbackSender::default())),
+                Err(err) => {
+                    error!("Error in macro, skipping the rest of it: {err:#}");
+                    self.commands.clear();
+                    std::mem::take(&mut self.done).call();
+                }
+            }
+        }
+
+        cmd_rx.blocking_recv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use termusiclib::config::v2::server::ServerSettings;
+    use termusiclib::config::v2::server::macros::MacroSettings;
+
+    use super::macro_commands;
+    use crate::command_bus::BusCommand;
+
+    #[test]
+    fn should_parse_macros() {
+        let settings = ServerSettings {
+            macros: vec![
+                MacroSettings {
+                    name: "party".to_string(),
+                    commands: vec![
+                        json!({"cmd": "PlaylistClear"}),
+                        json!({"cmd": "PlaylistAddRandom", "tracks": 50}),
+                        json!({"cmd": "PlaylistShuffle"}),
+                        json!({"cmd": "Play"}),
+                    ],
+                },
+                MacroSettings {
+                    name: "broken".to_string(),
+                    commands: vec![json!({"cmd": "VolumeSet"})],
+                },
+                MacroSettings {
+                    name: "nested".to_string(),
+                    commands: vec![json!({"cmd": "Macro", "name": "party"})],
+                },
+            ],
+            ..ServerSettings::default()
+        };
+
+        assert_eq!(
+            macro_commands(&settings, "party").unwrap(),
+            vec![
+                BusCommand::PlaylistClear,
+                BusCommand::PlaylistAddRandom { tracks: 50 },
+                BusCommand::PlaylistShuffle,
+                BusCommand::Play,
+            ]
+        );
+        assert!(macro_commands(&settings, "broken").is_err());
+        assert!(macro_commands(&settings, "nested").is_err());
+        assert!(macro_commands(&settings, "unknown").is_err());
+    }
+}
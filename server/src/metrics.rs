@@ -0,0 +1,91 @@
+//! Minimal HTTP endpoint to expose [`METRICS`] for Prometheus scraping.
+
+use std::net::SocketAddr;
+
+use anyhow::{Context as _, Result};
+use termusiclib::config::SharedServerSettings;
+use termusiclib::metrics::{CONTENT_TYPE, METRICS};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+
+/// The maximal request head size we read, anything after is ignored.
+pub const MAX_REQUEST_SIZE: usize = 4096;
+
+/// Start the metrics endpoint, if enabled in the config.
+pub async fn start_metrics_endpoint(
+    config: &SharedServerSettings,
+    cancel_token: CancellationToken,
+) -> Result<()> {
+    let settings = config.read().settings.metrics.clone();
+    if !settings.enable {
+        return Ok(());
+    }
+
+    let addr = SocketAddr::from(&settings);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Error binding metrics address: {addr}"))?;
+    info!(
+        "Metrics listening on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                res = listener.accept() => {
+                    match res {
+                        Ok((stream, _)) => {
+                            tokio::spawn(async move {
+                                if let Err(err) = handle_connection(stream).await {
+                                    debug!("Error handling metrics request: {err:#}");
+                                }
+                            });
+                        }
+                        Err(err) => warn!("Error accepting metrics connection: {err}"),
+                    }
+                },
+                () = cancel_token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Handle a single HTTP request, only `GET /metrics` is served.
+async fn handle_connection(mut stream: TcpStream) -> Result<()> {
+    let mut buf = vec![0; MAX_REQUEST_SIZE];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+
+    let response = match request_path(&request) {
+        Some("/metrics") => {
+            let body = METRICS.render();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+/// Get the path of a `GET` request line, ignoring any query.
+pub fn request_path(request: &str) -> Option<&str> {
+    let mut parts = request.lines().next()?.split(' ');
+    if parts.next()? != "GET" {
+        return None;
+    }
+
+    parts.next()?.split('?').next()
+}
@@ -0,0 +1,262 @@
+//! A minimal MQTT 3.1.1 client, only supporting what the [Home Assistant](crate::home_assistant) integration needs:
+//! publishing and subscribing with QoS 0, retained messages and a last will.
+
+use anyhow::{Result, bail};
+use tokio::io::{AsyncRead, AsyncReadExt as _};
+
+/// The largest packet that is accepted, commands are small.
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+
+/// The `PINGREQ` packet, sent to keep the connection alive.
+pub const PINGREQ: [u8; 2] = [0xC0, 0];
+pub const DISCONNECT: [u8; 2] = [0xE0, 0];
+
+/// A message published to the broker when the connection is lost, always retained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastWill<'a> {
+    pub topic: &'a str,
+    pub payload: &'a [u8],
+}
+
+/// The options for the `CONNECT` packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectOptions<'a> {
+    pub client_id: &'a str,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    /// The keep alive in seconds, the broker disconnects if nothing was sent for 1.5 times of it
+    pub keep_alive: u16,
+    pub last_will: Option<LastWill<'a>>,
+}
+
+/// A packet received from the broker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    ConnAck {
+        /// `0` if the connection was accepted
+        return_code: u8,
+    },
+    Publish {
+        topic: String,
+        payload: Vec<u8>,
+    },
+    /// Any other packet, like `SUBACK` or `PINGRESP`, with its type
+    Other(u8),
+}
+
+/// Encode the `CONNECT` packet.
+pub fn connect(options: &ConnectOptions<'_>) -> Vec<u8> {
+    let mut flags = 0b0000_0010; // clean session
+    if options.username.is_some() {
+        flags |= 0b1000_0000;
+    }
+    if options.password.is_some() {
+        flags |= 0b0100_0000;
+    }
+    if options.last_will.is_some() {
+        // will flag and will retain, with QoS 0
+        flags |= 0b0010_0100;
+    }
+
+    let mut body = Vec::new();
+    put_str(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&options.keep_alive.to_be_bytes());
+    put_str(&mut body, options.client_id);
+    if let Some(will) = &options.last_will {
+        put_str(&mut body, will.topic);
+        put_bytes(&mut body, will.payload);
+    }
+    if let Some(username) = options.username {
+        put_str(&mut body, username);
+    }
+    if let Some(password) = options.password {
+        put_str(&mut body, password);
+    }
+
+    packet(0x10, &body)
+}
+
+/// Encode a `PUBLISH` packet with QoS 0.
+pub fn publish(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    put_str(&mut body, topic);
+    body.extend_from_slice(payload);
+
+    packet(0x30 | u8::from(retain), &body)
+}
+
+/// Encode a `SUBSCRIBE` packet for `topics`, with QoS 0.
+pub fn subscribe(packet_id: u16, topics: &[&str]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&packet_id.to_be_bytes());
+    for topic in topics {
+        put_str(&mut body, topic);
+        body.push(0);
+    }
+
+    packet(0x82, &body)
+}
+
+/// Read a single packet from `reader`.
+pub async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Packet> {
+    let header = reader.read_u8().await?;
+
+    let mut len: usize = 0;
+    for shift in (0..4).map(|v| v * 7) {
+        let byte = reader.read_u8().await?;
+        len |= usize::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        } else if shift == 21 {
+            bail!("Malformed remaining length");
+        }
+    }
+    if len > MAX_PACKET_SIZE {
+        bail!("Packet of {len} bytes is too large");
+    }
+
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).await?;
+
+    decode(header, &body)
+}
+
+/// Decode a packet from its fixed `header` byte and the rest of it.
+fn decode(header: u8, body: &[u8]) -> Result<Packet> {
+    match header >> 4 {
+        2 => {
+            let Some(return_code) = body.get(1) else {
+                bail!("CONNACK is too short");
+            };
+            Ok(Packet::ConnAck {
+                return_code: *return_code,
+            })
+        }
+        3 => {
+            let Some((len, rest)) = body.split_first_chunk::<2>() else {
+                bail!("PUBLISH is too short");
+            };
+            let len = usize::from(u16::from_be_bytes(*len));
+            if rest.len() < len {
+                bail!("PUBLISH topic is too long");
+            }
+            let (topic, mut payload) = rest.split_at(len);
+            // the packet identifier is only present for QoS 1 and 2
+            if (header >> 1) & 0b11 != 0 {
+                payload = payload.get(2..).unwrap_or_default();
+            }
+
+            Ok(Packet::Publish {
+                topic: String::from_utf8(topic.to_vec())?,
+                payload: payload.to_vec(),
+            })
+        }
+        kind => Ok(Packet::Other(kind)),
+    }
+}
+
+/// Build a packet from its fixed `header` byte and `body`, adding the remaining length.
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(body.len() + 5);
+    packet.push(header);
+
+    let mut len = body.len();
+    loop {
+        #[allow(clippy::cast_possible_truncation)] // masked to 7 bits
+        let mut byte = (len & 0x7F) as u8;
+        len >>= 7;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+
+    packet
+}
+
+/// Add a UTF-8 string with its length.
+fn put_str(buf: &mut Vec<u8>, value: &str) {
+    put_bytes(buf, value.as_bytes());
+}
+
+/// Add binary data with its length, truncated to the maximum of `u16`.
+fn put_bytes(buf: &mut Vec<u8>, value: &[u8]) {
+    let len = u16::try_from(value.len()).unwrap_or(u16::MAX);
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(&value[..usize::from(len)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectOptions, LastWill, Packet, connect, publish, read_packet};
+
+    #[test]
+    fn should_encode_connect() {
+        let packet = connect(&ConnectOptions {
+            client_id: "id",
+            username: Some("u"),
+            password: None,
+            keep_alive: 60,
+            last_will: Some(LastWill {
+                topic: "t",
+                payload: b"off",
+            }),
+        });
+
+        assert_eq!(
+            packet,
+            [
+                0x10,
+                25, // header and remaining length
+                0,
+                4,
+                b'M',
+                b'Q',
+                b'T',
+                b'T',
+                4,
+                0b1010_0110,
+                0,
+                60, // variable header
+                0,
+                2,
+                b'i',
+                b'd', // client id
+                0,
+                1,
+                b't',
+                0,
+                3,
+                b'o',
+                b'f',
+                b'f', // will
+                0,
+                1,
+                b'u', // username
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_roundtrip_publish() {
+        // a payload needing 2 bytes of remaining length
+        let payload = vec![b'a'; 200];
+        let packet = publish("a/b", &payload, true);
+        assert_eq!(packet[..3], [0x31, 0x80 | (205 & 0x7F), 1]);
+
+        let read = read_packet(&mut packet.as_slice()).await.unwrap();
+        assert_eq!(
+            read,
+            Packet::Publish {
+                topic: "a/b".to_string(),
+                payload
+            }
+        );
+    }
+}
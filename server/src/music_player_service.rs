@@ -3,20 +3,32 @@ use parking_lot::Mutex;
 use std::pin::Pin;
 use std::sync::Arc;
 use termusiclib::config::SharedServerSettings;
+use termusiclib::downloads::DownloadAction;
+use termusiclib::metrics::METRICS;
 use termusiclib::player::music_player_server::MusicPlayer;
 use termusiclib::player::playlist_helpers::{PlaylistPlaySpecific, PlaylistRemoveTrackType};
 use termusiclib::player::{
-    self, Empty, GaplessState, GetProgressResponse, PlayState, PlayerTime, PlaylistLoopMode,
-    PlaylistSwapTracks, PlaylistTracks, PlaylistTracksToAdd, PlaylistTracksToRemove, SpeedReply,
-    StreamUpdates, UpdateMissedEvents, VolumeReply, stream_updates,
+    self, API_VERSION, AbLoop, AbLoopState, ActivityState, AlbumModeState, Capabilities,
+    DownloadControl, DownloadId, DownloadList, DownloadRequest, DuckingState, Empty,
+    EqualizerState, GaplessState, GetProgressResponse, MacroName, NormalizationState, OutputDevice,
+    OutputDevices, PlayState, PlayerTime, PlaylistAddRandom, PlaylistLoopMode, PlaylistSwapTracks,
+    PlaylistTracks, PlaylistTracksToAdd, PlaylistTracksToRemove, RecordingState, SeekToPosition,
+    ServerCapabilities, SessionTransfer, SmartSpeedState, SpeedReply, StreamUpdates, UpdateEvents,
+    UpdateMissedEvents, VocalReductionState, VolumeReply, features, stream_updates,
+};
+use termusicplayback::{
+    BackendSelect, PlayerCmd, PlayerCmdCallback, PlayerCmdSender, SharedPlaylist, StreamTX,
 };
-use termusicplayback::{PlayerCmd, PlayerCmdCallback, PlayerCmdSender, SharedPlaylist, StreamTX};
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::{Stream, StreamExt};
 use tonic::{Request, Response, Status};
 
 use crate::PlayerStats;
+use crate::ctl::server_address;
+use crate::download_manager::DownloadManager;
+use crate::macros;
+use crate::session_transfer::{self, Session};
 
 #[derive(Debug)]
 pub struct MusicPlayerService {
@@ -24,7 +36,10 @@ pub struct MusicPlayerService {
     stream_tx: StreamTX,
     config: SharedServerSettings,
     playlist: SharedPlaylist,
+    /// The backend the player loop was started with
+    backend: BackendSelect,
     pub(crate) player_stats: Arc<Mutex<PlayerStats>>,
+    downloads: DownloadManager,
 }
 
 impl MusicPlayerService {
@@ -33,12 +48,18 @@ impl MusicPlayerService {
         stream_tx: StreamTX,
         config: SharedServerSettings,
         playlist: SharedPlaylist,
+        backend: BackendSelect,
+        downloads: DownloadManager,
     ) -> Self {
         let mut player_stats = PlayerStats::new();
         let config_read = config.read();
         player_stats.volume = config_read.settings.player.volume;
         player_stats.gapless = config_read.settings.player.gapless;
         player_stats.speed = config_read.settings.player.speed;
+        player_stats.album_mode = config_read.settings.player.album_mode;
+        player_stats.equalizer = config_read.settings.equalizer.clone();
+        // only the rusty backend has a equalizer
+        player_stats.equalizer.enabled &= backend == BackendSelect::Rusty;
         drop(config_read);
 
         let player_stats = Arc::new(Mutex::new(player_stats));
@@ -48,7 +69,9 @@ impl MusicPlayerService {
             player_stats,
             stream_tx,
             playlist,
+            backend,
             config,
+            downloads,
         }
     }
 }
@@ -68,6 +91,20 @@ impl MusicPlayerService {
 
         Ok(rx)
     }
+
+    /// Collect the capabilities of this server.
+    fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            server_version: env!("TERMUSIC_VERSION").to_string(),
+            api_version: API_VERSION,
+            backends: BackendSelect::AVAILABLE
+                .iter()
+                .map(|v| v.as_str().to_string())
+                .collect(),
+            active_backend: self.backend.as_str().to_string(),
+            features: features::ALL.iter().map(ToString::to_string).collect(),
+        }
+    }
 }
 
 #[tonic::async_trait]
@@ -76,6 +113,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<PlaylistLoopMode>, Status> {
+        METRICS.inc_grpc_request("cycle_loop");
         let rx = self.command_cb(PlayerCmd::CycleLoop)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -91,6 +129,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<GetProgressResponse>, Status> {
+        METRICS.inc_grpc_request("get_progress");
         let r = self.player_stats.lock();
         let reply = r.as_getprogress_response(self.playlist.read().status());
 
@@ -101,6 +140,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         request: Request<player::PlaylistPlaySpecific>,
     ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("play_specific");
         let converted: PlaylistPlaySpecific = request
             .into_inner()
             .try_into()
@@ -116,6 +156,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn reload_config(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("reload_config");
         let reply = Empty {};
         self.command(PlayerCmd::ReloadConfig);
 
@@ -126,6 +167,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<PlayerTime>, Status> {
+        METRICS.inc_grpc_request("seek_backward");
         let rx = self.command_cb(PlayerCmd::SeekBackward)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -136,6 +178,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn seek_forward(&self, _request: Request<Empty>) -> Result<Response<PlayerTime>, Status> {
+        METRICS.inc_grpc_request("seek_forward");
         let rx = self.command_cb(PlayerCmd::SeekForward)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -146,13 +189,50 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn seek_to(
+        &self,
+        request: Request<SeekToPosition>,
+    ) -> Result<Response<PlayerTime>, Status> {
+        METRICS.inc_grpc_request("seek_to");
+        let position = request
+            .into_inner()
+            .position
+            .ok_or_else(|| Status::invalid_argument("Missing position"))?;
+        let rx = self.command_cb(PlayerCmd::SeekTo(position.into()))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let s = self.player_stats.lock();
+
+        let reply = s.as_playertime();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_ab_loop(
+        &self,
+        request: Request<AbLoopState>,
+    ) -> Result<Response<AbLoopState>, Status> {
+        METRICS.inc_grpc_request("set_ab_loop");
+        let to = AbLoop::from_grpc(request.into_inner());
+        let rx = self.command_cb(PlayerCmd::AbLoop(to))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let r = self.player_stats.lock();
+
+        let reply = AbLoop::to_grpc(r.ab_loop);
+
+        Ok(Response::new(reply))
+    }
+
     async fn skip_next(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("skip_next");
         let reply = Empty {};
         self.command(PlayerCmd::SkipNext);
 
         Ok(Response::new(reply))
     }
     async fn skip_previous(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("skip_previous");
         let reply = Empty {};
         self.command(PlayerCmd::SkipPrevious);
 
@@ -160,6 +240,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn speed_down(&self, _request: Request<Empty>) -> Result<Response<SpeedReply>, Status> {
+        METRICS.inc_grpc_request("speed_down");
         let rx = self.command_cb(PlayerCmd::SpeedDown)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -170,6 +251,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn speed_up(&self, _request: Request<Empty>) -> Result<Response<SpeedReply>, Status> {
+        METRICS.inc_grpc_request("speed_up");
         let rx = self.command_cb(PlayerCmd::SpeedUp)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -183,6 +265,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<GaplessState>, Status> {
+        METRICS.inc_grpc_request("toggle_gapless");
         let rx = self.command_cb(PlayerCmd::ToggleGapless)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -192,7 +275,102 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn set_vocal_reduction(
+        &self,
+        request: Request<VocalReductionState>,
+    ) -> Result<Response<VocalReductionState>, Status> {
+        METRICS.inc_grpc_request("set_vocal_reduction");
+        let rx = self.command_cb(PlayerCmd::VocalReduction(request.into_inner().enabled))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let r = self.player_stats.lock();
+        let reply = VocalReductionState {
+            enabled: r.vocal_reduction,
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_normalization(
+        &self,
+        request: Request<NormalizationState>,
+    ) -> Result<Response<NormalizationState>, Status> {
+        METRICS.inc_grpc_request("set_normalization");
+        let rx = self.command_cb(PlayerCmd::Normalization(request.into_inner().enabled))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let r = self.player_stats.lock();
+        let reply = NormalizationState {
+            enabled: r.normalization,
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_smart_speed(
+        &self,
+        request: Request<SmartSpeedState>,
+    ) -> Result<Response<SmartSpeedState>, Status> {
+        METRICS.inc_grpc_request("set_smart_speed");
+        let rx = self.command_cb(PlayerCmd::SmartSpeed(request.into_inner().enabled))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let r = self.player_stats.lock();
+        let reply = SmartSpeedState {
+            enabled: r.smart_speed,
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_equalizer(
+        &self,
+        request: Request<EqualizerState>,
+    ) -> Result<Response<EqualizerState>, Status> {
+        METRICS.inc_grpc_request("set_equalizer");
+        let rx = self.command_cb(PlayerCmd::Equalizer(request.into_inner().into()))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let reply = self.player_stats.lock().equalizer.clone().into();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_recording(
+        &self,
+        request: Request<RecordingState>,
+    ) -> Result<Response<RecordingState>, Status> {
+        METRICS.inc_grpc_request("set_recording");
+        let rx = self.command_cb(PlayerCmd::Recording(request.into_inner().recording))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let r = self.player_stats.lock();
+        let reply = RecordingState {
+            recording: r.recording,
+            error: r.recording_error.clone(),
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_album_mode(
+        &self,
+        request: Request<AlbumModeState>,
+    ) -> Result<Response<AlbumModeState>, Status> {
+        METRICS.inc_grpc_request("set_album_mode");
+        let rx = self.command_cb(PlayerCmd::AlbumMode(request.into_inner().enabled))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let r = self.player_stats.lock();
+        let reply = AlbumModeState {
+            enabled: r.album_mode,
+        };
+
+        Ok(Response::new(reply))
+    }
+
     async fn toggle_pause(&self, _request: Request<Empty>) -> Result<Response<PlayState>, Status> {
+        METRICS.inc_grpc_request("toggle_pause");
         let rx = self.command_cb(PlayerCmd::TogglePause)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -204,6 +382,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn volume_down(&self, _request: Request<Empty>) -> Result<Response<VolumeReply>, Status> {
+        METRICS.inc_grpc_request("volume_down");
         let rx = self.command_cb(PlayerCmd::VolumeDown)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -216,6 +395,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn volume_up(&self, _request: Request<Empty>) -> Result<Response<VolumeReply>, Status> {
+        METRICS.inc_grpc_request("volume_up");
         let rx = self.command_cb(PlayerCmd::VolumeUp)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -233,6 +413,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         _: Request<Empty>,
     ) -> Result<Response<Self::SubscribeServerUpdatesStream>, Status> {
+        METRICS.inc_grpc_request("subscribe_server_updates");
         let rx = self.stream_tx.subscribe();
 
         // map to the grpc types
@@ -257,6 +438,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         request: Request<PlaylistTracksToAdd>,
     ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("add_to_playlist");
         let converted = request
             .into_inner()
             .try_into()
@@ -269,10 +451,28 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn add_random_to_playlist(
+        &self,
+        request: Request<PlaylistAddRandom>,
+    ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("add_random_to_playlist");
+        let converted = request
+            .into_inner()
+            .try_into()
+            .map_err(|err: anyhow::Error| Status::from_error(err.into()))?;
+        let rx = self.command_cb(PlayerCmd::PlaylistAddRandom(converted))?;
+        // wait until the event was processed
+        let _ = rx.await;
+        let reply = Empty {};
+
+        Ok(Response::new(reply))
+    }
+
     async fn remove_from_playlist(
         &self,
         request: Request<PlaylistTracksToRemove>,
     ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("remove_from_playlist");
         let converted: PlaylistRemoveTrackType = request
             .into_inner()
             .try_into()
@@ -295,6 +495,7 @@ impl MusicPlayer for MusicPlayerService {
         &self,
         request: Request<PlaylistSwapTracks>,
     ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("swap_tracks");
         let converted = request
             .into_inner()
             .try_into()
@@ -309,6 +510,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn get_playlist(&self, _: Request<Empty>) -> Result<Response<PlaylistTracks>, Status> {
+        METRICS.inc_grpc_request("get_playlist");
         let playlist = self.playlist.read();
         let reply = playlist.as_grpc_playlist_tracks().unwrap();
 
@@ -316,6 +518,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn shuffle_playlist(&self, _: Request<Empty>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("shuffle_playlist");
         // execute shuffle in the player thread instead of the service thread
         // this does not necessarily need to be done, but its better to have the service read-only
         let rx = self.command_cb(PlayerCmd::PlaylistShuffle)?;
@@ -328,6 +531,7 @@ impl MusicPlayer for MusicPlayerService {
     }
 
     async fn remove_deleted_tracks(&self, _: Request<Empty>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("remove_deleted_tracks");
         let rx = self.command_cb(PlayerCmd::PlaylistRemoveDeletedTracks)?;
         // wait until the event was processed
         let _ = rx.await;
@@ -335,4 +539,155 @@ impl MusicPlayer for MusicPlayerService {
 
         Ok(Response::new(reply))
     }
+
+    async fn get_capabilities(&self, _: Request<Empty>) -> Result<Response<Capabilities>, Status> {
+        METRICS.inc_grpc_request("get_capabilities");
+        let reply = self.capabilities().into();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn report_activity(
+        &self,
+        request: Request<ActivityState>,
+    ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("report_activity");
+        let activity: player::ActivityInfo = request.into_inner().into();
+        {
+            let mut s = self.player_stats.lock();
+            if s.activity == activity {
+                return Ok(Response::new(Empty {}));
+            }
+            s.activity = activity.clone();
+        }
+
+        // there is only one error case: no receivers
+        let _ = self.stream_tx.send(UpdateEvents::ActivityChanged(activity));
+
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn transfer_session(
+        &self,
+        request: Request<SessionTransfer>,
+    ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("transfer_session");
+        let target = request.into_inner().target;
+        if target == server_address(&self.config.read().settings.com) {
+            return Err(Status::invalid_argument(
+                "Cannot transfer the session to this server itself",
+            ));
+        }
+
+        let position = self.player_stats.lock().progress.position;
+        let session = Session::new(&self.playlist.read(), position)
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+        session_transfer::transfer(&target, session)
+            .await
+            .map_err(|err| Status::unavailable(format!("{err:#}")))?;
+        info!("Transferred the session to \"{target}\"");
+
+        let rx = self.command_cb(PlayerCmd::Pause)?;
+        // wait until the event was processed
+        let _ = rx.await;
+
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn set_ducking(&self, request: Request<DuckingState>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("set_ducking");
+        self.command(PlayerCmd::Duck(request.into_inner().active));
+
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn get_output_devices(
+        &self,
+        _: Request<Empty>,
+    ) -> Result<Response<OutputDevices>, Status> {
+        METRICS.inc_grpc_request("get_output_devices");
+        let Some(current) = self.player_stats.lock().output_device.clone() else {
+            return Ok(Response::new(OutputDevices::default()));
+        };
+        let names = tokio::task::spawn_blocking(termusicplayback::list_output_devices)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .map_err(|err| Status::unavailable(format!("{err:#}")))?;
+        let configured = self
+            .config
+            .read()
+            .settings
+            .backends
+            .rusty
+            .output_device
+            .clone();
+
+        Ok(Response::new(OutputDevices {
+            names,
+            current,
+            configured,
+            supported: true,
+        }))
+    }
+
+    async fn set_output_device(
+        &self,
+        request: Request<OutputDevice>,
+    ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("set_output_device");
+        let rx = self.command_cb(PlayerCmd::OutputDevice(request.into_inner().name))?;
+        // wait until the event was processed
+        let _ = rx.await;
+
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn run_macro(&self, request: Request<MacroName>) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("run_macro");
+        let name = request.into_inner().name;
+        // check first, as errors while running are only logged
+        macros::macro_commands(&self.config.read().settings, &name)
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        let rx = self.command_cb(PlayerCmd::RunMacro(name))?;
+        // wait until all commands of the macro were processed
+        let _ = rx.await;
+
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn enqueue_download(
+        &self,
+        request: Request<DownloadRequest>,
+    ) -> Result<Response<DownloadId>, Status> {
+        METRICS.inc_grpc_request("enqueue_download");
+        let id = self
+            .downloads
+            .enqueue(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+
+        Ok(Response::new(DownloadId { id }))
+    }
+
+    async fn get_downloads(&self, _: Request<Empty>) -> Result<Response<DownloadList>, Status> {
+        METRICS.inc_grpc_request("get_downloads");
+        let items = self.downloads.list().into_iter().map(Into::into).collect();
+
+        Ok(Response::new(DownloadList { items }))
+    }
+
+    async fn control_download(
+        &self,
+        request: Request<DownloadControl>,
+    ) -> Result<Response<Empty>, Status> {
+        METRICS.inc_grpc_request("control_download");
+        let request = request.into_inner();
+        let action = DownloadAction::from_number(request.action).ok_or_else(|| {
+            Status::invalid_argument(format!("Unknown download action {}", request.action))
+        })?;
+        self.downloads
+            .control(request.id, action)
+            .map_err(|err| Status::failed_precondition(format!("{err:#}")))?;
+
+        Ok(Response::new(Empty {}))
+    }
 }
@@ -0,0 +1,163 @@
+//! Follow which track is played for how long, to record the plays and skips in the database.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// Ignore ticks further apart than this, like after the system was suspended.
+const MAX_TICK_GAP: Duration = Duration::from_secs(5);
+
+/// A skipped track only counts as skipped if less than this part of it was listened to.
+const SKIP_PART: f64 = 0.5;
+
+/// A play of a track that ended, see [`PlayTracker::tick`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinishedPlay {
+    pub path: PathBuf,
+    pub started_at: DateTime<Utc>,
+    /// The time actually played, without pauses
+    pub listened: Duration,
+    /// Whether the track was skipped before [`SKIP_PART`] of it was listened to
+    pub skipped: bool,
+}
+
+#[derive(Debug)]
+struct CurrentPlay {
+    path: PathBuf,
+    started_at: DateTime<Utc>,
+    listened: Duration,
+    duration: Option<Duration>,
+    skip_requested: bool,
+}
+
+impl CurrentPlay {
+    fn finish(self) -> FinishedPlay {
+        let skipped = self.skip_requested
+            && self
+                .duration
+                .is_none_or(|v| self.listened.as_secs_f64() < v.as_secs_f64() * SKIP_PART);
+
+        FinishedPlay {
+            path: self.path,
+            started_at: self.started_at,
+            listened: self.listened,
+            skipped,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PlayTracker {
+    current: Option<CurrentPlay>,
+    last_tick: Option<Instant>,
+}
+
+impl PlayTracker {
+    /// Remember that the current track was skipped by the user, which counts once the next track started.
+    pub fn skip(&mut self) {
+        if let Some(current) = &mut self.current {
+            current.skip_requested = true;
+        }
+    }
+
+    /// Count the time since the last tick if `playing`.
+    ///
+    /// `current` is the path and duration of the current local track, at `now` and `wall_now`.
+    /// Returns the previous play once the current track changed.
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        wall_now: DateTime<Utc>,
+        playing: bool,
+        current: Option<(&Path, Option<Duration>)>,
+    ) -> Option<FinishedPlay> {
+        let elapsed = self
+            .last_tick
+            .map(|v| now.saturating_duration_since(v))
+            .filter(|v| *v <= MAX_TICK_GAP)
+            .unwrap_or_default();
+        self.last_tick = Some(now);
+
+        let unchanged = match (&self.current, current) {
+            (Some(play), Some((path, _))) => play.path == path,
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            if playing && let Some(play) = &mut self.current {
+                play.listened += elapsed;
+            }
+            return None;
+        }
+
+        let finished = self.current.take().map(CurrentPlay::finish);
+        self.current = current.map(|(path, duration)| CurrentPlay {
+            path: path.to_path_buf(),
+            started_at: wall_now,
+            listened: Duration::ZERO,
+            duration,
+            skip_requested: false,
+        });
+
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use chrono::Utc;
+
+    use super::PlayTracker;
+
+    #[test]
+    fn should_count_listened_time_and_skips() {
+        let mut tracker = PlayTracker::default();
+        let start = Instant::now();
+        let wall = Utc::now();
+        let track_a = Some((Path::new("/music/a.mp3"), Some(Duration::from_secs(100))));
+        let track_b = Some((Path::new("/music/b.mp3"), Some(Duration::from_secs(100))));
+
+        assert_eq!(tracker.tick(start, wall, true, track_a), None);
+        for secs in 1..=30 {
+            let playing = secs <= 20;
+            let now = start + Duration::from_secs(secs);
+            assert_eq!(tracker.tick(now, wall, playing, track_a), None);
+        }
+        tracker.skip();
+
+        let finished = tracker
+            .tick(start + Duration::from_secs(31), wall, true, track_b)
+            .unwrap();
+        assert_eq!(finished.path, Path::new("/music/a.mp3"));
+        assert_eq!(finished.listened, Duration::from_secs(20));
+        assert!(finished.skipped);
+
+        // listened to more than half, so it was not really skipped
+        for secs in 32..=92 {
+            tracker.tick(start + Duration::from_secs(secs), wall, true, track_b);
+        }
+        tracker.skip();
+        let finished = tracker
+            .tick(start + Duration::from_secs(93), wall, true, None)
+            .unwrap();
+        assert_eq!(finished.listened, Duration::from_secs(61));
+        assert!(!finished.skipped);
+
+        // a gap like a suspend is not counted
+        assert_eq!(
+            tracker.tick(start + Duration::from_secs(94), wall, true, track_a),
+            None
+        );
+        tracker.tick(start + Duration::from_secs(200), wall, true, track_a);
+        tracker.tick(start + Duration::from_secs(201), wall, true, track_a);
+        let finished = tracker
+            .tick(start + Duration::from_secs(202), wall, true, None)
+            .unwrap();
+        assert_eq!(finished.listened, Duration::from_secs(1));
+        assert!(!finished.skipped);
+    }
+}
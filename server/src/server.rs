@@ -1,6 +1,24 @@
+mod audio_stream;
+mod bench;
 mod cli;
+mod command_bus;
+mod ctl;
+mod doctor;
+mod download_manager;
+mod ducking;
+mod home_assistant;
+mod library_watcher;
+mod listening_limit;
 mod logger;
+#[cfg(target_os = "macos")]
+mod macos;
+mod macros;
+mod metrics;
+mod mqtt;
 mod music_player_service;
+mod play_tracker;
+mod session_transfer;
+mod stream_overlay;
 
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
@@ -10,18 +28,37 @@ use std::time::Duration;
 
 use anyhow::{Context as _, Result, bail};
 use clap::Parser;
+use download_manager::DownloadManager;
+use ducking::Ducker;
+use listening_limit::{ListeningTimer, TimerAction};
+use macros::MacroQueue;
 use music_player_service::MusicPlayerService;
 use parking_lot::Mutex;
+use play_tracker::PlayTracker;
+use termusiclib::cache::CACHES;
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
-use termusiclib::config::v2::server::{ComProtocol, ScanDepth};
+use termusiclib::config::v2::server::equalizer::EqualizerSettings;
+use termusiclib::config::v2::server::recording::RecordingFormat;
+use termusiclib::config::v2::server::{ComProtocol, PlaybackErrorPolicy, ScanDepth};
 use termusiclib::config::{ServerOverlay, SharedServerSettings, new_shared_server_settings};
+use termusiclib::credentials::CREDENTIALS;
+use termusiclib::metrics::METRICS;
+use termusiclib::new_database::Database;
+use termusiclib::new_database::maintenance::IntegrityStatus;
+use termusiclib::new_database::track_ops;
 use termusiclib::player::music_player_server::MusicPlayerServer;
-use termusiclib::player::{GetProgressResponse, PlayerProgress, PlayerTime, RunningStatus};
-use termusiclib::track::{MediaTypesSimple, Track};
-use termusiclib::{podcast, utils};
+use termusiclib::player::{
+    AbLoop, ActivityInfo, GetProgressResponse, PlayerProgress, PlayerTime, RunningStatus,
+};
+use termusiclib::stats_import::{self, PathMapping};
+use termusiclib::track::{
+    DurationFmtShort, MediaTypesSimple, MetadataOptions, Track, parse_metadata_from_file,
+};
+use termusiclib::{loudness, podcast, utils};
 use termusicplayback::{
     Backend, BackendSelect, GeneralPlayer, PlayerCmd, PlayerCmdReciever, PlayerCmdSender,
-    PlayerErrorType, PlayerTrait, Playlist, SharedPlaylist, SpeedSigned, Volume, VolumeSigned,
+    PlayerErrorType, PlayerTrait, Playlist, RenderOptions, SharedPlaylist, SpeedSigned, Volume,
+    VolumeSigned,
 };
 use tokio::runtime::Handle;
 use tokio::select;
@@ -50,7 +87,22 @@ struct PlayerStats {
     pub volume: u16,
     pub speed: i32,
     pub gapless: bool,
+    pub vocal_reduction: bool,
+    pub normalization: bool,
+    pub smart_speed: bool,
+    /// The equalizer, disabled if the backend does not support it.
+    pub equalizer: EqualizerSettings,
+    pub recording: bool,
+    /// The error of the last [`PlayerCmd::Recording`], empty if there was none.
+    pub recording_error: String,
+    pub album_mode: bool,
+    /// The region of the current track that is repeated.
+    pub ab_loop: Option<AbLoop>,
+    /// The output device played on, [`None`] if the backend does not support choosing one.
+    pub output_device: Option<String>,
     pub radio_title: String,
+    /// The last activity reported by a client.
+    pub activity: ActivityInfo,
 }
 
 impl PlayerStats {
@@ -64,7 +116,17 @@ impl PlayerStats {
             volume: 0,
             speed: 10,
             gapless: true,
+            vocal_reduction: false,
+            normalization: false,
+            smart_speed: false,
+            equalizer: EqualizerSettings::default(),
+            recording: false,
+            recording_error: String::new(),
+            album_mode: false,
+            ab_loop: None,
+            output_device: None,
             radio_title: String::new(),
+            activity: ActivityInfo::default(),
         }
     }
 
@@ -77,6 +139,8 @@ impl PlayerStats {
             speed: self.speed,
             gapless: self.gapless,
             radio_title: self.radio_title.clone(),
+            activity: Some(self.activity.clone().into()),
+            ab_loop: Some(AbLoop::to_grpc(self.ab_loop)),
         }
     }
 
@@ -86,6 +150,9 @@ impl PlayerStats {
 }
 
 fn main() -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let res = macos::run_with_main_loop(actual_main);
+    #[cfg(not(target_os = "macos"))]
     let res = actual_main();
 
     trace!("Tokio Exited");
@@ -103,7 +170,19 @@ fn main() -> Result<()> {
 async fn actual_main() -> Result<()> {
     let args = cli::Args::parse();
     let _ = logger::setup(&args);
+
+    // before loading the config, as the doctor reports a invalid config itself
+    if let Some(cli::Action::Doctor) = args.action {
+        return doctor::run(&args);
+    }
+
     let config = get_config(&args)?;
+    CACHES.apply_settings(&config.settings.cache);
+    CREDENTIALS.apply_settings(&config.settings.credentials);
+
+    if !args.bench.is_empty() {
+        return bench::run(&args.bench, &config);
+    }
 
     if let Some(action) = args.action {
         return execute_action(action, &config).await;
@@ -139,11 +218,15 @@ async fn actual_main() -> Result<()> {
     let playlist =
         Playlist::new_shared(&config, stream_tx.clone()).context("Failed to load playlist")?;
 
+    let downloads = DownloadManager::new(stream_tx.clone(), config.clone());
+
     let music_player_service: MusicPlayerService = MusicPlayerService::new(
         cmd_tx.clone(),
         stream_tx.clone(),
         config.clone(),
         playlist.clone(),
+        backend,
+        downloads.clone(),
     );
     let playerstats = music_player_service.player_stats.clone();
 
@@ -162,6 +245,46 @@ async fn actual_main() -> Result<()> {
     let join_handle =
         start_service(&config, music_player_service, service_cancel_token.clone()).await?;
 
+    downloads.start(service_cancel_token.clone());
+    metrics::start_metrics_endpoint(&config, service_cancel_token.clone()).await?;
+    stream_overlay::start_stream_overlay_endpoint(
+        &config,
+        playlist.clone(),
+        playerstats.clone(),
+        service_cancel_token.clone(),
+    )
+    .await?;
+    audio_stream::start_audio_stream_endpoint(
+        &config,
+        playlist.clone(),
+        service_cancel_token.clone(),
+    )
+    .await?;
+    #[cfg(unix)]
+    command_bus::start_command_bus(
+        &config,
+        cmd_tx.clone(),
+        playlist.clone(),
+        service_cancel_token.clone(),
+    )?;
+    #[cfg(not(unix))]
+    if config.read().settings.command_bus.enable {
+        warn!("The command bus is only implemented for unix targets");
+    }
+    home_assistant::start_home_assistant(
+        &config,
+        cmd_tx.clone(),
+        playlist.clone(),
+        playerstats.clone(),
+        service_cancel_token.clone(),
+    );
+
+    library_watcher::start_library_watcher(
+        &config,
+        stream_tx.clone(),
+        service_cancel_token.clone(),
+    );
+
     let tokio_handle = Handle::current();
 
     let cancel_token = service_cancel_token.clone();
@@ -396,8 +519,12 @@ fn player_loop(
     let mut player = GeneralPlayer::new_backend(backend, config, cmd_tx, stream_tx, playlist)?;
 
     let mut had_enqueue_error = false;
+    let mut listening_timer = ListeningTimer::default();
+    let mut ducker = Ducker::default();
+    let mut play_tracker = PlayTracker::default();
+    let mut macro_queue = MacroQueue::default();
 
-    while let Some((cmd, cb)) = cmd_rx.blocking_recv() {
+    while let Some((cmd, mut cb)) = macro_queue.next_cmd(&mut cmd_rx, &player.playlist) {
         #[allow(unreachable_patterns)]
         match cmd {
             PlayerCmd::AboutToFinish => {
@@ -405,7 +532,10 @@ fn player_loop(
                 let playlist = player.playlist.read();
                 if !playlist.is_empty()
                     && !playlist.has_next_track()
-                    && player.config.read().settings.player.gapless
+                    && (player.config.read().settings.player.gapless
+                        // the rusty backend needs the next track enqueued to crossfade into it
+                        || (matches!(player.backend, Backend::Rusty(_))
+                            && player.config.read().settings.backends.rusty.crossfade_secs > 0))
                 {
                     drop(playlist);
                     player.enqueue_next_from_playlist();
@@ -416,6 +546,8 @@ fn player_loop(
                 // to have a consistent last position
                 player.pause();
                 player.player_save_last_position();
+                player.player_save_auto_bookmark();
+                player.player_save_resume_point(true);
                 if let Err(e) = player.playlist.write().save() {
                     error!("error when saving playlist: {e}");
                 };
@@ -435,25 +567,72 @@ fn player_loop(
             }
             PlayerCmd::Eos => {
                 info!("Eos received");
-                player_eos(&mut player, had_enqueue_error);
+                if had_enqueue_error && stop_on_error(&player) {
+                    info!("Not continuing to the failed track, as configured");
+                } else {
+                    player_eos(&mut player, had_enqueue_error);
+                }
                 had_enqueue_error = false;
             }
-            PlayerCmd::Error(ty) => {
-                info!("Error received: {ty:#?}");
+            PlayerCmd::Error(ty, reason) => {
+                info!("Error received: {ty:#?}: {reason}");
                 player.increment_errors();
+                METRICS.inc_decode_errors();
+
+                let mut playlist = player.playlist.write();
+                let index = if ty == PlayerErrorType::Current {
+                    Some(playlist.get_current_track_index())
+                } else {
+                    playlist.next_track_index()
+                };
+                if let Some(index) = index {
+                    playlist.set_track_error(index, Some(reason));
+                }
+                drop(playlist);
 
                 if ty == PlayerErrorType::Current {
-                    player_eos(&mut player, false);
+                    if stop_on_error(&player) {
+                        info!("Not skipping the failed track, as configured");
+                    } else {
+                        player_eos(&mut player, false);
+                    }
                 } else {
                     // delay handling until after finishing the current track
                     had_enqueue_error = true;
                 }
             }
+            PlayerCmd::Damaged(path, regions) => {
+                warn!(
+                    "Salvaged {} damaged regions of {}, flagging it for repair",
+                    regions.len(),
+                    path.display()
+                );
+                let db = player.db.clone();
+                tokio::spawn(async move {
+                    let name = path.display().to_string();
+                    let res = db
+                        .write(move |conn| track_ops::set_damaged(conn, &path, &regions))
+                        .await;
+                    if let Err(err) = res {
+                        warn!("Error flagging {name} as damaged: {err:#}");
+                    }
+                });
+            }
+            PlayerCmd::SponsorSegments(url, segments) => {
+                player.set_sponsor_segments(&url, segments);
+            }
+            PlayerCmd::OutputLost(name) => {
+                player.output_lost(name);
+            }
+            PlayerCmd::OutputSwitched(name) => {
+                player.output_switched(&name);
+            }
             PlayerCmd::GetProgress => {}
             PlayerCmd::SkipPrevious => {
                 player.reset_errors();
                 info!("skip to previous track");
                 player.player_save_last_position();
+                player.player_save_auto_bookmark();
                 player.previous();
             }
             PlayerCmd::ReloadConfig => {
@@ -478,16 +657,48 @@ fn player_loop(
                     p_tick.progress = progress
                 }
             }
+            PlayerCmd::SeekTo(position) => {
+                player.seek_to(position);
+                let mut p_tick = playerstats.lock();
+                if let Some(progress) = player.get_progress() {
+                    p_tick.progress = progress
+                }
+            }
+            PlayerCmd::AbLoop(to) => {
+                info!("player set A-B loop: {to:?}");
+                player.set_ab_loop(to);
+                let mut p_tick = playerstats.lock();
+                p_tick.ab_loop = player.ab_loop();
+                if let Some(progress) = player.get_progress() {
+                    p_tick.progress = progress
+                }
+            }
+            PlayerCmd::RunMacro(name) => {
+                let commands = macros::macro_commands(&player.config.read().settings, &name);
+                match commands {
+                    Ok(commands) => {
+                        info!("Running macro {name:?}");
+                        // answered once the last command ran
+                        macro_queue.start(commands, std::mem::take(&mut cb));
+                    }
+                    Err(err) => error!("Error running macro: {err:#}"),
+                }
+            }
+            PlayerCmd::AbLoopCheck => {
+                player.check_ab_loop();
+            }
             PlayerCmd::SkipNext => {
                 player.reset_errors();
                 info!("skip to next track.");
                 player.player_save_last_position();
+                player.player_save_auto_bookmark();
+                play_tracker.skip();
                 player.next();
             }
             PlayerCmd::SpeedDown => {
                 let new_speed = player.add_speed(-SPEED_STEP);
                 info!("after speed down: {new_speed}");
-                player.config.write().settings.player.speed = new_speed;
+                player.remember_speed(new_speed);
                 let mut p_tick = playerstats.lock();
                 p_tick.speed = new_speed;
             }
@@ -495,14 +706,33 @@ fn player_loop(
             PlayerCmd::SpeedUp => {
                 let new_speed = player.add_speed(SPEED_STEP);
                 info!("after speed up: {new_speed}");
-                player.config.write().settings.player.speed = new_speed;
+                player.remember_speed(new_speed);
                 let mut p_tick = playerstats.lock();
                 p_tick.speed = new_speed;
             }
             PlayerCmd::Tick => {
                 // info!("tick received");
                 player.mpris_handle_events();
+                handle_listening_limit(&mut player, &playerstats, &mut listening_timer);
+                handle_ducking(&mut player, &playerstats, &mut ducker, &listening_timer);
+                handle_play_tracker(&player, &mut play_tracker);
+                // the volume may have been changed outside of termusic, like with the system mixer
+                // but not while it is lowered by the listening limit fade out or ducking
+                let volume = player.volume();
+                if !listening_timer.is_fading()
+                    && !ducker.is_ducked()
+                    && volume != playerstats.lock().volume
+                {
+                    player.volume_changed_externally(volume);
+                    set_volume(&player, &playerstats, volume);
+                }
                 let mut p_tick = playerstats.lock();
+                // the speed may have changed with the track, like to the one of a podcast
+                p_tick.speed = player.speed();
+                // the output device may have been switched, like when a headset was connected
+                p_tick.output_device = player.output_device();
+                // the A-B loop is cleared on track change
+                p_tick.ab_loop = player.ab_loop();
                 let mut playlist = player.playlist.read();
                 // branch to auto-start playing if status is "stopped"(not paused) and playlist is not empty anymore
                 if playlist.status() == RunningStatus::Stopped {
@@ -536,6 +766,11 @@ fn player_loop(
                             || progress.position.is_some_and(|v| v > Duration::ZERO))
                     {
                         player.reset_errors();
+
+                        // the track plays now, so a previous failure is no longer relevant
+                        let mut playlist = player.playlist.write();
+                        let index = playlist.get_current_track_index();
+                        playlist.set_track_error(index, None);
                     }
 
                     playlist = player.playlist.read();
@@ -548,29 +783,100 @@ fn player_loop(
                 if let Some(track) = playlist.current_track() {
                     update_metadata_changed(&mut p_tick, &player, track);
                 }
+                drop(playlist);
+                drop(p_tick);
+                player.player_save_resume_point(false);
             }
             PlayerCmd::ToggleGapless => {
                 let new_gapless = player.toggle_gapless();
                 let mut p_tick = playerstats.lock();
                 p_tick.gapless = new_gapless;
             }
+            PlayerCmd::VocalReduction(to) => {
+                info!("player set vocal reduction: {to}");
+                player.set_vocal_reduction(to);
+                let mut p_tick = playerstats.lock();
+                p_tick.vocal_reduction = player.vocal_reduction();
+            }
+            PlayerCmd::Normalization(to) => {
+                info!("player set normalization: {to}");
+                player.set_normalization(to);
+                let mut p_tick = playerstats.lock();
+                p_tick.normalization = player.normalization();
+            }
+            PlayerCmd::SmartSpeed(to) => {
+                info!("player set smart speed: {to}");
+                player.set_smart_speed(to);
+                let mut p_tick = playerstats.lock();
+                p_tick.smart_speed = player.smart_speed();
+            }
+            PlayerCmd::Equalizer(settings) => {
+                info!("player set equalizer: {settings:?}");
+                player.set_equalizer(settings.active_gains());
+                let enabled = player.equalizer().is_some();
+                // keep the requested settings, even if the current backend does not support them
+                player.config.write().settings.equalizer = settings.clone();
+                let mut p_tick = playerstats.lock();
+                p_tick.equalizer = EqualizerSettings {
+                    enabled,
+                    ..settings
+                };
+            }
+            PlayerCmd::Recording(to) => {
+                info!("player set recording: {to}");
+                let res = player.set_recording(to);
+                let mut p_tick = playerstats.lock();
+                p_tick.recording = player.recording();
+                p_tick.recording_error = match res {
+                    Ok(()) => String::new(),
+                    Err(err) => {
+                        error!("Error setting recording: {err:#}");
+                        format!("{err:#}")
+                    }
+                };
+            }
+            PlayerCmd::AlbumMode(to) => {
+                info!("player set album mode: {to}");
+                player.set_album_mode(to);
+                let mut p_tick = playerstats.lock();
+                p_tick.album_mode = player.album_mode();
+            }
+            PlayerCmd::OutputDevice(name) => {
+                info!("player set output device: {name:?}");
+                player.set_output_device(name);
+            }
+            PlayerCmd::Duck(active) => {
+                info!("player set ducking: {active}");
+                let settings = player.config.read().settings.ducking.clone();
+                ducker.set_active(active, std::time::Instant::now(), &settings);
+                if ducker.is_ducked() {
+                    ducking_ramp_thread(player.cmd_tx.clone(), settings.ramp());
+                }
+                handle_ducking(&mut player, &playerstats, &mut ducker, &listening_timer);
+            }
+            PlayerCmd::DuckTick => {
+                handle_ducking(&mut player, &playerstats, &mut ducker, &listening_timer);
+            }
             PlayerCmd::TogglePause => {
                 info!("player toggled pause");
                 player.toggle_pause();
             }
             PlayerCmd::VolumeDown => {
+                end_ducking(&mut player, &playerstats, &mut ducker);
                 info!("before volumedown: {}", player.volume());
                 let new_volume = player.add_volume(-VOLUME_STEP);
                 set_volume(&player, &playerstats, new_volume);
                 info!("after volumedown: {new_volume}");
             }
             PlayerCmd::VolumeUp => {
+                end_ducking(&mut player, &playerstats, &mut ducker);
                 info!("before volumeup: {}", player.volume());
                 let new_volume = player.add_volume(VOLUME_STEP);
                 set_volume(&player, &playerstats, new_volume);
                 info!("after volumeup: {new_volume}");
             }
             PlayerCmd::VolumeSet(volume) => {
+                end_ducking(&mut player, &playerstats, &mut ducker);
                 info!("before volumeset: {}", player.volume());
                 let new_volume = player.set_volume(volume);
                 set_volume(&player, &playerstats, new_volume);
@@ -590,8 +896,13 @@ fn player_loop(
                     info.track_index, info.id
                 );
                 player.player_save_last_position();
+                player.player_save_auto_bookmark();
                 if let Err(err) = player.playlist.write().play_specific(&info) {
                     error!("Error setting specific track to play: {err}");
+                } else if player.album_mode()
+                    && let Err(err) = player.playlist_queue_album()
+                {
+                    error!("Error queueing the album: {err:#}");
                 }
                 player.next();
             }
@@ -600,6 +911,16 @@ fn player_loop(
                     error!("Error adding tracks: {err}");
                 }
             }
+            PlayerCmd::PlaylistAddRandom(kind) => {
+                // the callback is called once the tracks were sampled in the background and added
+                player.playlist_add_random(kind, std::mem::take(&mut cb));
+            }
+            PlayerCmd::RandomTracks(tracks) => {
+                if let Err(err) = player.playlist_add_to_end(&mut player.playlist.write(), &tracks)
+                {
+                    error!("Error adding random tracks: {err:#}");
+                }
+            }
             PlayerCmd::PlaylistRemoveTrack(info) => {
                 if let Err(err) = player.playlist.write().remove_tracks(info) {
                     error!("Error removing tracks: {err}");
@@ -641,6 +962,11 @@ fn player_loop(
 /// Deduplicated
 #[allow(irrefutable_let_patterns)] // if only one backend is enabled, rust will complain that it is the only thing that happens
 fn update_metadata_changed(p_tick: &mut PlayerStats, player: &GeneralPlayer, track: &Track) {
+    // the title of a video is only known from its extracted stream
+    if MediaTypesSimple::Video == track.media_type() {
+        p_tick.radio_title = player.media_info().media_title.unwrap_or_default();
+    }
+
     if MediaTypesSimple::LiveRadio == track.media_type() {
         // TODO: consider changing "radio_title" and "media_title" to be consistent
         p_tick.radio_title = player.media_info().media_title.unwrap_or_default();
@@ -665,6 +991,11 @@ fn update_metadata_changed(p_tick: &mut PlayerStats, player: &GeneralPlayer, tra
 /// Common [`PlayerCmd::Eos`] handler.
 ///
 /// Use `use_skip` to skip the next track instead of trying to play it.
+/// Check whether playback should stop at a failed track instead of skipping it.
+fn stop_on_error(player: &GeneralPlayer) -> bool {
+    player.config.read().settings.player.on_error == PlaybackErrorPolicy::Stop
+}
+
 fn player_eos(player: &mut GeneralPlayer, use_skip: bool) {
     let mut playlist = player.playlist.write();
     if playlist.is_empty() {
@@ -713,6 +1044,29 @@ fn ticker_thread(cmd_tx: PlayerCmdSender) -> Result<()> {
     Ok(())
 }
 
+/// How often the volume is updated while ducking ramps.
+const DUCKING_RAMP_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Spawn a thread that sends [`PlayerCmd::DuckTick`] until the ducking ramp of `ramp` is done.
+fn ducking_ramp_thread(cmd_tx: PlayerCmdSender, ramp: Duration) {
+    let res = std::thread::Builder::new()
+        .name("ducking ramp".into())
+        .spawn(move || {
+            let end = std::time::Instant::now() + ramp;
+            loop {
+                let done = std::time::Instant::now() >= end;
+                if cmd_tx.send(PlayerCmd::DuckTick).is_err() || done {
+                    break;
+                }
+                std::thread::sleep(DUCKING_RAMP_INTERVAL);
+            }
+        });
+
+    if let Err(err) = res {
+        error!("Error starting the ducking ramp thread: {err:#}");
+    }
+}
+
 fn get_config(args: &cli::Args) -> Result<ServerOverlay> {
     let config = ServerConfigVersionedDefaulted::from_config_path()?.into_settings();
 
@@ -754,6 +1108,70 @@ fn get_path(dir: &Path) -> Result<PathBuf> {
     bail!("Error: non-existing directory '{}'", dir.display());
 }
 
+/// Read the secret for `key` from the first line of stdin, with a prompt if it is a terminal.
+///
+/// Note that the input is visible while typing, pipe it in to avoid that.
+fn read_secret(key: &str) -> Result<String> {
+    use std::io::IsTerminal as _;
+
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Secret for \"{key}\": ");
+    }
+
+    let mut secret = String::new();
+    stdin.read_line(&mut secret)?;
+    let secret = secret.trim_end_matches(['\n', '\r']);
+    if secret.is_empty() {
+        bail!("Empty secret");
+    }
+
+    Ok(secret.to_string())
+}
+
+/// Render the saved queue to `file`, printing the rendered tracks.
+fn render_queue(file: &Path, config: &ServerOverlay) -> Result<()> {
+    let Some(format) = RecordingFormat::from_path(file) else {
+        bail!(
+            "Unknown format of \"{}\", expected a \"flac\" or \"ogg\" file",
+            file.display()
+        );
+    };
+    let (_, tracks) = Playlist::load().context("load queue")?;
+    if tracks.is_empty() {
+        bail!("The queue is empty");
+    }
+
+    let options = RenderOptions::from_settings(&config.settings, format);
+    let report = termusicplayback::render_queue(&tracks, file, &options)?;
+
+    for (location, err) in &report.skipped {
+        println!("Skipped {location}: {err:#}");
+    }
+    for track in &report.tracks {
+        println!(
+            "{} {} - {}",
+            DurationFmtShort(track.start),
+            track.artist.as_deref().unwrap_or("Unknown Artist"),
+            track
+                .title
+                .as_deref()
+                .unwrap_or_else(|| track.path.to_str().unwrap_or_default())
+        );
+    }
+    println!(
+        "Rendered {} tracks, {} in {:.2?} ({:.1}x realtime) to {} and {}",
+        report.tracks.len(),
+        DurationFmtShort(report.duration),
+        report.elapsed,
+        report.speed(),
+        file.display(),
+        report.cue_path.display()
+    );
+
+    Ok(())
+}
+
 async fn execute_action(action: cli::Action, config: &ServerOverlay) -> Result<()> {
     match action {
         cli::Action::Import { file } => {
@@ -774,12 +1192,264 @@ async fn execute_action(action: cli::Action, config: &ServerOverlay) -> Result<(
                 utils::get_app_config_path().context("getting app-config-path")?;
             podcast::export_to_opml(&config_dir_path, &path).context("export opml")?;
         }
+        cli::Action::Doctor => unreachable!("handled before loading the config"),
+        cli::Action::Ctl { command } => ctl::run(command, config).await?,
+        cli::Action::Credentials { action } => match action {
+            cli::CredentialsAction::Set { key } => {
+                let secret = read_secret(&key).context("read secret")?;
+                let store = CREDENTIALS.set(&key, &secret).context("store secret")?;
+                println!("Stored \"{key}\" in {store}");
+            }
+            cli::CredentialsAction::Remove { key } => {
+                if CREDENTIALS.remove(&key).context("remove secret")? {
+                    println!("Removed \"{key}\"");
+                } else {
+                    println!("No secret stored for \"{key}\"");
+                }
+            }
+        },
+        cli::Action::Render { file } => render_queue(&file, config)?,
+        cli::Action::Db { action } => {
+            let db = Database::new_default_path().context("open library database")?;
+            match action {
+                cli::DbAction::Repair => {
+                    let (status, report) = db.repair().context("repair database")?;
+                    match status {
+                        IntegrityStatus::Ok => println!("Integrity check: ok"),
+                        IntegrityStatus::Problems(problems) => {
+                            println!("Integrity check found problems:");
+                            for problem in problems {
+                                println!("  {problem}");
+                            }
+                        }
+                    }
+                    println!("Rebuilt indexes");
+                    println!(
+                        "Pruned {} rows: {} metadata, {} track-artist mappings, {} album-artist mappings, {} track-genre mappings, {} albums, {} artists, {} genres",
+                        report.pruned(),
+                        report.metadata,
+                        report.tracks_artists,
+                        report.albums_artists,
+                        report.tracks_genres,
+                        report.albums,
+                        report.artists,
+                        report.genres
+                    );
+                    println!(
+                        "Unset the missing album of {} tracks",
+                        report.tracks_album_unset
+                    );
+                }
+                cli::DbAction::Vacuum => {
+                    db.vacuum().context("vacuum database")?;
+                    println!("Vacuumed database");
+                }
+                cli::DbAction::Damaged { clear: Some(path) } => {
+                    let path = utils::absolute_path(&path)?;
+                    track_ops::set_damaged(&db.get_connection(), &path, &[])
+                        .with_context(|| path.display().to_string())?;
+                    println!("Cleared the damaged flag of {}", path.display());
+                }
+                cli::DbAction::Damaged { clear: None } => {
+                    let damaged = track_ops::get_damaged_tracks(&db.get_connection())
+                        .context("get damaged tracks")?;
+                    if damaged.is_empty() {
+                        println!("No tracks are flagged as damaged");
+                    }
+                    for (path, regions) in damaged {
+                        println!("{}: {regions}", path.display());
+                    }
+                }
+                cli::DbAction::Loudness { write_tags } => analyze_loudness(&db, write_tags)?,
+                cli::DbAction::ImportStats {
+                    file,
+                    map_root,
+                    dry_run,
+                } => import_stats(&db, &file, map_root.as_deref(), dry_run)?,
+            }
+        }
     };
 
     Ok(())
 }
 
+/// Import the play statistics of other players from `file`, a export or a directory of sidecars.
+fn import_stats(db: &Database, file: &Path, map_root: Option<&str>, dry_run: bool) -> Result<()> {
+    let mapping = map_root.map(PathMapping::parse).transpose()?;
+
+    let entries = if file.is_dir() {
+        let (entries, orphans) = stats_import::scan_sidecars(file);
+        for orphan in orphans {
+            println!("No track for sidecar {}", orphan.display());
+        }
+        entries
+    } else {
+        let content =
+            std::fs::read_to_string(file).with_context(|| format!("read {}", file.display()))?;
+        stats_import::parse_export(&content).with_context(|| file.display().to_string())?
+    };
+
+    let report = stats_import::import(&db.get_connection(), &entries, mapping.as_ref(), dry_run)
+        .context("import statistics")?;
+    for entry in &report.unmatched {
+        println!("No library track matches {entry}");
+    }
+    println!(
+        "{} {} of {} entries, {} did not match",
+        if dry_run { "Would import" } else { "Imported" },
+        report.matched,
+        entries.len(),
+        report.unmatched.len()
+    );
+
+    Ok(())
+}
+
+/// Analyze the loudness of all tracks without `ReplayGain` tags that were not analyzed yet.
+fn analyze_loudness(db: &Database, write_tags: bool) -> Result<()> {
+    let paths = track_ops::get_tracks_without_loudness(&db.get_connection())
+        .context("get tracks without loudness")?;
+
+    let mut analyzed = 0;
+    for path in paths {
+        let tagged = parse_metadata_from_file(
+            &path,
+            MetadataOptions {
+                replay_gain: true,
+                ..Default::default()
+            },
+        )
+        .is_ok_and(|v| v.replay_gain.is_some());
+        if tagged {
+            continue;
+        }
+
+        let loudness = match loudness::analyze_file(&path) {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                println!("{}: silent, skipped", path.display());
+                continue;
+            }
+            Err(err) => {
+                println!("{}: error analyzing: {err:#}", path.display());
+                continue;
+            }
+        };
+        track_ops::set_loudness(&db.get_connection(), &path, &loudness)
+            .with_context(|| path.display().to_string())?;
+        println!(
+            "{}: {:.1} LUFS, gain {:+.2} dB",
+            path.display(),
+            loudness.integrated,
+            loudness.replay_gain()
+        );
+        if write_tags && let Err(err) = loudness::write_replay_gain_tags(&path, &loudness) {
+            println!("{}: error writing tags: {err:#}", path.display());
+        }
+        analyzed += 1;
+    }
+
+    println!("Analyzed {analyzed} tracks");
+
+    Ok(())
+}
+
 /// Set the volume for the Config and the playerstats.
+/// Count the listening time and fade out and pause for a break once the limit is reached.
+fn handle_listening_limit(
+    player: &mut GeneralPlayer,
+    playerstats: &Arc<Mutex<PlayerStats>>,
+    timer: &mut ListeningTimer,
+) {
+    let settings = player.config.read().settings.listening_limit.clone();
+    let playing = player.playlist.read().status() == RunningStatus::Running;
+    // the volume before the fade, as the player volume is lowered while fading
+    let volume = playerstats.lock().volume;
+
+    match timer.tick(std::time::Instant::now(), playing, volume, &settings) {
+        TimerAction::None => (),
+        TimerAction::Report(info) => player.listening_time_update(info),
+        TimerAction::Fade(volume) => player.set_volume_unreported(volume),
+        TimerAction::Restore(volume) => player.set_volume_unreported(volume),
+        TimerAction::Break { volume, info } => {
+            info!("Listening limit reached, pausing for a break");
+            player.pause();
+            player.set_volume_unreported(volume);
+            player.listening_time_update(info);
+        }
+    }
+}
+
+/// Apply the volume of the ducking ramp, the listening limit fade takes precedence.
+fn handle_ducking(
+    player: &mut GeneralPlayer,
+    playerstats: &Arc<Mutex<PlayerStats>>,
+    ducker: &mut Ducker,
+    listening_timer: &ListeningTimer,
+) {
+    if listening_timer.is_fading() {
+        return;
+    }
+    let settings = player.config.read().settings.ducking.clone();
+    let volume = playerstats.lock().volume;
+
+    if let Some(volume) = ducker.tick(std::time::Instant::now(), volume, &settings) {
+        player.set_volume_unreported(volume);
+    }
+}
+
+/// Record the play of the previous track in the database in the background once the current track changed.
+fn handle_play_tracker(player: &GeneralPlayer, tracker: &mut PlayTracker) {
+    let finished = {
+        let playlist = player.playlist.read();
+        let playing = playlist.status() == RunningStatus::Running;
+        let current = playlist
+            .current_track()
+            .and_then(|track| Some((track.path()?, track.duration())));
+
+        tracker.tick(
+            std::time::Instant::now(),
+            chrono::Utc::now(),
+            playing,
+            current,
+        )
+    };
+    let Some(play) = finished else {
+        return;
+    };
+
+    let db = player.db.clone();
+    tokio::spawn(async move {
+        let res = db
+            .write(move |conn| {
+                let path = play.path.display();
+                track_ops::record_play(conn, &play.path, play.started_at, play.listened)
+                    .with_context(|| format!("record play of {path}"))?;
+                if play.skipped {
+                    track_ops::mark_skipped(conn, &play.path)
+                        .with_context(|| format!("record skip of {path}"))?;
+                }
+
+                Ok(())
+            })
+            .await;
+        if let Err(err) = res {
+            warn!("Error recording a play: {err:#?}");
+        }
+    });
+}
+
+/// End ducking at once, so that a volume change is not based on the lowered volume.
+fn end_ducking(
+    player: &mut GeneralPlayer,
+    playerstats: &Arc<Mutex<PlayerStats>>,
+    ducker: &mut Ducker,
+) {
+    if ducker.cancel() {
+        player.set_volume_unreported(playerstats.lock().volume);
+    }
+}
+
 fn set_volume(player: &GeneralPlayer, playerstats: &Arc<Mutex<PlayerStats>>, new_volume: Volume) {
     player.config.write().settings.player.volume = new_volume;
     let mut p_tick = playerstats.lock();
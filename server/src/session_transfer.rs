@@ -0,0 +1,119 @@
+//! Hand the session, the playlist and the position in the current track, over to another server.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use termusiclib::player::music_player_client::MusicPlayerClient;
+use termusiclib::player::{
+    self, Empty, PlaylistTracksToAdd, PlaylistTracksToRemove, PlaylistTracksToRemoveClear,
+    RunningStatus, SeekToPosition, ServerCapabilities, TrackId, TrackInfo, features,
+};
+use termusicplayback::Playlist;
+
+/// A snapshot of the session to continue on another server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    tracks: Vec<TrackId>,
+    /// The title and duration of each track in `tracks`, so that the target does not need to look up videos again
+    infos: Vec<TrackInfo>,
+    current_track_index: u64,
+    /// The position in the current track, if known
+    position: Option<Duration>,
+    playing: bool,
+}
+
+impl Session {
+    /// Take a snapshot of `playlist`, at `position` in the current track.
+    pub fn new(playlist: &Playlist, position: Option<Duration>) -> Result<Self> {
+        if playlist.is_empty() {
+            bail!("The playlist is empty, there is no session to transfer");
+        }
+
+        let (tracks, infos) = playlist
+            .tracks()
+            .iter()
+            .map(|track| {
+                let info = TrackInfo {
+                    optional_title: track
+                        .title()
+                        .map(|v| player::track_info::OptionalTitle::Title(v.to_string())),
+                    duration: track.duration().map(Into::into),
+                };
+                (track.as_track_source().into(), info)
+            })
+            .unzip();
+
+        Ok(Self {
+            tracks,
+            infos,
+            current_track_index: u64::try_from(playlist.get_current_track_index())
+                .context("current_track_index(usize) to u64")?,
+            position,
+            playing: playlist.status() == RunningStatus::Running,
+        })
+    }
+}
+
+/// Continue `session` on the server at `target`, replacing its playlist.
+pub async fn transfer(target: &str, session: Session) -> Result<()> {
+    let mut client = MusicPlayerClient::connect(target.to_string())
+        .await
+        .with_context(|| format!("connect to the target server at \"{target}\""))?;
+
+    // servers without "GetCapabilities" are too old to seek
+    let capabilities: ServerCapabilities = client
+        .get_capabilities(Empty {})
+        .await
+        .context("The target server is too old to receive a session")?
+        .into_inner()
+        .into();
+    if !capabilities.supports(features::PLAYLIST) || !capabilities.supports(features::SEEK_TO) {
+        bail!("The target server does not support the playlist or seeking");
+    }
+
+    let current_id = usize::try_from(session.current_track_index)
+        .ok()
+        .and_then(|idx| session.tracks.get(idx))
+        .cloned();
+
+    client
+        .remove_from_playlist(PlaylistTracksToRemove {
+            r#type: Some(player::playlist_tracks_to_remove::Type::Clear(
+                PlaylistTracksToRemoveClear {},
+            )),
+        })
+        .await
+        .context("clear the playlist of the target")?;
+    client
+        .add_to_playlist(PlaylistTracksToAdd {
+            at_index: 0,
+            tracks: session.tracks,
+            infos: session.infos,
+        })
+        .await
+        .context("add the tracks to the target")?;
+    client
+        .play_specific(player::PlaylistPlaySpecific {
+            track_index: session.current_track_index,
+            id: current_id,
+        })
+        .await
+        .context("play the current track on the target")?;
+
+    if let Some(position) = session.position.filter(|v| !v.is_zero()) {
+        client
+            .seek_to(SeekToPosition {
+                position: Some(position.into()),
+            })
+            .await
+            .context("seek on the target")?;
+    }
+    if !session.playing {
+        client
+            .toggle_pause(Empty {})
+            .await
+            .context("pause the target")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! Minimal HTTP endpoint to serve the current track as a page for streaming software, like a OBS browser source.
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use serde::Serialize;
+use termusiclib::common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use termusiclib::config::SharedServerSettings;
+use termusiclib::player::RunningStatus;
+use termusiclib::track::{DurationFmtShort, MediaTypes, Track};
+use termusicplayback::SharedPlaylist;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+
+use crate::PlayerStats;
+use crate::metrics::{MAX_REQUEST_SIZE, request_path};
+
+/// The page served if no template is configured, it refreshes itself from `/now-playing.json`.
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>termusic - now playing</title>
+<style>
+body { margin: 0; background: transparent; color: #fff; font-family: sans-serif; text-shadow: 0 0 4px #000; }
+#overlay { display: flex; align-items: center; gap: 12px; padding: 8px; }
+#cover { width: 96px; height: 96px; object-fit: cover; border-radius: 4px; }
+#title { font-size: 1.4em; font-weight: bold; }
+</style>
+</head>
+<body>
+<div id="overlay">
+<img id="cover" src="/cover" alt="" onerror="this.style.display='none'" onload="this.style.display=''">
+<div>
+<div id="title">{title}</div>
+<div id="artist">{artist}</div>
+<div id="album">{album}</div>
+<div><span id="position">{position}</span> / <span id="duration">{duration}</span></div>
+</div>
+</div>
+<script>
+let lastCover = "";
+async function refresh() {
+  try {
+    const data = await (await fetch("/now-playing.json")).json();
+    for (const key of ["title", "artist", "album", "position", "duration"]) {
+      document.getElementById(key).textContent = data[key];
+    }
+    document.getElementById("overlay").style.visibility = data.status === "stopped" ? "hidden" : "visible";
+    const cover = data.artist + "\n" + data.album + "\n" + data.title;
+    if (cover !== lastCover) {
+      lastCover = cover;
+      document.getElementById("cover").src = "/cover?" + encodeURIComponent(cover);
+    }
+  } catch (err) {}
+}
+setInterval(refresh, 1000);
+refresh();
+</script>
+</body>
+</html>
+"#;
+
+/// The data shared between all connections.
+#[derive(Debug)]
+struct OverlayState {
+    playlist: SharedPlaylist,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    template: String,
+}
+
+/// The current track information, as served on `/now-playing.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct NowPlayingInfo {
+    pub(crate) status: &'static str,
+    pub(crate) artist: String,
+    pub(crate) title: String,
+    pub(crate) album: String,
+    /// The position, formatted like `1:23`.
+    position: String,
+    /// The duration, formatted like `4:56`.
+    duration: String,
+    position_secs: u64,
+    pub(crate) duration_secs: u64,
+}
+
+impl NowPlayingInfo {
+    /// Collect the information for `track`, with the progress and radio title from `stats`.
+    pub(crate) fn new(track: Option<&Track>, status: RunningStatus, stats: &PlayerStats) -> Self {
+        let status = match status {
+            RunningStatus::Running => "playing",
+            RunningStatus::Paused => "paused",
+            RunningStatus::Stopped => "stopped",
+        };
+        let Some(track) = track else {
+            return Self {
+                status,
+                ..Self::default()
+            };
+        };
+
+        let album = match track.inner() {
+            MediaTypes::Track(data) => data.album().unwrap_or_default(),
+            MediaTypes::Podcast(_) | MediaTypes::Radio(_) | MediaTypes::Video(_) => "",
+        };
+        // radio streams have the station as the track title and the current song as the radio title
+        let (artist, title) = match track.inner() {
+            MediaTypes::Radio(_) if !stats.radio_title.is_empty() => (
+                track.title().unwrap_or(UNKNOWN_ARTIST),
+                stats.radio_title.as_str(),
+            ),
+            // the title of a video is only known from its extracted stream
+            MediaTypes::Video(_) if !stats.radio_title.is_empty() => {
+                (UNKNOWN_ARTIST, stats.radio_title.as_str())
+            }
+            _ => (
+                track.artist().unwrap_or(UNKNOWN_ARTIST),
+                track.title().unwrap_or(UNKNOWN_TITLE),
+            ),
+        };
+        let position = stats.progress.position.unwrap_or_default();
+        let duration = stats
+            .progress
+            .total_duration
+            .or_else(|| track.duration())
+            .unwrap_or_default();
+
+        Self {
+            status,
+            artist: artist.to_string(),
+            title: title.to_string(),
+            album: album.to_string(),
+            position: DurationFmtShort(position).to_string(),
+            duration: DurationFmtShort(duration).to_string(),
+            position_secs: position.as_secs(),
+            duration_secs: duration.as_secs(),
+        }
+    }
+
+    /// Replace all placeholders in `template` with the HTML-escaped values.
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("{status}", self.status)
+            .replace("{artist}", &escape_html(&self.artist))
+            .replace("{title}", &escape_html(&self.title))
+            .replace("{album}", &escape_html(&self.album))
+            .replace("{position}", &self.position)
+            .replace("{duration}", &self.duration)
+    }
+}
+
+/// Start the overlay endpoint, if enabled in the config.
+pub async fn start_stream_overlay_endpoint(
+    config: &SharedServerSettings,
+    playlist: SharedPlaylist,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    cancel_token: CancellationToken,
+) -> Result<()> {
+    let settings = config.read().settings.stream_overlay.clone();
+    if !settings.enable {
+        return Ok(());
+    }
+
+    let template = match &settings.template {
+        Some(path) => read_template(path)?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    let addr = SocketAddr::from(&settings);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Error binding stream overlay address: {addr}"))?;
+    info!(
+        "Stream overlay listening on http://{}/",
+        listener.local_addr()?
+    );
+
+    let state = Arc::new(OverlayState {
+        playlist,
+        playerstats,
+        template,
+    });
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                res = listener.accept() => {
+                    match res {
+                        Ok((stream, _)) => {
+                            let state = state.clone();
+                            tokio::spawn(async move {
+                                if let Err(err) = handle_connection(stream, &state).await {
+                                    debug!("Error handling stream overlay request: {err:#}");
+                                }
+                            });
+                        }
+                        Err(err) => warn!("Error accepting stream overlay connection: {err}"),
+                    }
+                },
+                () = cancel_token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Read the configured template, once on startup.
+fn read_template(path: &Path) -> Result<String> {
+    let path = shellexpand::path::tilde(path);
+    std::fs::read_to_string(&path)
+        .with_context(|| format!("Error reading stream overlay template: {}", path.display()))
+}
+
+/// Handle a single HTTP request, serving `/`, `/now-playing.json` and `/cover`.
+async fn handle_connection(mut stream: TcpStream, state: &OverlayState) -> Result<()> {
+    let mut buf = vec![0; MAX_REQUEST_SIZE];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+
+    let response = match request_path(&request) {
+        Some("/") => {
+            let body = state.now_playing().0.render(&state.template);
+            response("200 OK", "text/html; charset=utf-8", body.as_bytes())
+        }
+        Some("/now-playing.json") => {
+            let body = serde_json::to_vec(&state.now_playing().0)?;
+            response("200 OK", "application/json", &body)
+        }
+        Some("/cover") => {
+            let track = state.now_playing().1;
+            let picture = tokio::task::spawn_blocking(move || {
+                track.and_then(|track| track.get_picture().ok().flatten())
+            })
+            .await?;
+
+            match picture {
+                Some(picture) => {
+                    let mime = picture
+                        .mime_type()
+                        .map_or("application/octet-stream", |v| v.as_str());
+                    response("200 OK", mime, picture.data())
+                }
+                None => response("404 Not Found", "text/plain", &[]),
+            }
+        }
+        _ => response("404 Not Found", "text/plain", &[]),
+    };
+
+    stream.write_all(&response).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+impl OverlayState {
+    /// Get the current information and a copy of the current track.
+    fn now_playing(&self) -> (NowPlayingInfo, Option<Track>) {
+        let playlist = self.playlist.read();
+        let track = playlist.current_track().cloned();
+        let info = NowPlayingInfo::new(track.as_ref(), playlist.status(), &self.playerstats.lock());
+
+        (info, track)
+    }
+}
+
+/// Build a full HTTP response, not cached as the content changes with every track.
+fn response(status: &str, content_type: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+
+    response
+}
+
+/// Escape the characters that have a meaning in HTML.
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            char => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NowPlayingInfo, escape_html};
+
+    #[test]
+    fn should_escape_html() {
+        assert_eq!(
+            escape_html(r#"<b>"Tom" & 'Jerry'</b>"#),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+        assert_eq!(escape_html("plain"), "plain");
+    }
+
+    #[test]
+    fn should_render_template() {
+        let info = NowPlayingInfo {
+            status: "playing",
+            artist: "AC/DC".to_string(),
+            title: "<Title>".to_string(),
+            album: String::new(),
+            position: "1:02".to_string(),
+            duration: "3:04".to_string(),
+            position_secs: 62,
+            duration_secs: 184,
+        };
+
+        assert_eq!(
+            info.render("[{status}] {artist} - {title} ({album}) {position}/{duration} {unknown}"),
+            "[playing] AC/DC - &lt;Title&gt; () 1:02/3:04 {unknown}"
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use termusiclib::config::v2::server::Backend as ConfigBackend;
 
 #[derive(Parser, Debug)]
+#[allow(clippy::struct_excessive_bools)] // independent cli flags, this is not a state machine
 // mostly read from `Cargo.toml`
 #[clap(name = "Termusic", author, version = env!("TERMUSIC_VERSION"), about, long_about=None)]
 pub struct Args {
@@ -27,6 +28,13 @@ pub struct Args {
     /// Select the backend, default is `rusty`
     #[arg(short, long, env = "TMS_BACKEND")]
     pub backend: Option<Backend>,
+    /// Print how long each startup phase took, after exiting.
+    #[arg(long)]
+    pub profile_startup: bool,
+    /// Replay the keys of a script FILE without showing the TUI, printing the screens the script
+    /// takes a `snapshot` of.
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
     #[clap(flatten)]
     pub log_options: LogOptions,
 }
@@ -76,6 +84,28 @@ pub enum Action {
         #[arg(value_name = "FILE")]
         file: PathBuf,
     },
+    /// Check the config, yt-dlp, network and the server's backend, audio output and database, printing hints for any problem.
+    Doctor,
+    /// Sync the playlists with the WebDAV folder or git repository configured in the tui config.
+    SyncPlaylists,
+    /// Manage the backups made before tags got written or library files got renamed.
+    Tags {
+        #[command(subcommand)]
+        action: TagsAction,
+    },
+}
+
+/// Subcommands for the tag backups
+#[derive(Subcommand, Debug)]
+pub enum TagsAction {
+    /// List the backups, newest first.
+    List,
+    /// Restore the files of a backup, undoing the tag writes and renames.
+    Restore {
+        /// The backup to restore, as shown by `list`, defaults to the newest one
+        #[arg(value_name = "ID")]
+        id: Option<String>,
+    },
 }
 
 const DEFAULT_LOGFILE_FILENAME: &str = "termusic-tui.log";
@@ -0,0 +1,231 @@
+//! The `doctor` subcommand, checking the TUI side and forwarding to `termusic-server doctor`.
+
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use reqwest::Url;
+use termusiclib::config::v2::tui::config_extra::{FILE_NAME, TuiConfigVersionedDefaulted};
+use termusiclib::doctor::{Check, CheckStatus, Report};
+use termusiclib::invidious::INVIDIOUS_DOMAINS;
+use termusiclib::podcast::db::Database as DBPod;
+use termusiclib::utils::get_app_config_path;
+
+use crate::{cli, find_active_server_process, get_server_binary_exe};
+
+/// Time until a service is considered unreachable.
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+/// The maximal amount of distinct podcast hosts to check, to not take forever with many subscriptions.
+const MAX_PODCAST_HOSTS: usize = 5;
+
+/// Run all TUI-side checks, then the server checks.
+///
+/// Returns a error if any check failed, so that the exit code reflects the result.
+pub async fn run(args: &cli::Args) -> Result<()> {
+    let mut report = Report::default();
+
+    check_config(&mut report);
+    check_ytdlp(&mut report);
+    check_network(&mut report).await;
+    check_server(&mut report, args);
+
+    println!("{}", report.summary());
+
+    let errors = report.count(CheckStatus::Error);
+    if errors > 0 {
+        bail!("{errors} checks failed");
+    }
+
+    Ok(())
+}
+
+/// Check that the TUI config can be loaded.
+fn check_config(report: &mut Report) {
+    let path = get_app_config_path().map_or_else(
+        |_| FILE_NAME.to_string(),
+        |v| v.join(FILE_NAME).display().to_string(),
+    );
+
+    match TuiConfigVersionedDefaulted::from_config_path() {
+        Ok(_) => report.push(Check::ok("tui config", format!("loaded \"{path}\""))),
+        Err(err) => report.push(Check::error(
+            "tui config",
+            format!("could not load \"{path}\": {err:#}"),
+            "fix the reported value, or move the file away to have a default written on next start",
+        )),
+    }
+}
+
+/// Check that `yt-dlp` is callable, which is required for downloading from youtube.
+fn check_ytdlp(report: &mut Report) {
+    const HINT: &str = "install yt-dlp and make sure it is in your PATH, it is required to download from youtube and other sites";
+
+    match Command::new("yt-dlp").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            report.push(Check::ok("yt-dlp", format!("version {}", version.trim())));
+        }
+        Ok(output) => report.push(Check::warning(
+            "yt-dlp",
+            format!(
+                "exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            HINT,
+        )),
+        Err(err) => report.push(Check::warning(
+            "yt-dlp",
+            format!("not callable: {err}"),
+            HINT,
+        )),
+    }
+}
+
+/// Check that the services used by the TUI can be reached.
+async fn check_network(report: &mut Report) {
+    let client = match reqwest::Client::builder().timeout(NETWORK_TIMEOUT).build() {
+        Ok(v) => v,
+        Err(err) => {
+            report.push(Check::error(
+                "network",
+                format!("could not create a http client: {err}"),
+                "check the system TLS setup",
+            ));
+            return;
+        }
+    };
+
+    let mut targets = vec![("invidious", INVIDIOUS_DOMAINS.to_string())];
+
+    let feeds = get_app_config_path()
+        .and_then(|path| DBPod::new(&path))
+        .and_then(|db| db.get_podcasts());
+    match feeds {
+        Ok(podcasts) => targets.extend(
+            feed_hosts(podcasts.iter().map(|v| v.url.as_str()))
+                .into_iter()
+                .map(|v| ("podcasts", v)),
+        ),
+        Err(err) => report.push(Check::warning(
+            "podcasts",
+            format!("could not read the podcast database: {err:#}"),
+            "check the file permissions of \"data.db\" in the config directory",
+        )),
+    }
+
+    for (name, url) in targets {
+        match client.head(&url).send().await {
+            // any response means the host is reachable, some feeds simply do not support HEAD
+            Ok(response) => report.push(Check::ok(
+                name,
+                format!("reached {url} ({})", response.status()),
+            )),
+            Err(err) => report.push(Check::warning(
+                name,
+                format!("could not reach {url}: {err}"),
+                "check your internet connection, proxy and firewall settings",
+            )),
+        }
+    }
+}
+
+/// Get the distinct origins (`scheme://host`) of the given feed urls, limited to [`MAX_PODCAST_HOSTS`].
+fn feed_hosts<'a>(urls: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut hosts: Vec<String> = Vec::new();
+    for url in urls {
+        let Ok(url) = Url::parse(url) else {
+            continue;
+        };
+        let Some(host) = url.host_str() else {
+            continue;
+        };
+        let origin = format!("{}://{host}", url.scheme());
+        if !hosts.contains(&origin) {
+            hosts.push(origin);
+        }
+        if hosts.len() >= MAX_PODCAST_HOSTS {
+            break;
+        }
+    }
+
+    hosts
+}
+
+/// Check for a running server and run the server checks.
+fn check_server(report: &mut Report, args: &cli::Args) {
+    if let Some(pid) = find_active_server_process() {
+        report.push(Check::ok("server", format!("already running as pid {pid}")));
+    }
+
+    let exe = match get_server_binary_exe() {
+        Ok(v) => v,
+        Err(err) => {
+            report.push(Check::error(
+                "server",
+                format!("could not resolve the server binary: {err:#}"),
+                "install termusic-server next to termusic or in your PATH",
+            ));
+            return;
+        }
+    };
+
+    println!("Running \"{} doctor\":", exe.display());
+
+    let mut cmd = Command::new(&exe);
+    if let Some(backend) = args.backend {
+        cmd.arg("--backend").arg(backend.as_str());
+    }
+    // the server prints its own results and summary
+    match cmd.arg("doctor").status() {
+        Ok(status) if status.success() => {
+            report.push(Check::ok(
+                "server",
+                "all server checks passed without errors",
+            ));
+        }
+        Ok(status) => report.push(Check::error(
+            "server",
+            format!("server checks {status}"),
+            "see the server results above",
+        )),
+        Err(err) => report.push(Check::error(
+            "server",
+            format!("could not run \"{}\": {err}", exe.display()),
+            "install termusic-server next to termusic or in your PATH",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{MAX_PODCAST_HOSTS, feed_hosts};
+
+    #[test]
+    fn should_collect_distinct_feed_hosts() {
+        let urls = [
+            "https://feeds.example.com/a.xml",
+            "https://feeds.example.com/b.xml",
+            "http://other.example.org/feed",
+            "not a url",
+        ];
+
+        assert_eq!(
+            feed_hosts(urls.into_iter()),
+            vec![
+                "https://feeds.example.com".to_string(),
+                "http://other.example.org".to_string()
+            ]
+        );
+
+        let many: Vec<String> = (0..10)
+            .map(|i| format!("https://host{i}.example/"))
+            .collect();
+        assert_eq!(
+            feed_hosts(many.iter().map(String::as_str)).len(),
+            MAX_PODCAST_HOSTS
+        );
+    }
+}
@@ -12,6 +12,7 @@ use clap::Parser;
 use flexi_logger::LogSpecification;
 use parking_lot::Mutex;
 use sysinfo::{Pid, ProcessStatus, System};
+use termusiclib::cache::CACHES;
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
 use termusiclib::config::v2::server::{ComProtocol, ScanDepth};
 use termusiclib::config::v2::tui::config_extra::TuiConfigVersionedDefaulted;
@@ -19,8 +20,10 @@ use termusiclib::config::{
     ServerOverlay, SharedServerSettings, SharedTuiSettings, TuiOverlay, new_shared_server_settings,
     new_shared_tui_settings,
 };
+use termusiclib::credentials::CREDENTIALS;
 use termusiclib::player::music_player_client::MusicPlayerClient;
-use termusiclib::{podcast, utils};
+use termusiclib::tag_backup::BackupStore;
+use termusiclib::{playlist_sync, podcast, utils};
 use tokio::io::AsyncReadExt;
 use tokio::process::{Child, Command};
 use tokio::sync::RwLock;
@@ -28,9 +31,12 @@ use tokio::task::AbortHandle;
 use tokio_util::sync::CancellationToken;
 
 use ui::UI;
+use ui::headless::parse_script;
 
 mod cli;
+mod doctor;
 mod logger;
+mod startup;
 mod ui;
 
 #[macro_use]
@@ -74,8 +80,20 @@ pub static SERVER_PID: OnceLock<Pid> = OnceLock::new();
 // #[tokio::main]
 async fn actual_main() -> Result<()> {
     let args = cli::Args::parse();
+    if args.profile_startup {
+        startup::enable();
+    }
     let mut logger_handle = logger::setup(&args);
+
+    // before loading the config, as the doctor reports a invalid config itself
+    if let Some(cli::Action::Doctor) = args.action {
+        return doctor::run(&args).await;
+    }
+
     let config = get_config(&args)?;
+    CACHES.apply_settings(&config.server.read().settings.cache);
+    CREDENTIALS.apply_settings(&config.server.read().settings.credentials);
+    startup::mark("config loaded");
 
     ctrl_c_handler().expect("Error setting Ctrl-C handler");
 
@@ -83,6 +101,19 @@ async fn actual_main() -> Result<()> {
         return execute_action(action, &config).await;
     }
 
+    // before starting the server, so that a invalid script does not need to wait for it
+    let script = match &args.script {
+        Some(path) => {
+            let script = std::fs::read_to_string(path)
+                .with_context(|| format!("read script {}", path.display()))?;
+            Some(
+                parse_script(&script)
+                    .with_context(|| format!("parse script {}", path.display()))?,
+            )
+        }
+        None => None,
+    };
+
     // launch the daemon if it isn't already
     let (pid, child) = {
         let active_pid = find_active_server_process();
@@ -96,8 +127,12 @@ async fn actual_main() -> Result<()> {
     };
 
     let server_output = child.map(collect_server_output);
+    startup::mark("server found or launched");
 
-    println!("Server process ID: {pid}");
+    // the output of a script should only be its snapshots
+    if script.is_none() {
+        println!("Server process ID: {pid}");
+    }
     SERVER_PID
         .set(Pid::from_u32(pid))
         .unwrap_or_else(|_| error!("Could not set SERVER_PID."));
@@ -134,13 +169,24 @@ async fn actual_main() -> Result<()> {
         }
     };
     info!("Connected on {addr}");
+    startup::mark("server connected");
 
     if let Some(server_output) = server_output {
         server_output.cancel_token.cancel();
     }
 
-    let mut ui = UI::new(config, client).await?;
-    ui.run()?;
+    if let Some(script) = script {
+        UI::run_script(config, client, &script, &mut std::io::stdout()).await?;
+    } else {
+        let mut ui = UI::new(config, client).await?;
+        startup::mark("ui created");
+        ui.run()?;
+    }
+
+    if let Some(report) = startup::report() {
+        info!("{report}");
+        print!("{report}");
+    }
 
     info!("Bye");
 
@@ -347,7 +393,7 @@ async fn wait_till_connected_tcp(
         let status = sys.process(sys_pid);
 
         // dont endlessly try to connect, if the server exited / crashed
-        if status.is_none() || status.is_some_and(|v| v.status() == ProcessStatus::Zombie) {
+        if status.is_none_or(|v| v.status() == ProcessStatus::Zombie) {
             anyhow::bail!("Process {pid} exited before being able to connect!");
         }
 
@@ -407,7 +453,7 @@ async fn wait_till_connected_uds(
         let status = sys.process(sys_pid);
 
         // dont endlessly try to connect, if the server exited / crashed
-        if status.is_none() || status.is_some_and(|v| v.status() == ProcessStatus::Zombie) {
+        if status.is_none_or(|v| v.status() == ProcessStatus::Zombie) {
             anyhow::bail!("Process {pid} exited before being able to connect!");
         }
 
@@ -474,7 +520,18 @@ fn find_source<E: Error + 'static>(err: &dyn Error) -> Option<&E> {
 }
 
 fn get_config(args: &cli::Args) -> Result<CombinedSettings> {
-    let config_server = ServerConfigVersionedDefaulted::from_config_path()?.into_settings();
+    // both files are independent, so read and parse them at the same time
+    let (config_server, config_tui) = std::thread::scope(|s| {
+        let tui = s.spawn(TuiConfigVersionedDefaulted::from_config_path);
+        let server = ServerConfigVersionedDefaulted::from_config_path();
+
+        (server, tui.join())
+    });
+    let config_server = config_server?.into_settings();
+    let config_tui = match config_tui {
+        Ok(config_tui) => config_tui?.into_settings(),
+        Err(panic) => std::panic::resume_unwind(panic),
+    };
 
     let max_depth = args.max_depth.map(ScanDepth::Limited);
 
@@ -491,8 +548,6 @@ fn get_config(args: &cli::Args) -> Result<CombinedSettings> {
         metadata_scan_depth: max_depth,
     };
 
-    let config_tui = TuiConfigVersionedDefaulted::from_config_path()?.into_settings();
-
     let coverart_hidden_overwrite = if args.hide_cover { Some(true) } else { None };
 
     let overlay_tui = TuiOverlay {
@@ -550,6 +605,65 @@ async fn execute_action(action: cli::Action, config: &CombinedSettings) -> Resul
                 utils::get_app_config_path().context("getting app-config-path")?;
             podcast::export_to_opml(&config_dir_path, &path).context("export opml")?;
         }
+        cli::Action::SyncPlaylists => {
+            let settings = config.tui.read().settings.playlist_sync.clone();
+            let report = playlist_sync::sync_playlists(&settings)
+                .await
+                .context("sync playlists")?;
+            for (name, reason) in &report.conflicts {
+                println!("Conflict for \"{name}\": {reason}, kept it");
+            }
+            println!("Synced playlists: {report}");
+        }
+        cli::Action::Tags { action } => {
+            let store = BackupStore::from_settings(&config.tui.read().settings.tag_backups)?;
+            execute_tags_action(action, &store)?;
+        }
+        cli::Action::Doctor => unreachable!("handled before loading the config"),
+    }
+
+    Ok(())
+}
+
+fn execute_tags_action(action: cli::TagsAction, store: &BackupStore) -> Result<()> {
+    match action {
+        cli::TagsAction::List => {
+            let backups = store.list().context("list tag backups")?;
+            if backups.is_empty() {
+                println!("No backups");
+            }
+            for backup in backups {
+                let created = chrono::DateTime::parse_from_rfc3339(&backup.manifest.created)
+                    .map_or(backup.manifest.created.clone(), |v| {
+                        v.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M:%S")
+                            .to_string()
+                    });
+                println!(
+                    "{}  {created}  {} ({} files)",
+                    backup.id,
+                    backup.manifest.description,
+                    backup.manifest.entries.len()
+                );
+            }
+        }
+        cli::TagsAction::Restore { id } => {
+            let id = match id {
+                Some(v) => v,
+                None => store.latest()?.context("No backups to restore")?,
+            };
+            let report = store
+                .restore(&id)
+                .with_context(|| format!("restore backup {id}"))?;
+            for err in &report.errors {
+                println!("Error: {err}");
+            }
+            println!(
+                "Restored {} files of backup {id}, {} failed",
+                report.restored,
+                report.errors.len()
+            );
+        }
     }
 
     Ok(())
@@ -0,0 +1,86 @@
+//! Module for the startup timing report of `--profile-startup`
+
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// The phases reached so far, [`None`] if profiling is not enabled.
+static PROFILE: Mutex<Option<StartupProfile>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct StartupProfile {
+    start: Instant,
+    /// The phases in the order they were reached, with the time since the start
+    phases: Vec<(&'static str, Duration)>,
+}
+
+/// Start profiling the startup, the phases are timed from now.
+pub fn enable() {
+    *PROFILE.lock() = Some(StartupProfile {
+        start: Instant::now(),
+        phases: Vec::new(),
+    });
+}
+
+/// Record that `phase` was reached, only the first time. No-op if profiling is not enabled.
+pub fn mark(phase: &'static str) {
+    let mut profile = PROFILE.lock();
+    let Some(profile) = profile.as_mut() else {
+        return;
+    };
+
+    if !profile.phases.iter().any(|(name, _)| *name == phase) {
+        let elapsed = profile.start.elapsed();
+        profile.phases.push((phase, elapsed));
+    }
+}
+
+/// Get the report of the phases reached so far, [`None`] if profiling is not enabled.
+pub fn report() -> Option<String> {
+    PROFILE
+        .lock()
+        .as_ref()
+        .map(|profile| format_report(&profile.phases))
+}
+
+/// Format `phases` as lines of the time since the start and since the previous phase.
+fn format_report(phases: &[(&'static str, Duration)]) -> String {
+    let width = phases.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    let mut report = String::from("Startup profile:\n");
+    let mut previous = Duration::ZERO;
+    for (name, elapsed) in phases {
+        let _ = writeln!(
+            report,
+            "  {name:<width$}  {:>8.1}ms  (+{:.1}ms)",
+            elapsed.as_secs_f64() * 1000.0,
+            elapsed.saturating_sub(previous).as_secs_f64() * 1000.0,
+        );
+        previous = *elapsed;
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::format_report;
+
+    #[test]
+    fn should_format_report() {
+        let phases = [
+            ("config", Duration::from_micros(12_300)),
+            ("first draw", Duration::from_millis(150)),
+        ];
+
+        assert_eq!(
+            format_report(&phases),
+            "Startup profile:\n  config          12.3ms  (+12.3ms)\n  first draw     150.0ms  (+137.7ms)\n"
+        );
+    }
+}
@@ -22,6 +22,7 @@
  * SOFTWARE.
  */
 use anyhow::Result;
+use termusiclib::config::v2::server::backends::{CROSSFADE_SECS_MAX, CrossfadeCurve};
 use termusiclib::config::v2::server::{Backend, ComProtocol, default_uds_socket_path};
 use termusiclib::config::v2::tui::theme::styles::ColorTermusic;
 use termusiclib::config::v2::tui::{Alignment as XywhAlign, keys::Keys};
@@ -225,6 +226,198 @@ impl Component<Msg, UserEvent> for ExitConfirmation {
     }
 }
 
+#[derive(MockComponent)]
+pub struct ExitConfirmationPlaying {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl ExitConfirmationPlaying {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.confirm_quit_only_playing;
+        let component = common_radio_comp(&config_r, " Exit confirmation only while playing? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for ExitConfirmationPlaying {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfirmClearPlaylist {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl ConfirmClearPlaylist {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.confirm_clear_playlist;
+        let component = common_radio_comp(&config_r, " Confirm clearing the playlist? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for ConfirmClearPlaylist {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfirmDelete {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl ConfirmDelete {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.confirm_delete;
+        let component = common_radio_comp(&config_r, " Confirm deleting files? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for ConfirmDelete {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfirmTagOverwrite {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl ConfirmTagOverwrite {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.confirm_tag_overwrite;
+        let component = common_radio_comp(&config_r, " Confirm overwriting tags? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for ConfirmTagOverwrite {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfirmQueueReplace {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl ConfirmQueueReplace {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.confirm_queue_replace;
+        let component = common_radio_comp(&config_r, " Preview replacing the playlist? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for ConfirmQueueReplace {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ShowKeyHints {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl ShowKeyHints {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.show_key_hints;
+        let component = common_radio_comp(&config_r, " Show key hints of the focused pane? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for ShowKeyHints {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)]
 fn handle_radio_ev(
     component: &mut dyn MockComponent,
@@ -688,6 +881,38 @@ impl Component<Msg, UserEvent> for KillDaemon {
     }
 }
 
+#[derive(MockComponent)]
+pub struct DeleteToTrash {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl DeleteToTrash {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let config_r = config.read();
+        let enabled = config_r.settings.behavior.delete_to_trash;
+        let component = common_radio_comp(&config_r, " Delete files to the trash? ")
+            .choices(["Yes", "No"])
+            .rewind(true)
+            .value(usize::from(!enabled));
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for DeleteToTrash {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
 #[derive(MockComponent)]
 pub struct PlayerUseMpris {
     component: Radio,
@@ -959,6 +1184,91 @@ impl Component<Msg, UserEvent> for PlayerBackend {
     }
 }
 
+#[derive(MockComponent)]
+pub struct PlayerCrossfade {
+    component: Input,
+    config: SharedTuiSettings,
+}
+
+impl PlayerCrossfade {
+    pub fn new(config: CombinedSettings) -> Self {
+        let component = {
+            let config_tui = config.tui.read();
+            common_input_comp(&config_tui, " Crossfade Seconds (rusty backend): ")
+                .input_type(InputType::UnsignedInteger)
+                .placeholder(
+                    format!("0 to disable, at most {CROSSFADE_SECS_MAX}"),
+                    Style::default().fg(Color::Rgb(128, 128, 128)),
+                )
+                .value(
+                    config
+                        .server
+                        .read()
+                        .settings
+                        .backends
+                        .rusty
+                        .crossfade_secs
+                        .to_string(),
+                )
+        };
+
+        Self {
+            component,
+            config: config.tui,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for PlayerCrossfade {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_input_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct PlayerCrossfadeCurve {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl PlayerCrossfadeCurve {
+    pub fn new(config: CombinedSettings) -> Self {
+        let config_tui = config.tui.read();
+        let value = match config.server.read().settings.backends.rusty.crossfade_curve {
+            CrossfadeCurve::Linear => 0,
+            CrossfadeCurve::Exponential => 1,
+        };
+        let component = common_radio_comp(&config_tui, " Crossfade Curve: ")
+            .choices(["Linear", "Exponential"])
+            .rewind(true)
+            .value(value);
+
+        drop(config_tui);
+        Self {
+            component,
+            config: config.tui,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for PlayerCrossfadeCurve {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_radio_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
 #[derive(MockComponent)]
 pub struct ExtraYtdlpArgs {
     component: Input,
@@ -997,6 +1307,98 @@ impl Component<Msg, UserEvent> for ExtraYtdlpArgs {
     }
 }
 
+#[derive(MockComponent)]
+pub struct SponsorBlockCategories {
+    component: Input,
+    config: SharedTuiSettings,
+}
+
+impl SponsorBlockCategories {
+    pub fn new(config: CombinedSettings) -> Self {
+        let categories = config
+            .server
+            .read()
+            .settings
+            .sponsorblock
+            .categories
+            .iter()
+            .map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let component = {
+            let config_tui = config.tui.read();
+            common_input_comp(&config_tui, " SponsorBlock categories to skip: ")
+                .input_type(InputType::Text)
+                .placeholder(
+                    "sponsor, selfpromo, intro, outro, music_offtopic",
+                    Style::default().fg(Color::Rgb(128, 128, 128)),
+                )
+                .value(&categories)
+        };
+
+        Self {
+            component,
+            config: config.tui,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for SponsorBlockCategories {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_input_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ContentFilterKeywords {
+    component: Input,
+    config: SharedTuiSettings,
+}
+
+impl ContentFilterKeywords {
+    pub fn new(config: CombinedSettings) -> Self {
+        let component = {
+            let config_tui = config.tui.read();
+            let filter = &config_tui.settings.content_filter;
+            let title = if filter.locked {
+                " Blocked youtube keywords (locked): "
+            } else {
+                " Blocked youtube keywords: "
+            };
+            common_input_comp(&config_tui, title)
+                .input_type(InputType::Text)
+                .placeholder(
+                    "comma separated, hides search and trending results",
+                    Style::default().fg(Color::Rgb(128, 128, 128)),
+                )
+                .value(filter.blocked_keywords.join(", "))
+        };
+
+        Self {
+            component,
+            config: config.tui,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for ContentFilterKeywords {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        handle_input_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Next)),
+            Msg::ConfigEditor(ConfigEditorMsg::General(KFMsg::Previous)),
+        )
+    }
+}
+
 impl Model {
     /// Mount / Remount the Config-Editor's First Page, the General Options
     #[allow(clippy::too_many_lines)]
@@ -1014,6 +1416,44 @@ impl Model {
             Vec::new(),
         )?;
 
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(
+                IdCEGeneral::ExitConfirmationPlaying,
+            )),
+            Box::new(ExitConfirmationPlaying::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmClearPlaylist)),
+            Box::new(ConfirmClearPlaylist::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmDelete)),
+            Box::new(ConfirmDelete::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmTagOverwrite)),
+            Box::new(ConfirmTagOverwrite::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmQueueReplace)),
+            Box::new(ConfirmQueueReplace::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ShowKeyHints)),
+            Box::new(ShowKeyHints::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistDisplaySymbol)),
             Box::new(PlaylistDisplaySymbol::new(self.config_tui.clone())),
@@ -1074,6 +1514,12 @@ impl Model {
             Vec::new(),
         )?;
 
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::DeleteToTrash)),
+            Box::new(DeleteToTrash::new(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUseMpris)),
             Box::new(PlayerUseMpris::new(self.get_combined_settings())),
@@ -1116,12 +1562,36 @@ impl Model {
             Vec::new(),
         )?;
 
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerCrossfade)),
+            Box::new(PlayerCrossfade::new(self.get_combined_settings())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerCrossfadeCurve)),
+            Box::new(PlayerCrossfadeCurve::new(self.get_combined_settings())),
+            Vec::new(),
+        )?;
+
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ExtraYtdlpArgs)),
             Box::new(ExtraYtdlpArgs::new(self.get_combined_settings())),
             Vec::new(),
         )?;
 
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::SponsorBlockCategories)),
+            Box::new(SponsorBlockCategories::new(self.get_combined_settings())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ContentFilterKeywords)),
+            Box::new(ContentFilterKeywords::new(self.get_combined_settings())),
+            Vec::new(),
+        )?;
+
         Ok(())
     }
 
@@ -1135,6 +1605,30 @@ impl Model {
             IdCEGeneral::ExitConfirmation,
         )))?;
 
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ExitConfirmationPlaying,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ConfirmClearPlaylist,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ConfirmDelete,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ConfirmTagOverwrite,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ConfirmQueueReplace,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ShowKeyHints,
+        )))?;
+
         self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
             IdCEGeneral::PlaylistDisplaySymbol,
         )))?;
@@ -1171,6 +1665,10 @@ impl Model {
             IdCEGeneral::KillDamon,
         )))?;
 
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::DeleteToTrash,
+        )))?;
+
         self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
             IdCEGeneral::PlayerUseMpris,
         )))?;
@@ -1199,10 +1697,26 @@ impl Model {
             IdCEGeneral::PlayerBackend,
         )))?;
 
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::PlayerCrossfade,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::PlayerCrossfadeCurve,
+        )))?;
+
         self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
             IdCEGeneral::ExtraYtdlpArgs,
         )))?;
 
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::SponsorBlockCategories,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::General(
+            IdCEGeneral::ContentFilterKeywords,
+        )))?;
+
         Ok(())
     }
 }
@@ -982,11 +982,46 @@ impl KEModifierSelect {
             IdKey::Other(IdKeyOther::DatabaseAddSelected) => {
                 keys.database_keys.add_selected.mod_key()
             }
+            IdKey::Other(IdKeyOther::DatabaseReplacePlaylist) => {
+                keys.database_keys.replace_playlist.mod_key()
+            }
+            IdKey::Other(IdKeyOther::DatabaseMarkReviewed) => {
+                keys.database_keys.mark_reviewed.mod_key()
+            }
+            IdKey::Other(IdKeyOther::DatabaseMarkAllReviewed) => {
+                keys.database_keys.mark_all_reviewed.mod_key()
+            }
+            IdKey::Other(IdKeyOther::DatabaseLocate) => keys.database_keys.locate.mod_key(),
+            IdKey::Other(IdKeyOther::DatabaseVerifyLibrary) => {
+                keys.database_keys.verify_library.mod_key()
+            }
+            IdKey::Other(IdKeyOther::DatabaseNewSmartPlaylist) => {
+                keys.database_keys.new_smart_playlist.mod_key()
+            }
+            IdKey::Other(IdKeyOther::DatabaseEditSmartPlaylist) => {
+                keys.database_keys.edit_smart_playlist.mod_key()
+            }
             IdKey::Global(IdKeyGlobal::Config) => keys.select_view_keys.open_config.mod_key(),
             IdKey::Global(IdKeyGlobal::Down) => keys.navigation_keys.down.mod_key(),
             IdKey::Global(IdKeyGlobal::GotoBottom) => keys.navigation_keys.goto_bottom.mod_key(),
             IdKey::Global(IdKeyGlobal::GotoTop) => keys.navigation_keys.goto_top.mod_key(),
             IdKey::Global(IdKeyGlobal::Help) => keys.select_view_keys.open_help.mod_key(),
+            IdKey::Global(IdKeyGlobal::GlobalSearch) => {
+                keys.select_view_keys.open_global_search.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::Credits) => keys.select_view_keys.open_credits.mod_key(),
+            IdKey::Global(IdKeyGlobal::Downloads) => keys.select_view_keys.open_downloads.mod_key(),
+            IdKey::Global(IdKeyGlobal::Radio) => keys.select_view_keys.open_radio.mod_key(),
+            IdKey::Global(IdKeyGlobal::Equalizer) => keys.select_view_keys.open_equalizer.mod_key(),
+            IdKey::Global(IdKeyGlobal::OutputDevices) => {
+                keys.select_view_keys.open_output_devices.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::Statistics) => {
+                keys.select_view_keys.open_statistics.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::TrackSuggestions) => {
+                keys.select_view_keys.toggle_track_suggestions.mod_key()
+            }
             IdKey::Global(IdKeyGlobal::LayoutTreeview) => {
                 keys.select_view_keys.view_library.mod_key()
             }
@@ -1001,9 +1036,34 @@ impl KEModifierSelect {
                 keys.lyric_keys.adjust_offset_backwards.mod_key()
             }
             IdKey::Global(IdKeyGlobal::LyricCycle) => keys.lyric_keys.cycle_frames.mod_key(),
+            IdKey::Global(IdKeyGlobal::LyricSearch) => keys.lyric_keys.search.mod_key(),
+            IdKey::Global(IdKeyGlobal::LyricToggleTranslation) => {
+                keys.lyric_keys.toggle_translation.mod_key()
+            }
             IdKey::Global(IdKeyGlobal::PlayerToggleGapless) => {
                 keys.player_keys.toggle_prefetch.mod_key()
             }
+            IdKey::Global(IdKeyGlobal::PlayerToggleKaraoke) => {
+                keys.player_keys.toggle_karaoke.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleRecording) => {
+                keys.player_keys.toggle_recording.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleAlbumMode) => {
+                keys.player_keys.toggle_album_mode.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::PlayerTapTempo) => keys.player_keys.tap_tempo.mod_key(),
+            IdKey::Global(IdKeyGlobal::PlayerToggleNormalization) => {
+                keys.player_keys.toggle_normalization.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleSmartSpeed) => {
+                keys.player_keys.toggle_smart_speed.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::PlayerAbLoop) => keys.player_keys.ab_loop.mod_key(),
+            IdKey::Global(IdKeyGlobal::PlayerSeekToPosition) => {
+                keys.player_keys.seek_to_position.mod_key()
+            }
+            IdKey::Global(IdKeyGlobal::PlayerRateTrack) => keys.player_keys.rate_track.mod_key(),
             IdKey::Global(IdKeyGlobal::PlayerTogglePause) => {
                 keys.player_keys.toggle_pause.mod_key()
             }
@@ -1026,6 +1086,16 @@ impl KEModifierSelect {
             IdKey::Other(IdKeyOther::LibraryDelete) => keys.library_keys.delete.mod_key(),
             IdKey::Other(IdKeyOther::LibraryLoadDir) => keys.library_keys.load_dir.mod_key(),
             IdKey::Other(IdKeyOther::LibraryPaste) => keys.library_keys.paste.mod_key(),
+            IdKey::Other(IdKeyOther::LibraryCopy) => keys.library_keys.copy.mod_key(),
+            IdKey::Other(IdKeyOther::LibraryRename) => keys.library_keys.rename.mod_key(),
+            IdKey::Other(IdKeyOther::LibraryUndoDelete) => keys.library_keys.undo_delete.mod_key(),
+            IdKey::Other(IdKeyOther::LibrarySyncDevice) => keys.library_keys.sync_device.mod_key(),
+            IdKey::Other(IdKeyOther::LibraryFetchLyrics) => {
+                keys.library_keys.fetch_lyrics.mod_key()
+            }
+            IdKey::Other(IdKeyOther::LibraryReplacePlaylist) => {
+                keys.library_keys.replace_playlist.mod_key()
+            }
             IdKey::Other(IdKeyOther::LibrarySearch) => keys.library_keys.search.mod_key(),
             IdKey::Other(IdKeyOther::LibrarySearchYoutube) => {
                 keys.library_keys.youtube_search.mod_key()
@@ -1052,6 +1122,25 @@ impl KEModifierSelect {
             IdKey::Other(IdKeyOther::PlaylistAddRandomTracks) => {
                 keys.playlist_keys.add_random_songs.mod_key()
             }
+            IdKey::Other(IdKeyOther::PlaylistAddRandomUnplayed) => {
+                keys.playlist_keys.add_random_unplayed.mod_key()
+            }
+            IdKey::Other(IdKeyOther::PlaylistSyncDevice) => {
+                keys.playlist_keys.sync_device.mod_key()
+            }
+            IdKey::Other(IdKeyOther::PlaylistFetchLyrics) => {
+                keys.playlist_keys.fetch_lyrics.mod_key()
+            }
+            IdKey::Other(IdKeyOther::PlaylistSetOffsets) => {
+                keys.playlist_keys.set_offsets.mod_key()
+            }
+            IdKey::Other(IdKeyOther::PlaylistAddAutoDj) => keys.playlist_keys.add_auto_dj.mod_key(),
+            IdKey::Other(IdKeyOther::PlaylistAddBookmark) => {
+                keys.playlist_keys.add_bookmark.mod_key()
+            }
+            IdKey::Other(IdKeyOther::PlaylistShowBookmarks) => {
+                keys.playlist_keys.show_bookmarks.mod_key()
+            }
             IdKey::Other(IdKeyOther::LibrarySwitchRoot) => keys.library_keys.cycle_root.mod_key(),
             IdKey::Other(IdKeyOther::LibraryAddRoot) => keys.library_keys.add_root.mod_key(),
             IdKey::Other(IdKeyOther::LibraryRemoveRoot) => keys.library_keys.remove_root.mod_key(),
@@ -1313,6 +1402,62 @@ fn key_global_help(config: SharedTuiSettings) -> KEModifierSelect {
     KEModifierSelect::new(" Help ", IdKey::Global(IdKeyGlobal::Help), config)
 }
 
+#[inline]
+fn key_global_search(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Global Search ",
+        IdKey::Global(IdKeyGlobal::GlobalSearch),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_credits(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(" Credits ", IdKey::Global(IdKeyGlobal::Credits), config)
+}
+
+#[inline]
+fn key_global_downloads(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(" Downloads ", IdKey::Global(IdKeyGlobal::Downloads), config)
+}
+
+#[inline]
+fn key_global_radio(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(" Radio ", IdKey::Global(IdKeyGlobal::Radio), config)
+}
+
+#[inline]
+fn key_global_equalizer(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(" Equalizer ", IdKey::Global(IdKeyGlobal::Equalizer), config)
+}
+
+#[inline]
+fn key_global_output_devices(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Output Device ",
+        IdKey::Global(IdKeyGlobal::OutputDevices),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_statistics(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Statistics ",
+        IdKey::Global(IdKeyGlobal::Statistics),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_track_suggestions(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Track Suggestions ",
+        IdKey::Global(IdKeyGlobal::TrackSuggestions),
+        config,
+    )
+}
+
 #[inline]
 fn key_global_layout_treeview(config: SharedTuiSettings) -> KEModifierSelect {
     KEModifierSelect::new(
@@ -1486,6 +1631,87 @@ fn key_global_player_toggle_gapless(config: SharedTuiSettings) -> KEModifierSele
     )
 }
 
+#[inline]
+fn key_global_player_toggle_karaoke(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Karaoke Toggle ",
+        IdKey::Global(IdKeyGlobal::PlayerToggleKaraoke),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_toggle_recording(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Recording Toggle ",
+        IdKey::Global(IdKeyGlobal::PlayerToggleRecording),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_toggle_album_mode(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Album Mode Toggle ",
+        IdKey::Global(IdKeyGlobal::PlayerToggleAlbumMode),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_tap_tempo(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Tap Tempo ",
+        IdKey::Global(IdKeyGlobal::PlayerTapTempo),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_toggle_normalization(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Normalization Toggle ",
+        IdKey::Global(IdKeyGlobal::PlayerToggleNormalization),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_toggle_smart_speed(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Smart Speed Toggle ",
+        IdKey::Global(IdKeyGlobal::PlayerToggleSmartSpeed),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_ab_loop(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " A-B Loop ",
+        IdKey::Global(IdKeyGlobal::PlayerAbLoop),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_seek_to_position(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Seek To Timestamp ",
+        IdKey::Global(IdKeyGlobal::PlayerSeekToPosition),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_player_rate_track(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Rate Track ",
+        IdKey::Global(IdKeyGlobal::PlayerRateTrack),
+        config,
+    )
+}
+
 // --- Section Global Lyric Keys ---
 
 #[inline]
@@ -1515,6 +1741,24 @@ fn key_global_lyric_cycle(config: SharedTuiSettings) -> KEModifierSelect {
     )
 }
 
+#[inline]
+fn key_global_lyric_search(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Lyric Search ",
+        IdKey::Global(IdKeyGlobal::LyricSearch),
+        config,
+    )
+}
+
+#[inline]
+fn key_global_lyric_toggle_translation(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Lyric Translation ",
+        IdKey::Global(IdKeyGlobal::LyricToggleTranslation),
+        config,
+    )
+}
+
 // --- Section Global XYWH Keys ---
 
 #[inline]
@@ -1614,6 +1858,60 @@ fn key_library_paste(config: SharedTuiSettings) -> KEModifierSelect {
     )
 }
 
+#[inline]
+fn key_library_copy(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Library Copy ",
+        IdKey::Other(IdKeyOther::LibraryCopy),
+        config,
+    )
+}
+
+#[inline]
+fn key_library_rename(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Library Rename ",
+        IdKey::Other(IdKeyOther::LibraryRename),
+        config,
+    )
+}
+
+#[inline]
+fn key_library_undo_delete(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Library Undo Delete ",
+        IdKey::Other(IdKeyOther::LibraryUndoDelete),
+        config,
+    )
+}
+
+#[inline]
+fn key_library_sync_device(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Library Sync to Device ",
+        IdKey::Other(IdKeyOther::LibrarySyncDevice),
+        config,
+    )
+}
+
+#[inline]
+fn key_library_fetch_lyrics(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Library Fetch Lyrics ",
+        IdKey::Other(IdKeyOther::LibraryFetchLyrics),
+        config,
+    )
+}
+
+#[inline]
+fn key_library_replace_playlist(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Library Replace Playlist ",
+        IdKey::Other(IdKeyOther::LibraryReplacePlaylist),
+        config,
+    )
+}
+
 #[inline]
 fn key_library_search(config: SharedTuiSettings) -> KEModifierSelect {
     KEModifierSelect::new(
@@ -1751,6 +2049,15 @@ fn key_playlist_add_random_ablum(config: SharedTuiSettings) -> KEModifierSelect
     )
 }
 
+#[inline]
+fn key_playlist_add_random_unplayed(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Select Unplayed ",
+        IdKey::Other(IdKeyOther::PlaylistAddRandomUnplayed),
+        config,
+    )
+}
+
 #[inline]
 fn key_playlist_add_random_tracks(config: SharedTuiSettings) -> KEModifierSelect {
     KEModifierSelect::new(
@@ -1760,6 +2067,60 @@ fn key_playlist_add_random_tracks(config: SharedTuiSettings) -> KEModifierSelect
     )
 }
 
+#[inline]
+fn key_playlist_sync_device(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Sync to Device ",
+        IdKey::Other(IdKeyOther::PlaylistSyncDevice),
+        config,
+    )
+}
+
+#[inline]
+fn key_playlist_fetch_lyrics(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Fetch Lyrics ",
+        IdKey::Other(IdKeyOther::PlaylistFetchLyrics),
+        config,
+    )
+}
+
+#[inline]
+fn key_playlist_set_offsets(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Set Offsets ",
+        IdKey::Other(IdKeyOther::PlaylistSetOffsets),
+        config,
+    )
+}
+
+#[inline]
+fn key_playlist_add_auto_dj(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Add Auto-DJ ",
+        IdKey::Other(IdKeyOther::PlaylistAddAutoDj),
+        config,
+    )
+}
+
+#[inline]
+fn key_playlist_add_bookmark(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Add Bookmark ",
+        IdKey::Other(IdKeyOther::PlaylistAddBookmark),
+        config,
+    )
+}
+
+#[inline]
+fn key_playlist_show_bookmarks(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Playlist Show Bookmarks ",
+        IdKey::Other(IdKeyOther::PlaylistShowBookmarks),
+        config,
+    )
+}
+
 // --- Section Database Keys ---
 
 #[inline]
@@ -1780,6 +2141,69 @@ fn key_database_add_selected(config: SharedTuiSettings) -> KEModifierSelect {
     )
 }
 
+#[inline]
+fn key_database_replace_playlist(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database Replace Playlist ",
+        IdKey::Other(IdKeyOther::DatabaseReplacePlaylist),
+        config,
+    )
+}
+
+#[inline]
+fn key_database_mark_reviewed(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database Mark Reviewed ",
+        IdKey::Other(IdKeyOther::DatabaseMarkReviewed),
+        config,
+    )
+}
+
+#[inline]
+fn key_database_mark_all_reviewed(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database Mark All Reviewed ",
+        IdKey::Other(IdKeyOther::DatabaseMarkAllReviewed),
+        config,
+    )
+}
+
+#[inline]
+fn key_database_locate(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database Locate in Library ",
+        IdKey::Other(IdKeyOther::DatabaseLocate),
+        config,
+    )
+}
+
+#[inline]
+fn key_database_verify_library(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database Verify Library ",
+        IdKey::Other(IdKeyOther::DatabaseVerifyLibrary),
+        config,
+    )
+}
+
+#[inline]
+fn key_database_new_smart_playlist(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database New Smart Playlist ",
+        IdKey::Other(IdKeyOther::DatabaseNewSmartPlaylist),
+        config,
+    )
+}
+
+#[inline]
+fn key_database_edit_smart_playlist(config: SharedTuiSettings) -> KEModifierSelect {
+    KEModifierSelect::new(
+        " Database Edit Smart Playlist ",
+        IdKey::Other(IdKeyOther::DatabaseEditSmartPlaylist),
+        config,
+    )
+}
+
 // --- Section Podcast Keys ---
 
 #[inline]
@@ -1935,6 +2359,46 @@ impl Model {
             Box::new(key_global_help(self.config_tui.clone())),
             Vec::new(),
         )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::GlobalSearch)),
+            Box::new(key_global_search(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::Credits)),
+            Box::new(key_global_credits(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::Downloads)),
+            Box::new(key_global_downloads(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::Radio)),
+            Box::new(key_global_radio(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::Equalizer)),
+            Box::new(key_global_equalizer(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::OutputDevices)),
+            Box::new(key_global_output_devices(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::Statistics)),
+            Box::new(key_global_statistics(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::TrackSuggestions)),
+            Box::new(key_global_track_suggestions(self.config_tui.clone())),
+            Vec::new(),
+        )?;
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerVolumeUp)),
             Box::new(key_global_player_volume_up(self.config_tui.clone())),
@@ -1982,11 +2446,80 @@ impl Model {
             Box::new(key_global_lyric_cycle(self.config_tui.clone())),
             Vec::new(),
         )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::LyricSearch)),
+            Box::new(key_global_lyric_search(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::LyricToggleTranslation,
+            )),
+            Box::new(key_global_lyric_toggle_translation(self.config_tui.clone())),
+            Vec::new(),
+        )?;
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerToggleGapless)),
             Box::new(key_global_player_toggle_gapless(self.config_tui.clone())),
             Vec::new(),
         )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerToggleKaraoke)),
+            Box::new(key_global_player_toggle_karaoke(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleRecording,
+            )),
+            Box::new(key_global_player_toggle_recording(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleAlbumMode,
+            )),
+            Box::new(key_global_player_toggle_album_mode(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerTapTempo)),
+            Box::new(key_global_player_tap_tempo(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleNormalization,
+            )),
+            Box::new(key_global_player_toggle_normalization(
+                self.config_tui.clone(),
+            )),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleSmartSpeed,
+            )),
+            Box::new(key_global_player_toggle_smart_speed(
+                self.config_tui.clone(),
+            )),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerAbLoop)),
+            Box::new(key_global_player_ab_loop(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerSeekToPosition)),
+            Box::new(key_global_player_seek_to_position(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::PlayerRateTrack)),
+            Box::new(key_global_player_rate_track(self.config_tui.clone())),
+            Vec::new(),
+        )?;
 
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::KeyGlobal(IdKeyGlobal::LayoutTreeview)),
@@ -2079,6 +2612,36 @@ impl Model {
             Box::new(key_library_paste(self.config_tui.clone())),
             Vec::new(),
         )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibraryCopy)),
+            Box::new(key_library_copy(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibraryRename)),
+            Box::new(key_library_rename(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibraryUndoDelete)),
+            Box::new(key_library_undo_delete(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibrarySyncDevice)),
+            Box::new(key_library_sync_device(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibraryFetchLyrics)),
+            Box::new(key_library_fetch_lyrics(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibraryReplacePlaylist)),
+            Box::new(key_library_replace_playlist(self.config_tui.clone())),
+            Vec::new(),
+        )?;
         self.app.remount(
             Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::LibrarySearch)),
             Box::new(key_library_search(self.config_tui.clone())),
@@ -2172,6 +2735,50 @@ impl Model {
             Vec::new(),
         )?;
 
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(
+                IdKeyOther::PlaylistAddRandomUnplayed,
+            )),
+            Box::new(key_playlist_add_random_unplayed(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::PlaylistSyncDevice)),
+            Box::new(key_playlist_sync_device(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::PlaylistFetchLyrics)),
+            Box::new(key_playlist_fetch_lyrics(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::PlaylistSetOffsets)),
+            Box::new(key_playlist_set_offsets(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::PlaylistAddAutoDj)),
+            Box::new(key_playlist_add_auto_dj(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::PlaylistAddBookmark)),
+            Box::new(key_playlist_add_bookmark(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::PlaylistShowBookmarks)),
+            Box::new(key_playlist_show_bookmarks(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
         Ok(())
     }
 
@@ -2189,6 +2796,56 @@ impl Model {
             Vec::new(),
         )?;
 
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(
+                IdKeyOther::DatabaseReplacePlaylist,
+            )),
+            Box::new(key_database_replace_playlist(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::DatabaseMarkReviewed)),
+            Box::new(key_database_mark_reviewed(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(
+                IdKeyOther::DatabaseMarkAllReviewed,
+            )),
+            Box::new(key_database_mark_all_reviewed(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::DatabaseLocate)),
+            Box::new(key_database_locate(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(IdKeyOther::DatabaseVerifyLibrary)),
+            Box::new(key_database_verify_library(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(
+                IdKeyOther::DatabaseNewSmartPlaylist,
+            )),
+            Box::new(key_database_new_smart_playlist(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
+        self.app.remount(
+            Id::ConfigEditor(IdConfigEditor::KeyOther(
+                IdKeyOther::DatabaseEditSmartPlaylist,
+            )),
+            Box::new(key_database_edit_smart_playlist(self.config_tui.clone())),
+            Vec::new(),
+        )?;
+
         Ok(())
     }
 
@@ -2302,6 +2959,38 @@ impl Model {
             .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
                 IdKeyGlobal::Help,
             )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::GlobalSearch,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::Credits,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::Downloads,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::Radio,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::Equalizer,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::OutputDevices,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::Statistics,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::TrackSuggestions,
+            )))?;
         self.app
             .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
                 IdKeyGlobal::PlayerVolumeUp,
@@ -2339,6 +3028,14 @@ impl Model {
             .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
                 IdKeyGlobal::LyricCycle,
             )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::LyricSearch,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::LyricToggleTranslation,
+            )))?;
         self.app
             .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
                 IdKeyGlobal::LayoutDatabase,
@@ -2351,6 +3048,42 @@ impl Model {
             .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
                 IdKeyGlobal::PlayerToggleGapless,
             )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleKaraoke,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleRecording,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleAlbumMode,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerTapTempo,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleNormalization,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerToggleSmartSpeed,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerAbLoop,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerSeekToPosition,
+            )))?;
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
+                IdKeyGlobal::PlayerRateTrack,
+            )))?;
         self.app
             .umount(&Id::ConfigEditor(IdConfigEditor::KeyGlobal(
                 IdKeyGlobal::Config,
@@ -2411,6 +3144,24 @@ impl Model {
         self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
             IdKeyOther::LibraryPaste,
         )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::LibraryCopy,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::LibraryRename,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::LibraryUndoDelete,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::LibrarySyncDevice,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::LibraryFetchLyrics,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::LibraryReplacePlaylist,
+        )))?;
         self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
             IdKeyOther::LibrarySearch,
         )))?;
@@ -2471,6 +3222,30 @@ impl Model {
             IdKeyOther::PlaylistAddRandomTracks,
         )))?;
 
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistAddRandomUnplayed,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistSyncDevice,
+        )))?;
+
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistFetchLyrics,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistSetOffsets,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistAddAutoDj,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistAddBookmark,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::PlaylistShowBookmarks,
+        )))?;
+
         Ok(())
     }
 
@@ -2479,6 +3254,27 @@ impl Model {
         self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
             IdKeyOther::DatabaseAddAll,
         )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseReplacePlaylist,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseMarkReviewed,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseMarkAllReviewed,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseLocate,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseVerifyLibrary,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseNewSmartPlaylist,
+        )))?;
+        self.app.umount(&Id::ConfigEditor(IdConfigEditor::KeyOther(
+            IdKeyOther::DatabaseEditSmartPlaylist,
+        )))?;
 
         Ok(())
     }
@@ -229,6 +229,25 @@ impl Model {
             IdKey::Other(IdKeyOther::DatabaseAddSelected) => {
                 keys.database_keys.add_selected = binding;
             }
+            IdKey::Other(IdKeyOther::DatabaseReplacePlaylist) => {
+                keys.database_keys.replace_playlist = binding;
+            }
+            IdKey::Other(IdKeyOther::DatabaseMarkReviewed) => {
+                keys.database_keys.mark_reviewed = binding;
+            }
+            IdKey::Other(IdKeyOther::DatabaseMarkAllReviewed) => {
+                keys.database_keys.mark_all_reviewed = binding;
+            }
+            IdKey::Other(IdKeyOther::DatabaseLocate) => keys.database_keys.locate = binding,
+            IdKey::Other(IdKeyOther::DatabaseVerifyLibrary) => {
+                keys.database_keys.verify_library = binding;
+            }
+            IdKey::Other(IdKeyOther::DatabaseNewSmartPlaylist) => {
+                keys.database_keys.new_smart_playlist = binding;
+            }
+            IdKey::Other(IdKeyOther::DatabaseEditSmartPlaylist) => {
+                keys.database_keys.edit_smart_playlist = binding;
+            }
             IdKey::Global(IdKeyGlobal::Config) => keys.select_view_keys.open_config = binding,
             IdKey::Global(IdKeyGlobal::Down) => keys.navigation_keys.down = binding,
             IdKey::Global(IdKeyGlobal::GotoBottom) => {
@@ -236,6 +255,24 @@ impl Model {
             }
             IdKey::Global(IdKeyGlobal::GotoTop) => keys.navigation_keys.goto_top = binding,
             IdKey::Global(IdKeyGlobal::Help) => keys.select_view_keys.open_help = binding,
+            IdKey::Global(IdKeyGlobal::GlobalSearch) => {
+                keys.select_view_keys.open_global_search = binding;
+            }
+            IdKey::Global(IdKeyGlobal::Credits) => keys.select_view_keys.open_credits = binding,
+            IdKey::Global(IdKeyGlobal::Downloads) => keys.select_view_keys.open_downloads = binding,
+            IdKey::Global(IdKeyGlobal::Radio) => keys.select_view_keys.open_radio = binding,
+            IdKey::Global(IdKeyGlobal::Equalizer) => {
+                keys.select_view_keys.open_equalizer = binding;
+            }
+            IdKey::Global(IdKeyGlobal::OutputDevices) => {
+                keys.select_view_keys.open_output_devices = binding;
+            }
+            IdKey::Global(IdKeyGlobal::Statistics) => {
+                keys.select_view_keys.open_statistics = binding;
+            }
+            IdKey::Global(IdKeyGlobal::TrackSuggestions) => {
+                keys.select_view_keys.toggle_track_suggestions = binding;
+            }
             IdKey::Global(IdKeyGlobal::LayoutTreeview) => {
                 keys.select_view_keys.view_library = binding;
             }
@@ -250,9 +287,40 @@ impl Model {
                 keys.lyric_keys.adjust_offset_backwards = binding;
             }
             IdKey::Global(IdKeyGlobal::LyricCycle) => keys.lyric_keys.cycle_frames = binding,
+            IdKey::Global(IdKeyGlobal::LyricSearch) => keys.lyric_keys.search = binding,
+            IdKey::Global(IdKeyGlobal::LyricToggleTranslation) => {
+                keys.lyric_keys.toggle_translation = binding;
+            }
             IdKey::Global(IdKeyGlobal::PlayerToggleGapless) => {
                 keys.player_keys.toggle_prefetch = binding;
             }
+            IdKey::Global(IdKeyGlobal::PlayerToggleKaraoke) => {
+                keys.player_keys.toggle_karaoke = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleRecording) => {
+                keys.player_keys.toggle_recording = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleAlbumMode) => {
+                keys.player_keys.toggle_album_mode = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerTapTempo) => {
+                keys.player_keys.tap_tempo = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleNormalization) => {
+                keys.player_keys.toggle_normalization = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerToggleSmartSpeed) => {
+                keys.player_keys.toggle_smart_speed = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerAbLoop) => {
+                keys.player_keys.ab_loop = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerSeekToPosition) => {
+                keys.player_keys.seek_to_position = binding;
+            }
+            IdKey::Global(IdKeyGlobal::PlayerRateTrack) => {
+                keys.player_keys.rate_track = binding;
+            }
             IdKey::Global(IdKeyGlobal::PlayerTogglePause) => {
                 keys.player_keys.toggle_pause = binding;
             }
@@ -285,6 +353,20 @@ impl Model {
             IdKey::Other(IdKeyOther::LibraryDelete) => keys.library_keys.delete = binding,
             IdKey::Other(IdKeyOther::LibraryLoadDir) => keys.library_keys.load_dir = binding,
             IdKey::Other(IdKeyOther::LibraryPaste) => keys.library_keys.paste = binding,
+            IdKey::Other(IdKeyOther::LibraryCopy) => keys.library_keys.copy = binding,
+            IdKey::Other(IdKeyOther::LibraryRename) => keys.library_keys.rename = binding,
+            IdKey::Other(IdKeyOther::LibraryUndoDelete) => {
+                keys.library_keys.undo_delete = binding;
+            }
+            IdKey::Other(IdKeyOther::LibrarySyncDevice) => {
+                keys.library_keys.sync_device = binding;
+            }
+            IdKey::Other(IdKeyOther::LibraryFetchLyrics) => {
+                keys.library_keys.fetch_lyrics = binding;
+            }
+            IdKey::Other(IdKeyOther::LibraryReplacePlaylist) => {
+                keys.library_keys.replace_playlist = binding;
+            }
             IdKey::Other(IdKeyOther::LibrarySearch) => keys.library_keys.search = binding,
             IdKey::Other(IdKeyOther::LibrarySearchYoutube) => {
                 keys.library_keys.youtube_search = binding;
@@ -311,6 +393,27 @@ impl Model {
             IdKey::Other(IdKeyOther::PlaylistAddRandomTracks) => {
                 keys.playlist_keys.add_random_songs = binding;
             }
+            IdKey::Other(IdKeyOther::PlaylistAddRandomUnplayed) => {
+                keys.playlist_keys.add_random_unplayed = binding;
+            }
+            IdKey::Other(IdKeyOther::PlaylistSyncDevice) => {
+                keys.playlist_keys.sync_device = binding;
+            }
+            IdKey::Other(IdKeyOther::PlaylistFetchLyrics) => {
+                keys.playlist_keys.fetch_lyrics = binding;
+            }
+            IdKey::Other(IdKeyOther::PlaylistSetOffsets) => {
+                keys.playlist_keys.set_offsets = binding;
+            }
+            IdKey::Other(IdKeyOther::PlaylistAddAutoDj) => {
+                keys.playlist_keys.add_auto_dj = binding;
+            }
+            IdKey::Other(IdKeyOther::PlaylistAddBookmark) => {
+                keys.playlist_keys.add_bookmark = binding;
+            }
+            IdKey::Other(IdKeyOther::PlaylistShowBookmarks) => {
+                keys.playlist_keys.show_bookmarks = binding;
+            }
             IdKey::Other(IdKeyOther::LibrarySwitchRoot) => keys.library_keys.cycle_root = binding,
             IdKey::Other(IdKeyOther::LibraryAddRoot) => keys.library_keys.add_root = binding,
             IdKey::Other(IdKeyOther::LibraryRemoveRoot) => keys.library_keys.remove_root = binding,
@@ -28,6 +28,8 @@ use std::path::PathBuf;
 use anyhow::{Result, bail};
 use include_dir::DirEntry;
 use termusiclib::THEME_DIR;
+use termusiclib::config::v2::server::backends::{CROSSFADE_SECS_MAX, CrossfadeCurve};
+use termusiclib::config::v2::server::sponsorblock::SponsorBlockCategory;
 use termusiclib::config::v2::server::{
     Backend, ComProtocol, PositionYesNo, PositionYesNoLower, RememberLastPosition,
 };
@@ -177,31 +179,42 @@ impl Model {
                     Some(match v {
                         IdCEGeneral::MusicDir => 0,
                         IdCEGeneral::ExitConfirmation => 1,
-                        IdCEGeneral::PlaylistDisplaySymbol => 2,
-                        IdCEGeneral::PlaylistRandomTrack => 3,
-                        IdCEGeneral::PlaylistRandomAlbum => 4,
-                        IdCEGeneral::PodcastDir => 5,
-                        IdCEGeneral::PodcastSimulDownload => 6,
-                        IdCEGeneral::PodcastMaxRetries => 7,
-                        IdCEGeneral::AlbumPhotoAlign => 8,
-                        IdCEGeneral::SaveLastPosition => 9,
-                        IdCEGeneral::SeekStep => 10,
-                        IdCEGeneral::KillDamon => 11,
-                        IdCEGeneral::PlayerUseMpris => 12,
-                        IdCEGeneral::PlayerUseDiscord => 13,
-                        IdCEGeneral::PlayerPort => 14,
-                        IdCEGeneral::PlayerAddress => 15,
-                        IdCEGeneral::PlayerProtocol => 16,
-                        IdCEGeneral::PlayerUDSPath => 17,
-                        IdCEGeneral::PlayerBackend => 18,
-                        IdCEGeneral::ExtraYtdlpArgs => 19,
+                        IdCEGeneral::ExitConfirmationPlaying => 2,
+                        IdCEGeneral::ConfirmClearPlaylist => 3,
+                        IdCEGeneral::ConfirmDelete => 4,
+                        IdCEGeneral::ConfirmTagOverwrite => 5,
+                        IdCEGeneral::ConfirmQueueReplace => 6,
+                        IdCEGeneral::ShowKeyHints => 7,
+                        IdCEGeneral::PlaylistDisplaySymbol => 8,
+                        IdCEGeneral::PlaylistRandomTrack => 9,
+                        IdCEGeneral::PlaylistRandomAlbum => 10,
+                        IdCEGeneral::PodcastDir => 11,
+                        IdCEGeneral::PodcastSimulDownload => 12,
+                        IdCEGeneral::PodcastMaxRetries => 13,
+                        IdCEGeneral::AlbumPhotoAlign => 14,
+                        IdCEGeneral::SaveLastPosition => 15,
+                        IdCEGeneral::SeekStep => 16,
+                        IdCEGeneral::KillDamon => 17,
+                        IdCEGeneral::DeleteToTrash => 18,
+                        IdCEGeneral::PlayerUseMpris => 19,
+                        IdCEGeneral::PlayerUseDiscord => 20,
+                        IdCEGeneral::PlayerPort => 21,
+                        IdCEGeneral::PlayerAddress => 22,
+                        IdCEGeneral::PlayerProtocol => 23,
+                        IdCEGeneral::PlayerUDSPath => 24,
+                        IdCEGeneral::PlayerBackend => 25,
+                        IdCEGeneral::PlayerCrossfade => 26,
+                        IdCEGeneral::PlayerCrossfadeCurve => 27,
+                        IdCEGeneral::ExtraYtdlpArgs => 28,
+                        IdCEGeneral::SponsorBlockCategories => 29,
+                        IdCEGeneral::ContentFilterKeywords => 30,
                     })
                 } else {
                     None
                 }
             });
 
-        let cells = UniformDynamicGrid::new(20, 3, 56 + 2)
+        let cells = UniformDynamicGrid::new(31, 3, 56 + 2)
             .draw_row_low_space()
             .distribute_row_space()
             .focus_node(focus_elem)
@@ -212,31 +225,42 @@ impl Model {
 
             &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::MusicDir)) => cells[0],
             &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ExitConfirmation)) => cells[1],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistDisplaySymbol)) => cells[2],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistRandomTrack)) => cells[3],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistRandomAlbum)) => cells[4],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PodcastDir)) => cells[5],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PodcastSimulDownload)) => cells[6],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PodcastMaxRetries)) => cells[7],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::AlbumPhotoAlign)) => cells[8],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::SaveLastPosition)) => cells[9],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::SeekStep)) => cells[10],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::KillDamon)) => cells[11],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUseMpris)) => cells[12],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUseDiscord)) => cells[13],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerPort)) => cells[14],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerAddress)) => cells[15],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerProtocol)) => cells[16],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUDSPath)) => cells[17],
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerBackend)) => cells[18],
-
-            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ExtraYtdlpArgs)) => cells[19],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ExitConfirmationPlaying)) => cells[2],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmClearPlaylist)) => cells[3],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmDelete)) => cells[4],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmTagOverwrite)) => cells[5],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmQueueReplace)) => cells[6],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ShowKeyHints)) => cells[7],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistDisplaySymbol)) => cells[8],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistRandomTrack)) => cells[9],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistRandomAlbum)) => cells[10],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PodcastDir)) => cells[11],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PodcastSimulDownload)) => cells[12],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PodcastMaxRetries)) => cells[13],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::AlbumPhotoAlign)) => cells[14],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::SaveLastPosition)) => cells[15],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::SeekStep)) => cells[16],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::KillDamon)) => cells[17],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::DeleteToTrash)) => cells[18],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUseMpris)) => cells[19],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUseDiscord)) => cells[20],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerPort)) => cells[21],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerAddress)) => cells[22],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerProtocol)) => cells[23],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUDSPath)) => cells[24],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerBackend)) => cells[25],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerCrossfade)) => cells[26],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerCrossfadeCurve)) => cells[27],
+
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ExtraYtdlpArgs)) => cells[28],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::SponsorBlockCategories)) => cells[29],
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ContentFilterKeywords)) => cells[30],
         }
     }
 
@@ -570,6 +594,10 @@ impl Model {
         self.mount_config_editor_components()
             .expect("Expected Config Editor Components to mount correctly");
 
+        // only needed for the theme selection, so not done on startup
+        if let Err(e) = Self::theme_extract_all() {
+            self.mount_error_popup(e.context("theme save"));
+        }
         if let Err(e) = self.theme_select_load_themes() {
             self.mount_error_popup(e.context("load themes"));
         }
@@ -723,6 +751,46 @@ impl Model {
             config_tui.settings.behavior.confirm_quit = matches!(exit_confirmation, 0);
         }
 
+        if let Ok(State::One(StateValue::Usize(confirm_quit_only_playing))) =
+            self.app.state(&Id::ConfigEditor(IdConfigEditor::General(
+                IdCEGeneral::ExitConfirmationPlaying,
+            )))
+        {
+            config_tui.settings.behavior.confirm_quit_only_playing =
+                matches!(confirm_quit_only_playing, 0);
+        }
+
+        if let Ok(State::One(StateValue::Usize(confirm_clear_playlist))) = self.app.state(
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmClearPlaylist)),
+        ) {
+            config_tui.settings.behavior.confirm_clear_playlist =
+                matches!(confirm_clear_playlist, 0);
+        }
+
+        if let Ok(State::One(StateValue::Usize(confirm_delete))) = self.app.state(
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmDelete)),
+        ) {
+            config_tui.settings.behavior.confirm_delete = matches!(confirm_delete, 0);
+        }
+
+        if let Ok(State::One(StateValue::Usize(confirm_tag_overwrite))) = self.app.state(
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmTagOverwrite)),
+        ) {
+            config_tui.settings.behavior.confirm_tag_overwrite = matches!(confirm_tag_overwrite, 0);
+        }
+
+        if let Ok(State::One(StateValue::Usize(confirm_queue_replace))) = self.app.state(
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ConfirmQueueReplace)),
+        ) {
+            config_tui.settings.behavior.confirm_queue_replace = matches!(confirm_queue_replace, 0);
+        }
+
+        if let Ok(State::One(StateValue::Usize(show_key_hints))) = self.app.state(
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ShowKeyHints)),
+        ) {
+            config_tui.settings.behavior.show_key_hints = matches!(show_key_hints, 0);
+        }
+
         if let Ok(State::One(StateValue::Usize(display_symbol))) = self.app.state(
             &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlaylistDisplaySymbol)),
         ) {
@@ -829,6 +897,12 @@ impl Model {
             config_tui.settings.behavior.quit_server_on_exit = matches!(kill_daemon, 0);
         }
 
+        if let Ok(State::One(StateValue::Usize(delete_to_trash))) = self.app.state(
+            &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::DeleteToTrash)),
+        ) {
+            config_tui.settings.behavior.delete_to_trash = matches!(delete_to_trash, 0);
+        }
+
         if let Ok(State::One(StateValue::Usize(player_use_mpris))) = self.app.state(
             &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::PlayerUseMpris)),
         ) {
@@ -907,11 +981,80 @@ impl Model {
             config_server.settings.player.backend = backend;
         }
 
+        if let Ok(State::One(StateValue::String(crossfade))) = self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::General(IdCEGeneral::PlayerCrossfade),
+        )) && let Ok(secs) = crossfade.parse::<u8>()
+        {
+            if secs <= CROSSFADE_SECS_MAX {
+                config_server.settings.backends.rusty.crossfade_secs = secs;
+            } else {
+                bail!(" The crossfade can be at most {CROSSFADE_SECS_MAX} seconds. ");
+            }
+        }
+
+        if let Ok(State::One(StateValue::Usize(curve))) = self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::General(IdCEGeneral::PlayerCrossfadeCurve),
+        )) {
+            let curve = match curve {
+                0 => CrossfadeCurve::Linear,
+                1 => CrossfadeCurve::Exponential,
+                // numbers are specified in "PlayerCrossfadeCurve"
+                _ => unreachable!(),
+            };
+            config_server.settings.backends.rusty.crossfade_curve = curve;
+        }
+
         if let Ok(State::One(StateValue::String(extra_ytdlp_args))) = self.app.state(
             &Id::ConfigEditor(IdConfigEditor::General(IdCEGeneral::ExtraYtdlpArgs)),
         ) {
             config_tui.settings.ytdlp.extra_args = extra_ytdlp_args;
         }
+
+        if let Ok(State::One(StateValue::String(categories))) = self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::General(IdCEGeneral::SponsorBlockCategories),
+        )) {
+            let mut parsed = Vec::new();
+            for name in categories.split(',').filter(|v| !v.trim().is_empty()) {
+                let Some(category) = SponsorBlockCategory::from_name(name) else {
+                    let valid = SponsorBlockCategory::ALL.map(SponsorBlockCategory::as_str);
+                    bail!(
+                        "Unknown SponsorBlock category \"{}\".\nValid categories are: {}",
+                        name.trim(),
+                        valid.join(", ")
+                    );
+                };
+                if !parsed.contains(&category) {
+                    parsed.push(category);
+                }
+            }
+            config_server.settings.sponsorblock.categories = parsed;
+        }
+
+        if let Ok(State::One(StateValue::String(keywords))) = self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::General(IdCEGeneral::ContentFilterKeywords),
+        )) {
+            let keywords: Vec<String> = keywords
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect();
+            let filter = &mut config_tui.settings.content_filter;
+            if filter.locked {
+                let current = filter
+                    .blocked_keywords
+                    .iter()
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty());
+                if !current.eq(keywords.iter().map(String::as_str)) {
+                    bail!(
+                        "The content filter is locked, it can only be changed in the config file (`content_filter`)"
+                    );
+                }
+            } else {
+                filter.blocked_keywords = keywords;
+            }
+        }
         Ok(())
     }
 
@@ -1,30 +1,42 @@
 use std::borrow::Cow;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::Result;
 use either::Either;
+use rusqlite::Connection;
+use termusiclib::audio_analysis::EnergyLevel;
+use termusiclib::audio_verify::VerifyFailure;
 use termusiclib::common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_FILE, UNKNOWN_TITLE};
 use termusiclib::config::SharedTuiSettings;
+use termusiclib::config::v2::tui::UntaggedAlbums;
 use termusiclib::config::v2::tui::keys::Keys;
-use termusiclib::new_database::track_ops::TrackRead;
-use termusiclib::new_database::{album_ops, artist_ops, track_ops};
-use termusiclib::track::{DurationFmtShort, Track};
-use termusiclib::utils::{is_playlist, playlist_get_vec};
+use termusiclib::new_database::track_ops::{TrackLabel, TrackRead};
+use termusiclib::new_database::{album_ops, artist_ops, smart_playlist_ops, track_ops};
+use termusiclib::track::{DurationFmtShort, QualityBadge, Track};
+use termusiclib::utils::{get_sort_key, is_playlist, playlist_get_vec};
+use tokio::runtime::Handle;
 use tui_realm_stdlib::List;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::props::{Alignment, BorderType, Table, TableBuilder, TextSpan};
-use tuirealm::props::{Borders, Style};
+use tuirealm::props::{Borders, PropPayload, PropValue, Style};
 use tuirealm::{
     AttrValue, Attribute, Component, Event, MockComponent, State, StateValue,
     event::{Key, KeyEvent, KeyModifiers},
 };
 
 use super::popups::{YNConfirm, YNConfirmStyle};
+use super::{SearchMatcher, SearchMode};
 use crate::ui::Model;
 use crate::ui::ids::Id;
 use crate::ui::model::UserEvent;
-use crate::ui::msg::{DBMsg, GSMsg, Msg, SearchCriteria};
+use crate::ui::model::quality_badges::quality_span;
+use crate::ui::model::track_labels::{label_for_key, with_label};
+use crate::ui::msg::{
+    DBMsg, DBResultsData, DBTracksData, GSMsg, Msg, QueueReplaceMsg, SearchCriteria,
+    SmartPlaylistMsg,
+};
 
 /// Helper trait to accomedate mutable access to `self` while also allowing access to other `self` properties for [`common_list_movement`].
 trait OnKeyDB {
@@ -114,9 +126,17 @@ fn common_list_movement<C: MockComponent + OnKeyDB>(
 enum DBCriteria {
     Artists,
     Albums,
+    Composers,
+    Works,
+    Labels,
+    Inbox,
+    Energy,
+    Quality,
+    Corrupted,
     Genres,
     Directories,
     Playlists,
+    SmartPlaylists,
 }
 
 impl DBCriteria {
@@ -124,7 +144,7 @@ impl DBCriteria {
     /// This is for example used to get exact space allocation for the layout.
     ///
     /// Note: keep this in-sync with [`Self::build_table`]
-    const NUM_OPTIONS: u16 = 5;
+    const NUM_OPTIONS: u16 = 13;
 
     fn build_table() -> Table {
         TableBuilder::default()
@@ -132,11 +152,27 @@ impl DBCriteria {
             .add_row()
             .add_col(TextSpan::from("Album"))
             .add_row()
+            .add_col(TextSpan::from("Composer"))
+            .add_row()
+            .add_col(TextSpan::from("Work"))
+            .add_row()
+            .add_col(TextSpan::from("Label"))
+            .add_row()
+            .add_col(TextSpan::from("Inbox"))
+            .add_row()
+            .add_col(TextSpan::from("Energy"))
+            .add_row()
+            .add_col(TextSpan::from("Quality"))
+            .add_row()
+            .add_col(TextSpan::from("Corrupted"))
+            .add_row()
             .add_col(TextSpan::from("Genre"))
             .add_row()
             .add_col(TextSpan::from("Directory"))
             .add_row()
             .add_col(TextSpan::from("Playlists"))
+            .add_row()
+            .add_col(TextSpan::from("Smart Playlists"))
             .build()
     }
 
@@ -146,9 +182,17 @@ impl DBCriteria {
         let res = match idx {
             0 => Self::Artists,
             1 => Self::Albums,
-            2 => Self::Genres,
-            3 => Self::Directories,
-            4 => Self::Playlists,
+            2 => Self::Composers,
+            3 => Self::Works,
+            4 => Self::Labels,
+            5 => Self::Inbox,
+            6 => Self::Energy,
+            7 => Self::Quality,
+            8 => Self::Corrupted,
+            9 => Self::Genres,
+            10 => Self::Directories,
+            11 => Self::Playlists,
+            12 => Self::SmartPlaylists,
             _ => return None,
         };
 
@@ -161,9 +205,17 @@ impl From<DBCriteria> for SearchCriteria {
         match value {
             DBCriteria::Artists => Self::Artist,
             DBCriteria::Albums => Self::Album,
+            DBCriteria::Composers => Self::Composer,
+            DBCriteria::Works => Self::Work,
+            DBCriteria::Labels => Self::Label,
+            DBCriteria::Inbox => Self::Inbox,
+            DBCriteria::Energy => Self::Energy,
+            DBCriteria::Quality => Self::Quality,
+            DBCriteria::Corrupted => Self::Corrupted,
             DBCriteria::Genres => Self::Genre,
             DBCriteria::Directories => Self::Directory,
             DBCriteria::Playlists => Self::Playlist,
+            DBCriteria::SmartPlaylists => Self::SmartPlaylist,
         }
     }
 }
@@ -246,6 +298,11 @@ impl Component<Msg, UserEvent> for DBListCriteria {
                 Event::Keyboard(keyevent) if keyevent == keys.library_keys.search.get() => {
                     return Either::Right(Msg::GeneralSearch(GSMsg::PopupShowDatabase));
                 }
+                Event::Keyboard(keyevent)
+                    if keyevent == keys.database_keys.verify_library.get() =>
+                {
+                    return Either::Right(Msg::DataBase(DBMsg::VerifyLibrary));
+                }
                 _ => CmdResult::None,
             };
 
@@ -270,7 +327,7 @@ impl AddAlbumConfirm {
     pub fn new(config: SharedTuiSettings, criteria: &str) -> Self {
         let component = YNConfirm::new_with_cb(
             config,
-            format!(" Are you sure you want to add EVERYTHING from {criteria}? ",),
+            format!(" Are you sure you want to add EVERYTHING from {criteria}? "),
             |config| YNConfirmStyle {
                 foreground_color: config.settings.theme.important_popup_foreground(),
                 background_color: config.settings.theme.important_popup_background(),
@@ -381,6 +438,29 @@ impl Component<Msg, UserEvent> for DBListSearchResult {
                     return Either::Right(Msg::DataBase(DBMsg::AddAllResultsConfirmShow));
                 }
 
+                // only acted on while showing the smart playlists
+                Event::Keyboard(keyevent)
+                    if keyevent == keys.database_keys.new_smart_playlist.get() =>
+                {
+                    return Either::Right(Msg::SmartPlaylist(SmartPlaylistMsg::NewShow));
+                }
+                Event::Keyboard(keyevent)
+                    if keyevent == keys.database_keys.edit_smart_playlist.get() =>
+                {
+                    if let State::One(StateValue::Usize(index)) = self.state() {
+                        return Either::Right(Msg::SmartPlaylist(SmartPlaylistMsg::EditShow(
+                            index,
+                        )));
+                    }
+                    CmdResult::None
+                }
+                Event::Keyboard(keyevent) if keyevent == keys.library_keys.delete.get() => {
+                    if let State::One(StateValue::Usize(index)) = self.state() {
+                        return Either::Right(Msg::SmartPlaylist(SmartPlaylistMsg::Delete(index)));
+                    }
+                    CmdResult::None
+                }
+
                 _ => CmdResult::None,
             };
 
@@ -465,10 +545,39 @@ impl Component<Msg, UserEvent> for DBListSearchTracks {
                 Event::Keyboard(keyevent) if keyevent == keys.database_keys.add_all.get() => {
                     return Either::Right(Msg::DataBase(DBMsg::AddAllToPlaylist));
                 }
+                Event::Keyboard(keyevent)
+                    if keyevent == keys.database_keys.replace_playlist.get() =>
+                {
+                    return Either::Right(Msg::QueueReplace(QueueReplaceMsg::WithDatabaseTracks));
+                }
 
                 Event::Keyboard(keyevent) if keyevent == keys.library_keys.search.get() => {
                     return Either::Right(Msg::GeneralSearch(GSMsg::PopupShowDatabase));
                 }
+                Event::Keyboard(keyevent) if keyevent == keys.database_keys.mark_reviewed.get() => {
+                    if let State::One(StateValue::Usize(index)) = self.state() {
+                        return Either::Right(Msg::DataBase(DBMsg::MarkReviewed(index)));
+                    }
+                    CmdResult::None
+                }
+                Event::Keyboard(keyevent)
+                    if keyevent == keys.database_keys.mark_all_reviewed.get() =>
+                {
+                    return Either::Right(Msg::DataBase(DBMsg::MarkAllReviewed));
+                }
+                Event::Keyboard(keyevent) if keyevent == keys.database_keys.locate.get() => {
+                    if let State::One(StateValue::Usize(index)) = self.state() {
+                        return Either::Right(Msg::DataBase(DBMsg::Locate(index)));
+                    }
+                    CmdResult::None
+                }
+                Event::Keyboard(keyevent) if label_for_key(keyevent).is_some() => {
+                    if let State::One(StateValue::Usize(index)) = self.state() {
+                        let label = label_for_key(keyevent).flatten();
+                        return Either::Right(Msg::DataBase(DBMsg::SetLabel(index, label)));
+                    }
+                    CmdResult::None
+                }
 
                 _ => CmdResult::None,
             };
@@ -486,7 +595,7 @@ impl Component<Msg, UserEvent> for DBListSearchTracks {
 
 /// Get various values for matching.
 ///
-/// [`wildmatch`] requires matching against strings.
+/// [`SearchMatcher`] requires matching against strings.
 /// Aside from just matching, it is also used to display the found matches.
 pub trait Matchable {
     fn meta_file(&self) -> Option<Cow<'_, str>>;
@@ -606,11 +715,29 @@ impl Model {
                 .title
                 .as_ref()
                 .map_or_else(|| record.file_stem.to_string_lossy(), Cow::from);
+            let label = self.track_label(&record.as_pathbuf());
+            let quality = self.track_quality(&record.as_pathbuf());
 
             table
                 .add_col(TextSpan::from(format!("{}", idx + 1)))
                 .add_col(TextSpan::from(" "))
-                .add_col(TextSpan::from(name));
+                .add_col(with_label(TextSpan::from(name), label));
+            if quality.is_some() {
+                table
+                    .add_col(TextSpan::from(" "))
+                    .add_col(quality_span(quality));
+            }
+
+            // show what is wrong with the file
+            if self.dw.criteria == SearchCriteria::Corrupted {
+                let failure =
+                    track_ops::get_verify_failure(&self.db.get_connection(), &record.as_pathbuf())
+                        .ok()
+                        .flatten();
+                if let Some(failure) = failure {
+                    table.add_col(TextSpan::from(format!(" ({})", failure.detail)));
+                }
+            }
         }
         if self.dw.search_results.is_empty() {
             table.add_col(TextSpan::from("empty results"));
@@ -680,239 +807,165 @@ impl Model {
         // self.playlist_update_title();
     }
 
-    /// Update [`DBListSearchResult`] by querying the database or getting all playlists.
+    /// Query the database (or get all playlists) for [`DBListSearchResult`], in the background.
+    ///
+    /// The results are applied once [`DBMsg::SearchResultReady`] is received.
     pub fn database_update_search_results(&mut self) {
-        let mut res = match self.dw.criteria {
-            SearchCriteria::Playlist => self.database_get_playlist(),
-            SearchCriteria::Artist => {
-                let mut result = Vec::new();
-                let all_artists = artist_ops::get_all_artists(
-                    &self.db.get_connection(),
-                    artist_ops::RowOrdering::IdAsc,
-                );
-                if let Ok(all_artists) = all_artists {
-                    result.extend(all_artists.into_iter().map(|v| v.name));
-                }
-
-                result
-            }
-            SearchCriteria::Album => {
-                let mut result = Vec::new();
-                let all_albums = album_ops::get_all_albums(
-                    &self.db.get_connection(),
-                    album_ops::RowOrdering::IdAsc,
-                );
-                if let Ok(all_albums) = all_albums {
-                    result.extend(all_albums.into_iter().map(|v| v.title));
-                }
-
-                result
-            }
-            SearchCriteria::Genre => {
-                let mut result = Vec::new();
-                let all_genres = track_ops::all_distinct_genres(&self.db.get_connection());
-                if let Ok(all_genres) = all_genres {
-                    result.extend(all_genres);
-                }
-
-                result
-            }
-            SearchCriteria::Directory => {
-                let mut result = Vec::new();
-                let all_dirs = track_ops::all_distinct_directories(&self.db.get_connection());
-                if let Ok(all_dirs) = all_dirs {
-                    result.extend(all_dirs);
+        let criteria = self.dw.criteria;
+        // the refreshes query by the criteria
+        self.dw.tracks_generation += 1;
+        self.dw.smart_playlist_shown = None;
+        let music_dirs = self.config_server.read().settings.player.music_dirs.clone();
+        let (untagged, transliterate) = {
+            let config = self.config_tui.read();
+            let database = &config.settings.database;
+            (database.untagged_albums, database.transliterate_sort)
+        };
+        let tx = self.tx_to_main.clone();
+        let db = self.db.clone();
+
+        Handle::current().spawn(async move {
+            let res = db
+                .read(move |conn| {
+                    Ok(get_search_results(
+                        conn,
+                        criteria,
+                        &music_dirs,
+                        untagged,
+                        transliterate,
+                    ))
+                })
+                .await;
+
+            match res {
+                Ok(results) => {
+                    let _ = tx.send(Msg::DataBase(DBMsg::SearchResultReady(DBResultsData {
+                        criteria,
+                        results,
+                    })));
                 }
-
-                result
+                Err(err) => error!("Error querying database search results: {err:#?}"),
             }
-        };
+        });
+    }
 
-        res.sort_by(|a, b| alphanumeric_sort::compare_str(a, b));
+    /// Apply queried results to [`DBListSearchResult`].
+    pub fn database_apply_search_results(&mut self, data: DBResultsData) {
+        // the criteria changed while the query was running
+        if data.criteria != self.dw.criteria {
+            return;
+        }
 
-        self.dw.search_results = res;
+        self.dw.search_results = data.results;
         self.database_sync_results();
         self.app.active(&Id::DBListSearchResult).ok();
-    }
-
-    /// Scan all Music Roots for all playlists.
-    fn database_get_playlist(&self) -> Vec<String> {
-        let mut vec = Vec::new();
 
-        for dir in &self.config_server.read().settings.player.music_dirs {
-            let absolute_dir = shellexpand::path::tilde(dir);
+        if let Some(name) = self.dw.jump_to.take() {
+            self.database_apply_jump(&name);
+        }
+    }
 
-            let all_items = walkdir::WalkDir::new(absolute_dir).follow_links(true);
-            for record in all_items
-                .into_iter()
-                .filter_map(std::result::Result::ok)
-                .filter(|p| is_playlist(p.path()))
-            {
-                let full_path_name = record.path().to_string_lossy().to_string();
-                vec.push(full_path_name);
-            }
+    /// Switch to `criteria`, also selecting it in [`DBListCriteria`], and query its results.
+    pub fn database_select_criteria(&mut self, criteria: SearchCriteria) {
+        let index = (0..usize::from(DBCriteria::NUM_OPTIONS)).find(|idx| {
+            DBCriteria::from_table_index(*idx).map(SearchCriteria::from) == Some(criteria)
+        });
+        if let Some(index) = index {
+            self.app
+                .attr(
+                    &Id::DBListCriteria,
+                    Attribute::Value,
+                    AttrValue::Payload(PropPayload::One(PropValue::Usize(index))),
+                )
+                .ok();
         }
 
-        vec
+        self.dw.criteria = criteria;
+        self.database_update_search_results();
     }
 
-    /// Find all tracks for the given [`criteria`](SearchCriteria) which matches `val`.
+    /// Query all tracks for the given results, in the background.
     ///
-    /// Or for the [`Playlist`](SearchCriteria::Playlist) case, `val` is the path of the playlist.
-    #[expect(clippy::too_many_lines)]
-    pub fn database_get_tracks_by_criteria(
-        &mut self,
-        criteria: SearchCriteria,
-        val: &str,
-    ) -> Option<Vec<TrackRead>> {
-        match criteria {
-            SearchCriteria::Playlist => {
-                let path = Path::new(val);
-                if let Ok(vec) = playlist_get_vec(path) {
-                    let mut vec_db = Vec::with_capacity(vec.len());
-                    let conn = self.db.get_connection();
-                    for item in vec {
-                        let path = Path::new(&item);
-                        // TODO: do we really need to lookup each value in a playlist in the database first?
-                        let track = track_ops::get_track_from_path(&conn, path);
-                        if let Ok(i) = track {
-                            vec_db.push(i);
-                        }
-                    }
-                    return Some(vec_db);
-                }
-            }
-            SearchCriteria::Artist => {
-                let mut result = Vec::new();
-                let conn = self.db.get_connection();
-                let all_artists = artist_ops::get_all_artists_like(
-                    &conn,
-                    &format!("%{val}%"),
-                    artist_ops::RowOrdering::IdAsc,
-                );
-                if let Ok(all_artists) = all_artists {
-                    for artist in all_artists {
-                        let all_tracks = track_ops::get_tracks_from_artist(
-                            &conn,
-                            &artist.name,
-                            track_ops::RowOrdering::IdAsc,
-                        );
-                        if let Ok(all_tracks) = all_tracks {
-                            result.extend(all_tracks);
-                        }
-                    }
-                }
-
-                result.sort_by(|a, b| {
-                    alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf())
-                });
-
-                return Some(result);
-            }
-            SearchCriteria::Album => {
-                let mut result = Vec::new();
-                let conn = self.db.get_connection();
-                let all_albums = album_ops::get_all_albums_like(
-                    &conn,
-                    &format!("%{val}%"),
-                    album_ops::RowOrdering::IdAsc,
-                );
-                if let Ok(all_albums) = all_albums {
-                    for album in all_albums {
-                        let all_tracks = track_ops::get_tracks_from_album(
-                            &conn,
-                            &album.title,
-                            &album.artist_display,
-                            track_ops::RowOrdering::IdAsc,
-                        );
-                        if let Ok(all_tracks) = all_tracks {
-                            result.extend(all_tracks);
+    /// Once done, `to_msg` is called with the found tracks and sent.
+    pub fn database_spawn_get_tracks<F>(&self, results: Vec<String>, to_msg: F)
+    where
+        F: FnOnce(DBTracksData) -> Msg + Send + 'static,
+    {
+        let criteria = self.dw.criteria;
+        let tx = self.tx_to_main.clone();
+        let db = self.db.clone();
+
+        Handle::current().spawn(async move {
+            let res = db
+                .read(move |conn| {
+                    let mut tracks = Vec::new();
+                    for result in results {
+                        if let Some(mut res) = get_tracks_by_criteria(conn, criteria, &result) {
+                            tracks.append(&mut res);
                         }
                     }
-                }
-
-                result.sort_by(|a, b| {
-                    alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf())
-                });
-
-                return Some(result);
-            }
-            SearchCriteria::Genre => {
-                let mut result = Vec::new();
-                let conn = self.db.get_connection();
-                let all_tracks = if val == "[unknown]" {
-                    track_ops::get_tracks_from_genre(&conn, None, track_ops::RowOrdering::IdAsc)
-                } else {
-                    track_ops::get_tracks_from_genre_like(
-                        &conn,
-                        &format!("%{val}%"),
-                        track_ops::RowOrdering::IdAsc,
-                    )
-                };
-                if let Ok(all_tracks) = all_tracks {
-                    result.extend(all_tracks);
-                }
 
-                result.sort_by(|a, b| {
-                    alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf())
-                });
+                    Ok(tracks)
+                })
+                .await;
 
-                return Some(result);
-            }
-            SearchCriteria::Directory => {
-                let mut result = Vec::new();
-                let conn = self.db.get_connection();
-                let dir = Path::new(val);
-                let all_tracks =
-                    track_ops::get_tracks_from_directory(&conn, dir, track_ops::RowOrdering::IdAsc);
-                if let Ok(all_tracks) = all_tracks {
-                    result.extend(all_tracks);
+            match res {
+                Ok(tracks) => {
+                    let _ = tx.send(to_msg(DBTracksData { tracks }));
                 }
-
-                result.sort_by(|a, b| {
-                    alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf())
-                });
-
-                return Some(result);
+                Err(err) => error!("Error querying database tracks: {err:#?}"),
             }
-        }
-
-        None
+        });
     }
 
-    /// Update view `Tracks` by populating it with items from the selected `Result`(view) index.
+    /// Query the tracks for view `Tracks` from the selected `Result`(view) index, in the background.
     pub fn database_update_search_tracks(&mut self, index: usize) {
         self.dw.search_tracks.clear();
+        self.dw.tracks_generation += 1;
+        self.dw.smart_playlist_shown = None;
         let Some(at_index) = self.dw.search_results.get(index).cloned() else {
             return;
         };
+        if self.dw.criteria == SearchCriteria::SmartPlaylist {
+            self.dw.smart_playlist_shown = Some(at_index.clone());
+        }
 
-        let Some(result) = self.database_get_tracks_by_criteria(self.dw.criteria, &at_index) else {
+        let generation = self.dw.tracks_generation;
+        self.database_spawn_get_tracks(vec![at_index], move |data| {
+            Msg::DataBase(DBMsg::SearchTracksReady(generation, data))
+        });
+    }
+
+    /// Apply queried tracks to the `Tracks` view.
+    pub fn database_apply_search_tracks(&mut self, generation: u64, data: DBTracksData) {
+        // other tracks were queried while the query was running, which may finish out of order
+        if generation != self.dw.tracks_generation {
             return;
-        };
+        }
 
-        self.dw.search_tracks = result;
+        self.dw.search_tracks = data.tracks;
 
         self.database_sync_tracks_results();
         self.app.active(&Id::DBListSearchTracks).ok();
+        self.smart_playlist_schedule_refresh();
+    }
+
+    /// Add the tracks of the Result (from view `Result`) at `index` to the playlist.
+    pub fn database_add_result(&mut self, index: usize) {
+        if let Some(result) = self.dw.search_results.get(index).cloned() {
+            self.database_spawn_get_tracks(vec![result], |data| {
+                Msg::DataBase(DBMsg::AddTracksReady(data))
+            });
+        }
     }
 
     /// Add all Results (from view `Result`) to the playlist.
     pub fn database_add_all_results(&mut self) {
         self.umount_results_add_confirm_database();
         if !self.dw.search_results.is_empty() {
-            let mut tracks = Vec::new();
-            // clone once instead every value in every iteration
-            let search_results = self.dw.search_results.clone();
-            for result in search_results {
-                if let Some(mut res) =
-                    self.database_get_tracks_by_criteria(self.dw.criteria, &result)
-                {
-                    tracks.append(&mut res);
-                }
-            }
-
-            self.playlist_add_all_from_db(&tracks);
+            self.database_spawn_get_tracks(self.dw.search_results.clone(), |data| {
+                Msg::DataBase(DBMsg::AddTracksReady(data))
+            });
         }
     }
 
@@ -953,36 +1006,26 @@ impl Model {
     /// Reload database component data.
     pub fn database_reload(&mut self) {
         self.remount_database_search().unwrap();
+        self.track_qualities_reload();
 
         self.dw.reset_search_results();
         self.database_sync_tracks_results();
         self.database_sync_results();
     }
 
-    fn match_record<T: Matchable>(record: &T, search: &str) -> bool {
-        let artist_match: bool = if let Some(artist) = record.meta_artist() {
-            wildmatch::WildMatch::new(search).matches(&artist.to_lowercase())
-        } else {
-            false
-        };
-        let title_match: bool = if let Some(title) = record.meta_title() {
-            wildmatch::WildMatch::new(search).matches(&title.to_lowercase())
-        } else {
-            false
-        };
-        let album_match: bool = if let Some(album) = record.meta_album() {
-            wildmatch::WildMatch::new(search).matches(&album.to_lowercase())
-        } else {
-            false
-        };
+    fn match_record<T: Matchable>(record: &T, search: &SearchMatcher) -> bool {
+        let artist_match = record.meta_artist().is_some_and(|v| search.matches(v));
+        let title_match = record.meta_title().is_some_and(|v| search.matches(v));
+        let album_match = record.meta_album().is_some_and(|v| search.matches(v));
         artist_match || title_match || album_match
     }
 
     pub fn update_search<'a, T: Matchable>(
         indexable_songs: &'a [T],
         input: &'a str,
+        mode: SearchMode,
     ) -> impl Iterator<Item = &'a T> {
-        let search = format!("*{}*", input.to_lowercase());
+        let search = SearchMatcher::new(mode, input);
         indexable_songs
             .iter()
             .filter(move |&record| Model::match_record(record, &search))
@@ -1024,15 +1067,47 @@ impl Model {
         table.build()
     }
 
-    pub fn database_update_search(&mut self, input: &str) {
-        let mut db_tracks = Vec::new();
-        let all_tracks =
-            track_ops::get_all_tracks(&self.db.get_connection(), track_ops::RowOrdering::IdAsc);
-        if let Ok(all_tracks) = all_tracks {
-            db_tracks = all_tracks;
+    /// Query all tracks for the general search popup, in the background.
+    ///
+    /// Until [`DBMsg::GeneralSearchReady`] is received, the search shows no tracks.
+    pub fn database_load_general_search(&mut self) {
+        self.dw.general_search_tracks = None;
+        let tx = self.tx_to_main.clone();
+        let db = self.db.clone();
+
+        Handle::current().spawn(async move {
+            let res = db
+                .read(|conn| track_ops::get_all_tracks(conn, track_ops::RowOrdering::IdAsc))
+                .await;
+
+            match res {
+                Ok(tracks) => {
+                    let _ = tx.send(Msg::DataBase(DBMsg::GeneralSearchReady(DBTracksData {
+                        tracks,
+                    })));
+                }
+                Err(err) => error!("Error querying database tracks: {err:#?}"),
+            }
+        });
+    }
+
+    /// Apply the queried tracks to the general search popup, if it is still open.
+    pub fn database_apply_general_search(&mut self, data: DBTracksData) {
+        self.dw.general_search_tracks = Some(data.tracks);
+
+        if self.global_search.is_some() {
+            self.global_search_refresh();
+        } else if self.app.mounted(&Id::GeneralSearchTable) {
+            let input = std::mem::take(&mut self.dw.general_search_input);
+            self.database_update_search(&input);
         }
+    }
+
+    pub fn database_update_search(&mut self, input: &str) {
+        input.clone_into(&mut self.dw.general_search_input);
+        let db_tracks = self.dw.general_search_tracks.as_deref().unwrap_or_default();
 
-        let filtered_music = Model::update_search(&db_tracks, input);
+        let filtered_music = Model::update_search(db_tracks, input, self.general_search_mode);
         self.general_search_update_show(Model::build_table(filtered_music));
     }
 
@@ -1057,3 +1132,352 @@ impl Model {
         let _ = self.app.umount(&Id::DatabaseAddConfirmPopup);
     }
 }
+
+/// The album entry for tracks without a album, see [`UntaggedAlbums`].
+///
+/// With [`UntaggedAlbums::Directory`] the directory is appended after a space.
+const UNKNOWN_ALBUM_ENTRY: &str = "[unknown]";
+
+/// Get all search results for `criteria`, see [`Model::database_update_search_results`].
+#[allow(clippy::too_many_lines)]
+fn get_search_results(
+    conn: &Connection,
+    criteria: SearchCriteria,
+    music_dirs: &[PathBuf],
+    untagged: UntaggedAlbums,
+    transliterate: bool,
+) -> Vec<String> {
+    // the tagged names to sort by, like "Beatles, The" for "The Beatles"
+    let mut sort_names = HashMap::new();
+
+    let res = match criteria {
+        SearchCriteria::Playlist => get_all_playlists(music_dirs),
+        // already ordered by name
+        SearchCriteria::SmartPlaylist => {
+            return smart_playlist_ops::get_all(conn)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| v.name)
+                .collect();
+        }
+        SearchCriteria::Artist => {
+            let mut result = Vec::new();
+            let all_artists = artist_ops::get_all_artists(conn, artist_ops::RowOrdering::IdAsc);
+            if let Ok(all_artists) = all_artists {
+                for artist in all_artists {
+                    if let Some(sort_name) = artist.sort_name {
+                        sort_names.insert(artist.name.clone(), sort_name);
+                    }
+                    result.push(artist.name);
+                }
+            }
+
+            result
+        }
+        SearchCriteria::Album => {
+            let mut result = Vec::new();
+            let all_albums = album_ops::get_all_albums(conn, album_ops::RowOrdering::IdAsc);
+            if let Ok(all_albums) = all_albums {
+                for album in all_albums {
+                    if let Some(sort_title) = album.sort_title {
+                        sort_names.insert(album.title.clone(), sort_title);
+                    }
+                    result.push(album.title);
+                }
+            }
+
+            match untagged {
+                UntaggedAlbums::Hidden => (),
+                UntaggedAlbums::Unknown => {
+                    let tracks = track_ops::get_tracks_without_album(
+                        conn,
+                        None,
+                        track_ops::RowOrdering::IdAsc,
+                    );
+                    if tracks.is_ok_and(|v| !v.is_empty()) {
+                        result.push(UNKNOWN_ALBUM_ENTRY.to_string());
+                    }
+                }
+                UntaggedAlbums::Directory => {
+                    if let Ok(dirs) = track_ops::all_distinct_directories_without_album(conn) {
+                        result.extend(
+                            dirs.into_iter()
+                                .map(|dir| format!("{UNKNOWN_ALBUM_ENTRY} {dir}")),
+                        );
+                    }
+                }
+            }
+
+            result
+        }
+        SearchCriteria::Composer => track_ops::all_distinct_composers(conn).unwrap_or_default(),
+        SearchCriteria::Work => track_ops::all_distinct_works(conn).unwrap_or_default(),
+        // in the order of their numbers, not sorted by name
+        SearchCriteria::Label => {
+            return track_ops::all_distinct_labels(conn)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| v.name().to_string())
+                .collect();
+        }
+        // the days tracks were added on, the newest first
+        SearchCriteria::Inbox => {
+            return track_ops::all_distinct_unreviewed_days(conn).unwrap_or_default();
+        }
+        // from low to high energy
+        SearchCriteria::Energy => {
+            return track_ops::all_distinct_energy_levels(conn)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| v.name().to_string())
+                .collect();
+        }
+        // from the highest to the lowest quality
+        SearchCriteria::Quality => {
+            return track_ops::all_distinct_qualities(conn)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| v.name().to_string())
+                .collect();
+        }
+        // in the order of their numbers, not sorted by name
+        SearchCriteria::Corrupted => {
+            return track_ops::all_distinct_verify_failures(conn)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| v.name().to_string())
+                .collect();
+        }
+        SearchCriteria::Genre => {
+            let mut result = Vec::new();
+            let all_genres = track_ops::all_distinct_genres(conn);
+            if let Ok(all_genres) = all_genres {
+                result.extend(all_genres);
+            }
+
+            result
+        }
+        SearchCriteria::Directory => {
+            let mut result = Vec::new();
+            let all_dirs = track_ops::all_distinct_directories(conn);
+            if let Ok(all_dirs) = all_dirs {
+                result.extend(all_dirs);
+            }
+
+            result
+        }
+    };
+
+    let mut res: Vec<(String, String)> = res
+        .into_iter()
+        .map(|v| {
+            let key = get_sort_key(sort_names.get(&v).unwrap_or(&v), transliterate);
+            (key, v)
+        })
+        .collect();
+    res.sort_by(|a, b| alphanumeric_sort::compare_str(&a.0, &b.0).then_with(|| a.1.cmp(&b.1)));
+
+    res.into_iter().map(|(_, v)| v).collect()
+}
+
+/// Scan all Music Roots for all playlists.
+fn get_all_playlists(music_dirs: &[PathBuf]) -> Vec<String> {
+    let mut vec = Vec::new();
+
+    for dir in music_dirs {
+        let absolute_dir = shellexpand::path::tilde(dir);
+
+        let all_items = walkdir::WalkDir::new(absolute_dir).follow_links(true);
+        for record in all_items
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|p| is_playlist(p.path()))
+        {
+            let full_path_name = record.path().to_string_lossy().to_string();
+            vec.push(full_path_name);
+        }
+    }
+
+    vec
+}
+
+/// Find all tracks for the given [`criteria`](SearchCriteria) which matches `val`.
+///
+/// Or for the [`Playlist`](SearchCriteria::Playlist) case, `val` is the path of the playlist.
+#[allow(clippy::too_many_lines)]
+fn get_tracks_by_criteria(
+    conn: &Connection,
+    criteria: SearchCriteria,
+    val: &str,
+) -> Option<Vec<TrackRead>> {
+    match criteria {
+        SearchCriteria::Playlist => {
+            let path = Path::new(val);
+            if let Ok(vec) = playlist_get_vec(path) {
+                let mut vec_db = Vec::with_capacity(vec.len());
+                for item in vec {
+                    let path = Path::new(&item);
+                    // TODO: do we really need to lookup each value in a playlist in the database first?
+                    let track = track_ops::get_track_from_path(conn, path);
+                    if let Ok(i) = track {
+                        vec_db.push(i);
+                    }
+                }
+                return Some(vec_db);
+            }
+        }
+        SearchCriteria::SmartPlaylist => {
+            let query = smart_playlist_ops::get_by_name(conn, val).ok()??;
+            let query = match query.parse() {
+                Ok(v) => v,
+                Err(err) => {
+                    warn!("{err:#}");
+                    return None;
+                }
+            };
+            let mut result =
+                track_ops::get_tracks_matching(conn, &query, track_ops::RowOrdering::IdAsc).ok()?;
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        SearchCriteria::Artist => {
+            let mut result = Vec::new();
+            let all_artists = artist_ops::get_all_artists_like(
+                conn,
+                &format!("%{val}%"),
+                artist_ops::RowOrdering::IdAsc,
+            );
+            if let Ok(all_artists) = all_artists {
+                for artist in all_artists {
+                    let all_tracks = track_ops::get_tracks_from_artist(
+                        conn,
+                        &artist.name,
+                        track_ops::RowOrdering::IdAsc,
+                    );
+                    if let Ok(all_tracks) = all_tracks {
+                        result.extend(all_tracks);
+                    }
+                }
+            }
+
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        SearchCriteria::Album => {
+            if let Some(dir) = val.strip_prefix(UNKNOWN_ALBUM_ENTRY) {
+                let dir = dir.trim_start();
+                let dir = (!dir.is_empty()).then(|| Path::new(dir));
+                return track_ops::get_tracks_without_album(
+                    conn,
+                    dir,
+                    track_ops::RowOrdering::IdAsc,
+                )
+                .ok();
+            }
+
+            let mut result = Vec::new();
+            let all_albums = album_ops::get_all_albums_like(
+                conn,
+                &format!("%{val}%"),
+                album_ops::RowOrdering::IdAsc,
+            );
+            if let Ok(all_albums) = all_albums {
+                for album in all_albums {
+                    let all_tracks = track_ops::get_tracks_from_album(
+                        conn,
+                        &album.title,
+                        &album.artist_display,
+                        track_ops::RowOrdering::IdAsc,
+                    );
+                    if let Ok(all_tracks) = all_tracks {
+                        result.extend(all_tracks);
+                    }
+                }
+            }
+
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        // already ordered by work and movement, which the path order would lose
+        SearchCriteria::Composer => return track_ops::get_tracks_from_composer(conn, val).ok(),
+        SearchCriteria::Work => return track_ops::get_tracks_from_work(conn, val).ok(),
+        SearchCriteria::Label => {
+            let label = TrackLabel::from_name(val)?;
+            let mut result =
+                track_ops::get_tracks_with_label(conn, label, track_ops::RowOrdering::IdAsc)
+                    .ok()?;
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        // in the order they were added
+        SearchCriteria::Inbox => return track_ops::get_unreviewed_tracks(conn, Some(val)).ok(),
+        SearchCriteria::Energy => {
+            let level = EnergyLevel::from_name(val)?;
+            let mut result =
+                track_ops::get_tracks_with_energy(conn, level, track_ops::RowOrdering::IdAsc)
+                    .ok()?;
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        SearchCriteria::Quality => {
+            let quality = QualityBadge::from_name(val)?;
+            let mut result =
+                track_ops::get_tracks_with_quality(conn, quality, track_ops::RowOrdering::IdAsc)
+                    .ok()?;
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        SearchCriteria::Corrupted => {
+            let kind = VerifyFailure::from_name(val)?;
+            let mut result = track_ops::get_tracks_with_verify_failure(
+                conn,
+                kind,
+                track_ops::RowOrdering::IdAsc,
+            )
+            .ok()?;
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        SearchCriteria::Genre => {
+            let mut result = Vec::new();
+            let all_tracks = if val == "[unknown]" {
+                track_ops::get_tracks_from_genre(conn, None, track_ops::RowOrdering::IdAsc)
+            } else {
+                track_ops::get_tracks_from_genre_like(
+                    conn,
+                    &format!("%{val}%"),
+                    track_ops::RowOrdering::IdAsc,
+                )
+            };
+            if let Ok(all_tracks) = all_tracks {
+                result.extend(all_tracks);
+            }
+
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+        SearchCriteria::Directory => {
+            let mut result = Vec::new();
+            let dir = Path::new(val);
+            let all_tracks =
+                track_ops::get_tracks_from_directory(conn, dir, track_ops::RowOrdering::IdAsc);
+            if let Ok(all_tracks) = all_tracks {
+                result.extend(all_tracks);
+            }
+
+            result.sort_by(|a, b| alphanumeric_sort::compare_path(a.as_pathbuf(), b.as_pathbuf()));
+
+            return Some(result);
+        }
+    }
+
+    None
+}
@@ -1,5 +1,10 @@
 use termusiclib::config::TuiOverlay;
-use tuirealm::{Component, Event, MockComponent, props::TextSpan};
+use termusiclib::config::v2::tui::keys::KeyBinding;
+use tui_realm_stdlib::Span;
+use tuirealm::{
+    Component, Event, MockComponent,
+    props::{Alignment, TextSpan},
+};
 
 use crate::ui::{components::LabelSpan, model::UserEvent, msg::Msg};
 
@@ -77,3 +82,39 @@ impl Component<Msg, UserEvent> for Footer {
         None
     }
 }
+
+/// A line of the most useful keys of the focused pane, see [`Model::key_hints_update`](crate::ui::model::Model::key_hints_update).
+#[derive(MockComponent)]
+pub struct KeyHints {
+    component: Span,
+}
+
+impl KeyHints {
+    pub fn new(config: &TuiOverlay, hints: &[(&KeyBinding, &str)]) -> Self {
+        let theme = &config.settings.theme;
+        let spans: Vec<TextSpan> = hints
+            .iter()
+            .flat_map(|(key, description)| {
+                [
+                    TextSpan::new(format!(" <{key}>"))
+                        .fg(theme.fallback_highlight())
+                        .bold(),
+                    TextSpan::new(format!(" {description}")).fg(theme.fallback_foreground()),
+                ]
+            })
+            .collect();
+
+        Self {
+            component: Span::default()
+                .background(theme.library_background())
+                .spans(spans)
+                .alignment(Alignment::Left),
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for KeyHints {
+    fn on(&mut self, _ev: Event<UserEvent>) -> Option<Msg> {
+        None
+    }
+}
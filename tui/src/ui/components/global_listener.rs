@@ -12,8 +12,10 @@ use crate::ui::Model;
 use crate::ui::ids::{Id, IdConfigEditor, IdTagEditor};
 use crate::ui::model::{TxToMain, UserEvent};
 use crate::ui::msg::{
-    ConfigEditorMsg, HelpPopupMsg, LIMsg, LIReqNode, LyricMsg, MainLayoutMsg, Msg, PLMsg,
-    PlayerMsg, QuitPopupMsg, SavePlaylistMsg, XYWHMsg,
+    ConfigEditorMsg, CreditsMsg, DownloadsMsg, EqualizerMsg, GSMsg, HelpPopupMsg, LIMsg, LIReqNode,
+    LyricMsg, MainLayoutMsg, Msg, OutputDevicesMsg, PLMsg, PlayerMsg, QuitPopupMsg, RadioMsg,
+    SavePlaylistMsg, SeekToPositionMsg, StatisticsMsg, TrackSuggestionsMsg, TranslationMsg,
+    XYWHMsg,
 };
 
 #[derive(MockComponent)]
@@ -62,6 +64,38 @@ impl Component<Msg, UserEvent> for GlobalListener {
             Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.open_help.get() => {
                 Some(Msg::HelpPopup(HelpPopupMsg::Show))
             }
+            Event::Keyboard(keyevent)
+                if keyevent == keys.select_view_keys.open_global_search.get() =>
+            {
+                Some(Msg::GeneralSearch(GSMsg::PopupShowGlobal))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.open_credits.get() => {
+                Some(Msg::Credits(CreditsMsg::Show))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.open_downloads.get() => {
+                Some(Msg::Downloads(DownloadsMsg::Show))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.open_radio.get() => {
+                Some(Msg::Radio(RadioMsg::InputShow))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.open_equalizer.get() => {
+                Some(Msg::Equalizer(EqualizerMsg::Show))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == keys.select_view_keys.open_output_devices.get() =>
+            {
+                Some(Msg::OutputDevices(OutputDevicesMsg::Show))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == keys.select_view_keys.open_statistics.get() =>
+            {
+                Some(Msg::Statistics(StatisticsMsg::Show))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == keys.select_view_keys.toggle_track_suggestions.get() =>
+            {
+                Some(Msg::TrackSuggestions(TrackSuggestionsMsg::Toggle))
+            }
             Event::Keyboard(keyevent) if keyevent == keys.player_keys.seek_forward.get() => {
                 Some(Msg::Player(PlayerMsg::SeekForward))
             }
@@ -88,6 +122,12 @@ impl Component<Msg, UserEvent> for GlobalListener {
             Event::Keyboard(keyevent) if keyevent == keys.lyric_keys.cycle_frames.get() => {
                 Some(Msg::LyricMessage(LyricMsg::Cycle))
             }
+            Event::Keyboard(keyevent) if keyevent == keys.lyric_keys.search.get() => {
+                Some(Msg::GeneralSearch(GSMsg::PopupShowLyric))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.lyric_keys.toggle_translation.get() => {
+                Some(Msg::Translation(TranslationMsg::Toggle))
+            }
 
             Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.view_library.get() => {
                 Some(Msg::Layout(MainLayoutMsg::TreeView))
@@ -105,6 +145,44 @@ impl Component<Msg, UserEvent> for GlobalListener {
                 Some(Msg::Player(PlayerMsg::ToggleGapless))
             }
 
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.toggle_karaoke.get() => {
+                Some(Msg::Player(PlayerMsg::ToggleKaraoke))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.toggle_recording.get() => {
+                Some(Msg::Player(PlayerMsg::ToggleRecording))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.toggle_album_mode.get() => {
+                Some(Msg::Player(PlayerMsg::ToggleAlbumMode))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.tap_tempo.get() => {
+                Some(Msg::Player(PlayerMsg::TapTempo))
+            }
+
+            Event::Keyboard(keyevent)
+                if keyevent == keys.player_keys.toggle_normalization.get() =>
+            {
+                Some(Msg::Player(PlayerMsg::ToggleNormalization))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.toggle_smart_speed.get() => {
+                Some(Msg::Player(PlayerMsg::ToggleSmartSpeed))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.ab_loop.get() => {
+                Some(Msg::Player(PlayerMsg::AbLoop))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.seek_to_position.get() => {
+                Some(Msg::SeekToPosition(SeekToPositionMsg::Show))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.rate_track.get() => {
+                Some(Msg::Statistics(StatisticsMsg::RateShow))
+            }
+
             Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.open_config.get() => {
                 Some(Msg::ConfigEditor(ConfigEditorMsg::Open))
             }
@@ -157,6 +235,11 @@ impl Component<Msg, UserEvent> for GlobalListener {
             Event::Keyboard(keyevent) if keyevent == keys.move_cover_art_keys.toggle_hide.get() => {
                 Some(Msg::Xywh(XYWHMsg::ToggleHidden))
             }
+            Event::Keyboard(keyevent) => keys
+                .macros
+                .iter()
+                .find(|v| keyevent == v.key.get())
+                .map(|v| Msg::RunMacro(v.name.clone())),
 
             // just forward the message to "Update" as there is no way to bypass this component forwarding
             Event::User(UserEvent::Forward(msg)) => Some(msg),
@@ -188,7 +271,7 @@ impl Model {
 fn global_listener_subscriptions(keys: &Keys) -> Vec<Sub<Id, UserEvent>> {
     let no_popup_clause = Arc::new(no_popup_mounted_clause());
 
-    vec![
+    let mut subs = vec![
         // Sub::new(
         //     SubEventClause::Keyboard(keys.escape.get()),
         //     no_popup_clause.clone(),
@@ -237,6 +320,38 @@ fn global_listener_subscriptions(keys: &Keys) -> Vec<Sub<Id, UserEvent>> {
             SubEventClause::Keyboard(keys.select_view_keys.open_help.get()),
             no_popup_clause.clone(),
         ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_global_search.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_credits.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_downloads.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_radio.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_equalizer.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_output_devices.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.open_statistics.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.select_view_keys.toggle_track_suggestions.get()),
+            no_popup_clause.clone(),
+        ),
         Sub::new(
             SubEventClause::Keyboard(keys.player_keys.seek_forward.get()),
             no_popup_clause.clone(),
@@ -257,6 +372,14 @@ fn global_listener_subscriptions(keys: &Keys) -> Vec<Sub<Id, UserEvent>> {
             SubEventClause::Keyboard(keys.lyric_keys.cycle_frames.get()),
             no_popup_clause.clone(),
         ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.lyric_keys.search.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.lyric_keys.toggle_translation.get()),
+            no_popup_clause.clone(),
+        ),
         Sub::new(
             SubEventClause::Keyboard(keys.select_view_keys.view_library.get()),
             no_popup_clause.clone(),
@@ -269,6 +392,42 @@ fn global_listener_subscriptions(keys: &Keys) -> Vec<Sub<Id, UserEvent>> {
             SubEventClause::Keyboard(keys.player_keys.toggle_prefetch.get()),
             no_popup_clause.clone(),
         ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.toggle_karaoke.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.toggle_recording.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.toggle_album_mode.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.tap_tempo.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.toggle_normalization.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.toggle_smart_speed.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.ab_loop.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.seek_to_position.get()),
+            no_popup_clause.clone(),
+        ),
+        Sub::new(
+            SubEventClause::Keyboard(keys.player_keys.rate_track.get()),
+            no_popup_clause.clone(),
+        ),
         Sub::new(
             SubEventClause::Keyboard(keys.select_view_keys.open_config.get()),
             no_popup_clause.clone(),
@@ -279,7 +438,7 @@ fn global_listener_subscriptions(keys: &Keys) -> Vec<Sub<Id, UserEvent>> {
         ),
         Sub::new(
             SubEventClause::Keyboard(keys.select_view_keys.view_podcasts.get()),
-            no_popup_clause,
+            no_popup_clause.clone(),
         ),
         Sub::new(
             SubEventClause::Keyboard(keys.move_cover_art_keys.move_left.get()),
@@ -315,7 +474,15 @@ fn global_listener_subscriptions(keys: &Keys) -> Vec<Sub<Id, UserEvent>> {
             SubEventClause::Discriminant(UserEvent::Forward(Msg::ForceRedraw)),
             SubClause::Always,
         ),
-    ]
+    ];
+    subs.extend(keys.macros.iter().map(|v| {
+        Sub::new(
+            SubEventClause::Keyboard(v.key.get()),
+            no_popup_clause.clone(),
+        )
+    }));
+
+    subs
 }
 
 /// Generate the Clause for any popups to not be mounted.
@@ -360,6 +527,21 @@ fn general_popups(storage: &mut Vec<SubClause<Id>>) {
         SubClause::IsMounted(Id::SavePlaylistPopup),
         SubClause::IsMounted(Id::SavePlaylistConfirm),
         SubClause::IsMounted(Id::DatabaseAddConfirmPopup),
+        SubClause::IsMounted(Id::LibraryRenamePopup),
+        SubClause::IsMounted(Id::TrackOffsetsPopup),
+        SubClause::IsMounted(Id::SeekToPositionPopup),
+        SubClause::IsMounted(Id::QueueReplacePopup),
+        SubClause::IsMounted(Id::ActionConfirmPopup),
+        SubClause::IsMounted(Id::SearchHistoryPopup),
+        SubClause::IsMounted(Id::CreditsPopup),
+        SubClause::IsMounted(Id::DownloadsPopup),
+        SubClause::IsMounted(Id::RadioSearchInputPopup),
+        SubClause::IsMounted(Id::RadioStationsPopup),
+        SubClause::IsMounted(Id::EqualizerPopup),
+        SubClause::IsMounted(Id::OutputDevicesPopup),
+        SubClause::IsMounted(Id::SmartPlaylistEditPopup),
+        SubClause::IsMounted(Id::RatingPopup),
+        SubClause::IsMounted(Id::StatisticsPopup),
     ]);
 }
 
@@ -367,11 +549,12 @@ fn general_popups(storage: &mut Vec<SubClause<Id>>) {
 ///
 /// The values returned are meant to be used in a [`SubClause::OrMany`].
 #[inline]
-fn youtube_search_popups() -> [SubClause<Id>; 3] {
+fn youtube_search_popups() -> [SubClause<Id>; 4] {
     [
         SubClause::IsMounted(Id::YoutubeSearchInputPopup),
         SubClause::IsMounted(Id::YoutubeSearchTablePopup),
         SubClause::IsMounted(Id::YoutubeSearchTablePopup),
+        SubClause::IsMounted(Id::YoutubeRegionPopup),
     ]
 }
 
@@ -1,25 +1,33 @@
+use std::str::FromStr;
 use std::sync::LazyLock;
+use std::time::Duration;
 
 use anyhow::{Result, anyhow};
+use id3::frame::Lyrics as Id3Lyrics;
 use regex::Regex;
 use termusiclib::common::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
 use termusiclib::config::SharedTuiSettings;
+use termusiclib::config::v2::tui::Lyrics;
 use termusiclib::player::RunningStatus;
 use termusiclib::podcast::episode::Episode;
+use termusiclib::songtag::lyric_cache::LyricCache;
 use termusiclib::track::MediaTypes;
 use termusiclib::track::MediaTypesSimple;
+use termusiclib::track::{DurationFmtShort, LyricData, Track};
 use tui_realm_stdlib::Textarea;
 use tuirealm::command::{Cmd, Direction, Position};
 use tuirealm::event::{Key, KeyEvent, KeyModifiers};
 use tuirealm::props::{
-    Alignment, AttrValue, Attribute, BorderType, Borders, PropPayload, PropValue, Style, TextSpan,
+    Alignment, AttrValue, Attribute, BorderType, Borders, PropPayload, PropValue, Style,
+    TableBuilder, TextSpan,
 };
 use tuirealm::{Component, Event, MockComponent, State, StateValue};
 
-use super::TETrack;
+use super::{SearchMatcher, TETrack};
 use crate::ui::ids::Id;
-use crate::ui::model::{ExtraLyricData, UserEvent};
+use crate::ui::model::{ExtraLyricData, UserEvent, side_by_side};
 use crate::ui::msg::{LyricMsg, Msg};
+use crate::ui::tui_cmd::TuiCmd;
 use crate::ui::{Model, model::TermusicLayout};
 
 /// Regex for finding <br/> tags -- also captures any surrounding
@@ -34,6 +42,9 @@ static RE_HTML_TAGS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^<>]*>").
 static RE_MULT_LINE_BREAKS: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"((\r\n)|\r|\n){3,}").unwrap());
 
+/// The amount of lines to show before the current line in karaoke mode.
+const KARAOKE_CONTEXT_LINES: usize = 2;
+
 #[derive(MockComponent)]
 pub struct Lyric {
     component: Textarea,
@@ -204,14 +215,22 @@ impl Model {
         // remove anything more than two line breaks (i.e., one blank line)
         let no_line_breaks = RE_MULT_LINE_BREAKS.replace_all(&decoded, "\n\n");
 
-        let (term_width, _) = viuer::terminal_size();
-        let term_width = usize::from(term_width);
-        let lyric_width = term_width * 3 / 5;
+        let lyric_width = lyric_width();
         let lines_vec: Vec<_> = no_line_breaks.split('\n').collect();
+        self.translation_request(
+            &ep.url,
+            lines_vec.iter().map(|v| (*v).to_string()).collect(),
+        );
         let mut short_string_vec: Vec<_> = Vec::new();
         for line in lines_vec {
             let unicode_width = unicode_width::UnicodeWidthStr::width(line);
-            if unicode_width > lyric_width {
+            if let Some(translated) = self.lyric_translation.get(&ep.url, line) {
+                short_string_vec.extend(
+                    side_by_side(line, translated, translation_column_width(lyric_width))
+                        .into_iter()
+                        .map(std::borrow::Cow::Owned),
+                );
+            } else if unicode_width > lyric_width {
                 let mut string_tmp = textwrap::wrap(line, lyric_width);
                 short_string_vec.append(&mut string_tmp);
             } else {
@@ -271,8 +290,11 @@ impl Model {
             return;
         }
         if let Some(track) = self.playback.current_track() {
-            // radio only needs to be updated on track change, which is handled in a different function
-            if MediaTypesSimple::LiveRadio == track.media_type() {
+            // radio and videos only need to be updated on track change, which is handled in a different function
+            if matches!(
+                track.media_type(),
+                MediaTypesSimple::LiveRadio | MediaTypesSimple::Video
+            ) {
                 return;
             }
 
@@ -288,9 +310,16 @@ impl Model {
                 }
 
                 if let Ok(Some(data)) = track.get_lyrics() {
+                    // fall back to the lyric cache for tracks without embedded lyrics
+                    let data = if data.raw_lyrics.is_empty() {
+                        cached_lyric(&self.config_tui.read().settings.lyrics, track)
+                            .unwrap_or_else(|| (*data).clone())
+                    } else {
+                        (*data).clone()
+                    };
                     self.current_track_lyric = Some(ExtraLyricData {
                         for_track: track.as_track().unwrap().path().to_owned(),
-                        data: (*data).clone(),
+                        data,
                         selected_idx: 0,
                     });
                 } else {
@@ -313,30 +342,87 @@ impl Model {
                 return;
             }
 
-            let mut line = String::new();
+            let for_key = extra.for_track.to_string_lossy().to_string();
+            let captions: Vec<String> = parsed_lyrics
+                .captions
+                .iter()
+                .map(|v| v.text().to_string())
+                .collect();
+            let index = parsed_lyrics.get_caption_index(self.playback.current_track_pos());
+            self.lyric_show_captions(&for_key, &captions, index);
+        }
+    }
 
-            if let Some(l) = parsed_lyrics.get_text(self.playback.current_track_pos()) {
-                line = l.to_string();
-            }
+    /// Show the current caption at `index` of the synced lyric of `for_key`, or all captions from it in karaoke mode.
+    ///
+    /// Each caption is shown side by side with its translation, if shown.
+    fn lyric_show_captions(&mut self, for_key: &str, captions: &[String], index: Option<usize>) {
+        self.translation_request(for_key, captions.to_vec());
+        let column_width = translation_column_width(lyric_width());
+        // the rows of a caption, side by side with its translation if shown
+        let rows = |caption: &str| match self.lyric_translation.get(for_key, caption) {
+            Some(translated) => side_by_side(caption, translated, column_width),
+            None => vec![caption.to_string()],
+        };
 
-            self.lyric_set_lyric(line);
-        }
+        let lines = if self.karaoke {
+            let index = index.unwrap_or_default();
+            let highlight = self.config_tui.read().settings.theme.fallback_highlight();
+            captions
+                .iter()
+                .enumerate()
+                // keep some previous lines visible
+                .skip(index.saturating_sub(KARAOKE_CONTEXT_LINES))
+                .flat_map(|(idx, caption)| {
+                    rows(caption).into_iter().map(move |row| {
+                        let span = TextSpan::from(row);
+                        PropValue::TextSpan(if idx == index {
+                            span.fg(highlight).bold()
+                        } else {
+                            span
+                        })
+                    })
+                })
+                .collect()
+        } else {
+            let line = index
+                .and_then(|idx| captions.get(idx))
+                .map(String::as_str)
+                .unwrap_or_default();
+            rows(line)
+                .into_iter()
+                .map(|row| PropValue::TextSpan(TextSpan::from(row)))
+                .collect()
+        };
+
+        self.app
+            .attr(
+                &Id::Lyric,
+                Attribute::Text,
+                AttrValue::Payload(PropPayload::Vec(lines)),
+            )
+            .ok();
     }
 
-    /// Update the lyric field to show Radio information.
+    /// Update the lyric field to show Radio information, or the title of a video.
     ///
     /// Needs to be run on:
-    /// - track change from / to radio
+    /// - track change from / to radio or video
     pub fn lyric_update_for_radio<T: AsRef<str>>(&mut self, radio_title: T) {
-        if let Some(song) = self.playback.current_track()
-            && MediaTypesSimple::LiveRadio == song.media_type()
-        {
-            let radio_title = radio_title.as_ref();
-            if radio_title.is_empty() {
-                self.lyric_set_lyric("Radio");
-            } else {
-                self.lyric_set_lyric(format!("Currently Playing: {radio_title}"));
-            }
+        let Some(song) = self.playback.current_track() else {
+            return;
+        };
+        let fallback = match song.media_type() {
+            MediaTypesSimple::LiveRadio => "Radio",
+            MediaTypesSimple::Video => "Video",
+            MediaTypesSimple::Music | MediaTypesSimple::Podcast => return,
+        };
+
+        let radio_title = radio_title.as_ref();
+        if radio_title.is_empty() {
+            self.lyric_set_lyric(fallback);
+        } else {
+            self.lyric_set_lyric(format!("Currently Playing: {radio_title}"));
         }
     }
 
@@ -390,6 +476,78 @@ impl Model {
         }
     }
 
+    /// Get the position and text of all lines of the synced lyric of the current track matching `input`.
+    ///
+    /// Empty if the current track has no synced lyric.
+    fn lyric_search_matches(&self, input: &str) -> Vec<(Duration, &str)> {
+        let Some(parsed_lyrics) = self
+            .current_track_lyric
+            .as_ref()
+            .and_then(|extra| extra.data.parsed_lyrics.as_ref())
+        else {
+            return Vec::new();
+        };
+
+        let search = SearchMatcher::new(self.general_search_mode, input);
+        parsed_lyrics
+            .captions
+            .iter()
+            .enumerate()
+            .filter(|(_, caption)| search.matches(caption.text()))
+            .filter_map(|(idx, caption)| {
+                Some((parsed_lyrics.caption_position(idx)?, caption.text()))
+            })
+            .collect()
+    }
+
+    /// Open the search in the synced lyric of the current track, if there is one.
+    pub fn lyric_search_show(&mut self) {
+        if self.lyric_search_matches("").is_empty() {
+            self.update_show_message_timeout(
+                "Lyric search",
+                "The current track has no synced lyric",
+                None,
+            );
+            return;
+        }
+
+        self.mount_search_lyric();
+        self.lyric_search_update("");
+    }
+
+    /// Generate the result table of the lyric search for `input`.
+    pub fn lyric_search_update(&mut self, input: &str) {
+        let mut table = TableBuilder::default();
+        for (idx, (position, text)) in self.lyric_search_matches(input).into_iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(DurationFmtShort(position).to_string()))
+                .add_col(TextSpan::new(text));
+        }
+        let table = table.build();
+
+        self.general_search_update_show(table);
+    }
+
+    /// Seek to the lyric line selected in the lyric search.
+    pub fn lyric_search_seek(&mut self) {
+        let Ok(State::One(StateValue::Usize(index))) = self.app.state(&Id::GeneralSearchTable)
+        else {
+            return;
+        };
+        let input = match self.app.state(&Id::GeneralSearchInput) {
+            Ok(State::One(StateValue::String(input))) => input,
+            _ => String::new(),
+        };
+
+        // the results are in the same order as when the table was built
+        if let Some((position, _)) = self.lyric_search_matches(&input).get(index) {
+            self.command(TuiCmd::SeekTo(*position));
+        }
+    }
+
     const LYRIC_PODCAST_TITLE: &str = " Details: ";
 
     /// Update the Lyric Component's title.
@@ -418,16 +576,26 @@ impl Model {
             MediaTypes::Track(_track_data) => {
                 let artist = track.artist().unwrap_or(UNKNOWN_ARTIST);
                 let title = track.title().unwrap_or(UNKNOWN_TITLE);
-                format!(" Lyrics of {artist:^.20} - {title:^.20} ")
+                if self.karaoke {
+                    format!(" Karaoke: {artist:^.20} - {title:^.20} ")
+                } else {
+                    format!(" Lyrics of {artist:^.20} - {title:^.20} ")
+                }
             }
             MediaTypes::Radio(_radio_track_data) => " Live Radio ".to_string(),
+            MediaTypes::Video(_video_track_data) => " Video Stream ".to_string(),
             MediaTypes::Podcast(_podcast_track_data) => Self::LYRIC_PODCAST_TITLE.to_string(),
         };
         self.lyric_title_set(lyric_title);
     }
 
-    /// Set a Title for the Lyric Component.
+    /// Set a Title for the Lyric Component, marked if the translation is shown.
     fn lyric_title_set(&mut self, lyric_title: String) {
+        let lyric_title = if self.lyric_translation.enabled {
+            format!("{} (translated) ", lyric_title.trim_end())
+        } else {
+            lyric_title
+        };
         self.app
             .attr(
                 &Id::Lyric,
@@ -437,3 +605,32 @@ impl Model {
             .ok();
     }
 }
+
+/// Get the width the text of the lyric pane is wrapped to.
+fn lyric_width() -> usize {
+    let (term_width, _) = viuer::terminal_size();
+    usize::from(term_width) * 3 / 5
+}
+
+/// Get the width of each column when showing the translation side by side in `lyric_width`.
+fn translation_column_width(lyric_width: usize) -> usize {
+    lyric_width.saturating_sub(3) / 2
+}
+
+/// Get the lyric for `track` from the lyric cache, if there is one.
+fn cached_lyric(settings: &Lyrics, track: &Track) -> Option<LyricData> {
+    let (artist, title) = (track.artist()?, track.title()?);
+    let cache = LyricCache::from_settings(settings)
+        .inspect_err(|err| warn!("Error getting the lyric cache: {err:#}"))
+        .ok()?;
+    let text = cache.get(artist, title)?;
+
+    Some(LyricData {
+        parsed_lyrics: termusiclib::songtag::lrc::Lyric::from_str(&text).ok(),
+        raw_lyrics: vec![Id3Lyrics {
+            lang: "eng".to_string(),
+            description: "cached".to_string(),
+            text,
+        }],
+    })
+}
@@ -19,17 +19,19 @@ mod raw;
 )]
 /// Tag Editor Controls
 mod tag_editor;
+mod track_suggestions;
 mod vendored;
 mod xywh;
 
 // -- export
 pub use config_editor::*;
 pub use database::DBListCriteria;
-pub use footer::Footer;
+pub use footer::{Footer, KeyHints};
 pub use labels::{DownloadSpinner, LabelGeneric, LabelSpan};
 pub use lyric::Lyric;
 pub use playlist::Playlist;
 pub use podcast::{EpisodeList, FeedsList};
-pub use popups::general_search::{GSInputPopup, GSTablePopup, Source};
+pub use popups::SmartPlaylistField;
+pub use popups::general_search::{GSInputPopup, GSTablePopup, SearchMatcher, SearchMode, Source};
 pub use progress::Progress;
 pub use tag_editor::*;
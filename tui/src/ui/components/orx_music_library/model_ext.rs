@@ -1,27 +1,34 @@
 //! Music Library extensions on [`Model`]
 
-use std::{
-    fs::{remove_dir_all, remove_file},
-    path::{Path, PathBuf},
-};
+use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
-use termusiclib::config::v2::server::{ScanDepth, config_extra::ServerConfigVersionedDefaulted};
+use termusiclib::{
+    config::v2::server::{ScanDepth, config_extra::ServerConfigVersionedDefaulted},
+    file_ops::{delete_path, move_path},
+    new_database::track_ops,
+};
+use tokio::runtime::Handle;
 use tuirealm::{
     Sub, SubClause, SubEventClause,
     props::{TableBuilder, TextSpan},
 };
 
 use crate::ui::{
-    components::orx_music_library::{
-        music_library::OrxMusicLibraryComponent, scanner::library_scan,
+    components::{
+        SearchMatcher,
+        orx_music_library::{music_library::OrxMusicLibraryComponent, scanner::library_scan},
     },
     ids::Id,
-    model::{Model, UserEvent},
+    model::{Model, PendingDelete, UserEvent},
     msg::{LIMsg, LINodeReady, LINodeReadySub, LIReloadData, LIReloadPathData, LIReqNode, Msg},
     tui_cmd::TuiCmd,
 };
 
+/// How long a library delete can be undone for.
+pub const UNDO_DELETE_WINDOW: Duration = Duration::from_secs(10);
+
 /// Get all subscriptions for the [`MusicLibrary`] Component.
 fn library_subs() -> Vec<Sub<Id, UserEvent>> {
     vec![
@@ -100,49 +107,110 @@ impl Model {
             debug!("library reload, given path is not absolute! {path:#?}");
         }
 
+        self.new_library_scan_db(&path);
+
+        let _ = self
+            .tx_to_main
+            .send(Msg::Library(LIMsg::ReloadPath(LIReloadPathData {
+                path,
+                change_focus: true,
+            })));
+    }
+
+    /// Reload the given directories in the library, without changing the focus.
+    ///
+    /// Used when the server reports that their content changed on disk, the server already updated the database.
+    pub fn new_library_reload_dirs(&self, dirs: Vec<PathBuf>) {
+        for path in dirs {
+            let _ = self
+                .tx_to_main
+                .send(Msg::Library(LIMsg::ReloadPath(LIReloadPathData {
+                    path,
+                    change_focus: false,
+                })));
+        }
+    }
+
+    /// Re-index the given path for the database, if it is part of a music root.
+    fn new_library_scan_db(&self, path: &Path) {
         let config_read = self.config_server.read_recursive();
-        for dir in &self.config_server.read().settings.player.music_dirs {
+        for dir in &config_read.settings.player.music_dirs {
             let absolute_dir = shellexpand::path::tilde(dir);
 
             if path.starts_with(absolute_dir)
-                && let Err(err) = self.db.scan_path(&path, &config_read, false)
+                && let Err(err) = self.db.scan_path(path, &config_read, false)
             {
                 error!("Error scanning path {:#?}: {err:#?}", path.display());
             }
         }
-
-        let _ = self
-            .tx_to_main
-            .send(Msg::Library(LIMsg::ReloadPath(LIReloadPathData {
-                path,
-                change_focus: true,
-            })));
     }
 
     /// Show a deletion confirmation for the currently selected node.
+    ///
+    /// Deletes directly if [`confirm_delete`](termusiclib::config::v2::tui::BehaviorSettings::confirm_delete) is disabled.
     pub fn new_library_show_delete_confirm(&mut self, path: PathBuf, focus_node: Option<String>) {
-        if path.is_file() {
+        if !self.config_tui.read().settings.behavior.confirm_delete {
+            if let Err(err) = self.new_library_delete_node(&path, focus_node) {
+                self.mount_error_popup(err.context("library delete song"));
+            }
+        } else if path.is_file() {
             self.mount_confirm_radio(path, focus_node);
         } else {
-            self.mount_confirm_input(
-                path,
-                focus_node,
-                "You're about to delete the whole directory.",
-            );
+            let title = if self.config_tui.read().settings.behavior.delete_to_trash {
+                "You're about to move the whole directory to the trash."
+            } else {
+                "You're about to delete the whole directory permanently."
+            };
+            self.mount_confirm_input(path, focus_node, title);
         }
     }
 
-    /// Delete the currently selected node from the filesystem and reload the tree and remove the deleted paths from the playlist.
+    /// Delete the currently selected node and reload the tree and remove the deleted paths from the playlist.
+    ///
+    /// Depending on [`delete_to_trash`](termusiclib::config::v2::tui::BehaviorSettings::delete_to_trash), the node is moved to the trash
+    /// and can be restored with [`new_library_undo_delete`](Self::new_library_undo_delete) for [`UNDO_DELETE_WINDOW`],
+    /// or it is deleted permanently.
     pub fn new_library_delete_node(
         &mut self,
         path: &Path,
         focus_node: Option<String>,
     ) -> Result<()> {
-        if path.is_file() {
-            remove_file(path)?;
+        let to_trash = self.config_tui.read().settings.behavior.delete_to_trash;
+        // collect before deleting, as "RemoveDeletedItems" will drop them from the playlist
+        let playlist_entries = self.playlist_entries_in(path);
+
+        if let Some(trashed) = delete_path(path, to_trash)? {
+            // only one delete can be undone, so the previous one becomes final
+            self.new_library_finish_pending_delete();
+
+            self.pending_delete = Some(PendingDelete {
+                trashed,
+                playlist_entries,
+                deleted_at: Instant::now(),
+            });
+
+            let key = self
+                .config_tui
+                .read()
+                .settings
+                .keys
+                .library_keys
+                .undo_delete
+                .to_string();
+            self.update_show_message_timeout(
+                "Moved to trash",
+                &format!("Press \"{key}\" in the library to undo"),
+                Some(UNDO_DELETE_WINDOW.as_secs()),
+            );
+
+            let tx = self.tx_to_main.clone();
+            let expired_path = path.to_path_buf();
+            Handle::current().spawn(async move {
+                tokio::time::sleep(UNDO_DELETE_WINDOW).await;
+                let _ = tx.send(Msg::Library(LIMsg::UndoDeleteExpired(expired_path)));
+            });
         } else {
-            path.canonicalize()?;
-            remove_dir_all(path)?;
+            self.new_library_delete_db_path(path.to_path_buf());
         }
 
         // always scan the parent, as otherwise, if the deleted "path" is the root
@@ -156,16 +224,133 @@ impl Model {
         Ok(())
     }
 
+    /// Remove all database entries at or inside `path`, and clean up now unreferenced albums and artists.
+    fn new_library_delete_db_path(&self, path: PathBuf) {
+        let db = self.db.clone();
+        Handle::current().spawn(async move {
+            let res = db
+                .write(move |conn| track_ops::delete_tracks_path(conn, &path))
+                .await;
+
+            match res {
+                // remove albums and artists that may now be unreferenced
+                Ok(affected) if affected > 0 => db.run_cleanup(),
+                Ok(_) => (),
+                Err(err) => error!("Error removing deleted tracks from the database: {err:#?}"),
+            }
+        });
+    }
+
+    /// Make the pending delete final, if there is one.
+    ///
+    /// The database entries are kept until then, so that a undo keeps things like play positions.
+    pub fn new_library_finish_pending_delete(&mut self) {
+        if let Some(pending) = self.pending_delete.take() {
+            self.new_library_delete_db_path(pending.trashed.original);
+        }
+    }
+
+    /// Handle the undo window for the delete of `path` having run out.
+    pub fn new_library_handle_undo_expired(&mut self, path: &Path) {
+        // a newer delete or a undo may have happened in the meantime
+        let is_expired = self.pending_delete.as_ref().is_some_and(|pending| {
+            pending.trashed.original == path && pending.deleted_at.elapsed() >= UNDO_DELETE_WINDOW
+        });
+        if is_expired {
+            self.new_library_finish_pending_delete();
+        }
+    }
+
+    /// Restore the last deleted node from the trash, if still within [`UNDO_DELETE_WINDOW`].
+    pub fn new_library_undo_delete(&mut self) -> Result<()> {
+        let Some(pending) = self.pending_delete.take() else {
+            self.update_show_message_timeout("Undo delete", "Nothing to undo", None);
+            return Ok(());
+        };
+
+        if pending.deleted_at.elapsed() >= UNDO_DELETE_WINDOW {
+            self.new_library_delete_db_path(pending.trashed.original);
+            self.update_show_message_timeout("Undo delete", "Nothing to undo", None);
+            return Ok(());
+        }
+
+        if let Err(err) = pending.trashed.restore() {
+            // the file stays in the trash, so the entries are stale now
+            self.new_library_delete_db_path(pending.trashed.original.clone());
+            return Err(err.context(format!("restore {}", pending.trashed.original.display())));
+        }
+
+        self.playlist_restore_entries(pending.playlist_entries);
+        self.new_library_reload_and_focus(pending.trashed.original);
+        self.update_show_message_timeout("Undo delete", "Restored from the trash", None);
+
+        Ok(())
+    }
+
+    /// Rename the node at `path` to `new_name` in the same directory.
+    pub fn new_library_rename(&mut self, path: &Path, new_name: &str) -> Result<()> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() || new_name == "." || new_name == ".." {
+            bail!("Invalid name {new_name:?}");
+        }
+        if new_name.contains(MAIN_SEPARATOR) || new_name.contains('/') {
+            bail!("Name may not contain a path separator");
+        }
+
+        let parent = path.parent().context("path to rename has no parent")?;
+        let new_path = parent.join(new_name);
+
+        if new_path == path {
+            return Ok(());
+        }
+
+        self.tag_backup_rename("Rename in the library", path, &new_path)?;
+        move_path(path, &new_path)?;
+
+        self.new_library_handle_moved(path, &new_path);
+        // also change the focus to the new path
+        self.new_library_scan_dir(parent, Some(new_path.to_string_lossy().to_string()));
+
+        Ok(())
+    }
+
+    /// Update everything that references `from` to now reference `to`, after a node got moved.
+    ///
+    /// This keeps the database entries (including play positions) and matching playlist entries.
+    /// Does not reload the tree.
+    pub fn new_library_handle_moved(&mut self, from: &Path, to: &Path) {
+        let db = self.db.clone();
+        let (db_from, db_to) = (from.to_path_buf(), to.to_path_buf());
+        Handle::current().spawn(async move {
+            let res = db
+                .write(move |conn| track_ops::rename_tracks_path(conn, &db_from, &db_to))
+                .await;
+
+            if let Err(err) = res {
+                error!("Error updating moved tracks in the database: {err:#?}");
+            }
+        });
+
+        // for paths that got moved into a music root from outside of one
+        self.new_library_scan_db(to);
+
+        self.playlist_update_library_move(from, to);
+    }
+
+    /// Handle a node that got copied to `path`.
+    pub fn new_library_handle_copied(&mut self, path: &Path) {
+        self.new_library_scan_db(path);
+    }
+
     /// Generate the result table for search `input`, recursively from the tree's root node's path.
     pub fn new_library_update_search(&mut self, input: &str, path: &Path) {
         let mut table: TableBuilder = TableBuilder::default();
         let all_items = walkdir::WalkDir::new(path).follow_links(true);
         let mut idx: usize = 0;
-        let search = format!("*{}*", input.to_lowercase());
-        let search = wildmatch::WildMatch::new(&search);
+        let search = SearchMatcher::new(self.general_search_mode, input);
         for record in all_items.into_iter().filter_map(std::result::Result::ok) {
             let file_name = record.path();
-            if search.matches(&file_name.to_string_lossy().to_lowercase()) {
+            if search.matches(&file_name.to_string_lossy()) {
                 if idx > 0 {
                     table.add_row();
                 }
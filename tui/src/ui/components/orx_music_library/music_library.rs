@@ -2,13 +2,16 @@
 
 use std::{
     cell::OnceCell,
-    fs::rename,
     num::NonZeroUsize,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
-use termusiclib::config::{SharedTuiSettings, TuiOverlay, v2::server::ScanDepth};
+use termusiclib::{
+    config::{SharedTuiSettings, TuiOverlay, v2::server::ScanDepth},
+    file_ops::{copy_path, move_path},
+    tag_backup::BackupStore,
+};
 use tuirealm::{
     Component, Event, MockComponent,
     command::{Cmd, CmdResult, Direction, Position},
@@ -27,11 +30,14 @@ use tuirealm_orx_tree::{
 };
 
 use crate::ui::{
-    components::orx_music_library::scanner::{library_scan, library_scan_cb, recvec_to_tree},
+    components::orx_music_library::scanner::{
+        CHILDREN_CHUNK, add_pending_children, library_scan, library_scan_cb, recvec_to_tree,
+    },
     model::{DownloadTracker, TxToMain, UserEvent},
     msg::{
-        DeleteConfirmMsg, GSMsg, IsDir, LIMsg, LINodeReady, LINodeReadySub, LIReloadData,
-        LIReloadPathData, LIReqNode, Msg, PLMsg, TEMsg, YSMsg,
+        DeleteConfirmMsg, DeviceSyncMsg, GSMsg, IsDir, LIMsg, LINodeReady, LINodeReadySub,
+        LIReloadData, LIReloadPathData, LIReqNode, LyricsFetchMsg, Msg, PLMsg, QueueReplaceMsg,
+        RecVec, TEMsg, YSMsg,
     },
 };
 
@@ -52,6 +58,10 @@ pub struct MusicLibData {
     /// Lazily evaluated from `path`, only when it becomes necessary.
     // TODO: evaluate if it would be more performant to only cache if `path.file_name().to_str_lossy()` returns `Cow::Owned`.
     as_str: OnceCell<String>,
+    /// The children which are not added to the tree yet, in order after the added children.
+    ///
+    /// See [`CHILDREN_CHUNK`].
+    pub pending: Vec<RecVec>,
 }
 
 impl MusicLibData {
@@ -72,8 +82,16 @@ impl MusicLibData {
             is_loading: false,
             is_error: false,
             as_str: OnceCell::default(),
+            pending: Vec::new(),
         }
     }
+
+    /// Set the children which are not added to the tree yet.
+    #[must_use]
+    pub fn with_pending(mut self, pending: Vec<RecVec>) -> Self {
+        self.pending = pending;
+        self
+    }
 }
 
 /// Indicator when for directories when we already issued a load for it (and not have gotten a response back yet).
@@ -134,6 +152,13 @@ impl NodeValue for MusicLibData {
 
 const LOADING_TREE_TEXT: &str = "Loading...";
 
+/// What should happen with a yanked node on paste.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum YankMode {
+    Move,
+    Copy,
+}
+
 #[derive(Debug, MockComponent)]
 pub struct OrxMusicLibraryComponent {
     component: TreeView<MusicLibData>,
@@ -142,7 +167,7 @@ pub struct OrxMusicLibraryComponent {
     tx_to_main: TxToMain,
     download_tracker: DownloadTracker,
     /// The path of the last yanked node.
-    yanked_path: Option<PathBuf>,
+    yanked_path: Option<(PathBuf, YankMode)>,
 }
 
 impl OrxMusicLibraryComponent {
@@ -237,20 +262,24 @@ impl OrxMusicLibraryComponent {
     }
 
     /// Store the currently selected node as yanked (for pasting with [`Self::paste`]).
-    fn yank(&mut self) {
+    ///
+    /// The yank is kept when switching roots, so that nodes can be moved or copied between music roots.
+    fn yank(&mut self, mode: YankMode) {
         if let Some(path) = self.get_selected_path() {
-            self.yanked_path = Some(path.to_path_buf());
+            self.yanked_path = Some((path.to_path_buf(), mode));
         }
     }
 
     /// Paste the previously yanked node in the currently selected node if it is a directory, otherwise in its parent.
+    ///
+    /// Depending on how the node was yanked, it is either moved or copied.
     fn paste(&mut self) -> Result<Option<LIMsg>> {
         // This should happen before "yanked_path.take" so that we dont take, if we cannot apply it.
         // And "get_selected_path" cannot be put before here as that uses a immutable self reference, but ".take" requires mutable.
         if self.component.get_current_selected_node().is_none() {
             return Ok(None);
         }
-        let Some(old_path) = self.yanked_path.take() else {
+        let Some((old_path, mode)) = self.yanked_path.take() else {
             return Ok(None);
         };
         let Some(selected_node_path) = self.get_selected_path() else {
@@ -269,31 +298,47 @@ impl OrxMusicLibraryComponent {
             selected_parent.join(pold_filename)
         };
 
-        rename(&old_path, &new_path)?;
+        if mode == YankMode::Copy {
+            copy_path(&old_path, &new_path)?;
+
+            self.handle_reload_at(LIReloadPathData {
+                path: new_path.clone(),
+                change_focus: true,
+            });
+
+            return Ok(Some(LIMsg::Copied(new_path)));
+        }
+
+        BackupStore::from_settings(&self.config.read().settings.tag_backups)?.backup_rename(
+            "Move in the library",
+            &old_path,
+            &new_path,
+        )?;
+        move_path(&old_path, &new_path)?;
 
         if new_path.starts_with(old_parent) {
             // new path is contained within old path's parent directory
             self.handle_reload_at(LIReloadPathData {
-                path: new_path,
+                path: new_path.clone(),
                 change_focus: true,
             });
             self.handle_reload_at(LIReloadPathData {
-                path: old_path,
+                path: old_path.clone(),
                 change_focus: false,
             });
         } else if old_parent.starts_with(selected_parent) {
             self.handle_reload_at(LIReloadPathData {
-                path: new_path,
+                path: new_path.clone(),
                 change_focus: true,
             });
             self.handle_reload_at(LIReloadPathData {
-                path: old_path,
+                path: old_path.clone(),
                 change_focus: false,
             });
         } else {
             // new path is not contained within old path's parent directory, so need to load both
             self.handle_reload_at(LIReloadPathData {
-                path: new_path,
+                path: new_path.clone(),
                 change_focus: true,
             });
             self.handle_reload_at(LIReloadPathData {
@@ -302,7 +347,7 @@ impl OrxMusicLibraryComponent {
             });
         }
 
-        Ok(Some(LIMsg::PlaylistRunDelete))
+        Ok(Some(LIMsg::Moved(old_path, new_path)))
     }
 
     /// Get the current root node's path, if there is one.
@@ -537,6 +582,78 @@ impl OrxMusicLibraryComponent {
         self.trigger_subload_with_focus(nearest_path, ScanDepth::Limited(depth), focus_node);
     }
 
+    /// Move the selection with `cmd`, then add the pending children the selection got near to.
+    fn perform_move(&mut self, cmd: Cmd) -> CmdResult {
+        let result = self.perform(cmd);
+        let mut added = self.add_pending_near_selection();
+
+        // the last node is only the last once all pending children are added
+        if cmd == Cmd::GoTo(Position::End) {
+            while added {
+                self.perform(cmd);
+                added = self.add_pending_near_selection();
+            }
+        }
+
+        result
+    }
+
+    /// Add the next chunk of pending children to each parent of the selected node whose last added child is at most
+    /// a page away from the selection, returns whether any were added.
+    fn add_pending_near_selection(&mut self) -> bool {
+        /// How close to the last added child the selection has to be, more than a page down moves
+        const DISTANCE: usize = 100;
+
+        let mut parents = Vec::new();
+        let mut current = self.component.get_current_selected_node();
+        while let Some(node) = current {
+            let parent = node.parent();
+            if let Some(parent) = &parent
+                && !parent.data().pending.is_empty()
+                && node.sibling_idx() + DISTANCE >= parent.num_children()
+            {
+                parents.push(parent.idx());
+            }
+            current = parent;
+        }
+
+        let mut added = false;
+        for idx in parents {
+            if let Some(mut node) = self.component.get_node_mut(idx) {
+                added |= add_pending_children(&mut node, CHILDREN_CHUNK) > 0;
+            }
+        }
+
+        added
+    }
+
+    /// Get the [`NodeIdx`] of `path`, adding the pending children it is in to the tree if necessary.
+    fn get_idx_of_path_add_pending(&mut self, path: &Path) -> Option<NodeIdx<MusicLibData>> {
+        loop {
+            if let Some(idx) = self.get_idx_of_path(path) {
+                return Some(idx);
+            }
+
+            // find the node whose pending children contain the path
+            let (idx, pos) = {
+                let root_node = self.component.get_tree().get_root()?;
+                let mut traverser = Dfs::<OverNode>::new();
+                let mut walker = root_node.walk_with(&mut traverser);
+                walker.find_map(|node| {
+                    let pos = node
+                        .data()
+                        .pending
+                        .iter()
+                        .position(|v: &RecVec| path.starts_with(&v.path))?;
+                    Some((node.idx(), pos))
+                })?
+            };
+
+            let mut node = self.component.get_node_mut(idx)?;
+            add_pending_children(&mut node, (pos + 1).max(CHILDREN_CHUNK));
+        }
+    }
+
     /// Get the [`NodeIdx`] of a given [`Path`], searches from current tree root.
     fn get_idx_of_path(&self, path: &Path) -> Option<NodeIdx<MusicLibData>> {
         let root_node = self.component.get_tree().get_root()?;
@@ -554,6 +671,7 @@ impl OrxMusicLibraryComponent {
         self.component.open_all_parents(idx);
         // always open the selected node
         self.handle_right_key(true);
+        self.add_pending_near_selection();
     }
 
     /// Apply the given data as the root of the tree, resetting the state of the tree.
@@ -575,7 +693,7 @@ impl OrxMusicLibraryComponent {
         *unsafe { self.component.get_tree_mut() } = tree;
 
         if let Some(initial_node) = initial_node {
-            let idx = self.get_idx_of_path(&initial_node);
+            let idx = self.get_idx_of_path_add_pending(&initial_node);
             if let Some(idx) = idx {
                 self.select_and_open_node(idx);
             } else {
@@ -598,7 +716,7 @@ impl OrxMusicLibraryComponent {
         while let Some(parent) = remaining_path.parent()
             && parent.starts_with(&self.component.get_tree().root().data().path)
         {
-            if let Some(idx) = self.get_idx_of_path(parent) {
+            if let Some(idx) = self.get_idx_of_path_add_pending(parent) {
                 self.select_and_open_node(idx);
                 break;
             }
@@ -671,7 +789,7 @@ impl OrxMusicLibraryComponent {
         }
 
         if let Some(focus_node) = data.focus_node {
-            let idx = self.get_idx_of_path(&focus_node);
+            let idx = self.get_idx_of_path_add_pending(&focus_node);
             if let Some(idx) = idx {
                 self.select_and_open_node(idx);
             } else {
@@ -736,7 +854,7 @@ impl Component<Msg, UserEvent> for OrxMusicLibraryComponent {
                 }
             }
             Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.down.get() => {
-                self.perform(Cmd::Move(Direction::Down))
+                self.perform_move(Cmd::Move(Direction::Down))
             }
             Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.up.get() => {
                 self.perform(Cmd::Move(Direction::Up))
@@ -758,7 +876,7 @@ impl Component<Msg, UserEvent> for OrxMusicLibraryComponent {
             Event::Keyboard(KeyEvent {
                 code: Key::Down,
                 modifiers: KeyModifiers::NONE,
-            }) => self.perform(Cmd::Move(Direction::Down)),
+            }) => self.perform_move(Cmd::Move(Direction::Down)),
             Event::Keyboard(KeyEvent {
                 code: Key::Up,
                 modifiers: KeyModifiers::NONE,
@@ -768,7 +886,7 @@ impl Component<Msg, UserEvent> for OrxMusicLibraryComponent {
             Event::Keyboard(KeyEvent {
                 code: Key::PageDown,
                 modifiers: KeyModifiers::NONE,
-            }) => self.perform(Cmd::Custom(cmd::PG_DOWN)),
+            }) => self.perform_move(Cmd::Custom(cmd::PG_DOWN)),
             Event::Keyboard(KeyEvent {
                 code: Key::PageUp,
                 modifiers: KeyModifiers::NONE,
@@ -778,7 +896,7 @@ impl Component<Msg, UserEvent> for OrxMusicLibraryComponent {
                 self.perform(Cmd::GoTo(Position::Begin))
             }
             Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_bottom.get() => {
-                self.perform(Cmd::GoTo(Position::End))
+                self.perform_move(Cmd::GoTo(Position::End))
             }
             Event::Keyboard(KeyEvent {
                 code: Key::Home,
@@ -787,14 +905,18 @@ impl Component<Msg, UserEvent> for OrxMusicLibraryComponent {
             Event::Keyboard(KeyEvent {
                 code: Key::End,
                 modifiers: KeyModifiers::NONE,
-            }) => self.perform(Cmd::GoTo(Position::End)),
+            }) => self.perform_move(Cmd::GoTo(Position::End)),
 
             // file modifying
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.delete.get() => {
                 return self.handle_delete();
             }
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.yank.get() => {
-                self.yank();
+                self.yank(YankMode::Move);
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.copy.get() => {
+                self.yank(YankMode::Copy);
                 CmdResult::None
             }
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.paste.get() => {
@@ -804,6 +926,37 @@ impl Component<Msg, UserEvent> for OrxMusicLibraryComponent {
                     Err(err) => return Some(Msg::Library(LIMsg::PasteError(err.to_string()))),
                 }
             }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.undo_delete.get() => {
+                return Some(Msg::Library(LIMsg::UndoDelete));
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.sync_device.get() => {
+                if let Some(path) = self.get_selected_path() {
+                    return Some(Msg::DeviceSync(DeviceSyncMsg::SyncPath(path.to_path_buf())));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.fetch_lyrics.get() => {
+                if let Some(path) = self.get_selected_path() {
+                    return Some(Msg::LyricsFetch(LyricsFetchMsg::FetchPath(
+                        path.to_path_buf(),
+                    )));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.replace_playlist.get() => {
+                if let Some(path) = self.get_selected_path() {
+                    return Some(Msg::QueueReplace(QueueReplaceMsg::WithPath(
+                        path.to_path_buf(),
+                    )));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.rename.get() => {
+                if let Some(path) = self.get_selected_path() {
+                    return Some(Msg::Library(LIMsg::RenameShow(path.to_path_buf())));
+                }
+                CmdResult::None
+            }
 
             // music root modification
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.cycle_root.get() => {
@@ -2,8 +2,8 @@
 
 use std::path::{Path, PathBuf};
 
-use termusiclib::{config::v2::server::ScanDepth, utils::get_pin_yin};
-use tuirealm_orx_tree::types::{NodeIdx, Tree};
+use termusiclib::{config::v2::server::ScanDepth, utils::get_sort_key};
+use tuirealm_orx_tree::types::{NodeIdx, NodeMut, Tree};
 
 use crate::ui::{
     components::orx_music_library::music_library::MusicLibData,
@@ -11,6 +11,12 @@ use crate::ui::{
     msg::{IsDir, LIMsg, LINodeReady, Msg, RecVec},
 };
 
+/// The maximal amount of children of a directory added to the tree at once.
+///
+/// The other children are kept in [`MusicLibData`] and added once the selection gets near the last added child, so
+/// huge directories do not have to be added to the tree at once.
+pub const CHILDREN_CHUNK: usize = 500;
+
 /// Execute a library scan on a different thread.
 ///
 /// Executes [`library_dir_tree`] on a different thread and calls `cb` on finish.
@@ -26,11 +32,11 @@ pub fn library_scan_cb<P: Into<PathBuf>, F>(
     std::thread::Builder::new()
         .name("library tree scan".to_string())
         .spawn(move || {
-            download_tracker.increase_one(path.to_string_lossy());
+            download_tracker.increase_scan(path.to_string_lossy());
             let vec = library_dir_tree(&path, depth);
 
             cb(vec);
-            download_tracker.decrease_one(&path.to_string_lossy());
+            download_tracker.decrease_scan(&path.to_string_lossy());
         })
         .expect("Failed to spawn thread");
 }
@@ -91,7 +97,7 @@ fn library_dir_tree_inner(path: &Path, depth: ScanDepth, is_dir: Option<bool>) -
             // filter out hidden files
             .filter(|p| !p.file_name().to_string_lossy().starts_with('.'))
             .map(|v| {
-                let sort_str = get_pin_yin(&v.file_name().to_string_lossy());
+                let sort_str = get_sort_key(&v.file_name().to_string_lossy(), true);
                 let is_dir = v.file_type().is_ok_and(|v| v.is_dir());
                 let path = v.path();
                 (sort_str, (path, is_dir))
@@ -106,10 +112,12 @@ fn library_dir_tree_inner(path: &Path, depth: ScanDepth, is_dir: Option<bool>) -
             IsDir::YesLoaded
         };
 
-        for (_sort_str, (path, is_dir)) in paths {
+        for (idx, (_sort_str, (path, is_dir))) in paths.into_iter().enumerate() {
+            // children beyond the first chunk are not added to the tree yet, so dont scan them any further
+            let depth = if idx < CHILDREN_CHUNK { depth - 1 } else { 0 };
             node.children.push(library_dir_tree_inner(
                 &path,
-                ScanDepth::Limited(depth - 1),
+                ScanDepth::Limited(depth),
                 Some(is_dir),
             ));
         }
@@ -129,17 +137,21 @@ pub fn recvec_to_tree(vec: RecVec) -> (NodeIdx<MusicLibData>, Tree<MusicLibData>
 /// Convert the given `vec` to be child on `parent_node`.
 ///
 /// If `parent_node` is `None` the new node will be pushed as the root.
+///
+/// Only the first [`CHILDREN_CHUNK`] children are added, the others are kept as pending in the node's data.
 pub fn recvec_to_node_rec(
-    vec: RecVec,
+    mut vec: RecVec,
     parent_node: Option<NodeIdx<MusicLibData>>,
     tree: &mut Tree<MusicLibData>,
 ) -> NodeIdx<MusicLibData> {
+    let pending = vec
+        .children
+        .split_off(CHILDREN_CHUNK.min(vec.children.len()));
+    let data = MusicLibData::new(vec.path, vec.is_dir).with_pending(pending);
     let nodeidx = if let Some(idx) = parent_node {
-        tree.get_node_mut(idx)
-            .unwrap()
-            .push_child(MusicLibData::new(vec.path, vec.is_dir))
+        tree.get_node_mut(idx).unwrap().push_child(data)
     } else {
-        tree.push_root(MusicLibData::new(vec.path, vec.is_dir))
+        tree.push_root(data)
     };
 
     for val in vec.children {
@@ -148,3 +160,63 @@ pub fn recvec_to_node_rec(
 
     nodeidx
 }
+
+/// Add up to `count` of the pending children of `node` to the tree, returns how many were added.
+pub fn add_pending_children(node: &mut NodeMut<'_, MusicLibData>, count: usize) -> usize {
+    let pending = &mut node.data_mut().pending;
+    let children: Vec<RecVec> = pending.drain(..count.min(pending.len())).collect();
+    let added = children.len();
+
+    for child in children {
+        node.push_child_tree(recvec_to_tree(child).1);
+    }
+
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use tuirealm_orx_tree::NodeRef;
+
+    use super::{CHILDREN_CHUNK, add_pending_children, recvec_to_tree};
+    use crate::ui::msg::{IsDir, RecVec};
+
+    #[test]
+    fn should_add_huge_directories_in_chunks() {
+        let root = PathBuf::from("/music");
+        let children = (0..CHILDREN_CHUNK * 2 + 10)
+            .map(|idx| RecVec {
+                path: root.join(format!("{idx}.mp3")),
+                is_dir: IsDir::No,
+                children: Vec::new(),
+            })
+            .collect();
+        let (root_idx, mut tree) = recvec_to_tree(RecVec {
+            path: root,
+            is_dir: IsDir::YesLoaded,
+            children,
+        });
+
+        let root_node = tree.node(root_idx);
+        assert_eq!(root_node.num_children(), CHILDREN_CHUNK);
+        assert_eq!(root_node.data().pending.len(), CHILDREN_CHUNK + 10);
+
+        let mut root_node = tree.node_mut(root_idx);
+        assert_eq!(
+            add_pending_children(&mut root_node, CHILDREN_CHUNK),
+            CHILDREN_CHUNK
+        );
+        // the order is kept
+        assert_eq!(
+            root_node.data().pending[0].path,
+            PathBuf::from(format!("/music/{}.mp3", CHILDREN_CHUNK * 2))
+        );
+        assert_eq!(add_pending_children(&mut root_node, CHILDREN_CHUNK), 10);
+        assert_eq!(add_pending_children(&mut root_node, CHILDREN_CHUNK), 0);
+
+        let root_node = tree.node(root_idx);
+        assert_eq!(root_node.num_children(), CHILDREN_CHUNK * 2 + 10);
+    }
+}
@@ -3,23 +3,24 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::{Context as _, Result, anyhow};
-use rand::seq::IndexedRandom;
+use chrono::{DateTime, Local, TimeDelta};
 use termusiclib::common::const_unknown::{UNKNOWN_ALBUM, UNKNOWN_ARTIST};
 use termusiclib::config::SharedTuiSettings;
 use termusiclib::config::v2::server::{LoopMode, ScanDepth};
 use termusiclib::new_database::track_ops::TrackRead;
-use termusiclib::new_database::{album_ops, track_ops};
 use termusiclib::player::playlist_helpers::{
-    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
-    PlaylistTrackSource,
+    PlaylistAddRandom, PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed,
+    PlaylistSwapTrack, PlaylistTrackInfo, PlaylistTrackSource,
 };
 use termusiclib::player::{
     PlaylistAddTrackInfo, PlaylistLoopModeInfo, PlaylistRemoveTrackInfo, PlaylistShuffledInfo,
-    PlaylistSwapInfo,
+    PlaylistSwapInfo, PlaylistTrackErrorInfo, features,
 };
+use termusiclib::playlist::share::{self, ShareSource};
 use termusiclib::track::Track;
-use termusiclib::track::{DurationFmtShort, PodcastTrackData};
-use termusiclib::utils::{filetype_supported, is_playlist, playlist_get_vec};
+use termusiclib::track::{DurationFmtShort, PodcastTrackData, TrackData};
+use termusiclib::utils::{get_parent_folder, is_playlist, playlist_get_vec};
+use termusiclib::ytdlp;
 use tui_realm_stdlib::Table;
 use tuirealm::props::{Alignment, BorderType, PropPayload, PropValue, TableBuilder, TextSpan};
 use tuirealm::props::{Borders, Style};
@@ -35,8 +36,12 @@ use tuirealm::{
 use crate::ui::Model;
 use crate::ui::components::orx_music_library::scanner::library_dir_tree;
 use crate::ui::ids::Id;
+use crate::ui::model::quality_badges::quality_span;
+use crate::ui::model::track_labels::{label_for_key, with_label};
 use crate::ui::model::{TermusicLayout, UserEvent};
-use crate::ui::msg::{GSMsg, Msg, PLMsg, SearchCriteria};
+use crate::ui::msg::{
+    BookmarksMsg, DeviceSyncMsg, GSMsg, LyricsFetchMsg, Msg, PLMsg, SearchCriteria,
+};
 use crate::ui::tui_cmd::{PlaylistCmd, TuiCmd};
 
 #[derive(MockComponent)]
@@ -65,9 +70,9 @@ impl Playlist {
                 .rewind(false)
                 .step(4)
                 .row_height(1)
-                .headers(["Duration", "Artist", "Title", "Album"])
+                .headers(["Duration", "Quality", "Artist", "Title", "Album"])
                 .column_spacing(2)
-                .widths(&[12, 20, 25, 43])
+                .widths(&[12, 8, 19, 24, 37])
                 .table(
                     TableBuilder::default()
                         .add_col(TextSpan::from("Empty"))
@@ -191,6 +196,44 @@ impl Component<Msg, UserEvent> for Playlist {
             Event::Keyboard(key) if key == keys.playlist_keys.add_random_songs.get() => {
                 return Some(Msg::Playlist(PLMsg::AddRandomTracks));
             }
+            Event::Keyboard(key) if key == keys.playlist_keys.add_random_unplayed.get() => {
+                return Some(Msg::Playlist(PLMsg::AddRandomUnplayed));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.add_auto_dj.get() => {
+                return Some(Msg::Playlist(PLMsg::AddAutoDj));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.sync_device.get() => {
+                return Some(Msg::DeviceSync(DeviceSyncMsg::SyncPlaylist));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.fetch_lyrics.get() => {
+                return Some(Msg::LyricsFetch(LyricsFetchMsg::FetchPlaylist));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.add_bookmark.get() => {
+                return Some(Msg::Bookmarks(BookmarksMsg::AddShow));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.show_bookmarks.get() => {
+                match self.component.state() {
+                    State::One(StateValue::Usize(index_selected)) => {
+                        return Some(Msg::Bookmarks(BookmarksMsg::Show(index_selected)));
+                    }
+                    _ => CmdResult::None,
+                }
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.set_offsets.get() => {
+                match self.component.state() {
+                    State::One(StateValue::Usize(index_selected)) => {
+                        return Some(Msg::Playlist(PLMsg::OffsetsShow(index_selected)));
+                    }
+                    _ => CmdResult::None,
+                }
+            }
+            Event::Keyboard(key) if label_for_key(key).is_some() => match self.component.state() {
+                State::One(StateValue::Usize(index_selected)) => {
+                    let label = label_for_key(key).flatten();
+                    return Some(Msg::Playlist(PLMsg::SetLabel(index_selected, label)));
+                }
+                _ => CmdResult::None,
+            },
             _ => CmdResult::None,
         };
         match cmd_result {
@@ -217,7 +260,12 @@ impl Model {
 
     pub fn playlist_switch_layout(&mut self) {
         if self.layout == TermusicLayout::Podcast {
-            let headers = &["Duration", "Episodes"];
+            let show_start_time = self.config_tui.read().settings.playlist.show_start_time;
+            let headers: &[&str] = if show_start_time {
+                &["Duration", "Starts", "Episodes"]
+            } else {
+                &["Duration", "Episodes"]
+            };
             self.app
                 .attr(
                     &Id::Playlist,
@@ -232,7 +280,11 @@ impl Model {
                 )
                 .ok();
 
-            let widths = &[12, 88];
+            let widths: &[u16] = if show_start_time {
+                &[12, 8, 80]
+            } else {
+                &[12, 88]
+            };
             self.app
                 .attr(
                     &Id::Playlist,
@@ -246,7 +298,12 @@ impl Model {
             return;
         }
 
-        let headers = &["Duration", "Artist", "Title", "Album"];
+        let show_start_time = self.config_tui.read().settings.playlist.show_start_time;
+        let headers: &[&str] = if show_start_time {
+            &["Duration", "Starts", "Quality", "Artist", "Title", "Album"]
+        } else {
+            &["Duration", "Quality", "Artist", "Title", "Album"]
+        };
         self.app
             .attr(
                 &Id::Playlist,
@@ -261,7 +318,11 @@ impl Model {
             )
             .ok();
 
-        let widths = &[12, 20, 25, 43];
+        let widths: &[u16] = if show_start_time {
+            &[12, 8, 8, 17, 22, 33]
+        } else {
+            &[12, 8, 19, 24, 37]
+        };
         self.app
             .attr(
                 &Id::Playlist,
@@ -274,20 +335,86 @@ impl Model {
         self.playlist_sync();
     }
 
-    /// Add a playlist (like m3u) to the playlist.
-    fn playlist_add_playlist(&mut self, playlist_path: &Path) -> Result<()> {
+    /// Get all tracks of a playlist (like m3u) as sources.
+    pub fn playlist_get_file_sources(
+        &mut self,
+        playlist_path: &Path,
+    ) -> Result<Vec<PlaylistTrackSource>> {
+        if share::is_share_file(playlist_path) {
+            return self.playlist_get_share_sources(playlist_path);
+        }
+
         let vec = playlist_get_vec(playlist_path)?;
 
-        let sources = vec
+        Ok(vec
             .into_iter()
             .map(|v| {
-                if v.starts_with("http") {
+                if ytdlp::is_video_url(&v) {
+                    PlaylistTrackSource::VideoUrl(v)
+                } else if v.starts_with("http") {
                     PlaylistTrackSource::Url(v)
                 } else {
                     PlaylistTrackSource::Path(v)
                 }
             })
-            .collect();
+            .collect())
+    }
+
+    /// Get the sources of a [shared playlist](share), looking for the local files in the music directories.
+    ///
+    /// Youtube entries are streamed, or downloaded into the library if the server cannot stream them.
+    fn playlist_get_share_sources(
+        &mut self,
+        playlist_path: &Path,
+    ) -> Result<Vec<PlaylistTrackSource>> {
+        let content = std::fs::read_to_string(playlist_path)
+            .with_context(|| playlist_path.display().to_string())?;
+        let base = get_parent_folder(playlist_path).to_path_buf();
+        let music_dirs = self.config_server.read().settings.player.music_dirs.clone();
+
+        let mut sources = Vec::new();
+        let mut missing = Vec::new();
+        for entry in share::decode(&content) {
+            match &entry.source {
+                ShareSource::Youtube(id)
+                    if self.server_capabilities.supports(features::VIDEO_STREAM) =>
+                {
+                    sources.push(PlaylistTrackSource::VideoUrl(ShareSource::youtube_url(id)));
+                }
+                ShareSource::Youtube(id) => {
+                    self.youtube_dl(&ShareSource::youtube_url(id), None)?;
+                }
+                ShareSource::Url(url) => sources.push(PlaylistTrackSource::Url(url.clone())),
+                ShareSource::Path(_) => match entry.source.resolve_path(&music_dirs, &base) {
+                    Some(path) => {
+                        sources.push(PlaylistTrackSource::Path(
+                            path.to_string_lossy().to_string(),
+                        ));
+                    }
+                    None => missing.push(entry.title.clone()),
+                },
+            }
+        }
+
+        if !missing.is_empty() {
+            warn!("Shared playlist entries not found in the music directories: {missing:?}");
+            self.show_message_timeout_label_help(
+                format!(
+                    " {} shared tracks are not in your music directories ",
+                    missing.len()
+                ),
+                None,
+                None,
+                None,
+            );
+        }
+
+        Ok(sources)
+    }
+
+    /// Add a playlist (like m3u) to the playlist.
+    fn playlist_add_playlist(&mut self, playlist_path: &Path) -> Result<()> {
+        let sources = self.playlist_get_file_sources(playlist_path)?;
 
         self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
             PlaylistAddTrack::new_vec(
@@ -325,7 +452,8 @@ impl Model {
         Ok(())
     }
 
-    fn playlist_get_dir_entries(path: &Path) -> Vec<String> {
+    /// Get the direct children of the directory at `path`, in the order they are shown in the library.
+    pub fn playlist_get_dir_entries(path: &Path) -> Vec<String> {
         // use the same function as the tree order gets generated in, so that we add in a expected order
         let vec = library_dir_tree(path, ScanDepth::Limited(1));
         vec.children
@@ -369,7 +497,10 @@ impl Model {
             self.playlist_add_playlist(path)?;
             return Ok(());
         }
-        let source = if path.starts_with("http") {
+        let path_str = path.to_string_lossy();
+        let source = if ytdlp::is_video_url(&path_str) {
+            PlaylistTrackSource::VideoUrl(path_str.to_string())
+        } else if path.starts_with("http") {
             PlaylistTrackSource::Url(path.to_string_lossy().to_string())
         } else {
             PlaylistTrackSource::Path(path.to_string_lossy().to_string())
@@ -409,8 +540,9 @@ impl Model {
             .player
             .random_album_min_quantity
             .get();
-        let vec = self.playlist_get_random_album_tracks(playlist_select_random_album_quantity);
-        self.playlist_add_all_from_db(&vec);
+        self.playlist_add_random(PlaylistAddRandom::Album(
+            playlist_select_random_album_quantity,
+        ));
     }
 
     /// Add random tracks from the database to the playlist
@@ -422,18 +554,58 @@ impl Model {
             .player
             .random_track_quantity
             .get();
-        let vec = self.playlist_get_random_tracks(playlist_select_random_track_quantity);
-        self.playlist_add_all_from_db(&vec);
+        self.playlist_add_random(PlaylistAddRandom::Tracks(
+            playlist_select_random_track_quantity,
+        ));
+    }
+
+    /// Add random never-played tracks from the database to the playlist
+    pub fn playlist_add_random_unplayed(&mut self) {
+        let minutes = self
+            .config_server
+            .read()
+            .settings
+            .player
+            .random_unplayed_minutes
+            .get();
+        self.playlist_add_random(PlaylistAddRandom::Unplayed(Duration::from_secs(
+            u64::from(minutes) * 60,
+        )));
+    }
+
+    /// Add tracks chosen by the server's auto-DJ from the play history to the playlist
+    pub fn playlist_add_auto_dj(&mut self) {
+        if !self.server_capabilities.supports(features::AUTO_DJ) {
+            self.mount_error_popup(anyhow!("The auto-DJ is not supported by the server"));
+            return;
+        }
+
+        let tracks = self.config_server.read().settings.auto_dj.tracks.get();
+        self.playlist_add_random(PlaylistAddRandom::AutoDj(tracks));
+    }
+
+    /// Let the server sample the tracks from its database, if supported.
+    fn playlist_add_random(&mut self, kind: PlaylistAddRandom) {
+        if !self.server_capabilities.supports(features::RANDOM_SAMPLE) {
+            self.mount_error_popup(anyhow!(
+                "Adding random tracks is not supported by the server"
+            ));
+            return;
+        }
+
+        self.command(TuiCmd::Playlist(PlaylistCmd::AddRandom(kind)));
     }
 
     /// Handle when a playlist has added a track
     pub fn handle_playlist_add(&mut self, items: PlaylistAddTrackInfo) -> Result<()> {
         // piggyback off-of the server side implementation for now by re-parsing everything.
         self.playback.playlist.add_tracks(
-            PlaylistAddTrack {
-                at_index: items.at_index,
-                tracks: vec![items.trackid],
-            },
+            PlaylistAddTrack::new_single(items.at_index, items.trackid).with_infos(vec![
+                PlaylistTrackInfo {
+                    title: items.title,
+                    duration: Some(items.duration).filter(|v| !v.is_zero()),
+                },
+            ]),
             &self.podcast.db_podcast,
         )?;
 
@@ -465,7 +637,12 @@ impl Model {
             LoopMode::tryfrom_discriminant(as_u8).context("Failed to get LoopMode from u8")?;
         self.playback.playlist.set_loop_mode(loop_mode);
         self.config_server.write().settings.player.loop_mode = loop_mode;
-        self.playlist_update_title();
+        if self.playlist_start_base.is_some() {
+            // the start times depend on the order
+            self.playlist_sync();
+        } else {
+            self.playlist_update_title();
+        }
         // Force a redraw as stream updates are not part of the "tick" event and so cant send "Msg"
         // but need a redraw because ofthe title change
         self.force_redraw();
@@ -515,6 +692,38 @@ impl Model {
         Ok(())
     }
 
+    /// Handle when a track in the playlist failed to play, or played again after having failed
+    pub fn handle_playlist_track_error(
+        &mut self,
+        track_error: PlaylistTrackErrorInfo,
+    ) -> Result<()> {
+        let popup = track_error.error.clone().map(|reason| {
+            let name = self
+                .playback
+                .playlist
+                .tracks()
+                .get(usize::try_from(track_error.at_index).unwrap_or(usize::MAX))
+                .map(|track| {
+                    track
+                        .title()
+                        .map_or_else(|| track.id_str().into_owned(), ToString::to_string)
+                })
+                .unwrap_or_default();
+            anyhow!("Failed to play \"{name}\": {reason}")
+        });
+
+        self.playback
+            .playlist
+            .handle_grpc_track_error(track_error)?;
+        self.playlist_sync();
+
+        if let Some(popup) = popup {
+            self.mount_error_popup(popup);
+        }
+
+        Ok(())
+    }
+
     /// Handle setting the current track index in the TUI playlist and selecting the proper list item
     ///
     /// Note: currently this function is called twice per track change, once for `UpdateEvents::TrackChanged` and once for `run_playback::GetProgress`
@@ -553,6 +762,7 @@ impl Model {
 
     fn playlist_sync_podcasts(&mut self) {
         let mut table: TableBuilder = TableBuilder::default();
+        let start_times = self.playlist_start_times();
 
         for (idx, track) in self.playback.playlist.tracks().iter().enumerate() {
             if idx > 0 {
@@ -572,6 +782,9 @@ impl Model {
             {
                 title = format!("[D] {title}");
             }
+            if let Some(error) = track.error() {
+                title = format!("{title} ({error})");
+            }
             if Some(idx) == self.playback.playlist.current_track_index() {
                 title = format!(
                     "{}{title}",
@@ -584,12 +797,17 @@ impl Model {
                         .current_track_symbol
                 );
             }
-            table
-                .add_col(TextSpan::new(duration_str.as_str()))
-                .add_col(TextSpan::new(title).bold());
+            table.add_col(TextSpan::new(duration_str.as_str()));
+            if let Some(start_times) = &start_times {
+                table.add_col(TextSpan::new(start_times[idx].as_str()));
+            }
+            table.add_col(error_color(TextSpan::new(title).bold(), track));
         }
         if self.playback.playlist.is_empty() {
             table.add_col(TextSpan::from("0"));
+            if start_times.is_some() {
+                table.add_col(TextSpan::from(""));
+            }
             table.add_col(TextSpan::from("empty playlist"));
         }
 
@@ -612,6 +830,7 @@ impl Model {
         }
 
         let mut table: TableBuilder = TableBuilder::default();
+        let start_times = self.playlist_start_times();
 
         for (idx, track) in self.playback.playlist.tracks().iter().enumerate() {
             if idx > 0 {
@@ -624,13 +843,33 @@ impl Model {
                 "[--:--]".to_string()
             };
 
-            let mut title: Cow<'_, str> = track.title().map_or_else(|| track.id_str(), Into::into);
+            // classical movements are more recognizable by their work and movement than their title
+            let mut title: Cow<'_, str> = match track.as_track().and_then(TrackData::movement_title)
+            {
+                Some(movement_title) => movement_title.into(),
+                // a radio stream has no title, show the station and the song it plays instead of the url
+                None => match track.as_radio() {
+                    Some(radio) => self
+                        .radio
+                        .row_title(
+                            radio.url(),
+                            Some(idx) == self.playback.playlist.current_track_index(),
+                        )
+                        .into(),
+                    None => track.title().map_or_else(|| track.id_str(), Into::into),
+                },
+            };
 
             let artist = track.artist().unwrap_or(UNKNOWN_ARTIST);
-            let album = track
-                .as_track()
-                .and_then(|v| v.album())
-                .unwrap_or(UNKNOWN_ALBUM);
+            let label = track.path().and_then(|path| self.track_label(path));
+            let quality = track.path().and_then(|path| self.track_quality(path));
+            // a failed track shows why instead of the album, to have it visible without extra steps
+            let album = track.error().unwrap_or_else(|| {
+                track
+                    .as_track()
+                    .and_then(|v| v.album())
+                    .unwrap_or(UNKNOWN_ALBUM)
+            });
 
             // TODO: is there maybe a better option to do this on-demand instead of the whole playlist; like on draw-time?
             if Some(idx) == self.playback.playlist.current_track_index() {
@@ -647,14 +886,25 @@ impl Model {
                 .into();
             }
 
+            table.add_col(TextSpan::new(duration_str.as_str()));
+            if let Some(start_times) = &start_times {
+                table.add_col(TextSpan::new(start_times[idx].as_str()));
+            }
             table
-                .add_col(TextSpan::new(duration_str.as_str()))
+                .add_col(quality_span(quality))
                 .add_col(TextSpan::new(artist).fg(tuirealm::ratatui::style::Color::LightYellow))
-                .add_col(TextSpan::new(title).bold())
-                .add_col(TextSpan::new(album));
+                .add_col(error_color(
+                    with_label(TextSpan::new(title).bold(), label),
+                    track,
+                ))
+                .add_col(error_color(TextSpan::new(album), track));
         }
         if self.playback.playlist.is_empty() {
             table.add_col(TextSpan::from("0"));
+            if start_times.is_some() {
+                table.add_col(TextSpan::from(""));
+            }
+            table.add_col(TextSpan::from(""));
             table.add_col(TextSpan::from("empty playlist"));
             table.add_col(TextSpan::from(""));
             table.add_col(TextSpan::from(""));
@@ -672,6 +922,52 @@ impl Model {
         self.playlist_update_title();
     }
 
+    /// Get the formatted wall-clock time each track starts playing at, if enabled.
+    fn playlist_start_times(&mut self) -> Option<Vec<String>> {
+        if !self.config_tui.read().settings.playlist.show_start_time {
+            self.playlist_start_base = None;
+            return None;
+        }
+
+        let now = Local::now();
+        let position = self.playback.current_track_pos();
+        self.playlist_start_base = Some(playlist_start_base(now, position));
+
+        let times = self
+            .playback
+            .playlist
+            .start_offsets(position)
+            .into_iter()
+            .map(|offset| {
+                offset
+                    .and_then(|v| TimeDelta::from_std(v).ok())
+                    .and_then(|v| now.checked_add_signed(v))
+                    .map(|start| {
+                        if start.date_naive() == now.date_naive() {
+                            start.format("%H:%M").to_string()
+                        } else {
+                            start.format("%a %H:%M").to_string()
+                        }
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        Some(times)
+    }
+
+    /// Refresh the start times, if they drifted because of pausing or seeking.
+    pub fn playlist_update_start_times(&mut self) {
+        let Some(base) = self.playlist_start_base else {
+            return;
+        };
+
+        let base_now = playlist_start_base(Local::now(), self.playback.current_track_pos());
+        if (base_now - base).abs() >= START_TIMES_MAX_DRIFT {
+            self.playlist_sync();
+        }
+    }
+
     /// Delete a track at `index` from the playlist
     pub fn playlist_delete_item(&mut self, index: usize) {
         if self.playback.playlist.is_empty() || index >= self.playback.playlist.len() {
@@ -749,6 +1045,71 @@ impl Model {
         self.command(TuiCmd::Playlist(PlaylistCmd::RemoveDeletedItems));
     }
 
+    /// Get all playlist entries that are at or inside `path`, with their index.
+    pub fn playlist_entries_in(&self, path: &Path) -> Vec<(u64, PlaylistTrackSource)> {
+        self.playback
+            .playlist
+            .tracks()
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| track.path().is_some_and(|v| v.starts_with(path)))
+            .map(|(idx, track)| (u64::try_from(idx).unwrap(), track.as_track_source()))
+            .collect()
+    }
+
+    /// Re-add entries previously gotten from [`playlist_entries_in`](Self::playlist_entries_in) at their old index.
+    pub fn playlist_restore_entries(&mut self, mut entries: Vec<(u64, PlaylistTrackSource)>) {
+        // ascending, so that every entry ends up at its old index again
+        entries.sort_by_key(|(idx, _)| *idx);
+
+        for (idx, source) in entries {
+            self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+                PlaylistAddTrack::new_single(idx, source),
+            )));
+        }
+    }
+
+    /// Replace all playlist entries that are at or inside `from` with the same entry at `to`, keeping their position.
+    pub fn playlist_update_library_move(&mut self, from: &Path, to: &Path) {
+        let moved: Vec<(usize, PlaylistTrackSource, PathBuf)> = self
+            .playback
+            .playlist
+            .tracks()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, track)| {
+                let rest = track.path()?.strip_prefix(from).ok()?;
+                let new_path = if rest.as_os_str().is_empty() {
+                    to.to_path_buf()
+                } else {
+                    to.join(rest)
+                };
+                Some((idx, track.as_track_source(), new_path))
+            })
+            .collect();
+
+        if moved.is_empty() {
+            return;
+        }
+
+        for (idx, old_source, new_path) in moved {
+            let idx = u64::try_from(idx).unwrap();
+            // add before removing, so that the current track index keeps pointing at the same track
+            self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+                PlaylistAddTrack::new_single(
+                    idx,
+                    PlaylistTrackSource::Path(new_path.to_string_lossy().to_string()),
+                ),
+            )));
+            self.command(TuiCmd::Playlist(PlaylistCmd::RemoveTrack(
+                PlaylistRemoveTrackIndexed::new_single(idx + 1, old_source),
+            )));
+        }
+
+        // in case the new path could not be added (like a unsupported extension), dont keep the stale entry
+        self.playlist_update_library_delete();
+    }
+
     pub fn playlist_update_title(&mut self) {
         let duration = self
             .playback
@@ -799,7 +1160,11 @@ impl Model {
     }
 
     pub fn playlist_update_search(&mut self, input: &str) {
-        let filtered_music = Model::update_search(self.playback.playlist.tracks(), input);
+        let filtered_music = Model::update_search(
+            self.playback.playlist.tracks(),
+            input,
+            self.general_search_mode,
+        );
         self.general_search_update_show(Model::build_table(filtered_music));
     }
 
@@ -827,56 +1192,6 @@ impl Model {
         Some(val)
     }
 
-    pub fn playlist_get_random_tracks(&mut self, quantity: u32) -> Vec<TrackRead> {
-        let mut result = Vec::with_capacity(usize::try_from(quantity).unwrap_or_default());
-        let all_tracks =
-            track_ops::get_all_tracks(&self.db.get_connection(), track_ops::RowOrdering::IdAsc);
-        if let Ok(vec) = all_tracks {
-            let mut i = 0;
-            loop {
-                if let Some(record) = vec.choose(&mut rand::rng()) {
-                    let path = record.as_pathbuf();
-                    if filetype_supported(&path) {
-                        result.push(record.clone());
-                        i += 1;
-                        if i > quantity - 1 {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-        result
-    }
-
-    pub fn playlist_get_random_album_tracks(&mut self, quantity: u32) -> Vec<TrackRead> {
-        let mut result = Vec::with_capacity(usize::try_from(quantity).unwrap_or_default());
-        let all_albums =
-            album_ops::get_all_albums(&self.db.get_connection(), album_ops::RowOrdering::IdAsc);
-        if let Ok(vec) = all_albums {
-            loop {
-                if let Some(v) = vec.choose(&mut rand::rng()) {
-                    let all_tracks_in_album = track_ops::get_tracks_from_album(
-                        &self.db.get_connection(),
-                        &v.title,
-                        &v.artist_display,
-                        track_ops::RowOrdering::IdAsc,
-                    );
-                    if let Ok(vec2) = all_tracks_in_album {
-                        if vec2.len() < quantity as usize {
-                            continue;
-                        }
-
-                        result.extend(vec2);
-
-                        break;
-                    }
-                }
-            }
-        }
-        result
-    }
-
     /// Save the current playlist as m3u to the given path
     pub fn playlist_save_m3u_before(&mut self, path: PathBuf) -> Result<()> {
         if path.exists() {
@@ -888,10 +1203,17 @@ impl Model {
         self.playlist_save_m3u(path)
     }
 
-    /// Save the current playlist as m3u in the given full path.
+    /// Save the current playlist in the given full path, as a shared playlist if it is named like one, otherwise as m3u.
     pub fn playlist_save_m3u(&mut self, path: PathBuf) -> Result<()> {
         // TODO: move this to server?
-        self.playback.playlist.save_m3u(&path)?;
+        if share::is_share_file(&path) {
+            let music_dirs = self.config_server.read().settings.player.music_dirs.clone();
+            self.playback.playlist.save_share(&path, &music_dirs)?;
+        } else {
+            self.playback
+                .playlist
+                .save_m3u(&path, &self.db.get_connection())?;
+        }
 
         self.new_library_reload_and_focus(path);
 
@@ -903,3 +1225,23 @@ impl Model {
         Ok(())
     }
 }
+
+/// The drift after which the start times are refreshed.
+const START_TIMES_MAX_DRIFT: TimeDelta = TimeDelta::seconds(30);
+
+/// Get the time the current track started at, if it had been played without pause from `position`.
+/// Color `span` as a error, if `track` failed to play.
+fn error_color(span: TextSpan, track: &Track) -> TextSpan {
+    if track.error().is_some() {
+        span.fg(tuirealm::ratatui::style::Color::LightRed)
+    } else {
+        span
+    }
+}
+
+fn playlist_start_base(now: DateTime<Local>, position: Duration) -> DateTime<Local> {
+    TimeDelta::from_std(position)
+        .ok()
+        .and_then(|v| now.checked_sub_signed(v))
+        .unwrap_or(now)
+}
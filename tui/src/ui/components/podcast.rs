@@ -1,3 +1,4 @@
+use std::thread;
 use std::time::Duration;
 
 use anyhow::{Result, anyhow, bail};
@@ -6,7 +7,10 @@ use reqwest::ClientBuilder;
 use sanitize_filename::{Options, sanitize_with_options};
 use serde_json::Value;
 use termusiclib::config::SharedTuiSettings;
+use termusiclib::file_ops;
+use termusiclib::new_database::download_ops;
 use termusiclib::podcast::{EpData, PodcastFeed, PodcastNoId, download_list};
+use termusiclib::ratelimit::LIMITER;
 use tokio::runtime::Handle;
 use tui_realm_stdlib::List;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
@@ -18,8 +22,9 @@ use tuirealm::{
 };
 
 use crate::ui::Model;
+use crate::ui::components::SearchMatcher;
 use crate::ui::ids::Id;
-use crate::ui::model::UserEvent;
+use crate::ui::model::{TermusicLayout, UserEvent};
 use crate::ui::msg::{GSMsg, Msg, PCMsg};
 
 #[derive(MockComponent)]
@@ -361,7 +366,7 @@ impl Model {
     pub fn podcast_search_itunes(&self, search_str: &str) {
         let encoded: String = utf8_percent_encode(search_str, NON_ALPHANUMERIC).to_string();
         let url =
-            format!("https://itunes.apple.com/search?media=podcast&entity=podcast&term={encoded}",);
+            format!("https://itunes.apple.com/search?media=podcast&entity=podcast&term={encoded}");
         let agent = ClientBuilder::new()
             .connect_timeout(Duration::from_secs(5))
             .build()
@@ -380,7 +385,7 @@ impl Model {
         // this will work for now as the tui loop is a async function, and this function is called on the same thread
         Handle::current().spawn(async move {
             let request: Result<reqwest::Response> = loop {
-                let response = agent.get(&url).send().await;
+                let response = LIMITER.send_once(agent.get(&url)).await;
                 if let Ok(resp) = response {
                     break Ok(resp);
                 }
@@ -787,6 +792,19 @@ impl Model {
             );
         }
 
+        // deduplicate in the background, as hashing a large episode takes a moment
+        let db = self.db.clone();
+        thread::spawn(move || {
+            if let Err(err) =
+                download_ops::dedup_download(&db.get_connection(), &ep_data.url, &file_path)
+            {
+                warn!(
+                    "Deduplicating the episode download {} failed: {err:#}",
+                    file_path.display()
+                );
+            }
+        });
+
         let podcasts = self.podcast.db_podcast.get_podcasts()?;
         self.podcast.podcasts = podcasts;
 
@@ -801,6 +819,7 @@ impl Model {
         if self.podcast.podcasts.is_empty() {
             return Ok(());
         }
+        let to_trash = self.config_tui.read().settings.behavior.delete_to_trash;
         let podcast_selected = self
             .podcast
             .podcasts
@@ -815,8 +834,8 @@ impl Model {
         if ep.path.is_some() {
             let title = &ep.title;
             let path = ep.path.clone().unwrap();
-            match std::fs::remove_file(path) {
-                Ok(()) => {
+            match file_ops::delete_path(&path, to_trash) {
+                Ok(_) => {
                     self.podcast.db_podcast.remove_file(ep.id).map_err(|e| {
                         anyhow!(format!("Could not remove file from db: {title} {e}"))
                     })?;
@@ -843,6 +862,7 @@ impl Model {
     }
 
     pub fn podcast_delete_files(&mut self, pod_index: usize) -> Result<()> {
+        let to_trash = self.config_tui.read().settings.behavior.delete_to_trash;
         let mut eps_to_remove = Vec::new();
         let mut success = true;
         {
@@ -854,8 +874,8 @@ impl Model {
 
             for ep in &mut podcast_selected.episodes {
                 if let Some(path) = ep.path.as_ref() {
-                    match std::fs::remove_file(path) {
-                        Ok(()) => {
+                    match file_ops::delete_path(path, to_trash) {
+                        Ok(_) => {
                             eps_to_remove.push(ep.id);
                             ep.path = None;
                         }
@@ -955,6 +975,21 @@ impl Model {
         Ok(())
     }
 
+    /// Get the artwork of the selected feed while the podcast view is shown, which is shown instead of the cover of the
+    /// current track there.
+    pub fn podcast_feed_artwork_url(&self) -> Option<String> {
+        if self.layout != TermusicLayout::Podcast {
+            return None;
+        }
+
+        self.podcast
+            .podcasts
+            .get(self.podcast.podcasts_index)?
+            .image_url
+            .clone()
+            .filter(|v| !v.is_empty())
+    }
+
     pub fn podcast_get_album_photo_by_url(&self, url: &str) -> Option<String> {
         if self.podcast.podcasts.is_empty() {
             return None;
@@ -988,7 +1023,7 @@ impl Model {
     pub fn podcast_update_search_episode(&mut self, input: &str) {
         let mut table: TableBuilder = TableBuilder::default();
         let mut idx: usize = 0;
-        let search = format!("*{}*", input.to_lowercase());
+        let search = SearchMatcher::new(self.general_search_mode, input);
         let mut db_tracks = vec![];
         // Get all episodes
         for podcast in &self.podcast.podcasts {
@@ -1003,7 +1038,7 @@ impl Model {
             table.add_col(TextSpan::from(""));
         } else {
             for record in db_tracks {
-                if wildmatch::WildMatch::new(&search).matches(&record.title.to_lowercase()) {
+                if search.matches(&record.title) {
                     if idx > 0 {
                         table.add_row();
                     }
@@ -1023,7 +1058,7 @@ impl Model {
     pub fn podcast_update_search_podcast(&mut self, input: &str) {
         let mut table: TableBuilder = TableBuilder::default();
         let mut idx: usize = 0;
-        let search = format!("*{}*", input.to_lowercase());
+        let search = SearchMatcher::new(self.general_search_mode, input);
         // Get all episodes
         let db_tracks = &self.podcast.podcasts;
 
@@ -1033,7 +1068,7 @@ impl Model {
             table.add_col(TextSpan::from(""));
         } else {
             for record in db_tracks {
-                if wildmatch::WildMatch::new(&search).matches(&record.title.to_lowercase()) {
+                if search.matches(&record.title) {
                     if idx > 0 {
                         table.add_row();
                     }
@@ -0,0 +1,64 @@
+use termusiclib::config::SharedTuiSettings;
+use tuirealm::{Component, Event, MockComponent, props::Alignment};
+
+use super::{YNConfirm, YNConfirmStyle};
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{ActionConfirmMsg, ConfirmAction, Msg};
+
+/// Confirmation popup for actions that can be configured to ask first, see [`ConfirmAction`].
+#[derive(MockComponent)]
+pub struct ActionConfirmPopup {
+    component: YNConfirm,
+    action: ConfirmAction,
+}
+
+impl ActionConfirmPopup {
+    pub fn new(config: SharedTuiSettings, action: ConfirmAction) -> Self {
+        let title = match action {
+            ConfirmAction::ClearPlaylist => " Clear the whole playlist? ",
+            ConfirmAction::OverwriteTags => " Write the changed tags to the file? ",
+        };
+        let component = YNConfirm::new_with_cb(config, title, |config| YNConfirmStyle {
+            foreground_color: config.settings.theme.important_popup_foreground(),
+            background_color: config.settings.theme.important_popup_background(),
+            border_color: config.settings.theme.important_popup_border(),
+            title_alignment: Alignment::Center,
+        });
+
+        Self { component, action }
+    }
+}
+
+impl Component<Msg, UserEvent> for ActionConfirmPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::ActionConfirm(ActionConfirmMsg::Ok(self.action)),
+            Msg::ActionConfirm(ActionConfirmMsg::Cancel),
+        )
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`ActionConfirmPopup`] for `action`.
+    pub fn mount_action_confirm(&mut self, action: ConfirmAction) {
+        assert!(
+            self.app
+                .remount(
+                    Id::ActionConfirmPopup,
+                    Box::new(ActionConfirmPopup::new(self.config_tui.clone(), action)),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.app.active(&Id::ActionConfirmPopup).is_ok());
+    }
+
+    /// Unmount the [`ActionConfirmPopup`], if mounted.
+    pub fn umount_action_confirm(&mut self) {
+        if self.app.mounted(&Id::ActionConfirmPopup) {
+            let _ = self.app.umount(&Id::ActionConfirmPopup);
+        }
+    }
+}
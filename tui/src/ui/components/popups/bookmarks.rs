@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use termusiclib::config::{SharedTuiSettings, TuiOverlay};
+use termusiclib::new_database::bookmark_ops::Bookmark;
+use termusiclib::track::DurationFmtShort;
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType, TableBuilder, TextSpan},
+};
+
+use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{BookmarksMsg, Msg};
+
+/// Component for a "Bookmark name:" popup, pre-filled with the position to bookmark.
+#[derive(MockComponent)]
+pub struct BookmarkAddPopup {
+    component: Input,
+    location: String,
+    position: Duration,
+}
+
+impl BookmarkAddPopup {
+    pub fn new(config: &TuiOverlay, location: String, position: Duration) -> Self {
+        let settings = &config.settings;
+
+        Self {
+            component: Input::default()
+                .foreground(settings.theme.fallback_foreground())
+                .background(settings.theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(DurationFmtShort(position).to_string())
+                .title(" Bookmark name: (Enter to confirm) ", Alignment::Left),
+            location,
+            position,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for BookmarkAddPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Bookmarks(BookmarksMsg::AddCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => {
+                Some(Msg::Bookmarks(BookmarksMsg::AddOk(
+                    self.location.clone(),
+                    self.position,
+                    input_string,
+                )))
+            }
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+/// Component for a popup listing the bookmarks of a playlist entry, to jump to or delete them.
+#[derive(MockComponent)]
+pub struct BookmarksPopup {
+    component: Table,
+    /// The index of the entry in the playlist
+    index: usize,
+    bookmarks: Vec<Bookmark>,
+    config: SharedTuiSettings,
+}
+
+impl BookmarksPopup {
+    pub fn new(config: SharedTuiSettings, index: usize, bookmarks: Vec<Bookmark>) -> Self {
+        let mut table = TableBuilder::default();
+        for (idx, bookmark) in bookmarks.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(DurationFmtShort(bookmark.position).to_string()).bold())
+                .add_col(TextSpan::new(&bookmark.name));
+        }
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            let keys = &config.settings.keys;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    format!(
+                        " Bookmarks (Enter: jump to it, {}: delete, Esc: close) ",
+                        keys.playlist_keys.delete
+                    ),
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .column_spacing(2)
+                .widths(&[15, 85])
+                .table(table.build())
+        };
+
+        Self {
+            component,
+            index,
+            bookmarks,
+            config,
+        }
+    }
+
+    /// Get the bookmark of the selected row.
+    fn selected(&self) -> Option<&Bookmark> {
+        let State::One(StateValue::Usize(row)) = self.component.state() else {
+            return None;
+        };
+
+        self.bookmarks.get(row)
+    }
+}
+
+impl Component<Msg, UserEvent> for BookmarksPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let position = self.selected()?.position;
+                return Some(Msg::Bookmarks(BookmarksMsg::Jump(self.index, position)));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.delete.get() => {
+                let id = self.selected()?.id;
+                return Some(Msg::Bookmarks(BookmarksMsg::Delete(self.index, id)));
+            }
+
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::Bookmarks(BookmarksMsg::Close));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::Bookmarks(BookmarksMsg::Close));
+            }
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`BookmarkAddPopup`] to name a bookmark at `position` of `location`.
+    pub fn mount_bookmark_add(&mut self, location: String, position: Duration) -> Result<()> {
+        self.app.remount(
+            Id::BookmarkAddPopup,
+            Box::new(BookmarkAddPopup::new(
+                &self.config_tui.read(),
+                location,
+                position,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::BookmarkAddPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`BookmarkAddPopup`], if mounted.
+    pub fn umount_bookmark_add(&mut self) {
+        if self.app.mounted(&Id::BookmarkAddPopup) {
+            let _ = self.app.umount(&Id::BookmarkAddPopup);
+        }
+    }
+
+    /// Mount / Remount the [`BookmarksPopup`] listing `bookmarks` of the playlist entry at `index`.
+    pub fn mount_bookmarks(&mut self, index: usize, bookmarks: Vec<Bookmark>) -> Result<()> {
+        self.app.remount(
+            Id::BookmarksPopup,
+            Box::new(BookmarksPopup::new(
+                self.config_tui.clone(),
+                index,
+                bookmarks,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::BookmarksPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`BookmarksPopup`], if mounted.
+    pub fn umount_bookmarks(&mut self) {
+        if self.app.mounted(&Id::BookmarksPopup) {
+            let _ = self.app.umount(&Id::BookmarksPopup);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, TableBuilder, TextSpan},
+};
+
+use crate::ui::ids::Id;
+use crate::ui::model::credits::Credit;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{CreditsMsg, Msg};
+
+/// Component for a popup listing the credited artists of a track, to jump to one of them.
+#[derive(MockComponent)]
+pub struct CreditsPopup {
+    component: Table,
+    credits: Vec<Credit>,
+    config: SharedTuiSettings,
+}
+
+impl CreditsPopup {
+    pub fn new(config: SharedTuiSettings, credits: Vec<Credit>) -> Self {
+        let mut table = TableBuilder::default();
+        for (idx, credit) in credits.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(credit.role.as_str()).bold())
+                .add_col(TextSpan::new(&credit.name));
+        }
+        if credits.is_empty() {
+            table
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new("No credits in the tags of this track."));
+        }
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    " Credits (Enter: show in database, Esc: close) ",
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .column_spacing(2)
+                .widths(&[20, 80])
+                .table(table.build())
+        };
+
+        Self {
+            component,
+            credits,
+            config,
+        }
+    }
+
+    /// Get the selected credit.
+    fn selected(&self) -> Option<&Credit> {
+        match self.component.state() {
+            State::One(StateValue::Usize(row)) => self.credits.get(row),
+            _ => None,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for CreditsPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let credit = self.selected()?.clone();
+                return Some(Msg::Credits(CreditsMsg::Jump(credit)));
+            }
+
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::Credits(CreditsMsg::Close));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::Credits(CreditsMsg::Close));
+            }
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`CreditsPopup`] listing `credits`.
+    pub fn mount_credits(&mut self, credits: Vec<Credit>) -> Result<()> {
+        self.app.remount(
+            Id::CreditsPopup,
+            Box::new(CreditsPopup::new(self.config_tui.clone(), credits)),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::CreditsPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`CreditsPopup`], if mounted.
+    pub fn umount_credits(&mut self) {
+        if self.app.mounted(&Id::CreditsPopup) {
+            let _ = self.app.umount(&Id::CreditsPopup);
+        }
+    }
+}
@@ -26,15 +26,17 @@ pub struct DeleteConfirmRadioPopup {
 
 impl DeleteConfirmRadioPopup {
     pub fn new(config: SharedTuiSettings, on_confirm: Msg, on_cancel: Msg) -> Self {
-        let component =
-            YNConfirm::new_with_cb(config, " Are sure you want to delete? ", |config| {
-                YNConfirmStyle {
-                    foreground_color: config.settings.theme.important_popup_foreground(),
-                    background_color: config.settings.theme.important_popup_background(),
-                    border_color: config.settings.theme.important_popup_border(),
-                    title_alignment: Alignment::Left,
-                }
-            });
+        let title = if config.read().settings.behavior.delete_to_trash {
+            " Are you sure you want to move it to the trash? "
+        } else {
+            " Are you sure you want to delete it permanently? "
+        };
+        let component = YNConfirm::new_with_cb(config, title, |config| YNConfirmStyle {
+            foreground_color: config.settings.theme.important_popup_foreground(),
+            background_color: config.settings.theme.important_popup_background(),
+            border_color: config.settings.theme.important_popup_border(),
+            title_alignment: Alignment::Left,
+        });
 
         Self {
             component,
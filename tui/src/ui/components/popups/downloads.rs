@@ -0,0 +1,196 @@
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::downloads::{DownloadAction, DownloadInfo, DownloadStatus};
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, TableBuilder, TextSpan},
+};
+
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{DownloadsMsg, Msg};
+
+/// Component for a popup listing the download queue of the server, to pause, cancel or retry downloads.
+#[derive(MockComponent)]
+pub struct DownloadsPopup {
+    component: Table,
+    downloads: Vec<DownloadInfo>,
+    config: SharedTuiSettings,
+}
+
+impl DownloadsPopup {
+    pub fn new(config: SharedTuiSettings, downloads: Vec<DownloadInfo>, selected: usize) -> Self {
+        let mut table = TableBuilder::default();
+        for (idx, download) in downloads.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let detail = match (&download.error, download.attempts) {
+                (Some(err), attempts) => format!("attempt {attempts}: {err}"),
+                (None, 0 | 1) => String::new(),
+                (None, attempts) => format!("attempt {attempts}"),
+            };
+            table
+                .add_col(TextSpan::new(download.status.name()).bold())
+                .add_col(TextSpan::new(format!("{:>3}%", download.progress)))
+                .add_col(TextSpan::new(&download.title))
+                .add_col(TextSpan::new(detail));
+        }
+        if downloads.is_empty() {
+            table
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new("No downloads in the queue."))
+                .add_col(TextSpan::new(""));
+        }
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    " Downloads (p: pause/resume, x: cancel, r: retry, Esc: close) ",
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .column_spacing(2)
+                .widths(&[12, 5, 45, 38])
+                .table(table.build())
+                .selected_line(selected.min(downloads.len().saturating_sub(1)))
+        };
+
+        Self {
+            component,
+            downloads,
+            config,
+        }
+    }
+
+    /// Get the selected download.
+    fn selected(&self) -> Option<&DownloadInfo> {
+        match self.component.state() {
+            State::One(StateValue::Usize(row)) => self.downloads.get(row),
+            _ => None,
+        }
+    }
+
+    /// Get the message to pause or resume the selected download.
+    fn toggle_pause(&self) -> Option<Msg> {
+        let download = self.selected()?;
+        let action = match download.status {
+            DownloadStatus::Paused => DownloadAction::Resume,
+            DownloadStatus::Pending | DownloadStatus::Active => DownloadAction::Pause,
+            _ => return None,
+        };
+
+        Some(Msg::Downloads(DownloadsMsg::Control(download.id, action)))
+    }
+}
+
+impl Component<Msg, UserEvent> for DownloadsPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('p'),
+                modifiers: KeyModifiers::NONE,
+            }) => return self.toggle_pause(),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('x'),
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let id = self.selected()?.id;
+                return Some(Msg::Downloads(DownloadsMsg::Control(
+                    id,
+                    DownloadAction::Cancel,
+                )));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('r'),
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let id = self.selected()?.id;
+                return Some(Msg::Downloads(DownloadsMsg::Control(
+                    id,
+                    DownloadAction::Retry,
+                )));
+            }
+
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::Downloads(DownloadsMsg::Close));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::Downloads(DownloadsMsg::Close));
+            }
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`DownloadsPopup`] listing the download queue of the server, keeping the selection.
+    pub fn mount_downloads(&mut self) -> Result<()> {
+        let selected = match self.app.state(&Id::DownloadsPopup) {
+            Ok(State::One(StateValue::Usize(row))) => row,
+            _ => 0,
+        };
+        self.app.remount(
+            Id::DownloadsPopup,
+            Box::new(DownloadsPopup::new(
+                self.config_tui.clone(),
+                self.server_downloads.clone(),
+                selected,
+            )),
+            Vec::new(),
+        )?;
+        // dont take the focus from a error popup shown above
+        if !self.app.mounted(&Id::ErrorPopup) {
+            self.app.active(&Id::DownloadsPopup)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unmount the [`DownloadsPopup`], if mounted.
+    pub fn umount_downloads(&mut self) {
+        if self.app.mounted(&Id::DownloadsPopup) {
+            let _ = self.app.umount(&Id::DownloadsPopup);
+        }
+    }
+}
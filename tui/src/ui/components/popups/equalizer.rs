@@ -0,0 +1,265 @@
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::config::v2::server::equalizer::{
+    BAND_FREQUENCIES, BANDS, EqualizerSettings, GAIN_MAX_DB,
+};
+use tuirealm::command::{Cmd, CmdResult};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+use tuirealm::props::{AttrValue, Attribute, BorderType, Props, Style, TextModifiers};
+use tuirealm::ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+};
+use tuirealm::{Component, Event, Frame, MockComponent, State, StateValue};
+
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{EqualizerMsg, Msg};
+
+/// The amount of rows of a bar above and below the 0 dB line.
+const BAR_ROWS: i8 = 4;
+
+/// The width of a band, including the space to the next band.
+const BAND_WIDTH: usize = 5;
+
+/// Component for a popup showing the equalizer bands as bars, to adjust them.
+pub struct EqualizerPopup {
+    props: Props,
+    settings: EqualizerSettings,
+    /// The index of the selected band
+    band: usize,
+    config: SharedTuiSettings,
+}
+
+impl EqualizerPopup {
+    pub fn new(config: SharedTuiSettings, settings: EqualizerSettings, band: usize) -> Self {
+        Self {
+            props: Props::default(),
+            settings,
+            band: band.min(BANDS - 1),
+            config,
+        }
+    }
+}
+
+/// Get the label of the band with the center frequency `freq`, like `125` or `2k`.
+fn band_label(freq: u32) -> String {
+    if freq >= 1000 {
+        format!("{}k", freq / 1000)
+    } else {
+        freq.to_string()
+    }
+}
+
+/// Get the bar cell of a band with `gain` at `row`, which is the offset from the 0 dB line, positive upwards.
+fn bar_cell(gain: f32, row: i8) -> &'static str {
+    #[allow(clippy::cast_possible_truncation)] // the gains are clamped to GAIN_MAX_DB
+    let filled = (gain / GAIN_MAX_DB * f32::from(BAR_ROWS)).round() as i8;
+
+    match row {
+        0 => "────",
+        row if (row > 0 && row <= filled) || (row < 0 && row >= filled) => "████",
+        _ => "    ",
+    }
+}
+
+impl MockComponent for EqualizerPopup {
+    fn view(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let config = self.config.read();
+        let theme = &config.settings.theme;
+        let style = Style::default()
+            .fg(theme.fallback_foreground())
+            .bg(theme.fallback_background());
+        let bar_style = if self.settings.enabled {
+            style
+        } else {
+            style.add_modifier(TextModifiers::DIM)
+        };
+        let selected_style = bar_style.fg(theme.fallback_highlight());
+        let band_style = |band: usize| {
+            if band == self.band {
+                selected_style
+            } else {
+                bar_style
+            }
+        };
+
+        let state = if self.settings.enabled { "on" } else { "off" };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(style.fg(theme.fallback_border()))
+            .style(style)
+            .title(format!(
+                " Equalizer: {} ({state}) ",
+                self.settings.preset.name()
+            ));
+
+        let gains = self.settings.gains();
+        let mut lines = Vec::with_capacity(usize::from(BAR_ROWS.unsigned_abs()) * 2 + 4);
+        lines.push(Line::from(
+            gains
+                .iter()
+                .enumerate()
+                .map(|(band, gain)| {
+                    Span::styled(format!("{gain:^+BAND_WIDTH$.0}"), band_style(band))
+                })
+                .collect::<Vec<_>>(),
+        ));
+        for row in (-BAR_ROWS..=BAR_ROWS).rev() {
+            lines.push(Line::from(
+                gains
+                    .iter()
+                    .enumerate()
+                    .map(|(band, gain)| {
+                        Span::styled(format!("{} ", bar_cell(*gain, row)), band_style(band))
+                    })
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        lines.push(Line::from(
+            BAND_FREQUENCIES
+                .iter()
+                .enumerate()
+                .map(|(band, freq)| {
+                    Span::styled(
+                        format!("{:^width$}", band_label(*freq), width = BAND_WIDTH),
+                        band_style(band),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+        lines.push(Line::default());
+        lines.push(Line::styled(
+            "←→: band, ↑↓: gain, p: preset, e: on/off, Esc: close",
+            style,
+        ));
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::One(StateValue::Usize(self.band))
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, UserEvent> for EqualizerPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let msg = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('p'),
+                modifiers: KeyModifiers::NONE,
+            }) => EqualizerMsg::NextPreset,
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('e'),
+                modifiers: KeyModifiers::NONE,
+            }) => EqualizerMsg::ToggleEnabled,
+
+            Event::Keyboard(key) if key == keys.quit.get() => EqualizerMsg::Close,
+            Event::Keyboard(key) if key == keys.escape.get() => EqualizerMsg::Close,
+
+            Event::Keyboard(key) if key == keys.navigation_keys.left.get() => {
+                EqualizerMsg::SelectBand(self.band.saturating_sub(1))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.right.get() => {
+                EqualizerMsg::SelectBand((self.band + 1).min(BANDS - 1))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => EqualizerMsg::Adjust(1),
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                EqualizerMsg::Adjust(-1)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Left,
+                modifiers: KeyModifiers::NONE,
+            }) => EqualizerMsg::SelectBand(self.band.saturating_sub(1)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right,
+                modifiers: KeyModifiers::NONE,
+            }) => EqualizerMsg::SelectBand((self.band + 1).min(BANDS - 1)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => EqualizerMsg::Adjust(1),
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => EqualizerMsg::Adjust(-1),
+            _ => return None,
+        };
+
+        Some(Msg::Equalizer(msg))
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`EqualizerPopup`] with the current equalizer and band.
+    pub fn mount_equalizer(&mut self) -> Result<()> {
+        self.app.remount(
+            Id::EqualizerPopup,
+            Box::new(EqualizerPopup::new(
+                self.config_tui.clone(),
+                self.equalizer.clone(),
+                self.equalizer_band,
+            )),
+            Vec::new(),
+        )?;
+        // dont take the focus from a error popup shown above
+        if !self.app.mounted(&Id::ErrorPopup) {
+            self.app.active(&Id::EqualizerPopup)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unmount the [`EqualizerPopup`], if mounted.
+    pub fn umount_equalizer(&mut self) {
+        if self.app.mounted(&Id::EqualizerPopup) {
+            let _ = self.app.umount(&Id::EqualizerPopup);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{band_label, bar_cell};
+
+    #[test]
+    fn should_draw_bars() {
+        let column: Vec<&str> = (-4..=4).rev().map(|row| bar_cell(6.0, row)).collect();
+        assert_eq!(
+            column,
+            [
+                "    ",
+                "    ",
+                "████",
+                "████",
+                "────",
+                "    ",
+                "    ",
+                "    ",
+                "    "
+            ]
+        );
+        assert_eq!(bar_cell(-12.0, -4), "████");
+        assert_eq!(bar_cell(-1.0, -1), "    ");
+
+        assert_eq!(band_label(62), "62");
+        assert_eq!(band_label(16_000), "16k");
+    }
+}
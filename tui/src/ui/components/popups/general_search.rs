@@ -2,23 +2,134 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Result, anyhow, bail};
 use termusiclib::config::{SharedTuiSettings, TuiOverlay};
-use termusiclib::track::MediaTypes;
+use termusiclib::search_history::{HistoryKind, HistoryList};
 use tui_realm_stdlib::Table;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::event::{Key, KeyEvent, KeyModifiers};
 use tuirealm::props::{Alignment, BorderType, Borders, InputType, Style, TableBuilder, TextSpan};
 use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
+use wildmatch::WildMatch;
 
+use super::search_history::HistoryRecall;
 use crate::ui::Model;
 use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
 use crate::ui::ids::Id;
 use crate::ui::model::UserEvent;
-use crate::ui::msg::{GSMsg, Msg};
+use crate::ui::msg::{GSMsg, Msg, SearchHistoryMsg};
 
 #[derive(MockComponent)]
 pub struct GSInputPopup {
     component: Input,
     source: Source,
+    mode: SearchMode,
+    recall: HistoryRecall,
+}
+
+/// How the input of the search popup is matched against the searched values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// All characters of the input have to appear in order, but not necessarily next to each other
+    #[default]
+    Fuzzy,
+    /// The input has to appear as-is
+    Exact,
+    /// The input is a glob pattern, supporting `*` and `?`
+    Glob,
+    /// The input is a regular expression
+    Regex,
+}
+
+impl SearchMode {
+    /// Get the mode to switch to from this one.
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            Self::Fuzzy => Self::Exact,
+            Self::Exact => Self::Glob,
+            Self::Glob => Self::Regex,
+            Self::Regex => Self::Fuzzy,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Fuzzy => "fuzzy",
+            Self::Exact => "exact",
+            Self::Glob => "glob",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+/// A search input prepared for a [`SearchMode`], all modes match case-insensitively.
+///
+/// An empty input matches everything.
+pub enum SearchMatcher {
+    /// The lowercase characters of the input, without whitespace
+    Fuzzy(Vec<char>),
+    /// The lowercase input
+    Exact(String),
+    Glob(WildMatch),
+    /// [`None`] if the input is not a valid regex, which does not match anything
+    Regex(Option<regex::Regex>),
+}
+
+impl SearchMatcher {
+    #[must_use]
+    pub fn new(mode: SearchMode, input: &str) -> Self {
+        match mode {
+            SearchMode::Fuzzy => Self::Fuzzy(
+                input
+                    .chars()
+                    .filter(|ch| !ch.is_whitespace())
+                    .flat_map(char::to_lowercase)
+                    .collect(),
+            ),
+            SearchMode::Exact => Self::Exact(input.to_lowercase()),
+            SearchMode::Glob => Self::Glob(WildMatch::new(&format!("*{}*", input.to_lowercase()))),
+            SearchMode::Regex => Self::Regex(
+                regex::RegexBuilder::new(input)
+                    .case_insensitive(true)
+                    .build()
+                    .ok(),
+            ),
+        }
+    }
+
+    /// Check if `value` matches the search input.
+    #[must_use]
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Fuzzy(needle) => {
+                let mut needle = needle.iter().peekable();
+                for ch in value.chars().flat_map(char::to_lowercase) {
+                    if needle.peek() == Some(&&ch) {
+                        needle.next();
+                    }
+                }
+                needle.peek().is_none()
+            }
+            Self::Exact(needle) => value.to_lowercase().contains(needle.as_str()),
+            Self::Glob(pattern) => pattern.matches(&value.to_lowercase()),
+            Self::Regex(regex) => regex.as_ref().is_some_and(|regex| regex.is_match(value)),
+        }
+    }
+}
+
+/// Get the title for the search input of `source`, showing the active `mode`.
+fn input_title(source: &Source, mode: SearchMode) -> String {
+    let what = match source {
+        Source::Episode => " all episodes from all feeds",
+        Source::Global => " tracks, playlists and episodes",
+        Source::Lyric => " lines of the current lyric",
+        _ => "",
+    };
+
+    format!(
+        " Search for{what} [{}]: (Ctrl+t: switch mode, Ctrl+r: history) ",
+        mode.as_str()
+    )
 }
 
 /// Get a [`Input`] component with the common style applied.
@@ -37,23 +148,47 @@ fn common_input_comp(config: &TuiOverlay, title: &str) -> Input {
 }
 
 impl GSInputPopup {
-    pub fn new(source: Source, config: &TuiOverlay) -> Self {
-        match source {
-            Source::Episode => Self {
-                component: common_input_comp(
-                    config,
-                    " Search for all episodes from all feeds: (support * and ?) ",
-                )
+    pub fn new(
+        source: Source,
+        config: &TuiOverlay,
+        mode: SearchMode,
+        history: &HistoryList,
+    ) -> Self {
+        Self {
+            component: common_input_comp(config, &input_title(&source, mode))
                 .input_type(InputType::Text),
-                source,
-            },
-            _ => Self {
-                component: common_input_comp(config, " Search for: (support * and ?) ")
-                    .input_type(InputType::Text),
-                source,
-            },
+            source,
+            mode,
+            recall: HistoryRecall::new(history),
         }
     }
+
+    /// Put the recalled `query` into the input and get the message to search with it.
+    fn recall(&mut self, query: Option<String>) -> Option<Msg> {
+        let query = query?;
+        self.attr(Attribute::Value, AttrValue::String(query.clone()));
+
+        Some(Msg::GeneralSearch(self.source.update_msg(query)))
+    }
+
+    /// Switch to the next [`SearchMode`] and get the message to re-run the search with it.
+    fn switch_mode(&mut self) -> Msg {
+        self.mode = self.mode.next();
+        self.attr(
+            Attribute::Title,
+            AttrValue::Title((input_title(&self.source, self.mode), Alignment::Left)),
+        );
+
+        let input = match self.state() {
+            State::One(StateValue::String(input)) => input,
+            _ => String::new(),
+        };
+
+        Msg::GeneralSearch(GSMsg::PopupModeChanged(
+            self.mode,
+            Box::new(self.source.update_msg(input)),
+        ))
+    }
 }
 
 impl Component<Msg, UserEvent> for GSInputPopup {
@@ -91,28 +226,37 @@ impl Component<Msg, UserEvent> for GSInputPopup {
             Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
                 return Some(Msg::GeneralSearch(GSMsg::InputBlur));
             }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('t'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => return Some(self.switch_mode()),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => {
+                return Some(Msg::SearchHistory(SearchHistoryMsg::PopupShow(
+                    HistoryKind::Library,
+                )));
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                let current = match self.state() {
+                    State::One(StateValue::String(input)) => input,
+                    _ => String::new(),
+                };
+                let query = self.recall.older(&current).map(ToString::to_string);
+                return self.recall(query);
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => {
+                let query = self.recall.newer().map(ToString::to_string);
+                return self.recall(query);
+            }
             _ => CmdResult::None,
         };
         match cmd_result {
             CmdResult::Changed(State::One(StateValue::String(input_string))) => {
-                match &self.source {
-                    Source::Library(path) => Some(Msg::GeneralSearch(GSMsg::PopupUpdateLibrary(
-                        input_string,
-                        path.clone(),
-                    ))),
-                    Source::Playlist => {
-                        Some(Msg::GeneralSearch(GSMsg::PopupUpdatePlaylist(input_string)))
-                    }
-                    Source::Database => {
-                        Some(Msg::GeneralSearch(GSMsg::PopupUpdateDatabase(input_string)))
-                    }
-                    Source::Episode => {
-                        Some(Msg::GeneralSearch(GSMsg::PopupUpdateEpisode(input_string)))
-                    }
-                    Source::Podcast => {
-                        Some(Msg::GeneralSearch(GSMsg::PopupUpdatePodcast(input_string)))
-                    }
-                }
+                Some(Msg::GeneralSearch(self.source.update_msg(input_string)))
             }
             CmdResult::Submit(_) => Some(Msg::GeneralSearch(GSMsg::InputBlur)),
 
@@ -136,6 +280,26 @@ pub enum Source {
     Database,
     Episode,
     Podcast,
+    /// Tracks, playlists and podcast episodes at once
+    Global,
+    /// The lines of the synced lyric of the current track
+    Lyric,
+}
+
+impl Source {
+    /// Get the message to update the results of this source with `input`.
+    #[must_use]
+    pub fn update_msg(&self, input: String) -> GSMsg {
+        match self {
+            Self::Library(path) => GSMsg::PopupUpdateLibrary(input, path.clone()),
+            Self::Playlist => GSMsg::PopupUpdatePlaylist(input),
+            Self::Database => GSMsg::PopupUpdateDatabase(input),
+            Self::Episode => GSMsg::PopupUpdateEpisode(input),
+            Self::Podcast => GSMsg::PopupUpdatePodcast(input),
+            Self::Global => GSMsg::PopupUpdateGlobal(input),
+            Self::Lyric => GSMsg::PopupUpdateLyric(input),
+        }
+    }
 }
 
 /// Get a [`Table`] component with the common style applied.
@@ -188,6 +352,7 @@ impl GSTablePopup {
         );
 
         let title_podcast = " Results: (Enter: locate) ";
+        let title_lyric = " Results: (Enter: seek to line) ";
         let component = match source {
             Source::Library(_) => common_table_comp(&config_r, title_library)
                 .headers(["idx", "File name"])
@@ -205,6 +370,13 @@ impl GSTablePopup {
             Source::Podcast => common_table_comp(&config_r, title_podcast.to_string())
                 .headers(["idx", "Podcast Title"])
                 .widths(&[5, 95]),
+            // same actions as the library
+            Source::Global => common_table_comp(&config_r, title_library)
+                .headers(["idx", "Name", "Artist / Location"])
+                .widths(&[5, 60, 35]),
+            Source::Lyric => common_table_comp(&config_r, title_lyric.to_string())
+                .headers(["Time", "Line"])
+                .widths(&[10, 90]),
         };
 
         drop(config_r);
@@ -279,7 +451,10 @@ impl Component<Msg, UserEvent> for GSTablePopup {
                     Source::Episode => {
                         return Some(Msg::GeneralSearch(GSMsg::PopupCloseEpisodeAddPlaylist));
                     }
-                    Source::Podcast => return None,
+                    Source::Podcast | Source::Lyric => return None,
+                    Source::Global => {
+                        return Some(Msg::GeneralSearch(GSMsg::PopupCloseGlobalAddPlaylist));
+                    }
                 }
             }
             Event::Keyboard(KeyEvent {
@@ -298,6 +473,12 @@ impl Component<Msg, UserEvent> for GSTablePopup {
                 Source::Podcast => {
                     return Some(Msg::GeneralSearch(GSMsg::PopupCloseOkPodcastLocate));
                 }
+                Source::Global => {
+                    return Some(Msg::GeneralSearch(GSMsg::PopupCloseOkGlobalLocate));
+                }
+                Source::Lyric => {
+                    return Some(Msg::GeneralSearch(GSMsg::PopupCloseOkLyricSeek));
+                }
             },
             _ => CmdResult::None,
         };
@@ -357,17 +538,7 @@ impl Model {
         {
             let file_name = &file_name_text_span.content;
             for (idx, item) in self.playback.playlist.tracks().iter().enumerate() {
-                // NOTE: i dont know if this should apply to anything other than "track_data"
-                let lower_matched = match item.inner() {
-                    MediaTypes::Track(track_data) => {
-                        track_data.path().to_string_lossy() == file_name.as_str()
-                    }
-                    MediaTypes::Radio(radio_track_data) => radio_track_data.url() == file_name,
-                    MediaTypes::Podcast(podcast_track_data) => {
-                        podcast_track_data.url() == file_name
-                    }
-                };
-                if lower_matched {
+                if item.source().location() == file_name.as_str() {
                     index = idx;
                     matched = true;
                 }
@@ -391,17 +562,7 @@ impl Model {
         {
             let file_name = &file_name_text_span.content;
             for (idx, item) in self.playback.playlist.tracks().iter().enumerate() {
-                // NOTE: i dont know if this should apply to anything other than "track_data"
-                let lower_matched = match item.inner() {
-                    MediaTypes::Track(track_data) => {
-                        track_data.path().to_string_lossy() == file_name.as_str()
-                    }
-                    MediaTypes::Radio(radio_track_data) => radio_track_data.url() == file_name,
-                    MediaTypes::Podcast(podcast_track_data) => {
-                        podcast_track_data.url() == file_name
-                    }
-                };
-                if lower_matched {
+                if item.source().location() == file_name.as_str() {
                     index = idx;
                     matched = true;
                 }
@@ -461,3 +622,40 @@ impl Model {
         bail!("column cannot find in general search")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SearchMatcher, SearchMode};
+
+    #[test]
+    fn should_match_by_mode() {
+        let fuzzy = SearchMatcher::new(SearchMode::Fuzzy, "pnk flyd");
+        assert!(fuzzy.matches("Pink Floyd"));
+        assert!(!fuzzy.matches("Floyd Pink"));
+
+        let exact = SearchMatcher::new(SearchMode::Exact, "k f");
+        assert!(exact.matches("Pink Floyd"));
+        assert!(!exact.matches("Pink-Floyd"));
+
+        let glob = SearchMatcher::new(SearchMode::Glob, "p?nk*d");
+        assert!(glob.matches("The Pink Floyd"));
+        assert!(!glob.matches("Punk"));
+
+        let regex = SearchMatcher::new(SearchMode::Regex, "^pink (floyd|panther)$");
+        assert!(regex.matches("Pink Panther"));
+        assert!(!regex.matches("The Pink Panther"));
+
+        // invalid regex should not match anything
+        assert!(!SearchMatcher::new(SearchMode::Regex, "(").matches("("));
+
+        // empty input should match everything
+        for mode in [
+            SearchMode::Fuzzy,
+            SearchMode::Exact,
+            SearchMode::Glob,
+            SearchMode::Regex,
+        ] {
+            assert!(SearchMatcher::new(mode, "").matches("anything"));
+        }
+    }
+}
@@ -47,6 +47,11 @@ impl HelpPopup {
         ];
         let enter = &[&KeyEvent::new(Key::Enter, KeyModifiers::NONE).into()];
         let backspace = &[&KeyEvent::new(Key::Backspace, KeyModifiers::NONE).into()];
+        let label_keys = &[
+            &KeyEvent::new(Key::Char('1'), KeyModifiers::ALT).into(),
+            &KeyEvent::new(Key::Char('6'), KeyModifiers::ALT).into(),
+            &KeyEvent::new(Key::Char('0'), KeyModifiers::ALT).into(),
+        ];
 
         let component = {
             let config = config.read();
@@ -110,6 +115,33 @@ impl HelpPopup {
                         .add_col(Self::key(&[&keys.player_keys.toggle_prefetch]))
                         .add_col(Self::comment("Toggle gapless playback"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.toggle_karaoke]))
+                        .add_col(Self::comment("Toggle karaoke mode"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.toggle_recording]))
+                        .add_col(Self::comment("Start/Stop recording what is playing"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.toggle_album_mode]))
+                        .add_col(Self::comment("Toggle album mode"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.tap_tempo]))
+                        .add_col(Self::comment("Tap the tempo of the beat indicator"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.toggle_normalization]))
+                        .add_col(Self::comment("Toggle loudness normalization"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.toggle_smart_speed]))
+                        .add_col(Self::comment("Toggle shortening long silences in podcasts"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.ab_loop]))
+                        .add_col(Self::comment("Set A-B loop start/end, clear it"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.seek_to_position]))
+                        .add_col(Self::comment("Seek to a timestamp, like 1:23"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.player_keys.rate_track]))
+                        .add_col(Self::comment("Rate the current track, 1-5: stars, 0: remove"))
+                        .add_row()
                         .add_col(Self::key(&[
                             &keys.lyric_keys.adjust_offset_forwards,
                             &keys.lyric_keys.adjust_offset_backwards,
@@ -119,6 +151,12 @@ impl HelpPopup {
                         .add_col(Self::key(&[&keys.lyric_keys.cycle_frames]))
                         .add_col(Self::comment("Switch lyrics if more than 1 available"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.lyric_keys.search]))
+                        .add_col(Self::comment("Search the lyric and seek to a line"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.lyric_keys.toggle_translation]))
+                        .add_col(Self::comment("Show the translation side by side"))
+                        .add_row()
                         .add_col(Self::key(&[
                             &keys.player_keys.next_track,
                             &keys.player_keys.previous_track,
@@ -135,6 +173,46 @@ impl HelpPopup {
                         .add_col(Self::key(&[&keys.select_view_keys.open_config]))
                         .add_col(Self::comment("Open Config Editor(all configuration)"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_global_search]))
+                        .add_col(Self::comment(
+                            "Search tracks, playlists and podcast episodes",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_credits]))
+                        .add_col(Self::comment(
+                            "Show the credits of the playing track, Enter to show one in the database",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_downloads]))
+                        .add_col(Self::comment(
+                            "Show the download queue, p: pause/resume, x: cancel, r: retry",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_radio]))
+                        .add_col(Self::comment(
+                            "Search internet radio stations, empty to list bookmarks, b: bookmark",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_equalizer]))
+                        .add_col(Self::comment(
+                            "Show the equalizer, arrows: adjust bands, p: preset, e: enable",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_output_devices]))
+                        .add_col(Self::comment(
+                            "Choose the output device of the server, Enter: play on it",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.open_statistics]))
+                        .add_col(Self::comment(
+                            "Show the most played artists and tracks and the listening time per week",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.toggle_track_suggestions]))
+                        .add_col(Self::comment(
+                            "Show, focus or hide more from the album, artists and genre of the playing track",
+                        ))
+                        .add_row()
                         .add_col(Self::key(&[&keys.player_keys.save_playlist]))
                         .add_col(Self::comment("Save Playlist to m3u"))
                         .add_row()
@@ -177,10 +255,24 @@ impl HelpPopup {
                         .add_col(Self::comment("Add one/all tracks to playlist"))
                         .add_row()
                         .add_col(Self::key(&[&keys.library_keys.delete]))
-                        .add_col(Self::comment("Delete track or folder"))
+                        .add_col(Self::comment("Move track or folder to the trash"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.undo_delete]))
+                        .add_col(Self::comment("Undo the last delete"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.sync_device]))
+                        .add_col(Self::comment("Sync track or folder to the device"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.fetch_lyrics]))
+                        .add_col(Self::comment("Fetch lyrics for track or folder"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.replace_playlist]))
+                        .add_col(Self::comment(
+                            "Replace playlist with track, folder or playlist",
+                        ))
                         .add_row()
                         .add_col(Self::key(&[&keys.library_keys.youtube_search]))
-                        .add_col(Self::comment("Search or download track from youtube"))
+                        .add_col(Self::comment("Search, stream or download track from youtube"))
                         .add_row()
                         .add_col(Self::key(&[&keys.library_keys.open_tag_editor]))
                         .add_col(Self::comment("Open tag editor for tag and lyric download"))
@@ -191,6 +283,12 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Yank and Paste files"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.copy]))
+                        .add_col(Self::comment("Yank to copy on Paste"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.rename]))
+                        .add_col(Self::comment("Rename track or folder"))
+                        .add_row()
                         .add_col(Self::key(enter))
                         .add_col(Self::comment("Open sub directory as root"))
                         .add_row()
@@ -241,6 +339,32 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Select random tracks/albums to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.add_random_unplayed]))
+                        .add_col(Self::comment("Select random unplayed tracks to playlist"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.sync_device]))
+                        .add_col(Self::comment("Sync the whole playlist to the device"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.fetch_lyrics]))
+                        .add_col(Self::comment("Fetch lyrics for the whole playlist"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.set_offsets]))
+                        .add_col(Self::comment("Set start / end offset of a track"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.add_auto_dj]))
+                        .add_col(Self::comment("Add tracks chosen by the auto-DJ"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.add_bookmark]))
+                        .add_col(Self::comment("Bookmark the position of the playing track"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.show_bookmarks]))
+                        .add_col(Self::comment("List / jump to the bookmarks of a track"))
+                        .add_row()
+                        .add_col(Self::key(label_keys))
+                        .add_col(Self::comment(
+                            "Set color label 1(red) ... 6(magenta) / remove it",
+                        ))
+                        .add_row()
                         .add_col(TextSpan::new("Database").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[
@@ -249,6 +373,31 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Add one/all track(s) to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.database_keys.replace_playlist]))
+                        .add_col(Self::comment("Replace playlist with all tracks"))
+                        .add_row()
+                        .add_col(Self::key(label_keys))
+                        .add_col(Self::comment("Tracks: set color label 1 ... 6 / remove it"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.database_keys.mark_reviewed,
+                            &keys.database_keys.mark_all_reviewed,
+                        ]))
+                        .add_col(Self::comment("Inbox: mark one/all track(s) reviewed"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.database_keys.locate]))
+                        .add_col(Self::comment("Tracks: show in library to move / delete"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.database_keys.verify_library]))
+                        .add_col(Self::comment("Verify all tracks, see \"Corrupted\""))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.database_keys.new_smart_playlist,
+                            &keys.database_keys.edit_smart_playlist,
+                            &keys.library_keys.delete,
+                        ]))
+                        .add_col(Self::comment("Smart Playlists: new / edit / delete"))
+                        .add_row()
                         // TODO: add search key to database
                         .add_col(Self::key(&[&keys.library_keys.search]))
                         .add_col(Self::comment("Search in database"))
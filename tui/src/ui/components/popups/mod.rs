@@ -1,19 +1,43 @@
 #![allow(clippy::module_name_repetitions)]
 
+mod action_confirm;
+mod bookmarks;
+mod credits;
 mod deleteconfirm;
+mod downloads;
+mod equalizer;
 mod error;
 pub mod general_search;
 mod help;
 mod message;
 mod mock_yn_confirm;
+mod output_devices;
 mod podcast;
+mod queue_replace;
 mod quit;
+mod radio;
+mod rename;
 mod saveplaylist;
+pub mod search_history;
+mod seek_to_position;
+mod smart_playlist;
+mod statistics;
+mod track_offsets;
 pub mod youtube_search;
 
+#[allow(unused_imports)]
+pub use action_confirm::ActionConfirmPopup;
+#[allow(unused_imports)]
+pub use bookmarks::{BookmarkAddPopup, BookmarksPopup};
+#[allow(unused_imports)]
+pub use credits::CreditsPopup;
 #[allow(unused_imports)]
 pub use deleteconfirm::{DeleteConfirmInputPopup, DeleteConfirmRadioPopup};
 #[allow(unused_imports)]
+pub use downloads::DownloadsPopup;
+#[allow(unused_imports)]
+pub use equalizer::EqualizerPopup;
+#[allow(unused_imports)]
 pub use error::ErrorPopup;
 #[allow(unused_imports)]
 pub use help::HelpPopup;
@@ -21,8 +45,24 @@ pub use help::HelpPopup;
 pub use message::MessagePopup;
 pub use mock_yn_confirm::{YNConfirm, YNConfirmStyle};
 #[allow(unused_imports)]
+pub use output_devices::OutputDevicesPopup;
+#[allow(unused_imports)]
 pub use podcast::{FeedDeleteConfirmRadioPopup, PodcastAddPopup, PodcastSearchTablePopup};
 #[allow(unused_imports)]
+pub use queue_replace::QueueReplacePopup;
+#[allow(unused_imports)]
 pub use quit::QuitPopup;
 #[allow(unused_imports)]
+pub use radio::{RadioSearchInputPopup, RadioStationsPopup};
+#[allow(unused_imports)]
+pub use rename::LibraryRenamePopup;
+#[allow(unused_imports)]
 pub use saveplaylist::{SavePlaylistConfirmPopup, SavePlaylistPopup};
+#[allow(unused_imports)]
+pub use seek_to_position::SeekToPositionPopup;
+#[allow(unused_imports)]
+pub use smart_playlist::{SmartPlaylistEditPopup, SmartPlaylistField};
+#[allow(unused_imports)]
+pub use statistics::{RatingPopup, StatisticsPopup};
+#[allow(unused_imports)]
+pub use track_offsets::TrackOffsetsPopup;
@@ -0,0 +1,160 @@
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::player::OutputDevices;
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, TableBuilder, TextSpan},
+};
+
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, OutputDevicesMsg};
+
+/// Component for a popup listing the output devices of the server, to pick the one to play on.
+#[derive(MockComponent)]
+pub struct OutputDevicesPopup {
+    component: Table,
+    /// The device names of the rows, empty for the default device of the system
+    rows: Vec<String>,
+    config: SharedTuiSettings,
+}
+
+impl OutputDevicesPopup {
+    pub fn new(config: SharedTuiSettings, devices: &OutputDevices) -> Self {
+        let rows: Vec<String> = std::iter::once(String::new())
+            .chain(devices.names.iter().cloned())
+            .collect();
+
+        let mut table = TableBuilder::default();
+        for (idx, name) in rows.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let marker = if *name == devices.configured { "*" } else { "" };
+            let label = if name.is_empty() {
+                format!("System default ({})", devices.current)
+            } else if *name == devices.current {
+                format!("{name} (playing)")
+            } else {
+                name.clone()
+            };
+            table
+                .add_col(TextSpan::new(marker).bold())
+                .add_col(TextSpan::new(label));
+        }
+        let selected = rows
+            .iter()
+            .position(|v| *v == devices.configured)
+            .unwrap_or_default();
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    " Output Devices (Enter: play on it, Esc: close) ",
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .column_spacing(1)
+                .widths(&[2, 98])
+                .table(table.build())
+                .selected_line(selected)
+        };
+
+        Self {
+            component,
+            rows,
+            config,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for OutputDevicesPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let State::One(StateValue::Usize(row)) = self.component.state() else {
+                    return None;
+                };
+                let name = self.rows.get(row)?.clone();
+                return Some(Msg::OutputDevices(OutputDevicesMsg::Select(name)));
+            }
+
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::OutputDevices(OutputDevicesMsg::Close));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::OutputDevices(OutputDevicesMsg::Close));
+            }
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`OutputDevicesPopup`] listing the known output devices of the server.
+    pub fn mount_output_devices(&mut self) -> Result<()> {
+        self.app.remount(
+            Id::OutputDevicesPopup,
+            Box::new(OutputDevicesPopup::new(
+                self.config_tui.clone(),
+                &self.output_devices,
+            )),
+            Vec::new(),
+        )?;
+        // dont take the focus from a error popup shown above
+        if !self.app.mounted(&Id::ErrorPopup) {
+            self.app.active(&Id::OutputDevicesPopup)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unmount the [`OutputDevicesPopup`], if mounted.
+    pub fn umount_output_devices(&mut self) {
+        if self.app.mounted(&Id::OutputDevicesPopup) {
+            let _ = self.app.umount(&Id::OutputDevicesPopup);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt::Write as _;
+
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::track::DurationFmtShort;
+use tui_realm_stdlib::Radio;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction},
+    event::{Key, KeyEvent},
+    props::{Alignment, BorderType, Borders},
+};
+
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, QueueDurations, QueueReplaceMsg};
+
+/// Component for the preview before replacing the playlist, letting the user choose to replace, append or cancel.
+#[derive(MockComponent)]
+pub struct QueueReplacePopup {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl QueueReplacePopup {
+    pub fn new(
+        config: SharedTuiSettings,
+        added: usize,
+        removed: usize,
+        durations: QueueDurations,
+    ) -> Self {
+        let mut title = format!(
+            " Replace playlist: {added} added, {removed} removed, {} total",
+            DurationFmtShort(durations.total)
+        );
+        if durations.unknown > 0 {
+            let _ = write!(title, " (+{} unknown)", durations.unknown);
+        }
+        title.push(' ');
+
+        let component = {
+            let config = config.read();
+            Radio::default()
+                .foreground(config.settings.theme.important_popup_foreground())
+                .background(config.settings.theme.important_popup_background())
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.important_popup_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(title, Alignment::Left)
+                .rewind(true)
+                .choices(["Replace", "Append", "Cancel"])
+                .value(0)
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for QueueReplacePopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::QueueReplace(QueueReplaceMsg::Cancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+
+            Event::Keyboard(key) if key == keys.navigation_keys.left.get() => {
+                self.perform(Cmd::Move(Direction::Left))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.right.get() => {
+                self.perform(Cmd::Move(Direction::Right))
+            }
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::QueueReplace(QueueReplaceMsg::Cancel));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::QueueReplace(QueueReplaceMsg::Cancel));
+            }
+
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => return None,
+        };
+
+        match cmd_result {
+            // ordering is 0 = Replace, 1 = Append, 2 = Cancel
+            CmdResult::Submit(State::One(StateValue::Usize(0))) => {
+                Some(Msg::QueueReplace(QueueReplaceMsg::Confirm))
+            }
+            CmdResult::Submit(State::One(StateValue::Usize(1))) => {
+                Some(Msg::QueueReplace(QueueReplaceMsg::Append))
+            }
+            CmdResult::Submit(_) => Some(Msg::QueueReplace(QueueReplaceMsg::Cancel)),
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`QueueReplacePopup`].
+    pub fn mount_queue_replace(
+        &mut self,
+        added: usize,
+        removed: usize,
+        durations: QueueDurations,
+    ) -> Result<()> {
+        self.app.remount(
+            Id::QueueReplacePopup,
+            Box::new(QueueReplacePopup::new(
+                self.config_tui.clone(),
+                added,
+                removed,
+                durations,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::QueueReplacePopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`QueueReplacePopup`], if mounted.
+    pub fn umount_queue_replace(&mut self) {
+        if self.app.mounted(&Id::QueueReplacePopup) {
+            let _ = self.app.umount(&Id::QueueReplacePopup);
+        }
+    }
+}
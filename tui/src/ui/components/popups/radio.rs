@@ -0,0 +1,288 @@
+use anyhow::Result;
+use termusiclib::config::{SharedTuiSettings, TuiOverlay};
+use termusiclib::radio_browser::{Station, StationBookmarks};
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType, TableBuilder, TextSpan},
+};
+
+use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, RadioMsg};
+
+/// Component for the input searching internet radio stations by name.
+#[derive(MockComponent)]
+pub struct RadioSearchInputPopup {
+    component: Input,
+}
+
+impl RadioSearchInputPopup {
+    pub fn new(config: &TuiOverlay) -> Self {
+        let settings = &config.settings;
+        Self {
+            component: Input::default()
+                .background(settings.theme.fallback_background())
+                .foreground(settings.theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .title(
+                    " Search radio stations (empty for bookmarks, Enter to confirm): ",
+                    Alignment::Left,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for RadioSearchInputPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Radio(RadioMsg::InputCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                let query = match self.component.state() {
+                    State::One(StateValue::String(input)) => input,
+                    _ => String::new(),
+                };
+                return Some(Msg::Radio(RadioMsg::InputCloseOk(query)));
+            }
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+/// Component for a popup listing radio stations, to play or bookmark one.
+#[derive(MockComponent)]
+pub struct RadioStationsPopup {
+    component: Table,
+    config: SharedTuiSettings,
+}
+
+impl RadioStationsPopup {
+    pub fn new(
+        config: SharedTuiSettings,
+        stations: &[Station],
+        bookmarks: &StationBookmarks,
+        searching: bool,
+        selected: usize,
+    ) -> Self {
+        let mut table = TableBuilder::default();
+        for (idx, station) in stations.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let marker = if bookmarks.contains(&station.url) {
+                "[B]"
+            } else {
+                ""
+            };
+            let format = match station.bitrate {
+                0 => station.codec.clone(),
+                bitrate => format!("{} {bitrate}k", station.codec),
+            };
+            table
+                .add_col(TextSpan::new(marker))
+                .add_col(TextSpan::new(&station.name).bold())
+                .add_col(TextSpan::new(&station.country))
+                .add_col(TextSpan::new(format))
+                .add_col(TextSpan::new(&station.tags));
+        }
+        if stations.is_empty() {
+            let text = if searching {
+                "Loading..."
+            } else {
+                "No stations found."
+            };
+            table
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new(text))
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new(""));
+        }
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    " Radio stations (Enter: play, b: bookmark, Esc: close) ",
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(["", "Name", "Country", "Format", "Tags"])
+                .column_spacing(2)
+                .widths(&[4, 40, 14, 12, 30])
+                .table(table.build())
+                .selected_line(selected.min(stations.len().saturating_sub(1)))
+        };
+
+        Self { component, config }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        match self.component.state() {
+            State::One(StateValue::Usize(row)) => Some(row),
+            _ => None,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for RadioStationsPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::Radio(RadioMsg::Play(self.selected()?))),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('b'),
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::Radio(RadioMsg::ToggleBookmark(self.selected()?))),
+
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::Radio(RadioMsg::StationsCloseCancel));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::Radio(RadioMsg::StationsCloseCancel));
+            }
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    pub fn mount_radio_search_input(&mut self) -> Result<()> {
+        self.app.remount(
+            Id::RadioSearchInputPopup,
+            Box::new(RadioSearchInputPopup::new(&self.config_tui.read())),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::RadioSearchInputPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`RadioSearchInputPopup`], if mounted.
+    pub fn umount_radio_search_input(&mut self) {
+        if self.app.mounted(&Id::RadioSearchInputPopup) {
+            let _ = self.app.umount(&Id::RadioSearchInputPopup);
+        }
+    }
+
+    /// Mount / Remount the [`RadioStationsPopup`] listing the current stations, keeping the selection.
+    pub fn mount_radio_stations(&mut self) -> Result<()> {
+        let selected = match self.app.state(&Id::RadioStationsPopup) {
+            Ok(State::One(StateValue::Usize(row))) => row,
+            _ => 0,
+        };
+        self.app.remount(
+            Id::RadioStationsPopup,
+            Box::new(RadioStationsPopup::new(
+                self.config_tui.clone(),
+                self.radio.stations(),
+                self.radio.bookmarks(),
+                self.radio.is_searching(),
+                selected,
+            )),
+            Vec::new(),
+        )?;
+        // dont take the focus from a error popup shown above
+        if !self.app.mounted(&Id::ErrorPopup) {
+            self.app.active(&Id::RadioStationsPopup)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unmount the [`RadioStationsPopup`], if mounted.
+    pub fn umount_radio_stations(&mut self) {
+        if self.app.mounted(&Id::RadioStationsPopup) {
+            let _ = self.app.umount(&Id::RadioStationsPopup);
+        }
+    }
+}
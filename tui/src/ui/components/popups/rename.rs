@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use termusiclib::config::TuiOverlay;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType},
+};
+
+use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{LIMsg, Msg};
+
+/// Component for a "Rename to:" popup, pre-filled with the current file name.
+#[derive(MockComponent)]
+pub struct LibraryRenamePopup {
+    component: Input,
+    path: PathBuf,
+}
+
+impl LibraryRenamePopup {
+    pub fn new(config: &TuiOverlay, path: PathBuf) -> Self {
+        let settings = &config.settings;
+        let file_name = path
+            .file_name()
+            .map(|v| v.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Self {
+            component: Input::default()
+                .foreground(settings.theme.fallback_foreground())
+                .background(settings.theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(file_name)
+                .title(" Rename to: (Enter to confirm) ", Alignment::Left),
+            path,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for LibraryRenamePopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Library(LIMsg::RenameCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => Some(Msg::Library(
+                LIMsg::RenameOk(self.path.clone(), input_string),
+            )),
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`LibraryRenamePopup`] for `path`.
+    pub fn mount_library_rename(&mut self, path: PathBuf) -> Result<()> {
+        self.app.remount(
+            Id::LibraryRenamePopup,
+            Box::new(LibraryRenamePopup::new(&self.config_tui.read(), path)),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::LibraryRenamePopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`LibraryRenamePopup`], if mounted.
+    pub fn umount_library_rename(&mut self) {
+        if self.app.mounted(&Id::LibraryRenamePopup) {
+            let _ = self.app.umount(&Id::LibraryRenamePopup);
+        }
+    }
+}
@@ -21,7 +21,10 @@ use super::{YNConfirm, YNConfirmStyle};
 use crate::ui::model::{Model, UserEvent};
 use crate::ui::msg::{Msg, SavePlaylistMsg};
 use crate::ui::{components::vendored::tui_realm_stdlib_input::Input, model::TxToMain};
-use crate::ui::{ids::Id, msg::SPUpdateData};
+use crate::ui::{
+    ids::Id,
+    msg::{SPUpdateData, SaveFormat},
+};
 
 #[derive(MockComponent)]
 pub struct SavePlaylistPopup {
@@ -29,6 +32,7 @@ pub struct SavePlaylistPopup {
     tx_to_main: TxToMain,
 
     directory: PathBuf,
+    format: SaveFormat,
 }
 
 impl SavePlaylistPopup {
@@ -45,9 +49,13 @@ impl SavePlaylistPopup {
                 )
                 // .invalid_style(Style::default().fg(Color::Red))
                 .input_type(InputType::Text)
-                .title(" Save Playlist as: (Enter to confirm) ", Alignment::Left),
+                .title(
+                    " Save Playlist as: (Enter to confirm, Tab: m3u / shareable text) ",
+                    Alignment::Left,
+                ),
             tx_to_main,
             directory,
+            format: SaveFormat::default(),
         }
     }
 }
@@ -84,6 +92,10 @@ impl Component<Msg, UserEvent> for SavePlaylistPopup {
                 self.perform(Cmd::Type(ch));
                 self.perform(Cmd::Submit)
             }
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                self.format = self.format.toggle();
+                self.perform(Cmd::Submit)
+            }
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
                 return Some(Msg::SavePlaylist(SavePlaylistMsg::CloseCancel));
             }
@@ -91,7 +103,7 @@ impl Component<Msg, UserEvent> for SavePlaylistPopup {
                 code: Key::Enter, ..
             }) => match self.component.state() {
                 State::One(StateValue::String(mut input_string)) => {
-                    input_string.push_str(".m3u");
+                    input_string.push_str(self.format.suffix());
                     let joined = self.directory.join(input_string);
 
                     return Some(Msg::SavePlaylist(SavePlaylistMsg::CloseOk(joined)));
@@ -108,6 +120,7 @@ impl Component<Msg, UserEvent> for SavePlaylistPopup {
                     .tx_to_main
                     .send(Msg::SavePlaylist(SavePlaylistMsg::Update(SPUpdateData {
                         path: OsString::from(input_string),
+                        format: self.format,
                     })));
 
                 None
@@ -159,6 +172,7 @@ pub struct SavePlaylistFullpath {
 
     directory: PathBuf,
     filename: OsString,
+    format: SaveFormat,
 
     config: SharedTuiSettings,
 }
@@ -169,6 +183,7 @@ impl SavePlaylistFullpath {
         config: &TuiOverlay,
         directory: &Path,
         filename: &OsStr,
+        format: SaveFormat,
     ) -> [TextSpan; 4] {
         let mut path_string = directory.to_string_lossy().to_string();
         // push extra "/" as "Path::to_string()" does not end with a "/"
@@ -185,7 +200,7 @@ impl SavePlaylistFullpath {
                     .theme
                     .get_color_from_theme(ColorTermusic::Cyan))
                 .bold(),
-            TextSpan::new(".m3u").bold(),
+            TextSpan::new(format.suffix()).bold(),
         ]
     }
 
@@ -197,13 +212,19 @@ impl SavePlaylistFullpath {
             Span::default()
                 .foreground(config.settings.theme.fallback_foreground())
                 .background(config.settings.theme.fallback_background())
-                .spans(Self::get_text_spans(&config, &directory, OsStr::new("")))
+                .spans(Self::get_text_spans(
+                    &config,
+                    &directory,
+                    OsStr::new(""),
+                    SaveFormat::default(),
+                ))
         };
 
         Self {
             component,
             directory,
             filename: OsString::new(),
+            format: SaveFormat::default(),
             config,
         }
     }
@@ -225,11 +246,13 @@ impl Component<Msg, UserEvent> for SavePlaylistFullpath {
             ev
         {
             self.filename = update.path;
+            self.format = update.format;
 
             let values = Self::get_text_spans(
                 &self.config.read_recursive(),
                 &self.directory,
                 &self.filename,
+                self.format,
             );
 
             self.attr(
@@ -0,0 +1,356 @@
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::search_history::{HistoryKind, HistoryList, SearchHistory};
+use tui_realm_stdlib::Table;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+use tuirealm::props::{Alignment, BorderType, Borders, TableBuilder, TextSpan};
+use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
+
+use super::general_search::{SearchMatcher, SearchMode};
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{GSMsg, Msg, SearchHistoryMsg};
+
+/// Recall the queries of a history in a input with Up and Down, like in a shell.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryRecall {
+    /// The newest first
+    entries: Vec<String>,
+    /// The recalled entry, [`None`] while the own input is shown
+    pos: Option<usize>,
+    /// The own input, restored once going past the newest entry
+    draft: String,
+}
+
+impl HistoryRecall {
+    #[must_use]
+    pub fn new(history: &HistoryList) -> Self {
+        Self {
+            entries: history.recent().cloned().collect(),
+            pos: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Get the next older entry, `current` is the current input to restore later.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let next = self.pos.map_or(0, |v| v + 1);
+        if next >= self.entries.len() {
+            return None;
+        }
+        if self.pos.is_none() {
+            self.draft = current.to_string();
+        }
+        self.pos = Some(next);
+
+        Some(&self.entries[next])
+    }
+
+    /// Get the next newer entry, or the own input after the newest entry.
+    pub fn newer(&mut self) -> Option<&str> {
+        match self.pos? {
+            0 => {
+                self.pos = None;
+                Some(&self.draft)
+            }
+            v => {
+                self.pos = Some(v - 1);
+                Some(&self.entries[v - 1])
+            }
+        }
+    }
+}
+
+/// A popup listing the history of a search, filtered fuzzily by typing.
+#[derive(MockComponent)]
+pub struct SearchHistoryPopup {
+    component: Table,
+    kind: HistoryKind,
+    /// All queries and whether they are pinned, the pinned first
+    entries: Vec<(String, bool)>,
+    filter: String,
+    /// The indexes into `entries` that match the filter, in the order shown
+    shown: Vec<usize>,
+    config: SharedTuiSettings,
+}
+
+impl SearchHistoryPopup {
+    pub fn new(config: SharedTuiSettings, kind: HistoryKind, history: &HistoryList) -> Self {
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(["", "Query"])
+                .column_spacing(1)
+                .widths(&[3, 97])
+        };
+
+        let entries = history
+            .all()
+            .into_iter()
+            .map(|v| (v.to_string(), history.is_pinned(v)))
+            .collect();
+
+        let mut popup = Self {
+            component,
+            kind,
+            entries,
+            filter: String::new(),
+            shown: Vec::new(),
+            config,
+        };
+        popup.sync();
+
+        popup
+    }
+
+    /// Apply the filter and update the table and title.
+    fn sync(&mut self) {
+        let pin_color = self.config.read().settings.theme.fallback_highlight();
+        let matcher = SearchMatcher::new(SearchMode::Fuzzy, &self.filter);
+        self.shown = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (query, _))| matcher.matches(query))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let mut table = TableBuilder::default();
+        for (row, idx) in self.shown.iter().enumerate() {
+            if row > 0 {
+                table.add_row();
+            }
+            let (query, pinned) = &self.entries[*idx];
+            let pin = if *pinned { "*" } else { "" };
+            table
+                .add_col(TextSpan::new(pin).fg(pin_color))
+                .add_col(TextSpan::new(query));
+        }
+        if self.shown.is_empty() {
+            table
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new("No matching searches."));
+        }
+        self.component
+            .attr(Attribute::Content, AttrValue::Table(table.build()));
+
+        let title = format!(
+            " History: {}_ (Enter: use, Ctrl+p: pin, Esc: close) ",
+            self.filter
+        );
+        self.component
+            .attr(Attribute::Title, AttrValue::Title((title, Alignment::Left)));
+    }
+
+    /// Get the index into `entries` of the selected row.
+    fn selected(&self) -> Option<usize> {
+        match self.component.state() {
+            State::One(StateValue::Usize(row)) => self.shown.get(row).copied(),
+            _ => None,
+        }
+    }
+
+    /// Toggle the pin of the selected query and get the message to store it.
+    fn toggle_pin(&mut self) -> Option<Msg> {
+        let idx = self.selected()?;
+        let entry = &mut self.entries[idx];
+        entry.1 = !entry.1;
+
+        Some(Msg::SearchHistory(SearchHistoryMsg::TogglePin(
+            self.kind,
+            entry.0.clone(),
+        )))
+    }
+}
+
+impl Component<Msg, UserEvent> for SearchHistoryPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::SearchHistory(SearchHistoryMsg::PopupCloseCancel(
+                    self.kind,
+                )));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                let idx = self.selected()?;
+                return Some(Msg::SearchHistory(SearchHistoryMsg::PopupCloseOk(
+                    self.kind,
+                    self.entries[idx].0.clone(),
+                )));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('p'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => {
+                let msg = self.toggle_pin()?;
+                self.sync();
+                return Some(msg);
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => {
+                self.filter.push(ch);
+                self.sync();
+                CmdResult::Changed(State::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => {
+                self.filter.pop();
+                self.sync();
+                CmdResult::Changed(State::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount the [`SearchHistoryPopup`] for the searches of `kind`.
+    pub fn mount_search_history(&mut self, kind: HistoryKind) {
+        let history = SearchHistory::load_default();
+        assert!(
+            self.app
+                .remount(
+                    Id::SearchHistoryPopup,
+                    Box::new(SearchHistoryPopup::new(
+                        self.config_tui.clone(),
+                        kind,
+                        history.get(kind)
+                    )),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.app.active(&Id::SearchHistoryPopup).is_ok());
+    }
+
+    /// Unmount the [`SearchHistoryPopup`] and focus the search input it was opened from.
+    pub fn umount_search_history(&mut self, kind: HistoryKind) {
+        if self.app.mounted(&Id::SearchHistoryPopup) {
+            let _ = self.app.umount(&Id::SearchHistoryPopup);
+        }
+
+        let input = search_history_input(kind);
+        if self.app.mounted(&input) {
+            self.app.active(&input).ok();
+        }
+    }
+
+    /// Put `query` into the search input of `kind`.
+    ///
+    /// Returns the message to run the search with it, if the search live-updates.
+    #[must_use]
+    pub fn search_history_apply(&mut self, kind: HistoryKind, query: String) -> Option<GSMsg> {
+        let input = search_history_input(kind);
+        self.app
+            .attr(&input, Attribute::Value, AttrValue::String(query.clone()))
+            .ok();
+
+        match kind {
+            HistoryKind::Web => None,
+            HistoryKind::Library => self
+                .general_search_source
+                .as_ref()
+                .map(|source| source.update_msg(query)),
+        }
+    }
+
+    /// Add `query` to the history of `kind`, if the history is enabled.
+    pub fn search_history_push(&self, kind: HistoryKind, query: &str) {
+        let max = usize::from(self.config_tui.read().settings.search_history.size);
+        if max == 0 || query.trim().is_empty() {
+            return;
+        }
+
+        if let Err(err) =
+            SearchHistory::update_default(|history| history.get_mut(kind).push(query, max))
+        {
+            warn!("Saving the search history failed: {err:#}");
+        }
+    }
+
+    /// Pin or unpin `query` in the history of `kind`.
+    pub fn search_history_toggle_pin(&mut self, kind: HistoryKind, query: &str) {
+        if let Err(err) = SearchHistory::update_default(|history| {
+            history.get_mut(kind).toggle_pin(query);
+        }) {
+            self.mount_error_popup(err.context("pin search"));
+        }
+    }
+}
+
+/// Get the search input the history of `kind` belongs to.
+fn search_history_input(kind: HistoryKind) -> Id {
+    match kind {
+        HistoryKind::Web => Id::YoutubeSearchInputPopup,
+        HistoryKind::Library => Id::GeneralSearchInput,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::search_history::HistoryList;
+
+    use super::HistoryRecall;
+
+    #[test]
+    fn should_recall_like_a_shell() {
+        let mut history = HistoryList::default();
+        history.push("first", 10);
+        history.push("second", 10);
+        let mut recall = HistoryRecall::new(&history);
+
+        // nothing newer than the own input
+        assert_eq!(recall.newer(), None);
+
+        assert_eq!(recall.older("draft"), Some("second"));
+        assert_eq!(recall.older("second"), Some("first"));
+        assert_eq!(recall.older("first"), None);
+        assert_eq!(recall.newer(), Some("second"));
+        assert_eq!(recall.newer(), Some("draft"));
+        assert_eq!(recall.newer(), None);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use termusiclib::config::TuiOverlay;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType},
+};
+
+use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, SeekToPositionMsg};
+
+/// Component for a "Seek to" popup, to enter a timestamp in the current track.
+#[derive(MockComponent)]
+pub struct SeekToPositionPopup {
+    component: Input,
+}
+
+impl SeekToPositionPopup {
+    pub fn new(config: &TuiOverlay) -> Self {
+        let settings = &config.settings;
+
+        Self {
+            component: Input::default()
+                .foreground(settings.theme.fallback_foreground())
+                .background(settings.theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .title(
+                    " Seek to, like \"1:23\": (Enter to confirm) ",
+                    Alignment::Left,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for SeekToPositionPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::SeekToPosition(SeekToPositionMsg::Cancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => {
+                Some(Msg::SeekToPosition(SeekToPositionMsg::Ok(input_string)))
+            }
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`SeekToPositionPopup`].
+    pub fn mount_seek_to_position(&mut self) -> Result<()> {
+        self.app.remount(
+            Id::SeekToPositionPopup,
+            Box::new(SeekToPositionPopup::new(&self.config_tui.read())),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::SeekToPositionPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`SeekToPositionPopup`], if mounted.
+    pub fn umount_seek_to_position(&mut self) {
+        if self.app.mounted(&Id::SeekToPositionPopup) {
+            let _ = self.app.umount(&Id::SeekToPositionPopup);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use termusiclib::config::TuiOverlay;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType},
+};
+
+use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, SmartPlaylistMsg};
+
+/// Which value of a smart playlist the [`SmartPlaylistEditPopup`] edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartPlaylistField {
+    Name,
+    Query,
+}
+
+/// Component for a "Smart playlist name:" or "Query:" popup, the two steps of creating or editing a smart playlist.
+#[derive(MockComponent)]
+pub struct SmartPlaylistEditPopup {
+    component: Input,
+    field: SmartPlaylistField,
+}
+
+impl SmartPlaylistEditPopup {
+    pub fn new(config: &TuiOverlay, field: SmartPlaylistField, value: &str) -> Self {
+        let settings = &config.settings;
+        let title = match field {
+            SmartPlaylistField::Name => " Smart playlist name: (Enter to continue) ",
+            SmartPlaylistField::Query => {
+                " Query, like: genre:ambient AND added>30d AND playcount<2 (Enter to save) "
+            }
+        };
+
+        Self {
+            component: Input::default()
+                .foreground(settings.theme.fallback_foreground())
+                .background(settings.theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(value)
+                .title(title, Alignment::Left),
+            field,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for SmartPlaylistEditPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::SmartPlaylist(SmartPlaylistMsg::Cancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => {
+                let msg = match self.field {
+                    SmartPlaylistField::Name => SmartPlaylistMsg::NameOk(input_string),
+                    SmartPlaylistField::Query => SmartPlaylistMsg::QueryOk(input_string),
+                };
+                Some(Msg::SmartPlaylist(msg))
+            }
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`SmartPlaylistEditPopup`] to enter `field`, pre-filled with `value`.
+    pub fn mount_smart_playlist_edit(
+        &mut self,
+        field: SmartPlaylistField,
+        value: &str,
+    ) -> Result<()> {
+        self.app.remount(
+            Id::SmartPlaylistEditPopup,
+            Box::new(SmartPlaylistEditPopup::new(
+                &self.config_tui.read(),
+                field,
+                value,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::SmartPlaylistEditPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`SmartPlaylistEditPopup`], if mounted.
+    pub fn umount_smart_playlist_edit(&mut self) {
+        if self.app.mounted(&Id::SmartPlaylistEditPopup) {
+            let _ = self.app.umount(&Id::SmartPlaylistEditPopup);
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::new_database::track_ops::{ArtistPlays, RATING_MAX, TrackPlays, WeekListening};
+use termusiclib::track::DurationFmtShort;
+use tui_realm_stdlib::{Radio, Table};
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, TableBuilder, TextSpan},
+};
+
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, StatisticsMsg};
+
+/// The width of the bar of the week with the most listening time.
+const WEEK_BAR_WIDTH: usize = 30;
+
+/// Format `rating` as stars, empty if not rated.
+fn stars(rating: Option<u8>) -> String {
+    "★".repeat(rating.unwrap_or_default().into())
+}
+
+/// Get the length of the bar for `listened`, relative to the `longest` listening time.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn bar_len(listened: Duration, longest: Duration) -> usize {
+    if longest.is_zero() {
+        return 0;
+    }
+
+    // the fraction is at most 1, so this cannot overflow
+    (listened.as_secs_f64() / longest.as_secs_f64() * WEEK_BAR_WIDTH as f64).round() as usize
+}
+
+/// Component for a popup to rate the current track, with "no rating" as the first choice.
+#[derive(MockComponent)]
+pub struct RatingPopup {
+    component: Radio,
+    /// The path of the track to rate
+    path: PathBuf,
+    config: SharedTuiSettings,
+}
+
+impl RatingPopup {
+    pub fn new(config: SharedTuiSettings, path: PathBuf, title: &str, rating: Option<u8>) -> Self {
+        let choices: Vec<String> = std::iter::once("none".to_string())
+            .chain((1..=RATING_MAX).map(|v| stars(Some(v))))
+            .collect();
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Radio::default()
+                .foreground(theme.fallback_foreground())
+                .background(theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    format!(" Rate \"{title}\" (0-{RATING_MAX}, Enter: rate, Esc: cancel) "),
+                    Alignment::Left,
+                )
+                .rewind(true)
+                .choices(choices)
+                .value(rating.unwrap_or_default().into())
+        };
+
+        Self {
+            component,
+            path,
+            config,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for RatingPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch @ '0'..='9'),
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let rating = ch.to_digit(10).and_then(|v| u8::try_from(v).ok())?;
+                if rating > RATING_MAX {
+                    return None;
+                }
+                return Some(Msg::Statistics(StatisticsMsg::Rate(
+                    self.path.clone(),
+                    Some(rating).filter(|v| *v > 0),
+                )));
+            }
+
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::Statistics(StatisticsMsg::RateCancel));
+            }
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::Statistics(StatisticsMsg::RateCancel));
+            }
+
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(key) if key == keys.navigation_keys.left.get() => {
+                self.perform(Cmd::Move(Direction::Left))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.right.get() => {
+                self.perform(Cmd::Move(Direction::Right))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::Usize(choice))) => {
+                let rating = u8::try_from(choice).ok().filter(|v| *v > 0);
+                Some(Msg::Statistics(StatisticsMsg::Rate(
+                    self.path.clone(),
+                    rating,
+                )))
+            }
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+/// Component for a popup showing the most played artists and tracks and the listening time of the last weeks.
+#[derive(MockComponent)]
+pub struct StatisticsPopup {
+    component: Table,
+    config: SharedTuiSettings,
+}
+
+impl StatisticsPopup {
+    pub fn new(
+        config: SharedTuiSettings,
+        artists: &[ArtistPlays],
+        tracks: &[TrackPlays],
+        weeks: &[WeekListening],
+    ) -> Self {
+        let mut table = TableBuilder::default();
+        table
+            .add_col(TextSpan::new("Top artists").bold())
+            .add_col(TextSpan::new("Plays").bold());
+        for artist in artists {
+            table
+                .add_row()
+                .add_col(TextSpan::new(&artist.artist))
+                .add_col(TextSpan::new(artist.play_count.to_string()));
+        }
+
+        table
+            .add_row()
+            .add_row()
+            .add_col(TextSpan::new("Top tracks").bold())
+            .add_col(TextSpan::new("Plays / Skips / Rating").bold());
+        for track in tracks {
+            let name = match &track.artist {
+                Some(artist) => format!("{} - {artist}", track.title),
+                None => track.title.clone(),
+            };
+            table
+                .add_row()
+                .add_col(TextSpan::new(name))
+                .add_col(TextSpan::new(format!(
+                    "{} / {} / {}",
+                    track.play_count,
+                    track.skip_count,
+                    stars(track.rating)
+                )));
+        }
+
+        table
+            .add_row()
+            .add_row()
+            .add_col(TextSpan::new("Week").bold())
+            .add_col(TextSpan::new("Listening time").bold());
+        let longest = weeks.iter().map(|v| v.listened).max().unwrap_or_default();
+        for week in weeks {
+            let bar = "█".repeat(bar_len(week.listened, longest));
+            table
+                .add_row()
+                .add_col(TextSpan::new(format!("{} {bar}", week.week_start)))
+                .add_col(TextSpan::new(DurationFmtShort(week.listened).to_string()));
+        }
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .background(theme.fallback_background())
+                .foreground(theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(" Statistics (Esc: close) ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(theme.fallback_highlight())
+                .highlighted_str(&theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .column_spacing(1)
+                .widths(&[65, 35])
+                .table(table.build())
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, UserEvent> for StatisticsPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(key) if key == keys.quit.get() => {
+                return Some(Msg::Statistics(StatisticsMsg::Close));
+            }
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::Statistics(StatisticsMsg::Close));
+            }
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`RatingPopup`] to rate the track at `path` titled `title`, pre-selecting its current `rating`.
+    pub fn mount_rating(&mut self, path: PathBuf, title: &str, rating: Option<u8>) -> Result<()> {
+        self.app.remount(
+            Id::RatingPopup,
+            Box::new(RatingPopup::new(
+                self.config_tui.clone(),
+                path,
+                title,
+                rating,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::RatingPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`RatingPopup`], if mounted.
+    pub fn umount_rating(&mut self) {
+        if self.app.mounted(&Id::RatingPopup) {
+            let _ = self.app.umount(&Id::RatingPopup);
+        }
+    }
+
+    /// Mount / Remount the [`StatisticsPopup`] with the given statistics.
+    pub fn mount_statistics(
+        &mut self,
+        artists: &[ArtistPlays],
+        tracks: &[TrackPlays],
+        weeks: &[WeekListening],
+    ) -> Result<()> {
+        self.app.remount(
+            Id::StatisticsPopup,
+            Box::new(StatisticsPopup::new(
+                self.config_tui.clone(),
+                artists,
+                tracks,
+                weeks,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::StatisticsPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`StatisticsPopup`], if mounted.
+    pub fn umount_statistics(&mut self) {
+        if self.app.mounted(&Id::StatisticsPopup) {
+            let _ = self.app.umount(&Id::StatisticsPopup);
+        }
+    }
+}
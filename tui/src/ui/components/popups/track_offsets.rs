@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use termusiclib::config::TuiOverlay;
+use termusiclib::new_database::track_ops::TrackOffsets;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType},
+};
+
+use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, PLMsg};
+
+/// Component for a "Start - end offset:" popup, pre-filled with the current offsets of the track.
+#[derive(MockComponent)]
+pub struct TrackOffsetsPopup {
+    component: Input,
+    path: PathBuf,
+}
+
+impl TrackOffsetsPopup {
+    pub fn new(config: &TuiOverlay, path: PathBuf, offsets: TrackOffsets) -> Self {
+        let settings = &config.settings;
+        let value = if offsets.is_empty() {
+            String::new()
+        } else {
+            offsets.to_string()
+        };
+
+        Self {
+            component: Input::default()
+                .foreground(settings.theme.fallback_foreground())
+                .background(settings.theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(value)
+                .title(
+                    " Start - end offset, like \"0:05 - 3:20\": (Enter to confirm, empty to clear) ",
+                    Alignment::Left,
+                ),
+            path,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for TrackOffsetsPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Playlist(PLMsg::OffsetsCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => Some(Msg::Playlist(
+                PLMsg::OffsetsOk(self.path.clone(), input_string),
+            )),
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`TrackOffsetsPopup`] for `path`.
+    pub fn mount_track_offsets(&mut self, path: PathBuf, offsets: TrackOffsets) -> Result<()> {
+        self.app.remount(
+            Id::TrackOffsetsPopup,
+            Box::new(TrackOffsetsPopup::new(
+                &self.config_tui.read(),
+                path,
+                offsets,
+            )),
+            Vec::new(),
+        )?;
+        self.app.active(&Id::TrackOffsetsPopup)?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`TrackOffsetsPopup`], if mounted.
+    pub fn umount_track_offsets(&mut self) {
+        if self.app.mounted(&Id::TrackOffsetsPopup) {
+            let _ = self.app.umount(&Id::TrackOffsetsPopup);
+        }
+    }
+}
@@ -22,24 +22,37 @@
  * SOFTWARE.
  */
 use termusiclib::config::{SharedTuiSettings, TuiOverlay};
+use termusiclib::invidious::TRENDING_REGIONS;
+use termusiclib::search_history::{HistoryKind, HistoryList, SearchHistory};
+use termusiclib::search_provider::SearchSite;
 use tui_realm_stdlib::Table;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::event::{Key, KeyEvent, KeyModifiers};
 use tuirealm::props::{Alignment, BorderType, Borders, InputType, TableBuilder, TextSpan};
-use tuirealm::{Component, Event, MockComponent, State, StateValue};
+use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
 
+use super::search_history::HistoryRecall;
 use crate::ui::components::vendored::tui_realm_stdlib_input::Input;
 use crate::ui::ids::Id;
 use crate::ui::model::{Model, UserEvent};
-use crate::ui::msg::{Msg, YSMsg};
+use crate::ui::msg::{Msg, SearchHistoryMsg, YSMsg};
+
+/// Get the default title of [`YSInputPopup`], when searching `site`.
+fn input_title(site: SearchSite) -> String {
+    format!(
+        " Download url, import playlist url or search {} (Tab: suggest, Ctrl+s: site, Ctrl+r: history, Ctrl+t: trending): ",
+        site.name()
+    )
+}
 
 #[derive(MockComponent)]
 pub struct YSInputPopup {
     component: Input,
+    recall: HistoryRecall,
 }
 
 impl YSInputPopup {
-    pub fn new(config: &TuiOverlay) -> Self {
+    pub fn new(config: &TuiOverlay, history: &HistoryList, site: SearchSite) -> Self {
         let settings = &config.settings;
         Self {
             component: Input::default()
@@ -52,9 +65,20 @@ impl YSInputPopup {
                 )
                 // .invalid_style(Style::default().fg(Color::Red))
                 .input_type(InputType::Text)
-                .title(" Download url or search: ", Alignment::Left),
+                .title(input_title(site), Alignment::Left),
+            recall: HistoryRecall::new(history),
         }
     }
+
+    /// Put the recalled `query` into the input.
+    fn recall(&mut self, query: Option<String>) -> CmdResult {
+        let Some(query) = query else {
+            return CmdResult::None;
+        };
+        self.attr(Attribute::Value, AttrValue::String(query));
+
+        CmdResult::Changed(self.state())
+    }
 }
 
 impl Component<Msg, UserEvent> for YSInputPopup {
@@ -89,6 +113,45 @@ impl Component<Msg, UserEvent> for YSInputPopup {
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => self.perform(Cmd::Submit),
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                if let State::One(StateValue::String(input_string)) = self.state() {
+                    return Some(Msg::YoutubeSearch(YSMsg::ReqSuggestion(input_string)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => {
+                return Some(Msg::SearchHistory(SearchHistoryMsg::PopupShow(
+                    HistoryKind::Web,
+                )));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('t'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => return Some(Msg::YoutubeSearch(YSMsg::RegionPopupShow)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('s'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => return Some(Msg::YoutubeSearch(YSMsg::NextSite)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                let current = match self.state() {
+                    State::One(StateValue::String(input)) => input,
+                    _ => String::new(),
+                };
+                let query = self.recall.older(&current).map(ToString::to_string);
+                self.recall(query)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => {
+                let query = self.recall.newer().map(ToString::to_string);
+                self.recall(query)
+            }
             _ => CmdResult::None,
         };
         match cmd_result {
@@ -122,7 +185,7 @@ impl YSTablePopup {
                 )
                 // .foreground(Color::Yellow)
                 .title(
-                    " Tab/Shift+Tab for next and previous page ",
+                    " Tab/Shift+Tab: next and previous page, Enter: download, s: stream ",
                     Alignment::Left,
                 )
                 .scroll(true)
@@ -132,9 +195,9 @@ impl YSTablePopup {
                 .rewind(false)
                 .step(4)
                 .row_height(1)
-                .headers(["Duration", "Name"])
-                .column_spacing(3)
-                .widths(&[20, 80])
+                .headers(["Duration", "Name", "Channel", "Views", "Uploaded"])
+                .column_spacing(2)
+                .widths(&[12, 45, 20, 10, 13])
                 .table(
                     TableBuilder::default()
                         .add_col(TextSpan::from("Empty result."))
@@ -207,6 +270,125 @@ impl Component<Msg, UserEvent> for YSTablePopup {
                 }
                 CmdResult::None
             }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('s'),
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::YoutubeSearch(YSMsg::TablePopupStream(index)));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+/// A popup to pick the region of the youtube music trending.
+#[derive(MockComponent)]
+pub struct YSRegionPopup {
+    component: Table,
+    /// The region codes, in the order of the rows
+    regions: Vec<String>,
+}
+
+impl YSRegionPopup {
+    /// Create the popup with `current` selected, which is added if it is not a known region.
+    pub fn new(config: &TuiOverlay, current: &str) -> Self {
+        let mut rows: Vec<(String, &str)> = TRENDING_REGIONS
+            .iter()
+            .map(|(code, name)| ((*code).to_string(), *name))
+            .collect();
+        if !rows.iter().any(|(code, _)| code == current) {
+            rows.insert(0, (current.to_string(), "(detected)"));
+        }
+        let selected = rows
+            .iter()
+            .position(|(code, _)| code == current)
+            .unwrap_or_default();
+
+        let mut table = TableBuilder::default();
+        for (idx, (code, name)) in rows.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(code).bold())
+                .add_col(TextSpan::new(*name));
+        }
+
+        let settings = &config.settings;
+        let component = Table::default()
+            .background(settings.theme.fallback_background())
+            .foreground(settings.theme.fallback_foreground())
+            .borders(
+                Borders::default()
+                    .color(settings.theme.fallback_border())
+                    .modifiers(BorderType::Rounded),
+            )
+            .title(
+                " Music trending in: (Enter: show, Esc: back) ",
+                Alignment::Left,
+            )
+            .scroll(true)
+            .highlighted_color(settings.theme.fallback_highlight())
+            .highlighted_str(&settings.theme.style.library.highlight_symbol)
+            .rewind(true)
+            .step(4)
+            .row_height(1)
+            .column_spacing(2)
+            .widths(&[10, 90])
+            .table(table.build())
+            .selected_line(selected);
+
+        Self {
+            component,
+            regions: rows.into_iter().map(|(code, _)| code).collect(),
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for YSRegionPopup {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::YoutubeSearch(YSMsg::RegionPopupCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state()
+                    && let Some(region) = self.regions.get(index)
+                {
+                    return Some(Msg::YoutubeSearch(YSMsg::RegionPopupCloseOk(
+                        region.clone(),
+                    )));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
             _ => CmdResult::None,
         };
         match cmd_result {
@@ -222,7 +404,11 @@ impl Model {
             self.app
                 .remount(
                     Id::YoutubeSearchInputPopup,
-                    Box::new(YSInputPopup::new(&self.config_tui.read())),
+                    Box::new(YSInputPopup::new(
+                        &self.config_tui.read(),
+                        SearchHistory::load_default().get(HistoryKind::Web),
+                        self.youtube_search_site,
+                    )),
                     vec![]
                 )
                 .is_ok()
@@ -230,6 +416,19 @@ impl Model {
         assert!(self.app.active(&Id::YoutubeSearchInputPopup).is_ok());
     }
 
+    /// Search the next [`SearchSite`], keeping the input.
+    pub fn youtube_search_next_site(&mut self) {
+        self.youtube_search_site = self.youtube_search_site.next();
+        self.youtube_suggestions_clear();
+        self.app
+            .attr(
+                &Id::YoutubeSearchInputPopup,
+                Attribute::Title,
+                AttrValue::Title((input_title(self.youtube_search_site), Alignment::Left)),
+            )
+            .ok();
+    }
+
     pub fn mount_youtube_search_table(&mut self) {
         assert!(
             self.app
@@ -246,6 +445,26 @@ impl Model {
         }
     }
 
+    pub fn mount_youtube_region_popup(&mut self) {
+        let region = self.youtube_trending_region();
+        assert!(
+            self.app
+                .remount(
+                    Id::YoutubeRegionPopup,
+                    Box::new(YSRegionPopup::new(&self.config_tui.read(), &region)),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.app.active(&Id::YoutubeRegionPopup).is_ok());
+    }
+
+    pub fn umount_youtube_region_popup(&mut self) {
+        if self.app.mounted(&Id::YoutubeRegionPopup) {
+            assert!(self.app.umount(&Id::YoutubeRegionPopup).is_ok());
+        }
+    }
+
     pub fn umount_youtube_search_table_popup(&mut self) {
         if self.app.mounted(&Id::YoutubeSearchTablePopup) {
             assert!(self.app.umount(&Id::YoutubeSearchTablePopup).is_ok());
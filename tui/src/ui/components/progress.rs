@@ -4,7 +4,7 @@ use std::time::Duration;
 use termusiclib::config::TuiOverlay;
 use termusiclib::player::RunningStatus;
 use termusiclib::track::DurationFmtShort;
-use termusiclib::track::MediaTypesSimple;
+use termusiclib::track::{MediaTypesSimple, Track};
 use tuirealm::props::{Alignment, BorderType, Borders, PropPayload, PropValue};
 use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent};
 
@@ -47,6 +47,19 @@ impl Component<Msg, UserEvent> for Progress {
     }
 }
 
+/// The optional parts at the end of the title.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Indicators {
+    /// The applied normalization gain in dB, if normalization is enabled
+    gain: Option<f32>,
+    /// The tempo and whether the beat indicator is lit, if enabled
+    beat: Option<(f32, bool)>,
+    /// The time listened since the last break and the limit, if there is one
+    listened: Option<(Duration, Duration)>,
+    /// The start and, once set, the end of the A-B loop
+    ab_loop: Option<(Duration, Option<Duration>)>,
+}
+
 #[allow(clippy::cast_precision_loss)] // speed is never realisitcally expected to be above i16::MAX
 fn title_format(
     status: RunningStatus,
@@ -54,25 +67,67 @@ fn title_format(
     volume: u16,
     speed: i32,
     gapless: bool,
+    album_mode: bool,
+    indicators: &Indicators,
 ) -> String {
     let gapless = if gapless { "True" } else { "False" };
+    let album_mode = if album_mode { "| Album Mode " } else { "" };
+    let gain = indicators
+        .gain
+        .map(|gain| format!("| Gain: {gain:+.1} dB "))
+        .unwrap_or_default();
+    let beat = indicators
+        .beat
+        .map(|(bpm, lit)| format!("| {} {bpm} BPM ", if lit { '●' } else { '○' }))
+        .unwrap_or_default();
+    let listened = indicators
+        .listened
+        .map(|(listened, limit)| {
+            format!(
+                "| Listened: {}m/{}m ",
+                listened.as_secs() / 60,
+                limit.as_secs() / 60
+            )
+        })
+        .unwrap_or_default();
+    let ab_loop = indicators
+        .ab_loop
+        .map(|(start, end)| match end {
+            Some(end) => format!(
+                "| A-B: {}-{} ",
+                DurationFmtShort(start),
+                DurationFmtShort(end)
+            ),
+            None => format!("| A: {} ", DurationFmtShort(start)),
+        })
+        .unwrap_or_default();
 
     if let Some(title) = title {
         format!(
-            " Status: {} {:^.20} | Volume: {} | Speed: {:^.1} | Gapless: {} ",
+            " Status: {} {:^.20} | Volume: {} | Speed: {:^.1} | Gapless: {} {}{}{}{}{}",
             status,
             title,
             volume,
             speed as f32 / 10.0,
             gapless,
+            album_mode,
+            ab_loop,
+            gain,
+            beat,
+            listened,
         )
     } else {
         format!(
-            " Status: {} | Volume: {} | Speed: {:^.1} | Gapless: {} ",
+            " Status: {} | Volume: {} | Speed: {:^.1} | Gapless: {} {}{}{}{}{}",
             status,
             volume,
             speed as f32 / 10.0,
             gapless,
+            album_mode,
+            ab_loop,
+            gain,
+            beat,
+            listened,
         )
     }
 }
@@ -99,25 +154,51 @@ impl Model {
     /// - gapless
     /// - running status
     /// - moving onto / off a podcast track
+    /// - the normalization state
+    /// - the beat indicator pulse
+    /// - the listening time
+    /// - the A-B loop
     pub fn progress_update_title(&mut self) {
         let config_server = self.config_server.read();
         let player = &config_server.settings.player;
+        let indicators = Indicators {
+            gain: self.normalization.then(|| {
+                self.playback
+                    .current_track()
+                    .and_then(Track::as_track)
+                    .and_then(|v| v.normalization_gain_for(player.normalization_mode))
+                    .unwrap_or_default()
+            }),
+            beat: Some(self.beat_indicator.title_part())
+                .filter(|_| self.config_tui.read().settings.beat_indicator.enable)
+                .flatten(),
+            listened: self
+                .listening_time
+                .and_then(|v| Some((v.listened, v.limit?))),
+            ab_loop: self.ab_loop.title_part(),
+        };
 
         let progress_title = if let Some(track) = self.playback.current_track() {
             match track.media_type() {
-                MediaTypesSimple::Music | MediaTypesSimple::LiveRadio => title_format(
-                    self.playback.status(),
-                    None,
-                    player.volume,
-                    player.speed,
-                    player.gapless,
-                ),
+                MediaTypesSimple::Music | MediaTypesSimple::LiveRadio | MediaTypesSimple::Video => {
+                    title_format(
+                        self.playback.status(),
+                        None,
+                        player.volume,
+                        player.speed,
+                        player.gapless,
+                        player.album_mode,
+                        &indicators,
+                    )
+                }
                 MediaTypesSimple::Podcast => title_format(
                     self.playback.status(),
                     Some(track.title().unwrap_or("Unknown title")),
                     player.volume,
                     player.speed,
                     player.gapless,
+                    player.album_mode,
+                    &indicators,
                 ),
             }
         } else {
@@ -127,6 +208,8 @@ impl Model {
                 player.volume,
                 player.speed,
                 player.gapless,
+                player.album_mode,
+                &indicators,
             )
         };
 
@@ -149,6 +232,7 @@ impl Model {
         let time_pos = time_pos.unwrap_or_default();
 
         self.playback.set_current_track_pos(time_pos);
+        self.playlist_update_start_times();
 
         let progress = if time_pos.as_millis() > 0 && total_duration.as_millis() > 0 {
             (time_pos.as_millis() as f64).div(total_duration.as_millis() as f64)
@@ -160,6 +244,7 @@ impl Model {
 
         self.progress_set(progress, total_duration);
         self.lyric_update();
+        self.beat_indicator_progress(time_pos);
     }
 
     /// Set the progress bar text.
@@ -175,7 +260,7 @@ impl Model {
         let text = if self.playback.is_stopped() {
             DurationFmtShort::fmt_empty().to_string()
         } else if total_duration.is_zero() {
-            format!("{}", DurationFmtShort(self.playback.current_track_pos()),)
+            format!("{}", DurationFmtShort(self.playback.current_track_pos()))
         } else {
             format!(
                 "{}    -    {}",
@@ -380,6 +380,7 @@ impl Component<Msg, UserEvent> for TECounterSave {
 }
 impl Model {
     pub fn te_delete_lyric(&mut self) {
+        let backups = self.tag_backup_store();
         if let Some(song) = self.tageditor_song.as_mut() {
             if song.lyric_frames().is_empty() {
                 song.set_parsed_lyrics(None);
@@ -391,7 +392,10 @@ impl Model {
             {
                 song.set_lyric_selected_index(song.lyric_selected_index() - 1);
             }
-            match song.save_tag() {
+            let res = backups
+                .and_then(|v| v.backup_tags("Delete lyric", &[song.path()]))
+                .and_then(|_| song.save_tag());
+            match res {
                 Ok(()) => {
                     // the unwrap should never happen as we are in a branch where we had a reference to it
                     let song = self.tageditor_song.take().unwrap();
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result, anyhow};
 use termusiclib::config::SharedTuiSettings;
+use termusiclib::songtag::lyric_cache::LyricCache;
 use termusiclib::songtag::{SongTag, SongtagSearchResult, search};
 use tokio::runtime::Handle;
 use tui_realm_stdlib::Table;
@@ -317,6 +318,7 @@ impl Model {
             {
                 song.set_genre(&genre);
             }
+            self.tag_backup_tags("Tag editor", song.path())?;
             song.save_tag()?;
             // the unwrap should also never happen as all components should be properly mounted
             self.init_by_song(song).unwrap();
@@ -349,6 +351,11 @@ impl Model {
                 song.set_album(album);
             }
 
+            let cache = LyricCache::from_settings(&self.config_tui.read().settings.lyrics)
+                .inspect_err(|err| warn!("Error getting the lyric cache: {err:#}"))
+                .ok();
+
+            let backups = self.tag_backup_store()?;
             let tracker_id = song_tag.id().to_string();
             let tracker = self.download_tracker.clone();
             let tx_to_main = self.tx_to_main.clone();
@@ -359,13 +366,21 @@ impl Model {
                     tokio::join!(song_tag.fetch_lyric(), song_tag.fetch_photo());
 
                 if let Ok(Some(lyric_string)) = lyric_string {
+                    if let (Some(cache), Some(artist), Some(title)) =
+                        (&cache, song.artist(), song.title())
+                        && let Err(err) = cache.insert(artist, title, &lyric_string)
+                    {
+                        warn!("Error caching the lyric: {err:#}");
+                    }
                     song.set_lyric(&lyric_string, lang_ext, None::<String>);
                 }
                 if let Ok(artwork) = artwork {
                     song.set_picture(artwork);
                 }
 
-                let res = song.save_tag();
+                let res = backups
+                    .backup_tags("Tag editor embed", &[song.path()])
+                    .and_then(|_| song.save_tag());
 
                 tracker.decrease_one(&tracker_id);
 
@@ -5,7 +5,7 @@ use termusiclib::songtag::TrackDLMsg;
 
 use crate::ui::Model;
 use crate::ui::ids::{Id, IdTagEditor};
-use crate::ui::msg::{TEMsg, TFMsg};
+use crate::ui::msg::{ConfirmAction, TEMsg, TFMsg};
 
 impl Model {
     pub fn update_tageditor(&mut self, msg: TEMsg) {
@@ -51,11 +51,19 @@ impl Model {
             TEMsg::EmbedDone(song) => {
                 self.te_load_lyric_and_photo_done(song);
             }
-            TEMsg::EmbedErr(err) => {
+            TEMsg::EmbedErr(err) | TEMsg::TrackDownloadPreError(err) => {
                 self.mount_error_popup(anyhow!(err));
             }
             TEMsg::Save => {
-                if let Err(e) = self.te_rename_song_by_tag() {
+                if self
+                    .config_tui
+                    .read()
+                    .settings
+                    .behavior
+                    .confirm_tag_overwrite
+                {
+                    self.mount_action_confirm(ConfirmAction::OverwriteTags);
+                } else if let Err(e) = self.te_rename_song_by_tag() {
                     self.mount_error_popup(e.context("rename song by tag"));
                 }
             }
@@ -63,9 +71,6 @@ impl Model {
 
             TEMsg::SearchLyricResult(msg) => self.te_update_lyric_results(msg),
             TEMsg::TrackDownloadResult(msg) => self.te_update_download_msg(msg),
-            TEMsg::TrackDownloadPreError(err) => {
-                self.mount_error_popup(anyhow!(err));
-            }
         }
     }
 
@@ -150,7 +155,7 @@ impl Model {
                 }
             }
             TrackDLMsg::Err(url, title, error_message) => {
-                self.download_tracker.decrease_one(&url);
+                self.download_tracker.fail_one(&url, &*error_message);
                 self.mount_error_popup(anyhow!("download failed: {error_message}"));
                 self.show_message_timeout_label_help(
                     self.download_tracker
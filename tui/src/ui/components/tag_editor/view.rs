@@ -125,6 +125,11 @@ impl Model {
                         chunks_row4_right[2],
                     );
 
+                    if self.app.mounted(&Id::ActionConfirmPopup) {
+                        let popup = draw_area_in_absolute(f.area(), 50, 3);
+                        f.render_widget(Clear, popup);
+                        self.app.view(&Id::ActionConfirmPopup, f, popup);
+                    }
                     if self.app.mounted(&Id::MessagePopup) {
                         let popup = draw_area_top_right_absolute(f.area(), 25, 4);
                         f.render_widget(Clear, popup);
@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::track_suggestions::TrackSuggestion;
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    Component, Event, MockComponent, State, StateValue,
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, Style, TableBuilder, TextSpan},
+};
+
+use crate::ui::ids::Id;
+use crate::ui::model::{Model, UserEvent};
+use crate::ui::msg::{Msg, PLMsg, TrackSuggestionsMsg};
+
+/// Component for the panel next to the playlist, suggesting tracks of the library based on the playing one.
+#[derive(MockComponent)]
+pub struct TrackSuggestions {
+    component: Table,
+    /// The path of each row
+    paths: Vec<PathBuf>,
+    config: SharedTuiSettings,
+}
+
+impl TrackSuggestions {
+    pub fn new(config: SharedTuiSettings, suggestions: &[TrackSuggestion]) -> Self {
+        let mut table = TableBuilder::default();
+        for (idx, suggestion) in suggestions.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let text = match &suggestion.artist {
+                Some(artist) => format!("{} - {artist}", suggestion.title),
+                None => suggestion.title.clone(),
+            };
+            table
+                .add_col(TextSpan::new(suggestion.section.name()).bold())
+                .add_col(TextSpan::new(text));
+        }
+        if suggestions.is_empty() {
+            table
+                .add_col(TextSpan::new(""))
+                .add_col(TextSpan::new("Nothing to suggest for the playing track."));
+        }
+
+        let component = {
+            let config = config.read();
+            let theme = &config.settings.theme;
+            Table::default()
+                .borders(
+                    Borders::default()
+                        .modifiers(BorderType::Rounded)
+                        .color(theme.playlist_border()),
+                )
+                .background(theme.playlist_background())
+                .foreground(theme.playlist_foreground())
+                .inactive(Style::new().bg(theme.playlist_background()))
+                .title(" Suggestions ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(theme.playlist_highlight())
+                .highlighted_str(&theme.style.playlist.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .column_spacing(1)
+                .widths(&[20, 80])
+                .table(table.build())
+        };
+
+        Self {
+            component,
+            paths: suggestions.iter().map(|v| v.path.clone()).collect(),
+            config,
+        }
+    }
+
+    /// Get the path of the selected suggestion.
+    fn selected(&self) -> Option<&PathBuf> {
+        match self.component.state() {
+            State::One(StateValue::Usize(row)) => self.paths.get(row),
+            _ => None,
+        }
+    }
+}
+
+impl Component<Msg, UserEvent> for TrackSuggestions {
+    fn on(&mut self, ev: Event<UserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                let path = self.selected()?.clone();
+                return Some(Msg::Playlist(PLMsg::Add(path)));
+            }
+            Event::Keyboard(key) if key == keys.database_keys.add_selected.get() => {
+                let path = self.selected()?.clone();
+                return Some(Msg::Playlist(PLMsg::Add(path)));
+            }
+
+            Event::Keyboard(key) if key == keys.escape.get() => {
+                return Some(Msg::TrackSuggestions(TrackSuggestionsMsg::Blur));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab | Key::BackTab,
+                ..
+            }) => return Some(Msg::TrackSuggestions(TrackSuggestionsMsg::Blur)),
+
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            _ => CmdResult::None,
+        };
+
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    /// Mount / Remount the [`TrackSuggestions`] listing `suggestions`, keeping the focus.
+    pub fn mount_track_suggestions(&mut self, suggestions: &[TrackSuggestion]) -> Result<()> {
+        self.app.remount(
+            Id::TrackSuggestions,
+            Box::new(TrackSuggestions::new(self.config_tui.clone(), suggestions)),
+            Vec::new(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Unmount the [`TrackSuggestions`], if mounted.
+    pub fn umount_track_suggestions(&mut self) {
+        if self.app.mounted(&Id::TrackSuggestions) {
+            let _ = self.app.umount(&Id::TrackSuggestions);
+        }
+    }
+}
@@ -10,13 +10,13 @@ use std::io::Write;
 #[cfg(any(
     feature = "cover-viuer-iterm",
     feature = "cover-viuer-kitty",
-    feature = "cover-viuer-sixel"
+    feature = "cover-viuer-sixel",
+    feature = "cover-viuer-blocks"
 ))]
 use anyhow::Context;
 use anyhow::Result;
-use bytes::Buf;
 use image::DynamicImage;
-use lofty::picture::Picture;
+use termusiclib::podcast::artwork::ArtworkCache;
 use termusiclib::track::MediaTypes;
 use tokio::runtime::Handle;
 
@@ -87,7 +87,7 @@ impl Model {
             return true;
         }
 
-        if self.playback.is_stopped() {
+        if self.playback.is_stopped() && self.podcast_feed_artwork_url().is_none() {
             return true;
         }
 
@@ -111,6 +111,10 @@ impl Model {
         if self.should_not_show_photo() {
             return Ok(());
         }
+        if let Some(url) = self.podcast_feed_artwork_url() {
+            Handle::current().spawn(Self::fetch_podcast_image(self.tx_to_main.clone(), url));
+            return Ok(());
+        }
         let Some(track) = self.playback.current_track() else {
             return Ok(());
         };
@@ -135,7 +139,7 @@ impl Model {
                     return Ok(());
                 }
             }
-            MediaTypes::Radio(_radio_track_data) => (),
+            MediaTypes::Radio(_) | MediaTypes::Video(_) => (),
             MediaTypes::Podcast(podcast_track_data) => {
                 let url = {
                     if let Some(episode_photo_url) = podcast_track_data.image_url() {
@@ -161,72 +165,22 @@ impl Model {
         Ok(())
     }
 
-    /// Fetch the given url as a image and send events when done or error.
+    /// Fetch the given url as a image, from the artwork cache if already downloaded, and send events when done or error.
     async fn fetch_podcast_image(tx: TxToMain, url: String) {
-        match reqwest::get(&url).await {
-            Ok(result) => {
-                if result.status() != reqwest::StatusCode::OK {
-                    tx.send(Msg::Xywh(XYWHMsg::CoverDLResult(
-                        CoverDLResult::FetchPhotoErr(format!(
-                            "Error non-OK Status code: {}",
-                            result.status()
-                        )),
-                    )))
-                    .ok();
-                    return;
-                }
+        let result = match Self::load_podcast_image(&url).await {
+            Ok(data) => CoverDLResult::FetchPhotoSuccess(ImageWrapper { data }),
+            Err(err) => {
+                CoverDLResult::FetchPhotoErr(format!("Error fetching the artwork: {err:#}"))
+            }
+        };
 
-                let mut reader = {
-                    let bytes = match result.bytes().await {
-                        Ok(v) => v,
-                        Err(err) => {
-                            tx.send(Msg::Xywh(XYWHMsg::CoverDLResult(
-                                CoverDLResult::FetchPhotoErr(format!(
-                                    "Error in reqest::Response::bytes: {err}"
-                                )),
-                            )))
-                            .ok();
-                            return;
-                        }
-                    };
-
-                    bytes.reader()
-                };
+        tx.send(Msg::Xywh(XYWHMsg::CoverDLResult(result))).ok();
+    }
 
-                let picture = match Picture::from_reader(&mut reader) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        tx.send(Msg::Xywh(XYWHMsg::CoverDLResult(
-                            CoverDLResult::FetchPhotoErr(format!(
-                                "Error in picture from_reader: {e}"
-                            )),
-                        )))
-                        .ok();
-                        return;
-                    }
-                };
+    async fn load_podcast_image(url: &str) -> Result<DynamicImage> {
+        let data = ArtworkCache::new_default()?.fetch(url).await?;
 
-                match image::load_from_memory(picture.data()) {
-                    Ok(image) => {
-                        let image_wrapper = ImageWrapper { data: image };
-                        tx.send(Msg::Xywh(XYWHMsg::CoverDLResult(
-                            CoverDLResult::FetchPhotoSuccess(image_wrapper),
-                        )))
-                        .ok()
-                    }
-                    Err(e) => tx
-                        .send(Msg::Xywh(XYWHMsg::CoverDLResult(
-                            CoverDLResult::FetchPhotoErr(format!("Error in load_from_memory: {e}")),
-                        )))
-                        .ok(),
-                }
-            }
-            Err(e) => tx
-                .send(Msg::Xywh(XYWHMsg::CoverDLResult(
-                    CoverDLResult::FetchPhotoErr(format!("Error in ureq get: {e}")),
-                )))
-                .ok(),
-        };
+        Ok(image::load_from_memory(&data)?)
     }
 
     #[allow(clippy::cast_possible_truncation, clippy::unnecessary_wraps)]
@@ -257,7 +211,8 @@ impl Model {
             #[cfg(any(
                 feature = "cover-viuer-iterm",
                 feature = "cover-viuer-kitty",
-                feature = "cover-viuer-sixel"
+                feature = "cover-viuer-sixel",
+                feature = "cover-viuer-blocks"
             ))]
             _ => {
                 let config = viuer::Config {
@@ -267,7 +222,7 @@ impl Model {
                     y: xywh.y as i16,
                     width: Some(xywh.width),
                     height: None,
-                    // Force the specific protocol we probed for earlier
+                    // Force the specific protocol we probed for earlier, the block printer is used if none is set
                     #[cfg(feature = "cover-viuer-iterm")]
                     use_iterm: self.viuer_supported == ViuerSupported::ITerm,
                     #[cfg(feature = "cover-viuer-kitty")]
@@ -304,6 +259,14 @@ impl Model {
                     .context("clear_photo sixel")?;
                 // sixel does not use temp-files, so no cleaning necessary
             }
+            #[cfg(feature = "cover-viuer-blocks")]
+            ViuerSupported::Blocks => {
+                // the blocks are normal cells, which are only overwritten on a full redraw
+                self.terminal
+                    .raw_mut()
+                    .clear()
+                    .context("clear_photo blocks")?;
+            }
             ViuerSupported::NotSupported => {
                 #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
                 if let Some(instance) = self.ueberzug_instance.as_mut() {
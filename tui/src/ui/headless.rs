@@ -0,0 +1,317 @@
+//! Drive the [`Model`] without a terminal, with key events from tests or a script of `--script`.
+//!
+//! A script has one step per line, empty lines and lines starting with `#` are ignored:
+//! - `key control+e`: press a key, in the same format as the keys in the tui config
+//! - `type some text`: press the keys of the characters of the text
+//! - `wait 500`: keep handling events for some milliseconds, like to wait for the server
+//! - `resize 80x24`: resize the screen, which is [`DEFAULT_SIZE`] initially
+//! - `snapshot`: write the text of the current screen to the output
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+use termusiclib::config::v2::tui::keys::KeyBinding;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tuirealm::application::PollStrategy;
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+
+use crate::ui::model::Model;
+
+/// How long to wait for the first event of a poll when headless.
+pub const HEADLESS_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long to handle events at most until they settle, in case they never do.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The initial width and height of the screen.
+pub const DEFAULT_SIZE: (u16, u16) = (120, 40);
+
+/// Options to create a [`Model`] drawing to a buffer instead of the terminal.
+#[derive(Debug)]
+pub struct HeadlessOptions {
+    /// The width and height of the screen
+    pub size: (u16, u16),
+    /// The key events to handle instead of the ones of the terminal
+    pub keys: UnboundedReceiver<KeyEvent>,
+    /// The directory to open the databases in instead of the app config directory, like for tests
+    pub data_dir: Option<PathBuf>,
+}
+
+impl HeadlessOptions {
+    pub fn new(keys: UnboundedReceiver<KeyEvent>) -> Self {
+        Self {
+            size: DEFAULT_SIZE,
+            keys,
+            data_dir: None,
+        }
+    }
+}
+
+/// A step of a script, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptStep {
+    Key(KeyEvent),
+    Type(String),
+    Wait(Duration),
+    Resize(u16, u16),
+    Snapshot,
+}
+
+/// Parse all steps of `input`, see the [module documentation](self).
+pub fn parse_script(input: &str) -> Result<Vec<ScriptStep>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| parse_step(line).with_context(|| format!("script line {}", idx + 1)))
+        .collect()
+}
+
+/// Parse a single, trimmed, non-empty line of a script.
+fn parse_step(line: &str) -> Result<ScriptStep> {
+    let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+    let arg = arg.trim();
+
+    let step = match command {
+        "key" => ScriptStep::Key(KeyBinding::try_from_str(arg)?.get()),
+        "type" if !arg.is_empty() => ScriptStep::Type(arg.to_string()),
+        "wait" => ScriptStep::Wait(Duration::from_millis(
+            arg.parse().context("wait milliseconds")?,
+        )),
+        "resize" => {
+            let (width, height) = arg
+                .split_once('x')
+                .ok_or_else(|| anyhow!("Expected a size like \"80x24\", got {arg:#?}"))?;
+            ScriptStep::Resize(
+                width.parse().context("resize width")?,
+                height.parse().context("resize height")?,
+            )
+        }
+        "snapshot" => ScriptStep::Snapshot,
+        _ => bail!("Unknown script step {line:#?}"),
+    };
+
+    Ok(step)
+}
+
+/// Get the key event of typing `char`, like the terminal reports it.
+fn char_key(char: char) -> KeyEvent {
+    let modifiers = if char.is_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+
+    KeyEvent::new(Key::Char(char), modifiers)
+}
+
+/// Send key events to a headless [`Model`] and get the text of its screen.
+pub struct HeadlessDriver<'a> {
+    model: &'a mut Model,
+    /// The sender of the [`HeadlessOptions::keys`] of the model
+    keys: UnboundedSender<KeyEvent>,
+}
+
+impl<'a> HeadlessDriver<'a> {
+    pub fn new(model: &'a mut Model, keys: UnboundedSender<KeyEvent>) -> Self {
+        Self { model, keys }
+    }
+
+    /// Press `key` and handle the events until they settle.
+    pub fn press(&mut self, key: KeyEvent) {
+        let _ = self.keys.send(key);
+        self.settle();
+    }
+
+    /// Handle events until there were none for [`HEADLESS_POLL_TIMEOUT`], then draw.
+    pub fn settle(&mut self) {
+        let start = Instant::now();
+        while !self.model.quit
+            && start.elapsed() < SETTLE_TIMEOUT
+            && self.model.tick(PollStrategy::UpToNoWait(10))
+        {}
+    }
+
+    /// Handle events for `duration`, then draw.
+    pub fn wait(&mut self, duration: Duration) {
+        let start = Instant::now();
+        while !self.model.quit && start.elapsed() < duration {
+            self.model.tick(PollStrategy::UpToNoWait(10));
+        }
+    }
+
+    /// Resize the screen and draw it again.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.model.terminal.resize_headless(width, height);
+        self.model.force_redraw();
+        self.settle();
+    }
+
+    /// Get the text of the last drawn screen, without trailing whitespace in each line.
+    pub fn snapshot(&self) -> String {
+        self.model
+            .terminal
+            .snapshot()
+            .expect("Expected the driven model to be headless")
+    }
+
+    /// Run all steps of `script`, writing the snapshots to `out`.
+    ///
+    /// Stops early if the steps quit the application.
+    pub fn run(&mut self, script: &[ScriptStep], out: &mut impl Write) -> Result<()> {
+        self.settle();
+
+        let mut snapshots = 0;
+        for step in script {
+            if self.model.quit {
+                warn!("Script quit the application, skipping the remaining steps");
+                break;
+            }
+
+            match step {
+                ScriptStep::Key(key) => self.press(*key),
+                ScriptStep::Type(text) => {
+                    for char in text.chars() {
+                        self.press(char_key(char));
+                    }
+                }
+                ScriptStep::Wait(duration) => self.wait(*duration),
+                ScriptStep::Resize(width, height) => self.resize(*width, *height),
+                ScriptStep::Snapshot => {
+                    snapshots += 1;
+                    write!(out, "--- snapshot {snapshots} ---\n{}", self.snapshot())
+                        .context("write snapshot")?;
+                }
+            }
+        }
+
+        out.flush().context("flush snapshots")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::StreamExt;
+    use pretty_assertions::assert_eq;
+    use termusiclib::config::v2::server::ServerSettings;
+    use termusiclib::config::v2::server::equalizer::{EqualizerPreset, EqualizerSettings};
+    use termusiclib::config::v2::tui::TuiSettings;
+    use termusiclib::config::{
+        ServerOverlay, TuiOverlay, new_shared_server_settings, new_shared_tui_settings,
+    };
+    use termusiclib::player::StreamUpdates;
+    use tokio::sync::mpsc::unbounded_channel;
+    use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+
+    use super::{HeadlessDriver, HeadlessOptions, ScriptStep, parse_script};
+    use crate::CombinedSettings;
+    use crate::ui::model::Model;
+    use crate::ui::msg::{Msg, ServerReqResponse};
+    use crate::ui::tui_cmd::{BackendCmd, TuiCmd};
+
+    #[test]
+    fn should_parse_script() {
+        let script =
+            "# open the equalizer\nkey control+e\n\n  type aB \nwait 250\nresize 80x24\nsnapshot\n";
+
+        assert_eq!(
+            parse_script(script).unwrap(),
+            [
+                ScriptStep::Key(KeyEvent::new(Key::Char('e'), KeyModifiers::CONTROL)),
+                ScriptStep::Type("aB".to_string()),
+                ScriptStep::Wait(Duration::from_millis(250)),
+                ScriptStep::Resize(80, 24),
+                ScriptStep::Snapshot,
+            ]
+        );
+
+        let err = parse_script("snapshot\nresize 80").unwrap_err();
+        assert_eq!(err.to_string(), "script line 2");
+        assert!(parse_script("press q").is_err());
+        assert!(parse_script("type").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_drive_equalizer_popup() {
+        let data_dir =
+            std::env::temp_dir().join(format!("termusic-headless-test-{}", std::process::id()));
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let mut server = ServerSettings::default();
+        server.player.music_dirs = vec![data_dir.clone()];
+        let config = CombinedSettings {
+            server: new_shared_server_settings(ServerOverlay {
+                settings: server,
+                ..Default::default()
+            }),
+            tui: new_shared_tui_settings(TuiOverlay {
+                settings: TuiSettings::default(),
+                coverart_hidden_overwrite: None,
+                cover_features: false,
+            }),
+        };
+
+        // the mock server only records the commands and answers them manually
+        let (cmd_tx, mut cmd_rx) = unbounded_channel();
+        let stream = futures_util::stream::pending::<anyhow::Result<StreamUpdates>>().boxed();
+        let (keys_tx, keys) = unbounded_channel();
+        let mut model = Model::new(
+            config,
+            cmd_tx,
+            stream,
+            Some(HeadlessOptions {
+                size: (100, 30),
+                keys,
+                data_dir: Some(data_dir.clone()),
+            }),
+        );
+        let tx_to_main = model.tx_to_main.clone();
+
+        let mut driver = HeadlessDriver::new(&mut model, keys_tx);
+        driver.settle();
+
+        driver.press(KeyEvent::new(Key::Char('e'), KeyModifiers::CONTROL));
+        let screen = driver.snapshot();
+        assert!(screen.contains("Equalizer: flat (off)"), "{screen}");
+
+        // the popup already shows the requested preset, before the server responded
+        driver.press(KeyEvent::new(Key::Char('p'), KeyModifiers::NONE));
+        let requested = std::iter::from_fn(|| cmd_rx.try_recv().ok())
+            .find_map(|cmd| match cmd {
+                TuiCmd::Backend(BackendCmd::SetEqualizer(settings)) => Some(settings),
+                _ => None,
+            })
+            .expect("Expected the equalizer to be sent to the server");
+        assert_eq!(requested.preset, EqualizerPreset::Rock);
+        assert!(requested.enabled);
+        let screen = driver.snapshot();
+        assert!(screen.contains("Equalizer: rock (on)"), "{screen}");
+
+        // the backend of the mock server does not support the equalizer
+        tx_to_main
+            .send(Msg::ServerReqResponse(ServerReqResponse::Equalizer(
+                EqualizerSettings {
+                    enabled: false,
+                    ..requested
+                },
+            )))
+            .unwrap();
+        driver.settle();
+        let screen = driver.snapshot();
+        assert!(screen.contains("Equalizer: rock (off)"), "{screen}");
+
+        driver.press(KeyEvent::new(Key::Esc, KeyModifiers::NONE));
+        let screen = driver.snapshot();
+        assert!(!screen.contains("Equalizer:"), "{screen}");
+
+        drop(model);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
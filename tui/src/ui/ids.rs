@@ -15,6 +15,7 @@ pub enum Id {
     GeneralSearchTable,
     GlobalListener,
     HelpPopup,
+    KeyHints,
     Label,
     Library,
     Lyric,
@@ -33,7 +34,26 @@ pub enum Id {
     TagEditor(IdTagEditor),
     YoutubeSearchInputPopup,
     YoutubeSearchTablePopup,
+    YoutubeRegionPopup,
     DatabaseAddConfirmPopup,
+    LibraryRenamePopup,
+    TrackOffsetsPopup,
+    SeekToPositionPopup,
+    QueueReplacePopup,
+    ActionConfirmPopup,
+    SearchHistoryPopup,
+    CreditsPopup,
+    DownloadsPopup,
+    RadioSearchInputPopup,
+    RadioStationsPopup,
+    EqualizerPopup,
+    OutputDevicesPopup,
+    BookmarkAddPopup,
+    BookmarksPopup,
+    SmartPlaylistEditPopup,
+    RatingPopup,
+    StatisticsPopup,
+    TrackSuggestions,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
@@ -121,8 +141,16 @@ impl From<&IdCETheme> for IdConfigEditor {
 pub enum IdCEGeneral {
     MusicDir,
     ExitConfirmation,
+    ExitConfirmationPlaying,
+    ConfirmClearPlaylist,
+    ConfirmDelete,
+    ConfirmTagOverwrite,
+    ConfirmQueueReplace,
+    ShowKeyHints,
     AlbumPhotoAlign,
     ExtraYtdlpArgs,
+    SponsorBlockCategories,
+    ContentFilterKeywords,
     SaveLastPosition,
     SeekStep,
 
@@ -131,6 +159,8 @@ pub enum IdCEGeneral {
     PlayerProtocol,
     PlayerUDSPath,
     PlayerBackend,
+    PlayerCrossfade,
+    PlayerCrossfadeCurve,
     PlayerUseDiscord,
     PlayerUseMpris,
 
@@ -143,6 +173,7 @@ pub enum IdCEGeneral {
     PlaylistDisplaySymbol,
 
     KillDamon,
+    DeleteToTrash,
 }
 
 impl From<IdCEGeneral> for IdConfigEditor {
@@ -166,6 +197,14 @@ pub enum IdKeyGlobal {
     Quit,
     Config,
     Help,
+    GlobalSearch,
+    Credits,
+    Downloads,
+    Radio,
+    Equalizer,
+    OutputDevices,
+    Statistics,
+    TrackSuggestions,
     SavePlaylist,
 
     Up,
@@ -176,6 +215,15 @@ pub enum IdKeyGlobal {
     GotoTop,
 
     PlayerToggleGapless,
+    PlayerToggleKaraoke,
+    PlayerToggleRecording,
+    PlayerToggleAlbumMode,
+    PlayerTapTempo,
+    PlayerToggleNormalization,
+    PlayerToggleSmartSpeed,
+    PlayerAbLoop,
+    PlayerSeekToPosition,
+    PlayerRateTrack,
     PlayerTogglePause,
     PlayerNext,
     PlayerPrevious,
@@ -189,6 +237,8 @@ pub enum IdKeyGlobal {
     LyricAdjustForward,
     LyricAdjustBackward,
     LyricCycle,
+    LyricSearch,
+    LyricToggleTranslation,
 
     XywhMoveUp,
     XywhMoveDown,
@@ -208,6 +258,12 @@ pub enum IdKeyOther {
     LibraryLoadDir,
     LibraryYank,
     LibraryPaste,
+    LibraryCopy,
+    LibraryRename,
+    LibraryUndoDelete,
+    LibrarySyncDevice,
+    LibraryFetchLyrics,
+    LibraryReplacePlaylist,
     LibrarySearch,
     LibrarySearchYoutube,
     LibraryTagEditor,
@@ -222,9 +278,23 @@ pub enum IdKeyOther {
     PlaylistDeleteAll,
     PlaylistAddRandomAlbum,
     PlaylistAddRandomTracks,
+    PlaylistAddRandomUnplayed,
+    PlaylistSyncDevice,
+    PlaylistFetchLyrics,
+    PlaylistSetOffsets,
+    PlaylistAddAutoDj,
+    PlaylistAddBookmark,
+    PlaylistShowBookmarks,
 
     DatabaseAddAll,
     DatabaseAddSelected,
+    DatabaseReplacePlaylist,
+    DatabaseMarkReviewed,
+    DatabaseMarkAllReviewed,
+    DatabaseLocate,
+    DatabaseVerifyLibrary,
+    DatabaseNewSmartPlaylist,
+    DatabaseEditSmartPlaylist,
 
     PodcastSearchAddFeed,
     PodcastMarkPlayed,
@@ -1,14 +1,19 @@
+use std::io::Write;
+
 use anyhow::Result;
 use futures_util::StreamExt;
 use sysinfo::Pid;
 use sysinfo::System;
+use termusiclib::new_database::track_ops;
 use termusiclib::player::music_player_client::MusicPlayerClient;
+use termusiclib::player::{API_VERSION, ServerCapabilities};
 use tokio::sync::mpsc::{self};
 use tonic::transport::Channel;
+use tuirealm::Application;
 use tuirealm::application::PollStrategy;
-use tuirealm::{Application, Update};
 
 use crate::CombinedSettings;
+use crate::ui::headless::{HeadlessDriver, HeadlessOptions, ScriptStep};
 use crate::ui::server_req_actor::ServerRequestActor;
 use model::Model;
 use music_player_client::Playback;
@@ -16,6 +21,7 @@ use tui_cmd::PlaylistCmd;
 use tui_cmd::TuiCmd;
 
 pub mod components;
+pub mod headless;
 mod ids;
 pub mod model;
 mod msg;
@@ -34,12 +40,41 @@ pub struct UI {
 impl UI {
     /// Create a new [`UI`] instance
     pub async fn new(config: CombinedSettings, client: MusicPlayerClient<Channel>) -> Result<Self> {
+        Self::new_with(config, client, None).await
+    }
+
+    /// Replay `script` without a terminal, writing the requested snapshots to `out`.
+    pub async fn run_script(
+        config: CombinedSettings,
+        client: MusicPlayerClient<Channel>,
+        script: &[ScriptStep],
+        out: &mut impl Write,
+    ) -> Result<()> {
+        let (keys_tx, keys) = mpsc::unbounded_channel();
+        let mut ui = Self::new_with(config, client, Some(HeadlessOptions::new(keys))).await?;
+
+        ui.request_initial_state();
+        let res = HeadlessDriver::new(&mut ui.model, keys_tx).run(script, out);
+        ui.finish();
+
+        res
+    }
+
+    async fn new_with(
+        config: CombinedSettings,
+        client: MusicPlayerClient<Channel>,
+        headless: Option<HeadlessOptions>,
+    ) -> Result<Self> {
         let mut playback = Playback::new(client);
 
+        let capabilities = playback.get_capabilities().await?;
+        Self::check_capabilities(&capabilities);
+
         let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
         let stream_updates = playback.subscribe_to_stream_updates().await?;
 
-        let mut model = Model::new(config, cmd_tx, stream_updates.boxed());
+        let mut model = Model::new(config, cmd_tx, stream_updates.boxed(), headless);
+        model.server_capabilities = capabilities;
         model.init();
 
         ServerRequestActor::start_actor(playback, cmd_rx, model.tx_to_main.clone());
@@ -47,6 +82,29 @@ impl UI {
         Ok(Self { model })
     }
 
+    /// Log the server's capabilities and warn about a differing API version.
+    fn check_capabilities(capabilities: &ServerCapabilities) {
+        info!(
+            "Server version {:?}, api version {}, backend {:?} (available: {:?})",
+            capabilities.server_version,
+            capabilities.api_version,
+            capabilities.active_backend,
+            capabilities.backends
+        );
+
+        if capabilities.api_version < API_VERSION {
+            warn!(
+                "Server api version ({}) is older than the TUI's ({API_VERSION}), some features may be unavailable",
+                capabilities.api_version
+            );
+        } else if capabilities.api_version > API_VERSION {
+            warn!(
+                "Server api version ({}) is newer than the TUI's ({API_VERSION}), consider updating the TUI",
+                capabilities.api_version
+            );
+        }
+    }
+
     /// Handle terminal init & finalize and start the UI Loop.
     pub fn run(&mut self) -> Result<()> {
         self.model.init_terminal();
@@ -64,6 +122,21 @@ impl UI {
     /// This function does NOT handle initializing and finializing the terminal.
     #[allow(clippy::unnecessary_wraps)] // to easily change if it ever becomes required again
     fn run_inner(&mut self) -> Result<()> {
+        self.request_initial_state();
+
+        // Main loop
+        while !self.model.quit {
+            self.model.tick(PollStrategy::BlockCollectUpTo(10));
+            crate::startup::mark("first draw");
+        }
+
+        self.finish();
+
+        Ok(())
+    }
+
+    /// Request the initial state from the server, before the first events are handled.
+    fn request_initial_state(&mut self) {
         // load the initial playlist
         let _ = self
             .model
@@ -71,30 +144,19 @@ impl UI {
             .send(TuiCmd::Playlist(PlaylistCmd::SelfReloadPlaylist));
         // initial request for all the progress states / options
         self.model.request_progress();
+        self.model.playlist_sync_on_start();
+    }
 
-        // Main loop
-        while !self.model.quit {
-            match self.model.app.tick(PollStrategy::BlockCollectUpTo(10)) {
-                Err(err) => {
-                    self.model
-                        .mount_error_popup((anyhow::anyhow!(err)).context("tick poll error"));
-                }
-                Ok(messages) if !messages.is_empty() => {
-                    // NOTE: redraw if at least one msg has been processed
-                    self.model.redraw = true;
-                    for msg in messages {
-                        let mut msg = Some(msg);
-                        while msg.is_some() {
-                            msg = self.model.update(msg);
-                        }
-                    }
-                }
-                _ => {}
-            }
-
-            self.model.ensure_quit_popup_top_most_focus();
-
-            self.model.view();
+    /// Finish the pending work of the application, after the last event was handled.
+    fn finish(&mut self) {
+        // the undo window ends with the application, the async runtime may not process a spawned write anymore
+        if let Some(pending) = self.model.pending_delete.take()
+            && let Err(err) = track_ops::delete_tracks_path(
+                &self.model.db.get_connection(),
+                &pending.trashed.original,
+            )
+        {
+            warn!("Error removing deleted tracks from the database: {err:#?}");
         }
 
         if self
@@ -107,8 +169,6 @@ impl UI {
         {
             Self::quit_server();
         }
-
-        Ok(())
     }
 
     /// Quit the server, if any is found with the proper name.
@@ -0,0 +1,189 @@
+//! Seeking within the current track: the A-B loop, see [`features::AB_LOOP`], and seeking to a entered timestamp.
+
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use termusiclib::player::{AbLoop, features};
+use termusiclib::track::DurationFmtShort;
+
+use super::Model;
+use crate::ui::msg::SeekToPositionMsg;
+use crate::ui::tui_cmd::TuiCmd;
+
+/// The A-B loop as set in this client, which only knows the start until the end is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AbLoopMark {
+    #[default]
+    None,
+    /// Only the start is set, waiting for the end
+    Start(Duration),
+    /// The server repeats this region
+    Loop(AbLoop),
+}
+
+impl AbLoopMark {
+    /// Get the start and, if set, the end, to show in the progress title.
+    pub fn title_part(self) -> Option<(Duration, Option<Duration>)> {
+        match self {
+            Self::None => None,
+            Self::Start(start) => Some((start, None)),
+            Self::Loop(ab_loop) => Some((ab_loop.start, Some(ab_loop.end))),
+        }
+    }
+}
+
+/// Parse a timestamp as `[[H:]M:]S`, the seconds may have a fraction like `1:23.5`.
+fn parse_timestamp(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    if value.is_empty() {
+        bail!("Enter a timestamp like \"1:23\"");
+    }
+
+    let parts: Vec<&str> = value.split(':').map(str::trim).collect();
+    if parts.len() > 3 {
+        bail!("\"{value}\" has more parts than hours, minutes and seconds");
+    }
+    let (secs, rest) = parts.split_last().expect("split always has one part");
+    let Ok(secs) = secs.parse::<f64>() else {
+        bail!("\"{value}\" is not a timestamp like \"1:23\"");
+    };
+    if !secs.is_finite() || secs < 0.0 {
+        bail!("\"{value}\" is not a timestamp like \"1:23\"");
+    }
+
+    let mut whole: u64 = 0;
+    for part in rest {
+        let Ok(part) = part.parse::<u64>() else {
+            bail!("\"{value}\" is not a timestamp like \"1:23\"");
+        };
+        whole = whole.saturating_mul(60).saturating_add(part);
+    }
+
+    Ok(Duration::from_secs(whole.saturating_mul(60)) + Duration::from_secs_f64(secs))
+}
+
+impl Model {
+    /// Set the start of the A-B loop at the current position, then its end, then clear it.
+    pub fn player_ab_loop(&mut self) {
+        if !self.server_capabilities.supports(features::AB_LOOP) {
+            self.mount_error_popup(anyhow!("The A-B loop is not supported by the server"));
+            return;
+        }
+        let position = self.playback.current_track_pos();
+
+        match self.ab_loop {
+            AbLoopMark::None => {
+                self.ab_loop = AbLoopMark::Start(position);
+                self.progress_update_title();
+                self.update_show_message_timeout(
+                    "A-B Loop",
+                    &format!(
+                        "Start set at {}, press again to set the end",
+                        DurationFmtShort(position)
+                    ),
+                    None,
+                );
+            }
+            AbLoopMark::Start(start) => {
+                // allow setting the end before the start, like after seeking back
+                let Some(ab_loop) = AbLoop::new(start.min(position), start.max(position)) else {
+                    self.update_show_message_timeout(
+                        "A-B Loop",
+                        "The end has to be at another position than the start",
+                        None,
+                    );
+                    return;
+                };
+                self.command(TuiCmd::SetAbLoop(Some(ab_loop)));
+            }
+            AbLoopMark::Loop(_) => self.command(TuiCmd::SetAbLoop(None)),
+        }
+    }
+
+    /// Apply the A-B loop the server responded with, see [`TuiCmd::SetAbLoop`].
+    pub fn ab_loop_changed(&mut self, ab_loop: Option<AbLoop>) {
+        let title = match ab_loop {
+            Some(ab_loop) => format!(
+                "Repeating {} - {}",
+                DurationFmtShort(ab_loop.start),
+                DurationFmtShort(ab_loop.end)
+            ),
+            None => "Loop cleared".to_string(),
+        };
+        self.update_show_message_timeout("A-B Loop", &title, None);
+        self.ab_loop = ab_loop.map_or(AbLoopMark::None, AbLoopMark::Loop);
+        self.progress_update_title();
+    }
+
+    /// Sync the A-B loop with the one of [`GetProgress`](TuiCmd::GetProgress), keeping a start that was only set here.
+    pub fn ab_loop_sync(&mut self, ab_loop: Option<AbLoop>) {
+        if ab_loop.is_none() && matches!(self.ab_loop, AbLoopMark::Start(_)) {
+            return;
+        }
+        self.ab_loop = ab_loop.map_or(AbLoopMark::None, AbLoopMark::Loop);
+        self.progress_update_title();
+    }
+
+    /// Forget the A-B loop, as the server clears it on track change.
+    pub fn ab_loop_track_changed(&mut self) {
+        if self.ab_loop != AbLoopMark::None {
+            self.ab_loop = AbLoopMark::None;
+            self.progress_update_title();
+        }
+    }
+
+    /// Handle all [`SeekToPositionMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_seek_to_position(&mut self, msg: &SeekToPositionMsg) {
+        match msg {
+            SeekToPositionMsg::Show => {
+                if !self.server_capabilities.supports(features::SEEK_TO) {
+                    self.mount_error_popup(anyhow!(
+                        "Seeking to a timestamp is not supported by the server"
+                    ));
+                    return;
+                }
+                if let Err(err) = self.mount_seek_to_position() {
+                    self.mount_error_popup(err.context("show seek to timestamp"));
+                }
+            }
+            SeekToPositionMsg::Cancel => self.umount_seek_to_position(),
+            SeekToPositionMsg::Ok(input) => {
+                self.umount_seek_to_position();
+                match parse_timestamp(input) {
+                    Ok(position) => self.command(TuiCmd::SeekTo(position)),
+                    Err(err) => self.mount_error_popup(err.context("seek to timestamp")),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::parse_timestamp;
+
+    #[test]
+    fn should_parse_timestamps() {
+        assert_eq!(parse_timestamp("83").unwrap(), Duration::from_secs(83));
+        assert_eq!(parse_timestamp("1:23").unwrap(), Duration::from_secs(83));
+        assert_eq!(
+            parse_timestamp(" 01:02:03 ").unwrap(),
+            Duration::from_secs(3723)
+        );
+        assert_eq!(
+            parse_timestamp("0:01.5").unwrap(),
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn should_not_parse_invalid_timestamps() {
+        assert!(parse_timestamp("").is_err());
+        assert!(parse_timestamp("1:a").is_err());
+        assert!(parse_timestamp("1.5:00").is_err());
+        assert!(parse_timestamp("-1").is_err());
+        assert!(parse_timestamp("1:2:3:4").is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! A beat indicator in the progress bar title pulsing with the stored tempo of the current track, see [`BeatIndicator`].
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use termusiclib::new_database::track_ops;
+use termusiclib::player::RunningStatus;
+use tokio::runtime::Handle;
+
+use super::Model;
+use crate::ui::msg::{Msg, PlayerMsg};
+
+/// Taps further apart than this start a new tempo.
+const TAP_RESET: Duration = Duration::from_secs(2);
+/// The taps needed before the tempo is stored.
+const TAPS_NEEDED: usize = 4;
+/// The most recent taps the tempo is averaged over.
+const MAX_TAPS: usize = 16;
+/// The part of a beat the indicator is lit for.
+const PULSE_LENGTH: f64 = 0.25;
+
+/// State of the beat indicator for the current track.
+#[derive(Debug, Default)]
+pub struct BeatIndicator {
+    /// The track the tempo was loaded for
+    track: Option<PathBuf>,
+    /// The tempo of `track` in beats per minute
+    bpm: Option<f32>,
+    /// The position of the first beat, set by tapping
+    first_beat: Duration,
+    /// The last reported position and when it was reported, to know the position in between
+    anchor: (Duration, Option<Instant>),
+    /// The recent taps, reset when they are too far apart
+    taps: Vec<Instant>,
+    /// Whether the indicator is currently lit
+    lit: bool,
+    /// Incremented on each schedule, so that a pending tick of an outdated schedule is ignored
+    generation: u64,
+}
+
+impl BeatIndicator {
+    /// Get the indicator to show in the progress bar title: the tempo and whether it is lit.
+    #[must_use]
+    pub fn title_part(&self) -> Option<(f32, bool)> {
+        self.bpm.map(|bpm| (bpm, self.lit))
+    }
+}
+
+/// Get the tempo in beats per minute from the times of `taps`, [`None`] if there are too few taps.
+fn bpm_from_taps(taps: &[Instant]) -> Option<f32> {
+    if taps.len() < TAPS_NEEDED {
+        return None;
+    }
+    let (first, last) = (taps.first()?, taps.last()?);
+    let intervals = u32::try_from(taps.len() - 1).ok()?;
+
+    let interval = last.duration_since(*first) / intervals;
+    if interval.is_zero() {
+        return None;
+    }
+
+    #[allow(clippy::cast_possible_truncation)] // a tempo never realistically needs f64
+    Some((60.0 / interval.as_secs_f64()) as f32)
+}
+
+/// Get the fraction `0.0..1.0` of the current beat at `position`.
+fn beat_fraction(position: Duration, first_beat: Duration, bpm: f32) -> f64 {
+    let beats = position.abs_diff(first_beat).as_secs_f64() * f64::from(bpm) / 60.0;
+
+    if position >= first_beat {
+        beats.fract()
+    } else {
+        (1.0 - beats.fract()).fract()
+    }
+}
+
+impl Model {
+    /// The playback speed as a factor, `1.0` for normal speed.
+    #[allow(clippy::cast_precision_loss)] // speed is never realisitcally expected to be above i16::MAX
+    fn beat_speed(&self) -> f64 {
+        f64::from(self.config_server.read().settings.player.speed) / 10.0
+    }
+
+    /// Get the current playback position, extrapolated from the last reported one while running.
+    fn beat_position(&self) -> Duration {
+        let (position, at) = self.beat_indicator.anchor;
+        match at {
+            Some(at) if self.playback.status() == RunningStatus::Running => {
+                position + at.elapsed().mul_f64(self.beat_speed())
+            }
+            _ => position,
+        }
+    }
+
+    /// Handle a progress update of the server, to keep the beat in sync with the playback.
+    pub fn beat_indicator_progress(&mut self, position: Duration) {
+        self.beat_indicator.anchor = (position, Some(Instant::now()));
+        self.beat_indicator_tick(self.beat_indicator.generation);
+    }
+
+    /// Reload the tempo if the current track changed, and restart or stop the pulsing.
+    pub fn beat_indicator_update(&mut self) {
+        let track = self
+            .playback
+            .current_track()
+            .and_then(|v| v.path())
+            .map(Path::to_path_buf);
+
+        if track != self.beat_indicator.track {
+            let bpm = track.as_deref().and_then(|path| {
+                track_ops::get_bpm(&self.db.get_connection(), path)
+                    .inspect_err(|err| warn!("Error getting the tempo of the track: {err:#}"))
+                    .ok()
+                    .flatten()
+            });
+
+            self.beat_indicator = BeatIndicator {
+                track,
+                bpm,
+                generation: self.beat_indicator.generation,
+                ..BeatIndicator::default()
+            };
+        }
+        // the running status may have changed, so only extrapolate from now on
+        self.beat_indicator.anchor = (self.playback.current_track_pos(), Some(Instant::now()));
+
+        self.beat_indicator_tick(self.beat_indicator.generation);
+    }
+
+    /// Update whether the indicator is lit and schedule the next change, if `generation` is still current.
+    pub fn beat_indicator_tick(&mut self, generation: u64) {
+        if generation != self.beat_indicator.generation {
+            return;
+        }
+        self.beat_indicator.generation += 1;
+
+        let running = self.playback.status() == RunningStatus::Running;
+        let enabled = self.config_tui.read().settings.beat_indicator.enable;
+        let (Some(bpm), true, true) = (self.beat_indicator.bpm, enabled, running) else {
+            if self.beat_indicator.lit {
+                self.beat_indicator.lit = false;
+                self.progress_update_title();
+            }
+            return;
+        };
+
+        let fraction = beat_fraction(self.beat_position(), self.beat_indicator.first_beat, bpm);
+        let lit = fraction < PULSE_LENGTH;
+        if lit != self.beat_indicator.lit {
+            self.beat_indicator.lit = lit;
+            self.progress_update_title();
+        }
+
+        let until_change = if lit {
+            PULSE_LENGTH - fraction
+        } else {
+            1.0 - fraction
+        };
+        let beat_length = 60.0 / f64::from(bpm) / self.beat_speed();
+        // never spin on a tiny or broken delay
+        let delay = Duration::from_secs_f64((until_change * beat_length).max(0.01));
+
+        let tx = self.tx_to_main.clone();
+        let generation = self.beat_indicator.generation;
+        Handle::current().spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = tx.send(Msg::Player(PlayerMsg::BeatTick(generation)));
+        });
+    }
+
+    /// Handle a tap of the tempo, storing the tapped tempo and aligning the beat to the tap once there are enough taps.
+    pub fn beat_indicator_tap(&mut self) {
+        let Some(path) = self.beat_indicator.track.clone() else {
+            self.mount_error_popup(anyhow::anyhow!(
+                "Only local tracks can have a tempo, not radio streams or podcasts"
+            ));
+            return;
+        };
+
+        let now = Instant::now();
+        let taps = &mut self.beat_indicator.taps;
+        if taps.last().is_some_and(|last| now - *last > TAP_RESET) {
+            taps.clear();
+        }
+        taps.push(now);
+        if taps.len() > MAX_TAPS {
+            taps.remove(0);
+        }
+
+        let Some(tapped) = bpm_from_taps(taps) else {
+            let text = format!("{}/{TAPS_NEEDED} taps", taps.len());
+            self.update_show_message_timeout("Tap tempo", &text, Some(2));
+            return;
+        };
+
+        // the taps follow the sped-up playback, but the tempo of the track is stored
+        #[allow(clippy::cast_possible_truncation)] // a tempo never realistically needs f64
+        let bpm = ((f64::from(tapped) / self.beat_speed() * 10.0).round() / 10.0) as f32;
+        let res = track_ops::set_bpm(&self.db.get_connection(), &path, bpm);
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("set track tempo"));
+            return;
+        }
+
+        self.beat_indicator.bpm = Some(bpm);
+        self.beat_indicator.first_beat = self.beat_position();
+        self.beat_indicator_tick(self.beat_indicator.generation);
+        self.update_show_message_timeout("Tap tempo", &format!("Set to {bpm} BPM"), Some(2));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{beat_fraction, bpm_from_taps};
+
+    #[test]
+    fn should_calculate_bpm_from_taps() {
+        let start = Instant::now();
+        let taps: Vec<Instant> = [0, 500, 1000, 1500]
+            .into_iter()
+            .map(|v| start + Duration::from_millis(v))
+            .collect();
+
+        assert_eq!(bpm_from_taps(&taps[..3]), None);
+        assert_eq!(bpm_from_taps(&taps), Some(120.0));
+        assert_eq!(bpm_from_taps(&[start; 4]), None);
+    }
+
+    #[test]
+    fn should_get_beat_fraction() {
+        let fraction = |pos_ms, first_ms| {
+            beat_fraction(
+                Duration::from_millis(pos_ms),
+                Duration::from_millis(first_ms),
+                120.0,
+            )
+        };
+
+        assert!((fraction(0, 0) - 0.0).abs() < 1e-9);
+        assert!((fraction(1250, 0) - 0.5).abs() < 1e-9);
+        assert!((fraction(1250, 1000) - 0.5).abs() < 1e-9);
+        // before the first beat
+        assert!((fraction(875, 1000) - 0.75).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,118 @@
+//! Named positions in tracks to come back to, see [`bookmark_ops`].
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use termusiclib::new_database::bookmark_ops;
+use termusiclib::player::features;
+use termusiclib::track::DurationFmtShort;
+
+use super::Model;
+use crate::ui::msg::BookmarksMsg;
+use crate::ui::tui_cmd::TuiCmd;
+
+impl Model {
+    /// Handle all [`BookmarksMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_bookmarks(&mut self, msg: BookmarksMsg) {
+        match msg {
+            BookmarksMsg::AddShow => self.bookmark_add_show(),
+            BookmarksMsg::AddCancel => self.umount_bookmark_add(),
+            BookmarksMsg::AddOk(location, position, name) => {
+                self.umount_bookmark_add();
+                self.bookmark_add(&location, position, &name);
+            }
+            BookmarksMsg::Show(index) => self.bookmarks_show(index),
+            BookmarksMsg::Close => self.umount_bookmarks(),
+            BookmarksMsg::Jump(index, position) => {
+                self.umount_bookmarks();
+                self.bookmark_jump(index, position);
+            }
+            BookmarksMsg::Delete(index, id) => {
+                let res = bookmark_ops::remove_bookmark(&self.db.get_connection(), id);
+                if let Err(err) = res {
+                    self.mount_error_popup(err.context("delete bookmark"));
+                    return;
+                }
+                self.bookmarks_show(index);
+            }
+        }
+    }
+
+    /// Show the popup to name a bookmark at the current position of the playing track.
+    fn bookmark_add_show(&mut self) {
+        let Some(track) = self.playback.current_track() else {
+            self.mount_error_popup(anyhow!("Nothing is playing to bookmark"));
+            return;
+        };
+        if track.source().is_live() {
+            self.mount_error_popup(anyhow!("Live streams cannot be bookmarked"));
+            return;
+        }
+        let location = track.source().location().into_owned();
+        let position = self.playback.current_track_pos();
+
+        if let Err(err) = self.mount_bookmark_add(location, position) {
+            self.mount_error_popup(err.context("show add bookmark"));
+        }
+    }
+
+    /// Add a bookmark named `name` at `position` of `location`, named after the position if `name` is empty.
+    fn bookmark_add(&mut self, location: &str, position: Duration, name: &str) {
+        let name = match name.trim() {
+            "" => DurationFmtShort(position).to_string(),
+            name => name.to_string(),
+        };
+
+        let res = bookmark_ops::add_bookmark(&self.db.get_connection(), location, &name, position);
+        match res {
+            Ok(_) => self.update_show_message_timeout(
+                "Bookmarks",
+                &format!("Added \"{name}\" at {}", DurationFmtShort(position)),
+                None,
+            ),
+            Err(err) => self.mount_error_popup(err.context("add bookmark")),
+        }
+    }
+
+    /// Show the bookmarks of the track at `index` in the playlist.
+    fn bookmarks_show(&mut self, index: usize) {
+        let Some(track) = self.playback.playlist.tracks().get(index) else {
+            return;
+        };
+        let location = track.source().location().into_owned();
+
+        let res = bookmark_ops::get_bookmarks(&self.db.get_connection(), &location);
+        let bookmarks = match res {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(err.context("get bookmarks"));
+                return;
+            }
+        };
+        if bookmarks.is_empty() {
+            self.umount_bookmarks();
+            self.update_show_message_timeout("Bookmarks", "This track has no bookmarks", None);
+            return;
+        }
+
+        if let Err(err) = self.mount_bookmarks(index, bookmarks) {
+            self.mount_error_popup(err.context("show bookmarks"));
+        }
+    }
+
+    /// Seek to `position` of the track at `index` in the playlist, playing it first if it is not the current track.
+    fn bookmark_jump(&mut self, index: usize, position: Duration) {
+        if !self.server_capabilities.supports(features::SEEK_TO) {
+            self.mount_error_popup(anyhow!(
+                "Jumping to a bookmark is not supported by the server"
+            ));
+            return;
+        }
+
+        if self.playback.playlist.current_track_index() != Some(index) {
+            // the server handles commands in order, so the seek applies to the newly started track
+            self.playlist_play_selected(index);
+        }
+        self.command(TuiCmd::SeekTo(position));
+    }
+}
@@ -0,0 +1,231 @@
+//! The credits of the currently playing track, to jump to one of its artists in the database view.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use termusiclib::track::{MetadataOptions, TrackMetadata, parse_metadata_from_file};
+use tuirealm::props::{PropPayload, PropValue};
+use tuirealm::{AttrValue, Attribute};
+
+use super::Model;
+use crate::ui::ids::Id;
+use crate::ui::msg::{CreditsMsg, MainLayoutMsg, SearchCriteria};
+
+/// The role a artist is credited for on a track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreditRole {
+    /// The main artist
+    Artist,
+    /// Any further artist, like `ArtistB` in `ArtistA feat. ArtistB`
+    Featured,
+    Composer,
+    Performer,
+}
+
+impl CreditRole {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Artist => "Artist",
+            Self::Featured => "Featured",
+            Self::Composer => "Composer",
+            Self::Performer => "Performer",
+        }
+    }
+
+    /// Get the database criteria to browse a artist of this role by, [`None`] if it can only be searched for.
+    #[must_use]
+    pub const fn criteria(self) -> Option<SearchCriteria> {
+        match self {
+            Self::Artist | Self::Featured => Some(SearchCriteria::Artist),
+            Self::Composer => Some(SearchCriteria::Composer),
+            Self::Performer => None,
+        }
+    }
+}
+
+/// A single credited artist of a track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credit {
+    pub role: CreditRole,
+    pub name: String,
+}
+
+impl Credit {
+    fn new(role: CreditRole, name: &str) -> Self {
+        Self {
+            role,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Get all credits from `metadata`, in the order of artist, featured artists, composer and performers.
+///
+/// Performers that are already credited as a artist are not repeated.
+#[must_use]
+pub fn credits_from_metadata(metadata: &TrackMetadata) -> Vec<Credit> {
+    let mut credits = Vec::new();
+
+    let artists = metadata.artists.as_deref().unwrap_or_default();
+    if let Some((first, rest)) = artists.split_first() {
+        credits.push(Credit::new(CreditRole::Artist, first));
+        credits.extend(rest.iter().map(|v| Credit::new(CreditRole::Featured, v)));
+    } else if let Some(artist) = &metadata.artist {
+        credits.push(Credit::new(CreditRole::Artist, artist));
+    }
+
+    if let Some(composer) = &metadata.composer {
+        credits.push(Credit::new(CreditRole::Composer, composer));
+    }
+
+    for performer in metadata.performers.as_deref().unwrap_or_default() {
+        if !credits.iter().any(|v| v.name == *performer) {
+            credits.push(Credit::new(CreditRole::Performer, performer));
+        }
+    }
+
+    credits
+}
+
+impl Model {
+    /// Handle all [`CreditsMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_credits(&mut self, msg: CreditsMsg) {
+        match msg {
+            CreditsMsg::Show => {
+                if let Err(err) = self.credits_show() {
+                    self.mount_error_popup(err.context("show credits"));
+                }
+            }
+            CreditsMsg::Close => self.umount_credits(),
+            CreditsMsg::Jump(credit) => {
+                self.umount_credits();
+                self.credits_jump(credit);
+            }
+        }
+    }
+
+    /// Read the credits of the currently playing track and show them.
+    fn credits_show(&mut self) -> Result<()> {
+        let path = self
+            .playback
+            .current_track()
+            .ok_or_else(|| anyhow!("No track is playing"))?
+            .path()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| {
+                anyhow!("Only local tracks have credits, not radio streams or podcasts")
+            })?;
+
+        let separators = self
+            .config_server
+            .read()
+            .settings
+            .metadata
+            .artist_separators
+            .clone();
+        let separators: Vec<&str> = separators.iter().map(String::as_str).collect();
+        let metadata = parse_metadata_from_file(
+            &path,
+            MetadataOptions {
+                artist: true,
+                artists: true,
+                artist_separators: &separators,
+                composer: true,
+                performer: true,
+                ..Default::default()
+            },
+        )
+        .with_context(|| path.display().to_string())?;
+
+        self.mount_credits(credits_from_metadata(&metadata))
+    }
+
+    /// Jump to `credit` in the database view, or search for it if it cannot be browsed there.
+    fn credits_jump(&mut self, credit: Credit) {
+        let Some(criteria) = credit.role.criteria() else {
+            self.database_search_for(&credit.name);
+            return;
+        };
+
+        self.update_layout(MainLayoutMsg::DataBase);
+        self.dw.jump_to = Some(credit.name);
+        self.database_select_criteria(criteria);
+    }
+
+    /// Select the result `name` once the results of the criteria jumped to are loaded, see [`Self::credits_jump`].
+    ///
+    /// If `name` is not in the database, like for not yet scanned tracks, it is searched for instead.
+    pub fn database_apply_jump(&mut self, name: &str) {
+        let Some(index) = self.dw.search_results.iter().position(|v| v == name) else {
+            self.database_search_for(name);
+            return;
+        };
+
+        self.app
+            .attr(
+                &Id::DBListSearchResult,
+                Attribute::Value,
+                AttrValue::Payload(PropPayload::One(PropValue::Usize(index))),
+            )
+            .ok();
+        self.database_update_search_tracks(index);
+    }
+
+    /// Open the database search popup, searching for `name`.
+    fn database_search_for(&mut self, name: &str) {
+        self.mount_search_database();
+        self.database_load_general_search();
+        self.app
+            .attr(
+                &Id::GeneralSearchInput,
+                Attribute::Value,
+                AttrValue::String(name.to_string()),
+            )
+            .ok();
+        self.database_update_search(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::track::TrackMetadata;
+
+    use super::{Credit, CreditRole, credits_from_metadata};
+
+    #[test]
+    fn should_collect_credits() {
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA feat. ArtistB".to_string()),
+            artists: Some(vec!["ArtistA".to_string(), "ArtistB".to_string()]),
+            composer: Some("ComposerA".to_string()),
+            performers: Some(vec!["ArtistB".to_string(), "SoloistA".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            credits_from_metadata(&metadata),
+            [
+                Credit::new(CreditRole::Artist, "ArtistA"),
+                Credit::new(CreditRole::Featured, "ArtistB"),
+                Credit::new(CreditRole::Composer, "ComposerA"),
+                Credit::new(CreditRole::Performer, "SoloistA"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fallback_to_single_artist() {
+        let metadata = TrackMetadata {
+            artist: Some("ArtistA".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            credits_from_metadata(&metadata),
+            [Credit::new(CreditRole::Artist, "ArtistA")]
+        );
+        assert_eq!(credits_from_metadata(&TrackMetadata::default()), []);
+    }
+}
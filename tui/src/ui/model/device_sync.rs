@@ -0,0 +1,127 @@
+//! Syncing library nodes and the playlist to a device, see [`termusiclib::device_sync`].
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use termusiclib::device_sync::{SyncReport, sync_to_device};
+use termusiclib::utils::filetype_supported;
+use tokio::runtime::Handle;
+
+use super::Model;
+use crate::ui::msg::{DeviceSyncMsg, Msg};
+
+impl Model {
+    /// Handle all [`DeviceSyncMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_device_sync(&mut self, msg: DeviceSyncMsg) {
+        let res = match msg {
+            DeviceSyncMsg::SyncPath(path) => self.device_sync_path(&path),
+            DeviceSyncMsg::SyncPlaylist => self.device_sync_playlist(),
+            DeviceSyncMsg::Done(res) => {
+                self.device_sync_done(res);
+                Ok(())
+            }
+        };
+
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("device sync"));
+        }
+    }
+
+    /// Sync the track at `path`, or all tracks inside of it if it is a directory.
+    fn device_sync_path(&mut self, path: &Path) -> Result<()> {
+        let sources: Vec<PathBuf> = walkdir::WalkDir::new(path)
+            .follow_links(true)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| entry.file_type().is_file() && filetype_supported(entry.path()))
+            .map(walkdir::DirEntry::into_path)
+            .collect();
+
+        self.device_sync_start(sources)
+    }
+
+    /// Sync all local tracks in the playlist.
+    fn device_sync_playlist(&mut self) -> Result<()> {
+        let sources = self
+            .playback
+            .playlist
+            .tracks()
+            .iter()
+            .filter_map(|track| track.path().map(Path::to_path_buf))
+            .collect();
+
+        self.device_sync_start(sources)
+    }
+
+    /// Start syncing `sources` in the background, [`DeviceSyncMsg::Done`] is sent once finished.
+    fn device_sync_start(&mut self, sources: Vec<PathBuf>) -> Result<()> {
+        if self.device_sync_active {
+            bail!("A device sync is already running");
+        }
+        if sources.is_empty() {
+            bail!("Nothing to sync");
+        }
+
+        let settings = self.config_tui.read().settings.device_sync.clone();
+        if settings.device_path.is_none() {
+            bail!("No device path configured (\"device_sync.device_path\")");
+        }
+        let music_dirs: Vec<PathBuf> = self
+            .config_server
+            .read()
+            .settings
+            .player
+            .music_dirs
+            .iter()
+            .map(|dir| shellexpand::path::tilde(dir).into_owned())
+            .collect();
+
+        self.device_sync_active = true;
+        self.update_show_message_timeout(
+            "Device sync",
+            &format!("Syncing {} tracks", sources.len()),
+            None,
+        );
+
+        let tx = self.tx_to_main.clone();
+        Handle::current().spawn_blocking(move || {
+            let res =
+                sync_to_device(&sources, &music_dirs, &settings).map_err(|err| format!("{err:#}"));
+            let _ = tx.send(Msg::DeviceSync(DeviceSyncMsg::Done(res)));
+        });
+
+        Ok(())
+    }
+
+    /// Report the result of a finished sync.
+    fn device_sync_done(&mut self, res: Result<SyncReport, String>) {
+        self.device_sync_active = false;
+
+        let report = match res {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(anyhow::anyhow!(err).context("device sync"));
+                return;
+            }
+        };
+
+        for (path, err) in &report.failed {
+            error!("Error syncing {} to the device: {err}", path.display());
+        }
+
+        let text = format!(
+            "{} copied, {} transcoded, {} up-to-date",
+            report.copied, report.transcoded, report.skipped
+        );
+        if report.failed.is_empty() {
+            self.update_show_message_timeout("Device sync finished", &text, None);
+        } else {
+            self.mount_error_popup(anyhow::anyhow!(
+                "{text}, {} failed (see the log), first error: {}",
+                report.failed.len(),
+                report.failed[0].1
+            ));
+        }
+    }
+}
@@ -1,50 +1,98 @@
 use std::{collections::HashSet, sync::Arc};
 
 use parking_lot::RwLock;
+use termusiclib::player::ActivityInfo;
 
-/// A way to keep track of what downloads are currently happening
+/// A way to keep track of what downloads and library scans are currently happening
 ///
-/// As long as items is above 0, a Download-spinner should play
-#[derive(Debug, Clone)]
+/// As long as any item is tracked, a Download-spinner should play
+#[derive(Debug, Clone, Default)]
 pub struct DownloadTracker {
-    items: Arc<RwLock<HashSet<String>>>,
+    inner: Arc<RwLock<TrackerInner>>,
 }
 
-impl Default for DownloadTracker {
-    fn default() -> Self {
-        let items = HashSet::new();
-        let items = Arc::new(RwLock::new(items));
-        Self { items }
-    }
+#[derive(Debug, Default)]
+struct TrackerInner {
+    items: HashSet<String>,
+    scans: HashSet<String>,
+    /// The downloads finished since the first of the current ones started
+    done: u32,
+    /// The downloads failed since the first of the current ones started
+    failed: u32,
+    last_error: Option<String>,
 }
 
 #[allow(dead_code)]
 impl DownloadTracker {
     /// Add a new entry with key `url`
     pub fn increase_one<U: Into<String>>(&self, url: U) {
-        self.items.write().insert(url.into());
+        let mut inner = self.inner.write();
+        // a new batch of downloads, forget the previous one
+        if inner.items.is_empty() {
+            inner.done = 0;
+            inner.failed = 0;
+            inner.last_error = None;
+        }
+        inner.items.insert(url.into());
     }
 
     /// Remove a entry with key `url`
     pub fn decrease_one(&self, url: &str) {
-        self.items.write().remove(url);
+        let mut inner = self.inner.write();
+        if inner.items.remove(url) {
+            inner.done += 1;
+        }
+    }
+
+    /// Remove a entry with key `url` which failed with `error`
+    pub fn fail_one<E: Into<String>>(&self, url: &str, error: E) {
+        let mut inner = self.inner.write();
+        if inner.items.remove(url) {
+            inner.done += 1;
+            inner.failed += 1;
+            inner.last_error = Some(error.into());
+        }
+    }
+
+    /// Add a library scan of `path`
+    pub fn increase_scan<P: Into<String>>(&self, path: P) {
+        self.inner.write().scans.insert(path.into());
+    }
+
+    /// Remove a library scan of `path`
+    pub fn decrease_scan(&self, path: &str) {
+        self.inner.write().scans.remove(path);
     }
 
     pub fn contains(&self, url: &str) -> bool {
-        self.items.read().contains(url)
+        self.inner.read().items.contains(url)
     }
 
     /// Should the download spinner be visible?
     pub fn visible(&self) -> bool {
-        !self.items.read().is_empty()
+        let inner = self.inner.read();
+        !inner.items.is_empty() || !inner.scans.is_empty()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.items.read().is_empty()
+        self.inner.read().items.is_empty()
     }
 
+    /// The amount of running downloads, without scans
     pub fn len(&self) -> usize {
-        self.items.read().len()
+        self.inner.read().items.len()
+    }
+
+    /// Get the current activity, to report it to the server.
+    pub fn activity(&self) -> ActivityInfo {
+        let inner = self.inner.read();
+        ActivityInfo {
+            downloads: u32::try_from(inner.items.len()).unwrap_or(u32::MAX),
+            downloads_done: inner.done,
+            downloads_failed: inner.failed,
+            last_error: inner.last_error.clone(),
+            scans: u32::try_from(inner.scans.len()).unwrap_or(u32::MAX),
+        }
     }
 
     pub fn message_sync_success(&self) -> String {
@@ -73,7 +121,7 @@ impl DownloadTracker {
     pub fn message_feed_sync_failed(&self) -> String {
         let len = self.len();
         if len > 0 {
-            format!(" 1 feed sync failed. {len} are still running. ",)
+            format!(" 1 feed sync failed. {len} are still running. ")
         } else {
             " 1 feed sync failed. ".to_string()
         }
@@ -82,7 +130,7 @@ impl DownloadTracker {
     pub fn message_sync_start(&self) -> String {
         let len = self.len();
         if len > 1 {
-            format!(" {len} feeds are being fetching... ",)
+            format!(" {len} feeds are being fetching... ")
         } else {
             " 1 feed is being fetching... ".to_string()
         }
@@ -93,7 +141,7 @@ impl DownloadTracker {
         if len > 1 {
             format!(" {len} items downloading... ")
         } else {
-            format!(" {len} item {title:^.20} downloading...",)
+            format!(" {len} item {title:^.20} downloading...")
         }
     }
 
@@ -154,3 +202,33 @@ impl DownloadTracker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::DownloadTracker;
+
+    #[test]
+    fn should_track_batch_progress() {
+        let tracker = DownloadTracker::default();
+        tracker.increase_one("a");
+        tracker.increase_one("b");
+        tracker.increase_scan("/music");
+        tracker.decrease_one("a");
+        assert_eq!(tracker.len(), 1);
+        assert_eq!(
+            tracker.activity().to_string(),
+            "downloading 1 item, 50%, scanning the library"
+        );
+
+        tracker.fail_one("b", "no response");
+        tracker.decrease_scan("/music");
+        assert!(!tracker.visible());
+        // the failure is kept until the next downloads start
+        assert_eq!(tracker.activity().to_string(), "1 failed: no response");
+
+        tracker.increase_one("c");
+        assert_eq!(tracker.activity().to_string(), "downloading 1 item, 0%");
+    }
+}
@@ -0,0 +1,167 @@
+//! The download queue of the server, see [`features::DOWNLOAD_QUEUE`].
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use termusiclib::downloads::{DownloadInfo, DownloadStatus};
+use termusiclib::player::features;
+
+use super::Model;
+use super::youtube_options::YTDLMsg;
+use crate::ui::ids::Id;
+use crate::ui::msg::DownloadsMsg;
+use crate::ui::tui_cmd::{DownloadCmd, TuiCmd};
+
+/// Update `downloads` with `info`, returning the previous state of the download, none if it is new.
+///
+/// Done and cancelled downloads are removed.
+fn apply_download_change(
+    downloads: &mut Vec<DownloadInfo>,
+    info: DownloadInfo,
+) -> Option<DownloadStatus> {
+    let idx = downloads.iter().position(|v| v.id == info.id);
+    let previous = idx.map(|idx| downloads[idx].status);
+
+    match idx {
+        Some(idx) if info.status.is_finished() => {
+            downloads.remove(idx);
+        }
+        Some(idx) => downloads[idx] = info,
+        None if info.status.is_finished() => (),
+        None => downloads.push(info),
+    }
+
+    previous
+}
+
+impl Model {
+    /// Handle all [`DownloadsMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_downloads(&mut self, msg: DownloadsMsg) {
+        match msg {
+            DownloadsMsg::Show => {
+                if !self.server_capabilities.supports(features::DOWNLOAD_QUEUE) {
+                    self.mount_error_popup(anyhow!(
+                        "The server does not support a download queue, downloads run in the TUI instead"
+                    ));
+                    return;
+                }
+                if let Err(err) = self.mount_downloads() {
+                    self.mount_error_popup(err.context("show downloads"));
+                }
+                self.command(TuiCmd::Download(DownloadCmd::List));
+            }
+            DownloadsMsg::Close => self.umount_downloads(),
+            DownloadsMsg::Control(id, action) => {
+                self.command(TuiCmd::Download(DownloadCmd::Control(id, action)));
+            }
+        }
+    }
+
+    /// Replace the known download queue with `downloads` from the server.
+    pub fn downloads_listed(&mut self, downloads: Vec<DownloadInfo>) {
+        self.server_downloads = downloads;
+        self.downloads_update_popup();
+    }
+
+    /// Handle a changed download of the server queue, reporting it like a download of the TUI.
+    pub fn downloads_changed(&mut self, info: DownloadInfo) {
+        let url: Arc<str> = Arc::from(info.url.as_str());
+        let title = info.title.clone();
+        let status = info.status;
+        let error = info.error.clone();
+        let file = info.file.clone();
+
+        let previous = apply_download_change(&mut self.server_downloads, info);
+        match status {
+            DownloadStatus::Pending | DownloadStatus::Active
+                if previous.is_none_or(|v| {
+                    matches!(v, DownloadStatus::Failed | DownloadStatus::Paused)
+                }) =>
+            {
+                self.update_ys_download_msg(YTDLMsg::Start(url, title));
+            }
+            DownloadStatus::Done => {
+                self.update_ys_download_msg(YTDLMsg::Success(url.clone()));
+                self.update_ys_download_msg(YTDLMsg::Completed(
+                    url,
+                    file.map(|v| v.to_string_lossy().into_owned()),
+                ));
+            }
+            DownloadStatus::Failed if previous != Some(DownloadStatus::Failed) => {
+                self.update_ys_download_msg(YTDLMsg::Err(url, title, error.unwrap_or_default()));
+            }
+            DownloadStatus::Paused | DownloadStatus::Cancelled => {
+                self.download_tracker.decrease_one(&url);
+            }
+            _ => (),
+        }
+
+        self.downloads_update_popup();
+    }
+
+    /// Show the current download queue in the [`DownloadsPopup`](crate::ui::components::DownloadsPopup), if mounted.
+    fn downloads_update_popup(&mut self) {
+        if !self.app.mounted(&Id::DownloadsPopup) {
+            return;
+        }
+        if let Err(err) = self.mount_downloads() {
+            error!("Error updating the downloads popup: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::downloads::{DownloadInfo, DownloadStatus};
+
+    use super::apply_download_change;
+
+    fn info(id: u64, status: DownloadStatus) -> DownloadInfo {
+        DownloadInfo {
+            id,
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            title: String::new(),
+            status,
+            progress: 0,
+            attempts: 0,
+            error: None,
+            file: None,
+        }
+    }
+
+    #[test]
+    fn should_track_downloads() {
+        let mut downloads = Vec::new();
+
+        assert_eq!(
+            apply_download_change(&mut downloads, info(1, DownloadStatus::Pending)),
+            None
+        );
+        assert_eq!(
+            apply_download_change(&mut downloads, info(2, DownloadStatus::Pending)),
+            None
+        );
+        assert_eq!(
+            apply_download_change(&mut downloads, info(1, DownloadStatus::Active)),
+            Some(DownloadStatus::Pending)
+        );
+        assert_eq!(
+            downloads,
+            [
+                info(1, DownloadStatus::Active),
+                info(2, DownloadStatus::Pending)
+            ]
+        );
+
+        assert_eq!(
+            apply_download_change(&mut downloads, info(1, DownloadStatus::Done)),
+            Some(DownloadStatus::Active)
+        );
+        assert_eq!(
+            apply_download_change(&mut downloads, info(3, DownloadStatus::Cancelled)),
+            None
+        );
+        assert_eq!(downloads, [info(2, DownloadStatus::Pending)]);
+    }
+}
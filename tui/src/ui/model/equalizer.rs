@@ -0,0 +1,126 @@
+//! The equalizer of the server, see [`features::EQUALIZER`].
+
+use anyhow::anyhow;
+use termusiclib::config::v2::server::equalizer::{
+    BANDS, EqualizerPreset, EqualizerSettings, GAIN_MAX_DB,
+};
+use termusiclib::player::features;
+
+use super::Model;
+use crate::ui::ids::Id;
+use crate::ui::msg::EqualizerMsg;
+use crate::ui::tui_cmd::{BackendCmd, TuiCmd};
+
+/// Change the gain of `band` by `delta` dB, switching to the custom preset starting from the current gains.
+///
+/// Changing a band also enables the equalizer, as the change could not be heard otherwise.
+fn adjust_band(settings: &mut EqualizerSettings, band: usize, delta: i8) {
+    if settings.preset != EqualizerPreset::Custom {
+        settings.custom = settings.gains();
+        settings.preset = EqualizerPreset::Custom;
+    }
+    if let Some(gain) = settings.custom.get_mut(band) {
+        *gain = (*gain + f32::from(delta)).clamp(-GAIN_MAX_DB, GAIN_MAX_DB);
+    }
+    settings.enabled = true;
+}
+
+impl Model {
+    /// Handle all [`EqualizerMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_equalizer(&mut self, msg: EqualizerMsg) {
+        match msg {
+            EqualizerMsg::Show => {
+                if !self.server_capabilities.supports(features::EQUALIZER) {
+                    self.mount_error_popup(anyhow!("The equalizer is not supported by the server"));
+                    return;
+                }
+                if let Err(err) = self.mount_equalizer() {
+                    self.mount_error_popup(err.context("show equalizer"));
+                }
+            }
+            EqualizerMsg::Close => self.umount_equalizer(),
+            EqualizerMsg::SelectBand(band) => {
+                self.equalizer_band = band.min(BANDS - 1);
+                self.equalizer_update_popup();
+            }
+            EqualizerMsg::Adjust(delta) => {
+                adjust_band(&mut self.equalizer, self.equalizer_band, delta);
+                self.equalizer_send();
+            }
+            EqualizerMsg::NextPreset => {
+                self.equalizer.preset = self.equalizer.preset.next();
+                self.equalizer.enabled = true;
+                self.equalizer_send();
+            }
+            EqualizerMsg::ToggleEnabled => {
+                self.equalizer.enabled = !self.equalizer.enabled;
+                self.equalizer_send();
+            }
+        }
+    }
+
+    /// Send the current equalizer to the server, showing it already before the server responded.
+    fn equalizer_send(&mut self) {
+        self.command(TuiCmd::Backend(BackendCmd::SetEqualizer(
+            self.equalizer.clone(),
+        )));
+        self.equalizer_update_popup();
+    }
+
+    /// Handle the equalizer the server applied.
+    pub fn equalizer_applied(&mut self, applied: EqualizerSettings) {
+        if self.equalizer.enabled && !applied.enabled {
+            self.update_show_message_timeout(
+                "Equalizer",
+                "The equalizer is not supported by the server's backend",
+                None,
+            );
+        }
+        self.equalizer = applied;
+        self.equalizer_update_popup();
+    }
+
+    /// Show the current equalizer in the equalizer popup, if mounted.
+    fn equalizer_update_popup(&mut self) {
+        if !self.app.mounted(&Id::EqualizerPopup) {
+            return;
+        }
+        if let Err(err) = self.mount_equalizer() {
+            error!("Error updating the equalizer popup: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::config::v2::server::equalizer::{EqualizerPreset, EqualizerSettings};
+
+    use super::adjust_band;
+
+    #[test]
+    fn should_adjust_from_preset() {
+        let mut settings = EqualizerSettings {
+            enabled: false,
+            preset: EqualizerPreset::Rock,
+            ..Default::default()
+        };
+
+        adjust_band(&mut settings, 0, 1);
+        let mut expected = EqualizerPreset::Rock.gains();
+        expected[0] += 1.0;
+        assert_eq!(
+            settings,
+            EqualizerSettings {
+                enabled: true,
+                preset: EqualizerPreset::Custom,
+                custom: expected,
+            }
+        );
+
+        for _ in 0..20 {
+            adjust_band(&mut settings, 0, 1);
+        }
+        assert_eq!(settings.custom[0].to_bits(), 12.0_f32.to_bits());
+    }
+}
@@ -0,0 +1,366 @@
+//! The global search popup, searching database tracks, saved playlists and podcast episodes at once.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use termusiclib::utils::is_playlist;
+use tokio::runtime::Handle;
+use tuirealm::props::{TableBuilder, TextSpan};
+use tuirealm::{State, StateValue};
+
+use super::Model;
+use crate::ui::components::SearchMatcher;
+use crate::ui::ids::Id;
+use crate::ui::msg::{GSMsg, MainLayoutMsg, Msg};
+
+/// The maximal amount of results shown per category, to keep the table responsive for big libraries.
+const CATEGORY_LIMIT: usize = 200;
+
+/// Data for the global search popup, while it is open.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlobalSearchData {
+    /// The last search input
+    input: String,
+    /// Playlist files found in the music directories, [`None`] while still loading
+    playlists: Option<Vec<PathBuf>>,
+    /// What each row of the result table refers to, [`None`] for category headers and placeholders
+    rows: Vec<Option<GlobalSearchResult>>,
+}
+
+/// A single result of the global search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GlobalSearchResult {
+    Track(PathBuf),
+    Playlist(PathBuf),
+    /// The id of a podcast episode
+    Episode(i64),
+}
+
+/// Helper to build the result table with category headers.
+#[derive(Default)]
+struct ResultTable {
+    table: TableBuilder,
+    rows: Vec<Option<GlobalSearchResult>>,
+}
+
+impl ResultTable {
+    /// Add a category header, followed by the given results or a placeholder if `results` is empty.
+    ///
+    /// `results` are only added up to [`CATEGORY_LIMIT`], `total` is the amount of all matches.
+    fn add_category<I>(&mut self, name: &str, total: usize, results: I)
+    where
+        I: Iterator<Item = (String, String, GlobalSearchResult)>,
+    {
+        let header = if total > CATEGORY_LIMIT {
+            format!("── {name} ({CATEGORY_LIMIT} of {total}) ──")
+        } else {
+            format!("── {name} ({total}) ──")
+        };
+        self.push_row(
+            [
+                TextSpan::new(""),
+                TextSpan::new(header).bold(),
+                TextSpan::new(""),
+            ],
+            None,
+        );
+
+        let mut empty = true;
+        for (idx, (name, location, result)) in results.take(CATEGORY_LIMIT).enumerate() {
+            empty = false;
+            self.push_row(
+                [
+                    TextSpan::new((idx + 1).to_string()),
+                    TextSpan::new(name),
+                    TextSpan::new(location),
+                ],
+                Some(result),
+            );
+        }
+
+        if empty {
+            self.push_row(
+                [
+                    TextSpan::new(""),
+                    TextSpan::new("No results").italic(),
+                    TextSpan::new(""),
+                ],
+                None,
+            );
+        }
+    }
+
+    /// Add a category header for a category whose data is still loading.
+    fn add_loading(&mut self, name: &str) {
+        self.push_row(
+            [
+                TextSpan::new(""),
+                TextSpan::new(format!("── {name} (loading...) ──")).bold(),
+                TextSpan::new(""),
+            ],
+            None,
+        );
+    }
+
+    fn push_row(&mut self, cols: [TextSpan; 3], result: Option<GlobalSearchResult>) {
+        if !self.rows.is_empty() {
+            self.table.add_row();
+        }
+        for col in cols {
+            self.table.add_col(col);
+        }
+        self.rows.push(result);
+    }
+}
+
+impl Model {
+    /// Open the global search popup and start loading its data.
+    pub fn global_search_show(&mut self) {
+        self.mount_search_global();
+        self.global_search = Some(GlobalSearchData::default());
+        self.database_load_general_search();
+        self.global_search_load_playlists();
+        self.global_search_update("");
+    }
+
+    /// Find all playlist files in the music directories in the background.
+    fn global_search_load_playlists(&self) {
+        let music_dirs: Vec<PathBuf> = self
+            .config_server
+            .read()
+            .settings
+            .player
+            .music_dirs
+            .iter()
+            .map(|dir| shellexpand::path::tilde(dir).into_owned())
+            .collect();
+
+        let tx = self.tx_to_main.clone();
+        Handle::current().spawn_blocking(move || {
+            let playlists = music_dirs
+                .iter()
+                .flat_map(|dir| walkdir::WalkDir::new(dir).follow_links(true))
+                .filter_map(std::result::Result::ok)
+                .filter(|entry| entry.file_type().is_file() && is_playlist(entry.path()))
+                .map(walkdir::DirEntry::into_path)
+                .collect();
+
+            let _ = tx.send(Msg::GeneralSearch(GSMsg::GlobalPlaylistsReady(playlists)));
+        });
+    }
+
+    /// Apply the found playlists, if the global search is still open.
+    pub fn global_search_apply_playlists(&mut self, playlists: Vec<PathBuf>) {
+        let Some(data) = self.global_search.as_mut() else {
+            return;
+        };
+        data.playlists = Some(playlists);
+
+        self.global_search_refresh();
+    }
+
+    /// Re-run the global search with the last input, for example after some data finished loading.
+    pub fn global_search_refresh(&mut self) {
+        if !self.app.mounted(&Id::GeneralSearchTable) {
+            return;
+        }
+        let Some(input) = self.global_search.as_ref().map(|v| v.input.clone()) else {
+            return;
+        };
+
+        self.global_search_update(&input);
+    }
+
+    /// Generate the result table for search `input`.
+    pub fn global_search_update(&mut self, input: &str) {
+        let Some(data) = self.global_search.as_mut() else {
+            return;
+        };
+        input.clone_into(&mut data.input);
+
+        let search = SearchMatcher::new(self.general_search_mode, input);
+        let mut table = ResultTable::default();
+
+        if let Some(tracks) = self.dw.general_search_tracks.as_deref() {
+            let matches: Vec<_> = tracks
+                .iter()
+                .filter(|track| {
+                    track.title.as_deref().is_some_and(|v| search.matches(v))
+                        || track
+                            .artist_display
+                            .as_deref()
+                            .is_some_and(|v| search.matches(v))
+                        || track
+                            .album
+                            .as_ref()
+                            .is_some_and(|v| search.matches(&v.title))
+                })
+                .collect();
+            table.add_category(
+                "Tracks",
+                matches.len(),
+                matches.into_iter().map(|track| {
+                    let path = track.as_pathbuf();
+                    let name = track.title.clone().unwrap_or_else(|| file_name(&path));
+                    let artist = track.artist_display.clone().unwrap_or_default();
+                    (name, artist, GlobalSearchResult::Track(path))
+                }),
+            );
+        } else {
+            table.add_loading("Tracks");
+        }
+
+        if let Some(playlists) = data.playlists.as_deref() {
+            let matches: Vec<_> = playlists
+                .iter()
+                .filter(|path| search.matches(&file_name(path)))
+                .collect();
+            table.add_category(
+                "Playlists",
+                matches.len(),
+                matches.into_iter().map(|path| {
+                    let location = path
+                        .parent()
+                        .map(|v| v.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    (
+                        file_name(path),
+                        location,
+                        GlobalSearchResult::Playlist(path.clone()),
+                    )
+                }),
+            );
+        } else {
+            table.add_loading("Playlists");
+        }
+
+        let matches: Vec<_> = self
+            .podcast
+            .podcasts
+            .iter()
+            .flat_map(|podcast| podcast.episodes.iter().map(move |ep| (podcast, ep)))
+            .filter(|(_, ep)| search.matches(&ep.title))
+            .collect();
+        table.add_category(
+            "Podcast Episodes",
+            matches.len(),
+            matches.into_iter().map(|(podcast, ep)| {
+                (
+                    ep.title.clone(),
+                    podcast.title.clone(),
+                    GlobalSearchResult::Episode(ep.id),
+                )
+            }),
+        );
+
+        data.rows = table.rows;
+        self.general_search_update_show(table.table.build());
+    }
+
+    /// Get the result for the selected row, [`None`] if it is a header.
+    fn global_search_selected(&self) -> Option<GlobalSearchResult> {
+        let Ok(State::One(StateValue::Usize(index))) = self.app.state(&Id::GeneralSearchTable)
+        else {
+            return None;
+        };
+
+        self.global_search
+            .as_ref()?
+            .rows
+            .get(index)
+            .cloned()
+            .flatten()
+    }
+
+    /// Add the selected result to the playlist.
+    pub fn global_search_add_playlist(&mut self) -> Result<()> {
+        match self.global_search_selected() {
+            Some(GlobalSearchResult::Track(path) | GlobalSearchResult::Playlist(path)) => {
+                self.playlist_add(&path)?;
+            }
+            Some(GlobalSearchResult::Episode(id)) => {
+                let (_podcast_idx, episode_idx) = self.global_search_find_episode(id)?;
+                self.playlist_add_episode(episode_idx)?;
+            }
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    /// Close the popup and show the selected result in its view.
+    ///
+    /// Returns `false` if nothing could be located, like when a header is selected.
+    pub fn global_search_locate(&mut self) -> Result<bool> {
+        match self.global_search_selected() {
+            Some(GlobalSearchResult::Track(path) | GlobalSearchResult::Playlist(path)) => {
+                self.update_layout(MainLayoutMsg::TreeView);
+                let node = path.to_string_lossy().to_string();
+                self.new_library_scan_dir(path, Some(node));
+            }
+            Some(GlobalSearchResult::Episode(id)) => {
+                let (podcast_idx, episode_idx) = self.global_search_find_episode(id)?;
+                self.update_layout(MainLayoutMsg::Podcast);
+                self.podcast_locate_episode(podcast_idx, episode_idx);
+            }
+            None => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Find the podcast and episode index for the episode `id`.
+    fn global_search_find_episode(&mut self, id: i64) -> Result<(usize, usize)> {
+        let id = usize::try_from(id).context("episode id")?;
+        self.podcast_find_by_ep_id(id)
+    }
+}
+
+/// Get the file name of `path` for display.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{CATEGORY_LIMIT, GlobalSearchResult, ResultTable};
+
+    #[test]
+    fn should_add_categories_with_headers() {
+        let mut table = ResultTable::default();
+        table.add_category(
+            "Tracks",
+            CATEGORY_LIMIT + 1,
+            (0..=CATEGORY_LIMIT).map(|idx| {
+                (
+                    idx.to_string(),
+                    String::new(),
+                    GlobalSearchResult::Track(PathBuf::from(idx.to_string())),
+                )
+            }),
+        );
+        table.add_category("Playlists", 0, std::iter::empty());
+
+        // header, limited results, header, placeholder
+        assert_eq!(table.rows.len(), CATEGORY_LIMIT + 3);
+        assert_eq!(table.rows[0], None);
+        assert_eq!(
+            table.rows[1],
+            Some(GlobalSearchResult::Track(PathBuf::from("0")))
+        );
+        assert_eq!(table.rows[CATEGORY_LIMIT + 1], None);
+        assert_eq!(table.rows[CATEGORY_LIMIT + 2], None);
+
+        let built = table.table.build();
+        assert_eq!(built.len(), table.rows.len());
+        assert_eq!(
+            built[0][1].content,
+            format!("── Tracks ({CATEGORY_LIMIT} of {}) ──", CATEGORY_LIMIT + 1)
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! The inbox of newly added tracks, shown as [`SearchCriteria::Inbox`] in the database view.
+//!
+//! Tracks stay in the inbox until they are marked as reviewed, in the meantime they can be labeled, or located in
+//! the library to move or delete them.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use termusiclib::new_database::track_ops;
+
+use super::Model;
+use crate::ui::msg::{MainLayoutMsg, SearchCriteria};
+
+impl Model {
+    /// Mark the track at `index` in the `Tracks` view of the inbox as reviewed, or all of them if [`None`].
+    ///
+    /// Does nothing outside of the inbox.
+    pub fn database_mark_reviewed(&mut self, index: Option<usize>) {
+        if self.dw.criteria != SearchCriteria::Inbox {
+            return;
+        }
+
+        let paths: Vec<PathBuf> = match index {
+            Some(index) => self
+                .dw
+                .search_tracks
+                .get(index)
+                .map(track_ops::TrackRead::as_pathbuf)
+                .into_iter()
+                .collect(),
+            None => self
+                .dw
+                .search_tracks
+                .iter()
+                .map(track_ops::TrackRead::as_pathbuf)
+                .collect(),
+        };
+        if paths.is_empty() {
+            return;
+        }
+
+        let res = {
+            let conn = self.db.get_connection();
+            paths.iter().try_for_each(|path| {
+                track_ops::set_reviewed(&conn, path, true)
+                    .with_context(|| format!("\"{}\" is not in the library", path.display()))
+            })
+        };
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("mark track reviewed"));
+        }
+
+        self.dw
+            .search_tracks
+            .retain(|v| !paths.contains(&v.as_pathbuf()));
+        self.database_sync_tracks_results();
+
+        // the day is done, update the days left
+        if self.dw.search_tracks.is_empty() {
+            self.database_update_search_results();
+        }
+    }
+
+    /// Show the track at `index` in the `Tracks` view of the database in the library, to move or delete it there.
+    pub fn database_locate(&mut self, index: usize) {
+        let Some(path) = self
+            .dw
+            .search_tracks
+            .get(index)
+            .map(track_ops::TrackRead::as_pathbuf)
+        else {
+            return;
+        };
+
+        self.update_layout(MainLayoutMsg::TreeView);
+        let node = path.to_string_lossy().to_string();
+        self.new_library_scan_dir(path, Some(node));
+    }
+}
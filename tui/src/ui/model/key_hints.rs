@@ -0,0 +1,133 @@
+//! The line of key hints for the focused pane or popup, see `behavior.show_key_hints`.
+
+use termusiclib::config::v2::tui::keys::{KeyBinding, Keys};
+
+use super::Model;
+use crate::ui::components::KeyHints;
+use crate::ui::ids::Id;
+
+/// Get the most useful keys while `focus` has the focus, generated from the keymap so that rebound keys are shown.
+fn key_hints<'a>(keys: &'a Keys, focus: Option<&Id>) -> Vec<(&'a KeyBinding, &'static str)> {
+    let mut hints = match focus {
+        Some(Id::Library) => vec![
+            (&keys.library_keys.load_track, "add"),
+            (&keys.library_keys.load_dir, "add dir"),
+            (&keys.library_keys.search, "search"),
+            (&keys.library_keys.open_tag_editor, "tags"),
+            (&keys.library_keys.delete, "delete"),
+        ],
+        Some(Id::Playlist) => vec![
+            (&keys.playlist_keys.play_selected, "play"),
+            (&keys.playlist_keys.delete, "remove"),
+            (&keys.playlist_keys.swap_up, "move up"),
+            (&keys.playlist_keys.swap_down, "move down"),
+            (&keys.playlist_keys.shuffle, "shuffle"),
+            (&keys.playlist_keys.cycle_loop_mode, "loop mode"),
+            (&keys.playlist_keys.search, "search"),
+        ],
+        Some(Id::DBListCriteria | Id::DBListSearchResult | Id::DBListSearchTracks) => vec![
+            (&keys.database_keys.add_selected, "add"),
+            (&keys.database_keys.add_all, "add all"),
+            (&keys.database_keys.replace_playlist, "replace playlist"),
+            (&keys.database_keys.locate, "locate"),
+            (&keys.library_keys.search, "search"),
+        ],
+        Some(Id::Podcast) => vec![
+            (&keys.podcast_keys.search, "find feeds"),
+            (&keys.podcast_keys.refresh_feed, "refresh"),
+            (&keys.podcast_keys.refresh_all_feeds, "refresh all"),
+            (&keys.podcast_keys.delete_feed, "delete"),
+        ],
+        Some(Id::Episode) => vec![
+            (&keys.navigation_keys.right, "add"),
+            (&keys.podcast_keys.download_episode, "download"),
+            (&keys.podcast_keys.mark_played, "mark played"),
+            (&keys.podcast_keys.delete_local_episode, "delete download"),
+        ],
+        Some(Id::TrackSuggestions) => vec![
+            (&keys.database_keys.add_selected, "add"),
+            (&keys.escape, "back to playlist"),
+            (&keys.select_view_keys.toggle_track_suggestions, "hide"),
+        ],
+        Some(Id::Lyric) => vec![
+            (&keys.lyric_keys.cycle_frames, "cycle frames"),
+            (&keys.lyric_keys.search, "search"),
+            (&keys.lyric_keys.toggle_translation, "translate"),
+        ],
+        // popups and editors, which all close the same way
+        _ => {
+            return vec![
+                (&keys.escape, "close"),
+                (&keys.navigation_keys.up, "up"),
+                (&keys.navigation_keys.down, "down"),
+            ];
+        }
+    };
+
+    hints.extend([
+        (&keys.player_keys.toggle_pause, "pause"),
+        (&keys.select_view_keys.open_help, "all keys"),
+    ]);
+
+    hints
+}
+
+impl Model {
+    /// Show the key hints for the currently focused component, if enabled and the focus changed since the last update.
+    pub fn key_hints_update(&mut self) {
+        if !self.config_tui.read().settings.behavior.show_key_hints {
+            return;
+        }
+        let focus = self.app.focus().copied();
+        if self.app.mounted(&Id::KeyHints) && focus == self.key_hints_focus {
+            return;
+        }
+
+        let config = self.config_tui.read();
+        let hints = key_hints(&config.settings.keys, focus.as_ref());
+        let res = self.app.remount(
+            Id::KeyHints,
+            Box::new(KeyHints::new(&config, &hints)),
+            Vec::new(),
+        );
+        drop(config);
+        if let Err(err) = res {
+            error!("Error updating the key hints: {err:#}");
+        }
+        self.key_hints_focus = focus;
+    }
+
+    /// Get the lines to reserve at the bottom for the footer and the key hints, if enabled.
+    pub fn footer_height(&self) -> u16 {
+        if self.config_tui.read().settings.behavior.show_key_hints {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termusiclib::config::v2::tui::keys::Keys;
+
+    use super::key_hints;
+    use crate::ui::ids::Id;
+
+    #[test]
+    fn should_hint_keys_of_the_focus() {
+        let keys = Keys::default();
+
+        let hints = key_hints(&keys, Some(&Id::Playlist));
+        assert!(hints.contains(&(&keys.playlist_keys.play_selected, "play")));
+        assert!(hints.contains(&(&keys.select_view_keys.open_help, "all keys")));
+
+        let hints = key_hints(&keys, Some(&Id::Library));
+        assert!(hints.contains(&(&keys.library_keys.load_track, "add")));
+        assert!(!hints.contains(&(&keys.playlist_keys.play_selected, "play")));
+
+        let hints = key_hints(&keys, Some(&Id::QuitPopup));
+        assert_eq!(hints[0], (&keys.escape, "close"));
+        assert!(!hints.contains(&(&keys.select_view_keys.open_help, "all keys")));
+    }
+}
@@ -0,0 +1,196 @@
+//! Fetching lyrics for many tracks at once into the lyric cache, see [`LyricCache`].
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use termusiclib::songtag::fetch_lyric_by_tags;
+use termusiclib::songtag::lyric_cache::LyricCache;
+use termusiclib::tag_backup::BackupStore;
+use termusiclib::utils::filetype_supported;
+use tokio::runtime::Handle;
+
+use super::Model;
+use crate::ui::components::TETrack;
+use crate::ui::msg::{LyricsFetchMsg, LyricsFetchReport, Msg};
+
+/// What happened to a single track in a lyrics fetch.
+enum FetchAction {
+    Fetched,
+    Available,
+    NotFound,
+}
+
+impl Model {
+    /// Handle all [`LyricsFetchMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_lyrics_fetch(&mut self, msg: LyricsFetchMsg) {
+        let res = match msg {
+            LyricsFetchMsg::FetchPath(path) => self.lyrics_fetch_path(&path),
+            LyricsFetchMsg::FetchPlaylist => self.lyrics_fetch_playlist(),
+            LyricsFetchMsg::Progress(done, total) => {
+                self.update_show_message_timeout(
+                    "Fetching lyrics",
+                    &format!("{done} / {total} tracks"),
+                    None,
+                );
+                Ok(())
+            }
+            LyricsFetchMsg::Done(report) => {
+                self.lyrics_fetch_done(report);
+                Ok(())
+            }
+        };
+
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("fetch lyrics"));
+        }
+    }
+
+    /// Fetch lyrics for the track at `path`, or all tracks inside of it if it is a directory.
+    fn lyrics_fetch_path(&mut self, path: &Path) -> Result<()> {
+        let sources: Vec<PathBuf> = walkdir::WalkDir::new(path)
+            .follow_links(true)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| entry.file_type().is_file() && filetype_supported(entry.path()))
+            .map(walkdir::DirEntry::into_path)
+            .collect();
+
+        self.lyrics_fetch_start(sources)
+    }
+
+    /// Fetch lyrics for all local tracks in the playlist.
+    fn lyrics_fetch_playlist(&mut self) -> Result<()> {
+        let sources = self
+            .playback
+            .playlist
+            .tracks()
+            .iter()
+            .filter_map(|track| track.path().map(Path::to_path_buf))
+            .collect();
+
+        self.lyrics_fetch_start(sources)
+    }
+
+    /// Fetch lyrics for `sources` one after another in the background, [`LyricsFetchMsg::Done`] is sent once finished.
+    fn lyrics_fetch_start(&mut self, sources: Vec<PathBuf>) -> Result<()> {
+        if self.lyrics_fetch_active {
+            bail!("A lyrics fetch is already running");
+        }
+        if sources.is_empty() {
+            bail!("No tracks to fetch lyrics for");
+        }
+
+        let settings = self.config_tui.read().settings.lyrics.clone();
+        let cache = LyricCache::from_settings(&settings)?;
+        let backups = self.tag_backup_store()?;
+
+        self.lyrics_fetch_active = true;
+        self.update_show_message_timeout(
+            "Fetching lyrics",
+            &format!("0 / {} tracks", sources.len()),
+            None,
+        );
+
+        let tx = self.tx_to_main.clone();
+        Handle::current().spawn(async move {
+            let total = sources.len();
+            let mut report = LyricsFetchReport::default();
+            let embed = settings.embed_fetched && lyrics_fetch_backup(backups, &sources).await;
+
+            for (idx, path) in sources.into_iter().enumerate() {
+                match lyrics_fetch_track(path.clone(), &cache, embed).await {
+                    Ok(FetchAction::Fetched) => report.fetched += 1,
+                    Ok(FetchAction::Available) => report.available += 1,
+                    Ok(FetchAction::NotFound) => report.not_found += 1,
+                    Err(err) => {
+                        error!("Error fetching lyrics for {}: {err:#}", path.display());
+                        report.failed += 1;
+                    }
+                }
+
+                let _ = tx.send(Msg::LyricsFetch(LyricsFetchMsg::Progress(idx + 1, total)));
+            }
+
+            let _ = tx.send(Msg::LyricsFetch(LyricsFetchMsg::Done(report)));
+        });
+
+        Ok(())
+    }
+
+    /// Report the result of a finished fetch.
+    fn lyrics_fetch_done(&mut self, report: LyricsFetchReport) {
+        self.lyrics_fetch_active = false;
+        // re-evaluate the current track's lyric, as it may be cached now
+        self.current_track_lyric = None;
+        self.lyric_update();
+
+        let text = format!(
+            "{} fetched, {} already available, {} not found",
+            report.fetched, report.available, report.not_found
+        );
+        if report.failed == 0 {
+            self.update_show_message_timeout("Fetching lyrics finished", &text, None);
+        } else {
+            self.mount_error_popup(anyhow::anyhow!(
+                "{text}, {} failed (see the log)",
+                report.failed
+            ));
+        }
+    }
+}
+
+/// Back up the tags of all `sources` at once, so that embedding the lyrics can be undone as a whole.
+///
+/// Returns whether the backup succeeded, otherwise the lyrics should only be cached.
+async fn lyrics_fetch_backup(backups: BackupStore, sources: &[PathBuf]) -> bool {
+    let sources = sources.to_vec();
+    let res = tokio::task::spawn_blocking(move || backups.backup_tags("Fetch lyrics", &sources))
+        .await
+        .context("join backup tags");
+
+    match res {
+        Ok(Ok(_)) => true,
+        Ok(Err(err)) | Err(err) => {
+            error!("Error backing up the tags, not embedding the lyrics: {err:#}");
+            false
+        }
+    }
+}
+
+/// Fetch the lyric for the track at `path`, unless it already has one embedded or cached.
+///
+/// If `embed` is `true`, the lyric is also embedded into the track file.
+async fn lyrics_fetch_track(path: PathBuf, cache: &LyricCache, embed: bool) -> Result<FetchAction> {
+    let mut track = tokio::task::spawn_blocking(move || TETrack::read_metadata_from_file(path))
+        .await
+        .context("join read metadata")??;
+
+    if !track.lyric_frames().is_empty() {
+        return Ok(FetchAction::Available);
+    }
+    let (Some(artist), Some(title)) = (track.artist(), track.title()) else {
+        bail!("Track has no artist or title tag");
+    };
+    let (artist, title) = (artist.to_string(), title.to_string());
+
+    let (lyric, action) = if let Some(lyric) = cache.get(&artist, &title) {
+        (lyric, FetchAction::Available)
+    } else {
+        let Some(lyric) = fetch_lyric_by_tags(&artist, &title).await? else {
+            return Ok(FetchAction::NotFound);
+        };
+        cache.insert(&artist, &title, &lyric)?;
+
+        (lyric, FetchAction::Fetched)
+    };
+
+    if embed {
+        track.set_lyric(lyric, "eng", Some("saved by termusic"));
+        tokio::task::spawn_blocking(move || track.save_tag())
+            .await
+            .context("join save tag")??;
+    }
+
+    Ok(action)
+}
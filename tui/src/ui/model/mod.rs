@@ -1,47 +1,92 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::Ordering;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local};
 use id3::frame::Lyrics as Id3Lyrics;
 #[allow(unused_imports)]
+use termusiclib::config::v2::server::equalizer::EqualizerSettings;
 use termusiclib::config::v2::tui::CoverArtProtocol;
 use termusiclib::config::v2::tui::keys::Keys;
 use termusiclib::config::v2::tui::theme::ThemeWrap;
 use termusiclib::config::{ServerOverlay, SharedServerSettings, SharedTuiSettings, TuiOverlay};
+use termusiclib::downloads::DownloadInfo;
+use termusiclib::file_ops::TrashedPath;
 use termusiclib::new_database::Database;
-use termusiclib::new_database::track_ops::TrackRead;
+use termusiclib::new_database::track_ops::{TrackLabel, TrackRead};
 use termusiclib::player::playlist_helpers::PlaylistTrackSource;
-use termusiclib::player::{PlaylistTracks, RunningStatus};
+use termusiclib::player::{
+    ActivityInfo, ListeningTimeInfo, OutputDevices, PlaylistTracks, RunningStatus,
+    ServerCapabilities, features, playlist_add_track,
+};
 use termusiclib::podcast::{Podcast, PodcastFeed, db::Database as DBPod};
+use termusiclib::search_provider::SearchSite;
 use termusiclib::songtag::SongTag;
 use termusiclib::songtag::lrc::Lyric;
 use termusiclib::taskpool::TaskPool;
-use termusiclib::track::{LyricData, MediaTypesSimple, Track};
+use termusiclib::track::{LyricData, MediaTypesSimple, QualityBadge, Track};
 use termusiclib::utils::get_app_config_path;
 use termusiclib::xywh;
 use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
-use tuirealm::terminal::{CrosstermTerminalAdapter, TerminalBridge};
+use tuirealm::Update;
+use tuirealm::application::PollStrategy;
+use tuirealm::terminal::{TerminalAdapter, TerminalBridge};
 
-use super::components::TETrack;
+use super::components::{SearchMode, Source, TETrack};
 use super::tui_cmd::TuiCmd;
 use crate::CombinedSettings;
 use crate::ui::Application;
+use crate::ui::headless::HeadlessOptions;
 use crate::ui::ids::Id;
+use crate::ui::model::ab_loop::AbLoopMark;
+use crate::ui::model::ports::keys::PortKeys;
 use crate::ui::model::ports::stream_events::{PortStreamEvents, WrappedStreamEvents};
-use crate::ui::model::youtube_options::YoutubeOptions;
+use crate::ui::model::youtube_options::{YoutubeOptions, YoutubeSuggestions};
 use crate::ui::msg::{Msg, SearchCriteria};
 #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
 use crate::ui::ueberzug::UeInstance;
+pub use beat_indicator::BeatIndicator;
 pub use download_tracker::DownloadTracker;
+pub use global_search::GlobalSearchData;
+pub use radio::RadioBrowser;
+pub use terminal::TermusicTerminal;
+pub use track_suggestions::TrackSuggestionsData;
+pub use translation::{LyricTranslation, side_by_side};
 pub use user_events::UserEvent;
 
+mod ab_loop;
+mod beat_indicator;
+mod bookmarks;
+pub mod credits;
+mod device_sync;
 mod download_tracker;
+pub mod downloads;
+mod equalizer;
+mod global_search;
+mod inbox;
+mod key_hints;
+mod lyrics_fetch;
+mod output_devices;
 mod playlist;
+mod playlist_sync;
 mod ports;
+pub mod quality_badges;
+mod queue_replace;
+pub mod radio;
+mod smart_playlists;
+mod statistics;
+mod tag_backups;
+mod terminal;
+pub mod track_labels;
+mod track_offsets;
+mod track_suggestions;
+mod translation;
 mod update;
 mod user_events;
+mod verify_library;
 mod view;
 pub mod youtube_options;
 
@@ -60,6 +105,24 @@ pub enum ConfigEditorLayout {
     Key2,
 }
 
+/// A library delete that can still be undone.
+#[derive(Debug)]
+pub struct PendingDelete {
+    pub trashed: TrashedPath,
+    /// The playlist entries that got removed by the delete, with their index
+    pub playlist_entries: Vec<(u64, PlaylistTrackSource)>,
+    pub deleted_at: Instant,
+}
+
+/// A playlist replace waiting for confirmation.
+#[derive(Debug)]
+pub struct PendingReplace {
+    /// Id to match a preview result to this replace
+    pub id: usize,
+    /// The tracks to replace the playlist with
+    pub sources: Vec<PlaylistTrackSource>,
+}
+
 /// All data specific to the Database Widget / View
 #[derive(Debug)]
 pub struct DatabaseWidgetData {
@@ -69,6 +132,19 @@ pub struct DatabaseWidgetData {
     pub search_results: Vec<String>,
     /// Results of the critea results search `(criteria -> search_results -> this)`
     pub search_tracks: Vec<TrackRead>,
+    /// All tracks for the general search popup, [`None`] while still loading
+    pub general_search_tracks: Option<Vec<TrackRead>>,
+    /// The last input of the general search popup, to apply once loaded
+    pub general_search_input: String,
+    /// The result to select once the search results are loaded, see [`Model::database_apply_jump`]
+    pub jump_to: Option<String>,
+    /// The smart playlist whose tracks are shown, to refresh them, see [`Model::smart_playlist_schedule_refresh`]
+    pub smart_playlist_shown: Option<String>,
+    /// Bumped whenever other tracks are queried for the `Tracks` view, to drop outdated results and stop scheduled
+    /// refreshes of a smart playlist
+    pub tracks_generation: u64,
+    /// The smart playlist being created or edited
+    pub smart_playlist_edit: Option<SmartPlaylistEdit>,
 }
 
 impl DatabaseWidgetData {
@@ -82,6 +158,15 @@ impl DatabaseWidgetData {
     }
 }
 
+/// A smart playlist being created or edited, see [`Model::update_smart_playlist`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SmartPlaylistEdit {
+    /// The name the playlist was saved with, [`None`] for a new playlist
+    pub previous: Option<String>,
+    pub name: String,
+    pub query: String,
+}
+
 /// All data specific to the Podcast Widget / View
 #[derive(Debug)]
 pub struct PodcastWidgetData {
@@ -215,14 +300,21 @@ impl Playback {
                 bail!("Track does not have a id, which is required to load!");
             };
 
-            let track = match PlaylistTrackSource::try_from(id)? {
+            let error = track.optional_error.map(|v| {
+                let playlist_add_track::OptionalError::Error(v) = v;
+                v
+            });
+
+            let mut track = match PlaylistTrackSource::try_from(id)? {
                 PlaylistTrackSource::Path(v) => Track::read_track_from_path(v)?,
                 PlaylistTrackSource::Url(v) => Track::new_radio(&v),
                 PlaylistTrackSource::PodcastUrl(v) => {
                     let episode = podcast_db.get_episode_by_url(&v)?;
                     Track::from_podcast_episode(&episode)
                 }
+                PlaylistTrackSource::VideoUrl(v) => Track::new_video(v),
             };
+            track.set_error(error);
 
             playlist_items.push(track);
         }
@@ -275,6 +367,7 @@ impl ExtraLyricData {
 
 pub type TxToMain = UnboundedSender<Msg>;
 
+#[allow(clippy::struct_excessive_bools)] // independent flags, this is not a state machine
 pub struct Model {
     /// Indicates that the application must quit
     pub quit: bool,
@@ -282,10 +375,14 @@ pub struct Model {
     pub redraw: bool,
     pub app: Application<Id, Msg, UserEvent>,
     /// Used to draw to terminal
-    pub terminal: TerminalBridge<CrosstermTerminalAdapter>,
+    pub terminal: TermusicTerminal,
     pub tx_to_main: TxToMain,
     /// Sender for Player Commands
     pub cmd_to_server_tx: UnboundedSender<TuiCmd>,
+    /// What the connected server reported to support
+    pub server_capabilities: ServerCapabilities,
+    /// The activity last reported to the server
+    reported_activity: ActivityInfo,
 
     pub config_tui: SharedTuiSettings,
     pub config_server: SharedServerSettings,
@@ -299,6 +396,49 @@ pub struct Model {
     pub tageditor_song: Option<TETrack>,
     pub current_track_lyric: Option<ExtraLyricData>,
     pub playback: Playback,
+    /// The last library delete, while it can still be undone
+    pub pending_delete: Option<PendingDelete>,
+    /// Whether a device sync is currently running
+    pub device_sync_active: bool,
+    /// Whether a playlist sync is currently running
+    pub playlist_sync_active: bool,
+    /// Whether a library verification is currently running
+    pub verify_library_active: bool,
+    /// The color labels of the tracks in the library, see [`track_labels`]
+    pub track_labels: HashMap<PathBuf, TrackLabel>,
+    /// The quality badges of the tracks in the library, see [`quality_badges`]
+    pub track_qualities: HashMap<PathBuf, QualityBadge>,
+    /// Whether a lyrics fetch is currently running
+    pub lyrics_fetch_active: bool,
+    /// Whether karaoke mode is enabled, highlighting the current line in the full lyrics and reducing vocals
+    pub karaoke: bool,
+    /// The translation of the lyric or episode description, if shown
+    pub lyric_translation: LyricTranslation,
+    /// Whether the server is recording what is playing, as last reported by the server
+    pub recording: bool,
+    /// Whether the server applies the loudness normalization, initially as configured
+    pub normalization: bool,
+    /// Whether the server shortens long silences in podcasts, initially as configured
+    pub smart_speed: bool,
+    /// The equalizer of the server, initially as configured, see [`features::EQUALIZER`]
+    pub equalizer: EqualizerSettings,
+    /// The band selected in the equalizer popup
+    pub equalizer_band: usize,
+    /// The time listened since the last break, as last reported by the server, [`None`] if there is no limit
+    pub listening_time: Option<ListeningTimeInfo>,
+    /// The start of the current track the playlist start times were estimated with, [`None`] if not shown
+    pub playlist_start_base: Option<DateTime<Local>>,
+    /// The playlist replace waiting for confirmation
+    pub pending_replace: Option<PendingReplace>,
+    /// The last used mode of the general search popup
+    pub general_search_mode: SearchMode,
+    /// Data of the global search popup, while it is open
+    pub global_search: Option<GlobalSearchData>,
+    /// The source of the general search popup, while it is open
+    pub general_search_source: Option<Source>,
+    pub beat_indicator: BeatIndicator,
+    /// The suggestions of the panel next to the playlist, while it is shown
+    pub track_suggestions: TrackSuggestionsData,
 
     #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
     pub ueberzug_instance: Option<UeInstance>,
@@ -306,8 +446,21 @@ pub struct Model {
     pub xywh: xywh::Xywh,
 
     youtube_options: YoutubeOptions,
+    /// The suggestions for the current input of the youtube search
+    youtube_suggestions: Option<YoutubeSuggestions>,
+    /// The site the youtube search popup searches
+    pub youtube_search_site: SearchSite,
     pub songtag_options: Vec<SongTag>,
     pub download_tracker: DownloadTracker,
+    /// The download queue of the server, see [`features::DOWNLOAD_QUEUE`]
+    pub server_downloads: Vec<DownloadInfo>,
+    /// The output devices of the server, as last listed, see [`features::OUTPUT_DEVICE`]
+    pub output_devices: OutputDevices,
+    /// The A-B loop of the current track, see [`features::AB_LOOP`]
+    pub ab_loop: AbLoopMark,
+    /// The focus the key hints were last generated for
+    pub key_hints_focus: Option<Id>,
+    pub radio: RadioBrowser,
     /// Taskpool to limit number of active network requests
     ///
     /// Currently only used for podcast sync & download
@@ -322,6 +475,8 @@ pub enum ViuerSupported {
     ITerm,
     #[cfg(feature = "cover-viuer-sixel")]
     Sixel,
+    #[cfg(feature = "cover-viuer-blocks")]
+    Blocks,
     NotSupported,
 }
 
@@ -341,16 +496,26 @@ fn get_viuer_support(config: &TuiOverlay) -> ViuerSupported {
     if config.cover_protocol_enabled(CoverArtProtocol::Sixel) && viuer::is_sixel_supported() {
         return ViuerSupported::Sixel;
     }
+    // blocks are supported everywhere, but ueberzug is preferred as it draws actual pixels
+    #[cfg(feature = "cover-viuer-blocks")]
+    if config.cover_protocol_enabled(CoverArtProtocol::Blocks)
+        && !(cfg!(all(feature = "cover-ueberzug", not(target_os = "windows")))
+            && config.cover_protocol_enabled(CoverArtProtocol::Ueberzug))
+    {
+        return ViuerSupported::Blocks;
+    }
 
     ViuerSupported::NotSupported
 }
 
 impl Model {
+    /// Create the model, drawing to the terminal, or to a buffer if `headless` is set.
     #[allow(clippy::too_many_lines)]
     pub fn new(
         config: CombinedSettings,
         cmd_to_server_tx: UnboundedSender<TuiCmd>,
         stream_updates: WrappedStreamEvents,
+        headless: Option<HeadlessOptions>,
     ) -> Self {
         let CombinedSettings {
             server: config_server,
@@ -360,7 +525,8 @@ impl Model {
 
         let config_tui_read = config_tui.read();
 
-        let viuer_supported = if config_tui_read.cover_features_enabled() {
+        // covers are drawn outside of the buffer, so there is no way to show them when headless
+        let viuer_supported = if headless.is_none() && config_tui_read.cover_features_enabled() {
             get_viuer_support(&config_tui_read)
         } else {
             ViuerSupported::NotSupported
@@ -369,7 +535,8 @@ impl Model {
         info!("Using viuer protocol {viuer_supported:#?}");
 
         #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
-        let ueberzug_instance = if config_tui_read.cover_features_enabled()
+        let ueberzug_instance = if headless.is_none()
+            && config_tui_read.cover_features_enabled()
             && config_tui_read.cover_protocol_enabled(CoverArtProtocol::Ueberzug)
             && viuer_supported == ViuerSupported::NotSupported
         {
@@ -380,17 +547,45 @@ impl Model {
 
         drop(config_tui_read);
 
-        let db = Database::new_default_path().expect("Open Library Database");
-        let db_criteria = SearchCriteria::Artist;
-        let terminal = TerminalBridge::new_crossterm().expect("Could not initialize terminal");
-
-        let db_path = get_app_config_path().expect("failed to get podcast db path.");
-
-        let db_podcast = DBPod::new(&db_path).expect("error connecting to podcast db.");
+        let data_dir = headless.as_ref().and_then(|v| v.data_dir.clone());
+
+        // both databases are independent, so open (and maybe migrate) them at the same time
+        let (db, (db_podcast, podcasts)) = std::thread::scope(|s| {
+            let podcast = s.spawn(|| {
+                let db_path = match &data_dir {
+                    Some(dir) => dir.clone(),
+                    None => get_app_config_path().expect("failed to get podcast db path."),
+                };
+                let db_podcast = DBPod::new(&db_path).expect("error connecting to podcast db.");
+                let podcasts = db_podcast
+                    .get_podcasts()
+                    .expect("failed to get podcasts from db.");
+
+                (db_podcast, podcasts)
+            });
+            let db = match &data_dir {
+                Some(dir) => Database::new(&dir.join("library2.db")),
+                None => Database::new_default_path(),
+            }
+            .expect("Open Library Database");
 
-        let podcasts = db_podcast
-            .get_podcasts()
-            .expect("failed to get podcasts from db.");
+            match podcast.join() {
+                Ok(podcast) => (db, podcast),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        });
+        crate::startup::mark("databases opened");
+        let db_criteria = SearchCriteria::Artist;
+        let (terminal, keys) = match headless {
+            Some(headless) => (
+                TermusicTerminal::new_headless(headless.size.0, headless.size.1),
+                Some(PortKeys::new(headless.keys)),
+            ),
+            None => (
+                TermusicTerminal::new_crossterm().expect("Could not initialize terminal"),
+                None,
+            ),
+        };
         let taskpool = TaskPool::new(usize::from(
             config_server
                 .read()
@@ -403,13 +598,16 @@ impl Model {
 
         let stream_update_port = PortStreamEvents::new(stream_updates);
 
-        let app = Self::init_app(rx_to_main, stream_update_port);
+        let app = Self::init_app(rx_to_main, stream_update_port, keys);
 
         // This line is required, in order to show the playing message for the first track
         // playlist.set_current_track_index(0);
 
         let ce_theme = config_tui.read().settings.theme.clone();
         let xywh = xywh::Xywh::from(&config_tui.read().settings.coverart);
+        let normalization = config_server.read().settings.player.normalization;
+        let smart_speed = config_server.read().settings.player.smart_speed;
+        let equalizer = config_server.read().settings.equalizer.clone();
 
         let download_tracker = DownloadTracker::default();
 
@@ -423,6 +621,8 @@ impl Model {
             tageditor_song: None,
 
             youtube_options: YoutubeOptions::default(),
+            youtube_suggestions: None,
+            youtube_search_site: SearchSite::default(),
             #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
             ueberzug_instance,
             songtag_options: vec![],
@@ -433,6 +633,12 @@ impl Model {
                 criteria: db_criteria,
                 search_results: Vec::new(),
                 search_tracks: Vec::new(),
+                general_search_tracks: None,
+                general_search_input: String::new(),
+                jump_to: None,
+                smart_playlist_shown: None,
+                tracks_generation: 0,
+                smart_playlist_edit: None,
             },
             podcast: PodcastWidgetData {
                 podcasts,
@@ -450,9 +656,38 @@ impl Model {
             taskpool,
             tx_to_main,
             download_tracker,
+            server_downloads: Vec::new(),
+            output_devices: OutputDevices::default(),
+            ab_loop: AbLoopMark::default(),
+            key_hints_focus: None,
+            radio: RadioBrowser::new(),
             current_track_lyric: None,
             playback: Playback::new(),
+            pending_delete: None,
+            device_sync_active: false,
+            playlist_sync_active: false,
+            verify_library_active: false,
+            track_labels: HashMap::new(),
+            track_qualities: HashMap::new(),
+            lyrics_fetch_active: false,
+            karaoke: false,
+            lyric_translation: LyricTranslation::default(),
+            recording: false,
+            normalization,
+            smart_speed,
+            equalizer,
+            equalizer_band: 0,
+            listening_time: None,
+            playlist_start_base: None,
+            pending_replace: None,
+            general_search_mode: SearchMode::default(),
+            global_search: None,
+            general_search_source: None,
+            beat_indicator: BeatIndicator::default(),
+            track_suggestions: TrackSuggestionsData::default(),
             cmd_to_server_tx,
+            server_capabilities: ServerCapabilities::legacy(),
+            reported_activity: ActivityInfo::default(),
             xywh,
         };
 
@@ -491,11 +726,11 @@ impl Model {
     /// - Start Database scan
     /// - Generate playlist component data
     pub fn init(&mut self) {
-        if let Err(e) = Self::theme_extract_all() {
-            self.mount_error_popup(e.context("theme save"));
-        }
         self.scan_all_music_roots();
+        self.track_labels_reload();
+        self.track_qualities_reload();
         self.playlist_sync();
+        self.youtube_invidious_probe_start();
     }
 
     /// Trigger a database scan for all music roots.
@@ -549,17 +784,63 @@ impl Model {
         self.redraw = true;
     }
 
+    /// Poll the events with `strategy`, handle the resulting messages and draw.
+    ///
+    /// Returns whether at least one message was handled.
+    pub fn tick(&mut self, strategy: PollStrategy) -> bool {
+        let handled = match self.app.tick(strategy) {
+            Err(err) => {
+                self.mount_error_popup((anyhow!(err)).context("tick poll error"));
+                false
+            }
+            Ok(messages) if !messages.is_empty() => {
+                // NOTE: redraw if at least one msg has been processed
+                self.redraw = true;
+                for msg in messages {
+                    let mut msg = Some(msg);
+                    while msg.is_some() {
+                        msg = self.update(msg);
+                    }
+                }
+                true
+            }
+            _ => false,
+        };
+
+        self.ensure_quit_popup_top_most_focus();
+        self.report_activity();
+
+        self.view();
+
+        handled
+    }
+
     /// Send a command to request the Track Progress and set the titles to the current state.
     #[inline]
     pub fn request_progress(&mut self) {
         self.command(TuiCmd::GetProgress);
     }
 
+    /// Report the activity of the [`DownloadTracker`] to the server, if it changed since the last report.
+    pub fn report_activity(&mut self) {
+        if !self.server_capabilities.supports(features::ACTIVITY) {
+            return;
+        }
+
+        let activity = self.download_tracker.activity();
+        if activity != self.reported_activity {
+            self.reported_activity = activity.clone();
+            self.command(TuiCmd::ReportActivity(activity));
+        }
+    }
+
     /// Update all the places that need to be updated after a current track change or running status change.
     pub fn player_update_current_track_after(&mut self) {
         if let Err(e) = self.update_photo() {
             self.mount_error_popup(e.context("update_photo"));
         }
+        self.beat_indicator_update();
+        self.track_suggestions_update();
         self.progress_update_title();
         self.lyric_update_title();
         self.lyric_update();
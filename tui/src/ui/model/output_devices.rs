@@ -0,0 +1,74 @@
+//! The output devices of the server, see [`features::OUTPUT_DEVICE`].
+
+use anyhow::anyhow;
+use termusiclib::player::{OutputDevices, features};
+
+use super::Model;
+use crate::ui::ids::Id;
+use crate::ui::msg::OutputDevicesMsg;
+use crate::ui::tui_cmd::{BackendCmd, TuiCmd};
+
+impl Model {
+    /// Handle all [`OutputDevicesMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_output_devices(&mut self, msg: OutputDevicesMsg) {
+        match msg {
+            OutputDevicesMsg::Show => {
+                if !self.server_capabilities.supports(features::OUTPUT_DEVICE) {
+                    self.mount_error_popup(anyhow!(
+                        "Choosing the output device is not supported by the server"
+                    ));
+                    return;
+                }
+                if let Err(err) = self.mount_output_devices() {
+                    self.mount_error_popup(err.context("show output devices"));
+                }
+                self.command(TuiCmd::Backend(BackendCmd::GetOutputDevices));
+            }
+            OutputDevicesMsg::Close => self.umount_output_devices(),
+            OutputDevicesMsg::Select(name) => {
+                let title = if name.is_empty() {
+                    "Switching to the default output device".to_string()
+                } else {
+                    format!("Switching the output to \"{name}\"")
+                };
+                self.update_show_message_timeout("Output Device", &title, None);
+                self.output_devices.configured.clone_from(&name);
+                self.output_devices_update_popup();
+                self.command(TuiCmd::Backend(BackendCmd::SetOutputDevice(name)));
+            }
+        }
+    }
+
+    /// Replace the known output devices with `devices` from the server.
+    pub fn output_devices_listed(&mut self, devices: OutputDevices) {
+        if !devices.supported {
+            self.umount_output_devices();
+            self.update_show_message_timeout(
+                "Output Device",
+                "Choosing the output device is not supported by the server's backend",
+                None,
+            );
+            return;
+        }
+        // keep the local copy in sync, so that saving the config does not overwrite it
+        self.config_server
+            .write()
+            .settings
+            .backends
+            .rusty
+            .output_device
+            .clone_from(&devices.configured);
+        self.output_devices = devices;
+        self.output_devices_update_popup();
+    }
+
+    /// Show the known output devices in the output devices popup, if mounted.
+    fn output_devices_update_popup(&mut self) {
+        if !self.app.mounted(&Id::OutputDevicesPopup) {
+            return;
+        }
+        if let Err(err) = self.mount_output_devices() {
+            error!("Error updating the output devices popup: {err:#}");
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use std::fmt::Write as _;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use pathdiff::diff_paths;
-use termusiclib::player::PlaylistRemoveTrackInfo;
+use rusqlite::Connection;
+use termusiclib::new_database::track_ops;
 use termusiclib::player::playlist_helpers::{PlaylistAddTrack, PlaylistTrackSource};
+use termusiclib::player::{PlaylistRemoveTrackInfo, PlaylistTrackErrorInfo};
+use termusiclib::playlist::share::{self, ShareEntry};
 use termusiclib::podcast::db::Database as DBPod;
 use termusiclib::track::MediaTypes;
 use termusiclib::utils::get_parent_folder;
@@ -93,6 +97,13 @@ impl TUIPlaylist {
 
         self.tracks.remove(index);
 
+        // keep pointing at the same track, like the server does
+        if let Some(current) = &mut self.current_track_idx
+            && index <= *current
+        {
+            *current = current.saturating_sub(1);
+        }
+
         Ok(())
     }
 
@@ -115,6 +126,24 @@ impl TUIPlaylist {
         self.remove_simple(at_index)
     }
 
+    /// Handle a `PlaylistTrackError` message from the grpc interface
+    ///
+    /// # Errors
+    ///
+    /// - if the index is out-of-bound
+    /// - if same track checks fail (desync)
+    pub fn handle_grpc_track_error(&mut self, info: PlaylistTrackErrorInfo) -> Result<()> {
+        let at_index = usize::try_from(info.at_index).unwrap();
+        let Some(track_at_idx) = self.tracks.get_mut(at_index) else {
+            bail!("Failed to get track at index \"{at_index}\"");
+        };
+
+        Self::check_same_source(&info.trackid, track_at_idx.inner(), at_index)?;
+        track_at_idx.set_error(info.error);
+
+        Ok(())
+    }
+
     /// Add Paths / Urls from the music service
     ///
     /// # Errors
@@ -132,6 +161,7 @@ impl TUIPlaylist {
                     PlaylistTrackSource::PodcastUrl(uri) => {
                         Self::track_from_podcasturi(uri, db_pod)?
                     }
+                    PlaylistTrackSource::VideoUrl(uri) => Track::new_video(uri.as_str()),
                 };
 
                 self.tracks.push(track);
@@ -139,17 +169,22 @@ impl TUIPlaylist {
 
             return Ok(());
         }
-        let mut at_index = at_index;
         // insert tracks at position
-        for track_location in tracks.tracks {
+        for (at_index, track_location) in (at_index..).zip(tracks.tracks) {
             let track = match &track_location {
                 PlaylistTrackSource::Path(path) => Self::track_from_path(path)?,
                 PlaylistTrackSource::Url(uri) => Self::track_from_uri(uri),
                 PlaylistTrackSource::PodcastUrl(uri) => Self::track_from_podcasturi(uri, db_pod)?,
+                PlaylistTrackSource::VideoUrl(uri) => Track::new_video(uri.as_str()),
             };
 
             self.tracks.insert(at_index, track);
-            at_index += 1;
+            // keep pointing at the same track
+            if let Some(current) = &mut self.current_track_idx
+                && at_index <= *current
+            {
+                *current += 1;
+            }
         }
 
         Ok(())
@@ -213,6 +248,40 @@ impl TUIPlaylist {
         Ok(())
     }
 
+    /// Estimate the time until each track starts playing, with the current track being at `position`.
+    ///
+    /// Tracks that cannot be estimated are [`None`], like the current track, tracks after one without a known duration
+    /// and all tracks if the order is not known ahead, like in [`LoopMode::Random`] and [`LoopMode::Single`].
+    #[must_use]
+    pub fn start_offsets(&self, position: Duration) -> Vec<Option<Duration>> {
+        let mut offsets = vec![None; self.tracks.len()];
+        let Some(current) = self.current_track_idx else {
+            return offsets;
+        };
+        if self.loop_mode != LoopMode::Playlist {
+            return offsets;
+        }
+        let Some(mut offset) = self
+            .tracks
+            .get(current)
+            .and_then(Track::duration)
+            .map(|v| v.saturating_sub(position))
+        else {
+            return offsets;
+        };
+
+        // the playlist starts from the beginning again after the last track
+        for idx in (current + 1..self.tracks.len()).chain(0..current) {
+            offsets[idx] = Some(offset);
+            let Some(duration) = self.tracks[idx].duration() else {
+                break;
+            };
+            offset += duration;
+        }
+
+        offsets
+    }
+
     /// Get the current track in the playlist, if there is one.
     pub fn current_track(&self) -> Option<&Track> {
         let idx = self.current_track_idx?;
@@ -230,39 +299,66 @@ impl TUIPlaylist {
     // TODO: move "save_m3u" to server-side
     /// Export the current playlist to a `.m3u` playlist file.
     ///
+    /// The [offsets](track_ops::TrackOffsets) of tracks in `conn` are written as `#EXTVLCOPT` directives.
+    ///
     /// # Errors
     ///
     /// Error could happen when writing file to local disk.
-    pub fn save_m3u(&self, filename: &Path) -> Result<()> {
+    pub fn save_m3u(&self, filename: &Path, conn: &Connection) -> Result<()> {
         if self.tracks.is_empty() {
             bail!("Unable to save since the playlist is empty.");
         }
 
         let parent_folder = get_parent_folder(filename);
 
-        let m3u = self.get_m3u_file(&parent_folder);
+        let m3u = self.get_m3u_file(&parent_folder, conn);
 
         std::fs::write(filename, m3u)?;
         Ok(())
     }
 
+    /// Export the current playlist as a [shared playlist](termusiclib::playlist::share).
+    ///
+    /// Local files are written relative to the first of `music_dirs` they are in.
+    ///
+    /// # Errors
+    ///
+    /// Error could happen when writing file to local disk.
+    pub fn save_share(&self, filename: &Path, music_dirs: &[PathBuf]) -> Result<()> {
+        if self.tracks.is_empty() {
+            bail!("Unable to save since the playlist is empty.");
+        }
+
+        let entries: Vec<ShareEntry> = self
+            .tracks
+            .iter()
+            .map(|track| ShareEntry::from_track(track, music_dirs))
+            .collect();
+
+        std::fs::write(filename, share::encode(&entries))?;
+        Ok(())
+    }
+
     /// Generate the m3u's file content.
     ///
     /// All Paths are relative to the `parent_folder` directory.
-    fn get_m3u_file(&self, parent_folder: &Path) -> String {
+    fn get_m3u_file(&self, parent_folder: &Path, conn: &Connection) -> String {
         let mut m3u = String::from("#EXTM3U\n");
         for track in &self.tracks {
-            let file = match track.inner() {
-                MediaTypes::Track(track_data) => {
-                    let path_relative = diff_paths(track_data.path(), parent_folder);
+            let file = match track.as_track() {
+                Some(track_data) => {
+                    let offsets =
+                        track_ops::get_offsets(conn, track_data.path()).unwrap_or_default();
+                    for directive in offsets.extm3u_directives() {
+                        let _ = writeln!(m3u, "{directive}");
+                    }
 
-                    path_relative.map_or_else(
-                        || track_data.path().to_string_lossy(),
+                    diff_paths(track_data.path(), parent_folder).map_or_else(
+                        || track.source().location(),
                         |v| v.to_string_lossy().to_string().into(),
                     )
                 }
-                MediaTypes::Radio(radio_track_data) => radio_track_data.url().into(),
-                MediaTypes::Podcast(podcast_track_data) => podcast_track_data.url().into(),
+                None => track.source().location(),
             };
 
             let _ = writeln!(m3u, "{file}");
@@ -306,6 +402,14 @@ impl TUIPlaylist {
                     );
                 }
             }
+            (PlaylistTrackSource::VideoUrl(file_url), MediaTypes::Video(id)) => {
+                if file_url != id.url() {
+                    bail!(
+                        "URI mismatch, expected \"{file_url}\" at \"{at_index}\", found \"{}\"",
+                        id.url()
+                    );
+                }
+            }
             (expected, got) => {
                 bail!(
                     "Type mismatch, expected \"{expected:#?}\" at \"{at_index}\" found \"{got:#?}\""
@@ -316,3 +420,122 @@ impl TUIPlaylist {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use termusiclib::config::v2::server::LoopMode;
+    use termusiclib::player::PlaylistRemoveTrackInfo;
+    use termusiclib::player::playlist_helpers::{PlaylistAddTrack, PlaylistTrackSource};
+    use termusiclib::podcast::db::Database as DBPod;
+    use termusiclib::podcast::episode::Episode;
+    use termusiclib::track::Track;
+
+    use super::TUIPlaylist;
+
+    /// Create a playlist of episodes with the given durations in seconds, [`None`] being a unknown duration.
+    fn playlist(durations: &[Option<i64>]) -> TUIPlaylist {
+        let tracks = durations
+            .iter()
+            .enumerate()
+            .map(|(idx, duration)| {
+                Track::from_podcast_episode(&Episode {
+                    id: 0,
+                    pod_id: 0,
+                    title: String::new(),
+                    url: format!("http://example.com/{idx}.mp3"),
+                    guid: String::new(),
+                    description: String::new(),
+                    pubdate: None,
+                    duration: *duration,
+                    path: None,
+                    played: false,
+                    last_position: None,
+                    image_url: None,
+                })
+            })
+            .collect();
+        let mut playlist = TUIPlaylist::default();
+        playlist.set_tracks(tracks);
+        playlist.set_loop_mode(LoopMode::Playlist);
+
+        playlist
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn should_estimate_start_offsets() {
+        let mut playlist = playlist(&[Some(100), Some(200), Some(300), Some(400)]);
+        assert_eq!(
+            playlist.start_offsets(Duration::ZERO),
+            [None, None, None, None]
+        );
+
+        playlist.set_current_track_index(1).unwrap();
+        assert_eq!(
+            playlist.start_offsets(Duration::from_secs(50)),
+            [Some(secs(850)), None, Some(secs(150)), Some(secs(450))]
+        );
+    }
+
+    #[test]
+    fn should_stop_estimating_after_unknown_duration() {
+        let mut playlist = playlist(&[Some(100), None, Some(300)]);
+        playlist.set_current_track_index(0).unwrap();
+        assert_eq!(
+            playlist.start_offsets(Duration::from_secs(10)),
+            [None, Some(secs(90)), None]
+        );
+
+        playlist.set_loop_mode(LoopMode::Random);
+        assert_eq!(
+            playlist.start_offsets(Duration::from_secs(10)),
+            [None, None, None]
+        );
+    }
+
+    #[test]
+    fn should_keep_current_track_when_adding_and_removing() {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-tui-playlist-{}", std::process::id()));
+        let db_pod = DBPod::new(&dir).unwrap();
+        let mut playlist = playlist(&[Some(100), Some(200)]);
+        playlist.set_current_track_index(1).unwrap();
+        let current = playlist.current_track().cloned();
+
+        let tracks = vec![
+            PlaylistTrackSource::Url("http://example.com/radio-a".to_string()),
+            PlaylistTrackSource::Url("http://example.com/radio-b".to_string()),
+        ];
+        playlist
+            .add_tracks(PlaylistAddTrack::new_vec(0, tracks), &db_pod)
+            .unwrap();
+        assert_eq!(playlist.current_track_index(), Some(3));
+        assert_eq!(playlist.current_track().cloned(), current);
+
+        // removing after the current track does not move it
+        playlist
+            .handle_grpc_remove(&PlaylistRemoveTrackInfo {
+                at_index: 2,
+                trackid: PlaylistTrackSource::PodcastUrl("http://example.com/0.mp3".to_string()),
+            })
+            .unwrap();
+        assert_eq!(playlist.current_track_index(), Some(2));
+        assert_eq!(playlist.current_track().cloned(), current);
+
+        playlist
+            .handle_grpc_remove(&PlaylistRemoveTrackInfo {
+                at_index: 0,
+                trackid: PlaylistTrackSource::Url("http://example.com/radio-a".to_string()),
+            })
+            .unwrap();
+        assert_eq!(playlist.current_track_index(), Some(1));
+        assert_eq!(playlist.current_track().cloned(), current);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
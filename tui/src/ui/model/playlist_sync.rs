@@ -0,0 +1,82 @@
+//! Syncing playlists with a remote, see [`termusiclib::playlist_sync`].
+
+use anyhow::{Result, bail};
+use termusiclib::config::v2::tui::PlaylistSyncBackend;
+use termusiclib::playlist_sync::{SyncReport, sync_playlists};
+
+use super::Model;
+use crate::ui::msg::{Msg, PlaylistSyncMsg};
+
+impl Model {
+    /// Handle all [`PlaylistSyncMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_playlist_sync(&mut self, msg: PlaylistSyncMsg) {
+        match msg {
+            PlaylistSyncMsg::Start => {
+                if let Err(err) = self.playlist_sync_start() {
+                    self.mount_error_popup(err.context("playlist sync"));
+                }
+            }
+            PlaylistSyncMsg::Done(res) => self.playlist_sync_done(res),
+        }
+    }
+
+    /// Start a sync if it is configured to run on start.
+    pub fn playlist_sync_on_start(&mut self) {
+        let settings = &self.config_tui.read().settings.playlist_sync;
+        if !settings.on_start || settings.backend == PlaylistSyncBackend::None {
+            return;
+        }
+
+        let _ = self
+            .tx_to_main
+            .send(Msg::PlaylistSync(PlaylistSyncMsg::Start));
+    }
+
+    /// Start syncing in the background, [`PlaylistSyncMsg::Done`] is sent once finished.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    fn playlist_sync_start(&mut self) -> Result<()> {
+        if self.playlist_sync_active {
+            bail!("A playlist sync is already running");
+        }
+
+        let settings = self.config_tui.read().settings.playlist_sync.clone();
+        self.playlist_sync_active = true;
+
+        let tx = self.tx_to_main.clone();
+        tokio::spawn(async move {
+            let res = sync_playlists(&settings)
+                .await
+                .map_err(|err| format!("{err:#}"));
+            let _ = tx.send(Msg::PlaylistSync(PlaylistSyncMsg::Done(res)));
+        });
+
+        Ok(())
+    }
+
+    /// Report the result of a finished sync.
+    fn playlist_sync_done(&mut self, res: Result<SyncReport, String>) {
+        self.playlist_sync_active = false;
+
+        let report = match res {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(anyhow::anyhow!(err).context("playlist sync"));
+                return;
+            }
+        };
+
+        for (name, reason) in &report.conflicts {
+            warn!("Playlist sync conflict for \"{name}\": {reason}, kept it");
+        }
+
+        if report.conflicts.is_empty() {
+            self.update_show_message_timeout("Playlist sync finished", &report.to_string(), None);
+        } else {
+            let (name, reason) = &report.conflicts[0];
+            self.mount_error_popup(anyhow::anyhow!(
+                "{report} (see the log), first conflict: \"{name}\" {reason}, kept it"
+            ));
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use tokio::sync::mpsc::UnboundedReceiver;
+use tuirealm::{
+    Event,
+    event::KeyEvent,
+    listener::{ListenerResult, PollAsync},
+};
+
+use crate::ui::model::UserEvent;
+
+/// tuirealm async port to provide key events instead of the terminal, like for headless runs.
+#[derive(Debug)]
+pub struct PortKeys(UnboundedReceiver<KeyEvent>);
+
+impl PortKeys {
+    pub fn new(keys: UnboundedReceiver<KeyEvent>) -> Self {
+        Self(keys)
+    }
+}
+
+#[tuirealm::async_trait]
+impl PollAsync<UserEvent> for PortKeys {
+    async fn poll(&mut self) -> ListenerResult<Option<Event<UserEvent>>> {
+        match self.0.recv().await {
+            Some(key) => Ok(Some(Event::Keyboard(key))),
+            None => Ok(None),
+        }
+    }
+}
@@ -1,2 +1,3 @@
+pub mod keys;
 pub mod rx_main;
 pub mod stream_events;
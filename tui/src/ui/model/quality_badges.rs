@@ -0,0 +1,44 @@
+//! Quality badges of tracks, see [`QualityBadge`].
+
+use std::path::Path;
+
+use termusiclib::new_database::track_ops;
+use termusiclib::track::QualityBadge;
+use tuirealm::props::{Color, TextSpan};
+
+use super::Model;
+
+/// Get the terminal color `badge` is shown in.
+#[must_use]
+pub fn quality_color(badge: QualityBadge) -> Color {
+    match badge {
+        QualityBadge::HiRes => Color::Magenta,
+        QualityBadge::Lossless => Color::Green,
+        QualityBadge::Lossy => Color::DarkGray,
+    }
+}
+
+/// Get `badge` as a colored span, or an empty one if there is no badge.
+#[must_use]
+pub fn quality_span(badge: Option<QualityBadge>) -> TextSpan {
+    match badge {
+        Some(badge) => TextSpan::new(badge.name()).fg(quality_color(badge)).bold(),
+        None => TextSpan::new(""),
+    }
+}
+
+impl Model {
+    /// Load the quality badges of all tracks from the database.
+    pub fn track_qualities_reload(&mut self) {
+        match track_ops::get_all_qualities(&self.db.get_connection()) {
+            Ok(qualities) => self.track_qualities = qualities.into_iter().collect(),
+            Err(err) => error!("Loading the track quality badges failed: {err:#}"),
+        }
+    }
+
+    /// Get the quality badge of the track at `path`.
+    #[must_use]
+    pub fn track_quality(&self, path: &Path) -> Option<QualityBadge> {
+        self.track_qualities.get(path).copied()
+    }
+}
@@ -0,0 +1,272 @@
+//! Replacing the playlist, with a preview of the changes before doing so.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Result, bail};
+use termusiclib::new_database::track_ops;
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistTrackSource,
+};
+use termusiclib::track::Track;
+use termusiclib::utils::is_playlist;
+use tokio::runtime::Handle;
+
+use super::{Model, PendingReplace};
+use crate::ui::msg::{Msg, QueueDurations, QueueReplaceMsg};
+use crate::ui::tui_cmd::{PlaylistCmd, TuiCmd};
+
+/// Counter for [`PendingReplace::id`], so that late previews of a previous replace are ignored.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The difference between the current playlist and the tracks to replace it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueueDiff {
+    /// Tracks that are not in the current playlist
+    added: usize,
+    /// Tracks of the current playlist that are not in the new ones
+    removed: usize,
+}
+
+impl QueueDiff {
+    /// Compare the `current` playlist with the `new` tracks, ignoring order and duplicates.
+    fn new(current: &[PlaylistTrackSource], new: &[PlaylistTrackSource]) -> Self {
+        let current: HashSet<_> = current.iter().map(source_key).collect();
+        let new: HashSet<_> = new.iter().map(source_key).collect();
+
+        Self {
+            added: new.difference(&current).count(),
+            removed: current.difference(&new).count(),
+        }
+    }
+}
+
+/// Get a hashable key for `source`, as [`PlaylistTrackSource`] does not implement [`Hash`].
+fn source_key(source: &PlaylistTrackSource) -> (u8, &str) {
+    match source {
+        PlaylistTrackSource::Path(v) => (0, v),
+        PlaylistTrackSource::Url(v) => (1, v),
+        PlaylistTrackSource::PodcastUrl(v) => (2, v),
+        PlaylistTrackSource::VideoUrl(v) => (3, v),
+    }
+}
+
+impl Model {
+    /// Handle all [`QueueReplaceMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_queue_replace(&mut self, msg: QueueReplaceMsg) {
+        let res = match msg {
+            QueueReplaceMsg::WithPath(path) => self.queue_replace_path(&path),
+            QueueReplaceMsg::WithDatabaseTracks => self.queue_replace_database(),
+            QueueReplaceMsg::PreviewReady(id, durations) => {
+                self.queue_replace_preview(id, durations)
+            }
+            QueueReplaceMsg::Confirm => {
+                self.umount_queue_replace();
+                if let Some(pending) = self.pending_replace.take() {
+                    self.queue_replace_apply(pending.sources);
+                }
+                Ok(())
+            }
+            QueueReplaceMsg::Append => {
+                self.umount_queue_replace();
+                if let Some(pending) = self.pending_replace.take() {
+                    self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+                        PlaylistAddTrack::new_vec(
+                            u64::try_from(self.playback.playlist.len()).unwrap(),
+                            pending.sources,
+                        ),
+                    )));
+                }
+                Ok(())
+            }
+            QueueReplaceMsg::Cancel => {
+                self.pending_replace = None;
+                self.umount_queue_replace();
+                Ok(())
+            }
+        };
+
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("replace playlist"));
+        }
+    }
+
+    /// Replace the playlist with the track at `path`, all tracks in it if it is a directory, or its content if it is a playlist file.
+    fn queue_replace_path(&mut self, path: &Path) -> Result<()> {
+        let sources = if path.is_dir() {
+            Self::playlist_get_dir_entries(path)
+                .into_iter()
+                .map(PlaylistTrackSource::Path)
+                .collect()
+        } else if is_playlist(path) {
+            self.playlist_get_file_sources(path)?
+        } else {
+            let path = path.to_string_lossy().to_string();
+            if path.starts_with("http") {
+                vec![PlaylistTrackSource::Url(path)]
+            } else {
+                vec![PlaylistTrackSource::Path(path)]
+            }
+        };
+
+        self.queue_replace_request(sources)
+    }
+
+    /// Replace the playlist with all tracks in the database view "Tracks" section.
+    fn queue_replace_database(&mut self) -> Result<()> {
+        let sources = self
+            .dw
+            .search_tracks
+            .iter()
+            .map(|track| {
+                PlaylistTrackSource::Path(track.as_pathbuf().to_string_lossy().to_string())
+            })
+            .collect();
+
+        self.queue_replace_request(sources)
+    }
+
+    /// Replace the playlist with `sources`, after confirmation if enabled.
+    ///
+    /// The durations for the preview are queried in the background, see [`QueueReplaceMsg::PreviewReady`].
+    fn queue_replace_request(&mut self, sources: Vec<PlaylistTrackSource>) -> Result<()> {
+        if sources.is_empty() {
+            bail!("Nothing to replace the playlist with");
+        }
+
+        if !self
+            .config_tui
+            .read()
+            .settings
+            .behavior
+            .confirm_queue_replace
+        {
+            self.queue_replace_apply(sources);
+            return Ok(());
+        }
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let paths: Vec<String> = sources
+            .iter()
+            .filter_map(|source| match source {
+                PlaylistTrackSource::Path(v) => Some(v.clone()),
+                PlaylistTrackSource::Url(_)
+                | PlaylistTrackSource::PodcastUrl(_)
+                | PlaylistTrackSource::VideoUrl(_) => None,
+            })
+            .collect();
+        let remote = sources.len() - paths.len();
+        self.pending_replace = Some(PendingReplace { id, sources });
+
+        let tx = self.tx_to_main.clone();
+        let db = self.db.clone();
+        Handle::current().spawn(async move {
+            let res = db
+                .read(move |conn| {
+                    let mut durations = QueueDurations {
+                        unknown: remote,
+                        ..Default::default()
+                    };
+                    for path in paths {
+                        match track_ops::get_track_from_path(conn, Path::new(&path))
+                            .ok()
+                            .and_then(|track| track.duration)
+                        {
+                            Some(duration) => durations.total += duration,
+                            None => durations.unknown += 1,
+                        }
+                    }
+
+                    Ok(durations)
+                })
+                .await;
+
+            match res {
+                Ok(durations) => {
+                    let _ = tx.send(Msg::QueueReplace(QueueReplaceMsg::PreviewReady(
+                        id, durations,
+                    )));
+                }
+                Err(err) => error!("Error querying track durations: {err:#?}"),
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Show the preview for the pending replace, if `id` is still the pending one.
+    fn queue_replace_preview(&mut self, id: usize, durations: QueueDurations) -> Result<()> {
+        let Some(pending) = self.pending_replace.as_ref().filter(|v| v.id == id) else {
+            return Ok(());
+        };
+
+        let current: Vec<PlaylistTrackSource> = self
+            .playback
+            .playlist
+            .tracks()
+            .iter()
+            .map(Track::as_track_source)
+            .collect();
+        let diff = QueueDiff::new(&current, &pending.sources);
+
+        self.mount_queue_replace(diff.added, diff.removed, durations)
+    }
+
+    /// Clear the playlist, add `sources` and start playing the first of them.
+    fn queue_replace_apply(&mut self, sources: Vec<PlaylistTrackSource>) {
+        let Some(first) = sources.first().cloned() else {
+            return;
+        };
+
+        // the server handles commands in order, so the tracks are added to the already cleared playlist
+        self.command(TuiCmd::Playlist(PlaylistCmd::Clear));
+        self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+            PlaylistAddTrack::new_vec(0, sources),
+        )));
+        self.command(TuiCmd::Playlist(PlaylistCmd::PlaySpecific(
+            PlaylistPlaySpecific {
+                track_index: 0,
+                id: first,
+            },
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termusiclib::player::playlist_helpers::PlaylistTrackSource;
+
+    use super::QueueDiff;
+
+    #[test]
+    fn should_diff_queue() {
+        let current = [
+            PlaylistTrackSource::Path("/music/a.mp3".to_string()),
+            PlaylistTrackSource::Path("/music/b.mp3".to_string()),
+            PlaylistTrackSource::Url("https://example.com/stream".to_string()),
+        ];
+        let new = [
+            PlaylistTrackSource::Path("/music/b.mp3".to_string()),
+            PlaylistTrackSource::Path("/music/c.mp3".to_string()),
+            PlaylistTrackSource::Path("/music/c.mp3".to_string()),
+            // same value, but different kind of source
+            PlaylistTrackSource::PodcastUrl("https://example.com/stream".to_string()),
+        ];
+
+        assert_eq!(
+            QueueDiff::new(&current, &new),
+            QueueDiff {
+                added: 2,
+                removed: 2
+            }
+        );
+        assert_eq!(
+            QueueDiff::new(&current, &current),
+            QueueDiff {
+                added: 0,
+                removed: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! The internet radio station browser, backed by [`radio_browser`].
+
+use anyhow::{Context as _, Result, anyhow};
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistTrackInfo, PlaylistTrackSource,
+};
+use termusiclib::radio_browser::{self, Station, StationBookmarks};
+use tokio::runtime::Handle;
+
+use super::Model;
+use crate::ui::ids::Id;
+use crate::ui::msg::{Msg, RadioMsg};
+use crate::ui::tui_cmd::{PlaylistCmd, TuiCmd};
+
+/// The state of the radio station browser, and what the playing station currently plays.
+#[derive(Debug)]
+pub struct RadioBrowser {
+    /// The stations listed in the popup, the results of the last search or the bookmarks
+    stations: Vec<Station>,
+    bookmarks: StationBookmarks,
+    /// Whether a search is running, to show it instead of "no results"
+    searching: bool,
+    /// The title the playing station last sent with its stream, empty if none
+    now_playing: String,
+}
+
+impl RadioBrowser {
+    /// Create the state with the bookmarks loaded from the config directory.
+    pub fn new() -> Self {
+        Self {
+            stations: Vec::new(),
+            bookmarks: StationBookmarks::load_default(),
+            searching: false,
+            now_playing: String::new(),
+        }
+    }
+
+    pub fn stations(&self) -> &[Station] {
+        &self.stations
+    }
+
+    pub fn bookmarks(&self) -> &StationBookmarks {
+        &self.bookmarks
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching
+    }
+
+    /// Get the title of the radio stream `url` for the playlist, the station name instead of the url if known, and
+    /// the current song if `playing`.
+    pub fn row_title(&self, url: &str, playing: bool) -> String {
+        let name = self
+            .bookmarks
+            .name_of(url)
+            .or_else(|| {
+                self.stations
+                    .iter()
+                    .find(|v| v.url == url)
+                    .map(|v| v.name.as_str())
+            })
+            .unwrap_or(url);
+
+        if playing && !self.now_playing.is_empty() {
+            format!("{name}: {}", self.now_playing)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+impl Model {
+    /// Handle all [`RadioMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_radio(&mut self, msg: RadioMsg) {
+        match msg {
+            RadioMsg::InputShow => {
+                if let Err(err) = self.mount_radio_search_input() {
+                    self.mount_error_popup(err.context("show radio search"));
+                }
+            }
+            RadioMsg::InputCloseCancel => self.umount_radio_search_input(),
+            RadioMsg::InputCloseOk(query) => {
+                self.umount_radio_search_input();
+                self.radio_search(query.trim());
+                if let Err(err) = self.mount_radio_stations() {
+                    self.mount_error_popup(err.context("show radio stations"));
+                }
+            }
+            RadioMsg::StationsCloseCancel => self.umount_radio_stations(),
+            RadioMsg::Play(index) => {
+                if let Err(err) = self.radio_play(index) {
+                    self.mount_error_popup(err.context("play radio station"));
+                }
+            }
+            RadioMsg::ToggleBookmark(index) => {
+                if let Err(err) = self.radio_toggle_bookmark(index) {
+                    self.mount_error_popup(err.context("bookmark radio station"));
+                }
+            }
+            RadioMsg::SearchSuccess(stations) => {
+                self.radio.stations = stations;
+                self.radio.searching = false;
+                self.radio_update_popup();
+            }
+            RadioMsg::SearchError(err) => {
+                self.radio.searching = false;
+                self.radio_update_popup();
+                self.mount_error_popup(anyhow!("radio search: {err}"));
+            }
+        }
+    }
+
+    /// Search the stations named like `query` in the background, or list the bookmarks if `query` is empty.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    fn radio_search(&mut self, query: &str) {
+        if query.is_empty() {
+            self.radio.stations = self.radio.bookmarks.stations().to_vec();
+            self.radio.searching = false;
+            return;
+        }
+
+        self.radio.stations.clear();
+        self.radio.searching = true;
+
+        let tx = self.tx_to_main.clone();
+        let query = query.to_string();
+        Handle::current().spawn(async move {
+            let msg = match radio_browser::search(&query).await {
+                Ok(stations) => RadioMsg::SearchSuccess(stations),
+                Err(err) => RadioMsg::SearchError(format!("{err:#}")),
+            };
+            let _ = tx.send(Msg::Radio(msg));
+        });
+    }
+
+    /// Add the station at `index` to the end of the playlist and play it.
+    fn radio_play(&mut self, index: usize) -> Result<()> {
+        let station = self
+            .radio
+            .stations
+            .get(index)
+            .context("no station at the index")?;
+        let name = station.name.clone();
+        let source = PlaylistTrackSource::Url(station.url.clone());
+        let track_index = u64::try_from(self.playback.playlist.len())?;
+
+        // the server handles commands in order, so the track is added before it is played
+        self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+            PlaylistAddTrack::new_single(track_index, source.clone()).with_infos(vec![
+                PlaylistTrackInfo {
+                    title: Some(name.clone()),
+                    duration: None,
+                },
+            ]),
+        )));
+        self.command(TuiCmd::Playlist(PlaylistCmd::PlaySpecific(
+            PlaylistPlaySpecific {
+                track_index,
+                id: source,
+            },
+        )));
+        self.umount_radio_stations();
+        self.update_show_message_timeout("Radio", &name, Some(5));
+
+        Ok(())
+    }
+
+    /// Bookmark the station at `index`, or remove its bookmark.
+    fn radio_toggle_bookmark(&mut self, index: usize) -> Result<()> {
+        let station = self
+            .radio
+            .stations
+            .get(index)
+            .context("no station at the index")?
+            .clone();
+        let mut bookmarked = false;
+        self.radio.bookmarks =
+            StationBookmarks::update_default(|bookmarks| bookmarked = bookmarks.toggle(&station))?;
+
+        let text = if bookmarked {
+            format!("Bookmarked {}", station.name)
+        } else {
+            format!("Removed the bookmark of {}", station.name)
+        };
+        self.update_show_message_timeout("Radio", &text, Some(5));
+        self.radio_update_popup();
+        self.playlist_sync();
+
+        Ok(())
+    }
+
+    /// Set the title the playing station sent with its stream, to show it in the playlist.
+    pub fn radio_set_now_playing(&mut self, title: &str) {
+        if self.radio.now_playing == title {
+            return;
+        }
+        title.clone_into(&mut self.radio.now_playing);
+
+        let playing_radio = self
+            .playback
+            .current_track()
+            .is_some_and(|v| v.as_radio().is_some());
+        if playing_radio {
+            self.playlist_sync();
+        }
+    }
+
+    /// Show the current stations in the radio stations popup, if mounted.
+    fn radio_update_popup(&mut self) {
+        if !self.app.mounted(&Id::RadioStationsPopup) {
+            return;
+        }
+        if let Err(err) = self.mount_radio_stations() {
+            error!("Error updating the radio stations popup: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::radio_browser::{Station, StationBookmarks};
+
+    use super::RadioBrowser;
+
+    #[test]
+    fn should_title_radio_rows() {
+        let station = Station {
+            name: "Jazz Radio".to_string(),
+            url: "https://example.com/jazz.mp3".to_string(),
+            ..Default::default()
+        };
+        let mut bookmarks = StationBookmarks::default();
+        bookmarks.toggle(&station);
+        let mut radio = RadioBrowser {
+            stations: Vec::new(),
+            bookmarks,
+            searching: false,
+            now_playing: String::new(),
+        };
+
+        assert_eq!(radio.row_title(&station.url, true), "Jazz Radio");
+        assert_eq!(
+            radio.row_title("https://example.com/other", false),
+            "https://example.com/other"
+        );
+
+        radio.now_playing = "Artist - Song".to_string();
+        assert_eq!(
+            radio.row_title(&station.url, true),
+            "Jazz Radio: Artist - Song"
+        );
+        assert_eq!(radio.row_title(&station.url, false), "Jazz Radio");
+    }
+}
@@ -0,0 +1,193 @@
+//! Smart playlists in the database view, see [`smart_playlist`](termusiclib::smart_playlist).
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use termusiclib::new_database::smart_playlist_ops;
+use tokio::runtime::Handle;
+
+use super::{Model, SmartPlaylistEdit, TermusicLayout};
+use crate::ui::components::SmartPlaylistField;
+use crate::ui::msg::{DBTracksData, Msg, SearchCriteria, SmartPlaylistMsg};
+
+/// How often the shown tracks of a smart playlist are queried again, to follow changes of the library.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+impl Model {
+    /// Handle all [`SmartPlaylistMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_smart_playlist(&mut self, msg: SmartPlaylistMsg) {
+        match msg {
+            SmartPlaylistMsg::NewShow => {
+                self.dw.smart_playlist_edit = Some(SmartPlaylistEdit::default());
+                self.smart_playlist_mount(SmartPlaylistField::Name, "");
+            }
+            SmartPlaylistMsg::EditShow(index) => self.smart_playlist_edit_show(index),
+            SmartPlaylistMsg::NameOk(name) => self.smart_playlist_name_ok(name),
+            SmartPlaylistMsg::QueryOk(query) => self.smart_playlist_save(query),
+            SmartPlaylistMsg::Cancel => {
+                self.umount_smart_playlist_edit();
+                self.dw.smart_playlist_edit = None;
+            }
+            SmartPlaylistMsg::Delete(index) => self.smart_playlist_delete(index),
+            SmartPlaylistMsg::Refresh(generation) => self.smart_playlist_refresh(generation),
+            SmartPlaylistMsg::Refreshed(generation, data) => {
+                self.smart_playlist_apply_refresh(generation, data);
+            }
+        }
+    }
+
+    /// Mount the popup to enter `field`, showing a error if that fails.
+    fn smart_playlist_mount(&mut self, field: SmartPlaylistField, value: &str) {
+        if let Err(err) = self.mount_smart_playlist_edit(field, value) {
+            self.mount_error_popup(err.context("show smart playlist edit"));
+        }
+    }
+
+    /// Get the name of the smart playlist at `index` in view `Result`, if it shows the smart playlists.
+    fn smart_playlist_at(&self, index: usize) -> Option<String> {
+        if self.dw.criteria != SearchCriteria::SmartPlaylist {
+            return None;
+        }
+
+        self.dw.search_results.get(index).cloned()
+    }
+
+    /// Start editing the smart playlist at `index` in view `Result`.
+    fn smart_playlist_edit_show(&mut self, index: usize) {
+        let Some(name) = self.smart_playlist_at(index) else {
+            return;
+        };
+        let res = smart_playlist_ops::get_by_name(&self.db.get_connection(), &name);
+        let playlist = match res {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                self.mount_error_popup(anyhow!("The smart playlist \"{name}\" does not exist"));
+                return;
+            }
+            Err(err) => {
+                self.mount_error_popup(err.context("get smart playlist"));
+                return;
+            }
+        };
+
+        self.dw.smart_playlist_edit = Some(SmartPlaylistEdit {
+            previous: Some(playlist.name.clone()),
+            name: playlist.name,
+            query: playlist.query,
+        });
+        self.smart_playlist_mount(SmartPlaylistField::Name, &name);
+    }
+
+    /// Store the entered name and continue with the query.
+    fn smart_playlist_name_ok(&mut self, name: String) {
+        let Some(edit) = &mut self.dw.smart_playlist_edit else {
+            self.umount_smart_playlist_edit();
+            return;
+        };
+        if name.trim().is_empty() {
+            self.mount_error_popup(anyhow!("A smart playlist needs a name"));
+            return;
+        }
+        edit.name = name;
+
+        let query = edit.query.clone();
+        self.smart_playlist_mount(SmartPlaylistField::Query, &query);
+    }
+
+    /// Save the edited smart playlist with `query`, keeping the popup open if the query is not valid.
+    fn smart_playlist_save(&mut self, query: String) {
+        let Some(edit) = &mut self.dw.smart_playlist_edit else {
+            self.umount_smart_playlist_edit();
+            return;
+        };
+        edit.query = query;
+
+        let res = smart_playlist_ops::save(
+            &self.db.get_connection(),
+            edit.previous.as_deref(),
+            &edit.name,
+            &edit.query,
+        );
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("save smart playlist"));
+            return;
+        }
+
+        let name = edit.name.trim().to_string();
+        self.umount_smart_playlist_edit();
+        self.dw.smart_playlist_edit = None;
+        self.update_show_message_timeout("Smart playlist", &format!("Saved \"{name}\""), None);
+
+        // show the saved playlist with its current tracks
+        if self.layout == TermusicLayout::DataBase {
+            self.dw.jump_to = Some(name);
+            self.database_select_criteria(SearchCriteria::SmartPlaylist);
+        }
+    }
+
+    /// Delete the smart playlist at `index` in view `Result`.
+    fn smart_playlist_delete(&mut self, index: usize) {
+        let Some(name) = self.smart_playlist_at(index) else {
+            return;
+        };
+
+        let res = smart_playlist_ops::remove(&self.db.get_connection(), &name);
+        match res {
+            Ok(_) => {
+                self.update_show_message_timeout(
+                    "Smart playlist",
+                    &format!("Deleted \"{name}\""),
+                    None,
+                );
+                self.database_update_search_results();
+            }
+            Err(err) => self.mount_error_popup(err.context("delete smart playlist")),
+        }
+    }
+
+    /// Schedule a [`SmartPlaylistMsg::Refresh`] of the shown smart playlist, if one is shown.
+    pub fn smart_playlist_schedule_refresh(&self) {
+        if self.dw.smart_playlist_shown.is_none() {
+            return;
+        }
+
+        let tx = self.tx_to_main.clone();
+        let generation = self.dw.tracks_generation;
+        Handle::current().spawn(async move {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+            let _ = tx.send(Msg::SmartPlaylist(SmartPlaylistMsg::Refresh(generation)));
+        });
+    }
+
+    /// Query the tracks of the shown smart playlist again, if `generation` is still current.
+    fn smart_playlist_refresh(&mut self, generation: u64) {
+        if generation != self.dw.tracks_generation {
+            return;
+        }
+        let Some(name) = self.dw.smart_playlist_shown.clone() else {
+            return;
+        };
+        // nobody is looking, so only check again later
+        if self.layout != TermusicLayout::DataBase {
+            self.smart_playlist_schedule_refresh();
+            return;
+        }
+
+        self.database_spawn_get_tracks(vec![name], move |data| {
+            Msg::SmartPlaylist(SmartPlaylistMsg::Refreshed(generation, data))
+        });
+    }
+
+    /// Show the queried tracks of the smart playlist if they changed, without moving the focus.
+    fn smart_playlist_apply_refresh(&mut self, generation: u64, data: DBTracksData) {
+        if generation != self.dw.tracks_generation {
+            return;
+        }
+
+        if data.tracks != self.dw.search_tracks {
+            self.dw.search_tracks = data.tracks;
+            self.database_sync_tracks_results();
+        }
+        self.smart_playlist_schedule_refresh();
+    }
+}
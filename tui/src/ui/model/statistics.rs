@@ -0,0 +1,95 @@
+//! Ratings of tracks and the listening statistics, see [`track_ops::get_top_tracks`].
+
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use termusiclib::new_database::track_ops;
+
+use super::Model;
+use crate::ui::msg::StatisticsMsg;
+
+/// How many artists and tracks are shown in the statistics.
+const TOP_LIMIT: u32 = 10;
+/// How many weeks of listening time are shown in the statistics.
+const WEEKS: u32 = 8;
+
+impl Model {
+    /// Handle all [`StatisticsMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_statistics(&mut self, msg: StatisticsMsg) {
+        match msg {
+            StatisticsMsg::Show => {
+                if let Err(err) = self.statistics_show() {
+                    self.mount_error_popup(err.context("show statistics"));
+                }
+            }
+            StatisticsMsg::Close => self.umount_statistics(),
+            StatisticsMsg::RateShow => self.rating_show(),
+            StatisticsMsg::RateCancel => self.umount_rating(),
+            StatisticsMsg::Rate(path, rating) => {
+                self.umount_rating();
+                self.rating_set(&path, rating);
+            }
+        }
+    }
+
+    /// Query the statistics and show them in a popup.
+    fn statistics_show(&mut self) -> Result<()> {
+        let (artists, tracks, weeks) = {
+            let conn = self.db.get_connection();
+            (
+                track_ops::get_top_artists(&conn, TOP_LIMIT)?,
+                track_ops::get_top_tracks(&conn, TOP_LIMIT)?,
+                track_ops::get_listening_per_week(&conn, WEEKS, Utc::now())?,
+            )
+        };
+
+        self.mount_statistics(&artists, &tracks, &weeks)
+    }
+
+    /// Show the popup to rate the current track.
+    fn rating_show(&mut self) {
+        let Some(track) = self.playback.current_track() else {
+            self.mount_error_popup(anyhow!("Nothing is playing to rate"));
+            return;
+        };
+        let Some(path) = track.path() else {
+            self.mount_error_popup(anyhow!("Only tracks of the music library can be rated"));
+            return;
+        };
+        let path = path.to_path_buf();
+        let title = track.title().map_or_else(
+            || path.file_stem().unwrap_or_default().to_string_lossy(),
+            Into::into,
+        );
+        let title = title.into_owned();
+
+        let res = track_ops::get_rating(&self.db.get_connection(), &path);
+        let rating = match res {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(err.context("get rating"));
+                return;
+            }
+        };
+
+        if let Err(err) = self.mount_rating(path, &title, rating) {
+            self.mount_error_popup(err.context("show rating"));
+        }
+    }
+
+    /// Rate the track at `path` with `rating` stars, or remove its rating.
+    fn rating_set(&mut self, path: &Path, rating: Option<u8>) {
+        let res = track_ops::set_rating(&self.db.get_connection(), path, rating);
+        if let Err(err) = res {
+            self.mount_error_popup(err.context("set rating"));
+            return;
+        }
+
+        let text = match rating {
+            Some(rating) => format!("Rated {}", "★".repeat(rating.into())),
+            None => "Removed the rating".to_string(),
+        };
+        self.update_show_message_timeout("Rating", &text, None);
+    }
+}
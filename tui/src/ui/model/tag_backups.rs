@@ -0,0 +1,32 @@
+//! Backups before tags get written or library files get renamed, see [`tag_backup`](termusiclib::tag_backup).
+
+use std::path::Path;
+
+use anyhow::Result;
+use termusiclib::tag_backup::BackupStore;
+
+use super::Model;
+
+impl Model {
+    /// Get the configured [`BackupStore`].
+    pub fn tag_backup_store(&self) -> Result<BackupStore> {
+        BackupStore::from_settings(&self.config_tui.read().settings.tag_backups)
+    }
+
+    /// Back up the tags of the file at `path`, before they get written by `description`.
+    ///
+    /// The write should not happen if this fails, as it could not be undone.
+    pub fn tag_backup_tags(&self, description: &str, path: &Path) -> Result<()> {
+        self.tag_backup_store()?.backup_tags(description, &[path])?;
+
+        Ok(())
+    }
+
+    /// Record that `from` is about to be renamed to `to`.
+    pub fn tag_backup_rename(&self, description: &str, from: &Path, to: &Path) -> Result<()> {
+        self.tag_backup_store()?
+            .backup_rename(description, from, to)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+//! The terminal the [`Model`](super::Model) draws to, either the real one or a headless buffer.
+
+use std::io::{self, Stdout, Write};
+
+use tuirealm::ratatui::Terminal;
+use tuirealm::ratatui::backend::{Backend, ClearType, CrosstermBackend, TestBackend, WindowSize};
+use tuirealm::ratatui::buffer::{Buffer, Cell};
+use tuirealm::ratatui::layout::{Position, Size};
+use tuirealm::ratatui::{CompletedFrame, Frame};
+use tuirealm::terminal::{
+    CrosstermTerminalAdapter, TerminalAdapter, TerminalError, TerminalResult,
+};
+
+/// The backend of a [`TermusicTerminal`].
+pub enum TermusicBackend {
+    /// Draw to stdout
+    Crossterm(CrosstermBackend<Stdout>),
+    /// Draw to a buffer, for tests and `--script`
+    Headless(TestBackend),
+}
+
+impl Backend for TermusicBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        match self {
+            Self::Crossterm(backend) => backend.draw(content),
+            Self::Headless(backend) => backend.draw(content),
+        }
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.hide_cursor(),
+            Self::Headless(backend) => backend.hide_cursor(),
+        }
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.show_cursor(),
+            Self::Headless(backend) => backend.show_cursor(),
+        }
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        match self {
+            Self::Crossterm(backend) => backend.get_cursor_position(),
+            Self::Headless(backend) => backend.get_cursor_position(),
+        }
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.set_cursor_position(position),
+            Self::Headless(backend) => backend.set_cursor_position(position),
+        }
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.clear(),
+            Self::Headless(backend) => backend.clear(),
+        }
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.clear_region(clear_type),
+            Self::Headless(backend) => backend.clear_region(clear_type),
+        }
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        match self {
+            Self::Crossterm(backend) => backend.size(),
+            Self::Headless(backend) => backend.size(),
+        }
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        match self {
+            Self::Crossterm(backend) => backend.window_size(),
+            Self::Headless(backend) => backend.window_size(),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => Backend::flush(backend),
+            Self::Headless(backend) => Backend::flush(backend),
+        }
+    }
+}
+
+/// Raw writes, like the escape codes of the cover protocols, are discarded when headless.
+impl Write for TermusicBackend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Crossterm(backend) => backend.write(buf),
+            Self::Headless(_) => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => Write::flush(backend),
+            Self::Headless(_) => Ok(()),
+        }
+    }
+}
+
+/// Terminal adapter like [`CrosstermTerminalAdapter`], which can also draw to a headless buffer.
+pub struct TermusicTerminal {
+    terminal: Terminal<TermusicBackend>,
+}
+
+impl TermusicTerminal {
+    /// Create a terminal drawing to stdout.
+    pub fn new_crossterm() -> TerminalResult<Self> {
+        Self::new(TermusicBackend::Crossterm(CrosstermBackend::new(
+            io::stdout(),
+        )))
+    }
+
+    /// Create a terminal drawing to a buffer of `width` and `height`.
+    pub fn new_headless(width: u16, height: u16) -> Self {
+        Self::new(TermusicBackend::Headless(TestBackend::new(width, height)))
+            .expect("Expected a buffer terminal to always be creatable")
+    }
+
+    fn new(backend: TermusicBackend) -> TerminalResult<Self> {
+        let terminal = Terminal::new(backend).map_err(|_| TerminalError::CannotConnectStdout)?;
+
+        Ok(Self { terminal })
+    }
+
+    pub fn raw_mut(&mut self) -> &mut Terminal<TermusicBackend> {
+        &mut self.terminal
+    }
+
+    /// Get whether this terminal draws to a buffer instead of stdout.
+    pub fn is_headless(&self) -> bool {
+        matches!(self.terminal.backend(), TermusicBackend::Headless(_))
+    }
+
+    /// Resize the buffer of a headless terminal, no-op otherwise.
+    pub fn resize_headless(&mut self, width: u16, height: u16) {
+        if let TermusicBackend::Headless(backend) = self.terminal.backend_mut() {
+            backend.resize(width, height);
+        }
+    }
+
+    /// Get the text of the last drawn screen of a headless terminal, [`None`] otherwise.
+    pub fn snapshot(&self) -> Option<String> {
+        match self.terminal.backend() {
+            TermusicBackend::Headless(backend) => Some(buffer_text(backend.buffer())),
+            TermusicBackend::Crossterm(_) => None,
+        }
+    }
+}
+
+/// Get the symbols of `buffer` as lines, without trailing whitespace.
+fn buffer_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        let line: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Like [`CrosstermTerminalAdapter`], all terminal modes are no-ops when headless.
+impl TerminalAdapter for TermusicTerminal {
+    fn draw<F>(&mut self, render_callback: F) -> TerminalResult<CompletedFrame<'_>>
+    where
+        F: FnOnce(&mut Frame<'_>),
+    {
+        self.terminal
+            .draw(render_callback)
+            .map_err(|_| TerminalError::CannotDrawFrame)
+    }
+
+    fn clear_screen(&mut self) -> TerminalResult<()> {
+        self.terminal
+            .clear()
+            .map_err(|_| TerminalError::CannotClear)
+    }
+
+    fn enable_raw_mode(&mut self) -> TerminalResult<()> {
+        if self.is_headless() {
+            return Ok(());
+        }
+        crossterm_adapter()?.enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> TerminalResult<()> {
+        if self.is_headless() {
+            return Ok(());
+        }
+        crossterm_adapter()?.disable_raw_mode()
+    }
+
+    fn enter_alternate_screen(&mut self) -> TerminalResult<()> {
+        if self.is_headless() {
+            return Ok(());
+        }
+        crossterm_adapter()?.enter_alternate_screen()
+    }
+
+    fn leave_alternate_screen(&mut self) -> TerminalResult<()> {
+        if self.is_headless() {
+            return Ok(());
+        }
+        crossterm_adapter()?.leave_alternate_screen()
+    }
+
+    fn enable_mouse_capture(&mut self) -> TerminalResult<()> {
+        if self.is_headless() {
+            return Ok(());
+        }
+        crossterm_adapter()?.enable_mouse_capture()
+    }
+
+    fn disable_mouse_capture(&mut self) -> TerminalResult<()> {
+        if self.is_headless() {
+            return Ok(());
+        }
+        crossterm_adapter()?.disable_mouse_capture()
+    }
+}
+
+/// Get a adapter for the terminal modes, which are global to the process and so independent of the instance.
+fn crossterm_adapter() -> TerminalResult<CrosstermTerminalAdapter> {
+    CrosstermTerminalAdapter::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tuirealm::ratatui::widgets::Paragraph;
+
+    use super::TermusicTerminal;
+
+    #[test]
+    fn should_snapshot_headless() {
+        let mut terminal = TermusicTerminal::new_headless(6, 2);
+        terminal
+            .raw_mut()
+            .draw(|f| f.render_widget(Paragraph::new("ab\ncdef"), f.area()))
+            .unwrap();
+
+        assert_eq!(terminal.snapshot().as_deref(), Some("ab\ncdef\n"));
+        assert!(terminal.is_headless());
+
+        terminal.resize_headless(3, 1);
+        terminal
+            .raw_mut()
+            .draw(|f| f.render_widget(Paragraph::new("xyz"), f.area()))
+            .unwrap();
+        assert_eq!(terminal.snapshot().as_deref(), Some("xyz\n"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Color labels of tracks, see [`TrackLabel`].
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use termusiclib::new_database::track_ops::{self, TrackLabel};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+use tuirealm::props::{Color, TextSpan};
+
+use super::Model;
+
+/// The symbol shown in front of a labeled track.
+pub const LABEL_SYMBOL: &str = "\u{25cf} ";
+
+/// Get the label to set for `key`: `Alt+1` to `Alt+6` for the labels, `Alt+0` to remove it.
+///
+/// Returns [`None`] if `key` is not a label key.
+#[must_use]
+#[allow(clippy::option_option)] // "no label key" and "remove the label" are different
+pub fn label_for_key(key: KeyEvent) -> Option<Option<TrackLabel>> {
+    let KeyEvent {
+        code: Key::Char(ch),
+        modifiers: KeyModifiers::ALT,
+    } = key
+    else {
+        return None;
+    };
+
+    let number = u8::try_from(ch.to_digit(10)?).ok()?;
+    if number == 0 {
+        return Some(None);
+    }
+
+    TrackLabel::from_number(number).map(Some)
+}
+
+/// Get the terminal color `label` is shown in.
+#[must_use]
+pub fn label_color(label: TrackLabel) -> Color {
+    match label {
+        TrackLabel::Red => Color::Red,
+        TrackLabel::Yellow => Color::Yellow,
+        TrackLabel::Green => Color::Green,
+        TrackLabel::Cyan => Color::Cyan,
+        TrackLabel::Blue => Color::Blue,
+        TrackLabel::Magenta => Color::Magenta,
+    }
+}
+
+/// Prefix `span` with the [`LABEL_SYMBOL`] and color it in the color of `label`, if there is one.
+#[must_use]
+pub fn with_label(mut span: TextSpan, label: Option<TrackLabel>) -> TextSpan {
+    let Some(label) = label else {
+        return span;
+    };
+
+    span.content.insert_str(0, LABEL_SYMBOL);
+    span.fg(label_color(label))
+}
+
+impl Model {
+    /// Load the labels of all tracks from the database.
+    pub fn track_labels_reload(&mut self) {
+        match track_ops::get_all_labels(&self.db.get_connection()) {
+            Ok(labels) => self.track_labels = labels.into_iter().collect(),
+            Err(err) => error!("Loading the track labels failed: {err:#}"),
+        }
+    }
+
+    /// Get the label of the track at `path`.
+    #[must_use]
+    pub fn track_label(&self, path: &Path) -> Option<TrackLabel> {
+        self.track_labels.get(path).copied()
+    }
+
+    /// Set the label of the track at `path`, [`None`] to remove it, and update the playlist and database views.
+    pub fn track_label_set(&mut self, path: PathBuf, label: Option<TrackLabel>) {
+        if let Err(err) = self.track_label_store(&path, label) {
+            self.mount_error_popup(err.context("set track label"));
+            return;
+        }
+
+        match label {
+            Some(label) => {
+                self.track_labels.insert(path, label);
+            }
+            None => {
+                self.track_labels.remove(&path);
+            }
+        }
+
+        self.playlist_sync();
+        self.database_sync_tracks_results();
+    }
+
+    /// Set the label of the track at `index` in the playlist.
+    pub fn playlist_set_label(&mut self, index: usize, label: Option<TrackLabel>) {
+        let Some(track) = self.playback.playlist.tracks().get(index) else {
+            return;
+        };
+        let Some(path) = track.path().map(Path::to_path_buf) else {
+            self.mount_error_popup(anyhow::anyhow!(
+                "Only local tracks can be labeled, not radio streams or podcasts"
+            ));
+            return;
+        };
+
+        self.track_label_set(path, label);
+    }
+
+    /// Set the label of the track at `index` in the `Tracks` view of the database.
+    pub fn database_set_label(&mut self, index: usize, label: Option<TrackLabel>) {
+        let Some(track) = self.dw.search_tracks.get(index) else {
+            return;
+        };
+
+        self.track_label_set(track.as_pathbuf(), label);
+    }
+
+    fn track_label_store(&self, path: &Path, label: Option<TrackLabel>) -> Result<()> {
+        track_ops::set_label(&self.db.get_connection(), path, label)
+            .with_context(|| format!("\"{}\" is not in the library", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use termusiclib::new_database::track_ops::TrackLabel;
+    use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+
+    use super::label_for_key;
+
+    #[test]
+    fn should_map_label_keys() {
+        let alt = |ch| KeyEvent::new(Key::Char(ch), KeyModifiers::ALT);
+
+        assert_eq!(label_for_key(alt('1')), Some(Some(TrackLabel::Red)));
+        assert_eq!(label_for_key(alt('6')), Some(Some(TrackLabel::Magenta)));
+        assert_eq!(label_for_key(alt('0')), Some(None));
+        assert_eq!(label_for_key(alt('7')), None);
+        assert_eq!(label_for_key(alt('a')), None);
+        assert_eq!(
+            label_for_key(KeyEvent::new(Key::Char('1'), KeyModifiers::NONE)),
+            None
+        );
+    }
+}
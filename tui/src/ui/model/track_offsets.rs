@@ -0,0 +1,62 @@
+//! Custom start and end offsets of tracks, see [`TrackOffsets`].
+
+use std::path::Path;
+
+use anyhow::Context;
+use termusiclib::new_database::track_ops::{self, TrackOffsets};
+
+use super::Model;
+
+impl Model {
+    /// Show the offsets popup for the track at `index` in the playlist.
+    pub fn playlist_offsets_show(&mut self, index: usize) {
+        let Some(track) = self.playback.playlist.tracks().get(index) else {
+            return;
+        };
+        let Some(path) = track.path().map(Path::to_path_buf) else {
+            self.mount_error_popup(anyhow::anyhow!(
+                "Only local tracks can have offsets, not radio streams or podcasts"
+            ));
+            return;
+        };
+
+        let res = track_ops::get_offsets(&self.db.get_connection(), &path);
+        let offsets = match res {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(err.context("get track offsets"));
+                return;
+            }
+        };
+
+        if let Err(err) = self.mount_track_offsets(path, offsets) {
+            self.mount_error_popup(err.context("mount track offsets"));
+        }
+    }
+
+    /// Parse `input` as [`TrackOffsets`] and store them for the track at `path`, empty to remove them.
+    ///
+    /// The player uses them from the next time the track is played.
+    pub fn track_offsets_set(&mut self, path: &Path, input: &str) {
+        let res = TrackOffsets::parse(input).and_then(|offsets| {
+            track_ops::set_offsets(&self.db.get_connection(), path, offsets)
+                .with_context(|| format!("\"{}\" is not in the library", path.display()))?;
+
+            Ok(offsets)
+        });
+
+        match res {
+            Ok(offsets) if offsets.is_empty() => {
+                self.update_show_message_timeout("Track offsets", "Removed the offsets", None);
+            }
+            Ok(offsets) => {
+                self.update_show_message_timeout(
+                    "Track offsets",
+                    &format!("Set to {offsets}, used from the next play"),
+                    None,
+                );
+            }
+            Err(err) => self.mount_error_popup(err.context("set track offsets")),
+        }
+    }
+}
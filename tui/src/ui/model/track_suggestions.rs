@@ -0,0 +1,146 @@
+//! The panel next to the playlist suggesting tracks of the library based on the playing one.
+//!
+//! See [`TrackSuggestions`](termusiclib::config::v2::tui::TrackSuggestions) for the configuration.
+
+use std::path::{Path, PathBuf};
+
+use termusiclib::track_suggestions::{self, TrackSuggestion};
+use tokio::runtime::Handle;
+
+use super::Model;
+use crate::ui::ids::Id;
+use crate::ui::msg::{Msg, TrackSuggestionsMsg};
+
+/// The suggestions shown in the panel.
+#[derive(Debug, Default)]
+pub struct TrackSuggestionsData {
+    /// The track the suggestions are for, [`None`] if nothing is playing or they need to be refreshed
+    track: Option<PathBuf>,
+    suggestions: Vec<TrackSuggestion>,
+}
+
+impl Model {
+    /// Handle all [`TrackSuggestionsMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_track_suggestions(&mut self, msg: TrackSuggestionsMsg) {
+        match msg {
+            TrackSuggestionsMsg::Toggle => self.track_suggestions_toggle(),
+            TrackSuggestionsMsg::Blur => {
+                self.app.active(&Id::Playlist).ok();
+            }
+            TrackSuggestionsMsg::LastFm(path, suggestions) => {
+                self.track_suggestions_add(&path, suggestions);
+            }
+        }
+    }
+
+    /// Show and focus the panel, focus it if it is shown but not focused, or hide it if it is focused.
+    fn track_suggestions_toggle(&mut self) {
+        if !self.app.mounted(&Id::TrackSuggestions) {
+            self.track_suggestions.track = None;
+            if let Err(err) = self.mount_track_suggestions(&[]) {
+                self.mount_error_popup(err.context("show track suggestions"));
+                return;
+            }
+            self.track_suggestions_update();
+            self.app.active(&Id::TrackSuggestions).ok();
+        } else if self.app.focus() == Some(&Id::TrackSuggestions) {
+            self.umount_track_suggestions();
+            self.app.active(&Id::Playlist).ok();
+        } else {
+            self.app.active(&Id::TrackSuggestions).ok();
+        }
+    }
+
+    /// Refresh the suggestions if the panel is shown and the current track changed.
+    ///
+    /// The suggestions from Last.fm, if enabled, are added once they arrive with [`TrackSuggestionsMsg::LastFm`].
+    pub fn track_suggestions_update(&mut self) {
+        if !self.app.mounted(&Id::TrackSuggestions) {
+            return;
+        }
+
+        let track = self.playback.current_track();
+        let path = track.and_then(|v| v.path()).map(Path::to_path_buf);
+        if path.is_some() && path == self.track_suggestions.track {
+            return;
+        }
+
+        let settings = self.config_tui.read().settings.track_suggestions.clone();
+        let per_section = usize::from(settings.per_section);
+        let suggestions = path.as_deref().map_or_else(Vec::new, |path| {
+            track_suggestions::from_database(&self.db.get_connection(), path, per_section)
+                .inspect_err(|err| warn!("Error getting the track suggestions: {err:#}"))
+                .unwrap_or_default()
+        });
+
+        if settings.lastfm
+            && let Some(path) = &path
+            && let Some((artist, title)) = track.and_then(|v| Some((v.artist()?, v.title()?)))
+        {
+            let mut exclude: Vec<PathBuf> = suggestions.iter().map(|v| v.path.clone()).collect();
+            exclude.push(path.clone());
+            self.track_suggestions_request_lastfm(
+                path.clone(),
+                artist.to_string(),
+                title.to_string(),
+                exclude,
+                per_section,
+            );
+        }
+
+        if let Err(err) = self.mount_track_suggestions(&suggestions) {
+            warn!("Error showing the track suggestions: {err:#}");
+        }
+        self.track_suggestions = TrackSuggestionsData {
+            track: path,
+            suggestions,
+        };
+    }
+
+    /// Get the tracks of the library Last.fm lists as similar in the background.
+    fn track_suggestions_request_lastfm(
+        &self,
+        path: PathBuf,
+        artist: String,
+        title: String,
+        exclude: Vec<PathBuf>,
+        limit: usize,
+    ) {
+        let tx = self.tx_to_main.clone();
+        let db = self.db.clone();
+        Handle::current().spawn(async move {
+            let res = match track_suggestions::lastfm_similar(&artist, &title).await {
+                Ok(similar) => {
+                    db.read(move |conn| {
+                        track_suggestions::lastfm_in_library(conn, &similar, &exclude, limit)
+                    })
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+
+            match res {
+                Ok(suggestions) => {
+                    let _ = tx.send(Msg::TrackSuggestions(TrackSuggestionsMsg::LastFm(
+                        path,
+                        suggestions,
+                    )));
+                }
+                Err(err) => warn!("Error getting similar tracks from Last.fm: {err:#}"),
+            }
+        });
+    }
+
+    /// Add `suggestions` for the track at `path`, unless the current track changed in the meantime.
+    fn track_suggestions_add(&mut self, path: &Path, suggestions: Vec<TrackSuggestion>) {
+        if self.track_suggestions.track.as_deref() != Some(path) || suggestions.is_empty() {
+            return;
+        }
+
+        self.track_suggestions.suggestions.extend(suggestions);
+        let suggestions = self.track_suggestions.suggestions.clone();
+        if let Err(err) = self.mount_track_suggestions(&suggestions) {
+            warn!("Error showing the track suggestions: {err:#}");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Translating the lyric or episode description shown in the lyric pane, shown side by side with the original.
+//!
+//! See [`Translation`](termusiclib::config::v2::tui::Translation) for the configuration.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use termusiclib::translate;
+use tokio::runtime::Handle;
+use unicode_width::UnicodeWidthStr;
+
+use super::Model;
+use crate::ui::msg::{Msg, TranslationMsg};
+
+/// The separator between the original and the translated column.
+const SEPARATOR: &str = " │ ";
+
+/// The translated lines of the text shown in the lyric pane.
+#[derive(Debug, Default)]
+pub struct LyricTranslation {
+    /// Whether the translation is shown
+    pub enabled: bool,
+    /// What the lines belong to, like the path of a track or the url of a episode
+    for_key: String,
+    /// Whether the lines of `for_key` are currently being translated
+    pending: bool,
+    /// The translation of each original line
+    lines: HashMap<String, String>,
+}
+
+impl LyricTranslation {
+    /// Get the translation of `line` of the text of `for_key`, if shown and already translated.
+    pub fn get(&self, for_key: &str, line: &str) -> Option<&str> {
+        if !self.enabled || self.pending || self.for_key != for_key {
+            return None;
+        }
+
+        self.lines.get(line).map(String::as_str)
+    }
+}
+
+/// Lay out `original` and `translated` side by side in two columns of `width`, wrapping both.
+pub fn side_by_side(original: &str, translated: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let left = textwrap::wrap(original, width);
+    let right = textwrap::wrap(translated, width);
+
+    (0..left.len().max(right.len()))
+        .map(|idx| {
+            let left = left.get(idx).map_or("", |v| v.as_ref());
+            let right = right.get(idx).map_or("", |v| v.as_ref());
+            let pad = width.saturating_sub(left.width());
+            format!("{left}{:pad$}{SEPARATOR}{right}", "")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+impl Model {
+    /// Handle all [`TranslationMsg`] messages. Sub-function for [`update`](tuirealm::Update::update).
+    pub fn update_translation(&mut self, msg: TranslationMsg) {
+        match msg {
+            TranslationMsg::Toggle => self.translation_toggle(),
+            TranslationMsg::Done(for_key, res) => self.translation_done(&for_key, res),
+        }
+    }
+
+    /// Show or hide the translation.
+    fn translation_toggle(&mut self) {
+        if !self.config_tui.read().settings.translation.is_enabled() {
+            self.mount_error_popup(anyhow!(
+                "No translation provider is configured, see \"[translation]\" in the tui config"
+            ));
+            return;
+        }
+
+        let translation = &mut self.lyric_translation;
+        translation.enabled = !translation.enabled;
+        let state = if translation.enabled { "on" } else { "off" };
+        self.update_show_message_timeout("Translation", &format!("Translation {state}"), None);
+
+        self.lyric_update_title();
+        self.lyric_update();
+    }
+
+    /// Translate `lines` of the text of `for_key` in the background, if shown and not already translated.
+    ///
+    /// [`TranslationMsg::Done`] is sent once translated.
+    pub fn translation_request(&mut self, for_key: &str, lines: Vec<String>) {
+        let translation = &mut self.lyric_translation;
+        if !translation.enabled || translation.for_key == for_key {
+            return;
+        }
+
+        translation.for_key = for_key.to_string();
+        translation.pending = true;
+        translation.lines.clear();
+
+        let settings = self.config_tui.read().settings.translation.clone();
+        let tx = self.tx_to_main.clone();
+        let for_key = for_key.to_string();
+        Handle::current().spawn(async move {
+            let res = translate::translate(&settings, &lines)
+                .await
+                .map(|translated| lines.into_iter().zip(translated).collect())
+                .map_err(|err| format!("{err:#}"));
+
+            let _ = tx.send(Msg::Translation(TranslationMsg::Done(for_key, res)));
+        });
+    }
+
+    /// Apply the finished translation of the text of `for_key`, unless the text changed in the meantime.
+    fn translation_done(&mut self, for_key: &str, res: Result<Vec<(String, String)>, String>) {
+        let translation = &mut self.lyric_translation;
+        if translation.for_key != for_key {
+            return;
+        }
+        translation.pending = false;
+
+        match res {
+            Ok(lines) => translation.lines = lines.into_iter().collect(),
+            Err(err) => {
+                // hide it again, so that toggling retries
+                translation.enabled = false;
+                translation.for_key.clear();
+                self.mount_error_popup(anyhow!(err).context("translate"));
+                self.lyric_update_title();
+            }
+        }
+
+        self.lyric_update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::side_by_side;
+
+    #[test]
+    fn should_lay_out_side_by_side() {
+        assert_eq!(side_by_side("Hello", "Hallo", 8), ["Hello    │ Hallo"]);
+        assert_eq!(
+            side_by_side("Good morning", "Guten Morgen, Welt", 7),
+            ["Good    │ Guten", "morning │ Morgen,", "        │ Welt"]
+        );
+        // wide characters take two columns
+        assert_eq!(side_by_side("你好", "Hi", 5), ["你好  │ Hi"]);
+        assert_eq!(side_by_side("", "", 3), ["    │"]);
+    }
+}
@@ -3,23 +3,25 @@ use std::time::Duration;
 
 use anyhow::{Result, anyhow};
 use termusiclib::player::{
-    PlayerProgress, RunningStatus, UpdateEvents, UpdatePlaylistEvents, clamp_u16,
+    AbLoop, PlayerProgress, RunningStatus, UpdateEvents, UpdatePlaylistEvents, clamp_u16, features,
 };
 use termusiclib::podcast::{PodcastDLResult, PodcastSyncResult};
+use termusiclib::search_history::HistoryKind;
 use termusiclib::track::MediaTypesSimple;
+use termusiclib::ytdlp;
 use tokio::runtime::Handle;
 use tokio::time::sleep;
-use tuirealm::Update;
 use tuirealm::props::{AttrValue, Attribute};
+use tuirealm::{State, StateValue, Update};
 
 use crate::ui::ids::Id;
 use crate::ui::model::youtube_options::YTDLMsg;
 use crate::ui::msg::{
-    CoverDLResult, DBMsg, DeleteConfirmMsg, ErrorPopupMsg, GSMsg, HelpPopupMsg, LIMsg, LyricMsg,
-    MainLayoutMsg, Msg, NotificationMsg, PCMsg, PLMsg, PlayerMsg, QuitPopupMsg, SavePlaylistMsg,
-    ServerReqResponse, XYWHMsg, YSMsg,
+    ActionConfirmMsg, ConfirmAction, CoverDLResult, DBMsg, DeleteConfirmMsg, ErrorPopupMsg, GSMsg,
+    HelpPopupMsg, LIMsg, LyricMsg, MainLayoutMsg, Msg, NotificationMsg, PCMsg, PLMsg, PlayerMsg,
+    QuitPopupMsg, SavePlaylistMsg, SearchHistoryMsg, ServerReqResponse, XYWHMsg, YSMsg,
 };
-use crate::ui::tui_cmd::TuiCmd;
+use crate::ui::tui_cmd::{BackendCmd, TuiCmd};
 use crate::ui::{Model, model::TermusicLayout};
 
 impl Update<Msg> for Model {
@@ -34,9 +36,81 @@ impl Update<Msg> for Model {
             Msg::DataBase(msg) => self.update_database_list(msg),
 
             Msg::DeleteConfirm(msg) => self.update_delete_confirmation(msg),
+            Msg::DeviceSync(msg) => {
+                self.update_device_sync(msg);
+                None
+            }
+            Msg::PlaylistSync(msg) => {
+                self.update_playlist_sync(msg);
+                None
+            }
+            Msg::LyricsFetch(msg) => {
+                self.update_lyrics_fetch(msg);
+                None
+            }
+            Msg::Translation(msg) => {
+                self.update_translation(msg);
+                None
+            }
+            Msg::QueueReplace(msg) => {
+                self.update_queue_replace(msg);
+                None
+            }
 
             Msg::ErrorPopup(msg) => self.update_error_popup_msg(&msg),
             Msg::QuitPopup(msg) => self.update_quit_popup_msg(&msg),
+            Msg::ActionConfirm(msg) => {
+                self.update_action_confirm_msg(&msg);
+                None
+            }
+            Msg::SearchHistory(msg) => {
+                self.update_search_history_msg(msg);
+                None
+            }
+            Msg::Credits(msg) => {
+                self.update_credits(msg);
+                None
+            }
+            Msg::Downloads(msg) => {
+                self.update_downloads(msg);
+                None
+            }
+            Msg::Radio(msg) => {
+                self.update_radio(msg);
+                None
+            }
+            Msg::Equalizer(msg) => {
+                self.update_equalizer(msg);
+                None
+            }
+            Msg::OutputDevices(msg) => {
+                self.update_output_devices(msg);
+                None
+            }
+            Msg::Bookmarks(msg) => {
+                self.update_bookmarks(msg);
+                None
+            }
+            Msg::SmartPlaylist(msg) => {
+                self.update_smart_playlist(msg);
+                None
+            }
+            Msg::Statistics(msg) => {
+                self.update_statistics(msg);
+                None
+            }
+            Msg::TrackSuggestions(msg) => {
+                self.update_track_suggestions(msg);
+                None
+            }
+            Msg::SeekToPosition(msg) => {
+                self.update_seek_to_position(&msg);
+                None
+            }
+            Msg::RunMacro(name) => {
+                self.run_macro(name);
+                None
+            }
 
             Msg::Library(msg) => {
                 self.update_library(msg);
@@ -128,7 +202,9 @@ impl Model {
     fn update_quit_popup_msg(&mut self, msg: &QuitPopupMsg) -> Option<Msg> {
         match msg {
             QuitPopupMsg::Show => {
-                if self.config_tui.read().settings.behavior.confirm_quit {
+                let behavior = self.config_tui.read().settings.behavior.clone();
+                let playing = self.playback.status() == RunningStatus::Running;
+                if behavior.confirm_quit && (!behavior.confirm_quit_only_playing || playing) {
                     self.mount_quit_popup();
                 } else {
                     self.quit = true;
@@ -145,6 +221,38 @@ impl Model {
         None
     }
 
+    /// Handle all [`ActionConfirmMsg`] messages. Sub-function for [`update`](Self::update).
+    fn update_action_confirm_msg(&mut self, msg: &ActionConfirmMsg) {
+        self.umount_action_confirm();
+
+        match msg {
+            ActionConfirmMsg::Ok(ConfirmAction::ClearPlaylist) => self.playlist_clear(),
+            ActionConfirmMsg::Ok(ConfirmAction::OverwriteTags) => {
+                if let Err(e) = self.te_rename_song_by_tag() {
+                    self.mount_error_popup(e.context("rename song by tag"));
+                }
+            }
+            ActionConfirmMsg::Cancel => (),
+        }
+    }
+
+    /// Handle all [`SearchHistoryMsg`] messages. Sub-function for [`update`](Self::update).
+    fn update_search_history_msg(&mut self, msg: SearchHistoryMsg) {
+        match msg {
+            SearchHistoryMsg::PopupShow(kind) => self.mount_search_history(kind),
+            SearchHistoryMsg::PopupCloseCancel(kind) => self.umount_search_history(kind),
+            SearchHistoryMsg::PopupCloseOk(kind, query) => {
+                self.umount_search_history(kind);
+                if let Some(msg) = self.search_history_apply(kind, query) {
+                    self.update_general_search(&msg);
+                }
+            }
+            SearchHistoryMsg::TogglePin(kind, query) => {
+                self.search_history_toggle_pin(kind, &query);
+            }
+        }
+    }
+
     /// Handle all [`XYWHMsg`] messages. Sub-function for [`update`](Self::update).
     fn update_xywh_msg(&mut self, msg: XYWHMsg) -> Option<Msg> {
         match msg {
@@ -241,6 +349,9 @@ impl Model {
                 if let Err(e) = self.podcast_sync_episodes() {
                     self.mount_error_popup(e.context("podcast sync episodes"));
                 }
+                if let Err(e) = self.update_photo() {
+                    self.mount_error_popup(e.context("update_photo"));
+                }
             }
             PCMsg::DescriptionUpdate => self.lyric_update(),
             PCMsg::EpisodeAdd(index) => {
@@ -350,7 +461,8 @@ impl Model {
                 }
             }
             PodcastSyncResult::Error(feed) => {
-                self.download_tracker.decrease_one(&feed.url);
+                self.download_tracker
+                    .fail_one(&feed.url, "feed sync failed");
                 self.mount_error_popup(anyhow!("Error happened with feed: {:?}", feed.title));
                 self.show_message_timeout_label_help(
                     self.download_tracker.message_feed_sync_failed(),
@@ -387,7 +499,8 @@ impl Model {
                 );
             }
             PodcastDLResult::DLResponseError(ep_data) => {
-                self.download_tracker.decrease_one(&ep_data.url);
+                self.download_tracker
+                    .fail_one(&ep_data.url, "no response from website");
                 self.mount_error_popup(anyhow!("download failed for episode: {}", ep_data.title));
                 self.show_message_timeout_label_help(
                     self.download_tracker
@@ -398,7 +511,8 @@ impl Model {
                 );
             }
             PodcastDLResult::DLFileCreateError(ep_data) => {
-                self.download_tracker.decrease_one(&ep_data.url);
+                self.download_tracker
+                    .fail_one(&ep_data.url, "unable to create a file");
                 self.mount_error_popup(anyhow!("download failed for episode: {}", ep_data.title));
                 self.show_message_timeout_label_help(
                     self.download_tracker
@@ -409,7 +523,8 @@ impl Model {
                 );
             }
             PodcastDLResult::DLFileWriteError(ep_data) => {
-                self.download_tracker.decrease_one(&ep_data.url);
+                self.download_tracker
+                    .fail_one(&ep_data.url, "cannot write to file");
                 self.mount_error_popup(anyhow!("download failed for episode: {}", ep_data.title));
                 self.show_message_timeout_label_help(
                     self.download_tracker
@@ -467,13 +582,70 @@ impl Model {
             PlayerMsg::ToggleGapless => {
                 self.command(TuiCmd::ToggleGapless);
             }
+            PlayerMsg::ToggleKaraoke => {
+                self.karaoke = !self.karaoke;
+                if self.server_capabilities.supports(features::VOCAL_REDUCTION) {
+                    self.command(TuiCmd::Backend(BackendCmd::SetVocalReduction(self.karaoke)));
+                } else {
+                    self.karaoke_show_state(false);
+                }
+                self.lyric_update_title();
+                self.lyric_update();
+            }
+            PlayerMsg::ToggleRecording => {
+                if self.server_capabilities.supports(features::RECORDING) {
+                    self.command(TuiCmd::Backend(BackendCmd::SetRecording(!self.recording)));
+                } else {
+                    self.mount_error_popup(anyhow!("Recording is not supported by the server"));
+                }
+            }
+            PlayerMsg::ToggleAlbumMode => {
+                if self.server_capabilities.supports(features::ALBUM_MODE) {
+                    let album_mode = self.config_server.read().settings.player.album_mode;
+                    self.command(TuiCmd::Backend(BackendCmd::SetAlbumMode(!album_mode)));
+                } else {
+                    self.mount_error_popup(anyhow!("Album mode is not supported by the server"));
+                }
+            }
+            PlayerMsg::ToggleNormalization => {
+                if self.server_capabilities.supports(features::NORMALIZATION) {
+                    self.normalization = !self.normalization;
+                    self.command(TuiCmd::Backend(BackendCmd::SetNormalization(
+                        self.normalization,
+                    )));
+                } else {
+                    self.mount_error_popup(anyhow!("Normalization is not supported by the server"));
+                }
+            }
+            PlayerMsg::AbLoop => self.player_ab_loop(),
+            PlayerMsg::ToggleSmartSpeed => {
+                if self.server_capabilities.supports(features::SMART_SPEED) {
+                    self.smart_speed = !self.smart_speed;
+                    self.command(TuiCmd::Backend(BackendCmd::SetSmartSpeed(self.smart_speed)));
+                } else {
+                    self.mount_error_popup(anyhow!("Smart speed is not supported by the server"));
+                }
+            }
+            PlayerMsg::TapTempo => self.beat_indicator_tap(),
+            PlayerMsg::BeatTick(generation) => self.beat_indicator_tick(generation),
         }
 
         None
     }
 
+    /// Run the macro `name` on the server, if supported.
+    fn run_macro(&mut self, name: String) {
+        if !self.server_capabilities.supports(features::MACROS) {
+            self.mount_error_popup(anyhow!("Macros are not supported by the server"));
+            return;
+        }
+        self.update_show_message_timeout("Macro", &format!("Running \"{name}\""), None);
+        self.command(TuiCmd::RunMacro(name));
+    }
+
     /// Switch the main view / layout.
-    fn update_layout(&mut self, msg: MainLayoutMsg) -> Option<Msg> {
+    pub fn update_layout(&mut self, msg: MainLayoutMsg) -> Option<Msg> {
+        let was_podcast = self.layout == TermusicLayout::Podcast;
         match msg {
             MainLayoutMsg::DataBase => {
                 let mut need_to_set_focus = true;
@@ -568,6 +740,13 @@ impl Model {
             }
         }
 
+        // the podcast view shows the artwork of the selected feed instead of the cover of the current track
+        if was_podcast != (self.layout == TermusicLayout::Podcast)
+            && let Err(e) = self.update_photo()
+        {
+            self.mount_error_popup(e.context("update_photo"));
+        }
+
         None
     }
 
@@ -609,12 +788,7 @@ impl Model {
             }
 
             DBMsg::AddResultToPlaylist(index) => {
-                if let Some(result) = self.dw.search_results.get(index).cloned()
-                    && let Some(result) =
-                        self.database_get_tracks_by_criteria(self.dw.criteria, &result)
-                {
-                    self.playlist_add_all_from_db(&result);
-                }
+                self.database_add_result(index);
             }
             DBMsg::AddAllResultsToPlaylist => {
                 self.database_add_all_results();
@@ -629,6 +803,27 @@ impl Model {
             DBMsg::AddAllResultsConfirmCancel => {
                 self.umount_results_add_confirm_database();
             }
+
+            DBMsg::SearchResultReady(data) => {
+                self.database_apply_search_results(data);
+            }
+            DBMsg::SearchTracksReady(generation, data) => {
+                self.database_apply_search_tracks(generation, data);
+            }
+            DBMsg::AddTracksReady(data) => {
+                self.playlist_add_all_from_db(&data.tracks);
+            }
+            DBMsg::GeneralSearchReady(data) => {
+                self.database_apply_general_search(data);
+            }
+            DBMsg::SetLabel(index, label) => {
+                self.database_set_label(index, label);
+            }
+            DBMsg::MarkReviewed(index) => self.database_mark_reviewed(Some(index)),
+            DBMsg::MarkAllReviewed => self.database_mark_reviewed(None),
+            DBMsg::Locate(index) => self.database_locate(index),
+            DBMsg::VerifyLibrary => self.database_verify_library(),
+            DBMsg::VerifyLibraryDone(res) => self.database_verify_library_done(res),
         }
         None
     }
@@ -640,7 +835,26 @@ impl Model {
                 assert!(self.app.active(&Id::Playlist).is_ok());
             }
             LIMsg::PasteError(err) => self.mount_error_popup(anyhow!(err)),
-            LIMsg::PlaylistRunDelete => self.playlist_update_library_delete(),
+            LIMsg::Moved(from, to) => self.new_library_handle_moved(&from, &to),
+            LIMsg::Copied(path) => self.new_library_handle_copied(&path),
+            LIMsg::RenameShow(path) => {
+                if let Err(e) = self.mount_library_rename(path) {
+                    self.mount_error_popup(e.context("mount library rename"));
+                }
+            }
+            LIMsg::RenameCancel => self.umount_library_rename(),
+            LIMsg::RenameOk(path, new_name) => {
+                self.umount_library_rename();
+                if let Err(e) = self.new_library_rename(&path, &new_name) {
+                    self.mount_error_popup(e.context("library rename"));
+                }
+            }
+            LIMsg::UndoDelete => {
+                if let Err(e) = self.new_library_undo_delete() {
+                    self.mount_error_popup(e.context("library undo delete"));
+                }
+            }
+            LIMsg::UndoDeleteExpired(path) => self.new_library_handle_undo_expired(&path),
             LIMsg::SwitchRoot(old_path) => self.new_library_switch_root(&old_path),
             LIMsg::AddRoot(path) => {
                 if let Err(e) = self.new_library_add_root(path) {
@@ -666,6 +880,7 @@ impl Model {
     fn update_youtube_search(&mut self, msg: YSMsg) {
         match msg {
             YSMsg::InputPopupShow => {
+                self.youtube_suggestions_clear();
                 self.mount_youtube_search_input();
             }
             YSMsg::InputPopupCloseCancel => {
@@ -677,7 +892,11 @@ impl Model {
                 if self.app.mounted(&Id::YoutubeSearchInputPopup) {
                     assert!(self.app.umount(&Id::YoutubeSearchInputPopup).is_ok());
                 }
-                if url.starts_with("http") {
+                if let Some(collection) = ytdlp::youtube_collection(&url) {
+                    if let Err(e) = self.youtube_import(url, collection) {
+                        self.mount_error_popup(e.context("youtube import"));
+                    }
+                } else if url.starts_with("http") {
                     match self.youtube_dl(&url, None) {
                         Ok(()) => {}
                         Err(e) => {
@@ -685,10 +904,34 @@ impl Model {
                         }
                     }
                 } else {
+                    self.search_history_push(HistoryKind::Web, &url);
                     self.mount_youtube_search_table();
                     self.youtube_options_search(url);
                 }
             }
+            YSMsg::ReqSuggestion(input) => {
+                self.youtube_suggest(input);
+            }
+            YSMsg::SuggestionsLoaded(suggestions) => {
+                self.youtube_suggestions_loaded(suggestions);
+            }
+            YSMsg::NextSite => self.youtube_search_next_site(),
+            YSMsg::RegionPopupShow => self.mount_youtube_region_popup(),
+            YSMsg::RegionPopupCloseCancel => {
+                self.umount_youtube_region_popup();
+                if self.app.mounted(&Id::YoutubeSearchInputPopup) {
+                    self.app.active(&Id::YoutubeSearchInputPopup).ok();
+                }
+            }
+            YSMsg::RegionPopupCloseOk(region) => {
+                self.umount_youtube_region_popup();
+                if self.app.mounted(&Id::YoutubeSearchInputPopup) {
+                    assert!(self.app.umount(&Id::YoutubeSearchInputPopup).is_ok());
+                }
+                Self::youtube_trending_region_remember(&region);
+                self.mount_youtube_search_table();
+                self.youtube_options_trending(region);
+            }
             YSMsg::TablePopupCloseCancel => {
                 self.umount_youtube_search_table_popup();
             }
@@ -698,11 +941,12 @@ impl Model {
             YSMsg::ReqPreviousPage => {
                 self.youtube_options_prev_page();
             }
-            YSMsg::PageLoaded(data) => {
+            YSMsg::PageLoaded(mut data) => {
+                data.apply_filter(&self.config_tui.read().settings.content_filter);
                 self.youtube_options.data = data;
                 self.sync_youtube_options();
             }
-            YSMsg::PageLoadError(err) => {
+            YSMsg::PageLoadError(err) | YSMsg::SuggestionsError(err) => {
                 self.mount_error_popup(anyhow!(err));
             }
 
@@ -711,7 +955,15 @@ impl Model {
                     self.mount_error_popup(e.context("youtube-dl options download"));
                 }
             }
-            YSMsg::YoutubeSearchSuccess(youtube_options) => {
+            YSMsg::TablePopupStream(index) => {
+                if let Err(e) = self.youtube_options_stream(index) {
+                    self.mount_error_popup(e.context("youtube options stream"));
+                }
+            }
+            YSMsg::YoutubeSearchSuccess(mut youtube_options) => {
+                youtube_options
+                    .data
+                    .apply_filter(&self.config_tui.read().settings.content_filter);
                 self.youtube_options = youtube_options;
                 self.sync_youtube_options();
                 self.redraw = true;
@@ -720,12 +972,16 @@ impl Model {
                 self.redraw = true;
                 self.mount_error_popup(anyhow!("Youtube search fail: {e}"));
             }
+            YSMsg::ImportPage(page) => self.youtube_import_page(page),
+            YSMsg::ImportFail(e) => {
+                self.mount_error_popup(anyhow!("Youtube import fail: {e}"));
+            }
             YSMsg::Download(msg) => self.update_ys_download_msg(msg),
         }
     }
 
     /// Handle all [`YSMsg`] messages. Sub-function for [`update_youtube_search`](Self::update_youtube_search).
-    fn update_ys_download_msg(&mut self, msg: YTDLMsg) {
+    pub(super) fn update_ys_download_msg(&mut self, msg: YTDLMsg) {
         match msg {
             YTDLMsg::Start(url, title) => {
                 self.download_tracker.increase_one(&*url);
@@ -751,7 +1007,7 @@ impl Model {
                 }
             }
             YTDLMsg::Err(url, title, error_message) => {
-                self.download_tracker.decrease_one(&url);
+                self.download_tracker.fail_one(&url, &*error_message);
                 self.mount_error_popup(anyhow!("download failed: {error_message}"));
                 self.show_message_timeout_label_help(
                     self.download_tracker
@@ -770,24 +1026,25 @@ impl Model {
         match msg {
             GSMsg::PopupShowDatabase => {
                 self.mount_search_database();
-                self.database_update_search("*");
+                self.database_load_general_search();
+                self.database_update_search("");
             }
             GSMsg::PopupShowLibrary(path) => {
                 self.mount_search_library(path.clone());
-                self.new_library_update_search("*", path);
+                self.new_library_update_search("", path);
             }
             GSMsg::PopupShowPlaylist => {
                 self.mount_search_playlist();
-                self.playlist_update_search("*");
+                self.playlist_update_search("");
             }
             GSMsg::PopupShowEpisode => {
                 self.mount_search_episode();
-                self.podcast_update_search_episode("*");
+                self.podcast_update_search_episode("");
             }
 
             GSMsg::PopupShowPodcast => {
                 self.mount_search_podcast();
-                self.podcast_update_search_podcast("*");
+                self.podcast_update_search_podcast("");
             }
             GSMsg::PopupUpdateLibrary(input, path) => self.new_library_update_search(input, path),
 
@@ -795,7 +1052,17 @@ impl Model {
 
             GSMsg::PopupUpdateDatabase(input) => self.database_update_search(input),
 
+            GSMsg::PopupModeChanged(mode, update) => {
+                self.general_search_mode = *mode;
+                self.update_general_search(update);
+            }
+
             GSMsg::InputBlur => {
+                if let Ok(State::One(StateValue::String(query))) =
+                    self.app.state(&Id::GeneralSearchInput)
+                {
+                    self.search_history_push(HistoryKind::Library, &query);
+                }
                 if self.app.mounted(&Id::GeneralSearchTable) {
                     self.app.active(&Id::GeneralSearchTable).ok();
                 }
@@ -806,6 +1073,8 @@ impl Model {
                 }
             }
             GSMsg::PopupCloseCancel => {
+                self.global_search = None;
+                self.general_search_source = None;
                 self.app.umount(&Id::GeneralSearchInput).ok();
                 self.app.umount(&Id::GeneralSearchTable).ok();
                 if let Err(e) = self.update_photo() {
@@ -880,6 +1149,42 @@ impl Model {
                     self.mount_error_popup(e.context("update_photo"));
                 }
             }
+
+            GSMsg::PopupShowLyric => self.lyric_search_show(),
+            GSMsg::PopupUpdateLyric(input) => self.lyric_search_update(input),
+            GSMsg::PopupCloseOkLyricSeek => {
+                self.lyric_search_seek();
+                self.app.umount(&Id::GeneralSearchInput).ok();
+                self.app.umount(&Id::GeneralSearchTable).ok();
+                if let Err(e) = self.update_photo() {
+                    self.mount_error_popup(e.context("update_photo"));
+                }
+            }
+
+            GSMsg::PopupShowGlobal => self.global_search_show(),
+            GSMsg::PopupUpdateGlobal(input) => self.global_search_update(input),
+            GSMsg::GlobalPlaylistsReady(playlists) => {
+                self.global_search_apply_playlists(playlists.clone());
+            }
+            GSMsg::PopupCloseGlobalAddPlaylist => {
+                if let Err(e) = self.global_search_add_playlist() {
+                    self.mount_error_popup(e.context("global search add to playlist"));
+                }
+            }
+            GSMsg::PopupCloseOkGlobalLocate => {
+                match self.global_search_locate() {
+                    // headers cannot be located, keep the popup open
+                    Ok(false) => return,
+                    Ok(true) => (),
+                    Err(e) => self.mount_error_popup(e.context("global search locate")),
+                }
+                self.global_search = None;
+                self.app.umount(&Id::GeneralSearchInput).ok();
+                self.app.umount(&Id::GeneralSearchTable).ok();
+                if let Err(e) = self.update_photo() {
+                    self.mount_error_popup(e.context("update_photo"));
+                }
+            }
         }
     }
 
@@ -924,7 +1229,18 @@ impl Model {
                 self.playlist_delete_item(*index);
             }
             PLMsg::DeleteAll => {
-                self.playlist_clear();
+                if self
+                    .config_tui
+                    .read()
+                    .settings
+                    .behavior
+                    .confirm_clear_playlist
+                    && !self.playback.playlist.is_empty()
+                {
+                    self.mount_action_confirm(ConfirmAction::ClearPlaylist);
+                } else {
+                    self.playlist_clear();
+                }
             }
             PLMsg::Shuffle => {
                 self.playlist_shuffle();
@@ -962,6 +1278,21 @@ impl Model {
             PLMsg::AddRandomTracks => {
                 self.playlist_add_random_tracks();
             }
+            PLMsg::AddRandomUnplayed => {
+                self.playlist_add_random_unplayed();
+            }
+            PLMsg::AddAutoDj => {
+                self.playlist_add_auto_dj();
+            }
+            PLMsg::SetLabel(index, label) => {
+                self.playlist_set_label(*index, *label);
+            }
+            PLMsg::OffsetsShow(index) => self.playlist_offsets_show(*index),
+            PLMsg::OffsetsCancel => self.umount_track_offsets(),
+            PLMsg::OffsetsOk(path, input) => {
+                self.umount_track_offsets();
+                self.track_offsets_set(path, input);
+            }
             PLMsg::PlaylistTableBlurUp => match self.layout {
                 TermusicLayout::TreeView => assert!(self.app.active(&Id::Library).is_ok()),
                 TermusicLayout::DataBase => {
@@ -1022,12 +1353,14 @@ impl Model {
                     }
                     self.update_layout(MainLayoutMsg::Podcast);
                 }
-                MediaTypesSimple::Music | MediaTypesSimple::LiveRadio => match self.layout {
-                    TermusicLayout::TreeView | TermusicLayout::DataBase => {}
-                    TermusicLayout::Podcast => {
-                        self.update_layout(MainLayoutMsg::TreeView);
+                MediaTypesSimple::Music | MediaTypesSimple::LiveRadio | MediaTypesSimple::Video => {
+                    match self.layout {
+                        TermusicLayout::TreeView | TermusicLayout::DataBase => {}
+                        TermusicLayout::Podcast => {
+                            self.update_layout(MainLayoutMsg::TreeView);
+                        }
                     }
-                },
+                }
             }
         }
     }
@@ -1082,7 +1415,9 @@ impl Model {
 
                 self.config_server.write().settings.player.volume = clamp_u16(response.volume);
 
+                self.radio_set_now_playing(&response.radio_title);
                 self.lyric_update_for_radio(response.radio_title);
+                self.ab_loop_sync(response.ab_loop.and_then(AbLoop::from_grpc));
 
                 self.playback
                     .set_status(RunningStatus::from_u32(response.status));
@@ -1106,11 +1441,95 @@ impl Model {
                     true,
                 );
             }
+            ServerReqResponse::VocalReduction(applied) => self.karaoke_show_state(applied),
+            ServerReqResponse::Normalization(applied) => {
+                if applied {
+                    self.update_show_message_timeout(
+                        "Normalization",
+                        "Normalization enabled",
+                        None,
+                    );
+                } else if self.normalization {
+                    self.update_show_message_timeout(
+                        "Normalization",
+                        "Normalization is not supported by the server's backend",
+                        None,
+                    );
+                } else {
+                    self.update_show_message_timeout(
+                        "Normalization",
+                        "Normalization disabled",
+                        None,
+                    );
+                }
+                self.normalization = applied;
+                self.progress_update_title();
+            }
+            ServerReqResponse::SmartSpeed(applied) => {
+                if applied {
+                    self.update_show_message_timeout("Smart Speed", "Smart speed enabled", None);
+                } else if self.smart_speed {
+                    self.update_show_message_timeout(
+                        "Smart Speed",
+                        "Smart speed is not supported by the server's backend",
+                        None,
+                    );
+                } else {
+                    self.update_show_message_timeout("Smart Speed", "Smart speed disabled", None);
+                }
+                self.smart_speed = applied;
+            }
+            ServerReqResponse::Equalizer(applied) => self.equalizer_applied(applied),
+            ServerReqResponse::Recording(recording, error) => {
+                self.recording = recording;
+                if !error.is_empty() {
+                    self.mount_error_popup(anyhow!("Recording: {error}"));
+                } else if recording {
+                    self.update_show_message_timeout("Recording", "Recording started", None);
+                } else {
+                    self.update_show_message_timeout("Recording", "Recording stopped", None);
+                }
+            }
+            ServerReqResponse::AlbumMode(enabled) => {
+                // keep the local copy in sync, so that saving the config does not overwrite it
+                self.config_server.write().settings.player.album_mode = enabled;
+                self.progress_update_title();
+                if enabled {
+                    self.update_show_message_timeout(
+                        "Album mode",
+                        "Started tracks play their whole album in order",
+                        None,
+                    );
+                } else {
+                    self.update_show_message_timeout("Album mode", "Album mode disabled", None);
+                }
+            }
+            ServerReqResponse::Downloads(downloads) => self.downloads_listed(downloads),
+            ServerReqResponse::DownloadError(err) => {
+                self.mount_error_popup(anyhow!("download queue: {err}"));
+            }
+            ServerReqResponse::OutputDevices(devices) => self.output_devices_listed(devices),
+            ServerReqResponse::AbLoop(ab_loop) => self.ab_loop_changed(ab_loop),
         }
 
         None
     }
 
+    /// Notify about the new karaoke mode state, with `vocal_reduction` being whether the server applied it.
+    fn karaoke_show_state(&mut self, vocal_reduction: bool) {
+        if !self.karaoke {
+            self.update_show_message_timeout("Karaoke mode", "Karaoke mode disabled", None);
+        } else if vocal_reduction {
+            self.update_show_message_timeout("Karaoke mode", "Karaoke mode enabled", None);
+        } else {
+            self.update_show_message_timeout(
+                "Karaoke mode",
+                "Karaoke mode enabled, but vocal reduction is not supported by the server's backend",
+                None,
+            );
+        }
+    }
+
     /// Handle Stream updates [`UpdateEvents`].
     ///
     /// In case of lag, sends a [`TuiCmd::GetProgress`].
@@ -1152,6 +1571,7 @@ impl Model {
                 }
 
                 if track_changed_info.current_track_updated {
+                    self.ab_loop_track_changed();
                     self.handle_current_track_index(
                         usize::try_from(track_changed_info.current_track_index).unwrap(),
                         false,
@@ -1159,8 +1579,10 @@ impl Model {
                 }
 
                 if let Some(title) = track_changed_info.title {
+                    self.radio_set_now_playing(&title);
                     self.lyric_update_for_radio(title);
                 } else {
+                    self.radio_set_now_playing("");
                     // fallback in case no title is immediately available on radio start.
                     // matching that the current track is actually radio, is in the function itself.
                     self.lyric_update_for_radio("");
@@ -1181,6 +1603,32 @@ impl Model {
                     self.mount_error_popup(err);
                 }
             }
+            // the own activity is already shown by the download spinner
+            UpdateEvents::ActivityChanged(_) => (),
+            UpdateEvents::DownloadChanged(info) => self.downloads_changed(info),
+            UpdateEvents::ListeningTime(info) => {
+                if info.break_started {
+                    let key = self
+                        .config_tui
+                        .read()
+                        .settings
+                        .keys
+                        .player_keys
+                        .toggle_pause
+                        .to_string();
+                    self.update_show_message_timeout(
+                        "Take a break",
+                        &format!(
+                            "Listened for {} minutes, press {key} to continue",
+                            info.listened.as_secs() / 60
+                        ),
+                        Some(60),
+                    );
+                }
+                self.listening_time = info.limit.is_some().then_some(info);
+                self.progress_update_title();
+            }
+            UpdateEvents::LibraryChanged(dirs) => self.new_library_reload_dirs(dirs),
         }
 
         None
@@ -1207,6 +1655,9 @@ impl Model {
             UpdatePlaylistEvents::PlaylistShuffled(shuffled) => {
                 self.handle_playlist_shuffled(shuffled)?;
             }
+            UpdatePlaylistEvents::PlaylistTrackError(track_error) => {
+                self.handle_playlist_track_error(track_error)?;
+            }
         }
 
         Ok(())
@@ -0,0 +1,59 @@
+//! Verifying all tracks of the library by decoding them, the failed ones are shown as [`SearchCriteria::Corrupted`] in
+//! the database view.
+
+use termusiclib::new_database::VerifySummary;
+
+use super::Model;
+use crate::ui::msg::{DBMsg, Msg, SearchCriteria};
+
+impl Model {
+    /// Start verifying all tracks in the background, [`DBMsg::VerifyLibraryDone`] is sent once finished.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    pub fn database_verify_library(&mut self) {
+        if self.verify_library_active {
+            self.mount_error_popup(anyhow::anyhow!("A library verification is already running"));
+            return;
+        }
+        self.verify_library_active = true;
+
+        let tx = self.tx_to_main.clone();
+        self.db.verify_library(move |res| {
+            let res = res.map_err(|err| format!("{err:#}"));
+            let _ = tx.send(Msg::DataBase(DBMsg::VerifyLibraryDone(res)));
+        });
+
+        self.update_show_message_timeout(
+            "Verify library",
+            "Decoding all tracks in the background, this may take a while",
+            None,
+        );
+    }
+
+    /// Report the result of a finished verification.
+    pub fn database_verify_library_done(&mut self, res: Result<VerifySummary, String>) {
+        self.verify_library_active = false;
+
+        let summary = match res {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(anyhow::anyhow!(err).context("verify library"));
+                return;
+            }
+        };
+
+        let text = if summary.failed == 0 {
+            format!("All {} tracks are fine", summary.verified)
+        } else {
+            format!(
+                "{} of {} tracks failed, see \"Corrupted\"",
+                summary.failed, summary.verified
+            )
+        };
+        self.update_show_message_timeout("Library verified", &text, None);
+
+        if self.dw.criteria == SearchCriteria::Corrupted {
+            self.database_update_search_results();
+        }
+    }
+}
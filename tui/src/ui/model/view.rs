@@ -2,12 +2,13 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::Result;
+use termusiclib::search_history::{HistoryKind, SearchHistory};
 use tokio::runtime::Handle;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tuirealm::EventListenerCfg;
 use tuirealm::Frame;
 use tuirealm::props::{AttrValue, Attribute, Color, PropPayload, PropValue, TextSpan};
-use tuirealm::ratatui::layout::{Constraint, Layout};
+use tuirealm::ratatui::layout::{Constraint, Layout, Rect};
 use tuirealm::ratatui::widgets::Clear;
 
 use crate::ui::Application;
@@ -15,7 +16,9 @@ use crate::ui::components::{
     DBListCriteria, DownloadSpinner, EpisodeList, FeedsList, Footer, GSInputPopup, GSTablePopup,
     Lyric, Playlist, Progress, Source,
 };
+use crate::ui::headless::HEADLESS_POLL_TIMEOUT;
 use crate::ui::ids::{Id, IdConfigEditor, IdTagEditor};
+use crate::ui::model::ports::keys::PortKeys;
 use crate::ui::model::ports::rx_main::PortRxMain;
 use crate::ui::model::ports::stream_events::PortStreamEvents;
 use crate::ui::model::{Model, TermusicLayout, UserEvent};
@@ -25,21 +28,30 @@ use crate::ui::utils::{
 };
 
 impl Model {
+    /// Setup the application, reading the key events from `keys` instead of the terminal if set.
     pub fn init_app(
         rx_to_main: UnboundedReceiver<Msg>,
         stream_event_port: PortStreamEvents,
+        keys: Option<PortKeys>,
     ) -> Application<Id, Msg, UserEvent> {
-        // Setup application
-        Application::init(
-            EventListenerCfg::default()
-                .with_handle(Handle::current())
+        let cfg = EventListenerCfg::default()
+            .with_handle(Handle::current())
+            .async_tick(true)
+            .tick_interval(Duration::from_secs(1))
+            .add_async_port(Box::new(PortRxMain::new(rx_to_main)), Duration::ZERO, 10)
+            .add_async_port(Box::new(stream_event_port), Duration::ZERO, 1);
+
+        let cfg = match keys {
+            Some(keys) => cfg
+                .add_async_port(Box::new(keys), Duration::ZERO, 10)
+                // headless runs wait for the events to settle, which should not take long
+                .poll_timeout(HEADLESS_POLL_TIMEOUT),
+            None => cfg
                 .async_crossterm_input_listener(Duration::ZERO, 10)
-                .poll_timeout(Duration::from_secs(10))
-                .async_tick(true)
-                .tick_interval(Duration::from_secs(1))
-                .add_async_port(Box::new(PortRxMain::new(rx_to_main)), Duration::ZERO, 10)
-                .add_async_port(Box::new(stream_event_port), Duration::ZERO, 1),
-        )
+                .poll_timeout(Duration::from_secs(10)),
+        };
+
+        Application::init(cfg)
     }
 
     /// Mount the Main components for the TUI.
@@ -64,6 +76,9 @@ impl Model {
             Box::new(Lyric::new(self.config_tui.clone())),
             Vec::new(),
         )?;
+        if self.config_tui.read().settings.track_suggestions.show {
+            self.mount_track_suggestions(&[])?;
+        }
 
         self.app.mount(
             Id::Podcast,
@@ -114,6 +129,7 @@ impl Model {
                 return;
             }
 
+            self.key_hints_update();
             match self.layout {
                 TermusicLayout::TreeView => self.view_layout_treeview(),
                 TermusicLayout::DataBase => self.view_layout_database(),
@@ -123,13 +139,14 @@ impl Model {
     }
 
     fn view_layout_podcast(&mut self) {
+        let footer_height = self.footer_height();
         self.terminal
             .raw_mut()
             .draw(|f| {
                 let [chunks_main, progress, _bottom_help] = Layout::vertical([
                     Constraint::Min(2),
                     Constraint::Length(3),
-                    Constraint::Length(1),
+                    Constraint::Length(footer_height),
                 ])
                 .areas(f.area());
                 let [center_left, center_right] =
@@ -146,21 +163,28 @@ impl Model {
                 self.app.view(&Id::Podcast, f, left_podcasts);
                 self.app.view(&Id::Episode, f, left_episodes);
 
-                self.app.view(&Id::Playlist, f, right_playlist);
+                Self::view_playlist(f, &mut self.app, right_playlist);
                 self.app.view(&Id::Lyric, f, right_lyric);
                 self.app.view(&Id::Progress, f, progress);
 
-                Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
+                Self::view_layout_commons(
+                    f,
+                    &mut self.app,
+                    self.download_tracker.visible(),
+                    footer_height,
+                );
             })
             .expect("Expected to draw without error");
     }
 
     fn view_layout_database(&mut self) {
+        let footer_height = self.footer_height();
         self.terminal
             .raw_mut()
             .draw(|f| {
                 let [chunks_main, _bottom_help] =
-                    Layout::vertical([Constraint::Min(2), Constraint::Length(1)]).areas(f.area());
+                    Layout::vertical([Constraint::Min(2), Constraint::Length(footer_height)])
+                        .areas(f.area());
                 let [chunks_main_left, chunks_main_right] =
                     Layout::horizontal([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
                         .areas(chunks_main);
@@ -185,21 +209,28 @@ impl Model {
                 self.app
                     .view(&Id::DBListSearchTracks, f, left_search_tracks);
 
-                self.app.view(&Id::Playlist, f, right_playlist);
+                Self::view_playlist(f, &mut self.app, right_playlist);
                 self.app.view(&Id::Progress, f, right_progress);
                 self.app.view(&Id::Lyric, f, right_lyric);
 
-                Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
+                Self::view_layout_commons(
+                    f,
+                    &mut self.app,
+                    self.download_tracker.visible(),
+                    footer_height,
+                );
             })
             .expect("Expected to draw without error");
     }
 
     fn view_layout_treeview(&mut self) {
+        let footer_height = self.footer_height();
         self.terminal
             .raw_mut()
             .draw(|f| {
                 let [chunks_main, _bottom_help] =
-                    Layout::vertical([Constraint::Min(2), Constraint::Length(1)]).areas(f.area());
+                    Layout::vertical([Constraint::Min(2), Constraint::Length(footer_height)])
+                        .areas(f.area());
                 let [left_library, right] =
                     Layout::horizontal([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
                         .areas(chunks_main);
@@ -212,23 +243,49 @@ impl Model {
 
                 self.app.view(&Id::Library, f, left_library);
 
-                self.app.view(&Id::Playlist, f, right_playlist);
+                Self::view_playlist(f, &mut self.app, right_playlist);
                 self.app.view(&Id::Progress, f, right_progress);
                 self.app.view(&Id::Lyric, f, right_lyric);
 
-                Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
+                Self::view_layout_commons(
+                    f,
+                    &mut self.app,
+                    self.download_tracker.visible(),
+                    footer_height,
+                );
             })
             .expect("Expected to draw without error");
     }
 
-    /// Draw the footer in the last line.
+    /// Draw the playlist in `area`, next to the track suggestions if they are shown.
+    fn view_playlist(f: &mut Frame<'_>, app: &mut Application<Id, Msg, UserEvent>, area: Rect) {
+        if app.mounted(&Id::TrackSuggestions) {
+            let [playlist, suggestions] =
+                Layout::horizontal([Constraint::Ratio(2, 3), Constraint::Ratio(1, 3)]).areas(area);
+            app.view(&Id::Playlist, f, playlist);
+            app.view(&Id::TrackSuggestions, f, suggestions);
+        } else {
+            app.view(&Id::Playlist, f, area);
+        }
+    }
+
+    /// Draw the footer in the last line, and the key hints above it if `footer_height` has room for them.
     fn view_common_footer(
         f: &mut Frame<'_>,
         app: &mut Application<Id, Msg, UserEvent>,
         downloading_visible: bool,
+        footer_height: u16,
     ) {
-        let [_content, bottom_label] =
-            Layout::vertical([Constraint::Min(2), Constraint::Length(1)]).areas(f.area());
+        let [_content, bottom_hints, bottom_label] = Layout::vertical([
+            Constraint::Min(2),
+            Constraint::Length(footer_height.saturating_sub(1)),
+            Constraint::Length(1),
+        ])
+        .areas(f.area());
+
+        if footer_height > 1 {
+            app.view(&Id::KeyHints, f, bottom_hints);
+        }
 
         if downloading_visible {
             let [_spacer, spinner, remainder] = Layout::horizontal([
@@ -246,6 +303,7 @@ impl Model {
     }
 
     /// Draw any popup.
+    #[allow(clippy::too_many_lines)]
     fn view_popups(f: &mut Frame<'_>, app: &mut Application<Id, Msg, UserEvent>) {
         if app.mounted(&Id::QuitPopup) {
             let popup = draw_area_in_absolute(f.area(), 30, 3);
@@ -282,7 +340,7 @@ impl Model {
             app.view(&Id::GeneralSearchInput, f, popup_chunks[0]);
             app.view(&Id::GeneralSearchTable, f, popup_chunks[1]);
         } else if app.mounted(&Id::YoutubeSearchInputPopup) {
-            let popup = draw_area_in_absolute(f.area(), 50, 3);
+            let popup = draw_area_in_absolute(f.area(), 65, 3);
             f.render_widget(Clear, popup);
             app.view(&Id::YoutubeSearchInputPopup, f, popup);
         } else if app.mounted(&Id::YoutubeSearchTablePopup) {
@@ -300,6 +358,18 @@ impl Model {
                 Layout::vertical([Constraint::Length(3), Constraint::Length(3)]).split(popup);
             app.view(&Id::SavePlaylistPopup, f, popup_chunks[0]);
             app.view(&Id::SavePlaylistLabel, f, popup_chunks[1]);
+        } else if app.mounted(&Id::LibraryRenamePopup) {
+            let popup = draw_area_in_absolute(f.area(), 76, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::LibraryRenamePopup, f, popup);
+        } else if app.mounted(&Id::TrackOffsetsPopup) {
+            let popup = draw_area_in_absolute(f.area(), 90, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::TrackOffsetsPopup, f, popup);
+        } else if app.mounted(&Id::SeekToPositionPopup) {
+            let popup = draw_area_in_absolute(f.area(), 50, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::SeekToPositionPopup, f, popup);
         } else if app.mounted(&Id::SavePlaylistConfirm) {
             let popup = draw_area_in_absolute(f.area(), 40, 3);
             f.render_widget(Clear, popup);
@@ -312,6 +382,69 @@ impl Model {
             let popup = draw_area_in_absolute(f.area(), 60, 3);
             f.render_widget(Clear, popup);
             app.view(&Id::DatabaseAddConfirmPopup, f, popup);
+        } else if app.mounted(&Id::QueueReplacePopup) {
+            let popup = draw_area_in_absolute(f.area(), 76, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::QueueReplacePopup, f, popup);
+        } else if app.mounted(&Id::CreditsPopup) {
+            let popup = draw_area_in_absolute(f.area(), 60, 10);
+            f.render_widget(Clear, popup);
+            app.view(&Id::CreditsPopup, f, popup);
+        } else if app.mounted(&Id::DownloadsPopup) {
+            let popup = draw_area_in_absolute(f.area(), 80, 14);
+            f.render_widget(Clear, popup);
+            app.view(&Id::DownloadsPopup, f, popup);
+        } else if app.mounted(&Id::RadioSearchInputPopup) {
+            let popup = draw_area_in_absolute(f.area(), 65, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioSearchInputPopup, f, popup);
+        } else if app.mounted(&Id::RadioStationsPopup) {
+            let popup = draw_area_in_absolute(f.area(), 80, 20);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioStationsPopup, f, popup);
+        } else if app.mounted(&Id::EqualizerPopup) {
+            let popup = draw_area_in_absolute(f.area(), 56, 15);
+            f.render_widget(Clear, popup);
+            app.view(&Id::EqualizerPopup, f, popup);
+        } else if app.mounted(&Id::OutputDevicesPopup) {
+            let popup = draw_area_in_absolute(f.area(), 70, 12);
+            f.render_widget(Clear, popup);
+            app.view(&Id::OutputDevicesPopup, f, popup);
+        } else if app.mounted(&Id::BookmarkAddPopup) {
+            let popup = draw_area_in_absolute(f.area(), 60, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::BookmarkAddPopup, f, popup);
+        } else if app.mounted(&Id::BookmarksPopup) {
+            let popup = draw_area_in_absolute(f.area(), 70, 12);
+            f.render_widget(Clear, popup);
+            app.view(&Id::BookmarksPopup, f, popup);
+        } else if app.mounted(&Id::SmartPlaylistEditPopup) {
+            let popup = draw_area_in_absolute(f.area(), 90, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::SmartPlaylistEditPopup, f, popup);
+        } else if app.mounted(&Id::RatingPopup) {
+            let popup = draw_area_in_absolute(f.area(), 70, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RatingPopup, f, popup);
+        } else if app.mounted(&Id::StatisticsPopup) {
+            let popup = draw_area_in_absolute(f.area(), 80, 40);
+            f.render_widget(Clear, popup);
+            app.view(&Id::StatisticsPopup, f, popup);
+        } else if app.mounted(&Id::ActionConfirmPopup) {
+            let popup = draw_area_in_absolute(f.area(), 50, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::ActionConfirmPopup, f, popup);
+        }
+        // opened from the search inputs, so drawn on top of them
+        if app.mounted(&Id::SearchHistoryPopup) {
+            let popup = draw_area_in_relative(f.area(), 50, 50);
+            f.render_widget(Clear, popup);
+            app.view(&Id::SearchHistoryPopup, f, popup);
+        }
+        if app.mounted(&Id::YoutubeRegionPopup) {
+            let popup = draw_area_in_relative(f.area(), 40, 60);
+            f.render_widget(Clear, popup);
+            app.view(&Id::YoutubeRegionPopup, f, popup);
         }
         if app.mounted(&Id::MessagePopup) {
             let popup = draw_area_top_right_absolute(f.area(), 25, 4);
@@ -330,21 +463,30 @@ impl Model {
         f: &mut Frame<'_>,
         app: &mut Application<Id, Msg, UserEvent>,
         downloading_visible: bool,
+        footer_height: u16,
     ) {
-        Self::view_common_footer(f, app, downloading_visible);
+        Self::view_common_footer(f, app, downloading_visible, footer_height);
 
         Self::view_popups(f, app);
     }
 
     /// Mount / Remount a search popup for the provided source
     fn mount_search(&mut self, source: Source) {
+        // only kept while the global search is open
+        self.global_search = None;
         self.app
             .remount(
                 Id::GeneralSearchInput,
-                Box::new(GSInputPopup::new(source.clone(), &self.config_tui.read())),
+                Box::new(GSInputPopup::new(
+                    source.clone(),
+                    &self.config_tui.read(),
+                    self.general_search_mode,
+                    SearchHistory::load_default().get(HistoryKind::Library),
+                )),
                 Vec::new(),
             )
             .unwrap();
+        self.general_search_source = Some(source.clone());
         self.app
             .remount(
                 Id::GeneralSearchTable,
@@ -384,11 +526,26 @@ impl Model {
         self.mount_search(Source::Podcast);
     }
 
+    #[inline]
+    pub fn mount_search_global(&mut self) {
+        self.mount_search(Source::Global);
+    }
+
+    #[inline]
+    pub fn mount_search_lyric(&mut self) {
+        self.mount_search(Source::Lyric);
+    }
+
     pub fn mount_label_help(&mut self) {
         let config = self.config_tui.read();
         self.app
             .remount(Id::Label, Box::new(Footer::new(&config)), Vec::new())
             .expect("Expected to remount without error");
+        drop(config);
+        // regenerate the key hints on the next draw, in case the keys or the setting changed
+        if self.app.mounted(&Id::KeyHints) {
+            let _ = self.app.umount(&Id::KeyHints);
+        }
     }
 
     pub fn show_message_timeout_label_help<S: Into<String>>(
@@ -1,16 +1,30 @@
+use std::num::NonZeroU8;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, LazyLock};
+use std::process::Stdio;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-use anyhow::{Context, Result, anyhow};
-use id3::TagLike;
-use id3::Version::Id3v24;
-use regex::Regex;
+use anyhow::{Context, Result, anyhow, bail};
 use shell_words;
-use termusiclib::invidious::{Instance, YoutubeVideo};
+use termusiclib::config::v2::tui::{ContentFilter, SuggestionBackend, Youtube, Ytdlp};
+use termusiclib::downloads::{extract_filepath, finish_download};
+use termusiclib::invidious::{
+    COLLECTION_PAGE_SIZE, Instance, YoutubeVideo, probe_periodically, resolve_region,
+};
+use termusiclib::new_database::{Database, download_ops};
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistTrackInfo, PlaylistTrackSource,
+};
+use termusiclib::player::{DownloadRequest, download_request, features};
+use termusiclib::playlist::share::ShareSource;
+use termusiclib::search_history::{HistoryKind, SearchHistory};
+use termusiclib::search_provider::{Bandcamp, SearchProvider, SearchSite};
+use termusiclib::sponsorblock;
+use termusiclib::suggestions::{Offline, Provider, SuggestionProvider as _};
 use termusiclib::track::DurationFmtShort;
 use termusiclib::utils::get_parent_folder;
+use termusiclib::ytdlp::{self, YoutubeCollection};
 use tuirealm::props::{Alignment, AttrValue, Attribute, TableBuilder, TextSpan};
 use tuirealm::{State, StateValue};
 use ytd_rs::{Arg, YoutubeDL};
@@ -18,18 +32,14 @@ use ytd_rs::{Arg, YoutubeDL};
 use super::Model;
 use crate::ui::ids::Id;
 use crate::ui::msg::{Msg, YSMsg};
-
-#[expect(dead_code)]
-static RE_FILENAME: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\[ffmpeg\] Destination: (?P<name>.*)\.mp3").unwrap());
-
-static RE_FILENAME_YTDLP: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\[ExtractAudio\] Destination: (?P<name>.*)\.mp3").unwrap());
+use crate::ui::tui_cmd::{DownloadCmd, PlaylistCmd, TuiCmd};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct YoutubeData {
     pub items: Vec<YoutubeVideo>,
     pub page: u32,
+    /// How many results of the page were hidden by the content filter
+    pub hidden: usize,
 }
 
 impl Default for YoutubeData {
@@ -37,14 +47,247 @@ impl Default for YoutubeData {
         Self {
             items: Vec::new(),
             page: 1,
+            hidden: 0,
         }
     }
 }
 
+impl YoutubeData {
+    /// Remove the results blocked by `filter`.
+    pub fn apply_filter(&mut self, filter: &ContentFilter) {
+        let before = self.items.len();
+        self.items
+            .retain(|v| !filter.blocks(&v.title, v.uploader.as_deref(), v.age_restricted));
+        self.hidden = before - self.items.len();
+    }
+}
+
+/// The most pages imported from a youtube playlist or channel, youtube playlists have at most 5000 videos.
+const MAX_IMPORT_PAGES: u32 = 5000 / COLLECTION_PAGE_SIZE;
+
+/// A page of the videos imported from a youtube playlist or channel, see [`Model::youtube_import`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YoutubeImportPage {
+    /// The playlist index to add the videos at
+    pub at_index: u64,
+    pub items: Vec<YoutubeVideo>,
+    /// The videos imported so far, including this page
+    pub total: usize,
+    /// Whether this is the last page
+    pub done: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct YoutubeOptions {
     pub data: YoutubeData,
     pub invidious_instance: Instance,
+    /// Set if the search is not done with the invidious instance, like with yt-dlp or on another site
+    pub search: Option<Search>,
+}
+
+/// A search that is not done with a invidious instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Search {
+    Ytdlp(YtdlpSearch),
+    Bandcamp(Bandcamp),
+}
+
+impl SearchProvider for Search {
+    fn display_name(&self) -> &str {
+        match self {
+            Self::Ytdlp(v) => v.display_name(),
+            Self::Bandcamp(v) => v.display_name(),
+        }
+    }
+
+    fn site(&self) -> SearchSite {
+        match self {
+            Self::Ytdlp(v) => v.site(),
+            Self::Bandcamp(v) => v.site(),
+        }
+    }
+
+    async fn search(&self, page: u32) -> Result<Vec<YoutubeVideo>> {
+        match self {
+            Self::Ytdlp(v) => v.search(page).await,
+            Self::Bandcamp(v) => v.search(page).await,
+        }
+    }
+
+    fn paginated(&self) -> bool {
+        match self {
+            Self::Ytdlp(v) => v.paginated(),
+            Self::Bandcamp(v) => v.paginated(),
+        }
+    }
+}
+
+/// A search with yt-dlp, for youtube if no invidious instance is available, or for soundcloud.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YtdlpSearch {
+    query: String,
+    site: SearchSite,
+    /// The prefix of the search url, like `ytsearch`
+    prefix: &'static str,
+    results: NonZeroU8,
+    timeout: Duration,
+    extra_args: Vec<String>,
+}
+
+impl YtdlpSearch {
+    /// Create a new search for `query` on `site`, with the options from `config` and the shared options of `youtube`.
+    ///
+    /// # Errors
+    ///
+    /// - if the extra args cannot be parsed
+    /// - if yt-dlp cannot search `site`
+    pub fn new(query: String, site: SearchSite, config: &Ytdlp, youtube: &Youtube) -> Result<Self> {
+        let Some(prefix) = site.ytdlp_prefix() else {
+            bail!("yt-dlp cannot search {}", site.name());
+        };
+        let mut extra_args = youtube.common_args();
+        extra_args.extend(
+            shell_words::split(&config.extra_args).context("Parsing config `extra_ytdlp_args`")?,
+        );
+
+        Ok(Self {
+            query,
+            site,
+            prefix,
+            results: config.search_results,
+            timeout: Duration::from_secs(u64::from(config.search_timeout_secs.get())),
+            extra_args,
+        })
+    }
+
+    /// Get the yt-dlp arguments to get `page`, starting at 1.
+    fn args(&self, page: u32) -> Vec<String> {
+        let per_page = u32::from(self.results.get());
+        let end = page.max(1).saturating_mul(per_page);
+        let start = end.saturating_sub(per_page) + 1;
+
+        let mut args = vec![
+            "--flat-playlist".to_string(),
+            "--dump-json".to_string(),
+            "--playlist-items".to_string(),
+            format!("{start}:{end}"),
+        ];
+        args.extend(self.extra_args.iter().cloned());
+        // the search needs to include all results up to the page
+        args.push(format!("{}{end}:{}", self.prefix, self.query));
+
+        args
+    }
+}
+
+impl SearchProvider for YtdlpSearch {
+    fn display_name(&self) -> &str {
+        match self.site {
+            SearchSite::Youtube => "yt-dlp",
+            site => site.name(),
+        }
+    }
+
+    fn site(&self) -> SearchSite {
+        self.site
+    }
+
+    /// Get the results of `page`, starting at 1.
+    ///
+    /// yt-dlp is killed if it does not finish within the timeout.
+    ///
+    /// # Errors
+    ///
+    /// - if yt-dlp cannot be started
+    /// - if yt-dlp times out or fails
+    async fn search(&self, page: u32) -> Result<Vec<YoutubeVideo>> {
+        let child = tokio::process::Command::new("yt-dlp")
+            .args(self.args(page))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // the process is killed once the future is dropped on timeout
+            .kill_on_drop(true)
+            .spawn()
+            .context("run yt-dlp")?;
+
+        let Ok(output) = tokio::time::timeout(self.timeout, child.wait_with_output()).await else {
+            bail!("yt-dlp did not finish within {:?}", self.timeout);
+        };
+        let output = output.context("wait for yt-dlp")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(
+                "yt-dlp exited with {}: {}",
+                output.status,
+                stderr.lines().last().unwrap_or_default()
+            );
+        }
+
+        Ok(ytdlp::parse_flat_playlist(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+}
+
+/// The suggestions for a input of the youtube search, cycled through with Tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YoutubeSuggestions {
+    /// The input the suggestions are for
+    pub query: String,
+    pub items: Vec<String>,
+    /// The suggestion that is currently in the input
+    pub index: usize,
+    /// The name of the provider, for the title
+    pub provider: &'static str,
+}
+
+impl YoutubeSuggestions {
+    /// Get the currently selected suggestion.
+    #[must_use]
+    pub fn current(&self) -> Option<&str> {
+        self.items.get(self.index).map(String::as_str)
+    }
+
+    /// Select the next suggestion, wrapping around to the first.
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.index = (self.index + 1) % self.items.len();
+        }
+    }
+}
+
+/// Format a view count short, like `1.5M` for `1_500_000`.
+fn format_view_count(views: u64) -> String {
+    const UNITS: [(u64, &str); 3] = [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+
+    for (size, unit) in UNITS {
+        if views >= size {
+            // one decimal is enough to compare uploads, and only shown below 10
+            #[allow(clippy::cast_precision_loss)]
+            let value = views as f64 / size as f64;
+            return if value < 10.0 {
+                format!("{:.1}{unit}", (value * 10.0).floor() / 10.0)
+            } else {
+                format!("{}{unit}", views / size)
+            };
+        }
+    }
+
+    views.to_string()
+}
+
+/// Fetch `page` from `search` if set, otherwise from the invidious `instance`.
+async fn fetch_page(
+    instance: Instance,
+    search: Option<Search>,
+    page: u32,
+) -> Result<Vec<YoutubeVideo>> {
+    match search {
+        Some(search) => search.search(page).await,
+        None => instance.search(page).await,
+    }
 }
 
 impl YoutubeOptions {
@@ -65,9 +308,10 @@ impl YoutubeOptions {
                 ..Default::default()
             };
             let instance = self.invidious_instance.clone();
+            let search = self.search.clone();
 
             return Some(async move {
-                res.items = instance.get_search_query(res.page).await?;
+                res.items = fetch_page(instance, search, res.page).await?;
                 Ok(res)
             });
         }
@@ -75,20 +319,67 @@ impl YoutubeOptions {
         None
     }
 
-    /// Fetch the next page's content.
+    /// Fetch the next page's content, if the results have pages.
     ///
     /// The returned Future does not need the lifetime of `self` for the fetch and is safe to [`Send`].
-    pub fn get_next_page(&self) -> impl Future<Output = Result<YoutubeData>> + use<> {
+    pub fn get_next_page(&self) -> Option<impl Future<Output = Result<YoutubeData>> + use<>> {
+        if !self.paginated() {
+            return None;
+        }
+
         let mut res = YoutubeData {
             page: self.data.page + 1,
             ..Default::default()
         };
         let instance = self.invidious_instance.clone();
+        let search = self.search.clone();
 
-        async move {
-            res.items = instance.get_search_query(res.page).await?;
+        Some(async move {
+            res.items = fetch_page(instance, search, res.page).await?;
             Ok(res)
+        })
+    }
+
+    /// Get the region, if these are the results of the music trending, which has no pages.
+    #[must_use]
+    pub fn trending_region(&self) -> Option<&str> {
+        if self.search.is_some() {
+            return None;
         }
+
+        self.invidious_instance.region()
+    }
+
+    /// Get the name of where the results are from, none if there are no results yet.
+    #[must_use]
+    pub fn source(&self) -> Option<&str> {
+        match &self.search {
+            Some(search) => Some(search.display_name()),
+            None => self.invidious_instance.domain.as_deref(),
+        }
+    }
+
+    /// Get the site the results are from.
+    #[must_use]
+    pub fn site(&self) -> SearchSite {
+        self.search
+            .as_ref()
+            .map_or(SearchSite::Youtube, SearchProvider::site)
+    }
+
+    /// Check if the results have more than one page.
+    #[must_use]
+    pub fn paginated(&self) -> bool {
+        match &self.search {
+            Some(search) => search.paginated(),
+            None => self.invidious_instance.paginated(),
+        }
+    }
+
+    /// Get the page of `item`, to download or stream it.
+    #[must_use]
+    pub fn item_url(&self, item: &YoutubeVideo) -> String {
+        self.site().item_url(item)
     }
 
     #[must_use]
@@ -105,31 +396,51 @@ impl YoutubeOptions {
     }
 }
 
+/// Search the first page with `search`, instead of a invidious instance.
+async fn youtube_options_search_with(search: Result<Search>) -> Result<YoutubeOptions> {
+    let search = search?;
+    let items = search.search(1).await?;
+
+    Ok(YoutubeOptions {
+        data: YoutubeData {
+            items,
+            ..Default::default()
+        },
+        invidious_instance: Instance::default(),
+        search: Some(search),
+    })
+}
+
 /// Helper function to handle successful download completion
 fn handle_download_success(
     tx: &tokio::sync::mpsc::UnboundedSender<Msg>,
     url: Arc<str>,
     output: &str,
     path: &Path,
+    db: &Database,
 ) {
     tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Success(
         url.clone(),
     ))))
     .ok();
-    
+
     if let Some(file_fullname) = extract_filepath(output, &path.to_string_lossy()) {
+        finish_download(path, &file_fullname);
+        // after embedding the lyrics, as that changes the content
+        if let Err(err) =
+            download_ops::dedup_download(&db.get_connection(), &url, Path::new(&file_fullname))
+        {
+            warn!("Deduplicating the download {file_fullname} failed: {err:#}");
+        }
+
         tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Completed(
             url,
-            Some(file_fullname.clone()),
+            Some(file_fullname),
         ))))
         .ok();
-
-        remove_downloaded_json(path, &file_fullname);
-        embed_downloaded_lrc(path, &file_fullname);
     } else {
         tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Completed(
-            url,
-            None,
+            url, None,
         ))))
         .ok();
     }
@@ -139,28 +450,180 @@ impl Model {
     pub fn youtube_options_download(&mut self, index: usize) -> Result<()> {
         // download from search result here
         if let Ok(item) = self.youtube_options.get_by_index(index) {
-            let url = format!("https://www.youtube.com/watch?v={}", item.video_id);
+            let url = self.youtube_options.item_url(item);
             // Prepare fallback URL using the Invidious instance
-            let fallback_url = self.youtube_options.invidious_instance.domain.as_ref()
+            let fallback_url = self
+                .youtube_options
+                .invidious_instance
+                .domain
+                .as_ref()
+                .filter(|domain| !domain.is_empty() && self.youtube_options.search.is_none())
                 .map(|domain| format!("{}/watch?v={}", domain, item.video_id));
-            self.youtube_dl(url.as_ref(), fallback_url.as_deref()).context("YTDL Download")?;
+            self.youtube_dl(url.as_ref(), fallback_url.as_deref())
+                .context("YTDL Download")?;
         }
         Ok(())
     }
 
+    /// Stream the search result at `index` without downloading it, by adding it to the end of the playlist and playing it.
+    ///
+    /// The server extracts the audio stream with yt-dlp and caches it in a temporary file only while playing.
+    pub fn youtube_options_stream(&mut self, index: usize) -> Result<()> {
+        if !self.server_capabilities.supports(features::VIDEO_STREAM) {
+            bail!("Streaming videos is not supported by the server");
+        }
+        let item = self.youtube_options.get_by_index(index)?;
+        let title = item.title.clone();
+        let source = PlaylistTrackSource::VideoUrl(self.youtube_options.item_url(item));
+        let track_index = u64::try_from(self.playback.playlist.len())?;
+
+        // the server handles commands in order, so the track is added before it is played
+        self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+            PlaylistAddTrack::new_single(track_index, source.clone()),
+        )));
+        self.command(TuiCmd::Playlist(PlaylistCmd::PlaySpecific(
+            PlaylistPlaySpecific {
+                track_index,
+                id: source,
+            },
+        )));
+        self.update_show_message_timeout("Streaming", &title, Some(5));
+
+        Ok(())
+    }
+
+    /// Import all videos of the youtube playlist or channel at `url` to the end of the playlist, to be streamed.
+    ///
+    /// The videos are fetched a page at a time and each page is added once fetched, so that the first videos of a large
+    /// playlist can already be played.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    pub fn youtube_import(&mut self, url: String, collection: YoutubeCollection) -> Result<()> {
+        if !self.server_capabilities.supports(features::VIDEO_STREAM) {
+            bail!("Streaming videos is not supported by the server");
+        }
+        let mut at_index = u64::try_from(self.playback.playlist.len())?;
+        self.update_show_message_timeout("Youtube import", &format!("Fetching {url}"), None);
+
+        let tx = self.tx_to_main.clone();
+        tokio::spawn(async move {
+            let mut total = 0;
+            for page in 1..=MAX_IMPORT_PAGES {
+                let res = match collection {
+                    YoutubeCollection::Playlist => Instance::get_playlist(&url, page).await,
+                    YoutubeCollection::Channel => Instance::get_channel_uploads(&url, page).await,
+                };
+                let items = match res {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = tx.send(Msg::YoutubeSearch(YSMsg::ImportFail(format!(
+                            "{url} after {total} videos: {err:#}"
+                        ))));
+                        return;
+                    }
+                };
+
+                let count = items.len();
+                total += count;
+                let done = count < COLLECTION_PAGE_SIZE as usize || page == MAX_IMPORT_PAGES;
+                let _ = tx.send(Msg::YoutubeSearch(YSMsg::ImportPage(YoutubeImportPage {
+                    at_index,
+                    items,
+                    total,
+                    done,
+                })));
+                if done {
+                    break;
+                }
+                at_index += count as u64;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Add a page of videos imported by [`youtube_import`](Self::youtube_import) to the playlist.
+    pub fn youtube_import_page(&mut self, page: YoutubeImportPage) {
+        if !page.items.is_empty() {
+            let (tracks, infos) = page
+                .items
+                .into_iter()
+                .map(|item| {
+                    let source =
+                        PlaylistTrackSource::VideoUrl(ShareSource::youtube_url(&item.video_id));
+                    let info = PlaylistTrackInfo {
+                        title: Some(item.title),
+                        // live streams have no duration
+                        duration: (item.length_seconds > 0)
+                            .then(|| Duration::from_secs(item.length_seconds)),
+                    };
+
+                    (source, info)
+                })
+                .unzip();
+
+            self.command(TuiCmd::Playlist(PlaylistCmd::AddTrack(
+                PlaylistAddTrack::new_vec(page.at_index, tracks).with_infos(infos),
+            )));
+        }
+
+        let text = if page.done {
+            format!("Added {} videos", page.total)
+        } else {
+            format!("Added {} videos, fetching more", page.total)
+        };
+        self.update_show_message_timeout("Youtube import", &text, Some(5));
+    }
+
+    /// Search `keyword` on the site picked in the search input, see [`SearchSite`].
+    ///
     /// This function requires to be run in a tokio Runtime context
     pub fn youtube_options_search(&mut self, keyword: String) {
         let tx = self.tx_to_main.clone();
+        let site = self.youtube_search_site;
+        let (search, instances) = {
+            let config_tui = self.config_tui.read();
+            let search = match site {
+                SearchSite::Youtube | SearchSite::SoundCloud => YtdlpSearch::new(
+                    keyword.clone(),
+                    site,
+                    &config_tui.settings.ytdlp,
+                    &config_tui.settings.youtube,
+                )
+                .map(Search::Ytdlp),
+                SearchSite::Bandcamp => Bandcamp::new(keyword.clone()).map(Search::Bandcamp),
+            };
+            (
+                search,
+                config_tui.settings.youtube.invidious_instances.clone(),
+            )
+        };
         tokio::spawn(async move {
-            match Instance::new(&keyword).await {
-                Ok((instance, result)) => {
-                    let youtube_options = YoutubeOptions {
+            let res = if site == SearchSite::Youtube {
+                match Instance::new(&keyword, &instances).await {
+                    Ok((instance, result)) => Ok(YoutubeOptions {
                         data: YoutubeData {
                             items: result,
-                            page: 1,
+                            ..Default::default()
                         },
                         invidious_instance: instance,
-                    };
+                        search: None,
+                    }),
+                    Err(invidious_err) => {
+                        info!("Searching with yt-dlp, as invidious failed: {invidious_err:#}");
+                        youtube_options_search_with(search)
+                            .await
+                            .map_err(|err| anyhow!("{invidious_err}; yt-dlp: {err:#}"))
+                    }
+                }
+            } else {
+                youtube_options_search_with(search)
+                    .await
+                    .map_err(|err| anyhow!("{}: {err:#}", site.name()))
+            };
+
+            match res {
+                Ok(youtube_options) => {
                     tx.send(Msg::YoutubeSearch(YSMsg::YoutubeSearchSuccess(
                         youtube_options,
                     )))
@@ -174,6 +637,68 @@ impl Model {
         });
     }
 
+    /// Fetch the music trending in `region` and show it in the search table.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    pub fn youtube_options_trending(&mut self, region: String) {
+        let tx = self.tx_to_main.clone();
+        let instances = self
+            .config_tui
+            .read()
+            .settings
+            .youtube
+            .invidious_instances
+            .clone();
+        tokio::spawn(async move {
+            let msg = match Instance::new_trending(&region, &instances).await {
+                Ok((instance, items)) => YSMsg::YoutubeSearchSuccess(YoutubeOptions {
+                    data: YoutubeData {
+                        items,
+                        ..Default::default()
+                    },
+                    invidious_instance: instance,
+                    search: None,
+                }),
+                Err(err) => YSMsg::YoutubeSearchFail(format!("trending in {region}: {err:#}")),
+            };
+            let _ = tx.send(Msg::YoutubeSearch(msg));
+        });
+    }
+
+    /// Start the periodic health probe of the invidious instances, if enabled.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    pub fn youtube_invidious_probe_start(&self) {
+        let youtube = &self.config_tui.read().settings.youtube;
+        let Some(interval) = youtube.invidious_probe_interval() else {
+            return;
+        };
+
+        tokio::spawn(probe_periodically(
+            youtube.invidious_instances.clone(),
+            interval,
+        ));
+    }
+
+    /// Get the region to preselect for the music trending.
+    ///
+    /// The last picked region is preferred over the configured one, which is preferred over the locale.
+    pub fn youtube_trending_region(&self) -> String {
+        let history = SearchHistory::load_default();
+        let configured = self.config_tui.read().settings.trending.region.clone();
+
+        resolve_region(history.trending_region().or(configured.as_deref()))
+    }
+
+    /// Remember `region` as the last picked region of the music trending.
+    pub fn youtube_trending_region_remember(region: &str) {
+        if let Err(err) = SearchHistory::update_default(|history| {
+            history.set_trending_region(region.to_string());
+        }) {
+            warn!("Saving the trending region failed: {err:#}");
+        }
+    }
+
     /// This function requires to be run in a tokio Runtime context
     pub fn youtube_options_prev_page(&self) {
         let tx_to_main = self.tx_to_main.clone();
@@ -199,7 +724,9 @@ impl Model {
     pub fn youtube_options_next_page(&mut self) {
         let tx_to_main = self.tx_to_main.clone();
 
-        let fut = self.youtube_options.get_next_page();
+        let Some(fut) = self.youtube_options.get_next_page() else {
+            return;
+        };
 
         tokio::task::spawn(async move {
             match fut.await {
@@ -214,13 +741,127 @@ impl Model {
         });
     }
 
+    /// Complete the youtube search input with a suggestion for `input`.
+    ///
+    /// If `input` is the current suggestion, the next one is selected, otherwise new suggestions are fetched.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    pub fn youtube_suggest(&mut self, input: String) {
+        if let Some(suggestions) = &mut self.youtube_suggestions
+            && suggestions.current() == Some(input.as_str())
+        {
+            suggestions.select_next();
+            self.sync_youtube_suggestion();
+            return;
+        }
+
+        // urls are downloaded directly, there is nothing to suggest
+        if input.starts_with("http") {
+            return;
+        }
+
+        let backend = self.config_tui.read().settings.suggestions.provider;
+        let offline = if backend == SuggestionBackend::Offline {
+            Offline::new(
+                SearchHistory::load_default().get(HistoryKind::Web).clone(),
+                Some(self.db.clone()),
+            )
+        } else {
+            Offline::default()
+        };
+        let provider = match Provider::new(
+            backend,
+            self.youtube_options.invidious_instance.domain.clone(),
+            offline,
+        ) {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(err.context("youtube suggestions"));
+                return;
+            }
+        };
+
+        let tx = self.tx_to_main.clone();
+        tokio::spawn(async move {
+            let msg = match provider.suggest(&input).await {
+                Ok(items) => YSMsg::SuggestionsLoaded(YoutubeSuggestions {
+                    query: input,
+                    items,
+                    index: 0,
+                    provider: provider.display_name(),
+                }),
+                Err(err) => YSMsg::SuggestionsError(format!(
+                    "{} suggestions: {err:#}",
+                    provider.display_name()
+                )),
+            };
+            let _ = tx.send(Msg::YoutubeSearch(msg));
+        });
+    }
+
+    /// Apply loaded suggestions, if the input did not change in the meantime.
+    pub fn youtube_suggestions_loaded(&mut self, suggestions: YoutubeSuggestions) {
+        let Ok(State::One(StateValue::String(input))) =
+            self.app.state(&Id::YoutubeSearchInputPopup)
+        else {
+            return;
+        };
+        if input != suggestions.query {
+            return;
+        }
+
+        self.youtube_suggestions = Some(suggestions);
+        self.sync_youtube_suggestion();
+    }
+
+    /// Put the current suggestion into the youtube search input and show the position in the title.
+    fn sync_youtube_suggestion(&mut self) {
+        let Some(suggestions) = &self.youtube_suggestions else {
+            return;
+        };
+
+        let title = if let Some(current) = suggestions.current() {
+            self.app
+                .attr(
+                    &Id::YoutubeSearchInputPopup,
+                    Attribute::Value,
+                    AttrValue::String(current.to_string()),
+                )
+                .ok();
+            format!(
+                " Suggestion {}/{} from {} (Tab for next): ",
+                suggestions.index + 1,
+                suggestions.items.len(),
+                suggestions.provider
+            )
+        } else {
+            format!(" No suggestions from {}: ", suggestions.provider)
+        };
+
+        self.app
+            .attr(
+                &Id::YoutubeSearchInputPopup,
+                Attribute::Title,
+                AttrValue::Title((title, Alignment::Left)),
+            )
+            .ok();
+    }
+
+    /// Reset the suggestions, for a new input.
+    pub fn youtube_suggestions_clear(&mut self) {
+        self.youtube_suggestions = None;
+    }
+
     pub fn sync_youtube_options(&mut self) {
         if self.youtube_options.is_empty() {
+            let reason = if self.youtube_options.data.hidden > 0 {
+                "All results were hidden by the content filter."
+            } else {
+                "Nothing was found in 10 seconds, connection issue encountered."
+            };
             let table = TableBuilder::default()
                 .add_col(TextSpan::from("No results."))
-                .add_col(TextSpan::from(
-                    "Nothing was found in 10 seconds, connection issue encountered.",
-                ))
+                .add_col(TextSpan::from(reason))
                 .build();
             self.app
                 .attr(
@@ -241,10 +882,18 @@ impl Model {
             let duration_string = format!("[{duration:^10.10}]");
 
             let title = record.title.as_str();
+            let views = record.view_count.map(format_view_count);
 
             table
                 .add_col(TextSpan::new(duration_string))
-                .add_col(TextSpan::new(title).bold());
+                .add_col(TextSpan::new(title).bold())
+                .add_col(TextSpan::new(
+                    record.uploader.as_deref().unwrap_or_default(),
+                ))
+                .add_col(TextSpan::new(views.unwrap_or_default()))
+                .add_col(TextSpan::new(
+                    record.upload_date.as_deref().unwrap_or_default(),
+                ));
         }
         let table = table.build();
         self.app
@@ -255,12 +904,39 @@ impl Model {
             )
             .ok();
 
-        if let Some(domain) = &self.youtube_options.invidious_instance.domain {
+        let source = self.youtube_options.source();
+        let hidden = self.youtube_options.data.hidden;
+        let source = source.map(|v| {
+            if hidden > 0 {
+                format!("{v}, {hidden} hidden")
+            } else {
+                v.to_string()
+            }
+        });
+        if let Some(region) = self.youtube_options.trending_region() {
+            let title = format!(
+                "\u{2500}\u{2500}\u{2500} Music trending in {} \u{2500}\u{2500}\u{2500}\u{2524} {} \u{251c}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}",
+                region,
+                source.unwrap_or_default(),
+            );
+            self.app
+                .attr(
+                    &Id::YoutubeSearchTablePopup,
+                    Attribute::Title,
+                    AttrValue::Title((title, Alignment::Left)),
+                )
+                .ok();
+        } else if let Some(source) = source {
+            let keys = if self.youtube_options.paginated() {
+                "Tab/Shift+Tab switch pages, Enter download, s stream"
+            } else {
+                "Enter download, s stream"
+            };
             let title = format!(
                 "\u{2500}\u{2500}\u{2500} Page {} \u{2500}\u{2500}\u{2500}\u{2524} {} \u{251c}\u{2500}\u{2500} {} \u{2500}\u{2500}\u{2500}\u{2500}\u{2500}",
                 self.youtube_options.page(),
-                "Tab/Shift+Tab switch pages",
-                domain,
+                keys,
+                source,
             );
             self.app
                 .attr(
@@ -272,18 +948,51 @@ impl Model {
         }
     }
 
+    #[allow(clippy::too_many_lines)]
+    /// Add the download of `url` to the download queue of the server, saving into `path`.
+    fn youtube_dl_enqueue(
+        &mut self,
+        url: &str,
+        fallback_url: Option<&str>,
+        path: &Path,
+    ) -> Result<()> {
+        let config_tui = self.config_tui.read();
+        let youtube = &config_tui.settings.youtube;
+        let mut args = youtube.download_args();
+        args.extend(youtube.common_args());
+        args.extend(
+            shell_words::split(&config_tui.settings.ytdlp.extra_args)
+                .context("Parsing config `extra_ytdlp_args`")?,
+        );
+        drop(config_tui);
+
+        let request = DownloadRequest {
+            url: url.to_string(),
+            optional_fallback_url: fallback_url
+                .map(|v| download_request::OptionalFallbackUrl::FallbackUrl(v.to_string())),
+            // the server uses the file name until then
+            title: String::new(),
+            dir: path.to_string_lossy().into_owned(),
+            args,
+        };
+        self.command(TuiCmd::Download(DownloadCmd::Enqueue(request)));
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_lines)]
     pub fn youtube_dl(&mut self, url: &str, fallback_url: Option<&str>) -> Result<()> {
         let mut path: PathBuf = std::env::temp_dir();
         if let Ok(State::One(StateValue::String(node_id))) = self.app.state(&Id::Library) {
             path = get_parent_folder(Path::new(&node_id)).to_path_buf();
         }
+        if self.server_capabilities.supports(features::DOWNLOAD_QUEUE) {
+            return self.youtube_dl_enqueue(url, fallback_url, &path);
+        }
         let config_tui = self.config_tui.read();
         let mut args = vec![
             Arg::new("--no-playlist"),
             Arg::new("--extract-audio"),
-            // Arg::new_with_arg("--audio-format", "vorbis"),
-            Arg::new_with_arg("--audio-format", "mp3"),
             Arg::new("--add-metadata"),
             // Don't embed thumbnail to avoid downloading cover art
             // Arg::new("--embed-thumbnail"),
@@ -295,6 +1004,20 @@ impl Model {
             Arg::new_with_arg("--convert-subs", "lrc"),
             Arg::new_with_arg("--output", "%(title).90s.%(ext)s"),
         ];
+        let youtube = &config_tui.settings.youtube;
+        for pair in youtube
+            .download_args()
+            .chunks_exact(2)
+            .chain(youtube.common_args().chunks_exact(2))
+        {
+            args.push(Arg::new_with_arg(&pair[0], &pair[1]));
+        }
+        if let Some([remove, categories, api, api_url]) =
+            sponsorblock::ytdlp_remove_args(&self.config_server.read().settings.sponsorblock)
+        {
+            args.push(Arg::new_with_arg(&remove, &categories));
+            args.push(Arg::new_with_arg(&api, &api_url));
+        }
         let extra_args = parse_args(&config_tui.settings.ytdlp.extra_args)
             .context("Parsing config `extra_ytdlp_args`")?;
         let mut extra_args_parsed = convert_to_args(extra_args);
@@ -304,6 +1027,7 @@ impl Model {
 
         let ytd = YoutubeDL::new(&path, args.clone(), url)?;
         let tx = self.tx_to_main.clone();
+        let db = self.db.clone();
 
         // avoid full string clones when sending via a channel
         let url: Arc<str> = Arc::from(url);
@@ -315,14 +1039,14 @@ impl Model {
                 "youtube music".to_string(),
             ))))
             .ok();
-            
+
             // Try primary URL with yt-dlp first
             let download = ytd.download();
 
             // check what the result is and print out the path to the download or the error
             match download {
                 Ok(result) => {
-                    handle_download_success(&tx, url, result.output(), &path);
+                    handle_download_success(&tx, url, result.output(), &path, &db);
                 }
                 Err(e) => {
                     // If primary download fails and we have a fallback URL, try that
@@ -330,33 +1054,31 @@ impl Model {
                         tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Err(
                             url.clone(),
                             "youtube music".to_string(),
-                            format!("Primary download failed, trying fallback: {}", e),
+                            format!("Primary download failed, trying fallback: {e}"),
                         ))))
                         .ok();
-                        
+
                         // Try fallback URL
                         match YoutubeDL::new(&path, args, fallback.as_ref()) {
-                            Ok(ytd_fallback) => {
-                                match ytd_fallback.download() {
-                                    Ok(result) => {
-                                        handle_download_success(&tx, url, result.output(), &path);
-                                        return Ok(());
-                                    }
-                                    Err(fallback_err) => {
-                                        tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Err(
+                            Ok(ytd_fallback) => match ytd_fallback.download() {
+                                Ok(result) => {
+                                    handle_download_success(&tx, url, result.output(), &path, &db);
+                                    return Ok(());
+                                }
+                                Err(fallback_err) => {
+                                    tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Err(
                                             url.clone(),
                                             "youtube music".to_string(),
-                                            format!("Both primary and fallback failed. Primary: {} Fallback: {}", e, fallback_err),
+                                            format!("Both primary and fallback failed. Primary: {e} Fallback: {fallback_err}"),
                                         ))))
                                         .ok();
-                                    }
                                 }
-                            }
+                            },
                             Err(init_err) => {
                                 tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Err(
                                     url.clone(),
                                     "youtube music".to_string(),
-                                    format!("Primary download failed and fallback initialization failed. Primary: {} Fallback init: {}", e, init_err),
+                                    format!("Primary download failed and fallback initialization failed. Primary: {e} Fallback init: {init_err}"),
                                 ))))
                                 .ok();
                             }
@@ -369,7 +1091,7 @@ impl Model {
                         ))))
                         .ok();
                     }
-                    
+
                     // Send completion message on error
                     tx.send(Msg::YoutubeSearch(YSMsg::Download(YTDLMsg::Completed(
                         url, None,
@@ -405,109 +1127,6 @@ pub enum YTDLMsg {
     Err(YTDLMsgURL, String, String),
 }
 
-// This just parsing the output from youtubedl to get the audio path
-// This is used because we need to get the song name
-// example ~/path/to/song/song.mp3
-fn extract_filepath(output: &str, dir: &str) -> Option<String> {
-    // #[cfg(not(feature = "yt-dlp"))]
-    // if let Some(cap) = RE_FILENAME.captures(output) {
-    //     if let Some(c) = cap.name("name") {
-    //         let filename = format!("{}/{}.mp3", dir, c.as_str());
-    //         return Ok(filename);
-    //     }
-    // }
-    // #[cfg(feature = "yt-dlp")]
-    if let Some(cap) = RE_FILENAME_YTDLP.captures(output)
-        && let Some(c) = cap.name("name")
-    {
-        let filename = format!("{dir}/{}.mp3", c.as_str());
-        return Some(filename);
-    }
-    None
-}
-
-fn remove_downloaded_json(path: &Path, file_fullname: &str) {
-    let files = walkdir::WalkDir::new(path).follow_links(true);
-    for f in files
-        .into_iter()
-        .filter_map(std::result::Result::ok)
-        .filter(|f| {
-            let p = Path::new(f.file_name());
-            p.extension().is_some_and(|ext| ext == "json")
-        })
-        .filter(|f| {
-            let path_json = Path::new(f.file_name());
-            let p1: &Path = Path::new(file_fullname);
-            path_json.file_stem().is_some_and(|stem_lrc| {
-                p1.file_stem().is_some_and(|p_base| {
-                    stem_lrc
-                        .to_string_lossy()
-                        .contains(p_base.to_string_lossy().as_ref())
-                })
-            })
-        })
-    {
-        std::fs::remove_file(f.path()).ok();
-    }
-}
-
-fn embed_downloaded_lrc(path: &Path, file_fullname: &str) {
-    let mut id3_tag = if let Ok(tag) = id3::Tag::read_from_path(file_fullname) {
-        tag
-    } else {
-        let mut tags = id3::Tag::new();
-        let file_path = Path::new(file_fullname);
-        if let Some(p_base) = file_path.file_stem() {
-            tags.set_title(p_base.to_string_lossy());
-        }
-        tags.write_to_path(file_path, Id3v24).ok();
-        tags
-    };
-
-    // here we add all downloaded lrc file
-    let files = walkdir::WalkDir::new(path).follow_links(true);
-
-    for entry in files
-        .into_iter()
-        .filter_map(std::result::Result::ok)
-        .filter(|f| f.file_type().is_file())
-        .filter(|f| {
-            let name = f.file_name();
-            let p = Path::new(&name);
-            p.extension().is_some_and(|ext| ext == "lrc")
-        })
-        .filter(|f| {
-            let path_lrc = Path::new(f.file_name());
-            let p1: &Path = Path::new(file_fullname);
-            path_lrc.file_stem().is_some_and(|stem_lrc| {
-                p1.file_stem().is_some_and(|p_base| {
-                    stem_lrc
-                        .to_string_lossy()
-                        .contains(p_base.to_string_lossy().as_ref())
-                })
-            })
-        })
-    {
-        let path_lrc = Path::new(entry.file_name());
-        let mut lang_ext = "eng".to_string();
-        if let Some(p_short) = path_lrc.file_stem() {
-            let p2 = Path::new(p_short);
-            if let Some(ext2) = p2.extension() {
-                lang_ext = ext2.to_string_lossy().to_string();
-            }
-        }
-        let lyric_string = std::fs::read_to_string(entry.path());
-        id3_tag.add_frame(id3::frame::Lyrics {
-            lang: "eng".to_string(),
-            description: lang_ext,
-            text: lyric_string.unwrap_or_else(|_| String::from("[00:00:01] No lyric")),
-        });
-        std::fs::remove_file(entry.path()).ok();
-    }
-
-    id3_tag.write_to_path(file_fullname, Id3v24).ok();
-}
-
 #[derive(Debug, Clone, PartialEq)]
 enum ArgOrVal {
     ArgumentWithVal(String),
@@ -586,27 +1205,93 @@ fn convert_to_args(extra_args: Vec<ArgOrVal>) -> Vec<Arg> {
 #[cfg(test)]
 mod tests {
 
-    use crate::ui::model::youtube_options::extract_filepath;
+    use std::num::{NonZeroU8, NonZeroU32};
+
+    use crate::ui::model::youtube_options::{YoutubeSuggestions, YtdlpSearch, format_view_count};
     use pretty_assertions::assert_eq;
+    use termusiclib::config::v2::tui::{Youtube, Ytdlp};
+    use termusiclib::search_provider::SearchSite;
+
+    #[test]
+    fn should_format_view_count() {
+        assert_eq!(format_view_count(0), "0");
+        assert_eq!(format_view_count(999), "999");
+        assert_eq!(format_view_count(1_000), "1.0K");
+        assert_eq!(format_view_count(1_599), "1.5K");
+        assert_eq!(format_view_count(25_400), "25K");
+        assert_eq!(format_view_count(1_500_000), "1.5M");
+        assert_eq!(format_view_count(3_200_000_000), "3.2B");
+    }
 
     #[test]
-    fn test_youtube_output_parsing() {
-        // #[cfg(not(feature = "yt-dlp"))]
-        // assert_eq!(
-        //     extract_filepath(
-        //         r"sdflsdf [ffmpeg] Destination: 观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3 sldflsdfj",
-        //         "/tmp"
-        //     )
-        //     .unwrap(),
-        //     "/tmp/观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3".to_string()
-        // );
+    fn should_cycle_suggestions() {
+        let mut suggestions = YoutubeSuggestions {
+            query: "lo".to_string(),
+            items: vec!["lofi".to_string(), "love".to_string()],
+            index: 0,
+            provider: "Offline",
+        };
+        assert_eq!(suggestions.current(), Some("lofi"));
+        suggestions.select_next();
+        assert_eq!(suggestions.current(), Some("love"));
+        suggestions.select_next();
+        assert_eq!(suggestions.current(), Some("lofi"));
+
+        suggestions.items.clear();
+        suggestions.index = 0;
+        suggestions.select_next();
+        assert_eq!(suggestions.current(), None);
+    }
+
+    #[test]
+    fn should_page_ytdlp_search() {
+        let search = YtdlpSearch::new(
+            "some query".to_string(),
+            SearchSite::Youtube,
+            &Ytdlp {
+                extra_args: "--cookies-from-browser firefox".to_string(),
+                search_results: NonZeroU8::new(10).unwrap(),
+                search_timeout_secs: NonZeroU32::new(5).unwrap(),
+            },
+            &Youtube {
+                proxy: "socks5://127.0.0.1:1080".to_string(),
+                ..Youtube::default()
+            },
+        )
+        .unwrap();
+
         assert_eq!(
-            extract_filepath(
-                r"sdflsdf [ExtractAudio] Destination: 观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3 sldflsdfj",
-                "/tmp"
+            search.args(2),
+            [
+                "--flat-playlist",
+                "--dump-json",
+                "--playlist-items",
+                "11:20",
+                "--proxy",
+                "socks5://127.0.0.1:1080",
+                "--cookies-from-browser",
+                "firefox",
+                "ytsearch20:some query"
+            ]
+        );
+        assert_eq!(search.args(0)[3], "1:10");
+
+        let search = YtdlpSearch::new(
+            "some query".to_string(),
+            SearchSite::SoundCloud,
+            &Ytdlp::default(),
+            &Youtube::default(),
+        )
+        .unwrap();
+        assert_eq!(search.args(1).last().unwrap(), "scsearch20:some query");
+        assert!(
+            YtdlpSearch::new(
+                "some query".to_string(),
+                SearchSite::Bandcamp,
+                &Ytdlp::default(),
+                &Youtube::default(),
             )
-            .unwrap(),
-            "/tmp/观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3".to_string()
+            .is_err()
         );
     }
 }
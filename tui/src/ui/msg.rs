@@ -2,17 +2,32 @@
 
 use std::ffi::OsString;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use image::DynamicImage;
+use termusiclib::config::v2::server::equalizer::EqualizerSettings;
 use termusiclib::config::v2::tui::{keys::KeyBinding, theme::styles::ColorTermusic};
-use termusiclib::player::{GetProgressResponse, PlaylistTracks, UpdateEvents};
+use termusiclib::device_sync::SyncReport;
+use termusiclib::downloads::{DownloadAction, DownloadInfo};
+use termusiclib::new_database::track_ops::{TrackLabel, TrackRead};
+use termusiclib::new_database::{Integer, VerifySummary};
+use termusiclib::player::{
+    AbLoop, GetProgressResponse, OutputDevices, PlaylistTracks, UpdateEvents,
+};
+use termusiclib::playlist_sync::SyncReport as PlaylistSyncReport;
 use termusiclib::podcast::{PodcastDLResult, PodcastFeed, PodcastSyncResult};
+use termusiclib::radio_browser::Station;
+use termusiclib::search_history::HistoryKind;
 use termusiclib::songtag::{SongtagSearchResult, TrackDLMsg};
+use termusiclib::track_suggestions::TrackSuggestion;
 use tokio::sync::mpsc;
 
-use crate::ui::components::TETrack;
+use crate::ui::components::{SearchMode, TETrack};
 use crate::ui::ids::{IdCEGeneral, IdCETheme, IdConfigEditor, IdKey, IdKeyGlobal, IdKeyOther};
-use crate::ui::model::youtube_options::{YTDLMsg, YoutubeData, YoutubeOptions};
+use crate::ui::model::credits::Credit;
+use crate::ui::model::youtube_options::{
+    YTDLMsg, YoutubeData, YoutubeImportPage, YoutubeOptions, YoutubeSuggestions,
+};
 
 /// Main message type that encapsulates everything else.
 // Note that the style is for each thing to have a sub-type, unless it is top-level like "ForceRedraw".
@@ -33,9 +48,28 @@ pub enum Msg {
     Xywh(XYWHMsg),
     LyricMessage(LyricMsg),
     DeleteConfirm(DeleteConfirmMsg),
+    DeviceSync(DeviceSyncMsg),
+    PlaylistSync(PlaylistSyncMsg),
+    LyricsFetch(LyricsFetchMsg),
+    QueueReplace(QueueReplaceMsg),
     QuitPopup(QuitPopupMsg),
+    ActionConfirm(ActionConfirmMsg),
     HelpPopup(HelpPopupMsg),
     ErrorPopup(ErrorPopupMsg),
+    SearchHistory(SearchHistoryMsg),
+    Credits(CreditsMsg),
+    Downloads(DownloadsMsg),
+    Radio(RadioMsg),
+    Equalizer(EqualizerMsg),
+    OutputDevices(OutputDevicesMsg),
+    Bookmarks(BookmarksMsg),
+    SmartPlaylist(SmartPlaylistMsg),
+    Statistics(StatisticsMsg),
+    TrackSuggestions(TrackSuggestionsMsg),
+    SeekToPosition(SeekToPositionMsg),
+    Translation(TranslationMsg),
+    /// Run the macro with the given name on the server, see `keys.macros`
+    RunMacro(String),
 
     /// Same as [`ForceRedraw`](Msg::ForceRedraw), but also updated the drawn cover.
     UpdatePhoto,
@@ -66,6 +100,16 @@ pub enum MainLayoutMsg {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlayerMsg {
     ToggleGapless,
+    ToggleKaraoke,
+    ToggleRecording,
+    ToggleAlbumMode,
+    TapTempo,
+    ToggleNormalization,
+    ToggleSmartSpeed,
+    /// Set the start of the A-B loop, then its end, then clear it
+    AbLoop,
+    /// Update the beat indicator, if the schedule with the given generation is still current
+    BeatTick(u64),
     TogglePause,
     VolumeUp,
     VolumeDown,
@@ -84,6 +128,35 @@ pub enum PlayerMsg {
 #[derive(Clone, Debug, Eq, Default)]
 pub struct SPUpdateData {
     pub path: OsString,
+    pub format: SaveFormat,
+}
+
+/// The format to save the playlist in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SaveFormat {
+    #[default]
+    M3u,
+    /// A plain text list of titles and sources to share with other users, see [`share`](termusiclib::playlist::share).
+    Share,
+}
+
+impl SaveFormat {
+    /// The ending of the file name, including the extension.
+    #[must_use]
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::M3u => ".m3u",
+            Self::Share => termusiclib::playlist::share::FILE_SUFFIX,
+        }
+    }
+
+    #[must_use]
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::M3u => Self::Share,
+            Self::Share => Self::M3u,
+        }
+    }
 }
 
 /// `PartialEq` is only used for subscriptions.
@@ -163,6 +236,156 @@ pub enum QuitPopupMsg {
     Show,
 }
 
+/// Actions that can be configured to ask for confirmation first, see [`BehaviorSettings`](termusiclib::config::v2::tui::BehaviorSettings).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAction {
+    ClearPlaylist,
+    OverwriteTags,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchHistoryMsg {
+    /// Show the history of the searches of the given kind.
+    PopupShow(HistoryKind),
+    PopupCloseCancel(HistoryKind),
+    /// Close the popup and use the selected query in the search it was opened from.
+    PopupCloseOk(HistoryKind, String),
+    /// Pin or unpin the query in the history.
+    TogglePin(HistoryKind, String),
+}
+
+/// Messages for the credits popup of the currently playing track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CreditsMsg {
+    Show,
+    Close,
+    /// Close the popup and jump to the selected credit in the database view.
+    Jump(Credit),
+}
+
+/// Messages for the popup listing the download queue of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadsMsg {
+    Show,
+    Close,
+    /// Pause, resume, cancel or retry the download with the id.
+    Control(u64, DownloadAction),
+}
+
+/// Messages for the internet radio station browser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RadioMsg {
+    InputShow,
+    InputCloseCancel,
+    /// Search the stations by name, an empty query lists the bookmarks.
+    InputCloseOk(String),
+    StationsCloseCancel,
+    /// Play the station at the index of the results.
+    Play(usize),
+    /// Bookmark the station at the index of the results, or remove its bookmark.
+    ToggleBookmark(usize),
+    SearchSuccess(Vec<Station>),
+    SearchError(String),
+}
+
+/// Messages for the equalizer popup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqualizerMsg {
+    Show,
+    Close,
+    /// Select the band at the index.
+    SelectBand(usize),
+    /// Change the gain of the selected band by the amount of dB, switching to the custom preset.
+    Adjust(i8),
+    NextPreset,
+    ToggleEnabled,
+}
+
+/// Messages for the popup listing the output devices of the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputDevicesMsg {
+    Show,
+    Close,
+    /// Play on the device with the name, empty for the default device of the system.
+    Select(String),
+}
+
+/// Messages for the rating of the current track and the listening statistics popup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatisticsMsg {
+    Show,
+    Close,
+    /// Show the popup to rate the current track
+    RateShow,
+    RateCancel,
+    /// Rate the track at the path with the amount of stars, [`None`] to remove its rating
+    Rate(PathBuf, Option<u8>),
+}
+
+/// Messages for the panel suggesting tracks based on the playing one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackSuggestionsMsg {
+    /// Show the panel, focus it if it is shown but not focused, or hide it if it is focused
+    Toggle,
+    /// Move the focus back to the playlist
+    Blur,
+    /// Suggestions from Last.fm for the track at the path, to add to the ones from the database
+    LastFm(PathBuf, Vec<TrackSuggestion>),
+}
+
+/// Messages for the bookmarks of tracks, see [`bookmark_ops`](termusiclib::new_database::bookmark_ops).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookmarksMsg {
+    /// Show the popup to name a bookmark at the current position of the playing track
+    AddShow,
+    AddCancel,
+    /// Add a bookmark at the given position of the given location, with the entered name
+    AddOk(String, Duration, String),
+    /// Show the bookmarks of the track at INDEX in the playlist
+    Show(usize),
+    Close,
+    /// Jump to the given position of the track at INDEX in the playlist
+    Jump(usize, Duration),
+    /// Delete the bookmark with the given id, of the track at INDEX in the playlist
+    Delete(usize, Integer),
+}
+
+/// Messages for the smart playlists of the database view, see [`smart_playlist`](termusiclib::smart_playlist).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmartPlaylistMsg {
+    /// Show the popup to name a new smart playlist
+    NewShow,
+    /// Show the popup to rename the smart playlist at INDEX (from view `Result`)
+    EditShow(usize),
+    /// The entered name, show the popup to enter the query
+    NameOk(String),
+    /// The entered query, save the playlist
+    QueryOk(String),
+    Cancel,
+    /// Delete the smart playlist at INDEX (from view `Result`)
+    Delete(usize),
+    /// Query the shown tracks of the smart playlist again, if the generation is still current
+    Refresh(u64),
+    /// The tracks of the smart playlist have been queried again, for the generation
+    Refreshed(u64, DBTracksData),
+}
+
+/// Messages for the popup to seek to a entered timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeekToPositionMsg {
+    Show,
+    Cancel,
+    /// The entered timestamp, still to be parsed.
+    Ok(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionConfirmMsg {
+    /// The action has been confirmed, close the popup and execute it.
+    Ok(ConfirmAction),
+    Cancel,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HelpPopupMsg {
     Show,
@@ -343,8 +566,20 @@ pub enum LIMsg {
     ReloadPath(LIReloadPathData),
 
     TreeBlur,
-    PlaylistRunDelete,
+    /// A node got moved from the first path to the second path, update everything that referenced it.
+    Moved(PathBuf, PathBuf),
+    /// A node got copied to the given path.
+    Copied(PathBuf),
     PasteError(String),
+    /// Show the rename popup for the given path.
+    RenameShow(PathBuf),
+    RenameCancel,
+    /// Rename the given path to the new file name.
+    RenameOk(PathBuf, String),
+    /// Restore the last deleted node from the trash.
+    UndoDelete,
+    /// The undo window for the delete of the given path ran out.
+    UndoDeleteExpired(PathBuf),
     /// Switch the music root.
     ///
     /// Contains the *old* root
@@ -391,6 +626,12 @@ pub enum ConfigEditorMsg {
 pub const GENERAL_FOCUS_ORDER: &[IdCEGeneral] = &[
     IdCEGeneral::MusicDir,
     IdCEGeneral::ExitConfirmation,
+    IdCEGeneral::ExitConfirmationPlaying,
+    IdCEGeneral::ConfirmClearPlaylist,
+    IdCEGeneral::ConfirmDelete,
+    IdCEGeneral::ConfirmTagOverwrite,
+    IdCEGeneral::ConfirmQueueReplace,
+    IdCEGeneral::ShowKeyHints,
     IdCEGeneral::PlaylistDisplaySymbol,
     IdCEGeneral::PlaylistRandomTrack,
     IdCEGeneral::PlaylistRandomAlbum,
@@ -401,6 +642,7 @@ pub const GENERAL_FOCUS_ORDER: &[IdCEGeneral] = &[
     IdCEGeneral::SaveLastPosition,
     IdCEGeneral::SeekStep,
     IdCEGeneral::KillDamon,
+    IdCEGeneral::DeleteToTrash,
     IdCEGeneral::PlayerUseMpris,
     IdCEGeneral::PlayerUseDiscord,
     IdCEGeneral::PlayerPort,
@@ -408,7 +650,11 @@ pub const GENERAL_FOCUS_ORDER: &[IdCEGeneral] = &[
     IdCEGeneral::PlayerProtocol,
     IdCEGeneral::PlayerUDSPath,
     IdCEGeneral::PlayerBackend,
+    IdCEGeneral::PlayerCrossfade,
+    IdCEGeneral::PlayerCrossfadeCurve,
     IdCEGeneral::ExtraYtdlpArgs,
+    IdCEGeneral::SponsorBlockCategories,
+    IdCEGeneral::ContentFilterKeywords,
 ];
 
 /// This array defines the order the IDs listed are displayed and which gains next / previous focus.
@@ -450,6 +696,14 @@ pub const KFGLOBAL_FOCUS_ORDER: &[IdKey] = &[
     IdKey::Global(IdKeyGlobal::Quit),
     IdKey::Global(IdKeyGlobal::Config),
     IdKey::Global(IdKeyGlobal::Help),
+    IdKey::Global(IdKeyGlobal::GlobalSearch),
+    IdKey::Global(IdKeyGlobal::Credits),
+    IdKey::Global(IdKeyGlobal::Downloads),
+    IdKey::Global(IdKeyGlobal::Radio),
+    IdKey::Global(IdKeyGlobal::Equalizer),
+    IdKey::Global(IdKeyGlobal::OutputDevices),
+    IdKey::Global(IdKeyGlobal::Statistics),
+    IdKey::Global(IdKeyGlobal::TrackSuggestions),
     IdKey::Global(IdKeyGlobal::SavePlaylist),
     // global navigation
     IdKey::Global(IdKeyGlobal::Up),
@@ -460,6 +714,15 @@ pub const KFGLOBAL_FOCUS_ORDER: &[IdKey] = &[
     IdKey::Global(IdKeyGlobal::GotoTop),
     // global player controls
     IdKey::Global(IdKeyGlobal::PlayerToggleGapless),
+    IdKey::Global(IdKeyGlobal::PlayerToggleKaraoke),
+    IdKey::Global(IdKeyGlobal::PlayerToggleRecording),
+    IdKey::Global(IdKeyGlobal::PlayerToggleAlbumMode),
+    IdKey::Global(IdKeyGlobal::PlayerTapTempo),
+    IdKey::Global(IdKeyGlobal::PlayerToggleNormalization),
+    IdKey::Global(IdKeyGlobal::PlayerToggleSmartSpeed),
+    IdKey::Global(IdKeyGlobal::PlayerAbLoop),
+    IdKey::Global(IdKeyGlobal::PlayerSeekToPosition),
+    IdKey::Global(IdKeyGlobal::PlayerRateTrack),
     IdKey::Global(IdKeyGlobal::PlayerTogglePause),
     IdKey::Global(IdKeyGlobal::PlayerNext),
     IdKey::Global(IdKeyGlobal::PlayerPrevious),
@@ -473,6 +736,8 @@ pub const KFGLOBAL_FOCUS_ORDER: &[IdKey] = &[
     IdKey::Global(IdKeyGlobal::LyricAdjustForward),
     IdKey::Global(IdKeyGlobal::LyricAdjustBackward),
     IdKey::Global(IdKeyGlobal::LyricCycle),
+    IdKey::Global(IdKeyGlobal::LyricSearch),
+    IdKey::Global(IdKeyGlobal::LyricToggleTranslation),
     // coverart display adjustments
     IdKey::Global(IdKeyGlobal::XywhMoveUp),
     IdKey::Global(IdKeyGlobal::XywhMoveDown),
@@ -493,6 +758,12 @@ pub const KFOTHER_FOCUS_ORDER: &[IdKey] = &[
     IdKey::Other(IdKeyOther::LibraryLoadDir),
     IdKey::Other(IdKeyOther::LibraryYank),
     IdKey::Other(IdKeyOther::LibraryPaste),
+    IdKey::Other(IdKeyOther::LibraryCopy),
+    IdKey::Other(IdKeyOther::LibraryRename),
+    IdKey::Other(IdKeyOther::LibraryUndoDelete),
+    IdKey::Other(IdKeyOther::LibrarySyncDevice),
+    IdKey::Other(IdKeyOther::LibraryFetchLyrics),
+    IdKey::Other(IdKeyOther::LibraryReplacePlaylist),
     IdKey::Other(IdKeyOther::LibrarySearch),
     IdKey::Other(IdKeyOther::LibrarySearchYoutube),
     IdKey::Other(IdKeyOther::LibraryTagEditor),
@@ -507,9 +778,23 @@ pub const KFOTHER_FOCUS_ORDER: &[IdKey] = &[
     IdKey::Other(IdKeyOther::PlaylistDeleteAll),
     IdKey::Other(IdKeyOther::PlaylistAddRandomAlbum),
     IdKey::Other(IdKeyOther::PlaylistAddRandomTracks),
+    IdKey::Other(IdKeyOther::PlaylistAddRandomUnplayed),
+    IdKey::Other(IdKeyOther::PlaylistSyncDevice),
+    IdKey::Other(IdKeyOther::PlaylistFetchLyrics),
+    IdKey::Other(IdKeyOther::PlaylistSetOffsets),
+    IdKey::Other(IdKeyOther::PlaylistAddAutoDj),
+    IdKey::Other(IdKeyOther::PlaylistAddBookmark),
+    IdKey::Other(IdKeyOther::PlaylistShowBookmarks),
     // database keys
     IdKey::Other(IdKeyOther::DatabaseAddAll),
     IdKey::Other(IdKeyOther::DatabaseAddSelected),
+    IdKey::Other(IdKeyOther::DatabaseReplacePlaylist),
+    IdKey::Other(IdKeyOther::DatabaseMarkReviewed),
+    IdKey::Other(IdKeyOther::DatabaseMarkAllReviewed),
+    IdKey::Other(IdKeyOther::DatabaseLocate),
+    IdKey::Other(IdKeyOther::DatabaseVerifyLibrary),
+    IdKey::Other(IdKeyOther::DatabaseNewSmartPlaylist),
+    IdKey::Other(IdKeyOther::DatabaseEditSmartPlaylist),
     // podcast keys
     IdKey::Other(IdKeyOther::PodcastSearchAddFeed),
     IdKey::Other(IdKeyOther::PodcastMarkPlayed),
@@ -528,7 +813,37 @@ pub enum KFMsg {
     Previous,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Data for [`DBMsg::SearchResultReady`].
+#[derive(Clone, Debug, Eq)]
+pub struct DBResultsData {
+    /// The criteria the results were queried for
+    pub criteria: SearchCriteria,
+    pub results: Vec<String>,
+}
+
+/// `PartialEq` is only used for subscriptions.
+impl PartialEq for DBResultsData {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Data for [`DBMsg::SearchTracksReady`], [`DBMsg::AddTracksReady`] and [`DBMsg::GeneralSearchReady`].
+#[derive(Clone, Debug)]
+pub struct DBTracksData {
+    pub tracks: Vec<TrackRead>,
+}
+
+impl Eq for DBTracksData {}
+
+/// `PartialEq` is only used for subscriptions.
+impl PartialEq for DBTracksData {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DBMsg {
     /// Add all Track Results (from view `Tracks`) to the playlist
     AddAllToPlaylist,
@@ -551,6 +866,27 @@ pub enum DBMsg {
 
     AddAllResultsConfirmShow,
     AddAllResultsConfirmCancel,
+
+    /// Search Results (for view `Result`) have been queried
+    SearchResultReady(DBResultsData),
+    /// Tracks (for view `Tracks`) have been queried, for the given [`tracks_generation`](crate::ui::model::DatabaseWidgetData::tracks_generation)
+    SearchTracksReady(u64, DBTracksData),
+    /// Tracks to add to the playlist have been queried
+    AddTracksReady(DBTracksData),
+    /// All tracks for the general search popup have been queried
+    GeneralSearchReady(DBTracksData),
+    /// Set the color label of the track at INDEX (from view `Tracks`), [`None`] to remove it
+    SetLabel(usize, Option<TrackLabel>),
+    /// Mark the track at INDEX (from view `Tracks`) of the inbox as reviewed
+    MarkReviewed(usize),
+    /// Mark all tracks (from view `Tracks`) of the inbox as reviewed
+    MarkAllReviewed,
+    /// Show the track at INDEX (from view `Tracks`) in the library
+    Locate(usize),
+    /// Verify all tracks in the database by decoding them
+    VerifyLibrary,
+    /// The verification of all tracks finished, with the summary or the error
+    VerifyLibraryDone(Result<VerifySummary, String>),
 }
 
 /// Playlist Library View messages
@@ -584,7 +920,97 @@ pub enum PLMsg {
     AddRandomAlbum,
     /// Start choosing random tracks to be added to the playlist
     AddRandomTracks,
+    AddRandomUnplayed,
+    /// Add tracks chosen by the auto-DJ to the playlist
+    AddAutoDj,
+    /// Set the color label of the track at INDEX, [`None`] to remove it
+    SetLabel(usize, Option<TrackLabel>),
+    /// Show the offsets popup for the track at INDEX
+    OffsetsShow(usize),
+    OffsetsCancel,
+    /// Set the offsets of the given path to the entered value
+    OffsetsOk(PathBuf, String),
+}
+/// Messages for syncing tracks to a device, see [`DeviceSync`](termusiclib::config::v2::tui::DeviceSync).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeviceSyncMsg {
+    /// Sync the given track or all tracks in the given directory.
+    SyncPath(PathBuf),
+    /// Sync all tracks in the playlist.
+    SyncPlaylist,
+    /// A sync finished.
+    Done(Result<SyncReport, String>),
+}
+
+/// Messages for syncing playlists with a remote, see [`PlaylistSync`](termusiclib::config::v2::tui::PlaylistSync).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlaylistSyncMsg {
+    /// Start a sync.
+    Start,
+    /// A sync finished.
+    Done(Result<PlaylistSyncReport, String>),
+}
+
+/// Messages for fetching lyrics into the lyric cache, see [`LyricCache`](termusiclib::songtag::lyric_cache::LyricCache).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LyricsFetchMsg {
+    /// Fetch lyrics for the given track or all tracks in the given directory.
+    FetchPath(PathBuf),
+    /// Fetch lyrics for all tracks in the playlist.
+    FetchPlaylist,
+    /// `(Done, Total)` tracks of the running fetch.
+    Progress(usize, usize),
+    /// A fetch finished.
+    Done(LyricsFetchReport),
+}
+
+/// Counts of what happened in a lyrics fetch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct LyricsFetchReport {
+    pub fetched: usize,
+    /// Tracks which already had a embedded or cached lyric
+    pub available: usize,
+    /// Tracks for which no lyric could be found
+    pub not_found: usize,
+    /// Tracks that could not be read or stored, see the log
+    pub failed: usize,
+}
+
+/// Messages for translating the shown lyric or episode description, see
+/// [`Translation`](termusiclib::config::v2::tui::Translation).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranslationMsg {
+    /// Toggle showing the translation side by side with the original.
+    Toggle,
+    /// The lines of the text of the key were translated, as `(original, translated)` or the error.
+    Done(String, Result<Vec<(String, String)>, String>),
+}
+
+/// Messages for replacing the playlist, with a preview of the changes before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueueReplaceMsg {
+    /// Replace the playlist with the given track, all tracks in the given directory or the given playlist file.
+    WithPath(PathBuf),
+    /// Replace the playlist with all tracks in the database view "Tracks" section.
+    WithDatabaseTracks,
+    /// The durations for the preview of the replace with the given id have been queried.
+    PreviewReady(usize, QueueDurations),
+    /// Replace the playlist with the pending tracks and play them.
+    Confirm,
+    /// Add the pending tracks to the end of the playlist instead.
+    Append,
+    Cancel,
 }
+
+/// The total duration of the tracks to replace the playlist with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct QueueDurations {
+    /// The sum of all known durations
+    pub total: Duration,
+    /// The amount of tracks without a known duration
+    pub unknown: usize,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GSMsg {
     PopupShowDatabase,
@@ -593,6 +1019,10 @@ pub enum GSMsg {
     PopupShowPlaylist,
     PopupShowEpisode,
     PopupShowPodcast,
+    /// Show the search across tracks, playlists and podcast episodes.
+    PopupShowGlobal,
+    /// Show the search in the synced lyric of the current track.
+    PopupShowLyric,
     PopupCloseCancel,
     InputBlur,
     PopupUpdateDatabase(String),
@@ -601,6 +1031,12 @@ pub enum GSMsg {
     PopupUpdatePlaylist(String),
     PopupUpdateEpisode(String),
     PopupUpdatePodcast(String),
+    PopupUpdateGlobal(String),
+    PopupUpdateLyric(String),
+    /// The playlist files for the global search have been found.
+    GlobalPlaylistsReady(Vec<PathBuf>),
+    /// The search mode got switched, re-run the search with the contained update message.
+    PopupModeChanged(SearchMode, Box<GSMsg>),
     TableBlur,
     PopupCloseEpisodeAddPlaylist,
     PopupCloseDatabaseAddPlaylist,
@@ -610,6 +1046,10 @@ pub enum GSMsg {
     PopupCloseOkPlaylistLocate,
     PopupCloseOkEpisodeLocate,
     PopupCloseOkPodcastLocate,
+    PopupCloseGlobalAddPlaylist,
+    PopupCloseOkGlobalLocate,
+    /// Seek to the selected lyric line.
+    PopupCloseOkLyricSeek,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -617,6 +1057,22 @@ pub enum YSMsg {
     InputPopupShow,
     InputPopupCloseCancel,
     InputPopupCloseOk(String),
+    /// Complete the input with the next suggestion, with the current input.
+    ReqSuggestion(String),
+    SuggestionsLoaded(YoutubeSuggestions),
+    /// Indicates that getting the suggestions failed, with error message.
+    ///
+    /// `(ErrorAsString)`
+    SuggestionsError(String),
+
+    /// Search the next site, like soundcloud after youtube.
+    NextSite,
+
+    /// Show the popup to pick the region of the music trending.
+    RegionPopupShow,
+    RegionPopupCloseCancel,
+    /// Show the music trending in the picked region.
+    RegionPopupCloseOk(String),
 
     ReqNextPage,
     ReqPreviousPage,
@@ -628,6 +1084,8 @@ pub enum YSMsg {
 
     TablePopupCloseCancel,
     TablePopupCloseOk(usize),
+    /// Stream the search result at the index, without downloading it.
+    TablePopupStream(usize),
 
     /// The youtube search was a success, with all values.
     YoutubeSearchSuccess(YoutubeOptions),
@@ -636,6 +1094,13 @@ pub enum YSMsg {
     /// `(ErrorAsString)`
     YoutubeSearchFail(String),
 
+    /// A page of the videos imported from a youtube playlist or channel, to add to the playlist.
+    ImportPage(YoutubeImportPage),
+    /// Indicates that importing a youtube playlist or channel has failed, with error message.
+    ///
+    /// `(ErrorAsString)`
+    ImportFail(String),
+
     Download(YTDLMsg),
 }
 
@@ -744,11 +1209,27 @@ pub enum NotificationMsg {
 pub enum SearchCriteria {
     Artist,
     Album,
+    /// Classical tracks grouped by composer, then by work.
+    Composer,
+    /// Classical tracks grouped by work, in movement order.
+    Work,
+    /// Tracks grouped by their color label.
+    Label,
+    /// Tracks not yet reviewed, grouped by the day they were added.
+    Inbox,
+    /// Tracks grouped by the energy estimated from their audio.
+    Energy,
+    /// Tracks grouped by their quality badge, like lossless.
+    Quality,
+    /// Tracks grouped by how their last verification failed.
+    Corrupted,
 
     // TODO: the values below are current unused
     Genre,
     Directory,
     Playlist,
+    /// Tracks matching a saved query, see [`smart_playlist`](termusiclib::smart_playlist).
+    SmartPlaylist,
 }
 
 impl SearchCriteria {
@@ -757,9 +1238,17 @@ impl SearchCriteria {
         match self {
             SearchCriteria::Artist => "artist",
             SearchCriteria::Album => "album",
+            SearchCriteria::Composer => "composer",
+            SearchCriteria::Work => "work",
+            SearchCriteria::Label => "label",
+            SearchCriteria::Inbox => "inbox",
+            SearchCriteria::Energy => "energy",
+            SearchCriteria::Quality => "quality",
+            SearchCriteria::Corrupted => "corrupted",
             SearchCriteria::Genre => "genre",
             SearchCriteria::Directory => "directory",
             SearchCriteria::Playlist => "playlist",
+            SearchCriteria::SmartPlaylist => "smart playlist",
         }
     }
 }
@@ -768,6 +1257,26 @@ impl SearchCriteria {
 pub enum ServerReqResponse {
     GetProgress(GetProgressResponse),
     FullPlaylist(PlaylistTracks),
+    /// The vocal reduction state the server applied, see [`BackendCmd::SetVocalReduction`](crate::ui::tui_cmd::BackendCmd::SetVocalReduction).
+    VocalReduction(bool),
+    /// The normalization state the server applied, see [`BackendCmd::SetNormalization`](crate::ui::tui_cmd::BackendCmd::SetNormalization).
+    Normalization(bool),
+    /// The smart speed state the server applied, see [`BackendCmd::SetSmartSpeed`](crate::ui::tui_cmd::BackendCmd::SetSmartSpeed).
+    SmartSpeed(bool),
+    /// The equalizer the server applied, see [`BackendCmd::SetEqualizer`](crate::ui::tui_cmd::BackendCmd::SetEqualizer).
+    Equalizer(EqualizerSettings),
+    /// The recording state and possible error the server responded with, see [`BackendCmd::SetRecording`](crate::ui::tui_cmd::BackendCmd::SetRecording).
+    Recording(bool, String),
+    /// The album mode state the server applied, see [`BackendCmd::SetAlbumMode`](crate::ui::tui_cmd::BackendCmd::SetAlbumMode).
+    AlbumMode(bool),
+    /// The download queue of the server, see [`DownloadCmd::List`](crate::ui::tui_cmd::DownloadCmd::List).
+    Downloads(Vec<DownloadInfo>),
+    /// The server rejected a [`DownloadCmd`](crate::ui::tui_cmd::DownloadCmd).
+    DownloadError(String),
+    /// The output devices of the server, see [`BackendCmd::GetOutputDevices`](crate::ui::tui_cmd::BackendCmd::GetOutputDevices).
+    OutputDevices(OutputDevices),
+    /// The A-B loop the server applied, see [`TuiCmd::SetAbLoop`](crate::ui::tui_cmd::TuiCmd::SetAbLoop).
+    AbLoop(Option<AbLoop>),
 }
 
 impl Eq for ServerReqResponse {}
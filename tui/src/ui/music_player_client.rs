@@ -1,12 +1,20 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use termusiclib::config::v2::server::LoopMode;
+use termusiclib::config::v2::server::equalizer::EqualizerSettings;
+use termusiclib::downloads::{DownloadAction, DownloadInfo};
 use termusiclib::player::music_player_client::MusicPlayerClient;
 use termusiclib::player::playlist_helpers::{
-    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackType, PlaylistSwapTrack,
+    PlaylistAddRandom, PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackType,
+    PlaylistSwapTrack,
 };
 use termusiclib::player::{
-    Empty, GetProgressResponse, PlayerProgress, PlaylistSwapTracks, PlaylistTracks,
-    PlaylistTracksToAdd, PlaylistTracksToRemove, RunningStatus,
+    AbLoop, ActivityInfo, AlbumModeState, DownloadControl, DownloadRequest, Empty, EqualizerState,
+    GetProgressResponse, MacroName, NormalizationState, OutputDevice, OutputDevices,
+    PlayerProgress, PlaylistSwapTracks, PlaylistTracks, PlaylistTracksToAdd,
+    PlaylistTracksToRemove, RecordingState, RunningStatus, SeekToPosition, ServerCapabilities,
+    SmartSpeedState, VocalReductionState,
 };
 use tokio_stream::{Stream, StreamExt as _};
 use tonic::transport::Channel;
@@ -101,6 +109,84 @@ impl Playback {
         Ok(response.gapless)
     }
 
+    pub async fn set_vocal_reduction(&mut self, enabled: bool) -> Result<bool> {
+        let request = tonic::Request::new(VocalReductionState { enabled });
+        let response = self.client.set_vocal_reduction(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.enabled)
+    }
+
+    pub async fn set_normalization(&mut self, enabled: bool) -> Result<bool> {
+        let request = tonic::Request::new(NormalizationState { enabled });
+        let response = self.client.set_normalization(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.enabled)
+    }
+
+    pub async fn set_smart_speed(&mut self, enabled: bool) -> Result<bool> {
+        let request = tonic::Request::new(SmartSpeedState { enabled });
+        let response = self.client.set_smart_speed(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.enabled)
+    }
+
+    pub async fn set_equalizer(
+        &mut self,
+        settings: EqualizerSettings,
+    ) -> Result<EqualizerSettings> {
+        let request = tonic::Request::new(EqualizerState::from(settings));
+        let response = self.client.set_equalizer(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.into())
+    }
+
+    pub async fn get_output_devices(&mut self) -> Result<OutputDevices> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.get_output_devices(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response)
+    }
+
+    pub async fn set_output_device(&mut self, name: String) -> Result<()> {
+        let request = tonic::Request::new(OutputDevice { name });
+        let response = self.client.set_output_device(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(())
+    }
+
+    pub async fn run_macro(&mut self, name: String) -> Result<()> {
+        let request = tonic::Request::new(MacroName { name });
+        let response = self.client.run_macro(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(())
+    }
+
+    pub async fn set_recording(&mut self, recording: bool) -> Result<RecordingState> {
+        let request = tonic::Request::new(RecordingState {
+            recording,
+            error: String::new(),
+        });
+        let response = self.client.set_recording(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response)
+    }
+
+    pub async fn set_album_mode(&mut self, enabled: bool) -> Result<bool> {
+        let request = tonic::Request::new(AlbumModeState { enabled });
+        let response = self.client.set_album_mode(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.enabled)
+    }
+
     pub async fn seek_forward(&mut self) -> Result<PlayerProgress> {
         let request = tonic::Request::new(Empty {});
         let response = self.client.seek_forward(request).await?;
@@ -117,6 +203,24 @@ impl Playback {
         Ok(response.into())
     }
 
+    pub async fn seek_to(&mut self, position: Duration) -> Result<PlayerProgress> {
+        let request = tonic::Request::new(SeekToPosition {
+            position: Some(position.into()),
+        });
+        let response = self.client.seek_to(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.into())
+    }
+
+    pub async fn set_ab_loop(&mut self, ab_loop: Option<AbLoop>) -> Result<Option<AbLoop>> {
+        let request = tonic::Request::new(AbLoop::to_grpc(ab_loop));
+        let response = self.client.set_ab_loop(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(AbLoop::from_grpc(response))
+    }
+
     pub async fn reload_config(&mut self) -> Result<()> {
         let request = tonic::Request::new(Empty {});
         let response = self.client.reload_config(request).await?;
@@ -159,6 +263,14 @@ impl Playback {
         Ok(())
     }
 
+    pub async fn add_random_to_playlist(&mut self, info: PlaylistAddRandom) -> Result<()> {
+        let request = tonic::Request::new(termusiclib::player::PlaylistAddRandom::from(info));
+        let response = self.client.add_random_to_playlist(request).await?;
+        info!("Got response from server: {response:?}");
+
+        Ok(())
+    }
+
     pub async fn remove_from_playlist(&mut self, info: PlaylistRemoveTrackType) -> Result<()> {
         let request = tonic::Request::new(PlaylistTracksToRemove::from(info));
         let response = self.client.remove_from_playlist(request).await?;
@@ -199,4 +311,55 @@ impl Playback {
 
         Ok(())
     }
+
+    pub async fn report_activity(&mut self, activity: ActivityInfo) -> Result<()> {
+        let request = tonic::Request::new(activity.into());
+        let response = self.client.report_activity(request).await?;
+        info!("Got response from server: {response:?}");
+        Ok(())
+    }
+
+    pub async fn enqueue_download(&mut self, request: DownloadRequest) -> Result<u64> {
+        let request = tonic::Request::new(request);
+        let response = self.client.enqueue_download(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        Ok(response.id)
+    }
+
+    pub async fn get_downloads(&mut self) -> Result<Vec<DownloadInfo>> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.get_downloads(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {response:?}");
+        response.items.into_iter().map(TryInto::try_into).collect()
+    }
+
+    pub async fn control_download(&mut self, id: u64, action: DownloadAction) -> Result<()> {
+        let request = tonic::Request::new(DownloadControl {
+            id,
+            action: action.number(),
+        });
+        let response = self.client.control_download(request).await?;
+        info!("Got response from server: {response:?}");
+        Ok(())
+    }
+
+    /// Get the server's capabilities.
+    ///
+    /// Servers that do not implement `GetCapabilities` yet are assumed to be [`ServerCapabilities::legacy`].
+    pub async fn get_capabilities(&mut self) -> Result<ServerCapabilities> {
+        let request = tonic::Request::new(Empty {});
+        let response = match self.client.get_capabilities(request).await {
+            Ok(v) => v.into_inner(),
+            Err(status) if status.code() == tonic::Code::Unimplemented => {
+                info!("Server does not implement GetCapabilities, assuming legacy server");
+                return Ok(ServerCapabilities::legacy());
+            }
+            Err(status) => return Err(status.into()),
+        };
+        info!("Got response from server: {response:?}");
+
+        Ok(response.into())
+    }
 }
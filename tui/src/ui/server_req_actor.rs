@@ -6,7 +6,7 @@ use crate::ui::{
     model::TxToMain,
     msg::{Msg, ServerReqResponse},
     music_player_client::Playback,
-    tui_cmd::{PlaylistCmd, TuiCmd},
+    tui_cmd::{BackendCmd, DownloadCmd, PlaylistCmd, TuiCmd},
 };
 
 /// Actor that handles all requests to the Server via GRPC.
@@ -62,6 +62,15 @@ impl ServerRequestActor {
                 // result will be populated back via UpdateStream
                 let _ = self.client_handle.seek_backward().await?;
             }
+            TuiCmd::SeekTo(position) => {
+                // result will be populated back via UpdateStream
+                let _ = self.client_handle.seek_to(position).await?;
+            }
+            TuiCmd::SetAbLoop(ab_loop) => {
+                let res = self.client_handle.set_ab_loop(ab_loop).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::AbLoop(res)));
+            }
             TuiCmd::VolumeUp => {
                 // result will be populated back via UpdateStream
                 let _ = self.client_handle.volume_up().await?;
@@ -90,6 +99,10 @@ impl ServerRequestActor {
                 // result will be populated back via UpdateStream
                 let _ = self.client_handle.toggle_gapless().await?;
             }
+            TuiCmd::RunMacro(name) => {
+                // results will be populated back via UpdateStream
+                self.client_handle.run_macro(name).await?;
+            }
             TuiCmd::CycleLoop => {
                 // result will be populated back via UpdateStream
                 let _ = self.client_handle.cycle_loop().await?;
@@ -102,7 +115,102 @@ impl ServerRequestActor {
             TuiCmd::ReloadConfig => {
                 self.client_handle.reload_config().await?;
             }
+            TuiCmd::ReportActivity(activity) => {
+                self.client_handle.report_activity(activity).await?;
+            }
             TuiCmd::Playlist(playlist_cmd) => self.handle_playlist_cmd(playlist_cmd).await?,
+            TuiCmd::Download(download_cmd) => {
+                // errors are shown to the user, as they are likely caused by the request
+                if let Err(err) = self.handle_download_cmd(download_cmd).await {
+                    self.send_response(Msg::ServerReqResponse(ServerReqResponse::DownloadError(
+                        format!("{err:#}"),
+                    )));
+                }
+            }
+            TuiCmd::Backend(backend_cmd) => self.handle_backend_cmd(backend_cmd).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Handle requests changing the playback backend and its output.
+    async fn handle_backend_cmd(&mut self, cmd: BackendCmd) -> Result<()> {
+        match cmd {
+            BackendCmd::SetVocalReduction(enabled) => {
+                let res = self.client_handle.set_vocal_reduction(enabled).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::VocalReduction(
+                    res,
+                )));
+            }
+            BackendCmd::SetNormalization(enabled) => {
+                let res = self.client_handle.set_normalization(enabled).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::Normalization(
+                    res,
+                )));
+            }
+            BackendCmd::SetSmartSpeed(enabled) => {
+                let res = self.client_handle.set_smart_speed(enabled).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::SmartSpeed(res)));
+            }
+            BackendCmd::SetEqualizer(settings) => {
+                let res = self.client_handle.set_equalizer(settings).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::Equalizer(res)));
+            }
+            BackendCmd::SetRecording(recording) => {
+                let res = self.client_handle.set_recording(recording).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::Recording(
+                    res.recording,
+                    res.error,
+                )));
+            }
+            BackendCmd::SetAlbumMode(enabled) => {
+                let res = self.client_handle.set_album_mode(enabled).await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::AlbumMode(res)));
+            }
+            BackendCmd::GetOutputDevices => {
+                let res = self.client_handle.get_output_devices().await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::OutputDevices(
+                    res,
+                )));
+            }
+            BackendCmd::SetOutputDevice(name) => {
+                self.client_handle.set_output_device(name).await?;
+                let res = self.client_handle.get_output_devices().await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::OutputDevices(
+                    res,
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle download queue requests.
+    async fn handle_download_cmd(&mut self, cmd: DownloadCmd) -> Result<()> {
+        match cmd {
+            DownloadCmd::Enqueue(request) => {
+                // progress will be populated back via UpdateStream
+                let _ = self.client_handle.enqueue_download(request).await?;
+            }
+            DownloadCmd::List => {
+                let downloads = self.client_handle.get_downloads().await?;
+
+                self.send_response(Msg::ServerReqResponse(ServerReqResponse::Downloads(
+                    downloads,
+                )));
+            }
+            DownloadCmd::Control(id, action) => {
+                // result will be populated back via UpdateStream
+                self.client_handle.control_download(id, action).await?;
+            }
         }
 
         Ok(())
@@ -123,6 +231,12 @@ impl ServerRequestActor {
                     .add_to_playlist(playlist_add_track)
                     .await?;
             }
+            PlaylistCmd::AddRandom(playlist_add_random) => {
+                // result will be populated back via UpdateStream
+                self.client_handle
+                    .add_random_to_playlist(playlist_add_random)
+                    .await?;
+            }
             PlaylistCmd::RemoveTrack(playlist_remove_track_indexed) => {
                 // result will be populated back via UpdateStream
                 self.client_handle
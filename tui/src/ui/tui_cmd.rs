@@ -1,6 +1,12 @@
+use std::time::Duration;
+
+use termusiclib::config::v2::server::equalizer::EqualizerSettings;
+use termusiclib::downloads::DownloadAction;
 use termusiclib::player::playlist_helpers::{
-    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+    PlaylistAddRandom, PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed,
+    PlaylistSwapTrack,
 };
+use termusiclib::player::{AbLoop, ActivityInfo, DownloadRequest};
 
 #[allow(clippy::doc_link_with_quotes)]
 /// Enum for Commands to send to the [`MusicPlayerClient` "Actor"](crate::ui::music_player_client).
@@ -12,6 +18,10 @@ pub enum TuiCmd {
     // Pause,
     SeekForward,
     SeekBackward,
+    /// Seek to a absolute position in the current track.
+    SeekTo(Duration),
+    /// Repeat a region of the current track or stop it, responds with [`ServerReqResponse::AbLoop`](crate::ui::msg::ServerReqResponse::AbLoop).
+    SetAbLoop(Option<AbLoop>),
     VolumeUp,
     VolumeDown,
     SpeedUp,
@@ -19,12 +29,18 @@ pub enum TuiCmd {
     SkipNext,
     SkipPrevious,
     ToggleGapless,
+    /// Run the macro with the name, of the server's `macros`.
+    RunMacro(String),
     CycleLoop,
 
     GetProgress,
     ReloadConfig,
+    /// Report the downloads and library scans to the server, for other clients to show.
+    ReportActivity(ActivityInfo),
 
     Playlist(PlaylistCmd),
+    Download(DownloadCmd),
+    Backend(BackendCmd),
 }
 
 /// Enum for Commands to send specificly for Playlist
@@ -32,6 +48,7 @@ pub enum TuiCmd {
 pub enum PlaylistCmd {
     PlaySpecific(PlaylistPlaySpecific),
     AddTrack(PlaylistAddTrack),
+    AddRandom(PlaylistAddRandom),
     RemoveTrack(PlaylistRemoveTrackIndexed),
     Clear,
     SwapTrack(PlaylistSwapTrack),
@@ -42,3 +59,34 @@ pub enum PlaylistCmd {
     #[allow(dead_code)] // replace with "expect" on 1.81 upgrade
     SelfReloadPlaylist,
 }
+
+/// Enum for Commands to change the playback backend and its output of the server.
+#[derive(Clone, Debug)]
+pub enum BackendCmd {
+    /// Enable or disable vocal reduction, responds with [`ServerReqResponse::VocalReduction`](crate::ui::msg::ServerReqResponse::VocalReduction).
+    SetVocalReduction(bool),
+    /// Enable or disable loudness normalization, responds with [`ServerReqResponse::Normalization`](crate::ui::msg::ServerReqResponse::Normalization).
+    SetNormalization(bool),
+    /// Enable or disable shortening long silences in podcasts, responds with [`ServerReqResponse::SmartSpeed`](crate::ui::msg::ServerReqResponse::SmartSpeed).
+    SetSmartSpeed(bool),
+    /// Change the equalizer, responds with [`ServerReqResponse::Equalizer`](crate::ui::msg::ServerReqResponse::Equalizer).
+    SetEqualizer(EqualizerSettings),
+    /// Start or stop recording, responds with [`ServerReqResponse::Recording`](crate::ui::msg::ServerReqResponse::Recording).
+    SetRecording(bool),
+    /// Enable or disable album mode, responds with [`ServerReqResponse::AlbumMode`](crate::ui::msg::ServerReqResponse::AlbumMode).
+    SetAlbumMode(bool),
+    /// Get the output devices, responds with [`ServerReqResponse::OutputDevices`](crate::ui::msg::ServerReqResponse::OutputDevices).
+    GetOutputDevices,
+    /// Play on the output device with the name, or the default device if empty, responds with [`ServerReqResponse::OutputDevices`](crate::ui::msg::ServerReqResponse::OutputDevices).
+    SetOutputDevice(String),
+}
+
+/// Enum for Commands to the download queue of the server, errors respond with [`ServerReqResponse::DownloadError`](crate::ui::msg::ServerReqResponse::DownloadError).
+#[derive(Clone, Debug)]
+pub enum DownloadCmd {
+    /// Add a download, its progress is reported via the update stream.
+    Enqueue(DownloadRequest),
+    /// Get the queue, responds with [`ServerReqResponse::Downloads`](crate::ui::msg::ServerReqResponse::Downloads).
+    List,
+    Control(u64, DownloadAction),
+}